@@ -0,0 +1,200 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::job::{Job, JobSpec, JobStatus, Resource};
+use crate::ports::job_port::{JobPort, JobTicket};
+
+/// How long a finished job is still listed.
+const FINISHED_JOB_AGE: Duration = Duration::from_secs(24 * 3600);
+
+/// The most finished jobs listed; the ones that finished first are forgotten beyond it.
+const MAX_FINISHED_JOBS: usize = 1000;
+
+/// A job together with the handles used to control it.
+#[derive(Debug)]
+struct JobEntry {
+    job: Job,
+    cancel: CancellationToken,
+    status: watch::Sender<JobStatus>,
+    /// When the job finished, once it has.
+    finished: Option<Instant>,
+}
+
+impl JobEntry {
+    /// Updates the status of the job and notifies its ticket.
+    fn set_status(&mut self, status: JobStatus) {
+        self.job.status = status;
+        if status.is_finished() {
+            self.job.finished_at = Some(Local::now().to_rfc3339());
+            self.finished = Some(Instant::now());
+        }
+        self.status.send_replace(status);
+    }
+}
+
+/// InMemoryJobAdapter
+///
+/// Queues the jobs started by the running application and tracks them,
+/// together with the tokens used to cancel them.
+#[derive(Debug, Default)]
+pub struct InMemoryJobAdapter {
+    next_id: AtomicU64,
+    jobs: Mutex<BTreeMap<u64, JobEntry>>,
+}
+
+impl InMemoryJobAdapter {
+    /// new
+    ///
+    /// Constructs a new, empty InMemoryJobAdapter instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts every queued job whose resources are free, in submission order.
+    ///
+    /// The resources of a job that stays queued are reserved as well, so a
+    /// later job cannot overtake it and starve it.
+    fn admit_queued(jobs: &mut BTreeMap<u64, JobEntry>) {
+        let mut busy: BTreeSet<Resource> = jobs
+            .values()
+            .filter(|entry| entry.job.status == JobStatus::Running)
+            .flat_map(|entry| entry.job.resources.iter().copied())
+            .collect();
+
+        for entry in jobs.values_mut() {
+            if entry.job.status != JobStatus::Queued {
+                continue;
+            }
+            if entry.job.resources.is_disjoint(&busy) {
+                entry.set_status(JobStatus::Running);
+            }
+            busy.extend(entry.job.resources.iter().copied());
+        }
+    }
+
+    /// Forgets the jobs that finished more than `FINISHED_JOB_AGE` ago, and the
+    /// ones that finished first beyond `MAX_FINISHED_JOBS`, so a long-running
+    /// instance does not keep every job it ever ran.
+    fn prune_finished(jobs: &mut BTreeMap<u64, JobEntry>) {
+        jobs.retain(|_, entry| {
+            entry
+                .finished
+                .is_none_or(|finished| finished.elapsed() < FINISHED_JOB_AGE)
+        });
+        let mut finished: Vec<(Instant, u64)> = jobs
+            .iter()
+            .filter_map(|(id, entry)| entry.finished.map(|finished| (finished, *id)))
+            .collect();
+        if finished.len() > MAX_FINISHED_JOBS {
+            finished.sort_unstable();
+            for (_, id) in &finished[..finished.len() - MAX_FINISHED_JOBS] {
+                jobs.remove(id);
+            }
+        }
+    }
+
+    /// Cancels a single job: a queued job is dropped from the queue, while a
+    /// running job is asked to stop and records its own outcome.
+    fn cancel_entry(entry: &mut JobEntry) {
+        entry.cancel.cancel();
+        if entry.job.status == JobStatus::Queued {
+            entry.set_status(JobStatus::Cancelled);
+        }
+    }
+}
+
+// Implement the JobPort trait for the InMemoryJobAdapter struct.
+impl JobPort for InMemoryJobAdapter {
+    fn submit_job(&self, spec: JobSpec) -> JobTicket {
+        let job = Job {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed) + 1,
+            kind: spec.kind,
+            resources: spec.resources,
+            status: JobStatus::Queued,
+            started_at: Local::now().to_rfc3339(),
+            finished_at: None,
+            message: None,
+        };
+        let cancel = CancellationToken::new();
+        let (status, receiver) = watch::channel(JobStatus::Queued);
+
+        let mut submitted = job.clone();
+        if let Ok(mut jobs) = self.jobs.lock() {
+            jobs.insert(
+                job.id,
+                JobEntry {
+                    job: job.clone(),
+                    cancel: cancel.clone(),
+                    status,
+                    finished: None,
+                },
+            );
+            Self::admit_queued(&mut jobs);
+            if let Some(entry) = jobs.get(&job.id) {
+                submitted = entry.job.clone();
+            }
+        }
+        JobTicket::new(submitted, cancel, receiver)
+    }
+
+    fn finish_job(&self, id: u64, status: JobStatus, message: Option<String>) -> Option<Job> {
+        let mut jobs = self.jobs.lock().ok()?;
+        let entry = jobs.get_mut(&id)?;
+        entry.job.message = message;
+        entry.set_status(status);
+        let job = entry.job.clone();
+
+        Self::admit_queued(&mut jobs);
+        Self::prune_finished(&mut jobs);
+        Some(job)
+    }
+
+    fn cancel_job(&self, id: u64) -> Result<Job, String> {
+        let mut jobs = self
+            .jobs
+            .lock()
+            .map_err(|_| "Job registry is unavailable".to_string())?;
+        let entry = jobs
+            .get_mut(&id)
+            .ok_or_else(|| format!("Job {} not found", id))?;
+        if entry.job.status.is_finished() {
+            return Err(format!("Job {} is no longer running", id));
+        }
+        Self::cancel_entry(entry);
+        let job = entry.job.clone();
+
+        Self::admit_queued(&mut jobs);
+        Ok(job)
+    }
+
+    fn cancel_all(&self) {
+        if let Ok(mut jobs) = self.jobs.lock() {
+            for entry in jobs.values_mut() {
+                if !entry.job.status.is_finished() {
+                    Self::cancel_entry(entry);
+                }
+            }
+        }
+    }
+
+    fn get_job(&self, id: u64) -> Option<Job> {
+        let jobs = self.jobs.lock().ok()?;
+        jobs.get(&id).map(|entry| entry.job.clone())
+    }
+
+    fn list_jobs(&self) -> Vec<Job> {
+        self.jobs
+            .lock()
+            .map(|mut jobs| {
+                Self::prune_finished(&mut jobs);
+                jobs.values().map(|entry| entry.job.clone()).collect()
+            })
+            .unwrap_or_default()
+    }
+}
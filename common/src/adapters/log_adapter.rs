@@ -3,16 +3,23 @@ use std::fmt;
 use std::fs;
 // For filesystem operations like creating directories.
 use std::fs::File;
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
 
 use chrono::Local;
 // For timestamping log messages with the current local time.
 use colored::*;
 // To colorize log messages based on their severity level.
-use fern::{log_file, Dispatch};
+use fern::{log_file, Dispatch, Output};
 use log::LevelFilter;
 
 // For setting up the logging infrastructure.
+use crate::adapters::log_stream_adapter::InMemoryLogStreamAdapter;
+use crate::domain::log_record::LogRecord;
 use crate::ports::log_port::LoggerPort;
+use crate::ports::log_stream_port::LogStreamPort;
 
 // For file operations like creating log files. // For formatting.
 
@@ -20,6 +27,136 @@ use crate::ports::log_port::LoggerPort;
 
 // Custom trait for logging functionality.
 
+// Whether log messages are echoed to standard output. Full-screen terminal
+// interfaces turn this off so log lines do not tear through the display.
+static CONSOLE_OUTPUT: AtomicBool = AtomicBool::new(true);
+
+/// Enables or disables echoing log messages to standard output.
+///
+/// Log files are unaffected.
+///
+/// # Arguments
+///
+/// * `enabled` - Whether messages should be printed to standard output.
+pub fn set_console_output(enabled: bool) {
+    CONSOLE_OUTPUT.store(enabled, Ordering::Relaxed);
+}
+
+/// The socket journald reads native protocol messages from.
+#[cfg(unix)]
+const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// The identifier log messages are filed under in the journal.
+const SYSLOG_IDENTIFIER: &str = "one_for_all";
+
+// The connection to journald, once journal output has been enabled. Messages are
+// sent to it as structured entries instead of colored lines on standard output.
+#[cfg(unix)]
+static JOURNALD: OnceLock<UnixDatagram> = OnceLock::new();
+
+/// Sends every log message to the systemd journal, with its severity as the
+/// journal priority, so `journalctl -u` and `journalctl -p` work as for any
+/// other service.
+///
+/// Log files are unaffected. Standard output usually ends up in the journal as
+/// well, so callers turn console output off once this succeeds.
+///
+/// # Returns
+///
+/// * `Result<(), String>` - An error if journald is not running on this machine.
+#[cfg(unix)]
+pub fn enable_journald_output() -> Result<(), String> {
+    if JOURNALD.get().is_some() {
+        return Ok(());
+    }
+    let socket =
+        UnixDatagram::unbound().map_err(|e| format!("Failed to create a journal socket: {}", e))?;
+    socket.connect(JOURNALD_SOCKET).map_err(|e| {
+        format!(
+            "Failed to connect to journald at {}: {}",
+            JOURNALD_SOCKET, e
+        )
+    })?;
+    let _ = JOURNALD.set(socket);
+    Ok(())
+}
+
+/// The systemd journal only exists on Linux.
+#[cfg(not(unix))]
+pub fn enable_journald_output() -> Result<(), String> {
+    Err("journald is not available on this platform".to_string())
+}
+
+/// Maps a log level to a syslog priority, as used by the journal.
+fn journal_priority(level: log::Level) -> u8 {
+    match level {
+        log::Level::Error => 3,
+        log::Level::Warn => 4,
+        log::Level::Info => 6,
+        log::Level::Debug | log::Level::Trace => 7,
+    }
+}
+
+/// Appends one field to a journal entry in the native protocol. Values with a
+/// newline are written with an explicit length, everything else as `KEY=value`.
+fn push_journal_field(entry: &mut Vec<u8>, key: &str, value: &str) {
+    entry.extend_from_slice(key.as_bytes());
+    if value.contains('\n') {
+        entry.push(b'\n');
+        entry.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        entry.push(b'=');
+    }
+    entry.extend_from_slice(value.as_bytes());
+    entry.push(b'\n');
+}
+
+/// Sends a log record to journald, if journal output is enabled.
+#[cfg(unix)]
+fn write_to_journald(record: &log::Record) {
+    let Some(socket) = JOURNALD.get() else {
+        return;
+    };
+    let mut entry = Vec::new();
+    push_journal_field(
+        &mut entry,
+        "PRIORITY",
+        &journal_priority(record.level()).to_string(),
+    );
+    push_journal_field(&mut entry, "SYSLOG_IDENTIFIER", SYSLOG_IDENTIFIER);
+    push_journal_field(&mut entry, "ONEFORALL_TARGET", record.target());
+    push_journal_field(&mut entry, "MESSAGE", &record.args().to_string());
+    // A message too large for a datagram falls back to standard output, which
+    // systemd also files in the journal.
+    if socket.send(&entry).is_err() {
+        println!("{}", record.args());
+    }
+}
+
+#[cfg(not(unix))]
+fn write_to_journald(_record: &log::Record) {}
+
+// The recent log messages and their live subscribers, streamed by the REST API.
+static LOG_STREAM: OnceLock<Arc<InMemoryLogStreamAdapter>> = OnceLock::new();
+
+/// Returns the stream every log message is published to, for the web server
+/// to serve at `/api/v1/logs/stream`.
+pub fn log_stream() -> Arc<dyn LogStreamPort> {
+    LOG_STREAM
+        .get_or_init(|| Arc::new(InMemoryLogStreamAdapter::new()))
+        .clone()
+}
+
+/// Publishes a log record to the log stream.
+fn write_to_stream(record: &log::Record) {
+    log_stream().publish_log(LogRecord {
+        timestamp: Local::now().to_rfc3339(),
+        level: record.level().to_string(),
+        target: record.target().to_string(),
+        message: record.args().to_string(),
+    });
+}
+
 /// `FernLogger` is a struct that implements the `LoggerPort` trait.
 /// It is thread-safe due to the implementation of `Sync` and `Send` traits.
 pub struct FernLogger;
@@ -116,26 +253,24 @@ pub fn init(log_dir_path: &str, level_filter: LevelFilter) -> FernLogger {
             .unwrap_or_else(|_| panic!("Failed to create or truncate log file: {}", file_path));
     }
 
-    // Set up the base configuration for the logger, including message formatting.
-    let base_config = Dispatch::new()
-        .format(move |out, message, record| {
-            // Colorize messages based on their log level.
-            let color_message = match record.level() {
-                log::Level::Error => message.to_string().red(),
-                log::Level::Warn => message.to_string().yellow(),
-                log::Level::Info => message.to_string().green(),
-                log::Level::Debug => message.to_string().blue(),
-                log::Level::Trace => message.to_string().cyan(),
-            };
-            // Format the log message with a timestamp, level, and the colorized message.
-            out.finish(format_args!(
-                "{} [{}] - {}",
-                Local::now().format("%Y-%m-%dT%H:%M:%S"),
-                record.level(),
-                color_message
-            ))
-        })
-        .level(level_filter); // Apply the specified level filter to the logger.
+    // Set up the formatting of messages written to the log files and standard output.
+    let formatted_config = Dispatch::new().format(move |out, message, record| {
+        // Colorize messages based on their log level.
+        let color_message = match record.level() {
+            log::Level::Error => message.to_string().red(),
+            log::Level::Warn => message.to_string().yellow(),
+            log::Level::Info => message.to_string().green(),
+            log::Level::Debug => message.to_string().blue(),
+            log::Level::Trace => message.to_string().cyan(),
+        };
+        // Format the log message with a timestamp, level, and the colorized message.
+        out.finish(format_args!(
+            "{} [{}] - {}",
+            Local::now().format("%Y-%m-%dT%H:%M:%S"),
+            record.level(),
+            color_message
+        ))
+    });
 
     // Set up individual log files for each log level.
     let error_log = log_file(&format!("{}/one_4_all_error.log", log_dir_path)).unwrap();
@@ -162,13 +297,36 @@ pub fn init(log_dir_path: &str, level_filter: LevelFilter) -> FernLogger {
         .chain(trace_log);
 
     // Combine all dispatch configurations into one.
-    let combined_config = base_config
+    let formatted_config = formatted_config
         .chain(error_dispatch)
         .chain(warn_dispatch)
         .chain(info_dispatch)
         .chain(debug_dispatch)
         .chain(trace_dispatch)
-        .chain(std::io::stdout()); // Also log to standard output.
+        // Also log to standard output, unless a terminal interface has taken over the screen.
+        .chain(Output::call(|record| {
+            if CONSOLE_OUTPUT.load(Ordering::Relaxed) {
+                println!("{}", record.args());
+            }
+        }));
+
+    // Journal entries carry their own timestamp and priority, so they get the bare
+    // message rather than the formatted line.
+    let journald_config = Dispatch::new().chain(Output::call(write_to_journald));
+
+    // Remote readers get the bare message too, with its level and time as fields.
+    let stream_config = Dispatch::new().chain(Output::call(write_to_stream));
+
+    let combined_config = Dispatch::new()
+        .level(level_filter) // Apply the specified level filter to the logger.
+        // The HTTP client dumps every request byte by byte at debug and trace level.
+        .level_for("ureq", level_filter.min(LevelFilter::Info))
+        .level_for("ureq_proto", level_filter.min(LevelFilter::Info))
+        // The mDNS responder's event loop logs every socket wakeup at trace level.
+        .level_for("mio", level_filter.min(LevelFilter::Info))
+        .chain(formatted_config)
+        .chain(journald_config)
+        .chain(stream_config);
 
     // Apply the combined logger configuration.
     combined_config
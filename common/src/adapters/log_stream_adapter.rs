@@ -0,0 +1,76 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use tokio::sync::broadcast;
+
+use crate::domain::log_record::LogRecord;
+use crate::ports::log_stream_port::LogStreamPort;
+
+/// Number of recent messages kept for readers that start with a backlog.
+const RECENT_CAPACITY: usize = 1000;
+
+/// Number of messages buffered for slow subscribers.
+const EVENT_CAPACITY: usize = 256;
+
+/// InMemoryLogStreamAdapter
+///
+/// Keeps the most recent log messages in memory, so a reader can start with
+/// the messages logged just before it connected, and broadcasts every new
+/// message to live subscribers.
+#[derive(Debug)]
+pub struct InMemoryLogStreamAdapter {
+    recent: Mutex<VecDeque<LogRecord>>,
+    events: broadcast::Sender<LogRecord>,
+}
+
+impl InMemoryLogStreamAdapter {
+    /// new
+    ///
+    /// Constructs a new, empty InMemoryLogStreamAdapter instance.
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(EVENT_CAPACITY);
+        Self {
+            recent: Mutex::new(VecDeque::with_capacity(RECENT_CAPACITY)),
+            events,
+        }
+    }
+}
+
+impl Default for InMemoryLogStreamAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Implement the LogStreamPort trait for the InMemoryLogStreamAdapter struct.
+impl LogStreamPort for InMemoryLogStreamAdapter {
+    fn publish_log(&self, record: LogRecord) {
+        if let Ok(mut recent) = self.recent.lock() {
+            if recent.len() == RECENT_CAPACITY {
+                recent.pop_front();
+            }
+            recent.push_back(record.clone());
+        }
+        // Sending only fails when nobody is subscribed.
+        let _ = self.events.send(record);
+    }
+
+    fn recent_logs(&self, level: log::Level, lines: usize) -> Vec<LogRecord> {
+        let Ok(recent) = self.recent.lock() else {
+            return Vec::new();
+        };
+        let mut records: Vec<LogRecord> = recent
+            .iter()
+            .rev()
+            .filter(|record| record.is_at_least(level))
+            .take(lines)
+            .cloned()
+            .collect();
+        records.reverse();
+        records
+    }
+
+    fn subscribe_logs(&self) -> broadcast::Receiver<LogRecord> {
+        self.events.subscribe()
+    }
+}
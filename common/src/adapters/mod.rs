@@ -1,5 +1,8 @@
+pub mod job_adapter;
 pub mod log_adapter;
+pub mod log_stream_adapter;
 pub mod ps_wasm_adapter;
 
 mod system_info_adapter;
+pub mod telemetry_adapter;
 pub mod web_server_adapter;
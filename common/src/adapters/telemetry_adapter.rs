@@ -0,0 +1,118 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use tokio::sync::broadcast;
+
+use crate::domain::metric::Metric;
+use crate::domain::pipeline::PipelineStats;
+use crate::domain::telemetry::{PowerSample, StressProgress, TelemetryEvent};
+use crate::ports::telemetry_port::TelemetryPort;
+
+/// Number of events buffered for slow WebSocket subscribers and metric sinks;
+/// every collector publishes its readings as metrics, several batches a second.
+const EVENT_CAPACITY: usize = 256;
+
+/// InMemoryTelemetryAdapter
+///
+/// Keeps the latest published readings in memory so that they can be served
+/// by the web server without touching the hardware on every request, and
+/// broadcasts every reading to live subscribers. Power readings and stress
+/// test progress are published as metrics as well. The counters of the queues
+/// in front of the sinks are kept here too, for the REST API.
+#[derive(Debug)]
+pub struct InMemoryTelemetryAdapter {
+    power: RwLock<Option<PowerSample>>,
+    progress: RwLock<Option<StressProgress>>,
+    metrics: RwLock<BTreeMap<String, Metric>>,
+    pipelines: RwLock<BTreeMap<String, PipelineStats>>,
+    events: broadcast::Sender<TelemetryEvent>,
+}
+
+impl InMemoryTelemetryAdapter {
+    /// new
+    ///
+    /// Constructs a new, empty InMemoryTelemetryAdapter instance.
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(EVENT_CAPACITY);
+        Self {
+            power: RwLock::new(None),
+            progress: RwLock::new(None),
+            metrics: RwLock::new(BTreeMap::new()),
+            pipelines: RwLock::new(BTreeMap::new()),
+            events,
+        }
+    }
+}
+
+impl Default for InMemoryTelemetryAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Implement the TelemetryPort trait for the InMemoryTelemetryAdapter struct.
+impl TelemetryPort for InMemoryTelemetryAdapter {
+    fn publish_power(&self, sample: PowerSample) {
+        if let Ok(mut power) = self.power.write() {
+            *power = Some(sample.clone());
+        }
+        self.publish_metrics(sample.metrics());
+        // Sending only fails when nobody is subscribed.
+        let _ = self.events.send(TelemetryEvent::Power(sample));
+    }
+
+    fn latest_power(&self) -> Option<PowerSample> {
+        self.power.read().ok().and_then(|power| power.clone())
+    }
+
+    fn publish_progress(&self, progress: StressProgress) {
+        if let Ok(mut latest) = self.progress.write() {
+            *latest = Some(progress.clone());
+        }
+        self.publish_metrics(progress.metrics());
+        let _ = self.events.send(TelemetryEvent::StressProgress(progress));
+    }
+
+    fn latest_progress(&self) -> Option<StressProgress> {
+        self.progress
+            .read()
+            .ok()
+            .and_then(|progress| progress.clone())
+    }
+
+    fn publish_metrics(&self, metrics: Vec<Metric>) {
+        if metrics.is_empty() {
+            return;
+        }
+        if let Ok(mut latest) = self.metrics.write() {
+            for metric in &metrics {
+                latest.insert(metric.series(), metric.clone());
+            }
+        }
+        let _ = self.events.send(TelemetryEvent::Metrics { metrics });
+    }
+
+    fn latest_metrics(&self) -> Vec<Metric> {
+        self.metrics
+            .read()
+            .map(|latest| latest.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn publish_pipeline(&self, stats: PipelineStats) {
+        if let Ok(mut pipelines) = self.pipelines.write() {
+            pipelines.insert(stats.name.clone(), stats);
+        }
+    }
+
+    fn pipelines(&self) -> Vec<PipelineStats> {
+        self.pipelines
+            .read()
+            .map(|pipelines| pipelines.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<TelemetryEvent> {
+        self.events.subscribe()
+    }
+}
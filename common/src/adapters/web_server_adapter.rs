@@ -1,18 +1,241 @@
-use std::future;
-use std::sync::Arc;
+use std::convert::Infallible;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Component, Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
 
-use actix_web::{web, App, HttpResponse, HttpServer, Responder};
+use actix_web::body::{BodySize, MessageBody};
+use actix_web::dev::{ServerHandle, Service, ServiceRequest};
+use actix_web::error::InternalError;
+use actix_web::http::Method;
+use actix_web::web::Bytes;
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer, Responder, Route};
+use chrono::Local;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig};
+use serde::Deserialize;
 use tokio::io;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{mpsc, Notify};
 
+use crate::domain::api_config::{ApiConfig, TlsConfig};
+use crate::domain::health::{HealthReport, HealthResponse};
+use crate::domain::history::HistoryQuery;
+use crate::domain::job::LaunchRequest;
+use crate::domain::log_record::{parse_log_level, LogRecord};
+use crate::domain::metric::render_prometheus;
+use crate::domain::network_probe::{ProbeRequest, MAX_PROBE_PAYLOAD_BYTES};
+use crate::domain::openapi::{
+    check_routes, is_documentation, openapi_document, API_DOCS_PATH, API_VERSION, OPENAPI_PATH,
+    SWAGGER_UI_CSS_PATH, SWAGGER_UI_JS_PATH,
+};
+use crate::ports::footprint_port::FootprintPort;
+use crate::ports::health_port::HealthPort;
+use crate::ports::history_port::HistoryPort;
+use crate::ports::job_launcher_port::JobLauncherPort;
+use crate::ports::job_port::JobPort;
 use crate::ports::log_port::LoggerPort;
+use crate::ports::log_stream_port::LogStreamPort;
+use crate::ports::network_probe_port::NetworkProbePort;
+use crate::ports::score_port::ScorePort;
+use crate::ports::telemetry_port::TelemetryPort;
 // web_server_adapter.rs
 use crate::ports::web_server_port::WebServerPort;
 
+/// Reads the REST API settings from a configuration file, along with the token
+/// files it names. A missing file leaves the defaults: plain HTTP on the
+/// loopback address without authentication.
+///
+/// # Arguments
+///
+/// * `path` - The configuration file, usually `oneforall.toml`.
+///
+/// # Returns
+///
+/// * `Result<ApiConfig, String>` - The API settings, or why they could not be read.
+pub fn load_api_config(path: &Path) -> Result<ApiConfig, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(ApiConfig::default()),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    let mut config =
+        ApiConfig::from_toml(&text).map_err(|e| format!("Invalid {}: {}", path.display(), e))?;
+    for api_token in &mut config.tokens {
+        let Some(token_file) = api_token.token_file.take() else {
+            continue;
+        };
+        let token = fs::read_to_string(&token_file)
+            .map_err(|e| format!("Failed to read {}: {}", token_file.display(), e))?;
+        let token = token.trim();
+        if token.is_empty() {
+            return Err(format!(
+                "The API token in {} is empty",
+                token_file.display()
+            ));
+        }
+        api_token.token = Some(token.to_string());
+    }
+    Ok(config)
+}
+
+/// Reads every certificate from a PEM file.
+fn read_certificates(path: &Path) -> Result<Vec<CertificateDer<'static>>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let certificates = rustls_pemfile::certs(&mut BufReader::new(file))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read certificates from {}: {}", path.display(), e))?;
+    if certificates.is_empty() {
+        return Err(format!("No certificates found in {}", path.display()));
+    }
+    Ok(certificates)
+}
+
+/// Builds the TLS settings of the server, requiring a client certificate
+/// signed by `client_ca` when one is configured.
+fn tls_server_config(tls: &TlsConfig) -> Result<ServerConfig, String> {
+    let certificates = read_certificates(&tls.certificate)?;
+    let file = File::open(&tls.private_key)
+        .map_err(|e| format!("Failed to open {}: {}", tls.private_key.display(), e))?;
+    let key: PrivateKeyDer<'static> = rustls_pemfile::private_key(&mut BufReader::new(file))
+        .map_err(|e| format!("Failed to read {}: {}", tls.private_key.display(), e))?
+        .ok_or_else(|| format!("No private key found in {}", tls.private_key.display()))?;
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("Failed to set up TLS: {}", e))?;
+    let builder = match &tls.client_ca {
+        Some(client_ca) => {
+            let mut roots = RootCertStore::empty();
+            for certificate in read_certificates(client_ca)? {
+                roots
+                    .add(certificate)
+                    .map_err(|e| format!("Invalid client CA in {}: {}", client_ca.display(), e))?;
+            }
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                .build()
+                .map_err(|e| format!("Failed to set up client verification: {}", e))?;
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+    builder
+        .with_single_cert(certificates, key)
+        .map_err(|e| format!("Invalid certificate or key: {}", e))
+}
+
+/// Returns the bearer token of a request, from the `Authorization` header or,
+/// for browser WebSockets which cannot set headers, the `access_token` query parameter.
+fn presented_token(request: &ServiceRequest) -> Option<String> {
+    if let Some(header) = request.headers().get("Authorization") {
+        return header
+            .to_str()
+            .ok()
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(|token| token.trim().to_string());
+    }
+    request
+        .query_string()
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("access_token="))
+        .map(str::to_string)
+}
+
+/// The route patterns served without a token: the console page, the status
+/// check, the health probes and the dashboard. The API documentation is open too.
+const OPEN_ROUTES: &[&str] = &[
+    "/",
+    "/status",
+    "/healthz",
+    "/readyz",
+    "/console",
+    "/dashboard",
+    "/dashboard/{file:.*}",
+];
+
+/// Returns `true` if a request matches a route that needs no token.
+///
+/// The decision is made from the route pattern the request matches rather than
+/// its path, since the router decodes percent-escapes: "/%61pi/v1/jobs" reaches
+/// the jobs handler without starting with "/api/". A request whose raw path
+/// matches no route needs a token.
+fn is_open(request: &ServiceRequest) -> bool {
+    request.match_pattern().is_some_and(|pattern| {
+        OPEN_ROUTES.contains(&pattern.as_str()) || is_documentation(&pattern)
+    })
+}
+
+/// Checks that the caller of an API request presented a token whose role
+/// allows the request. Every route but those of `OPEN_ROUTES` and the API
+/// documentation needs one.
+///
+/// # Returns
+///
+/// * `Result<(), Box<InternalError<String>>>` - The error to reject the request
+///   with, carrying the reason to log, if it is not allowed.
+fn authorize(
+    request: &ServiceRequest,
+    config: &ApiConfig,
+) -> Result<(), Box<InternalError<String>>> {
+    if config.tokens.is_empty() || is_open(request) {
+        return Ok(());
+    }
+    let Some(token) =
+        presented_token(request).and_then(|presented| config.find_token(&presented).cloned())
+    else {
+        return Err(Box::new(InternalError::from_response(
+            "missing or invalid API token".to_string(),
+            HttpResponse::Unauthorized()
+                .insert_header(("WWW-Authenticate", "Bearer"))
+                .body("A valid API token is required"),
+        )));
+    };
+    let read_only = matches!(*request.method(), Method::GET | Method::HEAD);
+    if token.role.permits(read_only) {
+        return Ok(());
+    }
+    Err(Box::new(InternalError::from_response(
+        format!(
+            "{} token '{}' may not make changes",
+            token.role.label(),
+            token.name
+        ),
+        HttpResponse::Forbidden().body(format!(
+            "The {} role may only read; an operator token is required",
+            token.role.label()
+        )),
+    )))
+}
+
+/// WebServerPorts
+///
+/// The ports the REST API serves, handed to the web server together.
+pub struct WebServerPorts {
+    pub telemetry: Arc<dyn TelemetryPort>, // Live readings served by the REST API
+    pub jobs: Arc<dyn JobPort>,            // Running jobs listed and cancelled by the REST API
+    pub launcher: Arc<dyn JobLauncherPort>, // Starts profiles as jobs for remote callers
+    pub probe: Arc<dyn NetworkProbePort>,  // Measures the path to other agents for the orchestrator
+    pub scores: Arc<dyn ScorePort>,        // Benchmark scores the orchestrator ranks machines by
+    pub footprint: Arc<dyn FootprintPort>, // Overhead of the monitoring and the budget it keeps to
+    pub logs: Arc<dyn LogStreamPort>,      // Log messages streamed to remote readers
+    pub health: Arc<dyn HealthPort>,       // Subsystems reported by the health probes
+    pub history: Arc<dyn HistoryPort>,     // Stored readings charted by the dashboard
+}
+
 /// WebServerAdapter
 ///
 /// Adapter for the web server, integrating a logging facility.
 pub struct WebServerAdapter {
     logger: Arc<dyn LoggerPort>, // Use LoggerPort trait for the logger
+    ports: WebServerPorts,       // The ports the REST API serves
+    config: ApiConfig,           // Listening address, authentication and TLS settings
+    listening: Arc<Notify>,      // Notified once the REST API accepts connections
+    server: Mutex<Option<ServerHandle>>, // Stops the running server when shutting down
 }
 
 // Implement the Sync trait for the WebServerAdapter struct.
@@ -20,8 +243,22 @@ impl WebServerAdapter {
     /// new
     ///
     /// Constructs a new WebServerAdapter instance.
-    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
-        Self { logger }
+    pub fn new(logger: Arc<dyn LoggerPort>, ports: WebServerPorts, config: ApiConfig) -> Self {
+        Self {
+            logger,
+            ports,
+            config,
+            listening: Arc::new(Notify::new()),
+            server: Mutex::new(None),
+        }
+    }
+
+    /// listening
+    ///
+    /// Returns a handle that is notified once the server has bound its address
+    /// and accepts connections, e.g. to report readiness to a service manager.
+    pub fn listening(&self) -> Arc<Notify> {
+        self.listening.clone()
     }
 }
 // show_dashboard
@@ -242,24 +479,839 @@ async fn get_status() -> impl Responder {
     HttpResponse::Ok().body("Server is running")
 }
 
+/// Checks every subsystem off the async workers, since the checks read the database.
+async fn check_health(health: web::Data<dyn HealthPort>) -> Result<HealthReport, HttpResponse> {
+    web::block(move || health.check_health())
+        .await
+        .map_err(|e| HttpResponse::InternalServerError().body(e.to_string()))
+}
+
+/// Answers a health probe with every check as JSON, with 200 if the probe
+/// passed or 503 if it failed.
+fn health_response(passed: bool, report: HealthReport) -> HttpResponse {
+    let body = HealthResponse::new(passed, report);
+    if passed {
+        HttpResponse::Ok().json(body)
+    } else {
+        HttpResponse::ServiceUnavailable().json(body)
+    }
+}
+
+/// get_liveness
+///
+/// The liveness probe: fails with 503 if a subsystem the agent cannot work
+/// without is broken, such as the database, so the agent should be restarted.
+async fn get_liveness(health: web::Data<dyn HealthPort>) -> HttpResponse {
+    match check_health(health).await {
+        Ok(report) => health_response(report.is_live(), report),
+        Err(failure) => failure,
+    }
+}
+
+/// get_readiness
+///
+/// The readiness probe: fails with 503 if any check fails, e.g. while readings
+/// cannot be written, a collector stopped reporting or the agent shuts down,
+/// so no requests or jobs should be sent to it.
+async fn get_readiness(health: web::Data<dyn HealthPort>) -> HttpResponse {
+    match check_health(health).await {
+        Ok(report) => health_response(report.is_ready(), report),
+        Err(failure) => failure,
+    }
+}
+
+/// get_openapi
+///
+/// Returns the OpenAPI document describing every route of the REST API.
+async fn get_openapi(document: web::Data<serde_json::Value>) -> impl Responder {
+    HttpResponse::Ok().json(document.get_ref())
+}
+
+/// The Swagger UI stylesheet, bundled so the documentation works on lab networks
+/// without internet access.
+const SWAGGER_UI_CSS: &[u8] = include_bytes!("../../assets/swagger-ui/swagger-ui.css");
+
+/// The Swagger UI script, bundled alongside its stylesheet.
+const SWAGGER_UI_JS: &[u8] = include_bytes!("../../assets/swagger-ui/swagger-ui-bundle.js");
+
+/// get_swagger_ui_css
+///
+/// Returns the bundled Swagger UI stylesheet.
+async fn get_swagger_ui_css() -> impl Responder {
+    HttpResponse::Ok()
+        .content_type("text/css")
+        .insert_header(("Cache-Control", "max-age=86400"))
+        .body(SWAGGER_UI_CSS)
+}
+
+/// get_swagger_ui_js
+///
+/// Returns the bundled Swagger UI script.
+async fn get_swagger_ui_js() -> impl Responder {
+    HttpResponse::Ok()
+        .content_type("application/javascript")
+        .insert_header(("Cache-Control", "max-age=86400"))
+        .body(SWAGGER_UI_JS)
+}
+
+/// show_api_docs
+///
+/// Browses the OpenAPI document with the Swagger UI bundled with the server.
+async fn show_api_docs() -> impl Responder {
+    HttpResponse::Ok().content_type("text/html").body(format!(
+        r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <title>OneForAll REST API {version}</title>
+    <link rel="stylesheet" href="{css}">
+</head>
+<body>
+<div id="swagger-ui"></div>
+<script src="{js}"></script>
+<script>
+    window.ui = SwaggerUIBundle({{ url: "{document}", dom_id: "#swagger-ui" }});
+</script>
+</body>
+</html>"##,
+        version = API_VERSION,
+        css = SWAGGER_UI_CSS_PATH,
+        js = SWAGGER_UI_JS_PATH,
+        document = OPENAPI_PATH
+    ))
+}
+
+/// Returns the content type of a file of the built dashboard.
+fn dashboard_content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("js") => "application/javascript",
+        Some("wasm") => "application/wasm",
+        Some("css") => "text/css",
+        Some("svg") => "image/svg+xml",
+        Some("ico") => "image/x-icon",
+        _ => "application/octet-stream",
+    }
+}
+
+/// show_dashboard
+///
+/// Serves the WASM dashboard built from `dashboard/` with trunk. Paths naming
+/// no file get its index page, and 404 says how to build it when it has not
+/// been. The dashboard itself calls the API with the token the user enters.
+async fn show_dashboard(directory: web::Data<PathBuf>, request: HttpRequest) -> HttpResponse {
+    let file = request
+        .match_info()
+        .get("file")
+        .unwrap_or_default()
+        .to_string();
+    // Only files below the directory are served.
+    if Path::new(&file)
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return HttpResponse::NotFound().finish();
+    }
+    let root = directory.get_ref().clone();
+    let read = web::block(move || {
+        let path = root.join(&file);
+        let path = if path.is_file() {
+            path
+        } else {
+            root.join("index.html")
+        };
+        fs::read(&path).map(|body| (path, body))
+    })
+    .await;
+    match read {
+        Ok(Ok((path, body))) => HttpResponse::Ok()
+            .content_type(dashboard_content_type(&path))
+            .body(body),
+        Ok(Err(_)) => HttpResponse::NotFound().body(format!(
+            "The dashboard is not built in {}; run `trunk build --release` in dashboard/",
+            directory.display()
+        )),
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
+/// get_power
+///
+/// Returns the latest power reading published by the collectors as JSON, or
+/// 404 when no power source has reported yet on this machine.
+async fn get_power(telemetry: web::Data<dyn TelemetryPort>) -> impl Responder {
+    match telemetry.latest_power() {
+        Some(sample) => HttpResponse::Ok().json(sample),
+        None => HttpResponse::NotFound().body("No power readings available"),
+    }
+}
+
+/// get_progress
+///
+/// Returns the progress of the running (or last) stress test as JSON, or 404
+/// when no stress test has run since the server started.
+async fn get_progress(telemetry: web::Data<dyn TelemetryPort>) -> impl Responder {
+    match telemetry.latest_progress() {
+        Some(progress) => HttpResponse::Ok().json(progress),
+        None => HttpResponse::NotFound().body("No stress test progress available"),
+    }
+}
+
+/// get_metrics
+///
+/// Returns the latest reading of every metric in the Prometheus text exposition
+/// format, for Prometheus to scrape, followed by the counters of the sink queues.
+async fn get_metrics(telemetry: web::Data<dyn TelemetryPort>) -> impl Responder {
+    let timestamp = Local::now().to_rfc3339();
+    let mut metrics = telemetry.latest_metrics();
+    for pipeline in telemetry.pipelines() {
+        metrics.extend(pipeline.metrics(&timestamp));
+    }
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4; charset=utf-8")
+        .body(render_prometheus(&metrics))
+}
+
+/// list_pipelines
+///
+/// Returns the queue in front of each sink as JSON: how many batches wait and
+/// how many readings were written, dropped or aggregated since the start.
+async fn list_pipelines(telemetry: web::Data<dyn TelemetryPort>) -> impl Responder {
+    HttpResponse::Ok().json(telemetry.pipelines())
+}
+
+/// list_jobs
+///
+/// Returns every job submitted since the server started as JSON, including
+/// the jobs queued behind a conflicting job.
+async fn list_jobs(jobs: web::Data<dyn JobPort>) -> impl Responder {
+    HttpResponse::Ok().json(jobs.list_jobs())
+}
+
+/// get_job
+///
+/// Returns a single job as JSON, or 404 if the job is unknown.
+async fn get_job(jobs: web::Data<dyn JobPort>, id: web::Path<u64>) -> impl Responder {
+    match jobs.get_job(id.into_inner()) {
+        Some(job) => HttpResponse::Ok().json(job),
+        None => HttpResponse::NotFound().body("Job not found"),
+    }
+}
+
+/// launch_job
+///
+/// Starts running a profile as a job and returns it with 202 at once, or 400
+/// if the profile does not exist or cannot be run. Its progress can be followed
+/// through `GET /api/v1/jobs/{id}` and its report is stored when it finishes.
+async fn launch_job(
+    launcher: web::Data<dyn JobLauncherPort>,
+    request: web::Json<LaunchRequest>,
+) -> impl Responder {
+    let profile = request.into_inner().profile;
+    match web::block(move || launcher.launch_profile(&profile)).await {
+        Ok(Ok(job)) => HttpResponse::Accepted().json(job),
+        Ok(Err(e)) => HttpResponse::BadRequest().body(e),
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
+/// cancel_job
+///
+/// Requests cancellation of a queued or running job. A queued job is removed
+/// from the queue at once, while a running job is stopped asynchronously; its
+/// final status can be followed through `GET /api/v1/jobs/{id}`.
+async fn cancel_job(jobs: web::Data<dyn JobPort>, id: web::Path<u64>) -> impl Responder {
+    let id = id.into_inner();
+    match jobs.cancel_job(id) {
+        Ok(job) => HttpResponse::Accepted().json(job),
+        Err(e) if jobs.get_job(id).is_some() => HttpResponse::Conflict().body(e),
+        Err(e) => HttpResponse::NotFound().body(e),
+    }
+}
+
+/// The query of a probe payload request.
+#[derive(Deserialize)]
+struct PayloadQuery {
+    /// The number of bytes to send.
+    #[serde(default)]
+    bytes: usize,
+}
+
+/// The zeros a probe payload is made of, sent over and over so a payload takes
+/// no memory of its own however many are downloaded at once.
+static PAYLOAD_CHUNK: [u8; 64 * 1024] = [0; 64 * 1024];
+
+/// The body of a probe payload: zeros up to the requested size.
+struct PayloadStream {
+    remaining: usize,
+}
+
+impl MessageBody for PayloadStream {
+    type Error = Infallible;
+
+    fn size(&self) -> BodySize {
+        BodySize::Sized(self.remaining as u64)
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let stream = self.get_mut();
+        if stream.remaining == 0 {
+            return Poll::Ready(None);
+        }
+        let length = stream.remaining.min(PAYLOAD_CHUNK.len());
+        stream.remaining -= length;
+        Poll::Ready(Some(Ok(Bytes::from_static(&PAYLOAD_CHUNK[..length]))))
+    }
+}
+
+/// get_probe_payload
+///
+/// Returns a body of the requested size, at most `MAX_PROBE_PAYLOAD_BYTES`, for
+/// another agent to time its download; an empty body measures the round trip.
+async fn get_probe_payload(query: web::Query<PayloadQuery>) -> impl Responder {
+    HttpResponse::Ok()
+        .content_type("application/octet-stream")
+        .body(PayloadStream {
+            remaining: query.bytes.min(MAX_PROBE_PAYLOAD_BYTES),
+        })
+}
+
+/// probe_link
+///
+/// Measures the latency and bandwidth from this machine to another agent and
+/// returns them as JSON, or 502 if the agent could not be measured. Used by the
+/// orchestrator to build the network matrix of the fleet.
+async fn probe_link(
+    probe: web::Data<dyn NetworkProbePort>,
+    request: web::Json<ProbeRequest>,
+) -> impl Responder {
+    let target = request.into_inner().target;
+    match web::block(move || probe.probe_link(&target)).await {
+        Ok(Ok(measurement)) => HttpResponse::Ok().json(measurement),
+        Ok(Err(e)) => HttpResponse::BadGateway().body(e),
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
+/// get_score
+///
+/// Returns the benchmark scores of this machine as JSON, or 500 if its results
+/// could not be read. Used by the orchestrator to rank the machines of the fleet.
+async fn get_score(scores: web::Data<dyn ScorePort>) -> impl Responder {
+    match web::block(move || scores.score()).await {
+        Ok(Ok(score)) => HttpResponse::Ok().json(score),
+        Ok(Err(e)) => HttpResponse::InternalServerError().body(e),
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
+/// get_history
+///
+/// Returns the time-series of a metric over a time range as JSON, thinned to
+/// the number of points asked for, or 400 if the metric or range is invalid.
+/// Used by the dashboard to chart past runs, e.g. an overnight burn-in.
+async fn get_history(
+    history: web::Data<dyn HistoryPort>,
+    query: web::Query<HistoryQuery>,
+) -> impl Responder {
+    let query = query.into_inner();
+    match web::block(move || history.history(&query)).await {
+        Ok(Ok(history)) => HttpResponse::Ok().json(history),
+        Ok(Err(e)) => HttpResponse::BadRequest().body(e),
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
+/// get_footprint
+///
+/// Returns the overhead OneForAll's own collectors and web server add to the
+/// machine, with the budget they keep to, as JSON, or 404 until it is first measured.
+async fn get_footprint(footprint: web::Data<dyn FootprintPort>) -> impl Responder {
+    match footprint.footprint() {
+        Some(footprint) => HttpResponse::Ok().json(footprint),
+        None => HttpResponse::NotFound().body("The footprint has not been measured yet"),
+    }
+}
+
+/// Number of recent log messages returned when a reader does not ask for a number.
+const DEFAULT_LOG_LINES: usize = 20;
+
+/// How long a log stream stays silent before a comment keeps the connection
+/// open through proxies and tells the reader the server is still there.
+const LOG_KEEPALIVE: Duration = Duration::from_secs(15);
+
+/// The query of a log request.
+#[derive(Deserialize)]
+struct LogQuery {
+    /// The least severe level to return, e.g. "warn"; "info" when omitted.
+    #[serde(default)]
+    level: Option<String>,
+
+    /// The number of recent messages to return first.
+    #[serde(default)]
+    lines: Option<usize>,
+}
+
+impl LogQuery {
+    /// Returns the least severe level asked for, or why it is unknown.
+    fn level(&self) -> Result<log::Level, String> {
+        parse_log_level(self.level.as_deref().unwrap_or("info"))
+    }
+}
+
+/// get_logs
+///
+/// Returns the most recent log messages as JSON, oldest first.
+async fn get_logs(logs: web::Data<dyn LogStreamPort>, query: web::Query<LogQuery>) -> HttpResponse {
+    match query.level() {
+        Ok(level) => HttpResponse::Ok()
+            .json(logs.recent_logs(level, query.lines.unwrap_or(DEFAULT_LOG_LINES))),
+        Err(e) => HttpResponse::BadRequest().body(e),
+    }
+}
+
+/// The body of a server-sent event stream, fed chunk by chunk through a channel.
+struct EventStream {
+    chunks: mpsc::Receiver<Bytes>,
+}
+
+impl MessageBody for EventStream {
+    type Error = Infallible;
+
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        self.get_mut()
+            .chunks
+            .poll_recv(cx)
+            .map(|chunk| chunk.map(Ok))
+    }
+}
+
+/// stream_logs
+///
+/// Streams the log messages as server-sent events, one JSON `LogRecord` per
+/// `data` line, starting with the most recent ones. Messages a slow reader
+/// missed are announced with a `skipped` event carrying their number.
+async fn stream_logs(
+    logs: web::Data<dyn LogStreamPort>,
+    query: web::Query<LogQuery>,
+) -> HttpResponse {
+    let level = match query.level() {
+        Ok(level) => level,
+        Err(e) => return HttpResponse::BadRequest().body(e),
+    };
+    // Subscribe before reading the backlog, so no message falls between the two.
+    let mut records = logs.subscribe_logs();
+    let backlog = logs.recent_logs(level, query.lines.unwrap_or(DEFAULT_LOG_LINES));
+    let (sender, chunks) = mpsc::channel::<Bytes>(16);
+
+    actix_web::rt::spawn(async move {
+        let event = |record: &LogRecord| {
+            serde_json::to_string(record)
+                .map(|json| Bytes::from(format!("data: {}\n\n", json)))
+                .ok()
+        };
+        for record in &backlog {
+            if let Some(chunk) = event(record) {
+                if sender.send(chunk).await.is_err() {
+                    return;
+                }
+            }
+        }
+        loop {
+            let chunk = match actix_web::rt::time::timeout(LOG_KEEPALIVE, records.recv()).await {
+                Ok(Ok(record)) if record.is_at_least(level) => event(&record),
+                Ok(Ok(_)) => None,
+                Ok(Err(RecvError::Lagged(skipped))) => Some(Bytes::from(format!(
+                    "event: skipped\ndata: {}\n\n",
+                    skipped
+                ))),
+                Ok(Err(RecvError::Closed)) => return,
+                Err(_) => Some(Bytes::from_static(b": keepalive\n\n")),
+            };
+            // Sending fails once the reader has gone away.
+            if let Some(chunk) = chunk {
+                if sender.send(chunk).await.is_err() {
+                    return;
+                }
+            }
+        }
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .body(EventStream { chunks })
+}
+
+/// telemetry_socket
+///
+/// Upgrades the connection to a WebSocket and streams every telemetry event
+/// (power readings, stress test progress, metrics) to the client as JSON text frames.
+async fn telemetry_socket(
+    req: HttpRequest,
+    body: web::Payload,
+    telemetry: web::Data<dyn TelemetryPort>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (response, mut session, _messages) = actix_ws::handle(&req, body)?;
+    let mut events = telemetry.subscribe();
+
+    actix_web::rt::spawn(async move {
+        loop {
+            let event = match events.recv().await {
+                Ok(event) => event,
+                // A slow client skips the events it missed rather than disconnecting.
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            };
+            let json = match serde_json::to_string(&event) {
+                Ok(json) => json,
+                Err(_) => continue,
+            };
+            // Sending fails once the client has gone away.
+            if session.text(json).await.is_err() {
+                return;
+            }
+        }
+        let _ = session.close(None).await;
+    });
+
+    Ok(response)
+}
+
+/// The routes described by the OpenAPI table, as their method, path and handler.
+/// The server refuses to start if they and the table disagree.
+fn api_routes() -> Vec<(&'static str, &'static str, Route)> {
+    vec![
+        ("get", "/status", web::get().to(get_status)),
+        ("get", "/healthz", web::get().to(get_liveness)), // Liveness probe
+        ("get", "/readyz", web::get().to(get_readiness)), // Readiness probe
+        ("get", "/console", web::get().to(show_console)),
+        ("get", "/dashboard", web::get().to(show_dashboard)), // WASM dashboard
+        ("get", "/api/v1/power", web::get().to(get_power)),
+        ("get", "/api/v1/progress", web::get().to(get_progress)),
+        ("get", "/api/v1/metrics", web::get().to(get_metrics)), // Prometheus scrape
+        ("get", "/api/v1/pipelines", web::get().to(list_pipelines)), // Sink queues
+        ("get", "/api/v1/ws", web::get().to(telemetry_socket)), // Live telemetry stream
+        ("get", "/api/v1/jobs", web::get().to(list_jobs)),
+        ("post", "/api/v1/jobs", web::post().to(launch_job)),
+        ("get", "/api/v1/jobs/{id}", web::get().to(get_job)),
+        ("delete", "/api/v1/jobs/{id}", web::delete().to(cancel_job)),
+        ("post", "/api/v1/netprobe", web::post().to(probe_link)),
+        (
+            "get",
+            "/api/v1/netprobe/payload",
+            web::get().to(get_probe_payload),
+        ),
+        ("get", "/api/v1/score", web::get().to(get_score)),
+        ("get", "/api/v1/self", web::get().to(get_footprint)),
+        ("get", "/api/v1/history", web::get().to(get_history)),
+        ("get", "/api/v1/logs", web::get().to(get_logs)),
+        ("get", "/api/v1/logs/stream", web::get().to(stream_logs)), // Live log stream
+        ("get", OPENAPI_PATH, web::get().to(get_openapi)),
+        ("get", API_DOCS_PATH, web::get().to(show_api_docs)), // Swagger UI
+    ]
+}
+
+/// Seconds the requests in progress, such as log streams, get to finish once the server stops.
+const SHUTDOWN_TIMEOUT_SECS: u64 = 5;
+
 // Implement the WebServerPort trait for the WebServerAdapter struct.
 #[async_trait::async_trait]
 impl WebServerPort for WebServerAdapter {
     async fn start_server(&self) -> io::Result<()> {
-        let server = HttpServer::new(|| {
-            App::new()
+        let telemetry = web::Data::from(self.ports.telemetry.clone());
+        let jobs = web::Data::from(self.ports.jobs.clone());
+        let launcher = web::Data::from(self.ports.launcher.clone());
+        let probe = web::Data::from(self.ports.probe.clone());
+        let scores = web::Data::from(self.ports.scores.clone());
+        let footprint = web::Data::from(self.ports.footprint.clone());
+        let logs = web::Data::from(self.ports.logs.clone());
+        let health = web::Data::from(self.ports.health.clone());
+        let history = web::Data::from(self.ports.history.clone());
+        let openapi = web::Data::new(openapi_document(!self.config.tokens.is_empty()));
+        let dashboard = web::Data::new(self.config.dashboard.clone());
+        let workers = self.ports.footprint.budget().web_workers;
+        let config = Arc::new(self.config.clone());
+        let logger = self.logger.clone();
+
+        let documented: Vec<(&str, &str)> = api_routes()
+            .iter()
+            .map(|(method, path, _)| (*method, *path))
+            .collect();
+        check_routes(&documented).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("The REST API does not match its OpenAPI document: {}", e),
+            )
+        })?;
+
+        if !self.config.is_authenticated() && !self.config.is_local_only() {
+            self.logger.log_warn(&format!(
+                "The REST API on {} accepts requests from other machines without \
+                 authentication; set a token or a client CA in the [api] configuration.",
+                self.config.address
+            ));
+        }
+
+        let server = HttpServer::new(move || {
+            let config = config.clone();
+            let logger = logger.clone();
+            let app = App::new()
+                .app_data(telemetry.clone()) // Shared live readings
+                .app_data(jobs.clone()) // Shared job registry
+                .app_data(launcher.clone()) // Starts profiles as jobs
+                .app_data(probe.clone()) // Network path measurements
+                .app_data(scores.clone()) // Benchmark scores
+                .app_data(footprint.clone()) // Own overhead
+                .app_data(logs.clone()) // Log messages
+                .app_data(health.clone()) // Health checks
+                .app_data(history.clone()) // Stored readings
+                .app_data(openapi.clone()) // API documentation
+                .app_data(dashboard.clone()) // Built dashboard directory
+                // Every request must carry a token whose role allows it, except those to the
+                // console and dashboard pages, the status check, the health probes and the
+                // documentation.
+                .wrap_fn(move |request, service| {
+                    let response = match authorize(&request, &config) {
+                        Ok(()) => Ok(service.call(request)),
+                        Err(rejection) => {
+                            logger.log_warn(&format!(
+                                "Rejected request {} {} from {}: {}",
+                                request.method(),
+                                request.path(),
+                                request
+                                    .peer_addr()
+                                    .map_or_else(|| "unknown".to_string(), |peer| peer.to_string()),
+                                rejection
+                            ));
+                            Err(*rejection)
+                        }
+                    };
+                    async move {
+                        match response {
+                            Ok(response) => response.await,
+                            Err(rejection) => Err(rejection.into()),
+                        }
+                    }
+                })
                 .route("/", web::get().to(HttpResponse::Ok)) // Default route
-                .route("/status", web::get().to(get_status)) // Route for get_status
-                .route("/console", web::get().to(show_console)) // Route for show console
+                .route("/dashboard/{file:.*}", web::get().to(show_dashboard)) // Dashboard files
+                .route(SWAGGER_UI_CSS_PATH, web::get().to(get_swagger_ui_css)) // Swagger UI
+                .route(SWAGGER_UI_JS_PATH, web::get().to(get_swagger_ui_js)); // Swagger UI
+            api_routes()
+                .into_iter()
+                .fold(app, |app, (_, path, route)| app.route(path, route))
         })
-        .bind("127.0.0.1:8000")?
+        // Few workers keep the server's own threads within the footprint budget.
+        .workers(workers)
+        // The application stops the server itself when shutting down, after the jobs.
+        .disable_signals()
+        .shutdown_timeout(SHUTDOWN_TIMEOUT_SECS);
+        let server = match &self.config.tls {
+            Some(tls) => {
+                let tls_config = tls_server_config(tls)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+                server.bind_rustls_0_23(&self.config.address, tls_config)?
+            }
+            None => server.bind(&self.config.address)?,
+        }
         .run();
+        self.logger.log_info(&format!(
+            "Serving the REST API over {} on {}{}{}",
+            if self.config.tls.is_some() {
+                "HTTPS"
+            } else {
+                "HTTP"
+            },
+            self.config.address,
+            if !self.config.tokens.is_empty() {
+                ", token required"
+            } else {
+                ""
+            },
+            if self.config.requires_client_certificate() {
+                ", client certificate required"
+            } else {
+                ""
+            }
+        ));
+
+        if let Ok(mut handle) = self.server.lock() {
+            *handle = Some(server.handle());
+        }
+        self.listening.notify_one();
+
+        // Serve until the server is stopped.
+        server.await
+    }
+
+    async fn stop_server(&self) {
+        let handle = self.server.lock().ok().and_then(|mut handle| handle.take());
+        if let Some(handle) = handle {
+            handle.stop(true).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::test::{self, TestRequest};
+
+    const OPERATOR_TOKEN: &str = "operator-secret";
+    const VIEWER_TOKEN: &str = "viewer-secret";
+
+    fn config_with_tokens() -> ApiConfig {
+        ApiConfig::from_toml(&format!(
+            r#"
+            [[api.tokens]]
+            name = "ci"
+            role = "operator"
+            token = "{}"
+
+            [[api.tokens]]
+            name = "grafana"
+            role = "viewer"
+            token = "{}"
+            "#,
+            OPERATOR_TOKEN, VIEWER_TOKEN
+        ))
+        .unwrap()
+    }
+
+    /// Sends a request through the authorization of `start_server` to the
+    /// server's routes, each answered by a stub, and returns the status.
+    async fn status(config: ApiConfig, request: TestRequest) -> StatusCode {
+        let app = App::new()
+            .wrap_fn(move |request, service| {
+                let response = authorize(&request, &config).map(|()| service.call(request));
+                async move {
+                    match response {
+                        Ok(response) => response.await,
+                        Err(rejection) => Err((*rejection).into()),
+                    }
+                }
+            })
+            .route("/", web::get().to(HttpResponse::Ok))
+            .route("/dashboard/{file:.*}", web::get().to(HttpResponse::Ok))
+            .route(SWAGGER_UI_CSS_PATH, web::get().to(HttpResponse::Ok))
+            .route(SWAGGER_UI_JS_PATH, web::get().to(HttpResponse::Ok));
+        let app = api_routes().into_iter().fold(app, |app, (_, path, _)| {
+            app.route(path, web::route().to(HttpResponse::Ok))
+        });
+        let app = test::init_service(app).await;
+        match app.call(request.to_request()).await {
+            Ok(response) => response.status(),
+            Err(error) => error.as_response_error().status_code(),
+        }
+    }
+
+    fn bearer(request: TestRequest, token: &str) -> TestRequest {
+        request.insert_header(("Authorization", format!("Bearer {}", token)))
+    }
 
-        tokio::spawn(server);
+    #[actix_web::test]
+    async fn everything_is_open_without_configured_tokens() {
+        let request = TestRequest::delete().uri("/api/v1/jobs/1");
+        assert_eq!(status(ApiConfig::default(), request).await, StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn api_requests_without_a_token_are_rejected() {
+        for request in [
+            TestRequest::get().uri("/api/v1/jobs"),
+            TestRequest::delete().uri("/api/v1/jobs/1"),
+            TestRequest::post().uri("/api/v1/netprobe"),
+        ] {
+            assert_eq!(
+                status(config_with_tokens(), request).await,
+                StatusCode::UNAUTHORIZED
+            );
+        }
+    }
+
+    #[actix_web::test]
+    async fn percent_encoded_api_paths_need_a_token() {
+        for request in [
+            TestRequest::delete().uri("/%61pi/v1/jobs/1"),
+            TestRequest::post().uri("/%61pi/v1/jobs"),
+            TestRequest::post().uri("/%61pi/v1/netprobe"),
+            TestRequest::get().uri("/%2Fapi/v1/jobs"),
+        ] {
+            assert_eq!(
+                status(config_with_tokens(), request).await,
+                StatusCode::UNAUTHORIZED
+            );
+        }
+        // The encoded path does reach the handler once a token is presented.
+        let request = bearer(
+            TestRequest::delete().uri("/%61pi/v1/jobs/1"),
+            OPERATOR_TOKEN,
+        );
+        assert_eq!(status(config_with_tokens(), request).await, StatusCode::OK);
+    }
 
-        // Wait indefinitely, or until you have another condition to close the application
-        future::pending::<()>().await;
+    #[actix_web::test]
+    async fn open_routes_need_no_token() {
+        for uri in [
+            "/",
+            "/status",
+            "/healthz",
+            "/readyz",
+            "/console",
+            "/dashboard",
+            "/dashboard/index.html",
+            OPENAPI_PATH,
+            API_DOCS_PATH,
+            SWAGGER_UI_CSS_PATH,
+        ] {
+            let request = TestRequest::get().uri(uri);
+            assert_eq!(
+                status(config_with_tokens(), request).await,
+                StatusCode::OK,
+                "{}",
+                uri
+            );
+        }
+    }
+
+    #[actix_web::test]
+    async fn unknown_tokens_are_rejected() {
+        let request = bearer(TestRequest::get().uri("/api/v1/jobs"), "guess");
+        assert_eq!(
+            status(config_with_tokens(), request).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[actix_web::test]
+    async fn viewer_tokens_may_only_read() {
+        let request = bearer(TestRequest::get().uri("/api/v1/jobs"), VIEWER_TOKEN);
+        assert_eq!(status(config_with_tokens(), request).await, StatusCode::OK);
+        let request = bearer(TestRequest::delete().uri("/api/v1/jobs/1"), VIEWER_TOKEN);
+        assert_eq!(
+            status(config_with_tokens(), request).await,
+            StatusCode::FORBIDDEN
+        );
+    }
 
-        Ok(())
+    #[actix_web::test]
+    async fn operator_tokens_may_make_changes() {
+        let request = bearer(TestRequest::delete().uri("/api/v1/jobs/1"), OPERATOR_TOKEN);
+        assert_eq!(status(config_with_tokens(), request).await, StatusCode::OK);
+        let request =
+            TestRequest::delete().uri(&format!("/api/v1/jobs/1?access_token={}", OPERATOR_TOKEN));
+        assert_eq!(status(config_with_tokens(), request).await, StatusCode::OK);
     }
 }
@@ -0,0 +1,377 @@
+//! API Configuration Domain Entity
+//!
+//! This module provides the settings of the REST API, read from the `[api]`
+//! section of `oneforall.toml`: the address it listens on, the bearer tokens
+//! clients must present and the role each one grants, and the certificates
+//! used to serve it over HTTPS and, optionally, to require client certificates
+//! (mutual TLS). The same section tells the `jobs` command how to reach and
+//! authenticate to a running instance.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// The configuration file read from the directory OneForAll is run in.
+pub const CONFIG_FILE: &str = "oneforall.toml";
+
+/// The address the REST API listens on when none is configured.
+pub const DEFAULT_API_ADDRESS: &str = "127.0.0.1:8000";
+
+/// Where `trunk build --release` in `dashboard/` writes the browser dashboard.
+pub const DEFAULT_DASHBOARD_DIR: &str = "dashboard/dist";
+
+/// The configuration file; sections other than `[api]` are left to their own readers.
+#[derive(Debug, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    api: ApiConfig,
+}
+
+/// What a caller of the REST API is allowed to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiRole {
+    /// May read metrics, progress and jobs, e.g. a monitoring dashboard.
+    Viewer,
+
+    /// May also launch and cancel jobs.
+    Operator,
+}
+
+impl ApiRole {
+    /// Returns the role name used in the configuration and in logs.
+    pub fn label(&self) -> &'static str {
+        match self {
+            ApiRole::Viewer => "viewer",
+            ApiRole::Operator => "operator",
+        }
+    }
+
+    /// Returns `true` if the role may make a request; only operators may make
+    /// requests that change state, such as launching or cancelling a job.
+    ///
+    /// # Arguments
+    ///
+    /// * `read_only` - Whether the request only reads, i.e. is a GET or HEAD request.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` if the request is allowed.
+    pub fn permits(&self, read_only: bool) -> bool {
+        read_only || *self == ApiRole::Operator
+    }
+}
+
+/// A bearer token accepted by the REST API.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiToken {
+    /// A name identifying the holder in logs, e.g. "grafana".
+    pub name: String,
+
+    /// What the holder may do.
+    pub role: ApiRole,
+
+    /// The token itself.
+    #[serde(default)]
+    pub token: Option<String>,
+
+    /// A file holding the token, so it can be kept out of the configuration file.
+    #[serde(default)]
+    pub token_file: Option<PathBuf>,
+}
+
+/// The REST API settings.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiConfig {
+    /// The address and port to listen on, e.g. "0.0.0.0:8000" to accept remote clients.
+    #[serde(default = "default_address")]
+    pub address: String,
+
+    /// An operator token, shorthand for a `[[api.tokens]]` entry named "default".
+    #[serde(default)]
+    pub token: Option<String>,
+
+    /// A file holding the operator token.
+    #[serde(default)]
+    pub token_file: Option<PathBuf>,
+
+    /// The tokens accepted by the API; once any is set, every `/api/` request must present one.
+    #[serde(default)]
+    pub tokens: Vec<ApiToken>,
+
+    /// Serves the API over HTTPS when present.
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+
+    /// How the `jobs` command reaches a running instance.
+    #[serde(default)]
+    pub client: ClientConfig,
+
+    /// The directory of the built browser dashboard, served at `/dashboard`.
+    #[serde(default = "default_dashboard")]
+    pub dashboard: PathBuf,
+}
+
+/// The certificates the REST API is served with.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConfig {
+    /// The PEM certificate chain of the server, leaf first.
+    pub certificate: PathBuf,
+
+    /// The PEM private key of the server certificate.
+    pub private_key: PathBuf,
+
+    /// The PEM certificates of the CAs that sign client certificates. When set,
+    /// clients without a certificate signed by one of them cannot connect.
+    #[serde(default)]
+    pub client_ca: Option<PathBuf>,
+}
+
+/// The settings the `jobs` command uses to talk to a running instance.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClientConfig {
+    /// The base URL of the instance, e.g. "https://rack12-node3:8000"; derived
+    /// from the listening address when omitted.
+    #[serde(default)]
+    pub url: Option<String>,
+
+    /// The PEM certificate of the CA that signed the server certificate; the
+    /// public web roots are trusted when omitted.
+    #[serde(default)]
+    pub ca_certificate: Option<PathBuf>,
+
+    /// The PEM client certificate presented when the server requires one.
+    #[serde(default)]
+    pub certificate: Option<PathBuf>,
+
+    /// The PEM private key of the client certificate.
+    #[serde(default)]
+    pub private_key: Option<PathBuf>,
+}
+
+fn default_address() -> String {
+    DEFAULT_API_ADDRESS.to_string()
+}
+
+fn default_dashboard() -> PathBuf {
+    PathBuf::from(DEFAULT_DASHBOARD_DIR)
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        ApiConfig {
+            address: default_address(),
+            token: None,
+            token_file: None,
+            tokens: Vec::new(),
+            tls: None,
+            client: ClientConfig::default(),
+            dashboard: default_dashboard(),
+        }
+    }
+}
+
+impl ApiConfig {
+    /// Parses and validates the `[api]` section of a configuration file. The
+    /// `token` shorthand is moved into `tokens` as an operator token.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The TOML text of the configuration file.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ApiConfig, String>` - The API settings, or why they are invalid.
+    pub fn from_toml(text: &str) -> Result<ApiConfig, String> {
+        let file: ConfigFile = toml::from_str(text).map_err(|e| e.to_string())?;
+        let mut config = file.api;
+        config
+            .address
+            .parse::<SocketAddr>()
+            .map_err(|e| format!("Invalid API address '{}': {}", config.address, e))?;
+        if config.token.is_some() || config.token_file.is_some() {
+            config.tokens.insert(
+                0,
+                ApiToken {
+                    name: "default".to_string(),
+                    role: ApiRole::Operator,
+                    token: config.token.take(),
+                    token_file: config.token_file.take(),
+                },
+            );
+        }
+        for token in &config.tokens {
+            if token.token.is_some() == token.token_file.is_some() {
+                return Err(format!(
+                    "Set either `token` or `token_file` for the API token '{}'",
+                    token.name
+                ));
+            }
+            if token.token.as_deref().is_some_and(|t| t.trim().is_empty()) {
+                return Err(format!("The API token '{}' is empty", token.name));
+            }
+        }
+        if config.client.certificate.is_some() != config.client.private_key.is_some() {
+            return Err(
+                "The client `certificate` and `private_key` must be set together".to_string(),
+            );
+        }
+        Ok(config)
+    }
+
+    /// Returns `true` if clients must present a certificate.
+    pub fn requires_client_certificate(&self) -> bool {
+        self.tls.as_ref().is_some_and(|tls| tls.client_ca.is_some())
+    }
+
+    /// Returns `true` if requests are authenticated by a token or a client certificate.
+    pub fn is_authenticated(&self) -> bool {
+        !self.tokens.is_empty() || self.requires_client_certificate()
+    }
+
+    /// Finds the token a caller presented.
+    ///
+    /// Every configured token is compared, so the response time does not reveal
+    /// which one came close.
+    ///
+    /// # Arguments
+    ///
+    /// * `presented` - The token sent by the caller.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<&ApiToken>` - The matching token with its name and role, or `None`.
+    pub fn find_token(&self, presented: &str) -> Option<&ApiToken> {
+        self.tokens.iter().fold(None, |found, token| {
+            let matches = token
+                .token
+                .as_deref()
+                .is_some_and(|expected| token_matches(presented, expected));
+            if matches && found.is_none() {
+                Some(token)
+            } else {
+                found
+            }
+        })
+    }
+
+    /// Returns the token the `jobs` command presents: the first operator token,
+    /// since listing jobs is of little use without being able to cancel them,
+    /// or else the first token.
+    pub fn client_token(&self) -> Option<&str> {
+        self.tokens
+            .iter()
+            .find(|token| token.role == ApiRole::Operator)
+            .or_else(|| self.tokens.first())
+            .and_then(|token| token.token.as_deref())
+    }
+
+    /// Returns `true` if the API only listens on a loopback address.
+    pub fn is_local_only(&self) -> bool {
+        self.address
+            .parse::<SocketAddr>()
+            .is_ok_and(|address| address.ip().is_loopback())
+    }
+
+    /// Returns the base URL a client on this machine uses to reach the API,
+    /// e.g. "https://127.0.0.1:8000".
+    pub fn base_url(&self) -> String {
+        if let Some(url) = &self.client.url {
+            return url.trim_end_matches('/').to_string();
+        }
+        let scheme = if self.tls.is_some() { "https" } else { "http" };
+        // A server listening on every interface is reached through the loopback one.
+        let address = match self.address.parse::<SocketAddr>() {
+            Ok(mut address) if address.ip().is_unspecified() => {
+                address.set_ip(match address.ip() {
+                    IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::LOCALHOST),
+                    IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::LOCALHOST),
+                });
+                address.to_string()
+            }
+            _ => self.address.clone(),
+        };
+        format!("{}://{}", scheme, address)
+    }
+}
+
+/// Compares a presented token with the configured one in constant time, so the
+/// response time does not reveal how much of a guess was right.
+///
+/// # Arguments
+///
+/// * `presented` - The token sent by the client.
+/// * `expected` - The configured token.
+///
+/// # Returns
+///
+/// * `bool` - `true` if the tokens are equal.
+pub fn token_matches(presented: &str, expected: &str) -> bool {
+    let (presented, expected) = (presented.as_bytes(), expected.as_bytes());
+    if presented.len() != expected.len() {
+        return false;
+    }
+    presented
+        .iter()
+        .zip(expected)
+        .fold(0u8, |difference, (a, b)| difference | (a ^ b))
+        == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ApiConfig {
+        ApiConfig::from_toml(
+            r#"
+            [[api.tokens]]
+            name = "ci"
+            role = "operator"
+            token = "operator-secret"
+
+            [[api.tokens]]
+            name = "vault"
+            role = "operator"
+            token_file = "/etc/oneforall/vault.token"
+
+            [[api.tokens]]
+            name = "grafana"
+            role = "viewer"
+            token = "viewer-secret"
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn token_matches_only_the_same_token() {
+        assert!(token_matches("operator-secret", "operator-secret"));
+        assert!(!token_matches("operator-secreT", "operator-secret"));
+        assert!(!token_matches("operator-secre", "operator-secret"));
+        assert!(!token_matches("operator-secret!", "operator-secret"));
+        assert!(!token_matches("", "operator-secret"));
+    }
+
+    #[test]
+    fn find_token_returns_the_token_with_its_role() {
+        let config = config();
+        let token = config.find_token("viewer-secret").unwrap();
+        assert_eq!(token.name, "grafana");
+        assert_eq!(token.role, ApiRole::Viewer);
+        assert_eq!(config.find_token("operator-secret").unwrap().name, "ci");
+    }
+
+    #[test]
+    fn find_token_rejects_unknown_and_empty_tokens() {
+        let config = config();
+        assert!(config.find_token("operator").is_none());
+        // The token read from a file is not loaded yet, and never matches an empty one.
+        assert!(config.find_token("").is_none());
+    }
+}
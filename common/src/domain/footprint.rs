@@ -0,0 +1,171 @@
+//! Footprint Domain Entity
+//!
+//! This module provides the budget OneForAll's own monitoring must stay
+//! within, read from the `[footprint]` section of `oneforall.toml`, and the
+//! overhead it measures of itself: the CPU time of its collectors and web
+//! server, and the memory of the whole process. While the collectors use more
+//! CPU than the budget allows, they sample less often, so monitoring does not
+//! perturb the benchmark it observes. The queues of readings waiting for a
+//! slow sink are bounded by it as well. The REST API serves the overhead at
+//! `/api/v1/self`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::pipeline::OverflowPolicy;
+
+/// The collector the measured overhead is stored under.
+pub const FOOTPRINT_COLLECTOR: &str = "footprint";
+
+/// The most the collectors' intervals are stretched by, so readings never stop.
+pub const MAX_SLOWDOWN: f64 = 10.0;
+
+/// The configuration file; sections other than `[footprint]` are left to their own readers.
+#[derive(Debug, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    footprint: FootprintConfig,
+}
+
+/// The resources OneForAll's own monitoring may use.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FootprintConfig {
+    /// The CPU the collectors and web server may use together, in percent of one CPU.
+    #[serde(default = "default_cpu_percent")]
+    pub cpu_percent: f64,
+
+    /// The resident memory of the process above which buffered readings are written out early.
+    #[serde(default = "default_memory_mb")]
+    pub memory_mb: u64,
+
+    /// The threads serving the REST API.
+    #[serde(default = "default_web_workers")]
+    pub web_workers: usize,
+
+    /// The batches of readings queued for a sink that falls behind, such as a slow disk.
+    #[serde(default = "default_sink_queue")]
+    pub sink_queue: usize,
+
+    /// What a full sink queue gives up to take a new batch.
+    #[serde(default)]
+    pub sink_overflow: OverflowPolicy,
+}
+
+fn default_cpu_percent() -> f64 {
+    5.0
+}
+
+fn default_memory_mb() -> u64 {
+    256
+}
+
+fn default_web_workers() -> usize {
+    2
+}
+
+fn default_sink_queue() -> usize {
+    256
+}
+
+impl Default for FootprintConfig {
+    fn default() -> Self {
+        FootprintConfig {
+            cpu_percent: default_cpu_percent(),
+            memory_mb: default_memory_mb(),
+            web_workers: default_web_workers(),
+            sink_queue: default_sink_queue(),
+            sink_overflow: OverflowPolicy::default(),
+        }
+    }
+}
+
+impl FootprintConfig {
+    /// Parses the `[footprint]` section of a configuration file.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The TOML text of the configuration file.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<FootprintConfig, String>` - The budget, or why it is invalid.
+    pub fn from_toml(text: &str) -> Result<FootprintConfig, String> {
+        let file: ConfigFile = toml::from_str(text).map_err(|e| e.to_string())?;
+        let config = file.footprint;
+        if config.cpu_percent <= 0.0 {
+            return Err("footprint.cpu_percent must be greater than zero".to_string());
+        }
+        if config.web_workers == 0 {
+            return Err("footprint.web_workers must be at least 1".to_string());
+        }
+        if config.sink_queue == 0 {
+            return Err("footprint.sink_queue must be at least 1".to_string());
+        }
+        Ok(config)
+    }
+
+    /// Returns how much longer the collectors should wait between readings,
+    /// given the CPU they and the web server used: 1.0 within the budget, and
+    /// in proportion to the excess above it, up to `MAX_SLOWDOWN`.
+    ///
+    /// # Arguments
+    ///
+    /// * `cpu_percent` - The CPU used, in percent of one CPU.
+    ///
+    /// # Returns
+    ///
+    /// * `f64` - The factor the collectors' intervals are multiplied by.
+    pub fn slowdown(&self, cpu_percent: f64) -> f64 {
+        (cpu_percent / self.cpu_percent).clamp(1.0, MAX_SLOWDOWN)
+    }
+}
+
+/// The CPU used by one part of OneForAll's own monitoring.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentUsage {
+    /// The part, e.g. "power", "processes" or "web server".
+    pub name: String,
+
+    /// The CPU it used over the last interval, in percent of one CPU.
+    pub cpu_percent: f64,
+}
+
+/// The overhead of OneForAll's own monitoring.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Footprint {
+    /// The local time the overhead was measured, in RFC 3339 format.
+    pub timestamp: String,
+
+    /// The CPU used by the collectors and the web server, in percent of one CPU.
+    pub cpu_percent: f64,
+
+    /// The CPU used by the whole process, including benchmarks run in it.
+    pub process_cpu_percent: f64,
+
+    /// The resident memory of the process, in bytes.
+    pub rss_bytes: u64,
+
+    /// The threads of the process.
+    pub threads: usize,
+
+    /// The CPU used by each collector and the web server, busiest first.
+    pub components: Vec<ComponentUsage>,
+
+    /// The budget the monitoring must stay within.
+    pub budget: FootprintConfig,
+
+    /// The factor the collectors' intervals are currently stretched by.
+    pub slowdown: f64,
+}
+
+impl Footprint {
+    /// Returns `true` if the collectors and web server use more CPU than the budget allows.
+    pub fn over_cpu_budget(&self) -> bool {
+        self.cpu_percent > self.budget.cpu_percent
+    }
+
+    /// Returns `true` if the process holds more memory than the budget allows.
+    pub fn over_memory_budget(&self) -> bool {
+        self.rss_bytes > self.budget.memory_mb * 1024 * 1024
+    }
+}
@@ -0,0 +1,121 @@
+//! Health Domain Entity
+//!
+//! This module provides the state of the subsystems an agent needs to do its
+//! work, served at `/healthz` and `/readyz` so load balancers, Kubernetes and
+//! monitoring can tell a functional agent from a process that merely runs.
+
+use serde::{Deserialize, Serialize};
+
+/// The outcome of checking one subsystem.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthCheck {
+    /// The subsystem checked, e.g. "database" or "collector processes".
+    pub name: String,
+
+    /// Whether the subsystem works.
+    pub healthy: bool,
+
+    /// Whether the agent is considered broken, and not merely unready, while
+    /// this check fails; only such checks fail `/healthz`.
+    pub liveness: bool,
+
+    /// What was found, e.g. "2 running, 1 queued" or why the check failed.
+    pub detail: String,
+}
+
+impl HealthCheck {
+    /// Creates a check that only affects readiness.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The subsystem checked.
+    /// * `healthy` - Whether the subsystem works.
+    /// * `detail` - What was found.
+    ///
+    /// # Returns
+    ///
+    /// * `HealthCheck` - The check.
+    pub fn readiness(name: &str, healthy: bool, detail: String) -> Self {
+        HealthCheck {
+            name: name.to_string(),
+            healthy,
+            liveness: false,
+            detail,
+        }
+    }
+
+    /// Creates a check that affects liveness as well as readiness.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The subsystem checked.
+    /// * `healthy` - Whether the subsystem works.
+    /// * `detail` - What was found.
+    ///
+    /// # Returns
+    ///
+    /// * `HealthCheck` - The check.
+    pub fn liveness(name: &str, healthy: bool, detail: String) -> Self {
+        HealthCheck {
+            liveness: true,
+            ..HealthCheck::readiness(name, healthy, detail)
+        }
+    }
+}
+
+/// The outcome of checking every subsystem of an agent.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// The checks, in the order they were made.
+    pub checks: Vec<HealthCheck>,
+}
+
+impl HealthReport {
+    /// Returns `true` if every check the agent cannot work without passed, in
+    /// which case restarting it would not help.
+    pub fn is_live(&self) -> bool {
+        self.checks
+            .iter()
+            .filter(|check| check.liveness)
+            .all(|check| check.healthy)
+    }
+
+    /// Returns `true` if every check passed, so the agent can take requests and jobs.
+    pub fn is_ready(&self) -> bool {
+        self.checks.iter().all(|check| check.healthy)
+    }
+
+    /// Returns the checks that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &HealthCheck> {
+        self.checks.iter().filter(|check| !check.healthy)
+    }
+}
+
+/// The body of a `/healthz` or `/readyz` response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthResponse {
+    /// "ok" if the probe passed, "failing" otherwise.
+    pub status: String,
+
+    /// Every check, including those the probe ignores.
+    pub checks: Vec<HealthCheck>,
+}
+
+impl HealthResponse {
+    /// Creates the response to a probe.
+    ///
+    /// # Arguments
+    ///
+    /// * `passed` - Whether the probe passed.
+    /// * `report` - The checks made.
+    ///
+    /// # Returns
+    ///
+    /// * `HealthResponse` - The response body.
+    pub fn new(passed: bool, report: HealthReport) -> Self {
+        HealthResponse {
+            status: if passed { "ok" } else { "failing" }.to_string(),
+            checks: report.checks,
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//! History Domain Entity
+//!
+//! This module provides the time-series the REST API serves from the stored
+//! collector readings, so the dashboard can chart what happened overnight
+//! during a burn-in rather than only the live readings. Each metric is derived
+//! from the readings of one collector, and long ranges are thinned to a number
+//! of points a chart can draw, keeping the extremes of every thinned stretch.
+
+use serde::{Deserialize, Serialize};
+
+/// How many points a history holds when the query does not say.
+pub const DEFAULT_HISTORY_POINTS: usize = 500;
+
+/// The most points a history holds, whatever the query asks for.
+pub const MAX_HISTORY_POINTS: usize = 5000;
+
+/// How far back a history reaches when the query has no start, in hours.
+pub const DEFAULT_HISTORY_HOURS: i64 = 24;
+
+/// A metric the history can be queried for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryMetric {
+    /// The name of the metric in queries, e.g. "cpu".
+    pub name: &'static str,
+
+    /// The collector whose readings the metric is derived from.
+    pub collector: &'static str,
+
+    /// The unit of the values, e.g. "%".
+    pub unit: &'static str,
+
+    /// What the metric measures.
+    pub description: &'static str,
+
+    /// Whether the readings are rolled up per minute and per hour, so long
+    /// ranges are read from the rollups.
+    pub rolled_up: bool,
+}
+
+/// The metrics the history can be queried for.
+pub const HISTORY_METRICS: [HistoryMetric; 5] = [
+    HistoryMetric {
+        name: "cpu",
+        collector: "cgroups",
+        unit: "%",
+        description: "CPU used by every process together, in percent of one CPU",
+        rolled_up: true,
+    },
+    HistoryMetric {
+        name: "memory",
+        collector: "cgroups",
+        unit: "%",
+        description: "Share of physical memory used by every process together",
+        rolled_up: true,
+    },
+    HistoryMetric {
+        name: "power",
+        collector: "power",
+        unit: "W",
+        description: "Power drawn by the CPU packages",
+        rolled_up: true,
+    },
+    HistoryMetric {
+        name: "steal",
+        collector: "steal",
+        unit: "%",
+        description: "CPU time stolen by the hypervisor, on virtual machines",
+        rolled_up: true,
+    },
+    HistoryMetric {
+        name: "bursts",
+        collector: "bursts",
+        unit: "%",
+        description: "Busiest CPU over each sub-second tick of `overwatch --burst-interval`",
+        rolled_up: false,
+    },
+];
+
+/// Looks up a metric by name.
+///
+/// # Arguments
+///
+/// * `name` - The name of the metric, e.g. "power".
+///
+/// # Returns
+///
+/// * `Result<&'static HistoryMetric, String>` - The metric, or why the name is not one.
+pub fn history_metric(name: &str) -> Result<&'static HistoryMetric, String> {
+    HISTORY_METRICS
+        .iter()
+        .find(|metric| metric.name == name)
+        .ok_or_else(|| {
+            format!(
+                "Unknown metric '{}'; expected one of {}",
+                name,
+                HISTORY_METRICS
+                    .iter()
+                    .map(|metric| metric.name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })
+}
+
+/// The query of a history request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryQuery {
+    /// The metric to chart, e.g. "cpu".
+    pub metric: String,
+
+    /// The RFC 3339 time the history starts at; `DEFAULT_HISTORY_HOURS` before its end when omitted.
+    #[serde(default)]
+    pub from: Option<String>,
+
+    /// The RFC 3339 time the history ends before; now when omitted.
+    #[serde(default)]
+    pub to: Option<String>,
+
+    /// The most points to return; `DEFAULT_HISTORY_POINTS` when omitted.
+    #[serde(default)]
+    pub points: Option<usize>,
+}
+
+impl HistoryQuery {
+    /// Returns the most points to return, within `MAX_HISTORY_POINTS`.
+    pub fn max_points(&self) -> usize {
+        self.points
+            .unwrap_or(DEFAULT_HISTORY_POINTS)
+            .clamp(1, MAX_HISTORY_POINTS)
+    }
+}
+
+/// One point of a history, standing for one or more consecutive readings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryPoint {
+    /// The time of the first reading, in RFC 3339 format.
+    pub timestamp: String,
+
+    /// The average of the readings.
+    pub value: f64,
+
+    /// The lowest of the readings.
+    pub min: f64,
+
+    /// The highest of the readings.
+    pub max: f64,
+
+    /// The 99th percentile of the readings, when the point was read from rollups.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub p99: Option<f64>,
+}
+
+impl HistoryPoint {
+    /// Creates the point of a single reading.
+    pub fn new(timestamp: String, value: f64) -> Self {
+        HistoryPoint {
+            timestamp,
+            value,
+            min: value,
+            max: value,
+            p99: None,
+        }
+    }
+}
+
+/// The time-series of a metric over a time range.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct History {
+    /// The metric charted, e.g. "cpu".
+    pub metric: String,
+
+    /// The unit of the values, e.g. "%".
+    pub unit: String,
+
+    /// The start of the range, in RFC 3339 format.
+    pub from: String,
+
+    /// The end of the range, in RFC 3339 format; now when the query had none.
+    pub to: String,
+
+    /// How many readings the points stand for.
+    pub readings: usize,
+
+    /// What the points were read from: "raw" readings, or "1m" or "1h" rollups
+    /// for long ranges.
+    #[serde(default)]
+    pub resolution: String,
+
+    /// The points, oldest first.
+    pub points: Vec<HistoryPoint>,
+}
+
+/// Thins a time-series to at most `max_points` points, each standing for a
+/// stretch of consecutive points with their average and their extremes, so a
+/// short spike still shows on a chart of a whole night.
+///
+/// # Arguments
+///
+/// * `points` - The time-series, oldest first.
+/// * `max_points` - The most points to keep.
+///
+/// # Returns
+///
+/// * `Vec<HistoryPoint>` - The thinned time-series, oldest first.
+pub fn downsample(points: Vec<HistoryPoint>, max_points: usize) -> Vec<HistoryPoint> {
+    if points.len() <= max_points || max_points == 0 {
+        return points;
+    }
+    let stretch = points.len().div_ceil(max_points);
+    points
+        .chunks(stretch)
+        .map(|chunk| HistoryPoint {
+            timestamp: chunk[0].timestamp.clone(),
+            value: chunk.iter().map(|point| point.value).sum::<f64>() / chunk.len() as f64,
+            min: chunk
+                .iter()
+                .map(|point| point.min)
+                .fold(f64::INFINITY, f64::min),
+            max: chunk
+                .iter()
+                .map(|point| point.max)
+                .fold(f64::NEG_INFINITY, f64::max),
+            // The highest percentile of the stretch, as the extremes are kept.
+            p99: chunk
+                .iter()
+                .filter_map(|point| point.p99)
+                .reduce(f64::max),
+        })
+        .collect()
+}
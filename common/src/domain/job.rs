@@ -0,0 +1,138 @@
+//! Job Domain Entity
+//!
+//! This module provides the job record shared between the command handlers,
+//! which run stress tests and benchmarks as jobs, and the web server, which
+//! lists them and cancels them through the REST API. Each job declares the
+//! machine resources it loads so that mutually exclusive workloads are
+//! serialized while compatible ones run side by side.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+/// The lifecycle state of a job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    /// The job waits for a conflicting job to finish.
+    Queued,
+
+    /// The job is still running.
+    Running,
+
+    /// The job ran to completion.
+    Completed,
+
+    /// The job stopped because of an error.
+    Failed,
+
+    /// The job was cancelled by the operator.
+    Cancelled,
+}
+
+impl JobStatus {
+    /// Returns `true` once the job can no longer change state.
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled
+        )
+    }
+}
+
+/// A machine resource that a job loads and therefore needs to itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Resource {
+    Cpu,
+    Memory,
+    Disk,
+    Network,
+    Gpu,
+}
+
+/// What a job runs and which resources it needs exclusively.
+///
+/// Two jobs may run at the same time only if they share no resource, e.g. a
+/// CPU stress test and a disk benchmark, but not a CPU stress test and a
+/// memory benchmark whose results the stress test would skew.
+#[derive(Debug, Clone)]
+pub struct JobSpec {
+    /// What the job runs, e.g. "stress:cpu".
+    pub kind: String,
+
+    /// The resources the job needs exclusively.
+    pub resources: BTreeSet<Resource>,
+}
+
+impl JobSpec {
+    /// Creates a job specification.
+    pub fn new(kind: &str, resources: &[Resource]) -> Self {
+        JobSpec {
+            kind: kind.to_string(),
+            resources: resources.iter().copied().collect(),
+        }
+    }
+}
+
+/// A long-running operation, such as a stress test, that can be cancelled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    /// The job ID, unique within the running application.
+    pub id: u64,
+
+    /// What the job runs, e.g. "stress:cpu".
+    pub kind: String,
+
+    /// The resources the job needs exclusively.
+    pub resources: BTreeSet<Resource>,
+
+    /// The lifecycle state of the job.
+    pub status: JobStatus,
+
+    /// The local time the job was submitted, in RFC 3339 format.
+    pub started_at: String,
+
+    /// The local time the job finished, in RFC 3339 format.
+    pub finished_at: Option<String>,
+
+    /// A short explanation of the outcome, e.g. the error of a failed job.
+    pub message: Option<String>,
+}
+
+/// Renders a list of jobs as a table for the terminal.
+pub fn format_job_table(jobs: &[Job]) -> String {
+    if jobs.is_empty() {
+        return "No jobs.\n".to_string();
+    }
+
+    let mut output = format!(
+        "{:>4}  {:<18} {:<10} {:<24} {}\n",
+        "ID", "KIND", "STATUS", "RESOURCES", "MESSAGE"
+    );
+    for job in jobs {
+        let resources = job
+            .resources
+            .iter()
+            .map(|r| format!("{:?}", r).to_lowercase())
+            .collect::<Vec<_>>()
+            .join(",");
+        let status = format!("{:?}", job.status).to_lowercase();
+        output.push_str(&format!(
+            "{:>4}  {:<18} {:<10} {:<24} {}\n",
+            job.id,
+            job.kind,
+            status,
+            resources,
+            job.message.as_deref().unwrap_or("")
+        ));
+    }
+    output
+}
+
+/// The body of a request to launch a profile as a job through the API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LaunchRequest {
+    /// The name of a built-in or saved profile, e.g. "burn-in".
+    pub profile: String,
+}
@@ -0,0 +1,60 @@
+//! Log Record Domain Entity
+//!
+//! This module provides the log messages the daemon streams to remote
+//! readers, so operators can follow them through `oneforall logs --follow`
+//! or `GET /api/v1/logs/stream` instead of logging in to read the `logs/`
+//! directory.
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// A log message as streamed by the REST API.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogRecord {
+    /// The local time the message was logged, in RFC 3339 format.
+    pub timestamp: String,
+
+    /// The severity, e.g. "INFO" or "WARN".
+    pub level: String,
+
+    /// The module the message was logged from, e.g. "one_for_all".
+    pub target: String,
+
+    /// The message.
+    pub message: String,
+}
+
+impl LogRecord {
+    /// Returns `true` if the message is at least as severe as `level`.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The least severe level to keep.
+    pub fn is_at_least(&self, level: log::Level) -> bool {
+        log::Level::from_str(&self.level).is_ok_and(|own| own <= level)
+    }
+
+    /// Renders the message as one line, as in the log files.
+    pub fn render_line(&self) -> String {
+        format!("{} [{}] - {}", self.timestamp, self.level, self.message)
+    }
+}
+
+/// Parses the least severe level a reader wants to see.
+///
+/// # Arguments
+///
+/// * `level` - One of "error", "warn", "info", "debug" or "trace", in any case.
+///
+/// # Returns
+///
+/// * `Result<log::Level, String>` - The level, or why it is not one.
+pub fn parse_log_level(level: &str) -> Result<log::Level, String> {
+    log::Level::from_str(level).map_err(|_| {
+        format!(
+            "Unknown log level '{}'; expected error, warn, info, debug or trace",
+            level
+        )
+    })
+}
@@ -0,0 +1,215 @@
+//! Metric Domain Entity
+//!
+//! This module provides the metric model every collector publishes its readings
+//! in: a named value with its unit, the labels telling series apart, such as the
+//! sensor or the GPU, and the time it was taken. Power, temperatures, GPU
+//! readings, process usage and stress test progress all reach the registry in
+//! this one shape, so a sink such as the database, a Prometheus scrape, a CSV
+//! file or a WebSocket client handles every collector the same way.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// The collector the metrics are stored under in the database.
+pub const METRICS_COLLECTOR: &str = "metrics";
+
+/// The prefix of every metric name in the Prometheus exposition format.
+pub const PROMETHEUS_PREFIX: &str = "oneforall_";
+
+/// The header of the CSV files metrics are written to.
+pub const METRIC_CSV_HEADER: &str = "timestamp,name,unit,labels,value";
+
+/// How readings of one series are combined when several stand for one, such as
+/// when a full queue folds batches together.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Aggregation {
+    /// The readings are averaged, as for a gauge such as a temperature.
+    #[default]
+    Mean,
+
+    /// The highest reading is kept, as for a peak.
+    Max,
+
+    /// The readings are added up, as for a count of events since the last reading.
+    Sum,
+
+    /// The latest reading is kept, as for a counter that only grows.
+    Last,
+}
+
+impl Aggregation {
+    /// Returns `true` for `Mean`, the aggregation left out of serialized readings.
+    pub fn is_mean(&self) -> bool {
+        *self == Aggregation::Mean
+    }
+}
+
+/// One reading of one measurement.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Metric {
+    /// The measurement, ending in its unit as Prometheus names do, e.g. "power_watts".
+    pub name: String,
+
+    /// The unit of the value, e.g. "watts", "celsius" or "percent".
+    pub unit: String,
+
+    /// What tells this series apart from others of the same name, e.g. `domain` = "package-0".
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+
+    /// The value read.
+    pub value: f64,
+
+    /// The local time the value was read, in RFC 3339 format.
+    pub timestamp: String,
+
+    /// How readings of the series are combined; averaged unless set.
+    #[serde(default, skip_serializing_if = "Aggregation::is_mean")]
+    pub aggregation: Aggregation,
+}
+
+impl Metric {
+    /// Creates a reading without labels.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The measurement, e.g. "temperature_celsius".
+    /// * `unit` - The unit of the value, e.g. "celsius".
+    /// * `value` - The value read.
+    /// * `timestamp` - The local time the value was read, in RFC 3339 format.
+    ///
+    /// # Returns
+    ///
+    /// * `Metric` - The reading.
+    pub fn new(name: &str, unit: &str, value: f64, timestamp: &str) -> Self {
+        Metric {
+            name: name.to_string(),
+            unit: unit.to_string(),
+            labels: BTreeMap::new(),
+            value,
+            timestamp: timestamp.to_string(),
+            aggregation: Aggregation::Mean,
+        }
+    }
+
+    /// Adds a label to the reading.
+    pub fn with_label(mut self, key: &str, value: &str) -> Self {
+        self.labels.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Sets how readings of the series are combined, e.g. `Aggregation::Max` for a peak.
+    pub fn with_aggregation(mut self, aggregation: Aggregation) -> Self {
+        self.aggregation = aggregation;
+        self
+    }
+
+    /// Identifies the series the reading belongs to, e.g. `power_watts{domain="dram"}`;
+    /// the registry keeps the latest reading of each.
+    pub fn series(&self) -> String {
+        format!("{}{}", self.name, self.label_set())
+    }
+
+    /// Renders the labels as a Prometheus label set, e.g. `{domain="dram"}`, or
+    /// nothing when there are none.
+    fn label_set(&self) -> String {
+        if self.labels.is_empty() {
+            return String::new();
+        }
+        let labels: Vec<String> = self
+            .labels
+            .iter()
+            .map(|(key, value)| format!("{}=\"{}\"", sanitize_name(key), escape_label(value)))
+            .collect();
+        format!("{{{}}}", labels.join(","))
+    }
+
+    /// Renders the reading as a row of `METRIC_CSV_HEADER`, the labels as `key=value`
+    /// pairs separated by semicolons.
+    pub fn csv_row(&self) -> String {
+        let labels: Vec<String> = self
+            .labels
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        [
+            csv_field(&self.timestamp),
+            csv_field(&self.name),
+            csv_field(&self.unit),
+            csv_field(&labels.join(";")),
+            self.value.to_string(),
+        ]
+        .join(",")
+    }
+}
+
+/// Replaces the characters Prometheus does not allow in names with underscores.
+fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Escapes a label value for the Prometheus exposition format.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Quotes a CSV field if it holds a separator, a quote or a line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Renders readings in the Prometheus text exposition format, as gauges named
+/// with the `oneforall_` prefix.
+///
+/// # Arguments
+///
+/// * `metrics` - The latest reading of each series.
+///
+/// # Returns
+///
+/// * `String` - A `# HELP` and a `# TYPE` line per metric name, followed by a line per series.
+pub fn render_prometheus(metrics: &[Metric]) -> String {
+    let mut by_name: BTreeMap<String, Vec<&Metric>> = BTreeMap::new();
+    for metric in metrics {
+        by_name
+            .entry(sanitize_name(&metric.name))
+            .or_default()
+            .push(metric);
+    }
+
+    let mut output = String::new();
+    for (name, series) in by_name {
+        output.push_str(&format!(
+            "# HELP {}{} In {}.\n",
+            PROMETHEUS_PREFIX, name, series[0].unit
+        ));
+        output.push_str(&format!("# TYPE {}{} gauge\n", PROMETHEUS_PREFIX, name));
+        for metric in series {
+            output.push_str(&format!(
+                "{}{}{} {}\n",
+                PROMETHEUS_PREFIX,
+                name,
+                metric.label_set(),
+                metric.value
+            ));
+        }
+    }
+    output
+}
@@ -0,0 +1,11 @@
+pub mod api_config;
+pub mod footprint;
+pub mod health;
+pub mod history;
+pub mod job;
+pub mod log_record;
+pub mod metric;
+pub mod network_probe;
+pub mod openapi;
+pub mod pipeline;
+pub mod telemetry;
@@ -0,0 +1,30 @@
+//! Network Probe Domain Entity
+//!
+//! This module provides the types exchanged when one fleet agent measures the
+//! network path to another over the REST API, so the orchestrator can assemble
+//! an all-to-all latency and bandwidth matrix of the fleet.
+
+use serde::{Deserialize, Serialize};
+
+/// The largest payload an agent serves for a bandwidth measurement.
+pub const MAX_PROBE_PAYLOAD_BYTES: usize = 64 * 1024 * 1024;
+
+/// Asks an agent to measure the path to another agent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProbeRequest {
+    /// The base URL of the REST API of the agent to measure to.
+    pub target: String,
+}
+
+/// The latency and bandwidth measured from one agent to another.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkMeasurement {
+    /// The base URL of the REST API of the agent measured to.
+    pub target: String,
+
+    /// The median round trip of an empty request, in microseconds.
+    pub latency_us: f64,
+
+    /// The throughput of downloading payloads from the target, in megabits per second.
+    pub bandwidth_mbit_s: f64,
+}
@@ -0,0 +1,1036 @@
+//! OpenAPI Domain Entity
+//!
+//! This module describes every route of the REST API in one table, from which
+//! the OpenAPI 3 document served at `/api/openapi.json` is generated, so
+//! integrators can build clients against a documented API surface. The server
+//! registers its documented routes from a list checked against this table when
+//! it starts, so the document cannot drift from the routes served. Routes
+//! under `/api/v1` keep their meaning for as long as they are served; a
+//! change that would break a client is made under a new version prefix.
+
+use serde_json::{json, Map, Value};
+
+/// The version of the REST API, the prefix of its routes.
+pub const API_VERSION: &str = "v1";
+
+/// Where the OpenAPI document is served.
+pub const OPENAPI_PATH: &str = "/api/openapi.json";
+
+/// Where the interactive documentation is served.
+pub const API_DOCS_PATH: &str = "/api/docs";
+
+/// Where the Swagger UI stylesheet bundled with the server is served.
+pub const SWAGGER_UI_CSS_PATH: &str = "/api/docs/swagger-ui.css";
+
+/// Where the Swagger UI script bundled with the server is served.
+pub const SWAGGER_UI_JS_PATH: &str = "/api/docs/swagger-ui-bundle.js";
+
+/// Where a parameter is passed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterLocation {
+    Path,
+    Query,
+}
+
+/// A parameter of an operation.
+#[derive(Debug, Clone, Copy)]
+pub struct ApiParameter {
+    /// The parameter name, e.g. "id".
+    pub name: &'static str,
+
+    /// Where the parameter is passed.
+    pub location: ParameterLocation,
+
+    /// The JSON schema type of the parameter, e.g. "integer".
+    pub schema_type: &'static str,
+
+    /// What the parameter means.
+    pub description: &'static str,
+}
+
+/// What a response carries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiContent {
+    /// Nothing.
+    Empty,
+
+    /// A plain-text message, e.g. why the request failed.
+    Text,
+
+    /// A JSON document of a schema in the components of the document.
+    Json(&'static str),
+
+    /// A JSON array of a schema in the components of the document.
+    JsonArray(&'static str),
+
+    /// Server-sent events, each carrying a JSON document of a schema.
+    EventStream(&'static str),
+
+    /// Opaque bytes.
+    Binary,
+
+    /// HTML.
+    Html,
+}
+
+/// A response of an operation.
+#[derive(Debug, Clone, Copy)]
+pub struct ApiResponse {
+    /// The HTTP status.
+    pub status: u16,
+
+    /// When the response is sent.
+    pub description: &'static str,
+
+    /// What the response carries.
+    pub content: ApiContent,
+}
+
+/// An operation of the REST API.
+#[derive(Debug, Clone, Copy)]
+pub struct ApiOperation {
+    /// The HTTP method, in lowercase.
+    pub method: &'static str,
+
+    /// The route, with parameters in braces, e.g. "/api/v1/jobs/{id}".
+    pub path: &'static str,
+
+    /// A unique name for generated clients, e.g. "getJob".
+    pub operation_id: &'static str,
+
+    /// The group the operation is listed under, e.g. "jobs".
+    pub tag: &'static str,
+
+    /// What the operation does.
+    pub summary: &'static str,
+
+    /// The parameters of the operation.
+    pub parameters: &'static [ApiParameter],
+
+    /// The schema of the JSON request body, if the operation takes one.
+    pub request_body: Option<&'static str>,
+
+    /// The responses of the operation.
+    pub responses: &'static [ApiResponse],
+}
+
+impl ApiOperation {
+    /// Returns `true` if the caller needs a token, i.e. for the routes under `/api/`
+    /// other than the documentation itself.
+    pub fn is_protected(&self) -> bool {
+        self.path.starts_with("/api/") && !is_documentation(self.path)
+    }
+
+    /// Returns `true` if the operation changes something, which only operator
+    /// tokens may do.
+    pub fn is_write(&self) -> bool {
+        !matches!(self.method, "get" | "head")
+    }
+}
+
+/// Returns `true` if a path serves the API documentation, which needs no token.
+///
+/// # Arguments
+///
+/// * `path` - The path of a request.
+pub fn is_documentation(path: &str) -> bool {
+    [
+        OPENAPI_PATH,
+        API_DOCS_PATH,
+        SWAGGER_UI_CSS_PATH,
+        SWAGGER_UI_JS_PATH,
+    ]
+    .contains(&path)
+}
+
+/// Checks that the documented routes a server registers are exactly the
+/// operations of `API_OPERATIONS`.
+///
+/// # Arguments
+///
+/// * `routes` - The method, in lowercase, and the path of every documented route.
+///
+/// # Returns
+///
+/// * `Result<(), String>` - `Ok` if they match, or the routes missing from either side.
+pub fn check_routes(routes: &[(&str, &str)]) -> Result<(), String> {
+    let undocumented: Vec<String> = routes
+        .iter()
+        .filter(|(method, path)| {
+            !API_OPERATIONS
+                .iter()
+                .any(|operation| operation.method == *method && operation.path == *path)
+        })
+        .map(|(method, path)| format!("{} {}", method.to_uppercase(), path))
+        .collect();
+    let unrouted: Vec<String> = API_OPERATIONS
+        .iter()
+        .filter(|operation| !routes.contains(&(operation.method, operation.path)))
+        .map(|operation| format!("{} {}", operation.method.to_uppercase(), operation.path))
+        .collect();
+    let mut problems = Vec::new();
+    if !undocumented.is_empty() {
+        problems.push(format!(
+            "routes missing from the OpenAPI table: {}",
+            undocumented.join(", ")
+        ));
+    }
+    if !unrouted.is_empty() {
+        problems.push(format!(
+            "documented operations without a route: {}",
+            unrouted.join(", ")
+        ));
+    }
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems.join("; "))
+    }
+}
+
+/// Every operation of the REST API, in the order they are listed.
+pub const API_OPERATIONS: &[ApiOperation] = &[
+    ApiOperation {
+        method: "get",
+        path: "/status",
+        operation_id: "getStatus",
+        tag: "health",
+        summary: "Tells whether the server is running.",
+        parameters: &[],
+        request_body: None,
+        responses: &[ApiResponse {
+            status: 200,
+            description: "The server is running.",
+            content: ApiContent::Text,
+        }],
+    },
+    ApiOperation {
+        method: "get",
+        path: "/healthz",
+        operation_id: "getLiveness",
+        tag: "health",
+        summary:
+            "The liveness probe: fails if a subsystem the agent cannot work without is broken.",
+        parameters: &[],
+        request_body: None,
+        responses: &[
+            ApiResponse {
+                status: 200,
+                description: "The agent is live.",
+                content: ApiContent::Json("HealthResponse"),
+            },
+            ApiResponse {
+                status: 503,
+                description: "The agent is broken and should be restarted.",
+                content: ApiContent::Json("HealthResponse"),
+            },
+        ],
+    },
+    ApiOperation {
+        method: "get",
+        path: "/readyz",
+        operation_id: "getReadiness",
+        tag: "health",
+        summary: "The readiness probe: fails if any check fails, including while shutting down.",
+        parameters: &[],
+        request_body: None,
+        responses: &[
+            ApiResponse {
+                status: 200,
+                description: "The agent can take requests and jobs.",
+                content: ApiContent::Json("HealthResponse"),
+            },
+            ApiResponse {
+                status: 503,
+                description: "The agent should not be sent requests or jobs.",
+                content: ApiContent::Json("HealthResponse"),
+            },
+        ],
+    },
+    ApiOperation {
+        method: "get",
+        path: "/console",
+        operation_id: "showConsole",
+        tag: "telemetry",
+        summary: "The web console showing live readings.",
+        parameters: &[],
+        request_body: None,
+        responses: &[ApiResponse {
+            status: 200,
+            description: "The console page.",
+            content: ApiContent::Html,
+        }],
+    },
+    ApiOperation {
+        method: "get",
+        path: "/dashboard",
+        operation_id: "showDashboard",
+        tag: "telemetry",
+        summary: "The WebAssembly dashboard showing readings, jobs, health and footprint.",
+        parameters: &[],
+        request_body: None,
+        responses: &[
+            ApiResponse {
+                status: 200,
+                description: "The dashboard page.",
+                content: ApiContent::Html,
+            },
+            ApiResponse {
+                status: 404,
+                description: "The dashboard has not been built.",
+                content: ApiContent::Text,
+            },
+        ],
+    },
+    ApiOperation {
+        method: "get",
+        path: "/api/v1/power",
+        operation_id: "getPower",
+        tag: "telemetry",
+        summary: "Returns the latest power reading.",
+        parameters: &[],
+        request_body: None,
+        responses: &[
+            ApiResponse {
+                status: 200,
+                description: "The latest power reading.",
+                content: ApiContent::Json("PowerSample"),
+            },
+            ApiResponse {
+                status: 404,
+                description: "No power source has reported yet.",
+                content: ApiContent::Text,
+            },
+        ],
+    },
+    ApiOperation {
+        method: "get",
+        path: "/api/v1/progress",
+        operation_id: "getProgress",
+        tag: "telemetry",
+        summary: "Returns the progress of the running, or last, stress test.",
+        parameters: &[],
+        request_body: None,
+        responses: &[
+            ApiResponse {
+                status: 200,
+                description: "The progress of the stress test.",
+                content: ApiContent::Json("StressProgress"),
+            },
+            ApiResponse {
+                status: 404,
+                description: "No stress test has run since the server started.",
+                content: ApiContent::Text,
+            },
+        ],
+    },
+    ApiOperation {
+        method: "get",
+        path: "/api/v1/metrics",
+        operation_id: "getMetrics",
+        tag: "telemetry",
+        summary: "Returns the latest reading of every metric in the Prometheus text format.",
+        parameters: &[],
+        request_body: None,
+        responses: &[ApiResponse {
+            status: 200,
+            description: "One gauge per metric, named with the oneforall_ prefix.",
+            content: ApiContent::Text,
+        }],
+    },
+    ApiOperation {
+        method: "get",
+        path: "/api/v1/pipelines",
+        operation_id: "listPipelines",
+        tag: "telemetry",
+        summary: "Returns the queue in front of each sink and the readings it dropped.",
+        parameters: &[],
+        request_body: None,
+        responses: &[ApiResponse {
+            status: 200,
+            description: "The counters of every sink queue, by name.",
+            content: ApiContent::JsonArray("PipelineStats"),
+        }],
+    },
+    ApiOperation {
+        method: "get",
+        path: "/api/v1/ws",
+        operation_id: "streamTelemetry",
+        tag: "telemetry",
+        summary: "Upgrades to a WebSocket streaming every telemetry event as a JSON text frame.",
+        parameters: &[],
+        request_body: None,
+        responses: &[ApiResponse {
+            status: 101,
+            description: "Switched to a WebSocket of TelemetryEvent frames.",
+            content: ApiContent::Empty,
+        }],
+    },
+    ApiOperation {
+        method: "get",
+        path: "/api/v1/jobs",
+        operation_id: "listJobs",
+        tag: "jobs",
+        summary: "Lists every job submitted since the server started, oldest first.",
+        parameters: &[],
+        request_body: None,
+        responses: &[ApiResponse {
+            status: 200,
+            description: "The jobs.",
+            content: ApiContent::JsonArray("Job"),
+        }],
+    },
+    ApiOperation {
+        method: "post",
+        path: "/api/v1/jobs",
+        operation_id: "launchJob",
+        tag: "jobs",
+        summary: "Starts running a built-in or saved profile as a job without waiting for it.",
+        parameters: &[],
+        request_body: Some("LaunchRequest"),
+        responses: &[
+            ApiResponse {
+                status: 202,
+                description: "The profile was submitted as a job.",
+                content: ApiContent::Json("Job"),
+            },
+            ApiResponse {
+                status: 400,
+                description: "The profile does not exist or cannot be run.",
+                content: ApiContent::Text,
+            },
+        ],
+    },
+    ApiOperation {
+        method: "get",
+        path: "/api/v1/jobs/{id}",
+        operation_id: "getJob",
+        tag: "jobs",
+        summary: "Returns a job.",
+        parameters: &[JOB_ID],
+        request_body: None,
+        responses: &[
+            ApiResponse {
+                status: 200,
+                description: "The job.",
+                content: ApiContent::Json("Job"),
+            },
+            ApiResponse {
+                status: 404,
+                description: "The job is unknown.",
+                content: ApiContent::Text,
+            },
+        ],
+    },
+    ApiOperation {
+        method: "delete",
+        path: "/api/v1/jobs/{id}",
+        operation_id: "cancelJob",
+        tag: "jobs",
+        summary: "Cancels a queued or running job; a running job stops asynchronously.",
+        parameters: &[JOB_ID],
+        request_body: None,
+        responses: &[
+            ApiResponse {
+                status: 202,
+                description: "Cancellation was requested.",
+                content: ApiContent::Json("Job"),
+            },
+            ApiResponse {
+                status: 404,
+                description: "The job is unknown.",
+                content: ApiContent::Text,
+            },
+            ApiResponse {
+                status: 409,
+                description: "The job has already finished.",
+                content: ApiContent::Text,
+            },
+        ],
+    },
+    ApiOperation {
+        method: "post",
+        path: "/api/v1/netprobe",
+        operation_id: "probeLink",
+        tag: "fleet",
+        summary: "Measures the latency and bandwidth from this agent to another.",
+        parameters: &[],
+        request_body: Some("ProbeRequest"),
+        responses: &[
+            ApiResponse {
+                status: 200,
+                description: "The measured link.",
+                content: ApiContent::Json("LinkMeasurement"),
+            },
+            ApiResponse {
+                status: 502,
+                description: "The other agent could not be measured.",
+                content: ApiContent::Text,
+            },
+        ],
+    },
+    ApiOperation {
+        method: "get",
+        path: "/api/v1/netprobe/payload",
+        operation_id: "getProbePayload",
+        tag: "fleet",
+        summary: "Returns a payload of the requested size for another agent to time.",
+        parameters: &[ApiParameter {
+            name: "bytes",
+            location: ParameterLocation::Query,
+            schema_type: "integer",
+            description: "The size of the payload, at most 64 MiB; empty when omitted.",
+        }],
+        request_body: None,
+        responses: &[ApiResponse {
+            status: 200,
+            description: "The payload.",
+            content: ApiContent::Binary,
+        }],
+    },
+    ApiOperation {
+        method: "get",
+        path: "/api/v1/score",
+        operation_id: "getScore",
+        tag: "fleet",
+        summary: "Returns the benchmark scores of this machine, which the fleet is ranked by.",
+        parameters: &[],
+        request_body: None,
+        responses: &[
+            ApiResponse {
+                status: 200,
+                description: "The scores.",
+                content: ApiContent::Json("Score"),
+            },
+            ApiResponse {
+                status: 500,
+                description: "The stored results could not be read.",
+                content: ApiContent::Text,
+            },
+        ],
+    },
+    ApiOperation {
+        method: "get",
+        path: "/api/v1/self",
+        operation_id: "getFootprint",
+        tag: "telemetry",
+        summary: "Returns the overhead of the monitoring and the budget it keeps to.",
+        parameters: &[],
+        request_body: None,
+        responses: &[
+            ApiResponse {
+                status: 200,
+                description: "The last measurement.",
+                content: ApiContent::Json("Footprint"),
+            },
+            ApiResponse {
+                status: 404,
+                description: "The footprint has not been measured yet.",
+                content: ApiContent::Text,
+            },
+        ],
+    },
+    ApiOperation {
+        method: "get",
+        path: "/api/v1/history",
+        operation_id: "getHistory",
+        tag: "telemetry",
+        summary: "Returns the time-series of a stored metric over a time range.",
+        parameters: &[HISTORY_METRIC, HISTORY_FROM, HISTORY_TO, HISTORY_POINTS],
+        request_body: None,
+        responses: &[
+            ApiResponse {
+                status: 200,
+                description: "The history, thinned to the points asked for.",
+                content: ApiContent::Json("History"),
+            },
+            ApiResponse {
+                status: 400,
+                description: "The metric or the time range is invalid.",
+                content: ApiContent::Text,
+            },
+        ],
+    },
+    ApiOperation {
+        method: "get",
+        path: "/api/v1/logs",
+        operation_id: "getLogs",
+        tag: "logs",
+        summary: "Returns the most recent log messages, oldest first.",
+        parameters: &[LOG_LEVEL, LOG_LINES],
+        request_body: None,
+        responses: &[
+            ApiResponse {
+                status: 200,
+                description: "The messages.",
+                content: ApiContent::JsonArray("LogRecord"),
+            },
+            ApiResponse {
+                status: 400,
+                description: "The level is unknown.",
+                content: ApiContent::Text,
+            },
+        ],
+    },
+    ApiOperation {
+        method: "get",
+        path: "/api/v1/logs/stream",
+        operation_id: "streamLogs",
+        tag: "logs",
+        summary: "Streams the recent log messages, then every new one, as server-sent events.",
+        parameters: &[LOG_LEVEL, LOG_LINES],
+        request_body: None,
+        responses: &[
+            ApiResponse {
+                status: 200,
+                description: "One `data:` line per message; missed messages are announced \
+                              with a `skipped` event carrying their number.",
+                content: ApiContent::EventStream("LogRecord"),
+            },
+            ApiResponse {
+                status: 400,
+                description: "The level is unknown.",
+                content: ApiContent::Text,
+            },
+        ],
+    },
+    ApiOperation {
+        method: "get",
+        path: OPENAPI_PATH,
+        operation_id: "getOpenApi",
+        tag: "documentation",
+        summary: "Returns this document.",
+        parameters: &[],
+        request_body: None,
+        responses: &[ApiResponse {
+            status: 200,
+            description: "The OpenAPI document.",
+            content: ApiContent::Json("OpenApi"),
+        }],
+    },
+    ApiOperation {
+        method: "get",
+        path: API_DOCS_PATH,
+        operation_id: "showApiDocs",
+        tag: "documentation",
+        summary: "Browses this document with Swagger UI.",
+        parameters: &[],
+        request_body: None,
+        responses: &[ApiResponse {
+            status: 200,
+            description: "The Swagger UI page.",
+            content: ApiContent::Html,
+        }],
+    },
+];
+
+const JOB_ID: ApiParameter = ApiParameter {
+    name: "id",
+    location: ParameterLocation::Path,
+    schema_type: "integer",
+    description: "The job ID.",
+};
+
+const LOG_LEVEL: ApiParameter = ApiParameter {
+    name: "level",
+    location: ParameterLocation::Query,
+    schema_type: "string",
+    description:
+        "The least severe level returned: error, warn, info, debug or trace; info when omitted.",
+};
+
+const LOG_LINES: ApiParameter = ApiParameter {
+    name: "lines",
+    location: ParameterLocation::Query,
+    schema_type: "integer",
+    description: "The number of recent messages returned; 20 when omitted.",
+};
+
+const HISTORY_METRIC: ApiParameter = ApiParameter {
+    name: "metric",
+    location: ParameterLocation::Query,
+    schema_type: "string",
+    description: "The metric charted, required: cpu, memory, power, steal or bursts.",
+};
+
+const HISTORY_FROM: ApiParameter = ApiParameter {
+    name: "from",
+    location: ParameterLocation::Query,
+    schema_type: "string",
+    description: "The RFC 3339 time the history starts at; 24 hours before its end when omitted.",
+};
+
+const HISTORY_TO: ApiParameter = ApiParameter {
+    name: "to",
+    location: ParameterLocation::Query,
+    schema_type: "string",
+    description: "The RFC 3339 time the history ends before; now when omitted.",
+};
+
+const HISTORY_POINTS: ApiParameter = ApiParameter {
+    name: "points",
+    location: ParameterLocation::Query,
+    schema_type: "integer",
+    description: "The most points returned, at most 5000; 500 when omitted.",
+};
+
+/// Returns the JSON schemas of the documents the REST API exchanges.
+fn schemas() -> Value {
+    let nullable_number = json!({ "type": "number", "nullable": true });
+    let nullable_string = json!({ "type": "string", "nullable": true });
+    json!({
+        "DomainPower": {
+            "type": "object",
+            "required": ["domain", "watts"],
+            "properties": {
+                "domain": { "type": "string", "description": "The power domain, e.g. \"package-0\"." },
+                "watts": { "type": "number" }
+            }
+        },
+        "PowerSample": {
+            "type": "object",
+            "required": ["timestamp", "domains"],
+            "properties": {
+                "timestamp": { "type": "string", "format": "date-time" },
+                "domains": { "type": "array", "items": { "$ref": "#/components/schemas/DomainPower" } },
+                "bmc_watts": nullable_number
+            }
+        },
+        "StressProgress": {
+            "type": "object",
+            "required": ["timestamp", "test", "elapsed_secs", "duration_secs", "finished"],
+            "properties": {
+                "timestamp": { "type": "string", "format": "date-time" },
+                "test": { "type": "string" },
+                "elapsed_secs": { "type": "number" },
+                "duration_secs": { "type": "number" },
+                "bogo_ops_per_sec": nullable_number,
+                "hottest_celsius": nullable_number,
+                "package_watts": nullable_number,
+                "finished": { "type": "boolean" }
+            }
+        },
+        "Metric": {
+            "type": "object",
+            "required": ["name", "unit", "value", "timestamp"],
+            "properties": {
+                "name": { "type": "string", "description": "The measurement, e.g. \"power_watts\"." },
+                "unit": { "type": "string", "description": "The unit of the value, e.g. \"watts\"." },
+                "labels": { "type": "object", "additionalProperties": { "type": "string" } },
+                "value": { "type": "number" },
+                "timestamp": { "type": "string", "format": "date-time" },
+                "aggregation": { "type": "string", "enum": ["mean", "max", "sum", "last"], "description": "How readings of the series are combined; \"mean\" when omitted." }
+            }
+        },
+        "MetricBatch": {
+            "type": "object",
+            "required": ["metrics"],
+            "properties": {
+                "metrics": { "type": "array", "items": { "$ref": "#/components/schemas/Metric" } }
+            }
+        },
+        "PipelineStats": {
+            "type": "object",
+            "required": [
+                "name", "overflow", "capacity", "queued", "received", "written", "dropped", "aggregated"
+            ],
+            "properties": {
+                "name": { "type": "string", "description": "The sink, e.g. \"metrics-csv\" or \"database\"." },
+                "overflow": { "type": "string", "enum": ["drop-oldest", "drop-newest", "aggregate"] },
+                "capacity": { "type": "integer" },
+                "queued": { "type": "integer" },
+                "received": { "type": "integer", "format": "int64" },
+                "written": { "type": "integer", "format": "int64" },
+                "dropped": { "type": "integer", "format": "int64" },
+                "aggregated": { "type": "integer", "format": "int64" }
+            }
+        },
+        "TelemetryEvent": {
+            "description": "A WebSocket frame; `type` is \"power\", \"stress_progress\" or \"metrics\".",
+            "oneOf": [
+                { "$ref": "#/components/schemas/PowerSample" },
+                { "$ref": "#/components/schemas/StressProgress" },
+                { "$ref": "#/components/schemas/MetricBatch" }
+            ],
+            "discriminator": { "propertyName": "type" }
+        },
+        "JobStatus": {
+            "type": "string",
+            "enum": ["queued", "running", "completed", "failed", "cancelled"]
+        },
+        "Resource": {
+            "type": "string",
+            "enum": ["cpu", "memory", "disk", "network", "gpu"]
+        },
+        "Job": {
+            "type": "object",
+            "required": ["id", "kind", "resources", "status", "started_at"],
+            "properties": {
+                "id": { "type": "integer", "format": "int64" },
+                "kind": { "type": "string", "description": "What the job runs, e.g. \"stress:cpu\"." },
+                "resources": { "type": "array", "items": { "$ref": "#/components/schemas/Resource" } },
+                "status": { "$ref": "#/components/schemas/JobStatus" },
+                "started_at": { "type": "string", "format": "date-time" },
+                "finished_at": nullable_string,
+                "message": nullable_string
+            }
+        },
+        "LaunchRequest": {
+            "type": "object",
+            "required": ["profile"],
+            "properties": {
+                "profile": { "type": "string", "description": "The name of a built-in or saved profile, e.g. \"burn-in\"." }
+            }
+        },
+        "ProbeRequest": {
+            "type": "object",
+            "required": ["target"],
+            "properties": {
+                "target": { "type": "string", "description": "The base URL of the other agent's REST API." }
+            }
+        },
+        "LinkMeasurement": {
+            "type": "object",
+            "required": ["target", "latency_us", "bandwidth_mbit_s"],
+            "properties": {
+                "target": { "type": "string" },
+                "latency_us": { "type": "number" },
+                "bandwidth_mbit_s": { "type": "number" }
+            }
+        },
+        "Score": {
+            "type": "object",
+            "description": "The scores of each benchmark kind with stored results.",
+            "additionalProperties": true
+        },
+        "ComponentUsage": {
+            "type": "object",
+            "required": ["name", "cpu_percent"],
+            "properties": {
+                "name": { "type": "string" },
+                "cpu_percent": { "type": "number" }
+            }
+        },
+        "FootprintBudget": {
+            "type": "object",
+            "required": ["cpu_percent", "memory_mb", "web_workers", "sink_queue", "sink_overflow"],
+            "properties": {
+                "cpu_percent": { "type": "number" },
+                "memory_mb": { "type": "integer" },
+                "web_workers": { "type": "integer" },
+                "sink_queue": { "type": "integer" },
+                "sink_overflow": { "type": "string", "enum": ["drop-oldest", "drop-newest", "aggregate"] }
+            }
+        },
+        "Footprint": {
+            "type": "object",
+            "required": [
+                "timestamp", "cpu_percent", "process_cpu_percent", "rss_bytes", "threads",
+                "components", "budget", "slowdown"
+            ],
+            "properties": {
+                "timestamp": { "type": "string", "format": "date-time" },
+                "cpu_percent": { "type": "number" },
+                "process_cpu_percent": { "type": "number" },
+                "rss_bytes": { "type": "integer", "format": "int64" },
+                "threads": { "type": "integer" },
+                "components": { "type": "array", "items": { "$ref": "#/components/schemas/ComponentUsage" } },
+                "budget": { "$ref": "#/components/schemas/FootprintBudget" },
+                "slowdown": { "type": "number" }
+            }
+        },
+        "HistoryPoint": {
+            "type": "object",
+            "required": ["timestamp", "value", "min", "max"],
+            "properties": {
+                "timestamp": { "type": "string", "format": "date-time" },
+                "value": { "type": "number" },
+                "min": { "type": "number" },
+                "max": { "type": "number" },
+                "p99": { "type": "number" }
+            }
+        },
+        "History": {
+            "type": "object",
+            "required": ["metric", "unit", "from", "to", "readings", "resolution", "points"],
+            "properties": {
+                "metric": { "type": "string", "enum": ["cpu", "memory", "power", "steal", "bursts"] },
+                "unit": { "type": "string" },
+                "from": { "type": "string", "format": "date-time" },
+                "to": { "type": "string", "format": "date-time" },
+                "readings": { "type": "integer" },
+                "resolution": { "type": "string", "enum": ["raw", "1m", "1h"] },
+                "points": { "type": "array", "items": { "$ref": "#/components/schemas/HistoryPoint" } }
+            }
+        },
+        "LogRecord": {
+            "type": "object",
+            "required": ["timestamp", "level", "target", "message"],
+            "properties": {
+                "timestamp": { "type": "string", "format": "date-time" },
+                "level": { "type": "string", "enum": ["ERROR", "WARN", "INFO", "DEBUG", "TRACE"] },
+                "target": { "type": "string" },
+                "message": { "type": "string" }
+            }
+        },
+        "HealthCheck": {
+            "type": "object",
+            "required": ["name", "healthy", "liveness", "detail"],
+            "properties": {
+                "name": { "type": "string" },
+                "healthy": { "type": "boolean" },
+                "liveness": { "type": "boolean" },
+                "detail": { "type": "string" }
+            }
+        },
+        "HealthResponse": {
+            "type": "object",
+            "required": ["status", "checks"],
+            "properties": {
+                "status": { "type": "string", "enum": ["ok", "failing"] },
+                "checks": { "type": "array", "items": { "$ref": "#/components/schemas/HealthCheck" } }
+            }
+        },
+        "OpenApi": {
+            "type": "object",
+            "description": "An OpenAPI 3.0 document.",
+            "additionalProperties": true
+        }
+    })
+}
+
+/// Returns the media types and schema of a response.
+fn content(content: ApiContent) -> Option<Value> {
+    let reference = |schema: &str| json!({ "$ref": format!("#/components/schemas/{}", schema) });
+    match content {
+        ApiContent::Empty => None,
+        ApiContent::Text => Some(json!({ "text/plain": { "schema": { "type": "string" } } })),
+        ApiContent::Json(schema) => {
+            Some(json!({ "application/json": { "schema": reference(schema) } }))
+        }
+        ApiContent::JsonArray(schema) => Some(json!({
+            "application/json": { "schema": { "type": "array", "items": reference(schema) } }
+        })),
+        ApiContent::EventStream(schema) => {
+            let description = format!("Server-sent events whose data is a JSON {}.", schema);
+            Some(json!({
+                "text/event-stream": { "schema": { "type": "string", "description": description } }
+            }))
+        }
+        ApiContent::Binary => Some(json!({
+            "application/octet-stream": { "schema": { "type": "string", "format": "binary" } }
+        })),
+        ApiContent::Html => Some(json!({ "text/html": { "schema": { "type": "string" } } })),
+    }
+}
+
+/// Describes an operation in OpenAPI.
+fn operation(operation: &ApiOperation, authenticated: bool) -> Value {
+    let mut document = Map::new();
+    document.insert("operationId".into(), json!(operation.operation_id));
+    document.insert("tags".into(), json!([operation.tag]));
+    document.insert("summary".into(), json!(operation.summary));
+    if authenticated && operation.is_protected() {
+        let role = if operation.is_write() {
+            "Requires an operator token."
+        } else {
+            "Requires a viewer or operator token."
+        };
+        document.insert("description".into(), json!(role));
+    }
+    if !operation.parameters.is_empty() {
+        let parameters: Vec<Value> = operation
+            .parameters
+            .iter()
+            .map(|parameter| {
+                json!({
+                    "name": parameter.name,
+                    "in": match parameter.location {
+                        ParameterLocation::Path => "path",
+                        ParameterLocation::Query => "query",
+                    },
+                    "required": parameter.location == ParameterLocation::Path,
+                    "description": parameter.description,
+                    "schema": { "type": parameter.schema_type }
+                })
+            })
+            .collect();
+        document.insert("parameters".into(), Value::Array(parameters));
+    }
+    if let Some(schema) = operation.request_body {
+        document.insert(
+            "requestBody".into(),
+            json!({ "required": true, "content": content(ApiContent::Json(schema)) }),
+        );
+    }
+
+    let mut responses = Map::new();
+    for response in operation.responses {
+        let mut described = json!({ "description": response.description });
+        if let Some(content) = content(response.content) {
+            described["content"] = content;
+        }
+        responses.insert(response.status.to_string(), described);
+    }
+    if authenticated && operation.is_protected() {
+        responses.insert(
+            "401".into(),
+            json!({
+                "description": "A valid API token is required.",
+                "content": content(ApiContent::Text)
+            }),
+        );
+        if operation.is_write() {
+            responses.insert(
+                "403".into(),
+                json!({
+                    "description": "The token may only read.",
+                    "content": content(ApiContent::Text)
+                }),
+            );
+        }
+    } else if authenticated {
+        // Open routes override the document-wide token requirement.
+        document.insert("security".into(), json!([]));
+    }
+    document.insert("responses".into(), Value::Object(responses));
+    Value::Object(document)
+}
+
+/// Generates the OpenAPI 3 document of the REST API.
+///
+/// # Arguments
+///
+/// * `authenticated` - Whether the server requires an API token, in which case
+///   the document declares bearer authentication for the routes under `/api/`.
+///
+/// # Returns
+///
+/// * `Value` - The document, as JSON.
+pub fn openapi_document(authenticated: bool) -> Value {
+    let mut paths = Map::new();
+    for api_operation in API_OPERATIONS {
+        let path = paths
+            .entry(api_operation.path)
+            .or_insert_with(|| Value::Object(Map::new()));
+        path[api_operation.method] = operation(api_operation, authenticated);
+    }
+
+    let mut document = json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "OneForAll REST API",
+            "description": "Live telemetry, jobs, logs and health of a OneForAll agent.",
+            "version": API_VERSION
+        },
+        "paths": paths,
+        "components": { "schemas": schemas() }
+    });
+    if authenticated {
+        document["components"]["securitySchemes"] = json!({
+            "bearerAuth": { "type": "http", "scheme": "bearer" }
+        });
+        document["security"] = json!([{ "bearerAuth": [] }]);
+    }
+    document
+}
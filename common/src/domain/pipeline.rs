@@ -0,0 +1,259 @@
+//! Pipeline Domain Entity
+//!
+//! This module provides the bounded queue between the collectors and a sink
+//! such as the database or a CSV file. A collector sampling every second or
+//! faster never waits for its sink: readings queue up while the sink catches
+//! up, and once the queue is full the overflow policy decides what gives,
+//! the oldest batch, the newest one, or the detail of a batch folded into the
+//! one before it. Every reading lost that way is counted, so a slow disk or a
+//! slow remote store shows in the counters rather than in the memory of the
+//! process.
+
+use std::collections::{HashMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::metric::{Aggregation, Metric};
+
+/// What a full queue gives up to take a new batch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverflowPolicy {
+    /// The oldest batch waiting is dropped.
+    DropOldest,
+
+    /// The new batch is dropped.
+    DropNewest,
+
+    /// The new batch is folded into the newest one waiting, combining the
+    /// readings of the same series by their aggregation, so no series goes missing.
+    #[default]
+    Aggregate,
+}
+
+impl OverflowPolicy {
+    /// Returns the name of the policy in the configuration file.
+    pub fn name(&self) -> &'static str {
+        match self {
+            OverflowPolicy::DropOldest => "drop-oldest",
+            OverflowPolicy::DropNewest => "drop-newest",
+            OverflowPolicy::Aggregate => "aggregate",
+        }
+    }
+}
+
+/// A batch of readings a queue can hold.
+pub trait Batch {
+    /// What a batch keeps while later ones are folded into it, e.g. how many
+    /// readings each series stands for.
+    type Folds: Default + std::fmt::Debug;
+
+    /// Returns the number of readings in the batch.
+    fn samples(&self) -> usize;
+
+    /// Folds a later batch into this one.
+    ///
+    /// # Arguments
+    ///
+    /// * `later` - The batch to fold in.
+    /// * `folds` - What this batch kept of the batches folded into it so far.
+    fn fold(&mut self, later: Self, folds: &mut Self::Folds);
+}
+
+impl Batch for Vec<Metric> {
+    /// How many readings each series folded into stands for; one when absent.
+    /// Series are counted on their own, since a series missing from a batch
+    /// was not read in it.
+    type Folds = HashMap<String, usize>;
+
+    fn samples(&self) -> usize {
+        self.len()
+    }
+
+    fn fold(&mut self, later: Self, folds: &mut Self::Folds) {
+        for metric in later {
+            let series = metric.series();
+            match self.iter_mut().find(|queued| queued.series() == series) {
+                Some(queued) => {
+                    let readings = folds.entry(series).or_insert(1);
+                    queued.value = match metric.aggregation {
+                        Aggregation::Mean => {
+                            let weight = *readings as f64;
+                            (queued.value * weight + metric.value) / (weight + 1.0)
+                        }
+                        Aggregation::Max => queued.value.max(metric.value),
+                        Aggregation::Sum => queued.value + metric.value,
+                        Aggregation::Last => metric.value,
+                    };
+                    *readings += 1;
+                    queued.timestamp = metric.timestamp;
+                }
+                None => self.push(metric),
+            }
+        }
+    }
+}
+
+/// What happened to the readings a full queue gave up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Overflow {
+    /// The readings dropped.
+    pub dropped: usize,
+
+    /// The readings folded into a batch already waiting.
+    pub aggregated: usize,
+}
+
+/// The batches waiting for a sink, at most `capacity` of them.
+#[derive(Debug)]
+pub struct Backlog<T: Batch> {
+    /// Each batch waiting, with what it kept of the batches folded into it.
+    batches: VecDeque<(T, T::Folds)>,
+    capacity: usize,
+    overflow: OverflowPolicy,
+}
+
+impl<T: Batch> Backlog<T> {
+    /// Creates an empty queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The most batches waiting at once; at least one is kept.
+    /// * `overflow` - What gives once the queue is full.
+    ///
+    /// # Returns
+    ///
+    /// * `Backlog<T>` - The queue.
+    pub fn new(capacity: usize, overflow: OverflowPolicy) -> Self {
+        Backlog {
+            batches: VecDeque::new(),
+            capacity: capacity.max(1),
+            overflow,
+        }
+    }
+
+    /// Queues a batch, applying the overflow policy if the queue is full.
+    ///
+    /// # Arguments
+    ///
+    /// * `batch` - The batch to queue.
+    ///
+    /// # Returns
+    ///
+    /// * `Overflow` - The readings dropped or folded to make room; none while the queue has room.
+    pub fn push(&mut self, batch: T) -> Overflow {
+        if self.batches.len() < self.capacity {
+            self.batches.push_back((batch, T::Folds::default()));
+            return Overflow::default();
+        }
+        match self.overflow {
+            OverflowPolicy::DropOldest => {
+                let dropped = self
+                    .batches
+                    .pop_front()
+                    .map_or(0, |(oldest, _)| oldest.samples());
+                self.batches.push_back((batch, T::Folds::default()));
+                Overflow {
+                    dropped,
+                    aggregated: 0,
+                }
+            }
+            OverflowPolicy::DropNewest => Overflow {
+                dropped: batch.samples(),
+                aggregated: 0,
+            },
+            OverflowPolicy::Aggregate => {
+                let aggregated = batch.samples();
+                if let Some((newest, folds)) = self.batches.back_mut() {
+                    newest.fold(batch, folds);
+                }
+                Overflow {
+                    dropped: 0,
+                    aggregated,
+                }
+            }
+        }
+    }
+
+    /// Takes the oldest batch waiting.
+    pub fn pop(&mut self) -> Option<T> {
+        self.batches.pop_front().map(|(batch, _)| batch)
+    }
+
+    /// Returns the number of batches waiting.
+    pub fn len(&self) -> usize {
+        self.batches.len()
+    }
+
+    /// Returns `true` if no batch is waiting.
+    pub fn is_empty(&self) -> bool {
+        self.batches.is_empty()
+    }
+}
+
+/// The state of the queue in front of one sink.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PipelineStats {
+    /// The sink, e.g. "metrics-csv" or "database".
+    pub name: String,
+
+    /// What the queue gives up once full.
+    pub overflow: OverflowPolicy,
+
+    /// The most batches the queue holds.
+    pub capacity: usize,
+
+    /// The batches waiting for the sink.
+    pub queued: usize,
+
+    /// The readings handed to the queue.
+    pub received: u64,
+
+    /// The readings the sink has written.
+    pub written: u64,
+
+    /// The readings dropped because the queue was full or its subscriber fell behind.
+    pub dropped: u64,
+
+    /// The readings folded into a batch already waiting.
+    pub aggregated: u64,
+}
+
+impl PipelineStats {
+    /// Counts the readings a full queue gave up.
+    pub fn count(&mut self, overflow: Overflow) {
+        self.dropped += overflow.dropped as u64;
+        self.aggregated += overflow.aggregated as u64;
+    }
+
+    /// Converts the counters to metrics, labelled with the sink.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp` - The local time the counters were read, in RFC 3339 format.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Metric>` - `pipeline_queued_batches` and the `pipeline_*_samples` counters.
+    pub fn metrics(&self, timestamp: &str) -> Vec<Metric> {
+        [
+            ("pipeline_queued_batches", "batches", self.queued as f64),
+            ("pipeline_received_samples", "samples", self.received as f64),
+            ("pipeline_written_samples", "samples", self.written as f64),
+            ("pipeline_dropped_samples", "samples", self.dropped as f64),
+            (
+                "pipeline_aggregated_samples",
+                "samples",
+                self.aggregated as f64,
+            ),
+        ]
+        .into_iter()
+        .map(|(name, unit, value)| {
+            // The counters only grow, so the latest reading stands for those folded into it.
+            Metric::new(name, unit, value, timestamp)
+                .with_label("pipeline", &self.name)
+                .with_aggregation(Aggregation::Last)
+        })
+        .collect()
+    }
+}
@@ -0,0 +1,169 @@
+//! Telemetry Domain Entity
+//!
+//! This module provides the telemetry types shared between the collectors in
+//! the application crate and the web server, so the REST API and the reports
+//! serialize the same structures.
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::metric::{Aggregation, Metric};
+
+/// Average power drawn by one measurement domain over a sampling interval.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainPower {
+    /// The measurement domain, e.g. "package-0", "dram", or "bmc".
+    pub domain: String,
+
+    /// The average power in watts.
+    pub watts: f64,
+}
+
+/// A power reading published by the power collectors.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerSample {
+    /// The local time the sample was taken, in RFC 3339 format.
+    pub timestamp: String,
+
+    /// Per-domain power derived from the RAPL / AMD energy counters.
+    pub domains: Vec<DomainPower>,
+
+    /// The whole-system power reported by the BMC, if available.
+    pub bmc_watts: Option<f64>,
+}
+
+impl PowerSample {
+    /// Returns the total CPU package power, summing every top-level package domain.
+    pub fn package_watts(&self) -> Option<f64> {
+        let packages: Vec<f64> = self
+            .domains
+            .iter()
+            .filter(|d| d.domain.starts_with("package") && !d.domain.contains('/'))
+            .map(|d| d.watts)
+            .collect();
+        if packages.is_empty() {
+            None
+        } else {
+            Some(packages.iter().sum())
+        }
+    }
+
+    /// Returns the reading as metrics: `power_watts` for each domain, the BMC
+    /// reading under the `bmc` domain.
+    pub fn metrics(&self) -> Vec<Metric> {
+        let mut metrics: Vec<Metric> = self
+            .domains
+            .iter()
+            .map(|d| {
+                Metric::new("power_watts", "watts", d.watts, &self.timestamp)
+                    .with_label("domain", &d.domain)
+            })
+            .collect();
+        if let Some(watts) = self.bmc_watts {
+            metrics.push(
+                Metric::new("power_watts", "watts", watts, &self.timestamp)
+                    .with_label("domain", "bmc"),
+            );
+        }
+        metrics
+    }
+}
+
+/// Intermediate progress of a running stress test.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StressProgress {
+    /// The local time the progress was taken, in RFC 3339 format.
+    pub timestamp: String,
+
+    /// The stress test being run, e.g. "cpu".
+    pub test: String,
+
+    /// The time elapsed since the test started, in seconds.
+    pub elapsed_secs: f64,
+
+    /// The planned duration of the test, in seconds.
+    pub duration_secs: f64,
+
+    /// The bogo-ops rate, read from the metrics stress-ng has written so far.
+    pub bogo_ops_per_sec: Option<f64>,
+
+    /// The hottest temperature sensor reading in degrees Celsius, if available.
+    pub hottest_celsius: Option<f64>,
+
+    /// The total CPU package power in watts, if available.
+    pub package_watts: Option<f64>,
+
+    /// Whether the test has finished.
+    pub finished: bool,
+}
+
+impl StressProgress {
+    /// Returns the progress as metrics labelled with the test; the temperature and
+    /// power are left to the thermal and power collectors.
+    pub fn metrics(&self) -> Vec<Metric> {
+        let metric = |name: &str, unit: &str, value: f64| {
+            Metric::new(name, unit, value, &self.timestamp).with_label("test", &self.test)
+        };
+        let mut metrics = vec![
+            // Time only moves on, so the latest reading stands for those folded into it.
+            metric("stress_elapsed_seconds", "seconds", self.elapsed_secs)
+                .with_aggregation(Aggregation::Last),
+            metric("stress_duration_seconds", "seconds", self.duration_secs),
+        ];
+        if let Some(rate) = self.bogo_ops_per_sec {
+            metrics.push(metric(
+                "stress_bogo_ops_per_second",
+                "bogo-ops per second",
+                rate,
+            ));
+        }
+        metrics
+    }
+
+    /// Renders the progress as a single terminal status line.
+    pub fn render_line(&self) -> String {
+        const BAR_WIDTH: usize = 30;
+        let fraction = if self.duration_secs > 0.0 {
+            (self.elapsed_secs / self.duration_secs).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let filled = (fraction * BAR_WIDTH as f64).round() as usize;
+
+        let mut line = format!(
+            "{} [{}{}] {:>4.0}s/{:.0}s {:>3.0}%",
+            self.test,
+            "=".repeat(filled),
+            " ".repeat(BAR_WIDTH - filled),
+            self.elapsed_secs,
+            self.duration_secs,
+            fraction * 100.0
+        );
+        if let Some(rate) = self.bogo_ops_per_sec {
+            line.push_str(&format!(" | {:.0} bogo-ops/s", rate));
+        }
+        if let Some(celsius) = self.hottest_celsius {
+            line.push_str(&format!(" | {:.1} °C", celsius));
+        }
+        if let Some(watts) = self.package_watts {
+            line.push_str(&format!(" | {:.1} W", watts));
+        }
+        line
+    }
+}
+
+/// A live event streamed to WebSocket clients.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TelemetryEvent {
+    /// A new power reading.
+    Power(PowerSample),
+
+    /// Progress of the running stress test.
+    StressProgress(StressProgress),
+
+    /// Readings published to the metric registry, by any collector.
+    Metrics {
+        /// The readings, taken together.
+        metrics: Vec<Metric>,
+    },
+}
@@ -1,15 +1,23 @@
 // common/src/lib.rs
 
+#[cfg(feature = "server")]
 use std::fmt::{Debug, Formatter};
 
+#[cfg(feature = "server")]
 use async_trait::async_trait;
 
 // Importing the `async_trait` crate. This crate is used to enable async functions in traits,
 // which is not natively supported in Rust.
+#[cfg(feature = "server")]
 use crate::adapters::log_adapter::FernLogger;
+#[cfg(feature = "server")]
 use crate::ports::log_port::LoggerPort;
 
+// Only the domain types build without the `server` feature, for the WASM dashboard.
+#[cfg(feature = "server")]
 pub mod adapters;
+pub mod domain;
+#[cfg(feature = "server")]
 pub mod ports;
 
 /// This module provides a console logger that can be used across the frontend, primary, and backends of the application.
@@ -19,10 +27,12 @@ pub mod ports;
 ///
 /// This struct implements the `LoggerPort` trait, providing methods for logging messages at various levels (info, warning, error, debug).
 /// It uses an instance of `FernLogger` for the actual logging, allowing it to leverage all the capabilities of `FernLogger`.
+#[cfg(feature = "server")]
 pub struct ConsoleLogger {
     fern_logger: FernLogger,
 }
 
+#[cfg(feature = "server")]
 impl ConsoleLogger {
     /// Creates a new instance of `ConsoleLogger`.
     ///
@@ -40,6 +50,7 @@ impl ConsoleLogger {
 ///
 /// The `Debug` trait provides a method for formatting an instance of `ConsoleLogger` for output,
 /// typically for debugging purposes.
+#[cfg(feature = "server")]
 impl Debug for ConsoleLogger {
     /// Formats the `ConsoleLogger` struct for output.
     ///
@@ -61,6 +72,7 @@ impl Debug for ConsoleLogger {
 }
 
 // Implementing the `LoggerPort` trait for `ConsoleLogger`.
+#[cfg(feature = "server")]
 #[async_trait]
 impl LoggerPort for ConsoleLogger {
     /// Logs an informational message.
@@ -111,6 +123,7 @@ impl LoggerPort for ConsoleLogger {
 }
 
 // The main function where the `ConsoleLogger` is used.
+#[cfg(feature = "server")]
 #[actix_rt::main]
 async fn main() {
     // Create a new `ConsoleLogger` instance.
@@ -0,0 +1,27 @@
+use std::time::Duration;
+
+use crate::domain::footprint::{Footprint, FootprintConfig};
+
+/// `FootprintPort` Trait
+///
+/// Defines an interface through which OneForAll's own collectors and web
+/// server learn the budget they must stay within and how often to sample, and
+/// through which the REST API reports the overhead they add.
+pub trait FootprintPort: Send + Sync {
+    /// Returns the budget the monitoring must stay within.
+    fn budget(&self) -> FootprintConfig;
+
+    /// Returns the latest overhead measured, or `None` before the first measurement.
+    fn footprint(&self) -> Option<Footprint>;
+
+    /// Returns how long a collector should wait before its next reading.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - The interval the collector is configured with.
+    ///
+    /// # Returns
+    ///
+    /// * `Duration` - The interval, stretched while the monitoring is over its CPU budget.
+    fn pace(&self, interval: Duration) -> Duration;
+}
@@ -0,0 +1,15 @@
+use crate::domain::health::HealthReport;
+
+/// `HealthPort` Trait
+///
+/// Defines an interface for checking the subsystems an agent needs to do its
+/// work, such as its database, collectors and job manager, which the web
+/// server reports at `/healthz` and `/readyz`.
+pub trait HealthPort: Send + Sync {
+    /// Checks every subsystem.
+    ///
+    /// # Returns
+    ///
+    /// The outcome of each check.
+    fn check_health(&self) -> HealthReport;
+}
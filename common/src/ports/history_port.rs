@@ -0,0 +1,19 @@
+use crate::domain::history::{History, HistoryQuery};
+
+/// `HistoryPort` Trait
+///
+/// Defines an interface through which the web server serves the time-series
+/// of stored collector readings, so the dashboard can chart past runs without
+/// the web server knowing how readings are stored.
+pub trait HistoryPort: Send + Sync {
+    /// Reads the history of a metric over a time range. Blocks until done.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The metric, the time range and the most points to return.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<History, String>` - The history, or why the query is invalid or the readings could not be read.
+    fn history(&self, query: &HistoryQuery) -> Result<History, String>;
+}
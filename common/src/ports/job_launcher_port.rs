@@ -0,0 +1,19 @@
+use crate::domain::job::Job;
+
+/// `JobLauncherPort` Trait
+///
+/// Defines an interface through which the REST and gRPC APIs start workloads
+/// on this machine for remote callers, keeping the profile runner and its
+/// collectors out of the servers.
+pub trait JobLauncherPort: Send + Sync {
+    /// Starts running a profile as a job and returns without waiting for it.
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - The name of a built-in or saved profile.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Job, String>` - The submitted job, or why the profile cannot be run.
+    fn launch_profile(&self, profile: &str) -> Result<Job, String>;
+}
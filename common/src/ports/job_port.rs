@@ -0,0 +1,86 @@
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::job::{Job, JobSpec, JobStatus};
+
+/// A handle to a submitted job, held by the code that runs it.
+pub struct JobTicket {
+    /// The job record at the time of submission.
+    pub job: Job,
+
+    /// Cancelled when the job should stop.
+    pub cancel: CancellationToken,
+
+    status: watch::Receiver<JobStatus>,
+}
+
+impl JobTicket {
+    /// Creates a ticket following the status published by a `JobPort`.
+    pub fn new(job: Job, cancel: CancellationToken, status: watch::Receiver<JobStatus>) -> Self {
+        JobTicket {
+            job,
+            cancel,
+            status,
+        }
+    }
+
+    /// Waits until no conflicting job is running any more.
+    ///
+    /// # Returns
+    ///
+    /// `true` once the job may run, or `false` if it was cancelled while queued.
+    pub async fn admitted(&mut self) -> bool {
+        match self.status.wait_for(|s| *s != JobStatus::Queued).await {
+            Ok(status) => *status == JobStatus::Running,
+            Err(_) => false,
+        }
+    }
+}
+
+/// `JobPort` Trait
+///
+/// Defines an interface for queueing and tracking long-running jobs such as
+/// stress tests and benchmarks. Jobs that need the same resources run one
+/// after another in submission order, while jobs on disjoint resources run in
+/// parallel. The web server and the Ctrl+C handler cancel jobs through this port.
+pub trait JobPort: Send + Sync {
+    /// Submits a job, which starts immediately unless a conflicting job is running.
+    ///
+    /// # Arguments
+    ///
+    /// * `spec` - What the job runs and the resources it needs exclusively.
+    ///
+    /// # Returns
+    ///
+    /// The ticket used to wait for admission and watch for cancellation.
+    fn submit_job(&self, spec: JobSpec) -> JobTicket;
+
+    /// Records the outcome of a job and admits the queued jobs it was blocking.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The job ID.
+    /// * `status` - The final status of the job.
+    /// * `message` - A short explanation of the outcome, if any.
+    ///
+    /// # Returns
+    ///
+    /// The updated job record, or `None` if the job is unknown.
+    fn finish_job(&self, id: u64, status: JobStatus, message: Option<String>) -> Option<Job>;
+
+    /// Requests cancellation of a queued or running job.
+    ///
+    /// # Returns
+    ///
+    /// The job record, or an error if the job is unknown or already finished.
+    fn cancel_job(&self, id: u64) -> Result<Job, String>;
+
+    /// Requests cancellation of every queued or running job.
+    fn cancel_all(&self);
+
+    /// Returns the job with the given ID, if any.
+    fn get_job(&self, id: u64) -> Option<Job>;
+
+    /// Returns every job, oldest first.
+    fn list_jobs(&self) -> Vec<Job>;
+}
@@ -0,0 +1,28 @@
+use tokio::sync::broadcast;
+
+use crate::domain::log_record::LogRecord;
+
+/// `LogStreamPort` Trait
+///
+/// Defines an interface through which the logger publishes every message it
+/// writes and the web server streams them to remote readers, keeping the
+/// web server independent of the logging framework.
+pub trait LogStreamPort: Send + Sync {
+    /// Publishes a message just logged.
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - The message to publish.
+    fn publish_log(&self, record: LogRecord);
+
+    /// Returns the most recent messages at least as severe as a level, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The least severe level to return.
+    /// * `lines` - The number of messages to return, at most.
+    fn recent_logs(&self, level: log::Level, lines: usize) -> Vec<LogRecord>;
+
+    /// Subscribes to every message published from now on.
+    fn subscribe_logs(&self) -> broadcast::Receiver<LogRecord>;
+}
@@ -1,3 +1,12 @@
+pub mod footprint_port;
+pub mod health_port;
+pub mod history_port;
+pub mod job_launcher_port;
+pub mod job_port;
 pub mod log_port;
+pub mod log_stream_port;
+pub mod network_probe_port;
+pub mod score_port;
 
+pub mod telemetry_port;
 pub mod web_server_port;
@@ -0,0 +1,19 @@
+use crate::domain::network_probe::LinkMeasurement;
+
+/// `NetworkProbePort` Trait
+///
+/// Defines an interface through which the web server measures the network
+/// path from this machine to another agent when an orchestrator asks it to,
+/// keeping the HTTP client used for the measurement out of the web server.
+pub trait NetworkProbePort: Send + Sync {
+    /// Measures the latency and bandwidth to another agent. Blocks until done.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The base URL of the REST API of the agent to measure to.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<LinkMeasurement, String>` - The measurement, or why the agent could not be measured.
+    fn probe_link(&self, target: &str) -> Result<LinkMeasurement, String>;
+}
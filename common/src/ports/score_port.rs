@@ -0,0 +1,13 @@
+/// `ScorePort` Trait
+///
+/// Defines an interface through which the web server serves the benchmark
+/// scores of this machine, so an orchestrator can rank the machines of a
+/// fleet without the web server knowing how results are stored or scored.
+pub trait ScorePort: Send + Sync {
+    /// Scores this machine from its stored benchmark results. Blocks until done.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<serde_json::Value, String>` - The scores, or why they could not be computed.
+    fn score(&self) -> Result<serde_json::Value, String>;
+}
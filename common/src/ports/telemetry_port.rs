@@ -0,0 +1,58 @@
+use tokio::sync::broadcast;
+
+use crate::domain::metric::Metric;
+use crate::domain::pipeline::PipelineStats;
+use crate::domain::telemetry::{PowerSample, StressProgress, TelemetryEvent};
+
+/// `TelemetryPort` Trait
+///
+/// Defines an interface through which collectors publish live readings and
+/// the web server retrieves the latest values for the REST API. This keeps the
+/// web server independent of the adapters that actually read the hardware.
+///
+/// It is also the metric registry: every reading, power and progress included,
+/// is published as `Metric`s too, so sinks follow one stream of one shape.
+pub trait TelemetryPort: Send + Sync {
+    /// Publishes the latest power reading.
+    ///
+    /// # Arguments
+    ///
+    /// * `sample` - The power reading to publish.
+    fn publish_power(&self, sample: PowerSample);
+
+    /// Returns the most recently published power reading, if any.
+    fn latest_power(&self) -> Option<PowerSample>;
+
+    /// Publishes the progress of the running stress test.
+    ///
+    /// # Arguments
+    ///
+    /// * `progress` - The progress to publish.
+    fn publish_progress(&self, progress: StressProgress);
+
+    /// Returns the most recently published stress test progress, if any.
+    fn latest_progress(&self) -> Option<StressProgress>;
+
+    /// Publishes readings to the metric registry.
+    ///
+    /// # Arguments
+    ///
+    /// * `metrics` - The readings, taken together.
+    fn publish_metrics(&self, metrics: Vec<Metric>);
+
+    /// Returns the latest reading of every series published so far.
+    fn latest_metrics(&self) -> Vec<Metric>;
+
+    /// Publishes the state of the queue in front of a sink.
+    ///
+    /// # Arguments
+    ///
+    /// * `stats` - The counters of the queue, replacing those last published under its name.
+    fn publish_pipeline(&self, stats: PipelineStats);
+
+    /// Returns the latest state of every queue published so far.
+    fn pipelines(&self) -> Vec<PipelineStats>;
+
+    /// Subscribes to every event published from now on.
+    fn subscribe(&self) -> broadcast::Receiver<TelemetryEvent>;
+}
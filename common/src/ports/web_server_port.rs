@@ -8,4 +8,8 @@ use async_trait::async_trait;
 #[async_trait]
 pub trait WebServerPort {
     async fn start_server(&self) -> io::Result<()>;
+
+    /// Stops accepting connections and lets the requests in progress finish,
+    /// after which `start_server` returns.
+    async fn stop_server(&self);
 }
@@ -0,0 +1,188 @@
+//! REST API Calls
+//!
+//! This module calls the REST API of the agent serving the dashboard and
+//! decodes the responses into the `common` types the web server encodes them
+//! from. The token entered in the dashboard is kept in the browser's local
+//! storage and presented with every call.
+
+use common::domain::footprint::Footprint;
+use common::domain::health::HealthResponse;
+use common::domain::history::History;
+use common::domain::job::Job;
+use common::domain::telemetry::{PowerSample, StressProgress};
+use gloo_net::http::{Request, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+
+/// The local storage key the token is kept under.
+const TOKEN_KEY: &str = "oneforall.token";
+
+/// Everything the dashboard shows, as read in one refresh.
+#[derive(Debug, Default)]
+pub struct Snapshot {
+    /// The latest power reading, if a power source has reported.
+    pub power: Option<PowerSample>,
+
+    /// The progress of the running stress test, if any.
+    pub progress: Option<StressProgress>,
+
+    /// Every job submitted since the agent started.
+    pub jobs: Vec<Job>,
+
+    /// The overhead of the agent's own monitoring, once measured.
+    pub footprint: Option<Footprint>,
+
+    /// The readiness of the agent's subsystems.
+    pub health: Option<HealthResponse>,
+
+    /// Why some of the readings could not be read.
+    pub errors: Vec<String>,
+}
+
+/// Returns the local storage of the browser, if it allows one.
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// Returns the token kept in the browser, if one was entered.
+pub fn stored_token() -> Option<String> {
+    local_storage()?
+        .get_item(TOKEN_KEY)
+        .ok()?
+        .filter(|token| !token.is_empty())
+}
+
+/// Keeps the token in the browser for the next visits; an empty token is forgotten.
+pub fn store_token(token: &str) {
+    if let Some(storage) = local_storage() {
+        let _ = if token.is_empty() {
+            storage.remove_item(TOKEN_KEY)
+        } else {
+            storage.set_item(TOKEN_KEY, token)
+        };
+    }
+}
+
+/// Adds the token, if any, to a request.
+fn authorized(request: RequestBuilder) -> RequestBuilder {
+    match stored_token() {
+        Some(token) => request.header("Authorization", &format!("Bearer {}", token)),
+        None => request,
+    }
+}
+
+/// Sends a request, turning a rejected token into an explanation.
+async fn send(path: &str, request: RequestBuilder) -> Result<Response, String> {
+    let response = authorized(request)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach {}: {}", path, e))?;
+    match response.status() {
+        401 => Err("The API requires a token; enter one above".to_string()),
+        403 => Err(format!("The token is not allowed to call {}", path)),
+        _ => Ok(response),
+    }
+}
+
+/// Decodes the JSON body of a response.
+async fn decode<T: DeserializeOwned>(path: &str, response: Response) -> Result<T, String> {
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Invalid response from {}: {}", path, e))
+}
+
+/// Reads a resource of the API, `None` when the agent has nothing to report yet.
+async fn get<T: DeserializeOwned>(path: &str) -> Result<Option<T>, String> {
+    let response = send(path, Request::get(path)).await?;
+    match response.status() {
+        404 => Ok(None),
+        _ if response.ok() => decode(path, response).await.map(Some),
+        status => Err(format!("{} answered {}", path, status)),
+    }
+}
+
+/// Reads the readiness of the agent, which is reported whether it is ready or not.
+async fn get_health() -> Result<HealthResponse, String> {
+    let path = "/readyz";
+    let response = send(path, Request::get(path)).await?;
+    decode(path, response).await
+}
+
+/// Keeps a value that was read, or the reason it could not be.
+fn keep<T>(result: Result<T, String>, errors: &mut Vec<String>) -> Option<T> {
+    result.map_err(|e| errors.push(e)).ok()
+}
+
+/// Reads everything the dashboard shows.
+pub async fn fetch_snapshot() -> Snapshot {
+    let mut errors = Vec::new();
+    let power = keep(get("/api/v1/power").await, &mut errors).flatten();
+    let progress = keep(get("/api/v1/progress").await, &mut errors).flatten();
+    let jobs = keep(get("/api/v1/jobs").await, &mut errors)
+        .flatten()
+        .unwrap_or_default();
+    let footprint = keep(get("/api/v1/self").await, &mut errors).flatten();
+    let health = keep(get_health().await, &mut errors);
+    // A missing token fails every call alike.
+    errors.dedup();
+    Snapshot {
+        power,
+        progress,
+        jobs,
+        footprint,
+        health,
+        errors,
+    }
+}
+
+/// Returns the RFC 3339 time a number of hours ago, by the browser's clock.
+fn hours_ago(hours: u32) -> String {
+    let time = js_sys::Date::now() - f64::from(hours) * 3_600_000.0;
+    js_sys::Date::new(&wasm_bindgen::JsValue::from_f64(time))
+        .to_iso_string()
+        .into()
+}
+
+/// Reads the stored history of a metric over the last hours.
+///
+/// # Arguments
+///
+/// * `metric` - The metric, e.g. "cpu".
+/// * `hours` - How far back the history reaches.
+///
+/// # Returns
+///
+/// * `Result<History, String>` - The history, or why it could not be read.
+pub async fn fetch_history(metric: &str, hours: u32) -> Result<History, String> {
+    let path = "/api/v1/history";
+    let from = hours_ago(hours);
+    let request = Request::get(path).query([("metric", metric), ("from", from.as_str())]);
+    let response = send(path, request).await?;
+    if !response.ok() {
+        let reason = response.text().await.unwrap_or_default();
+        return Err(format!(
+            "Failed to read the history of {}: {}",
+            metric, reason
+        ));
+    }
+    decode(path, response).await
+}
+
+/// Requests cancellation of a queued or running job; needs an operator token.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the job.
+///
+/// # Returns
+///
+/// * `Result<Job, String>` - The job, or why it could not be cancelled.
+pub async fn cancel_job(id: u64) -> Result<Job, String> {
+    let path = format!("/api/v1/jobs/{}", id);
+    let response = send(&path, Request::delete(&path)).await?;
+    if !response.ok() {
+        let reason = response.text().await.unwrap_or_default();
+        return Err(format!("Failed to cancel job {}: {}", id, reason));
+    }
+    decode(&path, response).await
+}
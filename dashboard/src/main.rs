@@ -0,0 +1,148 @@
+//! OneForAll Dashboard
+//!
+//! This crate is the browser dashboard served by the agent at `/dashboard`.
+//! It is compiled to WebAssembly with `trunk build --release` and polls the
+//! REST API of the agent that served it for the power readings, the stress
+//! test progress, the jobs, the health probes and the agent's own footprint,
+//! and charts the stored history of a metric, e.g. over an overnight burn-in.
+//! The responses are decoded into the types of the `common` crate the web
+//! server encodes them from, so a change to the API breaks the build of the
+//! dashboard rather than the page.
+
+use common::domain::history::History;
+use gloo_timers::callback::Interval;
+use web_sys::HtmlInputElement;
+use yew::platform::spawn_local;
+use yew::prelude::*;
+
+mod api;
+mod views;
+
+use crate::api::Snapshot;
+use crate::views::{
+    footprint_view, health_view, history_view, jobs_view, power_view, progress_view,
+};
+
+/// How often the readings are refreshed, in milliseconds.
+const REFRESH_INTERVAL_MS: u32 = 2_000;
+
+/// How often the charted history is refreshed, in milliseconds.
+const HISTORY_INTERVAL_MS: u32 = 60_000;
+
+/// The dashboard page.
+#[function_component(App)]
+fn app() -> Html {
+    let snapshot = use_state(Snapshot::default);
+    let token = use_state(|| api::stored_token().unwrap_or_default());
+    let cancel_error = use_state(|| None::<String>);
+    let history = use_state(|| None::<History>);
+    let history_error = use_state(|| None::<String>);
+    let metric = use_state(|| "cpu".to_string());
+    let hours = use_state(|| 24u32);
+
+    let refresh = {
+        let snapshot = snapshot.clone();
+        move || {
+            let snapshot = snapshot.clone();
+            spawn_local(async move { snapshot.set(api::fetch_snapshot().await) });
+        }
+    };
+
+    {
+        let refresh = refresh.clone();
+        use_effect_with((), move |_| {
+            refresh();
+            let interval = Interval::new(REFRESH_INTERVAL_MS, refresh);
+            move || drop(interval)
+        });
+    }
+
+    // The history is read again whenever another metric or range is chosen.
+    {
+        let (history, history_error) = (history.clone(), history_error.clone());
+        use_effect_with(((*metric).clone(), *hours), move |(metric, hours)| {
+            let (metric, hours) = (metric.clone(), *hours);
+            let load = move || {
+                let (history, history_error, metric) =
+                    (history.clone(), history_error.clone(), metric.clone());
+                spawn_local(async move {
+                    match api::fetch_history(&metric, hours).await {
+                        Ok(read) => {
+                            history.set(Some(read));
+                            history_error.set(None);
+                        }
+                        Err(e) => history_error.set(Some(e)),
+                    }
+                });
+            };
+            load();
+            let interval = Interval::new(HISTORY_INTERVAL_MS, load);
+            move || drop(interval)
+        });
+    }
+
+    let on_metric = {
+        let (metric, history) = (metric.clone(), history.clone());
+        Callback::from(move |chosen: String| {
+            history.set(None);
+            metric.set(chosen);
+        })
+    };
+    let on_hours = {
+        let (hours, history) = (hours.clone(), history.clone());
+        Callback::from(move |chosen: u32| {
+            history.set(None);
+            hours.set(chosen);
+        })
+    };
+
+    let on_token = {
+        let token = token.clone();
+        Callback::from(move |event: InputEvent| {
+            let input: HtmlInputElement = event.target_unchecked_into();
+            api::store_token(&input.value());
+            token.set(input.value());
+        })
+    };
+
+    let on_cancel = {
+        let cancel_error = cancel_error.clone();
+        Callback::from(move |id: u64| {
+            let (cancel_error, refresh) = (cancel_error.clone(), refresh.clone());
+            spawn_local(async move {
+                cancel_error.set(api::cancel_job(id).await.err());
+                refresh();
+            });
+        })
+    };
+
+    html! {
+        <>
+            <header>
+                <h1>{ "OneForAll" }</h1>
+                <label>
+                    { "Token " }
+                    <input type="password" value={ (*token).clone() } oninput={ on_token } />
+                </label>
+            </header>
+            <div class="errors">
+                { for snapshot
+                    .errors
+                    .iter()
+                    .chain(cancel_error.iter())
+                    .chain(history_error.iter())
+                    .map(|error| html! { <p>{ error }</p> }) }
+            </div>
+            { health_view(snapshot.health.as_ref()) }
+            { power_view(snapshot.power.as_ref()) }
+            { progress_view(snapshot.progress.as_ref()) }
+            { jobs_view(&snapshot.jobs, on_cancel) }
+            { history_view((*history).as_ref(), &metric, *hours, on_metric, on_hours) }
+            { footprint_view(snapshot.footprint.as_ref()) }
+        </>
+    }
+}
+
+fn main() {
+    yew::Renderer::<App>::new().render();
+}
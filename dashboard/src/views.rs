@@ -0,0 +1,316 @@
+//! Dashboard Views
+//!
+//! This module renders each panel of the dashboard from the shared types the
+//! REST API returns. A panel whose reading is not available yet says so
+//! instead of disappearing, so the layout stays put between refreshes.
+
+use common::domain::footprint::Footprint;
+use common::domain::health::HealthResponse;
+use common::domain::history::{History, HistoryPoint, HISTORY_METRICS};
+use common::domain::job::Job;
+use common::domain::telemetry::{PowerSample, StressProgress};
+use web_sys::HtmlSelectElement;
+use yew::prelude::*;
+
+/// The time ranges the history can be charted over, in hours, with their labels.
+pub const HISTORY_RANGES: [(u32, &str); 5] = [
+    (1, "last hour"),
+    (6, "last 6 hours"),
+    (24, "last 24 hours"),
+    (72, "last 3 days"),
+    (168, "last week"),
+];
+
+/// The size of a chart, in SVG units.
+const CHART_WIDTH: f64 = 800.0;
+const CHART_HEIGHT: f64 = 200.0;
+
+/// Formats an optional reading with its unit, or a dash when it is missing.
+fn reading(value: Option<f64>, unit: &str) -> String {
+    value.map_or_else(|| "-".to_string(), |value| format!("{:.1} {}", value, unit))
+}
+
+/// Renders a panel with a title.
+fn panel(title: &str, content: Html) -> Html {
+    html! {
+        <section>
+            <h2>{ title }</h2>
+            { content }
+        </section>
+    }
+}
+
+/// Renders the latest power reading, per domain.
+pub fn power_view(sample: Option<&PowerSample>) -> Html {
+    let Some(sample) = sample else {
+        return panel(
+            "Power",
+            html! { <p>{ "No power source has reported yet." }</p> },
+        );
+    };
+    panel(
+        "Power",
+        html! {
+            <>
+                <p>
+                    { format!("CPU packages: {}", reading(sample.package_watts(), "W")) }
+                    { format!(" · BMC: {}", reading(sample.bmc_watts, "W")) }
+                    { format!(" · at {}", sample.timestamp) }
+                </p>
+                <table>
+                    <tr><th>{ "Domain" }</th><th>{ "Power" }</th></tr>
+                    { for sample.domains.iter().map(|domain| html! {
+                        <tr>
+                            <td>{ &domain.domain }</td>
+                            <td>{ reading(Some(domain.watts), "W") }</td>
+                        </tr>
+                    }) }
+                </table>
+            </>
+        },
+    )
+}
+
+/// Renders the progress of the running or last stress test.
+pub fn progress_view(progress: Option<&StressProgress>) -> Html {
+    let Some(progress) = progress else {
+        return panel(
+            "Stress Test",
+            html! { <p>{ "No stress test has run yet." }</p> },
+        );
+    };
+    let state = if progress.finished {
+        "finished"
+    } else {
+        "running"
+    };
+    panel(
+        "Stress Test",
+        html! {
+            <>
+                <p>{ format!("{} test {}", progress.test, state) }</p>
+                <progress max={ progress.duration_secs.to_string() }
+                          value={ progress.elapsed_secs.min(progress.duration_secs).to_string() } />
+                <p>
+                    { format!("{:.0} of {:.0} s", progress.elapsed_secs, progress.duration_secs) }
+                    { format!(" · {}", reading(progress.bogo_ops_per_sec, "bogo ops/s")) }
+                    { format!(" · hottest {}", reading(progress.hottest_celsius, "°C")) }
+                    { format!(" · {}", reading(progress.package_watts, "W")) }
+                </p>
+            </>
+        },
+    )
+}
+
+/// Renders the jobs, with a button cancelling those that have not finished.
+pub fn jobs_view(jobs: &[Job], on_cancel: Callback<u64>) -> Html {
+    if jobs.is_empty() {
+        return panel("Jobs", html! { <p>{ "No jobs." }</p> });
+    }
+    panel(
+        "Jobs",
+        html! {
+            <table>
+                <tr>
+                    <th>{ "ID" }</th><th>{ "Kind" }</th><th>{ "Status" }</th>
+                    <th>{ "Started" }</th><th>{ "Message" }</th><th></th>
+                </tr>
+                { for jobs.iter().rev().map(|job| {
+                    let id = job.id;
+                    html! {
+                        <tr>
+                            <td>{ job.id }</td>
+                            <td>{ &job.kind }</td>
+                            <td>{ format!("{:?}", job.status).to_lowercase() }</td>
+                            <td>{ &job.started_at }</td>
+                            <td>{ job.message.clone().unwrap_or_default() }</td>
+                            <td>
+                                if !job.status.is_finished() {
+                                    <button onclick={ on_cancel.reform(move |_| id) }>{ "Cancel" }</button>
+                                }
+                            </td>
+                        </tr>
+                    }
+                }) }
+            </table>
+        },
+    )
+}
+
+/// Renders the readiness of each subsystem of the agent.
+pub fn health_view(health: Option<&HealthResponse>) -> Html {
+    let Some(health) = health else {
+        return panel(
+            "Health",
+            html! { <p>{ "The health probe did not answer." }</p> },
+        );
+    };
+    let class = |healthy: bool| if healthy { "passing" } else { "failing" };
+    panel(
+        "Health",
+        html! {
+            <>
+                <p class={ class(health.status == "ok") }>{ format!("Readiness: {}", health.status) }</p>
+                <table>
+                    { for health.checks.iter().map(|check| html! {
+                        <tr>
+                            <td>{ &check.name }</td>
+                            <td class={ class(check.healthy) }>{ if check.healthy { "ok" } else { "failing" } }</td>
+                            <td>{ &check.detail }</td>
+                        </tr>
+                    }) }
+                </table>
+            </>
+        },
+    )
+}
+
+/// Renders the overhead of the agent's own monitoring against its budget.
+pub fn footprint_view(footprint: Option<&Footprint>) -> Html {
+    let Some(footprint) = footprint else {
+        return panel("Footprint", html! { <p>{ "Not measured yet." }</p> });
+    };
+    let budget = |over: bool| if over { "failing" } else { "passing" };
+    panel(
+        "Footprint",
+        html! {
+            <>
+                <p>
+                    <span class={ budget(footprint.over_cpu_budget()) }>
+                        { format!("CPU {:.1}% of {:.1}%", footprint.cpu_percent, footprint.budget.cpu_percent) }
+                    </span>
+                    { " · " }
+                    <span class={ budget(footprint.over_memory_budget()) }>
+                        { format!("memory {} MB of {} MB", footprint.rss_bytes / (1024 * 1024), footprint.budget.memory_mb) }
+                    </span>
+                    { format!(" · {} threads · intervals stretched {:.1}x", footprint.threads, footprint.slowdown) }
+                </p>
+                <table>
+                    { for footprint.components.iter().map(|component| html! {
+                        <tr>
+                            <td>{ &component.name }</td>
+                            <td>{ format!("{:.1}%", component.cpu_percent) }</td>
+                        </tr>
+                    }) }
+                </table>
+            </>
+        },
+    )
+}
+
+/// Returns the time of a point in milliseconds since the epoch, by the browser's parser.
+fn point_time(point: &HistoryPoint) -> f64 {
+    js_sys::Date::parse(&point.timestamp)
+}
+
+/// Splits a history into the stretches drawn as connected lines; a gap of more
+/// than three times the usual spacing, e.g. while the agent was stopped, is left open.
+fn stretches(points: &[HistoryPoint], start: f64, end: f64) -> Vec<&[HistoryPoint]> {
+    let spacing = (end - start) / points.len().max(1) as f64;
+    points
+        .chunk_by(|a, b| point_time(b) - point_time(a) <= 3.0 * spacing)
+        .collect()
+}
+
+/// Charts a history as an SVG line of the averages over a band of the extremes.
+fn chart(history: &History) -> Html {
+    let start = js_sys::Date::parse(&history.from);
+    let end = js_sys::Date::parse(&history.to);
+    let top = history
+        .points
+        .iter()
+        .map(|point| point.max)
+        .fold(0.0, f64::max)
+        .max(1.0);
+    let x = |point: &HistoryPoint| (point_time(point) - start) / (end - start) * CHART_WIDTH;
+    let y = |value: f64| CHART_HEIGHT - value / top * CHART_HEIGHT;
+    let coordinates = |points: &mut dyn Iterator<Item = (f64, f64)>| {
+        points
+            .map(|(x, y)| format!("{:.1},{:.1}", x, y))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    html! {
+        <svg viewBox={ format!("0 0 {} {}", CHART_WIDTH, CHART_HEIGHT) } width="100%" height="220"
+             preserveAspectRatio="none">
+            { for stretches(&history.points, start, end).into_iter().map(|stretch| {
+                let band = coordinates(&mut stretch
+                    .iter()
+                    .map(|point| (x(point), y(point.max)))
+                    .chain(stretch.iter().rev().map(|point| (x(point), y(point.min)))));
+                let line = coordinates(&mut stretch.iter().map(|point| (x(point), y(point.value))));
+                html! {
+                    <>
+                        <polygon points={ band } fill="#c6dbef" stroke="none" />
+                        <polyline points={ line } fill="none" stroke="#2171b5" stroke-width="1.5" />
+                    </>
+                }
+            }) }
+        </svg>
+    }
+}
+
+/// Renders the stored history of a metric, with selectors for the metric and the range.
+pub fn history_view(
+    history: Option<&History>,
+    metric: &str,
+    hours: u32,
+    on_metric: Callback<String>,
+    on_hours: Callback<u32>,
+) -> Html {
+    let on_metric =
+        on_metric.reform(|event: Event| event.target_unchecked_into::<HtmlSelectElement>().value());
+    let on_hours = on_hours.reform(|event: Event| {
+        let select: HtmlSelectElement = event.target_unchecked_into();
+        select.value().parse().unwrap_or(24)
+    });
+    let selectors = html! {
+        <p>
+            <select onchange={ on_metric }>
+                { for HISTORY_METRICS.iter().map(|option| html! {
+                    <option value={ option.name } selected={ option.name == metric }>
+                        { option.description }
+                    </option>
+                }) }
+            </select>
+            { " over the " }
+            <select onchange={ on_hours }>
+                { for HISTORY_RANGES.iter().map(|(range, label)| html! {
+                    <option value={ range.to_string() } selected={ *range == hours }>{ *label }</option>
+                }) }
+            </select>
+        </p>
+    };
+    let content = match history {
+        Some(history) if !history.points.is_empty() => {
+            let peak = history
+                .points
+                .iter()
+                .map(|point| point.max)
+                .fold(f64::NEG_INFINITY, f64::max);
+            let average = history.points.iter().map(|point| point.value).sum::<f64>()
+                / history.points.len() as f64;
+            html! {
+                <>
+                    { chart(history) }
+                    <p>
+                        { format!("{} readings from {} to {}", history.readings, history.from, history.to) }
+                        { format!(" · average {:.1} {} · peak {:.1} {}", average, history.unit, peak, history.unit) }
+                    </p>
+                </>
+            }
+        }
+        Some(_) => html! { <p>{ "No readings were stored in this range." }</p> },
+        None => html! { <p>{ "Loading..." }</p> },
+    };
+    panel(
+        "History",
+        html! {
+            <>
+                { selectors }
+                { content }
+            </>
+        },
+    )
+}
@@ -0,0 +1,18 @@
+// build.rs
+
+/// Generates the gRPC control plane client from the schema the agents serve,
+/// `oneforall-core/proto/oneforall.proto`, with the protoc bundled by
+/// `protoc-bin-vendored` unless `PROTOC` points to another.
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    if std::env::var_os("PROTOC").is_none() {
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    }
+    println!("cargo:rerun-if-changed=../oneforall-core/proto/oneforall.proto");
+    tonic_prost_build::configure()
+        .build_server(false)
+        .compile_protos(
+            &["../oneforall-core/proto/oneforall.proto"],
+            &["../oneforall-core/proto"],
+        )?;
+    Ok(())
+}
@@ -0,0 +1,351 @@
+//! Control Plane Client
+//!
+//! This module connects to the gRPC control plane of an agent and wraps each
+//! call in a typed async function. Failed calls are described as strings, like
+//! the errors of the rest of OneForAll; a job or result that does not exist is
+//! `None` rather than an error.
+
+use common::domain::job::Job;
+use common::domain::telemetry::TelemetryEvent;
+use serde::de::DeserializeOwned;
+use tokio_stream::{Stream, StreamExt};
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::transport::{Certificate, Channel, ClientTlsConfig, Endpoint, Identity};
+use tonic::{Code, Request};
+
+use crate::convert::{describe_status, job_from_proto, telemetry_from_proto};
+use crate::proto;
+use crate::proto::control_plane_client::ControlPlaneClient;
+use crate::results::{ResultFilter, ResultSummary, StoredResult, StoredSample};
+
+/// Configures and opens a connection to an agent.
+pub struct ClientBuilder {
+    endpoint: String,
+    token: Option<String>,
+    ca_certificate: Option<Vec<u8>>,
+    identity: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+impl ClientBuilder {
+    /// Creates a builder for the control plane of an agent.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The URL of the control plane, e.g. "http://rack12-node3:50051",
+    ///   or "https://..." when the agent serves its API over TLS.
+    ///
+    /// # Returns
+    ///
+    /// * `ClientBuilder` - A builder without authentication or certificates.
+    pub fn new(endpoint: &str) -> Self {
+        ClientBuilder {
+            endpoint: endpoint.to_string(),
+            token: None,
+            ca_certificate: None,
+            identity: None,
+        }
+    }
+
+    /// Presents a bearer token of the agent's `[api]` section with every call;
+    /// launching and cancelling jobs requires an operator token.
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    /// Trusts the agent's certificate when it is signed by this CA, in PEM format;
+    /// required for HTTPS.
+    pub fn with_ca_certificate(mut self, certificate: Vec<u8>) -> Self {
+        self.ca_certificate = Some(certificate);
+        self
+    }
+
+    /// Presents a client certificate, for agents that require one, in PEM format.
+    pub fn with_identity(mut self, certificate: Vec<u8>, private_key: Vec<u8>) -> Self {
+        self.identity = Some((certificate, private_key));
+        self
+    }
+
+    /// Connects to the agent.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<OneForAllClient, String>` - The client, or why the agent could not be reached.
+    pub async fn connect(self) -> Result<OneForAllClient, String> {
+        let authorization = match &self.token {
+            Some(token) => Some(
+                format!("Bearer {}", token)
+                    .parse::<MetadataValue<Ascii>>()
+                    .map_err(|_| {
+                        "The token contains characters not allowed in metadata".to_string()
+                    })?,
+            ),
+            None => None,
+        };
+
+        let mut endpoint = Endpoint::from_shared(self.endpoint.clone())
+            .map_err(|e| format!("Invalid endpoint {}: {}", self.endpoint, e))?;
+        // No system roots are loaded, so HTTPS needs the CA that signed the agent's certificate.
+        if self.endpoint.starts_with("https://")
+            || self.ca_certificate.is_some()
+            || self.identity.is_some()
+        {
+            let mut tls = ClientTlsConfig::new();
+            if let Some(certificate) = self.ca_certificate {
+                tls = tls.ca_certificate(Certificate::from_pem(certificate));
+            }
+            if let Some((certificate, private_key)) = self.identity {
+                tls = tls.identity(Identity::from_pem(certificate, private_key));
+            }
+            endpoint = endpoint
+                .tls_config(tls)
+                .map_err(|e| format!("Invalid TLS settings for {}: {}", self.endpoint, e))?;
+        }
+        let channel = endpoint
+            .connect()
+            .await
+            .map_err(|e| format!("Failed to connect to {}: {}", self.endpoint, e))?;
+
+        Ok(OneForAllClient {
+            inner: ControlPlaneClient::new(channel),
+            authorization,
+        })
+    }
+}
+
+/// A connection to the control plane of one agent. Cloning it is cheap and the
+/// clones share the connection.
+#[derive(Clone)]
+pub struct OneForAllClient {
+    inner: ControlPlaneClient<Channel>,
+    authorization: Option<MetadataValue<Ascii>>, // "Bearer <token>", if a token was given
+}
+
+impl OneForAllClient {
+    /// Wraps a message in a request carrying the token, if any.
+    fn request<T>(&self, message: T) -> Request<T> {
+        let mut request = Request::new(message);
+        if let Some(authorization) = &self.authorization {
+            request
+                .metadata_mut()
+                .insert("authorization", authorization.clone());
+        }
+        request
+    }
+
+    /// Starts running a built-in or saved profile as a job on the agent and
+    /// returns it without waiting; follow it with `watch_job` or `wait_for_job`.
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - The name of the profile, e.g. "burn-in-24h".
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Job, String>` - The submitted job, or why it could not be launched.
+    pub async fn launch_profile(&self, profile: &str) -> Result<Job, String> {
+        let request = self.request(proto::LaunchProfileRequest {
+            profile: profile.to_string(),
+        });
+        let job = self
+            .inner
+            .clone()
+            .launch_profile(request)
+            .await
+            .map_err(describe_status)?;
+        job_from_proto(job.into_inner())
+    }
+
+    /// Lists every job submitted on the agent since it started, including queued ones.
+    pub async fn list_jobs(&self) -> Result<Vec<Job>, String> {
+        let response = self
+            .inner
+            .clone()
+            .list_jobs(self.request(proto::ListJobsRequest {}))
+            .await
+            .map_err(describe_status)?;
+        response
+            .into_inner()
+            .jobs
+            .into_iter()
+            .map(job_from_proto)
+            .collect()
+    }
+
+    /// Returns a job, or `None` if the agent does not know it.
+    pub async fn get_job(&self, id: u64) -> Result<Option<Job>, String> {
+        match self
+            .inner
+            .clone()
+            .get_job(self.request(proto::GetJobRequest { id }))
+            .await
+        {
+            Ok(job) => job_from_proto(job.into_inner()).map(Some),
+            Err(status) if status.code() == Code::NotFound => Ok(None),
+            Err(status) => Err(describe_status(status)),
+        }
+    }
+
+    /// Requests cancellation of a queued or running job. A running job stops
+    /// asynchronously; its final status follows through `watch_job`.
+    pub async fn cancel_job(&self, id: u64) -> Result<Job, String> {
+        let job = self
+            .inner
+            .clone()
+            .cancel_job(self.request(proto::CancelJobRequest { id }))
+            .await
+            .map_err(describe_status)?;
+        job_from_proto(job.into_inner())
+    }
+
+    /// Streams a job now and after every status change, ending once it has finished.
+    pub async fn watch_job(
+        &self,
+        id: u64,
+    ) -> Result<impl Stream<Item = Result<Job, String>>, String> {
+        let stream = self
+            .inner
+            .clone()
+            .watch_job(self.request(proto::WatchJobRequest { id }))
+            .await
+            .map_err(describe_status)?
+            .into_inner();
+        Ok(stream.map(|job| job.map_err(describe_status).and_then(job_from_proto)))
+    }
+
+    /// Waits until a job has finished.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Job, String>` - The finished job, or why it could not be followed.
+    pub async fn wait_for_job(&self, id: u64) -> Result<Job, String> {
+        let mut updates = Box::pin(self.watch_job(id).await?);
+        while let Some(job) = updates.next().await {
+            let job = job?;
+            if job.status.is_finished() {
+                return Ok(job);
+            }
+        }
+        Err(format!(
+            "The agent stopped reporting job {} before it finished",
+            id
+        ))
+    }
+
+    /// Streams every power reading and stress test progress update of the agent from now on.
+    pub async fn stream_telemetry(
+        &self,
+    ) -> Result<impl Stream<Item = Result<TelemetryEvent, String>>, String> {
+        let stream = self
+            .inner
+            .clone()
+            .stream_telemetry(self.request(proto::StreamTelemetryRequest {}))
+            .await
+            .map_err(describe_status)?
+            .into_inner();
+        Ok(stream.map(|event| {
+            event
+                .map_err(describe_status)
+                .and_then(telemetry_from_proto)
+        }))
+    }
+
+    /// Lists the results stored on the agent, oldest first within each kind.
+    pub async fn list_results(&self, filter: &ResultFilter) -> Result<Vec<ResultSummary>, String> {
+        let request = self.request(proto::ListResultsRequest {
+            kind: filter.kind.clone().unwrap_or_default(),
+            since: filter.since.clone().unwrap_or_default(),
+            until: filter.until.clone().unwrap_or_default(),
+            tags: filter.tags.clone().into_iter().collect(),
+        });
+        let response = self
+            .inner
+            .clone()
+            .list_results(request)
+            .await
+            .map_err(describe_status)?;
+        Ok(response
+            .into_inner()
+            .results
+            .into_iter()
+            .map(|result| ResultSummary {
+                key: result.key,
+                kind: result.kind,
+            })
+            .collect())
+    }
+
+    /// Returns a stored result with its report, or `None` if there is none under
+    /// the key. The report can be read as `serde_json::Value` or as the report
+    /// type of its kind.
+    pub async fn get_result<T: DeserializeOwned>(
+        &self,
+        key: &str,
+    ) -> Result<Option<StoredResult<T>>, String> {
+        let request = self.request(proto::GetResultRequest {
+            key: key.to_string(),
+        });
+        let result = match self.inner.clone().get_result(request).await {
+            Ok(result) => result.into_inner(),
+            Err(status) if status.code() == Code::NotFound => return Ok(None),
+            Err(status) => return Err(describe_status(status)),
+        };
+        let report = serde_json::from_str(&result.json)
+            .map_err(|e| format!("Invalid report of result {}: {}", result.key, e))?;
+        Ok(Some(StoredResult {
+            key: result.key,
+            kind: result.kind,
+            report,
+        }))
+    }
+
+    /// Lists the readings a collector stored on the agent, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `collector` - The collector, e.g. "power" or "processes".
+    /// * `since` - Only readings taken at or after this RFC 3339 time.
+    /// * `until` - Only readings taken before this RFC 3339 time.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<StoredSample<T>>, String>` - The readings, or why they could not be listed.
+    pub async fn list_samples<T: DeserializeOwned>(
+        &self,
+        collector: &str,
+        since: Option<&str>,
+        until: Option<&str>,
+    ) -> Result<Vec<StoredSample<T>>, String> {
+        let request = self.request(proto::ListSamplesRequest {
+            collector: collector.to_string(),
+            since: since.unwrap_or_default().to_string(),
+            until: until.unwrap_or_default().to_string(),
+        });
+        let response = self
+            .inner
+            .clone()
+            .list_samples(request)
+            .await
+            .map_err(describe_status)?;
+        response
+            .into_inner()
+            .samples
+            .into_iter()
+            .map(|sample| {
+                let reading = serde_json::from_str(&sample.json).map_err(|e| {
+                    format!(
+                        "Invalid {} reading of {}: {}",
+                        collector, sample.recorded_at, e
+                    )
+                })?;
+                Ok(StoredSample {
+                    recorded_at: sample.recorded_at,
+                    sample: reading,
+                    wall_time: Some(sample.wall_time).filter(|time| !time.is_empty()),
+                    monotonic_ns: sample.monotonic_ns,
+                    boot_id: sample.boot_id,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,97 @@
+//! Conversions from the messages of the control plane to the domain types
+//! shared with the agents.
+
+use std::collections::BTreeSet;
+
+use common::domain::job::{Job, JobStatus, Resource};
+use common::domain::metric::{Aggregation, Metric};
+use common::domain::telemetry::{DomainPower, PowerSample, StressProgress, TelemetryEvent};
+use tonic::Status;
+
+use crate::proto;
+
+/// Describes a failed call, e.g. "NotFound: Job not found".
+pub(crate) fn describe_status(status: Status) -> String {
+    format!("{:?}: {}", status.code(), status.message())
+}
+
+/// Converts a job sent by an agent.
+pub(crate) fn job_from_proto(job: proto::Job) -> Result<Job, String> {
+    let status = match proto::JobStatus::try_from(job.status) {
+        Ok(proto::JobStatus::Queued) => JobStatus::Queued,
+        Ok(proto::JobStatus::Running) => JobStatus::Running,
+        Ok(proto::JobStatus::Completed) => JobStatus::Completed,
+        Ok(proto::JobStatus::Failed) => JobStatus::Failed,
+        Ok(proto::JobStatus::Cancelled) => JobStatus::Cancelled,
+        _ => {
+            return Err(format!(
+                "Job {} has an unknown status {}",
+                job.id, job.status
+            ))
+        }
+    };
+    let resources = job
+        .resources
+        .iter()
+        .map(|resource| {
+            serde_json::from_value::<Resource>(serde_json::Value::String(resource.clone()))
+                .map_err(|_| format!("Job {} needs an unknown resource '{}'", job.id, resource))
+        })
+        .collect::<Result<BTreeSet<_>, _>>()?;
+    Ok(Job {
+        id: job.id,
+        kind: job.kind,
+        resources,
+        status,
+        started_at: job.started_at,
+        finished_at: job.finished_at,
+        message: job.message,
+    })
+}
+
+/// Converts a live telemetry event sent by an agent.
+pub(crate) fn telemetry_from_proto(event: proto::TelemetryEvent) -> Result<TelemetryEvent, String> {
+    match event.event {
+        Some(proto::telemetry_event::Event::Power(sample)) => {
+            Ok(TelemetryEvent::Power(PowerSample {
+                timestamp: sample.timestamp,
+                domains: sample
+                    .domains
+                    .into_iter()
+                    .map(|d| DomainPower {
+                        domain: d.domain,
+                        watts: d.watts,
+                    })
+                    .collect(),
+                bmc_watts: sample.bmc_watts,
+            }))
+        }
+        Some(proto::telemetry_event::Event::StressProgress(progress)) => {
+            Ok(TelemetryEvent::StressProgress(StressProgress {
+                timestamp: progress.timestamp,
+                test: progress.test,
+                elapsed_secs: progress.elapsed_secs,
+                duration_secs: progress.duration_secs,
+                bogo_ops_per_sec: progress.bogo_ops_per_sec,
+                hottest_celsius: progress.hottest_celsius,
+                package_watts: progress.package_watts,
+                finished: progress.finished,
+            }))
+        }
+        Some(proto::telemetry_event::Event::Metrics(batch)) => Ok(TelemetryEvent::Metrics {
+            metrics: batch
+                .metrics
+                .into_iter()
+                .map(|metric| Metric {
+                    name: metric.name,
+                    unit: metric.unit,
+                    labels: metric.labels.into_iter().collect(),
+                    value: metric.value,
+                    timestamp: metric.timestamp,
+                    aggregation: Aggregation::default(),
+                })
+                .collect(),
+        }),
+        None => Err("Received an empty telemetry event".to_string()),
+    }
+}
@@ -0,0 +1,35 @@
+//! OneForAll Client
+//!
+//! This crate calls the gRPC control plane of remote OneForAll agents, the
+//! `oneforall.v1.ControlPlane` service they serve when the `[grpc]` section of
+//! their configuration sets an `address`. Lab automation written in Rust can
+//! launch profiles on an agent, follow and cancel its jobs, stream its power
+//! readings and stress test progress, and fetch the results and readings it
+//! stored, as the job and telemetry types of the `common` crate.
+//!
+//! ```no_run
+//! use oneforall_client::ClientBuilder;
+//!
+//! # async fn qualify() -> Result<(), String> {
+//! let client = ClientBuilder::new("http://rack12-node3:50051")
+//!     .with_token("operator-token")
+//!     .connect()
+//!     .await?;
+//! let job = client.launch_profile("quick-sanity").await?;
+//! let job = client.wait_for_job(job.id).await?;
+//! println!("{} finished: {:?}", job.kind, job.status);
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod client;
+mod convert;
+pub mod results;
+
+pub use client::{ClientBuilder, OneForAllClient};
+pub use results::{ResultFilter, ResultSummary, StoredResult, StoredSample};
+
+/// The messages and client generated from `oneforall-core/proto/oneforall.proto`.
+pub mod proto {
+    tonic::include_proto!("oneforall.v1");
+}
@@ -0,0 +1,65 @@
+//! Stored Results
+//!
+//! This module provides the results and readings an agent stored in its
+//! database, as returned by the client, and the filter results are listed by.
+
+use std::collections::BTreeMap;
+
+/// Which stored results to list; the default lists every result.
+#[derive(Debug, Clone, Default)]
+pub struct ResultFilter {
+    /// Only results of this kind, e.g. "burn-in" or "profile".
+    pub kind: Option<String>,
+
+    /// Only results of runs started at or after this RFC 3339 time.
+    pub since: Option<String>,
+
+    /// Only results of runs started before this RFC 3339 time.
+    pub until: Option<String>,
+
+    /// Only results carrying every one of these tags, e.g. "rack" = "r12".
+    pub tags: BTreeMap<String, String>,
+}
+
+/// A result stored on an agent, without its report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResultSummary {
+    /// The key the result is stored under, e.g. "burn-in:2024-05-02T08:00:00.000000Z".
+    pub key: String,
+
+    /// What produced the result, e.g. "burn-in", "profile" or "job".
+    pub kind: String,
+}
+
+/// A result stored on an agent with its report.
+#[derive(Debug, Clone)]
+pub struct StoredResult<T> {
+    /// The key the result is stored under.
+    pub key: String,
+
+    /// What produced the result.
+    pub kind: String,
+
+    /// The report, migrated by the agent to the current schema version.
+    pub report: T,
+}
+
+/// A reading a collector stored on an agent.
+#[derive(Debug, Clone)]
+pub struct StoredSample<T> {
+    /// When the reading was taken, in UTC; orders the readings.
+    pub recorded_at: String,
+
+    /// The reading, e.g. a `PowerSample`.
+    pub sample: T,
+
+    /// The wall-clock time the reading was taken, in RFC 3339 format, if the
+    /// agent kept it.
+    pub wall_time: Option<String>,
+
+    /// The monotonic clock when the reading was taken, in nanoseconds since boot.
+    pub monotonic_ns: u64,
+
+    /// The boot the monotonic clock counts from.
+    pub boot_id: String,
+}
@@ -0,0 +1,188 @@
+// kernel_latency.bpf.c
+//
+// Measures the distributions of run queue latency, off-CPU time and block I/O
+// latency across the whole machine, as log2 histograms in microseconds read by
+// the eBPF latency adapter. Only classic tracepoints are used, whose layouts are
+// stable, so the object loads on any kernel from 4.18 without BTF.
+//
+// Built by build.rs with `--features ebpf`:
+//   clang -O2 -target bpf -c bpf/kernel_latency.bpf.c
+
+#include <linux/bpf.h>
+#include <bpf/bpf_helpers.h>
+
+// The buckets of each histogram; bucket n counts latencies from 2^n µs, the first
+// those under 2 µs and the last those of 2^26 µs (67 s) or more.
+#define LATENCY_BUCKETS 27
+
+// The histograms, one after another in the `histograms` array.
+#define RUN_QUEUE 0
+#define OFF_CPU 1
+#define BLOCK_IO 2
+#define HISTOGRAMS 3
+
+// The threads and requests tracked at once; events beyond are not measured.
+#define MAX_ENTRIES 16384
+
+// A thread that is runnable, as `prev_state` of sched_switch reports it.
+#define TASK_RUNNING 0
+
+struct request_key {
+	__u32 dev;
+	__u32 padding;
+	__u64 sector;
+};
+
+// When each woken or preempted thread was queued to run, by thread ID.
+struct {
+	__uint(type, BPF_MAP_TYPE_HASH);
+	__uint(max_entries, MAX_ENTRIES);
+	__type(key, __u32);
+	__type(value, __u64);
+} queued SEC(".maps");
+
+// When each blocked thread left its CPU, by thread ID.
+struct {
+	__uint(type, BPF_MAP_TYPE_HASH);
+	__uint(max_entries, MAX_ENTRIES);
+	__type(key, __u32);
+	__type(value, __u64);
+} blocked SEC(".maps");
+
+// When each block request was issued to its device, by device and sector.
+struct {
+	__uint(type, BPF_MAP_TYPE_HASH);
+	__uint(max_entries, MAX_ENTRIES);
+	__type(key, struct request_key);
+	__type(value, __u64);
+} issued SEC(".maps");
+
+// The counts of the buckets of every histogram.
+struct {
+	__uint(type, BPF_MAP_TYPE_ARRAY);
+	__uint(max_entries, HISTOGRAMS * LATENCY_BUCKETS);
+	__type(key, __u32);
+	__type(value, __u64);
+} histograms SEC(".maps");
+
+// The tracepoint records, after the 8 bytes common to every tracepoint, as
+// /sys/kernel/tracing/events/<category>/<event>/format lays them out.
+struct sched_wakeup_args {
+	__u64 common;
+	char comm[16];
+	int pid;
+	int prio;
+	int target_cpu;
+};
+
+struct sched_switch_args {
+	__u64 common;
+	char prev_comm[16];
+	int prev_pid;
+	int prev_prio;
+	long prev_state;
+	char next_comm[16];
+	int next_pid;
+	int next_prio;
+};
+
+struct block_rq_args {
+	__u64 common;
+	__u32 dev;
+	__u64 sector;
+};
+
+static __always_inline void record(__u32 histogram, __u64 start)
+{
+	__u64 us = (bpf_ktime_get_ns() - start) / 1000;
+	__u32 slot = 0;
+
+#pragma unroll
+	for (int i = 0; i < LATENCY_BUCKETS - 1; i++) {
+		if (us <= 1)
+			break;
+		us >>= 1;
+		slot++;
+	}
+
+	__u32 index = histogram * LATENCY_BUCKETS + slot;
+	__u64 *count = bpf_map_lookup_elem(&histograms, &index);
+	if (count)
+		__sync_fetch_and_add(count, 1);
+}
+
+static __always_inline void mark(void *map, __u32 pid)
+{
+	__u64 now = bpf_ktime_get_ns();
+
+	if (pid)
+		bpf_map_update_elem(map, &pid, &now, BPF_ANY);
+}
+
+SEC("tracepoint/sched/sched_wakeup")
+int sched_wakeup(struct sched_wakeup_args *args)
+{
+	mark(&queued, args->pid);
+	return 0;
+}
+
+SEC("tracepoint/sched/sched_wakeup_new")
+int sched_wakeup_new(struct sched_wakeup_args *args)
+{
+	mark(&queued, args->pid);
+	return 0;
+}
+
+SEC("tracepoint/sched/sched_switch")
+int sched_switch(struct sched_switch_args *args)
+{
+	__u32 prev = args->prev_pid;
+	__u32 next = args->next_pid;
+	__u64 *start;
+
+	// A preempted thread waits in the run queue; a blocked one is off-CPU until
+	// it runs again.
+	if (args->prev_state == TASK_RUNNING)
+		mark(&queued, prev);
+	else
+		mark(&blocked, prev);
+
+	if (!next)
+		return 0;
+	start = bpf_map_lookup_elem(&queued, &next);
+	if (start) {
+		record(RUN_QUEUE, *start);
+		bpf_map_delete_elem(&queued, &next);
+	}
+	start = bpf_map_lookup_elem(&blocked, &next);
+	if (start) {
+		record(OFF_CPU, *start);
+		bpf_map_delete_elem(&blocked, &next);
+	}
+	return 0;
+}
+
+SEC("tracepoint/block/block_rq_issue")
+int block_rq_issue(struct block_rq_args *args)
+{
+	struct request_key key = { .dev = args->dev, .sector = args->sector };
+	__u64 now = bpf_ktime_get_ns();
+
+	bpf_map_update_elem(&issued, &key, &now, BPF_ANY);
+	return 0;
+}
+
+SEC("tracepoint/block/block_rq_complete")
+int block_rq_complete(struct block_rq_args *args)
+{
+	struct request_key key = { .dev = args->dev, .sector = args->sector };
+	__u64 *start = bpf_map_lookup_elem(&issued, &key);
+
+	if (start) {
+		record(BLOCK_IO, *start);
+		bpf_map_delete_elem(&issued, &key);
+	}
+	return 0;
+}
+
+char LICENSE[] SEC("license") = "Dual MIT/GPL";
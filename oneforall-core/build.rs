@@ -0,0 +1,44 @@
+// build.rs
+
+/// Generates the gRPC control plane service from `proto/oneforall.proto`, with
+/// the protoc bundled by `protoc-bin-vendored` unless `PROTOC` points to another,
+/// and with the `ebpf` feature compiles the kernel latency probes with clang, or
+/// the compiler `CLANG` points to.
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    if std::env::var_os("PROTOC").is_none() {
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    }
+    println!("cargo:rerun-if-changed=proto/oneforall.proto");
+    tonic_prost_build::configure()
+        .build_client(false)
+        .compile_protos(&["proto/oneforall.proto"], &["proto"])?;
+    if std::env::var_os("CARGO_FEATURE_EBPF").is_some() {
+        compile_bpf("kernel_latency")?;
+    }
+    Ok(())
+}
+
+/// Compiles `bpf/<name>.bpf.c` to BPF bytecode in `OUT_DIR`, where the adapter
+/// loading it includes it from.
+fn compile_bpf(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let source = format!("bpf/{}.bpf.c", name);
+    let object = std::path::Path::new(&std::env::var("OUT_DIR")?).join(format!("{}.bpf.o", name));
+    println!("cargo:rerun-if-changed={}", source);
+    println!("cargo:rerun-if-env-changed=CLANG");
+
+    // `linux/bpf.h` includes `asm/types.h`, which distributions keep under the
+    // multiarch directory of the host.
+    let arch = std::env::var("CARGO_CFG_TARGET_ARCH")?;
+    let status =
+        std::process::Command::new(std::env::var("CLANG").unwrap_or_else(|_| "clang".to_string()))
+            .args(["-O2", "-g", "-target", "bpf"])
+            .arg(format!("-I/usr/include/{}-linux-gnu", arch))
+            .args(["-c", &source, "-o"])
+            .arg(&object)
+            .status()
+            .map_err(|e| format!("Failed to run clang for the `ebpf` feature: {}", e))?;
+    if !status.success() {
+        return Err(format!("clang failed to compile {}", source).into());
+    }
+    Ok(())
+}
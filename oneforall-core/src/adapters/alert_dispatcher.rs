@@ -0,0 +1,178 @@
+//! Alert Dispatcher
+//!
+//! This module delivers alerts to the webhooks and email destinations of the
+//! `[alerts]` configuration. Alerts are queued and sent one after another on a
+//! background thread, so a slow or unreachable destination never holds up a
+//! test; `flush` waits for the queue to drain before the application exits.
+
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use chrono::Local;
+use common::ports::log_port::LoggerPort;
+
+use crate::adapters::smtp_alert_adapter::SmtpAlertAdapter;
+use crate::adapters::webhook_alert_adapter::WebhookAlertAdapter;
+use crate::domain::alert::{Alert, AlertConfig, AlertKind};
+use crate::ports::alert_port::AlertPort;
+
+/// The longest `flush` waits for queued alerts to be delivered.
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Reads the `[alerts]` section of the configuration file.
+///
+/// # Arguments
+///
+/// * `path` - The configuration file; a missing file means no alerts.
+///
+/// # Returns
+///
+/// * `Result<AlertConfig, String>` - The alert settings, or why they could not be read.
+pub fn load_alert_config(path: &Path) -> Result<AlertConfig, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(AlertConfig::default()),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    AlertConfig::from_toml(&text).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+}
+
+/// Returns the name of this machine, which every alert carries and fleet agents announce.
+pub fn hostname() -> String {
+    let mut buffer = [0u8; 256];
+    // SAFETY: the buffer is valid for its whole length, which is passed along.
+    let result = unsafe { libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len()) };
+    let length = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+    match String::from_utf8_lossy(&buffer[..length]).into_owned() {
+        name if result == 0 && !name.is_empty() => name,
+        _ => "localhost".to_string(),
+    }
+}
+
+/// Work for the delivery thread.
+enum Delivery {
+    /// An alert to send to every destination that subscribes to it.
+    Send(Alert),
+
+    /// Answered once every alert queued before it has been handled.
+    Flush(Sender<()>),
+}
+
+/// Sends alerts to the configured destinations in the background.
+pub struct AlertDispatcher {
+    logger: Arc<dyn LoggerPort>,
+    hostname: String,
+    queue: Sender<Delivery>,
+}
+
+impl AlertDispatcher {
+    /// Creates a dispatcher for the destinations of the `[alerts]` configuration
+    /// and starts its delivery thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger` - Logger implementation for logging messages.
+    /// * `config` - The alert settings.
+    ///
+    /// # Returns
+    ///
+    /// * `AlertDispatcher` - A dispatcher that sends nothing if no destination is configured.
+    pub fn new(logger: Arc<dyn LoggerPort>, config: &AlertConfig) -> Self {
+        let mut destinations: Vec<Box<dyn AlertPort>> = Vec::new();
+        for webhook in &config.webhooks {
+            destinations.push(Box::new(WebhookAlertAdapter::new(
+                logger.clone(),
+                webhook.clone(),
+            )));
+        }
+        for email in &config.emails {
+            destinations.push(Box::new(SmtpAlertAdapter::new(
+                logger.clone(),
+                email.clone(),
+            )));
+        }
+
+        let (queue, deliveries) = mpsc::channel();
+        let delivery_logger = logger.clone();
+        thread::spawn(move || Self::deliver(delivery_logger, destinations, deliveries));
+
+        AlertDispatcher {
+            logger,
+            hostname: hostname(),
+            queue,
+        }
+    }
+
+    /// Sends every queued alert in turn until the dispatcher is dropped.
+    fn deliver(
+        logger: Arc<dyn LoggerPort>,
+        destinations: Vec<Box<dyn AlertPort>>,
+        deliveries: Receiver<Delivery>,
+    ) {
+        for delivery in deliveries {
+            let alert = match delivery {
+                Delivery::Send(alert) => alert,
+                Delivery::Flush(done) => {
+                    let _ = done.send(());
+                    continue;
+                }
+            };
+            for destination in destinations.iter().filter(|d| d.accepts(alert.kind)) {
+                match destination.send(&alert) {
+                    Ok(()) => logger.log_info(&format!(
+                        "Sent the {} alert to {}",
+                        alert.kind.label(),
+                        destination.describe()
+                    )),
+                    Err(e) => logger.log_warn(&format!(
+                        "Failed to send the {} alert to {}: {}",
+                        alert.kind.label(),
+                        destination.describe(),
+                        e
+                    )),
+                }
+            }
+        }
+    }
+
+    /// Raises an alert, which is logged at once and delivered in the background.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - What the alert reports.
+    /// * `summary` - A one-line summary.
+    /// * `details` - Further lines, such as the faults found.
+    pub fn raise(&self, kind: AlertKind, summary: &str, details: Vec<String>) {
+        let message = format!("Alert ({}): {}", kind.label(), summary);
+        match kind {
+            AlertKind::Completed => self.logger.log_info(&message),
+            AlertKind::Failed | AlertKind::Threshold | AlertKind::Anomaly => {
+                self.logger.log_warn(&message)
+            }
+        }
+        let alert = Alert {
+            kind,
+            hostname: self.hostname.clone(),
+            summary: summary.to_string(),
+            details,
+            timestamp: Local::now().to_rfc3339(),
+        };
+        let _ = self.queue.send(Delivery::Send(alert));
+    }
+
+    /// Waits until every alert raised so far has been delivered, or for at most
+    /// 30 seconds, so alerts raised just before exiting are not lost.
+    pub fn flush(&self) {
+        let (done, finished) = mpsc::channel();
+        if self.queue.send(Delivery::Flush(done)).is_ok()
+            && finished.recv_timeout(FLUSH_TIMEOUT).is_err()
+        {
+            self.logger
+                .log_warn("Gave up waiting for alerts to be delivered.");
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! Alerting Job Adapter
+//!
+//! This module wraps a `JobPort` so that finishing a job raises an alert: every
+//! failed job raises a failure alert, and a burn-in or profile run that passes
+//! raises a completion alert. The command handlers finish their jobs as before
+//! and need not know whether alerts are configured.
+
+use std::sync::Arc;
+
+use common::domain::job::{Job, JobSpec, JobStatus};
+use common::ports::job_port::{JobPort, JobTicket};
+
+use crate::adapters::alert_dispatcher::AlertDispatcher;
+use crate::domain::alert::AlertKind;
+
+/// Returns `true` for the jobs whose completion is worth an alert: full-system
+/// burn-ins and profile runs, which take hours and are watched from afar.
+fn is_burn_in(kind: &str) -> bool {
+    kind == "stress:all" || kind.starts_with("profile:")
+}
+
+/// A job queue that raises alerts when jobs fail or burn-ins complete.
+pub struct AlertingJobAdapter {
+    jobs: Arc<dyn JobPort>,
+    alerts: Arc<AlertDispatcher>,
+}
+
+impl AlertingJobAdapter {
+    /// Creates a new instance of `AlertingJobAdapter`.
+    ///
+    /// # Arguments
+    /// * `jobs` - The job queue that actually tracks the jobs.
+    /// * `alerts` - Where the alerts are raised.
+    ///
+    /// # Returns
+    /// An instance of `AlertingJobAdapter`.
+    pub fn new(jobs: Arc<dyn JobPort>, alerts: Arc<AlertDispatcher>) -> Self {
+        AlertingJobAdapter { jobs, alerts }
+    }
+
+    /// Raises the alert for a finished job, if it deserves one.
+    fn alert(&self, job: &Job) {
+        let (kind, outcome) = match job.status {
+            JobStatus::Failed => (AlertKind::Failed, "failed"),
+            JobStatus::Completed if is_burn_in(&job.kind) => (AlertKind::Completed, "passed"),
+            _ => return,
+        };
+        let mut details = Vec::new();
+        if let Some(message) = &job.message {
+            details.push(message.clone());
+        }
+        details.push(format!(
+            "Started {}, finished {}",
+            job.started_at,
+            job.finished_at.as_deref().unwrap_or("-")
+        ));
+        self.alerts.raise(
+            kind,
+            &format!("Job {} ({}) {}", job.id, job.kind, outcome),
+            details,
+        );
+    }
+}
+
+// Implement the `JobPort` trait for `AlertingJobAdapter`.
+impl JobPort for AlertingJobAdapter {
+    fn submit_job(&self, spec: JobSpec) -> JobTicket {
+        self.jobs.submit_job(spec)
+    }
+
+    fn finish_job(&self, id: u64, status: JobStatus, message: Option<String>) -> Option<Job> {
+        let job = self.jobs.finish_job(id, status, message)?;
+        self.alert(&job);
+        Some(job)
+    }
+
+    fn cancel_job(&self, id: u64) -> Result<Job, String> {
+        self.jobs.cancel_job(id)
+    }
+
+    fn cancel_all(&self) {
+        self.jobs.cancel_all()
+    }
+
+    fn get_job(&self, id: u64) -> Option<Job> {
+        self.jobs.get_job(id)
+    }
+
+    fn list_jobs(&self) -> Vec<Job> {
+        self.jobs.list_jobs()
+    }
+}
@@ -0,0 +1,636 @@
+//! Archive Adapter
+//!
+//! This module writes a stored run to a portable archive and reads archives
+//! into the database, so runs from air-gapped lab machines can be carried over
+//! to an analyst's workstation on removable media. An archive holds the result
+//! of the run, the collector readings taken over it, the hardware inventory of
+//! the machine and the log messages the running instance still held, in the
+//! layout of `domain::archive`. Archives are signed with the ed25519 key of
+//! the operator exporting them, when given one, and their signatures checked
+//! against the keys trusted by the operator importing them.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, SecondsFormat, Utc};
+use common::domain::log_record::LogRecord;
+use common::ports::log_port::LoggerPort;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use serde::Serialize;
+
+use crate::adapters::alert_dispatcher::hostname;
+use crate::adapters::jobs_client_adapter::JobsClientAdapter;
+use crate::adapters::repository::Repository;
+use crate::domain::archive::{
+    archive_file_name, ed25519_public_key, imported_collector, key_fingerprint, run_window,
+    signed_content, ArchiveManifest, ArchiveSignature, ArchivedSample, SignatureCheck,
+    ARCHIVED_COLLECTORS, ARCHIVED_LOG_LINES, ARCHIVE_FORMAT_VERSION, HOST_TAG, INVENTORY_FILE,
+    LOGS_FILE, MANIFEST_FILE, RESULT_FILE, SAMPLES_DIR, SIGNATURE_ALGORITHM, SIGNATURE_FILE,
+    SIGNED_BY_TAG,
+};
+use crate::domain::database::{
+    RunKey, StoredSample, ARCHIVED_INVENTORY_PREFIX, ARCHIVED_LOGS_PREFIX,
+};
+use crate::domain::tags::TAGS_FIELD;
+
+/// Encodes values as JSON lines.
+fn json_lines<T: Serialize>(values: &[T]) -> Result<Vec<u8>, String> {
+    let mut body = Vec::new();
+    for value in values {
+        serde_json::to_writer(&mut body, value).map_err(|e| e.to_string())?;
+        body.push(b'\n');
+    }
+    Ok(body)
+}
+
+/// Decodes the JSON lines of an archived file.
+fn parse_lines<T: serde::de::DeserializeOwned>(name: &str, body: &[u8]) -> Result<Vec<T>, String> {
+    String::from_utf8_lossy(body)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(|e| format!("Invalid {}: {}", name, e)))
+        .collect()
+}
+
+/// Adds a file to the archive being written.
+fn append<W: Write>(builder: &mut tar::Builder<W>, name: &str, body: &[u8]) -> Result<(), String> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(body.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, name, body)
+        .map_err(|e| format!("Failed to write {} to the archive: {}", name, e))
+}
+
+/// Reads the DER content of the PEM block with a label from a file.
+fn read_pem(path: &Path, label: &str) -> Result<Vec<u8>, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let begin = format!("-----BEGIN {}-----", label);
+    let end = format!("-----END {}-----", label);
+    let body = text
+        .split_once(&begin)
+        .and_then(|(_, rest)| rest.split_once(&end))
+        .map(|(body, _)| body.split_whitespace().collect::<String>())
+        .ok_or_else(|| format!("{} holds no PEM {}", path.display(), label))?;
+    STANDARD
+        .decode(body)
+        .map_err(|e| format!("Invalid PEM in {}: {}", path.display(), e))
+}
+
+/// Reads the ed25519 private key archives are signed with, in PEM, as written
+/// by `openssl genpkey -algorithm ed25519`.
+///
+/// # Arguments
+///
+/// * `path` - The file holding the key.
+///
+/// # Returns
+///
+/// * `Result<Ed25519KeyPair, String>` - The key, or why it could not be read.
+pub fn load_signing_key(path: &Path) -> Result<Ed25519KeyPair, String> {
+    let der = read_pem(path, "PRIVATE KEY")?;
+    Ed25519KeyPair::from_pkcs8_maybe_unchecked(&der)
+        .map_err(|e| format!("{} is not an ed25519 private key: {}", path.display(), e))
+}
+
+/// Reads an ed25519 public key whose signatures are trusted, in PEM, as written
+/// by `openssl pkey -pubout`.
+///
+/// # Arguments
+///
+/// * `path` - The file holding the key.
+///
+/// # Returns
+///
+/// * `Result<Vec<u8>, String>` - The 32 bytes of the key, or why it could not be read.
+pub fn load_trusted_key(path: &Path) -> Result<Vec<u8>, String> {
+    let der = read_pem(path, "PUBLIC KEY")?;
+    ed25519_public_key(&der).map_err(|e| format!("Invalid key in {}: {}", path.display(), e))
+}
+
+/// Represents the run archive adapter.
+pub struct ArchiveAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    repository: Repository,
+    agent: Option<JobsClientAdapter>, // the running instance log messages are read from
+    signing_key: Option<Ed25519KeyPair>, // signs exported archives
+    trusted_keys: Vec<Vec<u8>>,       // imported archives must be signed by one, if any
+}
+
+impl ArchiveAdapter {
+    /// Creates a new instance of `ArchiveAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    /// * `repository` - Where the runs are stored.
+    ///
+    /// # Returns
+    /// An instance of `ArchiveAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>, repository: Repository) -> Self {
+        ArchiveAdapter {
+            logger,
+            repository,
+            agent: None,
+            signing_key: None,
+            trusted_keys: Vec::new(),
+        }
+    }
+
+    /// Archives the log messages of runs as held by a running instance, which
+    /// keeps the most recent `ARCHIVED_LOG_LINES` of them.
+    pub fn with_agent(mut self, agent: JobsClientAdapter) -> Self {
+        self.agent = Some(agent);
+        self
+    }
+
+    /// Signs exported archives with the key of the operator exporting them.
+    pub fn with_signing_key(mut self, key: Ed25519KeyPair) -> Self {
+        self.signing_key = Some(key);
+        self
+    }
+
+    /// Only imports archives signed with one of these public keys.
+    pub fn with_trusted_keys(mut self, keys: Vec<Vec<u8>>) -> Self {
+        self.trusted_keys = keys;
+        self
+    }
+
+    /// Writes a stored run to an archive.
+    ///
+    /// # Arguments
+    /// * `run` - The key of the run, e.g. "profile:2024-05-02T08:00:00.000000Z:burn-in-24h".
+    /// * `output` - The file to write; named after the machine and the run in the
+    ///   working directory when `None`.
+    ///
+    /// # Returns
+    /// A `Result` containing the file written and the manifest of the archive, or an error message.
+    pub fn export(
+        &self,
+        run: &str,
+        output: Option<&Path>,
+    ) -> Result<(PathBuf, ArchiveManifest), String> {
+        let key = RunKey::parse(run).ok_or_else(|| {
+            format!(
+                "Invalid run '{}'; expected a key such as \
+                 profile:2024-05-02T08:00:00.000000Z:burn-in-24h, which `db migrate` \
+                 gives the runs stored by older versions",
+                run
+            )
+        })?;
+        let result = self
+            .repository
+            .get_stored_run(run)?
+            .ok_or_else(|| format!("No run is stored under '{}'", run))?;
+        let (from, to) = run_window(&key, &result);
+        // A run imported from another machine is archived as that machine's.
+        let host = result
+            .get(TAGS_FIELD)
+            .and_then(|tags| tags.get(HOST_TAG))
+            .and_then(|host| host.as_str())
+            .map_or_else(hostname, str::to_string);
+        let path = output
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from(archive_file_name(&host, &key)));
+
+        let mut files = vec![(
+            RESULT_FILE.to_string(),
+            serde_json::to_vec_pretty(&result).map_err(|e| e.to_string())?,
+        )];
+        let mut samples = BTreeMap::new();
+        for collector in ARCHIVED_COLLECTORS {
+            let readings = self
+                .repository
+                .get_samples_in_range::<serde_json::Value>(collector, Some(&from), Some(&to))?
+                .into_iter()
+                .map(|(recorded_at, stored)| ArchivedSample {
+                    recorded_at: recorded_at.to_rfc3339_opts(SecondsFormat::Micros, true),
+                    time: stored.time,
+                    sample: stored.sample,
+                })
+                .collect::<Vec<_>>();
+            if readings.is_empty() {
+                continue;
+            }
+            samples.insert(collector.to_string(), readings.len());
+            files.push((
+                format!("{}{}.jsonl", SAMPLES_DIR, collector),
+                json_lines(&readings)?,
+            ));
+        }
+
+        // A run imported from another machine keeps that machine's inventory and logs.
+        let inventory = match self
+            .repository
+            .get_archived::<serde_json::Value>(ARCHIVED_INVENTORY_PREFIX, run)?
+        {
+            Some(inventory) => Some(inventory),
+            None => self
+                .repository
+                .get_inventory_at::<serde_json::Value>(&from)?
+                .map(|(_, inventory)| inventory),
+        };
+        if let Some(inventory) = &inventory {
+            files.push((
+                INVENTORY_FILE.to_string(),
+                serde_json::to_vec_pretty(inventory).map_err(|e| e.to_string())?,
+            ));
+        }
+        let logs = match self
+            .repository
+            .get_archived::<Vec<LogRecord>>(ARCHIVED_LOGS_PREFIX, run)?
+        {
+            Some(logs) => logs,
+            None => self.run_logs(&from, &to),
+        };
+        if !logs.is_empty() {
+            files.push((LOGS_FILE.to_string(), json_lines(&logs)?));
+        }
+
+        let manifest = ArchiveManifest {
+            format_version: ARCHIVE_FORMAT_VERSION,
+            run: run.to_string(),
+            hostname: host,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            exported_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            from: from.to_rfc3339_opts(SecondsFormat::Secs, true),
+            to: to.to_rfc3339_opts(SecondsFormat::Secs, true),
+            samples,
+            inventory: inventory.is_some(),
+            logs: logs.len(),
+            signed_by: self
+                .signing_key
+                .as_ref()
+                .map(|key| key_fingerprint(key.public_key().as_ref())),
+        };
+        files.insert(
+            0,
+            (
+                MANIFEST_FILE.to_string(),
+                serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?,
+            ),
+        );
+        if let Some(key) = &self.signing_key {
+            let content = signed_content(&files.iter().cloned().collect());
+            let signature = ArchiveSignature {
+                algorithm: SIGNATURE_ALGORITHM.to_string(),
+                public_key: STANDARD.encode(key.public_key()),
+                signature: STANDARD.encode(key.sign(&content)),
+            };
+            files.push((
+                SIGNATURE_FILE.to_string(),
+                serde_json::to_vec_pretty(&signature).map_err(|e| e.to_string())?,
+            ));
+        }
+
+        let file = File::create(&path)
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+        for (name, body) in &files {
+            append(&mut builder, name, body)?;
+        }
+        builder
+            .into_inner()
+            .and_then(|encoder| encoder.finish())
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        self.logger
+            .log_info(&format!("Archived {} to {}", manifest.run, path.display()));
+        Ok((path, manifest))
+    }
+
+    /// Reads the log messages logged over a run from the running instance, if any.
+    fn run_logs(&self, from: &DateTime<Utc>, to: &DateTime<Utc>) -> Vec<LogRecord> {
+        let Some(agent) = &self.agent else {
+            return Vec::new();
+        };
+        match agent.recent_logs("trace", ARCHIVED_LOG_LINES) {
+            Ok(records) => records
+                .into_iter()
+                .filter(|record| {
+                    DateTime::parse_from_rfc3339(&record.timestamp)
+                        .map(|time| time.with_timezone(&Utc))
+                        .is_ok_and(|time| time >= *from && time < *to)
+                })
+                .collect(),
+            Err(e) => {
+                self.logger.log_warn(&format!(
+                    "No log messages are archived, as the running instance could not be read: {}",
+                    e
+                ));
+                Vec::new()
+            }
+        }
+    }
+
+    /// Checks the signature of an archive: a signed archive must be intact, and
+    /// with trusted keys, signed by one of them.
+    fn check_signature(
+        &self,
+        files: &BTreeMap<String, Vec<u8>>,
+        manifest: &ArchiveManifest,
+    ) -> Result<SignatureCheck, String> {
+        let Some(body) = files.get(SIGNATURE_FILE) else {
+            if let Some(fingerprint) = &manifest.signed_by {
+                return Err(format!(
+                    "The archive was signed with the key {}, but its signature was removed",
+                    fingerprint
+                ));
+            }
+            if !self.trusted_keys.is_empty() {
+                return Err(
+                    "The archive is not signed, but only archives signed with a trusted key \
+                     are imported"
+                        .to_string(),
+                );
+            }
+            return Ok(SignatureCheck::Unsigned);
+        };
+        let signature: ArchiveSignature = serde_json::from_slice(body)
+            .map_err(|e| format!("Invalid {}: {}", SIGNATURE_FILE, e))?;
+        if signature.algorithm != SIGNATURE_ALGORITHM {
+            return Err(format!(
+                "The archive is signed with {}, but only {} signatures are supported",
+                signature.algorithm, SIGNATURE_ALGORITHM
+            ));
+        }
+        let public_key = STANDARD
+            .decode(&signature.public_key)
+            .map_err(|e| format!("Invalid public key in {}: {}", SIGNATURE_FILE, e))?;
+        let signed = STANDARD
+            .decode(&signature.signature)
+            .map_err(|e| format!("Invalid signature in {}: {}", SIGNATURE_FILE, e))?;
+        UnparsedPublicKey::new(&ED25519, &public_key)
+            .verify(&signed_content(files), &signed)
+            .map_err(|_| {
+                "The signature does not match the content of the archive, which was changed \
+                 after it was signed"
+                    .to_string()
+            })?;
+        let fingerprint = key_fingerprint(&public_key);
+        if self.trusted_keys.contains(&public_key) {
+            Ok(SignatureCheck::Trusted { fingerprint })
+        } else if self.trusted_keys.is_empty() {
+            Ok(SignatureCheck::Untrusted { fingerprint })
+        } else {
+            Err(format!(
+                "The archive is signed with the key {}, which is not trusted",
+                fingerprint
+            ))
+        }
+    }
+
+    /// Reads an archive into the database. The result is tagged with the machine
+    /// it ran on, and with the key it was signed with when that key is trusted,
+    /// and its readings are stored under collectors of that machine.
+    ///
+    /// # Arguments
+    /// * `path` - The archive written by `export`.
+    ///
+    /// # Returns
+    /// A `Result` containing the manifest of the archive and what its signature
+    /// showed, or an error message; a run already in the database is not
+    /// imported again.
+    pub fn import(&self, path: &Path) -> Result<(ArchiveManifest, SignatureCheck), String> {
+        let file =
+            File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        let mut archive = tar::Archive::new(GzDecoder::new(file));
+        let mut files = BTreeMap::new();
+        let entries = archive
+            .entries()
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        for entry in entries {
+            let mut entry =
+                entry.map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            let name = entry
+                .path()
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+                .to_string_lossy()
+                .into_owned();
+            let mut body = Vec::new();
+            entry
+                .read_to_end(&mut body)
+                .map_err(|e| format!("Failed to read {} from {}: {}", name, path.display(), e))?;
+            files.insert(name, body);
+        }
+
+        let read = |name: &str| {
+            files.get(name).ok_or_else(|| {
+                format!(
+                    "{} is not a run archive: it has no {}",
+                    path.display(),
+                    name
+                )
+            })
+        };
+        let manifest: ArchiveManifest = serde_json::from_slice(read(MANIFEST_FILE)?)
+            .map_err(|e| format!("Invalid {}: {}", MANIFEST_FILE, e))?;
+        if manifest.format_version > ARCHIVE_FORMAT_VERSION {
+            return Err(format!(
+                "{} was written by a newer version of OneForAll ({}); upgrade to import it",
+                path.display(),
+                manifest.version
+            ));
+        }
+        let check = self.check_signature(&files, &manifest)?;
+        let key = RunKey::parse(&manifest.run)
+            .ok_or_else(|| format!("Invalid run '{}' in {}", manifest.run, MANIFEST_FILE))?;
+        let mut result: serde_json::Value = serde_json::from_slice(read(RESULT_FILE)?)
+            .map_err(|e| format!("Invalid {}: {}", RESULT_FILE, e))?;
+
+        // Everything is read before anything is stored, so a damaged archive stores nothing.
+        let mut readings = Vec::new();
+        for collector in manifest.samples.keys() {
+            let name = format!("{}{}.jsonl", SAMPLES_DIR, collector);
+            let samples = parse_lines::<ArchivedSample>(&name, read(&name)?)?
+                .into_iter()
+                .map(|reading| {
+                    DateTime::parse_from_rfc3339(&reading.recorded_at)
+                        .map(|time| {
+                            let sample = StoredSample {
+                                time: reading.time,
+                                sample: reading.sample,
+                            };
+                            (time.with_timezone(&Utc), sample)
+                        })
+                        .map_err(|e| format!("Invalid {}: {}", name, e))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            readings.push((imported_collector(collector, &manifest.hostname), samples));
+        }
+        let inventory = if manifest.inventory {
+            Some(
+                serde_json::from_slice::<serde_json::Value>(read(INVENTORY_FILE)?)
+                    .map_err(|e| format!("Invalid {}: {}", INVENTORY_FILE, e))?,
+            )
+        } else {
+            None
+        };
+        let logs = if manifest.logs > 0 {
+            parse_lines::<LogRecord>(LOGS_FILE, read(LOGS_FILE)?)?
+        } else {
+            Vec::new()
+        };
+
+        // The machine the run came from is kept as a tag, so its runs can be found by it,
+        // and so is the trusted key that vouches for it. A signer tag carried in the result
+        // itself is dropped, as only the signature check may set it.
+        if let Some(object) = result.as_object_mut() {
+            let tags = object
+                .entry(TAGS_FIELD)
+                .or_insert_with(|| serde_json::Value::Object(Default::default()));
+            if let Some(tags) = tags.as_object_mut() {
+                tags.entry(HOST_TAG)
+                    .or_insert_with(|| serde_json::Value::from(manifest.hostname.as_str()));
+                tags.remove(SIGNED_BY_TAG);
+                if let SignatureCheck::Trusted { fingerprint } = &check {
+                    tags.insert(
+                        SIGNED_BY_TAG.to_string(),
+                        serde_json::Value::from(fingerprint.as_str()),
+                    );
+                }
+            }
+        }
+        self.repository.import_run(&key, &result)?;
+        for (collector, samples) in &readings {
+            self.repository.store_sample_batch(collector, samples)?;
+        }
+        if let Some(inventory) = &inventory {
+            self.repository
+                .store_archived(ARCHIVED_INVENTORY_PREFIX, &manifest.run, inventory)?;
+        }
+        if !logs.is_empty() {
+            self.repository
+                .store_archived(ARCHIVED_LOGS_PREFIX, &manifest.run, &logs)?;
+        }
+        self.repository.flush()?;
+        self.logger.log_info(&format!(
+            "Imported {} from {}",
+            manifest.run,
+            path.display()
+        ));
+        Ok((manifest, check))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ring::rand::SystemRandom;
+
+    use super::*;
+    use crate::adapters::sqlite_database_adapter::SqliteDatabaseAdapter;
+    use crate::domain::logging::MyLogger;
+
+    fn generate_key() -> Ed25519KeyPair {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap()
+    }
+
+    fn adapter(trusted_keys: &[&Ed25519KeyPair]) -> ArchiveAdapter {
+        let logger: Arc<dyn LoggerPort> = Arc::new(MyLogger);
+        let database = SqliteDatabaseAdapter::new(Path::new(":memory:"), logger.clone()).unwrap();
+        ArchiveAdapter::new(logger, Repository::new(Arc::new(database))).with_trusted_keys(
+            trusted_keys
+                .iter()
+                .map(|key| key.public_key().as_ref().to_vec())
+                .collect(),
+        )
+    }
+
+    /// Builds the files of an archive signed with a key, as `export` writes them.
+    fn signed_archive(key: &Ed25519KeyPair) -> (BTreeMap<String, Vec<u8>>, ArchiveManifest) {
+        let manifest = ArchiveManifest {
+            format_version: ARCHIVE_FORMAT_VERSION,
+            run: "profile:2024-05-02T08:00:00.000000Z:burn-in-24h".to_string(),
+            hostname: "rack14-node1".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            exported_at: "2024-05-03T08:00:00Z".to_string(),
+            from: "2024-05-02T08:00:00Z".to_string(),
+            to: "2024-05-03T08:00:00Z".to_string(),
+            samples: BTreeMap::new(),
+            inventory: false,
+            logs: 0,
+            signed_by: Some(key_fingerprint(key.public_key().as_ref())),
+        };
+        let mut files = BTreeMap::from([
+            (
+                MANIFEST_FILE.to_string(),
+                serde_json::to_vec(&manifest).unwrap(),
+            ),
+            (RESULT_FILE.to_string(), b"{\"bogo_ops\":42}".to_vec()),
+        ]);
+        let signature = ArchiveSignature {
+            algorithm: SIGNATURE_ALGORITHM.to_string(),
+            public_key: STANDARD.encode(key.public_key()),
+            signature: STANDARD.encode(key.sign(&signed_content(&files))),
+        };
+        files.insert(
+            SIGNATURE_FILE.to_string(),
+            serde_json::to_vec(&signature).unwrap(),
+        );
+        (files, manifest)
+    }
+
+    #[test]
+    fn accepts_an_intact_archive_signed_with_a_trusted_key() {
+        let key = generate_key();
+        let (files, manifest) = signed_archive(&key);
+        assert_eq!(
+            adapter(&[&key]).check_signature(&files, &manifest),
+            Ok(SignatureCheck::Trusted {
+                fingerprint: key_fingerprint(key.public_key().as_ref())
+            })
+        );
+    }
+
+    #[test]
+    fn accepts_an_intact_archive_as_untrusted_without_trusted_keys() {
+        let key = generate_key();
+        let (files, manifest) = signed_archive(&key);
+        assert_eq!(
+            adapter(&[]).check_signature(&files, &manifest),
+            Ok(SignatureCheck::Untrusted {
+                fingerprint: key_fingerprint(key.public_key().as_ref())
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_a_tampered_archive() {
+        let key = generate_key();
+        let (mut files, manifest) = signed_archive(&key);
+        files.insert(RESULT_FILE.to_string(), b"{\"bogo_ops\":4200}".to_vec());
+        let error = adapter(&[&key])
+            .check_signature(&files, &manifest)
+            .unwrap_err();
+        assert!(error.contains("changed after it was signed"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_an_archive_signed_with_a_key_that_is_not_trusted() {
+        let key = generate_key();
+        let (files, manifest) = signed_archive(&key);
+        let error = adapter(&[&generate_key()])
+            .check_signature(&files, &manifest)
+            .unwrap_err();
+        assert!(error.contains("which is not trusted"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_an_archive_whose_signature_was_removed() {
+        let key = generate_key();
+        let (mut files, manifest) = signed_archive(&key);
+        files.remove(SIGNATURE_FILE);
+        let error = adapter(&[]).check_signature(&files, &manifest).unwrap_err();
+        assert!(error.contains("signature was removed"), "{}", error);
+    }
+}
@@ -0,0 +1,82 @@
+//! Background Sampler
+//!
+//! This module provides a small helper that periodically invokes a sampling
+//! closure on a dedicated thread while a stress test or benchmark runs, and
+//! hands back the collected samples once the run has finished.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// A sample paired with the time elapsed since the sampler was started.
+pub type TimedSample<T> = (Duration, T);
+
+/// Periodically collects samples on a background thread until stopped.
+pub struct BackgroundSampler<T: Send + 'static> {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<Vec<TimedSample<T>>>>,
+}
+
+impl<T: Send + 'static> BackgroundSampler<T> {
+    /// Starts sampling on a background thread.
+    ///
+    /// The closure is invoked immediately and then once per `interval`. Samples
+    /// for which the closure returns `None` are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - The time between two samples.
+    /// * `sample` - The closure producing one sample.
+    ///
+    /// # Returns
+    ///
+    /// * `BackgroundSampler<T>` - A handle used to stop the sampler and collect the samples.
+    pub fn start<F>(interval: Duration, mut sample: F) -> Self
+    where
+        F: FnMut() -> Option<T> + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_flag = stop.clone();
+
+        let handle = thread::spawn(move || {
+            let started = Instant::now();
+            let mut samples = Vec::new();
+
+            while !stop_flag.load(Ordering::Relaxed) {
+                if let Some(value) = sample() {
+                    samples.push((started.elapsed(), value));
+                }
+
+                // Sleep in short slices so a stop request is honoured promptly.
+                let next = Instant::now() + interval;
+                while !stop_flag.load(Ordering::Relaxed) && Instant::now() < next {
+                    thread::sleep(Duration::from_millis(50).min(interval));
+                }
+            }
+
+            samples
+        });
+
+        BackgroundSampler {
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    /// Stops the sampler and returns every collected sample in order.
+    pub fn stop(mut self) -> Vec<TimedSample<T>> {
+        self.stop.store(true, Ordering::Relaxed);
+        self.handle
+            .take()
+            .and_then(|handle| handle.join().ok())
+            .unwrap_or_default()
+    }
+}
+
+impl<T: Send + 'static> Drop for BackgroundSampler<T> {
+    fn drop(&mut self) {
+        // Make sure an abandoned sampler does not keep its thread alive forever.
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,119 @@
+//! Battery Adapter
+//!
+//! This module provides an adapter that reads the AC adapter and batteries of a
+//! laptop from the Linux power supply class in sysfs. Batteries report their
+//! capacity either in energy (µWh) or in charge (µAh); charges are converted to
+//! energy with the design voltage of the battery, so every battery is described
+//! in watt-hours. Batteries of peripherals, such as wireless mice, are left out.
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use common::ports::log_port::LoggerPort;
+
+use crate::adapters::sysfs::read_sysfs;
+use crate::domain::battery::{Battery, PowerSupplyState};
+use crate::ports::battery_port::BatteryPort;
+
+/// Root of the power supply class exposing AC adapters and batteries.
+const SYSFS_POWER_SUPPLY_ROOT: &str = "/sys/class/power_supply";
+
+/// Represents the sysfs battery adapter.
+pub struct BatteryAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl BatteryAdapter {
+    /// Creates a new instance of `BatteryAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `BatteryAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        BatteryAdapter { logger }
+    }
+
+    /// Reads a numeric sysfs attribute.
+    fn read_number(path: &Path) -> Option<f64> {
+        read_sysfs(path).and_then(|value| value.parse().ok())
+    }
+
+    /// Reads a capacity in watt-hours from its energy attribute, in µWh, or from
+    /// its charge attribute, in µAh, at the design voltage of the battery.
+    fn read_watt_hours(supply: &Path, attribute: &str) -> Option<f64> {
+        if let Some(energy) = Self::read_number(&supply.join(format!("energy_{}", attribute))) {
+            return Some(energy / 1e6);
+        }
+        let charge = Self::read_number(&supply.join(format!("charge_{}", attribute)))?;
+        let voltage = Self::read_number(&supply.join("voltage_min_design"))
+            .or_else(|| Self::read_number(&supply.join("voltage_now")))?;
+        Some(charge * voltage / 1e12)
+    }
+
+    /// Reads a battery of the power supply class.
+    fn read_battery(name: String, supply: &Path) -> Battery {
+        let text = |attribute: &str| read_sysfs(supply.join(attribute)).unwrap_or_default();
+        // Batteries that report a current rather than a power draw, e.g. most Chromebooks.
+        let rate_watts = Self::read_number(&supply.join("power_now"))
+            .map(|microwatts| microwatts / 1e6)
+            .or_else(|| {
+                let current = Self::read_number(&supply.join("current_now"))?;
+                let voltage = Self::read_number(&supply.join("voltage_now"))?;
+                Some(current * voltage / 1e12)
+            })
+            .map(f64::abs);
+        Battery {
+            manufacturer: text("manufacturer"),
+            model: text("model_name"),
+            technology: text("technology"),
+            status: text("status"),
+            capacity_percent: Self::read_number(&supply.join("capacity"))
+                .map(|percent| percent.clamp(0.0, 100.0) as u8),
+            full_wh: Self::read_watt_hours(supply, "full"),
+            design_wh: Self::read_watt_hours(supply, "full_design"),
+            // Batteries that do not count their cycles report 0.
+            cycle_count: Self::read_number(&supply.join("cycle_count"))
+                .filter(|cycles| *cycles > 0.0)
+                .map(|cycles| cycles as u32),
+            rate_watts,
+            name,
+        }
+    }
+}
+
+// Implement the `BatteryPort` trait for `BatteryAdapter`.
+impl BatteryPort for BatteryAdapter {
+    fn read_power_supplies(&self) -> Result<PowerSupplyState, String> {
+        let entries = fs::read_dir(SYSFS_POWER_SUPPLY_ROOT)
+            .map_err(|e| format!("Failed to read {}: {}", SYSFS_POWER_SUPPLY_ROOT, e))?;
+
+        let mut state = PowerSupplyState::default();
+        for entry in entries.flatten() {
+            let supply = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            // Peripherals report the scope "Device"; the machine's own supplies "System" or nothing.
+            if read_sysfs(supply.join("scope")).as_deref() == Some("Device") {
+                continue;
+            }
+            match read_sysfs(supply.join("type")).as_deref() {
+                Some("Battery") => state.batteries.push(Self::read_battery(name, &supply)),
+                Some("Mains") | Some("USB") => {
+                    let online = read_sysfs(supply.join("online")).as_deref() == Some("1");
+                    state.on_mains = Some(state.on_mains.unwrap_or(false) || online);
+                }
+                _ => {}
+            }
+        }
+
+        state.batteries.sort_by(|a, b| a.name.cmp(&b.name));
+        self.logger.log_trace(&format!(
+            "Found {} batteries, AC adapter {:?}",
+            state.batteries.len(),
+            state.on_mains
+        ));
+        Ok(state)
+    }
+}
@@ -0,0 +1,89 @@
+//! Benchmark Runner
+//!
+//! This module repeats a benchmark as its repetition plan asks: the warm-up
+//! runs are made first and discarded, then the metrics of the measured runs
+//! are collected into a record with their statistics. A failed or cancelled
+//! run ends the benchmark, since the runs after it would not be comparable
+//! with the ones before.
+
+use std::sync::Arc;
+
+use chrono::Local;
+use common::ports::log_port::LoggerPort;
+
+use crate::domain::statistics::{summarize_runs, BenchmarkRecord, Measurement, RepetitionPlan};
+
+/// Runs benchmarks repeatedly.
+pub struct BenchmarkRunner {
+    logger: Arc<dyn LoggerPort>,
+    plan: RepetitionPlan,
+}
+
+impl BenchmarkRunner {
+    /// Creates a new instance of `BenchmarkRunner`.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger` - Logger implementation for logging messages.
+    /// * `plan` - How many runs to discard and how many to measure.
+    ///
+    /// # Returns
+    ///
+    /// * `BenchmarkRunner` - The runner.
+    pub fn new(logger: Arc<dyn LoggerPort>, plan: RepetitionPlan) -> Self {
+        BenchmarkRunner { logger, plan }
+    }
+
+    /// Runs a benchmark through its warm-up and measured runs.
+    ///
+    /// # Arguments
+    ///
+    /// * `benchmark` - The benchmark, as named on the command line, e.g. "cache".
+    /// * `run` - Runs the benchmark once; it returns an error once cancelled.
+    /// * `measurements` - Lists the metrics of a run.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(T, BenchmarkRecord), String>` - The result of the last measured
+    ///   run and the record of every metric over the measured runs, or the error
+    ///   of the first run that failed.
+    pub fn run<T>(
+        &self,
+        benchmark: &str,
+        mut run: impl FnMut() -> Result<T, String>,
+        measurements: impl Fn(&T) -> Vec<Measurement>,
+    ) -> Result<(T, BenchmarkRecord), String> {
+        let started_at = Local::now().to_rfc3339();
+        for number in 1..=self.plan.warmup {
+            self.logger.log_debug(&format!(
+                "Warm-up run {} of {} of the {} benchmark",
+                number, self.plan.warmup, benchmark
+            ));
+            run()?;
+        }
+
+        let mut runs = Vec::new();
+        let mut result = run()?;
+        for number in 2..=self.plan.repetitions {
+            self.logger.log_info(&format!(
+                "Repetition {} of {} of the {} benchmark",
+                number, self.plan.repetitions, benchmark
+            ));
+            runs.push(measurements(&result));
+            result = run()?;
+        }
+        let last = measurements(&result);
+        if !runs.is_empty() {
+            runs.push(last.clone());
+        }
+        let record = BenchmarkRecord {
+            benchmark: benchmark.to_string(),
+            started_at,
+            warmup: self.plan.warmup,
+            repetitions: self.plan.repetitions,
+            measurements: last,
+            statistics: summarize_runs(&runs),
+        };
+        Ok((result, record))
+    }
+}
@@ -0,0 +1,340 @@
+//! Block Device Adapter
+//!
+//! This module provides an adapter that benchmarks a drive through its raw
+//! block device with direct I/O, bypassing the page cache: large sequential
+//! and small random accesses from several threads at once, for a fixed time
+//! each. Writes stay within the first `WRITE_SPAN_BYTES` of the device. What
+//! lives on the device is read from sysfs, `/proc/self/mounts` and
+//! `/proc/swaps`, and unless forced, a device written to is opened with
+//! `O_EXCL`, so the kernel itself refuses it while mounted or claimed.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{Seek, SeekFrom};
+use std::os::unix::fs::{FileExt, FileTypeExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use common::ports::log_port::LoggerPort;
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::block_device::{DeviceAccess, DeviceBenchmarkResult, DeviceRate, DeviceUsage};
+use crate::ports::block_device_port::BlockDevicePort;
+
+/// The root of the block devices in sysfs.
+const SYSFS_BLOCK_ROOT: &str = "/sys/class/block";
+
+/// How long each access pattern is measured for.
+const PHASE_DURATION: Duration = Duration::from_secs(10);
+
+/// The size of each sequential access.
+const SEQUENTIAL_BLOCK_BYTES: usize = 1 << 20;
+
+/// The size of each random access.
+const RANDOM_BLOCK_BYTES: usize = 4096;
+
+/// The threads issuing accesses at once.
+const WORKERS: u64 = 4;
+
+/// The alignment O_DIRECT requires of buffers and offsets.
+const DIRECT_IO_ALIGNMENT: usize = 4096;
+
+/// The part of the device, from its start, the write phases are confined to.
+pub const WRITE_SPAN_BYTES: u64 = 8 * 1024 * 1024 * 1024;
+
+/// One access pattern of the benchmark.
+struct Phase {
+    operation: &'static str,
+    block_bytes: usize,
+    sequential: bool,
+    write: bool,
+}
+
+/// The access patterns run on every device.
+const READ_PHASES: [Phase; 2] = [
+    Phase {
+        operation: "sequential read",
+        block_bytes: SEQUENTIAL_BLOCK_BYTES,
+        sequential: true,
+        write: false,
+    },
+    Phase {
+        operation: "random read",
+        block_bytes: RANDOM_BLOCK_BYTES,
+        sequential: false,
+        write: false,
+    },
+];
+
+/// The access patterns run unless the benchmark is read-only.
+const WRITE_PHASES: [Phase; 2] = [
+    Phase {
+        operation: "sequential write",
+        block_bytes: SEQUENTIAL_BLOCK_BYTES,
+        sequential: true,
+        write: true,
+    },
+    Phase {
+        operation: "random write",
+        block_bytes: RANDOM_BLOCK_BYTES,
+        sequential: false,
+        write: true,
+    },
+];
+
+/// Represents the raw block device benchmark adapter.
+pub struct BlockDeviceAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+/// Resolves a device path, such as a /dev/disk/by-id link, to its kernel name, e.g. "nvme1n1".
+fn kernel_name(device: &str) -> Result<(PathBuf, String), String> {
+    let path = fs::canonicalize(device).map_err(|e| format!("Failed to find {}: {}", device, e))?;
+    let metadata =
+        fs::metadata(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    if !metadata.file_type().is_block_device() {
+        return Err(format!("{} is not a block device", path.display()));
+    }
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| format!("{} has no device name", path.display()))?;
+    Ok((path, name))
+}
+
+/// Lists the entries of a sysfs directory, or none if it does not exist.
+fn sysfs_entries(directory: &Path) -> Vec<String> {
+    let mut entries: Vec<String> = fs::read_dir(directory)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default();
+    entries.sort();
+    entries
+}
+
+/// Resolves the source of a mount or swap area to its device, if it is one.
+fn source_device(source: &str) -> Option<String> {
+    if !source.starts_with("/dev/") {
+        return None;
+    }
+    fs::canonicalize(source).ok().and_then(|path| {
+        path.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+    })
+}
+
+impl BlockDeviceAdapter {
+    /// Creates a new instance of `BlockDeviceAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `BlockDeviceAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        BlockDeviceAdapter { logger }
+    }
+
+    /// Times one access pattern from several threads until the phase ends.
+    fn time_accesses(
+        &self,
+        file: &Arc<File>,
+        phase: &Phase,
+        span_bytes: u64,
+        cancel: &CancellationToken,
+    ) -> Result<DeviceRate, String> {
+        let (block_bytes, sequential, write) = (phase.block_bytes, phase.sequential, phase.write);
+        let blocks = span_bytes / block_bytes as u64;
+        if blocks < WORKERS {
+            return Err(format!("The device is too small to {}", phase.operation));
+        }
+        self.logger.log_debug(&format!(
+            "Timing {} over {} blocks",
+            phase.operation, blocks
+        ));
+
+        let started = Instant::now();
+        let workers: Vec<_> = (0..WORKERS)
+            .map(|worker| {
+                let (file, cancel) = (file.clone(), cancel.clone());
+                thread::spawn(move || -> Result<u64, String> {
+                    let mut buffer = vec![0u8; block_bytes + DIRECT_IO_ALIGNMENT];
+                    let offset = buffer.as_ptr().align_offset(DIRECT_IO_ALIGNMENT);
+                    let aligned = &mut buffer[offset..offset + block_bytes];
+                    if write {
+                        aligned.fill(0xA5);
+                    }
+                    // Sequential workers take every WORKERS-th block, so the accesses stay
+                    // sequential overall; random ones pick blocks with xorshift64.
+                    let mut block = worker;
+                    let mut state = 0x9E37_79B9_7F4A_7C15u64 ^ (worker + 1);
+                    let mut count = 0;
+                    while started.elapsed() < PHASE_DURATION && !cancel.is_cancelled() {
+                        let index = if sequential {
+                            let index = block % blocks;
+                            block += WORKERS;
+                            index
+                        } else {
+                            state ^= state << 13;
+                            state ^= state >> 7;
+                            state ^= state << 17;
+                            state % blocks
+                        };
+                        let position = index * block_bytes as u64;
+                        let outcome = if write {
+                            file.write_all_at(aligned, position)
+                        } else {
+                            file.read_exact_at(aligned, position)
+                        };
+                        outcome.map_err(|e| e.to_string())?;
+                        count += 1;
+                    }
+                    Ok(count)
+                })
+            })
+            .collect();
+        let mut count = 0;
+        for worker in workers {
+            count += worker
+                .join()
+                .map_err(|_| format!("A worker panicked during {}", phase.operation))?
+                .map_err(|e| format!("Failed to {}: {}", phase.operation, e))?;
+        }
+        if write {
+            file.sync_all()
+                .map_err(|e| format!("Failed to flush the writes: {}", e))?;
+        }
+        let elapsed_secs = started.elapsed().as_secs_f64();
+        if cancel.is_cancelled() {
+            return Err("Block device benchmark cancelled".to_string());
+        }
+
+        Ok(DeviceRate {
+            operation: format!(
+                "{} ({})",
+                phase.operation,
+                if block_bytes >= 1 << 20 {
+                    format!("{} MiB", block_bytes >> 20)
+                } else {
+                    format!("{} KiB", block_bytes >> 10)
+                }
+            ),
+            count,
+            block_bytes,
+            elapsed_secs,
+        })
+    }
+}
+
+// Implement the `BlockDevicePort` trait for `BlockDeviceAdapter`.
+impl BlockDevicePort for BlockDeviceAdapter {
+    fn read_device_usage(&self, device: &str) -> Result<DeviceUsage, String> {
+        let (path, name) = kernel_name(device)?;
+        let sysfs = Path::new(SYSFS_BLOCK_ROOT).join(&name);
+
+        // Partitions are subdirectories of the disk holding a `partition` file.
+        let partitions: Vec<String> = sysfs_entries(&sysfs)
+            .into_iter()
+            .filter(|entry| {
+                entry.starts_with(&name) && sysfs.join(entry).join("partition").exists()
+            })
+            .collect();
+        let mut holders = sysfs_entries(&sysfs.join("holders"));
+        for partition in &partitions {
+            holders.extend(sysfs_entries(&sysfs.join(partition).join("holders")));
+        }
+
+        // Whatever is mounted from the device, a partition or a device stacked on them.
+        let claimed: Vec<&String> = std::iter::once(&name)
+            .chain(&partitions)
+            .chain(&holders)
+            .collect();
+        let mounts: Vec<String> = fs::read_to_string("/proc/self/mounts")
+            .map_err(|e| format!("Failed to read /proc/self/mounts: {}", e))?
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let (source, target) = (fields.next()?, fields.next()?);
+                let device = source_device(source)?;
+                claimed
+                    .contains(&&device)
+                    .then(|| format!("{} on {}", source, target))
+            })
+            .collect();
+        let swap: Vec<String> = fs::read_to_string("/proc/swaps")
+            .unwrap_or_default()
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let source = line.split_whitespace().next()?;
+                let device = source_device(source)?;
+                claimed.contains(&&device).then(|| source.to_string())
+            })
+            .collect();
+
+        Ok(DeviceUsage {
+            device: path.display().to_string(),
+            partitions,
+            mounts,
+            holders,
+            swap,
+        })
+    }
+
+    fn run_device_benchmark(
+        &self,
+        device: &str,
+        access: DeviceAccess,
+        cancel: &CancellationToken,
+    ) -> Result<DeviceBenchmarkResult, String> {
+        let write = access != DeviceAccess::ReadOnly;
+        let mut flags = libc::O_DIRECT;
+        // Claiming the device makes the kernel refuse it while mounted or stacked on.
+        if access == DeviceAccess::Exclusive {
+            flags |= libc::O_EXCL;
+        }
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(write)
+            .custom_flags(flags)
+            .open(device)
+            .map_err(|e| match e.raw_os_error() {
+                Some(libc::EBUSY) => format!(
+                    "{} is in use; the kernel refused to hand it over exclusively",
+                    device
+                ),
+                _ => format!("Failed to open {}: {}", device, e),
+            })?;
+        let size = file
+            .seek(SeekFrom::End(0))
+            .map_err(|e| format!("Failed to read the size of {}: {}", device, e))?;
+        let file = Arc::new(file);
+        self.logger.log_info(&format!(
+            "Benchmarking {} ({} GB){}",
+            device,
+            size / 1_000_000_000,
+            if write { "" } else { " read-only" }
+        ));
+
+        let mut rates = Vec::new();
+        for phase in &READ_PHASES {
+            rates.push(self.time_accesses(&file, phase, size, cancel)?);
+        }
+        if write {
+            for phase in &WRITE_PHASES {
+                rates.push(self.time_accesses(&file, phase, size.min(WRITE_SPAN_BYTES), cancel)?);
+            }
+        }
+
+        Ok(DeviceBenchmarkResult {
+            device: device.to_string(),
+            read_only: !write,
+            rates,
+        })
+    }
+}
@@ -0,0 +1,106 @@
+//! Boot Timing Adapter
+//!
+//! This module times the last boot with `systemd-analyze time`, which reads
+//! the timestamps systemd and an EFI boot loader recorded, and lists the
+//! slowest units with `systemd-analyze blame`. When the boot loader did not
+//! report the firmware time and the machine has a BMC, the POST is timed from
+//! the "System Boot Initiated" event `ipmitool sel elist` lists and the time
+//! the kernel started, from `btime` in `/proc/stat`. Both are wall-clock
+//! times, so a BMC clock that drifted from the host skews the POST; events
+//! more than an hour before the kernel started are taken as an earlier boot.
+
+use std::fs;
+use std::process::Command;
+use std::sync::Arc;
+
+use chrono::{Local, NaiveDateTime, TimeZone};
+use common::ports::log_port::LoggerPort;
+
+use crate::adapters::hardware_error_adapter::HardwareErrorAdapter;
+use crate::domain::boot::{
+    parse_blame, parse_systemd_analyze, sel_boot_events, sel_post_time, BootTiming, SLOWEST_UNITS,
+};
+use crate::ports::boot_timing_port::BootTimingPort;
+use crate::ports::hardware_error_port::HardwareErrorPort;
+
+/// Represents the systemd-analyze and ipmitool boot timing adapter.
+pub struct BootTimingAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl BootTimingAdapter {
+    /// Creates a new instance of `BootTimingAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `BootTimingAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        BootTimingAdapter { logger }
+    }
+
+    /// Runs a command and returns its standard output, or an error naming the command.
+    fn run(program: &str, args: &[&str]) -> Result<String, String> {
+        let output = Command::new(program)
+            .args(args)
+            .output()
+            .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "{} {} failed: {}",
+                program,
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Reads when the kernel started, in local time, from `btime` in `/proc/stat`.
+    fn kernel_start() -> Result<NaiveDateTime, String> {
+        let stat = fs::read_to_string("/proc/stat")
+            .map_err(|e| format!("Failed to read /proc/stat: {}", e))?;
+        let btime: i64 = stat
+            .lines()
+            .find_map(|line| line.strip_prefix("btime "))
+            .and_then(|value| value.trim().parse().ok())
+            .ok_or("No btime in /proc/stat")?;
+        Local
+            .timestamp_opt(btime, 0)
+            .single()
+            .map(|start| start.naive_local())
+            .ok_or_else(|| format!("Invalid boot time {}", btime))
+    }
+
+    /// Times the POST from the event log of the BMC, if the machine has one.
+    fn time_post(&self, timing: &mut BootTiming) -> Result<(), String> {
+        let entries = HardwareErrorAdapter::new(self.logger.clone()).read_sel()?;
+        let events = sel_boot_events(&entries);
+        let kernel_start = Self::kernel_start()?;
+        let (gap, kind) = sel_post_time(&events, kernel_start)
+            .ok_or("the BMC event log holds no boot event for the running kernel")?;
+        timing.kind = Some(kind);
+        // The event precedes the boot loader too, whose time is left out if it is known.
+        timing.sel_post = Some(gap.saturating_sub(timing.loader.unwrap_or_default()));
+        Ok(())
+    }
+}
+
+// Implement the `BootTimingPort` trait for `BootTimingAdapter`.
+impl BootTimingPort for BootTimingAdapter {
+    fn measure_boot(&self) -> Result<BootTiming, String> {
+        let mut timing = parse_systemd_analyze(&Self::run("systemd-analyze", &["time"])?)?;
+        match Self::run("systemd-analyze", &["blame"]) {
+            Ok(blame) => timing.slowest_units = parse_blame(&blame, SLOWEST_UNITS),
+            Err(e) => self.logger.log_debug(&e),
+        }
+        // The event log also tells a cold boot from a warm one, so it is read even
+        // when the boot loader reported the firmware time.
+        if let Err(e) = self.time_post(&mut timing) {
+            self.logger
+                .log_debug(&format!("Not timing the POST from the BMC: {}", e));
+        }
+        Ok(timing)
+    }
+}
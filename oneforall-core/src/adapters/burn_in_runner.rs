@@ -0,0 +1,408 @@
+//! Burn-in Runner
+//!
+//! This module runs the full-system burn-in started by `oneforall stress --all`.
+//! A single stress-ng run loads the CPU, memory, disk and network stack at the
+//! same time while the run monitor records telemetry, and the hardware error
+//! sources are read before and after the run to catch any new fault. With
+//! `--verify`, memory and disk patterns are re-read throughout the run as well.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use common::domain::job::{JobSpec, JobStatus, Resource};
+use common::ports::job_port::JobPort;
+use common::ports::log_port::LoggerPort;
+use common::ports::telemetry_port::TelemetryPort;
+
+use crate::adapters::cgroup_adapter::CgroupAdapter;
+use crate::adapters::hardware_error_adapter::HardwareErrorAdapter;
+use crate::adapters::integrity_monitor::IntegrityMonitor;
+use crate::adapters::nvme_adapter::NvmeAdapter;
+use crate::adapters::progress_reporter::ProgressReporter;
+use crate::adapters::run_monitor::{RunMonitor, DEFAULT_SAMPLE_INTERVAL};
+use crate::adapters::stress_ng_adapter::{run_output_file, take_run_output, StressNgAdapter};
+use crate::domain::burn_in::{BurnInReport, MonitorResult, MonitorStatus, WorkloadOutcome};
+use crate::domain::endurance::format_volume;
+use crate::domain::hardware_errors::{ecc_faults, smart_faults, EccCounts, SmartHealth};
+use crate::domain::integrity::IntegrityPlan;
+use crate::domain::nvme::{nvme_faults, NvmeHealth};
+use crate::domain::plan::{DiskWrites, ExecutionPlan, PlannedStep};
+use crate::domain::stress_ng::{
+    parse_bogo_ops_rate, parse_stressor_bogo_ops, StressRunOutcome, HDD_BYTES_PER_WORKER,
+};
+use crate::ports::container_port::ContainerPort;
+use crate::ports::hardware_error_port::HardwareErrorPort;
+use crate::ports::nvme_port::NvmePort;
+
+/// The stress-ng stressors run during a burn-in alongside the CPU and memory
+/// stressors, which are sized to the machine. The socket stressor exercises
+/// the network stack over loopback.
+const BURN_IN_STRESSORS: &[(&str, &str)] = &[("--hdd", "2"), ("--sock", "2")];
+
+/// The number of stress-ng memory workers started during a burn-in.
+const BURN_IN_VM_WORKERS: u64 = 2;
+
+/// The share of memory the memory workers fill between them.
+const BURN_IN_MEMORY_SHARE: f64 = 0.3;
+
+/// The hardware error sources read at one point in time.
+struct ErrorSnapshot {
+    ecc: Result<EccCounts, String>,
+    machine_checks: Result<u64, String>,
+    smart: Result<Vec<SmartHealth>, String>,
+    nvme: Result<Vec<NvmeHealth>, String>,
+}
+
+/// Compares two readings of one error source.
+fn compare_readings<T>(
+    source: &str,
+    before: &Result<T, String>,
+    after: &Result<T, String>,
+    faults: impl Fn(&T, &T) -> Vec<String>,
+    summary: impl Fn(&T) -> String,
+) -> MonitorResult {
+    let status = match (before, after) {
+        (Err(reason), _) => MonitorStatus::Unavailable(reason.clone()),
+        (Ok(_), Err(e)) => MonitorStatus::Faults(vec![format!("became unreadable: {}", e)]),
+        (Ok(before), Ok(after)) => {
+            let faults = faults(before, after);
+            if faults.is_empty() {
+                MonitorStatus::Clean(summary(after))
+            } else {
+                MonitorStatus::Faults(faults)
+            }
+        }
+    };
+    MonitorResult {
+        source: source.to_string(),
+        status,
+    }
+}
+
+/// Returns the number of CPU workers and the size of each memory worker.
+///
+/// On a host, stress-ng starts a CPU worker per online CPU and sizes the
+/// memory workers as a share of the host memory. In a cgroup with fewer
+/// CPUs or less memory, both are sized to its limits instead, so the CPU
+/// quota is not oversubscribed and the memory workers are not OOM-killed.
+fn stressor_sizes(logger: &dyn LoggerPort, container: &dyn ContainerPort) -> (String, String) {
+    let host = (
+        "0".to_string(),
+        format!("{:.0}%", BURN_IN_MEMORY_SHARE * 100.0),
+    );
+    let limits = match container.read_resource_limits() {
+        Ok(limits) => limits,
+        Err(e) => {
+            logger.log_warn(&format!("Sizing the burn-in for the host: {}", e));
+            return host;
+        }
+    };
+    let cpu_workers = if limits.is_cpu_limited() {
+        limits.effective_cpus().to_string()
+    } else {
+        host.0
+    };
+    // stress-ng takes an absolute size per memory worker.
+    let vm_bytes = if limits.is_memory_limited() {
+        let share = limits.effective_memory_bytes() as f64 * BURN_IN_MEMORY_SHARE;
+        (share as u64 / BURN_IN_VM_WORKERS).to_string()
+    } else {
+        host.1
+    };
+    if limits.is_cpu_limited() || limits.is_memory_limited() {
+        logger.log_info(&format!(
+            "Sizing the burn-in to the cgroup limits of {} CPUs and {} of memory.",
+            limits.effective_cpus(),
+            format_volume(limits.effective_memory_bytes())
+        ));
+    }
+    (cpu_workers, vm_bytes)
+}
+
+/// Returns the stress-ng arguments of a burn-in.
+fn stress_args(cpu_workers: &str, vm_bytes: &str, duration: Duration) -> Vec<String> {
+    let mut args: Vec<String> = vec![
+        "--cpu".to_string(),
+        cpu_workers.to_string(),
+        "--vm".to_string(),
+        BURN_IN_VM_WORKERS.to_string(),
+        "--vm-bytes".to_string(),
+        vm_bytes.to_string(),
+    ];
+    args.extend(
+        BURN_IN_STRESSORS
+            .iter()
+            .flat_map(|(option, value)| [option.to_string(), value.to_string()]),
+    );
+    args.extend([
+        "--timeout".to_string(),
+        format!("{}s", duration.as_secs()),
+        "--metrics-brief".to_string(),
+    ]);
+    args
+}
+
+/// Runs full-system burn-ins.
+pub struct BurnInRunner {
+    logger: Arc<dyn LoggerPort>,
+    telemetry: Arc<dyn TelemetryPort>,
+    jobs: Arc<dyn JobPort>,
+    errors: Box<dyn HardwareErrorPort>,
+    nvme: Box<dyn NvmePort>,
+    container: Box<dyn ContainerPort>,
+}
+
+impl BurnInRunner {
+    /// Creates a new instance of `BurnInRunner`.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger` - Logger implementation for logging messages.
+    /// * `telemetry` - Receives live readings and progress during the burn-in.
+    /// * `jobs` - The job queue the burn-in is submitted to.
+    ///
+    /// # Returns
+    ///
+    /// * `BurnInRunner` - A runner watching the hardware error sources of this machine.
+    pub fn new(
+        logger: Arc<dyn LoggerPort>,
+        telemetry: Arc<dyn TelemetryPort>,
+        jobs: Arc<dyn JobPort>,
+    ) -> Self {
+        BurnInRunner {
+            errors: Box::new(HardwareErrorAdapter::new(logger.clone())),
+            nvme: Box::new(NvmeAdapter::new(logger.clone())),
+            container: Box::new(CgroupAdapter::new(logger.clone())),
+            logger,
+            telemetry,
+            jobs,
+        }
+    }
+
+    /// Plans a burn-in for `--dry-run`, sized as it would be run, without running or queueing it.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger` - Logger implementation for logging messages.
+    /// * `duration` - How long the burn-in would run.
+    /// * `verify` - The data-integrity patterns that would be re-read meanwhile, if any.
+    ///
+    /// # Returns
+    ///
+    /// * `ExecutionPlan` - The stress-ng run, and the verification if requested.
+    pub fn plan(
+        logger: Arc<dyn LoggerPort>,
+        duration: Duration,
+        verify: Option<&IntegrityPlan>,
+    ) -> ExecutionPlan {
+        let container = CgroupAdapter::new(logger.clone());
+        let (cpu_workers, vm_bytes) = stressor_sizes(logger.as_ref(), &container);
+        let hdd_workers: u64 = BURN_IN_STRESSORS
+            .iter()
+            .filter(|(option, _)| *option == "--hdd")
+            .filter_map(|(_, workers)| workers.parse::<u64>().ok())
+            .sum();
+        let mut plan = ExecutionPlan::new("stress --all");
+        plan.steps.push(PlannedStep {
+            duration: Some(duration),
+            targets: vec![
+                if cpu_workers == "0" {
+                    "one CPU worker per online CPU".to_string()
+                } else {
+                    format!("{} CPU workers", cpu_workers)
+                },
+                format!("{} memory workers of {} each", BURN_IN_VM_WORKERS, vm_bytes),
+                "the working directory".to_string(),
+                "loopback sockets".to_string(),
+            ],
+            disk_writes: DiskWrites::Sustained(HDD_BYTES_PER_WORKER * hdd_workers),
+            ..PlannedStep::new(
+                "burn-in",
+                format!(
+                    "stress-ng {}",
+                    stress_args(&cpu_workers, &vm_bytes, duration).join(" ")
+                ),
+                &[
+                    Resource::Cpu,
+                    Resource::Memory,
+                    Resource::Disk,
+                    Resource::Network,
+                ],
+            )
+        });
+        plan.steps
+            .extend(verify.map(|verify| verify.planned_step(duration)));
+        plan
+    }
+
+    /// Reads every hardware error source.
+    fn snapshot(&self) -> ErrorSnapshot {
+        ErrorSnapshot {
+            ecc: self.errors.read_ecc_counts(),
+            machine_checks: self.errors.count_machine_checks(),
+            smart: self.errors.read_smart_health(),
+            nvme: self.nvme.read_all_nvme_health(),
+        }
+    }
+
+    /// Runs the burn-in for the given duration.
+    ///
+    /// The burn-in is queued as a job needing every resource, so it waits for
+    /// any other workload and can be cancelled like one.
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - How long to load the machine.
+    /// * `verify` - The memory and disk patterns to verify during the run, if any.
+    ///
+    /// # Returns
+    ///
+    /// * `BurnInReport` - The pass/fail report of the burn-in.
+    pub async fn run(&self, duration: Duration, verify: Option<IntegrityPlan>) -> BurnInReport {
+        let mut ticket = self.jobs.submit_job(JobSpec::new(
+            "stress:all",
+            &[
+                Resource::Cpu,
+                Resource::Memory,
+                Resource::Disk,
+                Resource::Network,
+            ],
+        ));
+        let job_id = ticket.job.id;
+        let mut report = BurnInReport {
+            started_at: Local::now().to_rfc3339(),
+            planned_secs: duration.as_secs(),
+            elapsed_secs: 0,
+            outcome: WorkloadOutcome::Cancelled,
+            bogo_ops: Vec::new(),
+            monitors: Vec::new(),
+            peak_celsius: None,
+        };
+        if ticket.job.status == JobStatus::Queued {
+            self.logger.log_info(&format!(
+                "Burn-in job {} queued behind a conflicting job.",
+                job_id
+            ));
+        }
+        if !ticket.admitted().await {
+            self.logger.log_warn("Burn-in cancelled while queued.");
+            return report;
+        }
+
+        let before = self.snapshot();
+        self.logger.log_info(&format!(
+            "Started burn-in job {}: loading CPU, memory, disk and network for {} seconds.",
+            job_id,
+            duration.as_secs()
+        ));
+
+        let (cpu_workers, vm_bytes) = stressor_sizes(self.logger.as_ref(), self.container.as_ref());
+        let args = stress_args(&cpu_workers, &vm_bytes, duration);
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+        let integrity_monitor =
+            verify.map(|plan| IntegrityMonitor::start(self.logger.clone(), plan));
+        let run_monitor = RunMonitor::start(
+            self.logger.clone(),
+            self.telemetry.clone(),
+            DEFAULT_SAMPLE_INTERVAL,
+        );
+        let progress =
+            ProgressReporter::start(self.logger.clone(), self.telemetry.clone(), "all", duration);
+        let started = Instant::now();
+        let output_file = run_output_file();
+        progress.follow_runs(std::slice::from_ref(&output_file));
+        let outcome = StressNgAdapter::execute_stress_ng_command_to(
+            self.logger.clone(),
+            &args,
+            false,
+            &output_file,
+            &ticket.cancel,
+        )
+        .await;
+        report.elapsed_secs = started.elapsed().as_secs();
+        let output = take_run_output(&output_file);
+        report.bogo_ops = parse_stressor_bogo_ops(&output);
+        progress.finish(parse_bogo_ops_rate(&output));
+        let run_telemetry = run_monitor.finish();
+        print!("{}", run_telemetry.report);
+        report.peak_celsius = run_telemetry.peak_celsius;
+        let integrity = integrity_monitor.map(IntegrityMonitor::finish);
+
+        let after = self.snapshot();
+        report.monitors = vec![
+            compare_readings("ECC", &before.ecc, &after.ecc, ecc_faults, |counts| {
+                format!("{} memory controller(s)", counts.controllers)
+            }),
+            compare_readings(
+                "MCE",
+                &before.machine_checks,
+                &after.machine_checks,
+                |before, after| {
+                    let new_events = after.saturating_sub(*before);
+                    if new_events > 0 {
+                        vec![format!(
+                            "{} new machine check event(s) in the kernel log",
+                            new_events
+                        )]
+                    } else {
+                        Vec::new()
+                    }
+                },
+                |_| "kernel log".to_string(),
+            ),
+            compare_readings(
+                "SMART",
+                &before.smart,
+                &after.smart,
+                |before, after| smart_faults(before, after),
+                |drives| format!("{} drive(s)", drives.len()),
+            ),
+            compare_readings(
+                "NVME",
+                &before.nvme,
+                &after.nvme,
+                |before, after| nvme_faults(before, after),
+                |controllers| format!("{} controller(s)", controllers.len()),
+            ),
+            run_telemetry.kernel_log.monitor_result(),
+            run_telemetry.sel.monitor_result(),
+        ];
+        if let Some(integrity) = integrity {
+            report.monitors.extend(integrity.monitors());
+        }
+
+        let (outcome, status, message) = match outcome {
+            Ok(StressRunOutcome::Completed) => {
+                (WorkloadOutcome::Completed, JobStatus::Completed, None)
+            }
+            Ok(StressRunOutcome::Cancelled) => {
+                (WorkloadOutcome::Cancelled, JobStatus::Cancelled, None)
+            }
+            Err(e) => (
+                WorkloadOutcome::Failed(e.clone()),
+                JobStatus::Failed,
+                Some(e),
+            ),
+        };
+        report.outcome = outcome;
+        let (status, message) = if status == JobStatus::Completed && !report.passed() {
+            let faulted: Vec<&str> = report
+                .monitors
+                .iter()
+                .filter(|m| matches!(m.status, MonitorStatus::Faults(_)))
+                .map(|m| m.source.as_str())
+                .collect();
+            (
+                JobStatus::Failed,
+                Some(format!("faults reported by {}", faulted.join(", "))),
+            )
+        } else {
+            (status, message)
+        };
+        self.jobs.finish_job(job_id, status, message);
+
+        report
+    }
+}
@@ -0,0 +1,161 @@
+//! Burst Sampler
+//!
+//! This module samples the utilization of every CPU every 100 ms or faster for
+//! `overwatch --burst-interval`, from the cumulative CPU times of
+//! `/proc/stat`, which are cheap enough to read ten times a second. Ticks are
+//! scheduled against fixed deadlines rather than slept between, so a tick the
+//! sampler could not take on time is measured as a stall instead of shifting
+//! every later one. The ticks of each second are stored as one frame.
+
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{Local, Utc};
+use common::domain::metric::{Aggregation, Metric};
+use common::ports::log_port::LoggerPort;
+use common::ports::telemetry_port::TelemetryPort;
+use tokio_util::sync::CancellationToken;
+
+use crate::adapters::sample_writer::SampleWriter;
+use crate::domain::microburst::{BurstFrame, BURSTS_COLLECTOR, BURST_FRAME};
+use crate::domain::system_stats::core_usage_between;
+use crate::ports::system_stats_port::SystemStatsPort;
+
+/// Samples CPU utilization at sub-second intervals.
+pub struct BurstSampler {
+    logger: Arc<dyn LoggerPort>,               // inject the logger port
+    stats: Arc<dyn SystemStatsPort>,           // where the CPU times are read
+    samples: SampleWriter,                     // where the frames are stored
+    telemetry: Option<Arc<dyn TelemetryPort>>, // where the peaks of each frame are published
+    interval: Duration,                        // the time between ticks
+}
+
+impl BurstSampler {
+    /// Creates a new instance of `BurstSampler`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    /// * `stats` - Reads the cumulative CPU times.
+    /// * `samples` - The writer the frames are stored through.
+    /// * `interval` - The time between ticks, at least `MIN_BURST_INTERVAL`.
+    ///
+    /// # Returns
+    /// An instance of `BurstSampler`.
+    pub fn new(
+        logger: Arc<dyn LoggerPort>,
+        stats: Arc<dyn SystemStatsPort>,
+        samples: SampleWriter,
+        interval: Duration,
+    ) -> Self {
+        BurstSampler {
+            logger,
+            stats,
+            samples,
+            telemetry: None,
+            interval,
+        }
+    }
+
+    /// Publishes the busiest tick, the bursts and the stalls of each frame to the
+    /// metric registry.
+    ///
+    /// # Arguments
+    /// * `telemetry` - The metric registry.
+    ///
+    /// # Returns
+    /// The sampler with the registry set.
+    pub fn with_telemetry(mut self, telemetry: Arc<dyn TelemetryPort>) -> Self {
+        self.telemetry = Some(telemetry);
+        self
+    }
+
+    /// Takes ticks until shutdown, storing a frame every `BURST_FRAME`.
+    ///
+    /// # Arguments
+    /// * `shutdown` - Cancelled when the application shuts down.
+    pub fn run(&self, shutdown: CancellationToken) {
+        let mut previous = match self.stats.read_cpu_times() {
+            Ok(times) => times,
+            Err(e) => {
+                self.logger
+                    .log_warn(&format!("Not sampling CPU bursts: {}", e));
+                return;
+            }
+        };
+        let mut frame = BurstFrame::new(self.interval);
+        let mut frame_end = Instant::now() + BURST_FRAME;
+        let mut deadline = Instant::now() + self.interval;
+
+        while !shutdown.is_cancelled() {
+            let now = Instant::now();
+            if deadline > now {
+                thread::sleep(deadline - now);
+            }
+            let late = Instant::now().saturating_duration_since(deadline);
+            if let Ok(times) = self.stats.read_cpu_times() {
+                let cores: Vec<(u32, f64)> = core_usage_between(&previous, &times)
+                    .iter()
+                    .map(|core| (core.cpu, core.percent))
+                    .collect();
+                previous = times;
+                frame.push(&cores, late);
+            }
+
+            // Deadlines missed during a stall are skipped rather than caught up
+            // on, so the stall shows as one late tick.
+            deadline += self.interval;
+            if deadline <= Instant::now() {
+                deadline = Instant::now() + self.interval;
+            }
+            if Instant::now() >= frame_end {
+                self.store(std::mem::replace(
+                    &mut frame,
+                    BurstFrame::new(self.interval),
+                ));
+                frame_end = Instant::now() + BURST_FRAME;
+            }
+        }
+        self.store(frame);
+    }
+
+    /// Stores a frame under the time its last tick was taken, and publishes its peaks.
+    fn store(&self, frame: BurstFrame) {
+        if frame.ticks() == 0 {
+            return;
+        }
+        if frame.stalls() > 0 {
+            self.logger.log_debug(&format!(
+                "The burst sampler stalled for {} tick(s), up to {:.0} ms late",
+                frame.stalls(),
+                frame.late_ms.iter().copied().fold(0.0, f64::max)
+            ));
+        }
+        if let Some(telemetry) = &self.telemetry {
+            let timestamp = Local::now().to_rfc3339();
+            let peak = frame.peak_core_percent.iter().copied().fold(0.0, f64::max);
+            telemetry.publish_metrics(vec![
+                Metric::new("cpu_burst_peak_percent", "percent", peak, &timestamp)
+                    .with_aggregation(Aggregation::Max),
+                Metric::new(
+                    "cpu_burst_ticks",
+                    "ticks",
+                    frame.bursts() as f64,
+                    &timestamp,
+                )
+                .with_aggregation(Aggregation::Sum),
+                Metric::new(
+                    "cpu_stall_ticks",
+                    "ticks",
+                    frame.stalls() as f64,
+                    &timestamp,
+                )
+                .with_aggregation(Aggregation::Sum),
+            ]);
+        }
+        if let Err(e) = self.samples.record(BURSTS_COLLECTOR, Utc::now(), &frame) {
+            self.logger
+                .log_warn(&format!("Failed to store the CPU burst frame: {}", e));
+        }
+    }
+}
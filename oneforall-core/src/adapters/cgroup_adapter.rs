@@ -0,0 +1,247 @@
+//! Cgroup Adapter
+//!
+//! This module provides an adapter that reads the resource limits of this
+//! process from the Linux cgroup filesystem, for both the unified cgroup v2
+//! hierarchy and the per-controller cgroup v1 hierarchies. A limit set on any
+//! ancestor of the cgroup applies to it as well, so the tightest limit along
+//! the path to the root of the hierarchy is the one reported.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use common::ports::log_port::LoggerPort;
+
+use crate::domain::container::{
+    parse_cfs_quota, parse_cpu_max, parse_memory_limit, ResourceLimits,
+};
+use crate::domain::cpu_topology::parse_cpu_list;
+use crate::domain::endurance::format_volume;
+use crate::ports::container_port::ContainerPort;
+
+/// Where the cgroup filesystem is mounted.
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// The cgroups of this process, one line per hierarchy.
+const PROC_SELF_CGROUP: &str = "/proc/self/cgroup";
+
+/// The list of online CPUs of the host.
+const SYSFS_CPU_ONLINE: &str = "/sys/devices/system/cpu/online";
+
+/// Files whose presence identifies a container runtime, with the runtime they identify.
+const RUNTIME_MARKERS: [(&str, &str); 2] =
+    [("/.dockerenv", "docker"), ("/run/.containerenv", "podman")];
+
+/// Fragments of a cgroup path that identify a container runtime, with the runtime they identify.
+const RUNTIME_CGROUP_FRAGMENTS: [(&str, &str); 4] = [
+    ("kubepods", "kubernetes"),
+    ("docker", "docker"),
+    ("libpod", "podman"),
+    ("lxc", "lxc"),
+];
+
+/// Represents the cgroup filesystem container adapter.
+pub struct CgroupAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl CgroupAdapter {
+    /// Creates a new instance of `CgroupAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `CgroupAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        CgroupAdapter { logger }
+    }
+
+    /// Reads a file and returns its trimmed contents.
+    fn read_file(path: &Path) -> Option<String> {
+        fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+    }
+
+    /// Reads the total memory of the host from /proc/meminfo, in bytes.
+    fn read_host_memory() -> Result<u64, String> {
+        let meminfo = fs::read_to_string("/proc/meminfo")
+            .map_err(|e| format!("Failed to read /proc/meminfo: {}", e))?;
+        meminfo
+            .lines()
+            .find(|line| line.starts_with("MemTotal:"))
+            .and_then(|line| line.split_whitespace().nth(1))
+            .and_then(|kb| kb.parse::<u64>().ok())
+            .map(|kb| kb * 1024)
+            .ok_or_else(|| "No MemTotal in /proc/meminfo".to_string())
+    }
+
+    /// Parses /proc/self/cgroup into the controllers and path of each hierarchy.
+    ///
+    /// Lines read "hierarchy-ID:controller-list:path"; the cgroup v2 line has an
+    /// empty controller list.
+    fn read_cgroups() -> Vec<(Vec<String>, String)> {
+        Self::read_file(Path::new(PROC_SELF_CGROUP))
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, ':');
+                let _id = fields.next()?;
+                let controllers = fields
+                    .next()?
+                    .split(',')
+                    .filter(|c| !c.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                Some((controllers, fields.next()?.to_string()))
+            })
+            .collect()
+    }
+
+    /// Returns the directories from the cgroup of the process up to the root of its hierarchy.
+    fn hierarchy(mount: &Path, path: &str) -> Vec<PathBuf> {
+        let mut directory = mount.join(path.trim_start_matches('/'));
+        // Without a cgroup namespace, a container is shown the host path of its
+        // cgroup, while its own cgroup is mounted as the root.
+        if !directory.is_dir() {
+            directory = mount.to_path_buf();
+        }
+        directory
+            .ancestors()
+            .take_while(|ancestor| ancestor.starts_with(mount))
+            .map(Path::to_path_buf)
+            .collect()
+    }
+
+    /// Returns the tightest limit read from a file along a hierarchy.
+    fn tightest<T: PartialOrd>(
+        hierarchy: &[PathBuf],
+        read: impl Fn(&Path) -> Option<T>,
+    ) -> Option<T> {
+        hierarchy
+            .iter()
+            .filter_map(|directory| read(directory))
+            .reduce(|a, b| if b < a { b } else { a })
+    }
+
+    /// Identifies the container runtime the process runs in, if any.
+    fn detect_runtime(cgroups: &[(Vec<String>, String)]) -> Option<String> {
+        if std::env::var_os("KUBERNETES_SERVICE_HOST").is_some() {
+            return Some("kubernetes".to_string());
+        }
+        if let Some((_, runtime)) = RUNTIME_MARKERS
+            .iter()
+            .find(|(marker, _)| Path::new(marker).exists())
+        {
+            return Some(runtime.to_string());
+        }
+        if let Some((_, runtime)) = RUNTIME_CGROUP_FRAGMENTS
+            .iter()
+            .find(|(fragment, _)| cgroups.iter().any(|(_, path)| path.contains(fragment)))
+        {
+            return Some(runtime.to_string());
+        }
+        // systemd-nspawn and LXC set `container` in the environment of their init.
+        std::env::var("container")
+            .ok()
+            .filter(|runtime| !runtime.is_empty())
+    }
+}
+
+// Implement the `ContainerPort` trait for `CgroupAdapter`.
+impl ContainerPort for CgroupAdapter {
+    fn read_resource_limits(&self) -> Result<ResourceLimits, String> {
+        let online = Self::read_file(Path::new(SYSFS_CPU_ONLINE))
+            .ok_or_else(|| format!("Failed to read {}", SYSFS_CPU_ONLINE))?;
+        let host_cpus = parse_cpu_list(&online)?;
+        let host_memory_bytes = Self::read_host_memory()?;
+        let cgroups = Self::read_cgroups();
+        let mut limits = ResourceLimits {
+            runtime: Self::detect_runtime(&cgroups),
+            host_cpus,
+            host_memory_bytes,
+            ..ResourceLimits::default()
+        };
+
+        let root = Path::new(CGROUP_ROOT);
+        let cpuset: Option<BTreeSet<u32>>;
+        if root.join("cgroup.controllers").exists() {
+            let path = cgroups
+                .iter()
+                .find(|(controllers, _)| controllers.is_empty())
+                .map_or("/", |(_, path)| path.as_str());
+            let hierarchy = Self::hierarchy(root, path);
+            limits.cgroup_version = Some(2);
+            limits.cgroup_path = Some(path.to_string());
+            limits.cpu_quota = Self::tightest(&hierarchy, |directory| {
+                parse_cpu_max(&Self::read_file(&directory.join("cpu.max"))?)
+            });
+            limits.memory_limit_bytes = Self::tightest(&hierarchy, |directory| {
+                parse_memory_limit(
+                    &Self::read_file(&directory.join("memory.max"))?,
+                    host_memory_bytes,
+                )
+            });
+            // The effective cpuset already accounts for the ancestors.
+            cpuset = hierarchy
+                .first()
+                .and_then(|leaf| Self::read_file(&leaf.join("cpuset.cpus.effective")))
+                .and_then(|list| parse_cpu_list(&list).ok());
+        } else {
+            let controller_hierarchy = |controller: &str| {
+                cgroups
+                    .iter()
+                    .find(|(controllers, _)| controllers.iter().any(|c| c == controller))
+                    .map(|(_, path)| (path.clone(), Self::hierarchy(&root.join(controller), path)))
+            };
+            let cpu = controller_hierarchy("cpu");
+            let memory = controller_hierarchy("memory");
+            let cpuset_hierarchy = controller_hierarchy("cpuset");
+            if cpu.is_none() && memory.is_none() {
+                self.logger
+                    .log_debug("No cgroup hierarchy found; assuming no resource limits.");
+                return Ok(limits);
+            }
+            limits.cgroup_version = Some(1);
+            limits.cgroup_path = memory
+                .as_ref()
+                .or(cpu.as_ref())
+                .map(|(path, _)| path.clone());
+            limits.cpu_quota = cpu.and_then(|(_, hierarchy)| {
+                Self::tightest(&hierarchy, |directory| {
+                    parse_cfs_quota(
+                        &Self::read_file(&directory.join("cpu.cfs_quota_us"))?,
+                        &Self::read_file(&directory.join("cpu.cfs_period_us"))?,
+                    )
+                })
+            });
+            limits.memory_limit_bytes = memory.and_then(|(_, hierarchy)| {
+                Self::tightest(&hierarchy, |directory| {
+                    parse_memory_limit(
+                        &Self::read_file(&directory.join("memory.limit_in_bytes"))?,
+                        host_memory_bytes,
+                    )
+                })
+            });
+            cpuset = cpuset_hierarchy
+                .and_then(|(_, hierarchy)| hierarchy.first().cloned())
+                .and_then(|leaf| {
+                    Self::read_file(&leaf.join("cpuset.effective_cpus"))
+                        .or_else(|| Self::read_file(&leaf.join("cpuset.cpus")))
+                })
+                .and_then(|list| parse_cpu_list(&list).ok());
+        }
+        limits.cpuset = cpuset.filter(|cpus| !cpus.is_empty() && *cpus != limits.host_cpus);
+
+        if limits.is_cpu_limited() || limits.is_memory_limited() {
+            self.logger.log_debug(&format!(
+                "cgroup limits the process to {} of {} CPUs and {} of {} memory",
+                limits.effective_cpus(),
+                limits.host_cpus.len(),
+                format_volume(limits.effective_memory_bytes()),
+                format_volume(limits.host_memory_bytes)
+            ));
+        }
+        Ok(limits)
+    }
+}
@@ -0,0 +1,104 @@
+//! Chaos Injector
+//!
+//! This module arms the failure injection of `--chaos` for the whole process.
+//! The plan is kept in a process-wide cell, like the stress-ng runs killed on
+//! shutdown, so the hardware error and thermal adapters add the injected ECC
+//! error and temperature to their readings wherever they are created: in the
+//! threshold monitor, the run monitors, profile steps and soak cycles alike.
+//! A background thread logs each event as it fires and kills the stress-ng
+//! run in progress, waiting for one to start if none is running yet.
+
+use std::sync::{Arc, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use common::ports::log_port::LoggerPort;
+
+use crate::adapters::stress_ng_adapter::kill_running_stress_ng;
+use crate::domain::chaos::{
+    ChaosEvent, ChaosPlan, CHAOS_ECC_ERRORS, CHAOS_SENSOR, CHAOS_SPIKE_CELSIUS,
+    CHAOS_SPIKE_DURATION,
+};
+use crate::domain::profile::format_duration;
+use crate::domain::thermal::TemperatureReading;
+
+/// Interval at which the injector looks for a stress-ng run to kill.
+const KILL_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// The armed plan and the time it was armed at.
+static CHAOS: OnceLock<(ChaosPlan, Instant)> = OnceLock::new();
+
+/// Arms the failure injection for the rest of the process.
+///
+/// # Arguments
+/// * `logger` - Receives a warning as each event fires.
+/// * `plan` - The events to inject and when.
+///
+/// # Returns
+/// A `Result` that is an error if the injection was already armed.
+pub fn arm_chaos(logger: Arc<dyn LoggerPort>, plan: ChaosPlan) -> Result<(), String> {
+    let armed = Instant::now();
+    CHAOS
+        .set((plan.clone(), armed))
+        .map_err(|_| "Failure injection is already armed".to_string())?;
+    logger.log_warn(&format!(
+        "Chaos mode: injecting {} in {}; results are tagged chaos={}",
+        plan.event_names(),
+        format_duration(plan.after),
+        plan.event_names()
+    ));
+
+    thread::spawn(move || {
+        thread::sleep(plan.after);
+        if plan.includes(ChaosEvent::Ecc) {
+            logger.log_warn(&format!(
+                "Chaos: injected {} corrected ECC error(s)",
+                CHAOS_ECC_ERRORS
+            ));
+        }
+        if plan.includes(ChaosEvent::Thermal) {
+            logger.log_warn(&format!(
+                "Chaos: {} reads {:.0} °C for {}",
+                CHAOS_SENSOR,
+                CHAOS_SPIKE_CELSIUS,
+                format_duration(CHAOS_SPIKE_DURATION)
+            ));
+        }
+        if plan.includes(ChaosEvent::KillStressor) {
+            logger.log_info("Chaos: waiting for a stress-ng run to kill");
+            loop {
+                let killed = kill_running_stress_ng();
+                if killed > 0 {
+                    logger.log_warn(&format!("Chaos: killed {} stress-ng run(s)", killed));
+                    break;
+                }
+                thread::sleep(KILL_POLL_INTERVAL);
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Returns the corrected ECC errors injected so far.
+///
+/// # Returns
+/// The errors to add to the EDAC counters, or `None` if no ECC error is injected.
+pub fn injected_ecc_errors() -> Option<u64> {
+    CHAOS
+        .get()
+        .and_then(|(plan, armed)| plan.injected_ecc_errors(armed.elapsed()))
+}
+
+/// Returns the injected temperature reading while the spike lasts.
+///
+/// # Returns
+/// A reading of the injected sensor, or `None` outside the spike.
+pub fn injected_temperature() -> Option<TemperatureReading> {
+    CHAOS
+        .get()
+        .and_then(|(plan, armed)| plan.injected_celsius(armed.elapsed()))
+        .map(|celsius| TemperatureReading {
+            name: CHAOS_SENSOR.to_string(),
+            celsius,
+        })
+}
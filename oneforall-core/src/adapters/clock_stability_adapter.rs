@@ -0,0 +1,315 @@
+//! Clock Stability Adapter
+//!
+//! This module provides an adapter that checks the clocks of the machine. The
+//! counter of each CPU is compared with the reference CPU by passing a token
+//! back and forth between two threads pinned to them: the reference stamps the
+//! token when it sends it and when it gets it back, the other CPU stamps it in
+//! between, so over many round trips the offset of its counter is bounded from
+//! both sides, as the kernel's TSC warp check does. The counter is the TSC on
+//! x86, read behind an `lfence` so it is not taken early, and the monotonic
+//! clock elsewhere. The clocksource comes from sysfs, and the resolution and
+//! cost of the monotonic clock from `clock_getres` and a loop of reads.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use common::ports::log_port::LoggerPort;
+use tokio_util::sync::CancellationToken;
+
+use crate::adapters::sysfs::read_sysfs;
+use crate::adapters::topology_adapter::pin_current_thread;
+use crate::domain::clock::{ClockResult, CoreSkew};
+use crate::ports::clock_stability_port::ClockStabilityPort;
+
+/// Directory of the clocksource the kernel uses.
+const SYSFS_CLOCKSOURCE_ROOT: &str = "/sys/devices/system/clocksource/clocksource0";
+
+/// Number of consecutive reads of the monotonic clock timed.
+const CLOCK_READS: usize = 200_000;
+
+/// Time the counter is calibrated against the monotonic clock for.
+const CALIBRATION_TIME: Duration = Duration::from_millis(100);
+
+/// Longest a thread waits for the token before the CPU pair is given up on.
+const EXCHANGE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Reads the monotonic clock, in nanoseconds.
+fn monotonic_ns() -> u64 {
+    let mut time = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: `time` is a valid, writable `timespec`.
+    unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut time) };
+    time.tv_sec as u64 * 1_000_000_000 + time.tv_nsec as u64
+}
+
+/// Reads the TSC once every earlier instruction has completed.
+#[cfg(target_arch = "x86_64")]
+fn read_counter() -> u64 {
+    let (low, high): (u32, u32);
+    // SAFETY: `lfence` and `rdtsc` are available on every x86_64 CPU and only
+    // write the named registers.
+    unsafe {
+        std::arch::asm!(
+            "lfence",
+            "rdtsc",
+            out("eax") low,
+            out("edx") high,
+            options(nostack, preserves_flags),
+        );
+    }
+    (u64::from(high) << 32) | u64::from(low)
+}
+
+/// Reads the monotonic clock, which the kernel keeps consistent across cores.
+#[cfg(not(target_arch = "x86_64"))]
+fn read_counter() -> u64 {
+    monotonic_ns()
+}
+
+/// The token passed between the reference CPU and the CPU compared with it.
+#[derive(Default)]
+struct Exchange {
+    /// Odd while the token is with the other CPU, even once it is back.
+    turn: AtomicU64,
+
+    /// The counter of the other CPU when it held the token.
+    stamp: AtomicU64,
+
+    /// Set by either thread when it gives up, so the other stops waiting.
+    abort: AtomicBool,
+}
+
+impl Exchange {
+    /// Spins until the turn reaches the given value, or returns `false` if the
+    /// other thread gave up or the wait timed out.
+    fn wait_for(&self, turn: u64) -> bool {
+        let started = Instant::now();
+        let mut spins = 0u32;
+        while self.turn.load(Ordering::Acquire) != turn {
+            if self.abort.load(Ordering::Relaxed) {
+                return false;
+            }
+            spins = spins.wrapping_add(1);
+            if spins.is_multiple_of(4096) && started.elapsed() > EXCHANGE_TIMEOUT {
+                self.abort.store(true, Ordering::Relaxed);
+                return false;
+            }
+            std::hint::spin_loop();
+        }
+        true
+    }
+}
+
+/// Represents the in-process clock stability adapter.
+pub struct ClockStabilityAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl ClockStabilityAdapter {
+    /// Creates a new instance of `ClockStabilityAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `ClockStabilityAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        ClockStabilityAdapter { logger }
+    }
+
+    /// Returns an error once the benchmark is cancelled.
+    fn check_cancelled(cancel: &CancellationToken) -> Result<(), String> {
+        if cancel.is_cancelled() {
+            Err("Clock stability benchmark cancelled".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Reads whether the CPU advertises an invariant TSC (leaf 0x80000007, EDX bit 8).
+    #[cfg(target_arch = "x86_64")]
+    fn invariant_tsc() -> Option<bool> {
+        use std::arch::x86_64::__cpuid;
+
+        if __cpuid(0x8000_0000).eax < 0x8000_0007 {
+            return Some(false);
+        }
+        Some(__cpuid(0x8000_0007).edx & (1 << 8) != 0)
+    }
+
+    /// Only x86 has a TSC.
+    #[cfg(not(target_arch = "x86_64"))]
+    fn invariant_tsc() -> Option<bool> {
+        None
+    }
+
+    /// Returns the name of the counter compared across cores.
+    fn counter_name() -> &'static str {
+        if cfg!(target_arch = "x86_64") {
+            "TSC"
+        } else {
+            "monotonic clock"
+        }
+    }
+
+    /// Measures the rate of the counter against the monotonic clock, in ticks per second.
+    fn counter_hz() -> f64 {
+        let (start_counter, start_ns) = (read_counter(), monotonic_ns());
+        thread::sleep(CALIBRATION_TIME);
+        let (end_counter, end_ns) = (read_counter(), monotonic_ns());
+        end_counter.wrapping_sub(start_counter) as f64 * 1e9
+            / end_ns.saturating_sub(start_ns).max(1) as f64
+    }
+
+    /// Times consecutive reads of the monotonic clock.
+    ///
+    /// # Returns
+    /// The average cost of a read and the smallest step between two reads, in nanoseconds.
+    fn time_clock_reads() -> (f64, f64) {
+        let started = Instant::now();
+        let mut previous = monotonic_ns();
+        let mut granularity = u64::MAX;
+        for _ in 0..CLOCK_READS {
+            let now = monotonic_ns();
+            if now > previous {
+                granularity = granularity.min(now - previous);
+            }
+            previous = now;
+        }
+        let cost = started.elapsed().as_nanos() as f64 / CLOCK_READS as f64;
+        (cost, granularity as f64)
+    }
+
+    /// Returns the resolution the kernel reports for the monotonic clock, in nanoseconds.
+    fn clock_resolution_ns() -> f64 {
+        let mut resolution = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        // SAFETY: `resolution` is a valid, writable `timespec`.
+        unsafe { libc::clock_getres(libc::CLOCK_MONOTONIC, &mut resolution) };
+        resolution.tv_sec as f64 * 1e9 + resolution.tv_nsec as f64
+    }
+
+    /// Bounds the offset of the counter of one CPU from the reference CPU.
+    ///
+    /// # Returns
+    /// The lowest and highest offset consistent with every round trip, in
+    /// ticks, or an error if a thread could not be pinned or the token was lost.
+    fn bound_offset(reference: u32, cpu: u32, rounds: usize) -> Result<(i64, i64), String> {
+        let exchange = Exchange::default();
+        let rounds = rounds.max(1) as u64;
+        thread::scope(|scope| {
+            let responder = scope.spawn(|| {
+                if let Err(e) = pin_current_thread(cpu) {
+                    exchange.abort.store(true, Ordering::Relaxed);
+                    return Err(e);
+                }
+                for round in 1..=rounds {
+                    if !exchange.wait_for(2 * round - 1) {
+                        break;
+                    }
+                    exchange.stamp.store(read_counter(), Ordering::Relaxed);
+                    exchange.turn.store(2 * round, Ordering::Release);
+                }
+                Ok(())
+            });
+
+            let bounds = scope
+                .spawn(|| {
+                    if let Err(e) = pin_current_thread(reference) {
+                        exchange.abort.store(true, Ordering::Relaxed);
+                        return Err(e);
+                    }
+                    let (mut lower, mut upper) = (i64::MIN, i64::MAX);
+                    for round in 1..=rounds {
+                        let sent = read_counter();
+                        exchange.turn.store(2 * round - 1, Ordering::Release);
+                        if !exchange.wait_for(2 * round) {
+                            return Err(format!("CPU {} stopped answering", cpu));
+                        }
+                        let received = read_counter();
+                        let stamp = exchange.stamp.load(Ordering::Relaxed);
+                        // The stamp was taken after the token was sent and before it came back.
+                        upper = upper.min(stamp.wrapping_sub(sent) as i64);
+                        lower = lower.max(stamp.wrapping_sub(received) as i64);
+                    }
+                    Ok((lower, upper))
+                })
+                .join()
+                .unwrap_or_else(|_| Err("The reference thread panicked".to_string()));
+            responder
+                .join()
+                .unwrap_or_else(|_| Err("The responding thread panicked".to_string()))?;
+            bounds
+        })
+    }
+}
+
+// Implement the `ClockStabilityPort` trait for `ClockStabilityAdapter`.
+impl ClockStabilityPort for ClockStabilityAdapter {
+    fn measure_clock_stability(
+        &self,
+        cpus: &[u32],
+        rounds: usize,
+        cancel: &CancellationToken,
+    ) -> Result<ClockResult, String> {
+        let (read_cost_ns, granularity_ns) = Self::time_clock_reads();
+        let mut result = ClockResult {
+            counter: Self::counter_name().to_string(),
+            invariant_tsc: Self::invariant_tsc(),
+            clocksource: read_sysfs(Path::new(SYSFS_CLOCKSOURCE_ROOT).join("current_clocksource"))
+                .filter(|clocksource| !clocksource.is_empty()),
+            available_clocksources: read_sysfs(
+                Path::new(SYSFS_CLOCKSOURCE_ROOT).join("available_clocksource"),
+            )
+            .map(|available| available.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default(),
+            resolution_ns: Self::clock_resolution_ns(),
+            granularity_ns,
+            read_cost_ns,
+            reference_cpu: cpus.first().copied().unwrap_or(0),
+            skews: Vec::new(),
+        };
+
+        // Threads cannot be pinned elsewhere, e.g. on macOS, so no pair of CPUs can be compared.
+        if !cfg!(target_os = "linux") {
+            self.logger.log_warn(
+                "Threads cannot be pinned on this platform; skipping the cross-core skew",
+            );
+            return Ok(result);
+        }
+        let hz = Self::counter_hz();
+        self.logger.log_info(&format!(
+            "Comparing the {} of {} CPUs with CPU {} ({:.0} MHz)",
+            result.counter,
+            cpus.len().saturating_sub(1),
+            result.reference_cpu,
+            hz / 1e6
+        ));
+        for &cpu in cpus.iter().skip(1) {
+            Self::check_cancelled(cancel)?;
+            match Self::bound_offset(result.reference_cpu, cpu, rounds) {
+                Ok((lower, upper)) => {
+                    let ticks_to_ns = |ticks: f64| ticks * 1e9 / hz.max(1.0);
+                    result.skews.push(CoreSkew {
+                        cpu,
+                        offset_ns: ticks_to_ns((lower as f64 + upper as f64) / 2.0),
+                        uncertainty_ns: ticks_to_ns((upper as f64 - lower as f64).abs() / 2.0),
+                    });
+                }
+                Err(e) => self.logger.log_warn(&format!(
+                    "Skipping the cross-core skew of CPU {}: {}",
+                    cpu, e
+                )),
+            }
+        }
+        Self::check_cancelled(cancel)?;
+        Ok(result)
+    }
+}
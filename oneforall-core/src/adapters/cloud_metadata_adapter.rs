@@ -0,0 +1,179 @@
+//! Cloud Metadata Adapter
+//!
+//! This module provides an adapter that describes the cloud instance this
+//! machine is. The provider is identified from the DMI system information the
+//! hypervisor exposes in sysfs, and only that provider's instance metadata
+//! service is then queried, so machines outside a cloud never wait on a link
+//! local address nobody answers.
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::ports::log_port::LoggerPort;
+use serde_json::Value;
+
+use crate::domain::cloud::{
+    detect_provider, parse_aws_identity, parse_azure_instance, parse_gcp_instance, CloudProvider,
+    DmiSystem, InstanceMetadata,
+};
+use crate::ports::cloud_metadata_port::CloudMetadataPort;
+
+/// Where the kernel exposes the DMI system information.
+const SYSFS_DMI_ID: &str = "/sys/class/dmi/id";
+
+/// The link local address every supported provider serves instance metadata on.
+const METADATA_ADDRESS: &str = "http://169.254.169.254";
+
+/// Time allowed for the metadata service to answer; it answers in milliseconds when present.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How long the IMDSv2 session token requested from AWS stays valid, in seconds.
+const AWS_TOKEN_TTL_SECONDS: &str = "60";
+
+/// The version of the Azure instance metadata API requested.
+const AZURE_API_VERSION: &str = "2021-02-01";
+
+/// Reads a DMI field, returning an empty string if it is missing or unreadable.
+pub(crate) fn read_dmi_field(name: &str) -> String {
+    fs::read_to_string(Path::new(SYSFS_DMI_ID).join(name))
+        .map(|value| value.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Reads the DMI system fields the hypervisor or firmware reports.
+pub(crate) fn read_dmi_system() -> DmiSystem {
+    DmiSystem {
+        sys_vendor: read_dmi_field("sys_vendor"),
+        product_name: read_dmi_field("product_name"),
+        bios_version: read_dmi_field("bios_version"),
+        chassis_asset_tag: read_dmi_field("chassis_asset_tag"),
+    }
+}
+
+/// Represents the instance metadata service adapter.
+pub struct CloudMetadataAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    agent: ureq::Agent,
+}
+
+impl CloudMetadataAdapter {
+    /// Creates a new instance of `CloudMetadataAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `CloudMetadataAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        let agent = ureq::Agent::config_builder()
+            .timeout_global(Some(REQUEST_TIMEOUT))
+            .build()
+            .into();
+        CloudMetadataAdapter { logger, agent }
+    }
+
+    /// Reads a metadata path as text, sending the given headers.
+    fn get_text(&self, path: &str, headers: &[(&str, &str)]) -> Result<String, String> {
+        let url = format!("{}{}", METADATA_ADDRESS, path);
+        let mut request = self.agent.get(&url);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        request
+            .call()
+            .map_err(|e| format!("Failed to reach {}: {}", url, e))?
+            .body_mut()
+            .read_to_string()
+            .map_err(|e| format!("Failed to read the response of {}: {}", url, e))
+    }
+
+    /// Reads a metadata path as JSON, sending the given headers.
+    fn get_json(&self, path: &str, headers: &[(&str, &str)]) -> Result<Value, String> {
+        let body = self.get_text(path, headers)?;
+        serde_json::from_str(&body)
+            .map_err(|e| format!("Invalid response from {}{}: {}", METADATA_ADDRESS, path, e))
+    }
+
+    /// Reads the metadata of an EC2 instance through IMDSv2.
+    ///
+    /// IMDSv2 requires a session token, requested with a PUT, on every read;
+    /// instances that still allow IMDSv1 accept reads without one.
+    fn read_aws(&self) -> Result<InstanceMetadata, String> {
+        let token = self
+            .agent
+            .put(format!("{}/latest/api/token", METADATA_ADDRESS))
+            .header(
+                "X-aws-ec2-metadata-token-ttl-seconds",
+                AWS_TOKEN_TTL_SECONDS,
+            )
+            .send_empty()
+            .and_then(|mut response| response.body_mut().read_to_string());
+        let token = match token {
+            Ok(token) => Some(token),
+            Err(e) => {
+                self.logger.log_debug(&format!(
+                    "No IMDSv2 token ({}); reading instance metadata without one",
+                    e
+                ));
+                None
+            }
+        };
+        let headers: Vec<(&str, &str)> = token
+            .as_deref()
+            .map(|token| ("X-aws-ec2-metadata-token", token))
+            .into_iter()
+            .collect();
+
+        let document = self.get_json("/latest/dynamic/instance-identity/document", &headers)?;
+        let tenancy = self
+            .get_text("/latest/meta-data/placement/tenancy", &headers)
+            .ok()
+            .map(|tenancy| tenancy.trim().to_string())
+            .filter(|tenancy| !tenancy.is_empty());
+        Ok(parse_aws_identity(&document, tenancy))
+    }
+
+    /// Reads the metadata of a Compute Engine VM.
+    fn read_gcp(&self) -> Result<InstanceMetadata, String> {
+        let instance = self.get_json(
+            "/computeMetadata/v1/instance/?recursive=true",
+            &[("Metadata-Flavor", "Google")],
+        )?;
+        Ok(parse_gcp_instance(&instance))
+    }
+
+    /// Reads the metadata of an Azure VM.
+    fn read_azure(&self) -> Result<InstanceMetadata, String> {
+        let instance = self.get_json(
+            &format!("/metadata/instance?api-version={}", AZURE_API_VERSION),
+            &[("Metadata", "true")],
+        )?;
+        Ok(parse_azure_instance(&instance))
+    }
+}
+
+// Implement the `CloudMetadataPort` trait for `CloudMetadataAdapter`.
+impl CloudMetadataPort for CloudMetadataAdapter {
+    fn read_instance_metadata(&self) -> Result<Option<InstanceMetadata>, String> {
+        let Some(provider) = detect_provider(&read_dmi_system()) else {
+            return Ok(None);
+        };
+        let instance = match provider {
+            CloudProvider::Aws => self.read_aws(),
+            CloudProvider::Gcp => self.read_gcp(),
+            CloudProvider::Azure => self.read_azure(),
+        }?;
+        self.logger.log_debug(&format!(
+            "Running on {} instance {} in {}",
+            provider.name(),
+            instance
+                .instance_type
+                .as_deref()
+                .unwrap_or("of unknown type"),
+            instance.zone.as_deref().unwrap_or("an unknown zone")
+        ));
+        Ok(Some(instance))
+    }
+}
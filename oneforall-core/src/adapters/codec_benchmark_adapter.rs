@@ -0,0 +1,417 @@
+//! Codec Benchmark Adapter
+//!
+//! This module provides an adapter that measures crypto and compression
+//! throughput in-process, with the libraries a Rust service would use: ring
+//! for AES-256-GCM, which uses AES-NI and carry-less multiplication on x86 and
+//! the Armv8 crypto extensions on Arm, sha2 for SHA-256, which uses the SHA
+//! extensions of either, zstd, and flate2 for gzip. Each thread is pinned to its
+//! logical CPU and works on its own buffers, so threads share nothing but the
+//! read-only input.
+
+use std::collections::BTreeMap;
+use std::hint::black_box;
+use std::io::{Read, Write};
+use std::sync::{Arc, Barrier};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use common::ports::log_port::LoggerPort;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM};
+use sha2::{Digest, Sha256};
+use tokio_util::sync::CancellationToken;
+
+use crate::adapters::topology_adapter::pin_current_thread;
+use crate::domain::codec::{CodecAlgorithm, CodecResult, CodecThroughput, SocketCodecThroughput};
+use crate::domain::cpu_topology::CpuTopology;
+use crate::ports::codec_benchmark_port::CodecBenchmarkPort;
+
+/// Size of the synthetic input every algorithm works on.
+const CORPUS_BYTES: usize = 4 << 20;
+
+/// Size of the records encrypted with AES-256-GCM, the largest TLS record.
+const AES_RECORD_BYTES: usize = 16 << 10;
+
+/// Size of the blocks hashed with SHA-256.
+const SHA_BLOCK_BYTES: usize = 64 << 10;
+
+/// Size of the blocks compressed and decompressed.
+const COMPRESSION_BLOCK_BYTES: usize = 1 << 20;
+
+/// The zstd compression level, its default.
+const ZSTD_LEVEL: i32 = 3;
+
+/// The gzip compression level, its default.
+const GZIP_LEVEL: u32 = 6;
+
+/// Generates synthetic web server log lines, which compress about as well as
+/// typical logs and JSON: repeated structure around varying numbers.
+fn generate_corpus() -> Vec<u8> {
+    const METHODS: [&str; 4] = ["GET", "GET", "POST", "PUT"];
+    const RESOURCES: [&str; 6] = ["items", "orders", "users", "carts", "search", "metrics"];
+    const STATUSES: [u32; 5] = [200, 200, 200, 304, 404];
+
+    let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    let mut corpus = Vec::with_capacity(CORPUS_BYTES + 256);
+    let mut timestamp: u64 = 1_714_636_800_000;
+    while corpus.len() < CORPUS_BYTES {
+        timestamp += next() % 50;
+        let line = format!(
+            "{{\"ts\":{},\"host\":\"web-{:02}\",\"method\":\"{}\",\"path\":\"/api/v1/{}/{}\",\"status\":{},\"bytes\":{},\"ms\":{}}}\n",
+            timestamp,
+            next() % 24,
+            METHODS[(next() % METHODS.len() as u64) as usize],
+            RESOURCES[(next() % RESOURCES.len() as u64) as usize],
+            next() % 100_000,
+            STATUSES[(next() % STATUSES.len() as u64) as usize],
+            next() % 65_536,
+            next() % 500
+        );
+        corpus.extend_from_slice(line.as_bytes());
+    }
+    corpus.truncate(CORPUS_BYTES);
+    corpus
+}
+
+/// Compresses a block with gzip.
+fn gzip(block: &[u8]) -> Result<Vec<u8>, String> {
+    let mut encoder = GzEncoder::new(
+        Vec::with_capacity(block.len()),
+        Compression::new(GZIP_LEVEL),
+    );
+    encoder
+        .write_all(block)
+        .and_then(|_| encoder.finish())
+        .map_err(|e| format!("gzip compression failed: {}", e))
+}
+
+/// What one thread of a run measured: the bytes it processed, how long it ran
+/// and the error met pinning it, if any.
+type ThreadResult = Result<(u64, Duration, Option<String>), String>;
+
+/// The input of an algorithm, prepared once and shared by every thread.
+struct Workload {
+    algorithm: CodecAlgorithm,
+    corpus: Arc<Vec<u8>>,
+    compressed: Vec<Vec<u8>>, // the compressed blocks of the corpus, for compressors
+}
+
+impl Workload {
+    /// Prepares the input of an algorithm, compressing the corpus for the compressors.
+    fn prepare(algorithm: CodecAlgorithm, corpus: Arc<Vec<u8>>) -> Result<Workload, String> {
+        let blocks = corpus.chunks(COMPRESSION_BLOCK_BYTES);
+        let compressed = match algorithm {
+            CodecAlgorithm::ZstdCompress | CodecAlgorithm::ZstdDecompress => blocks
+                .map(|block| {
+                    zstd::bulk::compress(block, ZSTD_LEVEL)
+                        .map_err(|e| format!("zstd compression failed: {}", e))
+                })
+                .collect::<Result<_, _>>()?,
+            CodecAlgorithm::GzipCompress | CodecAlgorithm::GzipDecompress => {
+                blocks.map(gzip).collect::<Result<_, _>>()?
+            }
+            CodecAlgorithm::Aes256Gcm | CodecAlgorithm::Sha256 => Vec::new(),
+        };
+        Ok(Workload {
+            algorithm,
+            corpus,
+            compressed,
+        })
+    }
+
+    /// Returns the size of the corpus divided by its compressed size, for compressors.
+    fn compression_ratio(&self) -> Option<f64> {
+        let compressed: usize = self.compressed.iter().map(Vec::len).sum();
+        (compressed > 0).then(|| self.corpus.len() as f64 / compressed as f64)
+    }
+
+    /// Runs the algorithm on one thread until the deadline.
+    ///
+    /// # Returns
+    /// The plaintext or uncompressed bytes processed, or why the algorithm failed.
+    fn run(&self, deadline: Instant, cancel: &CancellationToken) -> Result<u64, String> {
+        let running = || Instant::now() < deadline && !cancel.is_cancelled();
+        let mut bytes = 0u64;
+        let mut index = 0usize;
+        match self.algorithm {
+            CodecAlgorithm::Aes256Gcm => {
+                let key = UnboundKey::new(&AES_256_GCM, &[0x42; 32])
+                    .map(LessSafeKey::new)
+                    .map_err(|_| "Failed to set up the AES-256-GCM key".to_string())?;
+                let mut record = self.corpus[..AES_RECORD_BYTES].to_vec();
+                while running() {
+                    // A counter never repeats a nonce under the key, as GCM requires.
+                    let mut nonce = [0u8; 12];
+                    nonce[4..].copy_from_slice(&(index as u64).to_be_bytes());
+                    let tag = key
+                        .seal_in_place_separate_tag(
+                            Nonce::assume_unique_for_key(nonce),
+                            Aad::empty(),
+                            &mut record,
+                        )
+                        .map_err(|_| "AES-256-GCM encryption failed".to_string())?;
+                    black_box(&tag);
+                    bytes += record.len() as u64;
+                    index += 1;
+                }
+            }
+            CodecAlgorithm::Sha256 => {
+                let blocks: Vec<&[u8]> = self.corpus.chunks(SHA_BLOCK_BYTES).collect();
+                while running() {
+                    let block = blocks[index % blocks.len()];
+                    black_box(Sha256::digest(block));
+                    bytes += block.len() as u64;
+                    index += 1;
+                }
+            }
+            CodecAlgorithm::ZstdCompress => {
+                let blocks: Vec<&[u8]> = self.corpus.chunks(COMPRESSION_BLOCK_BYTES).collect();
+                let mut compressor = zstd::bulk::Compressor::new(ZSTD_LEVEL)
+                    .map_err(|e| format!("Failed to set up zstd: {}", e))?;
+                while running() {
+                    let block = blocks[index % blocks.len()];
+                    let compressed = compressor
+                        .compress(block)
+                        .map_err(|e| format!("zstd compression failed: {}", e))?;
+                    black_box(compressed);
+                    bytes += block.len() as u64;
+                    index += 1;
+                }
+            }
+            CodecAlgorithm::ZstdDecompress => {
+                let mut decompressor = zstd::bulk::Decompressor::new()
+                    .map_err(|e| format!("Failed to set up zstd: {}", e))?;
+                while running() {
+                    let block = decompressor
+                        .decompress(
+                            &self.compressed[index % self.compressed.len()],
+                            COMPRESSION_BLOCK_BYTES,
+                        )
+                        .map_err(|e| format!("zstd decompression failed: {}", e))?;
+                    bytes += block.len() as u64;
+                    black_box(block);
+                    index += 1;
+                }
+            }
+            CodecAlgorithm::GzipCompress => {
+                let blocks: Vec<&[u8]> = self.corpus.chunks(COMPRESSION_BLOCK_BYTES).collect();
+                while running() {
+                    let block = blocks[index % blocks.len()];
+                    black_box(gzip(block)?);
+                    bytes += block.len() as u64;
+                    index += 1;
+                }
+            }
+            CodecAlgorithm::GzipDecompress => {
+                let mut block = Vec::with_capacity(COMPRESSION_BLOCK_BYTES);
+                while running() {
+                    block.clear();
+                    GzDecoder::new(&self.compressed[index % self.compressed.len()][..])
+                        .read_to_end(&mut block)
+                        .map_err(|e| format!("gzip decompression failed: {}", e))?;
+                    bytes += block.len() as u64;
+                    black_box(&block);
+                    index += 1;
+                }
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+/// Represents the in-process crypto and compression benchmark adapter.
+pub struct CodecBenchmarkAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl CodecBenchmarkAdapter {
+    /// Creates a new instance of `CodecBenchmarkAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `CodecBenchmarkAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        CodecBenchmarkAdapter { logger }
+    }
+
+    /// Returns an error once the benchmark is cancelled.
+    fn check_cancelled(cancel: &CancellationToken) -> Result<(), String> {
+        if cancel.is_cancelled() {
+            Err("Codec benchmark cancelled".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Lists the CPU extensions accelerating an algorithm on this machine.
+    #[cfg(target_arch = "x86_64")]
+    fn acceleration(algorithm: CodecAlgorithm) -> Vec<String> {
+        let extensions = match algorithm {
+            CodecAlgorithm::Aes256Gcm => vec![
+                (is_x86_feature_detected!("aes"), "AES-NI"),
+                (is_x86_feature_detected!("pclmulqdq"), "PCLMULQDQ"),
+                (is_x86_feature_detected!("vaes"), "VAES"),
+                (is_x86_feature_detected!("vpclmulqdq"), "VPCLMULQDQ"),
+            ],
+            CodecAlgorithm::Sha256 => vec![(is_x86_feature_detected!("sha"), "SHA-NI")],
+            _ => Vec::new(),
+        };
+        extensions
+            .into_iter()
+            .filter(|(detected, _)| *detected)
+            .map(|(_, name)| name.to_string())
+            .collect()
+    }
+
+    /// Lists the CPU extensions accelerating an algorithm on this machine.
+    #[cfg(target_arch = "aarch64")]
+    fn acceleration(algorithm: CodecAlgorithm) -> Vec<String> {
+        use std::arch::is_aarch64_feature_detected;
+
+        let extensions = match algorithm {
+            CodecAlgorithm::Aes256Gcm => vec![(is_aarch64_feature_detected!("aes"), "AES+PMULL")],
+            CodecAlgorithm::Sha256 => vec![(is_aarch64_feature_detected!("sha2"), "SHA2")],
+            _ => Vec::new(),
+        };
+        extensions
+            .into_iter()
+            .filter(|(detected, _)| *detected)
+            .map(|(_, name)| name.to_string())
+            .collect()
+    }
+
+    /// No crypto extension is detected on other architectures.
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    fn acceleration(_algorithm: CodecAlgorithm) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Runs an algorithm on one thread per logical CPU, started together.
+    ///
+    /// # Returns
+    /// The data processed per second by every thread together, in GB/s, and the
+    /// first error met pinning a thread, if any.
+    fn measure(
+        workload: &Workload,
+        cpus: &[u32],
+        duration: Duration,
+        cancel: &CancellationToken,
+    ) -> Result<(f64, Option<String>), String> {
+        let barrier = Barrier::new(cpus.len());
+        let results: Vec<ThreadResult> = thread::scope(|scope| {
+            let handles: Vec<_> = cpus
+                .iter()
+                .map(|cpu| {
+                    let barrier = &barrier;
+                    scope.spawn(move || {
+                        let pinned = pin_current_thread(*cpu).err();
+                        barrier.wait();
+                        let started = Instant::now();
+                        let bytes = workload.run(started + duration, cancel)?;
+                        Ok((bytes, started.elapsed(), pinned))
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|_| Err("A benchmark thread panicked".to_string()))
+                })
+                .collect()
+        });
+
+        let mut gb_per_sec = 0.0;
+        let mut pin_error = None;
+        for result in results {
+            let (bytes, elapsed, error) = result?;
+            gb_per_sec += bytes as f64 / elapsed.as_secs_f64().max(1e-9) / 1e9;
+            pin_error = pin_error.or(error);
+        }
+        Ok((gb_per_sec, pin_error))
+    }
+}
+
+// Implement the `CodecBenchmarkPort` trait for `CodecBenchmarkAdapter`.
+impl CodecBenchmarkPort for CodecBenchmarkAdapter {
+    fn run_codec_benchmark(
+        &self,
+        algorithms: &[CodecAlgorithm],
+        topology: &CpuTopology,
+        step_duration: Duration,
+        cancel: &CancellationToken,
+    ) -> Result<CodecResult, String> {
+        let mut sockets: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+        for cpu in &topology.cpus {
+            sockets.entry(cpu.package_id).or_default().push(cpu.id);
+        }
+        let core = topology
+            .physical_cpu_ids()
+            .first()
+            .copied()
+            .ok_or("No online CPU to run the benchmark on")?;
+
+        let corpus = Arc::new(generate_corpus());
+        let mut result = CodecResult::default();
+        let mut warned = false;
+        let mut warn_unpinned = |error: Option<String>| {
+            if let (Some(error), false) = (error, warned) {
+                self.logger.log_warn(&format!(
+                    "{}; the scheduler places the threads, so the sockets may not be measured apart",
+                    error
+                ));
+                warned = true;
+            }
+        };
+
+        for algorithm in algorithms {
+            Self::check_cancelled(cancel)?;
+            let workload = Workload::prepare(*algorithm, corpus.clone())?;
+            self.logger
+                .log_info(&format!("Running {} on CPU {}", algorithm.label(), core));
+            let (core_gb_per_sec, pin_error) =
+                Self::measure(&workload, &[core], step_duration, cancel)?;
+            warn_unpinned(pin_error);
+
+            let mut throughput = CodecThroughput {
+                algorithm: *algorithm,
+                acceleration: Self::acceleration(*algorithm),
+                core_gb_per_sec,
+                sockets: Vec::new(),
+                compression_ratio: workload.compression_ratio(),
+            };
+            for (socket, cpus) in &sockets {
+                Self::check_cancelled(cancel)?;
+                self.logger.log_info(&format!(
+                    "Running {} on the {} logical CPUs of socket {}",
+                    algorithm.label(),
+                    cpus.len(),
+                    socket
+                ));
+                let (gb_per_sec, pin_error) =
+                    Self::measure(&workload, cpus, step_duration, cancel)?;
+                warn_unpinned(pin_error);
+                throughput.sockets.push(SocketCodecThroughput {
+                    socket: *socket,
+                    threads: cpus.len(),
+                    gb_per_sec,
+                });
+            }
+            result.algorithms.push(throughput);
+        }
+
+        Self::check_cancelled(cancel)?;
+        Ok(result)
+    }
+}
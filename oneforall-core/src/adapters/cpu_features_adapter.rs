@@ -0,0 +1,193 @@
+//! CPU Features Adapter
+//!
+//! This module provides an adapter that discovers the CPU's instruction set
+//! extensions with CPUID on x86_64 (and the kernel's feature detection on
+//! aarch64), its microcode revision from `/proc/cpuinfo` or `sysctl`, and the
+//! speculative-execution mitigations from `/sys/devices/system/cpu/vulnerabilities`.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::process::Command;
+use std::sync::Arc;
+
+use common::ports::log_port::LoggerPort;
+
+use crate::domain::cpu_architecture::parse_cpuinfo;
+use crate::domain::cpu_features::{CpuFeatures, Vulnerability};
+use crate::ports::cpu_features_port::CpuFeaturesPort;
+
+/// Per-CPU identification, including the microcode revision, on Linux.
+const PROC_CPUINFO: &str = "/proc/cpuinfo";
+
+/// One file per vulnerability, holding the kernel's verdict on it.
+const SYSFS_VULNERABILITIES: &str = "/sys/devices/system/cpu/vulnerabilities";
+
+/// Represents the CPU features discovery adapter.
+pub struct CpuFeaturesAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl CpuFeaturesAdapter {
+    /// Creates a new instance of `CpuFeaturesAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `CpuFeaturesAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        CpuFeaturesAdapter { logger }
+    }
+
+    /// Reads the extension bits with CPUID and the OS-enabled state with XGETBV.
+    #[cfg(target_arch = "x86_64")]
+    fn read_extensions(&self) -> Vec<String> {
+        use std::arch::x86_64::{__cpuid, __cpuid_count, _xgetbv};
+
+        use crate::domain::cpu_features::CpuidRegisters;
+
+        /// Reads XCR0, the register state the OS saves on context switches.
+        #[target_feature(enable = "xsave")]
+        unsafe fn read_xcr0() -> u64 {
+            _xgetbv(0)
+        }
+
+        let max_leaf = __cpuid(0).eax;
+        let leaf1 = __cpuid(1);
+        let mut registers = CpuidRegisters {
+            leaf1_ecx: leaf1.ecx,
+            ..CpuidRegisters::default()
+        };
+        if max_leaf >= 7 {
+            let leaf7 = __cpuid_count(7, 0);
+            registers.leaf7_ebx = leaf7.ebx;
+            registers.leaf7_ecx = leaf7.ecx;
+            registers.leaf7_edx = leaf7.edx;
+            if leaf7.eax >= 1 {
+                registers.leaf7_1_eax = __cpuid_count(7, 1).eax;
+            }
+        }
+
+        // OSXSAVE (leaf 1, ECX bit 27) means the OS enabled XSAVE and XGETBV is usable.
+        if leaf1.ecx & (1 << 27) != 0 {
+            // SAFETY: OSXSAVE guarantees the CPU supports XGETBV and the OS enabled it.
+            registers.xcr0 = Some(unsafe { read_xcr0() });
+        } else {
+            self.logger
+                .log_debug("OSXSAVE is not set; AVX-class extensions are unusable");
+        }
+
+        registers.x86_extensions()
+    }
+
+    /// Reads the extensions the kernel reports through the auxiliary vector or `sysctl`.
+    #[cfg(target_arch = "aarch64")]
+    fn read_extensions(&self) -> Vec<String> {
+        use std::arch::is_aarch64_feature_detected;
+
+        [
+            (is_aarch64_feature_detected!("neon"), "NEON"),
+            (is_aarch64_feature_detected!("aes"), "AES"),
+            (is_aarch64_feature_detected!("sha2"), "SHA2"),
+            (is_aarch64_feature_detected!("crc"), "CRC32"),
+            (is_aarch64_feature_detected!("lse"), "LSE"),
+            (is_aarch64_feature_detected!("dotprod"), "DOTPROD"),
+            (is_aarch64_feature_detected!("fp16"), "FP16"),
+            (is_aarch64_feature_detected!("bf16"), "BF16"),
+            (is_aarch64_feature_detected!("i8mm"), "I8MM"),
+            (is_aarch64_feature_detected!("sve"), "SVE"),
+            (is_aarch64_feature_detected!("sve2"), "SVE2"),
+        ]
+        .iter()
+        .filter(|(detected, _)| *detected)
+        .map(|(_, name)| name.to_string())
+        .collect()
+    }
+
+    /// Reports no extensions on architectures without a detection method.
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    fn read_extensions(&self) -> Vec<String> {
+        self.logger
+            .log_debug("Instruction set extensions are not detected on this architecture");
+        Vec::new()
+    }
+
+    /// Reads the microcode revision of every CPU.
+    fn read_microcode(&self) -> BTreeSet<String> {
+        if cfg!(target_os = "macos") {
+            // Only Intel Macs report a microcode version.
+            return Command::new("sysctl")
+                .arg("-n")
+                .arg("machdep.cpu.microcode_version")
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+                .filter(|version| !version.is_empty())
+                .into_iter()
+                .collect();
+        }
+
+        match fs::read_to_string(PROC_CPUINFO) {
+            Ok(contents) => parse_cpuinfo(&contents)
+                .into_values()
+                .filter_map(|entry| entry.microcode)
+                .collect(),
+            Err(e) => {
+                self.logger
+                    .log_debug(&format!("Failed to read {}: {}", PROC_CPUINFO, e));
+                BTreeSet::new()
+            }
+        }
+    }
+
+    /// Reads the kernel's verdict on every known vulnerability.
+    fn read_vulnerabilities(&self) -> Vec<Vulnerability> {
+        let entries = match fs::read_dir(SYSFS_VULNERABILITIES) {
+            Ok(entries) => entries,
+            Err(_) => {
+                self.logger
+                    .log_debug("No vulnerability information exposed by sysfs");
+                return Vec::new();
+            }
+        };
+
+        let mut vulnerabilities: Vec<Vulnerability> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let status = fs::read_to_string(entry.path()).ok()?;
+                Some(Vulnerability {
+                    name: entry.file_name().to_string_lossy().to_string(),
+                    status: status.trim().to_string(),
+                })
+            })
+            .collect();
+        vulnerabilities.sort_by(|a, b| a.name.cmp(&b.name));
+        vulnerabilities
+    }
+}
+
+// Implement the `CpuFeaturesPort` trait for `CpuFeaturesAdapter`.
+impl CpuFeaturesPort for CpuFeaturesAdapter {
+    fn discover_cpu_features(&self) -> Result<CpuFeatures, String> {
+        let features = CpuFeatures {
+            extensions: self.read_extensions(),
+            microcode: self.read_microcode(),
+            vulnerabilities: self.read_vulnerabilities(),
+        };
+
+        if features.microcode.len() > 1 {
+            self.logger.log_warn(&format!(
+                "CPUs run different microcode revisions: {:?}",
+                features.microcode
+            ));
+        }
+        self.logger.log_debug(&format!(
+            "Discovered {} instruction set extension(s) and {} vulnerability report(s)",
+            features.extensions.len(),
+            features.vulnerabilities.len()
+        ));
+
+        Ok(features)
+    }
+}
@@ -0,0 +1,154 @@
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use sled::{Db, IVec};
+
+use common::ports::log_port::LoggerPort;
+
+use crate::adapters::sqlite_database_adapter::SqliteDatabaseAdapter;
+use crate::domain::database::{DatabaseBackend, DatabaseConfig};
+use crate::ports::database_port::DatabasePort;
+
+/// Reads the `[database]` section of the configuration file.
+///
+/// # Arguments
+///
+/// * `path` - The configuration file; a missing file means the default sled database.
+///
+/// # Returns
+///
+/// * `Result<DatabaseConfig, String>` - The database settings, or why they could not be read.
+pub fn load_database_config(path: &Path) -> Result<DatabaseConfig, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(DatabaseConfig::default()),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    DatabaseConfig::from_toml(&text).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+}
+
+/// Opens the database of the configured backend.
+///
+/// # Arguments
+///
+/// * `config` - The database settings.
+/// * `logger` - Logger implementation for logging messages.
+///
+/// # Returns
+///
+/// * `Result<Arc<dyn DatabasePort>, Box<dyn Error>>` - The database, or why it could not be opened.
+pub fn open_database(
+    config: &DatabaseConfig,
+    logger: Arc<dyn LoggerPort>,
+) -> Result<Arc<dyn DatabasePort>, Box<dyn Error>> {
+    let path = config.path();
+    Ok(match config.backend {
+        DatabaseBackend::Sled => Arc::new(DatabaseAdapter::new(&path.to_string_lossy(), logger)?),
+        DatabaseBackend::Sqlite => Arc::new(SqliteDatabaseAdapter::new(&path, logger)?),
+    })
+}
+
+/// A struct that serves as an adapter for the `DatabasePort` trait using the Sled embedded database.
+pub struct DatabaseAdapter {
+    db: Db, // The sled database instance.
+}
+
+/// Implement the DatabaseAdapter struct as a port.
+/// This allows the adapter to be used as a port in the application, and it
+/// also provides a concrete implementation of the `DatabasePort` interface.
+/// The `DatabasePort` trait is defined in the `database_port.rs` file.
+impl DatabaseAdapter {
+    /// Opens a new database or creates it if it doesn't exist, acting as an adapter.
+    pub fn new(path: &str, logger: Arc<dyn LoggerPort>) -> Result<Self, Box<dyn Error>> {
+        let db = sled::open(path)?;
+        logger.log_info(&format!("Database opened at path: {}", path));
+        Ok(DatabaseAdapter { db })
+    }
+}
+
+/// Implement the `DatabasePort` trait for the `DatabaseAdapter` struct.
+/// This allows the adapter to be used as a port in the application, and it
+/// also provides a concrete implementation of the `DatabasePort` interface.
+impl DatabasePort for DatabaseAdapter {
+    /// Inserts a key-value pair
+    fn insert(&self, key: &[u8], value: &[u8]) -> Result<Option<IVec>, Box<dyn Error>> {
+        let previous_value = self.db.insert(key, value)?;
+        self.db.flush()?; // Ensure that changes are written to disk through the adapter.
+        Ok(previous_value)
+    }
+
+    /// Inserts key-value pairs atomically, flushing once for the whole batch
+    fn insert_batch(&self, pairs: &[(Vec<u8>, Vec<u8>)]) -> Result<(), Box<dyn Error>> {
+        let mut batch = sled::Batch::default();
+        for (key, value) in pairs {
+            batch.insert(key.as_slice(), value.as_slice());
+        }
+        self.db.apply_batch(batch)?;
+        self.db.flush()?; // Ensure that changes are written to disk through the adapter.
+        Ok(())
+    }
+
+    /// Retrieves a value
+    fn get(&self, key: &[u8]) -> Result<Option<IVec>, Box<dyn Error>> {
+        let value = self.db.get(key)?;
+        Ok(value)
+    }
+
+    /// Removes a key-value pair
+    fn remove(&self, key: &[u8]) -> Result<Option<IVec>, Box<dyn Error>> {
+        let previous_value = self.db.remove(key)?;
+        self.db.flush()?; // Ensure that changes are written to disk through the adapter.
+        Ok(previous_value)
+    }
+
+    /// Retrieves the key-value pairs under a prefix, in key order
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<Vec<(IVec, IVec)>, Box<dyn Error>> {
+        let pairs = self.db.scan_prefix(prefix).collect::<Result<Vec<_>, _>>()?;
+        Ok(pairs)
+    }
+
+    /// Retrieves the key-value pairs from `start` up to but excluding `end`, in key order
+    fn scan_range(&self, start: &[u8], end: &[u8]) -> Result<Vec<(IVec, IVec)>, Box<dyn Error>> {
+        if start >= end {
+            return Ok(Vec::new());
+        }
+        let pairs = self.db.range(start..end).collect::<Result<Vec<_>, _>>()?;
+        Ok(pairs)
+    }
+
+    /// Removes the key-value pairs from `start` up to but excluding `end`, returning how many
+    fn remove_range(&self, start: &[u8], end: &[u8]) -> Result<usize, Box<dyn Error>> {
+        if start >= end {
+            return Ok(0);
+        }
+        let mut batch = sled::Batch::default();
+        let mut removed = 0;
+        for key in self.db.range(start..end).keys() {
+            batch.remove(key?);
+            removed += 1;
+        }
+        self.db.apply_batch(batch)?;
+        self.db.flush()?; // Ensure that changes are written to disk through the adapter.
+        Ok(removed)
+    }
+
+    /// Returns the space the database takes on disk, in bytes
+    fn size_on_disk(&self) -> Result<u64, Box<dyn Error>> {
+        Ok(self.db.size_on_disk()?)
+    }
+
+    /// Writes pending changes to disk; sled reclaims the space of removed pairs
+    /// by itself as it rewrites its segments, so there is nothing to vacuum
+    fn compact(&self) -> Result<(), Box<dyn Error>> {
+        self.db.flush()?;
+        Ok(())
+    }
+
+    /// Writes everything still buffered to disk, e.g. before the application exits
+    fn flush(&self) -> Result<(), Box<dyn Error>> {
+        self.db.flush()?;
+        Ok(())
+    }
+}
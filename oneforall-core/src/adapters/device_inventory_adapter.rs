@@ -0,0 +1,132 @@
+//! Device Inventory Adapter
+//!
+//! This module provides an adapter that lists the disks and network interfaces
+//! of a machine from sysfs and reads its memory from `/proc/meminfo`, for
+//! checking the machine against its hardware manifest. Disks are the entries
+//! of `/sys/block` backed by a device, which leaves out loop, RAID and
+//! device-mapper devices; network interfaces are the entries of
+//! `/sys/class/net` backed by a device, which leaves out bridges, bonds and
+//! other virtual interfaces.
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use common::ports::log_port::LoggerPort;
+
+use crate::adapters::sysfs::read_sysfs;
+use crate::domain::hardware_manifest::{DiskInfo, HardwareManifest, NicInfo};
+use crate::ports::device_inventory_port::DeviceInventoryPort;
+
+/// The size of the sectors sysfs counts block device sizes in, whatever the
+/// logical block size of the device.
+const SYSFS_SECTOR_SIZE: u64 = 512;
+
+/// Reads a hardware manifest.
+///
+/// # Arguments
+///
+/// * `path` - The TOML manifest given with `discover --expect`.
+///
+/// # Returns
+///
+/// * `Result<HardwareManifest, String>` - The manifest, or why it could not be read.
+pub fn load_hardware_manifest(path: &Path) -> Result<HardwareManifest, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    HardwareManifest::from_toml(&text).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+}
+
+/// Represents the sysfs and procfs device inventory adapter.
+pub struct DeviceInventoryAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl DeviceInventoryAdapter {
+    /// Creates a new instance of `DeviceInventoryAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `DeviceInventoryAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        DeviceInventoryAdapter { logger }
+    }
+
+    /// Lists the entries of a sysfs directory that are backed by a device, sorted by name.
+    fn device_entries(directory: &str) -> Result<Vec<String>, String> {
+        let mut names: Vec<String> = fs::read_dir(directory)
+            .map_err(|e| format!("Failed to read {}: {}", directory, e))?
+            .flatten()
+            .filter(|entry| entry.path().join("device").exists())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+}
+
+// Implement the `DeviceInventoryPort` trait for `DeviceInventoryAdapter`.
+impl DeviceInventoryPort for DeviceInventoryAdapter {
+    fn list_disks(&self) -> Result<Vec<DiskInfo>, String> {
+        let mut disks = Vec::new();
+        for name in Self::device_entries("/sys/block")? {
+            // Optical drives are block devices too, but not disks.
+            if name.starts_with("sr") {
+                continue;
+            }
+            let path = Path::new("/sys/block").join(&name);
+            let sectors: u64 = read_sysfs(path.join("size"))
+                .and_then(|size| size.parse().ok())
+                .unwrap_or(0);
+            disks.push(DiskInfo {
+                model: read_sysfs(path.join("device/model")).unwrap_or_default(),
+                size_bytes: sectors * SYSFS_SECTOR_SIZE,
+                name,
+            });
+        }
+        self.logger
+            .log_debug(&format!("Found {} disks", disks.len()));
+        Ok(disks)
+    }
+
+    fn list_nics(&self) -> Result<Vec<NicInfo>, String> {
+        let mut nics = Vec::new();
+        for name in Self::device_entries("/sys/class/net")? {
+            let path = Path::new("/sys/class/net").join(&name);
+            let driver = fs::read_link(path.join("device/driver"))
+                .ok()
+                .and_then(|link| {
+                    link.file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                })
+                .unwrap_or_default();
+            // The kernel reports -1, or fails the read, while the link is down.
+            let speed_mbps = read_sysfs(path.join("speed"))
+                .and_then(|speed| speed.parse::<i64>().ok())
+                .filter(|speed| *speed > 0)
+                .map(|speed| speed as u64);
+            nics.push(NicInfo {
+                name,
+                driver,
+                speed_mbps,
+            });
+        }
+        self.logger
+            .log_debug(&format!("Found {} network interfaces", nics.len()));
+        Ok(nics)
+    }
+
+    fn read_memory_total(&self) -> Result<u64, String> {
+        let meminfo = fs::read_to_string("/proc/meminfo")
+            .map_err(|e| format!("Failed to read /proc/meminfo: {}", e))?;
+        meminfo
+            .lines()
+            .find(|line| line.starts_with("MemTotal:"))
+            .and_then(|line| line.split_whitespace().nth(1))
+            .and_then(|kb| kb.parse::<u64>().ok())
+            .map(|kb| kb * 1024)
+            .ok_or_else(|| "No MemTotal in /proc/meminfo".to_string())
+    }
+}
@@ -0,0 +1,232 @@
+//! Dynamic Plugin Adapter
+//!
+//! This module loads benchmark and stress test plugins built as shared
+//! libraries, in any language that can export C functions. A plugin exports
+//! `oneforall_plugin_v1`, which returns a `PluginDescriptor`: its name,
+//! description and resources, and the functions that run it and free what it
+//! returns. Everything crossing the boundary is a NUL-terminated UTF-8 string,
+//! the arguments and the output as JSON, so the ABI stays stable as the Rust
+//! types change. Libraries stay loaded for the life of the process, since the
+//! descriptor and its strings belong to them.
+
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use common::domain::job::Resource;
+use common::ports::log_port::LoggerPort;
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::plugin::{parse_plugin_response, validate_plugin_name, PluginOutput};
+use crate::ports::benchmark_plugin_port::BenchmarkPluginPort;
+
+/// The version of the plugin ABI this build loads.
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+/// The function a plugin library exports, returning its `PluginDescriptor`.
+pub const PLUGIN_ENTRY_POINT: &str = "oneforall_plugin_v1";
+
+/// Tells a running plugin whether its job was cancelled, given the context passed to `run`.
+pub type IsCancelledFn = extern "C" fn(context: *const c_void) -> bool;
+
+/// What a plugin library describes itself with. The layout is the stable C
+/// ABI of version 1; the strings are NUL-terminated UTF-8 owned by the library.
+#[repr(C)]
+pub struct PluginDescriptor {
+    /// Must be `PLUGIN_ABI_VERSION`.
+    pub abi_version: u32,
+
+    /// The subcommand the plugin runs as, e.g. "fpga-loopback".
+    pub name: *const c_char,
+
+    /// One line describing the plugin; may be null.
+    pub description: *const c_char,
+
+    /// The resources the plugin loads as a JSON array, e.g. `["cpu", "memory"]`; may be null.
+    pub resources: *const c_char,
+
+    /// Runs the plugin with its arguments as a JSON array of strings, polling
+    /// `is_cancelled(context)` to stop early. Returns a `PluginOutput` as
+    /// JSON, or `{"error": "..."}`, allocated by the library.
+    pub run: extern "C" fn(
+        args: *const c_char,
+        is_cancelled: IsCancelledFn,
+        context: *const c_void,
+    ) -> *mut c_char,
+
+    /// Frees a string returned by `run`.
+    pub free_string: extern "C" fn(text: *mut c_char),
+}
+
+/// The function a plugin library exports.
+type EntryPoint = extern "C" fn() -> *const PluginDescriptor;
+
+/// Represents a plugin loaded from a shared library.
+pub struct DynamicPluginAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    path: PathBuf,
+    descriptor: &'static PluginDescriptor,
+    name: String,
+    description: String,
+    resources: Vec<Resource>,
+}
+
+// SAFETY: the plugin ABI requires `run` and `free_string` to be callable from
+// any thread, and the descriptor is never written to and outlives the adapter.
+unsafe impl Send for DynamicPluginAdapter {}
+unsafe impl Sync for DynamicPluginAdapter {}
+
+/// Returns why the last `dlopen` or `dlsym` failed.
+fn loader_error() -> String {
+    // SAFETY: `dlerror` returns null or a NUL-terminated message owned by the loader.
+    let message = unsafe { libc::dlerror() };
+    if message.is_null() {
+        "unknown error".to_string()
+    } else {
+        // SAFETY: checked for null above.
+        unsafe { CStr::from_ptr(message) }
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+/// Reads a string of the descriptor, or `None` if the plugin left it null.
+fn read_string(text: *const c_char, field: &str) -> Result<Option<String>, String> {
+    if text.is_null() {
+        return Ok(None);
+    }
+    // SAFETY: the ABI requires a NUL-terminated string that lives as long as the library.
+    unsafe { CStr::from_ptr(text) }
+        .to_str()
+        .map(|text| Some(text.to_string()))
+        .map_err(|e| format!("Invalid {}: {}", field, e))
+}
+
+/// Reports whether a job was cancelled; the context is its `CancellationToken`.
+extern "C" fn is_cancelled(context: *const c_void) -> bool {
+    // SAFETY: `run` passes a token that outlives the call it is polled during.
+    unsafe { &*(context as *const CancellationToken) }.is_cancelled()
+}
+
+impl DynamicPluginAdapter {
+    /// Loads a plugin from a shared library.
+    ///
+    /// Loading a library runs its initializers with the privileges of
+    /// OneForAll, so only libraries as trusted as the binary should be listed.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    /// * `path` - The shared library, e.g. "/usr/lib/oneforall/plugins/libfpga.so".
+    ///
+    /// # Returns
+    /// A `Result` containing the plugin, or why it could not be loaded.
+    pub fn load(logger: Arc<dyn LoggerPort>, path: &Path) -> Result<Self, String> {
+        let library = CString::new(path.as_os_str().as_bytes())
+            .map_err(|e| format!("Invalid path {}: {}", path.display(), e))?;
+        // SAFETY: the path is NUL-terminated; the library's initializers are trusted.
+        let handle = unsafe { libc::dlopen(library.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+        if handle.is_null() {
+            return Err(format!(
+                "Failed to load {}: {}",
+                path.display(),
+                loader_error()
+            ));
+        }
+
+        let descriptor = Self::describe(handle).and_then(|descriptor| {
+            let name = read_string(descriptor.name, "name")?
+                .ok_or_else(|| "The plugin has no name".to_string())?;
+            validate_plugin_name(&name)?;
+            let description = read_string(descriptor.description, "description")?;
+            let resources: Vec<Resource> = match read_string(descriptor.resources, "resources")? {
+                Some(resources) => serde_json::from_str(&resources)
+                    .map_err(|e| format!("Invalid resources '{}': {}", resources, e))?,
+                None => Vec::new(),
+            };
+            Ok((descriptor, name, description.unwrap_or_default(), resources))
+        });
+        match descriptor {
+            Ok((descriptor, name, description, resources)) => Ok(DynamicPluginAdapter {
+                logger,
+                path: path.to_path_buf(),
+                descriptor,
+                name,
+                description,
+                resources,
+            }),
+            Err(e) => {
+                // SAFETY: nothing the library returned is kept.
+                unsafe { libc::dlclose(handle) };
+                Err(format!("{}: {}", path.display(), e))
+            }
+        }
+    }
+
+    /// Calls the entry point of a loaded library and checks the ABI version of its descriptor.
+    fn describe(handle: *mut c_void) -> Result<&'static PluginDescriptor, String> {
+        let symbol = CString::new(PLUGIN_ENTRY_POINT).map_err(|e| e.to_string())?;
+        // SAFETY: the handle is open and the symbol name is NUL-terminated.
+        let entry = unsafe { libc::dlsym(handle, symbol.as_ptr()) };
+        if entry.is_null() {
+            return Err(format!(
+                "No {} function: {}",
+                PLUGIN_ENTRY_POINT,
+                loader_error()
+            ));
+        }
+        // SAFETY: the ABI defines the exported function as an `EntryPoint`.
+        let entry = unsafe { std::mem::transmute::<*mut c_void, EntryPoint>(entry) };
+        // SAFETY: the descriptor lives as long as the library, which is never unloaded once loaded.
+        let descriptor = unsafe { entry().as_ref() }
+            .ok_or_else(|| "The plugin returned no descriptor".to_string())?;
+        if descriptor.abi_version != PLUGIN_ABI_VERSION {
+            return Err(format!(
+                "Built for plugin ABI version {}; this version of OneForAll loads version {}",
+                descriptor.abi_version, PLUGIN_ABI_VERSION
+            ));
+        }
+        Ok(descriptor)
+    }
+}
+
+// Implement the `BenchmarkPluginPort` trait for `DynamicPluginAdapter`.
+impl BenchmarkPluginPort for DynamicPluginAdapter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn resources(&self) -> &[Resource] {
+        &self.resources
+    }
+
+    fn run(&self, args: &[String], cancel: &CancellationToken) -> Result<PluginOutput, String> {
+        let args = serde_json::to_string(args)
+            .ok()
+            .and_then(|args| CString::new(args).ok())
+            .ok_or_else(|| "Arguments must not contain NUL characters".to_string())?;
+        self.logger.log_debug(&format!(
+            "Running plugin {} from {}",
+            self.name,
+            self.path.display()
+        ));
+        let response = (self.descriptor.run)(
+            args.as_ptr(),
+            is_cancelled,
+            cancel as *const CancellationToken as *const c_void,
+        );
+        if response.is_null() {
+            return Err(format!("Plugin {} returned no result", self.name));
+        }
+        // SAFETY: `run` returns a NUL-terminated string, freed by the plugin just below.
+        let response_text = unsafe { CStr::from_ptr(response) }
+            .to_string_lossy()
+            .into_owned();
+        (self.descriptor.free_string)(response);
+        parse_plugin_response(&response_text)
+    }
+}
@@ -0,0 +1,169 @@
+//! eBPF Latency Adapter
+//!
+//! This module measures run queue latency, off-CPU time and block I/O latency
+//! with the eBPF probes of `bpf/kernel_latency.bpf.c`, loaded with aya. The
+//! probes hook the scheduler and block layer tracepoints and time every event
+//! in the kernel, counting them in log2 histograms that are read once when the
+//! run ends, so the cost while the machine is under load is a few map updates
+//! per context switch rather than any work in user space.
+//!
+//! The probes are compiled by `build.rs` with clang only when OneForAll is
+//! built with the `ebpf` feature; without it the adapter reports that eBPF is
+//! not available. Loading them needs root, or `CAP_BPF` and `CAP_PERFMON`, on
+//! Linux 4.18 or newer.
+
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use common::ports::log_port::LoggerPort;
+
+use crate::domain::kernel_latency::KernelLatencyReport;
+use crate::ports::kernel_latency_port::KernelLatencyPort;
+
+/// The tracepoints the probes attach to, each handled by the program of its name.
+#[cfg(feature = "ebpf")]
+const TRACEPOINTS: [(&str, &str); 5] = [
+    ("sched", "sched_wakeup"),
+    ("sched", "sched_wakeup_new"),
+    ("sched", "sched_switch"),
+    ("block", "block_rq_issue"),
+    ("block", "block_rq_complete"),
+];
+
+/// The loaded probes, detached when dropped.
+#[cfg(feature = "ebpf")]
+type Loaded = aya::Ebpf;
+
+/// Stands in for the probes in builds without the `ebpf` feature.
+#[cfg(not(feature = "ebpf"))]
+struct Loaded;
+
+/// The probes attached by `start`.
+struct Probes {
+    loaded: Loaded,
+    started: Instant,
+}
+
+/// Loads the probes and attaches them to their tracepoints.
+#[cfg(feature = "ebpf")]
+fn load() -> Result<Loaded, String> {
+    use aya::programs::TracePoint;
+
+    // Kernels before 5.11 charge eBPF maps to the locked memory limit.
+    let unlimited = libc::rlimit {
+        rlim_cur: libc::RLIM_INFINITY,
+        rlim_max: libc::RLIM_INFINITY,
+    };
+    // SAFETY: `unlimited` is a valid `rlimit` that outlives the call.
+    unsafe { libc::setrlimit(libc::RLIMIT_MEMLOCK, &unlimited) };
+
+    let mut ebpf = aya::Ebpf::load(aya::include_bytes_aligned!(concat!(
+        env!("OUT_DIR"),
+        "/kernel_latency.bpf.o"
+    )))
+    .map_err(|e| {
+        format!(
+            "Failed to load the eBPF latency probes, which need root or CAP_BPF and CAP_PERFMON: {}",
+            e
+        )
+    })?;
+    for (category, name) in TRACEPOINTS {
+        let program: &mut TracePoint = ebpf
+            .program_mut(name)
+            .ok_or_else(|| format!("The eBPF latency probes have no {} program", name))?
+            .try_into()
+            .map_err(|e| format!("Invalid eBPF program {}: {}", name, e))?;
+        program
+            .load()
+            .map_err(|e| format!("Failed to load the eBPF program {}: {}", name, e))?;
+        program.attach(category, name).map_err(|e| {
+            format!(
+                "Failed to attach to the {}:{} tracepoint: {}",
+                category, name, e
+            )
+        })?;
+    }
+    Ok(ebpf)
+}
+
+/// Reports that the probes are not available in this build.
+#[cfg(not(feature = "ebpf"))]
+fn load() -> Result<Loaded, String> {
+    Err("OneForAll was built without eBPF support; rebuild it with `--features ebpf`".to_string())
+}
+
+/// Reads the buckets of every histogram, one histogram after another.
+#[cfg(feature = "ebpf")]
+fn read_counts(loaded: &Loaded) -> Result<Vec<u64>, String> {
+    use aya::maps::Array;
+
+    let map = loaded
+        .map("histograms")
+        .ok_or("The eBPF latency probes have no histograms map")?;
+    let histograms: Array<_, u64> =
+        Array::try_from(map).map_err(|e| format!("Invalid histograms map: {}", e))?;
+    (0..histograms.len())
+        .map(|index| {
+            histograms
+                .get(&index, 0)
+                .map_err(|e| format!("Failed to read the latency histograms: {}", e))
+        })
+        .collect()
+}
+
+/// Reads nothing, as no probes are loaded in this build.
+#[cfg(not(feature = "ebpf"))]
+fn read_counts(_loaded: &Loaded) -> Result<Vec<u64>, String> {
+    Ok(Vec::new())
+}
+
+/// Represents the eBPF kernel latency adapter.
+pub struct EbpfLatencyAdapter {
+    logger: Arc<dyn LoggerPort>,   // inject the logger port
+    probes: Mutex<Option<Probes>>, // the probes attached by `start`
+}
+
+impl EbpfLatencyAdapter {
+    /// Creates a new instance of `EbpfLatencyAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `EbpfLatencyAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        EbpfLatencyAdapter {
+            logger,
+            probes: Mutex::new(None),
+        }
+    }
+}
+
+// Implement the `KernelLatencyPort` trait for `EbpfLatencyAdapter`.
+impl KernelLatencyPort for EbpfLatencyAdapter {
+    fn start(&self) -> Result<(), String> {
+        let loaded = load()?;
+        *self.probes.lock().map_err(|e| e.to_string())? = Some(Probes {
+            loaded,
+            started: Instant::now(),
+        });
+        self.logger
+            .log_info("Measuring run queue, off-CPU and block I/O latency with eBPF for the run.");
+        Ok(())
+    }
+
+    fn finish(&self) -> Result<KernelLatencyReport, String> {
+        let probes = self
+            .probes
+            .lock()
+            .map_err(|e| e.to_string())?
+            .take()
+            .ok_or("The eBPF latency probes were not attached")?;
+        let counts = read_counts(&probes.loaded)?;
+        // Dropping the probes detaches them from the tracepoints.
+        Ok(KernelLatencyReport::from_counts(
+            &counts,
+            probes.started.elapsed(),
+        ))
+    }
+}
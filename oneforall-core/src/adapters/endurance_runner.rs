@@ -0,0 +1,217 @@
+//! Endurance Runner
+//!
+//! This module runs the storage endurance test started by `oneforall endurance`.
+//! The write and verify workload runs on a blocking thread while the run
+//! monitor records telemetry, and the drive's SMART wear indicators are read
+//! before and after the workload to estimate write amplification. On NVMe
+//! drives the error log is watched as well.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+use chrono::Local;
+use common::domain::job::{JobSpec, JobStatus, Resource};
+use common::ports::job_port::JobPort;
+use common::ports::log_port::LoggerPort;
+use common::ports::telemetry_port::TelemetryPort;
+
+use crate::adapters::hardware_error_adapter::HardwareErrorAdapter;
+use crate::adapters::nvme_adapter::NvmeAdapter;
+use crate::adapters::run_monitor::{RunMonitor, DEFAULT_SAMPLE_INTERVAL};
+use crate::adapters::storage_endurance_adapter::StorageEnduranceAdapter;
+use crate::domain::burn_in::WorkloadOutcome;
+use crate::domain::endurance::{format_volume, EnduranceReport};
+use crate::domain::nvme::{nvme_controller, NvmeHealth};
+use crate::ports::hardware_error_port::HardwareErrorPort;
+use crate::ports::nvme_port::NvmePort;
+use crate::ports::storage_endurance_port::StorageEndurancePort;
+
+/// Runs storage endurance tests.
+pub struct EnduranceRunner {
+    logger: Arc<dyn LoggerPort>,
+    telemetry: Arc<dyn TelemetryPort>,
+    jobs: Arc<dyn JobPort>,
+    errors: Box<dyn HardwareErrorPort>,
+    nvme: Box<dyn NvmePort>,
+    storage: Arc<dyn StorageEndurancePort>,
+}
+
+impl EnduranceRunner {
+    /// Creates a new instance of `EnduranceRunner`.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger` - Logger implementation for logging messages.
+    /// * `telemetry` - Receives live readings while the test runs.
+    /// * `jobs` - The job queue the test is submitted to.
+    ///
+    /// # Returns
+    ///
+    /// * `EnduranceRunner` - A runner writing to the drives of this machine.
+    pub fn new(
+        logger: Arc<dyn LoggerPort>,
+        telemetry: Arc<dyn TelemetryPort>,
+        jobs: Arc<dyn JobPort>,
+    ) -> Self {
+        EnduranceRunner {
+            errors: Box::new(HardwareErrorAdapter::new(logger.clone())),
+            nvme: Box::new(NvmeAdapter::new(logger.clone())),
+            storage: Arc::new(StorageEnduranceAdapter::new(logger.clone())),
+            logger,
+            telemetry,
+            jobs,
+        }
+    }
+
+    /// Reads the NVMe health of the controller of a drive.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<NvmeHealth>` - The health, or `None` if the drive is not an NVMe
+    ///   namespace or the log pages could not be read.
+    fn read_nvme_health(&self, device: &str) -> Option<NvmeHealth> {
+        let controller = nvme_controller(device)?;
+        self.nvme
+            .read_nvme_health(&controller)
+            .map_err(|e| {
+                self.logger
+                    .log_warn(&format!("NVMe health will not be tracked: {}", e))
+            })
+            .ok()
+    }
+
+    /// Runs the endurance test.
+    ///
+    /// The test is queued as a disk job, so it waits for any other disk
+    /// workload, whose writes would otherwise be counted as its own.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The directory to write the test files to.
+    /// * `total_bytes` - The volume to write.
+    /// * `file_bytes` - The size of each test file.
+    /// * `device` - The drive to track wear on; found from `path` when `None`.
+    ///
+    /// # Returns
+    ///
+    /// * `EnduranceReport` - The pass/fail report of the test.
+    pub async fn run(
+        &self,
+        path: &Path,
+        total_bytes: u64,
+        file_bytes: u64,
+        device: Option<String>,
+    ) -> EnduranceReport {
+        let mut ticket = self
+            .jobs
+            .submit_job(JobSpec::new("storage:endurance", &[Resource::Disk]));
+        let job_id = ticket.job.id;
+        let mut report = EnduranceReport {
+            started_at: Local::now().to_rfc3339(),
+            path: path.display().to_string(),
+            device: None,
+            planned_bytes: total_bytes,
+            written_bytes: 0,
+            verified_bytes: 0,
+            corrupt_blocks: 0,
+            mismatches: Vec::new(),
+            elapsed_secs: 0,
+            outcome: WorkloadOutcome::Cancelled,
+            wear_before: Err("not read".to_string()),
+            wear_after: Err("not read".to_string()),
+            kernel_faults: Vec::new(),
+            nvme_before: None,
+            nvme_after: None,
+        };
+        if ticket.job.status == JobStatus::Queued {
+            self.logger.log_info(&format!(
+                "Endurance job {} queued behind a conflicting job.",
+                job_id
+            ));
+        }
+        if !ticket.admitted().await {
+            self.logger
+                .log_warn("Endurance test cancelled while queued.");
+            return report;
+        }
+
+        let device = match device {
+            Some(device) => Ok(device),
+            None => self.storage.backing_device(path),
+        };
+        match &device {
+            Ok(device) => {
+                report.device = Some(device.clone());
+                report.wear_before = self.errors.read_wear(device);
+                report.nvme_before = self.read_nvme_health(device);
+            }
+            Err(e) => {
+                self.logger
+                    .log_warn(&format!("Wear will not be tracked: {}", e));
+                report.wear_before = Err(e.clone());
+            }
+        }
+
+        self.logger.log_info(&format!(
+            "Started endurance job {}: writing {} to {} in files of {}.",
+            job_id,
+            format_volume(total_bytes),
+            path.display(),
+            format_volume(file_bytes)
+        ));
+        let run_monitor = RunMonitor::start(
+            self.logger.clone(),
+            self.telemetry.clone(),
+            DEFAULT_SAMPLE_INTERVAL,
+        );
+        let started = Instant::now();
+        let storage = self.storage.clone();
+        let directory = PathBuf::from(path);
+        let cancel = ticket.cancel.clone();
+        let outcome = tokio::task::spawn_blocking(move || {
+            storage.write_and_verify(&directory, total_bytes, file_bytes, &cancel)
+        })
+        .await
+        .unwrap_or_else(|e| Err(format!("Endurance workload panicked: {}", e)));
+        report.elapsed_secs = started.elapsed().as_secs();
+        let run_telemetry = run_monitor.finish();
+        print!("{}", run_telemetry.report);
+        report.kernel_faults = run_telemetry.kernel_log.faults();
+
+        if let Ok(device) = &device {
+            report.wear_after = self.errors.read_wear(device);
+            report.nvme_after = self.read_nvme_health(device);
+        } else {
+            report.wear_after = report.wear_before.clone();
+        }
+
+        let (outcome, status, message) = match outcome {
+            Ok(result) => {
+                report.written_bytes = result.written_bytes;
+                report.verified_bytes = result.verified_bytes;
+                report.corrupt_blocks = result.corrupt_blocks;
+                report.mismatches = result.mismatches;
+                if result.cancelled {
+                    (WorkloadOutcome::Cancelled, JobStatus::Cancelled, None)
+                } else {
+                    (WorkloadOutcome::Completed, JobStatus::Completed, None)
+                }
+            }
+            Err(e) => (
+                WorkloadOutcome::Failed(e.clone()),
+                JobStatus::Failed,
+                Some(e),
+            ),
+        };
+        report.outcome = outcome;
+        let status = if status == JobStatus::Completed && !report.passed() {
+            JobStatus::Failed
+        } else {
+            status
+        };
+        self.jobs.finish_job(job_id, status, message);
+
+        report
+    }
+}
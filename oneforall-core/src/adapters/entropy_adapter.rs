@@ -0,0 +1,213 @@
+//! Entropy Adapter
+//!
+//! This module provides an adapter that checks the random number sources of
+//! the machine. On x86 it calls RDRAND and RDSEED in a tight loop on the
+//! current core for a fraction of a second, counting the values returned, the
+//! calls that failed and whether any two values differed. On Linux it reads
+//! the kernel entropy pool and hardware RNG device from procfs and sysfs, and
+//! asks `getrandom` for a byte without blocking to tell whether the CRNG is
+//! initialized.
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::ports::log_port::LoggerPort;
+
+use crate::domain::entropy::{EntropyState, RngInstruction};
+use crate::ports::entropy_port::EntropyPort;
+
+/// How long each CPU instruction is called for.
+const INSTRUCTION_CHECK_TIME: Duration = Duration::from_millis(50);
+
+/// Directory of the kernel entropy pool.
+const PROC_RANDOM_ROOT: &str = "/proc/sys/kernel/random";
+
+/// Directory of the hardware RNG device feeding the kernel pool.
+const SYSFS_HWRNG_ROOT: &str = "/sys/class/misc/hw_random";
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::arch::x86_64::{_rdrand64_step, _rdseed64_step};
+    use std::time::{Duration, Instant};
+
+    /// The values an instruction returned during a check.
+    pub struct Draw {
+        pub values: u64,
+        pub failures: u64,
+        pub varied: bool,
+        pub elapsed: Duration,
+    }
+
+    /// Calls a random number instruction until the time runs out.
+    fn draw(duration: Duration, step: impl Fn(&mut u64) -> bool) -> Draw {
+        let started = Instant::now();
+        let (mut values, mut failures, mut first, mut varied) = (0u64, 0u64, None, false);
+        while started.elapsed() < duration {
+            // Check the clock every 1024 calls rather than on each one.
+            for _ in 0..1024 {
+                let mut value = 0;
+                if step(&mut value) {
+                    values += 1;
+                    match first {
+                        None => first = Some(value),
+                        Some(first) => varied |= value != first,
+                    }
+                } else {
+                    failures += 1;
+                }
+            }
+        }
+        Draw {
+            values,
+            failures,
+            varied,
+            elapsed: started.elapsed(),
+        }
+    }
+
+    /// Calls RDRAND for the given time, if the CPU supports it.
+    pub fn draw_rdrand(duration: Duration) -> Option<Draw> {
+        is_x86_feature_detected!("rdrand").then(|| {
+            // SAFETY: the CPU was just found to support RDRAND.
+            draw(duration, |value| unsafe { _rdrand64_step(value) } == 1)
+        })
+    }
+
+    /// Calls RDSEED for the given time, if the CPU supports it.
+    pub fn draw_rdseed(duration: Duration) -> Option<Draw> {
+        is_x86_feature_detected!("rdseed").then(|| {
+            // SAFETY: the CPU was just found to support RDSEED.
+            draw(duration, |value| unsafe { _rdseed64_step(value) } == 1)
+        })
+    }
+}
+
+/// Represents the entropy adapter.
+pub struct EntropyAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl EntropyAdapter {
+    /// Creates a new instance of `EntropyAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `EntropyAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        EntropyAdapter { logger }
+    }
+
+    /// Reads a procfs or sysfs attribute and returns its trimmed contents.
+    fn read_attribute(path: &Path) -> Option<String> {
+        fs::read_to_string(path)
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    }
+
+    /// Checks RDRAND and RDSEED.
+    #[cfg(target_arch = "x86_64")]
+    fn check_instructions() -> Vec<RngInstruction> {
+        let result = |name: &str, draw: Option<x86::Draw>| {
+            let mut instruction = RngInstruction {
+                name: name.to_string(),
+                supported: draw.is_some(),
+                mb_per_sec: None,
+                failure_percent: None,
+                stuck: false,
+            };
+            if let Some(draw) = draw {
+                let calls = draw.values + draw.failures;
+                if draw.values > 0 {
+                    instruction.mb_per_sec =
+                        Some(draw.values as f64 * 8.0 / draw.elapsed.as_secs_f64().max(1e-9) / 1e6);
+                }
+                if calls > 0 {
+                    instruction.failure_percent = Some(draw.failures as f64 / calls as f64 * 100.0);
+                }
+                instruction.stuck = draw.values > 1 && !draw.varied;
+            }
+            instruction
+        };
+
+        vec![
+            result("RDRAND", x86::draw_rdrand(INSTRUCTION_CHECK_TIME)),
+            result("RDSEED", x86::draw_rdseed(INSTRUCTION_CHECK_TIME)),
+        ]
+    }
+
+    /// Other architectures have no random number instruction OneForAll checks.
+    #[cfg(not(target_arch = "x86_64"))]
+    fn check_instructions() -> Vec<RngInstruction> {
+        Vec::new()
+    }
+
+    /// Asks `getrandom` for a byte without blocking, which fails with `EAGAIN`
+    /// until the CRNG is initialized.
+    #[cfg(target_os = "linux")]
+    fn crng_ready() -> Option<bool> {
+        let mut byte = 0u8;
+        // SAFETY: the buffer is one valid, writable byte.
+        let read = unsafe {
+            libc::getrandom(
+                &mut byte as *mut u8 as *mut libc::c_void,
+                1,
+                libc::GRND_NONBLOCK,
+            )
+        };
+        match read {
+            1 => Some(true),
+            _ if std::io::Error::last_os_error().raw_os_error() == Some(libc::EAGAIN) => {
+                Some(false)
+            }
+            _ => None,
+        }
+    }
+
+    /// Only Linux reports the state of its CRNG.
+    #[cfg(not(target_os = "linux"))]
+    fn crng_ready() -> Option<bool> {
+        None
+    }
+}
+
+// Implement the `EntropyPort` trait for `EntropyAdapter`.
+impl EntropyPort for EntropyAdapter {
+    fn check_entropy(&self) -> Result<EntropyState, String> {
+        let random = Path::new(PROC_RANDOM_ROOT);
+        let number = |name: &str| {
+            Self::read_attribute(&random.join(name)).and_then(|value| value.parse().ok())
+        };
+        let hwrng = Path::new(SYSFS_HWRNG_ROOT);
+
+        let state = EntropyState {
+            instructions: Self::check_instructions(),
+            crng_ready: Self::crng_ready(),
+            entropy_bits: number("entropy_avail"),
+            pool_size_bits: number("poolsize"),
+            // The kernel reports "none" when no device is selected.
+            hwrng_current: Self::read_attribute(&hwrng.join("rng_current"))
+                .filter(|current| current != "none"),
+            hwrng_available: Self::read_attribute(&hwrng.join("rng_available"))
+                .map(|available| {
+                    available
+                        .split_whitespace()
+                        .filter(|device| *device != "none")
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        };
+        self.logger.log_trace(&format!(
+            "Checked {} random number instructions, CRNG ready {:?}, hardware RNG {:?}",
+            state.instructions.len(),
+            state.crng_ready,
+            state.hwrng_current
+        ));
+        Ok(state)
+    }
+}
@@ -0,0 +1,233 @@
+//! Filesystem Benchmark Adapter
+//!
+//! This module provides an adapter that benchmarks a filesystem through the
+//! standard library's file API: many small files are created, stat'ed and
+//! deleted, small blocks are written at random offsets of a file opened for
+//! synchronous writes, and blocks are appended and fsync'ed one at a time.
+//! Everything happens in a scratch directory that is removed afterwards.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::{FileExt, OpenOptionsExt};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use common::ports::log_port::LoggerPort;
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::filesystem::{FilesystemBenchmarkResult, LatencyStats, OperationRate};
+use crate::ports::filesystem_benchmark_port::FilesystemBenchmarkPort;
+
+/// The size of each small file, random write and fsync'ed append.
+const BLOCK_BYTES: usize = 4096;
+
+/// The size of the file the random writes land in.
+const RANDOM_WRITE_FILE_BYTES: u64 = 64 * 1024 * 1024;
+
+/// How long random writes are issued for.
+const RANDOM_WRITE_DURATION: Duration = Duration::from_secs(5);
+
+/// The number of appends whose fsync is timed.
+const FSYNC_SAMPLES: usize = 1000;
+
+/// How long fsyncs are timed for at most, on drives too slow for every sample.
+const FSYNC_MAX_DURATION: Duration = Duration::from_secs(10);
+
+/// The prefix of the scratch directory name.
+const SCRATCH_DIR_PREFIX: &str = "oneforall-fsbench";
+
+/// Represents the standard library filesystem benchmark adapter.
+pub struct FilesystemBenchmarkAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl FilesystemBenchmarkAdapter {
+    /// Creates a new instance of `FilesystemBenchmarkAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `FilesystemBenchmarkAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        FilesystemBenchmarkAdapter { logger }
+    }
+
+    /// Returns the data one run of the benchmark writes, for `--dry-run`: the
+    /// small files, the random write file, whose timed rewrites stay within it,
+    /// and the fsync'ed appends.
+    ///
+    /// # Arguments
+    /// * `files` - The number of small files created.
+    ///
+    /// # Returns
+    /// The bytes written.
+    pub fn planned_writes(files: u64) -> u64 {
+        (files + FSYNC_SAMPLES as u64) * BLOCK_BYTES as u64 + RANDOM_WRITE_FILE_BYTES
+    }
+
+    /// Returns an error once the benchmark is cancelled.
+    fn check_cancelled(cancel: &CancellationToken) -> Result<(), String> {
+        if cancel.is_cancelled() {
+            Err("Filesystem benchmark cancelled".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Times one metadata operation applied to every small file.
+    fn time_files(
+        &self,
+        operation: &str,
+        scratch: &Path,
+        files: u64,
+        cancel: &CancellationToken,
+        apply: impl Fn(&Path) -> std::io::Result<()>,
+    ) -> Result<OperationRate, String> {
+        self.logger
+            .log_debug(&format!("Timing {} of {} files", operation, files));
+        let started = Instant::now();
+        for index in 0..files {
+            Self::check_cancelled(cancel)?;
+            let path = scratch.join(format!("file-{}", index));
+            apply(&path)
+                .map_err(|e| format!("Failed to {} {}: {}", operation, path.display(), e))?;
+        }
+        Ok(OperationRate {
+            operation: operation.to_string(),
+            count: files,
+            elapsed_secs: started.elapsed().as_secs_f64(),
+        })
+    }
+
+    /// Writes blocks at random offsets of a file opened for synchronous writes,
+    /// so each write has reached the drive before the next one is issued.
+    fn time_random_writes(
+        &self,
+        scratch: &Path,
+        cancel: &CancellationToken,
+    ) -> Result<OperationRate, String> {
+        let path = scratch.join("random-writes");
+        let block = vec![0xA5u8; BLOCK_BYTES];
+        // The file is written out first, so the timed writes overwrite allocated blocks.
+        let mut file = File::create(&path)
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        for _ in 0..RANDOM_WRITE_FILE_BYTES / BLOCK_BYTES as u64 {
+            file.write_all(&block)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        }
+        file.sync_all()
+            .map_err(|e| format!("Failed to sync {}: {}", path.display(), e))?;
+        drop(file);
+
+        let file = OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_DSYNC)
+            .open(&path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        let blocks = RANDOM_WRITE_FILE_BYTES / BLOCK_BYTES as u64;
+        // xorshift64 picks the offsets without an RNG dependency.
+        let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+        let mut count = 0;
+        let started = Instant::now();
+        while started.elapsed() < RANDOM_WRITE_DURATION {
+            Self::check_cancelled(cancel)?;
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            file.write_all_at(&block, (state % blocks) * BLOCK_BYTES as u64)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            count += 1;
+        }
+        Ok(OperationRate {
+            operation: format!("random write ({} B)", BLOCK_BYTES),
+            count,
+            elapsed_secs: started.elapsed().as_secs_f64(),
+        })
+    }
+
+    /// Appends blocks to a file one at a time and times the fsync after each,
+    /// as a database does when committing to its log.
+    fn time_fsyncs(
+        &self,
+        scratch: &Path,
+        cancel: &CancellationToken,
+    ) -> Result<LatencyStats, String> {
+        let path = scratch.join("fsync");
+        let mut file = File::create(&path)
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        let block = vec![0x5Au8; BLOCK_BYTES];
+        let mut samples = Vec::with_capacity(FSYNC_SAMPLES);
+        let started = Instant::now();
+        while samples.len() < FSYNC_SAMPLES && started.elapsed() < FSYNC_MAX_DURATION {
+            Self::check_cancelled(cancel)?;
+            file.write_all(&block)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            let synced = Instant::now();
+            file.sync_all()
+                .map_err(|e| format!("Failed to sync {}: {}", path.display(), e))?;
+            samples.push(synced.elapsed());
+        }
+        Ok(LatencyStats::from_samples(&samples))
+    }
+
+    /// Runs every phase of the benchmark in the scratch directory.
+    fn run_phases(
+        &self,
+        directory: &Path,
+        scratch: &Path,
+        files: u64,
+        cancel: &CancellationToken,
+    ) -> Result<FilesystemBenchmarkResult, String> {
+        let block = vec![0x3Cu8; BLOCK_BYTES];
+        let create = self.time_files(
+            &format!("create ({} B)", BLOCK_BYTES),
+            scratch,
+            files,
+            cancel,
+            |path| File::create(path)?.write_all(&block),
+        )?;
+        let stat = self.time_files("stat", scratch, files, cancel, |path| {
+            fs::metadata(path).map(|_| ())
+        })?;
+        let delete = self.time_files("delete", scratch, files, cancel, |path| {
+            fs::remove_file(path)
+        })?;
+        let random_writes = self.time_random_writes(scratch, cancel)?;
+        let fsync = self.time_fsyncs(scratch, cancel)?;
+
+        Ok(FilesystemBenchmarkResult {
+            path: directory.display().to_string(),
+            metadata: vec![create, stat, delete],
+            random_writes,
+            write_bytes: BLOCK_BYTES,
+            fsync,
+        })
+    }
+}
+
+// Implement the `FilesystemBenchmarkPort` trait for `FilesystemBenchmarkAdapter`.
+impl FilesystemBenchmarkPort for FilesystemBenchmarkAdapter {
+    fn run_filesystem_benchmark(
+        &self,
+        directory: &Path,
+        files: u64,
+        cancel: &CancellationToken,
+    ) -> Result<FilesystemBenchmarkResult, String> {
+        if !directory.is_dir() {
+            return Err(format!("{} is not a directory", directory.display()));
+        }
+        let scratch = directory.join(format!("{}-{}", SCRATCH_DIR_PREFIX, std::process::id()));
+        fs::create_dir(&scratch)
+            .map_err(|e| format!("Failed to create {}: {}", scratch.display(), e))?;
+
+        let result = self.run_phases(directory, &scratch, files, cancel);
+        // The scratch directory goes whether the benchmark finished or not.
+        if let Err(e) = fs::remove_dir_all(&scratch) {
+            self.logger
+                .log_warn(&format!("Failed to remove {}: {}", scratch.display(), e));
+        }
+        result
+    }
+}
@@ -0,0 +1,198 @@
+//! Firmware Adapter
+//!
+//! This module provides an adapter that collects the firmware versions of a
+//! machine. The BIOS version comes from the DMI information in sysfs, the BMC
+//! firmware from `ipmitool mc info`, NIC firmware from `ethtool -i`, and the
+//! firmware of NVMe and SATA/SAS drives from sysfs. Components whose tool is
+//! missing are left out of the inventory rather than failing it.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+
+use common::ports::log_port::LoggerPort;
+
+use crate::adapters::cloud_metadata_adapter::read_dmi_field;
+use crate::adapters::sysfs::read_sysfs;
+use crate::domain::firmware::FirmwareComponent;
+use crate::ports::firmware_port::FirmwarePort;
+
+/// The device nodes of the IPMI driver; without one there is no BMC to ask.
+pub(crate) const IPMI_DEVICES: [&str; 3] = ["/dev/ipmi0", "/dev/ipmi/0", "/dev/ipmidev/0"];
+
+/// Represents the sysfs, ipmitool and ethtool firmware adapter.
+pub struct FirmwareAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl FirmwareAdapter {
+    /// Creates a new instance of `FirmwareAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `FirmwareAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        FirmwareAdapter { logger }
+    }
+
+    /// Lists the entries of a sysfs class directory, sorted by name.
+    fn class_entries(directory: &str) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(directory)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.file_name().to_string_lossy().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+
+    /// Runs a command and returns its standard output, or `None` if it could not be run or failed.
+    fn run(&self, program: &str, args: &[&str]) -> Option<String> {
+        match Command::new(program).args(args).output() {
+            Ok(output) if output.status.success() => {
+                Some(String::from_utf8_lossy(&output.stdout).to_string())
+            }
+            Ok(output) => {
+                self.logger.log_debug(&format!(
+                    "{} {} failed: {}",
+                    program,
+                    args.join(" "),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+                None
+            }
+            Err(e) => {
+                self.logger
+                    .log_debug(&format!("Failed to run {}: {}", program, e));
+                None
+            }
+        }
+    }
+
+    /// Returns the value of a "key: value" line of a tool's output.
+    fn field<'a>(output: &'a str, key: &str) -> Option<&'a str> {
+        output.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            (name.trim() == key)
+                .then(|| value.trim())
+                .filter(|value| !value.is_empty())
+        })
+    }
+
+    /// Reads the BIOS or UEFI firmware version.
+    fn bios() -> Option<FirmwareComponent> {
+        let version = read_dmi_field("bios_version");
+        if version.is_empty() {
+            return None;
+        }
+        Some(FirmwareComponent {
+            kind: "bios".to_string(),
+            device: String::new(),
+            model: read_dmi_field("bios_vendor"),
+            version,
+        })
+    }
+
+    /// Reads the BMC firmware version through the IPMI driver.
+    fn bmc(&self) -> Option<FirmwareComponent> {
+        if !IPMI_DEVICES.iter().any(|device| Path::new(device).exists()) {
+            return None;
+        }
+        let output = self.run("ipmitool", &["mc", "info"])?;
+        Some(FirmwareComponent {
+            kind: "bmc".to_string(),
+            device: String::new(),
+            model: Self::field(&output, "Manufacturer Name")
+                .unwrap_or_default()
+                .to_string(),
+            version: Self::field(&output, "Firmware Revision")?.to_string(),
+        })
+    }
+
+    /// Reads the firmware version of every physical network interface.
+    fn nics(&self) -> Vec<FirmwareComponent> {
+        let mut nics = Vec::new();
+        for interface in Self::class_entries("/sys/class/net") {
+            // Virtual interfaces, such as bridges and bonds, have no device.
+            if !Path::new("/sys/class/net")
+                .join(&interface)
+                .join("device")
+                .exists()
+            {
+                continue;
+            }
+            let Some(output) = self.run("ethtool", &["-i", &interface]) else {
+                continue;
+            };
+            // Drivers without firmware, such as virtio_net, report it empty or as "N/A".
+            let Some(version) =
+                Self::field(&output, "firmware-version").filter(|version| *version != "N/A")
+            else {
+                continue;
+            };
+            nics.push(FirmwareComponent {
+                kind: "nic".to_string(),
+                model: Self::field(&output, "driver")
+                    .unwrap_or_default()
+                    .to_string(),
+                device: interface,
+                // Mellanox appends the board ID, e.g. "22.39.1002 (MT_0000000359)".
+                version: version.split(" (").next().unwrap_or(version).to_string(),
+            });
+        }
+        nics
+    }
+
+    /// Reads the firmware revision of every NVMe controller and SATA/SAS drive.
+    fn drives() -> Vec<FirmwareComponent> {
+        let mut drives = Vec::new();
+        for controller in Self::class_entries("/sys/class/nvme") {
+            let path = Path::new("/sys/class/nvme").join(&controller);
+            let version = read_sysfs(path.join("firmware_rev")).unwrap_or_default();
+            if !version.is_empty() {
+                drives.push(FirmwareComponent {
+                    kind: "nvme".to_string(),
+                    model: read_sysfs(path.join("model")).unwrap_or_default(),
+                    device: controller,
+                    version,
+                });
+            }
+        }
+        for disk in Self::class_entries("/sys/block") {
+            let device = Path::new("/sys/block").join(&disk).join("device");
+            // SCSI disks, which include SATA and SAS drives, expose their firmware as `rev`.
+            let version = read_sysfs(device.join("rev")).unwrap_or_default();
+            if !disk.starts_with("sd") || version.is_empty() {
+                continue;
+            }
+            drives.push(FirmwareComponent {
+                kind: "disk".to_string(),
+                model: read_sysfs(device.join("model")).unwrap_or_default(),
+                device: disk,
+                version,
+            });
+        }
+        drives
+    }
+}
+
+// Implement the `FirmwarePort` trait for `FirmwareAdapter`.
+impl FirmwarePort for FirmwareAdapter {
+    fn discover_firmware(&self) -> Result<Vec<FirmwareComponent>, String> {
+        let mut inventory: Vec<FirmwareComponent> = Self::bios().into_iter().collect();
+        inventory.extend(self.bmc());
+        inventory.extend(self.nics());
+        inventory.extend(Self::drives());
+        self.logger.log_debug(&format!(
+            "Found firmware versions for {} components",
+            inventory.len()
+        ));
+        Ok(inventory)
+    }
+}
@@ -0,0 +1,310 @@
+//! Footprint Governor
+//!
+//! This module measures the overhead OneForAll's own monitoring adds to the
+//! machine and keeps it within the budget of the `[footprint]` section. The
+//! CPU time of each thread is read from `/proc/self/task`: threads named with
+//! `COLLECTOR_THREAD_PREFIX` are collectors, and the actix workers serve the
+//! REST API. While they use more CPU than the budget allows, collectors that
+//! ask the governor how long to wait sample less often; while the process
+//! holds more memory than allowed, buffered readings are written out early.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{Local, Utc};
+use common::domain::footprint::{ComponentUsage, Footprint, FootprintConfig, FOOTPRINT_COLLECTOR};
+use common::ports::footprint_port::FootprintPort;
+use common::ports::log_port::LoggerPort;
+
+use crate::adapters::sample_writer::SampleWriter;
+
+/// The prefix of the names of collector threads, e.g. "ofa-power".
+pub const COLLECTOR_THREAD_PREFIX: &str = "ofa-";
+
+/// The prefix of the names of the threads serving the REST API.
+const WEB_SERVER_THREAD_PREFIX: &str = "actix-";
+
+/// How often the overhead is measured.
+const FOOTPRINT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Reads the configuration file's `[footprint]` section.
+///
+/// # Arguments
+///
+/// * `path` - The configuration file; a missing file means the default budget.
+///
+/// # Returns
+///
+/// * `Result<FootprintConfig, String>` - The budget, or why it could not be read.
+pub fn load_footprint_config(path: &Path) -> Result<FootprintConfig, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(FootprintConfig::default()),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    FootprintConfig::from_toml(&text).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+}
+
+/// Returns the user and system CPU time, in clock ticks, of a `stat` file of procfs.
+fn parse_stat_ticks(text: &str) -> Option<u64> {
+    // The name in parentheses may hold spaces, so the fields are counted after it.
+    let fields: Vec<&str> = text.rsplit_once(')')?.1.split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some(utime + stime)
+}
+
+/// Returns the part of the monitoring a thread belongs to, if it belongs to any.
+fn component_of(thread_name: &str) -> Option<String> {
+    if let Some(collector) = thread_name.strip_prefix(COLLECTOR_THREAD_PREFIX) {
+        Some(collector.to_string())
+    } else if thread_name.starts_with(WEB_SERVER_THREAD_PREFIX) {
+        Some("web server".to_string())
+    } else {
+        None
+    }
+}
+
+/// Reads the name and CPU time of every thread of this process, by thread ID.
+fn read_threads() -> HashMap<String, (String, u64)> {
+    let Ok(tasks) = fs::read_dir("/proc/self/task") else {
+        return HashMap::new();
+    };
+    tasks
+        .flatten()
+        .filter_map(|task| {
+            let path = task.path();
+            let name = fs::read_to_string(path.join("comm")).ok()?;
+            let ticks = parse_stat_ticks(&fs::read_to_string(path.join("stat")).ok()?)?;
+            Some((
+                task.file_name().to_string_lossy().into_owned(),
+                (name.trim().to_string(), ticks),
+            ))
+        })
+        .collect()
+}
+
+/// Reads the resident memory of this process, in bytes.
+fn read_rss_bytes() -> Result<u64, String> {
+    let status = fs::read_to_string("/proc/self/status")
+        .map_err(|e| format!("Failed to read /proc/self/status: {}", e))?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))
+        .and_then(|value| {
+            value
+                .trim()
+                .trim_end_matches("kB")
+                .trim()
+                .parse::<u64>()
+                .ok()
+        })
+        .map(|kilobytes| kilobytes * 1024)
+        .ok_or_else(|| "No VmRSS in /proc/self/status".to_string())
+}
+
+/// The CPU times the next measurement is taken against.
+struct Reading {
+    taken: Instant,
+    process_ticks: u64,
+    threads: HashMap<String, (String, u64)>,
+}
+
+/// Measures the overhead of the monitoring and paces the collectors by it.
+pub struct FootprintGovernor {
+    logger: Arc<dyn LoggerPort>,       // inject the logger port
+    config: FootprintConfig,           // the budget the monitoring must stay within
+    samples: Option<SampleWriter>,     // where the overhead is stored, if anywhere
+    ticks_per_second: f64,             // the clock ticks CPU times are counted in
+    previous: Mutex<Option<Reading>>,  // the CPU times at the last measurement
+    latest: RwLock<Option<Footprint>>, // the last overhead measured
+}
+
+impl FootprintGovernor {
+    /// Creates a new instance of `FootprintGovernor`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    /// * `config` - The budget the monitoring must stay within.
+    ///
+    /// # Returns
+    /// An instance of `FootprintGovernor`.
+    pub fn new(logger: Arc<dyn LoggerPort>, config: FootprintConfig) -> Self {
+        // SAFETY: sysconf only reads a system setting.
+        let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+        FootprintGovernor {
+            logger,
+            config,
+            samples: None,
+            ticks_per_second: if ticks > 0 { ticks as f64 } else { 100.0 },
+            previous: Mutex::new(None),
+            latest: RwLock::new(None),
+        }
+    }
+
+    /// Stores each measured overhead, and writes buffered readings out early
+    /// while the process is over its memory budget.
+    ///
+    /// # Arguments
+    /// * `samples` - The writer the collectors' readings are buffered in.
+    ///
+    /// # Returns
+    /// The governor with the writer set.
+    pub fn with_samples(mut self, samples: SampleWriter) -> Self {
+        self.samples = Some(samples);
+        self
+    }
+
+    /// Measures the overhead since the previous measurement.
+    ///
+    /// # Returns
+    /// The overhead, or `None` for the first measurement, which only takes the baseline.
+    pub fn measure(&self) -> Result<Option<Footprint>, String> {
+        let process_ticks = parse_stat_ticks(
+            &fs::read_to_string("/proc/self/stat")
+                .map_err(|e| format!("Failed to read /proc/self/stat: {}", e))?,
+        )
+        .ok_or_else(|| "Failed to parse /proc/self/stat".to_string())?;
+        let reading = Reading {
+            taken: Instant::now(),
+            process_ticks,
+            threads: read_threads(),
+        };
+        let rss_bytes = read_rss_bytes()?;
+
+        let mut previous = self.previous.lock().map_err(|e| e.to_string())?;
+        let Some(last) = previous.replace(reading) else {
+            return Ok(None);
+        };
+        let Some(current) = previous.as_ref() else {
+            return Ok(None);
+        };
+        let elapsed_secs = current.taken.duration_since(last.taken).as_secs_f64();
+        if elapsed_secs <= 0.0 {
+            return Ok(None);
+        }
+        let percent = |ticks: u64| ticks as f64 / self.ticks_per_second / elapsed_secs * 100.0;
+
+        // A thread that started since the last measurement used all of its CPU time since.
+        let mut usage: HashMap<String, u64> = HashMap::new();
+        for (id, (name, ticks)) in &current.threads {
+            let Some(component) = component_of(name) else {
+                continue;
+            };
+            let before = last.threads.get(id).map_or(0, |(_, ticks)| *ticks);
+            *usage.entry(component).or_default() += ticks.saturating_sub(before);
+        }
+        let mut components: Vec<ComponentUsage> = usage
+            .into_iter()
+            .map(|(name, ticks)| ComponentUsage {
+                name,
+                cpu_percent: percent(ticks),
+            })
+            .collect();
+        components.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent));
+        let cpu_percent = components.iter().map(|c| c.cpu_percent).sum();
+
+        Ok(Some(Footprint {
+            timestamp: Local::now().to_rfc3339(),
+            cpu_percent,
+            process_cpu_percent: percent(current.process_ticks.saturating_sub(last.process_ticks)),
+            rss_bytes,
+            threads: current.threads.len(),
+            components,
+            budget: self.config.clone(),
+            slowdown: self.config.slowdown(cpu_percent),
+        }))
+    }
+
+    /// Measures the overhead every few seconds on a thread of its own, for as
+    /// long as the process runs.
+    pub fn start(self: &Arc<Self>) {
+        let governor = self.clone();
+        let spawned = thread::Builder::new()
+            .name(format!("{}footprint", COLLECTOR_THREAD_PREFIX))
+            .spawn(move || {
+                let mut over_budget = false;
+                loop {
+                    match governor.measure() {
+                        Ok(Some(footprint)) => {
+                            governor.report(&footprint, &mut over_budget);
+                            if let Ok(mut latest) = governor.latest.write() {
+                                *latest = Some(footprint);
+                            }
+                        }
+                        Ok(None) => {}
+                        Err(e) => governor
+                            .logger
+                            .log_debug(&format!("Failed to measure the footprint: {}", e)),
+                    }
+                    thread::sleep(FOOTPRINT_INTERVAL);
+                }
+            });
+        if let Err(e) = spawned {
+            self.logger
+                .log_warn(&format!("Not measuring the monitoring footprint: {}", e));
+        }
+    }
+
+    /// Logs when the monitoring goes over or back within its CPU budget, stores
+    /// the overhead, and writes buffered readings out while over the memory budget.
+    fn report(&self, footprint: &Footprint, over_budget: &mut bool) {
+        if footprint.over_cpu_budget() != *over_budget {
+            *over_budget = footprint.over_cpu_budget();
+            if *over_budget {
+                self.logger.log_warn(&format!(
+                    "The collectors and web server use {:.1}% CPU, over the {:.1}% budget; \
+                     sampling {:.1}x less often.",
+                    footprint.cpu_percent, self.config.cpu_percent, footprint.slowdown
+                ));
+            } else {
+                self.logger.log_info(&format!(
+                    "The collectors and web server are back within the {:.1}% CPU budget.",
+                    self.config.cpu_percent
+                ));
+            }
+        }
+        let Some(samples) = &self.samples else {
+            return;
+        };
+        if let Err(e) = samples.record(FOOTPRINT_COLLECTOR, Utc::now(), footprint) {
+            self.logger
+                .log_warn(&format!("Failed to store the footprint: {}", e));
+        }
+        if footprint.over_memory_budget() {
+            match samples.flush() {
+                Ok(written) if written > 0 => self.logger.log_debug(&format!(
+                    "Over the {} MB memory budget; wrote {} buffered reading(s) early",
+                    self.config.memory_mb, written
+                )),
+                Ok(_) => {}
+                Err(e) => self.logger.log_warn(&e),
+            }
+        }
+    }
+}
+
+// Implement the `FootprintPort` trait for `FootprintGovernor`.
+impl FootprintPort for FootprintGovernor {
+    fn budget(&self) -> FootprintConfig {
+        self.config.clone()
+    }
+
+    fn footprint(&self) -> Option<Footprint> {
+        self.latest.read().ok().and_then(|latest| latest.clone())
+    }
+
+    fn pace(&self, interval: Duration) -> Duration {
+        let slowdown = self
+            .latest
+            .read()
+            .ok()
+            .and_then(|latest| latest.as_ref().map(|footprint| footprint.slowdown))
+            .unwrap_or(1.0);
+        interval.mul_f64(slowdown)
+    }
+}
@@ -0,0 +1,115 @@
+//! Frequency Adapter
+//!
+//! This module provides an adapter that reads per-core clock speed, governor,
+//! and idle/performance state residency from the Linux `cpufreq` and `cpuidle`
+//! sysfs interfaces.
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use common::ports::log_port::LoggerPort;
+
+use crate::adapters::sysfs::read_sysfs;
+use crate::domain::cpu_frequency::{CoreFrequency, IdleStateResidency};
+use crate::domain::cpu_topology::parse_cpu_list;
+use crate::ports::frequency_port::FrequencyPort;
+
+/// Root of the sysfs CPU hierarchy on Linux.
+const SYSFS_CPU_ROOT: &str = "/sys/devices/system/cpu";
+
+/// Represents the cpufreq/cpuidle adapter.
+pub struct FrequencyAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl FrequencyAdapter {
+    /// Creates a new instance of `FrequencyAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `FrequencyAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        FrequencyAdapter { logger }
+    }
+
+    /// Reads a sysfs attribute expressed in kHz and converts it to MHz.
+    fn read_khz_as_mhz(path: &Path) -> Option<u32> {
+        read_sysfs(path)
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(|khz| (khz / 1000) as u32)
+    }
+
+    /// Reads the cumulative residency of every cpuidle state of a CPU.
+    fn read_idle_states(cpu_dir: &Path) -> Vec<IdleStateResidency> {
+        let mut states = Vec::new();
+        let entries = match fs::read_dir(cpu_dir.join("cpuidle")) {
+            Ok(entries) => entries,
+            Err(_) => return states,
+        };
+
+        for entry in entries.flatten() {
+            if !entry.file_name().to_string_lossy().starts_with("state") {
+                continue;
+            }
+            let name = read_sysfs(entry.path().join("name"));
+            let time = read_sysfs(entry.path().join("time")).and_then(|t| t.parse().ok());
+            if let (Some(name), Some(time_us)) = (name, time) {
+                states.push(IdleStateResidency { name, time_us });
+            }
+        }
+
+        states.sort_by(|a, b| a.name.cmp(&b.name));
+        states
+    }
+
+    /// Reads the cpufreq `time_in_state` statistics of a CPU, if enabled.
+    fn read_pstate_residency(cpu_dir: &Path) -> Vec<(u32, u64)> {
+        read_sysfs(cpu_dir.join("cpufreq/stats/time_in_state"))
+            .map(|table| {
+                table
+                    .lines()
+                    .filter_map(|line| {
+                        let mut fields = line.split_whitespace();
+                        let khz: u64 = fields.next()?.parse().ok()?;
+                        let time: u64 = fields.next()?.parse().ok()?;
+                        Some(((khz / 1000) as u32, time))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+// Implement the `FrequencyPort` trait for `FrequencyAdapter`.
+impl FrequencyPort for FrequencyAdapter {
+    fn sample_frequencies(&self) -> Result<Vec<CoreFrequency>, String> {
+        let online = read_sysfs(Path::new(SYSFS_CPU_ROOT).join("online"))
+            .ok_or("CPU frequency information is not available on this platform")?;
+        let online = parse_cpu_list(&online)?;
+
+        let mut readings = Vec::with_capacity(online.len());
+        for cpu in online {
+            let cpu_dir = Path::new(SYSFS_CPU_ROOT).join(format!("cpu{}", cpu));
+            let cpufreq = cpu_dir.join("cpufreq");
+
+            readings.push(CoreFrequency {
+                cpu,
+                frequency_mhz: Self::read_khz_as_mhz(&cpufreq.join("scaling_cur_freq")),
+                governor: read_sysfs(cpufreq.join("scaling_governor")),
+                base_frequency_mhz: Self::read_khz_as_mhz(&cpufreq.join("base_frequency")),
+                idle_states: Self::read_idle_states(&cpu_dir),
+                pstate_residency: Self::read_pstate_residency(&cpu_dir),
+            });
+        }
+
+        if readings.iter().all(|r| r.frequency_mhz.is_none()) {
+            self.logger
+                .log_trace("cpufreq does not report current frequencies on this system");
+        }
+
+        Ok(readings)
+    }
+}
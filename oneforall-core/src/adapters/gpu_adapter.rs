@@ -0,0 +1,148 @@
+//! GPU Adapter
+//!
+//! This module provides an adapter that reads the GPUs of the machine: NVIDIA
+//! GPUs through `nvidia-smi`, and AMD GPUs through the sysfs attributes of the
+//! amdgpu driver, which need no vendor tools.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use common::ports::log_port::LoggerPort;
+
+use crate::adapters::sysfs::read_sysfs;
+use crate::domain::gpu::{parse_nvidia_smi, GpuReading, NVIDIA_SMI_QUERY};
+use crate::ports::gpu_port::GpuPort;
+
+/// Root of the DRM hierarchy exposing the graphics cards.
+const SYSFS_DRM_ROOT: &str = "/sys/class/drm";
+
+/// The PCI vendor ID of AMD.
+const AMD_VENDOR_ID: &str = "0x1002";
+
+/// Represents the nvidia-smi/amdgpu GPU adapter.
+pub struct GpuAdapter {
+    logger: Arc<dyn LoggerPort>,  // inject the logger port
+    nvidia_available: AtomicBool, // cleared once nvidia-smi fails to answer
+}
+
+impl GpuAdapter {
+    /// Creates a new instance of `GpuAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `GpuAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        GpuAdapter {
+            logger,
+            nvidia_available: AtomicBool::new(true),
+        }
+    }
+
+    /// Reads a numeric sysfs attribute.
+    fn read_number(path: &Path) -> Option<f64> {
+        read_sysfs(path).and_then(|value| value.parse().ok())
+    }
+
+    /// Runs `nvidia-smi` and returns the reading of every NVIDIA GPU.
+    fn read_nvidia_smi() -> Result<Vec<GpuReading>, String> {
+        let output = Command::new("nvidia-smi")
+            .args([
+                &format!("--query-gpu={}", NVIDIA_SMI_QUERY),
+                "--format=csv,noheader,nounits",
+            ])
+            .output()
+            .map_err(|e| format!("Failed to run nvidia-smi: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "nvidia-smi failed: {}",
+                String::from_utf8_lossy(&output.stdout).trim()
+            ));
+        }
+        Ok(parse_nvidia_smi(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// Reads every AMD GPU driven by amdgpu.
+    fn read_amdgpu() -> Vec<GpuReading> {
+        let entries = match fs::read_dir(SYSFS_DRM_ROOT) {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut readings = Vec::new();
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            // Only the cards themselves, not their connectors such as "card0-DP-1".
+            let index = match file_name
+                .strip_prefix("card")
+                .and_then(|index| index.parse::<u32>().ok())
+            {
+                Some(index) => index,
+                None => continue,
+            };
+            let device = entry.path().join("device");
+            if read_sysfs(device.join("vendor")).as_deref() != Some(AMD_VENDOR_ID) {
+                continue;
+            }
+
+            let hwmon = fs::read_dir(device.join("hwmon"))
+                .into_iter()
+                .flatten()
+                .flatten()
+                .map(|entry| entry.path())
+                .next();
+            let hwmon_number = |attribute: &str| {
+                hwmon
+                    .as_ref()
+                    .and_then(|hwmon| Self::read_number(&hwmon.join(attribute)))
+            };
+            let slot = fs::read_link(&device)
+                .ok()
+                .and_then(|target| target.file_name().map(|n| n.to_string_lossy().to_string()))
+                .unwrap_or_else(|| file_name.clone());
+
+            readings.push(GpuReading {
+                index,
+                name: read_sysfs(device.join("product_name"))
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or_else(|| format!("AMD GPU {}", slot)),
+                // hwmon reports millidegrees Celsius, microwatts and hertz.
+                temperature_celsius: hwmon_number("temp1_input").map(|v| v / 1000.0),
+                clock_mhz: hwmon_number("freq1_input").map(|v| v / 1_000_000.0),
+                power_watts: hwmon_number("power1_average")
+                    .or_else(|| hwmon_number("power1_input"))
+                    .map(|v| v / 1_000_000.0),
+                utilization_percent: Self::read_number(&device.join("gpu_busy_percent")),
+            });
+        }
+        readings.sort_by_key(|reading| reading.index);
+        readings
+    }
+}
+
+// Implement the `GpuPort` trait for `GpuAdapter`.
+impl GpuPort for GpuAdapter {
+    fn sample_gpus(&self) -> Result<Vec<GpuReading>, String> {
+        let mut readings = Vec::new();
+        if self.nvidia_available.load(Ordering::Relaxed) {
+            match Self::read_nvidia_smi() {
+                Ok(nvidia) => readings.extend(nvidia),
+                Err(e) => {
+                    self.logger
+                        .log_trace(&format!("NVIDIA GPUs unavailable: {}", e));
+                    self.nvidia_available.store(false, Ordering::Relaxed);
+                }
+            }
+        }
+        readings.extend(Self::read_amdgpu());
+
+        if readings.is_empty() {
+            return Err("No NVIDIA or AMD GPU is readable on this machine".to_string());
+        }
+        Ok(readings)
+    }
+}
@@ -0,0 +1,176 @@
+//! GPU Interconnect Adapter
+//!
+//! This module provides an adapter that reads the interconnect topology of the
+//! GPUs from `nvidia-smi topo -m` on NVIDIA systems and `rocm-smi --showtopotype`
+//! on AMD systems, and measures the paths between NVIDIA GPUs with NVIDIA's
+//! nvbandwidth: copy engine reads for the bandwidth in one and both directions,
+//! and SM memory accesses for the latency.
+
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::process::Command;
+use std::sync::Arc;
+
+use common::ports::log_port::LoggerPort;
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::gpu_interconnect::{
+    parse_topology_matrix, GpuPairMeasurement, GpuTopology, InterconnectBenchmarkResult,
+};
+use crate::domain::pcie::parse_nvbandwidth_matrix;
+use crate::ports::gpu_interconnect_port::GpuInterconnectPort;
+
+/// The tools printing the GPU topology matrix, tried in order.
+const TOPOLOGY_TOOLS: [(&str, &[&str]); 2] = [
+    ("nvidia-smi", &["topo", "-m"]),
+    ("rocm-smi", &["--showtopotype"]),
+];
+
+/// The nvbandwidth test measuring the copy bandwidth from one GPU to another.
+const READ_TEST: &str = "device_to_device_memcpy_read_ce";
+
+/// The nvbandwidth test measuring the copy bandwidth in both directions at once.
+const BIDIRECTIONAL_TEST: &str = "device_to_device_bidirectional_memcpy_read_ce";
+
+/// The nvbandwidth test measuring the memory access latency from one GPU to another.
+const LATENCY_TEST: &str = "device_to_device_latency_sm";
+
+/// Represents the nvidia-smi/rocm-smi and nvbandwidth GPU interconnect adapter.
+pub struct GpuInterconnectAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl GpuInterconnectAdapter {
+    /// Creates a new instance of `GpuInterconnectAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `GpuInterconnectAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        GpuInterconnectAdapter { logger }
+    }
+
+    /// Runs one nvbandwidth test case and returns the cells of its matrix.
+    fn run_nvbandwidth(
+        &self,
+        test: &str,
+        heading_suffix: &str,
+    ) -> Result<Vec<(u32, u32, f64)>, String> {
+        self.logger
+            .log_debug(&format!("Running nvbandwidth -t {}", test));
+        // nvbandwidth numbers the GPUs in PCI address order, as nvidia-smi does.
+        let output = Command::new("nvbandwidth")
+            .args(["-t", test])
+            .env("CUDA_DEVICE_ORDER", "PCI_BUS_ID")
+            .output()
+            .map_err(|e| format!("Failed to run nvbandwidth: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "nvbandwidth {} failed: {}",
+                test,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(parse_nvbandwidth_matrix(
+            &String::from_utf8_lossy(&output.stdout),
+            heading_suffix,
+        ))
+    }
+
+    /// Returns the measurement of the pair of an nvbandwidth cell, adding it if new.
+    ///
+    /// nvbandwidth reads from the GPU of the column into the GPU of the row.
+    fn pair<'a>(
+        pairs: &'a mut BTreeMap<(u32, u32), GpuPairMeasurement>,
+        topology: &GpuTopology,
+        row: u32,
+        column: u32,
+    ) -> &'a mut GpuPairMeasurement {
+        pairs
+            .entry((column, row))
+            .or_insert_with(|| GpuPairMeasurement {
+                from: column,
+                to: row,
+                link: topology.link(row, column).map(|link| link.kind.clone()),
+                read_gb_s: None,
+                bidirectional_gb_s: None,
+                latency_us: None,
+            })
+    }
+}
+
+// Implement the `GpuInterconnectPort` trait for `GpuInterconnectAdapter`.
+impl GpuInterconnectPort for GpuInterconnectAdapter {
+    fn discover_gpu_topology(&self) -> Result<GpuTopology, String> {
+        for (tool, args) in TOPOLOGY_TOOLS {
+            let output = match Command::new(tool).args(args).output() {
+                Ok(output) => output,
+                // A machine without the vendor's GPUs does not have its tool either.
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(format!("Failed to run {}: {}", tool, e)),
+            };
+            if !output.status.success() {
+                return Err(format!(
+                    "{} failed: {}",
+                    tool,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+            return Ok(parse_topology_matrix(&String::from_utf8_lossy(
+                &output.stdout,
+            )));
+        }
+        Ok(GpuTopology::default())
+    }
+
+    fn run_interconnect_benchmark(
+        &self,
+        cancel: &CancellationToken,
+    ) -> Result<InterconnectBenchmarkResult, String> {
+        let topology = self.discover_gpu_topology().unwrap_or_else(|e| {
+            self.logger
+                .log_warn(&format!("GPU topology unknown: {}", e));
+            GpuTopology::default()
+        });
+
+        // The copy bandwidth is required; the other tests are missing from older nvbandwidth releases.
+        let read = self.run_nvbandwidth(READ_TEST, "bandwidth (GB/s)")?;
+        if read.is_empty() {
+            return Err(
+                "nvbandwidth measured no GPU pair; the benchmark needs at least two NVIDIA GPUs"
+                    .to_string(),
+            );
+        }
+        let run_optional = |test: &str, heading_suffix: &str| {
+            if cancel.is_cancelled() {
+                return Err("GPU interconnect benchmark cancelled".to_string());
+            }
+            Ok(self
+                .run_nvbandwidth(test, heading_suffix)
+                .unwrap_or_else(|e| {
+                    self.logger.log_warn(&format!("Skipping {}: {}", test, e));
+                    Vec::new()
+                }))
+        };
+        let bidirectional = run_optional(BIDIRECTIONAL_TEST, "bandwidth (GB/s)")?;
+        let latency = run_optional(LATENCY_TEST, "(ns)")?;
+
+        let mut pairs = BTreeMap::new();
+        for (row, column, value) in read {
+            Self::pair(&mut pairs, &topology, row, column).read_gb_s = Some(value);
+        }
+        for (row, column, value) in bidirectional {
+            Self::pair(&mut pairs, &topology, row, column).bidirectional_gb_s = Some(value);
+        }
+        // nvbandwidth reports latencies in nanoseconds.
+        for (row, column, value) in latency {
+            Self::pair(&mut pairs, &topology, row, column).latency_us = Some(value / 1000.0);
+        }
+
+        Ok(InterconnectBenchmarkResult {
+            pairs: pairs.into_values().collect(),
+        })
+    }
+}
@@ -0,0 +1,198 @@
+//! GPU Stress Runner
+//!
+//! This module runs the GPU stress test started by `oneforall stress --gpu`.
+//! The stress-ng GPU stressor loads every GPU while the GPU adapter samples
+//! their temperature, clock, power and utilization, and the peak temperature of
+//! each GPU is checked against the thermal limit once the load has stopped.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use common::domain::job::{JobSpec, JobStatus, Resource};
+use common::ports::job_port::JobPort;
+use common::ports::log_port::LoggerPort;
+use common::ports::telemetry_port::TelemetryPort;
+
+use crate::adapters::background_sampler::BackgroundSampler;
+use crate::adapters::gpu_adapter::GpuAdapter;
+use crate::adapters::progress_reporter::ProgressReporter;
+use crate::adapters::stress_ng_adapter::{run_output_file, take_run_output, StressNgAdapter};
+use crate::domain::burn_in::WorkloadOutcome;
+use crate::domain::gpu::{summarize_gpus, GpuStressReport};
+use crate::domain::stress_ng::{parse_bogo_ops, parse_bogo_ops_rate, StressRunOutcome};
+use crate::ports::gpu_port::GpuPort;
+
+/// Interval between two readings of the GPUs.
+const GPU_SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Runs GPU stress tests.
+pub struct GpuStressRunner {
+    logger: Arc<dyn LoggerPort>,
+    telemetry: Arc<dyn TelemetryPort>,
+    jobs: Arc<dyn JobPort>,
+    gpus: Arc<dyn GpuPort>,
+}
+
+impl GpuStressRunner {
+    /// Creates a new instance of `GpuStressRunner`.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger` - Logger implementation for logging messages.
+    /// * `telemetry` - Receives the progress of the test.
+    /// * `jobs` - The job queue the test is submitted to.
+    ///
+    /// # Returns
+    ///
+    /// * `GpuStressRunner` - A runner reading the GPUs of this machine.
+    pub fn new(
+        logger: Arc<dyn LoggerPort>,
+        telemetry: Arc<dyn TelemetryPort>,
+        jobs: Arc<dyn JobPort>,
+    ) -> Self {
+        GpuStressRunner {
+            gpus: Arc::new(GpuAdapter::new(logger.clone())),
+            logger,
+            telemetry,
+            jobs,
+        }
+    }
+
+    /// Runs the GPU stress test for the given duration.
+    ///
+    /// The test is queued as a job needing the GPUs, so it waits for another
+    /// GPU workload and can be cancelled like one.
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - How long to load the GPUs.
+    /// * `max_celsius` - The hottest any GPU may run.
+    ///
+    /// # Returns
+    ///
+    /// * `GpuStressReport` - The pass/fail report of the test.
+    pub async fn run(&self, duration: Duration, max_celsius: f64) -> GpuStressReport {
+        let mut ticket = self
+            .jobs
+            .submit_job(JobSpec::new("stress:gpu", &[Resource::Gpu]));
+        let job_id = ticket.job.id;
+        let mut report = GpuStressReport {
+            started_at: Local::now().to_rfc3339(),
+            planned_secs: duration.as_secs(),
+            elapsed_secs: 0,
+            outcome: WorkloadOutcome::Cancelled,
+            bogo_ops: None,
+            max_celsius,
+            gpus: Vec::new(),
+        };
+        if ticket.job.status == JobStatus::Queued {
+            self.logger.log_info(&format!(
+                "GPU stress job {} queued behind a conflicting job.",
+                job_id
+            ));
+        }
+        if !ticket.admitted().await {
+            self.logger
+                .log_warn("GPU stress test cancelled while queued.");
+            return report;
+        }
+
+        // Without a readable GPU there is nothing to load nor to judge.
+        let gpu_count = match self.gpus.sample_gpus() {
+            Ok(readings) => readings.len(),
+            Err(e) => {
+                report.outcome = WorkloadOutcome::Failed(e.clone());
+                self.jobs.finish_job(job_id, JobStatus::Failed, Some(e));
+                return report;
+            }
+        };
+        self.logger.log_info(&format!(
+            "Started GPU stress job {}: loading {} GPU(s) for {} seconds.",
+            job_id,
+            gpu_count,
+            duration.as_secs()
+        ));
+
+        let workers = gpu_count.to_string();
+        let timeout = format!("{}s", duration.as_secs());
+        let args = [
+            "--gpu",
+            workers.as_str(),
+            "--timeout",
+            timeout.as_str(),
+            "--metrics-brief",
+        ];
+
+        let gpus = self.gpus.clone();
+        let telemetry = self.telemetry.clone();
+        let sampler = BackgroundSampler::start(GPU_SAMPLE_INTERVAL, move || {
+            let readings = gpus.sample_gpus().ok()?;
+            let timestamp = Local::now().to_rfc3339();
+            telemetry.publish_metrics(
+                readings
+                    .iter()
+                    .flat_map(|reading| reading.metrics(&timestamp))
+                    .collect(),
+            );
+            Some(readings)
+        });
+        let progress =
+            ProgressReporter::start(self.logger.clone(), self.telemetry.clone(), "gpu", duration);
+        let started = Instant::now();
+        let output_file = run_output_file();
+        progress.follow_runs(std::slice::from_ref(&output_file));
+        let outcome = StressNgAdapter::execute_stress_ng_command_to(
+            self.logger.clone(),
+            &args,
+            false,
+            &output_file,
+            &ticket.cancel,
+        )
+        .await;
+        report.elapsed_secs = started.elapsed().as_secs();
+        let output = take_run_output(&output_file);
+        report.bogo_ops = parse_bogo_ops(&output);
+        progress.finish(parse_bogo_ops_rate(&output));
+        let samples: Vec<_> = sampler
+            .stop()
+            .into_iter()
+            .map(|(_, readings)| readings)
+            .collect();
+        report.gpus = summarize_gpus(&samples);
+
+        let (outcome, status, message) = match outcome {
+            Ok(StressRunOutcome::Completed) => {
+                (WorkloadOutcome::Completed, JobStatus::Completed, None)
+            }
+            Ok(StressRunOutcome::Cancelled) => {
+                (WorkloadOutcome::Cancelled, JobStatus::Cancelled, None)
+            }
+            Err(e) => (
+                WorkloadOutcome::Failed(e.clone()),
+                JobStatus::Failed,
+                Some(e),
+            ),
+        };
+        report.outcome = outcome;
+        let (status, message) = if status == JobStatus::Completed && !report.passed() {
+            let hot: Vec<String> = report
+                .checks()
+                .iter()
+                .filter(|(_, check)| check.missed())
+                .map(|(gpu, _)| format!("GPU {}", gpu.index))
+                .collect();
+            let message = if hot.is_empty() {
+                "no GPU was read during the test".to_string()
+            } else {
+                format!("{} above {:.0} °C", hot.join(", "), max_celsius)
+            };
+            (JobStatus::Failed, Some(message))
+        } else {
+            (status, message)
+        };
+        self.jobs.finish_job(job_id, status, message);
+
+        report
+    }
+}
@@ -0,0 +1,517 @@
+//! gRPC Server Adapter
+//!
+//! This module serves the gRPC control plane described by
+//! `proto/oneforall.proto`: job control, live telemetry streams and the results
+//! stored in the database. Callers authenticate with the bearer tokens of the
+//! `[api]` section, and the control plane is served over TLS, optionally
+//! requiring client certificates, whenever the REST API is.
+
+use std::fs;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use common::domain::api_config::{ApiConfig, TlsConfig};
+use common::domain::job::{Job, JobStatus};
+use common::domain::metric::Metric;
+use common::domain::telemetry::{PowerSample, StressProgress, TelemetryEvent};
+use common::ports::job_launcher_port::JobLauncherPort;
+use common::ports::job_port::JobPort;
+use common::ports::log_port::LoggerPort;
+use common::ports::telemetry_port::TelemetryPort;
+use common::ports::web_server_port::WebServerPort;
+use tokio::io;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio::time::sleep;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_util::sync::CancellationToken;
+use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
+use tonic::{Request, Response, Status};
+
+use crate::adapters::repository::Repository;
+use crate::domain::database::{key_time, validate_collector};
+use crate::domain::grpc::{result_kind, GrpcConfig, RESULT_KINDS};
+use crate::domain::tags::Tags;
+
+use self::proto::control_plane_server::{ControlPlane, ControlPlaneServer};
+
+/// The messages and service generated from `proto/oneforall.proto`.
+pub mod proto {
+    tonic::include_proto!("oneforall.v1");
+}
+
+/// How often `WatchJob` checks a job for a status change.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How many events a streaming call buffers for a slow client.
+const STREAM_BUFFER: usize = 16;
+
+/// Reads the `[grpc]` section of the configuration file.
+///
+/// # Arguments
+///
+/// * `path` - The configuration file; a missing file leaves the control plane off.
+///
+/// # Returns
+///
+/// * `Result<GrpcConfig, String>` - The gRPC settings, or why they could not be read.
+pub fn load_grpc_config(path: &Path) -> Result<GrpcConfig, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(GrpcConfig::default()),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    GrpcConfig::from_toml(&text).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+}
+
+/// Builds the TLS settings of the control plane from the certificates of the
+/// REST API, requiring a client certificate signed by `client_ca` when one is configured.
+fn tls_config(tls: &TlsConfig) -> Result<ServerTlsConfig, String> {
+    let read = |path: &Path| {
+        fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+    };
+    let identity = Identity::from_pem(read(&tls.certificate)?, read(&tls.private_key)?);
+    let mut config = ServerTlsConfig::new().identity(identity);
+    if let Some(client_ca) = &tls.client_ca {
+        config = config.client_ca_root(Certificate::from_pem(read(client_ca)?));
+    }
+    Ok(config)
+}
+
+impl From<&Job> for proto::Job {
+    fn from(job: &Job) -> Self {
+        let status = match job.status {
+            JobStatus::Queued => proto::JobStatus::Queued,
+            JobStatus::Running => proto::JobStatus::Running,
+            JobStatus::Completed => proto::JobStatus::Completed,
+            JobStatus::Failed => proto::JobStatus::Failed,
+            JobStatus::Cancelled => proto::JobStatus::Cancelled,
+        };
+        proto::Job {
+            id: job.id,
+            kind: job.kind.clone(),
+            resources: job
+                .resources
+                .iter()
+                .map(|r| format!("{:?}", r).to_lowercase())
+                .collect(),
+            status: status as i32,
+            started_at: job.started_at.clone(),
+            finished_at: job.finished_at.clone(),
+            message: job.message.clone(),
+        }
+    }
+}
+
+impl From<PowerSample> for proto::PowerSample {
+    fn from(sample: PowerSample) -> Self {
+        proto::PowerSample {
+            timestamp: sample.timestamp,
+            domains: sample
+                .domains
+                .into_iter()
+                .map(|d| proto::DomainPower {
+                    domain: d.domain,
+                    watts: d.watts,
+                })
+                .collect(),
+            bmc_watts: sample.bmc_watts,
+        }
+    }
+}
+
+impl From<StressProgress> for proto::StressProgress {
+    fn from(progress: StressProgress) -> Self {
+        proto::StressProgress {
+            timestamp: progress.timestamp,
+            test: progress.test,
+            elapsed_secs: progress.elapsed_secs,
+            duration_secs: progress.duration_secs,
+            bogo_ops_per_sec: progress.bogo_ops_per_sec,
+            hottest_celsius: progress.hottest_celsius,
+            package_watts: progress.package_watts,
+            finished: progress.finished,
+        }
+    }
+}
+
+impl From<Metric> for proto::Metric {
+    fn from(metric: Metric) -> Self {
+        proto::Metric {
+            name: metric.name,
+            unit: metric.unit,
+            labels: metric.labels.into_iter().collect(),
+            value: metric.value,
+            timestamp: metric.timestamp,
+        }
+    }
+}
+
+impl From<TelemetryEvent> for proto::TelemetryEvent {
+    fn from(event: TelemetryEvent) -> Self {
+        let event = match event {
+            TelemetryEvent::Power(sample) => proto::telemetry_event::Event::Power(sample.into()),
+            TelemetryEvent::StressProgress(progress) => {
+                proto::telemetry_event::Event::StressProgress(progress.into())
+            }
+            TelemetryEvent::Metrics { metrics } => {
+                proto::telemetry_event::Event::Metrics(proto::MetricBatch {
+                    metrics: metrics.into_iter().map(Into::into).collect(),
+                })
+            }
+        };
+        proto::TelemetryEvent { event: Some(event) }
+    }
+}
+
+/// Parses an optional RFC 3339 bound of a time range, left empty when unset.
+fn parse_time_bound(field: &str, value: &str) -> Result<Option<DateTime<Utc>>, Status> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| Some(time.with_timezone(&Utc)))
+        .map_err(|e| Status::invalid_argument(format!("Invalid {} '{}': {}", field, value, e)))
+}
+
+/// Represents the gRPC control plane server.
+#[derive(Clone)]
+pub struct GrpcServerAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    telemetry: Arc<dyn TelemetryPort>,
+    jobs: Arc<dyn JobPort>,
+    launcher: Arc<dyn JobLauncherPort>,
+    repository: Repository,
+    api: Arc<ApiConfig>, // the tokens and certificates shared with the REST API
+    address: SocketAddr,
+    shutdown: CancellationToken, // stops the server when the application shuts down
+}
+
+impl GrpcServerAdapter {
+    /// Creates a new instance of `GrpcServerAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    /// * `telemetry` - The live readings streamed to callers.
+    /// * `jobs` - The jobs listed, watched and cancelled by callers.
+    /// * `launcher` - Starts the profiles callers launch as jobs.
+    /// * `repository` - The repository the stored results and readings are read from.
+    /// * `api` - The REST API settings, whose tokens and certificates are reused.
+    /// * `address` - The address to listen on.
+    ///
+    /// # Returns
+    /// An instance of `GrpcServerAdapter`.
+    pub fn new(
+        logger: Arc<dyn LoggerPort>,
+        telemetry: Arc<dyn TelemetryPort>,
+        jobs: Arc<dyn JobPort>,
+        launcher: Arc<dyn JobLauncherPort>,
+        repository: Repository,
+        api: ApiConfig,
+        address: SocketAddr,
+    ) -> Self {
+        GrpcServerAdapter {
+            logger,
+            telemetry,
+            jobs,
+            launcher,
+            repository,
+            api: Arc::new(api),
+            address,
+            shutdown: CancellationToken::new(),
+        }
+    }
+
+    /// Checks that the caller presented a token whose role allows the call, as
+    /// the REST API does for its `/api/` requests.
+    fn authorize<T>(&self, request: &Request<T>, read_only: bool) -> Result<(), Status> {
+        if self.api.tokens.is_empty() {
+            return Ok(());
+        }
+        let presented = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::trim);
+        let peer = request
+            .remote_addr()
+            .map_or_else(|| "unknown".to_string(), |peer| peer.to_string());
+        let Some(token) = presented.and_then(|presented| self.api.find_token(presented)) else {
+            self.logger.log_warn(&format!(
+                "Rejected gRPC call from {}: missing or invalid API token",
+                peer
+            ));
+            return Err(Status::unauthenticated("A valid API token is required"));
+        };
+        if token.role.permits(read_only) {
+            return Ok(());
+        }
+        self.logger.log_warn(&format!(
+            "Rejected gRPC call from {}: {} token '{}' may not make changes",
+            peer,
+            token.role.label(),
+            token.name
+        ));
+        Err(Status::permission_denied(format!(
+            "The {} role may only read; an operator token is required",
+            token.role.label()
+        )))
+    }
+}
+
+// Implement the `ControlPlane` service for `GrpcServerAdapter`.
+#[tonic::async_trait]
+impl ControlPlane for GrpcServerAdapter {
+    type WatchJobStream = ReceiverStream<Result<proto::Job, Status>>;
+    type StreamTelemetryStream = ReceiverStream<Result<proto::TelemetryEvent, Status>>;
+
+    async fn list_jobs(
+        &self,
+        request: Request<proto::ListJobsRequest>,
+    ) -> Result<Response<proto::ListJobsResponse>, Status> {
+        self.authorize(&request, true)?;
+        let jobs = self.jobs.list_jobs().iter().map(proto::Job::from).collect();
+        Ok(Response::new(proto::ListJobsResponse { jobs }))
+    }
+
+    async fn get_job(
+        &self,
+        request: Request<proto::GetJobRequest>,
+    ) -> Result<Response<proto::Job>, Status> {
+        self.authorize(&request, true)?;
+        match self.jobs.get_job(request.get_ref().id) {
+            Some(job) => Ok(Response::new(proto::Job::from(&job))),
+            None => Err(Status::not_found("Job not found")),
+        }
+    }
+
+    async fn launch_profile(
+        &self,
+        request: Request<proto::LaunchProfileRequest>,
+    ) -> Result<Response<proto::Job>, Status> {
+        self.authorize(&request, false)?;
+        match self.launcher.launch_profile(&request.get_ref().profile) {
+            Ok(job) => Ok(Response::new(proto::Job::from(&job))),
+            Err(e) => Err(Status::invalid_argument(e)),
+        }
+    }
+
+    async fn cancel_job(
+        &self,
+        request: Request<proto::CancelJobRequest>,
+    ) -> Result<Response<proto::Job>, Status> {
+        self.authorize(&request, false)?;
+        let id = request.get_ref().id;
+        match self.jobs.cancel_job(id) {
+            Ok(job) => Ok(Response::new(proto::Job::from(&job))),
+            Err(e) if self.jobs.get_job(id).is_some() => Err(Status::failed_precondition(e)),
+            Err(e) => Err(Status::not_found(e)),
+        }
+    }
+
+    async fn watch_job(
+        &self,
+        request: Request<proto::WatchJobRequest>,
+    ) -> Result<Response<Self::WatchJobStream>, Status> {
+        self.authorize(&request, true)?;
+        let id = request.get_ref().id;
+        if self.jobs.get_job(id).is_none() {
+            return Err(Status::not_found("Job not found"));
+        }
+
+        let jobs = self.jobs.clone();
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+        tokio::spawn(async move {
+            let mut last_status = None;
+            while let Some(job) = jobs.get_job(id) {
+                if last_status != Some(job.status) {
+                    last_status = Some(job.status);
+                    if sender.send(Ok(proto::Job::from(&job))).await.is_err() {
+                        return;
+                    }
+                }
+                if job.status.is_finished() {
+                    return;
+                }
+                tokio::select! {
+                    _ = sleep(WATCH_INTERVAL) => {}
+                    _ = sender.closed() => return,
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+
+    async fn stream_telemetry(
+        &self,
+        request: Request<proto::StreamTelemetryRequest>,
+    ) -> Result<Response<Self::StreamTelemetryStream>, Status> {
+        self.authorize(&request, true)?;
+        let mut events = self.telemetry.subscribe();
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+        tokio::spawn(async move {
+            loop {
+                let event = tokio::select! {
+                    event = events.recv() => event,
+                    _ = sender.closed() => return,
+                };
+                let event = match event {
+                    Ok(event) => event,
+                    // A slow client skips the events it missed rather than disconnecting.
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return,
+                };
+                if sender.send(Ok(event.into())).await.is_err() {
+                    return;
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+
+    async fn list_results(
+        &self,
+        request: Request<proto::ListResultsRequest>,
+    ) -> Result<Response<proto::ListResultsResponse>, Status> {
+        self.authorize(&request, true)?;
+        let kinds: Vec<&str> = match request.get_ref().kind.as_str() {
+            "" => RESULT_KINDS.to_vec(),
+            kind if RESULT_KINDS.contains(&kind) => vec![kind],
+            kind => {
+                return Err(Status::invalid_argument(format!(
+                    "Unknown result kind '{}'; expected one of {}",
+                    kind,
+                    RESULT_KINDS.join(", ")
+                )))
+            }
+        };
+
+        let since = parse_time_bound("since", &request.get_ref().since)?;
+        let until = parse_time_bound("until", &request.get_ref().until)?;
+
+        let tags: Tags = request
+            .get_ref()
+            .tags
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
+        let mut results = Vec::new();
+        for kind in kinds {
+            let keys = self
+                .repository
+                .find_runs(kind, since.as_ref(), until.as_ref(), &tags)
+                .map_err(Status::internal)?;
+            results.extend(keys.into_iter().map(|key| proto::ResultSummary {
+                key,
+                kind: kind.to_string(),
+            }));
+        }
+        Ok(Response::new(proto::ListResultsResponse { results }))
+    }
+
+    async fn get_result(
+        &self,
+        request: Request<proto::GetResultRequest>,
+    ) -> Result<Response<proto::StoredResult>, Status> {
+        self.authorize(&request, true)?;
+        let key = &request.get_ref().key;
+        let not_found = || Status::not_found(format!("No result stored under '{}'", key));
+        let kind = result_kind(key).ok_or_else(not_found)?;
+        let value: serde_json::Value = self
+            .repository
+            .get_run(key)
+            .map_err(Status::internal)?
+            .ok_or_else(not_found)?;
+        Ok(Response::new(proto::StoredResult {
+            key: key.clone(),
+            kind: kind.to_string(),
+            json: value.to_string(),
+        }))
+    }
+
+    async fn list_samples(
+        &self,
+        request: Request<proto::ListSamplesRequest>,
+    ) -> Result<Response<proto::ListSamplesResponse>, Status> {
+        self.authorize(&request, true)?;
+        let collector = &request.get_ref().collector;
+        validate_collector(collector).map_err(Status::invalid_argument)?;
+        let since = parse_time_bound("since", &request.get_ref().since)?;
+        let until = parse_time_bound("until", &request.get_ref().until)?;
+        let samples = self
+            .repository
+            .get_samples_in_range::<serde_json::Value>(collector, since.as_ref(), until.as_ref())
+            .map_err(Status::internal)?;
+        Ok(Response::new(proto::ListSamplesResponse {
+            samples: samples
+                .into_iter()
+                .map(|(recorded_at, stored)| {
+                    let time = stored.time.unwrap_or_default();
+                    proto::StoredSample {
+                        recorded_at: key_time(&recorded_at),
+                        json: stored.sample.to_string(),
+                        wall_time: time.wall,
+                        monotonic_ns: time.monotonic_ns,
+                        boot_id: time.boot_id,
+                    }
+                })
+                .collect(),
+        }))
+    }
+}
+
+// Implement the `WebServerPort` trait for `GrpcServerAdapter`.
+#[async_trait::async_trait]
+impl WebServerPort for GrpcServerAdapter {
+    async fn start_server(&self) -> io::Result<()> {
+        let mut server = Server::builder();
+        if let Some(tls) = &self.api.tls {
+            server = tls_config(tls)
+                .and_then(|config| server.tls_config(config).map_err(|e| e.to_string()))
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        }
+
+        if !self.api.is_authenticated() && !self.address.ip().is_loopback() {
+            self.logger.log_warn(&format!(
+                "The gRPC control plane on {} accepts calls from other machines without \
+                 authentication; set a token or a client CA in the [api] configuration.",
+                self.address
+            ));
+        }
+        self.logger.log_info(&format!(
+            "Serving the gRPC control plane{} on {}{}{}",
+            if self.api.tls.is_some() {
+                " over TLS"
+            } else {
+                ""
+            },
+            self.address,
+            if !self.api.tokens.is_empty() {
+                ", token required"
+            } else {
+                ""
+            },
+            if self.api.requires_client_certificate() {
+                ", client certificate required"
+            } else {
+                ""
+            }
+        ));
+
+        let shutdown = self.shutdown.clone();
+        server
+            .add_service(ControlPlaneServer::new(self.clone()))
+            .serve_with_shutdown(self.address, async move { shutdown.cancelled().await })
+            .await
+            .map_err(io::Error::other)
+    }
+
+    async fn stop_server(&self) {
+        self.shutdown.cancel();
+    }
+}
@@ -0,0 +1,244 @@
+//! Hardware Discovery
+//!
+//! This module runs every discovery adapter in turn and gathers their sections
+//! into the report of `discover`, along with the snapshot of the hardware that
+//! `discover --diff` compares and the facts `discover --expect` checks. An
+//! adapter that fails is skipped with a warning, so one unreadable subsystem
+//! does not hide the rest of the machine. The Python bindings run the same
+//! discovery.
+
+use std::sync::Arc;
+
+use common::ports::log_port::LoggerPort;
+
+use crate::adapters::battery_adapter::BatteryAdapter;
+use crate::adapters::cgroup_adapter::CgroupAdapter;
+use crate::adapters::cpu_features_adapter::CpuFeaturesAdapter;
+use crate::adapters::entropy_adapter::EntropyAdapter;
+use crate::adapters::firmware_adapter::FirmwareAdapter;
+use crate::adapters::gpu_interconnect_adapter::GpuInterconnectAdapter;
+use crate::adapters::nvme_adapter::NvmeAdapter;
+use crate::adapters::pcie_adapter::PcieAdapter;
+use crate::adapters::rdma_adapter::RdmaAdapter;
+use crate::adapters::smbios_adapter::SmbiosAdapter;
+use crate::adapters::topology_adapter::TopologyAdapter;
+use crate::adapters::virtualization_adapter::VirtualizationAdapter;
+use crate::domain::battery::battery_section;
+use crate::domain::cache::cache_section;
+use crate::domain::cloud::{cloud_section, InstanceMetadata};
+use crate::domain::container::container_section;
+use crate::domain::dimm::{dimm_section, dimm_warnings};
+use crate::domain::discovery::DiscoveryReport;
+use crate::domain::entropy::{entropy_section, entropy_warnings};
+use crate::domain::firmware::firmware_section;
+use crate::domain::gpu_interconnect::interconnect_section;
+use crate::domain::hardware_changes::HardwareSnapshot;
+use crate::domain::hardware_manifest::MachineFacts;
+use crate::domain::numa::numa_section;
+use crate::domain::nvme::nvme_section;
+use crate::domain::pcie::{bandwidth_section, pcie_section, CLASS_NVME};
+use crate::domain::rdma::rdma_section;
+use crate::domain::virtualization::virtualization_section;
+use crate::ports::battery_port::BatteryPort;
+use crate::ports::container_port::ContainerPort;
+use crate::ports::cpu_features_port::CpuFeaturesPort;
+use crate::ports::dimm_port::DimmPort;
+use crate::ports::entropy_port::EntropyPort;
+use crate::ports::firmware_port::FirmwarePort;
+use crate::ports::gpu_interconnect_port::GpuInterconnectPort;
+use crate::ports::nvme_port::NvmePort;
+use crate::ports::pcie_port::PciePort;
+use crate::ports::rdma_port::RdmaPort;
+use crate::ports::topology_port::TopologyPort;
+use crate::ports::virtualization_port::VirtualizationPort;
+
+/// Discovers the hardware of the machine.
+pub struct HardwareDiscovery {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    cloud_instance: Option<InstanceMetadata>,
+    bandwidth: bool,
+}
+
+impl HardwareDiscovery {
+    /// Creates a new instance of `HardwareDiscovery`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `HardwareDiscovery`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        HardwareDiscovery {
+            logger,
+            cloud_instance: None,
+            bandwidth: false,
+        }
+    }
+
+    /// Describes the cloud instance the machine is, if it is one.
+    pub fn with_cloud_instance(mut self, cloud_instance: Option<InstanceMetadata>) -> Self {
+        self.cloud_instance = cloud_instance;
+        self
+    }
+
+    /// Also measures the PCIe bandwidth of the NVMe drives and GPUs, which
+    /// takes a few seconds per device.
+    pub fn with_bandwidth(mut self, bandwidth: bool) -> Self {
+        self.bandwidth = bandwidth;
+        self
+    }
+
+    /// Runs every discovery adapter.
+    ///
+    /// # Returns
+    /// The report, whose snapshot holds the hardware discovered, and the facts
+    /// a hardware manifest is checked against. The memory, disks and network
+    /// interfaces seen by the kernel are left for the caller to add.
+    pub fn discover(&self) -> (DiscoveryReport, MachineFacts) {
+        let logger = &self.logger;
+        let mut report = DiscoveryReport::default();
+        let mut snapshot = HardwareSnapshot::default();
+        let mut facts = MachineFacts::default();
+        let topology_adapter = TopologyAdapter::new(logger.clone());
+
+        match topology_adapter.discover_cpu_architecture() {
+            Ok(architecture) => {
+                report.sections.push(architecture.discovery_section());
+                facts.cpu_model = architecture.model_name;
+            }
+            Err(e) => logger.log_warn(&format!("Skipping CPU architecture: {}", e)),
+        }
+        match CpuFeaturesAdapter::new(logger.clone()).discover_cpu_features() {
+            Ok(features) => report.sections.push(features.discovery_section()),
+            Err(e) => logger.log_warn(&format!("Skipping CPU features: {}", e)),
+        }
+        match topology_adapter.discover_cpu_topology() {
+            Ok(topology) => {
+                report.sections.push(topology.discovery_section());
+                facts.sockets = topology.package_count();
+                facts.cores = topology.physical_cpu_ids().len();
+                facts.threads = topology.cpus.len();
+            }
+            Err(e) => logger.log_warn(&format!("Skipping CPU topology: {}", e)),
+        }
+        match VirtualizationAdapter::new(logger.clone()).detect_virtualization() {
+            Ok(info) => report.sections.push(virtualization_section(&info)),
+            Err(e) => logger.log_warn(&format!("Skipping virtualization: {}", e)),
+        }
+        if let Some(instance) = &self.cloud_instance {
+            report.sections.push(cloud_section(instance));
+        }
+        match EntropyAdapter::new(logger.clone()).check_entropy() {
+            Ok(state) => {
+                for warning in entropy_warnings(&state) {
+                    logger.log_warn(&format!("Entropy: {}", warning));
+                }
+                report.sections.push(entropy_section(&state));
+            }
+            Err(e) => logger.log_warn(&format!("Skipping entropy: {}", e)),
+        }
+        // The limits only matter inside a container or a limited cgroup.
+        match CgroupAdapter::new(logger.clone()).read_resource_limits() {
+            Ok(limits)
+                if limits.runtime.is_some()
+                    || limits.is_cpu_limited()
+                    || limits.is_memory_limited() =>
+            {
+                report.sections.push(container_section(&limits));
+            }
+            Ok(_) => {}
+            Err(e) => logger.log_warn(&format!("Skipping container limits: {}", e)),
+        }
+        match topology_adapter.discover_caches(0) {
+            Ok(caches) => report.sections.push(cache_section(&caches)),
+            Err(e) => logger.log_warn(&format!("Skipping cache hierarchy: {}", e)),
+        }
+        match topology_adapter.discover_numa_nodes() {
+            Ok(nodes) => report.sections.push(numa_section(&nodes)),
+            Err(e) => logger.log_warn(&format!("Skipping NUMA topology: {}", e)),
+        }
+        match FirmwareAdapter::new(logger.clone()).discover_firmware() {
+            Ok(inventory) if !inventory.is_empty() => {
+                report.sections.push(firmware_section(&inventory));
+                snapshot.firmware = inventory;
+            }
+            Ok(_) => {}
+            Err(e) => logger.log_warn(&format!("Skipping firmware: {}", e)),
+        }
+        match SmbiosAdapter::new(logger.clone()).discover_dimms() {
+            Ok(dimms) if !dimms.is_empty() => {
+                for warning in dimm_warnings(&dimms) {
+                    logger.log_warn(&format!("Memory population: {}", warning));
+                }
+                report.sections.push(dimm_section(&dimms));
+                facts.dimms = dimms.clone();
+                snapshot.dimms = dimms;
+            }
+            Ok(_) => {}
+            Err(e) => logger.log_warn(&format!("Skipping memory modules: {}", e)),
+        }
+        let pcie_adapter = PcieAdapter::new(logger.clone());
+        match pcie_adapter.discover_pcie_links() {
+            Ok(links) => {
+                report.sections.push(pcie_section(&links));
+                if self.bandwidth {
+                    let mut bandwidths = Vec::new();
+                    for link in links.iter().filter(|link| link.class == CLASS_NVME) {
+                        match pcie_adapter.measure_nvme_bandwidth(link) {
+                            Ok(measured) => bandwidths.push(measured),
+                            Err(e) => logger.log_warn(&format!("Skipping NVMe bandwidth: {}", e)),
+                        }
+                    }
+                    match pcie_adapter.measure_gpu_bandwidth(&links) {
+                        Ok(measured) => bandwidths.extend(measured),
+                        Err(e) => logger.log_warn(&format!("Skipping GPU bandwidth: {}", e)),
+                    }
+                    if !bandwidths.is_empty() {
+                        report.sections.push(bandwidth_section(&bandwidths));
+                    }
+                }
+                snapshot.pcie_links = links;
+            }
+            Err(e) => logger.log_warn(&format!("Skipping PCIe links: {}", e)),
+        }
+        let nvme_adapter = NvmeAdapter::new(logger.clone());
+        match nvme_adapter.discover_nvme_controllers() {
+            Ok(controllers) if !controllers.is_empty() => {
+                let health: Vec<_> = controllers
+                    .iter()
+                    .map(|controller| nvme_adapter.read_nvme_health(&controller.name))
+                    .collect();
+                report.sections.push(nvme_section(&controllers, &health));
+                snapshot.nvme = controllers;
+            }
+            Ok(_) => {}
+            Err(e) => logger.log_warn(&format!("Skipping NVMe drives: {}", e)),
+        }
+        match RdmaAdapter::new(logger.clone()).discover_rdma_devices() {
+            Ok(devices) if !devices.is_empty() => {
+                report.sections.push(rdma_section(&devices));
+            }
+            Ok(_) => {}
+            Err(e) => logger.log_warn(&format!("Skipping RDMA devices: {}", e)),
+        }
+        // A single GPU has no interconnect to map.
+        match GpuInterconnectAdapter::new(logger.clone()).discover_gpu_topology() {
+            Ok(topology) if topology.gpus.len() > 1 => {
+                report.sections.push(interconnect_section(&topology));
+            }
+            Ok(_) => {}
+            Err(e) => logger.log_warn(&format!("Skipping GPU interconnect: {}", e)),
+        }
+        // Only laptops and other battery-powered machines have a battery to describe.
+        match BatteryAdapter::new(logger.clone()).read_power_supplies() {
+            Ok(state) if !state.batteries.is_empty() => {
+                report.sections.push(battery_section(&state));
+            }
+            Ok(_) => {}
+            Err(e) => logger.log_warn(&format!("Skipping battery: {}", e)),
+        }
+
+        report.snapshot = Some(snapshot);
+        (report, facts)
+    }
+}
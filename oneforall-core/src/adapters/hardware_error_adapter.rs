@@ -0,0 +1,317 @@
+//! Hardware Error Adapter
+//!
+//! This module provides an adapter that reads ECC error counters from the
+//! EDAC sysfs interface, counts machine check events in the kernel log with
+//! `dmesg`, reads drive health and wear with `smartctl`, and reads and clears
+//! the System Event Log of the BMC with `ipmitool`.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+
+use common::ports::log_port::LoggerPort;
+
+use crate::adapters::chaos_injector::injected_ecc_errors;
+use crate::adapters::firmware_adapter::IPMI_DEVICES;
+use crate::domain::endurance::WearIndicators;
+use crate::domain::hardware_errors::{EccCounts, SmartHealth};
+use crate::domain::kernel_log::MACHINE_CHECK_MARKERS;
+use crate::domain::sel::{parse_sel_elist, SelEntry};
+use crate::ports::hardware_error_port::HardwareErrorPort;
+
+/// Root of the EDAC memory controller hierarchy.
+const EDAC_MC_ROOT: &str = "/sys/devices/system/edac/mc";
+
+/// ATA SMART attributes counting sectors the drive could not read or had to remap.
+const ATA_ERROR_ATTRIBUTES: &[u32] = &[5, 187, 197, 198];
+
+/// The size of an NVMe "data unit": one thousand 512-byte sectors.
+const NVME_DATA_UNIT_BYTES: u64 = 512_000;
+
+/// ATA SMART attributes counting host writes, with the bytes per raw unit.
+const ATA_HOST_WRITE_ATTRIBUTES: &[(&str, u64)] = &[
+    ("Total_LBAs_Written", 512),
+    ("Host_Writes_32MiB", 32 << 20),
+    ("Host_Writes_GiB", 1 << 30),
+    ("Lifetime_Writes_GiB", 1 << 30),
+];
+
+/// ATA SMART attributes counting NAND writes, with the bytes per raw unit.
+const ATA_NAND_WRITE_ATTRIBUTES: &[(&str, u64)] = &[
+    ("NAND_Writes_1GiB", 1 << 30),
+    ("NAND_Writes_GiB", 1 << 30),
+    ("NAND_Writes_32MiB", 32 << 20),
+];
+
+/// ATA SMART attributes whose normalized value counts down from 100 as the drive wears.
+const ATA_LIFE_ATTRIBUTES: &[&str] = &[
+    "Wear_Leveling_Count",
+    "Media_Wearout_Indicator",
+    "SSD_Life_Left",
+    "Percent_Lifetime_Remain",
+];
+
+/// Represents the EDAC, kernel log, `smartctl` and `ipmitool` hardware error adapter.
+pub struct HardwareErrorAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl HardwareErrorAdapter {
+    /// Creates a new instance of `HardwareErrorAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `HardwareErrorAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        HardwareErrorAdapter { logger }
+    }
+
+    /// Reads a counter file, treating unreadable files as zero.
+    fn read_counter(path: &str) -> u64 {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|text| text.trim().parse().ok())
+            .unwrap_or(0)
+    }
+
+    /// Lists the drives found by `smartctl --scan` with their device type arguments.
+    fn scan_drives(&self) -> Result<Vec<Vec<String>>, String> {
+        let output = Command::new("smartctl")
+            .arg("--scan")
+            .output()
+            .map_err(|e| format!("Failed to run smartctl: {}", e))?;
+
+        // Lines look like `/dev/sda -d sat # /dev/sda [SAT], ATA device`.
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let args: Vec<String> = line
+                    .split('#')
+                    .next()?
+                    .split_whitespace()
+                    .map(str::to_string)
+                    .collect();
+                (!args.is_empty()).then_some(args)
+            })
+            .collect())
+    }
+
+    /// Parses the output of `smartctl -H -A` for a single drive.
+    fn parse_smart_output(device: &str, output: &str) -> SmartHealth {
+        let mut passed = true;
+        let mut error_count = 0;
+
+        for line in output.lines() {
+            let line = line.trim();
+            if line.contains("self-assessment test result:")
+                || line.starts_with("SMART Health Status:")
+            {
+                passed = line.ends_with("PASSED") || line.ends_with("OK");
+            } else if let Some(value) = line.strip_prefix("Media and Data Integrity Errors:") {
+                error_count += value.trim().replace(',', "").parse::<u64>().unwrap_or(0);
+            } else {
+                // ATA attribute rows: ID NAME FLAG VALUE WORST THRESH TYPE UPDATED WHEN_FAILED RAW_VALUE
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                let id = fields.first().and_then(|f| f.parse::<u32>().ok());
+                if let (Some(id), Some(raw)) = (id, fields.get(9)) {
+                    if ATA_ERROR_ATTRIBUTES.contains(&id) {
+                        let digits: String = raw.chars().take_while(char::is_ascii_digit).collect();
+                        error_count += digits.parse::<u64>().unwrap_or(0);
+                    }
+                }
+            }
+        }
+
+        SmartHealth {
+            device: device.to_string(),
+            passed,
+            error_count,
+        }
+    }
+
+    /// Parses a number that `smartctl` may print with thousands separators and a
+    /// trailing unit, e.g. `12,345,678 [6.32 TB]` or `3%`.
+    fn parse_leading_number(text: &str) -> Option<u64> {
+        let digits: String = text
+            .trim()
+            .chars()
+            .take_while(|c| c.is_ascii_digit() || *c == ',')
+            .filter(char::is_ascii_digit)
+            .collect();
+        digits.parse().ok()
+    }
+
+    /// Parses the wear indicators from the output of `smartctl -A` for a single drive.
+    fn parse_wear_output(output: &str) -> WearIndicators {
+        let mut wear = WearIndicators::default();
+
+        for line in output.lines() {
+            let line = line.trim();
+            if let Some(value) = line.strip_prefix("Percentage Used:") {
+                wear.percentage_used = Self::parse_leading_number(value);
+            } else if let Some(value) = line.strip_prefix("Data Units Written:") {
+                wear.host_bytes_written =
+                    Self::parse_leading_number(value).map(|units| units * NVME_DATA_UNIT_BYTES);
+            } else if let Some(value) = line.strip_prefix("Media and Data Integrity Errors:") {
+                wear.media_errors = Self::parse_leading_number(value);
+            } else {
+                // ATA attribute rows: ID NAME FLAG VALUE WORST THRESH TYPE UPDATED WHEN_FAILED RAW_VALUE
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                let Some(id) = fields.first().and_then(|f| f.parse::<u32>().ok()) else {
+                    continue;
+                };
+                let (Some(name), Some(value), Some(raw)) =
+                    (fields.get(1), fields.get(3), fields.get(9))
+                else {
+                    continue;
+                };
+                let raw = Self::parse_leading_number(raw);
+                if let Some((_, unit)) = ATA_HOST_WRITE_ATTRIBUTES.iter().find(|(n, _)| n == name) {
+                    wear.host_bytes_written = raw.map(|raw| raw * unit);
+                } else if let Some((_, unit)) =
+                    ATA_NAND_WRITE_ATTRIBUTES.iter().find(|(n, _)| n == name)
+                {
+                    wear.nand_bytes_written = raw.map(|raw| raw * unit);
+                } else if ATA_LIFE_ATTRIBUTES.contains(name) {
+                    wear.percentage_used = value
+                        .parse::<u64>()
+                        .ok()
+                        .map(|remaining| 100u64.saturating_sub(remaining));
+                } else if ATA_ERROR_ATTRIBUTES.contains(&id) {
+                    *wear.media_errors.get_or_insert(0) += raw.unwrap_or(0);
+                }
+            }
+        }
+
+        wear
+    }
+
+    /// Runs `ipmitool` through the IPMI driver and returns its output.
+    pub(crate) fn ipmitool(&self, args: &[&str]) -> Result<String, String> {
+        if !IPMI_DEVICES.iter().any(|device| Path::new(device).exists()) {
+            return Err("no IPMI device, so no BMC to ask".to_string());
+        }
+        self.logger
+            .log_debug(&format!("Running ipmitool {}", args.join(" ")));
+        let output = Command::new("ipmitool")
+            .args(args)
+            .output()
+            .map_err(|e| format!("Failed to run ipmitool: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "ipmitool {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+// Implement the `HardwareErrorPort` trait for `HardwareErrorAdapter`.
+impl HardwareErrorPort for HardwareErrorAdapter {
+    fn read_ecc_counts(&self) -> Result<EccCounts, String> {
+        let mut counts = EccCounts::default();
+        for entry in fs::read_dir(EDAC_MC_ROOT).into_iter().flatten().flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !name.starts_with("mc") {
+                continue;
+            }
+            counts.controllers += 1;
+            counts.corrected += Self::read_counter(&format!("{}/{}/ce_count", EDAC_MC_ROOT, name));
+            counts.uncorrected +=
+                Self::read_counter(&format!("{}/{}/ue_count", EDAC_MC_ROOT, name));
+        }
+
+        // `--chaos` injects its error even on machines without EDAC, so it can be seen everywhere.
+        let injected = injected_ecc_errors();
+        if counts.controllers == 0 && injected.is_none() {
+            return Err("no EDAC memory controllers (ECC reporting unavailable)".to_string());
+        }
+        counts.corrected += injected.unwrap_or(0);
+        Ok(counts)
+    }
+
+    fn count_machine_checks(&self) -> Result<u64, String> {
+        let output = Command::new("dmesg")
+            .output()
+            .map_err(|e| format!("Failed to run dmesg: {}", e))?;
+        if !output.status.success() {
+            let message = format!(
+                "Failed to read the kernel log: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            self.logger.log_trace(&message);
+            return Err(message);
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| MACHINE_CHECK_MARKERS.iter().any(|m| line.contains(m)))
+            .count() as u64)
+    }
+
+    fn read_smart_health(&self) -> Result<Vec<SmartHealth>, String> {
+        let drives = self.scan_drives()?;
+        if drives.is_empty() {
+            return Err("smartctl found no drives".to_string());
+        }
+
+        let mut health = Vec::new();
+        for args in drives {
+            let device = args[0].clone();
+            self.logger
+                .log_debug(&format!("Reading SMART health of {}", device));
+            // smartctl sets status bits for warnings too, so the output is parsed regardless.
+            match Command::new("smartctl")
+                .args(["-H", "-A"])
+                .args(&args)
+                .output()
+            {
+                Ok(output) => health.push(Self::parse_smart_output(
+                    &device,
+                    &String::from_utf8_lossy(&output.stdout),
+                )),
+                Err(e) => {
+                    self.logger
+                        .log_warn(&format!("Failed to read SMART data of {}: {}", device, e));
+                }
+            }
+        }
+        Ok(health)
+    }
+
+    fn read_wear(&self, device: &str) -> Result<WearIndicators, String> {
+        self.logger
+            .log_debug(&format!("Reading SMART wear indicators of {}", device));
+        // smartctl sets status bits for warnings too, so only a missing attribute table is an error.
+        let output = Command::new("smartctl")
+            .arg("-A")
+            .arg(device)
+            .output()
+            .map_err(|e| format!("Failed to run smartctl: {}", e))?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let wear = Self::parse_wear_output(&stdout);
+        if wear == WearIndicators::default() {
+            return Err(format!(
+                "smartctl reported no wear indicators for {}",
+                device
+            ));
+        }
+        Ok(wear)
+    }
+
+    fn read_sel(&self) -> Result<Vec<SelEntry>, String> {
+        let output = self.ipmitool(&["sel", "elist"])?;
+        Ok(parse_sel_elist(&output))
+    }
+
+    fn clear_sel(&self) -> Result<(), String> {
+        self.ipmitool(&["sel", "clear"])?;
+        self.logger.log_info("Cleared the BMC event log.");
+        Ok(())
+    }
+}
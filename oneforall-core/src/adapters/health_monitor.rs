@@ -0,0 +1,158 @@
+//! Health Monitor
+//!
+//! This module checks the subsystems a running instance needs to do its work,
+//! for the `/healthz` and `/readyz` probes of the REST API: that the database
+//! can be read, that buffered readings are being written to it, that the
+//! collectors started by Overwatch are still reporting, and what the job
+//! manager is doing. Once the instance starts shutting down it reports itself
+//! not ready, so load balancers stop sending it requests before it stops.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use common::domain::footprint::MAX_SLOWDOWN;
+use common::domain::health::{HealthCheck, HealthReport};
+use common::domain::job::JobStatus;
+use common::ports::health_port::HealthPort;
+use common::ports::job_port::JobPort;
+use common::ports::log_port::LoggerPort;
+
+use crate::adapters::repository::Repository;
+use crate::adapters::sample_writer::SampleWriter;
+
+/// Checks the database, the sample writer, the collectors and the job manager.
+pub struct HealthMonitor {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    repository: Repository,
+    samples: SampleWriter,
+    jobs: Arc<dyn JobPort>,
+    collectors: Mutex<BTreeMap<String, (Duration, Instant)>>, // how long each may go without a reading, since when
+    stopping: AtomicBool,
+}
+
+impl HealthMonitor {
+    /// Creates a new instance of `HealthMonitor`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    /// * `repository` - The database checked for availability.
+    /// * `samples` - The writer whose backlog and collectors are checked.
+    /// * `jobs` - The job manager reported on.
+    ///
+    /// # Returns
+    /// An instance of `HealthMonitor`.
+    pub fn new(
+        logger: Arc<dyn LoggerPort>,
+        repository: Repository,
+        samples: SampleWriter,
+        jobs: Arc<dyn JobPort>,
+    ) -> Self {
+        HealthMonitor {
+            logger,
+            repository,
+            samples,
+            jobs,
+            collectors: Mutex::new(BTreeMap::new()),
+            stopping: AtomicBool::new(false),
+        }
+    }
+
+    /// Starts checking that a collector keeps reporting. The footprint budget may
+    /// slow a collector down up to `MAX_SLOWDOWN` times, so it is only considered
+    /// stopped after twice as long as that without a reading.
+    ///
+    /// # Arguments
+    /// * `collector` - The collector, e.g. "processes".
+    /// * `interval` - The time between two of its readings.
+    pub fn watch_collector(&self, collector: &str, interval: Duration) {
+        if let Ok(mut collectors) = self.collectors.lock() {
+            collectors.insert(
+                collector.to_string(),
+                (interval.mul_f64(MAX_SLOWDOWN * 2.0), Instant::now()),
+            );
+        }
+    }
+
+    /// Reports the instance as not ready from now on, as it is shutting down.
+    pub fn set_stopping(&self) {
+        self.stopping.store(true, Ordering::Relaxed);
+    }
+
+    /// Checks that the database can be read.
+    fn check_database(&self) -> HealthCheck {
+        match self.repository.check() {
+            Ok(()) => HealthCheck::liveness("database", true, "available".to_string()),
+            Err(e) => HealthCheck::liveness("database", false, e),
+        }
+    }
+
+    /// Checks that buffered readings are being written.
+    fn check_writes(&self) -> HealthCheck {
+        match self.samples.backlog() {
+            Ok(waiting) => HealthCheck::readiness(
+                "readings",
+                true,
+                format!("{} waiting to be written", waiting),
+            ),
+            Err(e) => HealthCheck::readiness("readings", false, e),
+        }
+    }
+
+    /// Checks that every watched collector reported recently enough.
+    fn check_collectors(&self) -> Vec<HealthCheck> {
+        let collectors = self
+            .collectors
+            .lock()
+            .map(|collectors| collectors.clone())
+            .unwrap_or_default();
+        collectors
+            .into_iter()
+            .map(|(collector, (stale_after, watched_since))| {
+                let name = format!("collector {}", collector);
+                // A collector that has not reported yet is given as long as one that has.
+                let (age, detail) = match self.samples.last_recorded(&collector) {
+                    Some(age) => (age, format!("last reading {} s ago", age.as_secs())),
+                    None => (
+                        watched_since.elapsed(),
+                        "no reading yet since it started".to_string(),
+                    ),
+                };
+                HealthCheck::readiness(&name, age <= stale_after, detail)
+            })
+            .collect()
+    }
+
+    /// Reports what the job manager is doing; it stops taking jobs when shutting down.
+    fn check_jobs(&self) -> HealthCheck {
+        let jobs = self.jobs.list_jobs();
+        let count = |status: JobStatus| jobs.iter().filter(|job| job.status == status).count();
+        let detail = format!(
+            "{} running, {} queued",
+            count(JobStatus::Running),
+            count(JobStatus::Queued)
+        );
+        if self.stopping.load(Ordering::Relaxed) {
+            return HealthCheck::readiness("jobs", false, format!("shutting down; {}", detail));
+        }
+        HealthCheck::readiness("jobs", true, detail)
+    }
+}
+
+// Implement the `HealthPort` trait for `HealthMonitor`.
+impl HealthPort for HealthMonitor {
+    fn check_health(&self) -> HealthReport {
+        let mut checks = vec![self.check_database(), self.check_writes()];
+        checks.extend(self.check_collectors());
+        checks.push(self.check_jobs());
+        let report = HealthReport { checks };
+        for failure in report.failures() {
+            self.logger.log_debug(&format!(
+                "Health check '{}' failed: {}",
+                failure.name, failure.detail
+            ));
+        }
+        report
+    }
+}
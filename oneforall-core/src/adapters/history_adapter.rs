@@ -0,0 +1,301 @@
+//! History Adapter
+//!
+//! This module reads the time-series of a metric from the collector readings
+//! stored in the database, for the historical charts of the dashboard. The
+//! readings of the range are reduced to one value each and thinned to the
+//! number of points the query asks for; a frame of sub-second ticks gives a
+//! point per tick. Ranges too long for the points to be a minute or an hour
+//! apart are read from the rollups of that resolution instead, and the
+//! readings not rolled up yet are read as they are.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use common::domain::history::{
+    downsample, history_metric, History, HistoryMetric, HistoryPoint, HistoryQuery,
+    DEFAULT_HISTORY_HOURS,
+};
+use common::domain::telemetry::PowerSample;
+use common::ports::history_port::HistoryPort;
+use common::ports::log_port::LoggerPort;
+use serde::de::DeserializeOwned;
+
+use crate::adapters::repository::Repository;
+use crate::domain::microburst::{
+    BurstFrame, BurstSummary, BURSTS_COLLECTOR, BURST_SUMMARIES_COLLECTOR,
+};
+use crate::domain::ps_command::CgroupUsage;
+use crate::domain::rollup::{Resolution, Rollup};
+use crate::domain::virtualization::{StealSample, STEAL_COLLECTOR};
+
+/// Parses an optional RFC 3339 bound of a time range.
+fn parse_time_bound(field: &str, value: Option<&str>) -> Result<Option<DateTime<Utc>>, String> {
+    value
+        .map(|value| {
+            DateTime::parse_from_rfc3339(value)
+                .map(|time| time.with_timezone(&Utc))
+                .map_err(|e| format!("Invalid {} '{}': {}", field, value, e))
+        })
+        .transpose()
+}
+
+/// Reads the readings of a collector in a time range, reduced to one value each.
+///
+/// # Arguments
+/// * `repository` - Where the collector readings are stored.
+/// * `collector` - The collector, e.g. "power".
+/// * `from` - The earliest time included; the first reading when `None`.
+/// * `to` - The time the range ends before; open-ended when `None`.
+/// * `value` - Reduces a reading to its value, `None` when it has none.
+///
+/// # Returns
+/// A `Result` containing the values with the time each was taken at, oldest first, or an
+/// error if the readings could not be read.
+fn series<T: DeserializeOwned>(
+    repository: &Repository,
+    collector: &str,
+    from: Option<&DateTime<Utc>>,
+    to: Option<&DateTime<Utc>>,
+    value: impl Fn(&T) -> Option<f64>,
+) -> Result<Vec<(DateTime<Utc>, f64)>, String> {
+    let samples = repository.get_samples_in_range::<T>(collector, from, to)?;
+    Ok(samples
+        .into_iter()
+        .filter_map(|(recorded_at, stored)| value(&stored.sample).map(|value| (recorded_at, value)))
+        .collect())
+}
+
+/// Reads the values of a metric that is rolled up, one per stored reading.
+///
+/// # Arguments
+/// * `repository` - Where the collector readings are stored.
+/// * `metric` - The metric, e.g. the one named "cpu".
+/// * `from` - The earliest time included; the first reading when `None`.
+/// * `to` - The time the range ends before; open-ended when `None`.
+///
+/// # Returns
+/// A `Result` containing the values with the time each was taken at, oldest first, or an
+/// error if the readings could not be read or the metric is not rolled up.
+pub fn metric_values(
+    repository: &Repository,
+    metric: &HistoryMetric,
+    from: Option<&DateTime<Utc>>,
+    to: Option<&DateTime<Utc>>,
+) -> Result<Vec<(DateTime<Utc>, f64)>, String> {
+    let collector = metric.collector;
+    match metric.name {
+        "cpu" => series(
+            repository,
+            collector,
+            from,
+            to,
+            |usage: &Vec<CgroupUsage>| Some(usage.iter().map(|group| group.cpu_percent).sum()),
+        ),
+        "memory" => series(
+            repository,
+            collector,
+            from,
+            to,
+            |usage: &Vec<CgroupUsage>| Some(usage.iter().map(|group| group.memory_percent).sum()),
+        ),
+        "power" => series(repository, collector, from, to, PowerSample::package_watts),
+        STEAL_COLLECTOR => series(repository, collector, from, to, |sample: &StealSample| {
+            Some(sample.percent)
+        }),
+        name => Err(format!("The values of {} cannot be read", name)),
+    }
+}
+
+/// Converts values to the points of a history.
+fn raw_points(values: Vec<(DateTime<Utc>, f64)>) -> Vec<HistoryPoint> {
+    values
+        .into_iter()
+        .map(|(taken, value)| {
+            HistoryPoint::new(taken.to_rfc3339_opts(SecondsFormat::Millis, true), value)
+        })
+        .collect()
+}
+
+/// Represents the stored reading history adapter.
+pub struct HistoryAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    repository: Repository,
+}
+
+impl HistoryAdapter {
+    /// Creates a new instance of `HistoryAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    /// * `repository` - Where the collector readings are stored.
+    ///
+    /// # Returns
+    /// An instance of `HistoryAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>, repository: Repository) -> Self {
+        HistoryAdapter { logger, repository }
+    }
+
+    /// Reads a metric in a time range from its rollups, and from the readings before the
+    /// first rollup and after the last, which are not rolled up yet.
+    ///
+    /// # Arguments
+    /// * `metric` - The metric, which is rolled up.
+    /// * `resolution` - The rollups to read.
+    /// * `from` - The earliest time included.
+    /// * `to` - The time the range ends before; open-ended when `None`.
+    ///
+    /// # Returns
+    /// A `Result` containing the points, oldest first, with how many readings they stand
+    /// for, or an error if the rollups or the readings could not be read.
+    fn rolled_up(
+        &self,
+        metric: &HistoryMetric,
+        resolution: Resolution,
+        from: &DateTime<Utc>,
+        to: Option<&DateTime<Utc>>,
+    ) -> Result<(Vec<HistoryPoint>, usize), String> {
+        let rollups = self.repository.get_samples_in_range::<Rollup>(
+            &resolution.collector(metric.name),
+            Some(from),
+            to,
+        )?;
+        let window = chrono::Duration::from_std(resolution.window()).map_err(|e| e.to_string())?;
+        let (head_end, tail_start) = match (rollups.first(), rollups.last()) {
+            (Some((first, _)), Some((last, _))) => (*first, *last + window),
+            _ => (*from, *from),
+        };
+
+        let head = if head_end > *from {
+            metric_values(&self.repository, metric, Some(from), Some(&head_end))?
+        } else {
+            Vec::new()
+        };
+        let tail = if to.is_none_or(|to| tail_start < *to) {
+            metric_values(&self.repository, metric, Some(&tail_start), to)?
+        } else {
+            Vec::new()
+        };
+
+        let readings = head.len()
+            + tail.len()
+            + rollups
+                .iter()
+                .map(|(_, stored)| stored.sample.count)
+                .sum::<usize>();
+        let mut points = raw_points(head);
+        points.extend(rollups.into_iter().map(|(start, stored)| HistoryPoint {
+            timestamp: start.to_rfc3339_opts(SecondsFormat::Millis, true),
+            value: stored.sample.avg,
+            min: stored.sample.min,
+            max: stored.sample.max,
+            p99: Some(stored.sample.p99),
+        }));
+        points.extend(raw_points(tail));
+        Ok((points, readings))
+    }
+
+    /// Reads the busiest CPU over every sub-second tick in a time range, from the
+    /// frames still at full resolution and the summaries of those downsampled.
+    ///
+    /// # Arguments
+    /// * `from` - The earliest time included.
+    /// * `to` - The time the range ends before; open-ended when `None`.
+    ///
+    /// # Returns
+    /// A `Result` containing a point per tick or summary, oldest first, or an error if the
+    /// readings could not be read.
+    fn bursts(
+        &self,
+        from: &DateTime<Utc>,
+        to: Option<&DateTime<Utc>>,
+    ) -> Result<Vec<HistoryPoint>, String> {
+        let mut points: Vec<HistoryPoint> = self
+            .repository
+            .get_samples_in_range::<BurstSummary>(BURST_SUMMARIES_COLLECTOR, Some(from), to)?
+            .into_iter()
+            .map(|(start, stored)| HistoryPoint {
+                timestamp: start.to_rfc3339_opts(SecondsFormat::Millis, true),
+                value: stored.sample.peak_core_mean_percent,
+                min: stored.sample.peak_core_min_percent,
+                max: stored.sample.peak_core_max_percent,
+                p99: None,
+            })
+            .collect();
+        for (recorded_at, stored) in
+            self.repository
+                .get_samples_in_range::<BurstFrame>(BURSTS_COLLECTOR, Some(from), to)?
+        {
+            // A frame is stored under its last tick, each earlier one an interval before.
+            let frame = stored.sample;
+            let interval = Duration::microseconds((frame.interval_ms * 1000.0) as i64);
+            let ticks = frame.peak_core_percent.len() as i32;
+            points.extend(frame.peak_core_percent.iter().enumerate().map(|(i, peak)| {
+                let taken = recorded_at - interval * (ticks - 1 - i as i32);
+                HistoryPoint::new(taken.to_rfc3339_opts(SecondsFormat::Millis, true), *peak)
+            }));
+        }
+        points.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        Ok(points)
+    }
+}
+
+// Implement the `HistoryPort` trait for `HistoryAdapter`.
+impl HistoryPort for HistoryAdapter {
+    fn history(&self, query: &HistoryQuery) -> Result<History, String> {
+        let metric = history_metric(&query.metric)?;
+        let to = parse_time_bound("to", query.to.as_deref())?;
+        let from = match parse_time_bound("from", query.from.as_deref())? {
+            Some(from) => from,
+            None => to.unwrap_or_else(Utc::now) - Duration::hours(DEFAULT_HISTORY_HOURS),
+        };
+        if to.is_some_and(|to| to <= from) {
+            return Err(format!(
+                "The history must end after it starts at {}",
+                from.to_rfc3339()
+            ));
+        }
+
+        // Points further apart than a minute or an hour are read from the rollups of
+        // that resolution, which hold the extremes the thinning would keep anyway.
+        let end = to.unwrap_or_else(Utc::now);
+        let spacing = (end - from).to_std().unwrap_or_default() / query.max_points() as u32;
+        let resolution = Resolution::for_spacing(spacing).filter(|_| metric.rolled_up);
+        let (points, readings) = match (metric.name, resolution) {
+            ("bursts", _) => {
+                let points = self.bursts(&from, to.as_ref())?;
+                let readings = points.len();
+                (points, readings)
+            }
+            (_, Some(resolution)) => self.rolled_up(metric, resolution, &from, to.as_ref())?,
+            (_, None) => {
+                let points = raw_points(metric_values(
+                    &self.repository,
+                    metric,
+                    Some(&from),
+                    to.as_ref(),
+                )?);
+                let readings = points.len();
+                (points, readings)
+            }
+        };
+        self.logger.log_debug(&format!(
+            "Read {} point(s) standing for {} {} reading(s) for the history of {}",
+            points.len(),
+            readings,
+            metric.collector,
+            metric.name
+        ));
+
+        Ok(History {
+            metric: metric.name.to_string(),
+            unit: metric.unit.to_string(),
+            from: from.to_rfc3339_opts(SecondsFormat::Secs, true),
+            to: end.to_rfc3339_opts(SecondsFormat::Secs, true),
+            readings,
+            resolution: resolution
+                .map_or("raw", |resolution| resolution.name())
+                .to_string(),
+            points: downsample(points, query.max_points()),
+        })
+    }
+}
@@ -0,0 +1,393 @@
+//! Hook Adapter
+//!
+//! This module runs the hooks of test profiles. A command runs like the
+//! workload of `benchmark exec`, in a process group of its own that is killed
+//! with everything it started when the hook times out or is cancelled. A
+//! Redfish operation is sent to the BMC at `ONEFORALL_BMC_URL` with the
+//! credentials in `ONEFORALL_BMC_USERNAME` and `ONEFORALL_BMC_PASSWORD`,
+//! trusting the CA certificates in `ONEFORALL_BMC_CA` when the BMC presents a
+//! certificate of a lab CA. When the BMC answers with a task, as it does for a
+//! firmware update, the task is followed until it finishes; it is polled with
+//! the same credentials, so only a path on the BMC itself is followed. A BMC
+//! updating its own firmware stops answering for a while, so failed polls are
+//! retried until the timeout. The machine is rebooted with `systemctl reboot`, and
+//! power cycled with the Redfish `ComputerSystem.Reset` action of its first
+//! system when the BMC address is set, or with `ipmitool chassis power cycle`
+//! through the local IPMI device otherwise. `oneforall power-cycle` power cycles
+//! another machine the same way, through the BMC address it is given, with
+//! IPMI over LAN for a BMC given by its host.
+
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use common::ports::log_port::LoggerPort;
+use serde_json::Value;
+use tokio_util::sync::CancellationToken;
+use ureq::tls::{RootCerts, TlsConfig};
+
+use crate::adapters::hardware_error_adapter::HardwareErrorAdapter;
+use crate::adapters::jobs_client_adapter::read_certificates;
+use crate::adapters::time_sync_adapter::read_boot_id;
+use crate::adapters::workload_adapter::WorkloadAdapter;
+use crate::domain::hook::{
+    redfish_task_outcome, redfish_task_path, HookAction, RedfishOperation, TaskOutcome,
+};
+use crate::domain::power_cycle::BmcTarget;
+use crate::ports::hook_port::HookPort;
+use crate::ports::workload_port::WorkloadPort;
+
+/// The environment variable holding the address of the BMC, e.g. "https://10.0.0.5".
+pub const BMC_URL_ENV: &str = "ONEFORALL_BMC_URL";
+
+/// The environment variable holding the user name Redfish operations log in with.
+pub const BMC_USERNAME_ENV: &str = "ONEFORALL_BMC_USERNAME";
+
+/// The environment variable holding the password Redfish operations log in with.
+pub const BMC_PASSWORD_ENV: &str = "ONEFORALL_BMC_PASSWORD";
+
+/// The environment variable naming a PEM file of CA certificates the BMC certificate is checked against.
+pub const BMC_CA_ENV: &str = "ONEFORALL_BMC_CA";
+
+/// Time allowed for the BMC to answer a request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How often a task the BMC started is checked for having finished.
+const TASK_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long a task the BMC started may run when the hook sets no timeout;
+/// firmware updates of some BMCs take the better part of an hour.
+const DEFAULT_TASK_TIMEOUT: Duration = Duration::from_secs(2 * 3600);
+
+/// A response of the BMC: its status, the `Location` header and the body.
+type BmcResponse = (u16, Option<String>, String);
+
+/// Reads an environment variable the Redfish hooks need.
+fn required_env(name: &str) -> Result<String, String> {
+    std::env::var(name)
+        .ok()
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| {
+            format!(
+                "{} is not set; operations through the BMC need its address and credentials",
+                name
+            )
+        })
+}
+
+/// Represents the command and Redfish hook adapter.
+pub struct HookAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    workloads: WorkloadAdapter,  // runs the commands of hooks
+}
+
+impl HookAdapter {
+    /// Creates a new instance of `HookAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `HookAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        HookAdapter {
+            workloads: WorkloadAdapter::new(logger.clone()),
+            logger,
+        }
+    }
+
+    /// Builds the HTTP client for the BMC at an address, the value of its `Authorization`
+    /// header and the address without a trailing slash.
+    fn bmc_client(base_url: &str) -> Result<(ureq::Agent, String, String), String> {
+        let base_url = base_url.trim_end_matches('/').to_string();
+        let username = required_env(BMC_USERNAME_ENV)?;
+        let password = required_env(BMC_PASSWORD_ENV)?;
+        let mut tls = TlsConfig::builder();
+        if let Ok(ca) = std::env::var(BMC_CA_ENV) {
+            let roots = read_certificates(Path::new(&ca))?;
+            tls = tls.root_certs(RootCerts::new_with_certs(&roots));
+        }
+        let agent = ureq::Agent::config_builder()
+            .http_status_as_error(false)
+            .timeout_global(Some(REQUEST_TIMEOUT))
+            .tls_config(tls.build())
+            .build()
+            .into();
+        let authorization = format!(
+            "Basic {}",
+            STANDARD.encode(format!("{}:{}", username, password))
+        );
+        Ok((agent, authorization, base_url))
+    }
+
+    /// Sends a request to the BMC, turning an error status into an error message.
+    fn send(
+        agent: &ureq::Agent,
+        authorization: &str,
+        method: &str,
+        url: &str,
+        body: Option<&str>,
+    ) -> Result<BmcResponse, String> {
+        let response = match method {
+            "GET" => agent.get(url).header("Authorization", authorization).call(),
+            "DELETE" => agent
+                .delete(url)
+                .header("Authorization", authorization)
+                .call(),
+            _ => {
+                let request = match method {
+                    "POST" => agent.post(url),
+                    "PATCH" => agent.patch(url),
+                    _ => agent.put(url),
+                };
+                // Actions without parameters still expect a JSON object.
+                request
+                    .header("Authorization", authorization)
+                    .header("Content-Type", "application/json")
+                    .send(body.unwrap_or("{}"))
+            }
+        };
+        let mut response = response.map_err(|e| format!("Failed to reach {}: {}", url, e))?;
+        let status = response.status();
+        let location = response
+            .headers()
+            .get("Location")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body = response
+            .body_mut()
+            .read_to_string()
+            .map_err(|e| format!("Failed to read the response of {}: {}", url, e))?;
+        if !status.is_success() {
+            return Err(format!(
+                "{} {} answered {}: {}",
+                method,
+                url,
+                status,
+                body.trim()
+            ));
+        }
+        Ok((status.as_u16(), location, body))
+    }
+
+    /// Sends a Redfish operation and waits for the task it starts, if any.
+    fn run_redfish(
+        &self,
+        operation: &RedfishOperation,
+        timeout: Option<Duration>,
+        cancel: &CancellationToken,
+    ) -> Result<String, String> {
+        let (agent, authorization, base_url) = Self::bmc_client(&required_env(BMC_URL_ENV)?)?;
+        let started = Instant::now();
+        let url = format!("{}{}", base_url, operation.path);
+        self.logger
+            .log_info(&format!("Sending Redfish {} {}", operation.method, url));
+        let (status, location, body) = Self::send(
+            &agent,
+            &authorization,
+            &operation.method,
+            &url,
+            operation.body.as_deref(),
+        )?;
+
+        // A task is announced by 202 Accepted, with its monitor in `Location`
+        // or the task itself in the body.
+        let task: Value = serde_json::from_str(&body).unwrap_or(Value::Null);
+        let task_path = task["@odata.id"]
+            .as_str()
+            .filter(|_| task.get("TaskState").is_some())
+            .map(str::to_string)
+            .or(location.filter(|_| status == 202));
+        let Some(task_path) = task_path else {
+            return Ok(format!(
+                "Redfish {} {} answered {}",
+                operation.method, operation.path, status
+            ));
+        };
+        let task_url = format!("{}{}", base_url, redfish_task_path(&base_url, &task_path)?);
+        self.logger
+            .log_info(&format!("Waiting for the BMC task {}", task_url));
+
+        let timeout = timeout.unwrap_or(DEFAULT_TASK_TIMEOUT);
+        loop {
+            if cancel.is_cancelled() {
+                return Err(format!(
+                    "Cancelled while waiting for the BMC task {}",
+                    task_url
+                ));
+            }
+            if started.elapsed() >= timeout {
+                return Err(format!(
+                    "The BMC task {} did not finish within {:.0} s",
+                    task_url,
+                    timeout.as_secs_f64()
+                ));
+            }
+            sleep(TASK_POLL_INTERVAL);
+            let (status, _, body) = match Self::send(&agent, &authorization, "GET", &task_url, None)
+            {
+                Ok(response) => response,
+                Err(e) => {
+                    self.logger
+                        .log_debug(&format!("Polling the BMC task failed: {}", e));
+                    continue;
+                }
+            };
+            // A task monitor answers 202 until the task finishes.
+            if status == 202 {
+                continue;
+            }
+            let task: Value = serde_json::from_str(&body).unwrap_or(Value::Null);
+            if task.get("TaskState").is_none() {
+                break;
+            }
+            match redfish_task_outcome(&task) {
+                TaskOutcome::Running => continue,
+                TaskOutcome::Completed => break,
+                TaskOutcome::Failed(reason) => {
+                    return Err(format!("The BMC task {} failed: {}", task_url, reason))
+                }
+            }
+        }
+        Ok(format!(
+            "Redfish {} {} finished its task in {:.0} s",
+            operation.method,
+            operation.path,
+            started.elapsed().as_secs_f64()
+        ))
+    }
+
+    /// Has a BMC power cycle the machine it manages, with the credentials in
+    /// `ONEFORALL_BMC_USERNAME` and `ONEFORALL_BMC_PASSWORD` unless it is the local one.
+    ///
+    /// # Arguments
+    /// * `target` - The BMC, reached over Redfish, IPMI over LAN or the local IPMI device.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the BMC accepted the power cycle.
+    pub fn power_cycle_bmc(&self, target: &BmcTarget) -> Result<(), String> {
+        let base_url = match target {
+            BmcTarget::Redfish(url) => url,
+            BmcTarget::Lan(host) => return self.ipmitool_lan(host, &["chassis", "power", "cycle"]),
+            BmcTarget::Local => {
+                self.logger
+                    .log_warn("Power cycling the machine with ipmitool");
+                return HardwareErrorAdapter::new(self.logger.clone())
+                    .ipmitool(&["chassis", "power", "cycle"])
+                    .map(|_| ());
+            }
+        };
+        let (agent, authorization, base_url) = Self::bmc_client(base_url)?;
+        let systems_url = format!("{}/redfish/v1/Systems", base_url);
+        let (_, _, body) = Self::send(&agent, &authorization, "GET", &systems_url, None)?;
+        let systems: Value = serde_json::from_str(&body)
+            .map_err(|e| format!("Invalid response from {}: {}", systems_url, e))?;
+        let system = systems["Members"][0]["@odata.id"]
+            .as_str()
+            .ok_or_else(|| format!("The BMC lists no system at {}", systems_url))?;
+        self.logger.log_warn(&format!(
+            "Power cycling {} through the BMC at {}",
+            system, base_url
+        ));
+        Self::send(
+            &agent,
+            &authorization,
+            "POST",
+            &format!("{}{}/Actions/ComputerSystem.Reset", base_url, system),
+            Some(r#"{"ResetType": "PowerCycle"}"#),
+        )
+        .map(|_| ())
+    }
+
+    /// Runs `ipmitool` against a remote BMC with IPMI over LAN, handing it the
+    /// password through the environment rather than the command line.
+    fn ipmitool_lan(&self, host: &str, args: &[&str]) -> Result<(), String> {
+        let username = required_env(BMC_USERNAME_ENV)?;
+        let password = required_env(BMC_PASSWORD_ENV)?;
+        self.logger.log_warn(&format!(
+            "Running ipmitool {} against the BMC at {}",
+            args.join(" "),
+            host
+        ));
+        let output = Command::new("ipmitool")
+            .args(["-I", "lanplus", "-H", host, "-U", username.as_str(), "-E"])
+            .args(args)
+            .env("IPMI_PASSWORD", password)
+            .output()
+            .map_err(|e| format!("Failed to run ipmitool: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "ipmitool {} against {} failed: {}",
+                args.join(" "),
+                host,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+}
+
+// Implement the `HookPort` trait for `HookAdapter`.
+impl HookPort for HookAdapter {
+    fn run_hook(
+        &self,
+        action: &HookAction,
+        timeout: Option<Duration>,
+        cancel: &CancellationToken,
+    ) -> Result<String, String> {
+        match action {
+            HookAction::Command(command) => {
+                self.logger
+                    .log_info(&format!("Running the hook {}", action));
+                let result = self.workloads.run_workload(command, timeout, cancel)?;
+                Ok(format!(
+                    "{} finished in {:.0} s",
+                    action,
+                    result.wall_time.as_secs_f64()
+                ))
+            }
+            HookAction::Redfish(operation) => self.run_redfish(operation, timeout, cancel),
+        }
+    }
+
+    fn reboot(&self) -> Result<(), String> {
+        let (program, args): (&str, &[&str]) = if cfg!(target_os = "macos") {
+            ("shutdown", &["-r", "now"])
+        } else {
+            ("systemctl", &["reboot"])
+        };
+        self.logger
+            .log_warn("Rebooting the machine for a profile step");
+        let status = Command::new(program)
+            .args(args)
+            .status()
+            .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+        if !status.success() {
+            return Err(format!(
+                "{} {} failed with {}",
+                program,
+                args.join(" "),
+                status
+            ));
+        }
+        Ok(())
+    }
+
+    fn power_cycle(&self) -> Result<(), String> {
+        let target = match std::env::var(BMC_URL_ENV) {
+            Ok(url) if !url.is_empty() => BmcTarget::Redfish(url),
+            _ => BmcTarget::Local,
+        };
+        self.power_cycle_bmc(&target)
+    }
+
+    fn boot_id(&self) -> Result<String, String> {
+        Some(read_boot_id())
+            .filter(|id| !id.is_empty())
+            .ok_or_else(|| "Failed to read the ID of the running boot".to_string())
+    }
+}
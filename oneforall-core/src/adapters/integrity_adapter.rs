@@ -0,0 +1,307 @@
+//! Integrity Adapter
+//!
+//! This module provides an adapter that verifies data integrity in-process.
+//! Patterns are checksummed in chunks when written, so each pass only has to
+//! recompute the checksums; a chunk whose checksum differs is then compared
+//! word by word to find the exact address or offset that changed.
+
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::hint::black_box;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use common::ports::log_port::LoggerPort;
+use tokio_util::sync::CancellationToken;
+
+use crate::adapters::storage_endurance_adapter::drop_cached_pages;
+use crate::domain::endurance::format_volume;
+use crate::domain::integrity::{checksum, describe_mismatch, pattern_word, IntegrityResult};
+use crate::ports::integrity_port::IntegrityPort;
+
+/// The number of words covered by one checksum: 32 KiB of memory or disk.
+const CHUNK_WORDS: usize = 4096;
+
+/// The number of chunks written or read at once on disk: 1 MiB per I/O.
+const DISK_CHUNKS_PER_IO: usize = 32;
+
+/// The prefix of the verification file name.
+const TEST_FILE_PREFIX: &str = "oneforall-integrity";
+
+/// Returns a seed that differs on every run, so a stale pattern left in memory
+/// or on disk from an earlier run can never pass for the current one.
+pub(crate) fn run_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+        ^ u64::from(std::process::id())
+}
+
+/// Translates a virtual address of this process into a physical address with
+/// `/proc/self/pagemap`. The kernel only reveals frame numbers to privileged
+/// processes, so this returns `None` when not run as root.
+#[cfg(target_os = "linux")]
+pub(crate) fn physical_address(virtual_address: usize) -> Option<u64> {
+    use std::io::{Seek, SeekFrom};
+
+    // SAFETY: sysconf has no preconditions.
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as u64;
+    let page = virtual_address as u64 / page_size;
+    let mut pagemap = File::open("/proc/self/pagemap").ok()?;
+    pagemap.seek(SeekFrom::Start(page * 8)).ok()?;
+    let mut entry = [0u8; 8];
+    pagemap.read_exact(&mut entry).ok()?;
+    let entry = u64::from_le_bytes(entry);
+
+    // Bit 63 marks a present page; bits 0-54 hold its frame number.
+    let frame = entry & ((1 << 55) - 1);
+    (entry & (1 << 63) != 0 && frame != 0)
+        .then(|| frame * page_size + virtual_address as u64 % page_size)
+}
+
+/// Physical addresses are only available on Linux.
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn physical_address(_virtual_address: usize) -> Option<u64> {
+    None
+}
+
+/// Represents the in-process data integrity adapter.
+pub struct IntegrityAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl IntegrityAdapter {
+    /// Creates a new instance of `IntegrityAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `IntegrityAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        IntegrityAdapter { logger }
+    }
+
+    /// Compares one chunk word by word and records every word that changed.
+    ///
+    /// `describe` turns the index of a corrupt word into its location.
+    fn find_corrupt_words(
+        &self,
+        words: &[u64],
+        first_index: usize,
+        seed: u64,
+        seen: &mut HashSet<usize>,
+        result: &mut IntegrityResult,
+        describe: impl Fn(usize, &u64) -> String,
+    ) {
+        for (offset, word) in words.iter().enumerate() {
+            let index = first_index + offset;
+            let expected = pattern_word(seed, index as u64);
+            // A word that stays corrupt is reported once, on the pass it was found.
+            if *word != expected && seen.insert(index) {
+                let location = describe(index, word);
+                let detail = describe_mismatch(expected, *word);
+                self.logger
+                    .log_error(&format!("Data corruption at {}: {}", location, detail));
+                result.record(location, detail);
+            }
+        }
+    }
+
+    /// Writes the pattern to a file a chunk at a time and syncs it to the drive.
+    ///
+    /// # Returns
+    /// A `Result` containing the checksum of every chunk, or an error message.
+    fn write_pattern_file(path: &Path, seed: u64, total_words: usize) -> Result<Vec<u64>, String> {
+        let mut file = File::create(path)
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        let mut checksums = Vec::with_capacity(total_words.div_ceil(CHUNK_WORDS));
+        let mut words = Vec::with_capacity(CHUNK_WORDS);
+        let mut bytes = Vec::with_capacity(CHUNK_WORDS * 8);
+        for first_index in (0..total_words).step_by(CHUNK_WORDS) {
+            let last_index = (first_index + CHUNK_WORDS).min(total_words);
+            words.clear();
+            words.extend((first_index..last_index).map(|i| pattern_word(seed, i as u64)));
+            checksums.push(checksum(&words));
+            bytes.clear();
+            bytes.extend(words.iter().flat_map(|word| word.to_le_bytes()));
+            file.write_all(&bytes)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        }
+        file.sync_all()
+            .map_err(|e| format!("Failed to sync {}: {}", path.display(), e))?;
+        Ok(checksums)
+    }
+
+    /// Makes one verification pass over the file.
+    fn verify_file_pass(
+        &self,
+        path: &Path,
+        seed: u64,
+        checksums: &[u64],
+        seen: &mut HashSet<usize>,
+        result: &mut IntegrityResult,
+    ) -> Result<(), String> {
+        let mut file =
+            File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        if let Err(e) = drop_cached_pages(&file) {
+            self.logger
+                .log_warn(&format!("{}; verification may read cached data", e));
+        }
+
+        let mut bytes = vec![0u8; CHUNK_WORDS * 8 * DISK_CHUNKS_PER_IO];
+        let total_words = result.bytes as usize / 8;
+        let mut first_index = 0;
+        while first_index < total_words {
+            let length = (total_words - first_index).min(CHUNK_WORDS * DISK_CHUNKS_PER_IO);
+            let offset = first_index as u64 * 8;
+            if let Err(e) = file.read_exact(&mut bytes[..length * 8]) {
+                // A failed read is as bad as corrupt data, and the rest of the file cannot be
+                // read in order, so it ends the pass.
+                if seen.insert(first_index) {
+                    self.logger.log_error(&format!(
+                        "Failed to read back {} at offset {}: {}",
+                        path.display(),
+                        offset,
+                        e
+                    ));
+                    result.record(format!("offset {}", offset), format!("read failed: {}", e));
+                }
+                return Ok(());
+            }
+            let words: Vec<u64> = bytes[..length * 8]
+                .chunks_exact(8)
+                .map(|b| u64::from_le_bytes(b.try_into().unwrap_or_default()))
+                .collect();
+            for (chunk, words) in words.chunks(CHUNK_WORDS).enumerate() {
+                let chunk_index = first_index / CHUNK_WORDS + chunk;
+                if checksum(words) != checksums[chunk_index] {
+                    self.find_corrupt_words(
+                        words,
+                        chunk_index * CHUNK_WORDS,
+                        seed,
+                        seen,
+                        result,
+                        |index, _| format!("offset {}", index * 8),
+                    );
+                }
+            }
+            first_index += length;
+        }
+        Ok(())
+    }
+}
+
+// Implement the `IntegrityPort` trait for `IntegrityAdapter`.
+impl IntegrityPort for IntegrityAdapter {
+    fn verify_memory(
+        &self,
+        bytes: usize,
+        stop: &CancellationToken,
+    ) -> Result<IntegrityResult, String> {
+        let total_words = bytes / 8;
+        if total_words == 0 {
+            return Err("the memory buffer is empty".to_string());
+        }
+        let seed = run_seed();
+        let mut buffer: Vec<u64> = Vec::new();
+        buffer
+            .try_reserve_exact(total_words)
+            .map_err(|e| format!("Failed to allocate {}: {}", format_volume(bytes as u64), e))?;
+        buffer.extend((0..total_words as u64).map(|index| pattern_word(seed, index)));
+        let checksums: Vec<u64> = buffer.chunks(CHUNK_WORDS).map(checksum).collect();
+        self.logger.log_info(&format!(
+            "Verifying {} of memory until the stress test ends",
+            format_volume(bytes as u64)
+        ));
+
+        let mut result = IntegrityResult {
+            bytes: total_words as u64 * 8,
+            ..IntegrityResult::default()
+        };
+        let mut seen = HashSet::new();
+        loop {
+            // The final pass runs after the stop request, once the load is gone.
+            let last_pass = stop.is_cancelled();
+            // Hide the buffer from the optimizer so every pass really reads memory.
+            let words = black_box(buffer.as_slice());
+            for (chunk_index, chunk) in words.chunks(CHUNK_WORDS).enumerate() {
+                if checksum(chunk) != checksums[chunk_index] {
+                    self.find_corrupt_words(
+                        chunk,
+                        chunk_index * CHUNK_WORDS,
+                        seed,
+                        &mut seen,
+                        &mut result,
+                        |_, word| {
+                            let address = word as *const u64 as usize;
+                            match physical_address(address) {
+                                Some(physical) => {
+                                    format!("address {:#x} (physical {:#x})", address, physical)
+                                }
+                                None => format!("address {:#x}", address),
+                            }
+                        },
+                    );
+                }
+            }
+            result.passes += 1;
+            if last_pass {
+                break;
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn verify_disk(
+        &self,
+        directory: &Path,
+        bytes: u64,
+        stop: &CancellationToken,
+    ) -> Result<IntegrityResult, String> {
+        let total_words = (bytes / 8) as usize;
+        if total_words == 0 {
+            return Err("the verification file is empty".to_string());
+        }
+        let path = directory.join(format!("{}-{}.bin", TEST_FILE_PREFIX, std::process::id()));
+        let seed = run_seed();
+
+        let checksums = match Self::write_pattern_file(&path, seed, total_words) {
+            Ok(checksums) => checksums,
+            Err(e) => {
+                let _ = fs::remove_file(&path);
+                return Err(e);
+            }
+        };
+        self.logger.log_info(&format!(
+            "Verifying {} in {} until the stress test ends",
+            format_volume(bytes),
+            path.display()
+        ));
+
+        let mut result = IntegrityResult {
+            bytes: total_words as u64 * 8,
+            ..IntegrityResult::default()
+        };
+        let mut seen = HashSet::new();
+        let outcome = loop {
+            let last_pass = stop.is_cancelled();
+            if let Err(e) = self.verify_file_pass(&path, seed, &checksums, &mut seen, &mut result) {
+                break Err(e);
+            }
+            result.passes += 1;
+            if last_pass {
+                break Ok(result);
+            }
+        };
+
+        if let Err(e) = fs::remove_file(&path) {
+            self.logger
+                .log_warn(&format!("Failed to remove {}: {}", path.display(), e));
+        }
+        outcome
+    }
+}
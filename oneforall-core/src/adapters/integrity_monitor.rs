@@ -0,0 +1,65 @@
+//! Integrity Monitor
+//!
+//! This module runs the data-integrity verification of `oneforall stress --verify`
+//! in the background while a stress test loads the machine, in the same way the
+//! run monitor collects telemetry, and builds the integrity report once the
+//! stress test has finished.
+
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use common::ports::log_port::LoggerPort;
+use tokio_util::sync::CancellationToken;
+
+use crate::adapters::integrity_adapter::IntegrityAdapter;
+use crate::domain::integrity::{IntegrityPlan, IntegrityReport, IntegrityResult};
+use crate::ports::integrity_port::IntegrityPort;
+
+/// Verifies memory and disk integrity in the background for the duration of a run.
+pub struct IntegrityMonitor {
+    stop: CancellationToken,
+    memory: JoinHandle<Result<IntegrityResult, String>>,
+    disk: JoinHandle<Result<IntegrityResult, String>>,
+}
+
+impl IntegrityMonitor {
+    /// Writes the memory and disk patterns and starts re-reading them.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger` - Logger implementation for logging messages.
+    /// * `plan` - How much memory and disk to verify, and where.
+    ///
+    /// # Returns
+    ///
+    /// * `IntegrityMonitor` - A handle used to stop the verification and build the report.
+    pub fn start(logger: Arc<dyn LoggerPort>, plan: IntegrityPlan) -> Self {
+        let stop = CancellationToken::new();
+        let integrity: Arc<dyn IntegrityPort> = Arc::new(IntegrityAdapter::new(logger));
+
+        let (memory_integrity, memory_stop) = (integrity.clone(), stop.clone());
+        let memory =
+            thread::spawn(move || memory_integrity.verify_memory(plan.memory_bytes, &memory_stop));
+
+        let disk_stop = stop.clone();
+        let disk = thread::spawn(move || {
+            integrity.verify_disk(&plan.directory, plan.disk_bytes, &disk_stop)
+        });
+
+        IntegrityMonitor { stop, memory, disk }
+    }
+
+    /// Stops the verification after a final pass over each pattern and builds the report.
+    pub fn finish(self) -> IntegrityReport {
+        self.stop.cancel();
+        let join = |handle: JoinHandle<Result<IntegrityResult, String>>| {
+            handle
+                .join()
+                .unwrap_or_else(|_| Err("the verification thread panicked".to_string()))
+        };
+        IntegrityReport {
+            memory: join(self.memory),
+            disk: join(self.disk),
+        }
+    }
+}
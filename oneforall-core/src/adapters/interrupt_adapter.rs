@@ -0,0 +1,177 @@
+//! Interrupt Adapter
+//!
+//! This module provides an adapter that reads per-CPU interrupt counters from
+//! `/proc`: network device interrupts from `/proc/interrupts`, `NET_RX` and
+//! `NET_TX` softirqs from `/proc/softirqs`, and the time spent servicing hard
+//! and soft interrupts from `/proc/stat`. An interrupt belongs to a network
+//! device if its number is one of the MSI vectors of a device under
+//! `/sys/class/net`, or if its name mentions an interface, as many drivers
+//! name their queue interrupts, e.g. `eth0-TxRx-3`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::sync::Arc;
+
+use common::ports::log_port::LoggerPort;
+
+use crate::domain::interrupts::CpuInterrupts;
+use crate::ports::interrupt_port::InterruptPort;
+
+/// Represents the `/proc` interrupt adapter.
+pub struct InterruptAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl InterruptAdapter {
+    /// Creates a new instance of `InterruptAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `InterruptAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        InterruptAdapter { logger }
+    }
+
+    /// Lists the network interfaces other than loopback, and the MSI vectors of their devices.
+    ///
+    /// Virtual NICs such as virtio hang below the PCI device that owns the
+    /// vectors, so the parent of each device is searched as well.
+    fn network_devices() -> (Vec<String>, BTreeSet<String>) {
+        let mut interfaces = Vec::new();
+        let mut vectors = BTreeSet::new();
+        let Ok(entries) = fs::read_dir("/sys/class/net") else {
+            return (interfaces, vectors);
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name == "lo" {
+                continue;
+            }
+            let device = entry.path().join("device");
+            for msi_irqs in [device.join("msi_irqs"), device.join("../msi_irqs")] {
+                if let Ok(irqs) = fs::read_dir(&msi_irqs) {
+                    vectors.extend(
+                        irqs.flatten()
+                            .map(|irq| irq.file_name().to_string_lossy().into_owned()),
+                    );
+                }
+            }
+            interfaces.push(name);
+        }
+        (interfaces, vectors)
+    }
+
+    /// Reads the header of a per-CPU table, e.g. `CPU0 CPU1 CPU3`, into CPU IDs.
+    ///
+    /// Offline CPUs are left out of the columns, so the header is the only way
+    /// to tell which CPU a column belongs to.
+    fn parse_cpu_header(header: &str) -> Vec<u32> {
+        header
+            .split_whitespace()
+            .filter_map(|column| column.strip_prefix("CPU")?.parse().ok())
+            .collect()
+    }
+
+    /// Sums the network device interrupts of `/proc/interrupts` per CPU.
+    ///
+    /// Each line holds the interrupt number, one count per CPU, then the chip,
+    /// trigger and action names, e.g. ` 45:  120  9012  IR-PCI-MSIX  eth0-TxRx-0`.
+    fn parse_interrupts(
+        interrupts: &str,
+        interfaces: &[String],
+        vectors: &BTreeSet<String>,
+    ) -> BTreeMap<u32, u64> {
+        let mut lines = interrupts.lines();
+        let cpus = Self::parse_cpu_header(lines.next().unwrap_or_default());
+        let mut counts = BTreeMap::new();
+        for line in lines {
+            let Some((irq, rest)) = line.split_once(':') else {
+                continue;
+            };
+            let irq = irq.trim();
+            let fields: Vec<&str> = rest.split_whitespace().collect();
+            let names = fields.get(cpus.len()..).unwrap_or_default();
+            let is_network = vectors.contains(irq)
+                || names.iter().any(|name| {
+                    interfaces
+                        .iter()
+                        .any(|interface| name.contains(interface.as_str()))
+                });
+            if !is_network {
+                continue;
+            }
+            for (cpu, count) in cpus.iter().zip(&fields) {
+                *counts.entry(*cpu).or_insert(0) += count.parse::<u64>().unwrap_or(0);
+            }
+        }
+        counts
+    }
+
+    /// Reads the `NET_RX` and `NET_TX` rows of `/proc/softirqs` per CPU.
+    fn parse_softirqs(softirqs: &str) -> (BTreeMap<u32, u64>, BTreeMap<u32, u64>) {
+        let mut lines = softirqs.lines();
+        let cpus = Self::parse_cpu_header(lines.next().unwrap_or_default());
+        let mut net_rx = BTreeMap::new();
+        let mut net_tx = BTreeMap::new();
+        for line in lines {
+            let mut fields = line.split_whitespace();
+            let target = match fields.next() {
+                Some("NET_RX:") => &mut net_rx,
+                Some("NET_TX:") => &mut net_tx,
+                _ => continue,
+            };
+            for (cpu, count) in cpus.iter().zip(fields) {
+                target.insert(*cpu, count.parse().unwrap_or(0));
+            }
+        }
+        (net_rx, net_tx)
+    }
+
+    /// Parses the per-CPU lines of `/proc/stat` into interrupt, softirq and total ticks.
+    fn parse_proc_stat(stat: &str) -> Vec<CpuInterrupts> {
+        stat.lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let cpu = fields.next()?.strip_prefix("cpu")?.parse().ok()?;
+                let ticks: Vec<u64> = fields.filter_map(|f| f.parse().ok()).collect();
+                // user nice system idle iowait irq softirq steal; guest time is
+                // already included in user and nice.
+                Some(CpuInterrupts {
+                    cpu,
+                    irq_ticks: ticks.get(5).copied().unwrap_or(0),
+                    softirq_ticks: ticks.get(6).copied().unwrap_or(0),
+                    total_ticks: ticks.iter().take(8).sum(),
+                    ..CpuInterrupts::default()
+                })
+            })
+            .collect()
+    }
+
+    /// Reads a file under `/proc`, logging failures at trace level.
+    fn read(&self, path: &str) -> Result<String, String> {
+        fs::read_to_string(path).map_err(|e| {
+            let message = format!("Failed to read {}: {}", path, e);
+            self.logger.log_trace(&message);
+            message
+        })
+    }
+}
+
+// Implement the `InterruptPort` trait for `InterruptAdapter`.
+impl InterruptPort for InterruptAdapter {
+    fn sample_interrupts(&self) -> Result<Vec<CpuInterrupts>, String> {
+        let mut cpus = Self::parse_proc_stat(&self.read("/proc/stat")?);
+        let (interfaces, vectors) = Self::network_devices();
+        let network_irqs =
+            Self::parse_interrupts(&self.read("/proc/interrupts")?, &interfaces, &vectors);
+        let (net_rx, net_tx) = Self::parse_softirqs(&self.read("/proc/softirqs")?);
+        for cpu in &mut cpus {
+            cpu.network_irqs = network_irqs.get(&cpu.cpu).copied().unwrap_or(0);
+            cpu.net_rx_softirqs = net_rx.get(&cpu.cpu).copied().unwrap_or(0);
+            cpu.net_tx_softirqs = net_tx.get(&cpu.cpu).copied().unwrap_or(0);
+        }
+        Ok(cpus)
+    }
+}
@@ -0,0 +1,349 @@
+//! Jobs Client Adapter
+//!
+//! This module provides a small HTTP client for the job endpoints of the REST
+//! API, used by the `jobs` command to inspect and cancel the jobs of an
+//! instance that is already running on this machine, by `logs` to follow its
+//! log messages, by `fleet --matrix` to
+//! have fleet agents measure the network paths between them, by `compare
+//! --fleet` to read their benchmark scores, and by `power-cycle` to follow the
+//! readiness of the agent on a machine it power cycles. It reads the same `[api]`
+//! configuration as the server, so it presents the API token and, when the API
+//! is served over HTTPS, trusts the configured CA and presents the client certificate.
+
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::domain::api_config::ApiConfig;
+use common::domain::health::HealthResponse;
+use common::domain::job::Job;
+use common::domain::log_record::LogRecord;
+use common::domain::network_probe::{LinkMeasurement, ProbeRequest};
+use common::ports::log_port::LoggerPort;
+use ureq::tls::{parse_pem, ClientCert, PemItem, PrivateKey, RootCerts, TlsConfig};
+
+use crate::domain::score::MachineScore;
+
+/// Time allowed for the running instance to answer a request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Time allowed for an agent to measure the path to another agent.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Represents a client of the job endpoints of a running instance.
+pub struct JobsClientAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    agent: ureq::Agent,
+    base_url: String,
+    probe_url: String,
+    score_url: String,
+    logs_url: String,
+    ready_url: String,
+    token: Option<String>,
+}
+
+/// Reads every certificate from a PEM file.
+pub(crate) fn read_certificates(
+    path: &Path,
+) -> Result<Vec<ureq::tls::Certificate<'static>>, String> {
+    let pem = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let certificates: Vec<_> = parse_pem(&pem)
+        .filter_map(|item| match item {
+            Ok(PemItem::Certificate(certificate)) => Some(certificate),
+            _ => None,
+        })
+        .collect();
+    if certificates.is_empty() {
+        return Err(format!("No certificates found in {}", path.display()));
+    }
+    Ok(certificates)
+}
+
+/// Builds the TLS settings of the client from the `[api.client]` configuration.
+pub(crate) fn tls_client_config(config: &ApiConfig) -> Result<TlsConfig, String> {
+    let mut builder = TlsConfig::builder();
+    if let Some(ca_certificate) = &config.client.ca_certificate {
+        let roots = read_certificates(ca_certificate)?;
+        builder = builder.root_certs(RootCerts::new_with_certs(&roots));
+    }
+    if let (Some(certificate), Some(private_key)) =
+        (&config.client.certificate, &config.client.private_key)
+    {
+        let chain = read_certificates(certificate)?;
+        let pem = fs::read(private_key)
+            .map_err(|e| format!("Failed to read {}: {}", private_key.display(), e))?;
+        let key = PrivateKey::from_pem(&pem)
+            .map_err(|e| format!("Failed to read {}: {}", private_key.display(), e))?;
+        builder = builder.client_cert(Some(ClientCert::new_with_certs(&chain, key)));
+    }
+    Ok(builder.build())
+}
+
+impl JobsClientAdapter {
+    /// Creates a new instance of `JobsClientAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    /// * `config` - The REST API settings of the running instance.
+    ///
+    /// # Returns
+    /// A `Result` containing an instance of `JobsClientAdapter`, or an error if the
+    /// configured certificates cannot be read.
+    pub fn new(logger: Arc<dyn LoggerPort>, config: &ApiConfig) -> Result<Self, String> {
+        Self::for_agent(logger, config, &config.base_url())
+    }
+
+    /// Creates a client of another instance, such as a fleet agent, which is
+    /// expected to accept the same token and certificates as this one.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    /// * `config` - The REST API settings holding the token and certificates.
+    /// * `base_url` - The base URL of the instance, e.g. "https://rack12-node3:8000".
+    ///
+    /// # Returns
+    /// A `Result` containing an instance of `JobsClientAdapter`, or an error if the
+    /// configured certificates cannot be read.
+    pub fn for_agent(
+        logger: Arc<dyn LoggerPort>,
+        config: &ApiConfig,
+        base_url: &str,
+    ) -> Result<Self, String> {
+        // Error statuses are turned into messages below, with the body the server sent.
+        let agent = ureq::Agent::config_builder()
+            .http_status_as_error(false)
+            .timeout_global(Some(REQUEST_TIMEOUT))
+            .tls_config(tls_client_config(config)?)
+            .build()
+            .into();
+        Ok(JobsClientAdapter {
+            logger,
+            agent,
+            base_url: format!("{}/api/v1/jobs", base_url),
+            probe_url: format!("{}/api/v1/netprobe", base_url),
+            score_url: format!("{}/api/v1/score", base_url),
+            logs_url: format!("{}/api/v1/logs", base_url),
+            ready_url: format!("{}/readyz", base_url),
+            token: config.client_token().map(str::to_string),
+        })
+    }
+
+    /// Sends a request and returns the response body, or the reason it failed.
+    fn send(&self, method: &str, url: &str) -> Result<String, String> {
+        self.logger.log_debug(&format!("{} {}", method, url));
+        let mut request = match method {
+            "DELETE" => self.agent.delete(url),
+            _ => self.agent.get(url),
+        };
+        if let Some(token) = &self.token {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+        let response = request.call();
+        self.read_response(response)
+    }
+
+    /// Returns the body of a response, or the reason the request failed.
+    fn read_response(
+        &self,
+        response: Result<ureq::http::Response<ureq::Body>, ureq::Error>,
+    ) -> Result<String, String> {
+        let mut response = response.map_err(|e| {
+            format!(
+                "Unable to reach OneForAll at {} ({}). Is `oneforall stress` or \
+                 `oneforall overwatch` running?",
+                self.base_url, e
+            )
+        })?;
+
+        let status = response.status();
+        let body = response
+            .body_mut()
+            .read_to_string()
+            .map_err(|e| format!("Failed to read the response: {}", e))?;
+        if status.is_success() {
+            Ok(body)
+        } else {
+            Err(format!("{} ({})", body.trim(), status))
+        }
+    }
+
+    /// Lists the jobs of the running instance, oldest first.
+    ///
+    /// # Returns
+    /// A `Result` containing the jobs, or an error message.
+    pub fn list_jobs(&self) -> Result<Vec<Job>, String> {
+        let body = self.send("GET", &self.base_url)?;
+        serde_json::from_str(&body).map_err(|e| format!("Unexpected job list: {}", e))
+    }
+
+    /// Requests cancellation of a queued or running job.
+    ///
+    /// # Arguments
+    /// * `id` - The job ID.
+    ///
+    /// # Returns
+    /// A `Result` containing the job record at the time of the request, or an error message.
+    pub fn cancel_job(&self, id: u64) -> Result<Job, String> {
+        let body = self.send("DELETE", &format!("{}/{}", self.base_url, id))?;
+        serde_json::from_str(&body).map_err(|e| format!("Unexpected job record: {}", e))
+    }
+
+    /// Asks the instance whether it is ready to take requests and jobs, through its
+    /// readiness probe.
+    ///
+    /// # Returns
+    /// A `Result` that is `Ok` if the instance is ready, or an error naming the checks
+    /// that failed or why the instance could not be reached.
+    pub fn readiness(&self) -> Result<(), String> {
+        self.logger.log_debug(&format!("GET {}", self.ready_url));
+        let mut request = self.agent.get(self.ready_url.as_str());
+        if let Some(token) = &self.token {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+        let mut response = request
+            .call()
+            .map_err(|e| format!("Unable to reach {}: {}", self.ready_url, e))?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let body = response.body_mut().read_to_string().unwrap_or_default();
+        let failures = match serde_json::from_str::<HealthResponse>(&body) {
+            Ok(health) => health
+                .checks
+                .iter()
+                .filter(|check| !check.healthy)
+                .map(|check| format!("{}: {}", check.name, check.detail))
+                .collect::<Vec<_>>()
+                .join("; "),
+            Err(_) => body.trim().to_string(),
+        };
+        Err(format!("not ready ({}): {}", status, failures))
+    }
+
+    /// Asks the instance to measure the network path to another agent.
+    ///
+    /// # Arguments
+    /// * `target` - The base URL of the REST API of the agent to measure to.
+    ///
+    /// # Returns
+    /// A `Result` containing the latency and bandwidth measured, or an error message.
+    pub fn probe_link(&self, target: &str) -> Result<LinkMeasurement, String> {
+        self.logger
+            .log_debug(&format!("POST {} ({})", self.probe_url, target));
+        let mut request = self
+            .agent
+            .post(&self.probe_url)
+            .config()
+            .timeout_global(Some(PROBE_TIMEOUT))
+            .build();
+        if let Some(token) = &self.token {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+        let json = serde_json::to_string(&ProbeRequest {
+            target: target.to_string(),
+        })
+        .map_err(|e| format!("Failed to encode the probe request: {}", e))?;
+        let response = request
+            .header("Content-Type", "application/json")
+            .send(json.as_str());
+        let body = self.read_response(response)?;
+        serde_json::from_str(&body).map_err(|e| format!("Unexpected measurement: {}", e))
+    }
+
+    /// Reads the benchmark scores of the instance.
+    ///
+    /// # Returns
+    /// A `Result` containing the scores, or an error message.
+    pub fn machine_score(&self) -> Result<MachineScore, String> {
+        let body = self.send("GET", &self.score_url)?;
+        serde_json::from_str(&body).map_err(|e| format!("Unexpected scores: {}", e))
+    }
+
+    /// Reads the most recent log messages of the instance.
+    ///
+    /// # Arguments
+    /// * `level` - The least severe level to read, e.g. "info".
+    /// * `lines` - The number of messages to read, at most.
+    ///
+    /// # Returns
+    /// A `Result` containing the messages, oldest first, or an error message.
+    pub fn recent_logs(&self, level: &str, lines: usize) -> Result<Vec<LogRecord>, String> {
+        let url = format!("{}?level={}&lines={}", self.logs_url, level, lines);
+        let body = self.send("GET", &url)?;
+        serde_json::from_str(&body).map_err(|e| format!("Unexpected log messages: {}", e))
+    }
+
+    /// Follows the log messages of the instance as they are logged, starting
+    /// with the most recent ones, until the instance stops or the stream fails.
+    ///
+    /// # Arguments
+    /// * `level` - The least severe level to follow, e.g. "info".
+    /// * `lines` - The number of recent messages to start with.
+    /// * `on_record` - Called with every message.
+    ///
+    /// # Returns
+    /// A `Result` that is `Ok` once the instance closed the stream, or an error message.
+    pub fn follow_logs(
+        &self,
+        level: &str,
+        lines: usize,
+        mut on_record: impl FnMut(&LogRecord),
+    ) -> Result<(), String> {
+        let url = format!("{}/stream?level={}&lines={}", self.logs_url, level, lines);
+        self.logger.log_debug(&format!("GET {}", url));
+        // The stream stays open for as long as the reader follows it.
+        let mut request = self
+            .agent
+            .get(&url)
+            .config()
+            .timeout_global(None)
+            .build()
+            .header("Accept", "text/event-stream");
+        if let Some(token) = &self.token {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+        let response = request.call().map_err(|e| {
+            format!(
+                "Unable to reach OneForAll at {} ({}). Is `oneforall stress` or \
+                 `oneforall overwatch` running?",
+                self.base_url, e
+            )
+        })?;
+        if !response.status().is_success() {
+            return self.read_response(Ok(response)).map(|_| ());
+        }
+
+        // Each event is a block of `field: value` lines ended by a blank line;
+        // lines starting with a colon are comments keeping the connection open.
+        let reader = BufReader::new(response.into_body().into_reader());
+        let (mut event, mut data) = (String::new(), String::new());
+        for line in reader.lines() {
+            let line = line.map_err(|e| format!("The log stream broke off: {}", e))?;
+            if let Some(value) = line.strip_prefix("event:") {
+                event = value.trim().to_string();
+            } else if let Some(value) = line.strip_prefix("data:") {
+                data.push_str(value.trim_start());
+            } else if line.is_empty() && !data.is_empty() {
+                if event == "skipped" {
+                    self.logger.log_warn(&format!(
+                        "Skipped {} log messages the stream could not keep up with",
+                        data
+                    ));
+                } else {
+                    match serde_json::from_str::<LogRecord>(&data) {
+                        Ok(record) => on_record(&record),
+                        Err(e) => self
+                            .logger
+                            .log_debug(&format!("Unexpected log message: {}", e)),
+                    }
+                }
+                event.clear();
+                data.clear();
+            }
+        }
+        Ok(())
+    }
+}
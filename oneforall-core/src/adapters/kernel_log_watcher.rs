@@ -0,0 +1,154 @@
+//! Kernel Log Watcher
+//!
+//! This module tails the kernel log while a test runs, through the kernel
+//! journal when journald is running and `dmesg --follow-new` otherwise, and
+//! collects every machine check, I/O error, OOM kill and thermal event it sees.
+
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use common::ports::log_port::LoggerPort;
+
+use crate::domain::kernel_log::{classify_kernel_line, KernelEvent, KernelLogFindings};
+
+/// The socket journald listens on; its presence means the kernel journal is kept.
+const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// Collects kernel log events in the background for the duration of a run.
+pub struct KernelLogWatcher {
+    child: Option<Child>,
+    reader: Option<JoinHandle<KernelLogFindings>>,
+    unavailable: Option<String>,
+}
+
+impl KernelLogWatcher {
+    /// Starts following the kernel log from now on; earlier messages are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger` - Logger implementation for logging messages.
+    ///
+    /// # Returns
+    ///
+    /// * `KernelLogWatcher` - A handle used to stop the watcher and collect its findings.
+    pub fn start(logger: Arc<dyn LoggerPort>) -> Self {
+        let (source, program, args): (&str, &str, &[&str]) = if Path::new(JOURNALD_SOCKET).exists()
+        {
+            (
+                "journald",
+                "journalctl",
+                &[
+                    "--dmesg",
+                    "--follow",
+                    "--lines=0",
+                    "--output=cat",
+                    "--no-pager",
+                ],
+            )
+        } else {
+            ("dmesg", "dmesg", &["--follow-new", "--notime"])
+        };
+
+        let mut child = match Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) => {
+                logger.log_debug(&format!("Not watching the kernel log: {}", e));
+                return KernelLogWatcher {
+                    child: None,
+                    reader: None,
+                    unavailable: Some(format!("failed to run {}: {}", program, e)),
+                };
+            }
+        };
+        logger.log_debug(&format!("Watching the kernel log with {}", program));
+
+        let stdout = child.stdout.take();
+        let reader = thread::spawn(move || {
+            let mut findings = KernelLogFindings::watching(source);
+            let Some(stdout) = stdout else {
+                return findings;
+            };
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                let Some(kind) = classify_kernel_line(&line) else {
+                    continue;
+                };
+                let message = format!("Kernel log {}: {}", kind.label(), line.trim());
+                if kind.is_serious() {
+                    logger.log_error(&message);
+                } else {
+                    logger.log_warn(&message);
+                }
+                findings.record(KernelEvent {
+                    kind,
+                    message: line.trim().to_string(),
+                });
+            }
+            findings
+        });
+
+        KernelLogWatcher {
+            child: Some(child),
+            reader: Some(reader),
+            unavailable: None,
+        }
+    }
+
+    /// Stops following the kernel log and returns the events seen.
+    ///
+    /// If the log could not be read, e.g. because `dmesg` is restricted to root,
+    /// the findings say why instead.
+    pub fn finish(mut self) -> KernelLogFindings {
+        let unavailable = |reason: String| KernelLogFindings {
+            source: Err(reason),
+            event_count: 0,
+            serious_count: 0,
+            events: Vec::new(),
+        };
+        if let Some(reason) = self.unavailable.take() {
+            return unavailable(reason);
+        }
+        let (Some(mut child), Some(reader)) = (self.child.take(), self.reader.take()) else {
+            return unavailable("the watcher was not started".to_string());
+        };
+
+        // A follower that already exited could not read the log; one still running is stopped.
+        let exited = match child.try_wait() {
+            Ok(Some(status)) => !status.success(),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                false
+            }
+        };
+        let findings = reader
+            .join()
+            .unwrap_or_else(|_| unavailable("the watcher thread panicked".to_string()));
+        if exited {
+            let mut stderr = String::new();
+            if let Some(mut pipe) = child.stderr.take() {
+                let _ = pipe.read_to_string(&mut stderr);
+            }
+            return unavailable(format!("the kernel log is not readable: {}", stderr.trim()));
+        }
+        findings
+    }
+}
+
+impl Drop for KernelLogWatcher {
+    fn drop(&mut self) {
+        // Make sure an abandoned watcher does not leave its follower running.
+        if let Some(child) = self.child.as_mut() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! Kubernetes Adapter
+//!
+//! This module provides an adapter that labels and taints nodes through the
+//! Kubernetes API from inside a pod. It uses the in-cluster configuration
+//! every pod is given: the API server address from the environment, and the
+//! service account token and cluster CA mounted into the container. The
+//! service account needs `get` and `patch` on nodes.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::ports::log_port::LoggerPort;
+use serde_json::{json, Value};
+use ureq::tls::{RootCerts, TlsConfig};
+
+use crate::adapters::jobs_client_adapter::read_certificates;
+use crate::domain::kubernetes::NodeTaint;
+use crate::ports::cluster_port::ClusterPort;
+
+/// Where the service account token and cluster CA are mounted in every pod.
+const SERVICE_ACCOUNT_DIR: &str = "/var/run/secrets/kubernetes.io/serviceaccount";
+
+/// Time allowed for the API server to answer a request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Represents the in-cluster Kubernetes API adapter.
+pub struct KubernetesAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    agent: ureq::Agent,
+    api_url: String,
+}
+
+impl KubernetesAdapter {
+    /// Creates a new instance of `KubernetesAdapter` from the in-cluster configuration.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// A `Result` containing an instance of `KubernetesAdapter`, or an error if
+    /// the process does not run in a Kubernetes pod.
+    pub fn in_cluster(logger: Arc<dyn LoggerPort>) -> Result<Self, String> {
+        let host = std::env::var("KUBERNETES_SERVICE_HOST")
+            .map_err(|_| "KUBERNETES_SERVICE_HOST is not set; not running in a pod".to_string())?;
+        let port = std::env::var("KUBERNETES_SERVICE_PORT").unwrap_or_else(|_| "443".to_string());
+        // IPv6 addresses are bracketed in URLs.
+        let api_url = if host.contains(':') {
+            format!("https://[{}]:{}", host, port)
+        } else {
+            format!("https://{}:{}", host, port)
+        };
+
+        let roots = read_certificates(&Path::new(SERVICE_ACCOUNT_DIR).join("ca.crt"))?;
+        let agent: ureq::Agent = ureq::Agent::config_builder()
+            .http_status_as_error(false)
+            .timeout_global(Some(REQUEST_TIMEOUT))
+            .tls_config(
+                TlsConfig::builder()
+                    .root_certs(RootCerts::new_with_certs(&roots))
+                    .build(),
+            )
+            .build()
+            .into();
+        Ok(KubernetesAdapter {
+            logger,
+            agent,
+            api_url,
+        })
+    }
+
+    /// Reads the service account token.
+    ///
+    /// The kubelet rotates the token, so it is read again for every request.
+    fn token() -> Result<String, String> {
+        let path = Path::new(SERVICE_ACCOUNT_DIR).join("token");
+        fs::read_to_string(&path)
+            .map(|token| token.trim().to_string())
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+    }
+
+    /// Returns the URL of a node.
+    fn node_url(&self, node: &str) -> String {
+        format!("{}/api/v1/nodes/{}", self.api_url, node)
+    }
+
+    /// Reads the body of a response, turning an error status into an error message.
+    fn read_response(
+        url: &str,
+        response: Result<ureq::http::Response<ureq::Body>, ureq::Error>,
+    ) -> Result<Value, String> {
+        let mut response = response.map_err(|e| format!("Failed to reach {}: {}", url, e))?;
+        let status = response.status();
+        let body = response
+            .body_mut()
+            .read_to_string()
+            .map_err(|e| format!("Failed to read the response of {}: {}", url, e))?;
+        if !status.is_success() {
+            // The API server explains refusals, such as missing RBAC permissions, in `message`.
+            let message = serde_json::from_str::<Value>(&body)
+                .ok()
+                .and_then(|status| status["message"].as_str().map(str::to_string))
+                .unwrap_or(body);
+            return Err(format!("{} answered {}: {}", url, status, message.trim()));
+        }
+        serde_json::from_str(&body).map_err(|e| format!("Invalid response from {}: {}", url, e))
+    }
+
+    /// Reads a node.
+    fn get_node(&self, node: &str) -> Result<Value, String> {
+        let url = self.node_url(node);
+        let response = self
+            .agent
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", Self::token()?))
+            .call();
+        Self::read_response(&url, response)
+    }
+
+    /// Applies a JSON merge patch to a node.
+    fn patch_node(&self, node: &str, patch: &Value) -> Result<(), String> {
+        let url = self.node_url(node);
+        let response = self
+            .agent
+            .patch(&url)
+            .header("Authorization", format!("Bearer {}", Self::token()?))
+            .header("Content-Type", "application/merge-patch+json")
+            .send(patch.to_string().as_str());
+        Self::read_response(&url, response).map(|_| ())
+    }
+
+    /// Replaces the taints of a node with those kept by a filter, plus an added taint.
+    ///
+    /// A merge patch replaces the whole list, so the patch carries the
+    /// resource version read, and the API server refuses it if another client
+    /// changed the node in between.
+    fn update_taints(
+        &self,
+        node: &str,
+        keep: impl Fn(&Value) -> bool,
+        added: Option<Value>,
+    ) -> Result<bool, String> {
+        let current = self.get_node(node)?;
+        let taints: Vec<Value> = current["spec"]["taints"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        let mut updated: Vec<Value> = taints.iter().filter(|taint| keep(taint)).cloned().collect();
+        updated.extend(added);
+        if updated == taints {
+            return Ok(false);
+        }
+        self.patch_node(
+            node,
+            &json!({
+                "metadata": { "resourceVersion": current["metadata"]["resourceVersion"] },
+                "spec": { "taints": updated },
+            }),
+        )?;
+        Ok(true)
+    }
+}
+
+// Implement the `ClusterPort` trait for `KubernetesAdapter`.
+impl ClusterPort for KubernetesAdapter {
+    fn label_node(&self, node: &str, labels: &BTreeMap<String, String>) -> Result<(), String> {
+        self.patch_node(node, &json!({ "metadata": { "labels": labels } }))?;
+        self.logger.log_info(&format!(
+            "Labeled node {}: {}",
+            node,
+            labels
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+        Ok(())
+    }
+
+    fn taint_node(&self, node: &str, taint: &NodeTaint) -> Result<(), String> {
+        let added = json!({ "key": taint.key, "value": taint.value, "effect": taint.effect });
+        if self.update_taints(node, |t| t["key"] != taint.key.as_str(), Some(added))? {
+            self.logger.log_info(&format!(
+                "Tainted node {} with {}={}:{}",
+                node, taint.key, taint.value, taint.effect
+            ));
+        }
+        Ok(())
+    }
+
+    fn untaint_node(&self, node: &str, key: &str) -> Result<(), String> {
+        if self.update_taints(node, |t| t["key"] != key, None)? {
+            self.logger
+                .log_info(&format!("Removed taint {} from node {}", key, node));
+        }
+        Ok(())
+    }
+}
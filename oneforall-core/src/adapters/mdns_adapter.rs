@@ -0,0 +1,215 @@
+//! mDNS Adapter
+//!
+//! This module provides an adapter that announces fleet agents and finds them
+//! with multicast DNS service discovery (DNS-SD over mDNS), as the
+//! `_oneforall._tcp` service. Agents carry their version, URL scheme and, when
+//! configured, their URL in TXT records.
+
+use std::collections::HashMap;
+use std::fs;
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use common::ports::log_port::LoggerPort;
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+
+use crate::adapters::alert_dispatcher::hostname;
+use crate::domain::fleet::{
+    merge_agents, Agent, AgentAdvertisement, AgentSource, FleetConfig, AGENT_SERVICE_TYPE,
+};
+use crate::ports::agent_discovery_port::AgentDiscoveryPort;
+
+/// Time allowed for the goodbye announcement to go out when withdrawing.
+const WITHDRAW_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Reads the `[fleet]` section of the configuration file.
+///
+/// # Arguments
+///
+/// * `path` - The configuration file; a missing file means no fleet settings.
+///
+/// # Returns
+///
+/// * `Result<FleetConfig, String>` - The fleet settings, or why they could not be read.
+pub fn load_fleet_config(path: &Path) -> Result<FleetConfig, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(FleetConfig::default()),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    FleetConfig::from_toml(&text).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+}
+
+/// Lists the agents of the fleet: those announcing themselves over mDNS and
+/// those listed in the `[fleet]` section. If discovery fails, only the listed
+/// agents are returned.
+///
+/// # Arguments
+///
+/// * `logger` - Logger implementation for logging messages.
+/// * `config` - The fleet settings.
+///
+/// # Returns
+///
+/// * `Vec<Agent>` - The agents, without duplicates.
+pub fn discover_agents(logger: Arc<dyn LoggerPort>, config: &FleetConfig) -> Vec<Agent> {
+    let discovered = MdnsAdapter::new(logger.clone())
+        .and_then(|mdns| mdns.discover(config.discovery_timeout()))
+        .unwrap_or_else(|e| {
+            logger.log_warn(&format!("Only listing the static agents: {}", e));
+            Vec::new()
+        });
+    merge_agents(discovered, &config.agents)
+}
+
+/// Represents the mDNS agent discovery adapter.
+pub struct MdnsAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    daemon: ServiceDaemon,
+    registered: Mutex<Option<String>>, // the full name of the announced agent
+}
+
+/// Builds the base URL of an announced agent: the URL it announced, or one
+/// made from its first address, preferring IPv4, which more lab tooling handles.
+fn announced_url(info: &ServiceInfo) -> Option<String> {
+    if let Some(url) = info.get_property_val_str("url") {
+        return Some(url.to_string());
+    }
+    let scheme = info.get_property_val_str("scheme").unwrap_or("http");
+    let mut addresses: Vec<&IpAddr> = info.get_addresses().iter().collect();
+    addresses.sort_by_key(|address| (address.is_ipv6(), address.to_string()));
+    let host = match addresses.first()? {
+        IpAddr::V4(address) => address.to_string(),
+        IpAddr::V6(address) => format!("[{}]", address),
+    };
+    Some(format!("{}://{}:{}", scheme, host, info.get_port()))
+}
+
+impl MdnsAdapter {
+    /// Creates a new instance of `MdnsAdapter`, starting the mDNS responder thread.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// A `Result` containing an instance of `MdnsAdapter`, or an error if the
+    /// multicast sockets cannot be opened.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Result<Self, String> {
+        let daemon = ServiceDaemon::new().map_err(|e| format!("Failed to start mDNS: {}", e))?;
+        Ok(MdnsAdapter {
+            logger,
+            daemon,
+            registered: Mutex::new(None),
+        })
+    }
+}
+
+// Implement the `AgentDiscoveryPort` trait for `MdnsAdapter`.
+impl AgentDiscoveryPort for MdnsAdapter {
+    fn advertise(&self, advertisement: &AgentAdvertisement) -> Result<(), String> {
+        if advertisement.address.ip().is_loopback() {
+            return Err(
+                "the REST API only listens on the loopback interface, which other machines cannot reach"
+                    .to_string(),
+            );
+        }
+
+        let mut properties = HashMap::new();
+        properties.insert("version".to_string(), env!("CARGO_PKG_VERSION").to_string());
+        properties.insert("scheme".to_string(), advertisement.scheme.to_string());
+        if let Some(url) = &advertisement.url {
+            properties.insert("url".to_string(), url.clone());
+        }
+        // An unspecified address listens on every interface, so every address is announced,
+        // and kept up to date as interfaces come and go.
+        let addresses = if advertisement.address.ip().is_unspecified() {
+            String::new()
+        } else {
+            advertisement.address.ip().to_string()
+        };
+        let mut info = ServiceInfo::new(
+            AGENT_SERVICE_TYPE,
+            &advertisement.name,
+            &format!("{}.local.", hostname()),
+            addresses.as_str(),
+            advertisement.address.port(),
+            properties,
+        )
+        .map_err(|e| format!("Invalid mDNS announcement: {}", e))?;
+        if advertisement.address.ip().is_unspecified() {
+            info = info.enable_addr_auto();
+        }
+
+        let fullname = info.get_fullname().to_string();
+        self.daemon
+            .register(info)
+            .map_err(|e| format!("Failed to announce the agent over mDNS: {}", e))?;
+        self.logger
+            .log_debug(&format!("Announced {} over mDNS", fullname));
+        if let Ok(mut registered) = self.registered.lock() {
+            *registered = Some(fullname);
+        }
+        Ok(())
+    }
+
+    fn withdraw(&self) {
+        let Some(fullname) = self.registered.lock().ok().and_then(|mut r| r.take()) else {
+            return;
+        };
+        match self.daemon.unregister(&fullname) {
+            Ok(status) => {
+                let _ = status.recv_timeout(WITHDRAW_TIMEOUT);
+                self.logger
+                    .log_debug(&format!("Withdrew {} from mDNS", fullname));
+            }
+            Err(e) => self
+                .logger
+                .log_warn(&format!("Failed to withdraw the mDNS announcement: {}", e)),
+        }
+    }
+
+    fn discover(&self, timeout: Duration) -> Result<Vec<Agent>, String> {
+        let events = self
+            .daemon
+            .browse(AGENT_SERVICE_TYPE)
+            .map_err(|e| format!("Failed to browse for agents: {}", e))?;
+        let deadline = Instant::now() + timeout;
+        let mut agents: HashMap<String, Agent> = HashMap::new();
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            let Ok(event) = events.recv_timeout(remaining) else {
+                break;
+            };
+            match event {
+                ServiceEvent::ServiceResolved(info) => {
+                    let Some(url) = announced_url(&info) else {
+                        continue;
+                    };
+                    let name = info
+                        .get_fullname()
+                        .trim_end_matches(AGENT_SERVICE_TYPE)
+                        .trim_end_matches('.')
+                        .to_string();
+                    self.logger
+                        .log_debug(&format!("Found agent {} at {}", name, url));
+                    agents.insert(
+                        info.get_fullname().to_string(),
+                        Agent {
+                            name,
+                            url,
+                            version: info.get_property_val_str("version").map(str::to_string),
+                            source: AgentSource::Mdns,
+                        },
+                    );
+                }
+                ServiceEvent::ServiceRemoved(_, fullname) => {
+                    agents.remove(&fullname);
+                }
+                _ => {}
+            }
+        }
+        let _ = self.daemon.stop_browse(AGENT_SERVICE_TYPE);
+        Ok(agents.into_values().collect())
+    }
+}
@@ -0,0 +1,227 @@
+//! Memory Benchmark Adapter
+//!
+//! This module provides an adapter that measures memory bandwidth and latency
+//! in-process. Buffers are placed on a NUMA node by first touching them from a
+//! thread pinned to that node, then measured from threads pinned elsewhere.
+//! Cache levels are measured the same way with working sets sized to fit them.
+
+use std::hint::black_box;
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+use common::ports::log_port::LoggerPort;
+use tokio_util::sync::CancellationToken;
+
+use crate::adapters::topology_adapter::pin_current_thread;
+use crate::domain::cache::{CacheLevelResult, CacheSweepLevel};
+use crate::domain::numa::{NumaMemoryResult, NumaNode};
+use crate::ports::memory_benchmark_port::MemoryBenchmarkPort;
+
+/// Number of `usize` slots per 64-byte cache line.
+const SLOTS_PER_LINE: usize = 64 / std::mem::size_of::<usize>();
+
+/// Number of sequential passes over the buffer when measuring bandwidth.
+const BANDWIDTH_PASSES: usize = 4;
+
+/// Minimum bytes read when measuring cache bandwidth, so that small working sets
+/// are timed over enough passes to be measurable.
+const CACHE_BANDWIDTH_BYTES: usize = 1024 * 1024 * 1024;
+
+/// Number of dependent loads performed when measuring latency.
+const LATENCY_STEPS: usize = 2_000_000;
+
+/// Represents the in-process memory benchmark adapter.
+pub struct MemoryBenchmarkAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl MemoryBenchmarkAdapter {
+    /// Creates a new instance of `MemoryBenchmarkAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `MemoryBenchmarkAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        MemoryBenchmarkAdapter { logger }
+    }
+
+    /// Builds a pointer-chasing buffer whose cache lines form a single random cycle.
+    ///
+    /// Each cache line's first slot holds the index of the next line to visit, so
+    /// every load depends on the previous one and hardware prefetchers cannot help.
+    /// Writing every slot also first-touches the pages on the calling thread's node.
+    pub(crate) fn build_chase_buffer(buffer_bytes: usize) -> Vec<usize> {
+        let lines = (buffer_bytes / 64).max(2);
+        let mut buffer = vec![0usize; lines * SLOTS_PER_LINE];
+
+        // Sattolo's algorithm produces a permutation consisting of one cycle.
+        let mut order: Vec<usize> = (0..lines).collect();
+        let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+        for i in (1..lines).rev() {
+            // xorshift64 keeps the shuffle deterministic without an RNG dependency.
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let j = (state % i as u64) as usize;
+            order.swap(i, j);
+        }
+        for i in 0..lines {
+            let next = order[(i + 1) % lines];
+            buffer[order[i] * SLOTS_PER_LINE] = next * SLOTS_PER_LINE;
+        }
+
+        buffer
+    }
+
+    /// Measures sequential read bandwidth over `passes` reads of the buffer in
+    /// megabytes per second.
+    pub(crate) fn measure_bandwidth(buffer: &[usize], passes: usize) -> f64 {
+        let start = Instant::now();
+        let mut sum = 0usize;
+        for _ in 0..passes {
+            for value in buffer {
+                sum = sum.wrapping_add(*value);
+            }
+        }
+        black_box(sum);
+
+        let bytes = std::mem::size_of_val(buffer) * passes;
+        bytes as f64 / start.elapsed().as_secs_f64() / 1_000_000.0
+    }
+
+    /// Measures average dependent-load latency over the buffer in nanoseconds.
+    pub(crate) fn measure_latency(buffer: &[usize], steps: usize) -> f64 {
+        let start = Instant::now();
+        let mut index = 0usize;
+        for _ in 0..steps {
+            index = buffer[index];
+        }
+        black_box(index);
+
+        start.elapsed().as_nanos() as f64 / steps as f64
+    }
+
+    /// Measures the latency of a chase buffer of `bytes` after one warm-up lap, so
+    /// that a working set that fits in a cache is measured from that cache.
+    fn measure_warm_latency(bytes: usize) -> f64 {
+        let buffer = Self::build_chase_buffer(bytes);
+        Self::measure_latency(&buffer, buffer.len() / SLOTS_PER_LINE);
+        Self::measure_latency(&buffer, LATENCY_STEPS)
+    }
+}
+
+// Implement the `MemoryBenchmarkPort` trait for `MemoryBenchmarkAdapter`.
+impl MemoryBenchmarkPort for MemoryBenchmarkAdapter {
+    fn run_numa_matrix(
+        &self,
+        nodes: &[NumaNode],
+        buffer_bytes: usize,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<NumaMemoryResult>, String> {
+        let mut results = Vec::new();
+
+        for memory_node in nodes {
+            let memory_cpu = *memory_node
+                .cpus
+                .first()
+                .ok_or_else(|| format!("NUMA node {} has no CPUs", memory_node.id))?;
+
+            // Allocate and first-touch the buffer from a thread on the memory node.
+            let mut buffer = thread::spawn(move || {
+                pin_current_thread(memory_cpu)?;
+                Ok::<_, String>(Self::build_chase_buffer(buffer_bytes))
+            })
+            .join()
+            .map_err(|_| "Memory placement thread panicked".to_string())??;
+
+            for cpu_node in nodes {
+                if cancel.is_cancelled() {
+                    return Err("Benchmark cancelled".to_string());
+                }
+                let cpu = match cpu_node.cpus.first() {
+                    Some(cpu) => *cpu,
+                    None => continue,
+                };
+                self.logger.log_debug(&format!(
+                    "Measuring node {} memory from CPU {} on node {}",
+                    memory_node.id, cpu, cpu_node.id
+                ));
+
+                // Measure from a thread pinned to the accessing node, then hand the
+                // buffer back so the next node can measure the same pages.
+                let (returned, bandwidth_mb_s, latency_ns) = thread::spawn(move || {
+                    pin_current_thread(cpu)?;
+                    let bandwidth = Self::measure_bandwidth(&buffer, BANDWIDTH_PASSES);
+                    let latency = Self::measure_latency(&buffer, LATENCY_STEPS);
+                    Ok::<_, String>((buffer, bandwidth, latency))
+                })
+                .join()
+                .map_err(|_| "Memory measurement thread panicked".to_string())??;
+                buffer = returned;
+
+                results.push(NumaMemoryResult {
+                    cpu_node: cpu_node.id,
+                    memory_node: memory_node.id,
+                    bandwidth_mb_s,
+                    latency_ns,
+                });
+            }
+        }
+
+        Ok(results)
+    }
+
+    fn run_cache_sweep(
+        &self,
+        cpu: u32,
+        levels: &[CacheSweepLevel],
+        cancel: &CancellationToken,
+    ) -> Result<Vec<CacheLevelResult>, String> {
+        let logger = self.logger.clone();
+        let levels = levels.to_vec();
+        let cancel = cancel.clone();
+
+        // Run every level on one pinned thread, so that the caches measured are
+        // always those of the same core.
+        thread::spawn(move || {
+            if let Err(e) = pin_current_thread(cpu) {
+                logger.log_warn(&format!("{}; results may mix several cores", e));
+            }
+
+            let mut results = Vec::new();
+            for level in levels {
+                if cancel.is_cancelled() {
+                    return Err("Benchmark cancelled".to_string());
+                }
+                logger.log_debug(&format!(
+                    "Measuring {} with a {} byte working set on CPU {}",
+                    level.name, level.working_set_bytes, cpu
+                ));
+
+                let buffer = Self::build_chase_buffer(level.working_set_bytes);
+                let passes =
+                    (CACHE_BANDWIDTH_BYTES / level.working_set_bytes.max(1)).max(BANDWIDTH_PASSES);
+                Self::measure_bandwidth(&buffer, 1);
+                let bandwidth_mb_s = Self::measure_bandwidth(&buffer, passes);
+                Self::measure_latency(&buffer, buffer.len() / SLOTS_PER_LINE);
+                let latency_ns = Self::measure_latency(&buffer, LATENCY_STEPS);
+                drop(buffer);
+
+                let probe_latency_ns = level.probe_bytes.map(Self::measure_warm_latency);
+
+                results.push(CacheLevelResult {
+                    level,
+                    bandwidth_mb_s,
+                    latency_ns,
+                    probe_latency_ns,
+                });
+            }
+            Ok(results)
+        })
+        .join()
+        .map_err(|_| "Cache measurement thread panicked".to_string())?
+    }
+}
@@ -0,0 +1,168 @@
+//! Memtest Adapter
+//!
+//! This module provides an adapter that tests RAM in-process, in the manner of
+//! memtester. The buffer is locked with mlock so every test reaches RAM rather
+//! than swap, and each word is written and read back with volatile accesses so
+//! the compiler cannot keep the pattern in registers instead.
+
+use std::collections::HashSet;
+use std::ptr;
+use std::sync::Arc;
+use std::time::Instant;
+
+use common::ports::log_port::LoggerPort;
+use tokio_util::sync::CancellationToken;
+
+use crate::adapters::integrity_adapter::{physical_address, run_seed};
+use crate::domain::endurance::format_volume;
+use crate::domain::integrity::describe_mismatch;
+use crate::domain::memtest::{MemtestPattern, MemtestResult, MemtestTestResult};
+use crate::ports::memtest_port::MemtestPort;
+
+/// Represents the in-process RAM test adapter.
+pub struct MemtestAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl MemtestAdapter {
+    /// Creates a new instance of `MemtestAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `MemtestAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        MemtestAdapter { logger }
+    }
+
+    /// Runs every round of one pattern test over the buffer.
+    ///
+    /// A failing word is listed once per test, on the round it was first found,
+    /// but counted on every round it fails.
+    ///
+    /// # Returns
+    /// `false` if the test was cancelled before it finished.
+    fn run_pattern(
+        &self,
+        buffer: &mut [u64],
+        pattern: MemtestPattern,
+        seed: u64,
+        stop: &CancellationToken,
+        seen: &mut HashSet<usize>,
+        result: &mut MemtestTestResult,
+    ) -> bool {
+        let base = buffer.as_ptr() as u64;
+        for round in 0..pattern.rounds() {
+            if stop.is_cancelled() {
+                return false;
+            }
+            for (index, word) in buffer.iter_mut().enumerate() {
+                let expected = pattern.word(round, seed, index as u64, base + index as u64 * 8);
+                // SAFETY: the pointer comes from a live mutable reference into the buffer.
+                unsafe { ptr::write_volatile(word, expected) };
+            }
+            for (index, word) in buffer.iter().enumerate() {
+                let expected = pattern.word(round, seed, index as u64, base + index as u64 * 8);
+                // SAFETY: the pointer comes from a live reference into the buffer.
+                let actual = unsafe { ptr::read_volatile(word) };
+                if actual == expected {
+                    continue;
+                }
+                if !seen.insert(index) {
+                    result.failures += 1;
+                    continue;
+                }
+                let address = word as *const u64 as usize;
+                let location = match physical_address(address) {
+                    Some(physical) => format!("address {:#x} (physical {:#x})", address, physical),
+                    None => format!("address {:#x}", address),
+                };
+                let detail = describe_mismatch(expected, actual);
+                self.logger.log_error(&format!(
+                    "{} test failed at {}: {}",
+                    pattern.name(),
+                    location,
+                    detail
+                ));
+                result.record(location, detail);
+            }
+        }
+        true
+    }
+}
+
+// Implement the `MemtestPort` trait for `MemtestAdapter`.
+impl MemtestPort for MemtestAdapter {
+    fn run_memtest(
+        &self,
+        bytes: usize,
+        deadline: Instant,
+        stop: &CancellationToken,
+    ) -> Result<MemtestResult, String> {
+        let total_words = bytes / 8;
+        if total_words == 0 {
+            return Err("the memory buffer is empty".to_string());
+        }
+        let mut buffer: Vec<u64> = Vec::new();
+        buffer
+            .try_reserve_exact(total_words)
+            .map_err(|e| format!("Failed to allocate {}: {}", format_volume(bytes as u64), e))?;
+        buffer.resize(total_words, 0);
+
+        // SAFETY: the range covers the buffer, which outlives the lock.
+        let locked =
+            unsafe { libc::mlock(buffer.as_ptr() as *const libc::c_void, total_words * 8) } == 0;
+        if !locked {
+            self.logger.log_warn(&format!(
+                "Failed to lock {} in RAM ({}); raise the memlock limit or run as root",
+                format_volume(total_words as u64 * 8),
+                std::io::Error::last_os_error()
+            ));
+        }
+        self.logger.log_info(&format!(
+            "Testing {} of memory with {} pattern tests per pass",
+            format_volume(total_words as u64 * 8),
+            MemtestPattern::ALL.len()
+        ));
+
+        let mut result = MemtestResult {
+            bytes: total_words as u64 * 8,
+            locked,
+            passes: 0,
+            tests: MemtestPattern::ALL
+                .iter()
+                .map(|pattern| MemtestTestResult::new(*pattern))
+                .collect(),
+        };
+        let mut seen: Vec<HashSet<usize>> = vec![HashSet::new(); MemtestPattern::ALL.len()];
+        let seed = run_seed();
+        'passes: loop {
+            for (test, pattern) in MemtestPattern::ALL.iter().enumerate() {
+                // Later passes end at the deadline; the first always runs every test.
+                if result.passes > 0 && Instant::now() >= deadline {
+                    break 'passes;
+                }
+                // The random pattern changes on every pass.
+                let pass_seed = seed ^ result.passes;
+                if !self.run_pattern(
+                    &mut buffer,
+                    *pattern,
+                    pass_seed,
+                    stop,
+                    &mut seen[test],
+                    &mut result.tests[test],
+                ) {
+                    break 'passes;
+                }
+            }
+            result.passes += 1;
+        }
+
+        if locked {
+            // SAFETY: unlocks the range locked above.
+            unsafe { libc::munlock(buffer.as_ptr() as *const libc::c_void, total_words * 8) };
+        }
+        Ok(result)
+    }
+}
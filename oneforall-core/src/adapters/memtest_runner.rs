@@ -0,0 +1,168 @@
+//! Memtest Runner
+//!
+//! This module runs the RAM test started by `oneforall stress --memtest`. The
+//! pattern tests run on a blocking thread for the duration of the test, and the
+//! ECC counters of the EDAC watcher are read before and after, so the report
+//! can say whether ECC saw the same faults as the tests.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use common::domain::job::{JobSpec, JobStatus, Resource};
+use common::ports::job_port::JobPort;
+use common::ports::log_port::LoggerPort;
+use common::ports::telemetry_port::TelemetryPort;
+
+use crate::adapters::hardware_error_adapter::HardwareErrorAdapter;
+use crate::adapters::memtest_adapter::MemtestAdapter;
+use crate::adapters::progress_reporter::ProgressReporter;
+use crate::domain::burn_in::WorkloadOutcome;
+use crate::domain::endurance::format_volume;
+use crate::domain::hardware_errors::ecc_faults;
+use crate::domain::memtest::MemtestReport;
+use crate::ports::hardware_error_port::HardwareErrorPort;
+use crate::ports::memtest_port::MemtestPort;
+
+/// Runs RAM tests.
+pub struct MemtestRunner {
+    logger: Arc<dyn LoggerPort>,
+    telemetry: Arc<dyn TelemetryPort>,
+    jobs: Arc<dyn JobPort>,
+    errors: Box<dyn HardwareErrorPort>,
+    memtest: Arc<dyn MemtestPort>,
+}
+
+impl MemtestRunner {
+    /// Creates a new instance of `MemtestRunner`.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger` - Logger implementation for logging messages.
+    /// * `telemetry` - Receives the progress of the test.
+    /// * `jobs` - The job queue the test is submitted to.
+    ///
+    /// # Returns
+    ///
+    /// * `MemtestRunner` - A runner testing the memory of this machine.
+    pub fn new(
+        logger: Arc<dyn LoggerPort>,
+        telemetry: Arc<dyn TelemetryPort>,
+        jobs: Arc<dyn JobPort>,
+    ) -> Self {
+        MemtestRunner {
+            errors: Box::new(HardwareErrorAdapter::new(logger.clone())),
+            memtest: Arc::new(MemtestAdapter::new(logger.clone())),
+            logger,
+            telemetry,
+            jobs,
+        }
+    }
+
+    /// Runs the RAM test for the given duration.
+    ///
+    /// The test is queued as a memory job, so it waits for any other memory
+    /// workload competing for the same RAM.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The size of the buffer to test.
+    /// * `duration` - How long to keep running passes; the first pass always completes.
+    ///
+    /// # Returns
+    ///
+    /// * `MemtestReport` - The pass/fail report of the test.
+    pub async fn run(&self, bytes: u64, duration: Duration) -> MemtestReport {
+        let mut ticket = self
+            .jobs
+            .submit_job(JobSpec::new("stress:memtest", &[Resource::Memory]));
+        let job_id = ticket.job.id;
+        let mut report = MemtestReport {
+            started_at: Local::now().to_rfc3339(),
+            planned_secs: duration.as_secs(),
+            elapsed_secs: 0,
+            outcome: WorkloadOutcome::Cancelled,
+            result: None,
+            ecc_errors: Err("not read".to_string()),
+        };
+        if ticket.job.status == JobStatus::Queued {
+            self.logger.log_info(&format!(
+                "RAM test job {} queued behind a conflicting job.",
+                job_id
+            ));
+        }
+        if !ticket.admitted().await {
+            self.logger.log_warn("RAM test cancelled while queued.");
+            return report;
+        }
+
+        let ecc_before = self.errors.read_ecc_counts();
+        if let Err(e) = &ecc_before {
+            self.logger
+                .log_warn(&format!("ECC errors will not be cross-checked: {}", e));
+        }
+        self.logger.log_info(&format!(
+            "Started RAM test job {}: testing {} for {} seconds.",
+            job_id,
+            format_volume(bytes),
+            duration.as_secs()
+        ));
+
+        let progress = ProgressReporter::start(
+            self.logger.clone(),
+            self.telemetry.clone(),
+            "memtest",
+            duration,
+        );
+        let started = Instant::now();
+        let memtest = self.memtest.clone();
+        let cancel = ticket.cancel.clone();
+        let deadline = started + duration;
+        let outcome = tokio::task::spawn_blocking(move || {
+            memtest.run_memtest(bytes as usize, deadline, &cancel)
+        })
+        .await
+        .unwrap_or_else(|e| Err(format!("RAM test panicked: {}", e)));
+        report.elapsed_secs = started.elapsed().as_secs();
+        progress.finish(None);
+
+        report.ecc_errors = match (ecc_before, self.errors.read_ecc_counts()) {
+            (Ok(before), Ok(after)) => Ok(ecc_faults(&before, &after)),
+            (Err(e), _) | (_, Err(e)) => Err(e),
+        };
+
+        let (outcome, status, message) = match outcome {
+            Ok(result) => {
+                let cancelled = ticket.cancel.is_cancelled();
+                report.result = Some(result);
+                if cancelled {
+                    (WorkloadOutcome::Cancelled, JobStatus::Cancelled, None)
+                } else {
+                    (WorkloadOutcome::Completed, JobStatus::Completed, None)
+                }
+            }
+            Err(e) => (
+                WorkloadOutcome::Failed(e.clone()),
+                JobStatus::Failed,
+                Some(e),
+            ),
+        };
+        report.outcome = outcome;
+        let (status, message) = if status == JobStatus::Completed && !report.passed() {
+            let failures = report.result.as_ref().map_or(0, |result| result.failures());
+            (
+                JobStatus::Failed,
+                Some(format!(
+                    "{} failing word(s); ECC: {}",
+                    failures,
+                    report.ecc_confirmation()
+                )),
+            )
+        } else {
+            (status, message)
+        };
+        self.jobs.finish_job(job_id, status, message);
+
+        report
+    }
+}
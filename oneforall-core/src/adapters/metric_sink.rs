@@ -0,0 +1,263 @@
+//! Metric Sink
+//!
+//! This module follows the metric registry and hands every batch of readings
+//! to a sink: the database, where they are stored as readings of the `metrics`
+//! collector, or a CSV file with one row per reading. Whatever published the
+//! readings, power, thermal, GPU or process collectors, they arrive in the
+//! same `Metric` shape, so a sink is written once for all of them.
+//!
+//! Batches wait for the sink in a queue bounded by the `[footprint]` section,
+//! written from a thread of their own, so a slow disk never holds up the
+//! registry. Once the queue is full, its overflow policy drops or aggregates
+//! batches, and the counters are published as the sink's pipeline.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+use chrono::Utc;
+use common::domain::footprint::FootprintConfig;
+use common::domain::metric::{Metric, METRICS_COLLECTOR, METRIC_CSV_HEADER};
+use common::domain::pipeline::{Backlog, OverflowPolicy, PipelineStats};
+use common::domain::telemetry::TelemetryEvent;
+use common::ports::log_port::LoggerPort;
+use common::ports::telemetry_port::TelemetryPort;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+
+use crate::adapters::footprint_governor::COLLECTOR_THREAD_PREFIX;
+use crate::adapters::sample_writer::SampleWriter;
+
+/// The batches waiting for a sink and the counters of its pipeline.
+struct Queue {
+    backlog: Backlog<Vec<Metric>>,
+    stats: PipelineStats,
+    /// Whether batches have been dropped or aggregated since the queue was last empty.
+    behind: bool,
+    stopped: bool,
+}
+
+/// Writes the readings published to the metric registry in the background, for as long
+/// as the registry is open or until stopped.
+pub struct MetricSink {
+    task: JoinHandle<()>,
+    queue: Arc<(Mutex<Queue>, Condvar)>,
+}
+
+impl MetricSink {
+    /// Starts handing every batch of readings published from now on to a sink.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger` - Receives a warning when readings are skipped.
+    /// * `telemetry` - The metric registry, where the counters of the pipeline are published.
+    /// * `name` - The pipeline, e.g. "metrics-csv".
+    /// * `budget` - The size of the queue and what gives once it is full.
+    /// * `write` - Writes a batch of readings, or says why it could not.
+    ///
+    /// # Returns
+    ///
+    /// * `MetricSink` - A handle used to stop the sink.
+    fn start(
+        logger: Arc<dyn LoggerPort>,
+        telemetry: &Arc<dyn TelemetryPort>,
+        name: &str,
+        budget: &FootprintConfig,
+        mut write: impl FnMut(&[Metric]) -> Result<(), String> + Send + 'static,
+    ) -> Self {
+        let stats = PipelineStats {
+            name: name.to_string(),
+            overflow: budget.sink_overflow,
+            capacity: budget.sink_queue,
+            ..PipelineStats::default()
+        };
+        telemetry.publish_pipeline(stats.clone());
+        let queue = Arc::new((
+            Mutex::new(Queue {
+                backlog: Backlog::new(budget.sink_queue, budget.sink_overflow),
+                stats,
+                behind: false,
+                stopped: false,
+            }),
+            Condvar::new(),
+        ));
+        let giving_way = match budget.sink_overflow {
+            OverflowPolicy::DropOldest => "dropping the oldest readings",
+            OverflowPolicy::DropNewest => "dropping new readings",
+            OverflowPolicy::Aggregate => "averaging new readings into those waiting",
+        };
+
+        // The subscriber only queues batches, so it keeps up with the registry
+        // however slow the sink is.
+        let mut receiver = telemetry.subscribe();
+        let (queued, subscriber_telemetry, subscriber_logger) =
+            (queue.clone(), telemetry.clone(), logger.clone());
+        let task = tokio::spawn(async move {
+            loop {
+                let metrics = match receiver.recv().await {
+                    Ok(TelemetryEvent::Metrics { metrics }) => metrics,
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(skipped)) => {
+                        subscriber_logger.log_warn(&format!(
+                            "A metric sink fell behind and skipped {} event(s).",
+                            skipped
+                        ));
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                let (lock, ready) = &*queued;
+                let Ok(mut queue) = lock.lock() else {
+                    break;
+                };
+                queue.stats.received += metrics.len() as u64;
+                let overflow = queue.backlog.push(metrics);
+                queue.stats.count(overflow);
+                if (overflow.dropped > 0 || overflow.aggregated > 0) && !queue.behind {
+                    queue.behind = true;
+                    subscriber_logger.log_warn(&format!(
+                        "The {} sink is falling behind; {} until it catches up.",
+                        queue.stats.name, giving_way
+                    ));
+                }
+                queue.stats.queued = queue.backlog.len();
+                subscriber_telemetry.publish_pipeline(queue.stats.clone());
+                ready.notify_one();
+            }
+        });
+
+        let (writer_queue, writer_telemetry, logger_for_spawn) =
+            (queue.clone(), telemetry.clone(), logger.clone());
+        let spawned = thread::Builder::new()
+            .name(format!("{}{}", COLLECTOR_THREAD_PREFIX, name))
+            .spawn(move || {
+                let (lock, ready) = &*writer_queue;
+                loop {
+                    let metrics = {
+                        let Ok(mut queue) = lock.lock() else {
+                            return;
+                        };
+                        while queue.backlog.is_empty() && !queue.stopped {
+                            queue = match ready.wait(queue) {
+                                Ok(queue) => queue,
+                                Err(_) => return,
+                            };
+                        }
+                        match queue.backlog.pop() {
+                            Some(metrics) => metrics,
+                            None => return,
+                        }
+                    };
+                    let result = write(&metrics);
+
+                    let Ok(mut queue) = lock.lock() else {
+                        return;
+                    };
+                    match result {
+                        Ok(()) => queue.stats.written += metrics.len() as u64,
+                        Err(e) => {
+                            queue.stats.dropped += metrics.len() as u64;
+                            logger.log_warn(&e);
+                        }
+                    }
+                    queue.stats.queued = queue.backlog.len();
+                    if queue.behind && queue.backlog.is_empty() {
+                        queue.behind = false;
+                        logger.log_info(&format!(
+                            "The {} sink caught up; {} reading(s) dropped, {} aggregated.",
+                            queue.stats.name, queue.stats.dropped, queue.stats.aggregated
+                        ));
+                    }
+                    writer_telemetry.publish_pipeline(queue.stats.clone());
+                }
+            });
+        if let Err(e) = spawned {
+            logger_for_spawn.log_warn(&format!("Not writing the {} sink: {}", name, e));
+        }
+        MetricSink { task, queue }
+    }
+
+    /// Stores every batch of readings in the database, through the buffered writer.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger` - Receives a warning when readings cannot be stored.
+    /// * `telemetry` - The metric registry.
+    /// * `samples` - The writer the readings are stored through.
+    /// * `budget` - The size of the queue and what gives once it is full.
+    ///
+    /// # Returns
+    ///
+    /// * `MetricSink` - A handle used to stop the sink.
+    pub fn database(
+        logger: Arc<dyn LoggerPort>,
+        telemetry: &Arc<dyn TelemetryPort>,
+        samples: SampleWriter,
+        budget: &FootprintConfig,
+    ) -> Self {
+        Self::start(logger, telemetry, "metrics-db", budget, move |metrics| {
+            samples
+                .record(METRICS_COLLECTOR, Utc::now(), &metrics)
+                .map_err(|e| format!("Failed to store metrics: {}", e))
+        })
+    }
+
+    /// Appends every reading to a CSV file, writing the header to a new file.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger` - Receives a warning when readings cannot be written.
+    /// * `telemetry` - The metric registry.
+    /// * `path` - The CSV file.
+    /// * `budget` - The size of the queue and what gives once it is full.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<MetricSink, String>` - A handle used to stop the sink, or why the file
+    ///   could not be opened.
+    pub fn csv(
+        logger: Arc<dyn LoggerPort>,
+        telemetry: &Arc<dyn TelemetryPort>,
+        path: &Path,
+        budget: &FootprintConfig,
+    ) -> Result<Self, String> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        let empty = file.metadata().map(|m| m.len() == 0).unwrap_or(false);
+        if empty {
+            writeln!(file, "{}", METRIC_CSV_HEADER)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        }
+        let display = path.display().to_string();
+        Ok(Self::start(
+            logger,
+            telemetry,
+            "metrics-csv",
+            budget,
+            move |metrics| {
+                let rows: String = metrics
+                    .iter()
+                    .map(|metric| format!("{}\n", metric.csv_row()))
+                    .collect();
+                file.write_all(rows.as_bytes())
+                    .map_err(|e| format!("Failed to write metrics to {}: {}", display, e))
+            },
+        ))
+    }
+
+    /// Stops the sink; readings published from now on are no longer written, and
+    /// those already queued are written before its thread ends.
+    pub fn stop(self) {
+        self.task.abort();
+        let (lock, ready) = &*self.queue;
+        if let Ok(mut queue) = lock.lock() {
+            queue.stopped = true;
+        }
+        ready.notify_one();
+    }
+}
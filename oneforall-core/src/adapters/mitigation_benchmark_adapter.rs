@@ -0,0 +1,206 @@
+//! Mitigation Benchmark Adapter
+//!
+//! This module provides an adapter that measures the costs CPU vulnerability
+//! mitigations add to: entering the kernel, timed with `getppid` against a
+//! `clock_gettime` the vDSO serves in user space, and switching between two
+//! processes, timed by passing a byte back and forth over a pair of pipes
+//! between this process and a forked child pinned to the same CPU. Processes
+//! are used rather than threads because mitigations such as IBPB only apply
+//! when the address space changes.
+
+use std::fs;
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+use common::ports::log_port::LoggerPort;
+use tokio_util::sync::CancellationToken;
+
+use crate::adapters::topology_adapter::pin_current_thread;
+use crate::domain::mitigations::MitigationOverhead;
+use crate::ports::mitigation_benchmark_port::MitigationBenchmarkPort;
+
+/// Number of calls timed per round when measuring vDSO and system calls.
+const CALLS: u32 = 1_000_000;
+
+/// Number of round trips timed per round when measuring context switches.
+const ROUND_TRIPS: u32 = 50_000;
+
+/// Number of rounds of each measurement; the fastest one is kept, since
+/// interrupts and other tasks only ever make a round slower.
+const ROUNDS: usize = 5;
+
+/// Represents the in-process mitigation benchmark adapter.
+pub struct MitigationBenchmarkAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl MitigationBenchmarkAdapter {
+    /// Creates a new instance of `MitigationBenchmarkAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `MitigationBenchmarkAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        MitigationBenchmarkAdapter { logger }
+    }
+
+    /// Runs a measurement several times and returns the fastest time per operation, in nanoseconds.
+    fn fastest<F>(operations: u32, cancel: &CancellationToken, mut round: F) -> Result<f64, String>
+    where
+        F: FnMut() -> Result<(), String>,
+    {
+        let mut fastest = f64::MAX;
+        for _ in 0..ROUNDS {
+            if cancel.is_cancelled() {
+                return Err("Benchmark cancelled".to_string());
+            }
+            let start = Instant::now();
+            round()?;
+            fastest = fastest.min(start.elapsed().as_nanos() as f64 / operations as f64);
+        }
+        Ok(fastest)
+    }
+
+    /// Times `clock_gettime`, which the vDSO answers without entering the kernel.
+    fn vdso_calls() -> Result<(), String> {
+        let mut time = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        for _ in 0..CALLS {
+            // SAFETY: `time` is a valid, writable `timespec`.
+            unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut time) };
+        }
+        Ok(())
+    }
+
+    /// Times `getppid`, a system call that does next to nothing once in the kernel.
+    fn system_calls() -> Result<(), String> {
+        for _ in 0..CALLS {
+            // SAFETY: `getppid` takes no arguments and cannot fail.
+            std::hint::black_box(unsafe { libc::getppid() });
+        }
+        Ok(())
+    }
+
+    /// Creates a pipe, returning its read and write ends.
+    fn pipe() -> Result<[libc::c_int; 2], String> {
+        let mut fds = [0; 2];
+        // SAFETY: `fds` has room for the two descriptors `pipe` writes.
+        if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+            return Err(format!(
+                "Failed to create a pipe: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        Ok(fds)
+    }
+
+    /// Times round trips of one byte to a forked child and back.
+    ///
+    /// Each round trip blocks this process and wakes the child, then the
+    /// reverse, so it takes two context switches when both run on one CPU.
+    ///
+    /// # Returns
+    /// The time of one context switch, in nanoseconds.
+    fn context_switches(cancel: &CancellationToken) -> Result<f64, String> {
+        let to_child = Self::pipe()?;
+        let to_parent = Self::pipe()?;
+
+        // SAFETY: the child only calls `read`, `write` and `_exit`, which are
+        // async-signal-safe and so sound after forking a multi-threaded process.
+        let child = unsafe { libc::fork() };
+        if child < 0 {
+            return Err(format!(
+                "Failed to fork: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        if child == 0 {
+            let mut byte = 0u8;
+            // SAFETY: the descriptors are open in the child and `byte` is one writable byte.
+            unsafe {
+                libc::close(to_child[1]);
+                libc::close(to_parent[0]);
+                while libc::read(to_child[0], &mut byte as *mut u8 as *mut libc::c_void, 1) == 1 {
+                    libc::write(to_parent[1], &byte as *const u8 as *const libc::c_void, 1);
+                }
+                libc::_exit(0);
+            }
+        }
+
+        // SAFETY: the descriptors belong to this process and the child end is closed once.
+        unsafe {
+            libc::close(to_child[0]);
+            libc::close(to_parent[1]);
+        }
+        let result = Self::fastest(ROUND_TRIPS * 2, cancel, || {
+            let mut byte = 0u8;
+            for _ in 0..ROUND_TRIPS {
+                // SAFETY: `byte` is one readable and writable byte.
+                let ok = unsafe {
+                    libc::write(to_child[1], &byte as *const u8 as *const libc::c_void, 1) == 1
+                        && libc::read(to_parent[0], &mut byte as *mut u8 as *mut libc::c_void, 1)
+                            == 1
+                };
+                if !ok {
+                    return Err("The context switch partner process stopped".to_string());
+                }
+            }
+            Ok(())
+        });
+
+        // Closing the pipe ends the child's loop.
+        // SAFETY: the descriptors are open and `child` is our child process.
+        unsafe {
+            libc::close(to_child[1]);
+            libc::close(to_parent[0]);
+            libc::waitpid(child, std::ptr::null_mut(), 0);
+        }
+        result
+    }
+}
+
+// Implement the `MitigationBenchmarkPort` trait for `MitigationBenchmarkAdapter`.
+impl MitigationBenchmarkPort for MitigationBenchmarkAdapter {
+    fn read_kernel_command_line(&self) -> Result<String, String> {
+        fs::read_to_string("/proc/cmdline")
+            .map(|cmdline| cmdline.trim().to_string())
+            .map_err(|e| format!("Failed to read /proc/cmdline: {}", e))
+    }
+
+    fn measure_overhead(
+        &self,
+        cpu: u32,
+        cancel: &CancellationToken,
+    ) -> Result<MitigationOverhead, String> {
+        let logger = self.logger.clone();
+        let cancel = cancel.clone();
+
+        // Run on a pinned thread of its own; the forked child inherits its
+        // affinity, so both ends of every context switch share the CPU.
+        thread::spawn(move || {
+            if let Err(e) = pin_current_thread(cpu) {
+                logger.log_warn(&format!(
+                    "{}; context switches may cross CPUs and measure wake-up latency instead",
+                    e
+                ));
+            }
+            logger.log_debug(&format!("Measuring mitigation overhead on CPU {}", cpu));
+
+            let vdso_call_ns = Self::fastest(CALLS, &cancel, Self::vdso_calls)?;
+            let syscall_ns = Self::fastest(CALLS, &cancel, Self::system_calls)?;
+            let context_switch_ns = Self::context_switches(&cancel)?;
+            Ok(MitigationOverhead {
+                vdso_call_ns,
+                syscall_ns,
+                context_switch_ns,
+            })
+        })
+        .join()
+        .map_err(|_| "Mitigation measurement thread panicked".to_string())?
+    }
+}
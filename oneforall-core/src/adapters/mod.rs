@@ -0,0 +1,96 @@
+// src/adapters/domain
+pub mod alert_dispatcher;
+pub mod alerting_job_adapter;
+pub mod archive_adapter;
+pub mod background_sampler;
+pub mod battery_adapter;
+pub mod benchmark_runner;
+pub mod block_device_adapter;
+pub mod boot_timing_adapter;
+pub mod burn_in_runner;
+pub mod burst_sampler;
+pub mod cgroup_adapter;
+pub mod chaos_injector;
+pub mod clock_stability_adapter;
+pub mod cloud_metadata_adapter;
+pub mod codec_benchmark_adapter;
+pub mod cpu_features_adapter;
+pub mod database_adapter;
+pub mod device_inventory_adapter;
+pub mod dynamic_plugin_adapter;
+pub mod ebpf_latency_adapter;
+pub mod endurance_runner;
+pub mod entropy_adapter;
+pub mod filesystem_benchmark_adapter;
+pub mod firmware_adapter;
+pub mod footprint_governor;
+pub mod frequency_adapter;
+pub mod gpu_adapter;
+pub mod gpu_interconnect_adapter;
+pub mod gpu_stress_runner;
+pub mod grpc_server_adapter;
+pub mod hardware_discovery;
+pub mod hardware_error_adapter;
+pub mod health_monitor;
+pub mod history_adapter;
+pub mod hook_adapter;
+pub mod integrity_adapter;
+pub mod integrity_monitor;
+pub mod interrupt_adapter;
+pub mod jobs_client_adapter;
+pub mod kernel_log_watcher;
+pub mod kubernetes_adapter;
+pub mod mdns_adapter;
+pub mod memory_benchmark_adapter;
+pub mod memtest_adapter;
+pub mod memtest_runner;
+pub mod metric_sink;
+pub mod mitigation_benchmark_adapter;
+pub mod network_probe_adapter;
+pub mod nvme_adapter;
+pub mod pcie_adapter;
+pub mod perf_counter_adapter;
+pub mod perf_flamegraph_adapter;
+pub mod plugin_registry;
+pub mod power_adapter;
+pub mod power_cycle_runner;
+pub mod powermetrics_adapter;
+pub mod profile_adapter;
+pub mod profile_launcher;
+pub mod profile_runner;
+pub mod progress_reporter;
+pub mod ps_command_adapter;
+pub mod rdma_adapter;
+pub mod repository;
+pub mod result_exporter;
+pub mod retention_monitor;
+pub mod run_monitor;
+pub mod s3_adapter;
+pub mod sample_writer;
+pub mod scaling_benchmark_adapter;
+pub mod scheduler_latency_adapter;
+pub mod score_adapter;
+pub mod shutdown_coordinator;
+pub mod simd_stress_adapter;
+pub mod simd_stress_runner;
+pub mod smbios_adapter;
+pub mod smtp_alert_adapter;
+pub mod soak_runner;
+pub mod sqlite_database_adapter;
+pub mod storage_endurance_adapter;
+pub mod stress_ng_adapter;
+pub mod stress_ng_manager_adapter;
+pub mod stressor_catalog_adapter;
+pub mod sysfs;
+pub mod system_stats_adapter;
+pub mod systemd_adapter;
+pub mod telemetry_recorder;
+pub mod thermal_adapter;
+pub mod threshold_monitor;
+pub mod time_sync_adapter;
+pub mod topology_adapter;
+pub mod tui_adapter;
+pub mod virtualization_adapter;
+pub mod vram_test_runner;
+pub mod webhook_alert_adapter;
+pub mod workload_adapter;
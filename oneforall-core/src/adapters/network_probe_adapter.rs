@@ -0,0 +1,116 @@
+//! Network Probe Adapter
+//!
+//! This module provides the adapter with which an agent measures the network
+//! path to another agent when the orchestrator asks it to. Both measurements go
+//! through the REST API of the other agent, with the same token and
+//! certificates the `fleet` command uses: the latency is the median round trip
+//! of an empty request on a kept-alive connection, and the bandwidth is the
+//! throughput of downloading payloads for a few seconds.
+
+use std::io::Read;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use common::domain::api_config::ApiConfig;
+use common::domain::network_probe::{LinkMeasurement, MAX_PROBE_PAYLOAD_BYTES};
+use common::ports::log_port::LoggerPort;
+use common::ports::network_probe_port::NetworkProbePort;
+
+use crate::adapters::jobs_client_adapter::tls_client_config;
+
+/// The number of round trips timed; the first, which opens the connection, is not counted.
+const LATENCY_SAMPLES: usize = 21;
+
+/// How long payloads are downloaded for.
+const BANDWIDTH_DURATION: Duration = Duration::from_secs(3);
+
+/// Time allowed for each request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Represents the REST API network probe adapter.
+pub struct NetworkProbeAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    config: ApiConfig,
+}
+
+impl NetworkProbeAdapter {
+    /// Creates a new instance of `NetworkProbeAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    /// * `config` - The REST API settings holding the token and certificates other agents accept.
+    ///
+    /// # Returns
+    /// An instance of `NetworkProbeAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>, config: ApiConfig) -> Self {
+        NetworkProbeAdapter { logger, config }
+    }
+
+    /// Downloads one payload from the target and returns its size in bytes.
+    fn fetch(&self, agent: &ureq::Agent, url: &str) -> Result<u64, String> {
+        let mut request = agent.get(url);
+        if let Some(token) = self.config.client_token() {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+        let mut response = request
+            .call()
+            .map_err(|e| format!("Failed to reach {}: {}", url, e))?;
+        if !response.status().is_success() {
+            return Err(format!("{} answered {}", url, response.status()));
+        }
+        // The payload is counted and dropped rather than collected, so it needs no size limit.
+        let mut reader = response.body_mut().with_config().limit(u64::MAX).reader();
+        let mut buffer = vec![0u8; 1 << 16];
+        let mut received = 0;
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) => return Ok(received),
+                Ok(read) => received += read as u64,
+                Err(e) => return Err(format!("Failed to read from {}: {}", url, e)),
+            }
+        }
+    }
+}
+
+// Implement the `NetworkProbePort` trait for `NetworkProbeAdapter`.
+impl NetworkProbePort for NetworkProbeAdapter {
+    fn probe_link(&self, target: &str) -> Result<LinkMeasurement, String> {
+        let target = target.trim_end_matches('/');
+        self.logger
+            .log_info(&format!("Measuring the network path to {}", target));
+        let agent: ureq::Agent = ureq::Agent::config_builder()
+            .http_status_as_error(false)
+            .timeout_global(Some(REQUEST_TIMEOUT))
+            .tls_config(tls_client_config(&self.config)?)
+            .build()
+            .into();
+
+        let empty = format!("{}/api/v1/netprobe/payload?bytes=0", target);
+        let mut round_trips = Vec::with_capacity(LATENCY_SAMPLES);
+        for _ in 0..LATENCY_SAMPLES {
+            let started = Instant::now();
+            self.fetch(&agent, &empty)?;
+            round_trips.push(started.elapsed());
+        }
+        round_trips.remove(0);
+        round_trips.sort();
+        let latency = round_trips[round_trips.len() / 2];
+
+        let payload = format!(
+            "{}/api/v1/netprobe/payload?bytes={}",
+            target, MAX_PROBE_PAYLOAD_BYTES
+        );
+        let started = Instant::now();
+        let mut received = 0;
+        while started.elapsed() < BANDWIDTH_DURATION {
+            received += self.fetch(&agent, &payload)?;
+        }
+        let elapsed = started.elapsed().as_secs_f64();
+
+        Ok(LinkMeasurement {
+            target: target.to_string(),
+            latency_us: latency.as_secs_f64() * 1e6,
+            bandwidth_mbit_s: received as f64 * 8.0 / elapsed / 1e6,
+        })
+    }
+}
@@ -0,0 +1,157 @@
+//! NVMe Adapter
+//!
+//! This module provides an adapter that reads the NVMe controllers of the
+//! machine and their namespaces from the sysfs attributes of the Linux NVMe
+//! driver, and their SMART / Health Information and Error Information log
+//! pages with nvme-cli.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+
+use common::ports::log_port::LoggerPort;
+
+use crate::adapters::sysfs::read_sysfs;
+use crate::domain::nvme::{
+    parse_error_log, parse_smart_log, NvmeController, NvmeHealth, NvmeNamespace,
+};
+use crate::ports::nvme_port::NvmePort;
+
+/// Root of the sysfs hierarchy listing the NVMe controllers.
+const SYSFS_NVME_ROOT: &str = "/sys/class/nvme";
+
+/// The size of the sectors the kernel counts block device sizes in.
+const SECTOR_SIZE: u64 = 512;
+
+/// Represents the sysfs and nvme-cli NVMe adapter.
+pub struct NvmeAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl NvmeAdapter {
+    /// Creates a new instance of `NvmeAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `NvmeAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        NvmeAdapter { logger }
+    }
+
+    /// Reads the namespaces attached to a controller.
+    ///
+    /// Without native multipath a namespace is named after its controller,
+    /// e.g. "nvme0n1"; with it the controller's path is named "nvme0c0n1".
+    fn read_namespaces(path: &Path, controller: &str) -> Vec<NvmeNamespace> {
+        let prefixes = [format!("{}n", controller), format!("{}c", controller)];
+        let mut namespaces: Vec<NvmeNamespace> = fs::read_dir(path)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter_map(|entry| {
+                        let name = entry.file_name().to_string_lossy().to_string();
+                        if !prefixes.iter().any(|prefix| name.starts_with(prefix)) {
+                            return None;
+                        }
+                        let sectors = read_sysfs(entry.path().join("size"))?.parse::<u64>().ok()?;
+                        let block_size = read_sysfs(entry.path().join("queue/logical_block_size"))
+                            .and_then(|size| size.parse().ok())
+                            .unwrap_or(SECTOR_SIZE);
+                        Some(NvmeNamespace {
+                            name,
+                            size_bytes: sectors * SECTOR_SIZE,
+                            block_size,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        namespaces.sort_by(|a, b| a.name.cmp(&b.name));
+        namespaces
+    }
+
+    /// Runs an nvme-cli log page command with JSON output and parses it.
+    fn run_nvme_json(&self, command: &str, controller: &str) -> Result<serde_json::Value, String> {
+        let device = format!("/dev/{}", controller);
+        self.logger
+            .log_debug(&format!("Reading the NVMe {} of {}", command, device));
+        let output = Command::new("nvme")
+            .args([command, device.as_str(), "-o", "json"])
+            .output()
+            .map_err(|e| format!("Failed to run nvme: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "nvme {} {} failed: {}",
+                command,
+                device,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        serde_json::from_slice(&output.stdout)
+            .map_err(|e| format!("Failed to parse nvme {} output: {}", command, e))
+    }
+}
+
+// Implement the `NvmePort` trait for `NvmeAdapter`.
+impl NvmePort for NvmeAdapter {
+    fn discover_nvme_controllers(&self) -> Result<Vec<NvmeController>, String> {
+        let entries = fs::read_dir(SYSFS_NVME_ROOT)
+            .map_err(|e| format!("Failed to read {}: {}", SYSFS_NVME_ROOT, e))?;
+        let mut controllers: Vec<NvmeController> = entries
+            .flatten()
+            .map(|entry| {
+                let path = entry.path();
+                let name = entry.file_name().to_string_lossy().to_string();
+                let attribute = |attribute: &str| {
+                    read_sysfs(path.join(attribute)).unwrap_or_else(|| "unknown".to_string())
+                };
+                NvmeController {
+                    model: attribute("model"),
+                    serial: attribute("serial"),
+                    firmware: attribute("firmware_rev"),
+                    transport: attribute("transport"),
+                    namespaces: Self::read_namespaces(&path, &name),
+                    name,
+                }
+            })
+            .collect();
+        controllers.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(controllers)
+    }
+
+    fn read_nvme_health(&self, controller: &str) -> Result<NvmeHealth, String> {
+        let smart_log = self.run_nvme_json("smart-log", controller)?;
+        let mut health = parse_smart_log(controller, &smart_log);
+        // The error log is only read for its entries; the count comes from the SMART log.
+        match self.run_nvme_json("error-log", controller) {
+            Ok(error_log) => health.errors = parse_error_log(&error_log),
+            Err(e) => self.logger.log_warn(&e),
+        }
+        Ok(health)
+    }
+
+    fn read_all_nvme_health(&self) -> Result<Vec<NvmeHealth>, String> {
+        let controllers = self.discover_nvme_controllers()?;
+        if controllers.is_empty() {
+            return Err("no NVMe controllers found".to_string());
+        }
+
+        let mut health = Vec::new();
+        for controller in controllers {
+            match self.read_nvme_health(&controller.name) {
+                Ok(reading) => health.push(reading),
+                Err(e) => self.logger.log_warn(&format!(
+                    "Failed to read NVMe health of {}: {}",
+                    controller.name, e
+                )),
+            }
+        }
+        if health.is_empty() {
+            return Err("no NVMe controller health could be read".to_string());
+        }
+        Ok(health)
+    }
+}
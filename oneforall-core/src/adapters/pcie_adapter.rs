@@ -0,0 +1,277 @@
+//! PCIe Adapter
+//!
+//! This module provides an adapter that reads the PCIe links of the devices of
+//! the machine from the sysfs attributes of the Linux PCI subsystem, and
+//! measures the bandwidth crossing them: NVMe drives are read directly,
+//! bypassing the page cache, and GPUs are measured with NVIDIA's nvbandwidth.
+
+use std::fs::{self, OpenOptions};
+use std::os::unix::fs::{FileExt, OpenOptionsExt};
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use common::ports::log_port::LoggerPort;
+
+use crate::adapters::sysfs::read_sysfs;
+use crate::domain::pcie::{
+    parse_link_speed, parse_nvbandwidth_matrix, PcieBandwidth, PcieLink, CLASS_NVME,
+};
+use crate::ports::pcie_port::PciePort;
+
+/// Root of the sysfs hierarchy listing the PCI devices.
+const SYSFS_PCI_ROOT: &str = "/sys/bus/pci/devices";
+
+/// The PCI vendor ID of NVIDIA, as written in device IDs.
+const NVIDIA_VENDOR_ID: &str = "10de";
+
+/// How long each NVMe drive is read for.
+const NVME_MEASURE_DURATION: Duration = Duration::from_secs(3);
+
+/// The size of each direct read from an NVMe drive.
+const NVME_READ_SIZE: usize = 1 << 20;
+
+/// The number of reads kept in flight on an NVMe drive, one per thread.
+const NVME_READERS: u64 = 4;
+
+/// The alignment O_DIRECT requires of buffers and offsets.
+const DIRECT_IO_ALIGNMENT: usize = 4096;
+
+/// The nvbandwidth test cases run, with how each measured cell is named.
+const NVBANDWIDTH_TESTS: [(&str, &str); 3] = [
+    ("host_to_device_memcpy_ce", "host to device"),
+    ("device_to_host_memcpy_ce", "device to host"),
+    ("device_to_device_memcpy_read_ce", "peer-to-peer"),
+];
+
+/// Represents the sysfs PCIe adapter.
+pub struct PcieAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl PcieAdapter {
+    /// Creates a new instance of `PcieAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `PcieAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        PcieAdapter { logger }
+    }
+
+    /// Reads the link speed and width attributes of a device or port.
+    fn read_link(device: &Path, prefix: &str) -> (Option<f64>, Option<u32>) {
+        let speed = read_sysfs(device.join(format!("{}_link_speed", prefix)))
+            .and_then(|speed| parse_link_speed(&speed));
+        let width = read_sysfs(device.join(format!("{}_link_width", prefix)))
+            .and_then(|width| width.parse::<u32>().ok())
+            .filter(|width| *width > 0);
+        (speed, width)
+    }
+
+    /// Returns the first entry of a directory whose name passes a filter, in name order.
+    fn first_entry(directory: &Path, filter: impl Fn(&str) -> bool) -> Option<String> {
+        let mut names: Vec<String> = fs::read_dir(directory)
+            .ok()?
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|name| filter(name))
+            .collect();
+        names.sort();
+        names.into_iter().next()
+    }
+
+    /// Finds the kernel name of a device: the namespace of an NVMe controller,
+    /// the interface of a network card or the DRM card of a GPU.
+    fn kernel_name(device: &Path, class: u32) -> Option<String> {
+        if class == CLASS_NVME {
+            let controller = Self::first_entry(&device.join("nvme"), |_| true)?;
+            let prefix = format!("{}n", controller);
+            return Self::first_entry(&device.join("nvme").join(&controller), |name| {
+                name.starts_with(&prefix)
+            })
+            .or(Some(controller));
+        }
+        Self::first_entry(&device.join("net"), |_| true)
+            .or_else(|| Self::first_entry(&device.join("drm"), |name| name.starts_with("card")))
+    }
+
+    /// Reads one device, or returns `None` for bridges and devices without a PCIe link.
+    fn read_device(path: &Path) -> Option<PcieLink> {
+        let class = read_sysfs(path.join("class"))
+            .and_then(|class| u32::from_str_radix(class.trim_start_matches("0x"), 16).ok())?;
+        if class >> 16 == 0x06 {
+            return None;
+        }
+        let (current_speed_gts, current_width) = Self::read_link(path, "current");
+        let (max_speed_gts, max_width) = Self::read_link(path, "max");
+        if current_speed_gts.is_none() && max_speed_gts.is_none() {
+            return None;
+        }
+
+        // The port the device is plugged into is its parent in the device tree.
+        let (slot_speed_gts, slot_width) = fs::canonicalize(path)
+            .ok()
+            .and_then(|path| path.parent().map(Path::to_path_buf))
+            .map_or((None, None), |port| Self::read_link(&port, "max"));
+        let id_part = |attribute: &str| {
+            read_sysfs(path.join(attribute))
+                .map(|id| id.trim_start_matches("0x").to_string())
+                .unwrap_or_else(|| "????".to_string())
+        };
+
+        Some(PcieLink {
+            address: path.file_name()?.to_string_lossy().to_string(),
+            class,
+            id: format!("{}:{}", id_part("vendor"), id_part("device")),
+            name: Self::kernel_name(path, class),
+            current_speed_gts,
+            current_width,
+            max_speed_gts,
+            max_width,
+            slot_speed_gts,
+            slot_width,
+        })
+    }
+}
+
+// Implement the `PciePort` trait for `PcieAdapter`.
+impl PciePort for PcieAdapter {
+    fn discover_pcie_links(&self) -> Result<Vec<PcieLink>, String> {
+        let entries = fs::read_dir(SYSFS_PCI_ROOT)
+            .map_err(|e| format!("Failed to read {}: {}", SYSFS_PCI_ROOT, e))?;
+        let mut links: Vec<PcieLink> = entries
+            .flatten()
+            .filter_map(|entry| Self::read_device(&entry.path()))
+            .collect();
+        links.sort_by(|a, b| a.address.cmp(&b.address));
+
+        for link in &links {
+            for degradation in link.degradations() {
+                self.logger.log_warn(&format!(
+                    "PCIe link of {} at {} is degraded: {}",
+                    link.describe(),
+                    link.address,
+                    degradation
+                ));
+            }
+        }
+        Ok(links)
+    }
+
+    fn measure_nvme_bandwidth(&self, link: &PcieLink) -> Result<PcieBandwidth, String> {
+        let name = link
+            .name
+            .as_deref()
+            .filter(|name| name.trim_start_matches("nvme").contains('n'))
+            .ok_or_else(|| format!("{} has no NVMe namespace", link.address))?;
+        let sectors = read_sysfs(Path::new("/sys/block").join(name).join("size"))
+            .and_then(|size| size.parse::<u64>().ok())
+            .ok_or_else(|| format!("Failed to read the size of {}", name))?;
+        let chunks = sectors * 512 / NVME_READ_SIZE as u64;
+        if chunks == 0 {
+            return Err(format!("{} is too small to measure", name));
+        }
+        let device = format!("/dev/{}", name);
+        let file = Arc::new(
+            OpenOptions::new()
+                .read(true)
+                .custom_flags(libc::O_DIRECT)
+                .open(&device)
+                .map_err(|e| format!("Failed to open {}: {}", device, e))?,
+        );
+
+        // Each reader takes every NVME_READERS-th chunk, so the reads stay sequential
+        // overall while several are in flight at once.
+        let started = Instant::now();
+        let readers: Vec<_> = (0..NVME_READERS)
+            .map(|reader| {
+                let file = file.clone();
+                thread::spawn(move || -> Result<u64, String> {
+                    let mut buffer = vec![0u8; NVME_READ_SIZE + DIRECT_IO_ALIGNMENT];
+                    let offset = buffer.as_ptr().align_offset(DIRECT_IO_ALIGNMENT);
+                    let aligned = &mut buffer[offset..offset + NVME_READ_SIZE];
+                    let mut read = 0;
+                    let mut chunk = reader;
+                    while chunk < chunks && started.elapsed() < NVME_MEASURE_DURATION {
+                        file.read_exact_at(aligned, chunk * NVME_READ_SIZE as u64)
+                            .map_err(|e| e.to_string())?;
+                        read += NVME_READ_SIZE as u64;
+                        chunk += NVME_READERS;
+                    }
+                    Ok(read)
+                })
+            })
+            .collect();
+        let mut total = 0;
+        for reader in readers {
+            total += reader
+                .join()
+                .map_err(|_| "an NVMe reader panicked".to_string())?
+                .map_err(|e| format!("Failed to read {}: {}", device, e))?;
+        }
+        let elapsed = started.elapsed().as_secs_f64();
+
+        Ok(PcieBandwidth {
+            target: format!("{} read", name),
+            measured_gb_s: total as f64 / elapsed / 1e9,
+            link_gb_s: link.current_bandwidth_gb_s(),
+        })
+    }
+
+    fn measure_gpu_bandwidth(&self, links: &[PcieLink]) -> Result<Vec<PcieBandwidth>, String> {
+        // nvbandwidth numbers the GPUs in PCI address order, as the links are sorted.
+        let gpus: Vec<&PcieLink> = links
+            .iter()
+            .filter(|link| link.is_gpu() && link.id.starts_with(NVIDIA_VENDOR_ID))
+            .collect();
+        if gpus.is_empty() {
+            return Ok(Vec::new());
+        }
+        let gpu_link = |index: u32| {
+            gpus.get(index as usize)
+                .and_then(|link| link.current_bandwidth_gb_s())
+        };
+
+        let mut bandwidths = Vec::new();
+        for (test, label) in NVBANDWIDTH_TESTS {
+            let output = Command::new("nvbandwidth")
+                .args(["-t", test])
+                .env("CUDA_DEVICE_ORDER", "PCI_BUS_ID")
+                .output()
+                .map_err(|e| format!("Failed to run nvbandwidth: {}", e))?;
+            if !output.status.success() {
+                self.logger.log_warn(&format!(
+                    "nvbandwidth {} failed: {}",
+                    test,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+                continue;
+            }
+            for (row, column, measured_gb_s) in parse_nvbandwidth_matrix(
+                &String::from_utf8_lossy(&output.stdout),
+                "bandwidth (GB/s)",
+            ) {
+                let (target, link_gb_s) = if test.starts_with("device_to_device") {
+                    // Peer-to-peer traffic crosses the links of both GPUs.
+                    (
+                        format!("GPU {} to GPU {} {}", column, row, label),
+                        gpu_link(row).zip(gpu_link(column)).map(|(a, b)| a.min(b)),
+                    )
+                } else {
+                    (format!("GPU {} {}", column, label), gpu_link(column))
+                };
+                bandwidths.push(PcieBandwidth {
+                    target,
+                    measured_gb_s,
+                    link_gb_s,
+                });
+            }
+        }
+        Ok(bandwidths)
+    }
+}
@@ -0,0 +1,253 @@
+//! Perf Counter Adapter
+//!
+//! This module counts hardware events on every online CPU with the Linux
+//! `perf_event_open` system call, the interface `perf stat` uses. Each event
+//! is counted by the PMU of the CPU itself and read once when the run ends,
+//! so counting costs nothing while the benchmark runs.
+//!
+//! Counting every process needs root, `CAP_PERFMON` or a
+//! `kernel.perf_event_paranoid` of 0 or less; otherwise the counters fall
+//! back to OneForAll and the processes it starts, in user space only. When
+//! more events are counted than the PMU has counters for, the kernel
+//! multiplexes them and the counts are scaled up to the whole run. Virtual
+//! machines often expose no PMU at all, in which case no counter opens.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::sync::{Arc, Mutex};
+
+use common::ports::log_port::LoggerPort;
+
+use crate::domain::perf_counters::{CoreCounters, PerfEvent};
+use crate::ports::perf_counter_port::PerfCounterPort;
+
+/// The sysfs file listing the online CPUs.
+#[cfg(target_os = "linux")]
+const SYSFS_CPU_ONLINE: &str = "/sys/devices/system/cpu/online";
+
+/// `perf_event_attr` as of `PERF_ATTR_SIZE_VER5`, which every kernel from 4.1
+/// accepts; later fields are left to their defaults.
+#[cfg(target_os = "linux")]
+#[repr(C)]
+#[derive(Default)]
+struct PerfEventAttr {
+    kind: u32,
+    size: u32,
+    config: u64,
+    sample_period: u64,
+    sample_type: u64,
+    read_format: u64,
+    flags: u64,
+    wakeup_events: u32,
+    bp_type: u32,
+    config1: u64,
+    config2: u64,
+    branch_sample_type: u64,
+    sample_regs_user: u64,
+    sample_stack_user: u32,
+    clockid: i32,
+    sample_regs_intr: u64,
+    aux_watermark: u32,
+    sample_max_stack: u16,
+    reserved: u16,
+}
+
+/// `PERF_TYPE_HARDWARE`, the generalized hardware events.
+#[cfg(target_os = "linux")]
+const PERF_TYPE_HARDWARE: u32 = 0;
+
+/// `PERF_FORMAT_TOTAL_TIME_ENABLED | PERF_FORMAT_TOTAL_TIME_RUNNING`, so a read
+/// returns how long the event was counted along with its count.
+#[cfg(target_os = "linux")]
+const READ_FORMAT_TIMES: u64 = 1 | 2;
+
+/// The `inherit` flag, counting the children started after the counter opened.
+#[cfg(target_os = "linux")]
+const FLAG_INHERIT: u64 = 1 << 1;
+
+/// The `exclude_kernel` flag, counting user space only.
+#[cfg(target_os = "linux")]
+const FLAG_EXCLUDE_KERNEL: u64 = 1 << 5;
+
+/// The `exclude_hv` flag, leaving out the hypervisor.
+#[cfg(target_os = "linux")]
+const FLAG_EXCLUDE_HV: u64 = 1 << 6;
+
+/// `PERF_FLAG_FD_CLOEXEC`, so the processes OneForAll starts do not inherit the counters.
+#[cfg(target_os = "linux")]
+const PERF_FLAG_FD_CLOEXEC: libc::c_ulong = 8;
+
+/// Opens a counter of an event on a CPU.
+///
+/// With `system_wide` every process on the CPU is counted, otherwise only
+/// this process and its future children, in user space.
+#[cfg(target_os = "linux")]
+fn open_counter(event: PerfEvent, cpu: u32, system_wide: bool) -> io::Result<File> {
+    use std::os::fd::FromRawFd;
+
+    let mut attr = PerfEventAttr {
+        kind: PERF_TYPE_HARDWARE,
+        size: std::mem::size_of::<PerfEventAttr>() as u32,
+        config: event.config(),
+        read_format: READ_FORMAT_TIMES,
+        flags: FLAG_EXCLUDE_HV,
+        ..PerfEventAttr::default()
+    };
+    let pid: libc::pid_t = if system_wide { -1 } else { 0 };
+    if !system_wide {
+        attr.flags |= FLAG_INHERIT | FLAG_EXCLUDE_KERNEL;
+    }
+    // SAFETY: `attr` is a valid `perf_event_attr` whose size is passed in its
+    // `size` field, and it outlives the call.
+    let fd = unsafe {
+        libc::syscall(
+            libc::SYS_perf_event_open,
+            &attr as *const PerfEventAttr,
+            pid,
+            cpu as libc::c_int,
+            -1 as libc::c_int,
+            PERF_FLAG_FD_CLOEXEC,
+        )
+    };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: the kernel returned a new file descriptor nothing else owns.
+    Ok(unsafe { File::from_raw_fd(fd as libc::c_int) })
+}
+
+/// Reports that hardware counters are only read on Linux.
+#[cfg(not(target_os = "linux"))]
+fn open_counter(_event: PerfEvent, _cpu: u32, _system_wide: bool) -> io::Result<File> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "hardware performance counters are only read on Linux",
+    ))
+}
+
+/// Lists the online CPUs.
+#[cfg(target_os = "linux")]
+fn online_cpus() -> Result<Vec<u32>, String> {
+    let online = std::fs::read_to_string(SYSFS_CPU_ONLINE)
+        .map_err(|e| format!("Failed to read {}: {}", SYSFS_CPU_ONLINE, e))?;
+    Ok(crate::domain::cpu_topology::parse_cpu_list(online.trim())?
+        .into_iter()
+        .collect())
+}
+
+/// Reports that hardware counters are only read on Linux.
+#[cfg(not(target_os = "linux"))]
+fn online_cpus() -> Result<Vec<u32>, String> {
+    Err("Hardware performance counters are only read on Linux".to_string())
+}
+
+/// Reads a counter, scaled up to the whole run if it shared the PMU with
+/// other events, or `None` if it was never scheduled on the PMU.
+fn read_counter(file: &mut File) -> Option<u64> {
+    let mut buffer = [0u8; 24];
+    file.read_exact(&mut buffer).ok()?;
+    let field = |index: usize| {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&buffer[index * 8..index * 8 + 8]);
+        u64::from_ne_bytes(bytes)
+    };
+    let (count, enabled, running) = (field(0), field(1), field(2));
+    if running == 0 {
+        return None;
+    }
+    Some((count as f64 * enabled as f64 / running as f64).round() as u64)
+}
+
+/// An open counter of one event on one CPU.
+struct Counter {
+    cpu: u32,
+    event: PerfEvent,
+    file: File,
+}
+
+/// Represents the perf_event hardware counter adapter.
+pub struct PerfCounterAdapter {
+    logger: Arc<dyn LoggerPort>,           // inject the logger port
+    counters: Mutex<Option<Vec<Counter>>>, // the counters opened by `start`
+}
+
+impl PerfCounterAdapter {
+    /// Creates a new instance of `PerfCounterAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `PerfCounterAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        PerfCounterAdapter {
+            logger,
+            counters: Mutex::new(None),
+        }
+    }
+
+    /// Opens a counter of every event on every CPU, skipping the events a CPU
+    /// does not count.
+    fn open_counters(cpus: &[u32], system_wide: bool) -> io::Result<Vec<Counter>> {
+        let mut counters = Vec::new();
+        let mut last_error = None;
+        for &cpu in cpus {
+            for event in PerfEvent::ALL {
+                match open_counter(event, cpu, system_wide) {
+                    Ok(file) => counters.push(Counter { cpu, event, file }),
+                    Err(e) => last_error = Some(e),
+                }
+            }
+        }
+        match last_error {
+            Some(e) if counters.is_empty() => Err(e),
+            _ => Ok(counters),
+        }
+    }
+}
+
+// Implement the `PerfCounterPort` trait for `PerfCounterAdapter`.
+impl PerfCounterPort for PerfCounterAdapter {
+    fn start(&self) -> Result<(), String> {
+        let cpus = online_cpus()?;
+        let counters = match Self::open_counters(&cpus, true) {
+            Ok(counters) => counters,
+            Err(e) if matches!(e.kind(), io::ErrorKind::PermissionDenied) => {
+                self.logger.log_debug(&format!(
+                    "Counting every process needs CAP_PERFMON ({}); counting OneForAll and its children only",
+                    e
+                ));
+                Self::open_counters(&cpus, false)
+                    .map_err(|e| format!("Failed to open the hardware counters: {}", e))?
+            }
+            Err(e) => return Err(format!("Failed to open the hardware counters: {}", e)),
+        };
+        self.logger.log_debug(&format!(
+            "Counting {} hardware events across {} CPUs",
+            counters.len(),
+            cpus.len()
+        ));
+        *self.counters.lock().map_err(|e| e.to_string())? = Some(counters);
+        Ok(())
+    }
+
+    fn finish(&self) -> Result<Vec<CoreCounters>, String> {
+        let counters = self
+            .counters
+            .lock()
+            .map_err(|e| e.to_string())?
+            .take()
+            .ok_or("The hardware counters were not opened")?;
+        let mut cores: Vec<CoreCounters> = Vec::new();
+        // Dropping each counter once read closes it.
+        for mut counter in counters {
+            if cores.last().map(|core| core.cpu) != Some(counter.cpu) {
+                cores.push(CoreCounters::new(counter.cpu));
+            }
+            if let (Some(core), Some(count)) = (cores.last_mut(), read_counter(&mut counter.file)) {
+                core.set(counter.event, count);
+            }
+        }
+        Ok(cores)
+    }
+}
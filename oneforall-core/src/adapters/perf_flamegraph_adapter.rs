@@ -0,0 +1,177 @@
+//! Perf Flamegraph Adapter
+//!
+//! This module samples stacks with `perf record` for the duration of a run and
+//! draws them as a flamegraph SVG once it ends. perf samples every CPU at
+//! `SAMPLE_FREQUENCY_HZ` with call graphs when OneForAll runs as root, and
+//! OneForAll with the threads and processes it starts otherwise, so the
+//! workload of a plugin shows up either way. The samples are symbolized with
+//! `perf script` and folded and drawn by the flamegraph domain module, so
+//! neither Perl nor the FlameGraph scripts need to be installed.
+//!
+//! perf is packaged as `linux-tools` or `linux-perf` on Debian and Ubuntu and
+//! as `perf` on Fedora and RHEL; it only exists on Linux.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+use common::ports::log_port::LoggerPort;
+
+use crate::domain::flamegraph::{fold_perf_script, render_svg};
+use crate::ports::flamegraph_port::FlamegraphPort;
+
+/// How long perf is given to attach before sampling is considered started.
+#[cfg(target_os = "linux")]
+const ATTACH_TIME: std::time::Duration = std::time::Duration::from_millis(300);
+
+/// A `perf record` process and the file it records to.
+struct Recording {
+    child: Child,
+    data: PathBuf,
+}
+
+impl Recording {
+    /// Stops perf with SIGINT, as Ctrl-C would, so it writes out its samples.
+    fn stop(&mut self) -> Result<(), String> {
+        // SAFETY: the signal only goes to the perf process spawned by `start`.
+        unsafe { libc::kill(self.child.id() as libc::pid_t, libc::SIGINT) };
+        self.child
+            .wait()
+            .map(|_| ())
+            .map_err(|e| format!("Failed to wait for perf to stop: {}", e))
+    }
+}
+
+/// Represents the perf flamegraph adapter.
+pub struct PerfFlamegraphAdapter {
+    logger: Arc<dyn LoggerPort>,         // inject the logger port
+    recording: Mutex<Option<Recording>>, // the perf process started by `start`
+}
+
+impl PerfFlamegraphAdapter {
+    /// Creates a new instance of `PerfFlamegraphAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `PerfFlamegraphAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        PerfFlamegraphAdapter {
+            logger,
+            recording: Mutex::new(None),
+        }
+    }
+
+    /// Returns whether every CPU can be sampled, which needs root.
+    #[cfg(target_os = "linux")]
+    fn system_wide() -> bool {
+        // SAFETY: `geteuid` only reads the effective user ID of the process.
+        unsafe { libc::geteuid() == 0 }
+    }
+
+    /// Symbolizes the samples perf recorded.
+    fn read_script(data: &Path) -> Result<String, String> {
+        let output = Command::new("perf")
+            .arg("script")
+            .arg("-i")
+            .arg(data)
+            .stderr(Stdio::null())
+            .output()
+            .map_err(|e| format!("Failed to run perf script: {}", e))?;
+        if !output.status.success() {
+            return Err(format!("perf script failed with {}", output.status));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+// Implement the `FlamegraphPort` trait for `PerfFlamegraphAdapter`.
+impl FlamegraphPort for PerfFlamegraphAdapter {
+    #[cfg(target_os = "linux")]
+    fn start(&self) -> Result<(), String> {
+        use crate::domain::flamegraph::SAMPLE_FREQUENCY_HZ;
+
+        let data = std::env::temp_dir().join(format!("oneforall-{}.perf.data", std::process::id()));
+        let mut command = Command::new("perf");
+        command
+            .arg("record")
+            .arg("--freq")
+            .arg(SAMPLE_FREQUENCY_HZ.to_string())
+            .arg("--call-graph")
+            .arg("fp")
+            .arg("--output")
+            .arg(&data)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        if Self::system_wide() {
+            command.arg("--all-cpus");
+        } else {
+            command.arg("--pid").arg(std::process::id().to_string());
+        }
+        let mut child = command.spawn().map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                "perf is not installed; install linux-tools or the perf package".to_string()
+            }
+            _ => format!("Failed to start perf record: {}", e),
+        })?;
+
+        // perf exits at once when it may not sample, e.g. under a strict perf_event_paranoid.
+        std::thread::sleep(ATTACH_TIME);
+        if let Ok(Some(status)) = child.try_wait() {
+            let _ = fs::remove_file(&data);
+            return Err(format!(
+                "perf record exited with {}; sampling needs root or a kernel.perf_event_paranoid of 1 or less",
+                status
+            ));
+        }
+        *self.recording.lock().map_err(|e| e.to_string())? = Some(Recording { child, data });
+        self.logger.log_info(&format!(
+            "Sampling stacks at {} Hz with perf for a flamegraph of the run.",
+            SAMPLE_FREQUENCY_HZ
+        ));
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn start(&self) -> Result<(), String> {
+        Err("Flamegraphs are sampled with perf, which only exists on Linux".to_string())
+    }
+
+    fn finish(&self, title: &str, output: &Path) -> Result<u64, String> {
+        let mut recording = self
+            .recording
+            .lock()
+            .map_err(|e| e.to_string())?
+            .take()
+            .ok_or("Stack sampling was not started")?;
+        let script = recording
+            .stop()
+            .and_then(|()| Self::read_script(&recording.data));
+        let _ = fs::remove_file(&recording.data);
+        let folded = fold_perf_script(&script?);
+        let samples: u64 = folded.values().sum();
+        if samples == 0 {
+            return Err("perf took no stack samples".to_string());
+        }
+        fs::write(output, render_svg(&folded, title))
+            .map_err(|e| format!("Failed to write {}: {}", output.display(), e))?;
+        self.logger.log_debug(&format!(
+            "Drew {} stack samples into {}",
+            samples,
+            output.display()
+        ));
+        Ok(samples)
+    }
+}
+
+impl Drop for PerfFlamegraphAdapter {
+    // A run that ends early still stops perf and removes its samples.
+    fn drop(&mut self) {
+        if let Some(mut recording) = self.recording.get_mut().ok().and_then(Option::take) {
+            let _ = recording.stop();
+            let _ = fs::remove_file(&recording.data);
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! Plugin Registry
+//!
+//! This module keeps the benchmark and stress test plugins available to run:
+//! those registered at compile time by a service embedding `oneforall-core`,
+//! and those loaded from the shared libraries listed in the `[plugins]`
+//! section of `oneforall.toml`, or found in the directories it lists.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use common::ports::log_port::LoggerPort;
+
+use crate::adapters::dynamic_plugin_adapter::DynamicPluginAdapter;
+use crate::domain::plugin::{validate_plugin_name, PluginConfig};
+use crate::ports::benchmark_plugin_port::BenchmarkPluginPort;
+
+/// The file extensions of the shared libraries loaded from a plugin directory.
+const LIBRARY_EXTENSIONS: [&str; 2] = ["so", "dylib"];
+
+/// Reads the `[plugins]` section of the configuration file.
+///
+/// # Arguments
+///
+/// * `path` - The configuration file; a missing file means no plugins.
+///
+/// # Returns
+///
+/// * `Result<PluginConfig, String>` - The plugin settings, or why they could not be read.
+pub fn load_plugin_config(path: &Path) -> Result<PluginConfig, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(PluginConfig::default()),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    PluginConfig::from_toml(&text).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+}
+
+/// Lists the shared libraries to load: each listed file, and the libraries
+/// in each listed directory in name order.
+fn library_paths(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut libraries = Vec::new();
+    for path in paths {
+        let Ok(entries) = fs::read_dir(path) else {
+            libraries.push(path.clone());
+            continue;
+        };
+        let mut found: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|library| {
+                library
+                    .extension()
+                    .and_then(|extension| extension.to_str())
+                    .is_some_and(|extension| LIBRARY_EXTENSIONS.contains(&extension))
+            })
+            .collect();
+        found.sort();
+        libraries.extend(found);
+    }
+    libraries
+}
+
+/// Keeps the plugins available to run, by name.
+pub struct PluginRegistry {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    plugins: Vec<Arc<dyn BenchmarkPluginPort>>,
+}
+
+impl PluginRegistry {
+    /// Creates a registry without plugins.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger` - Logger implementation for logging messages.
+    ///
+    /// # Returns
+    ///
+    /// * `PluginRegistry` - The registry.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        PluginRegistry {
+            logger,
+            plugins: Vec::new(),
+        }
+    }
+
+    /// Adds a plugin, e.g. one compiled into the service embedding `oneforall-core`.
+    ///
+    /// # Arguments
+    ///
+    /// * `plugin` - The plugin.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), String>` - Whether it was added; names must be valid and unique.
+    pub fn register(&mut self, plugin: Arc<dyn BenchmarkPluginPort>) -> Result<(), String> {
+        validate_plugin_name(plugin.name())?;
+        if self.get(plugin.name()).is_some() {
+            return Err(format!(
+                "A plugin named '{}' is already registered",
+                plugin.name()
+            ));
+        }
+        self.plugins.push(plugin);
+        Ok(())
+    }
+
+    /// Loads the plugins of the `[plugins]` section; a plugin that fails to
+    /// load is skipped with a warning, so it cannot keep the others from running.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The plugin settings.
+    pub fn load(&mut self, config: &PluginConfig) {
+        for path in library_paths(&config.paths) {
+            let loaded = DynamicPluginAdapter::load(self.logger.clone(), &path)
+                .and_then(|plugin| self.register(Arc::new(plugin)));
+            match loaded {
+                Ok(()) => self
+                    .logger
+                    .log_debug(&format!("Loaded plugin from {}", path.display())),
+                Err(e) => {
+                    self.logger
+                        .log_warn(&format!("Skipping plugin {}: {}", path.display(), e))
+                }
+            }
+        }
+    }
+
+    /// Returns the plugin of a name, if one is registered.
+    pub fn get(&self, name: &str) -> Option<Arc<dyn BenchmarkPluginPort>> {
+        self.plugins
+            .iter()
+            .find(|plugin| plugin.name() == name)
+            .cloned()
+    }
+
+    /// Returns every registered plugin, in the order they were registered.
+    pub fn plugins(&self) -> &[Arc<dyn BenchmarkPluginPort>] {
+        &self.plugins
+    }
+
+    /// Renders the registered plugins as a table for the terminal.
+    pub fn render(&self) -> String {
+        if self.plugins.is_empty() {
+            return "No plugins are loaded; list them under [plugins] paths.\n".to_string();
+        }
+        let width = self
+            .plugins
+            .iter()
+            .map(|plugin| plugin.name().len())
+            .max()
+            .unwrap_or(0)
+            .max("Plugin".len());
+        let mut output = format!("{:<width$}  {:<24}  Description\n", "Plugin", "Resources");
+        for plugin in &self.plugins {
+            let resources: Vec<String> = plugin
+                .resources()
+                .iter()
+                .map(|resource| format!("{:?}", resource).to_lowercase())
+                .collect();
+            output.push_str(&format!(
+                "{:<width$}  {:<24}  {}\n",
+                plugin.name(),
+                resources.join(", "),
+                plugin.description()
+            ));
+        }
+        output
+    }
+}
@@ -0,0 +1,236 @@
+//! Power Adapter
+//!
+//! This module provides an adapter that reads the cumulative CPU energy
+//! counters from the Linux powercap (Intel RAPL) and `amd_energy` hwmon
+//! interfaces, and the whole-system power reading of the BMC through
+//! `ipmitool dcmi power reading`.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+use std::time::Instant;
+
+use common::domain::telemetry::PowerSample;
+use common::ports::log_port::LoggerPort;
+
+use crate::adapters::sysfs::read_sysfs;
+use crate::domain::power::{power_between, EnergyCounter};
+use crate::ports::power_port::PowerPort;
+
+/// Root of the powercap hierarchy exposing the Intel RAPL zones.
+const SYSFS_POWERCAP_ROOT: &str = "/sys/class/powercap";
+
+/// Root of the hwmon hierarchy exposing the AMD energy counters.
+const SYSFS_HWMON_ROOT: &str = "/sys/class/hwmon";
+
+/// Represents the RAPL/AMD energy/IPMI power adapter.
+pub struct PowerAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl PowerAdapter {
+    /// Creates a new instance of `PowerAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `PowerAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        PowerAdapter { logger }
+    }
+
+    /// Reads the energy counter of a single RAPL zone directory.
+    fn read_rapl_zone(zone: &Path, domain: String) -> Option<EnergyCounter> {
+        Some(EnergyCounter {
+            domain,
+            energy_uj: read_sysfs(zone.join("energy_uj"))?.parse().ok()?,
+            max_energy_range_uj: read_sysfs(zone.join("max_energy_range_uj"))
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+        })
+    }
+
+    /// Reads every Intel RAPL zone and sub-zone, e.g. "package-0" and "package-0/dram".
+    fn read_rapl_counters() -> Vec<EnergyCounter> {
+        let mut counters = Vec::new();
+        let entries = match fs::read_dir(SYSFS_POWERCAP_ROOT) {
+            Ok(entries) => entries,
+            Err(_) => return counters,
+        };
+
+        for entry in entries.flatten() {
+            let zone_id = entry.file_name().to_string_lossy().to_string();
+            // Top-level zones are named "intel-rapl:N"; sub-zones "intel-rapl:N:M".
+            if !zone_id.starts_with("intel-rapl:") || zone_id.matches(':').count() != 1 {
+                continue;
+            }
+            let zone = entry.path();
+            let name = match read_sysfs(zone.join("name")) {
+                Some(name) => name,
+                None => continue,
+            };
+            counters.extend(Self::read_rapl_zone(&zone, name.clone()));
+
+            let subzone_prefix = format!("{}:", zone_id);
+            for sub in fs::read_dir(&zone).into_iter().flatten().flatten() {
+                if !sub
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with(&subzone_prefix)
+                {
+                    continue;
+                }
+                if let Some(sub_name) = read_sysfs(sub.path().join("name")) {
+                    let domain = format!("{}/{}", name, sub_name);
+                    counters.extend(Self::read_rapl_zone(&sub.path(), domain));
+                }
+            }
+        }
+
+        counters.sort_by(|a, b| a.domain.cmp(&b.domain));
+        counters
+    }
+
+    /// Reads the per-socket counters of the `amd_energy` hwmon driver.
+    ///
+    /// Per-core counters are skipped to keep the report readable on large parts.
+    fn read_amd_energy_counters() -> Vec<EnergyCounter> {
+        let mut counters = Vec::new();
+        let entries = match fs::read_dir(SYSFS_HWMON_ROOT) {
+            Ok(entries) => entries,
+            Err(_) => return counters,
+        };
+
+        for entry in entries.flatten() {
+            let hwmon = entry.path();
+            if read_sysfs(hwmon.join("name")).as_deref() != Some("amd_energy") {
+                continue;
+            }
+            for index in 1.. {
+                let label = match read_sysfs(hwmon.join(format!("energy{}_label", index))) {
+                    Some(label) => label,
+                    None => break,
+                };
+                let socket = match label.strip_prefix("Esocket") {
+                    Some(socket) => socket.to_string(),
+                    None => continue,
+                };
+                let energy = read_sysfs(hwmon.join(format!("energy{}_input", index)))
+                    .and_then(|v| v.parse().ok());
+                if let Some(energy_uj) = energy {
+                    counters.push(EnergyCounter {
+                        domain: format!("package-{}", socket),
+                        energy_uj,
+                        // The driver accumulates into 64-bit counters that do not wrap.
+                        max_energy_range_uj: 0,
+                    });
+                }
+            }
+        }
+
+        counters
+    }
+
+    /// Extracts the wattage from the output of `ipmitool dcmi power reading`.
+    fn parse_dcmi_power_reading(output: &str) -> Option<f64> {
+        output
+            .lines()
+            .find(|line| line.trim_start().starts_with("Instantaneous power reading"))
+            .and_then(|line| line.split(':').nth(1))
+            .and_then(|value| value.split_whitespace().next())
+            .and_then(|watts| watts.parse().ok())
+    }
+}
+
+// Implement the `PowerPort` trait for `PowerAdapter`.
+impl PowerPort for PowerAdapter {
+    fn read_energy_counters(&self) -> Result<Vec<EnergyCounter>, String> {
+        let mut counters = Self::read_rapl_counters();
+        if counters.is_empty() {
+            counters = Self::read_amd_energy_counters();
+        }
+
+        if counters.is_empty() {
+            self.logger
+                .log_trace("Neither RAPL nor amd_energy counters are readable on this system");
+            return Err("CPU energy counters are not available on this platform".to_string());
+        }
+        Ok(counters)
+    }
+
+    fn read_bmc_power_watts(&self) -> Result<f64, String> {
+        let output = Command::new("ipmitool")
+            .args(["dcmi", "power", "reading"])
+            .output()
+            .map_err(|e| format!("Failed to run ipmitool: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "ipmitool dcmi power reading failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        Self::parse_dcmi_power_reading(&String::from_utf8_lossy(&output.stdout)).ok_or_else(|| {
+            let message = "Unexpected output from ipmitool dcmi power reading".to_string();
+            self.logger.log_error(&message);
+            message
+        })
+    }
+}
+
+/// Turns successive power readings into `PowerSample`s.
+///
+/// The CPU counters are cumulative, so the first call only primes the meter.
+/// A BMC that fails to answer once is not queried again, as every `ipmitool`
+/// invocation against a missing BMC blocks until it times out.
+pub struct PowerMeter {
+    port: Box<dyn PowerPort>,
+    previous: Option<(Instant, Vec<EnergyCounter>)>,
+    bmc_available: bool,
+}
+
+impl PowerMeter {
+    /// Creates a new power meter reading from the given port.
+    pub fn new(port: Box<dyn PowerPort>) -> Self {
+        PowerMeter {
+            port,
+            previous: None,
+            bmc_available: true,
+        }
+    }
+
+    /// Takes one reading and returns the power drawn since the previous one.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<PowerSample>` - The sample, or `None` if no power source reported yet.
+    pub fn sample(&mut self) -> Option<PowerSample> {
+        let now = Instant::now();
+        let mut domains = Vec::new();
+        if let Ok(counters) = self.port.read_energy_counters() {
+            if let Some((then, before)) = &self.previous {
+                domains = power_between(before, &counters, now.duration_since(*then));
+            }
+            self.previous = Some((now, counters));
+        }
+
+        let bmc_watts = if self.bmc_available {
+            let reading = self.port.read_bmc_power_watts().ok();
+            self.bmc_available = reading.is_some();
+            reading
+        } else {
+            None
+        };
+
+        if domains.is_empty() && bmc_watts.is_none() {
+            return None;
+        }
+        Some(PowerSample {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            domains,
+            bmc_watts,
+        })
+    }
+}
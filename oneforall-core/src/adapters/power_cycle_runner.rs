@@ -0,0 +1,189 @@
+//! Power Cycle Runner
+//!
+//! This module runs the power-cycle endurance test started by `oneforall
+//! power-cycle` on a controller machine. Each cycle has the BMC of the
+//! machine under test power cycle it, then follows the readiness probe of the
+//! OneForAll agent on it as its heartbeat: the agent must stop answering,
+//! showing the machine lost power, and pass the probe again within the limit,
+//! showing the machine booted and the agent came up with it. A cycle the BMC
+//! refuses or the agent does not come back from is recorded and the test goes
+//! on, since the next power cycle often brings a hung machine back.
+
+use std::sync::Arc;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use common::ports::log_port::LoggerPort;
+use tokio_util::sync::CancellationToken;
+
+use crate::adapters::hook_adapter::HookAdapter;
+use crate::adapters::jobs_client_adapter::JobsClientAdapter;
+use crate::domain::power_cycle::{BmcTarget, CycleOutcome, CycleRecord, PowerCycleReport};
+
+/// How often the readiness probe of the agent is polled.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long the agent may keep answering after a power cycle before the
+/// machine is taken as never having lost power.
+const DOWN_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Runs power-cycle endurance tests against another machine.
+pub struct PowerCycleRunner {
+    logger: Arc<dyn LoggerPort>,
+    agent: JobsClientAdapter,
+    hooks: HookAdapter,
+    bmc: BmcTarget,
+    agent_url: String,
+    cancel: CancellationToken,
+}
+
+impl PowerCycleRunner {
+    /// Creates a new instance of `PowerCycleRunner`.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger` - Logger implementation for logging messages.
+    /// * `agent` - The client of the agent on the machine under test.
+    /// * `agent_url` - The base URL of that agent, for the report.
+    /// * `bmc` - The BMC of the machine under test.
+    ///
+    /// # Returns
+    ///
+    /// * `PowerCycleRunner` - A runner power cycling the machine through its BMC.
+    pub fn new(
+        logger: Arc<dyn LoggerPort>,
+        agent: JobsClientAdapter,
+        agent_url: &str,
+        bmc: BmcTarget,
+    ) -> Self {
+        PowerCycleRunner {
+            hooks: HookAdapter::new(logger.clone()),
+            logger,
+            agent,
+            bmc,
+            agent_url: agent_url.to_string(),
+            cancel: CancellationToken::new(),
+        }
+    }
+
+    /// Stops the test at the next heartbeat; the cycle under way is dropped.
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+
+    /// Power cycles the machine a number of times, following the agent on it after each.
+    ///
+    /// # Arguments
+    ///
+    /// * `cycles` - The number of power cycles.
+    /// * `ready_timeout` - How long the agent may take to be ready after each power cycle.
+    ///
+    /// # Returns
+    ///
+    /// * `PowerCycleReport` - The outcome of every cycle run.
+    pub fn run(&self, cycles: u32, ready_timeout: Duration) -> PowerCycleReport {
+        let mut report = PowerCycleReport {
+            agent: self.agent_url.clone(),
+            bmc: self.bmc.to_string(),
+            started_at: Local::now().to_rfc3339(),
+            planned: cycles,
+            ready_timeout_secs: ready_timeout.as_secs(),
+            cycles: Vec::new(),
+            cancelled: false,
+        };
+        for cycle in 1..=cycles {
+            self.logger.log_info(&format!(
+                "Power cycle {}/{} of {} through {}",
+                cycle, cycles, self.agent_url, self.bmc
+            ));
+            let Some(record) = self.cycle(cycle, ready_timeout) else {
+                report.cancelled = true;
+                break;
+            };
+            match record.outcome {
+                CycleOutcome::Ready => self.logger.log_info(&format!(
+                    "Power cycle {}/{}: the agent was ready after {:.0} s",
+                    cycle,
+                    cycles,
+                    record.ready_secs.unwrap_or_default()
+                )),
+                _ => self.logger.log_error(&format!(
+                    "Power cycle {}/{} failed: {}",
+                    cycle,
+                    cycles,
+                    record.error.as_deref().unwrap_or("unknown error")
+                )),
+            }
+            report.cycles.push(record);
+        }
+        report
+    }
+
+    /// Runs one power cycle.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<CycleRecord>` - The outcome of the cycle, or `None` if the test was
+    ///   cancelled before it ended.
+    fn cycle(&self, cycle: u32, ready_timeout: Duration) -> Option<CycleRecord> {
+        let mut record = CycleRecord {
+            cycle,
+            outcome: CycleOutcome::NotCycled,
+            down_secs: None,
+            ready_secs: None,
+            error: None,
+        };
+        // After a boot failure the agent is already gone, so it cannot be seen going down.
+        let was_up = self.agent.readiness().is_ok();
+        let started = Instant::now();
+        if let Err(e) = self.hooks.power_cycle_bmc(&self.bmc) {
+            record.error = Some(e);
+            return Some(record);
+        }
+
+        if was_up {
+            loop {
+                if !self.wait() {
+                    return None;
+                }
+                if self.agent.readiness().is_err() {
+                    record.down_secs = Some(started.elapsed().as_secs_f64());
+                    break;
+                }
+                if started.elapsed() >= DOWN_TIMEOUT {
+                    record.error = Some(format!(
+                        "the agent kept answering for {:.0} s after the power cycle",
+                        DOWN_TIMEOUT.as_secs_f64()
+                    ));
+                    return Some(record);
+                }
+            }
+        }
+
+        loop {
+            if !self.wait() {
+                return None;
+            }
+            match self.agent.readiness() {
+                Ok(()) => {
+                    record.outcome = CycleOutcome::Ready;
+                    record.ready_secs = Some(started.elapsed().as_secs_f64());
+                    record.error = None;
+                    return Some(record);
+                }
+                Err(e) => record.error = Some(e),
+            }
+            if started.elapsed() >= ready_timeout {
+                record.outcome = CycleOutcome::BootFailure;
+                return Some(record);
+            }
+        }
+    }
+
+    /// Waits for the next heartbeat, returning `false` if the test was cancelled.
+    fn wait(&self) -> bool {
+        sleep(HEARTBEAT_INTERVAL);
+        !self.cancel.is_cancelled()
+    }
+}
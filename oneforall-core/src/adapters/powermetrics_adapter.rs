@@ -0,0 +1,78 @@
+//! Powermetrics Adapter
+//!
+//! This module provides an adapter that samples the activity of the CPU
+//! clusters, the power and the thermal pressure of a Mac with `powermetrics`,
+//! which ships with macOS and has to run as root. Once powermetrics fails, e.g.
+//! because OneForAll was not started with sudo, it is not tried again.
+
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::ports::log_port::LoggerPort;
+
+use crate::domain::powermetrics::{parse_powermetrics, PowermetricsSample};
+use crate::ports::powermetrics_port::PowermetricsPort;
+
+/// The powermetrics samplers read: the CPU clusters and power, the GPU, and thermal pressure.
+const POWERMETRICS_SAMPLERS: &str = "cpu_power,gpu_power,thermal";
+
+/// Represents the powermetrics adapter.
+pub struct PowermetricsAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    available: AtomicBool,       // cleared once powermetrics fails to run
+}
+
+impl PowermetricsAdapter {
+    /// Creates a new instance of `PowermetricsAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `PowermetricsAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        PowermetricsAdapter {
+            logger,
+            available: AtomicBool::new(true),
+        }
+    }
+
+    /// Runs powermetrics for one sample and returns its output.
+    fn run_powermetrics(window: Duration) -> Result<String, String> {
+        let output = Command::new("powermetrics")
+            .args(["--samplers", POWERMETRICS_SAMPLERS])
+            .args(["-i", &window.as_millis().max(1).to_string()])
+            .args(["-n", "1"])
+            .output()
+            .map_err(|e| format!("Failed to run powermetrics: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "powermetrics failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+}
+
+// Implement the `PowermetricsPort` trait for `PowermetricsAdapter`.
+impl PowermetricsPort for PowermetricsAdapter {
+    fn sample_powermetrics(&self, window: Duration) -> Result<PowermetricsSample, String> {
+        if !self.available.load(Ordering::Relaxed) {
+            return Err("powermetrics is unavailable".to_string());
+        }
+        match Self::run_powermetrics(window) {
+            Ok(output) => Ok(parse_powermetrics(&output)),
+            Err(e) => {
+                self.logger.log_warn(&format!(
+                    "Core class activity and thermal pressure unavailable; powermetrics needs root: {}",
+                    e
+                ));
+                self.available.store(false, Ordering::Relaxed);
+                Err(e)
+            }
+        }
+    }
+}
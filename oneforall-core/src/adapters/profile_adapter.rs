@@ -0,0 +1,125 @@
+//! Profile Adapter
+//!
+//! This module provides an adapter that finds test profiles. The profiles in
+//! the repository's `profiles` directory are built into the binary, and a
+//! `profiles` directory next to where OneForAll is run can add profiles or
+//! override the built-in ones by name.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use common::ports::log_port::LoggerPort;
+
+use crate::domain::profile::Profile;
+use crate::ports::profile_port::ProfilePort;
+
+/// The profiles shipped with OneForAll, as (name, TOML) pairs.
+const BUILT_IN_PROFILES: &[(&str, &str)] = &[
+    (
+        "burn-in-24h",
+        include_str!("../../../profiles/burn-in-24h.toml"),
+    ),
+    (
+        "quick-sanity",
+        include_str!("../../../profiles/quick-sanity.toml"),
+    ),
+    (
+        "storage-qual",
+        include_str!("../../../profiles/storage-qual.toml"),
+    ),
+    (
+        "thermal-cycle-24h",
+        include_str!("../../../profiles/thermal-cycle-24h.toml"),
+    ),
+];
+
+/// The directory searched for operator-defined profiles.
+pub const PROFILE_DIRECTORY: &str = "profiles";
+
+/// Represents the built-in and on-disk profile adapter.
+pub struct ProfileAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    directory: PathBuf,
+}
+
+impl ProfileAdapter {
+    /// Creates a new instance of `ProfileAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `ProfileAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        ProfileAdapter {
+            logger,
+            directory: PathBuf::from(PROFILE_DIRECTORY),
+        }
+    }
+
+    /// Reads and validates a profile file.
+    fn read_profile_file(&self, path: &Path) -> Result<Profile, String> {
+        self.logger
+            .log_debug(&format!("Reading profile {}", path.display()));
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read profile {}: {}", path.display(), e))?;
+        Profile::from_toml(&text).map_err(|e| format!("Invalid profile {}: {}", path.display(), e))
+    }
+}
+
+// Implement the `ProfilePort` trait for `ProfileAdapter`.
+impl ProfilePort for ProfileAdapter {
+    fn list_profiles(&self) -> Result<Vec<Profile>, String> {
+        let mut profiles = BTreeMap::new();
+        for (name, text) in BUILT_IN_PROFILES {
+            let profile = Profile::from_toml(text)
+                .map_err(|e| format!("Invalid built-in profile {}: {}", name, e))?;
+            profiles.insert(name.to_string(), profile);
+        }
+
+        // Profiles on disk take precedence over built-in profiles of the same name.
+        if let Ok(entries) = fs::read_dir(&self.directory) {
+            for path in entries.flatten().map(|entry| entry.path()) {
+                if path.extension().is_some_and(|ext| ext == "toml") {
+                    match self.read_profile_file(&path) {
+                        Ok(profile) => {
+                            profiles.insert(profile.name.clone(), profile);
+                        }
+                        Err(e) => self.logger.log_warn(&e),
+                    }
+                }
+            }
+        }
+
+        Ok(profiles.into_values().collect())
+    }
+
+    fn load_profile(&self, name: &str) -> Result<Profile, String> {
+        if name.ends_with(".toml") {
+            return self.read_profile_file(Path::new(name));
+        }
+
+        let path = self.directory.join(format!("{}.toml", name));
+        if path.exists() {
+            return self.read_profile_file(&path);
+        }
+
+        match BUILT_IN_PROFILES
+            .iter()
+            .find(|(built_in, _)| *built_in == name)
+        {
+            Some((_, text)) => Profile::from_toml(text),
+            None => {
+                let available: Vec<&str> = BUILT_IN_PROFILES.iter().map(|(n, _)| *n).collect();
+                Err(format!(
+                    "Unknown profile '{}'. Built-in profiles: {}. Custom profiles are read from {}/<name>.toml.",
+                    name,
+                    available.join(", "),
+                    PROFILE_DIRECTORY
+                ))
+            }
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! Profile Launcher
+//!
+//! This module starts test profiles as jobs on behalf of remote callers, for
+//! `POST /api/v1/jobs` and the `LaunchProfile` call of the gRPC API, so lab
+//! automation can qualify a machine without a shell on it. The run continues
+//! in the background after the call returns; it is followed and cancelled like
+//! any other job, and its report is stored with the other results.
+
+use std::sync::Arc;
+
+use common::domain::job::{Job, JobSpec, JobStatus};
+use common::ports::job_launcher_port::JobLauncherPort;
+use common::ports::job_port::JobPort;
+use common::ports::log_port::LoggerPort;
+use common::ports::telemetry_port::TelemetryPort;
+use tokio::runtime::Handle;
+
+use crate::adapters::profile_adapter::ProfileAdapter;
+use crate::adapters::profile_runner::ProfileRunner;
+use crate::adapters::repository::Repository;
+use crate::ports::profile_port::ProfilePort;
+
+/// Runs built-in and saved profiles as jobs for the REST and gRPC APIs.
+pub struct ProfileLauncher {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    telemetry: Arc<dyn TelemetryPort>,
+    jobs: Arc<dyn JobPort>,
+    repository: Repository,
+    profiles: ProfileAdapter,
+    runtime: Handle, // runs the profiles, also when launched from a blocking thread
+}
+
+impl ProfileLauncher {
+    /// Creates a new instance of `ProfileLauncher`. Must be called within the
+    /// Tokio runtime the profiles are to run on.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    /// * `telemetry` - Receives live readings and progress while profiles run.
+    /// * `jobs` - The job queue the profiles and their steps are submitted to.
+    /// * `repository` - The database the profile reports are stored in.
+    ///
+    /// # Returns
+    /// An instance of `ProfileLauncher`.
+    pub fn new(
+        logger: Arc<dyn LoggerPort>,
+        telemetry: Arc<dyn TelemetryPort>,
+        jobs: Arc<dyn JobPort>,
+        repository: Repository,
+    ) -> Self {
+        ProfileLauncher {
+            profiles: ProfileAdapter::new(logger.clone()),
+            logger,
+            telemetry,
+            jobs,
+            repository,
+            runtime: Handle::current(),
+        }
+    }
+}
+
+// Implement the `JobLauncherPort` trait for `ProfileLauncher`.
+impl JobLauncherPort for ProfileLauncher {
+    fn launch_profile(&self, profile: &str) -> Result<Job, String> {
+        // Remote callers name a profile; they cannot make the agent read arbitrary files.
+        if profile.is_empty() || profile.ends_with(".toml") || profile.contains(['/', '\\']) {
+            return Err(format!(
+                "Invalid profile name '{}'; name a built-in profile or one saved in the profiles directory.",
+                profile
+            ));
+        }
+        let profile = self.profiles.load_profile(profile)?;
+
+        // As with `oneforall run`, the profile is a job without resources of its own
+        // and each step is queued separately for the resources it loads.
+        let profile_job = self
+            .jobs
+            .submit_job(JobSpec::new(&format!("profile:{}", profile.name), &[]));
+        let job = profile_job.job.clone();
+        self.logger.log_info(&format!(
+            "Launching profile {} as job {} ({} steps) for a remote caller",
+            profile.name,
+            job.id,
+            profile.steps.len()
+        ));
+
+        let runner = ProfileRunner::new(
+            self.logger.clone(),
+            self.telemetry.clone(),
+            self.jobs.clone(),
+        );
+        let logger = self.logger.clone();
+        let jobs = self.jobs.clone();
+        let repository = self.repository.clone();
+        self.runtime.spawn(async move {
+            let report = runner.run(&profile, &profile_job.cancel).await;
+            if let Err(e) = repository.store_run(
+                "profile",
+                &report.started_at,
+                Some(&report.profile),
+                &report,
+            ) {
+                logger.log_warn(&format!("Failed to store profile report: {}", e));
+            }
+
+            let (status, message) = if profile_job.cancel.is_cancelled() {
+                (JobStatus::Cancelled, None)
+            } else if report.passed() {
+                (JobStatus::Completed, None)
+            } else {
+                (
+                    JobStatus::Failed,
+                    Some(format!(
+                        "failed steps: {}",
+                        report.failed_steps().join(", ")
+                    )),
+                )
+            };
+            jobs.finish_job(profile_job.job.id, status, message);
+        });
+
+        Ok(job)
+    }
+}
@@ -0,0 +1,197 @@
+//! Progress Reporter
+//!
+//! This module streams the intermediate progress of a running stress test to
+//! the terminal as a live-updating status line and to the telemetry port, from
+//! where the web server forwards it to WebSocket clients.
+
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use common::domain::telemetry::StressProgress;
+use common::ports::log_port::LoggerPort;
+use common::ports::telemetry_port::TelemetryPort;
+
+use crate::adapters::background_sampler::BackgroundSampler;
+use crate::adapters::thermal_adapter::ThermalAdapter;
+use crate::domain::stress_ng::parse_bogo_ops_rate;
+use crate::ports::thermal_port::ThermalPort;
+
+/// Interval between two progress updates.
+pub const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Reports the progress of a single stress test run.
+pub struct ProgressReporter {
+    logger: Arc<dyn LoggerPort>,
+    interactive: bool,
+    telemetry: Arc<dyn TelemetryPort>,
+    test: String,
+    duration: Duration,
+    started: Instant,
+    rates: Arc<Mutex<RateSource>>,
+    updates: BackgroundSampler<()>,
+}
+
+/// The stress-ng runs whose metrics give the bogo-ops rate of the test.
+#[derive(Default)]
+struct RateSource {
+    /// The output file of each run loading the machine, with the rate last read from it.
+    outputs: Vec<(String, Option<f64>)>,
+
+    /// The rate of the runs followed before, kept until the current ones report theirs.
+    previous: Option<f64>,
+}
+
+impl RateSource {
+    /// Reads the metrics stress-ng has written so far and returns the current rate.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<f64>` - The summed rate of the runs that reported one, as they load the
+    ///   machine at once, or the rate of the earlier runs if none has yet.
+    fn read(&mut self) -> Option<f64> {
+        for (path, rate) in &mut self.outputs {
+            // The file is gone once its run was collected; its last rate still counts.
+            if let Some(latest) = fs::read_to_string(&*path)
+                .ok()
+                .and_then(|output| parse_bogo_ops_rate(&output))
+            {
+                *rate = Some(latest);
+            }
+        }
+        self.outputs
+            .iter()
+            .filter_map(|(_, rate)| *rate)
+            .reduce(|a, b| a + b)
+            .or(self.previous)
+    }
+}
+
+/// Builds the progress of the run at the current point in time.
+fn current_progress(
+    telemetry: &dyn TelemetryPort,
+    thermal: &ThermalAdapter,
+    test: &str,
+    elapsed: Duration,
+    duration: Duration,
+    bogo_ops_per_sec: Option<f64>,
+) -> StressProgress {
+    StressProgress {
+        timestamp: chrono::Local::now().to_rfc3339(),
+        test: test.to_string(),
+        elapsed_secs: elapsed.as_secs_f64(),
+        duration_secs: duration.as_secs_f64(),
+        bogo_ops_per_sec,
+        hottest_celsius: thermal.sample_thermals().ok().and_then(|s| s.hottest()),
+        package_watts: telemetry.latest_power().and_then(|p| p.package_watts()),
+        finished: false,
+    }
+}
+
+impl ProgressReporter {
+    /// Starts reporting progress for a stress test.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger` - Logger implementation for logging messages.
+    /// * `telemetry` - Receives every progress update for the REST API and WebSocket.
+    /// * `test` - The name of the stress test, e.g. "cpu".
+    /// * `duration` - The planned duration of the test.
+    ///
+    /// # Returns
+    ///
+    /// * `ProgressReporter` - A handle used to stop reporting once the test has finished.
+    pub fn start(
+        logger: Arc<dyn LoggerPort>,
+        telemetry: Arc<dyn TelemetryPort>,
+        test: &str,
+        duration: Duration,
+    ) -> Self {
+        let started = Instant::now();
+        let interactive = io::stderr().is_terminal();
+        let thermal = ThermalAdapter::new(logger.clone());
+        let rates = Arc::new(Mutex::new(RateSource::default()));
+
+        let updates = {
+            let telemetry = telemetry.clone();
+            let test = test.to_string();
+            let rates = rates.clone();
+            BackgroundSampler::start(PROGRESS_INTERVAL, move || {
+                let rate = rates.lock().ok().and_then(|mut rates| rates.read());
+                let progress = current_progress(
+                    &*telemetry,
+                    &thermal,
+                    &test,
+                    started.elapsed(),
+                    duration,
+                    rate,
+                );
+                // Redraw the status line in place; piped output only gets the final line.
+                if interactive {
+                    eprint!("\r\x1b[2K{}", progress.render_line());
+                    let _ = io::stderr().flush();
+                }
+                telemetry.publish_progress(progress);
+                None
+            })
+        };
+
+        ProgressReporter {
+            logger,
+            interactive,
+            telemetry,
+            test: test.to_string(),
+            duration,
+            started,
+            rates,
+            updates,
+        }
+    }
+
+    /// Takes the bogo-ops rate of the following updates from the metrics of stress-ng runs.
+    ///
+    /// Every update reads the metrics each run has written to its output file so far, so
+    /// the rate follows the runs as they report it. The runs replace those followed
+    /// before, whose rate is sent until the new runs report theirs.
+    ///
+    /// # Arguments
+    ///
+    /// * `output_files` - The output file of each stress-ng run now loading the machine.
+    pub fn follow_runs(&self, output_files: &[String]) {
+        if let Ok(mut rates) = self.rates.lock() {
+            rates.previous = rates.read();
+            rates.outputs = output_files
+                .iter()
+                .map(|path| (path.clone(), None))
+                .collect();
+        }
+    }
+
+    /// Stops reporting and publishes the final progress of the run.
+    ///
+    /// # Arguments
+    ///
+    /// * `bogo_ops_per_sec` - The bogo-ops rate reported by stress-ng, if the run produced
+    ///   metrics.
+    pub fn finish(self, bogo_ops_per_sec: Option<f64>) {
+        self.updates.stop();
+
+        let elapsed = self.started.elapsed();
+        let mut progress = current_progress(
+            &*self.telemetry,
+            &ThermalAdapter::new(self.logger.clone()),
+            &self.test,
+            elapsed,
+            self.duration,
+            bogo_ops_per_sec,
+        );
+        progress.finished = true;
+
+        if self.interactive {
+            eprint!("\r\x1b[2K");
+        }
+        eprintln!("{}", progress.render_line());
+        self.telemetry.publish_progress(progress);
+    }
+}
@@ -0,0 +1,527 @@
+//! PS Adapter
+//!
+//! This module provides the process monitor of Overwatch, which lists processes
+//! the way the `ps` command does, but reads them directly: from `/proc` on Linux
+//! and through libproc on macOS, without starting a subprocess or parsing its
+//! text. CPU usage is the share of CPU time a process used between two readings,
+//! which are taken at most once per sampling window however often processes are
+//! asked for. The processes are linked into a tree and grouped by the cgroup read
+//! from `/proc`, so CPU use can be attributed to the service or container running
+//! them.
+
+use std::collections::HashMap;
+#[cfg(target_os = "linux")]
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{Local, Utc};
+#[cfg(target_arch = "wasm32")]
+use common::adapters::ps_wasm_adapter;
+use common::ports::footprint_port::FootprintPort;
+use common::ports::log_port::LoggerPort;
+use common::ports::telemetry_port::TelemetryPort;
+
+use crate::adapters::sample_writer::SampleWriter;
+use crate::domain::ps_command::{format_top_processes, ProcessInfo, ProcessTree};
+use crate::ports::ps_command_port::PsCommandPort;
+
+/// The collector the usage of each cgroup is stored under.
+const CGROUPS_COLLECTOR: &str = "cgroups";
+
+/// The sampling window used unless another is configured.
+pub const DEFAULT_SAMPLE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Number of processes read at most per sample, which bounds the memory the
+/// monitor holds on machines running very many processes.
+const MAX_PROCESSES: usize = 32_768;
+
+/// Number of processes listed by `execute_ps_command`.
+const TOP_PROCESSES: usize = 10;
+
+/// A process as read from the kernel, with its cumulative CPU time.
+struct ProcessReading {
+    pid: u32,
+    ppid: u32,
+    cpu_nanos: u64,
+    resident_bytes: u64,
+    command: String,
+    cgroup: String,
+}
+
+/// The last sample taken, which the next one measures CPU usage against.
+struct Sample {
+    taken_at: Instant,
+    cpu_nanos: HashMap<u32, u64>,
+    processes: Vec<ProcessInfo>,
+}
+
+/// Represents the process monitor adapter, reading `/proc` or libproc.
+pub struct PsAdapter {
+    logger: Arc<dyn LoggerPort>,               // inject the logger port
+    samples: SampleWriter,                     // where the output is stored
+    sample_window: Duration, // the least time between two readings of the processes
+    last: Mutex<Option<Sample>>, // the readings CPU usage is measured against
+    footprint: Option<Arc<dyn FootprintPort>>, // paces the collection to the footprint budget
+    telemetry: Option<Arc<dyn TelemetryPort>>, // where the cgroup usage is published
+}
+
+impl PsAdapter {
+    /// Creates a new instance of `PsAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `Logger` trait.
+    /// * `samples` - The writer the output is stored through.
+    ///
+    /// # Returns
+    /// An instance of `PsAdapter` sampling once per `DEFAULT_SAMPLE_WINDOW` at most.
+    pub fn new(logger: Arc<dyn LoggerPort>, samples: SampleWriter) -> Self {
+        PsAdapter {
+            logger,
+            samples,
+            sample_window: DEFAULT_SAMPLE_WINDOW,
+            last: Mutex::new(None),
+            footprint: None,
+            telemetry: None,
+        }
+    }
+
+    /// Sets the sampling window.
+    ///
+    /// Processes are read at most once per window, and CPU usage is averaged
+    /// over at least one window; callers asking more often get the last sample.
+    ///
+    /// # Arguments
+    /// * `window` - The least time between two readings of the processes.
+    ///
+    /// # Returns
+    /// The adapter with the window set.
+    pub fn with_sample_window(mut self, window: Duration) -> Self {
+        self.sample_window = window;
+        self
+    }
+
+    /// Sets what paces `collect_cpu_statistics`, which waits longer between
+    /// readings while the monitoring is over its CPU budget.
+    ///
+    /// # Arguments
+    /// * `footprint` - The governor of the monitoring footprint.
+    ///
+    /// # Returns
+    /// The adapter with the pacing set.
+    pub fn with_footprint(mut self, footprint: Arc<dyn FootprintPort>) -> Self {
+        self.footprint = Some(footprint);
+        self
+    }
+
+    /// Sets the metric registry the usage of each cgroup is published to, alongside
+    /// the readings stored in the database.
+    ///
+    /// # Arguments
+    /// * `telemetry` - The metric registry.
+    ///
+    /// # Returns
+    /// The adapter with the registry set.
+    pub fn with_telemetry(mut self, telemetry: Arc<dyn TelemetryPort>) -> Self {
+        self.telemetry = Some(telemetry);
+        self
+    }
+
+    /// Returns every process with its CPU usage over the last sampling window.
+    ///
+    /// The first call reads the processes twice, one window apart; later calls
+    /// measure against the previous reading, or return it if it is younger
+    /// than the window.
+    fn sample_processes(&self) -> Result<Vec<ProcessInfo>, String> {
+        let mut last = self
+            .last
+            .lock()
+            .map_err(|_| "The process sample lock is poisoned".to_string())?;
+        if let Some(sample) = last.as_ref() {
+            if sample.taken_at.elapsed() < self.sample_window {
+                return Ok(sample.processes.clone());
+            }
+        }
+
+        let (taken_at, previous) = match last.take() {
+            Some(sample) => (sample.taken_at, sample.cpu_nanos),
+            None => {
+                let taken_at = Instant::now();
+                let readings = Self::read_processes()?;
+                thread::sleep(self.sample_window);
+                let cpu_nanos = readings.iter().map(|r| (r.pid, r.cpu_nanos)).collect();
+                (taken_at, cpu_nanos)
+            }
+        };
+        let readings = Self::read_processes()?;
+        let now = Instant::now();
+        let elapsed_nanos = now.duration_since(taken_at).as_nanos().max(1) as f64;
+        let total_memory = Self::read_total_memory().unwrap_or(0);
+
+        let mut cpu_nanos = HashMap::with_capacity(readings.len());
+        let processes: Vec<ProcessInfo> = readings
+            .into_iter()
+            .map(|reading| {
+                cpu_nanos.insert(reading.pid, reading.cpu_nanos);
+                // A process first seen has no baseline yet: it is measured from this
+                // reading on, rather than charged the CPU time of its whole life at once.
+                let used = previous
+                    .get(&reading.pid)
+                    .map_or(0, |before| reading.cpu_nanos.saturating_sub(*before));
+                ProcessInfo {
+                    pid: reading.pid,
+                    ppid: reading.ppid,
+                    cpu_percent: used as f64 * 100.0 / elapsed_nanos,
+                    memory_percent: if total_memory == 0 {
+                        0.0
+                    } else {
+                        reading.resident_bytes as f64 * 100.0 / total_memory as f64
+                    },
+                    cgroup: reading.cgroup,
+                    command: reading.command,
+                }
+            })
+            .collect();
+        self.logger.log_trace(&format!(
+            "Sampled {} processes over {} ms",
+            processes.len(),
+            now.duration_since(taken_at).as_millis()
+        ));
+
+        *last = Some(Sample {
+            taken_at: now,
+            cpu_nanos,
+            processes: processes.clone(),
+        });
+        Ok(processes)
+    }
+
+    /// Reads the cgroup of a process from `/proc/<pid>/cgroup`.
+    ///
+    /// Lines read "hierarchy-ID:controller-list:path". The cgroup v2 line, with
+    /// an empty controller list, is preferred; on cgroup v1 the hierarchy of the
+    /// cpu controller is used, or systemd's own, which names the unit.
+    ///
+    /// # Returns
+    /// The cgroup path, or an empty string on systems without cgroups or if the process has exited.
+    #[cfg(target_os = "linux")]
+    fn read_cgroup(pid: u32) -> String {
+        let Ok(cgroups) = fs::read_to_string(format!("/proc/{}/cgroup", pid)) else {
+            return String::new();
+        };
+        let hierarchies: Vec<(&str, &str)> = cgroups
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, ':');
+                let _id = fields.next()?;
+                Some((fields.next()?, fields.next()?))
+            })
+            .collect();
+        hierarchies
+            .iter()
+            .find(|(controllers, _)| controllers.is_empty())
+            .or_else(|| {
+                hierarchies
+                    .iter()
+                    .find(|(controllers, _)| controllers.split(',').any(|c| c == "cpu"))
+            })
+            .or_else(|| {
+                hierarchies
+                    .iter()
+                    .find(|(controllers, _)| *controllers == "name=systemd")
+            })
+            .map(|(_, path)| path.to_string())
+            .unwrap_or_default()
+    }
+
+    /// Parses `/proc/<pid>/stat`, e.g. `812 (nginx) S 1 812 812 0 -1 ... 1520 340 ... 2210 ...`.
+    ///
+    /// The command name is in parentheses and may itself contain spaces and
+    /// parentheses, so the fields are counted from the last closing one.
+    ///
+    /// # Arguments
+    /// * `stat` - The contents of the file.
+    /// * `tick_nanos` - The length of a clock tick, in nanoseconds.
+    /// * `page_bytes` - The size of a memory page, in bytes.
+    #[cfg(target_os = "linux")]
+    fn parse_stat(stat: &str, tick_nanos: u64, page_bytes: u64) -> Option<ProcessReading> {
+        let open = stat.find('(')?;
+        let close = stat.rfind(')')?;
+        let pid = stat[..open].trim().parse().ok()?;
+        let command = stat.get(open + 1..close)?.to_string();
+        // Fields after the command: state ppid pgrp session tty tpgid flags minflt
+        // cminflt majflt cmajflt utime stime cutime cstime priority nice
+        // num_threads itrealvalue starttime vsize rss.
+        let fields: Vec<&str> = stat[close + 1..].split_whitespace().collect();
+        let field = |index: usize| fields.get(index)?.parse::<u64>().ok();
+        Some(ProcessReading {
+            pid,
+            ppid: field(1)? as u32,
+            cpu_nanos: (field(11)? + field(12)?) * tick_nanos,
+            resident_bytes: field(21)? * page_bytes,
+            command,
+            cgroup: Self::read_cgroup(pid),
+        })
+    }
+
+    /// Reads every process from `/proc`.
+    #[cfg(target_os = "linux")]
+    fn read_processes() -> Result<Vec<ProcessReading>, String> {
+        // SAFETY: `sysconf` only reads system configuration values.
+        let (ticks_per_second, page_bytes) = unsafe {
+            (
+                libc::sysconf(libc::_SC_CLK_TCK),
+                libc::sysconf(libc::_SC_PAGESIZE),
+            )
+        };
+        let tick_nanos = 1_000_000_000 / ticks_per_second.max(1) as u64;
+        let page_bytes = page_bytes.max(1) as u64;
+
+        let entries = fs::read_dir("/proc").map_err(|e| format!("Failed to read /proc: {}", e))?;
+        Ok(entries
+            .flatten()
+            .filter(|entry| {
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                !name.is_empty() && name.bytes().all(|b| b.is_ascii_digit())
+            })
+            // Processes exiting between listing and reading are skipped.
+            .filter_map(|entry| fs::read_to_string(entry.path().join("stat")).ok())
+            .filter_map(|stat| Self::parse_stat(&stat, tick_nanos, page_bytes))
+            .take(MAX_PROCESSES)
+            .collect())
+    }
+
+    /// Reads the total physical memory from `/proc/meminfo`, in bytes.
+    #[cfg(target_os = "linux")]
+    fn read_total_memory() -> Option<u64> {
+        fs::read_to_string("/proc/meminfo")
+            .ok()?
+            .lines()
+            .find_map(|line| line.strip_prefix("MemTotal:"))?
+            .split_whitespace()
+            .next()?
+            .parse::<u64>()
+            .ok()
+            .map(|kb| kb * 1024)
+    }
+
+    /// Reads every process through libproc.
+    #[cfg(target_os = "macos")]
+    fn read_processes() -> Result<Vec<ProcessReading>, String> {
+        // Ask for the number of processes first, with room for some started meanwhile.
+        // SAFETY: a null buffer only queries the number of PIDs.
+        let count = unsafe { libc::proc_listallpids(std::ptr::null_mut(), 0) };
+        if count <= 0 {
+            return Err(format!(
+                "Failed to list processes: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        let mut pids = vec![0 as libc::pid_t; (count as usize + 64).min(MAX_PROCESSES)];
+        // SAFETY: the buffer holds `pids.len()` PIDs and its size is passed in bytes.
+        let count = unsafe {
+            libc::proc_listallpids(
+                pids.as_mut_ptr() as *mut libc::c_void,
+                (pids.len() * std::mem::size_of::<libc::pid_t>()) as libc::c_int,
+            )
+        };
+        pids.truncate(count.max(0) as usize);
+
+        // Task times are in Mach absolute time units, which are nanoseconds on Intel only.
+        let mut timebase = libc::mach_timebase_info { numer: 1, denom: 1 };
+        // SAFETY: `timebase` is a valid, writable `mach_timebase_info`.
+        unsafe { libc::mach_timebase_info(&mut timebase) };
+        let to_nanos = |ticks: u64| ticks * timebase.numer as u64 / timebase.denom.max(1) as u64;
+
+        Ok(pids
+            .into_iter()
+            .filter(|pid| *pid > 0)
+            .filter_map(|pid| {
+                // SAFETY: `proc_taskallinfo` is plain data, and its size is passed along.
+                let mut info: libc::proc_taskallinfo = unsafe { std::mem::zeroed() };
+                let size = std::mem::size_of::<libc::proc_taskallinfo>() as libc::c_int;
+                let read = unsafe {
+                    libc::proc_pidinfo(
+                        pid,
+                        libc::PROC_PIDTASKALLINFO,
+                        0,
+                        &mut info as *mut _ as *mut libc::c_void,
+                        size,
+                    )
+                };
+                // Processes of other users are not readable without root, and are skipped.
+                if read != size {
+                    return None;
+                }
+                // SAFETY: the kernel NUL-terminates the command name within the array.
+                let command = unsafe { std::ffi::CStr::from_ptr(info.pbsd.pbi_comm.as_ptr()) }
+                    .to_string_lossy()
+                    .into_owned();
+                Some(ProcessReading {
+                    pid: pid as u32,
+                    ppid: info.pbsd.pbi_ppid,
+                    cpu_nanos: to_nanos(info.ptinfo.pti_total_user + info.ptinfo.pti_total_system),
+                    resident_bytes: info.ptinfo.pti_resident_size,
+                    command,
+                    cgroup: String::new(),
+                })
+            })
+            .collect())
+    }
+
+    /// Reads the total physical memory from the `hw.memsize` sysctl, in bytes.
+    #[cfg(target_os = "macos")]
+    fn read_total_memory() -> Option<u64> {
+        let mut memory: u64 = 0;
+        let mut size = std::mem::size_of::<u64>();
+        // SAFETY: the name is NUL-terminated and `memory` has room for the `size` bytes asked for.
+        let status = unsafe {
+            libc::sysctlbyname(
+                c"hw.memsize".as_ptr(),
+                &mut memory as *mut u64 as *mut libc::c_void,
+                &mut size,
+                std::ptr::null_mut(),
+                0,
+            )
+        };
+        (status == 0).then_some(memory)
+    }
+
+    /// Reading processes is not supported on this platform, so this always fails.
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    fn read_processes() -> Result<Vec<ProcessReading>, String> {
+        Err("Listing processes is not supported on this platform".to_string())
+    }
+
+    /// The total physical memory is not known on this platform.
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    fn read_total_memory() -> Option<u64> {
+        None
+    }
+}
+
+// Implement the `PsCommandPort` trait for `PsAdapter`. This allows the adapter to be used
+// as a port in the application, and it also provides a concrete implementation of the
+// `PsCommandPort` interface.
+impl PsCommandPort for PsAdapter {
+    fn execute_ps_command(&self) -> Result<String, String> {
+        // List the busiest processes of the last sampling window, as `ps` sorted by CPU would
+        let mut processes = self.sample_processes()?;
+        processes.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent));
+        let output_str = format_top_processes(&processes, TOP_PROCESSES);
+
+        // Call the WASM serialization function from ps_wasm_adapter.rs. The wasm-bindgen
+        // functions abort the process on native targets, so the raw output is kept there.
+        #[cfg(target_arch = "wasm32")]
+        {
+            let wasm_output = ps_wasm_adapter::write_to_wasm(output_str).unwrap();
+            return Ok(String::try_from(wasm_output).unwrap()); // Return the output as a `String`
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        Ok(output_str)
+    }
+
+    fn read_process_tree(&self) -> Result<ProcessTree, String> {
+        Ok(ProcessTree::new(self.sample_processes()?))
+    }
+
+    /// Writes the output of the `ps` command to a specified file.
+    fn write_to_file(&self, output: String, file_path: &str) -> Result<(), String> {
+        match OpenOptions::new() // Create a new `OpenOptions` instance
+            .create(true) // Create the file if it doesn't exist
+            .append(true) // Append to the file instead of overwriting
+            .open(file_path) // Open the file
+        {
+            Ok(mut file) => { // The file was successfully opened
+                file.write_all(output.as_bytes()) // Write the output to the file
+                    .map_err(|e| e.to_string())?; // Convert the `std::io::Error` to a `String`
+                Ok(()) // Return `Ok` to indicate success
+            }
+            Err(e) => {
+                let error_message = format!("Failed to open file: {}", e); // Create an error message
+                self.logger.log_error(&error_message);
+                Err(error_message) // Return `Err` to indicate failure
+            }
+        }
+    }
+
+    /// Periodically executes the `ps` command to gather CPU statistics and writes to a file.
+    ///
+    /// # Arguments
+    /// * `output_file_path` - The path to the file where the command output will be saved.
+    /// * `interval` - The time between two executions of the command.
+    fn collect_cpu_statistics(&self, key: &str, interval: Duration) {
+        loop {
+            // Loop forever
+            match self.execute_ps_command() {
+                Ok(output) => {
+                    // write to the database if the command was successful and the output is not
+                    // empty (i.e., the command returned no results)
+                    if let Err(e) = self.write_to_db(output, key.as_bytes(), "database") {
+                        self.logger.log_error(&e);
+                        break; // Break out of the loop if an error occurs
+                    }
+                }
+                Err(e) => {
+                    self.logger.log_error(&e);
+                    break; // Break out of the loop if an error occurs
+                }
+            }
+            // Store what each service or container uses alongside the busiest processes.
+            match self.read_process_tree() {
+                Ok(tree) => {
+                    let usage = tree.cgroup_usage();
+                    if let Err(e) = self.samples.record(CGROUPS_COLLECTOR, Utc::now(), &usage) {
+                        self.logger
+                            .log_warn(&format!("Failed to store cgroup usage: {}", e));
+                    }
+                    if let Some(telemetry) = &self.telemetry {
+                        let timestamp = Local::now().to_rfc3339();
+                        telemetry.publish_metrics(
+                            usage
+                                .iter()
+                                .flat_map(|group| group.metrics(&timestamp))
+                                .collect(),
+                        );
+                    }
+                }
+                Err(e) => self.logger.log_warn(&e),
+            }
+            thread::sleep(
+                self.footprint
+                    .as_ref()
+                    .map_or(interval, |footprint| footprint.pace(interval)),
+            );
+        }
+    }
+
+    /// Writes the output of the `ps` command to a database.
+    ///
+    /// # Arguments
+    /// * `output` - The output string from the `ps` command.
+    /// * `key` - The collector name the command output is stored under, e.g. "processes".
+    /// * `db_identifier` - A string that identifies the database location.
+    ///
+    /// # Returns
+    /// A `Result` indicating the success or failure of the write operation.
+    /// This method writes the output of the `ps` command to a database.
+    /// This can be useful for logging, analysis, or real-time monitoring purposes.
+    fn write_to_db(&self, output: String, key: &[u8], db_identifier: &str) -> Result<(), String> {
+        // Store the output as a reading of the collector named by the key
+        let collector = String::from_utf8_lossy(key);
+        match self.samples.record(&collector, Utc::now(), &output) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let error_message =
+                    format!("Failed to write to database at {}: {}", db_identifier, e);
+                self.logger.log_error(&error_message);
+                Err(error_message)
+            }
+        }
+    }
+}
@@ -0,0 +1,274 @@
+//! RDMA Adapter
+//!
+//! This module provides an adapter that reads the RDMA devices of the machine
+//! and their ports from the sysfs attributes of the Linux RDMA subsystem, and
+//! benchmarks them with the perftest suite: ib_write_bw for the bandwidth and
+//! ib_read_lat for the latency. Each perftest run has a server and a client
+//! side; in loopback both run on this machine, otherwise the peer serves.
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use common::ports::log_port::LoggerPort;
+use tokio_util::sync::CancellationToken;
+
+use crate::adapters::sysfs::read_sysfs;
+use crate::domain::rdma::{
+    parse_perftest_bandwidth, parse_perftest_latency, port_state_name, RdmaBenchmarkResult,
+    RdmaDevice, RdmaDevicePort,
+};
+use crate::ports::rdma_port::RdmaPort;
+
+/// Root of the sysfs hierarchy listing the RDMA devices.
+const SYSFS_INFINIBAND_ROOT: &str = "/sys/class/infiniband";
+
+/// The perftest bandwidth test and the options it runs with.
+const BANDWIDTH_TEST: (&str, &[&str]) = ("ib_write_bw", &["--report_gbits", "-D", "5"]);
+
+/// The perftest latency test and the options it runs with.
+const LATENCY_TEST: (&str, &[&str]) = ("ib_read_lat", &[]);
+
+/// How long a server is given to start listening before its client connects.
+const SERVER_START_DELAY: Duration = Duration::from_secs(1);
+
+/// How many times a client tries to reach a peer whose server may not be listening yet.
+const CLIENT_ATTEMPTS: u32 = 10;
+
+/// Represents the sysfs and perftest RDMA adapter.
+pub struct RdmaAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl RdmaAdapter {
+    /// Creates a new instance of `RdmaAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `RdmaAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        RdmaAdapter { logger }
+    }
+
+    /// Reads the ports of a device, in port order.
+    fn read_ports(device: &Path) -> Vec<RdmaDevicePort> {
+        let mut ports: Vec<RdmaDevicePort> = fs::read_dir(device.join("ports"))
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter_map(|entry| {
+                        let number = entry.file_name().to_string_lossy().parse().ok()?;
+                        let path = entry.path();
+                        let read = |name: &str| {
+                            read_sysfs(path.join(name)).unwrap_or_else(|| "unknown".to_string())
+                        };
+                        Some(RdmaDevicePort {
+                            number,
+                            state: port_state_name(&read("state")),
+                            phys_state: port_state_name(&read("phys_state")),
+                            rate: read("rate"),
+                            link_layer: read("link_layer"),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        ports.sort_by_key(|port| port.number);
+        ports
+    }
+
+    /// Builds the arguments of one perftest run.
+    fn perftest_args(device: &str, port: u32, options: &[&str], peer: Option<&str>) -> Vec<String> {
+        // -F keeps perftest from refusing to run when CPU frequency scaling is on.
+        let mut args: Vec<String> = ["-d", device, "-i", &port.to_string(), "-F"]
+            .iter()
+            .chain(options)
+            .map(|arg| arg.to_string())
+            .collect();
+        args.extend(peer.map(str::to_string));
+        args
+    }
+
+    /// Runs one side of a perftest run and waits for it to finish.
+    fn run_perftest(&self, binary: &str, args: &[String]) -> Result<Output, String> {
+        self.logger
+            .log_debug(&format!("Running {} {}", binary, args.join(" ")));
+        Command::new(binary)
+            .args(args)
+            .output()
+            .map_err(|e| format!("Failed to run {} (is perftest installed?): {}", binary, e))
+    }
+
+    /// Runs the client side of a perftest run, retrying while the server is not
+    /// listening yet, and returns its output.
+    fn run_client(
+        &self,
+        binary: &str,
+        args: &[String],
+        cancel: &CancellationToken,
+    ) -> Result<String, String> {
+        let mut attempt = 1;
+        loop {
+            let output = self.run_perftest(binary, args)?;
+            if output.status.success() {
+                return Ok(String::from_utf8_lossy(&output.stdout).to_string());
+            }
+            let error = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            if attempt >= CLIENT_ATTEMPTS || cancel.is_cancelled() {
+                return Err(format!("{} failed: {}", binary, error));
+            }
+            self.logger.log_debug(&format!(
+                "{} attempt {} failed, retrying: {}",
+                binary, attempt, error
+            ));
+            attempt += 1;
+            thread::sleep(SERVER_START_DELAY);
+        }
+    }
+
+    /// Runs one perftest test against a peer, or in loopback through the device.
+    fn run_test(
+        &self,
+        (binary, options): (&str, &[&str]),
+        device: &str,
+        port: u32,
+        peer: Option<&str>,
+        cancel: &CancellationToken,
+    ) -> Result<String, String> {
+        if cancel.is_cancelled() {
+            return Err("RDMA benchmark cancelled".to_string());
+        }
+        if let Some(peer) = peer {
+            return self.run_client(
+                binary,
+                &Self::perftest_args(device, port, options, Some(peer)),
+                cancel,
+            );
+        }
+
+        let server_args = Self::perftest_args(device, port, options, None);
+        let mut server = Command::new(binary)
+            .args(&server_args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to run {} (is perftest installed?): {}", binary, e))?;
+        thread::sleep(SERVER_START_DELAY);
+        let output = self.run_client(
+            binary,
+            &Self::perftest_args(device, port, options, Some("localhost")),
+            cancel,
+        );
+        // A server whose client never connected would wait forever.
+        if output.is_err() {
+            let _ = server.kill();
+        }
+        let _ = server.wait();
+        output
+    }
+}
+
+// Implement the `RdmaPort` trait for `RdmaAdapter`.
+impl RdmaPort for RdmaAdapter {
+    fn discover_rdma_devices(&self) -> Result<Vec<RdmaDevice>, String> {
+        // The class only exists once an RDMA driver is loaded, so a machine without one has no devices.
+        if !Path::new(SYSFS_INFINIBAND_ROOT).exists() {
+            return Ok(Vec::new());
+        }
+        let entries = fs::read_dir(SYSFS_INFINIBAND_ROOT)
+            .map_err(|e| format!("Failed to read {}: {}", SYSFS_INFINIBAND_ROOT, e))?;
+        let mut devices: Vec<RdmaDevice> = entries
+            .flatten()
+            .map(|entry| {
+                let path = entry.path();
+                let read = |name: &str| {
+                    read_sysfs(path.join(name)).unwrap_or_else(|| "unknown".to_string())
+                };
+                RdmaDevice {
+                    name: entry.file_name().to_string_lossy().to_string(),
+                    hca_type: read("hca_type"),
+                    board_id: read("board_id"),
+                    firmware: read("fw_ver"),
+                    node_guid: read("node_guid"),
+                    ports: Self::read_ports(&path),
+                }
+            })
+            .collect();
+        devices.sort_by(|a, b| a.name.cmp(&b.name));
+
+        for device in &devices {
+            for port in device.ports.iter().filter(|port| !port.is_active()) {
+                self.logger.log_warn(&format!(
+                    "RDMA port {} of {} is not active: {}, {}",
+                    port.number, device.name, port.state, port.phys_state
+                ));
+            }
+        }
+        Ok(devices)
+    }
+
+    fn run_rdma_benchmark(
+        &self,
+        device: &str,
+        port: u32,
+        peer: Option<&str>,
+        cancel: &CancellationToken,
+    ) -> Result<RdmaBenchmarkResult, String> {
+        let bandwidth_output = self.run_test(BANDWIDTH_TEST, device, port, peer, cancel)?;
+        let bandwidth = parse_perftest_bandwidth(&bandwidth_output)
+            .ok_or_else(|| format!("No result in the {} output", BANDWIDTH_TEST.0))?;
+        // The peer starts its latency server once the bandwidth test has finished.
+        if peer.is_some() {
+            thread::sleep(SERVER_START_DELAY);
+        }
+        let latency_output = self.run_test(LATENCY_TEST, device, port, peer, cancel)?;
+        let latency = parse_perftest_latency(&latency_output)
+            .ok_or_else(|| format!("No result in the {} output", LATENCY_TEST.0))?;
+
+        let link_gbit_s = self
+            .discover_rdma_devices()
+            .ok()
+            .and_then(|devices| devices.into_iter().find(|d| d.name == device))
+            .and_then(|d| d.ports.into_iter().find(|p| p.number == port))
+            .and_then(|p| p.rate_gbit_s());
+        Ok(RdmaBenchmarkResult {
+            device: device.to_string(),
+            peer: peer.map(str::to_string),
+            link_gbit_s,
+            bandwidth,
+            latency,
+        })
+    }
+
+    fn serve_rdma_benchmark(
+        &self,
+        device: &str,
+        port: u32,
+        cancel: &CancellationToken,
+    ) -> Result<(), String> {
+        for (binary, options) in [BANDWIDTH_TEST, LATENCY_TEST] {
+            if cancel.is_cancelled() {
+                return Err("RDMA benchmark cancelled".to_string());
+            }
+            self.logger.log_info(&format!(
+                "Serving {} on {} port {}; waiting for the peer.",
+                binary, device, port
+            ));
+            let output =
+                self.run_perftest(binary, &Self::perftest_args(device, port, options, None))?;
+            if !output.status.success() {
+                return Err(format!(
+                    "{} failed: {}",
+                    binary,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+        }
+        Ok(())
+    }
+}
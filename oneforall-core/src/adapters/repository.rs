@@ -0,0 +1,568 @@
+//! Repository
+//!
+//! This module provides typed access to the records kept behind `DatabasePort`:
+//! run results, collector readings and hardware inventories. It owns the key
+//! schema of `domain::database` and the JSON encoding of the records, so the
+//! code storing or reading them never builds keys by hand. Results are stored
+//! with the tags of the instance, and can be found by them. They are stored
+//! at the current schema version and migrated to it when read, so results
+//! written by older versions of OneForAll stay readable.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::domain::database::{
+    key_time, validate_collector, OrderedSample, RunKey, SampleKey, StoredSample,
+    CHECKPOINT_PREFIX, HEALTH_PROBE_KEY, INVENTORY_PREFIX,
+};
+use crate::domain::grpc::RESULT_KINDS;
+use crate::domain::schema::{
+    legacy_run_key, migrate, schema_version, versioned, MigrationSummary, CURRENT_SCHEMA_VERSION,
+};
+use crate::domain::tags::{has_tags, tagged, Tags};
+use crate::ports::database_port::DatabasePort;
+
+/// Stores and reads typed records through a `DatabasePort`.
+#[derive(Clone)]
+pub struct Repository {
+    database: Arc<dyn DatabasePort>, // inject the database port
+    tags: Tags,                      // attached to every stored result
+}
+
+/// Parses the start time of a run, as found in reports and jobs.
+fn parse_started_at(started_at: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(started_at)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|e| format!("Invalid start time '{}': {}", started_at, e))
+}
+
+/// Decodes a stored result of a kind, migrating it to the current schema version first.
+fn decode_run<T: DeserializeOwned>(kind: &str, key: &str, value: &[u8]) -> Result<T, String> {
+    let result = serde_json::from_slice(value).map_err(|e| format!("Invalid {}: {}", key, e))?;
+    let result = migrate(kind, result).map_err(|e| format!("Cannot read {}: {}", key, e))?;
+    serde_json::from_value(result).map_err(|e| format!("Invalid {}: {}", key, e))
+}
+
+impl Repository {
+    /// Creates a new instance of `Repository`.
+    ///
+    /// # Arguments
+    ///
+    /// * `database` - Where the records are kept.
+    ///
+    /// # Returns
+    ///
+    /// * `Repository` - The repository.
+    pub fn new(database: Arc<dyn DatabasePort>) -> Self {
+        Repository {
+            database,
+            tags: Tags::new(),
+        }
+    }
+
+    /// Attaches tags to every result stored from now on, e.g. the rack and asset ID
+    /// given with `--tag`.
+    pub fn with_tags(mut self, tags: Tags) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Stores the result of a run.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The kind of run, one of `RESULT_KINDS`.
+    /// * `started_at` - When the run started, in RFC 3339 format.
+    /// * `name` - What distinguishes runs of the same kind, e.g. the profile name or the job ID.
+    /// * `result` - The result, stored with the tags of the repository and the schema version.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String, String>` - The key the result is stored under, or why it was not stored.
+    pub fn store_run<T: Serialize>(
+        &self,
+        kind: &str,
+        started_at: &str,
+        name: Option<&str>,
+        result: &T,
+    ) -> Result<String, String> {
+        if !RESULT_KINDS.contains(&kind) {
+            return Err(format!(
+                "Unknown result kind '{}'; expected one of {}",
+                kind,
+                RESULT_KINDS.join(", ")
+            ));
+        }
+        let key = RunKey {
+            kind: kind.to_string(),
+            started_at: parse_started_at(started_at)?,
+            name: name.map(str::to_string),
+        }
+        .encode();
+        let result =
+            tagged(result, &self.tags).map_err(|e| format!("Failed to encode {}: {}", key, e))?;
+        self.put(&key, &versioned(result))?;
+        Ok(key)
+    }
+
+    /// Reads the result stored under a key, migrated to the current schema version.
+    pub fn get_run<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, String> {
+        let value = self
+            .database
+            .get(key.as_bytes())
+            .map_err(|e| format!("Failed to read {}: {}", key, e))?;
+        let kind = key.split(':').next().unwrap_or_default();
+        value.map(|value| decode_run(kind, key, &value)).transpose()
+    }
+
+    /// Lists the keys of the stored results of a kind carrying every tag of a
+    /// filter, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The kind of run, one of `RESULT_KINDS`.
+    /// * `from` - The earliest start time included; the first run when `None`.
+    /// * `to` - The start time the range ends before; open-ended when `None`.
+    /// * `tags` - The tags the results must carry; every result when empty.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<String>, String>` - The keys, or why the results could not be read.
+    pub fn find_runs(
+        &self,
+        kind: &str,
+        from: Option<&DateTime<Utc>>,
+        to: Option<&DateTime<Utc>>,
+        tags: &Tags,
+    ) -> Result<Vec<String>, String> {
+        let runs: Vec<(String, serde_json::Value)> = if from.is_none() && to.is_none() {
+            // Without a time range, results stored before keys carried their start
+            // time in UTC are found too.
+            self.database
+                .scan_prefix(format!("{}:", kind).as_bytes())
+                .map_err(|e| format!("Failed to read the {} results: {}", kind, e))?
+                .into_iter()
+                .map(|(key, value)| {
+                    let key = String::from_utf8_lossy(&key).into_owned();
+                    serde_json::from_slice(&value)
+                        .map(|result| (key.clone(), result))
+                        .map_err(|e| format!("Invalid {}: {}", key, e))
+                })
+                .collect::<Result<_, _>>()?
+        } else {
+            self.get_runs_in_range(kind, from, to)?
+                .into_iter()
+                .map(|(key, result)| (key.encode(), result))
+                .collect()
+        };
+        Ok(runs
+            .into_iter()
+            .filter(|(_, result)| has_tags(result, tags))
+            .map(|(key, _)| key)
+            .collect())
+    }
+
+    /// Reads the results of the runs of a kind started in a time range, oldest
+    /// first, migrated to the current schema version.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The kind of run, one of `RESULT_KINDS`.
+    /// * `from` - The earliest start time included; the first run when `None`.
+    /// * `to` - The start time the range ends before; open-ended when `None`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<(RunKey, T)>, String>` - The results with their keys, or why they could not be read.
+    pub fn get_runs_in_range<T: DeserializeOwned>(
+        &self,
+        kind: &str,
+        from: Option<&DateTime<Utc>>,
+        to: Option<&DateTime<Utc>>,
+    ) -> Result<Vec<(RunKey, T)>, String> {
+        let (start, end) = RunKey::range(kind, from, to);
+        let pairs = self
+            .database
+            .scan_range(start.as_bytes(), end.as_bytes())
+            .map_err(|e| format!("Failed to read the {} results: {}", kind, e))?;
+        pairs
+            .into_iter()
+            .filter_map(|(key, value)| {
+                let key = RunKey::parse(&String::from_utf8_lossy(&key))?;
+                Some(decode_run(kind, &key.encode(), &value).map(|result| (key, result)))
+            })
+            .collect()
+    }
+
+    /// Rewrites every stored result at the current schema version, moving the
+    /// results stored under keys of the earliest schema to their current keys.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<MigrationSummary, String>` - What was migrated and what could
+    ///   not be, or why the results could not be read or written.
+    pub fn migrate_runs(&self) -> Result<MigrationSummary, String> {
+        let mut summary = MigrationSummary::default();
+        for kind in RESULT_KINDS {
+            let pairs = self
+                .database
+                .scan_prefix(format!("{}:", kind).as_bytes())
+                .map_err(|e| format!("Failed to read the {} results: {}", kind, e))?;
+            for (key, value) in pairs {
+                let key = String::from_utf8_lossy(&key).into_owned();
+                let result: serde_json::Value = match serde_json::from_slice(&value) {
+                    Ok(result) => result,
+                    Err(e) => {
+                        summary.failed.push(format!("{}: {}", key, e));
+                        continue;
+                    }
+                };
+                let target = match RunKey::parse(&key) {
+                    Some(_) => key.clone(),
+                    None => match legacy_run_key(kind, &result) {
+                        Some(run_key) => run_key.encode(),
+                        None => {
+                            summary.failed.push(format!("{}: no valid start time", key));
+                            continue;
+                        }
+                    },
+                };
+                if target == key && schema_version(&result) == CURRENT_SCHEMA_VERSION {
+                    summary.current += 1;
+                    continue;
+                }
+                let result = match migrate(kind, result) {
+                    Ok(result) => result,
+                    Err(e) => {
+                        summary.failed.push(format!("{}: {}", key, e));
+                        continue;
+                    }
+                };
+                self.put(&target, &result)?;
+                if target != key {
+                    self.database
+                        .remove(key.as_bytes())
+                        .map_err(|e| format!("Failed to delete {}: {}", key, e))?;
+                    summary.rekeyed += 1;
+                }
+                summary.upgraded += 1;
+            }
+        }
+        Ok(summary)
+    }
+
+    /// Stores readings of a collector, such as the power meter, in a single write.
+    ///
+    /// # Arguments
+    ///
+    /// * `collector` - The collector, e.g. "power".
+    /// * `samples` - The readings with the time each is ordered at and its timestamps.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), String>` - Whether the readings were stored.
+    pub fn store_sample_batch<T: Serialize>(
+        &self,
+        collector: &str,
+        samples: &[OrderedSample<T>],
+    ) -> Result<(), String> {
+        validate_collector(collector)?;
+        let pairs = samples
+            .iter()
+            .map(|(recorded_at, sample)| {
+                let key = SampleKey {
+                    collector: collector.to_string(),
+                    recorded_at: *recorded_at,
+                }
+                .encode();
+                serde_json::to_vec(sample)
+                    .map(|json| (key.clone().into_bytes(), json))
+                    .map_err(|e| format!("Failed to encode {}: {}", key, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.database
+            .insert_batch(&pairs)
+            .map_err(|e| format!("Failed to store the {} readings: {}", collector, e))
+    }
+
+    /// Reads the readings of a collector taken in a time range, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `collector` - The collector, e.g. "power".
+    /// * `from` - The earliest time included; the first reading when `None`.
+    /// * `to` - The time the range ends before; open-ended when `None`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<OrderedSample<T>>, String>` - The readings with the
+    ///   time each is ordered at and its timestamps.
+    pub fn get_samples_in_range<T: DeserializeOwned>(
+        &self,
+        collector: &str,
+        from: Option<&DateTime<Utc>>,
+        to: Option<&DateTime<Utc>>,
+    ) -> Result<Vec<OrderedSample<T>>, String> {
+        validate_collector(collector)?;
+        let (start, end) = SampleKey::range(collector, from, to);
+        let pairs = self
+            .database
+            .scan_range(start.as_bytes(), end.as_bytes())
+            .map_err(|e| format!("Failed to read the {} readings: {}", collector, e))?;
+        pairs
+            .into_iter()
+            .filter_map(|(key, value)| {
+                let key = SampleKey::parse(&String::from_utf8_lossy(&key))?;
+                // Readings stored before their timestamps were kept are the bare reading.
+                let stored = serde_json::from_slice::<StoredSample<T>>(&value).or_else(|_| {
+                    serde_json::from_slice(&value).map(|sample| StoredSample { time: None, sample })
+                });
+                Some(
+                    stored
+                        .map(|sample| (key.recorded_at, sample))
+                        .map_err(|e| format!("Invalid {}: {}", key.encode(), e)),
+                )
+            })
+            .collect()
+    }
+
+    /// Deletes the readings of a collector taken before a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `collector` - The collector, e.g. "power".
+    /// * `before` - The time the deleted readings were taken before.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<usize, String>` - How many readings were deleted, or why they were not.
+    pub fn prune_samples(&self, collector: &str, before: &DateTime<Utc>) -> Result<usize, String> {
+        validate_collector(collector)?;
+        let (start, end) = SampleKey::range(collector, None, Some(before));
+        self.database
+            .remove_range(start.as_bytes(), end.as_bytes())
+            .map_err(|e| format!("Failed to delete the {} readings: {}", collector, e))
+    }
+
+    /// Returns the space the database takes on disk, in bytes.
+    pub fn size_on_disk(&self) -> Result<u64, String> {
+        self.database
+            .size_on_disk()
+            .map_err(|e| format!("Failed to read the database size: {}", e))
+    }
+
+    /// Returns the space freed by deleted records to the file system.
+    pub fn compact(&self) -> Result<(), String> {
+        self.database
+            .compact()
+            .map_err(|e| format!("Failed to compact the database: {}", e))
+    }
+
+    /// Reads from the database, to check it is available.
+    pub fn check(&self) -> Result<(), String> {
+        self.database
+            .get(HEALTH_PROBE_KEY.as_bytes())
+            .map(|_| ())
+            .map_err(|e| format!("Failed to read the database: {}", e))
+    }
+
+    /// Writes everything the database still buffers to disk.
+    pub fn flush(&self) -> Result<(), String> {
+        self.database
+            .flush()
+            .map_err(|e| format!("Failed to flush the database: {}", e))
+    }
+
+    /// Stores a hardware inventory.
+    ///
+    /// # Arguments
+    ///
+    /// * `collected_at` - When the inventory was collected.
+    /// * `inventory` - The inventory, e.g. a discovery report.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String, String>` - The key the inventory is stored under, or why it was not stored.
+    pub fn store_inventory<T: Serialize>(
+        &self,
+        collected_at: &DateTime<Utc>,
+        inventory: &T,
+    ) -> Result<String, String> {
+        let key = format!("{}{}", INVENTORY_PREFIX, key_time(collected_at));
+        self.put(&key, inventory)?;
+        Ok(key)
+    }
+
+    /// Reads the hardware inventory collected last.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<(String, T)>, String>` - When the inventory was collected, as it
+    ///   appears in its key, and the inventory; `None` if none is stored.
+    pub fn get_latest_inventory<T: DeserializeOwned>(&self) -> Result<Option<(String, T)>, String> {
+        let inventories = self
+            .database
+            .scan_prefix(INVENTORY_PREFIX.as_bytes())
+            .map_err(|e| format!("Failed to read the inventories: {}", e))?;
+        // Keys hold the time the inventory was collected, so they sort chronologically.
+        let Some((key, value)) = inventories.into_iter().max_by(|a, b| a.0.cmp(&b.0)) else {
+            return Ok(None);
+        };
+        let key = String::from_utf8_lossy(&key).into_owned();
+        let inventory =
+            serde_json::from_slice(&value).map_err(|e| format!("Invalid {}: {}", key, e))?;
+        let collected_at = key
+            .strip_prefix(INVENTORY_PREFIX)
+            .unwrap_or(&key)
+            .to_string();
+        Ok(Some((collected_at, inventory)))
+    }
+
+    /// Reads the hardware inventory collected last before a time, e.g. the start of a run.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The latest time the inventory may have been collected at.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<(String, T)>, String>` - When the inventory was collected, as it
+    ///   appears in its key, and the inventory; `None` if none was collected by then.
+    pub fn get_inventory_at<T: DeserializeOwned>(
+        &self,
+        time: &DateTime<Utc>,
+    ) -> Result<Option<(String, T)>, String> {
+        let latest = format!("{}{}", INVENTORY_PREFIX, key_time(time));
+        let inventories = self
+            .database
+            .scan_prefix(INVENTORY_PREFIX.as_bytes())
+            .map_err(|e| format!("Failed to read the inventories: {}", e))?;
+        let Some((key, value)) = inventories
+            .into_iter()
+            .filter(|(key, _)| key.as_ref() <= latest.as_bytes())
+            .max_by(|a, b| a.0.cmp(&b.0))
+        else {
+            return Ok(None);
+        };
+        let key = String::from_utf8_lossy(&key).into_owned();
+        let inventory =
+            serde_json::from_slice(&value).map_err(|e| format!("Invalid {}: {}", key, e))?;
+        let collected_at = key
+            .strip_prefix(INVENTORY_PREFIX)
+            .unwrap_or(&key)
+            .to_string();
+        Ok(Some((collected_at, inventory)))
+    }
+
+    /// Reads the result stored under a key as it was stored, at the schema
+    /// version it was written at, e.g. to archive it.
+    pub fn get_stored_run(&self, key: &str) -> Result<Option<serde_json::Value>, String> {
+        self.database
+            .get(key.as_bytes())
+            .map_err(|e| format!("Failed to read {}: {}", key, e))?
+            .map(|value| {
+                serde_json::from_slice(&value).map_err(|e| format!("Invalid {}: {}", key, e))
+            })
+            .transpose()
+    }
+
+    /// Stores the result of a run from another database, e.g. of an imported
+    /// archive, under its own key and as it was stored there.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the run.
+    /// * `result` - The result, with its tags and schema version.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), String>` - Whether the result was stored; a run already in the
+    ///   database is not replaced.
+    pub fn import_run(&self, key: &RunKey, result: &serde_json::Value) -> Result<(), String> {
+        let key = key.encode();
+        if self.get_stored_run(&key)?.is_some() {
+            return Err(format!("The run {} is already in the database", key));
+        }
+        self.put(&key, result)
+    }
+
+    /// Stores a record kept with an imported run, e.g. its inventory under
+    /// `ARCHIVED_INVENTORY_PREFIX`.
+    pub fn store_archived<T: Serialize>(
+        &self,
+        prefix: &str,
+        run: &str,
+        record: &T,
+    ) -> Result<(), String> {
+        self.put(&format!("{}{}", prefix, run), record)
+    }
+
+    /// Reads a record kept with an imported run, `None` when the run has none.
+    pub fn get_archived<T: DeserializeOwned>(
+        &self,
+        prefix: &str,
+        run: &str,
+    ) -> Result<Option<T>, String> {
+        let key = format!("{}{}", prefix, run);
+        self.database
+            .get(key.as_bytes())
+            .map_err(|e| format!("Failed to read {}: {}", key, e))?
+            .map(|value| {
+                serde_json::from_slice(&value).map_err(|e| format!("Invalid {}: {}", key, e))
+            })
+            .transpose()
+    }
+
+    /// Saves the checkpoint of an unfinished profile run, replacing the previous one.
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - The profile name; a profile has at most one unfinished run.
+    /// * `checkpoint` - The progress of the run.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), String>` - Whether the checkpoint was saved.
+    pub fn store_checkpoint<T: Serialize>(
+        &self,
+        profile: &str,
+        checkpoint: &T,
+    ) -> Result<(), String> {
+        self.put(&format!("{}{}", CHECKPOINT_PREFIX, profile), checkpoint)
+    }
+
+    /// Reads the checkpoints of every unfinished profile run, by profile name.
+    pub fn get_checkpoints<T: DeserializeOwned>(&self) -> Result<Vec<T>, String> {
+        self.database
+            .scan_prefix(CHECKPOINT_PREFIX.as_bytes())
+            .map_err(|e| format!("Failed to read the checkpoints: {}", e))?
+            .into_iter()
+            .map(|(key, value)| {
+                serde_json::from_slice(&value)
+                    .map_err(|e| format!("Invalid {}: {}", String::from_utf8_lossy(&key), e))
+            })
+            .collect()
+    }
+
+    /// Removes the checkpoint of a profile run once it finished or was discarded.
+    pub fn remove_checkpoint(&self, profile: &str) -> Result<(), String> {
+        let key = format!("{}{}", CHECKPOINT_PREFIX, profile);
+        self.database
+            .remove(key.as_bytes())
+            .map_err(|e| format!("Failed to remove {}: {}", key, e))?;
+        Ok(())
+    }
+
+    /// Stores a record as JSON.
+    fn put<T: Serialize>(&self, key: &str, record: &T) -> Result<(), String> {
+        let json =
+            serde_json::to_vec(record).map_err(|e| format!("Failed to encode {}: {}", key, e))?;
+        self.database
+            .insert(key.as_bytes(), &json)
+            .map_err(|e| format!("Failed to store {}: {}", key, e))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+//! Result Exporter
+//!
+//! This module uploads the artifacts of finished runs to the S3-compatible
+//! bucket of the `[export.s3]` configuration, so the qualification history of
+//! a lab machine is kept elsewhere when it is reimaged. A failed upload is
+//! logged but never changes the outcome of the run.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use common::ports::log_port::LoggerPort;
+use common::ports::telemetry_port::TelemetryPort;
+
+use crate::adapters::alert_dispatcher::hostname;
+use crate::adapters::s3_adapter::S3Adapter;
+use crate::adapters::telemetry_recorder::TelemetryRecorder;
+use crate::domain::export::{ExportConfig, RunArtifacts, S3Config};
+use crate::ports::object_store_port::ObjectStorePort;
+
+/// How many times an artifact is sent before giving up on it.
+const UPLOAD_ATTEMPTS: u32 = 3;
+
+/// The pause between two attempts to upload an artifact.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Reads the `[export]` section of the configuration file, along with the
+/// secret access key it names. Credentials that are not configured are taken
+/// from `AWS_ACCESS_KEY_ID` and `AWS_SECRET_ACCESS_KEY`.
+///
+/// # Arguments
+///
+/// * `path` - The configuration file; a missing file means nothing is uploaded.
+///
+/// # Returns
+///
+/// * `Result<ExportConfig, String>` - The export settings, or why they could not be read.
+pub fn load_export_config(path: &Path) -> Result<ExportConfig, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(ExportConfig::default()),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    let mut config =
+        ExportConfig::from_toml(&text).map_err(|e| format!("Invalid {}: {}", path.display(), e))?;
+    if let Some(s3) = &mut config.s3 {
+        if let Some(key_file) = s3.secret_access_key_file.take() {
+            let key = fs::read_to_string(&key_file)
+                .map_err(|e| format!("Failed to read {}: {}", key_file.display(), e))?;
+            s3.secret_access_key = Some(key.trim().to_string());
+        }
+        s3.access_key_id = s3
+            .access_key_id
+            .take()
+            .or_else(|| env::var("AWS_ACCESS_KEY_ID").ok());
+        s3.secret_access_key = s3
+            .secret_access_key
+            .take()
+            .or_else(|| env::var("AWS_SECRET_ACCESS_KEY").ok());
+        if s3.access_key_id.is_none() || s3.secret_access_key.as_deref().unwrap_or("").is_empty() {
+            return Err(format!(
+                "No credentials for the S3 bucket '{}': set `access_key_id` and \
+                 `secret_access_key_file` in [export.s3], or AWS_ACCESS_KEY_ID and \
+                 AWS_SECRET_ACCESS_KEY",
+                s3.bucket
+            ));
+        }
+    }
+    Ok(config)
+}
+
+/// Uploads the artifacts of finished runs.
+pub struct ResultExporter {
+    logger: Arc<dyn LoggerPort>,
+    hostname: String,
+    destination: Option<(S3Config, Box<dyn ObjectStorePort>)>,
+}
+
+impl ResultExporter {
+    /// Creates an exporter for the bucket of the `[export]` configuration, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger` - Logger implementation for logging messages.
+    /// * `config` - The export settings, with their credentials.
+    ///
+    /// # Returns
+    ///
+    /// * `ResultExporter` - The exporter; without a bucket, exporting does nothing.
+    pub fn new(logger: Arc<dyn LoggerPort>, config: &ExportConfig) -> Self {
+        let destination = config.s3.clone().map(|s3| {
+            let store: Box<dyn ObjectStorePort> =
+                Box::new(S3Adapter::new(logger.clone(), s3.clone()));
+            (s3, store)
+        });
+        ResultExporter {
+            logger,
+            hostname: hostname(),
+            destination,
+        }
+    }
+
+    /// Returns whether finished runs are uploaded anywhere.
+    pub fn is_enabled(&self) -> bool {
+        self.destination.is_some()
+    }
+
+    /// Starts recording the telemetry of a run, so its time-series can be
+    /// uploaded with its results; nothing is recorded when nothing is uploaded.
+    pub fn record(&self, telemetry: &Arc<dyn TelemetryPort>) -> Option<TelemetryRecorder> {
+        self.destination
+            .as_ref()
+            .map(|_| TelemetryRecorder::start(telemetry))
+    }
+
+    /// Uploads the artifacts of a finished run, along with the telemetry recorded over it.
+    ///
+    /// # Arguments
+    ///
+    /// * `artifacts` - The results and reports of the run.
+    /// * `recording` - The telemetry recorded since the run started, if any.
+    pub fn export(&self, mut artifacts: RunArtifacts, recording: Option<TelemetryRecorder>) {
+        let Some((config, store)) = &self.destination else {
+            return;
+        };
+        if let Some(recording) = recording {
+            artifacts.add_timeseries(&recording.finish());
+        }
+
+        let mut uploaded = 0;
+        for artifact in &artifacts.artifacts {
+            let key = config.object_key(&self.hostname, &artifacts.run, &artifact.name);
+            let mut attempt = 1;
+            loop {
+                match store.put_object(&key, artifact.content_type, &artifact.body) {
+                    Ok(()) => {
+                        uploaded += 1;
+                        break;
+                    }
+                    Err(e) if attempt < UPLOAD_ATTEMPTS => {
+                        self.logger
+                            .log_debug(&format!("{}; retrying in {:?}", e, RETRY_DELAY));
+                        thread::sleep(RETRY_DELAY);
+                        attempt += 1;
+                    }
+                    Err(e) => {
+                        self.logger.log_warn(&e);
+                        break;
+                    }
+                }
+            }
+        }
+
+        let location = format!(
+            "{}/{}",
+            store.describe(),
+            config.object_key(&self.hostname, &artifacts.run, "")
+        );
+        if uploaded == artifacts.artifacts.len() {
+            self.logger.log_info(&format!(
+                "Uploaded the {} artifact(s) of the run to {}",
+                uploaded, location
+            ));
+        } else {
+            self.logger.log_warn(&format!(
+                "Uploaded only {} of the {} artifact(s) of the run to {}",
+                uploaded,
+                artifacts.artifacts.len(),
+                location
+            ));
+        }
+    }
+}
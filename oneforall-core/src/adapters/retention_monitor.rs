@@ -0,0 +1,363 @@
+//! Retention Monitor
+//!
+//! This module applies the retention policy of `[database.retention]`: it
+//! deletes collector readings older than the maximum age and, when the database
+//! grows past its size limit, shortens the age readings are kept for until it
+//! fits again. Before that, sub-second frames older than `burst_age` are
+//! replaced by a summary per minute, and the readings of the history metrics
+//! are rolled up per minute and per hour, so the history of long ranges
+//! outlives them. The policy is applied on a background thread while the
+//! application runs, and on demand by `db vacuum`.
+
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use chrono::{DateTime, DurationRound, Utc};
+use common::domain::history::{HistoryMetric, HISTORY_METRICS};
+use common::ports::log_port::LoggerPort;
+
+use crate::adapters::history_adapter::metric_values;
+use crate::adapters::repository::Repository;
+use crate::domain::database::{
+    RetentionConfig, StoredSample, COLLECTORS, DEFAULT_SAMPLE_AGE, MIN_SAMPLE_AGE,
+};
+use crate::domain::endurance::format_volume;
+use crate::domain::microburst::{
+    downsample_frames, BurstFrame, BURSTS_COLLECTOR, BURST_SUMMARIES_COLLECTOR,
+    BURST_SUMMARY_WINDOW,
+};
+use crate::domain::profile::format_duration;
+use crate::domain::rollup::{roll_up, Resolution, Rollup};
+
+/// How long after a window ends it is rolled up, so the readings of its last
+/// seconds still waiting in the write buffer are stored by then.
+const ROLLUP_SETTLE: Duration = Duration::from_secs(60);
+
+/// What a vacuum of the database did.
+#[derive(Debug, Clone)]
+pub struct VacuumSummary {
+    /// How many readings were deleted.
+    pub removed: usize,
+    /// The size of the database before, in bytes.
+    pub size_before: u64,
+    /// The size of the database after, in bytes.
+    pub size_after: u64,
+}
+
+impl VacuumSummary {
+    /// Renders the summary as a single line for the terminal.
+    pub fn render(&self) -> String {
+        format!(
+            "Deleted {} readings; the database went from {} to {}.",
+            self.removed,
+            format_volume(self.size_before),
+            format_volume(self.size_after)
+        )
+    }
+}
+
+/// Applies the retention policy of collector readings.
+pub struct RetentionMonitor {
+    logger: Arc<dyn LoggerPort>,
+    repository: Repository,
+    config: RetentionConfig,
+    sample_age: Duration, // shortened by the size limit, never lengthened again
+}
+
+impl RetentionMonitor {
+    /// Creates a new instance of `RetentionMonitor`.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger` - Logger implementation for logging messages.
+    /// * `repository` - Where the readings are kept.
+    /// * `config` - The retention policy.
+    ///
+    /// # Returns
+    ///
+    /// * `RetentionMonitor` - The monitor.
+    pub fn new(
+        logger: Arc<dyn LoggerPort>,
+        repository: Repository,
+        config: RetentionConfig,
+    ) -> Self {
+        let sample_age = config.max_sample_age.unwrap_or(DEFAULT_SAMPLE_AGE);
+        RetentionMonitor {
+            logger,
+            repository,
+            config,
+            sample_age,
+        }
+    }
+
+    /// Applies the policy on a background thread, immediately and then once
+    /// per check interval, until the application exits.
+    pub fn start(mut self) {
+        thread::spawn(move || loop {
+            if let Err(e) = self.apply() {
+                self.logger
+                    .log_warn(&format!("Failed to apply the retention policy: {}", e));
+            }
+            thread::sleep(self.config.check_interval());
+        });
+    }
+
+    /// Applies the policy and returns the space freed to the file system.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<VacuumSummary, String>` - What was deleted, or why the policy could not be applied.
+    pub fn vacuum(&mut self) -> Result<VacuumSummary, String> {
+        let size_before = self.repository.size_on_disk()?;
+        let removed = self.apply()?;
+        self.repository.compact()?;
+        Ok(VacuumSummary {
+            removed,
+            size_before,
+            size_after: self.repository.size_on_disk()?,
+        })
+    }
+
+    /// Deletes the readings older than the age they are kept for and, if the
+    /// database is still over its size limit, shortens that age one step.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<usize, String>` - How many readings were deleted.
+    fn apply(&mut self) -> Result<usize, String> {
+        let mut removed = self.downsample_bursts()?;
+        // Readings are rolled up before any of them is deleted.
+        self.roll_up()?;
+        if self.config.max_sample_age.is_some() {
+            removed += self.prune()?;
+        }
+
+        let Some(max_size) = self.config.max_size else {
+            return Ok(removed);
+        };
+        let size = self.repository.size_on_disk()?;
+        if size <= max_size {
+            return Ok(removed);
+        }
+        if self.sample_age <= MIN_SAMPLE_AGE {
+            self.logger.log_warn(&format!(
+                "The database takes {}, over its limit of {}, though only readings of the last {} are kept.",
+                format_volume(size),
+                format_volume(max_size),
+                format_duration(self.sample_age)
+            ));
+            return Ok(removed);
+        }
+
+        // The database only shrinks once it is compacted, so the age is
+        // shortened one step per check rather than until it fits.
+        self.sample_age = (self.sample_age / 2).max(MIN_SAMPLE_AGE);
+        removed += self.prune()?;
+        self.repository.compact()?;
+        self.logger.log_info(&format!(
+            "The database took {}, over its limit of {}; readings are now kept for {}.",
+            format_volume(size),
+            format_volume(max_size),
+            format_duration(self.sample_age)
+        ));
+        Ok(removed)
+    }
+
+    /// Replaces the sub-second frames older than `burst_age` with a summary per
+    /// minute, leaving the minute the cutoff falls in whole.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<usize, String>` - How many frames were replaced.
+    fn downsample_bursts(&self) -> Result<usize, String> {
+        let age = chrono::Duration::from_std(self.config.burst_age()).map_err(|e| e.to_string())?;
+        let window = chrono::Duration::from_std(BURST_SUMMARY_WINDOW).map_err(|e| e.to_string())?;
+        let before = (Utc::now() - age)
+            .duration_trunc(window)
+            .map_err(|e| e.to_string())?;
+        let frames = self.repository.get_samples_in_range::<BurstFrame>(
+            BURSTS_COLLECTOR,
+            None,
+            Some(&before),
+        )?;
+        if frames.is_empty() {
+            return Ok(0);
+        }
+
+        let frames: Vec<_> = frames
+            .into_iter()
+            .map(|(taken, stored)| (taken, stored.sample))
+            .collect();
+        let summaries: Vec<_> = downsample_frames(&frames, BURST_SUMMARY_WINDOW)
+            .into_iter()
+            .map(|(start, summary)| {
+                (
+                    start,
+                    StoredSample {
+                        time: None,
+                        sample: summary,
+                    },
+                )
+            })
+            .collect();
+        // The summaries are stored before the frames are deleted, so a failure
+        // in between leaves both rather than neither.
+        self.repository
+            .store_sample_batch(BURST_SUMMARIES_COLLECTOR, &summaries)?;
+        let removed = self.repository.prune_samples(BURSTS_COLLECTOR, &before)?;
+        self.logger.log_info(&format!(
+            "Downsampled {} sub-second frames taken before {} to {} summaries.",
+            removed,
+            before.to_rfc3339(),
+            summaries.len()
+        ));
+        Ok(removed)
+    }
+
+    /// Rolls up the readings of the history metrics taken in every minute and every
+    /// hour since the last rollups, and deletes the minute rollups older than
+    /// `rollup_age`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<usize, String>` - How many rollups were stored.
+    fn roll_up(&self) -> Result<usize, String> {
+        let now = Utc::now();
+        let age =
+            chrono::Duration::from_std(self.config.rollup_age()).map_err(|e| e.to_string())?;
+        let mut stored = 0;
+        for metric in HISTORY_METRICS.iter().filter(|metric| metric.rolled_up) {
+            for resolution in Resolution::ALL {
+                stored += self.roll_up_metric(metric, resolution, now)?;
+            }
+            self.repository
+                .prune_samples(&Resolution::Minute.collector(metric.name), &(now - age))?;
+        }
+        if stored > 0 {
+            self.logger.log_debug(&format!(
+                "Stored {} rollups of the history metrics.",
+                stored
+            ));
+        }
+        Ok(stored)
+    }
+
+    /// Rolls up the readings of one metric taken in every complete window since its
+    /// last rollup, or in the age readings are kept for if it has none yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `metric` - The metric, which is rolled up.
+    /// * `resolution` - The window each rollup covers.
+    /// * `now` - The time the pass started at.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<usize, String>` - How many rollups were stored.
+    fn roll_up_metric(
+        &self,
+        metric: &HistoryMetric,
+        resolution: Resolution,
+        now: DateTime<Utc>,
+    ) -> Result<usize, String> {
+        let window = chrono::Duration::from_std(resolution.window()).map_err(|e| e.to_string())?;
+        let settle = chrono::Duration::from_std(ROLLUP_SETTLE).map_err(|e| e.to_string())?;
+        let collector = resolution.collector(metric.name);
+        let end = (now - settle)
+            .duration_trunc(window)
+            .map_err(|e| e.to_string())?;
+        // Minute rollups are not backfilled past the age they are kept for, or
+        // they would be stored and deleted again on every pass.
+        let lookback = match resolution {
+            Resolution::Minute => self.sample_age.min(self.config.rollup_age()),
+            Resolution::Hour => self.sample_age,
+        };
+        let oldest = (end - chrono::Duration::from_std(lookback).map_err(|e| e.to_string())?)
+            .duration_trunc(window)
+            .map_err(|e| e.to_string())?;
+        let mut start = self
+            .rolled_up_to(&collector, window, end, oldest)?
+            .unwrap_or(oldest);
+
+        // A day of readings at most is held at once, however far behind the rollups are.
+        let mut stored = 0;
+        while start < end {
+            let until = (start + chrono::Duration::days(1)).min(end);
+            let values = metric_values(&self.repository, metric, Some(&start), Some(&until))?;
+            let rollups: Vec<_> = roll_up(&values, resolution)
+                .into_iter()
+                .map(|(window_start, rollup)| {
+                    (
+                        window_start,
+                        StoredSample {
+                            time: None,
+                            sample: rollup,
+                        },
+                    )
+                })
+                .collect();
+            if !rollups.is_empty() {
+                self.repository.store_sample_batch(&collector, &rollups)?;
+                stored += rollups.len();
+            }
+            start = until;
+        }
+        Ok(stored)
+    }
+
+    /// Finds where the rollups of a collector end, looking back from the end of the
+    /// last complete window over ever longer stretches back to `oldest`, so a regular
+    /// pass reads a few rollups rather than all of them.
+    ///
+    /// # Arguments
+    ///
+    /// * `collector` - The collector of the rollups, e.g. "rollup_1m_cpu".
+    /// * `window` - The time each rollup covers.
+    /// * `end` - The end of the last complete window.
+    /// * `oldest` - The earliest time looked back to.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<DateTime<Utc>>, String>` - The end of the window of the latest
+    ///   rollup, or `None` if there is none since `oldest`.
+    fn rolled_up_to(
+        &self,
+        collector: &str,
+        window: chrono::Duration,
+        end: DateTime<Utc>,
+        oldest: DateTime<Utc>,
+    ) -> Result<Option<DateTime<Utc>>, String> {
+        let mut from = (end - window * 4).max(oldest);
+        loop {
+            let rollups =
+                self.repository
+                    .get_samples_in_range::<Rollup>(collector, Some(&from), None)?;
+            if let Some((start, _)) = rollups.last() {
+                return Ok(Some(*start + window));
+            }
+            if from <= oldest {
+                return Ok(None);
+            }
+            from = (end - (end - from) * 4).max(oldest);
+        }
+    }
+
+    /// Deletes the readings of every collector older than the age they are kept for.
+    fn prune(&self) -> Result<usize, String> {
+        let age = chrono::Duration::from_std(self.sample_age).map_err(|e| e.to_string())?;
+        let before = chrono::Utc::now() - age;
+        let mut removed = 0;
+        for collector in COLLECTORS {
+            removed += self.repository.prune_samples(collector, &before)?;
+        }
+        if removed > 0 {
+            self.logger.log_info(&format!(
+                "Deleted {} readings taken before {}.",
+                removed,
+                before.to_rfc3339()
+            ));
+        }
+        Ok(removed)
+    }
+}
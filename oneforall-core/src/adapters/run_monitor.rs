@@ -0,0 +1,321 @@
+//! Run Monitor
+//!
+//! This module bundles the telemetry collectors that sample the machine while
+//! a stress test or benchmark is running, along with the kernel log watcher,
+//! the hardware performance counters and the System Event Log of the BMC, and
+//! renders their findings into the report printed once the run has finished.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Local;
+use common::domain::telemetry::PowerSample;
+use common::ports::log_port::LoggerPort;
+use common::ports::telemetry_port::TelemetryPort;
+
+use crate::adapters::background_sampler::BackgroundSampler;
+use crate::adapters::battery_adapter::BatteryAdapter;
+use crate::adapters::frequency_adapter::FrequencyAdapter;
+use crate::adapters::hardware_error_adapter::HardwareErrorAdapter;
+use crate::adapters::interrupt_adapter::InterruptAdapter;
+use crate::adapters::kernel_log_watcher::KernelLogWatcher;
+use crate::adapters::perf_counter_adapter::PerfCounterAdapter;
+use crate::adapters::power_adapter::{PowerAdapter, PowerMeter};
+use crate::adapters::powermetrics_adapter::PowermetricsAdapter;
+use crate::adapters::thermal_adapter::ThermalAdapter;
+use crate::domain::anomaly::{detect_anomalies, format_anomaly_report};
+use crate::domain::battery::{format_battery_report, PowerSupplyState};
+use crate::domain::cpu_frequency::{format_frequency_report, CoreFrequency};
+use crate::domain::interrupts::{format_interrupt_report, CpuInterrupts};
+use crate::domain::kernel_log::KernelLogFindings;
+use crate::domain::perf_counters::format_counter_report;
+use crate::domain::power::PowerSummary;
+use crate::domain::powermetrics::{format_powermetrics_report, PowermetricsSample};
+use crate::domain::sel::{SelEntry, SelFindings};
+use crate::domain::thermal::{format_thermal_report, ThermalSample};
+use crate::ports::battery_port::BatteryPort;
+use crate::ports::frequency_port::FrequencyPort;
+use crate::ports::hardware_error_port::HardwareErrorPort;
+use crate::ports::interrupt_port::InterruptPort;
+use crate::ports::perf_counter_port::PerfCounterPort;
+use crate::ports::powermetrics_port::PowermetricsPort;
+use crate::ports::thermal_port::ThermalPort;
+
+/// Default interval between two telemetry samples.
+pub const DEFAULT_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The telemetry collected over a whole run.
+pub struct RunTelemetry {
+    /// The rendered telemetry report.
+    pub report: String,
+
+    /// The power drawn over the run, if any power source reported.
+    pub power: Option<PowerSummary>,
+
+    /// The hottest temperature seen over the run, if any sensor reported.
+    pub peak_celsius: Option<f64>,
+
+    /// The kernel log events seen over the run.
+    pub kernel_log: KernelLogFindings,
+
+    /// The entries the BMC added to its event log over the run.
+    pub sel: SelFindings,
+}
+
+/// Collects telemetry in the background for the duration of a run.
+pub struct RunMonitor {
+    logger: Arc<dyn LoggerPort>,
+    frequency: BackgroundSampler<Vec<CoreFrequency>>,
+    power: BackgroundSampler<PowerSample>,
+    thermal: BackgroundSampler<ThermalSample>,
+    battery: BackgroundSampler<PowerSupplyState>,
+    powermetrics: Option<BackgroundSampler<PowermetricsSample>>,
+    interrupts: BackgroundSampler<Vec<CpuInterrupts>>,
+    perf_counters: Option<PerfCounterAdapter>,
+    kernel_log: KernelLogWatcher,
+    hardware_errors: HardwareErrorAdapter,
+    sel_before: Result<Vec<SelEntry>, String>,
+}
+
+impl RunMonitor {
+    /// Starts every telemetry collector.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger` - Logger implementation for logging messages.
+    /// * `telemetry` - Receives live readings so they can be served by the REST API.
+    /// * `interval` - The time between two samples of each collector.
+    ///
+    /// # Returns
+    ///
+    /// * `RunMonitor` - A handle used to stop the collectors and build the report.
+    pub fn start(
+        logger: Arc<dyn LoggerPort>,
+        telemetry: Arc<dyn TelemetryPort>,
+        interval: Duration,
+    ) -> Self {
+        logger.log_debug(&format!(
+            "Starting run telemetry with a {} ms sampling interval",
+            interval.as_millis()
+        ));
+
+        let frequency_adapter = FrequencyAdapter::new(logger.clone());
+        let frequency = BackgroundSampler::start(interval, move || {
+            frequency_adapter.sample_frequencies().ok()
+        });
+
+        // Apple Silicon has no RAPL counters; powermetrics reports its power instead,
+        // along with the activity of each class of cores and the thermal pressure.
+        let powermetrics = cfg!(target_os = "macos").then(|| {
+            let powermetrics_adapter = PowermetricsAdapter::new(logger.clone());
+            let telemetry = telemetry.clone();
+            BackgroundSampler::start(interval, move || {
+                let sample = powermetrics_adapter.sample_powermetrics(interval).ok()?;
+                if let Some(power) = sample.power_sample() {
+                    telemetry.publish_power(power);
+                }
+                Some(sample)
+            })
+        });
+
+        let mut power_meter = PowerMeter::new(Box::new(PowerAdapter::new(logger.clone())));
+        let power_telemetry = telemetry.clone();
+        let power = BackgroundSampler::start(interval, move || {
+            let sample = power_meter.sample()?;
+            power_telemetry.publish_power(sample.clone());
+            Some(sample)
+        });
+
+        let thermal_adapter = ThermalAdapter::new(logger.clone());
+        let thermal = BackgroundSampler::start(interval, move || {
+            let sample = thermal_adapter.sample_thermals().ok()?;
+            telemetry.publish_metrics(sample.metrics(&Local::now().to_rfc3339()));
+            Some(sample)
+        });
+
+        // Only kept on machines with a battery, to show how the run fared unplugged.
+        let battery_adapter = BatteryAdapter::new(logger.clone());
+        let battery = BackgroundSampler::start(interval, move || {
+            battery_adapter
+                .read_power_supplies()
+                .ok()
+                .filter(|state| !state.batteries.is_empty())
+        });
+
+        // Only rendered when the run loaded the network, to show how its interrupts were spread.
+        let interrupt_adapter = InterruptAdapter::new(logger.clone());
+        let interrupts =
+            BackgroundSampler::start(interval, move || interrupt_adapter.sample_interrupts().ok());
+
+        // Instructions per cycle and miss rates explain a low score, so count them
+        // wherever the CPU exposes its PMU; virtual machines often do not.
+        let perf_counter_adapter = PerfCounterAdapter::new(logger.clone());
+        let perf_counters = match perf_counter_adapter.start() {
+            Ok(()) => Some(perf_counter_adapter),
+            Err(e) => {
+                logger.log_debug(&format!("Not counting hardware events: {}", e));
+                None
+            }
+        };
+
+        let kernel_log = KernelLogWatcher::start(logger.clone());
+
+        // The BMC logs faults the kernel never sees, so its event log is compared
+        // with this reading once the run ends.
+        let hardware_errors = HardwareErrorAdapter::new(logger.clone());
+        let sel_before = hardware_errors.read_sel();
+
+        RunMonitor {
+            logger,
+            frequency,
+            power,
+            thermal,
+            battery,
+            powermetrics,
+            interrupts,
+            perf_counters,
+            kernel_log,
+            hardware_errors,
+            sel_before,
+        }
+    }
+
+    /// Stops every collector and renders the telemetry report for the run.
+    pub fn finish(self) -> RunTelemetry {
+        let frequency_samples = self.frequency.stop();
+        let powermetrics_samples = self
+            .powermetrics
+            .map(BackgroundSampler::stop)
+            .unwrap_or_default();
+        let mut power_samples = self.power.stop();
+        if power_samples.is_empty() {
+            power_samples = powermetrics_samples
+                .iter()
+                .filter_map(|(at, sample)| Some((*at, sample.power_sample()?)))
+                .collect();
+        }
+        let thermal_samples = self.thermal.stop();
+        let battery_samples = self.battery.stop();
+        let interrupt_samples = self.interrupts.stop();
+        let core_counters = self
+            .perf_counters
+            .map(|adapter| adapter.finish())
+            .transpose()
+            .unwrap_or_else(|e| {
+                self.logger
+                    .log_warn(&format!("Failed to read the hardware counters: {}", e));
+                None
+            });
+        let kernel_log = self.kernel_log.finish();
+        let sel_after = match &self.sel_before {
+            Ok(_) => self.hardware_errors.read_sel(),
+            Err(reason) => Err(reason.clone()),
+        };
+        let sel = SelFindings::compare(&self.sel_before, &sel_after);
+        self.logger.log_debug(&format!(
+            "Run telemetry collected {} frequency, {} power and {} thermal samples",
+            frequency_samples.len(),
+            power_samples.len(),
+            thermal_samples.len()
+        ));
+
+        let mut report = format_frequency_report(&frequency_samples);
+        let power = PowerSummary::from_samples(&power_samples);
+        match &power {
+            Some(summary) => {
+                report.push('\n');
+                report.push_str(&summary.render());
+            }
+            None => report
+                .push_str("\nPower consumption: no RAPL, AMD energy, powermetrics or BMC readings available.\n"),
+        }
+        report.push('\n');
+        report.push_str(&format_thermal_report(&thermal_samples));
+        let powermetrics_report = format_powermetrics_report(&powermetrics_samples);
+        if !powermetrics_report.is_empty() {
+            report.push('\n');
+            report.push_str(&powermetrics_report);
+        }
+
+        // Laptop firmware often caps the CPU once unplugged, so compare the clocks
+        // reached on battery with those reached on AC.
+        let average_clocks: Vec<(Duration, f64)> = frequency_samples
+            .iter()
+            .filter_map(|(at, cores)| {
+                let readings: Vec<f64> = cores
+                    .iter()
+                    .filter_map(|c| c.frequency_mhz)
+                    .map(f64::from)
+                    .collect();
+                (!readings.is_empty())
+                    .then(|| (*at, readings.iter().sum::<f64>() / readings.len() as f64))
+            })
+            .collect();
+        let base_mhz = frequency_samples
+            .iter()
+            .flat_map(|(_, cores)| cores.iter().filter_map(|c| c.base_frequency_mhz))
+            .max()
+            .map(f64::from);
+        let battery_report = format_battery_report(&battery_samples, &average_clocks, base_mhz);
+        if !battery_report.is_empty() {
+            report.push('\n');
+            report.push_str(&battery_report);
+        }
+
+        // Flag readings that stand out from the rest of the run, such as a sudden
+        // temperature jump or clock drop, even when they stay within every limit.
+        let temperatures: Vec<(Duration, f64)> = thermal_samples
+            .iter()
+            .filter_map(|(at, sample)| Some((*at, sample.hottest()?)))
+            .collect();
+        let watts: Vec<(Duration, f64)> = power_samples
+            .iter()
+            .filter_map(|(at, sample)| Some((*at, sample.package_watts().or(sample.bmc_watts)?)))
+            .collect();
+        let clocks: Vec<(Duration, f64)> = frequency_samples
+            .iter()
+            .filter_map(|(at, cores)| {
+                let lowest = cores.iter().filter_map(|c| c.frequency_mhz).min()?;
+                Some((*at, lowest as f64))
+            })
+            .collect();
+        let mut anomalies = detect_anomalies("hottest temperature", "°C", 1.0, &temperatures);
+        anomalies.extend(detect_anomalies("power", "W", 5.0, &watts));
+        anomalies.extend(detect_anomalies("lowest core clock", "MHz", 50.0, &clocks));
+        anomalies.sort_by_key(|anomaly| anomaly.elapsed);
+        let anomaly_report = format_anomaly_report(&anomalies);
+        if !anomaly_report.is_empty() {
+            report.push('\n');
+            report.push_str(&anomaly_report);
+        }
+        let peak_celsius = thermal_samples
+            .iter()
+            .filter_map(|(_, sample)| sample.hottest())
+            .reduce(f64::max);
+        let interrupt_report = format_interrupt_report(&interrupt_samples);
+        if !interrupt_report.is_empty() {
+            report.push('\n');
+            report.push_str(&interrupt_report);
+        }
+        match core_counters.as_deref().map(format_counter_report) {
+            Some(counter_report) if !counter_report.is_empty() => {
+                report.push('\n');
+                report.push_str(&counter_report);
+            }
+            _ => report.push_str(
+                "\nHardware performance counters: none available, e.g. in a virtual machine without a PMU.\n",
+            ),
+        }
+        report.push('\n');
+        report.push_str(&kernel_log.render());
+        report.push_str(&sel.render());
+
+        RunTelemetry {
+            report,
+            power,
+            peak_celsius,
+            kernel_log,
+            sel,
+        }
+    }
+}
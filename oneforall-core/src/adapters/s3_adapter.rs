@@ -0,0 +1,195 @@
+//! S3 Adapter
+//!
+//! This module provides an adapter that stores objects in an S3-compatible
+//! bucket, such as AWS S3, MinIO or Ceph, with single `PutObject` requests
+//! signed with AWS Signature Version 4.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use common::ports::log_port::LoggerPort;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::domain::export::S3Config;
+use crate::ports::object_store_port::ObjectStorePort;
+
+/// Time allowed for an upload, which may carry hours of telemetry.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// The headers covered by the signature.
+const SIGNED_HEADERS: &str = "host;x-amz-content-sha256;x-amz-date";
+
+/// Represents an S3-compatible bucket.
+pub struct S3Adapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    agent: ureq::Agent,
+    config: S3Config,
+}
+
+/// Computes the HMAC-SHA256 of a message.
+fn hmac_sha256(key: &[u8], message: &str) -> Vec<u8> {
+    let mut mac =
+        <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Renders bytes as lowercase hexadecimal.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Percent-encodes an object key for the request path, keeping the slashes
+/// that separate its segments, as Signature Version 4 expects.
+fn encode_key(key: &str) -> String {
+    key.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Extracts the message of an S3 error response, e.g. "Access Denied".
+fn error_message(body: &str) -> Option<&str> {
+    let start = body.find("<Message>")? + "<Message>".len();
+    let end = start + body[start..].find("</Message>")?;
+    Some(&body[start..end])
+}
+
+impl S3Adapter {
+    /// Creates a new instance of `S3Adapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    /// * `config` - The bucket from the `[export.s3]` configuration, with its credentials.
+    ///
+    /// # Returns
+    /// An instance of `S3Adapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>, config: S3Config) -> Self {
+        let agent = ureq::Agent::config_builder()
+            .timeout_global(Some(REQUEST_TIMEOUT))
+            .http_status_as_error(false)
+            .build()
+            .into();
+        S3Adapter {
+            logger,
+            agent,
+            config,
+        }
+    }
+
+    /// Returns the URL of an object, along with the host name and path the
+    /// request is signed for.
+    fn locate(&self, key: &str) -> (String, String, String) {
+        let endpoint = self
+            .config
+            .endpoint
+            .clone()
+            .unwrap_or_else(|| format!("https://s3.{}.amazonaws.com", self.config.region));
+        let (scheme, authority) = endpoint
+            .trim_end_matches('/')
+            .split_once("://")
+            .map(|(scheme, authority)| (scheme.to_string(), authority.to_string()))
+            .unwrap_or_else(|| ("https".to_string(), endpoint.clone()));
+        // The HTTP client leaves out a default port, so the signature must too.
+        let default_port = if scheme == "https" { ":443" } else { ":80" };
+        let authority = authority
+            .strip_suffix(default_port)
+            .unwrap_or(&authority)
+            .to_string();
+
+        let (host, path) = if self.config.path_style {
+            (
+                authority,
+                format!("/{}/{}", self.config.bucket, encode_key(key)),
+            )
+        } else {
+            (
+                format!("{}.{}", self.config.bucket, authority),
+                format!("/{}", encode_key(key)),
+            )
+        };
+        (format!("{}://{}{}", scheme, host, path), host, path)
+    }
+
+    /// Builds the `Authorization` header of a request.
+    fn authorization(&self, host: &str, path: &str, payload_hash: &str, timestamp: &str) -> String {
+        let date = &timestamp[..8];
+        let scope = format!("{}/{}/s3/aws4_request", date, self.config.region);
+        let canonical_request = format!(
+            "PUT\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            path, host, payload_hash, timestamp, SIGNED_HEADERS, payload_hash
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            timestamp,
+            scope,
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let secret = self.config.secret_access_key.as_deref().unwrap_or_default();
+        let key = hmac_sha256(format!("AWS4{}", secret).as_bytes(), date);
+        let key = hmac_sha256(&key, &self.config.region);
+        let key = hmac_sha256(&key, "s3");
+        let key = hmac_sha256(&key, "aws4_request");
+        let signature = hex(&hmac_sha256(&key, &string_to_sign));
+
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.config.access_key_id.as_deref().unwrap_or_default(),
+            scope,
+            SIGNED_HEADERS,
+            signature
+        )
+    }
+}
+
+// Implement the `ObjectStorePort` trait for `S3Adapter`.
+impl ObjectStorePort for S3Adapter {
+    fn describe(&self) -> String {
+        format!("s3://{}", self.config.bucket)
+    }
+
+    fn put_object(&self, key: &str, content_type: &str, body: &[u8]) -> Result<(), String> {
+        let (url, host, path) = self.locate(key);
+        let payload_hash = hex(&Sha256::digest(body));
+        let timestamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        self.logger.log_debug(&format!(
+            "Uploading {} ({} bytes) to {}",
+            key,
+            body.len(),
+            self.describe()
+        ));
+
+        let mut response = self
+            .agent
+            .put(&url)
+            .header("Content-Type", content_type)
+            .header("x-amz-content-sha256", &payload_hash)
+            .header("x-amz-date", &timestamp)
+            .header(
+                "Authorization",
+                &self.authorization(&host, &path, &payload_hash, &timestamp),
+            )
+            .send(body)
+            .map_err(|e| format!("Failed to upload {}: {}", key, e))?;
+        if response.status().is_success() {
+            return Ok(());
+        }
+        let status = response.status();
+        let text = response.body_mut().read_to_string().unwrap_or_default();
+        Err(format!(
+            "Failed to upload {}: {} {}",
+            key,
+            status,
+            error_message(&text).unwrap_or_default()
+        )
+        .trim_end()
+        .to_string())
+    }
+}
@@ -0,0 +1,431 @@
+//! Sample Writer
+//!
+//! This module buffers collector readings and writes them to the database in
+//! batches, once per flush interval or whenever the buffer fills, so collectors
+//! sampling every second or faster do not wait for a flush to disk per reading.
+//!
+//! Each buffered reading is first appended to a journal next to the database.
+//! The journal is emptied once its readings are written, and replayed when the
+//! writer is opened again after a crash, so no reading is lost with the buffer.
+//! While writes fail, at most `max_pending` readings wait; the oldest are
+//! dropped beyond that and counted, rather than held in memory for as long as
+//! the database is unavailable. The journal is rewritten without the dropped
+//! readings once it holds as many of them as it waits for, so it stays within
+//! twice `max_pending` readings however long the outage, and it is synced to
+//! disk after each failed write, so the waiting readings survive a power loss.
+//!
+//! Readings are stamped with the monotonic clock as well as the wall-clock time
+//! they were taken at, and stored in the order of `SampleClock`, so a step of
+//! the wall clock never reorders the time-series.
+
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use common::domain::pipeline::{OverflowPolicy, PipelineStats};
+use common::ports::log_port::LoggerPort;
+use common::ports::telemetry_port::TelemetryPort;
+use serde::{Deserialize, Serialize};
+
+use crate::adapters::repository::Repository;
+use crate::adapters::time_sync_adapter::{read_boot_id, read_monotonic_ns};
+use crate::domain::database::{validate_collector, OrderedSample, StoredSample, WriteBufferConfig};
+use crate::domain::time_sync::{SampleClock, SampleTime};
+
+/// The name the counters of the buffer are published under.
+pub const DATABASE_PIPELINE: &str = "database";
+
+/// A buffered reading, as kept in the journal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JournalEntry {
+    collector: String,
+    /// The time the reading is ordered at, in RFC 3339 format.
+    recorded_at: String,
+    /// The timestamps of the reading; `None` in journals written before they were kept.
+    #[serde(default)]
+    time: Option<SampleTime>,
+    sample: serde_json::Value,
+}
+
+impl JournalEntry {
+    /// Returns the time the reading is ordered at.
+    fn recorded_at(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.recorded_at)
+            .ok()
+            .map(|time| time.with_timezone(&Utc))
+    }
+}
+
+/// The readings waiting to be written, their journal, and the clock ordering them.
+struct Buffer {
+    entries: Vec<JournalEntry>,
+    journal: File,
+    clock: SampleClock,
+    /// When each collector last recorded a reading.
+    last_recorded: BTreeMap<String, Instant>,
+    /// Why the last write failed, until a write succeeds again.
+    write_error: Option<String>,
+    /// The readings dropped since the last successful write.
+    dropped_since_write: u64,
+    /// The dropped readings still in the journal, until it is rewritten.
+    stale: usize,
+    /// The counters of the buffer, each reading counted as a batch of one.
+    stats: PipelineStats,
+    /// Where the counters are published, if anywhere.
+    telemetry: Option<Arc<dyn TelemetryPort>>,
+}
+
+impl Buffer {
+    /// Publishes the counters of the buffer.
+    fn publish(&mut self) {
+        self.stats.queued = self.entries.len();
+        if let Some(telemetry) = &self.telemetry {
+            telemetry.publish_pipeline(self.stats.clone());
+        }
+    }
+
+    /// Rewrites the journal with the readings waiting to be written, leaving
+    /// out those dropped, and syncs it to disk.
+    fn rewrite_journal(&mut self) -> Result<(), String> {
+        let error = |e: std::io::Error| format!("Failed to rewrite the sample journal: {}", e);
+        self.journal.set_len(0).map_err(error)?;
+        // The journal is opened for appending, so writes start at its new end.
+        let mut writer = BufWriter::new(&self.journal);
+        for entry in &self.entries {
+            let line = serde_json::to_string(entry)
+                .map_err(|e| format!("Failed to encode the {} reading: {}", entry.collector, e))?;
+            writeln!(writer, "{}", line).map_err(error)?;
+        }
+        writer.flush().map_err(error)?;
+        drop(writer);
+        self.journal.sync_data().map_err(error)?;
+        self.stale = 0;
+        Ok(())
+    }
+}
+
+/// Writes collector readings to the database in batches.
+#[derive(Clone)]
+pub struct SampleWriter {
+    logger: Arc<dyn LoggerPort>,
+    repository: Repository,
+    buffer: Arc<Mutex<Buffer>>,
+    max_samples: usize,
+    max_pending: usize,
+    boot_id: String,
+}
+
+impl SampleWriter {
+    /// Opens the journal, writes the readings left in it by a previous run, and
+    /// starts writing buffered readings once per flush interval. Readings that
+    /// cannot be written yet stay buffered and journaled, like any other.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger` - Logger implementation for logging messages.
+    /// * `repository` - Where the readings are stored.
+    /// * `config` - How readings are buffered.
+    /// * `journal` - Where buffered readings are journaled until they are written.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<SampleWriter, String>` - The writer, or why the journal could not be opened.
+    pub fn open(
+        logger: Arc<dyn LoggerPort>,
+        repository: Repository,
+        config: &WriteBufferConfig,
+        journal: &Path,
+    ) -> Result<Self, String> {
+        let mut entries = read_journal(journal)?;
+        let max_pending = config.max_pending();
+        let excess = entries.len().saturating_sub(max_pending);
+        entries.drain(..excess);
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(journal)
+            .map_err(|e| format!("Failed to open {}: {}", journal.display(), e))?;
+        let writer = SampleWriter {
+            logger,
+            repository,
+            buffer: Arc::new(Mutex::new(Buffer {
+                entries,
+                journal: file,
+                clock: SampleClock::default(),
+                last_recorded: BTreeMap::new(),
+                write_error: None,
+                dropped_since_write: 0,
+                stale: 0,
+                stats: PipelineStats {
+                    name: DATABASE_PIPELINE.to_string(),
+                    overflow: OverflowPolicy::DropOldest,
+                    capacity: max_pending,
+                    dropped: excess as u64,
+                    ..PipelineStats::default()
+                },
+                telemetry: None,
+            })),
+            max_samples: config.max_samples(),
+            max_pending,
+            boot_id: read_boot_id(),
+        };
+
+        if excess > 0 {
+            writer.lock()?.rewrite_journal()?;
+            writer.logger.log_warn(&format!(
+                "Dropped the oldest {} readings of {}, over the `max_pending` limit.",
+                excess,
+                journal.display()
+            ));
+        }
+        match writer.flush() {
+            Ok(0) => {}
+            Ok(recovered) => writer.logger.log_info(&format!(
+                "Recovered {} readings from {}.",
+                recovered,
+                journal.display()
+            )),
+            Err(e) => writer.logger.log_warn(&format!(
+                "Keeping the {} readings of {} until they can be written: {}",
+                writer.lock()?.entries.len(),
+                journal.display(),
+                e
+            )),
+        }
+
+        let (background, interval) = (writer.clone(), config.flush_interval());
+        thread::spawn(move || loop {
+            thread::sleep(interval);
+            if let Err(e) = background.flush() {
+                background.logger.log_warn(&e);
+            }
+        });
+        Ok(writer)
+    }
+
+    /// Publishes the counters of the buffer as the `database` pipeline from now on.
+    ///
+    /// # Arguments
+    ///
+    /// * `telemetry` - Where the counters are published.
+    ///
+    /// # Returns
+    ///
+    /// * `SampleWriter` - The writer, whose clones publish the counters too.
+    pub fn with_telemetry(self, telemetry: Arc<dyn TelemetryPort>) -> Self {
+        if let Ok(mut buffer) = self.lock() {
+            buffer.telemetry = Some(telemetry);
+            buffer.publish();
+        }
+        self
+    }
+
+    /// Buffers a reading of a collector, writing the buffer when it is full, and
+    /// drops the oldest readings while more than `max_pending` wait for a failing write.
+    ///
+    /// # Arguments
+    ///
+    /// * `collector` - The collector, e.g. "power".
+    /// * `recorded_at` - The wall-clock time the reading was taken.
+    /// * `sample` - The reading.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), String>` - Whether the reading was journaled.
+    pub fn record<T: Serialize>(
+        &self,
+        collector: &str,
+        recorded_at: DateTime<Utc>,
+        sample: &T,
+    ) -> Result<(), String> {
+        validate_collector(collector)?;
+        let sample = serde_json::to_value(sample)
+            .map_err(|e| format!("Failed to encode the {} reading: {}", collector, e))?;
+
+        let full = {
+            let mut buffer = self.lock()?;
+            // Read under the lock, so readings are ordered in the order they are buffered.
+            let monotonic_ns = read_monotonic_ns();
+            let (ordered_at, jump) = buffer.clock.order(recorded_at, monotonic_ns);
+            if let Some(jump) = jump {
+                self.logger.log_warn(&format!(
+                    "The wall clock jumped {:+.3} s; ordering readings by the monotonic clock \
+                     until it catches up.",
+                    jump.secs
+                ));
+            }
+            let entry = JournalEntry {
+                collector: collector.to_string(),
+                recorded_at: ordered_at.to_rfc3339_opts(SecondsFormat::Micros, true),
+                time: Some(SampleTime {
+                    wall: recorded_at.to_rfc3339_opts(SecondsFormat::Micros, true),
+                    monotonic_ns,
+                    boot_id: self.boot_id.clone(),
+                }),
+                sample,
+            };
+            let line = serde_json::to_string(&entry)
+                .map_err(|e| format!("Failed to encode the {} reading: {}", collector, e))?;
+            writeln!(buffer.journal, "{}", line)
+                .map_err(|e| format!("Failed to journal the {} reading: {}", collector, e))?;
+            buffer.entries.push(entry);
+            buffer
+                .last_recorded
+                .insert(collector.to_string(), Instant::now());
+            buffer.stats.received += 1;
+            let excess = buffer.entries.len().saturating_sub(self.max_pending);
+            if excess > 0 {
+                buffer.entries.drain(..excess);
+                if buffer.dropped_since_write == 0 {
+                    self.logger.log_warn(&format!(
+                        "More than {} readings are waiting for the database; dropping the oldest \
+                         until it is written to again.",
+                        self.max_pending
+                    ));
+                }
+                buffer.dropped_since_write += excess as u64;
+                buffer.stats.dropped += excess as u64;
+                // Rewriting the journal on every drop would copy every waiting
+                // reading per reading recorded, so the dropped ones are left
+                // out once there are as many of them as there are waiting.
+                buffer.stale += excess;
+                if buffer.stale >= self.max_pending {
+                    buffer.rewrite_journal()?;
+                }
+            }
+            buffer.publish();
+            buffer.entries.len() >= self.max_samples
+        };
+        if full {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the buffered readings, one batch per collector, and empties the journal.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<usize, String>` - How many readings were written.
+    pub fn flush(&self) -> Result<usize, String> {
+        let mut buffer = self.lock()?;
+        if buffer.entries.is_empty() {
+            return Ok(0);
+        }
+
+        let mut batches: BTreeMap<&str, Vec<OrderedSample<&serde_json::Value>>> = BTreeMap::new();
+        for entry in &buffer.entries {
+            if let Some(recorded_at) = entry.recorded_at() {
+                batches.entry(&entry.collector).or_default().push((
+                    recorded_at,
+                    StoredSample {
+                        time: entry.time.clone(),
+                        sample: &entry.sample,
+                    },
+                ));
+            }
+        }
+        let mut write_error = None;
+        for (collector, samples) in batches {
+            // Readings stay buffered and journaled until they are written, so a
+            // failed write is retried with the next flush.
+            if let Err(e) = self.repository.store_sample_batch(collector, &samples) {
+                write_error = Some(e);
+                break;
+            }
+        }
+        if let Some(e) = write_error {
+            // The readings wait in the journal for the next flush; sync it so
+            // they survive a power loss as well as a crash.
+            if let Err(sync) = buffer.journal.sync_data() {
+                self.logger
+                    .log_warn(&format!("Failed to sync the sample journal: {}", sync));
+            }
+            buffer.write_error = Some(e.clone());
+            return Err(e);
+        }
+        buffer.write_error = None;
+        if buffer.dropped_since_write > 0 {
+            self.logger.log_info(&format!(
+                "The database is written to again; {} readings were dropped meanwhile.",
+                buffer.dropped_since_write
+            ));
+            buffer.dropped_since_write = 0;
+        }
+
+        let written = buffer.entries.len();
+        buffer.entries.clear();
+        buffer.stats.written += written as u64;
+        buffer.publish();
+        buffer.stale = 0;
+        buffer
+            .journal
+            .set_len(0)
+            .map_err(|e| format!("Failed to empty the sample journal: {}", e))?;
+        Ok(written)
+    }
+
+    /// Returns how long ago a collector last recorded a reading.
+    ///
+    /// # Arguments
+    ///
+    /// * `collector` - The collector, e.g. "processes".
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Duration>` - The age of its latest reading, or `None` if it has not recorded one.
+    pub fn last_recorded(&self, collector: &str) -> Option<Duration> {
+        self.lock()
+            .ok()?
+            .last_recorded
+            .get(collector)
+            .map(Instant::elapsed)
+    }
+
+    /// Returns why the buffered readings could not be written, with how many are waiting.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<usize, String>` - The number of readings waiting to be written, or
+    ///   why the last write failed.
+    pub fn backlog(&self) -> Result<usize, String> {
+        let buffer = self.lock()?;
+        match &buffer.write_error {
+            Some(e) => Err(format!(
+                "{} readings are waiting to be written: {}",
+                buffer.entries.len(),
+                e
+            )),
+            None => Ok(buffer.entries.len()),
+        }
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Buffer>, String> {
+        self.buffer
+            .lock()
+            .map_err(|_| "the sample buffer is poisoned".to_string())
+    }
+}
+
+/// Reads the readings left in a journal; a line cut short by a crash is skipped.
+fn read_journal(path: &Path) -> Result<Vec<JournalEntry>, String> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    let mut entries = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        match serde_json::from_str::<JournalEntry>(&line) {
+            Ok(entry) if entry.recorded_at().is_some() => entries.push(entry),
+            _ => {}
+        }
+    }
+    Ok(entries)
+}
@@ -0,0 +1,174 @@
+//! Scaling Benchmark Adapter
+//!
+//! This module provides an adapter that measures how a CPU workload scales
+//! across threads of this process. Each thread is pinned to its logical CPU
+//! and runs a chain of dependent integer multiplies and shifts, which stays in
+//! registers and leaves execution ports idle on every cycle, so a single thread
+//! neither touches memory nor saturates its core: the throughput of the threads
+//! together only falls short of linear where they contend for a shared resource,
+//! be it a core shared with an SMT sibling, the socket interconnect, or power
+//! and thermal headroom. Where threads cannot be pinned, e.g. on macOS, the
+//! scheduler places them.
+
+use std::hint::black_box;
+use std::sync::{Arc, Barrier};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use common::ports::log_port::LoggerPort;
+use tokio_util::sync::CancellationToken;
+
+use crate::adapters::topology_adapter::pin_current_thread;
+use crate::domain::cpu_topology::CpuTopology;
+use crate::domain::scaling::{
+    plan_scaling, plan_socket_runs, ScalingPoint, ScalingResult, SocketThroughput,
+};
+use crate::ports::scaling_benchmark_port::ScalingBenchmarkPort;
+
+/// Number of workload iterations run between two checks of the deadline.
+const BATCH_ITERATIONS: u64 = 100_000;
+
+/// Represents the in-process CPU scaling benchmark adapter.
+pub struct ScalingBenchmarkAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl ScalingBenchmarkAdapter {
+    /// Creates a new instance of `ScalingBenchmarkAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `ScalingBenchmarkAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        ScalingBenchmarkAdapter { logger }
+    }
+
+    /// Returns an error once the benchmark is cancelled.
+    fn check_cancelled(cancel: &CancellationToken) -> Result<(), String> {
+        if cancel.is_cancelled() {
+            Err("CPU scaling benchmark cancelled".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Runs the workload until a deadline and returns the iterations completed.
+    fn run_workload(seed: u64, deadline: Instant, cancel: &CancellationToken) -> u64 {
+        let mut value = seed | 1;
+        let mut iterations = 0;
+        while Instant::now() < deadline && !cancel.is_cancelled() {
+            for _ in 0..BATCH_ITERATIONS {
+                value = value
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                value ^= value >> 29;
+            }
+            iterations += BATCH_ITERATIONS;
+        }
+        black_box(value);
+        iterations
+    }
+
+    /// Runs the workload on one thread per logical CPU, started together.
+    ///
+    /// # Returns
+    /// The iterations completed per second by every thread together, and the
+    /// first error met pinning a thread, if any.
+    fn measure(
+        cpus: &[u32],
+        duration: Duration,
+        cancel: &CancellationToken,
+    ) -> (f64, Option<String>) {
+        let barrier = Barrier::new(cpus.len());
+        let results: Vec<(u64, Duration, Option<String>)> = thread::scope(|scope| {
+            let handles: Vec<_> = cpus
+                .iter()
+                .map(|cpu| {
+                    let barrier = &barrier;
+                    scope.spawn(move || {
+                        let pinned = pin_current_thread(*cpu).err();
+                        barrier.wait();
+                        let started = Instant::now();
+                        let iterations =
+                            Self::run_workload(u64::from(*cpu), started + duration, cancel);
+                        (iterations, started.elapsed(), pinned)
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap_or((0, duration, None)))
+                .collect()
+        });
+
+        let ops_per_sec = results
+            .iter()
+            .map(|(iterations, elapsed, _)| *iterations as f64 / elapsed.as_secs_f64().max(1e-9))
+            .sum();
+        let pin_error = results.into_iter().find_map(|(_, _, error)| error);
+        (ops_per_sec, pin_error)
+    }
+}
+
+// Implement the `ScalingBenchmarkPort` trait for `ScalingBenchmarkAdapter`.
+impl ScalingBenchmarkPort for ScalingBenchmarkAdapter {
+    fn run_scaling_benchmark(
+        &self,
+        topology: &CpuTopology,
+        max_threads: Option<usize>,
+        step_duration: Duration,
+        cancel: &CancellationToken,
+    ) -> Result<ScalingResult, String> {
+        let steps = plan_scaling(topology, max_threads)?;
+        let mut result = ScalingResult::default();
+        let mut warned = false;
+        let mut warn_unpinned = |error: Option<String>| {
+            if let (Some(error), false) = (error, warned) {
+                self.logger.log_warn(&format!(
+                    "{}; the scheduler places the threads, so the curve may not follow the topology",
+                    error
+                ));
+                warned = true;
+            }
+        };
+
+        for step in steps {
+            Self::check_cancelled(cancel)?;
+            self.logger.log_info(&format!(
+                "Running the CPU workload on {} threads across {} socket(s){}",
+                step.cpus.len(),
+                step.sockets,
+                if step.smt { " with SMT siblings" } else { "" }
+            ));
+            let (ops_per_sec, pin_error) = Self::measure(&step.cpus, step_duration, cancel);
+            warn_unpinned(pin_error);
+            result.points.push(ScalingPoint {
+                threads: step.cpus.len(),
+                sockets: step.sockets,
+                smt: step.smt,
+                ops_per_sec,
+            });
+        }
+
+        for (socket, cpus) in plan_socket_runs(topology) {
+            Self::check_cancelled(cancel)?;
+            self.logger.log_info(&format!(
+                "Running the CPU workload on the {} cores of socket {} alone",
+                cpus.len(),
+                socket
+            ));
+            let (ops_per_sec, pin_error) = Self::measure(&cpus, step_duration, cancel);
+            warn_unpinned(pin_error);
+            result.sockets.push(SocketThroughput {
+                socket,
+                cores: cpus.len(),
+                ops_per_sec,
+            });
+        }
+
+        Self::check_cancelled(cancel)?;
+        Ok(result)
+    }
+}
@@ -0,0 +1,260 @@
+//! Scheduler Latency Adapter
+//!
+//! This module provides an adapter that measures scheduler latencies with
+//! threads of this process talking over Unix socket pairs: a byte passed back
+//! and forth between two threads pinned to one CPU times context switches, a
+//! timestamp sent from another CPU to a blocked thread times wake-ups, and a
+//! thread sleeping until the next period of a fixed timer times how late it
+//! runs, as cyclictest does. The measured threads ask for a real-time
+//! priority, which only root is granted.
+
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use common::ports::log_port::LoggerPort;
+use tokio_util::sync::CancellationToken;
+
+use crate::adapters::topology_adapter::pin_current_thread;
+use crate::domain::filesystem::LatencyStats;
+use crate::domain::scheduler_latency::SchedulerLatencyResult;
+use crate::ports::scheduler_latency_port::SchedulerLatencyPort;
+
+/// Number of round trips timed when measuring context switches.
+const ROUND_TRIPS: usize = 100_000;
+
+/// Number of wake-ups timed.
+const WAKEUPS: usize = 10_000;
+
+/// Pause between wake-ups, long enough for the woken thread to block again.
+const WAKEUP_GAP: Duration = Duration::from_micros(200);
+
+/// The `SCHED_FIFO` priority of the measured threads, the one cyclictest
+/// is commonly run with; it leaves room above for kernel threads.
+const REALTIME_PRIORITY: libc::c_int = 80;
+
+/// Represents the in-process scheduler latency adapter.
+pub struct SchedulerLatencyAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl SchedulerLatencyAdapter {
+    /// Creates a new instance of `SchedulerLatencyAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `SchedulerLatencyAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        SchedulerLatencyAdapter { logger }
+    }
+
+    /// Returns an error once the benchmark is cancelled.
+    fn check_cancelled(cancel: &CancellationToken) -> Result<(), String> {
+        if cancel.is_cancelled() {
+            Err("Scheduler latency benchmark cancelled".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Pins the calling thread to a CPU and raises it to a real-time priority.
+    ///
+    /// # Returns
+    /// Whether the real-time priority was granted; failing to pin only logs a warning.
+    fn prepare_thread(logger: &Arc<dyn LoggerPort>, cpu: u32) -> bool {
+        if let Err(e) = pin_current_thread(cpu) {
+            logger.log_warn(&format!("{}; latencies may include migrations", e));
+        }
+        let param = libc::sched_param {
+            sched_priority: REALTIME_PRIORITY,
+        };
+        // SAFETY: `param` is a valid `sched_param` and the call only affects the calling thread.
+        let status =
+            unsafe { libc::pthread_setschedparam(libc::pthread_self(), libc::SCHED_FIFO, &param) };
+        if status != 0 {
+            logger.log_debug(&format!(
+                "No real-time priority for the thread on CPU {}: {}",
+                cpu,
+                std::io::Error::from_raw_os_error(status)
+            ));
+        }
+        status == 0
+    }
+
+    /// Times round trips of one byte between this thread and a partner thread on the same CPU.
+    ///
+    /// Each round trip blocks this thread and runs the partner, then the
+    /// reverse, so half of it is one context switch.
+    ///
+    /// # Returns
+    /// The latency distribution and whether both threads ran with a real-time priority.
+    fn context_switches(
+        logger: &Arc<dyn LoggerPort>,
+        cpu: u32,
+        cancel: &CancellationToken,
+    ) -> Result<(LatencyStats, bool), String> {
+        let (mut local, mut remote) =
+            UnixStream::pair().map_err(|e| format!("Failed to create a socket pair: {}", e))?;
+        let partner_logger = logger.clone();
+        let partner = thread::spawn(move || {
+            let realtime = Self::prepare_thread(&partner_logger, cpu);
+            let mut byte = [0u8; 1];
+            // The loop ends once the other end is closed.
+            while remote.read_exact(&mut byte).is_ok() && remote.write_all(&byte).is_ok() {}
+            realtime
+        });
+        let realtime = Self::prepare_thread(logger, cpu);
+
+        let mut samples = Vec::with_capacity(ROUND_TRIPS);
+        let mut byte = [0u8; 1];
+        let mut outcome = Ok(());
+        for _ in 0..ROUND_TRIPS {
+            if let Err(e) = Self::check_cancelled(cancel) {
+                outcome = Err(e);
+                break;
+            }
+            let start = Instant::now();
+            if local.write_all(&byte).is_err() || local.read_exact(&mut byte).is_err() {
+                outcome = Err("The context switch partner thread stopped".to_string());
+                break;
+            }
+            samples.push(start.elapsed() / 2);
+        }
+
+        drop(local);
+        let partner_realtime = partner
+            .join()
+            .map_err(|_| "Context switch partner thread panicked".to_string())?;
+        outcome?;
+        Ok((
+            LatencyStats::from_samples(&samples),
+            realtime && partner_realtime,
+        ))
+    }
+
+    /// Times wake-ups of a thread blocked on `cpu`, sent from this thread on `waker_cpu`.
+    ///
+    /// The waker writes the time it sends at; the woken thread subtracts it
+    /// from the time it runs at. Both read the same monotonic clock.
+    ///
+    /// # Returns
+    /// The latency distribution and whether both threads ran with a real-time priority.
+    fn wakeups(
+        logger: &Arc<dyn LoggerPort>,
+        cpu: u32,
+        waker_cpu: u32,
+        cancel: &CancellationToken,
+    ) -> Result<(LatencyStats, bool), String> {
+        let (mut waker, mut sleeper) =
+            UnixStream::pair().map_err(|e| format!("Failed to create a socket pair: {}", e))?;
+        let base = Instant::now();
+        let sleeper_logger = logger.clone();
+        let sleeper = thread::spawn(move || {
+            let realtime = Self::prepare_thread(&sleeper_logger, cpu);
+            let mut samples = Vec::with_capacity(WAKEUPS);
+            let mut sent = [0u8; 8];
+            // The loop ends once the waker closes its end.
+            while sleeper.read_exact(&mut sent).is_ok() {
+                let sent = Duration::from_nanos(u64::from_ne_bytes(sent));
+                samples.push(base.elapsed().saturating_sub(sent));
+            }
+            (samples, realtime)
+        });
+        let realtime = Self::prepare_thread(logger, waker_cpu);
+
+        let mut outcome = Ok(());
+        for _ in 0..WAKEUPS {
+            if let Err(e) = Self::check_cancelled(cancel) {
+                outcome = Err(e);
+                break;
+            }
+            thread::sleep(WAKEUP_GAP);
+            let sent = base.elapsed().as_nanos() as u64;
+            if waker.write_all(&sent.to_ne_bytes()).is_err() {
+                outcome = Err("The woken thread stopped".to_string());
+                break;
+            }
+        }
+
+        drop(waker);
+        let (samples, sleeper_realtime) = sleeper
+            .join()
+            .map_err(|_| "Woken thread panicked".to_string())?;
+        outcome?;
+        Ok((
+            LatencyStats::from_samples(&samples),
+            realtime && sleeper_realtime,
+        ))
+    }
+
+    /// Sleeps until each period of a fixed timer and times how late this thread runs.
+    ///
+    /// The periods are fixed in advance, as with cyclictest's absolute timers,
+    /// so a late wake-up does not shift the ones after it.
+    fn timer_jitter(
+        interval: Duration,
+        duration: Duration,
+        cancel: &CancellationToken,
+    ) -> Result<LatencyStats, String> {
+        let periods = (duration.as_nanos() / interval.as_nanos().max(1)).max(1) as usize;
+        let mut samples = Vec::with_capacity(periods);
+        let mut next = Instant::now() + interval;
+        for _ in 0..periods {
+            Self::check_cancelled(cancel)?;
+            thread::sleep(next.saturating_duration_since(Instant::now()));
+            samples.push(Instant::now().saturating_duration_since(next));
+            next += interval;
+        }
+        Ok(LatencyStats::from_samples(&samples))
+    }
+}
+
+// Implement the `SchedulerLatencyPort` trait for `SchedulerLatencyAdapter`.
+impl SchedulerLatencyPort for SchedulerLatencyAdapter {
+    fn measure_scheduler_latency(
+        &self,
+        cpu: u32,
+        waker_cpu: u32,
+        timer_interval: Duration,
+        timer_duration: Duration,
+        cancel: &CancellationToken,
+    ) -> Result<SchedulerLatencyResult, String> {
+        let logger = self.logger.clone();
+        let cancel = cancel.clone();
+
+        // Run on a thread of its own so pinning and priorities stay off the caller's thread.
+        thread::spawn(move || {
+            logger.log_debug(&format!("Measuring context switches on CPU {}", cpu));
+            let (context_switch, switch_realtime) = Self::context_switches(&logger, cpu, &cancel)?;
+
+            logger.log_debug(&format!(
+                "Measuring wake-ups of CPU {} from CPU {}",
+                cpu, waker_cpu
+            ));
+            let (wakeup, wakeup_realtime) = Self::wakeups(&logger, cpu, waker_cpu, &cancel)?;
+
+            logger.log_debug(&format!(
+                "Measuring timer jitter on CPU {} for {:?}",
+                cpu, timer_duration
+            ));
+            let timer_realtime = Self::prepare_thread(&logger, cpu);
+            let timer_jitter = Self::timer_jitter(timer_interval, timer_duration, &cancel)?;
+
+            Ok(SchedulerLatencyResult {
+                cpu,
+                waker_cpu,
+                realtime: switch_realtime && wakeup_realtime && timer_realtime,
+                context_switch,
+                wakeup,
+                timer_interval_us: timer_interval.as_micros() as u64,
+                timer_jitter,
+            })
+        })
+        .join()
+        .map_err(|_| "Scheduler latency measurement thread panicked".to_string())?
+    }
+}
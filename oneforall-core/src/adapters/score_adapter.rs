@@ -0,0 +1,60 @@
+//! Score Adapter
+//!
+//! This module scores this machine from the benchmark results stored in the
+//! database, for `oneforall compare` and for the REST API that `compare
+//! --fleet` queries on every agent.
+
+use std::sync::Arc;
+
+use common::ports::log_port::LoggerPort;
+use common::ports::score_port::ScorePort;
+
+use crate::adapters::repository::Repository;
+use crate::domain::score::{score_machine, MachineScore};
+use crate::domain::statistics::BenchmarkRecord;
+
+/// Represents the stored benchmark result scoring adapter.
+pub struct ScoreAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    repository: Repository,
+}
+
+impl ScoreAdapter {
+    /// Creates a new instance of `ScoreAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    /// * `repository` - Where the benchmark results are stored.
+    ///
+    /// # Returns
+    /// An instance of `ScoreAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>, repository: Repository) -> Self {
+        ScoreAdapter { logger, repository }
+    }
+
+    /// Scores this machine from the latest stored result of every benchmark.
+    ///
+    /// # Returns
+    /// A `Result` containing the scores, or an error if the results could not be read.
+    pub fn machine_score(&self) -> Result<MachineScore, String> {
+        let records: Vec<BenchmarkRecord> = self
+            .repository
+            .get_runs_in_range("benchmark", None, None)?
+            .into_iter()
+            .map(|(_, record)| record)
+            .collect();
+        self.logger.log_debug(&format!(
+            "Scoring {} stored benchmark result(s)",
+            records.len()
+        ));
+        Ok(score_machine(&records))
+    }
+}
+
+// Implement the `ScorePort` trait for `ScoreAdapter`.
+impl ScorePort for ScoreAdapter {
+    fn score(&self) -> Result<serde_json::Value, String> {
+        serde_json::to_value(self.machine_score()?)
+            .map_err(|e| format!("Failed to encode the scores: {}", e))
+    }
+}
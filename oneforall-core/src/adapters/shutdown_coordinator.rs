@@ -0,0 +1,184 @@
+//! Shutdown Coordinator
+//!
+//! This module stops the subsystems of a running instance in order when it is
+//! asked to shut down: the jobs are cancelled and waited for, stress-ng
+//! processes still running are killed, the REST API and gRPC servers stop
+//! accepting requests, and buffered readings, alerts, the database and the
+//! log files are written out. Every step runs within what is left of one
+//! deadline, so a job that hangs while cancelling cannot keep the readings
+//! from being saved, and the background tasks still running at the end are
+//! waited for within the same deadline rather than abandoned mid-write.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use common::ports::log_port::LoggerPort;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+/// The time shutting down may take, below the `TimeoutStopSec=30` of the
+/// bundled systemd unit, after which systemd kills the process.
+pub const DEFAULT_SHUTDOWN_DEADLINE: Duration = Duration::from_secs(25);
+
+/// A step of the shutdown, run once.
+type ShutdownStep = Box<dyn FnOnce() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send>;
+
+/// Coordinates the shutdown of the subsystems of a running instance.
+pub struct ShutdownCoordinator {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    deadline: Duration,
+    token: CancellationToken,
+    reason: Mutex<Option<String>>,
+    steps: Mutex<Vec<(String, ShutdownStep)>>,
+    tasks: Mutex<Vec<(String, JoinHandle<()>)>>,
+}
+
+impl ShutdownCoordinator {
+    /// Creates a new instance of `ShutdownCoordinator`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    /// * `deadline` - The time the whole shutdown may take.
+    ///
+    /// # Returns
+    /// An instance of `ShutdownCoordinator`.
+    pub fn new(logger: Arc<dyn LoggerPort>, deadline: Duration) -> Self {
+        ShutdownCoordinator {
+            logger,
+            deadline,
+            token: CancellationToken::new(),
+            reason: Mutex::new(None),
+            steps: Mutex::new(Vec::new()),
+            tasks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns a token cancelled as soon as the shutdown is requested, for
+    /// background loops to stop at.
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// Asks the application to shut down, e.g. because a subsystem it cannot
+    /// run without failed; only the first reason given is kept.
+    ///
+    /// # Arguments
+    /// * `reason` - Why the application shuts down, for the log.
+    pub fn request(&self, reason: &str) {
+        if let Ok(mut requested) = self.reason.lock() {
+            requested.get_or_insert_with(|| reason.to_string());
+        }
+        self.token.cancel();
+    }
+
+    /// Waits until the application is asked to shut down.
+    ///
+    /// # Returns
+    /// Why the application shuts down.
+    pub async fn requested(&self) -> String {
+        self.token.cancelled().await;
+        self.reason
+            .lock()
+            .ok()
+            .and_then(|reason| reason.clone())
+            .unwrap_or_else(|| "a shutdown request".to_string())
+    }
+
+    /// Adds a step to the shutdown, run after the steps added before it.
+    ///
+    /// # Arguments
+    /// * `name` - The subsystem the step stops, e.g. "jobs", for the log.
+    /// * `step` - Stops the subsystem.
+    pub fn on_shutdown<F, Fut>(&self, name: &str, step: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        if let Ok(mut steps) = self.steps.lock() {
+            steps.push((
+                name.to_string(),
+                Box::new(move || Box::pin(step()) as Pin<Box<dyn Future<Output = ()> + Send>>),
+            ));
+        }
+    }
+
+    /// Adds a background task the shutdown waits for once every step has run.
+    ///
+    /// # Arguments
+    /// * `name` - What the task does, e.g. "command", for the log.
+    /// * `task` - The task.
+    pub fn track(&self, name: &str, task: JoinHandle<()>) {
+        if let Ok(mut tasks) = self.tasks.lock() {
+            tasks.push((name.to_string(), task));
+        }
+    }
+
+    /// Runs every step in order, then waits for the tracked tasks, all within
+    /// the deadline. A step still running at the deadline is abandoned, and so
+    /// are the tasks still running, which are aborted.
+    ///
+    /// # Arguments
+    /// * `reason` - Why the application shuts down, e.g. "SIGTERM", for the log.
+    ///
+    /// # Returns
+    /// `true` if everything stopped within the deadline.
+    pub async fn shutdown(&self, reason: &str) -> bool {
+        self.logger.log_info(&format!(
+            "Shutting down ({}) within {} s.",
+            reason,
+            self.deadline.as_secs()
+        ));
+        self.request(reason);
+        let started = Instant::now();
+        let mut clean = true;
+
+        let steps = self
+            .steps
+            .lock()
+            .map(|mut steps| std::mem::take(&mut *steps))
+            .unwrap_or_default();
+        for (name, step) in steps {
+            // A step past the deadline still gets polled once, so steps that only
+            // write out buffers finish even when an earlier step used up the time.
+            let remaining = self.deadline.saturating_sub(started.elapsed());
+            self.logger.log_debug(&format!("Stopping {}", name));
+            if tokio::time::timeout(remaining, step()).await.is_err() {
+                self.logger.log_warn(&format!(
+                    "Stopping {} did not finish within the shutdown deadline",
+                    name
+                ));
+                clean = false;
+            }
+        }
+
+        let tasks = self
+            .tasks
+            .lock()
+            .map(|mut tasks| std::mem::take(&mut *tasks))
+            .unwrap_or_default();
+        for (name, mut task) in tasks {
+            let remaining = self.deadline.saturating_sub(started.elapsed());
+            if tokio::time::timeout(remaining, &mut task).await.is_err() {
+                self.logger.log_warn(&format!(
+                    "Aborting the {} task, still running at the shutdown deadline",
+                    name
+                ));
+                task.abort();
+                clean = false;
+            }
+        }
+
+        self.logger.log_info(&format!(
+            "Shut down in {:.1} s{}.",
+            started.elapsed().as_secs_f64(),
+            if clean {
+                ""
+            } else {
+                ", abandoning what did not stop in time"
+            }
+        ));
+        clean
+    }
+}
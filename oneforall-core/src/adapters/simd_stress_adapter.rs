@@ -0,0 +1,295 @@
+//! SIMD Stress Adapter
+//!
+//! This module provides an adapter that loads every core with fused
+//! multiply-adds in one instruction set at a time. Each kernel keeps eight or
+//! more independent accumulators in vector registers, enough to hide the
+//! latency of the FMA units so they issue every cycle, and never touches
+//! memory, so the clock is bound by the current the vector units draw rather
+//! than by the caches. The accumulators stay at 1.0 instead of overflowing,
+//! however long the kernel runs; their inputs pass through `black_box` so the
+//! compiler cannot fold the loop into that constant. The kernels are written with intrinsics, or inline
+//! assembly for SVE, rather than left to the auto-vectorizer, which would not
+//! choose 512-bit registers by itself.
+
+use std::hint::black_box;
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+use common::ports::log_port::LoggerPort;
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::simd::SimdIsa;
+use crate::ports::simd_stress_port::SimdStressPort;
+
+/// Number of independent accumulators each kernel updates per round.
+const ACCUMULATORS: usize = 12;
+
+/// The factor each accumulator is multiplied by.
+const MULTIPLIER: f64 = 0.5;
+
+/// The term added to each accumulator; with the factor, it keeps the accumulators at 1.0.
+const ADDEND: f64 = 0.5;
+
+/// Number of rounds run between two checks of the deadline.
+const ROUNDS_PER_CALL: u64 = 1 << 18;
+
+/// Runs the scalar baseline: a separate multiply and add per accumulator, which
+/// the compiler may pack into 128-bit SSE or NEON registers but never wider.
+///
+/// # Returns
+/// The floating-point operations completed.
+fn scalar_kernel(rounds: u64) -> u64 {
+    let (multiplier, addend) = (black_box(MULTIPLIER), black_box(ADDEND));
+    let mut accumulators = [black_box(1.0f64); ACCUMULATORS];
+    for _ in 0..rounds {
+        for value in accumulators.iter_mut() {
+            *value = *value * multiplier + addend;
+        }
+    }
+    black_box(accumulators);
+    rounds * ACCUMULATORS as u64 * 2
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::arch::x86_64::*;
+    use std::hint::black_box;
+
+    use super::{ACCUMULATORS, ADDEND, MULTIPLIER};
+
+    /// Runs 256-bit fused multiply-adds on four doubles per register.
+    ///
+    /// # Safety
+    /// The CPU must support AVX2 and FMA.
+    #[target_feature(enable = "avx2,fma")]
+    pub unsafe fn avx2_kernel(rounds: u64) -> u64 {
+        let (multiplier, addend) = (
+            _mm256_set1_pd(black_box(MULTIPLIER)),
+            _mm256_set1_pd(black_box(ADDEND)),
+        );
+        let mut accumulators = [_mm256_set1_pd(black_box(1.0)); ACCUMULATORS];
+        for _ in 0..rounds {
+            for value in accumulators.iter_mut() {
+                *value = _mm256_fmadd_pd(*value, multiplier, addend);
+            }
+        }
+        black_box(accumulators);
+        rounds * ACCUMULATORS as u64 * 4 * 2
+    }
+
+    /// Runs 512-bit fused multiply-adds on eight doubles per register.
+    ///
+    /// # Safety
+    /// The CPU must support AVX-512F.
+    #[target_feature(enable = "avx512f")]
+    pub unsafe fn avx512_kernel(rounds: u64) -> u64 {
+        let (multiplier, addend) = (
+            _mm512_set1_pd(black_box(MULTIPLIER)),
+            _mm512_set1_pd(black_box(ADDEND)),
+        );
+        let mut accumulators = [_mm512_set1_pd(black_box(1.0)); ACCUMULATORS];
+        for _ in 0..rounds {
+            for value in accumulators.iter_mut() {
+                *value = _mm512_fmadd_pd(*value, multiplier, addend);
+            }
+        }
+        black_box(accumulators);
+        rounds * ACCUMULATORS as u64 * 8 * 2
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod arm {
+    use std::arch::aarch64::*;
+    use std::hint::black_box;
+
+    use super::{ACCUMULATORS, ADDEND, MULTIPLIER};
+
+    /// Runs 128-bit NEON fused multiply-adds on two doubles per register.
+    ///
+    /// # Safety
+    /// The CPU must support NEON.
+    #[target_feature(enable = "neon")]
+    pub unsafe fn neon_kernel(rounds: u64) -> u64 {
+        let (multiplier, addend) = (
+            vdupq_n_f64(black_box(MULTIPLIER)),
+            vdupq_n_f64(black_box(ADDEND)),
+        );
+        let mut accumulators = [vdupq_n_f64(black_box(1.0)); ACCUMULATORS];
+        for _ in 0..rounds {
+            for value in accumulators.iter_mut() {
+                *value = vfmaq_f64(addend, *value, multiplier);
+            }
+        }
+        black_box(accumulators);
+        rounds * ACCUMULATORS as u64 * 2 * 2
+    }
+
+    /// Runs SVE fused multiply-adds over the full vector length of the CPU on
+    /// eight accumulators. Rust has no SVE intrinsics, so the loop is assembly;
+    /// `fmad` sets each accumulator to 0.5 + accumulator * 0.5, as the other kernels do.
+    ///
+    /// # Safety
+    /// The CPU must support SVE.
+    #[target_feature(enable = "sve")]
+    pub unsafe fn sve_kernel(rounds: u64) -> u64 {
+        let rounds = rounds.max(1);
+        let lanes: u64;
+        std::arch::asm!(
+            "cntd {lanes}",
+            "ptrue p0.d",
+            "fmov z0.d, #1.0",
+            "fmov z1.d, #1.0",
+            "fmov z2.d, #1.0",
+            "fmov z3.d, #1.0",
+            "fmov z4.d, #1.0",
+            "fmov z5.d, #1.0",
+            "fmov z6.d, #1.0",
+            "fmov z7.d, #1.0",
+            "fmov z8.d, #0.5",
+            "2:",
+            "fmad z0.d, p0/m, z8.d, z8.d",
+            "fmad z1.d, p0/m, z8.d, z8.d",
+            "fmad z2.d, p0/m, z8.d, z8.d",
+            "fmad z3.d, p0/m, z8.d, z8.d",
+            "fmad z4.d, p0/m, z8.d, z8.d",
+            "fmad z5.d, p0/m, z8.d, z8.d",
+            "fmad z6.d, p0/m, z8.d, z8.d",
+            "fmad z7.d, p0/m, z8.d, z8.d",
+            "subs {rounds}, {rounds}, #1",
+            "b.ne 2b",
+            rounds = inout(reg) rounds => _,
+            lanes = out(reg) lanes,
+            out("v0") _,
+            out("v1") _,
+            out("v2") _,
+            out("v3") _,
+            out("v4") _,
+            out("v5") _,
+            out("v6") _,
+            out("v7") _,
+            out("v8") _,
+            out("p0") _,
+            options(nostack, nomem),
+        );
+        black_box(lanes);
+        rounds * 8 * lanes * 2
+    }
+}
+
+/// Represents the in-process SIMD stress adapter.
+pub struct SimdStressAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl SimdStressAdapter {
+    /// Creates a new instance of `SimdStressAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `SimdStressAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        SimdStressAdapter { logger }
+    }
+
+    /// Detects the vector instruction sets with CPUID and the OS-enabled state.
+    #[cfg(target_arch = "x86_64")]
+    fn detect_isas() -> Vec<SimdIsa> {
+        [
+            (
+                is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma"),
+                SimdIsa::Avx2,
+            ),
+            (is_x86_feature_detected!("avx512f"), SimdIsa::Avx512),
+        ]
+        .into_iter()
+        .filter_map(|(detected, isa)| detected.then_some(isa))
+        .collect()
+    }
+
+    /// Detects the vector instruction sets the kernel reports.
+    #[cfg(target_arch = "aarch64")]
+    fn detect_isas() -> Vec<SimdIsa> {
+        use std::arch::is_aarch64_feature_detected;
+
+        [
+            (is_aarch64_feature_detected!("neon"), SimdIsa::Neon),
+            (is_aarch64_feature_detected!("sve"), SimdIsa::Sve),
+        ]
+        .into_iter()
+        .filter_map(|(detected, isa)| detected.then_some(isa))
+        .collect()
+    }
+
+    /// No vector kernel is written for other architectures.
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    fn detect_isas() -> Vec<SimdIsa> {
+        Vec::new()
+    }
+
+    /// Runs one call of the kernel of an instruction set the CPU was found to support.
+    ///
+    /// # Returns
+    /// The floating-point operations completed.
+    fn run_kernel(isa: SimdIsa, rounds: u64) -> u64 {
+        match isa {
+            // SAFETY: `run_simd_load` only runs the kernels of detected instruction sets.
+            #[cfg(target_arch = "x86_64")]
+            SimdIsa::Avx2 => unsafe { x86::avx2_kernel(rounds) },
+            #[cfg(target_arch = "x86_64")]
+            SimdIsa::Avx512 => unsafe { x86::avx512_kernel(rounds) },
+            #[cfg(target_arch = "aarch64")]
+            SimdIsa::Neon => unsafe { arm::neon_kernel(rounds) },
+            #[cfg(target_arch = "aarch64")]
+            SimdIsa::Sve => unsafe { arm::sve_kernel(rounds) },
+            _ => scalar_kernel(rounds),
+        }
+    }
+}
+
+// Implement the `SimdStressPort` trait for `SimdStressAdapter`.
+impl SimdStressPort for SimdStressAdapter {
+    fn supported_isas(&self) -> Vec<SimdIsa> {
+        Self::detect_isas()
+    }
+
+    fn run_simd_load(
+        &self,
+        isa: SimdIsa,
+        threads: usize,
+        deadline: Instant,
+        stop: &CancellationToken,
+    ) -> Result<f64, String> {
+        if isa != SimdIsa::Scalar && !Self::detect_isas().contains(&isa) {
+            return Err(format!("This CPU does not support {}", isa.label()));
+        }
+        self.logger.log_info(&format!(
+            "Running {} fused multiply-adds on {} threads",
+            isa.label(),
+            threads
+        ));
+
+        let started = Instant::now();
+        let flops: u64 = thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads.max(1))
+                .map(|_| {
+                    scope.spawn(move || {
+                        let mut flops = 0;
+                        while Instant::now() < deadline && !stop.is_cancelled() {
+                            flops += Self::run_kernel(isa, ROUNDS_PER_CALL);
+                        }
+                        flops
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().unwrap_or(0))
+                .sum()
+        });
+        Ok(flops as f64 / started.elapsed().as_secs_f64().max(1e-9))
+    }
+}
@@ -0,0 +1,194 @@
+//! SIMD Stress Runner
+//!
+//! This module runs the SIMD stress test started by `oneforall stress --simd`.
+//! The duration is split evenly between a scalar phase and one phase per vector
+//! instruction set tested, narrowest first, each loading every online CPU on a
+//! blocking thread while the clock of every core is sampled, so the report can
+//! show how far each instruction set pulls the clock below the scalar one.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use common::domain::job::{JobSpec, JobStatus, Resource};
+use common::ports::job_port::JobPort;
+use common::ports::log_port::LoggerPort;
+use common::ports::telemetry_port::TelemetryPort;
+use tokio_util::sync::CancellationToken;
+
+use crate::adapters::background_sampler::BackgroundSampler;
+use crate::adapters::frequency_adapter::FrequencyAdapter;
+use crate::adapters::progress_reporter::ProgressReporter;
+use crate::adapters::simd_stress_adapter::SimdStressAdapter;
+use crate::domain::burn_in::WorkloadOutcome;
+use crate::domain::simd::{phase_clock, select_phases, SimdIsa, SimdPhase, SimdStressReport};
+use crate::ports::frequency_port::FrequencyPort;
+use crate::ports::simd_stress_port::SimdStressPort;
+
+/// Interval between two readings of the clocks during a phase.
+const CLOCK_SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Runs SIMD stress tests.
+pub struct SimdStressRunner {
+    logger: Arc<dyn LoggerPort>,
+    telemetry: Arc<dyn TelemetryPort>,
+    jobs: Arc<dyn JobPort>,
+    simd: Arc<dyn SimdStressPort>,
+    frequencies: Arc<dyn FrequencyPort>,
+}
+
+impl SimdStressRunner {
+    /// Creates a new instance of `SimdStressRunner`.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger` - Logger implementation for logging messages.
+    /// * `telemetry` - Receives the progress of the test.
+    /// * `jobs` - The job queue the test is submitted to.
+    ///
+    /// # Returns
+    ///
+    /// * `SimdStressRunner` - A runner loading the CPUs of this machine.
+    pub fn new(
+        logger: Arc<dyn LoggerPort>,
+        telemetry: Arc<dyn TelemetryPort>,
+        jobs: Arc<dyn JobPort>,
+    ) -> Self {
+        SimdStressRunner {
+            simd: Arc::new(SimdStressAdapter::new(logger.clone())),
+            frequencies: Arc::new(FrequencyAdapter::new(logger.clone())),
+            logger,
+            telemetry,
+            jobs,
+        }
+    }
+
+    /// Runs the phases one after another on every online CPU.
+    fn run_phases(
+        simd: Arc<dyn SimdStressPort>,
+        frequencies: Arc<dyn FrequencyPort>,
+        phases: &[SimdIsa],
+        threads: usize,
+        phase_duration: Duration,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<SimdPhase>, String> {
+        let mut results = Vec::new();
+        for isa in phases {
+            if cancel.is_cancelled() {
+                break;
+            }
+            let clocks = {
+                let frequencies = frequencies.clone();
+                BackgroundSampler::start(CLOCK_SAMPLE_INTERVAL, move || {
+                    frequencies.sample_frequencies().ok()
+                })
+            };
+            let started = Instant::now();
+            let outcome = simd.run_simd_load(*isa, threads, started + phase_duration, cancel);
+            let elapsed = started.elapsed();
+            let (avg_mhz, min_mhz) = phase_clock(&clocks.stop());
+            results.push(SimdPhase {
+                isa: *isa,
+                elapsed_secs: elapsed.as_secs_f64(),
+                gflops: outcome? / 1e9,
+                avg_mhz,
+                min_mhz,
+            });
+        }
+        Ok(results)
+    }
+
+    /// Runs the SIMD stress test for the given duration.
+    ///
+    /// # Arguments
+    ///
+    /// * `requested` - The vector instruction set to test; every supported one when `None`.
+    /// * `duration` - How long the whole test runs, split evenly between its phases.
+    ///
+    /// # Returns
+    ///
+    /// * `SimdStressReport` - The throughput and clock of each phase.
+    pub async fn run(&self, requested: Option<SimdIsa>, duration: Duration) -> SimdStressReport {
+        let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+        let mut report = SimdStressReport {
+            started_at: Local::now().to_rfc3339(),
+            planned_secs: duration.as_secs(),
+            elapsed_secs: 0,
+            outcome: WorkloadOutcome::Cancelled,
+            threads,
+            phases: Vec::new(),
+        };
+        let phases = match select_phases(&self.simd.supported_isas(), requested) {
+            Ok(phases) => phases,
+            Err(e) => {
+                self.logger.log_error(&e);
+                report.outcome = WorkloadOutcome::Failed(e);
+                return report;
+            }
+        };
+
+        let mut ticket = self
+            .jobs
+            .submit_job(JobSpec::new("stress:simd", &[Resource::Cpu]));
+        let job_id = ticket.job.id;
+        if ticket.job.status == JobStatus::Queued {
+            self.logger.log_info(&format!(
+                "SIMD stress test job {} queued behind a conflicting job.",
+                job_id
+            ));
+        }
+        if !ticket.admitted().await {
+            self.logger
+                .log_warn("SIMD stress test cancelled while queued.");
+            return report;
+        }
+        let phase_duration = duration / phases.len() as u32;
+        self.logger.log_info(&format!(
+            "Started SIMD stress test job {}: {} for {} seconds each.",
+            job_id,
+            phases
+                .iter()
+                .map(SimdIsa::label)
+                .collect::<Vec<_>>()
+                .join(", then "),
+            phase_duration.as_secs()
+        ));
+
+        let progress = ProgressReporter::start(
+            self.logger.clone(),
+            self.telemetry.clone(),
+            "simd",
+            duration,
+        );
+        let started = Instant::now();
+        let (simd, frequencies) = (self.simd.clone(), self.frequencies.clone());
+        let cancel = ticket.cancel.clone();
+        let outcome = tokio::task::spawn_blocking(move || {
+            Self::run_phases(simd, frequencies, &phases, threads, phase_duration, &cancel)
+        })
+        .await
+        .unwrap_or_else(|e| Err(format!("SIMD stress test panicked: {}", e)));
+        report.elapsed_secs = started.elapsed().as_secs();
+        progress.finish(None);
+
+        let (status, message) = match outcome {
+            Ok(results) if ticket.cancel.is_cancelled() => {
+                report.phases = results;
+                (JobStatus::Cancelled, None)
+            }
+            Ok(results) => {
+                report.phases = results;
+                report.outcome = WorkloadOutcome::Completed;
+                (JobStatus::Completed, None)
+            }
+            Err(e) => {
+                self.logger.log_error(&e);
+                report.outcome = WorkloadOutcome::Failed(e.clone());
+                (JobStatus::Failed, Some(e))
+            }
+        };
+        self.jobs.finish_job(job_id, status, message);
+
+        report
+    }
+}
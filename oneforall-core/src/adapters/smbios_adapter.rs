@@ -0,0 +1,70 @@
+//! SMBIOS Adapter
+//!
+//! This module provides an adapter that reads the SMBIOS table the firmware
+//! publishes, which the kernel exposes unchanged in sysfs. The table describes
+//! hardware the operating system cannot probe itself, such as which DIMM sits
+//! in which slot and the speed it is rated for. The table is only readable by
+//! root.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::sync::Arc;
+
+use common::ports::log_port::LoggerPort;
+
+use crate::domain::dimm::{parse_smbios_memory_devices, Dimm};
+use crate::ports::dimm_port::DimmPort;
+
+/// Where the kernel exposes the raw SMBIOS table.
+const SYSFS_SMBIOS_TABLE: &str = "/sys/firmware/dmi/tables/DMI";
+
+/// Represents the sysfs SMBIOS adapter.
+pub struct SmbiosAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl SmbiosAdapter {
+    /// Creates a new instance of `SmbiosAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `SmbiosAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        SmbiosAdapter { logger }
+    }
+
+    /// Reads the raw SMBIOS table, or `None` if the firmware publishes none.
+    fn read_table(&self) -> Result<Option<Vec<u8>>, String> {
+        match fs::read(SYSFS_SMBIOS_TABLE) {
+            Ok(table) => Ok(Some(table)),
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                self.logger
+                    .log_debug("No SMBIOS table published by the firmware.");
+                Ok(None)
+            }
+            Err(e) if e.kind() == ErrorKind::PermissionDenied => Err(format!(
+                "Permission denied reading {}; run as root to read the SMBIOS table",
+                SYSFS_SMBIOS_TABLE
+            )),
+            Err(e) => Err(format!("Failed to read {}: {}", SYSFS_SMBIOS_TABLE, e)),
+        }
+    }
+}
+
+// Implement the `DimmPort` trait for `SmbiosAdapter`.
+impl DimmPort for SmbiosAdapter {
+    fn discover_dimms(&self) -> Result<Vec<Dimm>, String> {
+        let Some(table) = self.read_table()? else {
+            return Ok(Vec::new());
+        };
+        let dimms = parse_smbios_memory_devices(&table);
+        self.logger.log_debug(&format!(
+            "SMBIOS lists {} DIMM slots, {} populated",
+            dimms.len(),
+            dimms.iter().filter(|dimm| dimm.is_populated()).count()
+        ));
+        Ok(dimms)
+    }
+}
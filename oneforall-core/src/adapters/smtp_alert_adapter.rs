@@ -0,0 +1,129 @@
+//! SMTP Alert Adapter
+//!
+//! This module provides an adapter that emails alerts through an SMTP relay.
+//! The connection is secured with STARTTLS unless the destination asks for
+//! implicit TLS or, for a relay on the machine itself or the lab network such
+//! as a local Postfix, plain SMTP; the relay may require a user name and
+//! password.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::ports::log_port::LoggerPort;
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::transport::smtp::client::{Tls, TlsParameters};
+use lettre::transport::smtp::extension::ClientId;
+use lettre::{Message, SmtpTransport, Transport};
+
+use crate::domain::alert::{subscribes, Alert, AlertKind, EmailTarget, SmtpTls};
+use crate::ports::alert_port::AlertPort;
+
+/// Time allowed for the relay to connect and to answer each command.
+const SMTP_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Represents an email destination reached through an SMTP relay.
+pub struct SmtpAlertAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    target: EmailTarget,
+}
+
+/// Parses an address of the destination, e.g. "hw-lab@example.com".
+fn mailbox(address: &str) -> Result<Mailbox, String> {
+    address
+        .parse()
+        .map_err(|e| format!("Invalid email address '{}': {}", address, e))
+}
+
+/// Builds the message; its headers are encoded by lettre, which rejects
+/// addresses with line breaks, and the subject is kept to a single line.
+fn build_message(target: &EmailTarget, alert: &Alert) -> Result<Message, String> {
+    let mut builder = Message::builder()
+        .from(mailbox(&target.from)?)
+        .subject(alert.subject())
+        .header(ContentType::TEXT_PLAIN);
+    for recipient in &target.to {
+        builder = builder.to(mailbox(recipient)?);
+    }
+    builder
+        .body(alert.render())
+        .map_err(|e| format!("Failed to build the email: {}", e))
+}
+
+/// Builds the transport to the relay of the destination.
+fn build_transport(target: &EmailTarget, hostname: &str) -> Result<SmtpTransport, String> {
+    let (host, port) = target
+        .server
+        .rsplit_once(':')
+        .and_then(|(host, port)| Some((host, port.parse::<u16>().ok()?)))
+        .ok_or_else(|| {
+            format!(
+                "Invalid SMTP server '{}': expected host:port",
+                target.server
+            )
+        })?;
+    let parameters = || {
+        TlsParameters::new(host.to_string())
+            .map_err(|e| format!("Failed to set up TLS for {}: {}", host, e))
+    };
+    let tls = match target.tls {
+        SmtpTls::Starttls => Tls::Required(parameters()?),
+        SmtpTls::Implicit => Tls::Wrapper(parameters()?),
+        SmtpTls::None => Tls::None,
+    };
+    let mut builder = SmtpTransport::builder_dangerous(host)
+        .port(port)
+        .tls(tls)
+        .timeout(Some(SMTP_TIMEOUT))
+        .hello_name(ClientId::Domain(hostname.to_string()));
+    if let Some(username) = &target.username {
+        let password = match &target.password_file {
+            Some(path) => std::fs::read_to_string(path)
+                .map(|password| password.trim().to_string())
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?,
+            None => target.password.clone().unwrap_or_default(),
+        };
+        builder = builder.credentials(Credentials::new(username.clone(), password));
+    }
+    Ok(builder.build())
+}
+
+impl SmtpAlertAdapter {
+    /// Creates a new instance of `SmtpAlertAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    /// * `target` - The email destination from the `[alerts]` configuration.
+    ///
+    /// # Returns
+    /// An instance of `SmtpAlertAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>, target: EmailTarget) -> Self {
+        SmtpAlertAdapter { logger, target }
+    }
+}
+
+// Implement the `AlertPort` trait for `SmtpAlertAdapter`.
+impl AlertPort for SmtpAlertAdapter {
+    fn describe(&self) -> String {
+        format!("email '{}'", self.target.name)
+    }
+
+    fn accepts(&self, kind: AlertKind) -> bool {
+        subscribes(&self.target.events, kind)
+    }
+
+    fn send(&self, alert: &Alert) -> Result<(), String> {
+        self.logger.log_debug(&format!(
+            "Mailing a {} alert to {} through {}",
+            alert.kind.label(),
+            self.target.to.join(", "),
+            self.target.server
+        ));
+        let message = build_message(&self.target, alert)?;
+        build_transport(&self.target, &alert.hostname)?
+            .send(&message)
+            .map_err(|e| format!("Failed to send the email: {}", e))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,386 @@
+//! Soak Runner
+//!
+//! This module runs the soak test started by `oneforall stress --soak`, or by
+//! a profile step with a duty cycle. Each cycle runs a stress-ng stressor for
+//! the load period, then leaves the machine idle for the idle period, until
+//! the duration is used up. The temperature is sampled over the whole run and
+//! split by cycle afterwards, while the ECC and machine check counters are
+//! read and the kernel log is watched around each cycle, so every fault is
+//! reported against the cycle it happened in.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use common::domain::job::{JobSpec, JobStatus};
+use common::ports::job_port::JobPort;
+use common::ports::log_port::LoggerPort;
+use common::ports::telemetry_port::TelemetryPort;
+use tokio_util::sync::CancellationToken;
+
+use crate::adapters::background_sampler::BackgroundSampler;
+use crate::adapters::cgroup_adapter::CgroupAdapter;
+use crate::adapters::hardware_error_adapter::HardwareErrorAdapter;
+use crate::adapters::kernel_log_watcher::KernelLogWatcher;
+use crate::adapters::progress_reporter::ProgressReporter;
+use crate::adapters::stress_ng_adapter::{run_output_file, take_run_output, StressNgAdapter};
+use crate::adapters::thermal_adapter::ThermalAdapter;
+use crate::domain::burn_in::WorkloadOutcome;
+use crate::domain::hardware_errors::new_ecc_errors;
+use crate::domain::plan::{ExecutionPlan, PlannedStep};
+use crate::domain::profile::format_duration;
+use crate::domain::soak::{cycle_temperatures, DutyCycle, SoakCycle, SoakReport};
+use crate::domain::stress_ng::{
+    parse_bogo_ops, parse_bogo_ops_rate, stressor_disk_writes, stressor_resource, StressRunOutcome,
+};
+use crate::ports::container_port::ContainerPort;
+use crate::ports::hardware_error_port::HardwareErrorPort;
+use crate::ports::thermal_port::ThermalPort;
+
+/// Interval between two temperature readings; long enough not to load the
+/// BMC over a 24 hour soak, short enough to catch the peak of each cycle.
+const THERMAL_SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Returns the stress-ng arguments of one load period.
+fn load_args(stressor: &str, workers: u32, load: Duration) -> Vec<String> {
+    vec![
+        format!("--{}", stressor),
+        workers.to_string(),
+        "--timeout".to_string(),
+        format!("{}s", load.as_secs().max(1)),
+        "--metrics-brief".to_string(),
+    ]
+}
+
+/// Plans a soak test as one step, for `--dry-run` and profile plans.
+///
+/// # Arguments
+///
+/// * `name` - The name of the planned step.
+/// * `stressor` - The stress-ng stressor of the load periods.
+/// * `workers` - The stress-ng workers; `0` starts one per online CPU.
+/// * `duty` - The load and idle periods to alternate.
+/// * `duration` - How long the whole soak test runs.
+///
+/// # Returns
+///
+/// * `PlannedStep` - The stress-ng command of a load period and the number of cycles.
+pub fn planned_soak_step(
+    name: &str,
+    stressor: &str,
+    workers: u32,
+    duty: DutyCycle,
+    duration: Duration,
+) -> PlannedStep {
+    let started = match workers {
+        0 => std::thread::available_parallelism().map_or(1, |cpus| cpus.get()),
+        workers => workers as usize,
+    };
+    let cycles = duty.cycles(duration).len();
+    PlannedStep {
+        duration: Some(duration),
+        targets: vec![
+            if workers == 0 {
+                "one worker per online CPU".to_string()
+            } else {
+                format!("{} worker(s)", workers)
+            },
+            format!("{} cycle(s) of {}", cycles, duty.describe()),
+        ],
+        disk_writes: stressor_disk_writes(stressor, (started * cycles) as u64),
+        ..PlannedStep::new(
+            name,
+            format!(
+                "stress-ng {} per load period",
+                load_args(stressor, workers, duty.on).join(" ")
+            ),
+            &[stressor_resource(stressor)],
+        )
+    }
+}
+
+/// Runs soak tests.
+pub struct SoakRunner {
+    logger: Arc<dyn LoggerPort>,
+    telemetry: Arc<dyn TelemetryPort>,
+    jobs: Arc<dyn JobPort>,
+    errors: Box<dyn HardwareErrorPort>,
+    container: Box<dyn ContainerPort>,
+    thermal: Arc<dyn ThermalPort>,
+}
+
+impl SoakRunner {
+    /// Creates a new instance of `SoakRunner`.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger` - Logger implementation for logging messages.
+    /// * `telemetry` - Receives the progress of the test.
+    /// * `jobs` - The job queue the test is submitted to.
+    ///
+    /// # Returns
+    ///
+    /// * `SoakRunner` - A runner cycling the load on this machine.
+    pub fn new(
+        logger: Arc<dyn LoggerPort>,
+        telemetry: Arc<dyn TelemetryPort>,
+        jobs: Arc<dyn JobPort>,
+    ) -> Self {
+        SoakRunner {
+            errors: Box::new(HardwareErrorAdapter::new(logger.clone())),
+            container: Box::new(CgroupAdapter::new(logger.clone())),
+            thermal: Arc::new(ThermalAdapter::new(logger.clone())),
+            logger,
+            telemetry,
+            jobs,
+        }
+    }
+
+    /// Returns the stress-ng workers for the CPU stressor: one per usable CPU
+    /// in a CPU-limited cgroup, where stress-ng's one per host CPU oversubscribes.
+    fn cpu_workers(container: &dyn ContainerPort) -> u32 {
+        container
+            .read_resource_limits()
+            .ok()
+            .filter(|limits| limits.is_cpu_limited())
+            .map_or(0, |limits| limits.effective_cpus() as u32)
+    }
+
+    /// Plans `stress --soak` for `--dry-run`, without running or queueing it.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger` - Logger implementation for logging messages.
+    /// * `duty` - The load and idle periods to alternate.
+    /// * `duration` - How long the whole soak test runs.
+    ///
+    /// # Returns
+    ///
+    /// * `ExecutionPlan` - The soak test as a single step.
+    pub fn plan(logger: Arc<dyn LoggerPort>, duty: DutyCycle, duration: Duration) -> ExecutionPlan {
+        let workers = Self::cpu_workers(&CgroupAdapter::new(logger));
+        let mut plan = ExecutionPlan::new("stress --soak");
+        plan.steps
+            .push(planned_soak_step("soak", "cpu", workers, duty, duration));
+        plan
+    }
+
+    /// Queues the soak test as a job and runs it once admitted, loading every
+    /// usable CPU with the stress-ng CPU stressor.
+    ///
+    /// # Arguments
+    ///
+    /// * `duty` - The load and idle periods to alternate.
+    /// * `duration` - How long the whole soak test runs.
+    ///
+    /// # Returns
+    ///
+    /// * `SoakReport` - The temperatures and error counters of each cycle.
+    pub async fn run(&self, duty: DutyCycle, duration: Duration) -> SoakReport {
+        let stressor = "cpu";
+        let mut ticket = self.jobs.submit_job(JobSpec::new(
+            &format!("stress:{}", stressor),
+            &[stressor_resource(stressor)],
+        ));
+        let job_id = ticket.job.id;
+        if ticket.job.status == JobStatus::Queued {
+            self.logger.log_info(&format!(
+                "Soak test job {} queued behind a conflicting job.",
+                job_id
+            ));
+        }
+        if !ticket.admitted().await {
+            self.logger.log_warn("Soak test cancelled while queued.");
+            return SoakReport {
+                started_at: Local::now().to_rfc3339(),
+                planned_secs: duration.as_secs(),
+                elapsed_secs: 0,
+                outcome: WorkloadOutcome::Cancelled,
+                stressor: stressor.to_string(),
+                on_secs: duty.on.as_secs(),
+                off_secs: duty.off.as_secs(),
+                cycles: Vec::new(),
+                kernel_log_unavailable: None,
+            };
+        }
+        self.logger.log_info(&format!(
+            "Started soak test job {}: {} for {}.",
+            job_id,
+            duty.describe(),
+            format_duration(duration)
+        ));
+
+        let workers = Self::cpu_workers(self.container.as_ref());
+        let report = self
+            .run_cycles(stressor, workers, duty, duration, &ticket.cancel)
+            .await;
+        let (status, message) = match &report.outcome {
+            WorkloadOutcome::Completed => (JobStatus::Completed, None),
+            WorkloadOutcome::Cancelled => (JobStatus::Cancelled, None),
+            WorkloadOutcome::Failed(e) => (JobStatus::Failed, Some(e.clone())),
+        };
+        self.jobs.finish_job(job_id, status, message);
+        report
+    }
+
+    /// Runs the cycles of a soak test on an already admitted job.
+    ///
+    /// # Arguments
+    ///
+    /// * `stressor` - The stress-ng stressor of the load periods, e.g. "cpu".
+    /// * `workers` - The stress-ng workers; `0` starts one per online CPU.
+    /// * `duty` - The load and idle periods to alternate.
+    /// * `duration` - How long the whole soak test runs.
+    /// * `cancel` - Stops the current period and skips the remaining cycles once cancelled.
+    ///
+    /// # Returns
+    ///
+    /// * `SoakReport` - The temperatures and error counters of each cycle.
+    pub async fn run_cycles(
+        &self,
+        stressor: &str,
+        workers: u32,
+        duty: DutyCycle,
+        duration: Duration,
+        cancel: &CancellationToken,
+    ) -> SoakReport {
+        let mut report = SoakReport {
+            started_at: Local::now().to_rfc3339(),
+            planned_secs: duration.as_secs(),
+            elapsed_secs: 0,
+            outcome: WorkloadOutcome::Completed,
+            stressor: stressor.to_string(),
+            on_secs: duty.on.as_secs(),
+            off_secs: duty.off.as_secs(),
+            cycles: Vec::new(),
+            kernel_log_unavailable: None,
+        };
+        let plan = duty.cycles(duration);
+
+        let progress = ProgressReporter::start(
+            self.logger.clone(),
+            self.telemetry.clone(),
+            stressor,
+            duration,
+        );
+        let thermals = {
+            let thermal = self.thermal.clone();
+            BackgroundSampler::start(THERMAL_SAMPLE_INTERVAL, move || {
+                thermal.sample_thermals().ok()
+            })
+        };
+        let started = Instant::now();
+        // Each cycle's offsets are kept so the temperatures can be split once sampling stops.
+        let mut periods = Vec::new();
+        let mut bogo_ops_rates = Vec::new();
+
+        for (index, (load, idle)) in plan.iter().enumerate() {
+            if cancel.is_cancelled() {
+                report.outcome = WorkloadOutcome::Cancelled;
+                break;
+            }
+            self.logger.log_info(&format!(
+                "Soak cycle {}/{}: loading for {}",
+                index + 1,
+                plan.len(),
+                format_duration(*load)
+            ));
+
+            let ecc_before = self.errors.read_ecc_counts().ok();
+            let machine_checks_before = self.errors.count_machine_checks().ok();
+            let kernel_log = KernelLogWatcher::start(self.logger.clone());
+            let load_started = started.elapsed();
+
+            let args = load_args(stressor, workers, *load);
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            let output_file = run_output_file();
+            progress.follow_runs(std::slice::from_ref(&output_file));
+            let outcome = StressNgAdapter::execute_stress_ng_command_to(
+                self.logger.clone(),
+                &args,
+                false,
+                &output_file,
+                cancel,
+            )
+            .await;
+            let output = take_run_output(&output_file);
+            let bogo_ops = parse_bogo_ops(&output);
+            bogo_ops_rates.extend(parse_bogo_ops_rate(&output));
+            let load_ended = started.elapsed();
+            match outcome {
+                Ok(StressRunOutcome::Completed) => {}
+                Ok(StressRunOutcome::Cancelled) => report.outcome = WorkloadOutcome::Cancelled,
+                Err(e) => {
+                    self.logger.log_error(&e);
+                    report.outcome = WorkloadOutcome::Failed(e);
+                }
+            }
+
+            // The idle period ends early once cancelled, so the cycle is still reported.
+            if report.outcome == WorkloadOutcome::Completed && !idle.is_zero() {
+                self.logger.log_info(&format!(
+                    "Soak cycle {}/{}: idle for {}",
+                    index + 1,
+                    plan.len(),
+                    format_duration(*idle)
+                ));
+                tokio::select! {
+                    _ = tokio::time::sleep(*idle) => {}
+                    _ = cancel.cancelled() => {
+                        report.outcome = WorkloadOutcome::Cancelled;
+                    }
+                }
+            }
+            let idle_ended = started.elapsed();
+
+            let findings = kernel_log.finish();
+            if let Err(reason) = &findings.source {
+                report.kernel_log_unavailable = Some(reason.clone());
+            }
+            let cycle = SoakCycle {
+                number: index as u32 + 1,
+                load_secs: (load_ended - load_started).as_secs_f64(),
+                idle_secs: (idle_ended - load_ended).as_secs_f64(),
+                bogo_ops,
+                load_peak_celsius: None,
+                idle_low_celsius: None,
+                ecc_errors: ecc_before
+                    .zip(self.errors.read_ecc_counts().ok())
+                    .map(|(before, after)| new_ecc_errors(&before, &after)),
+                machine_checks: machine_checks_before
+                    .zip(self.errors.count_machine_checks().ok())
+                    .map(|(before, after)| after.saturating_sub(before)),
+                kernel_faults: findings.faults(),
+            };
+            if cycle.faulted() {
+                self.logger.log_warn(&format!(
+                    "Soak cycle {} saw {} ECC error(s), {} machine check(s) and {} kernel fault(s)",
+                    cycle.number,
+                    cycle.ecc_errors.unwrap_or(0),
+                    cycle.machine_checks.unwrap_or(0),
+                    cycle.kernel_faults.len()
+                ));
+            }
+            report.cycles.push(cycle);
+            periods.push((load_started..load_ended, load_ended..idle_ended));
+
+            if report.outcome != WorkloadOutcome::Completed {
+                break;
+            }
+        }
+
+        report.elapsed_secs = started.elapsed().as_secs();
+        // Every load period runs the same workers, so their mean rate is the rate of the run.
+        progress.finish(
+            Some(bogo_ops_rates.iter().sum::<f64>() / bogo_ops_rates.len() as f64)
+                .filter(|_| !bogo_ops_rates.is_empty()),
+        );
+        let samples = thermals.stop();
+        for (cycle, (load, idle)) in report.cycles.iter_mut().zip(periods) {
+            (cycle.load_peak_celsius, cycle.idle_low_celsius) =
+                cycle_temperatures(&samples, load, idle);
+        }
+
+        report
+    }
+}
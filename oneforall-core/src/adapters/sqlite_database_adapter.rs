@@ -0,0 +1,323 @@
+//! SQLite Database Adapter
+//!
+//! This module provides a `DatabasePort` backed by SQLite. Instead of a single
+//! key/value tree it keeps run results, collector readings and hardware
+//! inventories in tables of their own, so users can write their own reports
+//! with SQL, e.g.
+//!
+//! ```sql
+//! SELECT started_at, finished_at, json_extract(result, '$.status') FROM runs WHERE kind = 'job';
+//! ```
+//!
+//! The `records` view presents every table as the key/value pairs of the
+//! port, which is what prefix and range scans read.
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use common::ports::log_port::LoggerPort;
+use rusqlite::{params, Connection, OptionalExtension, ToSql};
+use sled::IVec;
+
+use crate::domain::database::{RecordKind, INVENTORY_PREFIX};
+use crate::ports::database_port::DatabasePort;
+
+/// The tables of the database, created when it is first opened.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    key         TEXT PRIMARY KEY,
+    kind        TEXT NOT NULL,
+    started_at  TEXT,
+    finished_at TEXT,
+    result      TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS runs_by_kind ON runs (kind, started_at);
+CREATE TABLE IF NOT EXISTS samples (
+    name        TEXT NOT NULL,
+    recorded_at TEXT NOT NULL,
+    value       TEXT NOT NULL,
+    PRIMARY KEY (name, recorded_at)
+);
+CREATE TABLE IF NOT EXISTS inventory (
+    key          TEXT PRIMARY KEY,
+    collected_at TEXT NOT NULL,
+    report       TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS entries (
+    key   BLOB PRIMARY KEY,
+    value BLOB NOT NULL
+);
+CREATE VIEW IF NOT EXISTS records (key, value) AS
+    SELECT CAST(key AS BLOB), CAST(result AS BLOB) FROM runs
+    UNION ALL
+    SELECT CAST(key AS BLOB), CAST(report AS BLOB) FROM inventory
+    UNION ALL
+    SELECT CAST('sample:' || name || ':' || recorded_at AS BLOB), CAST(value AS BLOB) FROM samples
+    UNION ALL
+    SELECT key, value FROM entries;
+";
+
+/// How long a write waits for another process holding the database, such as a
+/// user running a report, before failing.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A struct that serves as an adapter for the `DatabasePort` trait using an SQLite database.
+pub struct SqliteDatabaseAdapter {
+    connection: Mutex<Connection>, // rusqlite connections are not shared between threads
+    path: PathBuf,
+}
+
+/// Reads a string field of a stored JSON document, such as its start time.
+fn json_field(value: &[u8], field: &str) -> Option<String> {
+    let document: serde_json::Value = serde_json::from_slice(value).ok()?;
+    document.get(field)?.as_str().map(str::to_string)
+}
+
+impl SqliteDatabaseAdapter {
+    /// Opens a new database or creates it if it doesn't exist, acting as an adapter.
+    pub fn new(path: &Path, logger: Arc<dyn LoggerPort>) -> Result<Self, Box<dyn Error>> {
+        let connection = Connection::open(path)?;
+        connection.busy_timeout(BUSY_TIMEOUT)?;
+        // Readers running reports never block the collectors writing readings.
+        connection.pragma_update(None, "journal_mode", "WAL")?;
+        connection.execute_batch(SCHEMA)?;
+        logger.log_info(&format!(
+            "SQLite database opened at path: {}",
+            path.display()
+        ));
+        Ok(SqliteDatabaseAdapter {
+            connection: Mutex::new(connection),
+            path: path.to_path_buf(),
+        })
+    }
+
+    fn connection(&self) -> Result<MutexGuard<'_, Connection>, Box<dyn Error>> {
+        self.connection
+            .lock()
+            .map_err(|_| "the SQLite connection is poisoned".into())
+    }
+
+    /// Reads the value stored under a key.
+    fn read(connection: &Connection, key: &[u8]) -> Result<Option<IVec>, Box<dyn Error>> {
+        let text = String::from_utf8_lossy(key);
+        let value: Option<Vec<u8>> = match RecordKind::of(key) {
+            RecordKind::Run { .. } => connection
+                .query_row(
+                    "SELECT CAST(result AS BLOB) FROM runs WHERE key = ?1",
+                    params![text],
+                    |row| row.get(0),
+                )
+                .optional()?,
+            RecordKind::Inventory => connection
+                .query_row(
+                    "SELECT CAST(report AS BLOB) FROM inventory WHERE key = ?1",
+                    params![text],
+                    |row| row.get(0),
+                )
+                .optional()?,
+            RecordKind::Sample {
+                collector,
+                recorded_at,
+            } => connection
+                .query_row(
+                    "SELECT CAST(value AS BLOB) FROM samples WHERE name = ?1 AND recorded_at = ?2",
+                    params![collector, recorded_at],
+                    |row| row.get(0),
+                )
+                .optional()?,
+            RecordKind::Other => connection
+                .query_row(
+                    "SELECT value FROM entries WHERE key = ?1",
+                    params![key],
+                    |row| row.get(0),
+                )
+                .optional()?,
+        };
+        Ok(value.map(IVec::from))
+    }
+
+    /// Writes a key-value pair to the table of its kind of record.
+    fn write(connection: &Connection, key: &[u8], value: &[u8]) -> Result<(), Box<dyn Error>> {
+        let text = String::from_utf8_lossy(key);
+        let document = String::from_utf8_lossy(value);
+        match RecordKind::of(key) {
+            RecordKind::Run { kind } => connection.execute(
+                "INSERT OR REPLACE INTO runs (key, kind, started_at, finished_at, result) \
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    text,
+                    kind,
+                    json_field(value, "started_at"),
+                    json_field(value, "finished_at"),
+                    document
+                ],
+            )?,
+            RecordKind::Inventory => connection.execute(
+                "INSERT OR REPLACE INTO inventory (key, collected_at, report) VALUES (?1, ?2, ?3)",
+                params![
+                    text,
+                    text.strip_prefix(INVENTORY_PREFIX).unwrap_or_default(),
+                    document
+                ],
+            )?,
+            RecordKind::Sample {
+                collector,
+                recorded_at,
+            } => connection.execute(
+                "INSERT OR REPLACE INTO samples (name, recorded_at, value) VALUES (?1, ?2, ?3)",
+                params![collector, recorded_at, document],
+            )?,
+            RecordKind::Other => connection.execute(
+                "INSERT OR REPLACE INTO entries (key, value) VALUES (?1, ?2)",
+                params![key, value],
+            )?,
+        };
+        Ok(())
+    }
+
+    /// Reads the key-value pairs of the `records` view matching a condition on their key.
+    fn scan(
+        &self,
+        condition: &str,
+        parameters: &[&dyn ToSql],
+    ) -> Result<Vec<(IVec, IVec)>, Box<dyn Error>> {
+        let connection = self.connection()?;
+        // Keys are blobs, so they are compared and sorted as bytes, as sled does.
+        let mut statement = connection.prepare(&format!(
+            "SELECT key, value FROM records WHERE {} ORDER BY key",
+            condition
+        ))?;
+        let pairs = statement
+            .query_map(parameters, |row| {
+                Ok((row.get::<_, Vec<u8>>(0)?, row.get::<_, Vec<u8>>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(pairs
+            .into_iter()
+            .map(|(key, value)| (IVec::from(key), IVec::from(value)))
+            .collect())
+    }
+}
+
+/// Implement the `DatabasePort` trait for the `SqliteDatabaseAdapter` struct.
+impl DatabasePort for SqliteDatabaseAdapter {
+    /// Inserts a key-value pair
+    fn insert(&self, key: &[u8], value: &[u8]) -> Result<Option<IVec>, Box<dyn Error>> {
+        let mut connection = self.connection()?;
+        let transaction = connection.transaction()?;
+        let previous_value = Self::read(&transaction, key)?;
+        Self::write(&transaction, key, value)?;
+        transaction.commit()?;
+        Ok(previous_value)
+    }
+
+    /// Inserts key-value pairs in a single transaction
+    fn insert_batch(&self, pairs: &[(Vec<u8>, Vec<u8>)]) -> Result<(), Box<dyn Error>> {
+        let mut connection = self.connection()?;
+        let transaction = connection.transaction()?;
+        for (key, value) in pairs {
+            Self::write(&transaction, key, value)?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// Retrieves a value
+    fn get(&self, key: &[u8]) -> Result<Option<IVec>, Box<dyn Error>> {
+        let connection = self.connection()?;
+        Self::read(&connection, key)
+    }
+
+    /// Removes a key-value pair
+    fn remove(&self, key: &[u8]) -> Result<Option<IVec>, Box<dyn Error>> {
+        let mut connection = self.connection()?;
+        let transaction = connection.transaction()?;
+        let previous_value = Self::read(&transaction, key)?;
+        let text = String::from_utf8_lossy(key);
+        match RecordKind::of(key) {
+            RecordKind::Run { .. } => {
+                transaction.execute("DELETE FROM runs WHERE key = ?1", params![text])?
+            }
+            RecordKind::Inventory => {
+                transaction.execute("DELETE FROM inventory WHERE key = ?1", params![text])?
+            }
+            RecordKind::Sample {
+                collector,
+                recorded_at,
+            } => transaction.execute(
+                "DELETE FROM samples WHERE name = ?1 AND recorded_at = ?2",
+                params![collector, recorded_at],
+            )?,
+            RecordKind::Other => {
+                transaction.execute("DELETE FROM entries WHERE key = ?1", params![key])?
+            }
+        };
+        transaction.commit()?;
+        Ok(previous_value)
+    }
+
+    /// Retrieves the key-value pairs under a prefix, in key order
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<Vec<(IVec, IVec)>, Box<dyn Error>> {
+        self.scan(
+            "substr(key, 1, ?1) = ?2",
+            &[&(prefix.len() as i64), &prefix],
+        )
+    }
+
+    /// Retrieves the key-value pairs from `start` up to but excluding `end`, in key order
+    fn scan_range(&self, start: &[u8], end: &[u8]) -> Result<Vec<(IVec, IVec)>, Box<dyn Error>> {
+        self.scan("key >= ?1 AND key < ?2", &[&start, &end])
+    }
+
+    /// Removes the key-value pairs from `start` up to but excluding `end`, returning how many
+    fn remove_range(&self, start: &[u8], end: &[u8]) -> Result<usize, Box<dyn Error>> {
+        let mut connection = self.connection()?;
+        let transaction = connection.transaction()?;
+        let mut removed = 0;
+        for (table, key) in [
+            ("runs", "CAST(key AS BLOB)"),
+            ("inventory", "CAST(key AS BLOB)"),
+            (
+                "samples",
+                "CAST('sample:' || name || ':' || recorded_at AS BLOB)",
+            ),
+            ("entries", "key"),
+        ] {
+            removed += transaction.execute(
+                &format!("DELETE FROM {} WHERE {} >= ?1 AND {} < ?2", table, key, key),
+                params![start, end],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(removed)
+    }
+
+    /// Returns the space the database and its write-ahead log take on disk, in bytes
+    fn size_on_disk(&self) -> Result<u64, Box<dyn Error>> {
+        let mut size = fs::metadata(&self.path)?.len();
+        let mut wal = self.path.clone().into_os_string();
+        wal.push("-wal");
+        if let Ok(metadata) = fs::metadata(wal) {
+            size += metadata.len();
+        }
+        Ok(size)
+    }
+
+    /// Rebuilds the database without the pages freed by removed pairs
+    fn compact(&self) -> Result<(), Box<dyn Error>> {
+        let connection = self.connection()?;
+        connection.execute_batch("VACUUM; PRAGMA wal_checkpoint(TRUNCATE);")?;
+        Ok(())
+    }
+
+    /// Moves the changes in the write-ahead log into the database file, e.g. before the
+    /// application exits
+    fn flush(&self) -> Result<(), Box<dyn Error>> {
+        let connection = self.connection()?;
+        connection.execute_batch("PRAGMA wal_checkpoint(TRUNCATE);")?;
+        Ok(())
+    }
+}
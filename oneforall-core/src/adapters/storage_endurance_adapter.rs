@@ -0,0 +1,250 @@
+//! Storage Endurance Adapter
+//!
+//! This module provides an adapter that writes pseudorandom test files to a
+//! directory, syncs them to the drive, drops them from the page cache and reads
+//! them back to verify every block. Files are deleted as soon as they are
+//! verified, so the test can write far more than the free space of the drive.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+
+use common::ports::log_port::LoggerPort;
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::endurance::{format_volume, whole_disk, WriteVerifyResult};
+use crate::ports::storage_endurance_port::StorageEndurancePort;
+
+/// The size of each block written and compared.
+const BLOCK_BYTES: usize = 1024 * 1024;
+
+/// The number of corrupt blocks whose location is reported; the rest are only counted.
+const MAX_REPORTED_MISMATCHES: usize = 20;
+
+/// The prefix of the test file names.
+const TEST_FILE_PREFIX: &str = "oneforall-endurance";
+
+/// Asks the kernel to drop a file from the page cache, so that it is read back
+/// from the drive.
+#[cfg(target_os = "linux")]
+pub(crate) fn drop_cached_pages(file: &File) -> Result<(), String> {
+    use std::os::unix::io::AsRawFd;
+
+    // SAFETY: the descriptor is owned by `file` and stays open for the call.
+    let result = unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED) };
+    if result != 0 {
+        return Err(format!(
+            "Failed to drop the file from the page cache (error {})",
+            result
+        ));
+    }
+    Ok(())
+}
+
+/// Turns off caching for reads of a file, so that it is read back from the drive.
+#[cfg(target_os = "macos")]
+pub(crate) fn drop_cached_pages(file: &File) -> Result<(), String> {
+    use std::os::unix::io::AsRawFd;
+
+    // SAFETY: the descriptor is owned by `file` and stays open for the call.
+    if unsafe { libc::fcntl(file.as_raw_fd(), libc::F_NOCACHE, 1) } == -1 {
+        return Err("Failed to disable caching of the file".to_string());
+    }
+    Ok(())
+}
+
+/// Page cache control is unavailable on other platforms.
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub(crate) fn drop_cached_pages(_file: &File) -> Result<(), String> {
+    Err("Page cache control is unsupported on this platform".to_string())
+}
+
+/// Represents the file-based storage endurance adapter.
+pub struct StorageEnduranceAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl StorageEnduranceAdapter {
+    /// Creates a new instance of `StorageEnduranceAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `StorageEnduranceAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        StorageEnduranceAdapter { logger }
+    }
+
+    /// Fills a block with the pseudorandom pattern of one block of one file.
+    ///
+    /// The pattern is seeded by the file and block index, so it can be rebuilt
+    /// for verification and no two blocks of the test are alike, which keeps
+    /// drives that deduplicate or compress from skipping the writes.
+    fn fill_block(block: &mut [u8], file_index: u64, block_index: u64) {
+        // xorshift64 needs a non-zero seed; the odd constant guarantees one.
+        let mut state = ((file_index << 32) ^ block_index) | 0x9E37_79B9_0000_0001;
+        for chunk in block.chunks_mut(8) {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            chunk.copy_from_slice(&state.to_le_bytes()[..chunk.len()]);
+        }
+    }
+
+    /// Drops a file from the page cache, warning if verification may read cached data.
+    fn drop_cached_pages(&self, file: &File) {
+        if let Err(e) = drop_cached_pages(file) {
+            self.logger
+                .log_warn(&format!("{}; verification may read cached data", e));
+        }
+    }
+
+    /// Writes, syncs and verifies one test file, then deletes it.
+    fn write_and_verify_file(
+        &self,
+        path: &Path,
+        file_index: u64,
+        file_bytes: u64,
+        cancel: &CancellationToken,
+        result: &mut WriteVerifyResult,
+    ) -> Result<(), String> {
+        let mut expected = vec![0u8; BLOCK_BYTES];
+        let mut actual = vec![0u8; BLOCK_BYTES];
+        let blocks = file_bytes.div_ceil(BLOCK_BYTES as u64);
+
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        let mut written = 0;
+        for block_index in 0..blocks {
+            if cancel.is_cancelled() {
+                result.cancelled = true;
+                break;
+            }
+            let length = (file_bytes - block_index * BLOCK_BYTES as u64).min(BLOCK_BYTES as u64);
+            let block = &mut expected[..length as usize];
+            Self::fill_block(block, file_index, block_index);
+            file.write_all(block)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            written += length;
+        }
+        file.sync_all()
+            .map_err(|e| format!("Failed to sync {}: {}", path.display(), e))?;
+        result.written_bytes += written;
+        self.drop_cached_pages(&file);
+        drop(file);
+
+        let mut file =
+            File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        self.drop_cached_pages(&file);
+        for block_index in 0..written.div_ceil(BLOCK_BYTES as u64) {
+            let length = (written - block_index * BLOCK_BYTES as u64).min(BLOCK_BYTES as u64);
+            let block = &mut expected[..length as usize];
+            Self::fill_block(block, file_index, block_index);
+            file.read_exact(&mut actual[..length as usize])
+                .map_err(|e| format!("Failed to read back {}: {}", path.display(), e))?;
+            if actual[..length as usize] != *block {
+                result.corrupt_blocks += 1;
+                let offset = block_index * BLOCK_BYTES as u64;
+                self.logger.log_error(&format!(
+                    "Corrupt block in test file {} at offset {}",
+                    file_index, offset
+                ));
+                if result.mismatches.len() < MAX_REPORTED_MISMATCHES {
+                    result
+                        .mismatches
+                        .push(format!("file {} at offset {}", file_index, offset));
+                }
+            }
+            result.verified_bytes += length;
+        }
+        drop(file);
+
+        fs::remove_file(path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))
+    }
+}
+
+// Implement the `StorageEndurancePort` trait for `StorageEnduranceAdapter`.
+impl StorageEndurancePort for StorageEnduranceAdapter {
+    fn write_and_verify(
+        &self,
+        directory: &Path,
+        total_bytes: u64,
+        file_bytes: u64,
+        cancel: &CancellationToken,
+    ) -> Result<WriteVerifyResult, String> {
+        if !directory.is_dir() {
+            return Err(format!("{} is not a directory", directory.display()));
+        }
+
+        let mut result = WriteVerifyResult::default();
+        let mut file_index = 0;
+        while result.written_bytes < total_bytes && !result.cancelled {
+            if cancel.is_cancelled() {
+                result.cancelled = true;
+                break;
+            }
+            let path = directory.join(format!(
+                "{}-{}-{}.bin",
+                TEST_FILE_PREFIX,
+                std::process::id(),
+                file_index
+            ));
+            let file_bytes = file_bytes.min(total_bytes - result.written_bytes);
+            let outcome =
+                self.write_and_verify_file(&path, file_index, file_bytes, cancel, &mut result);
+            if outcome.is_err() {
+                // Leave no test file behind on a failed write or read.
+                let _ = fs::remove_file(&path);
+            }
+            outcome?;
+
+            self.logger.log_info(&format!(
+                "Endurance progress: {} of {} written and verified, {} corrupt block(s)",
+                format_volume(result.verified_bytes),
+                format_volume(total_bytes),
+                result.corrupt_blocks
+            ));
+            file_index += 1;
+        }
+
+        Ok(result)
+    }
+
+    fn backing_device(&self, path: &Path) -> Result<String, String> {
+        let output = Command::new("df")
+            .arg("-P")
+            .arg(path)
+            .output()
+            .map_err(|e| format!("Failed to run df: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "Failed to find the filesystem of {}: {}",
+                path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        // The second line holds the filesystem the path is on, device first.
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let device = stdout
+            .lines()
+            .nth(1)
+            .and_then(|line| line.split_whitespace().next())
+            .ok_or_else(|| format!("df printed no filesystem for {}", path.display()))?;
+        if !device.starts_with("/dev/") {
+            return Err(format!(
+                "{} is on {}, which is not a block device",
+                path.display(),
+                device
+            ));
+        }
+        Ok(whole_disk(device))
+    }
+}
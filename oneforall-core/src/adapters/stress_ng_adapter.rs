@@ -0,0 +1,242 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use common::ports::log_port::LoggerPort;
+use tokio::process::Command;
+use tokio_util::sync::CancellationToken;
+
+use crate::adapters::stress_ng_manager_adapter::StressNgManagerAdapter;
+use crate::domain::stress_ng::StressRunOutcome;
+use crate::ports::stress_ng_binary_port::StressNgBinaryPort;
+
+/// File capturing the output of the last stress-ng run.
+pub const STRESS_NG_OUTPUT_FILE: &str = "stress_ng_output.txt";
+
+/// The stress-ng runs this process has given an output file, numbering the next one.
+static OUTPUT_FILES: AtomicU64 = AtomicU64::new(0);
+
+/// Returns a file of its own for a stress-ng run to capture its output in, so
+/// runs going on at once, such as two jobs started through the REST API, do
+/// not overwrite each other's output.
+///
+/// # Returns
+/// A path in the temporary directory, unique to this process and run.
+pub fn run_output_file() -> String {
+    std::env::temp_dir()
+        .join(format!(
+            "stress_ng_output.{}.{}.txt",
+            std::process::id(),
+            OUTPUT_FILES.fetch_add(1, Ordering::Relaxed)
+        ))
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Reads the output a run captured in a file of `run_output_file` and removes the file.
+///
+/// # Arguments
+/// * `path` - The file the output was captured in.
+///
+/// # Returns
+/// The output of stress-ng, empty if it could not be read.
+pub fn take_run_output(path: &str) -> String {
+    let output = fs::read_to_string(path).unwrap_or_default();
+    let _ = fs::remove_file(path);
+    output
+}
+
+/// Time stress-ng is given to stop its workers after SIGTERM before they are killed.
+const CANCEL_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+// The process groups of the stress-ng runs in progress, killed when shutting down
+// so no workers outlive the application.
+static RUNNING_GROUPS: Mutex<BTreeSet<libc::pid_t>> = Mutex::new(BTreeSet::new());
+
+/// Kills every stress-ng run still in progress, with all of its workers.
+///
+/// # Returns
+/// The number of stress-ng runs killed.
+pub fn kill_running_stress_ng() -> usize {
+    let Ok(groups) = RUNNING_GROUPS.lock() else {
+        return 0;
+    };
+    for pgid in groups.iter() {
+        // SAFETY: killpg only signals a process group created for a stress-ng run.
+        unsafe { libc::killpg(*pgid, libc::SIGKILL) };
+    }
+    groups.len()
+}
+
+/// Runs stress-ng; each run is given the logger it reports to.
+pub struct StressNgAdapter;
+
+impl StressNgAdapter {
+    /// Runs stress-ng with the given arguments until it exits or `cancel` is triggered.
+    ///
+    /// # Arguments
+    /// * `logger` - Logger implementation for logging messages.
+    /// * `args` - The arguments passed to stress-ng.
+    /// * `background` - Runs stress-ng at background priority, which keeps every worker
+    ///   on the efficiency cores of Apple Silicon; ignored on other platforms, where
+    ///   workers are confined to a class of cores with `--taskset`.
+    /// * `cancel` - Cancelling this token stops stress-ng and all of its workers.
+    ///
+    /// # Returns
+    /// Whether the run completed or was cancelled, or an error message if stress-ng
+    /// could not run or exited with a failure status.
+    pub async fn execute_stress_ng_command(
+        logger: Arc<dyn LoggerPort>,
+        args: &[&str],
+        background: bool,
+        cancel: &CancellationToken,
+    ) -> Result<StressRunOutcome, String> {
+        Self::execute_stress_ng_command_to(logger, args, background, STRESS_NG_OUTPUT_FILE, cancel)
+            .await
+    }
+
+    /// Runs stress-ng like `execute_stress_ng_command`, capturing its output in the
+    /// given file, so several runs can go on at once without mixing their output.
+    ///
+    /// # Arguments
+    /// * `logger` - Logger implementation for logging messages.
+    /// * `args` - The arguments passed to stress-ng.
+    /// * `background` - Runs stress-ng at background priority on Apple Silicon.
+    /// * `output_file_path` - The file the output of stress-ng is written to.
+    /// * `cancel` - Cancelling this token stops stress-ng and all of its workers.
+    ///
+    /// # Returns
+    /// Whether the run completed or was cancelled, or an error message if stress-ng
+    /// could not run or exited with a failure status.
+    pub async fn execute_stress_ng_command_to(
+        logger: Arc<dyn LoggerPort>,
+        args: &[&str],
+        background: bool,
+        output_file_path: &str,
+        cancel: &CancellationToken,
+    ) -> Result<StressRunOutcome, String> {
+        let binary_path = StressNgManagerAdapter::new(logger.clone()).locate_binary()?;
+
+        // Create or open the file to capture the command's output
+        let output_file = match fs::File::create(output_file_path) {
+            Ok(file) => file,
+            Err(e) => {
+                let error_msg = format!("Failed to create output file: {}", e);
+                logger.log_error(&error_msg);
+                return Err(error_msg);
+            }
+        };
+
+        // Prepare the stress-ng command with redirection of output to file
+        let mut command = Command::new(&binary_path);
+        command.args(args);
+        logger.log_debug(&format!(
+            "Preparing stress-ng command with args: {:?}",
+            args
+        ));
+
+        // Setup the output redirection
+        logger.log_debug(&format!(
+            "Redirecting stress-ng output to {}",
+            output_file_path
+        ));
+        command.stdout(Stdio::from(output_file.try_clone().unwrap()));
+        command.stderr(Stdio::from(output_file));
+
+        // Run stress-ng in its own process group so that cancelling the run stops
+        // every worker it forked, not just the parent process.
+        command.process_group(0);
+        command.kill_on_drop(true);
+
+        #[cfg(target_os = "macos")]
+        if background {
+            logger.log_debug("Running stress-ng at background priority, on the efficiency cores");
+            // SAFETY: setpriority only lowers the priority of the child about to exec stress-ng.
+            unsafe {
+                command.pre_exec(|| {
+                    if libc::setpriority(libc::PRIO_DARWIN_PROCESS, 0, libc::PRIO_DARWIN_BG) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+        }
+        #[cfg(not(target_os = "macos"))]
+        let _ = background;
+
+        // Execute the stress-ng command
+        match command.spawn() {
+            Ok(mut child) => {
+                logger.log_debug("stress-ng command spawned, waiting for it to finish");
+                let pgid = child.id().map(|pid| pid as libc::pid_t);
+                if let (Some(pgid), Ok(mut groups)) = (pgid, RUNNING_GROUPS.lock()) {
+                    groups.insert(pgid);
+                }
+
+                let outcome = tokio::select! {
+                    status = child.wait() => match status {
+                        Ok(status) if status.success() => {
+                            logger.log_debug("stress-ng command finished successfully");
+                            Ok(StressRunOutcome::Completed)
+                        }
+                        // A non-zero status means a stressor failed or could not run.
+                        Ok(status) => {
+                            let error_msg = format!("stress-ng exited with {}", status);
+                            logger.log_error(&error_msg);
+                            Err(error_msg)
+                        }
+                        Err(e) => {
+                            logger.log_error(&format!("Execution failed for stress-ng command: {}", e));
+                            Err(e.to_string())
+                        }
+                    },
+                    _ = cancel.cancelled() => {
+                        logger.log_warn("Cancelling stress-ng command");
+                        StressNgAdapter::stop_process_group(logger.clone(), &mut child).await;
+                        Ok(StressRunOutcome::Cancelled)
+                    }
+                };
+
+                if let (Some(pgid), Ok(mut groups)) = (pgid, RUNNING_GROUPS.lock()) {
+                    groups.remove(&pgid);
+                }
+                outcome
+            }
+            Err(e) => {
+                logger.log_error(&format!("Failed to spawn stress-ng command: {}", e));
+                Err(e.to_string())
+            }
+        }
+    }
+
+    /// Stops every process in the process group led by `child`.
+    ///
+    /// stress-ng is sent SIGTERM first so it can stop and reap its workers, and the
+    /// whole group is killed if it has not exited after the grace period.
+    ///
+    /// # Arguments
+    /// * `logger` - Logger implementation for logging messages.
+    /// * `child` - The stress-ng parent process, leader of its process group.
+    async fn stop_process_group(logger: Arc<dyn LoggerPort>, child: &mut tokio::process::Child) {
+        let pgid = match child.id() {
+            Some(pid) => pid as libc::pid_t,
+            None => return, // Already exited and reaped.
+        };
+
+        // SAFETY: killpg only signals the process group created for this stress-ng run.
+        unsafe { libc::killpg(pgid, libc::SIGTERM) };
+        if tokio::time::timeout(CANCEL_GRACE_PERIOD, child.wait())
+            .await
+            .is_err()
+        {
+            logger.log_warn("stress-ng did not stop within the grace period, killing it");
+        }
+
+        // Kill anything left in the group, including workers orphaned by the parent.
+        unsafe { libc::killpg(pgid, libc::SIGKILL) };
+        let _ = child.wait().await;
+    }
+}
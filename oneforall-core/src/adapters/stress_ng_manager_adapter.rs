@@ -0,0 +1,297 @@
+//! stress-ng Manager Adapter
+//!
+//! Locates a stress-ng executable for the platform OneForAll runs on. The
+//! binaries bundled at compile time are checked against their SHA-256 checksum
+//! and the platform in their executable header, then extracted once into the
+//! data directory and reused by later runs. Platforms without a bundled binary,
+//! such as aarch64 Linux (Graviton, Ampere), use an installed stress-ng.
+
+use std::fs;
+use std::io::{Read, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use common::ports::log_port::LoggerPort;
+use sha2::{Digest, Sha256};
+
+use crate::domain::platform::{Arch, Os, Platform};
+use crate::ports::stress_ng_binary_port::StressNgBinaryPort;
+
+/// Environment variable naming a stress-ng executable to use instead of the bundled one.
+pub const STRESS_NG_ENV_VAR: &str = "ONEFORALL_STRESS_NG";
+
+/// Directories searched for an installed stress-ng after `PATH`, covering package
+/// managers whose prefix is often missing from the environment of services.
+const INSTALL_DIRECTORIES: [&str; 4] = [
+    "/opt/homebrew/bin",
+    "/usr/local/bin",
+    "/usr/bin",
+    "/usr/sbin",
+];
+
+// Constants holding the embedded binary data for stress-ng for different operating systems.
+// These binaries are included at compile time and used for stress testing.
+pub const STRESS_NG_LINUX: &[u8] = include_bytes!("linux/stress-ng");
+pub const STRESS_NG_MACOS: &[u8] = include_bytes!("macOS/stress-ng");
+
+/// A stress-ng binary bundled with OneForAll.
+struct BundledBinary {
+    /// The platform the binary is meant to run on.
+    platform: Platform,
+
+    /// The binary itself.
+    bytes: &'static [u8],
+
+    /// The SHA-256 checksum of `bytes`, in lowercase hex.
+    sha256: &'static str,
+}
+
+/// Returns the stress-ng binaries bundled with OneForAll.
+///
+/// No aarch64 build is bundled yet; Apple Silicon runs the x86_64 macOS build
+/// through Rosetta 2.
+fn bundled_binaries() -> Vec<BundledBinary> {
+    vec![
+        BundledBinary {
+            platform: Platform {
+                os: Os::Linux,
+                arch: Arch::X86_64,
+            },
+            bytes: STRESS_NG_LINUX,
+            sha256: "5282585766b441bf9f062a80bed38c6778a1eea516be0d8cb2b9d875e69385be",
+        },
+        BundledBinary {
+            platform: Platform {
+                os: Os::MacOS,
+                arch: Arch::X86_64,
+            },
+            bytes: STRESS_NG_MACOS,
+            sha256: "ab313a755056bd0cb2b1ba3b81804955b66a0f30f9eb8e7299d2d18f436d7e08",
+        },
+    ]
+}
+
+/// Returns the SHA-256 checksum of `data` in lowercase hex.
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+pub struct StressNgManagerAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    platform: Platform,
+    cache_directory: Option<PathBuf>,
+}
+
+impl StressNgManagerAdapter {
+    /// Creates a new instance of `StressNgManagerAdapter` for the current platform.
+    ///
+    /// Extracted binaries are cached under `oneforall/bin` in the user's local
+    /// data directory, e.g. `~/.local/share/oneforall/bin` on Linux.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `StressNgManagerAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        StressNgManagerAdapter {
+            logger,
+            platform: Platform::current(),
+            cache_directory: dirs::data_local_dir().map(|dir| dir.join("oneforall").join("bin")),
+        }
+    }
+
+    /// Returns the bundled binary for the current platform, if one exists and is intact.
+    ///
+    /// A native binary is preferred over one that needs translation.
+    fn bundled_binary(&self) -> Option<BundledBinary> {
+        let mut candidates: Vec<BundledBinary> = bundled_binaries()
+            .into_iter()
+            .filter(|b| self.platform.can_run(&b.platform))
+            .collect();
+        candidates.sort_by_key(|b| b.platform != self.platform);
+        let bundled = candidates.into_iter().next()?;
+
+        let checksum = sha256_hex(bundled.bytes);
+        if checksum != bundled.sha256 {
+            self.logger.log_warn(&format!(
+                "Bundled stress-ng for {} has checksum {}, expected {}; ignoring it",
+                bundled.platform, checksum, bundled.sha256
+            ));
+            return None;
+        }
+
+        // The checksum only proves the binary is the one that was bundled; the header
+        // tells whether it can actually run here.
+        match Platform::of_executable(bundled.bytes) {
+            Some(built_for) if built_for == self.platform => Some(bundled),
+            Some(built_for) if self.platform.can_run(&built_for) => {
+                self.logger.log_info(&format!(
+                    "Using the bundled {} stress-ng on {} through translation",
+                    built_for, self.platform
+                ));
+                Some(bundled)
+            }
+            Some(built_for) => {
+                self.logger.log_warn(&format!(
+                    "Bundled stress-ng for {} is built for {}; ignoring it",
+                    bundled.platform, built_for
+                ));
+                None
+            }
+            None => {
+                self.logger.log_warn(&format!(
+                    "Bundled stress-ng for {} is not a recognised executable; ignoring it",
+                    bundled.platform
+                ));
+                None
+            }
+        }
+    }
+
+    /// Extracts a bundled binary into the cache directory, reusing an earlier copy
+    /// if its checksum still matches.
+    ///
+    /// # Arguments
+    /// * `bundled` - The verified bundled binary.
+    ///
+    /// # Returns
+    /// A `Result` containing the path of the cached executable, or an error message.
+    fn cache_binary(&self, bundled: &BundledBinary) -> Result<PathBuf, String> {
+        let directory = self
+            .cache_directory
+            .as_ref()
+            .ok_or("Could not determine a data directory to cache stress-ng in")?;
+        let path = directory.join(format!(
+            "stress-ng-{}-{}",
+            bundled.platform.to_string().replace('/', "-"),
+            &bundled.sha256[..12]
+        ));
+
+        if let Ok(existing) = fs::read(&path) {
+            if sha256_hex(&existing) == bundled.sha256 {
+                self.logger
+                    .log_debug(&format!("Using cached stress-ng at {}", path.display()));
+                return Ok(path);
+            }
+            self.logger.log_warn(&format!(
+                "Cached stress-ng at {} does not match its checksum, extracting it again",
+                path.display()
+            ));
+        }
+
+        fs::create_dir_all(directory)
+            .map_err(|e| format!("Failed to create {}: {}", directory.display(), e))?;
+
+        // Write to a temporary file and rename it, so that a concurrent run never
+        // executes a partially written binary.
+        let temp_path = path.with_extension(format!("tmp-{}", std::process::id()));
+        let write = || -> std::io::Result<()> {
+            let mut file = fs::File::create(&temp_path)?;
+            file.write_all(bundled.bytes)?;
+            file.set_permissions(fs::Permissions::from_mode(0o755))?;
+            fs::rename(&temp_path, &path)
+        };
+        if let Err(e) = write() {
+            let _ = fs::remove_file(&temp_path);
+            return Err(format!(
+                "Failed to extract stress-ng to {}: {}",
+                path.display(),
+                e
+            ));
+        }
+
+        self.logger
+            .log_info(&format!("Extracted stress-ng to {}", path.display()));
+        Ok(path)
+    }
+
+    /// Checks that `path` is an executable file.
+    fn check_executable(path: &Path) -> Result<(), String> {
+        let metadata = fs::metadata(path)
+            .map_err(|e| format!("Cannot use stress-ng at {}: {}", path.display(), e))?;
+        if !metadata.is_file() || metadata.permissions().mode() & 0o111 == 0 {
+            return Err(format!(
+                "stress-ng at {} is not an executable file",
+                path.display()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Searches `PATH` and the usual install directories for a stress-ng that can
+    /// run on this platform, skipping builds for another architecture.
+    fn find_installed(&self) -> Option<PathBuf> {
+        let mut directories: Vec<PathBuf> = std::env::var_os("PATH")
+            .map(|paths| std::env::split_paths(&paths).collect())
+            .unwrap_or_default();
+        directories.extend(INSTALL_DIRECTORIES.iter().map(PathBuf::from));
+
+        directories
+            .into_iter()
+            .map(|dir| dir.join("stress-ng"))
+            .filter(|candidate| Self::check_executable(candidate).is_ok())
+            .find(|candidate| {
+                let mut header = [0u8; 20];
+                let read = fs::File::open(candidate)
+                    .and_then(|mut file| file.read(&mut header))
+                    .unwrap_or(0);
+                match Platform::of_executable(&header[..read]) {
+                    Some(built_for) if !self.platform.can_run(&built_for) => {
+                        self.logger.log_debug(&format!(
+                            "Skipping {}: built for {}",
+                            candidate.display(),
+                            built_for
+                        ));
+                        false
+                    }
+                    // Unrecognised headers include wrapper scripts and fat binaries.
+                    _ => true,
+                }
+            })
+    }
+}
+
+// Implement the `StressNgBinaryPort` trait for `StressNgManagerAdapter`.
+impl StressNgBinaryPort for StressNgManagerAdapter {
+    fn locate_binary(&self) -> Result<PathBuf, String> {
+        // An explicit override always wins, so that a custom build can be tested.
+        if let Some(path) = std::env::var_os(STRESS_NG_ENV_VAR) {
+            let path = PathBuf::from(path);
+            Self::check_executable(&path)
+                .map_err(|e| format!("{} (set by {})", e, STRESS_NG_ENV_VAR))?;
+            self.logger.log_debug(&format!(
+                "Using stress-ng at {} from {}",
+                path.display(),
+                STRESS_NG_ENV_VAR
+            ));
+            return Ok(path);
+        }
+
+        if let Some(bundled) = self.bundled_binary() {
+            match self.cache_binary(&bundled) {
+                Ok(path) => return Ok(path),
+                Err(e) => self.logger.log_warn(&e),
+            }
+        }
+
+        if let Some(path) = self.find_installed() {
+            self.logger
+                .log_debug(&format!("Using installed stress-ng at {}", path.display()));
+            return Ok(path);
+        }
+
+        let error_msg = format!(
+            "No compatible stress-ng binary for {}: no working binary is bundled for this platform and \
+             no installed stress-ng was found. Install stress-ng (e.g. `apt install stress-ng` or \
+             `brew install stress-ng`) or set {} to the path of a stress-ng executable.",
+            self.platform, STRESS_NG_ENV_VAR
+        );
+        self.logger.log_error(&error_msg);
+        Err(error_msg)
+    }
+}
@@ -0,0 +1,121 @@
+//! Stressor Catalog Adapter
+//!
+//! Reads the stressors the stress-ng binary of this machine offers, and the
+//! options of each, from its `--stressors` and `--help` output. The catalog is
+//! cached as JSON next to the extracted binaries and read again only when the
+//! binary or its version changes, since `--help` of a recent stress-ng runs to
+//! thousands of lines.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+
+use common::ports::log_port::LoggerPort;
+
+use crate::adapters::stress_ng_manager_adapter::StressNgManagerAdapter;
+use crate::domain::stress_ng::{parse_stress_ng_version, StressorCatalog};
+use crate::ports::stress_ng_binary_port::StressNgBinaryPort;
+use crate::ports::stressor_catalog_port::StressorCatalogPort;
+
+/// File name of the cached catalog.
+const CATALOG_FILE: &str = "stress-ng-stressors.json";
+
+pub struct StressorCatalogAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    binary: Box<dyn StressNgBinaryPort>,
+    cache_path: Option<PathBuf>,
+}
+
+impl StressorCatalogAdapter {
+    /// Creates a new instance of `StressorCatalogAdapter`.
+    ///
+    /// The catalog is cached under `oneforall` in the user's local data
+    /// directory, e.g. `~/.local/share/oneforall/stress-ng-stressors.json` on Linux.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `StressorCatalogAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        StressorCatalogAdapter {
+            binary: Box::new(StressNgManagerAdapter::new(logger.clone())),
+            logger,
+            cache_path: dirs::data_local_dir().map(|dir| dir.join("oneforall").join(CATALOG_FILE)),
+        }
+    }
+
+    /// Runs stress-ng with one argument and returns what it printed.
+    fn inspect(binary: &Path, argument: &str) -> Result<String, String> {
+        let output = Command::new(binary)
+            .arg(argument)
+            .output()
+            .map_err(|e| format!("Failed to run {} {}: {}", binary.display(), argument, e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "{} {} failed with {}: {}",
+                binary.display(),
+                argument,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Returns the cached catalog if it was read from the same binary and version.
+    fn cached_catalog(&self, binary: &Path, version: &str) -> Option<StressorCatalog> {
+        let text = fs::read_to_string(self.cache_path.as_ref()?).ok()?;
+        let catalog: StressorCatalog = serde_json::from_str(&text).ok()?;
+        (catalog.binary == binary.display().to_string() && catalog.version == version)
+            .then_some(catalog)
+    }
+
+    /// Caches the catalog for later runs.
+    fn save_catalog(&self, catalog: &StressorCatalog) -> Result<(), String> {
+        let path = self
+            .cache_path
+            .as_ref()
+            .ok_or("Could not determine a data directory to cache the stressors in")?;
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)
+                .map_err(|e| format!("Failed to create {}: {}", directory.display(), e))?;
+        }
+        let json = serde_json::to_string(catalog)
+            .map_err(|e| format!("Failed to serialize the stressors: {}", e))?;
+        fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+// Implement the `StressorCatalogPort` trait for `StressorCatalogAdapter`.
+impl StressorCatalogPort for StressorCatalogAdapter {
+    fn load_catalog(&self) -> Result<StressorCatalog, String> {
+        let binary = self.binary.locate_binary()?;
+        let version = parse_stress_ng_version(&Self::inspect(&binary, "--version")?);
+        if let Some(catalog) = self.cached_catalog(&binary, &version) {
+            self.logger.log_debug(&format!(
+                "Using the cached stressors of stress-ng {}",
+                version
+            ));
+            return Ok(catalog);
+        }
+
+        self.logger.log_info(&format!(
+            "Reading the stressors of stress-ng {} at {}",
+            version,
+            binary.display()
+        ));
+        let catalog = StressorCatalog::parse(
+            &binary.display().to_string(),
+            &version,
+            &Self::inspect(&binary, "--stressors")?,
+            &Self::inspect(&binary, "--help")?,
+        )?;
+        if let Err(e) = self.save_catalog(&catalog) {
+            self.logger
+                .log_warn(&format!("Failed to cache the stressors: {}", e));
+        }
+        Ok(catalog)
+    }
+}
@@ -0,0 +1,21 @@
+//! Sysfs
+//!
+//! This module reads the attributes the kernel exposes under `/sys`, which
+//! hold a single value followed by a newline, for the adapters discovering
+//! and sampling hardware through them.
+
+use std::fs;
+use std::path::Path;
+
+/// Reads a sysfs attribute and returns its trimmed contents.
+///
+/// # Arguments
+///
+/// * `path` - The attribute, e.g. "/sys/class/hwmon/hwmon0/name".
+///
+/// # Returns
+///
+/// * `Option<String>` - The value, or `None` if the attribute cannot be read.
+pub fn read_sysfs(path: impl AsRef<Path>) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
@@ -0,0 +1,110 @@
+//! System Statistics Adapter
+//!
+//! This module provides an adapter that reads per-core CPU times and memory
+//! usage from `/proc`, and the busiest processes from the `ps` command.
+
+use std::fs;
+use std::process::Command;
+use std::sync::Arc;
+
+use common::ports::log_port::LoggerPort;
+
+use crate::domain::system_stats::{CpuTimes, MemoryUsage, ProcessUsage};
+use crate::ports::system_stats_port::SystemStatsPort;
+
+/// Represents the `/proc` and `ps` system statistics adapter.
+pub struct SystemStatsAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl SystemStatsAdapter {
+    /// Creates a new instance of `SystemStatsAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `SystemStatsAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        SystemStatsAdapter { logger }
+    }
+
+    /// Parses the per-CPU lines of `/proc/stat`, e.g. `cpu3 4705 150 1120 16250 520 0 7 0 0 0`.
+    fn parse_proc_stat(stat: &str) -> Vec<(u32, CpuTimes)> {
+        stat.lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let cpu = fields.next()?.strip_prefix("cpu")?.parse().ok()?;
+                let ticks: Vec<u64> = fields.filter_map(|f| f.parse().ok()).collect();
+                // user nice system idle iowait irq softirq steal; guest time is
+                // already included in user and nice.
+                let total = ticks.iter().take(8).sum();
+                let idle = ticks.get(3).copied().unwrap_or(0) + ticks.get(4).copied().unwrap_or(0);
+                let steal = ticks.get(7).copied().unwrap_or(0);
+                Some((cpu, CpuTimes { idle, steal, total }))
+            })
+            .collect()
+    }
+
+    /// Parses `/proc/meminfo`.
+    fn parse_meminfo(meminfo: &str) -> MemoryUsage {
+        let mut usage = MemoryUsage::default();
+        for line in meminfo.lines() {
+            let mut fields = line.split_whitespace();
+            let key = fields.next().unwrap_or_default();
+            let value = fields.next().and_then(|v| v.parse().ok()).unwrap_or(0);
+            match key {
+                "MemTotal:" => usage.total_kb = value,
+                "MemAvailable:" => usage.available_kb = value,
+                _ => {}
+            }
+        }
+        usage
+    }
+}
+
+// Implement the `SystemStatsPort` trait for `SystemStatsAdapter`.
+impl SystemStatsPort for SystemStatsAdapter {
+    fn read_cpu_times(&self) -> Result<Vec<(u32, CpuTimes)>, String> {
+        let stat = fs::read_to_string("/proc/stat").map_err(|e| {
+            let message = format!("Failed to read /proc/stat: {}", e);
+            self.logger.log_trace(&message);
+            message
+        })?;
+        Ok(Self::parse_proc_stat(&stat))
+    }
+
+    fn read_memory_usage(&self) -> Result<MemoryUsage, String> {
+        let meminfo = fs::read_to_string("/proc/meminfo").map_err(|e| {
+            let message = format!("Failed to read /proc/meminfo: {}", e);
+            self.logger.log_trace(&message);
+            message
+        })?;
+        Ok(Self::parse_meminfo(&meminfo))
+    }
+
+    fn read_top_processes(&self, limit: usize) -> Result<Vec<ProcessUsage>, String> {
+        // `-A -o` is understood by both procps and BSD ps; sorting is done here.
+        let output = Command::new("ps")
+            .args(["-A", "-o", "pid=,pcpu=,pmem=,comm="])
+            .output()
+            .map_err(|e| format!("Failed to run ps: {}", e))?;
+
+        let mut processes: Vec<ProcessUsage> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                Some(ProcessUsage {
+                    pid: fields.next()?.parse().ok()?,
+                    cpu_percent: fields.next()?.parse().ok()?,
+                    memory_percent: fields.next()?.parse().ok()?,
+                    command: fields.collect::<Vec<_>>().join(" "),
+                })
+            })
+            .collect();
+
+        processes.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent));
+        processes.truncate(limit);
+        Ok(processes)
+    }
+}
@@ -0,0 +1,108 @@
+//! Systemd Adapter
+//!
+//! This module implements the sd_notify protocol, so that an instance started
+//! by a `Type=notify` unit reports when it is ready, when it is stopping and,
+//! with `WatchdogSec=` set, that it is still alive. Notifications are datagrams
+//! sent to the socket systemd names in `NOTIFY_SOCKET`.
+
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::ports::log_port::LoggerPort;
+
+use crate::ports::service_manager_port::ServiceManagerPort;
+
+/// Represents the systemd service manager adapter.
+pub struct SystemdAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    notify_socket: Option<String>,
+    watchdog_interval: Option<Duration>,
+}
+
+impl SystemdAdapter {
+    /// Creates a new instance of `SystemdAdapter` from the environment systemd
+    /// passes to the services it starts.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `SystemdAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        let notify_socket = env::var("NOTIFY_SOCKET")
+            .ok()
+            .filter(|socket| !socket.is_empty());
+
+        // The watchdog applies to this process only if WATCHDOG_PID is unset or names it.
+        let watchdog_for_us = env::var("WATCHDOG_PID")
+            .map_or(true, |pid| pid.trim() == std::process::id().to_string());
+        let watchdog_interval = env::var("WATCHDOG_USEC")
+            .ok()
+            .and_then(|usec| usec.trim().parse::<u64>().ok())
+            .filter(|usec| *usec > 0 && watchdog_for_us)
+            .map(Duration::from_micros);
+
+        SystemdAdapter {
+            logger,
+            notify_socket,
+            watchdog_interval,
+        }
+    }
+
+    /// Sends one notification, e.g. `READY=1`, to the notification socket.
+    #[cfg(target_os = "linux")]
+    fn notify(&self, state: &str) -> Result<(), String> {
+        use std::os::linux::net::SocketAddrExt;
+        use std::os::unix::net::{SocketAddr, UnixDatagram};
+
+        let Some(path) = self.notify_socket.as_deref() else {
+            return Err("NOTIFY_SOCKET is not set".to_string());
+        };
+        // A leading '@' names a socket in the abstract namespace.
+        let address = match path.strip_prefix('@') {
+            Some(name) => SocketAddr::from_abstract_name(name.as_bytes()),
+            None => SocketAddr::from_pathname(path),
+        }
+        .map_err(|e| format!("Invalid NOTIFY_SOCKET {}: {}", path, e))?;
+        let socket = UnixDatagram::unbound()
+            .map_err(|e| format!("Failed to create a notification socket: {}", e))?;
+        socket
+            .send_to_addr(state.as_bytes(), &address)
+            .map_err(|e| format!("Failed to notify systemd at {}: {}", path, e))?;
+        self.logger
+            .log_trace(&format!("Notified systemd: {}", state.replace('\n', " ")));
+        Ok(())
+    }
+
+    /// systemd only runs on Linux.
+    #[cfg(not(target_os = "linux"))]
+    fn notify(&self, _state: &str) -> Result<(), String> {
+        Err("systemd notifications are only supported on Linux".to_string())
+    }
+}
+
+// Implement the `ServiceManagerPort` trait for `SystemdAdapter`.
+impl ServiceManagerPort for SystemdAdapter {
+    fn is_supervised(&self) -> bool {
+        self.notify_socket.is_some()
+    }
+
+    fn notify_ready(&self, status: &str) -> Result<(), String> {
+        // Status lines end at the first newline.
+        let status = status.lines().next().unwrap_or_default();
+        self.notify(&format!("READY=1\nSTATUS={}", status))
+    }
+
+    fn notify_stopping(&self) -> Result<(), String> {
+        self.notify("STOPPING=1\nSTATUS=Shutting down")
+    }
+
+    fn watchdog_interval(&self) -> Option<Duration> {
+        self.watchdog_interval
+    }
+
+    fn notify_watchdog(&self) -> Result<(), String> {
+        self.notify("WATCHDOG=1")
+    }
+}
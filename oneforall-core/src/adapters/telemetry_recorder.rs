@@ -0,0 +1,90 @@
+//! Telemetry Recorder
+//!
+//! This module records every live telemetry event published while a run is in
+//! progress, such as power readings and stress test progress, so the raw
+//! time-series can be kept with the results of the run.
+
+use std::sync::{Arc, Mutex};
+
+use common::domain::telemetry::TelemetryEvent;
+use common::ports::telemetry_port::TelemetryPort;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+
+/// Records telemetry events in the background until finished.
+pub struct TelemetryRecorder {
+    events: Arc<Mutex<Vec<TelemetryEvent>>>,
+    task: JoinHandle<()>,
+}
+
+impl TelemetryRecorder {
+    /// Starts recording every event published from now on.
+    ///
+    /// # Arguments
+    ///
+    /// * `telemetry` - Where the collectors publish their readings.
+    ///
+    /// # Returns
+    ///
+    /// * `TelemetryRecorder` - A handle used to stop recording and collect the events.
+    pub fn start(telemetry: &Arc<dyn TelemetryPort>) -> Self {
+        let mut receiver = telemetry.subscribe();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                let event = match receiver.recv().await {
+                    Ok(event) => event,
+                    // Events are published about once a second, so falling behind only
+                    // happens on a stalled runtime; the gap is left in the time-series.
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                };
+                // Metric batches repeat the readings in the registry's shape; they are
+                // followed by the metric sinks, and the run keeps the events themselves.
+                if matches!(event, TelemetryEvent::Metrics { .. }) {
+                    continue;
+                }
+                if let Ok(mut recorded) = recorded.lock() {
+                    recorded.push(event);
+                }
+            }
+        });
+        TelemetryRecorder { events, task }
+    }
+
+    /// Puts the events recorded before a restart ahead of those recorded since,
+    /// so a resumed run keeps a single time-series.
+    ///
+    /// # Arguments
+    ///
+    /// * `earlier` - The events recorded before the restart, oldest first.
+    pub fn prepend(&self, earlier: Vec<TelemetryEvent>) {
+        if let Ok(mut events) = self.events.lock() {
+            events.splice(0..0, earlier);
+        }
+    }
+
+    /// Returns the events recorded so far, oldest first, and keeps recording.
+    pub fn events(&self) -> Vec<TelemetryEvent> {
+        self.events
+            .lock()
+            .map(|events| events.clone())
+            .unwrap_or_default()
+    }
+
+    /// Stops recording and returns the events, oldest first.
+    pub fn finish(self) -> Vec<TelemetryEvent> {
+        self.task.abort();
+        self.events
+            .lock()
+            .map(|mut events| std::mem::take(&mut *events))
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for TelemetryRecorder {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
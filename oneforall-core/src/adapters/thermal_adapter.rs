@@ -0,0 +1,163 @@
+//! Thermal Adapter
+//!
+//! This module provides an adapter that reads fan speeds and temperatures from
+//! the Linux hwmon sysfs interface, falling back to the BMC sensor data
+//! repository through `ipmitool sdr` on servers whose fans are managed by the BMC.
+
+use std::fs;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use common::ports::log_port::LoggerPort;
+
+use crate::adapters::chaos_injector::injected_temperature;
+use crate::adapters::sysfs::read_sysfs;
+use crate::domain::thermal::{FanReading, TemperatureReading, ThermalSample};
+use crate::ports::thermal_port::ThermalPort;
+
+/// Root of the hwmon hierarchy exposing fan and temperature sensors.
+const SYSFS_HWMON_ROOT: &str = "/sys/class/hwmon";
+
+/// Represents the hwmon/IPMI thermal adapter.
+pub struct ThermalAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    ipmi_available: AtomicBool,  // cleared once the BMC fails to answer
+}
+
+impl ThermalAdapter {
+    /// Creates a new instance of `ThermalAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `ThermalAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        ThermalAdapter {
+            logger,
+            ipmi_available: AtomicBool::new(true),
+        }
+    }
+
+    /// Reads every fan and temperature sensor exposed by the hwmon drivers.
+    fn read_hwmon() -> ThermalSample {
+        let mut sample = ThermalSample::default();
+        let entries = match fs::read_dir(SYSFS_HWMON_ROOT) {
+            Ok(entries) => entries,
+            Err(_) => return sample,
+        };
+
+        for entry in entries.flatten() {
+            let hwmon = entry.path();
+            let chip = read_sysfs(hwmon.join("name"))
+                .unwrap_or_else(|| entry.file_name().to_string_lossy().to_string());
+
+            for attribute in fs::read_dir(&hwmon).into_iter().flatten().flatten() {
+                let file_name = attribute.file_name().to_string_lossy().to_string();
+                let (kind, channel) = match file_name.strip_suffix("_input").and_then(|base| {
+                    base.find(|c: char| c.is_ascii_digit())
+                        .map(|i| base.split_at(i))
+                }) {
+                    Some(parts) => parts,
+                    None => continue,
+                };
+                let value = match read_sysfs(attribute.path()).and_then(|v| v.parse::<i64>().ok()) {
+                    Some(value) => value,
+                    None => continue,
+                };
+                let label = read_sysfs(hwmon.join(format!("{}{}_label", kind, channel)))
+                    .unwrap_or_else(|| format!("{}{}", kind, channel));
+                let name = format!("{}/{}", chip, label);
+
+                match kind {
+                    "fan" => sample.fans.push(FanReading {
+                        name,
+                        rpm: value.max(0) as u32,
+                        max_rpm: read_sysfs(hwmon.join(format!("fan{}_max", channel)))
+                            .and_then(|v| v.parse().ok()),
+                    }),
+                    // hwmon reports temperatures in millidegrees Celsius.
+                    "temp" => sample.temperatures.push(TemperatureReading {
+                        name,
+                        celsius: value as f64 / 1000.0,
+                    }),
+                    _ => {}
+                }
+            }
+        }
+
+        sample.fans.sort_by(|a, b| a.name.cmp(&b.name));
+        sample.temperatures.sort_by(|a, b| a.name.cmp(&b.name));
+        sample
+    }
+
+    /// Runs `ipmitool sdr type <sensor_type>` and returns the name and reading of
+    /// every sensor whose reading carries the given unit.
+    fn read_ipmi_sdr(sensor_type: &str, unit: &str) -> Result<Vec<(String, f64)>, String> {
+        let output = Command::new("ipmitool")
+            .args(["sdr", "type", sensor_type])
+            .output()
+            .map_err(|e| format!("Failed to run ipmitool: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "ipmitool sdr type {} failed: {}",
+                sensor_type,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        // Lines look like "FAN1 | 41h | ok | 29.1 | 4200 RPM".
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split('|').map(str::trim).collect();
+                let reading = fields.get(4)?.strip_suffix(unit)?.trim();
+                Some((fields[0].to_string(), reading.parse().ok()?))
+            })
+            .collect())
+    }
+}
+
+// Implement the `ThermalPort` trait for `ThermalAdapter`.
+impl ThermalPort for ThermalAdapter {
+    fn sample_thermals(&self) -> Result<ThermalSample, String> {
+        let mut sample = Self::read_hwmon();
+
+        // Server fans are usually driven by the BMC and invisible to hwmon.
+        if sample.fans.is_empty() && self.ipmi_available.load(Ordering::Relaxed) {
+            match Self::read_ipmi_sdr("Fan", "RPM") {
+                Ok(fans) => {
+                    sample.fans = fans
+                        .into_iter()
+                        .map(|(name, rpm)| FanReading {
+                            name,
+                            rpm: rpm as u32,
+                            max_rpm: None,
+                        })
+                        .collect();
+                    if sample.temperatures.is_empty() {
+                        sample.temperatures = Self::read_ipmi_sdr("Temperature", "degrees C")
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|(name, celsius)| TemperatureReading { name, celsius })
+                            .collect();
+                    }
+                }
+                Err(e) => {
+                    self.logger
+                        .log_trace(&format!("BMC fan sensors unavailable: {}", e));
+                    self.ipmi_available.store(false, Ordering::Relaxed);
+                }
+            }
+        }
+
+        // The temperature spike of `--chaos`, if one is under way.
+        sample.temperatures.extend(injected_temperature());
+
+        if sample.fans.is_empty() && sample.temperatures.is_empty() {
+            return Err("No fan or temperature sensors are available on this platform".to_string());
+        }
+        Ok(sample)
+    }
+}
@@ -0,0 +1,165 @@
+//! Threshold Monitor
+//!
+//! This module watches the temperature and ECC thresholds of the `[alerts]`
+//! configuration for as long as the application runs, whether or not a test
+//! is running. The readings are checked with the same acceptance-criteria
+//! engine as profile steps, and each crossing raises one threshold alert; the
+//! alert is raised again only after the reading has come back within its limit.
+//! With anomaly alerts enabled, the hottest temperature and the ECC errors
+//! found per check are also followed by anomaly detectors, which raise an
+//! anomaly alert when a reading jumps away from its recent level.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use common::ports::log_port::LoggerPort;
+
+use crate::adapters::alert_dispatcher::AlertDispatcher;
+use crate::adapters::hardware_error_adapter::HardwareErrorAdapter;
+use crate::adapters::thermal_adapter::ThermalAdapter;
+use crate::domain::alert::AlertKind;
+use crate::domain::anomaly::{Anomaly, AnomalyDetector};
+use crate::domain::hardware_errors::new_ecc_errors;
+use crate::domain::profile::{AcceptanceCriteria, StepMetrics};
+use crate::ports::hardware_error_port::HardwareErrorPort;
+use crate::ports::thermal_port::ThermalPort;
+
+/// Checks alert thresholds on a background thread.
+pub struct ThresholdMonitor {
+    logger: Arc<dyn LoggerPort>,
+    alerts: Arc<AlertDispatcher>,
+    thresholds: AcceptanceCriteria,
+    anomalies: bool,
+    thermals: Box<dyn ThermalPort>,
+    errors: Box<dyn HardwareErrorPort>,
+}
+
+impl ThresholdMonitor {
+    /// Starts checking the thresholds on a background thread, immediately and
+    /// then once per `interval`, until the application exits.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger` - Logger implementation for logging messages.
+    /// * `alerts` - Where threshold alerts are raised.
+    /// * `thresholds` - The temperature and ECC limits to watch.
+    /// * `anomalies` - Whether to raise anomaly alerts as well.
+    /// * `interval` - The time between two checks.
+    pub fn start(
+        logger: Arc<dyn LoggerPort>,
+        alerts: Arc<AlertDispatcher>,
+        thresholds: AcceptanceCriteria,
+        anomalies: bool,
+        interval: Duration,
+    ) {
+        let monitor = ThresholdMonitor {
+            thermals: Box::new(ThermalAdapter::new(logger.clone())),
+            errors: Box::new(HardwareErrorAdapter::new(logger.clone())),
+            logger,
+            alerts,
+            thresholds,
+            anomalies,
+        };
+        thread::spawn(move || monitor.run(interval));
+    }
+
+    /// Checks the thresholds forever.
+    fn run(&self, interval: Duration) {
+        // ECC errors are counted from the start, and again from each ECC alert.
+        let mut ecc_baseline = self.errors.read_ecc_counts().ok();
+        if self.thresholds.max_ecc_errors.is_some() && ecc_baseline.is_none() {
+            self.logger
+                .log_warn("ECC error counters are unavailable; the ECC threshold is not watched.");
+        }
+        let mut crossed: HashSet<&'static str> = HashSet::new();
+        let started = Instant::now();
+        let mut temperature = AnomalyDetector::new(1.0);
+        let mut ecc_rate = AnomalyDetector::new(1.0);
+        let mut ecc_previous = ecc_baseline;
+
+        loop {
+            let ecc_now = self.errors.read_ecc_counts().ok();
+            let metrics = StepMetrics {
+                bogo_ops_per_sec: None,
+                peak_celsius: self
+                    .thermals
+                    .sample_thermals()
+                    .ok()
+                    .and_then(|sample| sample.hottest()),
+                worst_remote_penalty: None,
+                ecc_errors: ecc_baseline
+                    .zip(ecc_now)
+                    .map(|(before, after)| new_ecc_errors(&before, &after) as f64),
+                kernel_faults: Ok(Vec::new()),
+                sel_entries: Ok(Vec::new()),
+                firmware: Vec::new(),
+                hook: None,
+                memtest: None,
+                devices: Vec::new(),
+            };
+
+            for check in self.thresholds.check(&metrics) {
+                if !check.missed() {
+                    crossed.remove(check.label);
+                    continue;
+                }
+                if crossed.insert(check.label) {
+                    self.alerts.raise(
+                        AlertKind::Threshold,
+                        &format!("{} above its limit", check.label),
+                        vec![check.render()],
+                    );
+                }
+            }
+            let ecc_crossed = metrics
+                .ecc_errors
+                .zip(self.thresholds.max_ecc_errors)
+                .is_some_and(|(errors, limit)| errors > limit);
+            if ecc_crossed {
+                ecc_baseline = ecc_now;
+            }
+
+            if self.anomalies {
+                let new_errors = ecc_previous
+                    .zip(ecc_now)
+                    .map(|(before, after)| new_ecc_errors(&before, &after) as f64);
+                let readings = [
+                    (
+                        "hottest temperature",
+                        "°C",
+                        &mut temperature,
+                        metrics.peak_celsius,
+                    ),
+                    ("new ECC errors", "per check", &mut ecc_rate, new_errors),
+                ];
+                for (metric, unit, detector, reading) in readings {
+                    let Some(value) = reading else {
+                        continue;
+                    };
+                    if let Some((expected, z_score)) = detector.observe(value) {
+                        let anomaly = Anomaly {
+                            metric: metric.to_string(),
+                            unit: unit.to_string(),
+                            elapsed: started.elapsed(),
+                            value,
+                            expected,
+                            z_score,
+                        };
+                        self.alerts.raise(
+                            AlertKind::Anomaly,
+                            &format!("Unusual {}", metric),
+                            vec![anomaly.render()],
+                        );
+                    }
+                }
+                if ecc_now.is_some() {
+                    ecc_previous = ecc_now;
+                }
+            }
+
+            thread::sleep(interval);
+        }
+    }
+}
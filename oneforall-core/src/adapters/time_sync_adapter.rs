@@ -0,0 +1,150 @@
+//! Time Sync Adapter
+//!
+//! This module provides an adapter that checks how closely the system clock
+//! follows its reference. A PTP grandmaster is asked through ptp4l's
+//! management client `pmc` first, then chrony through `chronyc`; when neither
+//! answers, the kernel's own synchronization state is read with `adjtimex`,
+//! which ntpd and systemd-timesyncd keep up to date as well.
+//!
+//! It also reads the monotonic clock and the boot ID that collector readings
+//! are stamped with alongside the wall-clock time.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+
+use common::ports::log_port::LoggerPort;
+
+use crate::domain::time_sync::{
+    parse_chrony_tracking, parse_pmc_time_status, ClockSync, TimeSyncConfig,
+};
+use crate::ports::time_sync_port::TimeSyncPort;
+
+/// Where the kernel exposes the ID of the current boot.
+const BOOT_ID_PATH: &str = "/proc/sys/kernel/random/boot_id";
+
+/// Reads the configuration file's `[time_sync]` section.
+///
+/// # Arguments
+///
+/// * `path` - The configuration file; a missing file means the default limits.
+///
+/// # Returns
+///
+/// * `Result<TimeSyncConfig, String>` - The settings, or why they could not be read.
+pub fn load_time_sync_config(path: &Path) -> Result<TimeSyncConfig, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(TimeSyncConfig::default()),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    TimeSyncConfig::from_toml(&text).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+}
+
+/// Reads the monotonic clock, in nanoseconds since boot.
+pub fn read_monotonic_ns() -> u64 {
+    let mut now = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: clock_gettime only writes the timespec it is given.
+    unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut now) };
+    now.tv_sec as u64 * 1_000_000_000 + now.tv_nsec as u64
+}
+
+/// Reads the ID of the current boot, which the monotonic clock counts from.
+pub fn read_boot_id() -> String {
+    fs::read_to_string(BOOT_ID_PATH)
+        .map(|id| id.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Represents the NTP/PTP time synchronization adapter.
+pub struct TimeSyncAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl TimeSyncAdapter {
+    /// Creates a new instance of `TimeSyncAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `TimeSyncAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        TimeSyncAdapter { logger }
+    }
+
+    /// Runs a command and returns its standard output.
+    fn run(program: &str, args: &[&str]) -> Result<String, String> {
+        let output = Command::new(program)
+            .args(args)
+            .output()
+            .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "{} failed: {}",
+                program,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Asks ptp4l for the offset of the PTP hardware clock from its grandmaster.
+    /// The system clock follows the hardware clock through phc2sys.
+    fn read_ptp() -> Result<ClockSync, String> {
+        let output = Self::run("pmc", &["-u", "-b", "0", "GET TIME_STATUS_NP"])?;
+        parse_pmc_time_status(&output).ok_or_else(|| "ptp4l did not answer pmc".to_string())
+    }
+
+    /// Asks chrony for the offset of the system clock from its NTP or PTP source.
+    fn read_chrony() -> Result<ClockSync, String> {
+        let output = Self::run("chronyc", &["-c", "tracking"])?;
+        parse_chrony_tracking(&output)
+            .ok_or_else(|| format!("Unexpected chronyc output: {}", output.trim()))
+    }
+
+    /// Reads the synchronization state the kernel keeps for the system clock.
+    fn read_kernel() -> Result<ClockSync, String> {
+        // SAFETY: a zeroed timex with no mode bits set only reads the clock state.
+        let mut timex: libc::timex = unsafe { std::mem::zeroed() };
+        let state = unsafe { libc::adjtimex(&mut timex) };
+        if state < 0 {
+            return Err(format!(
+                "adjtimex failed: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        // The offset is in microseconds unless the kernel reports it in nanoseconds.
+        let offset_unit = if timex.status & libc::STA_NANO != 0 {
+            1e9
+        } else {
+            1e6
+        };
+        Ok(ClockSync {
+            source: "kernel".to_string(),
+            synchronized: state != libc::TIME_ERROR && timex.status & libc::STA_UNSYNC == 0,
+            offset_secs: Some(timex.offset as f64 / offset_unit),
+            max_error_secs: Some(timex.maxerror as f64 / 1e6),
+            reference: None,
+        })
+    }
+}
+
+// Implement the `TimeSyncPort` trait for `TimeSyncAdapter`.
+impl TimeSyncPort for TimeSyncAdapter {
+    fn check_clock(&self) -> Result<ClockSync, String> {
+        match Self::read_ptp() {
+            Ok(sync) => return Ok(sync),
+            Err(e) => self.logger.log_debug(&format!("No PTP clock: {}", e)),
+        }
+        match Self::read_chrony() {
+            Ok(sync) => return Ok(sync),
+            Err(e) => self.logger.log_debug(&format!("No chrony: {}", e)),
+        }
+        Self::read_kernel()
+    }
+}
@@ -0,0 +1,513 @@
+//! Topology Adapter
+//!
+//! This module provides an adapter that discovers the CPU topology, caches and
+//! architecture from `/sys/devices/system` and `/proc/cpuinfo` on Linux,
+//! falling back to `sysctl` on macOS where per-core topology is not exposed.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+
+use common::ports::log_port::LoggerPort;
+
+use crate::adapters::sysfs::read_sysfs;
+use crate::domain::cache::{parse_cache_size, CacheLevel};
+use crate::domain::cpu_architecture::{
+    arm_core_name, group_core_classes, parse_cpuinfo, simd_extensions, CoreClass, CpuArchitecture,
+};
+use crate::domain::cpu_topology::{parse_cpu_list, CpuTopology, LogicalCpu};
+use crate::domain::numa::NumaNode;
+use crate::domain::platform::Platform;
+use crate::ports::topology_port::TopologyPort;
+
+/// Root of the sysfs CPU hierarchy on Linux.
+const SYSFS_CPU_ROOT: &str = "/sys/devices/system/cpu";
+
+/// Root of the sysfs NUMA node hierarchy on Linux.
+const SYSFS_NODE_ROOT: &str = "/sys/devices/system/node";
+
+/// Per-CPU identification and feature flags on Linux.
+const PROC_CPUINFO: &str = "/proc/cpuinfo";
+
+/// Default SVE vector length in bytes, exposed by arm64 kernels on SVE hardware.
+const SVE_VECTOR_LENGTH: &str = "/proc/sys/abi/sve_default_vector_length";
+
+/// The PMU devices listing the performance and efficiency cores of Intel hybrid CPUs.
+const INTEL_HYBRID_PMUS: [(&str, &str); 2] = [
+    ("/sys/devices/cpu_core/cpus", "P-core"),
+    ("/sys/devices/cpu_atom/cpus", "E-core"),
+];
+
+/// Represents the topology discovery adapter.
+pub struct TopologyAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl TopologyAdapter {
+    /// Creates a new instance of `TopologyAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `TopologyAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        TopologyAdapter { logger }
+    }
+
+    /// Reads every NUMA node exposed under sysfs, including memory and distances.
+    fn read_numa_nodes(&self) -> Vec<NumaNode> {
+        let mut nodes = Vec::new();
+
+        let entries = match fs::read_dir(SYSFS_NODE_ROOT) {
+            Ok(entries) => entries,
+            Err(_) => {
+                self.logger
+                    .log_debug("No NUMA information exposed by sysfs; assuming a single node");
+                return nodes;
+            }
+        };
+
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let node_id = match name
+                .strip_prefix("node")
+                .and_then(|id| id.parse::<u32>().ok())
+            {
+                Some(id) => id,
+                None => continue,
+            };
+
+            let cpus = match read_sysfs(entry.path().join("cpulist")) {
+                Some(list) => match parse_cpu_list(&list) {
+                    Ok(cpus) => cpus,
+                    Err(e) => {
+                        self.logger
+                            .log_warn(&format!("Ignoring NUMA node {}: {}", node_id, e));
+                        continue;
+                    }
+                },
+                None => continue,
+            };
+
+            // Per-node meminfo lines look like "Node 0 MemTotal:   32768000 kB".
+            let meminfo = read_sysfs(entry.path().join("meminfo")).unwrap_or_default();
+            let meminfo_value = |field: &str| {
+                meminfo
+                    .lines()
+                    .find(|line| line.contains(field))
+                    .and_then(|line| line.split_whitespace().rev().nth(1))
+                    .and_then(|value| value.parse::<u64>().ok())
+            };
+
+            let distances = read_sysfs(entry.path().join("distance"))
+                .map(|line| {
+                    line.split_whitespace()
+                        .filter_map(|d| d.parse().ok())
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            nodes.push(NumaNode {
+                id: node_id,
+                cpus,
+                memory_total_kb: meminfo_value("MemTotal:"),
+                memory_free_kb: meminfo_value("MemFree:"),
+                distances,
+            });
+        }
+
+        nodes.sort_by_key(|node| node.id);
+        nodes
+    }
+
+    /// Discovers the topology from the Linux sysfs hierarchy.
+    fn discover_from_sysfs(&self) -> Result<CpuTopology, String> {
+        let online = read_sysfs(Path::new(SYSFS_CPU_ROOT).join("online"))
+            .ok_or_else(|| format!("Failed to read {}/online", SYSFS_CPU_ROOT))?;
+        let online = parse_cpu_list(&online)?;
+        let numa_nodes = self.read_numa_nodes();
+
+        let mut cpus = Vec::with_capacity(online.len());
+        for id in online {
+            let topology_dir = Path::new(SYSFS_CPU_ROOT)
+                .join(format!("cpu{}", id))
+                .join("topology");
+
+            // Missing attributes are treated as "each CPU is its own core on socket 0".
+            let core_id = read_sysfs(topology_dir.join("core_id"))
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(id);
+            let package_id = read_sysfs(topology_dir.join("physical_package_id"))
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0);
+            let thread_siblings = read_sysfs(topology_dir.join("thread_siblings_list"))
+                .and_then(|v| parse_cpu_list(&v).ok())
+                .unwrap_or_else(|| BTreeSet::from([id]));
+            let numa_node = numa_nodes
+                .iter()
+                .find(|node| node.cpus.contains(&id))
+                .map(|node| node.id);
+
+            cpus.push(LogicalCpu {
+                id,
+                core_id,
+                package_id,
+                numa_node,
+                thread_siblings,
+            });
+        }
+
+        Ok(CpuTopology { cpus })
+    }
+
+    /// Reads a `sysctl` value, or `None` if the key does not exist.
+    fn read_sysctl(name: &str) -> Option<String> {
+        let output = Command::new("sysctl").arg("-n").arg(name).output().ok()?;
+        let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (output.status.success() && !value.is_empty()).then_some(value)
+    }
+
+    /// Discovers the core classes and SIMD extensions on Linux.
+    ///
+    /// Arm cores are told apart by the capacity the kernel assigns them and by
+    /// their part number; Intel hybrid cores by the PMU device they belong to.
+    fn architecture_from_sysfs(&self) -> Result<CpuArchitecture, String> {
+        let platform = Platform::current();
+        let cpuinfo = fs::read_to_string(PROC_CPUINFO)
+            .map(|contents| parse_cpuinfo(&contents))
+            .map_err(|e| format!("Failed to read {}: {}", PROC_CPUINFO, e))?;
+        let online = read_sysfs(Path::new(SYSFS_CPU_ROOT).join("online"))
+            .ok_or_else(|| format!("Failed to read {}/online", SYSFS_CPU_ROOT))?;
+        let online = parse_cpu_list(&online)?;
+
+        let hybrid: Vec<(BTreeSet<u32>, &str)> = INTEL_HYBRID_PMUS
+            .iter()
+            .filter_map(|(path, model)| {
+                let cpus = parse_cpu_list(&read_sysfs(Path::new(path))?).ok()?;
+                Some((cpus, *model))
+            })
+            .collect();
+
+        let cpus: Vec<(u32, Option<u32>, Option<String>)> = online
+            .iter()
+            .map(|&id| {
+                let capacity = read_sysfs(
+                    Path::new(SYSFS_CPU_ROOT)
+                        .join(format!("cpu{}", id))
+                        .join("cpu_capacity"),
+                )
+                .and_then(|v| v.parse().ok());
+                let model = hybrid
+                    .iter()
+                    .find(|(cpus, _)| cpus.contains(&id))
+                    .map(|(_, model)| model.to_string())
+                    .or_else(|| {
+                        let entry = cpuinfo.get(&id)?;
+                        let name = arm_core_name(entry.implementer?, entry.part?)?;
+                        Some(name.to_string())
+                    });
+                (id, capacity, model)
+            })
+            .collect();
+
+        let first = cpuinfo.values().next();
+        let features = first.map(|e| e.features.clone()).unwrap_or_default();
+        let sve_vector_bits = read_sysfs(Path::new(SVE_VECTOR_LENGTH))
+            .and_then(|bytes| bytes.parse::<u32>().ok())
+            .map(|bytes| bytes * 8);
+
+        Ok(CpuArchitecture {
+            model_name: first.and_then(|e| e.model_name.clone()),
+            core_classes: group_core_classes(&cpus),
+            simd: simd_extensions(&platform.arch, &features, sve_vector_bits),
+            platform,
+        })
+    }
+
+    /// Discovers the core classes and SIMD extensions on macOS.
+    ///
+    /// Apple Silicon reports its performance and efficiency clusters as
+    /// "performance levels", but not which logical CPUs belong to each.
+    fn architecture_from_sysctl(&self) -> Result<CpuArchitecture, String> {
+        let platform = Platform::current();
+        let levels: u32 = Self::read_sysctl("hw.nperflevels")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+
+        let mut core_classes: Vec<CoreClass> = (0..levels)
+            .filter_map(|level| {
+                let cpu_count = Self::read_sysctl(&format!("hw.perflevel{}.logicalcpu", level))?
+                    .parse()
+                    .ok()?;
+                let label = Self::read_sysctl(&format!("hw.perflevel{}.name", level))
+                    .map(|name| name.to_lowercase())
+                    .unwrap_or_else(|| format!("level {}", level));
+                Some(CoreClass {
+                    label,
+                    model: None,
+                    cpu_count,
+                    cpus: BTreeSet::new(),
+                    capacity: None,
+                })
+            })
+            .collect();
+
+        // Intel Macs have no performance levels; all their cores are alike.
+        if core_classes.len() <= 1 {
+            let cpu_count = Self::read_sysctl("hw.logicalcpu")
+                .and_then(|v| v.parse().ok())
+                .ok_or("Failed to read hw.logicalcpu from sysctl")?;
+            core_classes = vec![CoreClass {
+                label: "uniform".to_string(),
+                model: None,
+                cpu_count,
+                cpus: BTreeSet::new(),
+                capacity: None,
+            }];
+        }
+
+        // Translate the macOS feature keys to their Linux names.
+        let features = [
+            ("hw.optional.neon", "asimd"),
+            ("hw.optional.arm.FEAT_SME", "sme"),
+        ]
+        .iter()
+        .filter(|(key, _)| Self::read_sysctl(key).as_deref() == Some("1"))
+        .map(|(_, feature)| feature.to_string())
+        .collect();
+
+        Ok(CpuArchitecture {
+            model_name: Self::read_sysctl("machdep.cpu.brand_string"),
+            core_classes,
+            simd: simd_extensions(&platform.arch, &features, None),
+            platform,
+        })
+    }
+
+    /// Reads the caches of a logical CPU from `cpuN/cache/index*` in sysfs.
+    fn caches_from_sysfs(&self, cpu: u32) -> Result<Vec<CacheLevel>, String> {
+        let cache_root = Path::new(SYSFS_CPU_ROOT)
+            .join(format!("cpu{}", cpu))
+            .join("cache");
+        let entries = fs::read_dir(&cache_root)
+            .map_err(|e| format!("Failed to read {}: {}", cache_root.display(), e))?;
+
+        let mut caches = Vec::new();
+        for entry in entries.flatten() {
+            if !entry.file_name().to_string_lossy().starts_with("index") {
+                continue;
+            }
+            let read = |name: &str| read_sysfs(entry.path().join(name));
+            let kind = read("type").unwrap_or_default();
+            let (Some(level), Some(size_bytes)) = (
+                read("level").and_then(|v| v.parse().ok()),
+                read("size").and_then(|v| parse_cache_size(&v)),
+            ) else {
+                continue;
+            };
+            if kind == "Instruction" {
+                continue;
+            }
+
+            caches.push(CacheLevel {
+                level,
+                kind,
+                size_bytes,
+                line_bytes: read("coherency_line_size").and_then(|v| v.parse().ok()),
+                ways: read("ways_of_associativity").and_then(|v| v.parse().ok()),
+                shared_cpus: read("shared_cpu_list")
+                    .and_then(|v| parse_cpu_list(&v).ok())
+                    .unwrap_or_default(),
+            });
+        }
+
+        caches.sort_by_key(|cache| cache.level);
+        Ok(caches)
+    }
+
+    /// Reads the cache sizes from `sysctl` on macOS, which does not report sharing.
+    fn caches_from_sysctl(&self) -> Result<Vec<CacheLevel>, String> {
+        let line_bytes = Self::read_sysctl("hw.cachelinesize").and_then(|v| v.parse().ok());
+        let caches: Vec<CacheLevel> = [
+            (1, "Data", "hw.l1dcachesize"),
+            (2, "Unified", "hw.l2cachesize"),
+            (3, "Unified", "hw.l3cachesize"),
+        ]
+        .iter()
+        .filter_map(|(level, kind, key)| {
+            let size_bytes = Self::read_sysctl(key)?
+                .parse()
+                .ok()
+                .filter(|size| *size > 0)?;
+            Some(CacheLevel {
+                level: *level,
+                kind: kind.to_string(),
+                size_bytes,
+                line_bytes,
+                ways: None,
+                shared_cpus: BTreeSet::new(),
+            })
+        })
+        .collect();
+
+        if caches.is_empty() {
+            return Err("sysctl reported no cache sizes".to_string());
+        }
+        Ok(caches)
+    }
+
+    /// Discovers a flat topology on macOS using `sysctl`.
+    ///
+    /// macOS does not expose SMT sibling or NUMA information, so every logical
+    /// CPU is reported as its own core on a single package.
+    fn discover_from_sysctl(&self) -> Result<CpuTopology, String> {
+        let output = Command::new("sysctl")
+            .arg("-n")
+            .arg("hw.logicalcpu")
+            .output()
+            .map_err(|e| format!("Failed to execute sysctl: {}", e))?;
+        let count: u32 = String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse()
+            .map_err(|_| "Failed to parse hw.logicalcpu from sysctl".to_string())?;
+
+        let cpus = (0..count)
+            .map(|id| LogicalCpu {
+                id,
+                core_id: id,
+                package_id: 0,
+                numa_node: None,
+                thread_siblings: BTreeSet::from([id]),
+            })
+            .collect();
+
+        Ok(CpuTopology { cpus })
+    }
+}
+
+// Implement the `TopologyPort` trait for `TopologyAdapter`.
+impl TopologyPort for TopologyAdapter {
+    fn discover_cpu_topology(&self) -> Result<CpuTopology, String> {
+        let result = if cfg!(target_os = "macos") {
+            self.discover_from_sysctl()
+        } else {
+            self.discover_from_sysfs()
+        };
+
+        match &result {
+            Ok(topology) => self.logger.log_debug(&format!(
+                "Discovered {} logical CPUs across {} package(s)",
+                topology.cpus.len(),
+                topology.package_count()
+            )),
+            Err(e) => self
+                .logger
+                .log_error(&format!("CPU topology discovery failed: {}", e)),
+        }
+
+        result
+    }
+
+    fn discover_cpu_architecture(&self) -> Result<CpuArchitecture, String> {
+        let result = if cfg!(target_os = "macos") {
+            self.architecture_from_sysctl()
+        } else {
+            self.architecture_from_sysfs()
+        };
+
+        match &result {
+            Ok(architecture) => self.logger.log_debug(&format!(
+                "Discovered {} CPU with {} core class(es)",
+                architecture.platform,
+                architecture.core_classes.len()
+            )),
+            Err(e) => self
+                .logger
+                .log_error(&format!("CPU architecture discovery failed: {}", e)),
+        }
+
+        result
+    }
+
+    fn discover_caches(&self, cpu: u32) -> Result<Vec<CacheLevel>, String> {
+        let result = if cfg!(target_os = "macos") {
+            self.caches_from_sysctl()
+        } else {
+            self.caches_from_sysfs(cpu)
+        };
+
+        match &result {
+            Ok(caches) => self.logger.log_debug(&format!(
+                "Discovered {} cache level(s) for CPU {}",
+                caches.len(),
+                cpu
+            )),
+            Err(e) => self
+                .logger
+                .log_error(&format!("Cache discovery failed: {}", e)),
+        }
+
+        result
+    }
+
+    fn discover_numa_nodes(&self) -> Result<Vec<NumaNode>, String> {
+        let nodes = self.read_numa_nodes();
+        if !nodes.is_empty() {
+            return Ok(nodes);
+        }
+
+        // Without NUMA information, treat the whole machine as a single node.
+        let topology = self.discover_cpu_topology()?;
+        Ok(vec![NumaNode {
+            id: 0,
+            cpus: topology.all_cpu_ids(),
+            memory_total_kb: None,
+            memory_free_kb: None,
+            distances: vec![10],
+        }])
+    }
+}
+
+/// Pins the calling thread to a single logical CPU.
+///
+/// Memory benchmarks rely on this together with the kernel's first-touch policy:
+/// pages are allocated on the NUMA node of the CPU that first writes them.
+///
+/// # Arguments
+///
+/// * `cpu` - The logical CPU to pin the calling thread to.
+///
+/// # Returns
+///
+/// A `Result` indicating whether the affinity was applied.
+#[cfg(target_os = "linux")]
+pub fn pin_current_thread(cpu: u32) -> Result<(), String> {
+    // SAFETY: `cpu_set_t` is a plain bitmask, and `sched_setaffinity` with pid 0
+    // only affects the calling thread.
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_SET(cpu as usize, &mut set);
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(format!(
+                "Failed to pin thread to CPU {}: {}",
+                cpu,
+                std::io::Error::last_os_error()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Pins the calling thread to a single logical CPU.
+///
+/// Thread affinity is not supported on this platform, so this always fails.
+#[cfg(not(target_os = "linux"))]
+pub fn pin_current_thread(cpu: u32) -> Result<(), String> {
+    Err(format!(
+        "Pinning threads to CPU {} is not supported on this platform",
+        cpu
+    ))
+}
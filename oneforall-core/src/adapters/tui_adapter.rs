@@ -0,0 +1,352 @@
+//! Overwatch TUI Adapter
+//!
+//! This module provides the interactive terminal interface of Overwatch, built
+//! on ratatui, for operators working on headless machines over SSH. It shows
+//! live per-core CPU utilization, memory usage, temperatures, the busiest
+//! processes and the services and containers they belong to, and the status of
+//! any running stress test. On a virtual machine the CPU time stolen by the
+//! hypervisor is shown with the cores.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use common::ports::log_port::LoggerPort;
+use common::ports::telemetry_port::TelemetryPort;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Gauge, Paragraph, Row, Table};
+use ratatui::Frame;
+
+use crate::adapters::system_stats_adapter::SystemStatsAdapter;
+use crate::adapters::thermal_adapter::ThermalAdapter;
+use crate::domain::ps_command::CgroupUsage;
+use crate::domain::system_stats::{
+    core_usage_between, CoreUsage, CpuTimes, MemoryUsage, ProcessUsage,
+};
+use crate::domain::thermal::TemperatureReading;
+use crate::domain::virtualization::StealSample;
+use crate::ports::ps_command_port::PsCommandPort;
+use crate::ports::system_stats_port::SystemStatsPort;
+use crate::ports::thermal_port::ThermalPort;
+
+/// Interval between two refreshes of the statistics.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Number of processes listed in the process table.
+const TOP_PROCESSES: usize = 15;
+
+/// Number of services and containers listed in the cgroup table.
+const TOP_CGROUPS: usize = 15;
+
+/// Width of the text bar drawn for each core.
+const CORE_BAR_WIDTH: usize = 10;
+
+/// Everything shown on one frame of the interface.
+#[derive(Default)]
+struct Snapshot {
+    cores: Vec<CoreUsage>,
+    memory: MemoryUsage,
+    temperatures: Vec<TemperatureReading>,
+    processes: Vec<ProcessUsage>,
+    cgroups: Vec<CgroupUsage>,
+    active_test: String,
+}
+
+/// Represents the Overwatch terminal interface.
+pub struct OverwatchTui {
+    logger: Arc<dyn LoggerPort>,          // inject the logger port
+    telemetry: Arc<dyn TelemetryPort>,    // progress of stress tests run by this process
+    process_tree: Arc<dyn PsCommandPort>, // processes grouped by service or container
+    stats: SystemStatsAdapter,
+    thermal: ThermalAdapter,
+}
+
+impl OverwatchTui {
+    /// Creates a new instance of `OverwatchTui`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    /// * `telemetry` - The telemetry port publishing stress test progress.
+    /// * `process_tree` - The port listing processes with their cgroups.
+    ///
+    /// # Returns
+    /// An instance of `OverwatchTui`.
+    pub fn new(
+        logger: Arc<dyn LoggerPort>,
+        telemetry: Arc<dyn TelemetryPort>,
+        process_tree: Arc<dyn PsCommandPort>,
+    ) -> Self {
+        OverwatchTui {
+            stats: SystemStatsAdapter::new(logger.clone()),
+            thermal: ThermalAdapter::new(logger.clone()),
+            logger,
+            telemetry,
+            process_tree,
+        }
+    }
+
+    /// Runs the interface until the operator presses `q`, `Esc` or `Ctrl+C`.
+    ///
+    /// The terminal is restored before returning, including on error.
+    pub fn run(&self) -> Result<(), String> {
+        self.logger
+            .log_info("Starting Overwatch terminal interface.");
+        let mut terminal = ratatui::init();
+        let outcome = self.event_loop(&mut terminal);
+        ratatui::restore();
+        outcome
+    }
+
+    /// Refreshes and redraws the interface until a quit key is pressed.
+    fn event_loop(&self, terminal: &mut ratatui::DefaultTerminal) -> Result<(), String> {
+        let mut previous_times = self.stats.read_cpu_times().unwrap_or_default();
+        let mut snapshot = Snapshot::default();
+        let mut last_refresh: Option<Instant> = None;
+
+        loop {
+            if last_refresh.is_none_or(|t| t.elapsed() >= REFRESH_INTERVAL) {
+                snapshot = self.snapshot(&mut previous_times);
+                last_refresh = Some(Instant::now());
+            }
+
+            terminal
+                .draw(|frame| Self::draw(frame, &snapshot))
+                .map_err(|e| format!("Failed to draw the terminal interface: {}", e))?;
+
+            if !event::poll(Duration::from_millis(250)).map_err(|e| e.to_string())? {
+                continue;
+            }
+            if let Event::Key(key) = event::read().map_err(|e| e.to_string())? {
+                let ctrl_c =
+                    key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                if key.kind == KeyEventKind::Press
+                    && (matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) || ctrl_c)
+                {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Collects the statistics for the next frame.
+    fn snapshot(&self, previous_times: &mut Vec<(u32, CpuTimes)>) -> Snapshot {
+        let cores = match self.stats.read_cpu_times() {
+            Ok(times) => {
+                let cores = core_usage_between(previous_times, &times);
+                *previous_times = times;
+                cores
+            }
+            Err(_) => Vec::new(),
+        };
+        let processes = self
+            .stats
+            .read_top_processes(TOP_PROCESSES)
+            .unwrap_or_default();
+        let mut cgroups = self
+            .process_tree
+            .read_process_tree()
+            .map(|tree| tree.cgroup_usage())
+            .unwrap_or_default();
+        cgroups.truncate(TOP_CGROUPS);
+
+        // Prefer the progress published by this process; otherwise look for
+        // stress-ng workers started elsewhere.
+        let active_test = match self.telemetry.latest_progress() {
+            Some(progress) if !progress.finished => progress.render_line(),
+            _ => {
+                let workers = processes
+                    .iter()
+                    .filter(|p| p.command.contains("stress-ng"))
+                    .count();
+                if workers > 0 {
+                    format!("stress-ng running ({} busy processes)", workers)
+                } else {
+                    "No stress test running".to_string()
+                }
+            }
+        };
+
+        Snapshot {
+            cores,
+            memory: self.stats.read_memory_usage().unwrap_or_default(),
+            temperatures: self
+                .thermal
+                .sample_thermals()
+                .map(|s| s.temperatures)
+                .unwrap_or_default(),
+            processes,
+            cgroups,
+            active_test,
+        }
+    }
+
+    /// Returns the color used for a utilization or temperature level.
+    fn level_color(percent: f64) -> Color {
+        if percent >= 90.0 {
+            Color::Red
+        } else if percent >= 60.0 {
+            Color::Yellow
+        } else {
+            Color::Green
+        }
+    }
+
+    /// Draws one frame of the interface.
+    fn draw(frame: &mut Frame, snapshot: &Snapshot) {
+        let [header, cores, middle, bottom] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(6),
+            Constraint::Length(8),
+            Constraint::Min(8),
+        ])
+        .areas(frame.area());
+
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                " OneForAll Overwatch ".bold().reversed(),
+                Span::raw(format!("  {}   ", snapshot.active_test)),
+                "q".bold(),
+                Span::raw(" quit"),
+            ])),
+            header,
+        );
+
+        Self::draw_cores(frame, cores, &snapshot.cores);
+
+        let [memory_and_test, temperatures] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(middle);
+        let memory = snapshot.memory;
+        frame.render_widget(
+            Gauge::default()
+                .block(Block::bordered().title(" Memory "))
+                .gauge_style(Style::default().fg(Self::level_color(memory.used_percent())))
+                .percent(memory.used_percent().round().clamp(0.0, 100.0) as u16)
+                .label(format!(
+                    "{:.1} / {:.1} GiB",
+                    memory.used_kb() as f64 / 1048576.0,
+                    memory.total_kb as f64 / 1048576.0
+                )),
+            memory_and_test,
+        );
+
+        let temperature_lines: Vec<Line> = if snapshot.temperatures.is_empty() {
+            vec![Line::from("No temperature sensors available")]
+        } else {
+            snapshot
+                .temperatures
+                .iter()
+                .map(|t| {
+                    Line::from(vec![
+                        Span::raw(format!("{:<28} ", t.name)),
+                        Span::styled(
+                            format!("{:>5.1} °C", t.celsius),
+                            Style::default().fg(Self::level_color(t.celsius)),
+                        ),
+                    ])
+                })
+                .collect()
+        };
+        frame.render_widget(
+            Paragraph::new(temperature_lines).block(Block::bordered().title(" Temperatures ")),
+            temperatures,
+        );
+
+        let [processes, cgroups] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(bottom);
+        let rows = snapshot.processes.iter().map(|p| {
+            Row::new(vec![
+                p.pid.to_string(),
+                format!("{:.1}", p.cpu_percent),
+                format!("{:.1}", p.memory_percent),
+                p.command.clone(),
+            ])
+        });
+        frame.render_widget(
+            Table::new(
+                rows,
+                [
+                    Constraint::Length(8),
+                    Constraint::Length(7),
+                    Constraint::Length(7),
+                    Constraint::Min(10),
+                ],
+            )
+            .header(Row::new(vec!["PID", "CPU%", "MEM%", "COMMAND"]).bold())
+            .block(Block::bordered().title(" Top processes ")),
+            processes,
+        );
+
+        let rows = snapshot.cgroups.iter().map(|g| {
+            Row::new(vec![
+                g.name.clone(),
+                g.processes.to_string(),
+                format!("{:.1}", g.cpu_percent),
+                format!("{:.1}", g.memory_percent),
+                format!("{} {}", g.leader_pid, g.leader_command),
+            ])
+        });
+        frame.render_widget(
+            Table::new(
+                rows,
+                [
+                    Constraint::Min(16),
+                    Constraint::Length(6),
+                    Constraint::Length(7),
+                    Constraint::Length(7),
+                    Constraint::Min(10),
+                ],
+            )
+            .header(Row::new(vec!["SERVICE", "PROCS", "CPU%", "MEM%", "LEADER"]).bold())
+            .block(Block::bordered().title(" Services and containers ")),
+            cgroups,
+        );
+    }
+
+    /// Draws one text bar per core, laid out in as many columns as fit.
+    fn draw_cores(frame: &mut Frame, area: Rect, cores: &[CoreUsage]) {
+        const COLUMN_WIDTH: usize = CORE_BAR_WIDTH + 15;
+        // Steal time is only ever non-zero on a virtual machine.
+        let title = match StealSample::from_cores(cores) {
+            Some(steal) if steal.worst_percent > 0.0 => {
+                format!(" CPU cores (steal {:.1}%) ", steal.percent)
+            }
+            _ => " CPU cores ".to_string(),
+        };
+        let block = Block::bordered().title(title);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let columns = (inner.width as usize / COLUMN_WIDTH).max(1);
+        let rows = cores.len().div_ceil(columns).max(1);
+        let lines: Vec<Line> = (0..rows)
+            .map(|row| {
+                let spans = (0..columns)
+                    .filter_map(|column| cores.get(column * rows + row))
+                    .flat_map(|core| {
+                        let filled =
+                            ((core.percent / 100.0) * CORE_BAR_WIDTH as f64).round() as usize;
+                        let filled = filled.min(CORE_BAR_WIDTH);
+                        vec![
+                            Span::raw(format!("{:>4} [", core.cpu)),
+                            Span::styled(
+                                "|".repeat(filled),
+                                Style::default().fg(Self::level_color(core.percent)),
+                            ),
+                            Span::raw(format!(
+                                "{}] {:>3.0}%  ",
+                                " ".repeat(CORE_BAR_WIDTH - filled),
+                                core.percent
+                            )),
+                        ]
+                    })
+                    .collect::<Vec<_>>();
+                Line::from(spans)
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), inner);
+    }
+}
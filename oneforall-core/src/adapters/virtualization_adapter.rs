@@ -0,0 +1,216 @@
+//! Virtualization Adapter
+//!
+//! This module provides an adapter that detects the hypervisor this machine
+//! runs on and the paravirtual devices it exposes. The hypervisor announces
+//! itself through CPUID leaf 0x40000000 on x86; elsewhere, and on hypervisors
+//! that hide that leaf, the Xen sysfs interface and the DMI system information
+//! of the VM firmware are used. Paravirtual devices are listed from the virtio,
+//! Hyper-V VMBus and Xen buses, plus the VMware paravirtual PCI devices.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use common::ports::log_port::LoggerPort;
+
+use crate::adapters::cloud_metadata_adapter::read_dmi_system;
+use crate::adapters::system_stats_adapter::SystemStatsAdapter;
+use crate::domain::system_stats::steal_percent_since_boot;
+use crate::domain::virtualization::{
+    hypervisor_from_cpuid, hypervisor_from_dmi, paravirt_driver_kind, virtio_device_kind,
+    Hypervisor, ParavirtDevice, VirtualizationInfo,
+};
+use crate::ports::system_stats_port::SystemStatsPort;
+use crate::ports::virtualization_port::VirtualizationPort;
+
+/// Where the Xen hypervisor interface is exposed in dom0 and Xen guests.
+const SYSFS_HYPERVISOR_TYPE: &str = "/sys/hypervisor/type";
+
+/// The PCI vendor ID of VMware, whose paravirtual devices sit on the PCI bus.
+const VMWARE_PCI_VENDOR: &str = "0x15ad";
+
+/// Represents the CPUID and sysfs virtualization adapter.
+pub struct VirtualizationAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    stats: SystemStatsAdapter,
+}
+
+impl VirtualizationAdapter {
+    /// Creates a new instance of `VirtualizationAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `VirtualizationAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        VirtualizationAdapter {
+            stats: SystemStatsAdapter::new(logger.clone()),
+            logger,
+        }
+    }
+
+    /// Reads the hypervisor vendor signature from CPUID leaf 0x40000000.
+    ///
+    /// The leaf is only defined when the hypervisor bit, bit 31 of ECX in leaf
+    /// 1, is set; on bare metal it returns unrelated data.
+    #[cfg(target_arch = "x86_64")]
+    fn cpuid_signature() -> Option<String> {
+        use std::arch::x86_64::__cpuid;
+
+        if __cpuid(1).ecx & (1 << 31) == 0 {
+            return None;
+        }
+        let leaf = __cpuid(0x4000_0000);
+        let bytes: Vec<u8> = [leaf.ebx, leaf.ecx, leaf.edx]
+            .iter()
+            .flat_map(|register| register.to_le_bytes())
+            .collect();
+        Some(String::from_utf8_lossy(&bytes).to_string())
+    }
+
+    /// Reads the hypervisor vendor signature from CPUID leaf 0x40000000.
+    ///
+    /// CPUID only exists on x86, so this always returns `None`.
+    #[cfg(not(target_arch = "x86_64"))]
+    fn cpuid_signature() -> Option<String> {
+        None
+    }
+
+    /// Detects the hypervisor, returning it with how it was detected.
+    fn detect_hypervisor() -> Option<(Hypervisor, &'static str)> {
+        if let Some(hypervisor) = Self::cpuid_signature()
+            .as_deref()
+            .and_then(hypervisor_from_cpuid)
+        {
+            return Some((hypervisor, "CPUID"));
+        }
+        if fs::read_to_string(SYSFS_HYPERVISOR_TYPE).is_ok_and(|kind| kind.trim() == "xen") {
+            return Some((Hypervisor::Xen, "sysfs"));
+        }
+        hypervisor_from_dmi(&read_dmi_system()).map(|hypervisor| (hypervisor, "DMI"))
+    }
+
+    /// Returns the name of the driver bound to a device, if any.
+    fn driver(device: &Path) -> Option<String> {
+        fs::read_link(device.join("driver")).ok().and_then(|link| {
+            link.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+        })
+    }
+
+    /// Lists the devices of a bus, sorted by name.
+    fn bus_devices(bus: &str) -> Vec<(String, PathBuf)> {
+        let mut devices: Vec<(String, PathBuf)> =
+            fs::read_dir(Path::new("/sys/bus").join(bus).join("devices"))
+                .map(|entries| {
+                    entries
+                        .flatten()
+                        .map(|entry| {
+                            (
+                                entry.file_name().to_string_lossy().to_string(),
+                                entry.path(),
+                            )
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+        devices.sort_by(|a, b| a.0.cmp(&b.0));
+        devices
+    }
+
+    /// Lists the paravirtual devices of every supported bus.
+    fn paravirt_devices() -> Vec<ParavirtDevice> {
+        let mut devices = Vec::new();
+
+        for (name, path) in Self::bus_devices("virtio") {
+            let id = fs::read_to_string(path.join("device"))
+                .ok()
+                .and_then(|id| u32::from_str_radix(id.trim().trim_start_matches("0x"), 16).ok());
+            devices.push(ParavirtDevice {
+                bus: "virtio".to_string(),
+                name,
+                kind: id.map_or("unknown", virtio_device_kind).to_string(),
+                driver: Self::driver(&path),
+            });
+        }
+
+        for (name, path) in Self::bus_devices("vmbus") {
+            let driver = Self::driver(&path);
+            devices.push(ParavirtDevice {
+                bus: "vmbus".to_string(),
+                name,
+                kind: driver
+                    .as_deref()
+                    .map_or("unknown", paravirt_driver_kind)
+                    .to_string(),
+                driver,
+            });
+        }
+
+        // Xen names its devices after their kind, e.g. "vif-0" or "vbd-51712".
+        for (name, path) in Self::bus_devices("xen") {
+            let kind = match name.split('-').next().unwrap_or_default() {
+                "vif" => "network",
+                "vbd" => "block",
+                "console" => "console",
+                "vkbd" => "input",
+                "vfb" => "framebuffer",
+                _ => "unknown",
+            };
+            devices.push(ParavirtDevice {
+                bus: "xen".to_string(),
+                kind: kind.to_string(),
+                driver: Self::driver(&path),
+                name,
+            });
+        }
+
+        for (name, path) in Self::bus_devices("pci") {
+            let vendor = fs::read_to_string(path.join("vendor")).unwrap_or_default();
+            if vendor.trim() != VMWARE_PCI_VENDOR {
+                continue;
+            }
+            let driver = Self::driver(&path);
+            let kind = driver.as_deref().map_or("unknown", paravirt_driver_kind);
+            // VMware also emulates ordinary hardware, such as its PCI bridges.
+            if kind == "unknown" {
+                continue;
+            }
+            devices.push(ParavirtDevice {
+                bus: "pci".to_string(),
+                name,
+                kind: kind.to_string(),
+                driver,
+            });
+        }
+
+        devices
+    }
+}
+
+// Implement the `VirtualizationPort` trait for `VirtualizationAdapter`.
+impl VirtualizationPort for VirtualizationAdapter {
+    fn detect_virtualization(&self) -> Result<VirtualizationInfo, String> {
+        let hypervisor = Self::detect_hypervisor();
+        let devices = Self::paravirt_devices();
+        let steal_since_boot_percent = self
+            .stats
+            .read_cpu_times()
+            .map(|times| steal_percent_since_boot(&times))?;
+        if let Some((hypervisor, source)) = &hypervisor {
+            self.logger.log_debug(&format!(
+                "Running on {} (detected from {}) with {} paravirtual devices",
+                hypervisor.name(),
+                source,
+                devices.len()
+            ));
+        }
+        Ok(VirtualizationInfo {
+            detected_by: hypervisor.as_ref().map(|(_, source)| source.to_string()),
+            hypervisor: hypervisor.map(|(hypervisor, _)| hypervisor),
+            devices,
+            steal_since_boot_percent,
+        })
+    }
+}
@@ -0,0 +1,266 @@
+//! VRAM Test Runner
+//!
+//! This module runs the GPU memory test started by `oneforall stress --vram`.
+//! memtest_vulkan, which tests NVIDIA, AMD and Intel GPUs alike through Vulkan,
+//! writes, reads back and verifies patterns over the video memory of one GPU
+//! until it is interrupted; its output is parsed as it runs, and it is stopped
+//! with Ctrl+C once the test duration has elapsed.
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use common::domain::job::{JobSpec, JobStatus, Resource};
+use common::ports::job_port::JobPort;
+use common::ports::log_port::LoggerPort;
+use common::ports::telemetry_port::TelemetryPort;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::Command;
+
+use crate::adapters::progress_reporter::ProgressReporter;
+use crate::domain::burn_in::WorkloadOutcome;
+use crate::domain::vram::{parse_memtest_line, VramTestReport};
+
+/// Environment variable overriding the memtest_vulkan executable that is run.
+pub const MEMTEST_VULKAN_ENV_VAR: &str = "ONEFORALL_MEMTEST_VULKAN";
+
+/// Time memtest_vulkan is given to print its summary after Ctrl+C before it is killed.
+const STOP_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+/// Runs GPU memory tests.
+pub struct VramTestRunner {
+    logger: Arc<dyn LoggerPort>,
+    telemetry: Arc<dyn TelemetryPort>,
+    jobs: Arc<dyn JobPort>,
+}
+
+impl VramTestRunner {
+    /// Creates a new instance of `VramTestRunner`.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger` - Logger implementation for logging messages.
+    /// * `telemetry` - Receives the progress of the test.
+    /// * `jobs` - The job queue the test is submitted to.
+    ///
+    /// # Returns
+    ///
+    /// * `VramTestRunner` - A new runner.
+    pub fn new(
+        logger: Arc<dyn LoggerPort>,
+        telemetry: Arc<dyn TelemetryPort>,
+        jobs: Arc<dyn JobPort>,
+    ) -> Self {
+        VramTestRunner {
+            logger,
+            telemetry,
+            jobs,
+        }
+    }
+
+    /// Finds memtest_vulkan, from `ONEFORALL_MEMTEST_VULKAN` or `PATH`.
+    fn locate_binary() -> Result<PathBuf, String> {
+        let is_executable = |path: &Path| {
+            fs::metadata(path)
+                .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+                .unwrap_or(false)
+        };
+        if let Some(path) = std::env::var_os(MEMTEST_VULKAN_ENV_VAR) {
+            let path = PathBuf::from(path);
+            return if is_executable(&path) {
+                Ok(path)
+            } else {
+                Err(format!(
+                    "memtest_vulkan at {} is not an executable file (set by {})",
+                    path.display(),
+                    MEMTEST_VULKAN_ENV_VAR
+                ))
+            };
+        }
+        std::env::var_os("PATH")
+            .map(|paths| std::env::split_paths(&paths).collect::<Vec<_>>())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|dir| dir.join("memtest_vulkan"))
+            .find(|candidate| is_executable(candidate))
+            .ok_or_else(|| {
+                format!(
+                    "memtest_vulkan was not found. Download it from \
+                     https://github.com/GpuZelenograd/memtest_vulkan/releases or set {} to \
+                     the path of a memtest_vulkan executable.",
+                    MEMTEST_VULKAN_ENV_VAR
+                )
+            })
+    }
+
+    /// Runs memtest_vulkan until the duration has elapsed or the job is
+    /// cancelled, adding its output to the report.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<bool, String>` - Whether the test ran to the end, or why it could not run.
+    async fn run_memtest(
+        &self,
+        device: u32,
+        duration: Duration,
+        cancel: &tokio_util::sync::CancellationToken,
+        report: &mut VramTestReport,
+    ) -> Result<bool, String> {
+        let binary = Self::locate_binary()?;
+        let mut child = Command::new(&binary)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("Failed to run {}: {}", binary.display(), e))?;
+
+        // memtest_vulkan asks which GPU to test; closing its input afterwards also
+        // answers the key press it waits for before exiting.
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(format!("{}\n", device).as_bytes())
+                .await
+                .map_err(|e| format!("Failed to select GPU {}: {}", device, e))?;
+        }
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| "memtest_vulkan output is unavailable".to_string())?;
+        let mut lines = BufReader::new(stdout).lines();
+
+        let deadline = tokio::time::sleep(duration);
+        tokio::pin!(deadline);
+        let mut stopped = false;
+        loop {
+            tokio::select! {
+                line = lines.next_line() => match line {
+                    Ok(Some(line)) => {
+                        self.logger.log_trace(&format!("memtest_vulkan: {}", line));
+                        if let Some(line) = parse_memtest_line(&line) {
+                            report.record(line);
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(e) => return Err(format!("Failed to read memtest_vulkan output: {}", e)),
+                },
+                _ = &mut deadline, if !stopped => {
+                    stopped = true;
+                    if let Some(pid) = child.id() {
+                        // SAFETY: the signal only goes to the memtest_vulkan process spawned above.
+                        unsafe { libc::kill(pid as libc::pid_t, libc::SIGINT) };
+                    }
+                }
+                _ = cancel.cancelled() => {
+                    self.logger.log_warn("Cancelling memtest_vulkan");
+                    let _ = child.kill().await;
+                    return Ok(false);
+                }
+            }
+        }
+
+        match tokio::time::timeout(STOP_GRACE_PERIOD, child.wait()).await {
+            // memtest_vulkan exits with a failure status when it found errors, which
+            // the report already holds, or when it was interrupted.
+            Ok(Ok(status)) if status.success() || stopped || report.errors > 0 => Ok(true),
+            Ok(Ok(status)) => Err(format!("memtest_vulkan exited with {}", status)),
+            Ok(Err(e)) => Err(format!("Failed to wait for memtest_vulkan: {}", e)),
+            Err(_) => {
+                self.logger
+                    .log_warn("memtest_vulkan did not stop within the grace period, killing it");
+                let _ = child.kill().await;
+                Ok(stopped)
+            }
+        }
+    }
+
+    /// Runs the GPU memory test on one GPU for the given duration.
+    ///
+    /// # Arguments
+    ///
+    /// * `device` - The GPU to test, numbered from 1 as listed by memtest_vulkan.
+    /// * `duration` - How long to keep verifying the video memory.
+    ///
+    /// # Returns
+    ///
+    /// * `VramTestReport` - The pass/fail report of the test.
+    pub async fn run(&self, device: u32, duration: Duration) -> VramTestReport {
+        let mut ticket = self
+            .jobs
+            .submit_job(JobSpec::new("stress:vram", &[Resource::Gpu]));
+        let job_id = ticket.job.id;
+        let mut report = VramTestReport {
+            started_at: Local::now().to_rfc3339(),
+            planned_secs: duration.as_secs(),
+            elapsed_secs: 0,
+            outcome: WorkloadOutcome::Cancelled,
+            device: None,
+            iterations: 0,
+            checked_bytes: 0,
+            errors: 0,
+            bad_regions: Vec::new(),
+            unreported_regions: 0,
+        };
+        if ticket.job.status == JobStatus::Queued {
+            self.logger.log_info(&format!(
+                "VRAM test job {} queued behind a conflicting job.",
+                job_id
+            ));
+        }
+        if !ticket.admitted().await {
+            self.logger.log_warn("VRAM test cancelled while queued.");
+            return report;
+        }
+        self.logger.log_info(&format!(
+            "Started VRAM test job {}: verifying the memory of GPU {} for {} seconds.",
+            job_id,
+            device,
+            duration.as_secs()
+        ));
+
+        let progress = ProgressReporter::start(
+            self.logger.clone(),
+            self.telemetry.clone(),
+            "vram",
+            duration,
+        );
+        let started = Instant::now();
+        let result = self
+            .run_memtest(device, duration, &ticket.cancel, &mut report)
+            .await;
+        report.elapsed_secs = started.elapsed().as_secs();
+        progress.finish(None);
+
+        let (status, message) = match result {
+            Ok(true) => {
+                report.outcome = WorkloadOutcome::Completed;
+                if report.passed() {
+                    (JobStatus::Completed, None)
+                } else if report.iterations == 0 {
+                    (
+                        JobStatus::Failed,
+                        Some("no iteration of the memory test completed".to_string()),
+                    )
+                } else {
+                    (
+                        JobStatus::Failed,
+                        Some(format!("{} VRAM errors found", report.errors)),
+                    )
+                }
+            }
+            Ok(false) => (JobStatus::Cancelled, None),
+            Err(e) => {
+                self.logger.log_error(&e);
+                report.outcome = WorkloadOutcome::Failed(e.clone());
+                (JobStatus::Failed, Some(e))
+            }
+        };
+        self.jobs.finish_job(job_id, status, message);
+
+        report
+    }
+}
@@ -0,0 +1,83 @@
+//! Webhook Alert Adapter
+//!
+//! This module provides an adapter that posts alerts to an HTTP webhook,
+//! either as the JSON alert object for generic receivers or as a Slack
+//! incoming-webhook message.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::ports::log_port::LoggerPort;
+
+use crate::domain::alert::{subscribes, Alert, AlertKind, WebhookFormat, WebhookTarget};
+use crate::ports::alert_port::AlertPort;
+
+/// Time allowed for the webhook to accept an alert.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Represents a webhook alerts are posted to.
+pub struct WebhookAlertAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+    agent: ureq::Agent,
+    target: WebhookTarget,
+}
+
+impl WebhookAlertAdapter {
+    /// Creates a new instance of `WebhookAlertAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    /// * `target` - The webhook from the `[alerts]` configuration.
+    ///
+    /// # Returns
+    /// An instance of `WebhookAlertAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>, target: WebhookTarget) -> Self {
+        let agent = ureq::Agent::config_builder()
+            .timeout_global(Some(REQUEST_TIMEOUT))
+            .build()
+            .into();
+        WebhookAlertAdapter {
+            logger,
+            agent,
+            target,
+        }
+    }
+
+    /// Builds the request body in the format the webhook expects.
+    fn payload(&self, alert: &Alert) -> Result<String, String> {
+        match self.target.format {
+            WebhookFormat::Json => serde_json::to_string(alert),
+            WebhookFormat::Slack => {
+                serde_json::to_string(&serde_json::json!({ "text": alert.render() }))
+            }
+        }
+        .map_err(|e| format!("Failed to encode the alert: {}", e))
+    }
+}
+
+// Implement the `AlertPort` trait for `WebhookAlertAdapter`.
+impl AlertPort for WebhookAlertAdapter {
+    fn describe(&self) -> String {
+        format!("webhook '{}'", self.target.name)
+    }
+
+    fn accepts(&self, kind: AlertKind) -> bool {
+        subscribes(&self.target.events, kind)
+    }
+
+    fn send(&self, alert: &Alert) -> Result<(), String> {
+        let body = self.payload(alert)?;
+        // Webhook URLs embed their secret, as Slack's do, so only the name is logged.
+        self.logger.log_debug(&format!(
+            "Posting a {} alert to {}",
+            alert.kind.label(),
+            self.describe()
+        ));
+        self.agent
+            .post(&self.target.url)
+            .header("Content-Type", "application/json")
+            .send(body)
+            .map(|_| ())
+            .map_err(|e| format!("Failed to post to {}: {}", self.describe(), e))
+    }
+}
@@ -0,0 +1,149 @@
+//! Workload Adapter
+//!
+//! This module runs the command given to `benchmark exec` and measures it.
+//! The command runs in a process group of its own, with the terminal as its
+//! standard input and output, and is reaped with `wait4`, which reports the
+//! CPU time and peak resident set of the command and every process it waited
+//! for. A command that exits with an error, outlives its timeout or is
+//! cancelled fails the run; a timed-out or cancelled one is killed with its
+//! whole process group, so nothing it started keeps running.
+
+use std::os::unix::process::CommandExt;
+use std::process::Command;
+use std::sync::Arc;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use common::ports::log_port::LoggerPort;
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::workload::WorkloadResult;
+use crate::ports::workload_port::WorkloadPort;
+
+/// How often the command is checked for having exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Converts a `timeval` of `rusage` to a duration.
+fn to_duration(time: libc::timeval) -> Duration {
+    Duration::from_secs(time.tv_sec.max(0) as u64)
+        + Duration::from_micros(time.tv_usec.max(0) as u64)
+}
+
+/// Returns the peak resident set of `rusage` in kilobytes; Linux reports it in
+/// kilobytes and macOS in bytes.
+fn max_rss_kb(usage: &libc::rusage) -> u64 {
+    let max_rss = usage.ru_maxrss.max(0) as u64;
+    if cfg!(target_os = "macos") {
+        max_rss / 1024
+    } else {
+        max_rss
+    }
+}
+
+/// Represents the workload adapter.
+pub struct WorkloadAdapter {
+    logger: Arc<dyn LoggerPort>, // inject the logger port
+}
+
+impl WorkloadAdapter {
+    /// Creates a new instance of `WorkloadAdapter`.
+    ///
+    /// # Arguments
+    /// * `logger` - A reference to an object that implements the `LoggerPort` trait.
+    ///
+    /// # Returns
+    /// An instance of `WorkloadAdapter`.
+    pub fn new(logger: Arc<dyn LoggerPort>) -> Self {
+        WorkloadAdapter { logger }
+    }
+
+    /// Kills the process group of the command and reaps the command.
+    fn kill(pid: libc::pid_t) {
+        // SAFETY: killpg only signals the process group created for the command,
+        // and waitpid only reaps the command itself.
+        unsafe {
+            libc::killpg(pid, libc::SIGKILL);
+            libc::waitpid(pid, std::ptr::null_mut(), 0);
+        }
+    }
+}
+
+// Implement the `WorkloadPort` trait for `WorkloadAdapter`.
+impl WorkloadPort for WorkloadAdapter {
+    fn run_workload(
+        &self,
+        command: &[String],
+        timeout: Option<Duration>,
+        cancel: &CancellationToken,
+    ) -> Result<WorkloadResult, String> {
+        let (program, args) = command
+            .split_first()
+            .ok_or("No command given to run, e.g. `benchmark exec -- make -j8`")?;
+        let command_line = command.join(" ");
+        self.logger
+            .log_debug(&format!("Running the workload `{}`", command_line));
+
+        let started = Instant::now();
+        let child = Command::new(program)
+            .args(args)
+            .process_group(0)
+            .spawn()
+            .map_err(|e| format!("Failed to start `{}`: {}", program, e))?;
+        let pid = child.id() as libc::pid_t;
+
+        let mut status: libc::c_int = 0;
+        // SAFETY: `rusage` is plain data, filled in by `wait4`.
+        let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+        loop {
+            // SAFETY: `status` and `usage` are valid and writable, and the PID is the
+            // command spawned above, which is only reaped here or by `kill`.
+            let reaped = unsafe { libc::wait4(pid, &mut status, libc::WNOHANG, &mut usage) };
+            if reaped == pid {
+                break;
+            }
+            if reaped < 0 {
+                return Err(format!(
+                    "Failed to wait for `{}`: {}",
+                    program,
+                    std::io::Error::last_os_error()
+                ));
+            }
+            if cancel.is_cancelled() {
+                Self::kill(pid);
+                return Err(format!("`{}` was cancelled", command_line));
+            }
+            if timeout.is_some_and(|timeout| started.elapsed() >= timeout) {
+                Self::kill(pid);
+                return Err(format!(
+                    "`{}` did not finish within {:.0} s and was killed",
+                    command_line,
+                    started.elapsed().as_secs_f64()
+                ));
+            }
+            sleep(POLL_INTERVAL);
+        }
+        let wall_time = started.elapsed();
+
+        if libc::WIFSIGNALED(status) {
+            return Err(format!(
+                "`{}` was killed by signal {}",
+                command_line,
+                libc::WTERMSIG(status)
+            ));
+        }
+        let exit_code = libc::WEXITSTATUS(status);
+        if exit_code != 0 {
+            return Err(format!(
+                "`{}` exited with status {}",
+                command_line, exit_code
+            ));
+        }
+        Ok(WorkloadResult {
+            command: command_line,
+            wall_time,
+            user_time: to_duration(usage.ru_utime),
+            system_time: to_duration(usage.ru_stime),
+            max_rss_kb: max_rss_kb(&usage),
+        })
+    }
+}
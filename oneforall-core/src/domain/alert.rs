@@ -0,0 +1,295 @@
+//! Alert Domain Entity
+//!
+//! This module provides the alerts raised when a test fails, a temperature or
+//! ECC threshold is crossed, a reading jumps away from its recent level, or a
+//! burn-in completes, and their destinations: Slack or generic HTTP webhooks
+//! and email. Both are read from the `[alerts]` section of `oneforall.toml`;
+//! the thresholds are acceptance criteria, so they are written and checked
+//! exactly like the limits of a profile step.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::profile::{deserialize_duration, AcceptanceCriteria};
+
+/// How often thresholds are checked when no interval is configured.
+pub const DEFAULT_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The configuration file; sections other than `[alerts]` are left to their own readers.
+#[derive(Debug, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    alerts: AlertConfig,
+}
+
+/// What an alert reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    /// A test or job failed.
+    Failed,
+
+    /// A temperature or ECC threshold was crossed.
+    Threshold,
+
+    /// A temperature or ECC error reading stood out from its recent level.
+    Anomaly,
+
+    /// A burn-in or profile run completed and passed.
+    Completed,
+}
+
+impl AlertKind {
+    /// Returns the kind name used in the configuration and in messages.
+    pub fn label(&self) -> &'static str {
+        match self {
+            AlertKind::Failed => "failed",
+            AlertKind::Threshold => "threshold",
+            AlertKind::Anomaly => "anomaly",
+            AlertKind::Completed => "completed",
+        }
+    }
+}
+
+/// A notification sent to every destination that subscribes to its kind.
+#[derive(Debug, Clone, Serialize)]
+pub struct Alert {
+    /// What the alert reports.
+    pub kind: AlertKind,
+
+    /// The machine that raised the alert.
+    pub hostname: String,
+
+    /// A one-line summary, e.g. "Job 3 (stress:all) failed".
+    pub summary: String,
+
+    /// Further lines, such as the faults found or the threshold crossed.
+    pub details: Vec<String>,
+
+    /// The local time the alert was raised, in RFC 3339 format.
+    pub timestamp: String,
+}
+
+impl Alert {
+    /// Returns the subject line of an email or chat message, e.g.
+    /// "[OneForAll] rack12-node3: Job 3 (stress:all) failed".
+    ///
+    /// Line breaks, which a job name or a fault message may carry, become spaces, so
+    /// the subject stays a single header line.
+    pub fn subject(&self) -> String {
+        format!("[OneForAll] {}: {}", self.hostname, self.summary).replace(['\r', '\n'], " ")
+    }
+
+    /// Renders the alert as plain text suitable for a chat message or email body.
+    pub fn render(&self) -> String {
+        let mut output = format!("{}\n", self.subject());
+        for detail in &self.details {
+            output.push_str(&format!("  {}\n", detail));
+        }
+        output.push_str(&format!(
+            "Raised at {} ({})\n",
+            self.timestamp,
+            self.kind.label()
+        ));
+        output
+    }
+}
+
+/// The payload a webhook receives.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFormat {
+    /// The alert as a JSON object, for generic HTTP receivers.
+    #[default]
+    Json,
+
+    /// A Slack incoming-webhook message with the alert as its text.
+    Slack,
+}
+
+/// A webhook alerts are posted to.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookTarget {
+    /// A name identifying the webhook in logs, e.g. "ops-slack".
+    pub name: String,
+
+    /// The URL alerts are posted to.
+    pub url: String,
+
+    /// The payload to post.
+    #[serde(default)]
+    pub format: WebhookFormat,
+
+    /// The kinds of alert to send; every kind when empty.
+    #[serde(default)]
+    pub events: Vec<AlertKind>,
+}
+
+/// How the connection to an SMTP relay is secured.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTls {
+    /// Plain SMTP upgraded with STARTTLS, which the relay must offer, e.g. on port 587.
+    #[default]
+    Starttls,
+
+    /// TLS from the first byte, e.g. on port 465.
+    Implicit,
+
+    /// Plain SMTP, only for a relay on the machine itself or a trusted lab network.
+    None,
+}
+
+/// An email destination, delivered through an SMTP relay.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EmailTarget {
+    /// A name identifying the destination in logs, e.g. "lab-team".
+    pub name: String,
+
+    /// The SMTP relay as "host:port", e.g. "smtp.example.com:587".
+    pub server: String,
+
+    /// How the connection to the relay is secured; STARTTLS when omitted.
+    #[serde(default)]
+    pub tls: SmtpTls,
+
+    /// The user name to authenticate to the relay with, if it requires authentication.
+    #[serde(default)]
+    pub username: Option<String>,
+
+    /// The password of `username`.
+    #[serde(default)]
+    pub password: Option<String>,
+
+    /// A file holding the password, so it can be kept out of the configuration file.
+    #[serde(default)]
+    pub password_file: Option<PathBuf>,
+
+    /// The sender address.
+    pub from: String,
+
+    /// The recipient addresses.
+    pub to: Vec<String>,
+
+    /// The kinds of alert to send; every kind when empty.
+    #[serde(default)]
+    pub events: Vec<AlertKind>,
+}
+
+/// Returns `true` if a destination subscribed to `events` receives alerts of `kind`.
+pub fn subscribes(events: &[AlertKind], kind: AlertKind) -> bool {
+    events.is_empty() || events.contains(&kind)
+}
+
+/// The alert settings.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlertConfig {
+    /// The temperature and ECC limits that raise a threshold alert when crossed.
+    #[serde(default)]
+    pub thresholds: AcceptanceCriteria,
+
+    /// How often the thresholds are checked, e.g. "30s".
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub check_interval: Option<Duration>,
+
+    /// Whether to raise an anomaly alert when the temperature or the rate of
+    /// new ECC errors stands out from its recent level, even within the thresholds.
+    #[serde(default)]
+    pub anomalies: bool,
+
+    /// The webhooks alerts are posted to.
+    #[serde(default, rename = "webhook")]
+    pub webhooks: Vec<WebhookTarget>,
+
+    /// The email destinations alerts are sent to.
+    #[serde(default, rename = "email")]
+    pub emails: Vec<EmailTarget>,
+}
+
+impl AlertConfig {
+    /// Parses and validates the `[alerts]` section of a configuration file.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The TOML text of the configuration file.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<AlertConfig, String>` - The alert settings, or why they are invalid.
+    pub fn from_toml(text: &str) -> Result<AlertConfig, String> {
+        let file: ConfigFile = toml::from_str(text).map_err(|e| e.to_string())?;
+        let config = file.alerts;
+        if config.thresholds.min_bogo_ops_per_sec.is_some()
+            || config.thresholds.max_remote_penalty.is_some()
+        {
+            return Err(
+                "Alert thresholds may only set `max_temperature_celsius` and `max_ecc_errors`"
+                    .to_string(),
+            );
+        }
+        if config
+            .check_interval
+            .is_some_and(|interval| interval.is_zero())
+        {
+            return Err("The alert `check_interval` must be longer than zero".to_string());
+        }
+        for webhook in &config.webhooks {
+            if !webhook.url.starts_with("http://") && !webhook.url.starts_with("https://") {
+                return Err(format!(
+                    "The webhook '{}' needs an http:// or https:// URL",
+                    webhook.name
+                ));
+            }
+        }
+        for email in &config.emails {
+            if email.to.is_empty() {
+                return Err(format!(
+                    "The email alert '{}' has no recipients",
+                    email.name
+                ));
+            }
+            if !email.server.contains(':') {
+                return Err(format!(
+                    "The email alert '{}' needs its server as host:port",
+                    email.name
+                ));
+            }
+            if email.password.is_some() && email.password_file.is_some() {
+                return Err(format!(
+                    "Set either `password` or `password_file` for the email alert '{}'",
+                    email.name
+                ));
+            }
+            if email.username.is_none()
+                && (email.password.is_some() || email.password_file.is_some())
+            {
+                return Err(format!(
+                    "The email alert '{}' has a password but no `username`",
+                    email.name
+                ));
+            }
+        }
+        Ok(config)
+    }
+
+    /// Returns `true` if any destination is configured.
+    pub fn is_enabled(&self) -> bool {
+        !self.webhooks.is_empty() || !self.emails.is_empty()
+    }
+
+    /// Returns `true` if any threshold is set.
+    pub fn has_thresholds(&self) -> bool {
+        self.thresholds.max_temperature_celsius.is_some()
+            || self.thresholds.max_ecc_errors.is_some()
+    }
+
+    /// Returns how often the thresholds are checked.
+    pub fn check_interval(&self) -> Duration {
+        self.check_interval.unwrap_or(DEFAULT_CHECK_INTERVAL)
+    }
+}
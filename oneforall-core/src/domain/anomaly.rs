@@ -0,0 +1,180 @@
+//! Anomaly Domain Entity
+//!
+//! This module provides a lightweight anomaly detector for the time series
+//! collected while tests run and Overwatch watches a machine. Each series is
+//! followed with an exponentially weighted moving average and variance, and a
+//! reading more than a few standard deviations away from what came before is
+//! flagged. Unlike a fixed threshold, this catches a temperature that jumps
+//! from 60 °C to 75 °C halfway through a steady burn-in, or a burst of ECC
+//! errors on a machine that never had any, without knowing the normal level
+//! of each machine in advance.
+
+use std::time::Duration;
+
+use crate::domain::profile::format_duration;
+
+/// Weight of each new reading in the moving average and variance.
+const EWMA_ALPHA: f64 = 0.1;
+
+/// Distance from the moving average, in standard deviations, beyond which a reading is anomalous.
+const Z_SCORE_THRESHOLD: f64 = 4.0;
+
+/// Number of readings taken to learn a series before any is flagged.
+const WARMUP_SAMPLES: usize = 10;
+
+/// Follows one series and flags readings far from its recent behavior.
+#[derive(Debug, Clone)]
+pub struct AnomalyDetector {
+    /// The smallest standard deviation assumed, in the unit of the series, so
+    /// a series that was flat until now does not flag every small change.
+    min_deviation: f64,
+    mean: f64,
+    variance: f64,
+    samples: usize,
+}
+
+impl AnomalyDetector {
+    /// Creates a detector that has seen no readings yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_deviation` - The smallest standard deviation assumed, e.g. 1.0 for °C.
+    ///
+    /// # Returns
+    ///
+    /// * `AnomalyDetector` - The detector.
+    pub fn new(min_deviation: f64) -> Self {
+        AnomalyDetector {
+            min_deviation,
+            mean: 0.0,
+            variance: 0.0,
+            samples: 0,
+        }
+    }
+
+    /// Adds a reading and checks it against the readings before it.
+    ///
+    /// The reading joins the moving average either way, so a lasting change
+    /// of level is flagged once and then becomes the new normal.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The reading.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<(f64, f64)>` - The expected value and the z-score of the
+    ///   reading if it is anomalous, `None` otherwise or while warming up.
+    pub fn observe(&mut self, value: f64) -> Option<(f64, f64)> {
+        self.samples += 1;
+        if self.samples == 1 {
+            self.mean = value;
+            return None;
+        }
+
+        let expected = self.mean;
+        let deviation = self.variance.sqrt().max(self.min_deviation);
+        let z_score = (value - expected) / deviation;
+
+        let difference = value - self.mean;
+        self.mean += EWMA_ALPHA * difference;
+        self.variance = (1.0 - EWMA_ALPHA) * (self.variance + EWMA_ALPHA * difference * difference);
+
+        (self.samples > WARMUP_SAMPLES && z_score.abs() > Z_SCORE_THRESHOLD)
+            .then_some((expected, z_score))
+    }
+}
+
+/// An unusual reading in a series.
+#[derive(Debug, Clone)]
+pub struct Anomaly {
+    /// The series, e.g. "hottest temperature".
+    pub metric: String,
+
+    /// The unit of the series, e.g. "°C".
+    pub unit: String,
+
+    /// The offset of the reading into the run.
+    pub elapsed: Duration,
+
+    /// The reading.
+    pub value: f64,
+
+    /// The moving average before the reading.
+    pub expected: f64,
+
+    /// The distance from the moving average, in standard deviations; negative below it.
+    pub z_score: f64,
+}
+
+impl Anomaly {
+    /// Renders the anomaly as one line, e.g.
+    /// "hottest temperature 78.0 °C at 45m, expected 61.2 °C (z-score 6.3)".
+    pub fn render(&self) -> String {
+        format!(
+            "{} {:.1} {} at {}, expected {:.1} {} (z-score {:.1})",
+            self.metric,
+            self.value,
+            self.unit,
+            format_duration(Duration::from_secs(self.elapsed.as_secs())),
+            self.expected,
+            self.unit,
+            self.z_score
+        )
+    }
+}
+
+/// Finds the anomalous readings of a series.
+///
+/// # Arguments
+///
+/// * `metric` - The name of the series, e.g. "hottest temperature".
+/// * `unit` - The unit of the series, e.g. "°C".
+/// * `min_deviation` - The smallest standard deviation assumed, in that unit.
+/// * `series` - The readings with their offset into the run, in order.
+///
+/// # Returns
+///
+/// * `Vec<Anomaly>` - The anomalous readings, in order.
+pub fn detect_anomalies(
+    metric: &str,
+    unit: &str,
+    min_deviation: f64,
+    series: &[(Duration, f64)],
+) -> Vec<Anomaly> {
+    let mut detector = AnomalyDetector::new(min_deviation);
+    series
+        .iter()
+        .filter_map(|(elapsed, value)| {
+            let (expected, z_score) = detector.observe(*value)?;
+            Some(Anomaly {
+                metric: metric.to_string(),
+                unit: unit.to_string(),
+                elapsed: *elapsed,
+                value: *value,
+                expected,
+                z_score,
+            })
+        })
+        .collect()
+}
+
+/// Renders the anomalies found over a run.
+///
+/// # Arguments
+///
+/// * `anomalies` - The anomalies of every series.
+///
+/// # Returns
+///
+/// * `String` - One line per anomaly, or an empty string if there were none.
+pub fn format_anomaly_report(anomalies: &[Anomaly]) -> String {
+    if anomalies.is_empty() {
+        return String::new();
+    }
+    let mut output = format!("Anomalies ({})\n", anomalies.len());
+    for anomaly in anomalies {
+        output.push_str(&format!("  {}\n", anomaly.render()));
+    }
+    output
+}
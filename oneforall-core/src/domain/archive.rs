@@ -0,0 +1,370 @@
+//! Run Archive Domain Entity
+//!
+//! This module provides the portable bundle `archive export` writes of a run
+//! stored on one machine and `archive import` reads into the database of
+//! another, so runs from air-gapped lab machines can be carried over to an
+//! analyst's workstation. An archive is a gzip-compressed tar file holding:
+//!
+//! * `manifest.json`, describing the run and the machine it ran on;
+//! * `result.json`, the result as stored, at the schema version it was written at;
+//! * `samples/<collector>.jsonl`, the collector readings taken over the run;
+//! * `inventory.json`, the hardware inventory of the machine when the run started;
+//! * `logs.jsonl`, the log messages of the run the running instance still held;
+//! * `signature.json`, when signed, the ed25519 signature of every other file.
+//!
+//! Acceptance reports often serve as evidence between buyers and vendors, so
+//! an archive can be signed with the key of the operator who exported it. The
+//! signature covers the SHA-256 digest of each file, so an archive changed in
+//! any way after it was signed fails to import, and an import can demand a
+//! signature by one of the keys it trusts.
+//!
+//! Readings imported from another machine are stored under the collector name
+//! followed by `@<hostname>`, and its inventory and logs under the run, so they
+//! never mix with the readings and inventories of the machine importing them.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::domain::database::{RunKey, COLLECTORS};
+use crate::domain::time_sync::SampleTime;
+
+/// The version of the archive layout written by this version of OneForAll.
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+/// The file describing the archived run.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// The file holding the result of the run.
+pub const RESULT_FILE: &str = "result.json";
+
+/// The file holding the hardware inventory of the machine.
+pub const INVENTORY_FILE: &str = "inventory.json";
+
+/// The file holding the log messages of the run, one JSON record per line.
+pub const LOGS_FILE: &str = "logs.jsonl";
+
+/// The file holding the signature of the archive.
+pub const SIGNATURE_FILE: &str = "signature.json";
+
+/// The only signature algorithm archives are signed with.
+pub const SIGNATURE_ALGORITHM: &str = "ed25519";
+
+/// The tag naming the fingerprint of the trusted key an imported result was signed with.
+pub const SIGNED_BY_TAG: &str = "signed_by";
+
+/// The directory holding the readings of each collector, one JSON reading per line.
+pub const SAMPLES_DIR: &str = "samples/";
+
+/// The collectors whose readings are archived with a run: every collector, so
+/// one added to `COLLECTORS` is archived as well as pruned.
+pub const ARCHIVED_COLLECTORS: &[&str] = &COLLECTORS;
+
+/// The tag naming the machine an imported result ran on.
+pub const HOST_TAG: &str = "host";
+
+/// How many recent log messages are read from the running instance; it keeps no more.
+pub const ARCHIVED_LOG_LINES: usize = 1000;
+
+/// How long a run is taken to have lasted when its result does not say, e.g. a benchmark.
+pub const DEFAULT_RUN_WINDOW: Duration = Duration::from_secs(600);
+
+/// The description of an archived run, written as `manifest.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    /// The version of the archive layout.
+    pub format_version: u32,
+
+    /// The key the run is stored under, e.g. "profile:2024-05-02T08:00:00.000000Z:burn-in-24h".
+    pub run: String,
+
+    /// The machine the run ran on.
+    pub hostname: String,
+
+    /// The version of OneForAll that wrote the archive.
+    pub version: String,
+
+    /// When the archive was written, in RFC 3339 format.
+    pub exported_at: String,
+
+    /// The start of the time range the readings and log messages were taken from.
+    pub from: String,
+
+    /// The end of the time range the readings and log messages were taken from.
+    pub to: String,
+
+    /// How many readings of each collector are archived.
+    #[serde(default)]
+    pub samples: BTreeMap<String, usize>,
+
+    /// Whether the hardware inventory is archived.
+    #[serde(default)]
+    pub inventory: bool,
+
+    /// How many log messages are archived.
+    #[serde(default)]
+    pub logs: usize,
+
+    /// The fingerprint of the key the archive is signed with, if it is signed.
+    #[serde(default)]
+    pub signed_by: Option<String>,
+}
+
+impl ArchiveManifest {
+    /// Renders the manifest as one line, e.g. for the output of `archive import`.
+    pub fn render_line(&self) -> String {
+        let readings: usize = self.samples.values().sum();
+        format!(
+            "{} from {}: {} reading(s) of {} collector(s), {}, {} log message(s)",
+            self.run,
+            self.hostname,
+            readings,
+            self.samples.values().filter(|count| **count > 0).count(),
+            if self.inventory {
+                "hardware inventory"
+            } else {
+                "no hardware inventory"
+            },
+            self.logs
+        )
+    }
+}
+
+/// A collector reading as archived, one per line of `samples/<collector>.jsonl`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedSample {
+    /// The time the reading is ordered at, in RFC 3339 format.
+    pub recorded_at: String,
+
+    /// The wall-clock and monotonic time the reading was taken, if they were kept.
+    #[serde(default)]
+    pub time: Option<SampleTime>,
+
+    /// The reading.
+    pub sample: serde_json::Value,
+}
+
+/// Returns the time range a run's readings and log messages are archived from:
+/// from its start to when its result says it finished, or how long it says it
+/// ran, or `DEFAULT_RUN_WINDOW` when it says neither.
+///
+/// # Arguments
+///
+/// * `key` - The key of the run.
+/// * `result` - The stored result.
+///
+/// # Returns
+///
+/// * `(DateTime<Utc>, DateTime<Utc>)` - The start and end of the range.
+pub fn run_window(key: &RunKey, result: &serde_json::Value) -> (DateTime<Utc>, DateTime<Utc>) {
+    let start = key.started_at;
+    let finished_at = result
+        .get("finished_at")
+        .and_then(|value| value.as_str())
+        .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+        .map(|time| time.with_timezone(&Utc));
+    let elapsed = result
+        .get("elapsed_secs")
+        .and_then(|value| value.as_f64())
+        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+        .map(Duration::from_secs_f64);
+    let end = finished_at.filter(|end| *end > start).unwrap_or_else(|| {
+        let elapsed = elapsed.unwrap_or(DEFAULT_RUN_WINDOW);
+        start + chrono::Duration::from_std(elapsed).unwrap_or(chrono::Duration::zero())
+    });
+    (start, end)
+}
+
+/// Keeps the characters of a name that are safe in a file name, replacing the others with '-'.
+fn file_safe(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Returns the file name an archive is written to when none is given, e.g.
+/// "rack12-node3-profile-20240502T080000-burn-in-24h.tar.gz".
+///
+/// # Arguments
+///
+/// * `hostname` - The machine the run ran on.
+/// * `key` - The key of the run.
+///
+/// # Returns
+///
+/// * `String` - The file name.
+pub fn archive_file_name(hostname: &str, key: &RunKey) -> String {
+    let mut name = format!(
+        "{}-{}-{}",
+        hostname,
+        key.kind,
+        key.started_at.format("%Y%m%dT%H%M%S")
+    );
+    if let Some(run) = &key.name {
+        name.push('-');
+        name.push_str(run);
+    }
+    format!("{}.tar.gz", file_safe(&name))
+}
+
+/// Returns the collector the readings of another machine are imported under, e.g. "power@rack12-node3".
+pub fn imported_collector(collector: &str, hostname: &str) -> String {
+    format!("{}@{}", collector, hostname).replace(':', "-")
+}
+
+/// The signature of an archive, written as `signature.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveSignature {
+    /// The signature algorithm, always `SIGNATURE_ALGORITHM`.
+    pub algorithm: String,
+
+    /// The public key of the operator who signed the archive, in base64.
+    pub public_key: String,
+
+    /// The signature of `signed_content`, in base64.
+    pub signature: String,
+}
+
+/// What checking the signature of an archive found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureCheck {
+    /// The archive is not signed.
+    Unsigned,
+
+    /// The archive is intact and signed with a key that is not among the trusted ones.
+    Untrusted {
+        /// The fingerprint of the key.
+        fingerprint: String,
+    },
+
+    /// The archive is intact and signed with a trusted key.
+    Trusted {
+        /// The fingerprint of the key.
+        fingerprint: String,
+    },
+}
+
+impl SignatureCheck {
+    /// Renders the outcome as one line, e.g. for the output of `archive import`.
+    pub fn render_line(&self) -> String {
+        match self {
+            SignatureCheck::Unsigned => "not signed".to_string(),
+            SignatureCheck::Untrusted { fingerprint } => {
+                format!("signed with the untrusted key {}", fingerprint)
+            }
+            SignatureCheck::Trusted { fingerprint } => {
+                format!("signed with the trusted key {}", fingerprint)
+            }
+        }
+    }
+}
+
+/// Returns what the signature of an archive covers: the name and SHA-256 digest
+/// of every file but the signature, one per line in name order.
+///
+/// # Arguments
+///
+/// * `files` - The files of the archive by name.
+///
+/// # Returns
+///
+/// * `Vec<u8>` - The signed content.
+pub fn signed_content(files: &BTreeMap<String, Vec<u8>>) -> Vec<u8> {
+    let mut content = String::new();
+    for (name, body) in files {
+        if name == SIGNATURE_FILE {
+            continue;
+        }
+        let _ = writeln!(content, "{} {}", hex_digest(body), name);
+    }
+    content.into_bytes()
+}
+
+/// Returns the SHA-256 digest of some bytes in lowercase hex.
+fn hex_digest(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Returns the fingerprint of a public key, the first 16 bytes of its SHA-256
+/// digest in hex pairs, e.g. "3f:a1:...".
+pub fn key_fingerprint(public_key: &[u8]) -> String {
+    Sha256::digest(public_key)
+        .iter()
+        .take(16)
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// The DER prefix of an ed25519 public key in a SubjectPublicKeyInfo structure,
+/// followed by the 32 bytes of the key.
+const ED25519_SPKI_PREFIX: [u8; 12] = [
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+
+/// Extracts the 32 bytes of an ed25519 public key from the DER of a PEM
+/// "PUBLIC KEY", as written by `openssl pkey -pubout`.
+///
+/// # Arguments
+///
+/// * `der` - The SubjectPublicKeyInfo structure.
+///
+/// # Returns
+///
+/// * `Result<Vec<u8>, String>` - The key, or why it is not an ed25519 public key.
+pub fn ed25519_public_key(der: &[u8]) -> Result<Vec<u8>, String> {
+    der.strip_prefix(&ED25519_SPKI_PREFIX[..])
+        .filter(|key| key.len() == 32)
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| "not an ed25519 public key".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files() -> BTreeMap<String, Vec<u8>> {
+        BTreeMap::from([
+            (RESULT_FILE.to_string(), b"{\"bogo_ops\":42}".to_vec()),
+            (MANIFEST_FILE.to_string(), b"{}".to_vec()),
+        ])
+    }
+
+    #[test]
+    fn signed_content_lists_each_file_digest_in_name_order() {
+        let content = String::from_utf8(signed_content(&files())).unwrap();
+        let names: Vec<_> = content
+            .lines()
+            .map(|line| line.split_once(' ').unwrap().1)
+            .collect();
+        assert_eq!(names, [MANIFEST_FILE, RESULT_FILE]);
+        assert!(content.starts_with(&hex_digest(b"{}")));
+    }
+
+    #[test]
+    fn signed_content_leaves_out_the_signature() {
+        let mut signed = files();
+        signed.insert(SIGNATURE_FILE.to_string(), b"{\"signature\":\"\"}".to_vec());
+        assert_eq!(signed_content(&signed), signed_content(&files()));
+    }
+
+    #[test]
+    fn signed_content_changes_with_any_file() {
+        let mut tampered = files();
+        tampered.insert(RESULT_FILE.to_string(), b"{\"bogo_ops\":43}".to_vec());
+        assert_ne!(signed_content(&tampered), signed_content(&files()));
+    }
+}
@@ -0,0 +1,287 @@
+//! Battery Domain Entity
+//!
+//! This module provides domain entities for the batteries and AC adapter of a
+//! laptop, the Battery section of `discover`, and the report attached to the
+//! results of a stress test or benchmark that ran on battery for part of its
+//! duration. Laptop firmware often caps the CPU when unplugged, so a machine
+//! that passes on the bench can fall short in the field; the report compares
+//! the clocks reached on battery with those reached on AC to catch it.
+
+use std::time::Duration;
+
+use crate::domain::discovery::DiscoverySection;
+use crate::domain::statistics::mean;
+
+/// Health, in percent of the design capacity, below which a battery is flagged as worn.
+pub const WORN_HEALTH_PERCENT: f64 = 80.0;
+
+/// Share of the clock reached on AC below which the clock on battery counts as throttled.
+const BATTERY_THROTTLE_RATIO: f64 = 0.85;
+
+/// A battery as reported by the kernel power supply class.
+#[derive(Debug, Clone, Default)]
+pub struct Battery {
+    /// The power supply name, e.g. "BAT0".
+    pub name: String,
+
+    /// The manufacturer of the battery, if reported.
+    pub manufacturer: String,
+
+    /// The model of the battery, if reported.
+    pub model: String,
+
+    /// The cell chemistry, e.g. "Li-ion".
+    pub technology: String,
+
+    /// The charging status, e.g. "Charging", "Discharging" or "Full".
+    pub status: String,
+
+    /// The charge left, in percent of the current full capacity.
+    pub capacity_percent: Option<u8>,
+
+    /// The capacity of the battery when fully charged today, in watt-hours.
+    pub full_wh: Option<f64>,
+
+    /// The capacity the battery was designed for, in watt-hours.
+    pub design_wh: Option<f64>,
+
+    /// The charge and discharge cycles the battery went through, if it counts them.
+    pub cycle_count: Option<u32>,
+
+    /// The rate the battery charges or discharges at, in watts.
+    pub rate_watts: Option<f64>,
+}
+
+impl Battery {
+    /// Returns the capacity of the battery when full, in percent of its design capacity.
+    pub fn health_percent(&self) -> Option<f64> {
+        match (self.full_wh, self.design_wh) {
+            (Some(full), Some(design)) if design > 0.0 => Some(full / design * 100.0),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the battery is powering the machine.
+    pub fn is_discharging(&self) -> bool {
+        self.status == "Discharging"
+    }
+}
+
+/// One reading of the AC adapter and every battery.
+#[derive(Debug, Clone, Default)]
+pub struct PowerSupplyState {
+    /// Whether an AC adapter is plugged in, if the machine reports one.
+    pub on_mains: Option<bool>,
+
+    /// The batteries of the machine, ordered by name.
+    pub batteries: Vec<Battery>,
+}
+
+impl PowerSupplyState {
+    /// Returns `true` if the machine runs on battery: the AC adapter is unplugged,
+    /// or, without one reported, a battery is discharging.
+    pub fn on_battery(&self) -> bool {
+        match self.on_mains {
+            Some(online) => !online && !self.batteries.is_empty(),
+            None => self.batteries.iter().any(Battery::is_discharging),
+        }
+    }
+
+    /// Returns the charge left in every battery together, in percent.
+    pub fn capacity_percent(&self) -> Option<f64> {
+        let capacities: Vec<f64> = self
+            .batteries
+            .iter()
+            .filter_map(|battery| battery.capacity_percent)
+            .map(f64::from)
+            .collect();
+        (!capacities.is_empty()).then(|| capacities.iter().sum::<f64>() / capacities.len() as f64)
+    }
+
+    /// Returns the power the batteries supply together while discharging, in watts.
+    pub fn discharge_watts(&self) -> Option<f64> {
+        self.batteries
+            .iter()
+            .filter(|battery| battery.is_discharging())
+            .filter_map(|battery| battery.rate_watts)
+            .reduce(|total, watts| total + watts)
+    }
+}
+
+/// Builds the Battery section of the discovery report.
+///
+/// # Arguments
+///
+/// * `state` - The AC adapter and batteries of the machine.
+///
+/// # Returns
+///
+/// * `DiscoverySection` - The AC adapter, then the capacity, health, cycle count
+///   and charge rate of each battery.
+pub fn battery_section(state: &PowerSupplyState) -> DiscoverySection {
+    let mut section = DiscoverySection::new("Battery");
+    section.push(
+        "AC adapter",
+        match state.on_mains {
+            Some(true) => "plugged in",
+            Some(false) => "unplugged",
+            None => "not reported",
+        },
+    );
+
+    for battery in &state.batteries {
+        let description: Vec<&str> = [
+            battery.manufacturer.as_str(),
+            battery.model.as_str(),
+            battery.technology.as_str(),
+        ]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect();
+        let mut value = format!("{} | {}", description.join(" "), battery.status);
+        if let Some(capacity) = battery.capacity_percent {
+            value.push_str(&format!(", {}%", capacity));
+        }
+        section.push(&battery.name, value);
+
+        if let (Some(full), Some(design)) = (battery.full_wh, battery.design_wh) {
+            let mut value = format!("{:.1} Wh of {:.1} Wh designed", full, design);
+            if let Some(health) = battery.health_percent() {
+                value.push_str(&format!(" ({:.0}%)", health));
+                if health < WORN_HEALTH_PERCENT {
+                    value.push_str(" | WORN");
+                }
+            }
+            section.push(&format!("{} capacity", battery.name), value);
+        }
+        if let Some(cycles) = battery.cycle_count {
+            section.push(&format!("{} cycle count", battery.name), cycles);
+        }
+        if let Some(watts) = battery.rate_watts {
+            let rate = if battery.is_discharging() {
+                "discharge rate"
+            } else {
+                "charge rate"
+            };
+            section.push(
+                &format!("{} {}", battery.name, rate),
+                format!("{:.1} W", watts),
+            );
+        }
+    }
+
+    section
+}
+
+/// Returns the reading of the power supplies in effect at an offset into the
+/// run: the latest taken at or before it, or the first one.
+fn state_at(samples: &[(Duration, PowerSupplyState)], at: Duration) -> Option<&PowerSupplyState> {
+    samples
+        .iter()
+        .take_while(|(offset, _)| *offset <= at)
+        .last()
+        .or_else(|| samples.first())
+        .map(|(_, state)| state)
+}
+
+/// Formats the battery report of a run: how long the machine ran on battery,
+/// the charge it used and whether the CPU was throttled while on battery.
+///
+/// # Arguments
+///
+/// * `samples` - Readings of the power supplies with their offset into the run.
+/// * `clocks` - The average clock of every core in MHz, with its offset into the run.
+/// * `base_mhz` - The base frequency of the CPU in MHz, if the driver reports it.
+///
+/// # Returns
+///
+/// * `String` - The rendered report, empty if the machine has no battery.
+pub fn format_battery_report(
+    samples: &[(Duration, PowerSupplyState)],
+    clocks: &[(Duration, f64)],
+    base_mhz: Option<f64>,
+) -> String {
+    if samples.iter().all(|(_, state)| state.batteries.is_empty()) {
+        return String::new();
+    }
+
+    let on_battery = samples
+        .iter()
+        .filter(|(_, state)| state.on_battery())
+        .count();
+    let mut output = String::from("Battery\n");
+    output.push_str(&format!(
+        "  On battery             {} of {} samples ({:.0}%)\n",
+        on_battery,
+        samples.len(),
+        on_battery as f64 * 100.0 / samples.len() as f64
+    ));
+    let capacities: Vec<f64> = samples
+        .iter()
+        .filter_map(|(_, state)| state.capacity_percent())
+        .collect();
+    if let (Some(first), Some(last)) = (capacities.first(), capacities.last()) {
+        output.push_str(&format!(
+            "  Charge                 {:.0}% -> {:.0}%\n",
+            first, last
+        ));
+    }
+    let discharge: Vec<f64> = samples
+        .iter()
+        .filter(|(_, state)| state.on_battery())
+        .filter_map(|(_, state)| state.discharge_watts())
+        .collect();
+    if let Some(average) = mean(&discharge) {
+        output.push_str(&format!(
+            "  Discharge rate         avg {:.1} W, peak {:.1} W\n",
+            average,
+            discharge.iter().cloned().fold(0.0, f64::max)
+        ));
+    }
+    if on_battery == 0 {
+        output.push_str("  Clocks on battery      not measured: the machine stayed on AC\n");
+        return output;
+    }
+
+    // Each clock reading is attributed to the power source in effect when it was taken.
+    let (mut battery_clocks, mut mains_clocks) = (Vec::new(), Vec::new());
+    for (at, mhz) in clocks {
+        match state_at(samples, *at).map(PowerSupplyState::on_battery) {
+            Some(true) => battery_clocks.push(*mhz),
+            Some(false) => mains_clocks.push(*mhz),
+            None => {}
+        }
+    }
+    let Some(battery_mhz) = mean(&battery_clocks) else {
+        output.push_str("  Clocks on battery      not reported on this platform\n");
+        return output;
+    };
+    match mean(&mains_clocks) {
+        Some(mains_mhz) => {
+            let ratio = battery_mhz / mains_mhz;
+            output.push_str(&format!("  Avg clock on AC        {:.0} MHz\n", mains_mhz));
+            output.push_str(&format!(
+                "  Avg clock on battery   {:.0} MHz ({:.0}% of AC){}\n",
+                battery_mhz,
+                ratio * 100.0,
+                if ratio < BATTERY_THROTTLE_RATIO {
+                    " THROTTLED ON BATTERY"
+                } else {
+                    ""
+                }
+            ));
+        }
+        None => {
+            output.push_str(&format!(
+                "  Avg clock on battery   {:.0} MHz{}\n",
+                battery_mhz,
+                match base_mhz {
+                    Some(base) if battery_mhz < base => " BELOW BASE CLOCK ON BATTERY",
+                    _ => "",
+                }
+            ));
+        }
+    }
+
+    output
+}
@@ -0,0 +1,293 @@
+//! Block Device Domain Entity
+//!
+//! This module provides the raw block device benchmark, which measures a drive
+//! beneath any filesystem: sequential and random reads and, unless it runs
+//! read-only, sequential and random writes straight to the device. Writing to
+//! a raw device destroys whatever it holds, so a device that is mounted, holds
+//! partitions or is claimed by LVM, RAID or swap is refused unless `--force` is
+//! given together with the confirmation token of that device.
+//!
+//! `--all-disks` benchmarks every disk the device inventory lists instead of a
+//! single one, skipping the disk the machine boots from, so a whole storage
+//! shelf can be measured without naming each drive.
+
+use crate::domain::statistics::Measurement;
+
+/// The mount points that mark a disk as the one the machine boots from.
+pub const BOOT_MOUNT_POINTS: [&str; 4] = ["/", "/boot", "/boot/efi", "/usr"];
+
+/// How the benchmark may access a device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceAccess {
+    /// Only read; the device is never written to, whatever lives on it.
+    ReadOnly,
+
+    /// Read and write, with the device claimed exclusively, so the kernel refuses
+    /// the benchmark if anything mounts or stacks on it in the meantime.
+    Exclusive,
+
+    /// Read and write a device in use, after `--force` and its confirmation token.
+    Forced,
+}
+
+/// What a device is used for, read before writing to it.
+#[derive(Debug, Clone, Default)]
+pub struct DeviceUsage {
+    /// The device, e.g. "/dev/nvme1n1".
+    pub device: String,
+
+    /// The partitions of the device, e.g. "nvme1n1p1".
+    pub partitions: Vec<String>,
+
+    /// The mounts of the device or its partitions, e.g. "/dev/nvme1n1p1 on /data".
+    pub mounts: Vec<String>,
+
+    /// The devices stacked on it, such as LVM volumes or RAID arrays, e.g. "dm-0".
+    pub holders: Vec<String>,
+
+    /// The swap areas on the device or its partitions.
+    pub swap: Vec<String>,
+}
+
+impl DeviceUsage {
+    /// Returns `true` if nothing lives on the device, so writing to it loses no data.
+    pub fn is_unused(&self) -> bool {
+        self.partitions.is_empty()
+            && self.mounts.is_empty()
+            && self.holders.is_empty()
+            && self.swap.is_empty()
+    }
+
+    /// Returns `true` if the machine boots from the device, i.e. it or a device
+    /// stacked on it is mounted on one of `BOOT_MOUNT_POINTS`.
+    pub fn is_boot_disk(&self) -> bool {
+        self.mounts.iter().any(|mount| {
+            mount
+                .rsplit_once(" on ")
+                .is_some_and(|(_, target)| BOOT_MOUNT_POINTS.contains(&target))
+        })
+    }
+
+    /// Describes everything that lives on the device, one reason per line.
+    pub fn reasons(&self) -> Vec<String> {
+        let mut reasons = Vec::new();
+        if !self.partitions.is_empty() {
+            reasons.push(format!("holds partitions {}", self.partitions.join(", ")));
+        }
+        reasons.extend(
+            self.mounts
+                .iter()
+                .map(|mount| format!("is mounted: {}", mount)),
+        );
+        if !self.holders.is_empty() {
+            reasons.push(format!(
+                "is used by {} (LVM, RAID or device-mapper)",
+                self.holders.join(", ")
+            ));
+        }
+        reasons.extend(self.swap.iter().map(|swap| format!("is swap: {}", swap)));
+        reasons
+    }
+}
+
+/// Returns the token that must be passed with `--confirm` to write to a device
+/// in use, e.g. "erase-nvme1n1", so it has to be typed for that very device.
+pub fn confirmation_token(device: &str) -> String {
+    format!("erase-{}", device.trim_start_matches("/dev/"))
+}
+
+/// Decides how the benchmark may access a device.
+///
+/// # Arguments
+///
+/// * `usage` - What the device is used for.
+/// * `read_only` - Whether `--read-only` was given.
+/// * `force` - Whether `--force` was given.
+/// * `confirm` - The token given with `--confirm`, if any.
+///
+/// # Returns
+///
+/// * `Result<DeviceAccess, String>` - The access allowed, or why writing is refused.
+pub fn device_access(
+    usage: &DeviceUsage,
+    read_only: bool,
+    force: bool,
+    confirm: Option<&str>,
+) -> Result<DeviceAccess, String> {
+    if read_only {
+        return Ok(DeviceAccess::ReadOnly);
+    }
+    if usage.is_unused() {
+        return Ok(DeviceAccess::Exclusive);
+    }
+    let token = confirmation_token(&usage.device);
+    if force && confirm == Some(token.as_str()) {
+        return Ok(DeviceAccess::Forced);
+    }
+    Err(format!(
+        "Refusing to write to {}, which {}. Writing would destroy its data; use --read-only, \
+         or pass --force --confirm {} to erase it anyway",
+        usage.device,
+        usage.reasons().join(" and "),
+        token
+    ))
+}
+
+/// The rate of one kind of raw device access.
+#[derive(Debug, Clone)]
+pub struct DeviceRate {
+    /// The access measured, e.g. "sequential read (1 MiB)".
+    pub operation: String,
+
+    /// The accesses completed.
+    pub count: u64,
+
+    /// The size of each access, in bytes.
+    pub block_bytes: usize,
+
+    /// The time the accesses took, in seconds.
+    pub elapsed_secs: f64,
+}
+
+impl DeviceRate {
+    /// Returns the accesses completed per second.
+    pub fn iops(&self) -> f64 {
+        if self.elapsed_secs > 0.0 {
+            self.count as f64 / self.elapsed_secs
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns the throughput in megabytes per second.
+    pub fn mb_per_second(&self) -> f64 {
+        self.iops() * self.block_bytes as f64 / 1e6
+    }
+}
+
+/// The results of the raw block device benchmark.
+#[derive(Debug, Clone)]
+pub struct DeviceBenchmarkResult {
+    /// The device measured, e.g. "/dev/nvme1n1".
+    pub device: String,
+
+    /// Whether the benchmark only read from the device.
+    pub read_only: bool,
+
+    /// The rates measured, reads first.
+    pub rates: Vec<DeviceRate>,
+}
+
+/// Lists the metrics of a raw device benchmark run, for statistics over repeated runs.
+pub fn device_measurements(result: &DeviceBenchmarkResult) -> Vec<Measurement> {
+    result
+        .rates
+        .iter()
+        .map(|rate| {
+            // Named without the block size, so the metric stays the same if it changes.
+            let operation = rate.operation.split(" (").next().unwrap_or_default();
+            if operation.starts_with("sequential") {
+                Measurement::new(
+                    format!("{} bandwidth", operation),
+                    "MB/s",
+                    rate.mb_per_second(),
+                )
+            } else {
+                Measurement::new(format!("{} rate", operation), "IOPS", rate.iops())
+            }
+        })
+        .collect()
+}
+
+/// Formats the results of the raw device benchmark as a table.
+///
+/// # Arguments
+///
+/// * `result` - The results of the benchmark.
+///
+/// # Returns
+///
+/// * `String` - One row per access pattern.
+pub fn format_device_results(result: &DeviceBenchmarkResult) -> String {
+    let mut output = format!(
+        "Block device benchmark of {}{}\n",
+        result.device,
+        if result.read_only { " (read-only)" } else { "" }
+    );
+    output.push_str(&format!(
+        "{:<26}  {:>10}  {:>9}  {:>9}\n",
+        "Access", "Count", "IOPS", "MB/s"
+    ));
+    for rate in &result.rates {
+        output.push_str(&format!(
+            "{:<26}  {:>10}  {:>9.0}  {:>9.1}\n",
+            rate.operation,
+            rate.count,
+            rate.iops(),
+            rate.mb_per_second()
+        ));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mounted() -> DeviceUsage {
+        DeviceUsage {
+            device: "/dev/nvme1n1".to_string(),
+            partitions: vec!["nvme1n1p1".to_string()],
+            mounts: vec!["/dev/nvme1n1p1 on /data".to_string()],
+            ..DeviceUsage::default()
+        }
+    }
+
+    #[test]
+    fn read_only_access_is_always_allowed() {
+        assert_eq!(
+            device_access(&mounted(), true, false, None),
+            Ok(DeviceAccess::ReadOnly)
+        );
+    }
+
+    #[test]
+    fn an_unused_device_is_written_exclusively() {
+        let unused = DeviceUsage {
+            device: "/dev/nvme2n1".to_string(),
+            ..DeviceUsage::default()
+        };
+        assert_eq!(
+            device_access(&unused, false, false, None),
+            Ok(DeviceAccess::Exclusive)
+        );
+    }
+
+    #[test]
+    fn a_device_in_use_is_refused_with_its_reasons_and_token() {
+        let error = device_access(&mounted(), false, false, None).unwrap_err();
+        assert!(error.contains("holds partitions nvme1n1p1"), "{}", error);
+        assert!(
+            error.contains("is mounted: /dev/nvme1n1p1 on /data"),
+            "{}",
+            error
+        );
+        assert!(
+            error.contains("--force --confirm erase-nvme1n1"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn a_device_in_use_needs_both_force_and_its_token() {
+        let usage = mounted();
+        assert!(device_access(&usage, false, true, None).is_err());
+        assert!(device_access(&usage, false, false, Some("erase-nvme1n1")).is_err());
+        assert!(device_access(&usage, false, true, Some("erase-nvme0n1")).is_err());
+        assert_eq!(
+            device_access(&usage, false, true, Some("erase-nvme1n1")),
+            Ok(DeviceAccess::Forced)
+        );
+    }
+}
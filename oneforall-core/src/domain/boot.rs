@@ -0,0 +1,350 @@
+//! Boot Domain Entity
+//!
+//! This module provides how long the last boot of the machine took, broken
+//! down the way `systemd-analyze time` reports it: the firmware (POST), the
+//! boot loader, the kernel, the initrd and userspace. systemd only learns the
+//! firmware time from an EFI boot loader that reports it, such as
+//! systemd-boot, so without one the POST time is taken from the BMC instead:
+//! the time between the "System Boot Initiated" event in its event log and
+//! the start of the kernel. That event also tells a cold boot, from power up,
+//! from a warm one, from a reset, which matters as a cold boot trains memory
+//! and probes devices a warm one may skip. Fleets accept machines on their
+//! POST time, so a POST longer than the limit fails the benchmark.
+
+use std::fmt;
+use std::time::Duration;
+
+use chrono::NaiveDateTime;
+
+use crate::domain::sel::SelEntry;
+use crate::domain::statistics::Measurement;
+
+/// The longest gap between the boot event of the BMC and the start of the
+/// kernel taken as a POST; an older event belongs to an earlier boot.
+pub const MAX_SEL_POST: Duration = Duration::from_secs(3600);
+
+/// The number of slowest units listed in the report.
+pub const SLOWEST_UNITS: usize = 5;
+
+/// How the machine last started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootKind {
+    /// Booted from power off, running the full POST.
+    Cold,
+
+    /// Booted from a reset, which may skip parts of the POST.
+    Warm,
+}
+
+impl fmt::Display for BootKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            BootKind::Cold => "cold",
+            BootKind::Warm => "warm",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A "System Boot Initiated" event of the BMC event log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelBootEvent {
+    /// When the BMC recorded the event, in the local time `ipmitool` prints.
+    pub time: NaiveDateTime,
+
+    /// Whether the boot started from power up or from a reset.
+    pub kind: BootKind,
+}
+
+/// The stages of the last boot.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BootTiming {
+    /// The firmware time reported by the EFI boot loader, if it reported one.
+    pub firmware: Option<Duration>,
+
+    /// The boot loader time, if the boot loader reported it.
+    pub loader: Option<Duration>,
+
+    /// The time from the start of the kernel until the initrd or userspace started.
+    pub kernel: Option<Duration>,
+
+    /// The time spent in the initrd, if there was one.
+    pub initrd: Option<Duration>,
+
+    /// The time until the default target was reached.
+    pub userspace: Option<Duration>,
+
+    /// The POST time taken from the event log of the BMC, if it has one: the time
+    /// from its boot event until the kernel started, which includes a boot loader
+    /// that does not report its own time.
+    pub sel_post: Option<Duration>,
+
+    /// How the machine last started.
+    pub kind: Option<BootKind>,
+
+    /// The units that took longest to start, slowest first.
+    pub slowest_units: Vec<(String, Duration)>,
+}
+
+impl BootTiming {
+    /// Returns the POST time, preferring what the boot loader reported over the BMC.
+    pub fn post(&self) -> Option<Duration> {
+        self.firmware.or(self.sel_post)
+    }
+
+    /// Returns the time from power up or reset until the default target was
+    /// reached, counting the stages that were measured.
+    pub fn total(&self) -> Duration {
+        [
+            self.post(),
+            self.loader,
+            self.kernel,
+            self.initrd,
+            self.userspace,
+        ]
+        .iter()
+        .flatten()
+        .sum()
+    }
+}
+
+/// Parses a time span as systemd prints it, e.g. "1min 2.345s", "345ms" or "12us".
+///
+/// # Arguments
+///
+/// * `text` - The time span, in one or more space-separated parts.
+///
+/// # Returns
+///
+/// * `Option<Duration>` - The time span, or `None` if a part has no known unit.
+pub fn parse_systemd_timespan(text: &str) -> Option<Duration> {
+    let mut total = 0.0;
+    for part in text.split_whitespace() {
+        let split = part
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(part.len());
+        let (number, unit) = part.split_at(split);
+        let number: f64 = number.parse().ok()?;
+        let seconds = match unit {
+            "d" => 86400.0,
+            "h" => 3600.0,
+            "min" => 60.0,
+            "s" => 1.0,
+            "ms" => 1e-3,
+            "us" | "µs" => 1e-6,
+            _ => return None,
+        };
+        total += number * seconds;
+    }
+    Some(Duration::from_secs_f64(total))
+}
+
+/// Parses the output of `systemd-analyze time`, e.g. "Startup finished in 12.3s
+/// (firmware) + 3.1s (loader) + 1.5s (kernel) + 2.1s (initrd) + 8.3s (userspace) = 27.3s".
+///
+/// # Arguments
+///
+/// * `text` - The output of `systemd-analyze time`.
+///
+/// # Returns
+///
+/// * `Result<BootTiming, String>` - The stages systemd measured, or an error if the
+///   output holds no startup time.
+pub fn parse_systemd_analyze(text: &str) -> Result<BootTiming, String> {
+    let line = text
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Startup finished in "))
+        .ok_or("systemd-analyze reported no startup time")?;
+    let stages = line.split('=').next().unwrap_or(line);
+
+    let mut timing = BootTiming::default();
+    for stage in stages.split(" + ") {
+        let Some((span, name)) = stage.trim().split_once(" (") else {
+            continue;
+        };
+        let Some(span) = parse_systemd_timespan(span) else {
+            continue;
+        };
+        let field = match name.trim_end_matches(')') {
+            "firmware" => &mut timing.firmware,
+            "loader" => &mut timing.loader,
+            "kernel" => &mut timing.kernel,
+            "initrd" => &mut timing.initrd,
+            "userspace" => &mut timing.userspace,
+            _ => continue,
+        };
+        *field = Some(span);
+    }
+    if timing.kernel.is_none() && timing.userspace.is_none() {
+        return Err(format!(
+            "Unexpected startup time from systemd-analyze: {}",
+            line
+        ));
+    }
+    Ok(timing)
+}
+
+/// Parses the output of `systemd-analyze blame`, e.g. "  5.123s NetworkManager-wait-online.service".
+///
+/// # Arguments
+///
+/// * `text` - The output of `systemd-analyze blame`, slowest unit first.
+/// * `count` - How many units to keep.
+///
+/// # Returns
+///
+/// * `Vec<(String, Duration)>` - The slowest units with how long each took to start.
+pub fn parse_blame(text: &str, count: usize) -> Vec<(String, Duration)> {
+    text.lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            // The unit name is the last field; the time span before it may have several parts.
+            let (span, unit) = line.rsplit_once(' ')?;
+            Some((unit.to_string(), parse_systemd_timespan(span.trim())?))
+        })
+        .take(count)
+        .collect()
+}
+
+/// Picks the "System Boot Initiated" events out of the BMC event log, e.g. the entry
+/// "1c | 04/18/2026 | 15:17:27 | System Boot Initiated #0xe0 | Initiated by power up | Asserted".
+///
+/// # Arguments
+///
+/// * `entries` - The entries of the BMC event log.
+///
+/// # Returns
+///
+/// * `Vec<SelBootEvent>` - The boot events, in the order the BMC logged them.
+pub fn sel_boot_events(entries: &[SelEntry]) -> Vec<SelBootEvent> {
+    entries
+        .iter()
+        .filter(|entry| entry.sensor.starts_with("System Boot Initiated"))
+        .filter_map(|entry| {
+            let event = entry.event.to_lowercase();
+            let kind = if event.contains("power up") {
+                BootKind::Cold
+            } else if event.contains("reset") || event.contains("restart") {
+                BootKind::Warm
+            } else {
+                return None;
+            };
+            let stamp = format!("{} {}", entry.date, entry.time);
+            let time = NaiveDateTime::parse_from_str(&stamp, "%m/%d/%Y %H:%M:%S")
+                .or_else(|_| NaiveDateTime::parse_from_str(&stamp, "%m/%d/%Y %I:%M:%S %p"))
+                .ok()?;
+            Some(SelBootEvent { time, kind })
+        })
+        .collect()
+}
+
+/// Finds the boot event of the BMC that started the running kernel.
+///
+/// # Arguments
+///
+/// * `events` - The boot events of the BMC event log.
+/// * `kernel_start` - When the kernel started, in local time.
+///
+/// # Returns
+///
+/// * `Option<(Duration, BootKind)>` - The time from the event until the kernel started
+///   and the kind of boot, or `None` if no event precedes the kernel closely enough.
+pub fn sel_post_time(
+    events: &[SelBootEvent],
+    kernel_start: NaiveDateTime,
+) -> Option<(Duration, BootKind)> {
+    let event = events
+        .iter()
+        .filter(|event| event.time <= kernel_start)
+        .max_by_key(|event| event.time)?;
+    let gap = (kernel_start - event.time).to_std().ok()?;
+    (gap <= MAX_SEL_POST).then_some((gap, event.kind))
+}
+
+/// Converts the stages of a boot to benchmark measurements.
+///
+/// # Arguments
+///
+/// * `timing` - The stages of the boot.
+///
+/// # Returns
+///
+/// * `Vec<Measurement>` - The time of each stage measured and of the whole boot.
+pub fn boot_measurements(timing: &BootTiming) -> Vec<Measurement> {
+    let stages = [
+        ("POST time", timing.post()),
+        ("loader time", timing.loader),
+        ("kernel time", timing.kernel),
+        ("initrd time", timing.initrd),
+        ("userspace time", timing.userspace),
+        ("boot time", Some(timing.total())),
+    ];
+    stages
+        .iter()
+        .filter_map(|(metric, span)| {
+            span.map(|span| Measurement::new(*metric, "s", span.as_secs_f64()))
+        })
+        .collect()
+}
+
+/// Renders the stages of a boot, flagging a POST longer than the limit.
+///
+/// # Arguments
+///
+/// * `timing` - The stages of the boot.
+/// * `max_post` - The longest POST accepted, if there is a limit.
+///
+/// # Returns
+///
+/// * `String` - The boot broken down by stage, with the slowest units.
+pub fn format_boot_results(timing: &BootTiming, max_post: Option<Duration>) -> String {
+    let mut output = match timing.kind {
+        Some(kind) => format!("Last boot ({} boot)\n", kind),
+        None => "Last boot\n".to_string(),
+    };
+    let post_source = if timing.firmware.is_some() {
+        "as reported by the boot loader"
+    } else {
+        "from the BMC event log"
+    };
+    let stages = [
+        ("POST", timing.post(), post_source),
+        ("Loader", timing.loader, ""),
+        ("Kernel", timing.kernel, ""),
+        ("Initrd", timing.initrd, ""),
+        ("Userspace", timing.userspace, ""),
+    ];
+    for (name, span, note) in stages {
+        if let Some(span) = span {
+            let line = format!("  {:<10} {:>8.2} s  {}", name, span.as_secs_f64(), note);
+            output.push_str(line.trim_end());
+            output.push('\n');
+        }
+    }
+    output.push_str(&format!(
+        "  {:<10} {:>8.2} s\n",
+        "Total",
+        timing.total().as_secs_f64()
+    ));
+    if timing.post().is_none() {
+        output.push_str("  POST time unknown: the boot loader did not report it and no BMC event log was read.\n");
+    }
+
+    if !timing.slowest_units.is_empty() {
+        output.push_str("Slowest units\n");
+        for (unit, span) in &timing.slowest_units {
+            output.push_str(&format!("  {:>8.2} s  {}\n", span.as_secs_f64(), unit));
+        }
+    }
+
+    if let (Some(post), Some(max_post)) = (timing.post(), max_post) {
+        if post > max_post {
+            output.push_str(&format!(
+                "FAILED: the POST took {:.1} s, longer than the {:.1} s accepted.\n",
+                post.as_secs_f64(),
+                max_post.as_secs_f64()
+            ));
+        }
+    }
+    output
+}
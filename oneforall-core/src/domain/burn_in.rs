@@ -0,0 +1,136 @@
+//! Burn-in Domain Entity
+//!
+//! This module provides the report of a full-system burn-in, which loads the
+//! CPU, memory, disk and network at the same time while the hardware error
+//! monitors watch for faults. A burn-in passes only if every workload ran for
+//! the full duration and no monitor saw a new fault.
+
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::domain::profile::format_duration;
+
+/// How the burn-in workloads ended.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WorkloadOutcome {
+    /// Every stressor ran for the full duration.
+    Completed,
+
+    /// A stressor failed or stress-ng could not run.
+    Failed(String),
+
+    /// The burn-in was cancelled before the end.
+    Cancelled,
+}
+
+/// What a hardware error monitor saw during the burn-in.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MonitorStatus {
+    /// The source reported no new error; holds a short summary of what was watched.
+    Clean(String),
+
+    /// The source reported new errors.
+    Faults(Vec<String>),
+
+    /// The source could not be read on this machine; holds the reason.
+    Unavailable(String),
+}
+
+/// The findings of a single hardware error monitor.
+#[derive(Debug, Clone, Serialize)]
+pub struct MonitorResult {
+    /// The error source, e.g. "ECC".
+    pub source: String,
+
+    /// What the monitor saw.
+    pub status: MonitorStatus,
+}
+
+/// The result of a full-system burn-in.
+#[derive(Debug, Clone, Serialize)]
+pub struct BurnInReport {
+    /// The local time the burn-in started, in RFC 3339 format.
+    pub started_at: String,
+
+    /// The configured duration, in seconds.
+    pub planned_secs: u64,
+
+    /// The time the workloads actually ran, in seconds.
+    pub elapsed_secs: u64,
+
+    /// How the workloads ended.
+    pub outcome: WorkloadOutcome,
+
+    /// The bogo-ops completed by each stressor.
+    pub bogo_ops: Vec<(String, u64)>,
+
+    /// The findings of each hardware error monitor.
+    pub monitors: Vec<MonitorResult>,
+
+    /// The hottest temperature seen during the burn-in, in degrees Celsius.
+    pub peak_celsius: Option<f64>,
+}
+
+impl BurnInReport {
+    /// Returns `true` if the workloads completed and no monitor saw a fault.
+    pub fn passed(&self) -> bool {
+        self.outcome == WorkloadOutcome::Completed
+            && !self
+                .monitors
+                .iter()
+                .any(|m| matches!(m.status, MonitorStatus::Faults(_)))
+    }
+
+    /// Renders the report as plain text suitable for the terminal.
+    pub fn render(&self) -> String {
+        let mut output = String::from("== Burn-in report ==\n");
+        output.push_str(&format!(
+            "Duration:    {} of {} planned\n",
+            format_duration(Duration::from_secs(self.elapsed_secs)),
+            format_duration(Duration::from_secs(self.planned_secs))
+        ));
+        output.push_str(&format!(
+            "Workloads:   {}\n",
+            match &self.outcome {
+                WorkloadOutcome::Completed => "completed".to_string(),
+                WorkloadOutcome::Failed(e) => format!("FAILED ({})", e),
+                WorkloadOutcome::Cancelled => "cancelled".to_string(),
+            }
+        ));
+        for (stressor, ops) in &self.bogo_ops {
+            output.push_str(&format!("  {:<10} {:>14} bogo-ops\n", stressor, ops));
+        }
+        if let Some(celsius) = self.peak_celsius {
+            output.push_str(&format!("Peak temp:   {:.1} °C\n", celsius));
+        }
+
+        output.push_str("Fault monitors:\n");
+        for monitor in &self.monitors {
+            match &monitor.status {
+                MonitorStatus::Clean(summary) => output.push_str(&format!(
+                    "  {:<6} ok            {}\n",
+                    monitor.source, summary
+                )),
+                MonitorStatus::Faults(faults) => {
+                    output.push_str(&format!("  {:<6} FAULT\n", monitor.source));
+                    for fault in faults {
+                        output.push_str(&format!("           {}\n", fault));
+                    }
+                }
+                MonitorStatus::Unavailable(reason) => output.push_str(&format!(
+                    "  {:<6} not watched   {}\n",
+                    monitor.source, reason
+                )),
+            }
+        }
+
+        output.push_str(&format!(
+            "Result: {}\n",
+            if self.passed() { "PASS" } else { "FAIL" }
+        ));
+        output
+    }
+}
@@ -0,0 +1,292 @@
+//! Cache Domain Entity
+//!
+//! This module provides domain entities describing the CPU cache hierarchy and
+//! the results of the cache benchmark, which measures bandwidth and latency
+//! with a working set sized to fit each level in turn and then DRAM. Comparing
+//! the levels catches caches that are slower or smaller than reported, such as
+//! an L3 with disabled slices.
+
+use std::collections::BTreeSet;
+
+use crate::domain::cpu_topology::format_cpu_list;
+use crate::domain::discovery::DiscoverySection;
+use crate::domain::statistics::Measurement;
+
+/// The smallest working set used to measure DRAM, large enough to miss every cache.
+const MIN_DRAM_WORKING_SET: usize = 256 * 1024 * 1024;
+
+/// Fraction of the way from one level's latency to the next above which the
+/// capacity probe is considered to have spilled out of the level.
+const SPILL_THRESHOLD: f64 = 0.5;
+
+/// A data or unified cache as seen from one logical CPU.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheLevel {
+    /// The cache level, e.g. `2` for L2.
+    pub level: u8,
+
+    /// The cache type, "Data" or "Unified".
+    pub kind: String,
+
+    /// The capacity in bytes.
+    pub size_bytes: usize,
+
+    /// The cache line size in bytes, if known.
+    pub line_bytes: Option<u32>,
+
+    /// The associativity, if known.
+    pub ways: Option<u32>,
+
+    /// The logical CPUs sharing this cache, if known.
+    pub shared_cpus: BTreeSet<u32>,
+}
+
+impl CacheLevel {
+    /// Returns the short name of the level, e.g. "L2".
+    pub fn name(&self) -> String {
+        format!("L{}", self.level)
+    }
+}
+
+/// Parses a sysfs cache size such as `48K` or `32M` into bytes.
+///
+/// # Arguments
+///
+/// * `size` - The size string, with an optional `K`, `M` or `G` suffix.
+///
+/// # Returns
+///
+/// * `Option<usize>` - The size in bytes, or `None` if the string is malformed.
+pub fn parse_cache_size(size: &str) -> Option<usize> {
+    let size = size.trim();
+    let (digits, multiplier) = match size.chars().last()? {
+        'K' | 'k' => (&size[..size.len() - 1], 1024),
+        'M' | 'm' => (&size[..size.len() - 1], 1024 * 1024),
+        'G' | 'g' => (&size[..size.len() - 1], 1024 * 1024 * 1024),
+        _ => (size, 1),
+    };
+    digits.trim().parse::<usize>().ok().map(|n| n * multiplier)
+}
+
+/// Formats a byte count with a binary unit, e.g. `48 KiB` or `1.5 MiB`.
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if value.fract() == 0.0 {
+        format!("{:.0} {}", value, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Builds the discovery section describing the cache hierarchy.
+///
+/// # Arguments
+///
+/// * `caches` - The caches seen from the first online CPU, ordered by level.
+///
+/// # Returns
+///
+/// * `DiscoverySection` - One entry per cache with its size, geometry and sharing.
+pub fn cache_section(caches: &[CacheLevel]) -> DiscoverySection {
+    let mut section = DiscoverySection::new("Cache Hierarchy");
+    for cache in caches {
+        let mut details = vec![format_bytes(cache.size_bytes)];
+        if let Some(ways) = cache.ways {
+            details.push(format!("{}-way", ways));
+        }
+        if let Some(line) = cache.line_bytes {
+            details.push(format!("{} B lines", line));
+        }
+        if !cache.shared_cpus.is_empty() {
+            details.push(format!(
+                "shared by CPUs {}",
+                format_cpu_list(&cache.shared_cpus)
+            ));
+        }
+        section.push(
+            &format!("{} {}", cache.name(), cache.kind.to_lowercase()),
+            details.join(", "),
+        );
+    }
+    section
+}
+
+/// One step of the cache benchmark: a working set sized to fit a single level.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheSweepLevel {
+    /// The level measured, e.g. "L2" or "DRAM".
+    pub name: String,
+
+    /// The reported capacity of the level, or `None` for DRAM.
+    pub cache_bytes: Option<usize>,
+
+    /// The working set measured: half the capacity, so it fits with room to spare.
+    pub working_set_bytes: usize,
+
+    /// A second, larger working set used to check the capacity is really there:
+    /// three quarters of the reported capacity, or `None` for DRAM.
+    pub probe_bytes: Option<usize>,
+}
+
+/// Plans the cache benchmark from the discovered cache hierarchy.
+///
+/// # Arguments
+///
+/// * `caches` - The data and unified caches of the CPU being measured.
+///
+/// # Returns
+///
+/// * `Vec<CacheSweepLevel>` - One step per cache level, smallest first, then DRAM.
+pub fn plan_cache_sweep(caches: &[CacheLevel]) -> Vec<CacheSweepLevel> {
+    let mut caches: Vec<&CacheLevel> = caches.iter().filter(|c| c.kind != "Instruction").collect();
+    caches.sort_by_key(|c| c.level);
+
+    let mut levels: Vec<CacheSweepLevel> = caches
+        .iter()
+        .map(|cache| CacheSweepLevel {
+            name: cache.name(),
+            cache_bytes: Some(cache.size_bytes),
+            working_set_bytes: cache.size_bytes / 2,
+            probe_bytes: Some(cache.size_bytes / 4 * 3),
+        })
+        .collect();
+
+    let largest = caches.iter().map(|c| c.size_bytes).max().unwrap_or(0);
+    levels.push(CacheSweepLevel {
+        name: "DRAM".to_string(),
+        cache_bytes: None,
+        working_set_bytes: (largest * 4).max(MIN_DRAM_WORKING_SET),
+        probe_bytes: None,
+    });
+    levels
+}
+
+/// The measurements taken for one level of the cache benchmark.
+#[derive(Debug, Clone)]
+pub struct CacheLevelResult {
+    /// The step that was measured.
+    pub level: CacheSweepLevel,
+
+    /// Sequential read bandwidth in megabytes per second.
+    pub bandwidth_mb_s: f64,
+
+    /// Average dependent-load latency in nanoseconds.
+    pub latency_ns: f64,
+
+    /// Average dependent-load latency with the probe working set, if one was measured.
+    pub probe_latency_ns: Option<f64>,
+}
+
+/// Lists what looks wrong with one level compared with the next, slower one.
+///
+/// # Arguments
+///
+/// * `results` - Every measured level, smallest first.
+/// * `index` - The level to check.
+///
+/// # Returns
+///
+/// * `Vec<String>` - One description per anomaly; empty if the level looks healthy.
+pub fn cache_anomalies(results: &[CacheLevelResult], index: usize) -> Vec<String> {
+    let mut anomalies = Vec::new();
+    let (Some(result), Some(next)) = (results.get(index), results.get(index + 1)) else {
+        return anomalies;
+    };
+
+    if result.latency_ns >= next.latency_ns {
+        anomalies.push(format!("latency no better than {}", next.level.name));
+    }
+    if result.bandwidth_mb_s <= next.bandwidth_mb_s {
+        anomalies.push(format!("bandwidth no better than {}", next.level.name));
+    }
+
+    // A probe at 3/4 of the capacity should still hit the level. If its latency is
+    // closer to the next level, part of the cache is missing.
+    if let Some(probe) = result.probe_latency_ns {
+        let spill = (probe - result.latency_ns) / (next.latency_ns - result.latency_ns);
+        if next.latency_ns > result.latency_ns && spill > SPILL_THRESHOLD {
+            let reported = result
+                .level
+                .cache_bytes
+                .map(format_bytes)
+                .unwrap_or_default();
+            let hint = if result.level.name == "L3" {
+                " (disabled L3 slice?)"
+            } else {
+                ""
+            };
+            anomalies.push(format!(
+                "effective capacity below 3/4 of the reported {}{}",
+                reported, hint
+            ));
+        }
+    }
+
+    anomalies
+}
+
+/// Lists the metrics of a cache benchmark run, for statistics over repeated runs.
+pub fn cache_measurements(results: &[CacheLevelResult]) -> Vec<Measurement> {
+    results
+        .iter()
+        .flat_map(|r| {
+            [
+                Measurement::new(
+                    format!("{} bandwidth", r.level.name),
+                    "MB/s",
+                    r.bandwidth_mb_s,
+                ),
+                Measurement::new(format!("{} latency", r.level.name), "ns", r.latency_ns),
+            ]
+        })
+        .collect()
+}
+
+/// Formats the cache benchmark results as a per-level table.
+///
+/// # Arguments
+///
+/// * `results` - Every measured level, smallest first.
+///
+/// # Returns
+///
+/// * `String` - The table, with each level marked "ok" or "ANOMALY" with the reasons.
+pub fn format_cache_results(results: &[CacheLevelResult]) -> String {
+    let mut output =
+        String::from("Level  Size        Working set  Bandwidth (MB/s)  Latency (ns)  Status\n");
+
+    for (index, result) in results.iter().enumerate() {
+        let anomalies = cache_anomalies(results, index);
+        let status = if anomalies.is_empty() {
+            "ok".to_string()
+        } else {
+            format!("ANOMALY: {}", anomalies.join("; "))
+        };
+        output.push_str(&format!(
+            "{:<5}  {:<10}  {:>11}  {:>16.0}  {:>12.1}  {}\n",
+            result.level.name,
+            result
+                .level
+                .cache_bytes
+                .map(format_bytes)
+                .unwrap_or_else(|| "-".to_string()),
+            format_bytes(result.level.working_set_bytes),
+            result.bandwidth_mb_s,
+            result.latency_ns,
+            status
+        ));
+    }
+
+    output
+}
+
+/// Returns `true` if any level of the cache benchmark shows an anomaly.
+pub fn has_cache_anomalies(results: &[CacheLevelResult]) -> bool {
+    (0..results.len()).any(|index| !cache_anomalies(results, index).is_empty())
+}
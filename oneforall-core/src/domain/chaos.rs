@@ -0,0 +1,160 @@
+//! Chaos Domain Entity
+//!
+//! This module provides the failure injection plan of `--chaos`, a developer
+//! mode that fakes the faults a qualification run is meant to catch: a
+//! corrected ECC error, a temperature spike and a stress-ng run killed
+//! mid-test. Each event fires once, a set time after the application starts,
+//! through the same adapters the real readings come from, so alert thresholds,
+//! profile acceptance criteria and reports can be checked to respond before
+//! they are trusted on hardware that really fails.
+
+use std::time::Duration;
+
+/// The sensor name of the injected temperature spike.
+pub const CHAOS_SENSOR: &str = "chaos/injected";
+
+/// The temperature of the injected spike, in degrees Celsius; above the limit
+/// of any sensible alert threshold or acceptance criterion.
+pub const CHAOS_SPIKE_CELSIUS: f64 = 110.0;
+
+/// How long the injected spike lasts, so a threshold alert can be seen to
+/// clear once the reading is back within its limit.
+pub const CHAOS_SPIKE_DURATION: Duration = Duration::from_secs(60);
+
+/// The corrected ECC errors injected.
+pub const CHAOS_ECC_ERRORS: u64 = 1;
+
+/// A fault `--chaos` can inject.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChaosEvent {
+    /// A corrected ECC error added to the EDAC counters.
+    Ecc,
+
+    /// A sensor reading `CHAOS_SPIKE_CELSIUS` for `CHAOS_SPIKE_DURATION`.
+    Thermal,
+
+    /// The stress-ng run in progress killed with SIGKILL.
+    KillStressor,
+}
+
+impl ChaosEvent {
+    /// Every event, in the order they are listed.
+    pub const ALL: [ChaosEvent; 3] = [
+        ChaosEvent::Ecc,
+        ChaosEvent::Thermal,
+        ChaosEvent::KillStressor,
+    ];
+
+    /// Returns the name of the event on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChaosEvent::Ecc => "ecc",
+            ChaosEvent::Thermal => "thermal",
+            ChaosEvent::KillStressor => "kill-stressor",
+        }
+    }
+}
+
+/// The faults to inject and when.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChaosPlan {
+    /// The events to inject.
+    pub events: Vec<ChaosEvent>,
+
+    /// The time after the application starts at which the events fire.
+    pub after: Duration,
+}
+
+impl ChaosPlan {
+    /// Reads the events requested with `--chaos`.
+    ///
+    /// # Arguments
+    ///
+    /// * `events` - A comma-separated list of event names, or `all`.
+    /// * `after` - The time after the application starts at which the events fire.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ChaosPlan, String>` - The plan, or an error naming an unknown event.
+    pub fn parse(events: &str, after: Duration) -> Result<ChaosPlan, String> {
+        let mut plan = ChaosPlan {
+            events: Vec::new(),
+            after,
+        };
+        for name in events.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            let requested: Vec<ChaosEvent> = match name {
+                "all" => ChaosEvent::ALL.to_vec(),
+                name => vec![ChaosEvent::ALL
+                    .into_iter()
+                    .find(|event| event.name() == name)
+                    .ok_or_else(|| {
+                        format!(
+                            "Unknown chaos event '{}'; expected all or {}",
+                            name,
+                            ChaosEvent::ALL.map(|event| event.name()).join(", ")
+                        )
+                    })?],
+            };
+            for event in requested {
+                if !plan.events.contains(&event) {
+                    plan.events.push(event);
+                }
+            }
+        }
+        if plan.events.is_empty() {
+            return Err("No chaos event given".to_string());
+        }
+        Ok(plan)
+    }
+
+    /// Returns `true` if the plan injects the event.
+    pub fn includes(&self, event: ChaosEvent) -> bool {
+        self.events.contains(&event)
+    }
+
+    /// Lists the events, e.g. "ecc,thermal", as recorded in the `chaos` tag of results.
+    pub fn event_names(&self) -> String {
+        self.events
+            .iter()
+            .map(ChaosEvent::name)
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Returns the corrected ECC errors injected by a given time.
+    ///
+    /// # Arguments
+    ///
+    /// * `elapsed` - The time since the application started.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<u64>` - The errors to add to the EDAC counters, or `None` if
+    ///   the plan injects no ECC error.
+    pub fn injected_ecc_errors(&self, elapsed: Duration) -> Option<u64> {
+        self.includes(ChaosEvent::Ecc).then(|| {
+            if elapsed >= self.after {
+                CHAOS_ECC_ERRORS
+            } else {
+                0
+            }
+        })
+    }
+
+    /// Returns the injected temperature at a given time.
+    ///
+    /// # Arguments
+    ///
+    /// * `elapsed` - The time since the application started.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<f64>` - The temperature of the injected sensor while the spike
+    ///   lasts, in degrees Celsius.
+    pub fn injected_celsius(&self, elapsed: Duration) -> Option<f64> {
+        (self.includes(ChaosEvent::Thermal)
+            && elapsed >= self.after
+            && elapsed < self.after + CHAOS_SPIKE_DURATION)
+            .then_some(CHAOS_SPIKE_CELSIUS)
+    }
+}
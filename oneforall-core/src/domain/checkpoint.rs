@@ -0,0 +1,207 @@
+//! Run Checkpoint Domain Entity
+//!
+//! This module provides the checkpoint of a profile run, saved in the database
+//! when the run starts and after every step. If the daemon or the machine
+//! restarts before the run finishes, its checkpoint is left behind, and
+//! `oneforall resume` continues with the first step that did not finish
+//! rather than starting a 24-hour profile from scratch. The steps that did
+//! finish keep their results, and the resumed run keeps the start time of the
+//! original one along with the telemetry recorded before the restart, so both
+//! halves are stored and uploaded as one run. A step interrupted by the
+//! restart starts over.
+//!
+//! Steps may also restart the machine on purpose, rebooting it after a
+//! firmware update or between the passes of a RAM test, or power cycling it
+//! through the BMC. The checkpoint then records the passes of the step that
+//! finished, moves to the phase waiting for the restart and notes the boot it
+//! was saved in, so `oneforall resume --after-reboot`, run at boot, continues
+//! with the next pass, and a restart of the daemon alone is told apart from
+//! the restart the step asked for.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use common::domain::telemetry::TelemetryEvent;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::profile::{Profile, StepResult};
+
+/// Where a profile run is between its steps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunPhase {
+    /// Steps are running; a restart in this phase interrupted the step that was running.
+    #[default]
+    Running,
+
+    /// A step rebooted or power cycled the machine, and the run continues once it is back.
+    AwaitingReboot,
+}
+
+/// The saved progress of a profile run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunCheckpoint {
+    /// The profile as given with `--profile`, a name or the path of a TOML file.
+    pub source: String,
+
+    /// The profile name, e.g. "burn-in-24h".
+    pub profile: String,
+
+    /// The local time the run first started, in RFC 3339 format.
+    pub started_at: String,
+
+    /// The names of every step of the profile, to notice it changed before resuming.
+    pub step_names: Vec<String>,
+
+    /// The results of the steps that finished, in order.
+    pub steps: Vec<StepResult>,
+
+    /// The telemetry recorded so far, kept when the run is uploaded.
+    #[serde(default)]
+    pub telemetry: Vec<TelemetryEvent>,
+
+    /// How often the run was resumed.
+    pub resumes: u32,
+
+    /// Whether the run is running steps or waiting for the machine to restart.
+    #[serde(default)]
+    pub phase: RunPhase,
+
+    /// The results of the passes of the next step that finished, when it runs several.
+    #[serde(default)]
+    pub passes: Vec<StepResult>,
+
+    /// The boot the machine was in when a step restarted it, from the kernel's boot ID.
+    #[serde(default)]
+    pub boot_id: Option<String>,
+
+    /// The local time a step restarted the machine, in RFC 3339 format.
+    #[serde(default)]
+    pub restarted_at: Option<String>,
+
+    /// The local time the checkpoint was saved, in RFC 3339 format.
+    pub updated_at: String,
+}
+
+impl RunCheckpoint {
+    /// Creates the checkpoint of a run about to start.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The profile as given with `--profile`.
+    /// * `profile` - The loaded profile.
+    ///
+    /// # Returns
+    ///
+    /// * `RunCheckpoint` - A checkpoint without finished steps.
+    pub fn new(source: &str, profile: &Profile) -> Self {
+        let now = Local::now().to_rfc3339();
+        RunCheckpoint {
+            source: source.to_string(),
+            profile: profile.name.clone(),
+            started_at: now.clone(),
+            step_names: profile.steps.iter().map(|step| step.name.clone()).collect(),
+            steps: Vec::new(),
+            telemetry: Vec::new(),
+            resumes: 0,
+            phase: RunPhase::Running,
+            passes: Vec::new(),
+            boot_id: None,
+            restarted_at: None,
+            updated_at: now,
+        }
+    }
+
+    /// Checks that a profile can continue this run: it must have the same steps,
+    /// in the same order, as when the run started.
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - The profile loaded again to resume.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), String>` - Whether the run can be resumed, or why not.
+    pub fn check_profile(&self, profile: &Profile) -> Result<(), String> {
+        let step_names: Vec<&str> = profile
+            .steps
+            .iter()
+            .map(|step| step.name.as_str())
+            .collect();
+        if profile.name != self.profile || step_names != self.step_names {
+            return Err(format!(
+                "The profile {} changed since the run started at {}; discard the checkpoint \
+                 with `resume --profile {} --discard` and run it again",
+                self.profile, self.started_at, self.profile
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks that the machine restarted since a step asked for it, so a run waiting for
+    /// the restart is not continued by a restart of the daemon alone.
+    ///
+    /// # Arguments
+    ///
+    /// * `boot_id` - The boot ID of the running kernel, if it can be read.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), String>` - Whether the run can continue, or why not.
+    pub fn check_restarted(&self, boot_id: Option<&str>) -> Result<(), String> {
+        if self.phase != RunPhase::AwaitingReboot {
+            return Ok(());
+        }
+        match (self.boot_id.as_deref(), boot_id) {
+            (Some(before), Some(now)) if before == now => Err(format!(
+                "The machine has not restarted since step '{}' of profile {} asked for it at {}; \
+                 restart it, or discard the run with `resume --profile {} --discard`",
+                self.remaining_steps().first().map_or("", String::as_str),
+                self.profile,
+                self.restarted_at.as_deref().unwrap_or(&self.updated_at),
+                self.profile
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns how long the machine was away after a step restarted it, until now.
+    pub fn restart_downtime(&self) -> Option<Duration> {
+        let restarted_at = DateTime::parse_from_rfc3339(self.restarted_at.as_deref()?).ok()?;
+        (Local::now() - restarted_at.with_timezone(&Local))
+            .to_std()
+            .ok()
+    }
+
+    /// Returns the names of the steps left to run.
+    pub fn remaining_steps(&self) -> &[String] {
+        &self.step_names[self.steps.len().min(self.step_names.len())..]
+    }
+
+    /// Renders the checkpoint as one line for listing the runs that can be resumed.
+    pub fn render_line(&self) -> String {
+        format!(
+            "  {:<16} started {}  {}/{} steps done{}  saved {}{}{}",
+            self.profile,
+            self.started_at,
+            self.steps.len(),
+            self.step_names.len(),
+            if self.passes.is_empty() {
+                String::new()
+            } else {
+                format!(" (+{} pass(es))", self.passes.len())
+            },
+            self.updated_at,
+            if self.resumes > 0 {
+                format!("  resumed {} time(s)", self.resumes)
+            } else {
+                String::new()
+            },
+            if self.phase == RunPhase::AwaitingReboot {
+                "  waiting for a reboot"
+            } else {
+                ""
+            }
+        )
+    }
+}
@@ -0,0 +1,213 @@
+//! Clock Stability Domain Entity
+//!
+//! This module provides the results of the clock stability benchmark, which
+//! tells whether a machine keeps time well enough for latency-sensitive work
+//! such as trading, telemetry with nanosecond timestamps or distributed
+//! tracing. Such workloads read the clock constantly and compare readings
+//! taken on different cores, so they need a counter that ticks at a constant
+//! rate (an invariant TSC on x86), a kernel clocksource that is both stable
+//! and cheap to read from user space, and counters that agree across cores.
+//! The kernel falls back to the HPET or ACPI PM timer when it finds the TSC
+//! unstable, which makes every clock read a slow trip to a platform device.
+
+use crate::domain::statistics::Measurement;
+
+/// Cross-core skew beyond the measurement uncertainty, in nanoseconds, above
+/// which the counters of two cores count as disagreeing.
+pub const SKEW_LIMIT_NS: f64 = 100.0;
+
+/// Average cost of reading the monotonic clock, in nanoseconds, above which it
+/// is not served from user space.
+pub const SLOW_CLOCK_READ_NS: f64 = 100.0;
+
+/// Resolution of the monotonic clock, in nanoseconds, above which it is too
+/// coarse to time latencies.
+pub const COARSE_RESOLUTION_NS: f64 = 1000.0;
+
+/// Clocksources the kernel falls back to when the TSC or the architected
+/// timer is unusable; they are slow to read and often drift.
+pub const FALLBACK_CLOCKSOURCES: [&str; 5] =
+    ["hpet", "acpi_pm", "pit", "jiffies", "refined-jiffies"];
+
+/// The offset of the counter of one CPU from the reference CPU.
+#[derive(Debug, Clone)]
+pub struct CoreSkew {
+    /// The logical CPU compared with the reference.
+    pub cpu: u32,
+
+    /// The most likely offset of its counter, in nanoseconds, positive when it runs ahead.
+    pub offset_ns: f64,
+
+    /// Half the width of the interval the offset was bounded to, in nanoseconds.
+    pub uncertainty_ns: f64,
+}
+
+impl CoreSkew {
+    /// Returns the offset that the measurement cannot explain by its uncertainty, in nanoseconds.
+    pub fn excess_ns(&self) -> f64 {
+        (self.offset_ns.abs() - self.uncertainty_ns).max(0.0)
+    }
+}
+
+/// The results of the clock stability benchmark.
+#[derive(Debug, Clone, Default)]
+pub struct ClockResult {
+    /// The counter compared across cores, e.g. "TSC" or "CLOCK_MONOTONIC".
+    pub counter: String,
+
+    /// Whether the CPU advertises an invariant TSC; `None` on other architectures.
+    pub invariant_tsc: Option<bool>,
+
+    /// The clocksource the kernel uses, e.g. "tsc".
+    pub clocksource: Option<String>,
+
+    /// The clocksources the kernel found usable.
+    pub available_clocksources: Vec<String>,
+
+    /// The resolution the kernel reports for the monotonic clock, in nanoseconds.
+    pub resolution_ns: f64,
+
+    /// The smallest step seen between two consecutive readings of the monotonic clock, in nanoseconds.
+    pub granularity_ns: f64,
+
+    /// The average cost of reading the monotonic clock, in nanoseconds.
+    pub read_cost_ns: f64,
+
+    /// The logical CPU every other one was compared with.
+    pub reference_cpu: u32,
+
+    /// The offset of every other CPU; empty if threads could not be pinned.
+    pub skews: Vec<CoreSkew>,
+}
+
+impl ClockResult {
+    /// Returns the CPU whose counter is furthest from the reference, beyond the uncertainty.
+    pub fn worst_skew(&self) -> Option<&CoreSkew> {
+        self.skews
+            .iter()
+            .max_by(|a, b| a.excess_ns().total_cmp(&b.excess_ns()))
+    }
+
+    /// Lists what makes the machine unsuitable for latency-sensitive workloads.
+    pub fn findings(&self) -> Vec<String> {
+        let mut findings = Vec::new();
+
+        if self.invariant_tsc == Some(false) {
+            findings.push(
+                "The TSC is not invariant: its rate follows the clock and it may stop in deep C-states"
+                    .to_string(),
+            );
+        }
+        if let Some(clocksource) = &self.clocksource {
+            if FALLBACK_CLOCKSOURCES.contains(&clocksource.as_str()) {
+                findings.push(format!(
+                    "The kernel uses the {} clocksource, a slow fallback for an unstable TSC or timer",
+                    clocksource
+                ));
+            }
+        }
+        if self.invariant_tsc.is_some()
+            && !self.available_clocksources.is_empty()
+            && !self.available_clocksources.iter().any(|c| c == "tsc")
+        {
+            findings.push("The kernel marked the TSC unstable and no longer offers it".to_string());
+        }
+        for skew in self.skews.iter().filter(|s| s.excess_ns() > SKEW_LIMIT_NS) {
+            findings.push(format!(
+                "The {} of CPU {} is {:+.0} ns (±{:.0} ns) off CPU {}",
+                self.counter, skew.cpu, skew.offset_ns, skew.uncertainty_ns, self.reference_cpu
+            ));
+        }
+        if self.read_cost_ns > SLOW_CLOCK_READ_NS {
+            findings.push(format!(
+                "Reading the clock takes {:.0} ns; it is not served from user space",
+                self.read_cost_ns
+            ));
+        }
+        if self.resolution_ns.max(self.granularity_ns) > COARSE_RESOLUTION_NS {
+            findings.push(format!(
+                "The clock only advances every {:.0} ns",
+                self.resolution_ns.max(self.granularity_ns)
+            ));
+        }
+
+        findings
+    }
+}
+
+/// Lists the metrics of a clock stability run, for statistics over repeated runs.
+pub fn clock_measurements(result: &ClockResult) -> Vec<Measurement> {
+    let mut measurements = vec![
+        Measurement::new("Clock read cost", "ns", result.read_cost_ns),
+        Measurement::new("Clock granularity", "ns", result.granularity_ns),
+    ];
+    if let Some(skew) = result.worst_skew() {
+        measurements.push(Measurement::new(
+            "Max cross-core skew",
+            "ns",
+            skew.excess_ns(),
+        ));
+    }
+    measurements
+}
+
+/// Formats the results of a clock stability run.
+///
+/// # Arguments
+///
+/// * `result` - The results of the benchmark.
+///
+/// # Returns
+///
+/// * `String` - The counter, clocksource and timer figures, the worst skew
+///   between cores, and whether the machine suits latency-sensitive workloads.
+pub fn format_clock_results(result: &ClockResult) -> String {
+    let mut output = format!("Clock stability ({})\n", result.counter);
+    if let Some(invariant) = result.invariant_tsc {
+        output.push_str(&format!(
+            "{:<20}  {}\n",
+            "Invariant TSC",
+            if invariant { "yes" } else { "NO" }
+        ));
+    }
+    if let Some(clocksource) = &result.clocksource {
+        output.push_str(&format!(
+            "{:<20}  {} (available: {})\n",
+            "Clocksource",
+            clocksource,
+            result.available_clocksources.join(", ")
+        ));
+    }
+    output.push_str(&format!(
+        "{:<20}  {:.0} ns reported, {:.0} ns measured\n",
+        "Timer resolution", result.resolution_ns, result.granularity_ns
+    ));
+    output.push_str(&format!(
+        "{:<20}  {:.0} ns\n",
+        "Clock read cost", result.read_cost_ns
+    ));
+    match result.worst_skew() {
+        Some(skew) => output.push_str(&format!(
+            "{:<20}  {:.0} ns beyond the uncertainty, CPU {} ({:+.0} ns ±{:.0} ns), over {} CPUs\n",
+            "Cross-core skew",
+            skew.excess_ns(),
+            skew.cpu,
+            skew.offset_ns,
+            skew.uncertainty_ns,
+            result.skews.len()
+        )),
+        None => output.push_str(&format!("{:<20}  not measured\n", "Cross-core skew")),
+    }
+
+    let findings = result.findings();
+    if findings.is_empty() {
+        output.push_str("\nSuitable for latency-sensitive workloads.\n");
+    } else {
+        output.push('\n');
+        for finding in &findings {
+            output.push_str(&format!("WARNING: {}\n", finding));
+        }
+        output.push_str("Not suitable for latency-sensitive workloads.\n");
+    }
+    output
+}
@@ -0,0 +1,220 @@
+//! Cloud Domain Entity
+//!
+//! This module provides the cloud instance a run happened on, as described by
+//! the instance metadata service of AWS, Google Cloud or Azure. The instance
+//! type, region, zone and tenancy are attached to every result as tags, so
+//! results from different instance types can be compared without the operator
+//! having to record where each run came from.
+
+use serde_json::Value;
+
+use crate::domain::discovery::DiscoverySection;
+use crate::domain::tags::Tags;
+
+/// The asset tag Azure writes into the DMI chassis information of its VMs.
+const AZURE_CHASSIS_ASSET_TAG: &str = "7783-7084-3265-9085-8269-3286-77";
+
+/// A cloud provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloudProvider {
+    Aws,
+    Gcp,
+    Azure,
+}
+
+impl CloudProvider {
+    /// Returns the short name of the provider, as used in tags.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CloudProvider::Aws => "aws",
+            CloudProvider::Gcp => "gcp",
+            CloudProvider::Azure => "azure",
+        }
+    }
+}
+
+/// The DMI system fields that identify the cloud provider of a machine.
+#[derive(Debug, Clone, Default)]
+pub struct DmiSystem {
+    /// The system vendor, e.g. "Amazon EC2".
+    pub sys_vendor: String,
+
+    /// The product name, e.g. "Google Compute Engine".
+    pub product_name: String,
+
+    /// The BIOS version, e.g. "4.11.amazon" on Xen-based EC2 instances.
+    pub bios_version: String,
+
+    /// The chassis asset tag.
+    pub chassis_asset_tag: String,
+}
+
+/// Identifies the cloud provider of a machine from its DMI system fields.
+///
+/// Checking DMI first keeps machines outside a cloud from waiting on a
+/// metadata service that does not exist.
+///
+/// # Arguments
+///
+/// * `dmi` - The DMI system fields of the machine.
+///
+/// # Returns
+///
+/// * `Option<CloudProvider>` - The provider, or `None` if the machine is not a known cloud instance.
+pub fn detect_provider(dmi: &DmiSystem) -> Option<CloudProvider> {
+    if dmi.sys_vendor.contains("Amazon") || dmi.bios_version.to_lowercase().contains("amazon") {
+        Some(CloudProvider::Aws)
+    } else if dmi.product_name.contains("Google Compute Engine") || dmi.sys_vendor == "Google" {
+        Some(CloudProvider::Gcp)
+    } else if dmi.chassis_asset_tag == AZURE_CHASSIS_ASSET_TAG {
+        Some(CloudProvider::Azure)
+    } else {
+        None
+    }
+}
+
+/// The cloud instance a run happened on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstanceMetadata {
+    /// The cloud provider.
+    pub provider: CloudProvider,
+
+    /// The instance type, e.g. "c7i.8xlarge", "n2-standard-8" or "Standard_D8s_v5".
+    pub instance_type: Option<String>,
+
+    /// The region, e.g. "us-east-1".
+    pub region: Option<String>,
+
+    /// The availability zone, e.g. "us-east-1a".
+    pub zone: Option<String>,
+
+    /// Whether the host is shared with other customers: "shared", "dedicated" or "sole-tenant".
+    pub tenancy: Option<String>,
+}
+
+impl InstanceMetadata {
+    /// Returns the metadata as tags, e.g. "cloud.instance_type" to "c7i.8xlarge".
+    pub fn tags(&self) -> Tags {
+        let mut tags = Tags::new();
+        tags.insert(
+            "cloud.provider".to_string(),
+            self.provider.name().to_string(),
+        );
+        for (key, value) in [
+            ("cloud.instance_type", &self.instance_type),
+            ("cloud.region", &self.region),
+            ("cloud.zone", &self.zone),
+            ("cloud.tenancy", &self.tenancy),
+        ] {
+            if let Some(value) = value {
+                tags.insert(key.to_string(), value.clone());
+            }
+        }
+        tags
+    }
+}
+
+/// Reads a string field of a metadata document, treating an empty string as missing.
+fn text(value: &Value) -> Option<String> {
+    value
+        .as_str()
+        .filter(|text| !text.is_empty())
+        .map(str::to_string)
+}
+
+/// Returns the last segment of a resource path, e.g. "n2-standard-8" for
+/// "projects/123/machineTypes/n2-standard-8".
+fn last_segment(value: &Value) -> Option<String> {
+    text(value).and_then(|path| path.rsplit('/').next().map(str::to_string))
+}
+
+/// Parses the AWS instance identity document.
+///
+/// # Arguments
+///
+/// * `document` - The document served at `/latest/dynamic/instance-identity/document`.
+/// * `tenancy` - The tenancy served at `/latest/meta-data/placement/tenancy`, if read.
+///
+/// # Returns
+///
+/// * `InstanceMetadata` - The instance type, region, zone and tenancy.
+pub fn parse_aws_identity(document: &Value, tenancy: Option<String>) -> InstanceMetadata {
+    InstanceMetadata {
+        provider: CloudProvider::Aws,
+        instance_type: text(&document["instanceType"]),
+        region: text(&document["region"]),
+        zone: text(&document["availabilityZone"]),
+        // EC2 calls instances on shared hardware "default" tenancy.
+        tenancy: tenancy.map(|tenancy| match tenancy.as_str() {
+            "default" => "shared".to_string(),
+            _ => tenancy,
+        }),
+    }
+}
+
+/// Parses the Google Cloud instance metadata, read with `?recursive=true`.
+///
+/// # Returns
+///
+/// * `InstanceMetadata` - The machine type, region, zone and whether the VM runs on a sole-tenant node.
+pub fn parse_gcp_instance(instance: &Value) -> InstanceMetadata {
+    let zone = last_segment(&instance["zone"]);
+    // Zones are named after their region, e.g. "us-central1-a" in "us-central1".
+    let region = zone
+        .as_ref()
+        .and_then(|zone| zone.rsplit_once('-'))
+        .map(|(region, _)| region.to_string());
+    let sole_tenant = instance["scheduling"]["nodeAffinities"]
+        .as_array()
+        .is_some_and(|affinities| !affinities.is_empty());
+    InstanceMetadata {
+        provider: CloudProvider::Gcp,
+        instance_type: last_segment(&instance["machineType"]),
+        region,
+        zone,
+        tenancy: Some(if sole_tenant { "sole-tenant" } else { "shared" }.to_string()),
+    }
+}
+
+/// Parses the Azure instance metadata served at `/metadata/instance`.
+///
+/// # Returns
+///
+/// * `InstanceMetadata` - The VM size, location, zone and whether the VM runs on a dedicated host.
+pub fn parse_azure_instance(instance: &Value) -> InstanceMetadata {
+    let compute = &instance["compute"];
+    let dedicated =
+        text(&compute["hostGroup"]["id"]).is_some() || text(&compute["host"]["id"]).is_some();
+    InstanceMetadata {
+        provider: CloudProvider::Azure,
+        instance_type: text(&compute["vmSize"]),
+        region: text(&compute["location"]),
+        // Azure numbers the zones of a region, e.g. "2" in "eastus"; VMs
+        // outside a zone report an empty string.
+        zone: text(&compute["zone"]),
+        tenancy: Some(if dedicated { "dedicated" } else { "shared" }.to_string()),
+    }
+}
+
+/// Builds the discovery section describing the cloud instance.
+///
+/// # Arguments
+///
+/// * `instance` - The metadata of the cloud instance.
+///
+/// # Returns
+///
+/// * `DiscoverySection` - The provider, instance type, region, zone and tenancy.
+pub fn cloud_section(instance: &InstanceMetadata) -> DiscoverySection {
+    let mut section = DiscoverySection::new("Cloud Instance");
+    let unknown = || "unknown".to_string();
+    section.push("Provider", instance.provider.name());
+    section.push(
+        "Instance type",
+        instance.instance_type.clone().unwrap_or_else(unknown),
+    );
+    section.push("Region", instance.region.clone().unwrap_or_else(unknown));
+    section.push("Zone", instance.zone.clone().unwrap_or_else(unknown));
+    section.push("Tenancy", instance.tenancy.clone().unwrap_or_else(unknown));
+    section
+}
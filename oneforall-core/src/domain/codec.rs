@@ -0,0 +1,184 @@
+//! Codec Benchmark Domain Entity
+//!
+//! This module provides the crypto and compression throughput benchmarks, which
+//! size storage servers and TLS-terminating hosts by how fast their CPUs encrypt,
+//! hash and compress. Each algorithm runs first on a single core, then on every
+//! logical CPU of each socket at once, and is reported in GB/s of plaintext or
+//! uncompressed data, per core and per socket. Crypto throughput depends mostly
+//! on whether the CPU accelerates the algorithm in hardware, so the extensions
+//! used are reported next to each result; compression throughput also depends
+//! on the data, so the benchmark compresses synthetic log lines, which compress
+//! about as well as typical logs and JSON do.
+
+use serde::Serialize;
+
+use crate::domain::statistics::Measurement;
+
+/// An algorithm the codec benchmarks measure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CodecAlgorithm {
+    /// AES-256-GCM encryption of 16 KiB records, the largest TLS record.
+    Aes256Gcm,
+
+    /// SHA-256 hashing of 64 KiB blocks.
+    Sha256,
+
+    /// zstd compression at level 3, its default, of 1 MiB blocks.
+    ZstdCompress,
+
+    /// zstd decompression of 1 MiB blocks.
+    ZstdDecompress,
+
+    /// gzip compression at level 6, its default, of 1 MiB blocks.
+    GzipCompress,
+
+    /// gzip decompression of 1 MiB blocks.
+    GzipDecompress,
+}
+
+impl CodecAlgorithm {
+    /// The algorithms of `benchmark crypto`.
+    pub const CRYPTO: [CodecAlgorithm; 2] = [CodecAlgorithm::Aes256Gcm, CodecAlgorithm::Sha256];
+
+    /// The algorithms of `benchmark compression`.
+    pub const COMPRESSION: [CodecAlgorithm; 4] = [
+        CodecAlgorithm::ZstdCompress,
+        CodecAlgorithm::ZstdDecompress,
+        CodecAlgorithm::GzipCompress,
+        CodecAlgorithm::GzipDecompress,
+    ];
+
+    /// Returns the name of the algorithm in reports and metrics, e.g. "zstd-3 compress".
+    pub fn label(&self) -> &'static str {
+        match self {
+            CodecAlgorithm::Aes256Gcm => "AES-256-GCM",
+            CodecAlgorithm::Sha256 => "SHA-256",
+            CodecAlgorithm::ZstdCompress => "zstd-3 compress",
+            CodecAlgorithm::ZstdDecompress => "zstd decompress",
+            CodecAlgorithm::GzipCompress => "gzip-6 compress",
+            CodecAlgorithm::GzipDecompress => "gzip decompress",
+        }
+    }
+}
+
+/// The throughput of an algorithm on every logical CPU of one socket.
+#[derive(Debug, Clone, Serialize)]
+pub struct SocketCodecThroughput {
+    /// The socket (package) number.
+    pub socket: u32,
+
+    /// The threads run, one per logical CPU of the socket.
+    pub threads: usize,
+
+    /// The data processed per second by every thread together, in GB/s.
+    pub gb_per_sec: f64,
+}
+
+/// The throughput of one algorithm.
+#[derive(Debug, Clone, Serialize)]
+pub struct CodecThroughput {
+    /// The algorithm measured.
+    pub algorithm: CodecAlgorithm,
+
+    /// The CPU extensions accelerating the algorithm, e.g. "AES-NI, PCLMULQDQ";
+    /// empty if it runs in software or gains nothing from them.
+    pub acceleration: Vec<String>,
+
+    /// The data processed per second by a single core, in GB/s.
+    pub core_gb_per_sec: f64,
+
+    /// The throughput of each socket with every logical CPU busy.
+    pub sockets: Vec<SocketCodecThroughput>,
+
+    /// The size of the data before compression divided by its size after, for compressors.
+    pub compression_ratio: Option<f64>,
+}
+
+/// The results of a crypto or compression benchmark.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CodecResult {
+    /// The throughput of each algorithm, in the order run.
+    pub algorithms: Vec<CodecThroughput>,
+}
+
+/// Lists the metrics of a codec benchmark run, for statistics over repeated runs.
+pub fn codec_measurements(result: &CodecResult) -> Vec<Measurement> {
+    let mut measurements = Vec::new();
+    for throughput in &result.algorithms {
+        let label = throughput.algorithm.label();
+        measurements.push(Measurement::new(
+            format!("{} per core", label),
+            "GB/s",
+            throughput.core_gb_per_sec,
+        ));
+        measurements.extend(throughput.sockets.iter().map(|socket| {
+            Measurement::new(
+                format!("{} socket {}", label, socket.socket),
+                "GB/s",
+                socket.gb_per_sec,
+            )
+        }));
+    }
+    measurements
+}
+
+/// Formats the results of a codec benchmark as a table.
+///
+/// # Arguments
+///
+/// * `title` - The title of the table, e.g. "Crypto throughput".
+/// * `result` - The results of the benchmark.
+///
+/// # Returns
+///
+/// * `String` - One row per algorithm: its throughput on one core and on each
+///   socket, its compression ratio and the extensions accelerating it.
+pub fn format_codec_results(title: &str, result: &CodecResult) -> String {
+    let sockets: Vec<u32> = result
+        .algorithms
+        .first()
+        .map(|throughput| throughput.sockets.iter().map(|s| s.socket).collect())
+        .unwrap_or_default();
+
+    let mut output = format!("{}\n", title);
+    output.push_str(&format!("{:<16}  {:>10}", "Algorithm", "Core GB/s"));
+    for socket in &sockets {
+        output.push_str(&format!("  {:>10}", format!("Socket {}", socket)));
+    }
+    output.push_str(&format!("  {:>6}  {}\n", "Ratio", "Acceleration"));
+
+    for throughput in &result.algorithms {
+        output.push_str(&format!(
+            "{:<16}  {:>10.2}",
+            throughput.algorithm.label(),
+            throughput.core_gb_per_sec
+        ));
+        for socket in &throughput.sockets {
+            output.push_str(&format!("  {:>10.2}", socket.gb_per_sec));
+        }
+        output.push_str(&format!(
+            "  {:>6}  {}\n",
+            throughput
+                .compression_ratio
+                .map(|ratio| format!("{:.2}x", ratio))
+                .unwrap_or_else(|| "-".to_string()),
+            if throughput.acceleration.is_empty() {
+                "-".to_string()
+            } else {
+                throughput.acceleration.join(", ")
+            }
+        ));
+    }
+
+    if let Some(throughput) = result.algorithms.first() {
+        for socket in &throughput.sockets {
+            output.push_str(&format!(
+                "\nSocket {} ran {} threads, one per logical CPU.",
+                socket.socket, socket.threads
+            ));
+        }
+        output.push('\n');
+    }
+    output
+}
@@ -0,0 +1,173 @@
+//! Container Domain Entity
+//!
+//! This module provides the CPU and memory limits the cgroup of the process
+//! places on it. Inside a container, or a systemd unit with resource limits,
+//! the machine reports every host CPU and all host memory while only a share
+//! of them is usable: a stress test sized for the host oversubscribes the CPU
+//! quota, and a memory test sized for the host is killed by the OOM killer.
+//! Workloads are therefore sized to the effective limits.
+
+use std::collections::BTreeSet;
+
+use crate::domain::cpu_topology::format_cpu_list;
+use crate::domain::discovery::DiscoverySection;
+use crate::domain::endurance::format_volume;
+
+/// The CPU and memory the process may use, as limited by its cgroup.
+#[derive(Debug, Clone, Default)]
+pub struct ResourceLimits {
+    /// The container runtime the process runs in, e.g. "docker", if one is detected.
+    pub runtime: Option<String>,
+
+    /// The cgroup version, 1 or 2, or `None` without cgroups.
+    pub cgroup_version: Option<u32>,
+
+    /// The cgroup of the process, e.g. "/system.slice/oneforall.service".
+    pub cgroup_path: Option<String>,
+
+    /// The online CPUs of the host.
+    pub host_cpus: BTreeSet<u32>,
+
+    /// The CPUs the cgroup may run on, if narrower than the host.
+    pub cpuset: Option<BTreeSet<u32>>,
+
+    /// The CPU time the cgroup may use, in CPUs, e.g. 2.5 for 250 ms every 100 ms.
+    pub cpu_quota: Option<f64>,
+
+    /// The total memory of the host, in bytes.
+    pub host_memory_bytes: u64,
+
+    /// The memory the cgroup may use, in bytes, if below the host total.
+    pub memory_limit_bytes: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// Returns the number of CPUs worth loading: the host CPUs, narrowed to the
+    /// cpuset and rounded up to the CPU quota.
+    pub fn effective_cpus(&self) -> usize {
+        let mut cpus = self.host_cpus.len();
+        if let Some(cpuset) = &self.cpuset {
+            cpus = cpus.min(cpuset.len());
+        }
+        if let Some(quota) = self.cpu_quota {
+            cpus = cpus.min(quota.ceil() as usize);
+        }
+        cpus.max(1)
+    }
+
+    /// Returns the memory usable by the process, in bytes.
+    pub fn effective_memory_bytes(&self) -> u64 {
+        self.memory_limit_bytes
+            .map_or(self.host_memory_bytes, |limit| {
+                limit.min(self.host_memory_bytes)
+            })
+    }
+
+    /// Returns `true` if fewer CPUs are usable than the host has.
+    pub fn is_cpu_limited(&self) -> bool {
+        self.effective_cpus() < self.host_cpus.len()
+    }
+
+    /// Returns `true` if less memory is usable than the host has.
+    pub fn is_memory_limited(&self) -> bool {
+        self.effective_memory_bytes() < self.host_memory_bytes
+    }
+}
+
+/// Parses the `cpu.max` file of a cgroup v2, e.g. "250000 100000".
+///
+/// # Returns
+///
+/// * `Option<f64>` - The quota in CPUs, or `None` without a quota ("max").
+pub fn parse_cpu_max(text: &str) -> Option<f64> {
+    let mut fields = text.split_whitespace();
+    let quota: f64 = fields.next()?.parse().ok()?;
+    let period: f64 = fields.next().unwrap_or("100000").parse().ok()?;
+    Some(quota / period).filter(|_| period > 0.0)
+}
+
+/// Converts the `cpu.cfs_quota_us` and `cpu.cfs_period_us` files of a cgroup v1.
+///
+/// # Returns
+///
+/// * `Option<f64>` - The quota in CPUs, or `None` without a quota (-1).
+pub fn parse_cfs_quota(quota: &str, period: &str) -> Option<f64> {
+    let quota: f64 = quota.trim().parse().ok()?;
+    let period: f64 = period.trim().parse().ok()?;
+    Some(quota / period).filter(|_| quota > 0.0 && period > 0.0)
+}
+
+/// Parses a cgroup memory limit, from `memory.max` (v2) or `memory.limit_in_bytes` (v1).
+///
+/// # Arguments
+///
+/// * `text` - The contents of the file.
+/// * `host_memory_bytes` - The total memory of the host.
+///
+/// # Returns
+///
+/// * `Option<u64>` - The limit in bytes, or `None` for "max" and for the
+///   page-aligned maximum cgroup v1 writes when no limit is set.
+pub fn parse_memory_limit(text: &str, host_memory_bytes: u64) -> Option<u64> {
+    text.trim()
+        .parse()
+        .ok()
+        .filter(|limit| *limit < host_memory_bytes)
+}
+
+/// Builds the discovery section describing the limits of the cgroup of the process.
+///
+/// # Arguments
+///
+/// * `limits` - The resource limits of the process.
+///
+/// # Returns
+///
+/// * `DiscoverySection` - The container runtime, the cgroup and the effective CPU and memory limits.
+pub fn container_section(limits: &ResourceLimits) -> DiscoverySection {
+    let mut section = DiscoverySection::new("Container Limits");
+    section.push(
+        "Container runtime",
+        limits.runtime.as_deref().unwrap_or("none detected"),
+    );
+    section.push(
+        "cgroup",
+        match (limits.cgroup_version, &limits.cgroup_path) {
+            (Some(version), Some(path)) => format!("v{} {}", version, path),
+            (Some(version), None) => format!("v{}", version),
+            _ => "none".to_string(),
+        },
+    );
+    section.push(
+        "CPU quota",
+        limits
+            .cpu_quota
+            .map_or("none".to_string(), |quota| format!("{:.2} CPUs", quota)),
+    );
+    section.push(
+        "CPU set",
+        limits
+            .cpuset
+            .as_ref()
+            .map_or("all".to_string(), format_cpu_list),
+    );
+    section.push(
+        "Effective CPUs",
+        format!("{} of {}", limits.effective_cpus(), limits.host_cpus.len()),
+    );
+    section.push(
+        "Memory limit",
+        limits
+            .memory_limit_bytes
+            .map_or("none".to_string(), format_volume),
+    );
+    section.push(
+        "Effective memory",
+        format!(
+            "{} of {}",
+            format_volume(limits.effective_memory_bytes()),
+            format_volume(limits.host_memory_bytes)
+        ),
+    );
+    section
+}
@@ -0,0 +1,357 @@
+//! CPU Architecture Domain Entity
+//!
+//! This module provides the architecture-specific description of the CPU:
+//! the platform it runs, the classes of cores it is built from (performance
+//! and efficiency cores on big.LITTLE and hybrid designs), and the SIMD
+//! extensions available to workloads. Benchmark results on heterogeneous or
+//! SVE-capable CPUs can only be compared with this context.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::domain::cpu_topology::format_cpu_list;
+use crate::domain::discovery::DiscoverySection;
+use crate::domain::platform::{Arch, Platform};
+
+/// A group of cores sharing the same micro-architecture and performance level.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoreClass {
+    /// The role of the class, e.g. "performance" or "efficiency"; "uniform" when
+    /// every core is the same.
+    pub label: String,
+
+    /// The core model, e.g. "Neoverse-N1" or "E-core", if known.
+    pub model: Option<String>,
+
+    /// The number of logical CPUs in the class.
+    pub cpu_count: usize,
+
+    /// The logical CPUs in the class; empty when the platform does not say which
+    /// CPUs belong to which class, as on macOS.
+    pub cpus: BTreeSet<u32>,
+
+    /// The relative compute capacity reported by the kernel, where 1024 is the
+    /// fastest core of the system.
+    pub capacity: Option<u32>,
+}
+
+impl CoreClass {
+    /// Describes the class in one line, e.g. "4 x Cortex-X1 (CPUs 0-3, capacity 1024)".
+    pub fn describe(&self) -> String {
+        let mut details = Vec::new();
+        if !self.cpus.is_empty() {
+            details.push(format!("CPUs {}", format_cpu_list(&self.cpus)));
+        }
+        if let Some(capacity) = self.capacity {
+            details.push(format!("capacity {}", capacity));
+        }
+
+        let mut description = match &self.model {
+            Some(model) => format!("{} x {}", self.cpu_count, model),
+            None => format!("{} logical CPU(s)", self.cpu_count),
+        };
+        if !details.is_empty() {
+            description.push_str(&format!(" ({})", details.join(", ")));
+        }
+        description
+    }
+}
+
+/// The architecture-specific description of the CPU.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CpuArchitecture {
+    /// The platform OneForAll runs on.
+    pub platform: Platform,
+
+    /// The marketing name of the CPU, e.g. "Apple M2" or "AMD EPYC 9654", if known.
+    pub model_name: Option<String>,
+
+    /// The classes of cores, fastest first.
+    pub core_classes: Vec<CoreClass>,
+
+    /// The SIMD extensions available, e.g. "NEON" or "SVE2 (256-bit)".
+    pub simd: Vec<String>,
+}
+
+impl CpuArchitecture {
+    /// Returns `true` if the CPU mixes cores of different performance levels.
+    pub fn is_heterogeneous(&self) -> bool {
+        self.core_classes.len() > 1
+    }
+
+    /// Returns the class of cores with a label, e.g. "performance" or "efficiency".
+    pub fn core_class(&self, label: &str) -> Option<&CoreClass> {
+        self.core_classes.iter().find(|class| class.label == label)
+    }
+
+    /// Builds the discovery section describing the CPU architecture.
+    pub fn discovery_section(&self) -> DiscoverySection {
+        let mut section = DiscoverySection::new("CPU Architecture");
+        section.push("Platform", &self.platform);
+        if let Some(model_name) = &self.model_name {
+            section.push("Model", model_name);
+        }
+        for class in &self.core_classes {
+            section.push(
+                &format!("{} cores", capitalize(&class.label)),
+                class.describe(),
+            );
+        }
+        section.push("SIMD", self.simd_summary());
+        section
+    }
+
+    /// Renders the architecture as a short block for benchmark reports.
+    ///
+    /// On heterogeneous CPUs the core classes are listed, since a result depends
+    /// on which class the scheduler placed the workers on.
+    pub fn report(&self) -> String {
+        let mut output = format!(
+            "Architecture: {}{}\n",
+            self.platform,
+            self.model_name
+                .as_ref()
+                .map(|name| format!(" ({})", name))
+                .unwrap_or_default()
+        );
+        if self.is_heterogeneous() {
+            for class in &self.core_classes {
+                output.push_str(&format!("  {:<12} {}\n", class.label, class.describe()));
+            }
+        }
+        output.push_str(&format!("SIMD: {}\n", self.simd_summary()));
+        output
+    }
+
+    /// Joins the SIMD extensions, or says none were detected.
+    fn simd_summary(&self) -> String {
+        if self.simd.is_empty() {
+            "none detected".to_string()
+        } else {
+            self.simd.join(", ")
+        }
+    }
+}
+
+/// Upper-cases the first letter of `text`.
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Returns the name of an Arm core from the implementer and part numbers found
+/// in `/proc/cpuinfo` or the MIDR register.
+///
+/// # Arguments
+///
+/// * `implementer` - The implementer code, e.g. `0x41` for Arm Ltd.
+/// * `part` - The part number, e.g. `0xd0c` for Neoverse-N1.
+///
+/// # Returns
+///
+/// * `Option<&'static str>` - The core name, or `None` for an unknown part.
+pub fn arm_core_name(implementer: u32, part: u32) -> Option<&'static str> {
+    let name = match (implementer, part) {
+        (0x41, 0xd03) => "Cortex-A53",
+        (0x41, 0xd04) => "Cortex-A35",
+        (0x41, 0xd05) => "Cortex-A55",
+        (0x41, 0xd07) => "Cortex-A57",
+        (0x41, 0xd08) => "Cortex-A72",
+        (0x41, 0xd09) => "Cortex-A73",
+        (0x41, 0xd0a) => "Cortex-A75",
+        (0x41, 0xd0b) => "Cortex-A76",
+        (0x41, 0xd0c) => "Neoverse-N1",
+        (0x41, 0xd0d) => "Cortex-A77",
+        (0x41, 0xd40) => "Neoverse-V1",
+        (0x41, 0xd41) => "Cortex-A78",
+        (0x41, 0xd44) => "Cortex-X1",
+        (0x41, 0xd46) => "Cortex-A510",
+        (0x41, 0xd47) => "Cortex-A710",
+        (0x41, 0xd48) => "Cortex-X2",
+        (0x41, 0xd49) => "Neoverse-N2",
+        (0x41, 0xd4a) => "Neoverse-E1",
+        (0x41, 0xd4d) => "Cortex-A715",
+        (0x41, 0xd4e) => "Cortex-X3",
+        (0x41, 0xd4f) => "Neoverse-V2",
+        (0x41, 0xd80) => "Cortex-A520",
+        (0x41, 0xd81) => "Cortex-A720",
+        (0x41, 0xd82) => "Cortex-X4",
+        (0x41, 0xd84) => "Neoverse-V3",
+        (0x41, 0xd8e) => "Neoverse-N3",
+        (0x46, 0x001) => "A64FX",
+        (0x48, 0xd01) => "TaiShan v110",
+        (0x61, _) => "Apple",
+        (0xc0, 0xac3) => "AmpereOne",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// The per-CPU identification read from `/proc/cpuinfo`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CpuInfoEntry {
+    /// The `model name` line, present on x86.
+    pub model_name: Option<String>,
+
+    /// The `CPU implementer` code, present on Arm.
+    pub implementer: Option<u32>,
+
+    /// The `CPU part` number, present on Arm.
+    pub part: Option<u32>,
+
+    /// The `microcode` revision, e.g. "0x2b000571", present on x86.
+    pub microcode: Option<String>,
+
+    /// The `flags` (x86) or `Features` (Arm) list.
+    pub features: BTreeSet<String>,
+}
+
+/// Parses `/proc/cpuinfo` into one entry per logical CPU.
+///
+/// # Arguments
+///
+/// * `cpuinfo` - The contents of `/proc/cpuinfo`.
+///
+/// # Returns
+///
+/// * `BTreeMap<u32, CpuInfoEntry>` - The entries keyed by logical CPU id.
+pub fn parse_cpuinfo(cpuinfo: &str) -> BTreeMap<u32, CpuInfoEntry> {
+    let mut entries = BTreeMap::new();
+    let mut current: Option<(u32, CpuInfoEntry)> = None;
+
+    for line in cpuinfo.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        let parse_hex = |v: &str| u32::from_str_radix(v.trim_start_matches("0x"), 16).ok();
+
+        if key == "processor" {
+            if let Some((id, entry)) = current.take() {
+                entries.insert(id, entry);
+            }
+            current = value.parse().ok().map(|id| (id, CpuInfoEntry::default()));
+            continue;
+        }
+        let Some((_, entry)) = current.as_mut() else {
+            continue;
+        };
+        match key {
+            "model name" => entry.model_name = Some(value.to_string()),
+            "CPU implementer" => entry.implementer = parse_hex(value),
+            "CPU part" => entry.part = parse_hex(value),
+            "microcode" => entry.microcode = Some(value.to_string()),
+            "flags" | "Features" => {
+                entry.features = value.split_whitespace().map(str::to_string).collect()
+            }
+            _ => {}
+        }
+    }
+    if let Some((id, entry)) = current {
+        entries.insert(id, entry);
+    }
+
+    entries
+}
+
+/// Groups logical CPUs into core classes by capacity and core model.
+///
+/// # Arguments
+///
+/// * `cpus` - Each logical CPU with its kernel capacity and core model, if known.
+///
+/// # Returns
+///
+/// * `Vec<CoreClass>` - The classes, fastest first. A single class is labelled
+///   "uniform"; otherwise the fastest is "performance", the slowest "efficiency"
+///   and any in between "mid".
+pub fn group_core_classes(cpus: &[(u32, Option<u32>, Option<String>)]) -> Vec<CoreClass> {
+    let mut groups: BTreeMap<(Option<u32>, Option<String>), BTreeSet<u32>> = BTreeMap::new();
+    for (id, capacity, model) in cpus {
+        groups
+            .entry((*capacity, model.clone()))
+            .or_default()
+            .insert(*id);
+    }
+
+    // Fastest first: by capacity, then by lowest CPU id for a stable order.
+    let mut classes: Vec<CoreClass> = groups
+        .into_iter()
+        .map(|((capacity, model), cpus)| CoreClass {
+            label: String::new(),
+            model,
+            cpu_count: cpus.len(),
+            cpus,
+            capacity,
+        })
+        .collect();
+    classes.sort_by(|a, b| {
+        b.capacity
+            .cmp(&a.capacity)
+            .then_with(|| a.cpus.first().cmp(&b.cpus.first()))
+    });
+
+    let last = classes.len().saturating_sub(1);
+    for (index, class) in classes.iter_mut().enumerate() {
+        class.label = match (index, last) {
+            (_, 0) => "uniform",
+            (0, _) => "performance",
+            (i, _) if i == last => "efficiency",
+            _ => "mid",
+        }
+        .to_string();
+    }
+    classes
+}
+
+/// Lists the SIMD extensions advertised by a CPU feature list.
+///
+/// # Arguments
+///
+/// * `arch` - The CPU architecture the features belong to.
+/// * `features` - The `flags` or `Features` list from `/proc/cpuinfo`, or its equivalent.
+/// * `sve_vector_bits` - The default SVE vector length, if known.
+///
+/// # Returns
+///
+/// * `Vec<String>` - The extensions, oldest first, e.g. `["NEON", "SVE (256-bit)"]`.
+pub fn simd_extensions(
+    arch: &Arch,
+    features: &BTreeSet<String>,
+    sve_vector_bits: Option<u32>,
+) -> Vec<String> {
+    let mut simd = Vec::new();
+    if *arch == Arch::X86_64 {
+        let extensions = [
+            ("sse4_2", "SSE4.2"),
+            ("avx", "AVX"),
+            ("avx2", "AVX2"),
+            ("avx512f", "AVX-512"),
+        ];
+        for (flag, name) in extensions {
+            if features.contains(flag) {
+                simd.push(name.to_string());
+            }
+        }
+    }
+    if *arch == Arch::Aarch64 {
+        let vector = sve_vector_bits
+            .map(|bits| format!(" ({}-bit)", bits))
+            .unwrap_or_default();
+        if features.contains("asimd") {
+            simd.push("NEON".to_string());
+        }
+        if features.contains("sve") {
+            simd.push(format!("SVE{}", vector));
+        }
+        if features.contains("sve2") {
+            simd.push(format!("SVE2{}", vector));
+        }
+        if features.contains("sme") {
+            simd.push("SME".to_string());
+        }
+    }
+    simd
+}
@@ -0,0 +1,222 @@
+//! CPU Features Domain Entity
+//!
+//! This module provides the instruction set extensions, microcode revision and
+//! speculative-execution mitigations of the CPU. All three change how fast the
+//! same hardware runs a benchmark: AVX-512 or AMX may be fused off or disabled
+//! by the OS, a microcode update can change performance, and mitigations add
+//! overhead to system calls and context switches.
+
+use std::collections::BTreeSet;
+
+use crate::domain::discovery::DiscoverySection;
+
+/// The CPUID registers that hold the extension bits, captured once per machine.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CpuidRegisters {
+    /// ECX of leaf 1.
+    pub leaf1_ecx: u32,
+
+    /// EBX of leaf 7, sub-leaf 0.
+    pub leaf7_ebx: u32,
+
+    /// ECX of leaf 7, sub-leaf 0.
+    pub leaf7_ecx: u32,
+
+    /// EDX of leaf 7, sub-leaf 0.
+    pub leaf7_edx: u32,
+
+    /// EAX of leaf 7, sub-leaf 1.
+    pub leaf7_1_eax: u32,
+
+    /// The register state the OS saves on context switches (XCR0), if XSAVE is
+    /// enabled; an extension is unusable unless the OS saves its registers.
+    pub xcr0: Option<u64>,
+}
+
+/// The CPUID register an extension bit lives in.
+#[derive(Debug, Clone, Copy)]
+enum Register {
+    Leaf1Ecx,
+    Leaf7Ebx,
+    Leaf7Ecx,
+    Leaf7Edx,
+    Leaf7_1Eax,
+}
+
+/// The XCR0 bits the OS must enable for SSE and AVX state.
+const XCR0_AVX: u64 = 0x6;
+
+/// The XCR0 bits the OS must enable for AVX-512 state (AVX plus opmask and ZMM).
+const XCR0_AVX512: u64 = 0xe6;
+
+/// The XCR0 bits the OS must enable for AMX tile state.
+const XCR0_AMX: u64 = 0x6_0000;
+
+/// The x86 extensions reported, in order: register, bit, name and the XCR0 state
+/// the OS must enable for it (0 when none is needed).
+const X86_EXTENSIONS: [(Register, u32, &str, u64); 31] = [
+    (Register::Leaf1Ecx, 19, "SSE4.1", 0),
+    (Register::Leaf1Ecx, 20, "SSE4.2", 0),
+    (Register::Leaf1Ecx, 25, "AES-NI", 0),
+    (Register::Leaf7Ebx, 29, "SHA", 0),
+    (Register::Leaf7Ebx, 3, "BMI1", 0),
+    (Register::Leaf7Ebx, 8, "BMI2", 0),
+    (Register::Leaf1Ecx, 28, "AVX", XCR0_AVX),
+    (Register::Leaf1Ecx, 12, "FMA", XCR0_AVX),
+    (Register::Leaf1Ecx, 29, "F16C", XCR0_AVX),
+    (Register::Leaf7Ebx, 5, "AVX2", XCR0_AVX),
+    (Register::Leaf7_1Eax, 4, "AVX-VNNI", XCR0_AVX),
+    (Register::Leaf7Ecx, 8, "GFNI", 0),
+    (Register::Leaf7Ecx, 9, "VAES", XCR0_AVX),
+    (Register::Leaf7Ebx, 16, "AVX-512F", XCR0_AVX512),
+    (Register::Leaf7Ebx, 17, "AVX-512DQ", XCR0_AVX512),
+    (Register::Leaf7Ebx, 28, "AVX-512CD", XCR0_AVX512),
+    (Register::Leaf7Ebx, 30, "AVX-512BW", XCR0_AVX512),
+    (Register::Leaf7Ebx, 31, "AVX-512VL", XCR0_AVX512),
+    (Register::Leaf7Ebx, 21, "AVX-512IFMA", XCR0_AVX512),
+    (Register::Leaf7Ecx, 1, "AVX-512VBMI", XCR0_AVX512),
+    (Register::Leaf7Ecx, 6, "AVX-512VBMI2", XCR0_AVX512),
+    (Register::Leaf7Ecx, 11, "AVX-512VNNI", XCR0_AVX512),
+    (Register::Leaf7Ecx, 12, "AVX-512BITALG", XCR0_AVX512),
+    (Register::Leaf7Ecx, 14, "AVX-512VPOPCNTDQ", XCR0_AVX512),
+    (Register::Leaf7_1Eax, 5, "AVX-512BF16", XCR0_AVX512),
+    (Register::Leaf7Edx, 23, "AVX-512FP16", XCR0_AVX512),
+    (Register::Leaf7Edx, 24, "AMX-TILE", XCR0_AMX),
+    (Register::Leaf7Edx, 22, "AMX-BF16", XCR0_AMX),
+    (Register::Leaf7Edx, 25, "AMX-INT8", XCR0_AMX),
+    (Register::Leaf7_1Eax, 21, "AMX-FP16", XCR0_AMX),
+    (Register::Leaf7Edx, 14, "SERIALIZE", 0),
+];
+
+impl CpuidRegisters {
+    /// Decodes the x86 instruction set extensions the CPU reports and the OS enables.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<String>` - The extensions, e.g. `["SSE4.2", "AVX2", "AVX-512F"]`.
+    pub fn x86_extensions(&self) -> Vec<String> {
+        // Without XSAVE the OS saves no extended state, so no AVX-class extension is usable.
+        let enabled_state = self.xcr0.unwrap_or(0);
+
+        X86_EXTENSIONS
+            .iter()
+            .filter(|(register, bit, _, state)| {
+                let value = match register {
+                    Register::Leaf1Ecx => self.leaf1_ecx,
+                    Register::Leaf7Ebx => self.leaf7_ebx,
+                    Register::Leaf7Ecx => self.leaf7_ecx,
+                    Register::Leaf7Edx => self.leaf7_edx,
+                    Register::Leaf7_1Eax => self.leaf7_1_eax,
+                };
+                value & (1 << bit) != 0 && enabled_state & state == *state
+            })
+            .map(|(_, _, name, _)| name.to_string())
+            .collect()
+    }
+}
+
+/// How a CPU is affected by a speculative-execution vulnerability.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum VulnerabilityState {
+    NotAffected,
+    Mitigated,
+    Vulnerable,
+    Unknown,
+}
+
+/// A speculative-execution vulnerability and the kernel's verdict on it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vulnerability {
+    /// The sysfs name, e.g. "spectre_v2".
+    pub name: String,
+
+    /// The kernel's status line, e.g. "Mitigation: Retpolines".
+    pub status: String,
+}
+
+impl Vulnerability {
+    /// Classifies the kernel's status line.
+    pub fn state(&self) -> VulnerabilityState {
+        if self.status.starts_with("Not affected") {
+            VulnerabilityState::NotAffected
+        } else if self.status.starts_with("Mitigation") {
+            VulnerabilityState::Mitigated
+        } else if self.status.starts_with("Vulnerable") {
+            VulnerabilityState::Vulnerable
+        } else {
+            VulnerabilityState::Unknown
+        }
+    }
+}
+
+/// The instruction set extensions, microcode and mitigations of the CPU.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CpuFeatures {
+    /// The instruction set extensions usable by workloads.
+    pub extensions: Vec<String>,
+
+    /// The microcode revisions loaded, one per distinct value; more than one
+    /// means the CPUs of the machine run different microcode.
+    pub microcode: BTreeSet<String>,
+
+    /// The vulnerabilities the kernel reports on, sorted by name.
+    pub vulnerabilities: Vec<Vulnerability>,
+}
+
+impl CpuFeatures {
+    /// Builds the discovery section describing the CPU features.
+    ///
+    /// Vulnerabilities the CPU is not affected by are only counted, so the
+    /// section lists just the ones that cost performance or leave a risk.
+    pub fn discovery_section(&self) -> DiscoverySection {
+        let mut section = DiscoverySection::new("CPU Features");
+        section.push(
+            "Extensions",
+            if self.extensions.is_empty() {
+                "none detected".to_string()
+            } else {
+                self.extensions.join(" ")
+            },
+        );
+
+        let microcode = match self.microcode.len() {
+            0 => "not reported".to_string(),
+            1 => self.microcode.iter().cloned().collect(),
+            _ => format!(
+                "MIXED ({})",
+                self.microcode
+                    .iter()
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+        section.push("Microcode", microcode);
+
+        if self.vulnerabilities.is_empty() {
+            section.push("Vulnerabilities", "not reported");
+            return section;
+        }
+        let count = |state: VulnerabilityState| {
+            self.vulnerabilities
+                .iter()
+                .filter(|v| v.state() == state)
+                .count()
+        };
+        section.push(
+            "Vulnerabilities",
+            format!(
+                "{} mitigated, {} vulnerable, {} not affected",
+                count(VulnerabilityState::Mitigated),
+                count(VulnerabilityState::Vulnerable),
+                count(VulnerabilityState::NotAffected)
+            ),
+        );
+        for vulnerability in &self.vulnerabilities {
+            if vulnerability.state() != VulnerabilityState::NotAffected {
+                section.push(&format!("  {}", vulnerability.name), &vulnerability.status);
+            }
+        }
+        section
+    }
+}
@@ -0,0 +1,217 @@
+//! CPU Frequency Domain Entity
+//!
+//! This module provides domain entities for per-core clock speed, governor,
+//! and C-state/P-state residency samples, along with the report that shows
+//! turbo behavior and throttling over the course of a run.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Cumulative time a core has spent in one idle state (C-state).
+#[derive(Debug, Clone)]
+pub struct IdleStateResidency {
+    /// The idle state name as reported by cpuidle, e.g. "C6".
+    pub name: String,
+
+    /// Cumulative residency in microseconds since boot.
+    pub time_us: u64,
+}
+
+/// A point-in-time frequency reading for a single logical CPU.
+#[derive(Debug, Clone)]
+pub struct CoreFrequency {
+    /// The logical CPU number.
+    pub cpu: u32,
+
+    /// The current clock speed in MHz, if the platform exposes it.
+    pub frequency_mhz: Option<u32>,
+
+    /// The active frequency scaling governor, e.g. "performance".
+    pub governor: Option<String>,
+
+    /// The guaranteed base frequency in MHz, if the driver reports it.
+    pub base_frequency_mhz: Option<u32>,
+
+    /// Cumulative C-state residencies.
+    pub idle_states: Vec<IdleStateResidency>,
+
+    /// Cumulative P-state residencies as (frequency MHz, time in 10 ms units).
+    pub pstate_residency: Vec<(u32, u64)>,
+}
+
+/// Percentage of samples below base frequency that is reported as throttling.
+const THROTTLE_REPORT_THRESHOLD: f64 = 5.0;
+
+/// Renders the frequency samples collected during a run.
+///
+/// The report contains a timeline of the average/min/max clock across all cores,
+/// a per-core summary with the share of samples spent below base frequency, and
+/// the C-state and P-state residency accumulated over the run.
+///
+/// # Arguments
+///
+/// * `samples` - Frequency snapshots of every core with their offset into the run.
+///
+/// # Returns
+///
+/// * `String` - The formatted report, or a short notice if nothing was sampled.
+pub fn format_frequency_report(samples: &[(Duration, Vec<CoreFrequency>)]) -> String {
+    let has_frequencies = samples
+        .iter()
+        .any(|(_, cores)| cores.iter().any(|c| c.frequency_mhz.is_some()));
+    if !has_frequencies {
+        let mut output =
+            String::from("CPU frequency: clock speeds are not reported on this platform\n");
+        output.push_str(&format_residencies(samples));
+        return output;
+    }
+
+    let mut output = String::from("CPU frequency timeline\n");
+    output.push_str("  Elapsed     Avg MHz   Min MHz   Max MHz\n");
+    for (elapsed, cores) in samples {
+        let readings: Vec<u32> = cores.iter().filter_map(|c| c.frequency_mhz).collect();
+        if readings.is_empty() {
+            continue;
+        }
+        let avg = readings.iter().map(|f| *f as u64).sum::<u64>() / readings.len() as u64;
+        output.push_str(&format!(
+            "  {:>6}s  {:>9}  {:>8}  {:>8}\n",
+            elapsed.as_secs(),
+            avg,
+            readings.iter().min().copied().unwrap_or(0),
+            readings.iter().max().copied().unwrap_or(0)
+        ));
+    }
+
+    // Group the readings per core to summarize clocks and throttling.
+    let mut per_core: BTreeMap<u32, Vec<&CoreFrequency>> = BTreeMap::new();
+    for (_, cores) in samples {
+        for core in cores {
+            per_core.entry(core.cpu).or_default().push(core);
+        }
+    }
+
+    output.push_str("\nPer-core summary\n");
+    output.push_str("  CPU  Governor       Min MHz   Avg MHz   Max MHz   Below base\n");
+    for (cpu, readings) in &per_core {
+        let freqs: Vec<u32> = readings.iter().filter_map(|r| r.frequency_mhz).collect();
+        if freqs.is_empty() {
+            continue;
+        }
+        let governor = readings
+            .iter()
+            .find_map(|r| r.governor.clone())
+            .unwrap_or_else(|| "-".to_string());
+        let avg = freqs.iter().map(|f| *f as u64).sum::<u64>() / freqs.len() as u64;
+        let below_base = match readings.iter().find_map(|r| r.base_frequency_mhz) {
+            Some(base) => {
+                let below = freqs.iter().filter(|f| **f < base).count();
+                let percent = below as f64 * 100.0 / freqs.len() as f64;
+                if percent > THROTTLE_REPORT_THRESHOLD {
+                    format!("{:.0}% THROTTLED", percent)
+                } else {
+                    format!("{:.0}%", percent)
+                }
+            }
+            None => "n/a".to_string(),
+        };
+        output.push_str(&format!(
+            "  {:>3}  {:<12}  {:>8}  {:>8}  {:>8}   {}\n",
+            cpu,
+            governor,
+            freqs.iter().min().copied().unwrap_or(0),
+            avg,
+            freqs.iter().max().copied().unwrap_or(0),
+            below_base
+        ));
+    }
+
+    output.push_str(&format_residencies(samples));
+    output
+}
+
+/// Summarizes C-state and P-state residency between the first and last snapshot.
+fn format_residencies(samples: &[(Duration, Vec<CoreFrequency>)]) -> String {
+    match (samples.first(), samples.last()) {
+        (Some((first_at, first)), Some((last_at, last))) => {
+            let wall = last_at.saturating_sub(*first_at);
+            format_cstate_residency(first, last, wall) + &format_pstate_residency(first, last)
+        }
+        _ => String::new(),
+    }
+}
+
+/// Summarizes the average C-state residency across cores between two snapshots.
+fn format_cstate_residency(
+    first: &[CoreFrequency],
+    last: &[CoreFrequency],
+    wall: Duration,
+) -> String {
+    let wall_us = wall.as_micros() as f64;
+    if wall_us <= 0.0 {
+        return String::new();
+    }
+
+    let mut totals: BTreeMap<String, f64> = BTreeMap::new();
+    let mut cores = 0usize;
+    for end in last {
+        let start = match first.iter().find(|c| c.cpu == end.cpu) {
+            Some(start) => start,
+            None => continue,
+        };
+        cores += 1;
+        for state in &end.idle_states {
+            let before = start
+                .idle_states
+                .iter()
+                .find(|s| s.name == state.name)
+                .map_or(0, |s| s.time_us);
+            *totals.entry(state.name.clone()).or_default() +=
+                state.time_us.saturating_sub(before) as f64;
+        }
+    }
+
+    if totals.is_empty() || cores == 0 {
+        return String::new();
+    }
+
+    let states = totals
+        .iter()
+        .map(|(name, time)| format!("{} {:.1}%", name, time * 100.0 / wall_us / cores as f64))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("\nC-state residency (avg per core): {}\n", states)
+}
+
+/// Summarizes the share of time spent in each P-state between two snapshots.
+fn format_pstate_residency(first: &[CoreFrequency], last: &[CoreFrequency]) -> String {
+    let mut totals: BTreeMap<u32, u64> = BTreeMap::new();
+    for end in last {
+        let start = match first.iter().find(|c| c.cpu == end.cpu) {
+            Some(start) => start,
+            None => continue,
+        };
+        for (freq, time) in &end.pstate_residency {
+            let before = start
+                .pstate_residency
+                .iter()
+                .find(|(f, _)| f == freq)
+                .map_or(0, |(_, t)| *t);
+            *totals.entry(*freq).or_default() += time.saturating_sub(before);
+        }
+    }
+
+    let total: u64 = totals.values().sum();
+    if total == 0 {
+        return String::new();
+    }
+
+    let states = totals
+        .iter()
+        .rev()
+        .filter(|(_, time)| **time > 0)
+        .map(|(freq, time)| format!("{} MHz {:.1}%", freq, *time as f64 * 100.0 / total as f64))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("P-state residency (all cores): {}\n", states)
+}
@@ -0,0 +1,249 @@
+//! CPU Topology Domain Entity
+//!
+//! This module provides domain entities describing how logical CPUs map onto
+//! physical cores, sockets (packages), and NUMA nodes. The topology is used to
+//! place stress workers on specific parts of the machine instead of letting
+//! the scheduler migrate them freely.
+
+use std::collections::BTreeSet;
+
+use crate::domain::discovery::DiscoverySection;
+
+/// The most CPUs a Linux kernel supports (`NR_CPUS` with `CONFIG_MAXSMP`); CPU
+/// ids in a list must be below it, so a typo such as `0-4294967295` is
+/// rejected instead of expanded into billions of ids.
+pub const MAX_CPUS: u32 = 8192;
+
+/// Represents a single logical CPU (hardware thread) as seen by the operating system.
+#[derive(Debug, Clone)]
+pub struct LogicalCpu {
+    /// The logical CPU number, e.g. `3` for `/sys/devices/system/cpu/cpu3`.
+    pub id: u32,
+
+    /// The physical core identifier within the package.
+    pub core_id: u32,
+
+    /// The physical package (socket) the CPU belongs to.
+    pub package_id: u32,
+
+    /// The NUMA node the CPU belongs to, if the platform exposes NUMA information.
+    pub numa_node: Option<u32>,
+
+    /// All logical CPUs sharing the same physical core (SMT siblings), including this one.
+    pub thread_siblings: BTreeSet<u32>,
+}
+
+impl LogicalCpu {
+    /// Returns `true` if this CPU is the first hardware thread of its physical core.
+    ///
+    /// Selecting only primary threads yields one worker per physical core, which
+    /// avoids two stressors competing for the same execution units.
+    pub fn is_primary_thread(&self) -> bool {
+        self.thread_siblings
+            .first()
+            .is_none_or(|first| *first == self.id)
+    }
+}
+
+/// Represents the CPU topology of the machine.
+#[derive(Debug, Clone, Default)]
+pub struct CpuTopology {
+    /// Every online logical CPU, sorted by id.
+    pub cpus: Vec<LogicalCpu>,
+}
+
+impl CpuTopology {
+    /// Returns the ids of every online logical CPU.
+    pub fn all_cpu_ids(&self) -> BTreeSet<u32> {
+        self.cpus.iter().map(|cpu| cpu.id).collect()
+    }
+
+    /// Returns the ids of the logical CPUs attached to the given NUMA node.
+    pub fn cpus_in_numa_node(&self, node: u32) -> BTreeSet<u32> {
+        self.cpus
+            .iter()
+            .filter(|cpu| cpu.numa_node == Some(node))
+            .map(|cpu| cpu.id)
+            .collect()
+    }
+
+    /// Returns the ids of one logical CPU per physical core (no SMT siblings).
+    pub fn physical_cpu_ids(&self) -> BTreeSet<u32> {
+        // Track (package, core) pairs as well, in case sibling lists are missing.
+        let mut seen_cores = BTreeSet::new();
+        self.cpus
+            .iter()
+            .filter(|cpu| {
+                cpu.is_primary_thread() && seen_cores.insert((cpu.package_id, cpu.core_id))
+            })
+            .map(|cpu| cpu.id)
+            .collect()
+    }
+
+    /// Returns the number of distinct physical packages (sockets).
+    pub fn package_count(&self) -> usize {
+        self.cpus
+            .iter()
+            .map(|cpu| cpu.package_id)
+            .collect::<BTreeSet<_>>()
+            .len()
+    }
+
+    /// Builds the discovery section summarizing the CPU topology.
+    pub fn discovery_section(&self) -> DiscoverySection {
+        let mut section = DiscoverySection::new("CPU Topology");
+        section.push("Packages", self.package_count());
+        section.push("Physical cores", self.physical_cpu_ids().len());
+        section.push("Logical CPUs", self.cpus.len());
+        section.push("Online CPUs", format_cpu_list(&self.all_cpu_ids()));
+        section
+    }
+}
+
+/// Describes where stress workers should be placed.
+///
+/// Each constraint narrows the set of eligible CPUs; constraints that are not
+/// set leave the selection untouched.
+#[derive(Debug, Clone, Default)]
+pub struct AffinitySpec {
+    /// Explicit list of logical CPUs, e.g. parsed from `--pin 0-7`.
+    pub pin: Option<BTreeSet<u32>>,
+
+    /// Restrict placement to the CPUs of a single NUMA node.
+    pub numa_node: Option<u32>,
+
+    /// Use only one hardware thread per physical core.
+    pub physical_only: bool,
+}
+
+impl AffinitySpec {
+    /// Returns `true` if no placement constraint has been requested.
+    pub fn is_unconstrained(&self) -> bool {
+        self.pin.is_none() && self.numa_node.is_none() && !self.physical_only
+    }
+
+    /// Resolves the placement constraints against a discovered topology.
+    ///
+    /// # Arguments
+    ///
+    /// * `topology` - The discovered CPU topology of the machine.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(BTreeSet<u32>)` - The logical CPUs workers should be pinned to.
+    /// * `Err(String)` - If a constraint references CPUs or nodes that do not exist,
+    ///   or the constraints leave no CPU to run on.
+    pub fn resolve(&self, topology: &CpuTopology) -> Result<BTreeSet<u32>, String> {
+        let mut selected = topology.all_cpu_ids();
+
+        if let Some(pin) = &self.pin {
+            let unknown: Vec<u32> = pin.difference(&selected).copied().collect();
+            if !unknown.is_empty() {
+                return Err(format!(
+                    "Requested CPUs {} are not online on this machine",
+                    format_cpu_list(&unknown.into_iter().collect())
+                ));
+            }
+            selected = pin.clone();
+        }
+
+        if let Some(node) = self.numa_node {
+            let node_cpus = topology.cpus_in_numa_node(node);
+            if node_cpus.is_empty() {
+                return Err(format!("NUMA node {} has no online CPUs", node));
+            }
+            selected = selected.intersection(&node_cpus).copied().collect();
+        }
+
+        if self.physical_only {
+            let physical = topology.physical_cpu_ids();
+            selected = selected.intersection(&physical).copied().collect();
+        }
+
+        if selected.is_empty() {
+            return Err("The requested placement constraints leave no CPUs to run on".to_string());
+        }
+
+        Ok(selected)
+    }
+}
+
+/// Parses a Linux-style CPU list such as `0-3,8,10-11`.
+///
+/// # Arguments
+///
+/// * `list` - The CPU list string, as used by sysfs and `taskset`.
+///
+/// # Returns
+///
+/// * `Ok(BTreeSet<u32>)` - The set of CPU ids described by the list.
+/// * `Err(String)` - If the list contains malformed entries, reversed ranges or
+///   CPU ids of `MAX_CPUS` or above.
+pub fn parse_cpu_list(list: &str) -> Result<BTreeSet<u32>, String> {
+    let mut cpus = BTreeSet::new();
+
+    for part in list
+        .trim()
+        .split(',')
+        .filter(|part| !part.trim().is_empty())
+    {
+        let part = part.trim();
+        match part.split_once('-') {
+            Some((start, end)) => {
+                let start: u32 = start
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("Invalid CPU range start in '{}'", part))?;
+                let end: u32 = end
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("Invalid CPU range end in '{}'", part))?;
+                if start > end {
+                    return Err(format!("CPU range '{}' is reversed", part));
+                }
+                if end >= MAX_CPUS {
+                    return Err(format!(
+                        "CPU range '{}' goes beyond the {} CPUs Linux supports",
+                        part, MAX_CPUS
+                    ));
+                }
+                cpus.extend(start..=end);
+            }
+            None => {
+                let cpu: u32 = part
+                    .parse()
+                    .map_err(|_| format!("Invalid CPU id '{}'", part))?;
+                if cpu >= MAX_CPUS {
+                    return Err(format!(
+                        "CPU id {} is beyond the {} CPUs Linux supports",
+                        cpu, MAX_CPUS
+                    ));
+                }
+                cpus.insert(cpu);
+            }
+        }
+    }
+
+    Ok(cpus)
+}
+
+/// Formats a set of CPU ids back into the compact `0-3,8` list notation.
+pub fn format_cpu_list(cpus: &BTreeSet<u32>) -> String {
+    let mut ranges: Vec<String> = Vec::new();
+    let mut iter = cpus.iter().copied().peekable();
+
+    while let Some(start) = iter.next() {
+        let mut end = start;
+        // Extend the range while the next id is contiguous.
+        while iter.peek() == Some(&(end + 1)) {
+            end = iter.next().unwrap_or(end);
+        }
+        if start == end {
+            ranges.push(start.to_string());
+        } else {
+            ranges.push(format!("{}-{}", start, end));
+        }
+    }
+
+    ranges.join(",")
+}
@@ -0,0 +1,529 @@
+//! Database Domain Entity
+//!
+//! This module provides the database settings, read from the `[database]`
+//! section of `oneforall.toml`, and the key schema of the records kept behind
+//! the key/value interface of `DatabasePort`:
+//!
+//! * results under "kind:started_at" or "kind:started_at:name", e.g.
+//!   "profile:2024-05-02T08:00:00.000000Z:burn-in-24h";
+//! * readings of collectors under "sample:collector:recorded_at", each stored
+//!   with its wall-clock and monotonic timestamps;
+//! * hardware inventories under "inventory:collected_at";
+//! * the inventories and log messages of runs imported from other machines
+//!   under "archived-inventory:run" and "archived-logs:run".
+//!
+//! Times are in UTC with a fixed width, so keys sort chronologically and a
+//! time range is a key range. The SQLite backend keeps each kind in its own
+//! table, so the history of a machine can be queried with SQL.
+//!
+//! Readings are written in batches from the buffer of `[database.write_buffer]`,
+//! which journals them so a crash loses none, rather than one write each.
+//!
+//! Readings accumulate for as long as Overwatch runs, so the retention policy
+//! of `[database.retention]` deletes the old ones and keeps the database under
+//! a size limit. Results and inventories are never deleted. The sub-second
+//! frames of `overwatch --burst-interval` are downsampled once older than
+//! `burst_age`, rather than kept at full resolution until deleted, and the
+//! readings of the history metrics are rolled up per minute and per hour, as
+//! the readings of collectors such as "rollup_1m_cpu" and "rollup_1h_cpu",
+//! before they are deleted.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use common::domain::footprint::FOOTPRINT_COLLECTOR;
+use common::domain::metric::METRICS_COLLECTOR;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::domain::endurance::parse_size;
+use crate::domain::grpc::RESULT_KINDS;
+use crate::domain::microburst::DEFAULT_BURST_AGE;
+use crate::domain::profile::deserialize_duration;
+use crate::domain::rollup::DEFAULT_ROLLUP_AGE;
+use crate::domain::time_sync::SampleTime;
+use crate::domain::virtualization::STEAL_COLLECTOR;
+
+/// The sled database used when no path is configured.
+pub const DEFAULT_SLED_PATH: &str = "OneForAll_database_file.db";
+
+/// The SQLite database used when no path is configured.
+pub const DEFAULT_SQLITE_PATH: &str = "OneForAll_database.sqlite";
+
+/// The key prefix of hardware inventories, followed by the time they were collected.
+pub const INVENTORY_PREFIX: &str = "inventory:";
+
+/// The key prefix of the inventory of the machine an imported run ran on, followed by the run key.
+pub const ARCHIVED_INVENTORY_PREFIX: &str = "archived-inventory:";
+
+/// The key prefix of the log messages of an imported run, followed by the run key.
+pub const ARCHIVED_LOGS_PREFIX: &str = "archived-logs:";
+
+/// The key prefix of the checkpoints of unfinished profile runs, followed by the profile name.
+pub const CHECKPOINT_PREFIX: &str = "checkpoint:";
+
+/// The key prefix of collector readings, followed by the collector and the time of the reading.
+pub const SAMPLE_PREFIX: &str = "sample:";
+
+/// The key read to check that the database is available; it is never written.
+pub const HEALTH_PROBE_KEY: &str = "health";
+
+/// The collectors storing readings: the power meter, the `ps` snapshots of Overwatch
+/// with the usage of each cgroup they add up to, its sub-second frames with their
+/// downsampled summaries, the steal time of a virtual machine, the overhead of
+/// OneForAll's own monitoring, and the metrics of the database sink.
+pub const COLLECTORS: [&str; 8] = [
+    "power",
+    "processes",
+    "cgroups",
+    "bursts",
+    "burst_summaries",
+    STEAL_COLLECTOR,
+    FOOTPRINT_COLLECTOR,
+    METRICS_COLLECTOR,
+];
+
+/// How often the retention policy is applied when no interval is configured.
+pub const DEFAULT_RETENTION_CHECK_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// How far the size limit may shorten the age readings are kept for.
+pub const MIN_SAMPLE_AGE: Duration = Duration::from_secs(3600);
+
+/// The age readings are kept for before the size limit shortens it, when no
+/// maximum age is configured.
+pub const DEFAULT_SAMPLE_AGE: Duration = Duration::from_secs(30 * 24 * 3600);
+
+/// How often buffered readings are written when no interval is configured.
+pub const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How many readings are buffered before they are written, when no limit is configured.
+pub const DEFAULT_MAX_BUFFERED_SAMPLES: usize = 1000;
+
+/// How many readings wait for a failing database before the oldest are dropped,
+/// when no limit is configured.
+pub const DEFAULT_MAX_PENDING_SAMPLES: usize = 100_000;
+
+/// The format of the times in keys, e.g. "2024-05-02T08:00:00.000000Z".
+const KEY_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.6fZ";
+
+/// The length of a time formatted with `KEY_TIME_FORMAT`.
+const KEY_TIME_LENGTH: usize = 27;
+
+/// The configuration file; sections other than `[database]` are left to their own readers.
+#[derive(Debug, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    database: DatabaseConfig,
+}
+
+/// The embedded database results and readings are stored in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DatabaseBackend {
+    /// A sled key/value store.
+    #[default]
+    Sled,
+
+    /// A SQLite database with a table per kind of record.
+    Sqlite,
+}
+
+/// The database settings.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DatabaseConfig {
+    /// The database backend.
+    #[serde(default)]
+    pub backend: DatabaseBackend,
+
+    /// Where the database is kept; a file in the working directory named
+    /// after the backend when omitted.
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+
+    /// When readings are deleted; they are kept forever when omitted.
+    #[serde(default)]
+    pub retention: RetentionConfig,
+
+    /// How readings are buffered before they are written.
+    #[serde(default)]
+    pub write_buffer: WriteBufferConfig,
+}
+
+/// The buffer collector readings are written from in batches.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WriteBufferConfig {
+    /// How often buffered readings are written, e.g. "5s".
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub flush_interval: Option<Duration>,
+
+    /// How many readings are buffered before they are written regardless of the interval.
+    #[serde(default)]
+    pub max_samples: Option<usize>,
+
+    /// How many readings wait while writes fail before the oldest are dropped.
+    #[serde(default)]
+    pub max_pending: Option<usize>,
+}
+
+impl WriteBufferConfig {
+    /// Returns how often buffered readings are written.
+    pub fn flush_interval(&self) -> Duration {
+        self.flush_interval.unwrap_or(DEFAULT_FLUSH_INTERVAL)
+    }
+
+    /// Returns how many readings are buffered before they are written.
+    pub fn max_samples(&self) -> usize {
+        self.max_samples.unwrap_or(DEFAULT_MAX_BUFFERED_SAMPLES)
+    }
+
+    /// Returns how many readings wait while writes fail, never fewer than are buffered.
+    pub fn max_pending(&self) -> usize {
+        self.max_pending
+            .unwrap_or(DEFAULT_MAX_PENDING_SAMPLES)
+            .max(self.max_samples())
+    }
+}
+
+/// The retention policy of collector readings.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RetentionConfig {
+    /// How long readings are kept, e.g. "30d".
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub max_sample_age: Option<Duration>,
+
+    /// The size the database is kept under, e.g. "2G", by deleting the oldest
+    /// readings; results are never deleted to make room.
+    #[serde(default, deserialize_with = "deserialize_size")]
+    pub max_size: Option<u64>,
+
+    /// How often the policy is applied while the application runs, e.g. "1h".
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub check_interval: Option<Duration>,
+
+    /// How long the sub-second frames of `overwatch --burst-interval` are kept before
+    /// they are downsampled to a summary per minute, e.g. "24h".
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub burst_age: Option<Duration>,
+
+    /// How long the minute rollups of the history metrics are kept, e.g. "90d";
+    /// hour rollups are never deleted.
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub rollup_age: Option<Duration>,
+}
+
+/// Deserializes sizes written as a number with an optional `K`, `M`, `G` or `T` suffix.
+fn deserialize_size<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let text = String::deserialize(deserializer)?;
+    parse_size(&text)
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+impl RetentionConfig {
+    /// Returns whether any reading is ever deleted.
+    pub fn is_enabled(&self) -> bool {
+        self.max_sample_age.is_some() || self.max_size.is_some()
+    }
+
+    /// Returns how often the policy is applied.
+    pub fn check_interval(&self) -> Duration {
+        self.check_interval
+            .unwrap_or(DEFAULT_RETENTION_CHECK_INTERVAL)
+    }
+
+    /// Returns how long sub-second frames are kept before they are downsampled.
+    pub fn burst_age(&self) -> Duration {
+        self.burst_age.unwrap_or(DEFAULT_BURST_AGE)
+    }
+
+    /// Returns how long minute rollups are kept.
+    pub fn rollup_age(&self) -> Duration {
+        self.rollup_age.unwrap_or(DEFAULT_ROLLUP_AGE)
+    }
+}
+
+impl DatabaseConfig {
+    /// Parses the `[database]` section of a configuration file.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The TOML text of the configuration file.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<DatabaseConfig, String>` - The database settings, or why they are invalid.
+    pub fn from_toml(text: &str) -> Result<DatabaseConfig, String> {
+        let file: ConfigFile = toml::from_str(text).map_err(|e| e.to_string())?;
+        let retention = &file.database.retention;
+        if retention.max_sample_age.is_some_and(|age| age.is_zero()) {
+            return Err("The database `max_sample_age` must be longer than zero".to_string());
+        }
+        if retention
+            .check_interval
+            .is_some_and(|interval| interval.is_zero())
+        {
+            return Err(
+                "The database retention `check_interval` must be longer than zero".to_string(),
+            );
+        }
+        if retention.burst_age.is_some_and(|age| age.is_zero()) {
+            return Err("The database retention `burst_age` must be longer than zero".to_string());
+        }
+        if retention.rollup_age.is_some_and(|age| age.is_zero()) {
+            return Err("The database retention `rollup_age` must be longer than zero".to_string());
+        }
+        let write_buffer = &file.database.write_buffer;
+        if write_buffer
+            .flush_interval
+            .is_some_and(|interval| interval.is_zero())
+        {
+            return Err("The database `flush_interval` must be longer than zero".to_string());
+        }
+        if write_buffer.max_samples == Some(0) {
+            return Err("The database `max_samples` must be at least 1".to_string());
+        }
+        if write_buffer.max_pending == Some(0) {
+            return Err("The database `max_pending` must be at least 1".to_string());
+        }
+        Ok(file.database)
+    }
+
+    /// Returns where the database is kept.
+    pub fn path(&self) -> PathBuf {
+        self.path.clone().unwrap_or_else(|| {
+            PathBuf::from(match self.backend {
+                DatabaseBackend::Sled => DEFAULT_SLED_PATH,
+                DatabaseBackend::Sqlite => DEFAULT_SQLITE_PATH,
+            })
+        })
+    }
+
+    /// Returns where buffered readings are journaled until they are written,
+    /// next to the database.
+    pub fn journal_path(&self) -> PathBuf {
+        let mut path = self.path().into_os_string();
+        path.push(".journal");
+        PathBuf::from(path)
+    }
+}
+
+/// Formats a time as it appears in keys.
+pub fn key_time(time: &DateTime<Utc>) -> String {
+    time.format(KEY_TIME_FORMAT).to_string()
+}
+
+/// Returns the key range `[start, end)` of the keys made of a prefix ending
+/// with ':' and a time in a range.
+fn key_range(
+    prefix: &str,
+    from: Option<&DateTime<Utc>>,
+    to: Option<&DateTime<Utc>>,
+) -> (String, String) {
+    let start = format!("{}{}", prefix, from.map(key_time).unwrap_or_default());
+    let end = match to {
+        Some(to) => format!("{}{}", prefix, key_time(to)),
+        // ';' follows ':', so this bound comes after every key with the prefix.
+        None => format!("{};", prefix.trim_end_matches(':')),
+    };
+    (start, end)
+}
+
+/// Parses a time as it appears in keys.
+fn parse_key_time(text: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(text, KEY_TIME_FORMAT)
+        .ok()
+        .map(|time| time.and_utc())
+}
+
+/// The key a run result is stored under.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunKey {
+    /// The kind of run, one of `RESULT_KINDS`.
+    pub kind: String,
+
+    /// When the run started.
+    pub started_at: DateTime<Utc>,
+
+    /// What distinguishes runs of the same kind, e.g. the profile name or the job ID.
+    pub name: Option<String>,
+}
+
+impl RunKey {
+    /// Renders the key, e.g. "job:2024-05-02T08:00:00.000000Z:17".
+    pub fn encode(&self) -> String {
+        let mut key = format!("{}:{}", self.kind, key_time(&self.started_at));
+        if let Some(name) = &self.name {
+            key.push(':');
+            key.push_str(name);
+        }
+        key
+    }
+
+    /// Parses a key written by `encode`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The database key.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<RunKey>` - The key, or `None` when it is not a run key.
+    pub fn parse(key: &str) -> Option<RunKey> {
+        let (kind, rest) = key.split_once(':')?;
+        if !RESULT_KINDS.contains(&kind) {
+            return None;
+        }
+        let started_at = parse_key_time(rest.get(..KEY_TIME_LENGTH)?)?;
+        let name = match &rest[KEY_TIME_LENGTH..] {
+            "" => None,
+            suffix => Some(suffix.strip_prefix(':')?.to_string()),
+        };
+        Some(RunKey {
+            kind: kind.to_string(),
+            started_at,
+            name,
+        })
+    }
+
+    /// Returns the key range `[start, end)` holding the runs of a kind started
+    /// in a time range; an open bound extends the range to every run of the kind.
+    pub fn range(
+        kind: &str,
+        from: Option<&DateTime<Utc>>,
+        to: Option<&DateTime<Utc>>,
+    ) -> (String, String) {
+        key_range(&format!("{}:", kind), from, to)
+    }
+}
+
+/// The key a reading of a collector is stored under.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SampleKey {
+    /// The collector, e.g. "power".
+    pub collector: String,
+
+    /// When the reading was taken.
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl SampleKey {
+    /// Renders the key, e.g. "sample:power:2024-05-02T08:00:00.000000Z".
+    pub fn encode(&self) -> String {
+        format!(
+            "{}{}:{}",
+            SAMPLE_PREFIX,
+            self.collector,
+            key_time(&self.recorded_at)
+        )
+    }
+
+    /// Parses a key written by `encode`.
+    pub fn parse(key: &str) -> Option<SampleKey> {
+        let (collector, recorded_at) = key.strip_prefix(SAMPLE_PREFIX)?.split_once(':')?;
+        Some(SampleKey {
+            collector: collector.to_string(),
+            recorded_at: parse_key_time(recorded_at)?,
+        })
+    }
+
+    /// Returns the key range `[start, end)` holding the readings of a
+    /// collector taken in a time range; an open bound extends the range to
+    /// every reading of the collector.
+    pub fn range(
+        collector: &str,
+        from: Option<&DateTime<Utc>>,
+        to: Option<&DateTime<Utc>>,
+    ) -> (String, String) {
+        key_range(&format!("{}{}:", SAMPLE_PREFIX, collector), from, to)
+    }
+}
+
+/// A reading of a collector as stored, with the timestamps it was taken at.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredSample<T> {
+    /// The wall-clock and monotonic time the reading was taken; `None` for
+    /// readings stored before both were kept.
+    #[serde(default)]
+    pub time: Option<SampleTime>,
+
+    /// The reading.
+    pub sample: T,
+}
+
+/// A stored reading with the time it is ordered at, the time in its key.
+pub type OrderedSample<T> = (DateTime<Utc>, StoredSample<T>);
+
+/// Checks a collector name, which is part of the keys of its readings.
+pub fn validate_collector(collector: &str) -> Result<(), String> {
+    if collector.is_empty() || collector.contains(':') {
+        return Err(format!(
+            "Invalid collector name '{}': expected a non-empty name without ':'",
+            collector
+        ));
+    }
+    Ok(())
+}
+
+/// What a database key holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordKind<'a> {
+    /// The result of a run, e.g. "burn-in:2024-05-02T08:00:00.000000Z".
+    Run {
+        /// The kind of run, one of `RESULT_KINDS`.
+        kind: &'a str,
+    },
+
+    /// A hardware inventory collected by `discover`.
+    Inventory,
+
+    /// A reading of a collector, e.g. "sample:power:2024-05-02T08:00:00.000000Z".
+    Sample {
+        /// The collector, e.g. "power".
+        collector: &'a str,
+
+        /// When the reading was taken, as it appears in the key.
+        recorded_at: &'a str,
+    },
+
+    /// Anything else.
+    Other,
+}
+
+impl RecordKind<'_> {
+    /// Tells what a key holds from its form.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The database key.
+    ///
+    /// # Returns
+    ///
+    /// * `RecordKind` - What the key holds.
+    pub fn of(key: &[u8]) -> RecordKind<'_> {
+        let Ok(key) = std::str::from_utf8(key) else {
+            return RecordKind::Other;
+        };
+        if key.starts_with(INVENTORY_PREFIX) {
+            return RecordKind::Inventory;
+        }
+        if let Some(sample) = key.strip_prefix(SAMPLE_PREFIX) {
+            return match sample.split_once(':') {
+                Some((collector, recorded_at)) => RecordKind::Sample {
+                    collector,
+                    recorded_at,
+                },
+                None => RecordKind::Other,
+            };
+        }
+        match key.split_once(':') {
+            Some((kind, _)) if RESULT_KINDS.contains(&kind) => RecordKind::Run { kind },
+            _ => RecordKind::Other,
+        }
+    }
+}
@@ -0,0 +1,431 @@
+//! DIMM Domain Entity
+//!
+//! This module provides the memory modules of a machine, as described by the
+//! SMBIOS Memory Device (type 17) structures the firmware publishes. Besides
+//! listing each module, it checks the two population mistakes that cost
+//! memory bandwidth without failing any test: modules running below the speed
+//! they are rated for, and channels populated unevenly, which keeps the
+//! memory controller from interleaving across all of them.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::discovery::DiscoverySection;
+use crate::domain::endurance::format_volume;
+
+/// The SMBIOS structure type describing a memory device.
+const SMBIOS_MEMORY_DEVICE: u8 = 17;
+
+/// The SMBIOS structure type marking the end of the table.
+const SMBIOS_END_OF_TABLE: u8 = 127;
+
+/// The position of a DIMM slot on the memory controllers, parsed from its locator.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SlotPosition {
+    /// The socket, numbered as on the board, e.g. 1 for "CPU1" or 0 for "P0".
+    pub socket: u32,
+
+    /// The channel, e.g. "A".
+    pub channel: String,
+}
+
+/// A DIMM slot, populated or not.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Dimm {
+    /// The slot label on the board, e.g. "CPU1_DIMM_A1".
+    pub locator: String,
+
+    /// The bank label, e.g. "P0_Node0_Channel0_Dimm0".
+    pub bank_locator: String,
+
+    /// The capacity in bytes, or 0 for an empty slot.
+    pub size_bytes: u64,
+
+    /// The memory type, e.g. "DDR5".
+    pub memory_type: String,
+
+    /// The speed the module is rated for, in MT/s.
+    pub rated_speed_mts: Option<u32>,
+
+    /// The speed the memory controller runs the module at, in MT/s.
+    pub configured_speed_mts: Option<u32>,
+
+    /// The manufacturer, e.g. "Samsung".
+    pub manufacturer: String,
+
+    /// The part number, e.g. "M321R4GA3BB6-CQKET".
+    pub part_number: String,
+
+    /// The serial number.
+    pub serial_number: String,
+
+    /// The number of ranks, if reported.
+    pub ranks: Option<u8>,
+}
+
+impl Dimm {
+    /// Returns `true` if a module is installed in the slot.
+    pub fn is_populated(&self) -> bool {
+        self.size_bytes > 0
+    }
+
+    /// Returns `true` if the module runs slower than it is rated for.
+    pub fn is_underclocked(&self) -> bool {
+        matches!(
+            (self.configured_speed_mts, self.rated_speed_mts),
+            (Some(configured), Some(rated)) if configured < rated
+        )
+    }
+
+    /// Returns the position of the slot on the memory controllers, if its locator names one.
+    pub fn position(&self) -> Option<SlotPosition> {
+        parse_slot_position(&self.locator, &self.bank_locator)
+    }
+}
+
+/// Returns the name of an SMBIOS memory type.
+fn memory_type_name(code: u8) -> String {
+    match code {
+        0x12 => "DDR",
+        0x13 => "DDR2",
+        0x18 => "DDR3",
+        0x1A => "DDR4",
+        0x1B => "LPDDR",
+        0x1C => "LPDDR2",
+        0x1D => "LPDDR3",
+        0x1E => "LPDDR4",
+        0x1F => "Logical non-volatile device",
+        0x20 => "HBM",
+        0x21 => "HBM2",
+        0x22 => "DDR5",
+        0x23 => "LPDDR5",
+        0x24 => "HBM3",
+        _ => return format!("type {:#04x}", code),
+    }
+    .to_string()
+}
+
+/// Reads a little-endian word of a structure, if the structure is long enough to hold it.
+fn word(structure: &[u8], offset: usize) -> Option<u16> {
+    structure
+        .get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+/// Reads a little-endian double word of a structure, if the structure is long enough to hold it.
+fn dword(structure: &[u8], offset: usize) -> Option<u32> {
+    structure
+        .get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Reads a speed, following the 0xFFFF escape to the extended field of SMBIOS 3.3.
+fn speed(structure: &[u8], offset: usize, extended_offset: usize) -> Option<u32> {
+    match word(structure, offset)? {
+        0 => None,
+        0xFFFF => dword(structure, extended_offset)
+            .map(|speed| speed & 0x7FFF_FFFF)
+            .filter(|speed| *speed > 0),
+        speed => Some(speed as u32),
+    }
+}
+
+/// Decodes the size of a memory device in bytes, or 0 for an empty slot.
+fn memory_size(structure: &[u8]) -> u64 {
+    match word(structure, 0x0C).unwrap_or(0) {
+        0 | 0xFFFF => 0,
+        // Sizes of 32 GiB and above are in the extended size field, in MiB.
+        0x7FFF => dword(structure, 0x1C).map_or(0, |mib| (mib & 0x7FFF_FFFF) as u64) << 20,
+        size if size & 0x8000 != 0 => ((size & 0x7FFF) as u64) << 10,
+        size => (size as u64) << 20,
+    }
+}
+
+/// Parses the memory devices of an SMBIOS table.
+///
+/// The table is a sequence of structures, each a formatted area of the length
+/// given in its header followed by its strings, each terminated by a NUL, and
+/// a final NUL.
+///
+/// # Arguments
+///
+/// * `table` - The raw table, as read from `/sys/firmware/dmi/tables/DMI`.
+///
+/// # Returns
+///
+/// * `Vec<Dimm>` - One entry per DIMM slot, populated or not, in table order.
+pub fn parse_smbios_memory_devices(table: &[u8]) -> Vec<Dimm> {
+    let mut dimms = Vec::new();
+    let mut offset = 0;
+    while offset + 4 <= table.len() {
+        let kind = table[offset];
+        let length = table[offset + 1] as usize;
+        if length < 4 || offset + length > table.len() {
+            break;
+        }
+        let structure = &table[offset..offset + length];
+
+        // The strings end at the first double NUL.
+        let strings_start = offset + length;
+        let mut strings_end = strings_start;
+        while strings_end + 1 < table.len()
+            && !(table[strings_end] == 0 && table[strings_end + 1] == 0)
+        {
+            strings_end += 1;
+        }
+        let strings: Vec<String> = table[strings_start..strings_end]
+            .split(|byte| *byte == 0)
+            .map(|text| String::from_utf8_lossy(text).trim().to_string())
+            .collect();
+        let string = |field: usize| -> String {
+            match structure.get(field) {
+                Some(&index) if index > 0 => {
+                    strings.get(index as usize - 1).cloned().unwrap_or_default()
+                }
+                _ => String::new(),
+            }
+        };
+
+        if kind == SMBIOS_MEMORY_DEVICE && length >= 0x15 {
+            dimms.push(Dimm {
+                locator: string(0x10),
+                bank_locator: string(0x11),
+                size_bytes: memory_size(structure),
+                memory_type: structure
+                    .get(0x12)
+                    .map(|code| memory_type_name(*code))
+                    .unwrap_or_default(),
+                rated_speed_mts: speed(structure, 0x15, 0x54),
+                configured_speed_mts: speed(structure, 0x20, 0x58),
+                manufacturer: string(0x17),
+                serial_number: string(0x18),
+                part_number: string(0x1A),
+                ranks: structure
+                    .get(0x1B)
+                    .map(|attributes| attributes & 0x0F)
+                    .filter(|ranks| *ranks > 0),
+            });
+        }
+        if kind == SMBIOS_END_OF_TABLE {
+            break;
+        }
+        offset = strings_end + 2;
+    }
+    dimms
+}
+
+/// Reads the number following a prefix in a locator, e.g. 1 for "CPU" in "CPU1_DIMM_A1".
+fn number_after(text: &str, prefix: &str) -> Option<u32> {
+    text.match_indices(prefix).find_map(|(index, _)| {
+        let digits: String = text[index + prefix.len()..]
+            .trim_start_matches([' ', '_', '-'])
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .collect();
+        digits.parse().ok()
+    })
+}
+
+/// Parses the socket and channel of a DIMM slot from its locators.
+///
+/// Vendors label slots differently, so common conventions are recognized: the
+/// socket from "CPU1", "PROC 1", "SOCKET 1", "P0" or "NODE0", and the channel
+/// from "CHANNEL A", "CHANNEL0" or a letter followed by the slot number in the
+/// channel, as in "DIMM_A1" or "A2".
+///
+/// # Arguments
+///
+/// * `locator` - The device locator, e.g. "CPU1_DIMM_A1".
+/// * `bank_locator` - The bank locator, e.g. "P0_Node0_Channel0_Dimm0".
+///
+/// # Returns
+///
+/// * `Option<SlotPosition>` - The socket and channel, or `None` if no channel is named.
+pub fn parse_slot_position(locator: &str, bank_locator: &str) -> Option<SlotPosition> {
+    let text = format!("{} {}", bank_locator, locator).to_uppercase();
+
+    // Boards count sockets from 0 or from 1; they are kept as labeled.
+    let socket = number_after(&text, "CPU")
+        .or_else(|| number_after(&text, "PROC"))
+        .or_else(|| number_after(&text, "SOCKET"))
+        .or_else(|| {
+            text.split(|c: char| !c.is_ascii_alphanumeric())
+                .find_map(|token| token.strip_prefix('P')?.parse().ok())
+        })
+        .or_else(|| number_after(&text, "NODE"))
+        .unwrap_or(0);
+
+    let channel = text.match_indices("CHANNEL").find_map(|(index, _)| {
+        let name: String = text[index + "CHANNEL".len()..]
+            .trim_start_matches([' ', '_', '-'])
+            .chars()
+            .take_while(char::is_ascii_alphanumeric)
+            .collect();
+        Some(name).filter(|name| !name.is_empty())
+    });
+    let channel = channel.or_else(|| {
+        // A lone letter followed by the slot number, as in "DIMM_A1", "DIMMA1"
+        // or "A1"; a bare "P1" names a socket instead.
+        locator
+            .to_uppercase()
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|token| !token.is_empty())
+            .rev()
+            .find_map(|token| {
+                let slot = match token.strip_prefix("DIMM") {
+                    Some(slot) => slot,
+                    None if token.starts_with('P') => return None,
+                    None => token,
+                };
+                let mut chars = slot.chars();
+                let letter = chars.next().filter(char::is_ascii_alphabetic)?;
+                let number = chars.as_str();
+                (!number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+                    .then(|| letter.to_string())
+            })
+    })?;
+
+    Some(SlotPosition { socket, channel })
+}
+
+/// Checks the DIMMs for modules running below their rated speed and for uneven population.
+///
+/// The balance rules follow the population guides of server vendors: each
+/// socket should have every channel populated, every channel of a socket
+/// should hold the same capacity, and every socket should hold the same
+/// capacity.
+///
+/// # Arguments
+///
+/// * `dimms` - Every DIMM slot of the machine.
+///
+/// # Returns
+///
+/// * `Vec<String>` - One message per problem found.
+pub fn dimm_warnings(dimms: &[Dimm]) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    for dimm in dimms.iter().filter(|dimm| dimm.is_underclocked()) {
+        warnings.push(format!(
+            "{} runs at {} MT/s, below its rated {} MT/s",
+            dimm.locator,
+            dimm.configured_speed_mts.unwrap_or(0),
+            dimm.rated_speed_mts.unwrap_or(0)
+        ));
+    }
+
+    // Capacity per channel, per socket, including empty channels.
+    let mut sockets: BTreeMap<u32, BTreeMap<String, u64>> = BTreeMap::new();
+    for dimm in dimms {
+        if let Some(position) = dimm.position() {
+            *sockets
+                .entry(position.socket)
+                .or_default()
+                .entry(position.channel)
+                .or_default() += dimm.size_bytes;
+        }
+    }
+    let populated_sockets: BTreeMap<u32, u64> = sockets
+        .iter()
+        .map(|(socket, channels)| (*socket, channels.values().sum()))
+        .filter(|(_, total)| *total > 0)
+        .collect();
+
+    for (socket, channels) in &sockets {
+        if !populated_sockets.contains_key(socket) {
+            continue;
+        }
+        let empty: Vec<&str> = channels
+            .iter()
+            .filter(|(_, size)| **size == 0)
+            .map(|(channel, _)| channel.as_str())
+            .collect();
+        if !empty.is_empty() {
+            warnings.push(format!(
+                "Socket {} has empty channels ({}) while others are populated",
+                socket,
+                empty.join(", ")
+            ));
+        }
+        let sizes: Vec<u64> = channels
+            .values()
+            .copied()
+            .filter(|size| *size > 0)
+            .collect();
+        if sizes.iter().any(|size| *size != sizes[0]) {
+            warnings.push(format!(
+                "Socket {} has unequal capacity per channel: {}",
+                socket,
+                channels
+                    .iter()
+                    .filter(|(_, size)| **size > 0)
+                    .map(|(channel, size)| format!("{} {}", channel, format_volume(*size)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+    }
+
+    let totals: Vec<u64> = populated_sockets.values().copied().collect();
+    if totals.iter().any(|total| *total != totals[0]) {
+        warnings.push(format!(
+            "Sockets hold unequal memory: {}",
+            populated_sockets
+                .iter()
+                .map(|(socket, total)| format!("socket {} {}", socket, format_volume(*total)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    warnings
+}
+
+/// Builds the discovery section listing the memory modules.
+///
+/// # Arguments
+///
+/// * `dimms` - Every DIMM slot of the machine.
+///
+/// # Returns
+///
+/// * `DiscoverySection` - The slot count and total capacity, one entry per
+///   populated slot, and one per population problem.
+pub fn dimm_section(dimms: &[Dimm]) -> DiscoverySection {
+    let mut section = DiscoverySection::new("Memory Modules");
+    let populated: Vec<&Dimm> = dimms.iter().filter(|dimm| dimm.is_populated()).collect();
+    section.push(
+        "Slots",
+        format!("{} of {} populated", populated.len(), dimms.len()),
+    );
+    section.push(
+        "Total",
+        format_volume(populated.iter().map(|dimm| dimm.size_bytes).sum()),
+    );
+
+    for dimm in populated {
+        let mut value = format!("{} {}", format_volume(dimm.size_bytes), dimm.memory_type);
+        if let Some(ranks) = dimm.ranks {
+            value.push_str(&format!(" {}R", ranks));
+        }
+        match (dimm.configured_speed_mts, dimm.rated_speed_mts) {
+            (Some(configured), Some(rated)) => {
+                value.push_str(&format!(" | {} of {} MT/s", configured, rated))
+            }
+            (None, Some(rated)) => value.push_str(&format!(" | {} MT/s", rated)),
+            _ => {}
+        }
+        value.push_str(&format!(" | {} {}", dimm.manufacturer, dimm.part_number));
+        // The serial number traces a module swapped between inventories.
+        if !dimm.serial_number.is_empty() {
+            value.push_str(&format!(" | S/N {}", dimm.serial_number));
+        }
+        section.push(&dimm.locator, value);
+    }
+
+    for warning in dimm_warnings(dimms) {
+        section.push("Warning", warning);
+    }
+    section
+}
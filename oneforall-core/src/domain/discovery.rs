@@ -0,0 +1,76 @@
+//! Discovery Domain Entity
+//!
+//! This module provides the report produced by the `Discover` command. Each
+//! discovery adapter contributes a titled section of key/value facts, which
+//! keeps the report format independent of the hardware being described.
+//! The report also keeps a snapshot of the hardware itself, so the inventory
+//! stored after each run can be compared with the next.
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::hardware_changes::HardwareSnapshot;
+
+/// A titled group of facts gathered by a single discovery adapter.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DiscoverySection {
+    /// The section heading, e.g. "CPU Topology".
+    pub title: String,
+
+    /// Ordered key/value pairs describing the discovered hardware.
+    pub entries: Vec<(String, String)>,
+}
+
+impl DiscoverySection {
+    /// Creates a new, empty section with the given title.
+    pub fn new(title: &str) -> Self {
+        DiscoverySection {
+            title: title.to_string(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Appends a fact to the section.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The name of the fact, e.g. "Logical CPUs".
+    /// * `value` - The value of the fact.
+    pub fn push(&mut self, key: &str, value: impl ToString) {
+        self.entries.push((key.to_string(), value.to_string()));
+    }
+}
+
+/// The full output of a discovery run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DiscoveryReport {
+    /// The sections contributed by each discovery adapter, in display order.
+    pub sections: Vec<DiscoverySection>,
+
+    /// The hardware discovered, compared by `discover --diff`; `None` in
+    /// inventories stored before snapshots were kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<HardwareSnapshot>,
+}
+
+impl DiscoveryReport {
+    /// Renders the report as aligned plain text suitable for the terminal.
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+
+        for section in &self.sections {
+            output.push_str(&format!("== {} ==\n", section.title));
+            let width = section
+                .entries
+                .iter()
+                .map(|(key, _)| key.len())
+                .max()
+                .unwrap_or(0);
+            for (key, value) in &section.entries {
+                output.push_str(&format!("  {:<width$}  {}\n", key, value, width = width));
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+}
@@ -0,0 +1,369 @@
+//! Endurance Domain Entity
+//!
+//! This module provides the report of a storage endurance test, which writes a
+//! configured volume of pseudorandom data to a drive, reads every byte back to
+//! verify it, and compares the drive's SMART wear indicators before and after.
+//! The difference between the bytes the test wrote, the bytes the drive saw
+//! from the host and the bytes it programmed to NAND gives the amplification
+//! added by the filesystem and by the drive itself.
+
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::domain::burn_in::WorkloadOutcome;
+use crate::domain::nvme::{nvme_faults, NvmeHealth};
+use crate::domain::profile::format_duration;
+
+/// Parses a data volume such as `100G`, `512M` or `4096`.
+///
+/// # Arguments
+///
+/// * `text` - A number of bytes with an optional binary `K`, `M`, `G` or `T` suffix.
+///
+/// # Returns
+///
+/// * `Ok(u64)` - The volume in bytes.
+/// * `Err(String)` - If the text is malformed or the volume is zero.
+pub fn parse_size(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (digits, unit) = text.split_at(split);
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        "T" | "TB" | "TIB" => 1 << 40,
+        other => return Err(format!("Unknown size unit '{}' in '{}'", other, text)),
+    };
+    let value: u64 = digits
+        .parse()
+        .map_err(|_| format!("Invalid size '{}'", text))?;
+    if value == 0 {
+        return Err(format!("Size '{}' must be greater than zero", text));
+    }
+    value
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("Size '{}' is too large", text))
+}
+
+/// Formats a byte count with a binary unit, e.g. `1.5 GiB`.
+pub fn format_volume(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Returns the whole-disk device of a partition, e.g. `/dev/nvme0n1` for
+/// `/dev/nvme0n1p2` and `/dev/sda` for `/dev/sda3`.
+///
+/// SMART data belongs to the disk, so wear is always read from the whole device.
+/// Devices that are not partitions, such as device-mapper volumes, are returned
+/// unchanged.
+pub fn whole_disk(device: &str) -> String {
+    let Some(name) = device.strip_prefix("/dev/") else {
+        return device.to_string();
+    };
+    // NVMe and MMC partitions are suffixed "p<N>" after a digit.
+    if name.starts_with("nvme") || name.starts_with("mmcblk") {
+        if let Some(index) = name.rfind('p') {
+            let (disk, partition) = name.split_at(index);
+            if disk.ends_with(|c: char| c.is_ascii_digit())
+                && partition.len() > 1
+                && partition[1..].chars().all(|c| c.is_ascii_digit())
+            {
+                return format!("/dev/{}", disk);
+            }
+        }
+        return device.to_string();
+    }
+    if name.starts_with("sd") || name.starts_with("vd") || name.starts_with("xvd") {
+        return format!(
+            "/dev/{}",
+            name.trim_end_matches(|c: char| c.is_ascii_digit())
+        );
+    }
+    device.to_string()
+}
+
+/// The SMART wear indicators of a drive at one point in time.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct WearIndicators {
+    /// The share of the rated endurance used, in percent.
+    pub percentage_used: Option<u64>,
+
+    /// The bytes the drive has received from the host over its lifetime.
+    pub host_bytes_written: Option<u64>,
+
+    /// The bytes the drive has programmed to its NAND over its lifetime.
+    pub nand_bytes_written: Option<u64>,
+
+    /// The media errors the drive has recorded.
+    pub media_errors: Option<u64>,
+}
+
+/// Formats one wear counter as "before -> after", or says the drive does not report it.
+fn wear_row(
+    name: &str,
+    before: Option<u64>,
+    after: Option<u64>,
+    format: fn(u64) -> String,
+) -> String {
+    match (before, after) {
+        (Some(before), Some(after)) => {
+            format!("  {:<16} {} -> {}\n", name, format(before), format(after))
+        }
+        _ => format!("  {:<16} not reported\n", name),
+    }
+}
+
+/// The result of writing and verifying the test data.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WriteVerifyResult {
+    /// The bytes written and synced to the drive.
+    pub written_bytes: u64,
+
+    /// The bytes read back and compared.
+    pub verified_bytes: u64,
+
+    /// The number of blocks that did not read back as written.
+    pub corrupt_blocks: u64,
+
+    /// Where the first corrupt blocks were found, e.g. "file 3 at offset 1048576".
+    pub mismatches: Vec<String>,
+
+    /// Whether the test was cancelled before the full volume was written.
+    pub cancelled: bool,
+}
+
+/// The result of a storage endurance test.
+#[derive(Debug, Clone, Serialize)]
+pub struct EnduranceReport {
+    /// The local time the test started, in RFC 3339 format.
+    pub started_at: String,
+
+    /// The directory the test files were written to.
+    pub path: String,
+
+    /// The drive whose wear was tracked, if it could be determined.
+    pub device: Option<String>,
+
+    /// The volume the test was asked to write, in bytes.
+    pub planned_bytes: u64,
+
+    /// The volume written and synced, in bytes.
+    pub written_bytes: u64,
+
+    /// The volume read back and compared, in bytes.
+    pub verified_bytes: u64,
+
+    /// The number of blocks that did not read back as written.
+    pub corrupt_blocks: u64,
+
+    /// Where the first corrupt blocks were found.
+    pub mismatches: Vec<String>,
+
+    /// The time the test ran, in seconds.
+    pub elapsed_secs: u64,
+
+    /// How the write and verify workload ended.
+    pub outcome: WorkloadOutcome,
+
+    /// The wear indicators read before the test, or why they could not be read.
+    pub wear_before: Result<WearIndicators, String>,
+
+    /// The wear indicators read after the test, or why they could not be read.
+    pub wear_after: Result<WearIndicators, String>,
+
+    /// The serious kernel log events seen during the test, such as I/O errors.
+    pub kernel_faults: Vec<String>,
+
+    /// The NVMe health read before the test, if the drive is an NVMe namespace.
+    pub nvme_before: Option<NvmeHealth>,
+
+    /// The NVMe health read after the test, if the drive is an NVMe namespace.
+    pub nvme_after: Option<NvmeHealth>,
+}
+
+impl EnduranceReport {
+    /// Returns the change of a wear counter over the test, if read both times.
+    fn wear_delta(&self, counter: impl Fn(&WearIndicators) -> Option<u64>) -> Option<u64> {
+        let before = counter(self.wear_before.as_ref().ok()?)?;
+        let after = counter(self.wear_after.as_ref().ok()?)?;
+        Some(after.saturating_sub(before))
+    }
+
+    /// Returns the ratio of the bytes the drive received from the host to the
+    /// bytes the test wrote, which includes filesystem metadata and journaling.
+    pub fn filesystem_amplification(&self) -> Option<f64> {
+        let host = self.wear_delta(|w| w.host_bytes_written)?;
+        (self.written_bytes > 0).then(|| host as f64 / self.written_bytes as f64)
+    }
+
+    /// Returns the drive's write amplification: the bytes it programmed to NAND
+    /// per byte received from the host during the test.
+    pub fn write_amplification(&self) -> Option<f64> {
+        let nand = self.wear_delta(|w| w.nand_bytes_written)?;
+        let host = self.wear_delta(|w| w.host_bytes_written)?;
+        (host > 0).then(|| nand as f64 / host as f64)
+    }
+
+    /// Returns the number of media errors the drive recorded during the test.
+    pub fn new_media_errors(&self) -> Option<u64> {
+        self.wear_delta(|w| w.media_errors)
+    }
+
+    /// Returns the NVMe faults that appeared during the test, such as new error log entries.
+    pub fn nvme_faults(&self) -> Vec<String> {
+        match (&self.nvme_before, &self.nvme_after) {
+            (Some(before), Some(after)) => {
+                nvme_faults(std::slice::from_ref(before), std::slice::from_ref(after))
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns `true` if the full volume was written and verified without
+    /// corruption, new media errors, NVMe faults or kernel-reported errors.
+    pub fn passed(&self) -> bool {
+        self.outcome == WorkloadOutcome::Completed
+            && self.corrupt_blocks == 0
+            && self.new_media_errors().unwrap_or(0) == 0
+            && self.nvme_faults().is_empty()
+            && self.kernel_faults.is_empty()
+    }
+
+    /// Renders the report as plain text suitable for the terminal.
+    pub fn render(&self) -> String {
+        let mut output = String::from("== Storage endurance report ==\n");
+        output.push_str(&format!(
+            "Target:      {}{}\n",
+            self.path,
+            self.device
+                .as_ref()
+                .map(|d| format!(" on {}", d))
+                .unwrap_or_default()
+        ));
+        output.push_str(&format!(
+            "Duration:    {}\n",
+            format_duration(Duration::from_secs(self.elapsed_secs))
+        ));
+        output.push_str(&format!(
+            "Written:     {} of {} planned",
+            format_volume(self.written_bytes),
+            format_volume(self.planned_bytes)
+        ));
+        if let Some(rate) = self.written_bytes.checked_div(self.elapsed_secs) {
+            output.push_str(&format!(" ({}/s)", format_volume(rate)));
+        }
+        output.push('\n');
+        output.push_str(&format!(
+            "Verified:    {}, {} corrupt block(s)\n",
+            format_volume(self.verified_bytes),
+            self.corrupt_blocks
+        ));
+        for mismatch in &self.mismatches {
+            output.push_str(&format!("  {}\n", mismatch));
+        }
+        output.push_str(&format!(
+            "Workload:    {}\n",
+            match &self.outcome {
+                WorkloadOutcome::Completed => "completed".to_string(),
+                WorkloadOutcome::Failed(e) => format!("FAILED ({})", e),
+                WorkloadOutcome::Cancelled => "cancelled".to_string(),
+            }
+        ));
+
+        if !self.kernel_faults.is_empty() {
+            output.push_str("Kernel log:\n");
+            for fault in &self.kernel_faults {
+                output.push_str(&format!("  {}\n", fault));
+            }
+        }
+
+        output.push_str("Wear:\n");
+        match (&self.wear_before, &self.wear_after) {
+            (Ok(before), Ok(after)) => {
+                output.push_str(&wear_row(
+                    "Percentage used",
+                    before.percentage_used,
+                    after.percentage_used,
+                    |v| format!("{}%", v),
+                ));
+                output.push_str(&wear_row(
+                    "Host writes",
+                    before.host_bytes_written,
+                    after.host_bytes_written,
+                    format_volume,
+                ));
+                output.push_str(&wear_row(
+                    "NAND writes",
+                    before.nand_bytes_written,
+                    after.nand_bytes_written,
+                    format_volume,
+                ));
+                output.push_str(&wear_row(
+                    "Media errors",
+                    before.media_errors,
+                    after.media_errors,
+                    |v| v.to_string(),
+                ));
+            }
+            (Err(reason), _) | (_, Err(reason)) => {
+                output.push_str(&format!("  not tracked: {}\n", reason));
+            }
+        }
+        output.push_str(&format!(
+            "Filesystem amplification: {}\n",
+            self.filesystem_amplification()
+                .map(|f| format!("{:.2}x", f))
+                .unwrap_or_else(|| "unknown (no host write counter)".to_string())
+        ));
+        output.push_str(&format!(
+            "Write amplification:      {}\n",
+            self.write_amplification()
+                .map(|f| format!("{:.2}x", f))
+                .unwrap_or_else(|| "unknown (no NAND write counter)".to_string())
+        ));
+
+        if let (Some(before), Some(after)) = (&self.nvme_before, &self.nvme_after) {
+            output.push_str(&format!(
+                "NVMe ({}):
+",
+                after.controller
+            ));
+            output.push_str(&wear_row(
+                "Error log",
+                Some(before.error_log_entries),
+                Some(after.error_log_entries),
+                |v| format!("{} entries", v),
+            ));
+            output.push_str(&wear_row(
+                "Throttled",
+                Some(before.throttle_secs),
+                Some(after.throttle_secs),
+                |v| format!("{} s", v),
+            ));
+            if let Some(temperature) = after.temperature_celsius {
+                output.push_str(&format!("  {:<16} {:.0} °C\n", "Temperature", temperature));
+            }
+            for fault in self.nvme_faults() {
+                output.push_str(&format!("  FAULT: {}\n", fault));
+            }
+        }
+
+        output.push_str(&format!(
+            "Result: {}\n",
+            if self.passed() { "PASS" } else { "FAIL" }
+        ));
+        output
+    }
+}
@@ -0,0 +1,187 @@
+//! Entropy Domain Entity
+//!
+//! This module provides domain entities for the random number sources of the
+//! machine and the Entropy section of `discover`. A VM image with no working
+//! entropy source boots fine, then stalls on the first `getrandom` call of a
+//! TLS library or hands out predictable keys, which is hard to trace back to
+//! the hardware. The section reports the RDRAND and RDSEED instructions with a
+//! short throughput and sanity check, since some CPUs are known to return the
+//! same value forever after a firmware bug, and the state of the kernel pool:
+//! whether the CRNG is initialized, the entropy it holds and the hardware RNG
+//! device feeding it, such as virtio-rng in a VM.
+
+use crate::domain::discovery::DiscoverySection;
+
+/// Entropy, in bits, below which the kernel pool is reported as low. Kernels
+/// from 5.18 on report a constant 256 once the CRNG is initialized.
+pub const LOW_ENTROPY_BITS: u32 = 128;
+
+/// The result of a quick check of a CPU random number instruction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RngInstruction {
+    /// The instruction, e.g. "RDRAND" or "RDSEED".
+    pub name: String,
+
+    /// Whether the CPU advertises the instruction.
+    pub supported: bool,
+
+    /// The random data the instruction returned per second on one core, in MB/s.
+    pub mb_per_sec: Option<f64>,
+
+    /// The share of calls that returned no value, in percent. RDSEED fails
+    /// often when drained; RDRAND should almost never fail.
+    pub failure_percent: Option<f64>,
+
+    /// Whether every value returned was the same, as with the firmware bug
+    /// that makes RDRAND return all ones.
+    pub stuck: bool,
+}
+
+impl RngInstruction {
+    /// Returns `true` if the instruction is supported and returns random values.
+    pub fn is_healthy(&self) -> bool {
+        self.supported && !self.stuck && self.mb_per_sec.is_some_and(|rate| rate > 0.0)
+    }
+}
+
+/// The random number sources of the machine.
+#[derive(Debug, Clone, Default)]
+pub struct EntropyState {
+    /// The CPU random number instructions; empty on architectures without them.
+    pub instructions: Vec<RngInstruction>,
+
+    /// Whether the kernel CRNG is initialized, so `getrandom` returns without blocking.
+    pub crng_ready: Option<bool>,
+
+    /// The entropy the kernel pool holds, in bits.
+    pub entropy_bits: Option<u32>,
+
+    /// The size of the kernel pool, in bits.
+    pub pool_size_bits: Option<u32>,
+
+    /// The hardware RNG device feeding the kernel pool, e.g. "virtio_rng.0".
+    pub hwrng_current: Option<String>,
+
+    /// Every hardware RNG device the kernel found.
+    pub hwrng_available: Vec<String>,
+}
+
+impl EntropyState {
+    /// Returns `true` if a healthy CPU instruction or a hardware RNG device seeds the kernel.
+    pub fn has_hardware_source(&self) -> bool {
+        self.instructions.iter().any(RngInstruction::is_healthy) || self.hwrng_current.is_some()
+    }
+}
+
+/// Lists the problems with the random number sources of the machine.
+///
+/// # Arguments
+///
+/// * `state` - The random number sources discovered.
+///
+/// # Returns
+///
+/// * `Vec<String>` - One message per problem found.
+pub fn entropy_warnings(state: &EntropyState) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    for instruction in state.instructions.iter().filter(|i| i.supported) {
+        if instruction.stuck {
+            warnings.push(format!(
+                "{} returns the same value on every call; it is broken and must not be trusted",
+                instruction.name
+            ));
+        } else if instruction.mb_per_sec.is_none() {
+            warnings.push(format!(
+                "{} is advertised but returned no value",
+                instruction.name
+            ));
+        }
+    }
+    if state.crng_ready == Some(false) {
+        warnings.push(
+            "The kernel CRNG is not initialized; getrandom and /dev/random will block".to_string(),
+        );
+    }
+    if let Some(bits) = state.entropy_bits.filter(|bits| *bits < LOW_ENTROPY_BITS) {
+        warnings.push(format!("The kernel entropy pool holds only {} bits", bits));
+    }
+    if !state.has_hardware_source() {
+        warnings.push(
+            "No hardware entropy source; VMs need a virtio-rng device to seed the kernel quickly"
+                .to_string(),
+        );
+    }
+
+    warnings
+}
+
+/// Builds the Entropy section of the discovery report.
+///
+/// # Arguments
+///
+/// * `state` - The random number sources discovered.
+///
+/// # Returns
+///
+/// * `DiscoverySection` - The CPU instructions with their throughput, then the
+///   state of the kernel pool and its hardware RNG device.
+pub fn entropy_section(state: &EntropyState) -> DiscoverySection {
+    let mut section = DiscoverySection::new("Entropy");
+
+    for instruction in &state.instructions {
+        let value = if !instruction.supported {
+            "not supported".to_string()
+        } else if instruction.stuck {
+            "BROKEN (returns a constant)".to_string()
+        } else {
+            match instruction.mb_per_sec {
+                Some(rate) => {
+                    let mut value = format!("{:.0} MB/s per core", rate);
+                    if let Some(percent) = instruction.failure_percent.filter(|p| *p > 0.0) {
+                        value.push_str(&format!(", {:.1}% of calls failed", percent));
+                    }
+                    value
+                }
+                None => "FAILING (returned no value)".to_string(),
+            }
+        };
+        section.push(&instruction.name, value);
+    }
+
+    if let Some(ready) = state.crng_ready {
+        section.push(
+            "Kernel CRNG",
+            if ready {
+                "initialized"
+            } else {
+                "NOT INITIALIZED"
+            },
+        );
+    }
+    if let Some(bits) = state.entropy_bits {
+        let mut value = match state.pool_size_bits {
+            Some(size) => format!("{} of {} bits", bits, size),
+            None => format!("{} bits", bits),
+        };
+        if bits < LOW_ENTROPY_BITS {
+            value.push_str(" | LOW");
+        }
+        section.push("Entropy pool", value);
+    }
+    if state.hwrng_current.is_some() || !state.hwrng_available.is_empty() {
+        section.push(
+            "Hardware RNG",
+            state.hwrng_current.as_deref().unwrap_or("none selected"),
+        );
+        if state.hwrng_available.len() > 1 {
+            section.push("Hardware RNGs available", state.hwrng_available.join(", "));
+        }
+    }
+
+    for warning in entropy_warnings(state) {
+        section.push("Warning", warning);
+    }
+
+    section
+}
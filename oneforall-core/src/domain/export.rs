@@ -0,0 +1,263 @@
+//! Result Export Domain Entity
+//!
+//! This module provides the artifacts of a finished run (its JSON result, the
+//! rendered report and the raw telemetry time-series) and the settings used to
+//! upload them to an S3-compatible bucket, read from the `[export.s3]` section
+//! of `oneforall.toml`. Each run is uploaded under its own prefix, by default
+//! `<hostname>/<run>/`, so the qualification history of a machine survives it
+//! being reimaged.
+
+use std::path::PathBuf;
+
+use chrono::DateTime;
+use common::domain::telemetry::TelemetryEvent;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::tags::{tagged, Tags};
+
+/// The object key prefix runs are uploaded under when none is configured.
+pub const DEFAULT_KEY_PREFIX: &str = "{hostname}/{run}";
+
+/// The region requests are signed for when none is configured.
+pub const DEFAULT_REGION: &str = "us-east-1";
+
+/// The placeholders a key prefix may contain.
+const PREFIX_PLACEHOLDERS: [&str; 2] = ["{hostname}", "{run}"];
+
+/// The configuration file; sections other than `[export]` are left to their own readers.
+#[derive(Debug, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    export: ExportConfig,
+}
+
+/// The result export settings.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExportConfig {
+    /// The bucket finished runs are uploaded to; nothing is uploaded when omitted.
+    #[serde(default)]
+    pub s3: Option<S3Config>,
+}
+
+/// An S3-compatible bucket, e.g. on AWS, MinIO or Ceph.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct S3Config {
+    /// The bucket name.
+    pub bucket: String,
+
+    /// The URL of the object store, e.g. "http://minio.lab.example:9000"; the
+    /// AWS endpoint of the region when omitted.
+    #[serde(default)]
+    pub endpoint: Option<String>,
+
+    /// The region requests are signed for.
+    #[serde(default = "default_region")]
+    pub region: String,
+
+    /// Addresses the bucket in the path rather than the host name, as most
+    /// S3-compatible stores expect.
+    #[serde(default)]
+    pub path_style: bool,
+
+    /// The key prefix of each run, where `{hostname}` and `{run}` are replaced
+    /// by the machine name and the run name, e.g. "burn-in-20240502T100000".
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+
+    /// The access key ID; `AWS_ACCESS_KEY_ID` when omitted.
+    #[serde(default)]
+    pub access_key_id: Option<String>,
+
+    /// The secret access key; `AWS_SECRET_ACCESS_KEY` when omitted.
+    #[serde(default)]
+    pub secret_access_key: Option<String>,
+
+    /// A file holding the secret access key, so it can be kept out of the configuration file.
+    #[serde(default)]
+    pub secret_access_key_file: Option<PathBuf>,
+}
+
+fn default_region() -> String {
+    DEFAULT_REGION.to_string()
+}
+
+fn default_prefix() -> String {
+    DEFAULT_KEY_PREFIX.to_string()
+}
+
+impl ExportConfig {
+    /// Parses and validates the `[export]` section of a configuration file.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The TOML text of the configuration file.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ExportConfig, String>` - The export settings, or why they are invalid.
+    pub fn from_toml(text: &str) -> Result<ExportConfig, String> {
+        let file: ConfigFile = toml::from_str(text).map_err(|e| e.to_string())?;
+        if let Some(s3) = &file.export.s3 {
+            if s3.bucket.trim().is_empty() {
+                return Err("The S3 bucket is empty".to_string());
+            }
+            if let Some(endpoint) = &s3.endpoint {
+                if !endpoint.starts_with("http://") && !endpoint.starts_with("https://") {
+                    return Err(format!(
+                        "Invalid S3 endpoint '{}': expected an http:// or https:// URL",
+                        endpoint
+                    ));
+                }
+            }
+            if s3.secret_access_key.is_some() && s3.secret_access_key_file.is_some() {
+                return Err(
+                    "Set either `secret_access_key` or `secret_access_key_file` for S3".to_string(),
+                );
+            }
+            let unknown = PREFIX_PLACEHOLDERS
+                .iter()
+                .fold(s3.prefix.clone(), |prefix, placeholder| {
+                    prefix.replace(placeholder, "")
+                });
+            if unknown.contains('{') || unknown.contains('}') {
+                return Err(format!(
+                    "Invalid S3 prefix '{}': only {} may be used",
+                    s3.prefix,
+                    PREFIX_PLACEHOLDERS.join(" and ")
+                ));
+            }
+        }
+        Ok(file.export)
+    }
+}
+
+impl S3Config {
+    /// Returns the key an artifact of a run is uploaded under, e.g.
+    /// "rack12-node3/burn-in-20240502T100000/result.json".
+    ///
+    /// # Arguments
+    ///
+    /// * `hostname` - The name of this machine.
+    /// * `run` - The name of the run.
+    /// * `artifact` - The file name of the artifact.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The object key.
+    pub fn object_key(&self, hostname: &str, run: &str, artifact: &str) -> String {
+        let prefix = self
+            .prefix
+            .replace("{hostname}", hostname)
+            .replace("{run}", run);
+        let prefix = prefix.trim_matches('/');
+        if prefix.is_empty() {
+            artifact.to_string()
+        } else {
+            format!("{}/{}", prefix, artifact)
+        }
+    }
+}
+
+/// Keeps the characters that are safe in an object key everywhere, replacing the others with '-'.
+fn key_safe(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// A file produced by a run.
+#[derive(Debug, Clone)]
+pub struct Artifact {
+    /// The file name, e.g. "result.json".
+    pub name: String,
+
+    /// The MIME type of the content.
+    pub content_type: &'static str,
+
+    /// The content.
+    pub body: Vec<u8>,
+}
+
+/// The artifacts of a finished run.
+#[derive(Debug, Clone)]
+pub struct RunArtifacts {
+    /// The run name, e.g. "burn-in-20240502T100000", unique on the machine.
+    pub run: String,
+
+    /// The files to upload.
+    pub artifacts: Vec<Artifact>,
+}
+
+impl RunArtifacts {
+    /// Starts the artifacts of a run, named after what it ran and when.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - What the run was, e.g. "burn-in" or "profile:burn-in-24h".
+    /// * `started_at` - The local time the run started, in RFC 3339 format.
+    ///
+    /// # Returns
+    ///
+    /// * `RunArtifacts` - The run, without artifacts yet.
+    pub fn new(kind: &str, started_at: &str) -> Self {
+        let started = DateTime::parse_from_rfc3339(started_at)
+            .map(|time| time.format("%Y%m%dT%H%M%S").to_string())
+            .unwrap_or_else(|_| key_safe(started_at));
+        RunArtifacts {
+            run: format!("{}-{}", key_safe(kind), started),
+            artifacts: Vec::new(),
+        }
+    }
+
+    /// Adds a value as a JSON artifact.
+    pub fn add_json<T: Serialize>(&mut self, name: &str, value: &T) {
+        if let Ok(body) = serde_json::to_vec_pretty(value) {
+            self.add(name, "application/json", body);
+        }
+    }
+
+    /// Adds the result of the run as "result.json", with the tags of the run.
+    pub fn add_result<T: Serialize>(&mut self, result: &T, tags: &Tags) {
+        if let Ok(result) = tagged(result, tags) {
+            self.add_json("result.json", &result);
+        }
+    }
+
+    /// Adds a plain-text artifact, such as a rendered report.
+    pub fn add_text(&mut self, name: &str, text: &str) {
+        self.add(name, "text/plain; charset=utf-8", text.as_bytes().to_vec());
+    }
+
+    /// Adds the telemetry recorded over the run as "timeseries.jsonl", one JSON
+    /// event per line in the format streamed by `/api/v1/ws`.
+    pub fn add_timeseries(&mut self, events: &[TelemetryEvent]) {
+        if events.is_empty() {
+            return;
+        }
+        let mut body = Vec::new();
+        for event in events {
+            if let Ok(line) = serde_json::to_vec(event) {
+                body.extend_from_slice(&line);
+                body.push(b'\n');
+            }
+        }
+        self.add("timeseries.jsonl", "application/x-ndjson", body);
+    }
+
+    /// Adds an artifact.
+    pub fn add(&mut self, name: &str, content_type: &'static str, body: Vec<u8>) {
+        self.artifacts.push(Artifact {
+            name: name.to_string(),
+            content_type,
+            body,
+        });
+    }
+}
@@ -0,0 +1,170 @@
+//! Filesystem Domain Entity
+//!
+//! This module provides the results of the filesystem benchmark, which measures
+//! what databases, mail spools and VM hosts depend on rather than raw device
+//! throughput: how fast files are created, looked up and deleted, how many
+//! small random writes reach the drive per second, and how long an fsync takes.
+
+use std::time::Duration;
+
+use crate::domain::statistics::{mean, quantile, Measurement};
+
+/// The rate of one kind of filesystem operation.
+#[derive(Debug, Clone)]
+pub struct OperationRate {
+    /// The operation measured, e.g. "create".
+    pub operation: String,
+
+    /// The number of operations completed.
+    pub count: u64,
+
+    /// The time the operations took, in seconds.
+    pub elapsed_secs: f64,
+}
+
+impl OperationRate {
+    /// Returns the operations completed per second.
+    pub fn per_second(&self) -> f64 {
+        if self.elapsed_secs > 0.0 {
+            self.count as f64 / self.elapsed_secs
+        } else {
+            0.0
+        }
+    }
+}
+
+/// The distribution of a set of latency samples.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyStats {
+    /// The number of samples.
+    pub samples: usize,
+
+    /// The lowest latency in microseconds.
+    pub min_us: f64,
+
+    /// The mean latency in microseconds.
+    pub mean_us: f64,
+
+    /// The median latency in microseconds.
+    pub p50_us: f64,
+
+    /// The 99th percentile latency in microseconds.
+    pub p99_us: f64,
+
+    /// The 99.9th percentile latency in microseconds.
+    pub p999_us: f64,
+
+    /// The highest latency in microseconds.
+    pub max_us: f64,
+}
+
+impl LatencyStats {
+    /// Summarizes latency samples.
+    ///
+    /// # Arguments
+    ///
+    /// * `samples` - The measured latencies, in any order.
+    ///
+    /// # Returns
+    ///
+    /// * `LatencyStats` - The distribution; all zero without samples.
+    pub fn from_samples(samples: &[Duration]) -> Self {
+        if samples.is_empty() {
+            return LatencyStats::default();
+        }
+        let mut micros: Vec<f64> = samples.iter().map(|s| s.as_secs_f64() * 1e6).collect();
+        micros.sort_by(f64::total_cmp);
+        LatencyStats {
+            samples: micros.len(),
+            min_us: micros[0],
+            mean_us: mean(&micros).unwrap_or_default(),
+            p50_us: quantile(&micros, 0.5),
+            p99_us: quantile(&micros, 0.99),
+            p999_us: quantile(&micros, 0.999),
+            max_us: micros[micros.len() - 1],
+        }
+    }
+}
+
+/// The results of the filesystem benchmark.
+#[derive(Debug, Clone)]
+pub struct FilesystemBenchmarkResult {
+    /// The directory the benchmark ran in.
+    pub path: String,
+
+    /// The rates of the metadata operations: create, stat and delete.
+    pub metadata: Vec<OperationRate>,
+
+    /// The rate of small random writes into a preallocated file, up to and
+    /// including the fsync that made them durable.
+    pub random_writes: OperationRate,
+
+    /// The size of each random write, in bytes.
+    pub write_bytes: usize,
+
+    /// The latency of appending a block to a file and fsyncing it.
+    pub fsync: LatencyStats,
+}
+
+/// Lists the metrics of a filesystem benchmark run, for statistics over repeated runs.
+pub fn filesystem_measurements(result: &FilesystemBenchmarkResult) -> Vec<Measurement> {
+    let mut measurements: Vec<Measurement> = result
+        .metadata
+        .iter()
+        .map(|rate| {
+            Measurement::new(
+                format!("{} rate", rate.operation),
+                "ops/s",
+                rate.per_second(),
+            )
+        })
+        .collect();
+    // Named without the block size, so the metric stays the same if it changes.
+    measurements.push(Measurement::new(
+        "random write rate",
+        "ops/s",
+        result.random_writes.per_second(),
+    ));
+    measurements.push(Measurement::new("fsync mean", "us", result.fsync.mean_us));
+    measurements.push(Measurement::new("fsync p99", "us", result.fsync.p99_us));
+    measurements
+}
+
+/// Formats the results of the filesystem benchmark as a table.
+///
+/// # Arguments
+///
+/// * `result` - The results of the benchmark.
+///
+/// # Returns
+///
+/// * `String` - One row per measurement, followed by the fsync latency distribution.
+pub fn format_filesystem_results(result: &FilesystemBenchmarkResult) -> String {
+    let mut output = format!("Filesystem benchmark in {}\n", result.path);
+    output.push_str(&format!(
+        "{:<22}  {:>8}  {:>9}\n",
+        "Operation", "Count", "Ops/s"
+    ));
+    for rate in result
+        .metadata
+        .iter()
+        .chain(std::iter::once(&result.random_writes))
+    {
+        output.push_str(&format!(
+            "{:<22}  {:>8}  {:>9.0}\n",
+            rate.operation,
+            rate.count,
+            rate.per_second()
+        ));
+    }
+    output.push_str(&format!(
+        "fsync latency ({} samples of {} B): mean {:.0} us, p50 {:.0} us, p99 {:.0} us, max {:.0} us\n",
+        result.fsync.samples,
+        result.write_bytes,
+        result.fsync.mean_us,
+        result.fsync.p50_us,
+        result.fsync.p99_us,
+        result.fsync.max_us
+    ));
+    output
+}
@@ -0,0 +1,204 @@
+//! Firmware Domain Entity
+//!
+//! This module provides the firmware versions of a machine (the BIOS or UEFI,
+//! the BMC, and the firmware of each NIC and drive) and the expected-firmware
+//! manifest they are checked against. Fleets are qualified on an approved
+//! firmware baseline, and a node whose firmware drifted from it behaves like
+//! a different machine, so a profile step fails when any version differs.
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::discovery::DiscoverySection;
+
+/// A component running firmware, with the version it runs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FirmwareComponent {
+    /// The kind of component: "bios", "bmc", "nic", "nvme" or "disk".
+    pub kind: String,
+
+    /// The device, e.g. "eth0" or "nvme0"; empty for the BIOS and BMC.
+    pub device: String,
+
+    /// The model, vendor or driver identifying the component, e.g. "mlx5_core".
+    pub model: String,
+
+    /// The firmware version, e.g. "22.39.1002".
+    pub version: String,
+}
+
+impl FirmwareComponent {
+    /// Describes the component, e.g. "nic eth0 (mlx5_core)".
+    pub fn describe(&self) -> String {
+        let mut text = self.kind.clone();
+        if !self.device.is_empty() {
+            text.push_str(&format!(" {}", self.device));
+        }
+        if !self.model.is_empty() {
+            text.push_str(&format!(" ({})", self.model));
+        }
+        text
+    }
+}
+
+/// The firmware version a kind of component is expected to run.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExpectedFirmware {
+    /// The kind of component, e.g. "nic".
+    pub component: String,
+
+    /// Only components whose model contains this text, e.g. "mlx5".
+    #[serde(default)]
+    pub model: Option<String>,
+
+    /// Only this device, e.g. "eth0".
+    #[serde(default)]
+    pub device: Option<String>,
+
+    /// The approved version.
+    pub version: String,
+}
+
+impl ExpectedFirmware {
+    /// Returns `true` if the expectation applies to a component.
+    pub fn matches(&self, component: &FirmwareComponent) -> bool {
+        component.kind == self.component
+            && self
+                .model
+                .as_ref()
+                .is_none_or(|model| component.model.contains(model.as_str()))
+            && self
+                .device
+                .as_ref()
+                .is_none_or(|device| component.device == *device)
+    }
+
+    /// Describes the expectation, e.g. "nic mlx5".
+    fn describe(&self) -> String {
+        [
+            Some(self.component.as_str()),
+            self.device.as_deref(),
+            self.model.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ")
+    }
+}
+
+/// The approved firmware baseline of a fleet.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FirmwareManifest {
+    /// The expected versions.
+    #[serde(rename = "firmware")]
+    pub expected: Vec<ExpectedFirmware>,
+}
+
+impl FirmwareManifest {
+    /// Parses a manifest written in TOML, one `[[firmware]]` table per expected version.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The TOML document.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<FirmwareManifest, String>` - The manifest, or a description of the problem.
+    pub fn from_toml(text: &str) -> Result<FirmwareManifest, String> {
+        let manifest: FirmwareManifest = toml::from_str(text).map_err(|e| e.to_string())?;
+        if manifest.expected.is_empty() {
+            return Err("The firmware manifest expects no firmware".to_string());
+        }
+        Ok(manifest)
+    }
+
+    /// Checks a firmware inventory against every expected version.
+    ///
+    /// # Arguments
+    ///
+    /// * `inventory` - The firmware of the machine.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<FirmwareCheck>` - One check per matching component, or one per
+    ///   expectation that matches no component.
+    pub fn check(&self, inventory: &[FirmwareComponent]) -> Vec<FirmwareCheck> {
+        let mut checks = Vec::new();
+        for expected in &self.expected {
+            let matching: Vec<&FirmwareComponent> = inventory
+                .iter()
+                .filter(|component| expected.matches(component))
+                .collect();
+            if matching.is_empty() {
+                checks.push(FirmwareCheck {
+                    component: expected.describe(),
+                    expected: expected.version.clone(),
+                    found: None,
+                });
+            }
+            checks.extend(matching.into_iter().map(|component| FirmwareCheck {
+                component: component.describe(),
+                expected: expected.version.clone(),
+                found: Some(component.version.clone()),
+            }));
+        }
+        checks
+    }
+}
+
+/// The check of one component against its expected firmware version.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FirmwareCheck {
+    /// The component checked, e.g. "bios (Dell Inc.)".
+    pub component: String,
+
+    /// The approved version.
+    pub expected: String,
+
+    /// The version found, or `None` if no such component was found.
+    pub found: Option<String>,
+}
+
+impl FirmwareCheck {
+    /// Returns `true` if the component runs another version than the approved one.
+    pub fn drifted(&self) -> bool {
+        self.found
+            .as_ref()
+            .is_some_and(|found| found != &self.expected)
+    }
+
+    /// Renders the check as one line of a step result, e.g.
+    /// "FAIL firmware bios (Dell Inc.): 2.17.0 (expected 2.19.1)".
+    pub fn render(&self) -> String {
+        match &self.found {
+            Some(found) if self.drifted() => format!(
+                "FAIL firmware {}: {} (expected {})",
+                self.component, found, self.expected
+            ),
+            Some(found) => format!("ok   firmware {}: {}", self.component, found),
+            None => format!(
+                "n/a  firmware {}: not found on this machine",
+                self.component
+            ),
+        }
+    }
+}
+
+/// Builds the discovery section listing the firmware of the machine.
+///
+/// # Arguments
+///
+/// * `inventory` - The firmware of the machine.
+///
+/// # Returns
+///
+/// * `DiscoverySection` - One entry per component with its firmware version.
+pub fn firmware_section(inventory: &[FirmwareComponent]) -> DiscoverySection {
+    let mut section = DiscoverySection::new("Firmware");
+    for component in inventory {
+        section.push(&component.describe(), &component.version);
+    }
+    section
+}
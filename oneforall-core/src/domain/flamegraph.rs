@@ -0,0 +1,231 @@
+//! Flamegraph Domain Entity
+//!
+//! This module turns the stack samples `perf record` takes while a benchmark
+//! or plugin runs into a flamegraph: the samples are folded into one line per
+//! distinct stack with the number of times it was seen, as Brendan Gregg's
+//! `stackcollapse-perf.pl` does, and the folded stacks are drawn as an SVG in
+//! which each frame is as wide as the share of the samples it was on the
+//! stack for. A benchmark of one's own workload then shows where its time
+//! went, not only how long it took.
+
+use std::collections::BTreeMap;
+
+/// How often each CPU is sampled, in hertz; 99 rather than 100 so sampling
+/// does not run in lockstep with timers of the workload.
+pub const SAMPLE_FREQUENCY_HZ: u32 = 99;
+
+/// The width of the SVG, in pixels.
+const IMAGE_WIDTH: f64 = 1200.0;
+
+/// The height of each frame, in pixels.
+const FRAME_HEIGHT: f64 = 16.0;
+
+/// The margin left around the frames, in pixels.
+const MARGIN: f64 = 10.0;
+
+/// The space above the frames holding the title, in pixels.
+const TITLE_HEIGHT: f64 = 40.0;
+
+/// Frames narrower than this are left out, in pixels, as they could not be seen.
+const MIN_FRAME_WIDTH: f64 = 0.1;
+
+/// The average width of a character of the frame labels, in pixels.
+const CHAR_WIDTH: f64 = 6.6;
+
+/// Folds the output of `perf script` into one entry per distinct stack.
+///
+/// # Arguments
+///
+/// * `script` - The text `perf script` prints: a header line per sample, naming the
+///   command, followed by its stack, innermost frame first, and a blank line.
+///
+/// # Returns
+///
+/// * `BTreeMap<String, u64>` - Each stack, outermost frame first and prefixed with the
+///   command, its frames joined by `;`, with the number of samples it was seen in.
+pub fn fold_perf_script(script: &str) -> BTreeMap<String, u64> {
+    let mut folded = BTreeMap::new();
+    let mut command: Option<String> = None;
+    let mut frames: Vec<String> = Vec::new();
+    let mut flush = |command: &mut Option<String>, frames: &mut Vec<String>| {
+        if let Some(command) = command.take() {
+            let mut stack = vec![command];
+            stack.extend(frames.drain(..).rev());
+            *folded.entry(stack.join(";")).or_insert(0) += 1;
+        }
+        frames.clear();
+    };
+    for line in script.lines() {
+        if line.trim().is_empty() {
+            flush(&mut command, &mut frames);
+        } else if line.starts_with(char::is_whitespace) {
+            if command.is_some() {
+                frames.push(parse_frame(line.trim()));
+            }
+        } else if !line.starts_with('#') {
+            flush(&mut command, &mut frames);
+            // The command name may hold spaces; the PID follows it.
+            let name = line
+                .split_whitespace()
+                .take_while(|field| !field.chars().next().is_some_and(|c| c.is_ascii_digit()))
+                .collect::<Vec<_>>()
+                .join("_");
+            command = Some(if name.is_empty() {
+                "[unknown]".to_string()
+            } else {
+                name.replace(';', ":")
+            });
+        }
+    }
+    flush(&mut command, &mut frames);
+    folded
+}
+
+/// Names a stack frame of `perf script`, e.g. "ffffffff8100 do_syscall_64+0x5c ([kernel.kallsyms])".
+fn parse_frame(line: &str) -> String {
+    let (symbol, module) = match line.rfind(" (") {
+        Some(index) if line.ends_with(')') => (&line[..index], &line[index + 2..line.len() - 1]),
+        _ => (line, ""),
+    };
+    // Drop the address in front of the symbol and the offset behind it.
+    let symbol = symbol
+        .split_once(' ')
+        .map_or(symbol, |(_, symbol)| symbol)
+        .trim();
+    let symbol = match symbol.rfind("+0x") {
+        Some(index) => &symbol[..index],
+        None => symbol,
+    };
+    let name = if symbol.is_empty() || symbol == "[unknown]" {
+        // Without symbols the module at least tells the workload from its libraries.
+        let module = module.rsplit('/').next().unwrap_or(module);
+        format!("[{}]", module.trim_matches(|c| c == '[' || c == ']'))
+    } else {
+        symbol.to_string()
+    };
+    name.replace(';', ":")
+}
+
+/// A frame of the flamegraph and the frames called from it.
+#[derive(Default)]
+struct Frame {
+    samples: u64,
+    children: BTreeMap<String, Frame>,
+}
+
+/// Escapes text for SVG.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Picks a warm color for a frame from its name, so a function keeps its
+/// color wherever it appears.
+fn color(name: &str) -> String {
+    let hash = name.bytes().fold(2166136261u32, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(16777619)
+    });
+    let red = 205 + hash % 50;
+    let green = (hash >> 8) % 230;
+    let blue = (hash >> 16) % 55;
+    format!("rgb({},{},{})", red, green, blue)
+}
+
+/// Draws folded stacks as a flamegraph.
+///
+/// # Arguments
+///
+/// * `folded` - Each stack, outermost frame first, with the samples it was seen in.
+/// * `title` - The title drawn above the frames, e.g. the name of the benchmark.
+///
+/// # Returns
+///
+/// * `String` - The SVG document; hovering a frame shows its samples and share.
+pub fn render_svg(folded: &BTreeMap<String, u64>, title: &str) -> String {
+    let mut root = Frame::default();
+    let mut depth = 0;
+    for (stack, samples) in folded {
+        root.samples += samples;
+        let mut frame = &mut root;
+        for (level, name) in stack.split(';').enumerate() {
+            frame = frame.children.entry(name.to_string()).or_default();
+            frame.samples += samples;
+            depth = depth.max(level + 1);
+        }
+    }
+
+    let height = TITLE_HEIGHT + (depth + 1) as f64 * FRAME_HEIGHT + MARGIN * 2.0;
+    let mut svg = format!(
+        concat!(
+            "<?xml version=\"1.0\" standalone=\"no\"?>\n",
+            "<svg version=\"1.1\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\" ",
+            "xmlns=\"http://www.w3.org/2000/svg\">\n",
+            "<rect x=\"0\" y=\"0\" width=\"100%\" height=\"100%\" fill=\"rgb(248,248,248)\"/>\n",
+            "<text x=\"{center}\" y=\"24\" text-anchor=\"middle\" font-family=\"Verdana\" font-size=\"17\">{title}</text>\n",
+            "<g font-family=\"Verdana\" font-size=\"12\">\n"
+        ),
+        width = IMAGE_WIDTH,
+        height = height,
+        center = IMAGE_WIDTH / 2.0,
+        title = escape(title)
+    );
+    if root.samples > 0 {
+        let scale = (IMAGE_WIDTH - MARGIN * 2.0) / root.samples as f64;
+        let bottom = height - MARGIN - FRAME_HEIGHT;
+        draw_frame(&mut svg, "all", &root, root.samples, MARGIN, bottom, scale);
+    }
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
+
+/// Draws a frame and, above it, the frames called from it.
+fn draw_frame(svg: &mut String, name: &str, frame: &Frame, total: u64, x: f64, y: f64, scale: f64) {
+    let width = frame.samples as f64 * scale;
+    if width < MIN_FRAME_WIDTH {
+        return;
+    }
+    let share = frame.samples as f64 * 100.0 / total as f64;
+    // Labels are cut to what fits in the frame, and left out of narrow ones.
+    let fits = ((width - 6.0) / CHAR_WIDTH).floor().max(0.0) as usize;
+    let label = if name.chars().count() <= fits {
+        name.to_string()
+    } else if fits >= 3 {
+        let cut: String = name.chars().take(fits - 2).collect();
+        format!("{}..", cut)
+    } else {
+        String::new()
+    };
+    svg.push_str(&format!(
+        concat!(
+            "<g><title>{name} ({samples} samples, {share:.2}%)</title>",
+            "<rect x=\"{x:.1}\" y=\"{y:.1}\" width=\"{width:.1}\" height=\"{height:.1}\" fill=\"{fill}\" rx=\"2\" ry=\"2\"/>",
+            "<text x=\"{text_x:.1}\" y=\"{text_y:.1}\">{label}</text></g>\n"
+        ),
+        name = escape(name),
+        samples = frame.samples,
+        share = share,
+        x = x,
+        y = y,
+        width = width,
+        height = FRAME_HEIGHT - 1.0,
+        fill = color(name),
+        text_x = x + 3.0,
+        text_y = y + FRAME_HEIGHT - 4.5,
+        label = escape(&label)
+    ));
+    let mut child_x = x;
+    for (child_name, child) in &frame.children {
+        draw_frame(
+            svg,
+            child_name,
+            child,
+            total,
+            child_x,
+            y - FRAME_HEIGHT,
+            scale,
+        );
+        child_x += child.samples as f64 * scale;
+    }
+}
@@ -0,0 +1,237 @@
+//! Fleet Domain Entity
+//!
+//! This module provides the agents of a multi-node fleet. Any OneForAll
+//! instance serving the REST API can act as an agent; with `advertise` set in
+//! the `[fleet]` section of `oneforall.toml` it announces itself over mDNS, so
+//! `oneforall fleet` on the orchestrating machine finds every agent on the lab
+//! subnet. Agents on other subnets, which multicast does not reach, are listed
+//! statically in the same section.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use common::domain::job::{Job, JobStatus};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::profile::deserialize_duration;
+
+/// The DNS-SD service type agents advertise.
+pub const AGENT_SERVICE_TYPE: &str = "_oneforall._tcp.local.";
+
+/// How long `oneforall fleet` listens for agents when no timeout is configured.
+pub const DEFAULT_DISCOVERY_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// The configuration file; sections other than `[fleet]` are left to their own readers.
+#[derive(Debug, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    fleet: FleetConfig,
+}
+
+/// The fleet settings.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FleetConfig {
+    /// Advertises this instance as an agent over mDNS while its REST API is serving.
+    #[serde(default)]
+    pub advertise: bool,
+
+    /// The agent name advertised; the hostname when omitted.
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// The URL other machines reach this agent at, e.g. a name in its server
+    /// certificate; derived from its addresses when omitted.
+    #[serde(default)]
+    pub url: Option<String>,
+
+    /// Agents enrolled whether or not they are discovered, e.g. "10.20.0.7:8000"
+    /// or "https://rack14-node1.lab.example:8000".
+    #[serde(default)]
+    pub agents: Vec<String>,
+
+    /// How long `oneforall fleet` listens for mDNS announcements, e.g. "3s".
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub discovery_timeout: Option<Duration>,
+}
+
+impl FleetConfig {
+    /// Parses and validates the `[fleet]` section of a configuration file.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The TOML text of the configuration file.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<FleetConfig, String>` - The fleet settings, or why they are invalid.
+    pub fn from_toml(text: &str) -> Result<FleetConfig, String> {
+        let file: ConfigFile = toml::from_str(text).map_err(|e| e.to_string())?;
+        let mut config = file.fleet;
+        config.agents = config
+            .agents
+            .iter()
+            .map(String::as_str)
+            .map(agent_url)
+            .collect::<Result<_, _>>()?;
+        config.url = config.url.as_deref().map(agent_url).transpose()?;
+        Ok(config)
+    }
+
+    /// Returns how long `oneforall fleet` listens for agents.
+    pub fn discovery_timeout(&self) -> Duration {
+        self.discovery_timeout.unwrap_or(DEFAULT_DISCOVERY_TIMEOUT)
+    }
+}
+
+/// Turns an agent entry into the base URL of its REST API.
+///
+/// # Arguments
+///
+/// * `entry` - A URL, or "host:port" for an agent serving plain HTTP.
+///
+/// # Returns
+///
+/// * `Result<String, String>` - The base URL without a trailing slash, or why the entry is invalid.
+pub fn agent_url(entry: &str) -> Result<String, String> {
+    let entry = entry.trim().trim_end_matches('/');
+    let url = if entry.starts_with("http://") || entry.starts_with("https://") {
+        entry.to_string()
+    } else {
+        format!("http://{}", entry)
+    };
+    let authority = url.split("://").nth(1).unwrap_or_default();
+    let port = authority.rsplit_once(':').map(|(_, port)| port);
+    if authority.is_empty()
+        || authority.contains('/')
+        || port.is_none_or(|p| p.parse::<u16>().is_err())
+    {
+        return Err(format!(
+            "Invalid agent '{}': expected host:port or a URL with a port",
+            entry
+        ));
+    }
+    Ok(url)
+}
+
+/// What an agent announces about itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentAdvertisement {
+    /// The agent name, unique on the subnet.
+    pub name: String,
+
+    /// The address the REST API listens on; an unspecified IP announces every interface.
+    pub address: SocketAddr,
+
+    /// "https" if the REST API is served over TLS, "http" otherwise.
+    pub scheme: &'static str,
+
+    /// The URL to reach the agent at, if it is not derived from its addresses.
+    pub url: Option<String>,
+}
+
+/// How an agent was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AgentSource {
+    /// Announced over mDNS.
+    Mdns,
+
+    /// Listed in the `[fleet]` configuration.
+    Static,
+}
+
+/// An agent of the fleet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Agent {
+    /// The agent name, or its URL for a static agent.
+    pub name: String,
+
+    /// The base URL of the agent's REST API.
+    pub url: String,
+
+    /// The OneForAll version the agent announced, if any.
+    pub version: Option<String>,
+
+    /// How the agent was found.
+    pub source: AgentSource,
+}
+
+/// Combines the agents discovered over mDNS with the static ones, dropping
+/// static entries for agents that were also discovered, sorted by name.
+///
+/// # Arguments
+///
+/// * `discovered` - The agents announced over mDNS.
+/// * `listed` - The base URLs from the `[fleet]` configuration.
+///
+/// # Returns
+///
+/// * `Vec<Agent>` - Every agent once.
+pub fn merge_agents(discovered: Vec<Agent>, listed: &[String]) -> Vec<Agent> {
+    let mut agents = discovered;
+    for url in listed {
+        if !agents.iter().any(|agent| agent.url == *url) {
+            agents.push(Agent {
+                name: url.split("://").nth(1).unwrap_or(url).to_string(),
+                url: url.clone(),
+                version: None,
+                source: AgentSource::Static,
+            });
+        }
+    }
+    agents.sort_by(|a, b| a.name.cmp(&b.name).then(a.url.cmp(&b.url)));
+    agents
+}
+
+/// An agent enrolled in the fleet, with what it answered when probed.
+#[derive(Debug, Clone, Serialize)]
+pub struct FleetMember {
+    /// The agent.
+    pub agent: Agent,
+
+    /// The jobs of the agent, or why it could not be reached.
+    pub jobs: Result<Vec<Job>, String>,
+}
+
+/// Renders the fleet as a table for the terminal.
+pub fn format_fleet_table(members: &[FleetMember]) -> String {
+    if members.is_empty() {
+        return "No agents found.\n".to_string();
+    }
+
+    let mut output = format!(
+        "{:<20} {:<36} {:<7} {:<8} {}\n",
+        "NAME", "URL", "SOURCE", "VERSION", "STATUS"
+    );
+    for member in members {
+        let status = match &member.jobs {
+            Ok(jobs) => {
+                let running = jobs
+                    .iter()
+                    .filter(|job| job.status == JobStatus::Running)
+                    .count();
+                if running == 0 {
+                    "idle".to_string()
+                } else {
+                    format!("{} job(s) running", running)
+                }
+            }
+            Err(_) => "unreachable".to_string(),
+        };
+        output.push_str(&format!(
+            "{:<20} {:<36} {:<7} {:<8} {}\n",
+            member.agent.name,
+            member.agent.url,
+            format!("{:?}", member.agent.source).to_lowercase(),
+            member.agent.version.as_deref().unwrap_or("-"),
+            status
+        ));
+    }
+    for member in members {
+        if let Err(e) = &member.jobs {
+            output.push_str(&format!("{}: {}\n", member.agent.name, e));
+        }
+    }
+    output
+}
@@ -0,0 +1,306 @@
+//! GPU Domain Entity
+//!
+//! This module provides the readings of the GPUs of a machine and the report of
+//! the GPU stress test started by `oneforall stress --gpu`. The GPUs are loaded
+//! with the stress-ng GPU stressor while their temperature, clock, power and
+//! utilization are sampled; the test passes only if the load ran for the full
+//! duration and no GPU grew hotter than the thermal limit.
+
+use std::time::Duration;
+
+use common::domain::metric::Metric;
+use serde::Serialize;
+
+use crate::domain::burn_in::WorkloadOutcome;
+use crate::domain::profile::{format_duration, AcceptanceCriteria, CriterionCheck, StepMetrics};
+
+/// The hottest a GPU may run during a stress test when no limit is given, in degrees Celsius.
+pub const DEFAULT_MAX_GPU_CELSIUS: f64 = 90.0;
+
+/// The `nvidia-smi --query-gpu` fields read, in the order of `parse_nvidia_smi`.
+pub const NVIDIA_SMI_QUERY: &str =
+    "index,name,temperature.gpu,clocks.sm,power.draw,utilization.gpu";
+
+/// One reading of a single GPU.
+#[derive(Debug, Clone, Serialize)]
+pub struct GpuReading {
+    /// The GPU index, as numbered by the vendor tools.
+    pub index: u32,
+
+    /// The GPU model, e.g. "NVIDIA A100-SXM4-80GB".
+    pub name: String,
+
+    /// The GPU temperature in degrees Celsius.
+    pub temperature_celsius: Option<f64>,
+
+    /// The shader clock in MHz.
+    pub clock_mhz: Option<f64>,
+
+    /// The power drawn by the board, in watts.
+    pub power_watts: Option<f64>,
+
+    /// The share of time the GPU was busy, in percent.
+    pub utilization_percent: Option<f64>,
+}
+
+impl GpuReading {
+    /// Returns the reading as metrics labelled with the GPU index and model, leaving
+    /// out the fields the GPU does not report.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp` - The local time the reading was taken, in RFC 3339 format.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Metric>` - The `gpu_temperature_celsius`, `gpu_clock_mhz`, `gpu_power_watts`
+    ///   and `gpu_utilization_percent` metrics of the GPU.
+    pub fn metrics(&self, timestamp: &str) -> Vec<Metric> {
+        [
+            (
+                "gpu_temperature_celsius",
+                "celsius",
+                self.temperature_celsius,
+            ),
+            ("gpu_clock_mhz", "MHz", self.clock_mhz),
+            ("gpu_power_watts", "watts", self.power_watts),
+            (
+                "gpu_utilization_percent",
+                "percent",
+                self.utilization_percent,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(name, unit, value)| {
+            Some(
+                Metric::new(name, unit, value?, timestamp)
+                    .with_label("gpu", &self.index.to_string())
+                    .with_label("model", &self.name),
+            )
+        })
+        .collect()
+    }
+}
+
+/// Parses the output of `nvidia-smi --query-gpu=<NVIDIA_SMI_QUERY> --format=csv,noheader,nounits`.
+///
+/// # Arguments
+///
+/// * `output` - One line per GPU, e.g. "0, NVIDIA A100-SXM4-80GB, 34, 1410, 62.51, 0".
+///
+/// # Returns
+///
+/// * `Vec<GpuReading>` - The readings; fields the GPU does not report, shown as "[N/A]", are `None`.
+pub fn parse_nvidia_smi(output: &str) -> Vec<GpuReading> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            if fields.len() < 6 {
+                return None;
+            }
+            let number = |field: &str| field.parse::<f64>().ok();
+            Some(GpuReading {
+                index: fields[0].parse().ok()?,
+                name: fields[1].to_string(),
+                temperature_celsius: number(fields[2]),
+                clock_mhz: number(fields[3]),
+                power_watts: number(fields[4]),
+                utilization_percent: number(fields[5]),
+            })
+        })
+        .collect()
+}
+
+/// What a single GPU went through during a stress test.
+#[derive(Debug, Clone, Serialize)]
+pub struct GpuSummary {
+    /// The GPU index.
+    pub index: u32,
+
+    /// The GPU model.
+    pub name: String,
+
+    /// The hottest temperature seen, in degrees Celsius.
+    pub peak_celsius: Option<f64>,
+
+    /// The highest shader clock seen, in MHz.
+    pub peak_clock_mhz: Option<f64>,
+
+    /// The lowest shader clock seen while the GPU was busy, in MHz; well below
+    /// the peak, it points at thermal or power throttling.
+    pub lowest_busy_clock_mhz: Option<f64>,
+
+    /// The highest board power seen, in watts.
+    pub peak_watts: Option<f64>,
+
+    /// The average utilization over the test, in percent.
+    pub mean_utilization_percent: Option<f64>,
+}
+
+/// The largest of a set of optional values.
+fn peak(values: impl Iterator<Item = Option<f64>>) -> Option<f64> {
+    values
+        .flatten()
+        .fold(None, |max, v| Some(max.map_or(v, |m: f64| m.max(v))))
+}
+
+/// Summarizes the readings taken during a stress test, one summary per GPU.
+///
+/// # Arguments
+///
+/// * `samples` - The readings of every GPU, one set per sampling interval.
+///
+/// # Returns
+///
+/// * `Vec<GpuSummary>` - The summaries, ordered by GPU index.
+pub fn summarize_gpus(samples: &[Vec<GpuReading>]) -> Vec<GpuSummary> {
+    let mut indexes: Vec<(u32, String)> = samples
+        .iter()
+        .flatten()
+        .map(|reading| (reading.index, reading.name.clone()))
+        .collect();
+    indexes.sort();
+    indexes.dedup_by_key(|(index, _)| *index);
+
+    indexes
+        .into_iter()
+        .map(|(index, name)| {
+            let readings: Vec<&GpuReading> = samples
+                .iter()
+                .flatten()
+                .filter(|reading| reading.index == index)
+                .collect();
+            let utilization: Vec<f64> = readings
+                .iter()
+                .filter_map(|r| r.utilization_percent)
+                .collect();
+            GpuSummary {
+                index,
+                name,
+                peak_celsius: peak(readings.iter().map(|r| r.temperature_celsius)),
+                peak_clock_mhz: peak(readings.iter().map(|r| r.clock_mhz)),
+                lowest_busy_clock_mhz: readings
+                    .iter()
+                    .filter(|r| r.utilization_percent.is_some_and(|u| u >= 50.0))
+                    .filter_map(|r| r.clock_mhz)
+                    .fold(None, |min, c| Some(min.map_or(c, |m: f64| m.min(c)))),
+                peak_watts: peak(readings.iter().map(|r| r.power_watts)),
+                mean_utilization_percent: (!utilization.is_empty())
+                    .then(|| utilization.iter().sum::<f64>() / utilization.len() as f64),
+            }
+        })
+        .collect()
+}
+
+/// The result of a GPU stress test.
+#[derive(Debug, Clone, Serialize)]
+pub struct GpuStressReport {
+    /// The local time the test started, in RFC 3339 format.
+    pub started_at: String,
+
+    /// The configured duration, in seconds.
+    pub planned_secs: u64,
+
+    /// The time the load actually ran, in seconds.
+    pub elapsed_secs: u64,
+
+    /// How the load ended.
+    pub outcome: WorkloadOutcome,
+
+    /// The bogo-ops completed by the GPU stressor, if reported.
+    pub bogo_ops: Option<u64>,
+
+    /// The hottest any GPU may run, in degrees Celsius.
+    pub max_celsius: f64,
+
+    /// What each GPU went through.
+    pub gpus: Vec<GpuSummary>,
+}
+
+impl GpuStressReport {
+    /// Checks the peak temperature of each GPU against the thermal limit.
+    pub fn checks(&self) -> Vec<(&GpuSummary, CriterionCheck)> {
+        let criteria = AcceptanceCriteria {
+            max_temperature_celsius: Some(self.max_celsius),
+            ..AcceptanceCriteria::default()
+        };
+        self.gpus
+            .iter()
+            .flat_map(|gpu| {
+                let metrics = StepMetrics {
+                    bogo_ops_per_sec: None,
+                    peak_celsius: gpu.peak_celsius,
+                    worst_remote_penalty: None,
+                    ecc_errors: None,
+                    kernel_faults: Ok(Vec::new()),
+                    sel_entries: Ok(Vec::new()),
+                    firmware: Vec::new(),
+                    hook: None,
+                    memtest: None,
+                    devices: Vec::new(),
+                };
+                criteria
+                    .check(&metrics)
+                    .into_iter()
+                    .map(move |check| (gpu, check))
+            })
+            .collect()
+    }
+
+    /// Returns `true` if the load completed on at least one GPU and none ran too hot.
+    pub fn passed(&self) -> bool {
+        self.outcome == WorkloadOutcome::Completed
+            && !self.gpus.is_empty()
+            && !self.checks().iter().any(|(_, check)| check.missed())
+    }
+
+    /// Renders the report as plain text suitable for the terminal.
+    pub fn render(&self) -> String {
+        let mut output = String::from("== GPU stress report ==\n");
+        output.push_str(&format!(
+            "Duration:    {} of {} planned\n",
+            format_duration(Duration::from_secs(self.elapsed_secs)),
+            format_duration(Duration::from_secs(self.planned_secs))
+        ));
+        output.push_str(&format!(
+            "Workload:    {}\n",
+            match &self.outcome {
+                WorkloadOutcome::Completed => "completed".to_string(),
+                WorkloadOutcome::Failed(e) => format!("FAILED ({})", e),
+                WorkloadOutcome::Cancelled => "cancelled".to_string(),
+            }
+        ));
+        if let Some(ops) = self.bogo_ops {
+            output.push_str(&format!("  {:<10} {:>14} bogo-ops\n", "gpu", ops));
+        }
+
+        let value = |value: Option<f64>, unit: &str| {
+            value.map_or("n/a".to_string(), |v| format!("{:.0} {}", v, unit))
+        };
+        output.push_str("GPUs:\n");
+        for gpu in &self.gpus {
+            output.push_str(&format!("  GPU {} ({})\n", gpu.index, gpu.name));
+            output.push_str(&format!(
+                "    peak {}, clock {} peak / {} lowest busy, power {} peak, utilization {} mean\n",
+                value(gpu.peak_celsius, "°C"),
+                value(gpu.peak_clock_mhz, "MHz"),
+                value(gpu.lowest_busy_clock_mhz, "MHz"),
+                value(gpu.peak_watts, "W"),
+                value(gpu.mean_utilization_percent, "%"),
+            ));
+        }
+        if self.gpus.is_empty() {
+            output.push_str("  no GPU was read\n");
+        }
+        for (gpu, check) in self.checks() {
+            output.push_str(&format!("  GPU {} {}\n", gpu.index, check.render()));
+        }
+
+        output.push_str(&format!(
+            "Result: {}\n",
+            if self.passed() { "PASS" } else { "FAIL" }
+        ));
+        output
+    }
+}
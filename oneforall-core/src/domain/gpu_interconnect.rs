@@ -0,0 +1,325 @@
+//! GPU Interconnect Domain Entity
+//!
+//! This module provides the interconnect topology of the GPUs of a machine, how
+//! each pair of GPUs is connected as reported by `nvidia-smi topo -m` or
+//! `rocm-smi --showtopotype`, and the results of measuring the bandwidth and
+//! latency between every pair. A multi-GPU server is only worth its price if
+//! its GPUs reach each other over NVLink or xGMI as specced, so a missing link
+//! or a pair much slower than its peers over the same kind of link points at a
+//! badly seated bridge, a disabled link or a faulty baseboard.
+
+use crate::domain::discovery::DiscoverySection;
+use crate::domain::statistics::{median, Measurement};
+
+/// A pair is flagged when its bandwidth falls below the median of the pairs
+/// over the same kind of link by this factor.
+pub const BANDWIDTH_OUTLIER_FACTOR: f64 = 0.75;
+
+/// How two GPUs are connected, as named by the vendor tools.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GpuLink {
+    /// The index of the first GPU.
+    pub first: u32,
+
+    /// The index of the second GPU.
+    pub second: u32,
+
+    /// The connection type, e.g. "NV12", "XGMI", "PIX" or "SYS".
+    pub kind: String,
+}
+
+impl GpuLink {
+    /// Returns `true` if the GPUs are connected directly over NVLink or xGMI
+    /// rather than through PCIe.
+    pub fn is_direct(&self) -> bool {
+        self.kind.starts_with("NV") || self.kind == "XGMI"
+    }
+
+    /// Describes the connection type in words.
+    pub fn describe(&self) -> String {
+        let kind = self.kind.as_str();
+        match kind {
+            "XGMI" => "xGMI".to_string(),
+            "PIX" => "PCIe through one switch".to_string(),
+            "PXB" => "PCIe through several switches".to_string(),
+            "PHB" => "PCIe through a CPU host bridge".to_string(),
+            "NODE" => "PCIe across host bridges of one NUMA node".to_string(),
+            "SYS" => "PCIe across the CPU interconnect between sockets".to_string(),
+            "PCIE" => "PCIe".to_string(),
+            _ => match kind.strip_prefix("NV").and_then(|n| n.parse::<u32>().ok()) {
+                Some(1) => "NVLink, 1 link".to_string(),
+                Some(links) => format!("NVLink, {} links", links),
+                None => kind.to_string(),
+            },
+        }
+    }
+}
+
+/// The interconnect topology of the GPUs of a machine.
+#[derive(Debug, Clone, Default)]
+pub struct GpuTopology {
+    /// The GPU indexes, in order.
+    pub gpus: Vec<u32>,
+
+    /// One link per pair of distinct GPUs, with the lower index first.
+    pub links: Vec<GpuLink>,
+}
+
+impl GpuTopology {
+    /// Returns the link between two GPUs, in either order.
+    pub fn link(&self, a: u32, b: u32) -> Option<&GpuLink> {
+        let (first, second) = (a.min(b), a.max(b));
+        self.links
+            .iter()
+            .find(|link| link.first == first && link.second == second)
+    }
+}
+
+/// Returns the index of a GPU named like "GPU3" in a topology matrix.
+fn gpu_index(name: &str) -> Option<u32> {
+    name.strip_prefix("GPU")?.parse().ok()
+}
+
+/// Parses the topology matrix printed by `nvidia-smi topo -m` or `rocm-smi --showtopotype`.
+///
+/// The matrix has a header naming one column per device, followed by affinity
+/// columns in the NVIDIA output, and one row per device. Only the GPU rows and
+/// columns are kept; NICs and the diagonal are skipped.
+///
+/// # Arguments
+///
+/// * `output` - The output of the vendor tool.
+///
+/// # Returns
+///
+/// * `GpuTopology` - The GPUs and the link between each pair; empty without a matrix.
+pub fn parse_topology_matrix(output: &str) -> GpuTopology {
+    let mut lines = output
+        .lines()
+        .skip_while(|line| gpu_index(line.split_whitespace().next().unwrap_or("")).is_none());
+    // The header is the first line whose first field names a GPU.
+    let columns: Vec<&str> = match lines.next() {
+        Some(header) => header
+            .split_whitespace()
+            .take_while(|column| *column != "CPU")
+            .collect(),
+        None => return GpuTopology::default(),
+    };
+
+    let mut topology = GpuTopology::default();
+    for line in lines {
+        let mut fields = line.split_whitespace();
+        let Some(row) = fields.next().and_then(gpu_index) else {
+            continue;
+        };
+        topology.gpus.push(row);
+        for (column, kind) in columns.iter().zip(fields) {
+            match gpu_index(column) {
+                Some(column) if column > row => topology.links.push(GpuLink {
+                    first: row,
+                    second: column,
+                    kind: kind.to_string(),
+                }),
+                _ => {}
+            }
+        }
+    }
+    topology
+}
+
+/// Builds the discovery section mapping how the GPUs of the machine are connected.
+///
+/// # Arguments
+///
+/// * `topology` - The GPU interconnect topology of the machine.
+///
+/// # Returns
+///
+/// * `DiscoverySection` - The number of direct and PCIe pairs, and one entry per GPU listing its peers.
+pub fn interconnect_section(topology: &GpuTopology) -> DiscoverySection {
+    let mut section = DiscoverySection::new("GPU Interconnect");
+    let direct = topology
+        .links
+        .iter()
+        .filter(|link| link.is_direct())
+        .count();
+    section.push("GPUs", topology.gpus.len());
+    section.push("Pairs over NVLink/xGMI", direct);
+    section.push("Pairs over PCIe", topology.links.len() - direct);
+
+    for gpu in &topology.gpus {
+        let peers: Vec<String> = topology
+            .gpus
+            .iter()
+            .filter(|peer| *peer != gpu)
+            .filter_map(|peer| topology.link(*gpu, *peer).map(|link| (peer, link)))
+            .map(|(peer, link)| format!("GPU {} {} ({})", peer, link.kind, link.describe()))
+            .collect();
+        section.push(&format!("GPU {}", gpu), peers.join(" | "));
+    }
+
+    section
+}
+
+/// The measurements of the path from one GPU to another.
+#[derive(Debug, Clone)]
+pub struct GpuPairMeasurement {
+    /// The GPU the data is read from.
+    pub from: u32,
+
+    /// The GPU reading the data.
+    pub to: u32,
+
+    /// The connection type between the GPUs, if the topology is known.
+    pub link: Option<String>,
+
+    /// The bandwidth of copies from one GPU to the other, in GB/s.
+    pub read_gb_s: Option<f64>,
+
+    /// The bandwidth of copies in both directions at once, in GB/s.
+    pub bidirectional_gb_s: Option<f64>,
+
+    /// The latency of memory accesses from one GPU to the other, in microseconds.
+    pub latency_us: Option<f64>,
+}
+
+/// The results of benchmarking the interconnect between every pair of GPUs.
+#[derive(Debug, Clone)]
+pub struct InterconnectBenchmarkResult {
+    /// One measurement per ordered pair of distinct GPUs.
+    pub pairs: Vec<GpuPairMeasurement>,
+}
+
+impl InterconnectBenchmarkResult {
+    /// Returns the median copy bandwidth of the pairs over a kind of link, in GB/s.
+    fn median_read_gb_s(&self, link: &Option<String>) -> Option<f64> {
+        median(
+            &self
+                .pairs
+                .iter()
+                .filter(|pair| pair.link == *link)
+                .filter_map(|pair| pair.read_gb_s)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Returns `true` if a pair is much slower than the other pairs over the same kind of link.
+    fn is_slow(&self, pair: &GpuPairMeasurement) -> bool {
+        match (pair.read_gb_s, self.median_read_gb_s(&pair.link)) {
+            (Some(read), Some(median)) => read < median * BANDWIDTH_OUTLIER_FACTOR,
+            _ => false,
+        }
+    }
+
+    /// Lists the pairs that were not measured or are much slower than their peers.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<String>` - One description per pair; empty if every pair performs alike.
+    pub fn anomalies(&self) -> Vec<String> {
+        let mut anomalies = Vec::new();
+        for pair in &self.pairs {
+            let path = format!("GPU {} to GPU {}", pair.from, pair.to);
+            if pair.read_gb_s.is_none() {
+                anomalies.push(format!("{}: bandwidth not measured", path));
+            } else if self.is_slow(pair) {
+                anomalies.push(format!(
+                    "{}: {:.1} GB/s, under {:.0}% of the median of {:.1} GB/s over {}",
+                    path,
+                    pair.read_gb_s.unwrap_or_default(),
+                    BANDWIDTH_OUTLIER_FACTOR * 100.0,
+                    self.median_read_gb_s(&pair.link).unwrap_or_default(),
+                    pair.link.as_deref().unwrap_or("the same link")
+                ));
+            }
+        }
+        anomalies
+    }
+}
+
+/// Formats an optional value with a fixed precision, or "-" when missing.
+fn format_optional(value: Option<f64>, precision: usize) -> String {
+    value.map_or("-".to_string(), |value| {
+        format!("{:.precision$}", value, precision = precision)
+    })
+}
+
+/// Lists the metrics of a GPU interconnect benchmark run, for statistics over
+/// repeated runs: the medians over every pair, which machines are scored by,
+/// then each pair.
+pub fn interconnect_measurements(result: &InterconnectBenchmarkResult) -> Vec<Measurement> {
+    let mut measurements = Vec::new();
+    let medians = [
+        (
+            "median read",
+            "GB/s",
+            result
+                .pairs
+                .iter()
+                .filter_map(|p| p.read_gb_s)
+                .collect::<Vec<_>>(),
+        ),
+        (
+            "median latency",
+            "us",
+            result
+                .pairs
+                .iter()
+                .filter_map(|p| p.latency_us)
+                .collect::<Vec<_>>(),
+        ),
+    ];
+    for (metric, unit, values) in medians {
+        if let Some(value) = median(&values) {
+            measurements.push(Measurement::new(metric, unit, value));
+        }
+    }
+    for pair in &result.pairs {
+        let name = format!("GPU {} -> GPU {}", pair.from, pair.to);
+        let metrics = [
+            ("read", "GB/s", pair.read_gb_s),
+            ("bidirectional", "GB/s", pair.bidirectional_gb_s),
+            ("latency", "us", pair.latency_us),
+        ];
+        for (metric, unit, value) in metrics {
+            if let Some(value) = value {
+                measurements.push(Measurement::new(
+                    format!("{} {}", name, metric),
+                    unit,
+                    value,
+                ));
+            }
+        }
+    }
+    measurements
+}
+
+/// Formats the results of the GPU interconnect benchmark.
+pub fn format_interconnect_results(result: &InterconnectBenchmarkResult) -> String {
+    let mut output = format!(
+        "{:<16}  {:<6}  {:>10}  {:>12}  {:>12}\n",
+        "Pair", "Link", "Copy GB/s", "Bidir GB/s", "Latency us"
+    );
+    for pair in &result.pairs {
+        output.push_str(&format!(
+            "{:<16}  {:<6}  {:>10}  {:>12}  {:>12}{}\n",
+            format!("GPU {} -> GPU {}", pair.from, pair.to),
+            pair.link.as_deref().unwrap_or("?"),
+            format_optional(pair.read_gb_s, 1),
+            format_optional(pair.bidirectional_gb_s, 1),
+            format_optional(pair.latency_us, 2),
+            if result.is_slow(pair) { "  SLOW" } else { "" }
+        ));
+    }
+
+    let anomalies = result.anomalies();
+    if anomalies.is_empty() {
+        output.push_str("\nEvery GPU pair performs in line with its link type.\n");
+    } else {
+        output.push_str("\nUnderperforming GPU pairs:\n");
+        for anomaly in anomalies {
+            output.push_str(&format!("  {}\n", anomaly));
+        }
+    }
+    output
+}
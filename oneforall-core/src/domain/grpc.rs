@@ -0,0 +1,80 @@
+//! gRPC Control Plane Domain Entity
+//!
+//! This module provides the settings of the gRPC control plane, read from the
+//! `[grpc]` section of `oneforall.toml`, and the kinds of results it serves
+//! from the database. The control plane is an alternative to the REST API for
+//! lab automation written against gRPC; it accepts the same tokens and is
+//! served with the same certificates as the REST API.
+
+use std::net::SocketAddr;
+
+use serde::Deserialize;
+
+/// The kinds of results stored in the database, each under keys of the form
+/// "kind:...": burn-in, GPU stress, VRAM test, RAM test, SIMD stress, soak, endurance
+/// and profile reports, the outcome of jobs, and the metrics of benchmarks.
+pub const RESULT_KINDS: [&str; 10] = [
+    "burn-in",
+    "gpu-stress",
+    "vram-test",
+    "memtest",
+    "simd-stress",
+    "soak",
+    "endurance",
+    "profile",
+    "job",
+    "benchmark",
+];
+
+/// The configuration file; sections other than `[grpc]` are left to their own readers.
+#[derive(Debug, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    grpc: GrpcConfig,
+}
+
+/// The gRPC control plane settings.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GrpcConfig {
+    /// The address and port to listen on, e.g. "0.0.0.0:50051"; the control
+    /// plane is not served when omitted.
+    #[serde(default)]
+    pub address: Option<String>,
+}
+
+impl GrpcConfig {
+    /// Parses and validates the `[grpc]` section of a configuration file.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The TOML text of the configuration file.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<GrpcConfig, String>` - The gRPC settings, or why they are invalid.
+    pub fn from_toml(text: &str) -> Result<GrpcConfig, String> {
+        let file: ConfigFile = toml::from_str(text).map_err(|e| e.to_string())?;
+        if let Some(address) = &file.grpc.address {
+            address
+                .parse::<SocketAddr>()
+                .map_err(|e| format!("Invalid gRPC address '{}': {}", address, e))?;
+        }
+        Ok(file.grpc)
+    }
+
+    /// Returns the address to serve the control plane on, if it is enabled.
+    pub fn socket_address(&self) -> Option<SocketAddr> {
+        self.address
+            .as_deref()
+            .and_then(|address| address.parse().ok())
+    }
+}
+
+/// Returns the kind of a result from the key it is stored under, e.g. "burn-in"
+/// for "burn-in:2024-05-02T08:00:00.000000Z", or `None` for keys holding other
+/// data, such as the latest power reading.
+pub fn result_kind(key: &str) -> Option<&'static str> {
+    let (prefix, _) = key.split_once(':')?;
+    RESULT_KINDS.iter().copied().find(|kind| *kind == prefix)
+}
@@ -0,0 +1,351 @@
+//! Hardware Changes Domain Entity
+//!
+//! This module provides the snapshot of the hardware kept with every inventory
+//! `discover` stores, and the comparison of two snapshots that
+//! `discover --diff` reports: memory modules removed, added or swapped, PCIe
+//! links that trained at a lower generation or width, firmware updated or
+//! rolled back, and NVMe drives swapped or gone. Maintenance that reseats a
+//! card or replaces a drive changes none of the test results on its own, so
+//! these changes would otherwise go unnoticed until a benchmark comes in slow.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::dimm::Dimm;
+use crate::domain::endurance::format_volume;
+use crate::domain::firmware::FirmwareComponent;
+use crate::domain::nvme::NvmeController;
+use crate::domain::pcie::{describe_link, pcie_generation, PcieLink};
+
+/// The hardware of a machine as discovered, kept to compare with later discoveries.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HardwareSnapshot {
+    /// The memory slots, populated or not.
+    #[serde(default)]
+    pub dimms: Vec<Dimm>,
+
+    /// The links of the PCIe devices, bridges excluded.
+    #[serde(default)]
+    pub pcie_links: Vec<PcieLink>,
+
+    /// The firmware of the BIOS, BMC and devices.
+    #[serde(default)]
+    pub firmware: Vec<FirmwareComponent>,
+
+    /// The NVMe drives.
+    #[serde(default)]
+    pub nvme: Vec<NvmeController>,
+}
+
+/// How a piece of hardware changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChangeKind {
+    /// It is gone.
+    Removed,
+
+    /// It now runs slower, e.g. a link trained at a lower generation or width.
+    Downgraded,
+
+    /// Another part of the same kind took its place, e.g. a drive with another serial number.
+    Swapped,
+
+    /// Its firmware or configuration changed.
+    Changed,
+
+    /// It now runs faster.
+    Upgraded,
+
+    /// It is new.
+    Added,
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ChangeKind::Removed => "REMOVED",
+            ChangeKind::Downgraded => "DOWNGRADED",
+            ChangeKind::Swapped => "SWAPPED",
+            ChangeKind::Changed => "CHANGED",
+            ChangeKind::Upgraded => "UPGRADED",
+            ChangeKind::Added => "ADDED",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A change found between two snapshots.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HardwareChange {
+    /// How the hardware changed.
+    pub kind: ChangeKind,
+
+    /// What changed, e.g. "memory module CPU1_DIMM_A1".
+    pub subject: String,
+
+    /// What it was and what it is now, e.g. "Gen4 x16 -> Gen4 x8".
+    pub detail: String,
+}
+
+impl HardwareChange {
+    fn new(kind: ChangeKind, subject: String, detail: String) -> Self {
+        HardwareChange {
+            kind,
+            subject,
+            detail,
+        }
+    }
+}
+
+/// Describes a memory module, e.g. "32 GiB DDR5 | Samsung M321R4GA3BB6-CQKET | S/N 1234".
+fn describe_dimm(dimm: &Dimm) -> String {
+    let mut text = format!("{} {}", format_volume(dimm.size_bytes), dimm.memory_type);
+    if let Some(speed) = dimm.configured_speed_mts {
+        text.push_str(&format!(" {} MT/s", speed));
+    }
+    text.push_str(&format!(" | {} {}", dimm.manufacturer, dimm.part_number));
+    if !dimm.serial_number.is_empty() {
+        text.push_str(&format!(" | S/N {}", dimm.serial_number));
+    }
+    text
+}
+
+/// Indexes items by a key, so the same slot, address or component is compared across snapshots.
+fn by_key<T, F: Fn(&T) -> String>(items: &[T], key: F) -> BTreeMap<String, &T> {
+    items.iter().map(|item| (key(item), item)).collect()
+}
+
+/// Compares the memory modules, slot by slot.
+fn diff_dimms(before: &[Dimm], after: &[Dimm], changes: &mut Vec<HardwareChange>) {
+    let populated = |dimms: &[Dimm]| {
+        dimms
+            .iter()
+            .filter(|dimm| dimm.is_populated())
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+    let (before, after) = (populated(before), populated(after));
+    let (before, after) = (
+        by_key(&before, |dimm| dimm.locator.clone()),
+        by_key(&after, |dimm| dimm.locator.clone()),
+    );
+    for (locator, old) in &before {
+        let subject = format!("memory module {}", locator);
+        let Some(new) = after.get(locator) else {
+            changes.push(HardwareChange::new(
+                ChangeKind::Removed,
+                subject,
+                describe_dimm(old),
+            ));
+            continue;
+        };
+        let detail = format!("{} -> {}", describe_dimm(old), describe_dimm(new));
+        if old.serial_number != new.serial_number || old.part_number != new.part_number {
+            changes.push(HardwareChange::new(ChangeKind::Swapped, subject, detail));
+        } else if new.configured_speed_mts < old.configured_speed_mts {
+            changes.push(HardwareChange::new(ChangeKind::Downgraded, subject, detail));
+        } else if new.configured_speed_mts > old.configured_speed_mts {
+            changes.push(HardwareChange::new(ChangeKind::Upgraded, subject, detail));
+        }
+    }
+    for (locator, new) in &after {
+        if !before.contains_key(locator) {
+            changes.push(HardwareChange::new(
+                ChangeKind::Added,
+                format!("memory module {}", locator),
+                describe_dimm(new),
+            ));
+        }
+    }
+}
+
+/// Compares the PCIe links, device address by device address.
+fn diff_pcie_links(before: &[PcieLink], after: &[PcieLink], changes: &mut Vec<HardwareChange>) {
+    let (before, after) = (
+        by_key(before, |link| link.address.clone()),
+        by_key(after, |link| link.address.clone()),
+    );
+    // Compares generations rather than speeds, and counts an unknown link as the slowest.
+    // GPUs drop their link speed when idle, so only their width is compared.
+    let rank = |link: &PcieLink| {
+        let generation = match link.current_speed_gts {
+            Some(_) if link.is_gpu() => 0,
+            Some(speed) => pcie_generation(speed),
+            None => 0,
+        };
+        (generation, link.current_width.unwrap_or(0))
+    };
+    for (address, old) in &before {
+        let subject = format!("PCIe {} {}", address, old.describe());
+        let Some(new) = after.get(address) else {
+            changes.push(HardwareChange::new(
+                ChangeKind::Removed,
+                subject,
+                describe_link(old.current_speed_gts, old.current_width),
+            ));
+            continue;
+        };
+        if old.id != new.id {
+            changes.push(HardwareChange::new(
+                ChangeKind::Swapped,
+                subject,
+                format!("now {}", new.describe()),
+            ));
+            continue;
+        }
+        let detail = format!(
+            "{} -> {}",
+            describe_link(old.current_speed_gts, old.current_width),
+            describe_link(new.current_speed_gts, new.current_width)
+        );
+        let (old_rank, new_rank) = (rank(old), rank(new));
+        if new_rank.0 < old_rank.0 || new_rank.1 < old_rank.1 {
+            changes.push(HardwareChange::new(ChangeKind::Downgraded, subject, detail));
+        } else if new_rank > old_rank {
+            changes.push(HardwareChange::new(ChangeKind::Upgraded, subject, detail));
+        }
+    }
+    for (address, new) in &after {
+        if !before.contains_key(address) {
+            changes.push(HardwareChange::new(
+                ChangeKind::Added,
+                format!("PCIe {} {}", address, new.describe()),
+                describe_link(new.current_speed_gts, new.current_width),
+            ));
+        }
+    }
+}
+
+/// Compares the firmware versions, component by component.
+fn diff_firmware(
+    before: &[FirmwareComponent],
+    after: &[FirmwareComponent],
+    changes: &mut Vec<HardwareChange>,
+) {
+    let (before, after) = (
+        by_key(before, FirmwareComponent::describe),
+        by_key(after, FirmwareComponent::describe),
+    );
+    for (component, old) in &before {
+        let subject = format!("firmware of {}", component);
+        match after.get(component) {
+            None => changes.push(HardwareChange::new(
+                ChangeKind::Removed,
+                subject,
+                old.version.clone(),
+            )),
+            Some(new) if new.version != old.version => changes.push(HardwareChange::new(
+                ChangeKind::Changed,
+                subject,
+                format!("{} -> {}", old.version, new.version),
+            )),
+            Some(_) => {}
+        }
+    }
+    for (component, new) in &after {
+        if !before.contains_key(component) {
+            changes.push(HardwareChange::new(
+                ChangeKind::Added,
+                format!("firmware of {}", component),
+                new.version.clone(),
+            ));
+        }
+    }
+}
+
+/// Compares the NVMe drives, controller by controller.
+fn diff_nvme(
+    before: &[NvmeController],
+    after: &[NvmeController],
+    changes: &mut Vec<HardwareChange>,
+) {
+    let describe = |drive: &NvmeController| format!("{} | S/N {}", drive.model, drive.serial);
+    let (before, after) = (
+        by_key(before, |drive| drive.name.clone()),
+        by_key(after, |drive| drive.name.clone()),
+    );
+    for (name, old) in &before {
+        let subject = format!("NVMe drive {}", name);
+        let Some(new) = after.get(name) else {
+            changes.push(HardwareChange::new(
+                ChangeKind::Removed,
+                subject,
+                describe(old),
+            ));
+            continue;
+        };
+        if old.serial != new.serial {
+            changes.push(HardwareChange::new(
+                ChangeKind::Swapped,
+                subject,
+                format!("{} -> {}", describe(old), describe(new)),
+            ));
+        } else if old.firmware != new.firmware {
+            changes.push(HardwareChange::new(
+                ChangeKind::Changed,
+                subject,
+                format!("firmware {} -> {}", old.firmware, new.firmware),
+            ));
+        }
+    }
+    for (name, new) in &after {
+        if !before.contains_key(name) {
+            changes.push(HardwareChange::new(
+                ChangeKind::Added,
+                format!("NVMe drive {}", name),
+                describe(new),
+            ));
+        }
+    }
+}
+
+/// Compares two snapshots of the same machine.
+///
+/// # Arguments
+///
+/// * `before` - The snapshot of an earlier discovery.
+/// * `after` - The snapshot of the discovery just made.
+///
+/// # Returns
+///
+/// * `Vec<HardwareChange>` - The changes, losses and downgrades first.
+pub fn diff_snapshots(before: &HardwareSnapshot, after: &HardwareSnapshot) -> Vec<HardwareChange> {
+    let mut changes = Vec::new();
+    diff_dimms(&before.dimms, &after.dimms, &mut changes);
+    diff_pcie_links(&before.pcie_links, &after.pcie_links, &mut changes);
+    diff_firmware(&before.firmware, &after.firmware, &mut changes);
+    diff_nvme(&before.nvme, &after.nvme, &mut changes);
+    changes.sort_by_key(|change| change.kind);
+    changes
+}
+
+/// Formats the changes found since an earlier discovery.
+///
+/// # Arguments
+///
+/// * `changes` - The changes, as found by `diff_snapshots`.
+/// * `since` - When the earlier discovery was made.
+///
+/// # Returns
+///
+/// * `String` - One line per change, or a line saying nothing changed.
+pub fn format_changes(changes: &[HardwareChange], since: &str) -> String {
+    if changes.is_empty() {
+        return format!("No hardware changes since the discovery of {}.\n", since);
+    }
+    let mut output = format!(
+        "{} hardware change(s) since the discovery of {}:\n",
+        changes.len(),
+        since
+    );
+    for change in changes {
+        output.push_str(&format!(
+            "  {:<10}  {}: {}\n",
+            change.kind.to_string(),
+            change.subject,
+            change.detail
+        ));
+    }
+    output
+}
@@ -0,0 +1,97 @@
+//! Hardware Error Domain Entity
+//!
+//! This module provides domain entities for the hardware error sources watched
+//! during a burn-in: ECC memory errors reported by the EDAC subsystem, machine
+//! check exceptions logged by the kernel, and the SMART health of drives. Each
+//! source is read before and after the run, and any new error is a fault.
+
+/// The ECC error counters summed over every memory controller.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EccCounts {
+    /// The number of memory controllers reporting.
+    pub controllers: usize,
+
+    /// Errors that were detected and corrected.
+    pub corrected: u64,
+
+    /// Errors that could not be corrected.
+    pub uncorrected: u64,
+}
+
+/// The SMART health of a single drive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmartHealth {
+    /// The device path, e.g. "/dev/nvme0".
+    pub device: String,
+
+    /// Whether the drive's overall health self-assessment passed.
+    pub passed: bool,
+
+    /// The sum of the error attributes: reallocated, pending and uncorrectable
+    /// sectors on ATA drives, and media errors on NVMe drives.
+    pub error_count: u64,
+}
+
+/// Lists the ECC errors that appeared between two readings.
+///
+/// # Arguments
+///
+/// * `before` - The counters read when the run started.
+/// * `after` - The counters read when the run finished.
+///
+/// # Returns
+///
+/// * `Vec<String>` - One description per kind of new error; empty if there were none.
+pub fn ecc_faults(before: &EccCounts, after: &EccCounts) -> Vec<String> {
+    let mut faults = Vec::new();
+    let corrected = after.corrected.saturating_sub(before.corrected);
+    let uncorrected = after.uncorrected.saturating_sub(before.uncorrected);
+    if uncorrected > 0 {
+        faults.push(format!("{} new uncorrected ECC error(s)", uncorrected));
+    }
+    if corrected > 0 {
+        faults.push(format!("{} new corrected ECC error(s)", corrected));
+    }
+    faults
+}
+
+/// Counts the ECC errors, corrected or not, reported between two readings.
+pub fn new_ecc_errors(before: &EccCounts, after: &EccCounts) -> u64 {
+    after.corrected.saturating_sub(before.corrected)
+        + after.uncorrected.saturating_sub(before.uncorrected)
+}
+
+/// Lists the drives whose SMART health got worse between two readings.
+///
+/// # Arguments
+///
+/// * `before` - The drive health read when the run started.
+/// * `after` - The drive health read when the run finished.
+///
+/// # Returns
+///
+/// * `Vec<String>` - One description per degraded drive; empty if there were none.
+pub fn smart_faults(before: &[SmartHealth], after: &[SmartHealth]) -> Vec<String> {
+    let mut faults = Vec::new();
+    for drive in after {
+        let previous = before.iter().find(|d| d.device == drive.device);
+        if !drive.passed && previous.is_none_or(|p| p.passed) {
+            faults.push(format!("{} failed its SMART health check", drive.device));
+        }
+        let new_errors = drive
+            .error_count
+            .saturating_sub(previous.map_or(0, |p| p.error_count));
+        if new_errors > 0 && previous.is_some() {
+            faults.push(format!(
+                "{} reported {} new SMART error(s)",
+                drive.device, new_errors
+            ));
+        }
+    }
+    for drive in before {
+        if !after.iter().any(|d| d.device == drive.device) {
+            faults.push(format!("{} disappeared during the run", drive.device));
+        }
+    }
+    faults
+}
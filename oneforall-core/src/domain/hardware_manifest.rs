@@ -0,0 +1,453 @@
+//! Hardware Manifest Domain Entity
+//!
+//! This module provides the manifest of the hardware a machine is expected to
+//! have, e.g. as listed on its purchase order, and the validation of the
+//! machine against it that `discover --expect` runs: the CPU model, sockets,
+//! cores and threads, the memory installed, the disks by model and size, and
+//! the network interfaces by driver and link speed. A machine delivered with
+//! half its DIMMs or a 25G NIC in place of a 100G one then fails its intake
+//! check rather than passing every test a little slower.
+
+use serde::Deserialize;
+
+use crate::domain::dimm::Dimm;
+use crate::domain::endurance::{format_volume, parse_size};
+
+/// How far short of the expected memory the memory visible to the kernel may
+/// fall when the installed modules cannot be read, as the firmware and kernel
+/// reserve some of it.
+pub const KERNEL_MEMORY_TOLERANCE: f64 = 0.05;
+
+/// The CPUs a machine is expected to have.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExpectedCpu {
+    /// Text the CPU model name contains, e.g. "EPYC 9654".
+    #[serde(default)]
+    pub model: Option<String>,
+
+    /// The number of sockets populated.
+    #[serde(default)]
+    pub sockets: Option<usize>,
+
+    /// The number of physical cores, over all sockets.
+    #[serde(default)]
+    pub cores: Option<usize>,
+
+    /// The number of hardware threads, over all sockets.
+    #[serde(default)]
+    pub threads: Option<usize>,
+}
+
+/// The memory a machine is expected to have.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExpectedMemory {
+    /// The memory installed, e.g. "1536G".
+    #[serde(default)]
+    pub size: Option<String>,
+
+    /// The number of memory modules installed.
+    #[serde(default)]
+    pub modules: Option<usize>,
+
+    /// The memory type of every module, e.g. "DDR5".
+    #[serde(default, rename = "type")]
+    pub memory_type: Option<String>,
+
+    /// The speed every module must run at, at least, in MT/s.
+    #[serde(default)]
+    pub speed: Option<u32>,
+}
+
+/// A group of disks a machine is expected to have.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExpectedDisks {
+    /// Text the model of the disks contains, e.g. "MZQL27T6"; any model when omitted.
+    #[serde(default)]
+    pub model: Option<String>,
+
+    /// The size each disk has at least, e.g. "7T".
+    #[serde(default)]
+    pub min_size: Option<String>,
+
+    /// The number of such disks.
+    pub count: usize,
+}
+
+/// A group of network interfaces a machine is expected to have.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExpectedNics {
+    /// Text the driver of the interfaces contains, e.g. "mlx5"; any driver when omitted.
+    #[serde(default)]
+    pub driver: Option<String>,
+
+    /// The link speed each interface runs at, at least, in Gbit/s.
+    #[serde(default)]
+    pub speed_gbps: Option<u64>,
+
+    /// The number of such interfaces.
+    pub count: usize,
+}
+
+/// The hardware a machine is expected to have.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HardwareManifest {
+    /// The CPUs.
+    #[serde(default)]
+    pub cpu: Option<ExpectedCpu>,
+
+    /// The memory.
+    #[serde(default)]
+    pub memory: Option<ExpectedMemory>,
+
+    /// The disks, one `[[disk]]` table per kind.
+    #[serde(default, rename = "disk")]
+    pub disks: Vec<ExpectedDisks>,
+
+    /// The network interfaces, one `[[nic]]` table per kind.
+    #[serde(default, rename = "nic")]
+    pub nics: Vec<ExpectedNics>,
+}
+
+/// A disk of the machine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiskInfo {
+    /// The block device, e.g. "nvme0n1" or "sda".
+    pub name: String,
+
+    /// The model reported by the disk.
+    pub model: String,
+
+    /// The capacity in bytes.
+    pub size_bytes: u64,
+}
+
+/// A physical network interface of the machine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NicInfo {
+    /// The interface, e.g. "ens1f0".
+    pub name: String,
+
+    /// The kernel driver, e.g. "mlx5_core".
+    pub driver: String,
+
+    /// The link speed in Mbit/s, or `None` while the link is down.
+    pub speed_mbps: Option<u64>,
+}
+
+/// The hardware of the machine, as discovered, that a manifest is checked against.
+#[derive(Debug, Clone, Default)]
+pub struct MachineFacts {
+    /// The CPU model name, if known.
+    pub cpu_model: Option<String>,
+
+    /// The number of sockets populated.
+    pub sockets: usize,
+
+    /// The number of physical cores.
+    pub cores: usize,
+
+    /// The number of hardware threads.
+    pub threads: usize,
+
+    /// The installed memory modules; empty if the SMBIOS table could not be read.
+    pub dimms: Vec<Dimm>,
+
+    /// The memory visible to the kernel, in bytes.
+    pub kernel_memory_bytes: u64,
+
+    /// The disks.
+    pub disks: Vec<DiskInfo>,
+
+    /// The physical network interfaces.
+    pub nics: Vec<NicInfo>,
+}
+
+/// The check of one expectation of a manifest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestCheck {
+    /// What was checked, e.g. "cpu cores".
+    pub item: String,
+
+    /// What the manifest expects, e.g. "192".
+    pub expected: String,
+
+    /// What the machine has, e.g. "96".
+    pub found: String,
+
+    /// Whether the machine meets the expectation.
+    pub passed: bool,
+}
+
+impl ManifestCheck {
+    fn new(item: &str, expected: impl ToString, found: impl ToString, passed: bool) -> Self {
+        ManifestCheck {
+            item: item.to_string(),
+            expected: expected.to_string(),
+            found: found.to_string(),
+            passed,
+        }
+    }
+
+    /// Renders the check as one line, e.g. "FAIL cpu cores: 96 (expected 192)".
+    pub fn render(&self) -> String {
+        if self.passed {
+            format!("ok   {}: {}", self.item, self.found)
+        } else {
+            format!(
+                "FAIL {}: {} (expected {})",
+                self.item, self.found, self.expected
+            )
+        }
+    }
+}
+
+/// Returns `true` if `text` contains `pattern`, ignoring case.
+fn contains_ignore_case(text: &str, pattern: &str) -> bool {
+    text.to_lowercase().contains(&pattern.to_lowercase())
+}
+
+impl HardwareManifest {
+    /// Parses a manifest written in TOML.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The TOML document, with `[cpu]`, `[memory]`, `[[disk]]` and `[[nic]]` tables.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<HardwareManifest, String>` - The manifest, or a description of the problem.
+    pub fn from_toml(text: &str) -> Result<HardwareManifest, String> {
+        let manifest: HardwareManifest = toml::from_str(text).map_err(|e| e.to_string())?;
+        if let Some(size) = manifest
+            .memory
+            .as_ref()
+            .and_then(|memory| memory.size.as_ref())
+        {
+            parse_size(size).map_err(|e| format!("memory.size: {}", e))?;
+        }
+        for disks in &manifest.disks {
+            if let Some(min_size) = &disks.min_size {
+                parse_size(min_size).map_err(|e| format!("disk.min_size: {}", e))?;
+            }
+        }
+        if manifest.cpu.is_none()
+            && manifest.memory.is_none()
+            && manifest.disks.is_empty()
+            && manifest.nics.is_empty()
+        {
+            return Err("The hardware manifest expects no hardware".to_string());
+        }
+        Ok(manifest)
+    }
+
+    /// Checks the machine against every expectation of the manifest.
+    ///
+    /// # Arguments
+    ///
+    /// * `facts` - The hardware of the machine.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<ManifestCheck>` - One check per expectation, in manifest order.
+    pub fn check(&self, facts: &MachineFacts) -> Vec<ManifestCheck> {
+        let mut checks = Vec::new();
+        if let Some(cpu) = &self.cpu {
+            if let Some(model) = &cpu.model {
+                let found = facts.cpu_model.as_deref().unwrap_or("unknown");
+                checks.push(ManifestCheck::new(
+                    "cpu model",
+                    model,
+                    found,
+                    contains_ignore_case(found, model),
+                ));
+            }
+            for (item, expected, found) in [
+                ("cpu sockets", cpu.sockets, facts.sockets),
+                ("cpu cores", cpu.cores, facts.cores),
+                ("cpu threads", cpu.threads, facts.threads),
+            ] {
+                if let Some(expected) = expected {
+                    checks.push(ManifestCheck::new(item, expected, found, found == expected));
+                }
+            }
+        }
+        if let Some(memory) = &self.memory {
+            Self::check_memory(memory, facts, &mut checks);
+        }
+        for disks in &self.disks {
+            let min_size = disks
+                .min_size
+                .as_deref()
+                .and_then(|size| parse_size(size).ok());
+            let matching: Vec<&DiskInfo> = facts
+                .disks
+                .iter()
+                .filter(|disk| {
+                    disks
+                        .model
+                        .as_ref()
+                        .is_none_or(|model| contains_ignore_case(&disk.model, model))
+                        && min_size.is_none_or(|min_size| disk.size_bytes >= min_size)
+                })
+                .collect();
+            let mut item = "disks".to_string();
+            if let Some(model) = &disks.model {
+                item.push_str(&format!(" {}", model));
+            }
+            if let Some(min_size) = &disks.min_size {
+                item.push_str(&format!(" of at least {}", min_size));
+            }
+            let names: Vec<&str> = matching.iter().map(|disk| disk.name.as_str()).collect();
+            checks.push(ManifestCheck::new(
+                &item,
+                disks.count,
+                format!("{} ({})", matching.len(), names.join(", ")),
+                matching.len() == disks.count,
+            ));
+        }
+        for nics in &self.nics {
+            let matching: Vec<&NicInfo> = facts
+                .nics
+                .iter()
+                .filter(|nic| {
+                    nics.driver
+                        .as_ref()
+                        .is_none_or(|driver| contains_ignore_case(&nic.driver, driver))
+                        && nics.speed_gbps.is_none_or(|speed| {
+                            nic.speed_mbps.is_some_and(|found| found >= speed * 1000)
+                        })
+                })
+                .collect();
+            let mut item = "nics".to_string();
+            if let Some(driver) = &nics.driver {
+                item.push_str(&format!(" {}", driver));
+            }
+            if let Some(speed) = nics.speed_gbps {
+                item.push_str(&format!(" at {}G or faster", speed));
+            }
+            let names: Vec<&str> = matching.iter().map(|nic| nic.name.as_str()).collect();
+            checks.push(ManifestCheck::new(
+                &item,
+                nics.count,
+                format!("{} ({})", matching.len(), names.join(", ")),
+                matching.len() == nics.count,
+            ));
+        }
+        checks
+    }
+
+    /// Checks the installed memory; when the modules could not be read, only
+    /// the size is checked, against the memory visible to the kernel.
+    fn check_memory(
+        memory: &ExpectedMemory,
+        facts: &MachineFacts,
+        checks: &mut Vec<ManifestCheck>,
+    ) {
+        let modules: Vec<&Dimm> = facts
+            .dimms
+            .iter()
+            .filter(|dimm| dimm.is_populated())
+            .collect();
+        if let Some(size) = memory
+            .size
+            .as_deref()
+            .and_then(|size| parse_size(size).ok())
+        {
+            if modules.is_empty() {
+                let found = facts.kernel_memory_bytes;
+                checks.push(ManifestCheck::new(
+                    "memory size",
+                    format_volume(size),
+                    format!("{} visible to the kernel", format_volume(found)),
+                    found as f64 >= size as f64 * (1.0 - KERNEL_MEMORY_TOLERANCE),
+                ));
+            } else {
+                let found: u64 = modules.iter().map(|dimm| dimm.size_bytes).sum();
+                checks.push(ManifestCheck::new(
+                    "memory size",
+                    format_volume(size),
+                    format_volume(found),
+                    found == size,
+                ));
+            }
+        }
+        let unreadable = "unknown (the memory modules could not be read)";
+        if let Some(expected) = memory.modules {
+            let passed = modules.len() == expected;
+            let found = if modules.is_empty() {
+                unreadable.to_string()
+            } else {
+                modules.len().to_string()
+            };
+            checks.push(ManifestCheck::new(
+                "memory modules",
+                expected,
+                found,
+                passed,
+            ));
+        }
+        if let Some(expected) = &memory.memory_type {
+            let mut types: Vec<&str> = modules
+                .iter()
+                .map(|dimm| dimm.memory_type.as_str())
+                .collect();
+            types.sort_unstable();
+            types.dedup();
+            let passed = !modules.is_empty()
+                && modules
+                    .iter()
+                    .all(|dimm| dimm.memory_type.eq_ignore_ascii_case(expected));
+            let found = if modules.is_empty() {
+                unreadable.to_string()
+            } else {
+                types.join(", ")
+            };
+            checks.push(ManifestCheck::new("memory type", expected, found, passed));
+        }
+        if let Some(expected) = memory.speed {
+            let slowest = modules
+                .iter()
+                .filter_map(|dimm| dimm.configured_speed_mts)
+                .min();
+            checks.push(ManifestCheck::new(
+                "memory speed",
+                format!("{} MT/s", expected),
+                slowest.map_or(unreadable.to_string(), |speed| format!("{} MT/s", speed)),
+                slowest.is_some_and(|speed| speed >= expected),
+            ));
+        }
+    }
+}
+
+/// Formats the checks of a machine against a manifest.
+///
+/// # Arguments
+///
+/// * `manifest` - Where the manifest was read from, e.g. "intake.toml".
+/// * `checks` - The checks, as returned by `HardwareManifest::check`.
+///
+/// # Returns
+///
+/// * `String` - A heading with the number of failed checks, then one line per check.
+pub fn format_manifest_checks(manifest: &str, checks: &[ManifestCheck]) -> String {
+    let failed = checks.iter().filter(|check| !check.passed).count();
+    let mut output = if failed == 0 {
+        format!("The machine matches {}.\n", manifest)
+    } else {
+        format!(
+            "The machine does not match {}: {} of {} checks failed.\n",
+            manifest,
+            failed,
+            checks.len()
+        )
+    };
+    for check in checks {
+        output.push_str(&format!("  {}\n", check.render()));
+    }
+    output
+}
@@ -0,0 +1,172 @@
+//! Hook Domain Entity
+//!
+//! This module provides the hooks of a test profile: steps that run a command
+//! or send a Redfish operation to the BMC instead of running a test, placed
+//! before, between or after the tests of the profile. A hook can update the
+//! firmware and then reboot the machine; the run is saved before the reboot
+//! and `oneforall resume --after-reboot` continues it with the next step once
+//! the machine is back, so one profile updates the firmware and qualifies the
+//! machine on it.
+
+use std::fmt;
+
+use serde_json::Value;
+
+/// The HTTP methods a Redfish operation may use.
+const REDFISH_METHODS: &[&str] = &["GET", "POST", "PATCH", "PUT", "DELETE"];
+
+/// A Redfish operation sent to the BMC, e.g. a `SimpleUpdate` of the firmware.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedfishOperation {
+    /// The HTTP method, e.g. "POST".
+    pub method: String,
+
+    /// The path of the resource or action, e.g. "/redfish/v1/UpdateService/Actions/UpdateService.SimpleUpdate".
+    pub path: String,
+
+    /// The JSON body sent with the operation, if any.
+    pub body: Option<String>,
+}
+
+impl RedfishOperation {
+    /// Parses an operation written as a method followed by a path.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The operation, e.g. "POST /redfish/v1/Systems/1/Actions/ComputerSystem.Reset".
+    /// * `body` - The JSON body sent with the operation, if any.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<RedfishOperation, String>` - The operation, or why it is invalid.
+    pub fn parse(text: &str, body: Option<&str>) -> Result<Self, String> {
+        let (method, path) = text.trim().split_once(char::is_whitespace).ok_or_else(|| {
+            format!(
+                "Invalid Redfish operation '{}'; expected e.g. 'POST /redfish/v1/...'",
+                text
+            )
+        })?;
+        let method = method.to_uppercase();
+        if !REDFISH_METHODS.contains(&method.as_str()) {
+            return Err(format!(
+                "Invalid Redfish method '{}'; expected one of {}",
+                method,
+                REDFISH_METHODS.join(", ")
+            ));
+        }
+        let path = path.trim();
+        if !path.starts_with("/redfish/") {
+            return Err(format!(
+                "Invalid Redfish path '{}'; expected a path under /redfish/",
+                path
+            ));
+        }
+        if let Some(body) = body {
+            serde_json::from_str::<Value>(body)
+                .map_err(|e| format!("Invalid JSON body for {} {}: {}", method, path, e))?;
+        }
+        Ok(RedfishOperation {
+            method,
+            path: path.to_string(),
+            body: body.map(str::to_string),
+        })
+    }
+}
+
+/// What a hook step does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookAction {
+    /// Runs a program with its arguments, e.g. `["fwupdmgr", "update", "-y"]`.
+    Command(Vec<String>),
+
+    /// Sends an operation to the BMC over Redfish.
+    Redfish(RedfishOperation),
+}
+
+impl fmt::Display for HookAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HookAction::Command(command) => write!(f, "`{}`", command.join(" ")),
+            HookAction::Redfish(operation) => {
+                write!(f, "Redfish {} {}", operation.method, operation.path)
+            }
+        }
+    }
+}
+
+/// The state of a Redfish task, such as a firmware update the BMC runs in the background.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskOutcome {
+    /// The task has not finished yet.
+    Running,
+
+    /// The task finished without error.
+    Completed,
+
+    /// The task failed, was killed or was cancelled, with the reason the BMC gave.
+    Failed(String),
+}
+
+/// Resolves where the BMC says its task can be followed to a path under its base URL.
+///
+/// The task is polled with the credentials of the BMC, so a reference to any
+/// other host, or an absolute URL outside the base URL, is refused rather than
+/// followed.
+///
+/// # Arguments
+///
+/// * `base_url` - The base URL of the BMC, without a trailing slash, e.g. "https://10.0.0.5".
+/// * `reference` - The `@odata.id` of the task or the `Location` of its monitor.
+///
+/// # Returns
+///
+/// * `Result<String, String>` - The path of the task, e.g. "/redfish/v1/TaskService/Tasks/1",
+///   or why it is refused.
+pub fn redfish_task_path(base_url: &str, reference: &str) -> Result<String, String> {
+    let path = reference
+        .strip_prefix(base_url)
+        .filter(|path| path.starts_with('/'))
+        .unwrap_or(reference);
+    if path.starts_with('/') && !path.starts_with("//") {
+        Ok(path.to_string())
+    } else {
+        Err(format!(
+            "The BMC pointed its task at '{}', which is not a path under {}",
+            reference, base_url
+        ))
+    }
+}
+
+/// Reads the outcome of a Redfish task from its resource.
+///
+/// # Arguments
+///
+/// * `task` - The task resource, as returned by the `TaskMonitor` or `Tasks` URI.
+///
+/// # Returns
+///
+/// * `TaskOutcome` - Whether the task is running, completed or failed; a completed
+///   task whose status is critical counts as failed.
+pub fn redfish_task_outcome(task: &Value) -> TaskOutcome {
+    let state = task["TaskState"].as_str().unwrap_or("Running");
+    let status = task["TaskStatus"].as_str().unwrap_or("OK");
+    let reason = || {
+        let messages: Vec<&str> = task["Messages"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|message| message["Message"].as_str())
+            .collect();
+        if messages.is_empty() {
+            format!("task {} ({})", state, status)
+        } else {
+            messages.join("; ")
+        }
+    };
+    match state {
+        "Completed" if status == "Critical" => TaskOutcome::Failed(reason()),
+        "Completed" => TaskOutcome::Completed,
+        "Exception" | "Killed" | "Cancelled" => TaskOutcome::Failed(reason()),
+        _ => TaskOutcome::Running,
+    }
+}
@@ -0,0 +1,206 @@
+//! Integrity Domain Entity
+//!
+//! This module provides the data-integrity verification run alongside a stress
+//! test. Pseudorandom patterns are written to a memory buffer and a file on
+//! disk, checksummed, and re-read over and over while the machine is under
+//! load. Any word that reads back differently is reported with its address or
+//! offset, which points at a flaky DIMM, memory controller or disk controller.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::domain::burn_in::{MonitorResult, MonitorStatus};
+use crate::domain::endurance::format_volume;
+use crate::domain::plan::{DiskWrites, PlannedStep};
+
+/// The number of corrupt words whose location is reported; the rest are only counted.
+pub const MAX_REPORTED_CORRUPTIONS: usize = 20;
+
+/// Returns the pattern word stored at one index of a verification buffer.
+///
+/// Every word is derived from the seed and its index alone (with splitmix64),
+/// so the expected value of any word can be recomputed without storing it.
+///
+/// # Arguments
+///
+/// * `seed` - The seed of the verification run.
+/// * `index` - The index of the word in the buffer or file.
+///
+/// # Returns
+///
+/// * `u64` - The expected word.
+pub fn pattern_word(seed: u64, index: u64) -> u64 {
+    let mut z = seed.wrapping_add(index.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Computes the checksum of a run of words (FNV-1a over the words).
+pub fn checksum(words: &[u64]) -> u64 {
+    words.iter().fold(0xCBF2_9CE4_8422_2325, |hash, word| {
+        (hash ^ word).wrapping_mul(0x0000_0100_0000_01B3)
+    })
+}
+
+/// Describes how a word read back differs from the one written, e.g.
+/// "expected 0x00ff00ff00ff00ff, read 0x00ff00ff00ff00fe (1 bit flipped)".
+pub fn describe_mismatch(expected: u64, actual: u64) -> String {
+    let flipped = (expected ^ actual).count_ones();
+    format!(
+        "expected {:#018x}, read {:#018x} ({} bit{} flipped)",
+        expected,
+        actual,
+        flipped,
+        if flipped == 1 { "" } else { "s" }
+    )
+}
+
+/// What to verify alongside a stress test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegrityPlan {
+    /// The size of the memory buffer to verify, in bytes.
+    pub memory_bytes: usize,
+
+    /// The size of the file to verify, in bytes.
+    pub disk_bytes: u64,
+
+    /// The directory the file is written to.
+    pub directory: PathBuf,
+}
+
+impl IntegrityPlan {
+    /// Plans the verification for `--dry-run`: the pattern file is written once
+    /// and re-read, with the memory buffer, until the stress test ends.
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - How long the stress test it runs alongside lasts.
+    ///
+    /// # Returns
+    ///
+    /// * `PlannedStep` - The verification as a planned step.
+    pub fn planned_step(&self, duration: Duration) -> PlannedStep {
+        PlannedStep {
+            duration: Some(duration),
+            targets: vec![
+                format!("{} of memory", format_volume(self.memory_bytes as u64)),
+                self.directory.display().to_string(),
+            ],
+            disk_writes: DiskWrites::Volume(self.disk_bytes),
+            ..PlannedStep::new(
+                "integrity",
+                "write pseudorandom patterns to memory and a file, re-reading them throughout",
+                &[],
+            )
+        }
+    }
+}
+
+/// A word that did not read back as written.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Corruption {
+    /// Where the word lives, e.g. "address 0x7f3a2c001040 (physical 0x2b4c01040)"
+    /// or "offset 1048576".
+    pub location: String,
+
+    /// What was wrong with it, e.g. the expected and actual values or the read error.
+    pub detail: String,
+}
+
+/// The result of verifying one target.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct IntegrityResult {
+    /// The bytes of pattern written.
+    pub bytes: u64,
+
+    /// The number of complete verification passes over the pattern.
+    pub passes: u64,
+
+    /// The number of distinct words found corrupt.
+    pub corrupt_words: u64,
+
+    /// The first corrupt words found.
+    pub corruptions: Vec<Corruption>,
+}
+
+impl IntegrityResult {
+    /// Records a corrupt word, keeping only the first locations.
+    pub fn record(&mut self, location: String, detail: String) {
+        self.corrupt_words += 1;
+        if self.corruptions.len() < MAX_REPORTED_CORRUPTIONS {
+            self.corruptions.push(Corruption { location, detail });
+        }
+    }
+}
+
+/// The result of the data-integrity verification of a stress test.
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityReport {
+    /// The memory verification, or why it could not run.
+    pub memory: Result<IntegrityResult, String>,
+
+    /// The disk verification, or why it could not run.
+    pub disk: Result<IntegrityResult, String>,
+}
+
+impl IntegrityReport {
+    /// Returns `true` if no target found corruption.
+    pub fn passed(&self) -> bool {
+        [&self.memory, &self.disk]
+            .iter()
+            .all(|result| result.as_ref().map_or(true, |r| r.corrupt_words == 0))
+    }
+
+    /// Converts the verification into fault monitor results for the burn-in report.
+    pub fn monitors(&self) -> Vec<MonitorResult> {
+        [("MEMORY", &self.memory), ("DISK", &self.disk)]
+            .into_iter()
+            .map(|(source, result)| MonitorResult {
+                source: source.to_string(),
+                status: match result {
+                    Err(reason) => MonitorStatus::Unavailable(reason.clone()),
+                    Ok(result) if result.corrupt_words == 0 => MonitorStatus::Clean(format!(
+                        "{} verified {} time(s)",
+                        format_volume(result.bytes),
+                        result.passes
+                    )),
+                    Ok(result) => MonitorStatus::Faults(
+                        std::iter::once(format!("{} corrupt word(s)", result.corrupt_words))
+                            .chain(
+                                result
+                                    .corruptions
+                                    .iter()
+                                    .map(|c| format!("{}: {}", c.location, c.detail)),
+                            )
+                            .collect(),
+                    ),
+                },
+            })
+            .collect()
+    }
+
+    /// Renders the verification as plain text suitable for the terminal.
+    pub fn render(&self) -> String {
+        let mut output = String::from("Data integrity:\n");
+        for monitor in self.monitors() {
+            match monitor.status {
+                MonitorStatus::Clean(summary) => {
+                    output.push_str(&format!("  {:<6} ok      {}\n", monitor.source, summary))
+                }
+                MonitorStatus::Faults(faults) => {
+                    output.push_str(&format!("  {:<6} CORRUPT\n", monitor.source));
+                    for fault in faults {
+                        output.push_str(&format!("           {}\n", fault));
+                    }
+                }
+                MonitorStatus::Unavailable(reason) => {
+                    output.push_str(&format!("  {:<6} skipped {}\n", monitor.source, reason))
+                }
+            }
+        }
+        output
+    }
+}
@@ -0,0 +1,186 @@
+//! Interrupts Domain Entity
+//!
+//! This module provides the per-CPU interrupt counters sampled while a run
+//! loads the network, and the report that shows how network interrupts and
+//! their softirqs were spread over the CPUs. A NIC without receive side
+//! scaling, or whose queues all have their IRQ affinity on one CPU, makes
+//! that CPU handle every packet; it saturates long before the link does.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Number of network interrupts and softirqs below which a run is not
+/// considered to have loaded the network, and no report is rendered.
+const MIN_NETWORK_EVENTS: u64 = 10_000;
+
+/// Share of the network interrupts and softirqs, in percent, above which a
+/// single CPU is reported as handling the network on its own.
+const CONCENTRATION_PERCENT: f64 = 75.0;
+
+/// Share of an interval, in percent, above which a CPU busy with hard and
+/// soft interrupts is reported as saturated.
+const SATURATION_PERCENT: f64 = 80.0;
+
+/// Cumulative interrupt counters and CPU time of a single logical CPU.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CpuInterrupts {
+    /// The logical CPU ID.
+    pub cpu: u32,
+
+    /// Interrupts raised by network devices.
+    pub network_irqs: u64,
+
+    /// `NET_RX` softirqs, in which received packets are processed.
+    pub net_rx_softirqs: u64,
+
+    /// `NET_TX` softirqs, in which sent packets are completed.
+    pub net_tx_softirqs: u64,
+
+    /// Ticks spent servicing hard interrupts.
+    pub irq_ticks: u64,
+
+    /// Ticks spent servicing softirqs.
+    pub softirq_ticks: u64,
+
+    /// Ticks spent in every state.
+    pub total_ticks: u64,
+}
+
+/// The interrupts a CPU handled over a run.
+#[derive(Debug, Clone)]
+pub struct CpuInterruptLoad {
+    /// The logical CPU ID.
+    pub cpu: u32,
+
+    /// Interrupts raised by network devices.
+    pub network_irqs: u64,
+
+    /// `NET_RX` softirqs.
+    pub net_rx_softirqs: u64,
+
+    /// `NET_TX` softirqs.
+    pub net_tx_softirqs: u64,
+
+    /// The share of the run spent servicing hard and soft interrupts, in percent.
+    pub average_percent: f64,
+
+    /// The highest share of a single sampling interval spent servicing them, in percent.
+    pub peak_percent: f64,
+}
+
+impl CpuInterruptLoad {
+    /// Returns the network interrupts and softirqs together.
+    pub fn network_events(&self) -> u64 {
+        self.network_irqs + self.net_rx_softirqs + self.net_tx_softirqs
+    }
+}
+
+/// Returns the share of the ticks between two readings spent servicing interrupts, in percent.
+fn interrupt_percent(before: &CpuInterrupts, after: &CpuInterrupts) -> f64 {
+    let total = after.total_ticks.saturating_sub(before.total_ticks);
+    let busy = after.irq_ticks.saturating_sub(before.irq_ticks)
+        + after.softirq_ticks.saturating_sub(before.softirq_ticks);
+    if total == 0 {
+        0.0
+    } else {
+        busy.min(total) as f64 * 100.0 / total as f64
+    }
+}
+
+/// Summarizes the interrupts every CPU handled between the first and last sample.
+///
+/// # Arguments
+///
+/// * `samples` - Interrupt counters of every CPU with their offset into the run.
+///
+/// # Returns
+///
+/// * `Vec<CpuInterruptLoad>` - One entry per CPU present in the first and last sample, by CPU ID.
+pub fn interrupt_loads(samples: &[(Duration, Vec<CpuInterrupts>)]) -> Vec<CpuInterruptLoad> {
+    let (Some((_, first)), Some((_, last))) = (samples.first(), samples.last()) else {
+        return Vec::new();
+    };
+
+    let mut peaks: BTreeMap<u32, f64> = BTreeMap::new();
+    for pair in samples.windows(2) {
+        for after in &pair[1].1 {
+            if let Some(before) = pair[0].1.iter().find(|c| c.cpu == after.cpu) {
+                let peak = peaks.entry(after.cpu).or_default();
+                *peak = peak.max(interrupt_percent(before, after));
+            }
+        }
+    }
+
+    let mut loads: Vec<CpuInterruptLoad> = last
+        .iter()
+        .filter_map(|end| {
+            let start = first.iter().find(|c| c.cpu == end.cpu)?;
+            Some(CpuInterruptLoad {
+                cpu: end.cpu,
+                network_irqs: end.network_irqs.saturating_sub(start.network_irqs),
+                net_rx_softirqs: end.net_rx_softirqs.saturating_sub(start.net_rx_softirqs),
+                net_tx_softirqs: end.net_tx_softirqs.saturating_sub(start.net_tx_softirqs),
+                average_percent: interrupt_percent(start, end),
+                peak_percent: peaks.get(&end.cpu).copied().unwrap_or_default(),
+            })
+        })
+        .collect();
+    loads.sort_by_key(|load| load.cpu);
+    loads
+}
+
+/// Renders how network interrupts were spread over the CPUs during a run.
+///
+/// # Arguments
+///
+/// * `samples` - Interrupt counters of every CPU with their offset into the run.
+///
+/// # Returns
+///
+/// * `String` - The per-CPU distribution followed by any concentration or
+///   saturation found, or an empty string if the run hardly used the network.
+pub fn format_interrupt_report(samples: &[(Duration, Vec<CpuInterrupts>)]) -> String {
+    let loads = interrupt_loads(samples);
+    let total: u64 = loads.iter().map(CpuInterruptLoad::network_events).sum();
+    if total < MIN_NETWORK_EVENTS {
+        return String::new();
+    }
+
+    let mut output = String::from("Network interrupt distribution\n");
+    output.push_str("  CPU   Net IRQs    NET_RX    NET_TX   Share   IRQ time avg/peak\n");
+    for load in loads.iter().filter(|load| load.network_events() > 0) {
+        output.push_str(&format!(
+            "  {:>3}  {:>9}  {:>8}  {:>8}  {:>5.1}%   {:>5.1}% / {:>5.1}%\n",
+            load.cpu,
+            load.network_irqs,
+            load.net_rx_softirqs,
+            load.net_tx_softirqs,
+            load.network_events() as f64 * 100.0 / total as f64,
+            load.average_percent,
+            load.peak_percent
+        ));
+    }
+
+    if let Some(busiest) = loads.iter().max_by_key(|load| load.network_events()) {
+        let share = busiest.network_events() as f64 * 100.0 / total as f64;
+        if loads.len() > 1 && share > CONCENTRATION_PERCENT {
+            output.push_str(&format!(
+                "WARNING: CPU {} handled {:.0}% of the network interrupts; check that RSS spreads \
+                 the NIC queues (ethtool -l) and that their IRQ affinity (/proc/irq/*/smp_affinity) \
+                 or irqbalance places them on different CPUs.\n",
+                busiest.cpu, share
+            ));
+        }
+    }
+    for load in loads
+        .iter()
+        .filter(|load| load.peak_percent > SATURATION_PERCENT)
+    {
+        output.push_str(&format!(
+            "WARNING: CPU {} spent up to {:.0}% of its time servicing interrupts and is likely \
+             limiting network throughput.\n",
+            load.cpu, load.peak_percent
+        ));
+    }
+    output
+}
@@ -0,0 +1,150 @@
+//! Kernel Latency Domain Entity
+//!
+//! This module provides the distributions measured in the kernel by the eBPF
+//! probes of `stress --ebpf`: how long runnable threads wait in the run queue
+//! before a CPU picks them up, how long blocked threads stay off their CPU,
+//! and how long block I/O requests take from issue to completion. Sampling
+//! `/proc` only shows averages over a second at best, while the probes time
+//! every event, so a run queue backing up under load or a drive stalling for
+//! a few hundred milliseconds shows in the tail of the distribution.
+//!
+//! The probes count events in log2 buckets of microseconds, as the BCC tools
+//! `runqlat`, `offcputime` and `biolatency` do, so percentiles are known to a
+//! factor of two and reported as the bound of their bucket.
+
+use std::time::Duration;
+
+/// The buckets of each histogram; bucket n counts latencies from 2^n µs, the
+/// first those under 2 µs and the last those of 2^26 µs (67 s) or more.
+pub const LATENCY_BUCKETS: usize = 27;
+
+/// The distribution of the latencies of one kind of event.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LatencyHistogram {
+    /// The events counted in each bucket.
+    pub buckets: Vec<u64>,
+}
+
+impl LatencyHistogram {
+    /// Returns the number of events measured.
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    /// Returns the bound a share of the events stayed under.
+    ///
+    /// # Arguments
+    ///
+    /// * `percentile` - The share of the events, e.g. 99.0.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<u64>` - The upper bound of the bucket the percentile falls in, in
+    ///   microseconds, or `None` without events.
+    pub fn percentile_us(&self, percentile: f64) -> Option<u64> {
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+        let rank = ((percentile / 100.0 * count as f64).ceil() as u64).clamp(1, count);
+        let mut seen = 0;
+        self.buckets
+            .iter()
+            .enumerate()
+            .find_map(|(bucket, events)| {
+                seen += events;
+                (seen >= rank).then(|| bucket_bound_us(bucket))
+            })
+    }
+
+    /// Returns the upper bound of the highest bucket holding an event, in
+    /// microseconds, or `None` without events.
+    pub fn max_us(&self) -> Option<u64> {
+        self.buckets
+            .iter()
+            .rposition(|events| *events > 0)
+            .map(bucket_bound_us)
+    }
+}
+
+/// Returns the latency a bucket counts events under, in microseconds.
+fn bucket_bound_us(bucket: usize) -> u64 {
+    2u64 << bucket
+}
+
+/// Formats a bucket bound, e.g. "<64 µs" or "<1.0 ms".
+fn format_bound(us: Option<u64>) -> String {
+    match us {
+        None => "-".to_string(),
+        Some(us) if us >= 1_000_000 => format!("<{:.1} s", us as f64 / 1e6),
+        Some(us) if us >= 1000 => format!("<{:.1} ms", us as f64 / 1e3),
+        Some(us) => format!("<{} µs", us),
+    }
+}
+
+/// The latencies measured in the kernel over a run.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KernelLatencyReport {
+    /// How long the probes were attached.
+    pub duration: Duration,
+
+    /// The time from a thread becoming runnable until it ran.
+    pub run_queue: LatencyHistogram,
+
+    /// The time from a thread blocking until it ran again.
+    pub off_cpu: LatencyHistogram,
+
+    /// The time from a block I/O request being issued to its device until it completed.
+    pub block_io: LatencyHistogram,
+}
+
+impl KernelLatencyReport {
+    /// Creates the report from the histograms as the probes store them, one after
+    /// another in a single array.
+    ///
+    /// # Arguments
+    ///
+    /// * `counts` - The run queue, off-CPU and block I/O buckets, `LATENCY_BUCKETS` each.
+    /// * `duration` - How long the probes were attached.
+    ///
+    /// # Returns
+    ///
+    /// * `KernelLatencyReport` - The report; histograms missing from `counts` are empty.
+    pub fn from_counts(counts: &[u64], duration: Duration) -> Self {
+        let mut histograms = counts
+            .chunks(LATENCY_BUCKETS)
+            .map(|buckets| LatencyHistogram {
+                buckets: buckets.to_vec(),
+            });
+        KernelLatencyReport {
+            duration,
+            run_queue: histograms.next().unwrap_or_default(),
+            off_cpu: histograms.next().unwrap_or_default(),
+            block_io: histograms.next().unwrap_or_default(),
+        }
+    }
+
+    /// Renders the report as a table of the distributions.
+    pub fn render(&self) -> String {
+        let mut output = format!(
+            "Kernel latency (eBPF, {:.0}s)\n  Latency       Events       p50       p99     p99.9       Max\n",
+            self.duration.as_secs_f64()
+        );
+        for (name, histogram) in [
+            ("run queue", &self.run_queue),
+            ("off-CPU", &self.off_cpu),
+            ("block I/O", &self.block_io),
+        ] {
+            output.push_str(&format!(
+                "  {:<10} {:>9} {:>9} {:>9} {:>9} {:>9}\n",
+                name,
+                histogram.count(),
+                format_bound(histogram.percentile_us(50.0)),
+                format_bound(histogram.percentile_us(99.0)),
+                format_bound(histogram.percentile_us(99.9)),
+                format_bound(histogram.max_us())
+            ));
+        }
+        output
+    }
+}
@@ -0,0 +1,210 @@
+//! Kernel Log Domain Entity
+//!
+//! This module provides the classification of kernel log messages seen while
+//! a test runs. Machine checks, I/O errors and OOM kills mean the hardware or
+//! the test setup failed and the run cannot be trusted; thermal events are
+//! reported alongside them but do not fail a run on their own.
+
+use serde::Serialize;
+
+use crate::domain::burn_in::{MonitorResult, MonitorStatus};
+
+/// Kernel log fragments that mark a machine check event.
+pub const MACHINE_CHECK_MARKERS: &[&str] = &["mce:", "Machine check", "[Hardware Error]"];
+
+/// Kernel log fragments that mark a failed or retried block device request.
+const IO_ERROR_MARKERS: &[&str] = &[
+    "I/O error",
+    "critical medium error",
+    "Medium Error",
+    "failed command:",
+    "timeout, aborting",
+    "controller is down",
+    "EXT4-fs error",
+    "BTRFS error",
+    "metadata I/O error",
+];
+
+/// Kernel log fragments that mark the OOM killer ending a process.
+const OUT_OF_MEMORY_MARKERS: &[&str] = &["invoked oom-killer", "Out of memory:", "oom-kill:"];
+
+/// Kernel log fragments that mark thermal throttling or a thermal trip.
+const THERMAL_MARKERS: &[&str] = &[
+    "temperature above threshold",
+    "cpu clock throttled",
+    "critical temperature reached",
+    "thermal shutdown",
+];
+
+/// The number of serious and of thermal events kept in the findings; the rest are only counted.
+const MAX_RECORDED_EVENTS: usize = 50;
+
+/// The kind of a kernel log event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KernelEventKind {
+    /// A machine check exception or other hardware error report.
+    MachineCheck,
+
+    /// A failed block device request or filesystem error.
+    IoError,
+
+    /// The OOM killer ended a process.
+    OutOfMemory,
+
+    /// A CPU or device was throttled or tripped for temperature.
+    Thermal,
+}
+
+impl KernelEventKind {
+    /// Returns a short label for reports, e.g. "I/O error".
+    pub fn label(&self) -> &'static str {
+        match self {
+            KernelEventKind::MachineCheck => "machine check",
+            KernelEventKind::IoError => "I/O error",
+            KernelEventKind::OutOfMemory => "OOM kill",
+            KernelEventKind::Thermal => "thermal",
+        }
+    }
+
+    /// Returns `true` if events of this kind fail a run.
+    pub fn is_serious(&self) -> bool {
+        !matches!(self, KernelEventKind::Thermal)
+    }
+}
+
+/// Classifies one kernel log line.
+///
+/// # Arguments
+///
+/// * `line` - A message from `dmesg` or the kernel journal.
+///
+/// # Returns
+///
+/// * `Option<KernelEventKind>` - The kind of event, or `None` for routine messages.
+pub fn classify_kernel_line(line: &str) -> Option<KernelEventKind> {
+    let matches = |markers: &[&str]| markers.iter().any(|marker| line.contains(marker));
+    if matches(MACHINE_CHECK_MARKERS) {
+        Some(KernelEventKind::MachineCheck)
+    } else if matches(IO_ERROR_MARKERS) {
+        Some(KernelEventKind::IoError)
+    } else if matches(OUT_OF_MEMORY_MARKERS) {
+        Some(KernelEventKind::OutOfMemory)
+    } else if matches(THERMAL_MARKERS) {
+        Some(KernelEventKind::Thermal)
+    } else {
+        None
+    }
+}
+
+/// A kernel log message that matters to a test run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KernelEvent {
+    /// The kind of event.
+    pub kind: KernelEventKind,
+
+    /// The kernel message.
+    pub message: String,
+}
+
+/// The kernel log events seen during a run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KernelLogFindings {
+    /// The log that was watched, e.g. "journald", or why none could be.
+    pub source: Result<String, String>,
+
+    /// The number of events seen.
+    pub event_count: usize,
+
+    /// The number of serious events seen.
+    pub serious_count: usize,
+
+    /// The first events seen.
+    pub events: Vec<KernelEvent>,
+}
+
+impl KernelLogFindings {
+    /// Creates empty findings for a watched log.
+    pub fn watching(source: &str) -> Self {
+        KernelLogFindings {
+            source: Ok(source.to_string()),
+            event_count: 0,
+            serious_count: 0,
+            events: Vec::new(),
+        }
+    }
+
+    /// Records an event, keeping only the first ones of each severity so a flood
+    /// of thermal messages cannot crowd out a machine check.
+    pub fn record(&mut self, event: KernelEvent) {
+        self.event_count += 1;
+        if event.kind.is_serious() {
+            self.serious_count += 1;
+        }
+        let kept = self
+            .events
+            .iter()
+            .filter(|e| e.kind.is_serious() == event.kind.is_serious())
+            .count();
+        if kept < MAX_RECORDED_EVENTS {
+            self.events.push(event);
+        }
+    }
+
+    /// Returns `true` if a serious event was seen.
+    pub fn has_serious_events(&self) -> bool {
+        self.serious_count > 0
+    }
+
+    /// Lists the serious events as "<kind>: <message>", for run results and acceptance checks.
+    pub fn faults(&self) -> Vec<String> {
+        self.events
+            .iter()
+            .filter(|event| event.kind.is_serious())
+            .map(|event| format!("{}: {}", event.kind.label(), event.message))
+            .collect()
+    }
+
+    /// Returns the serious events, or why the kernel log was not watched.
+    pub fn watched_faults(&self) -> Result<Vec<String>, String> {
+        self.source
+            .as_ref()
+            .map(|_| self.faults())
+            .map_err(Clone::clone)
+    }
+
+    /// Converts the findings into a fault monitor result for the burn-in report.
+    pub fn monitor_result(&self) -> MonitorResult {
+        let faults = self.faults();
+        let status = match &self.source {
+            Err(reason) => MonitorStatus::Unavailable(reason.clone()),
+            Ok(_) if self.has_serious_events() => MonitorStatus::Faults(faults),
+            Ok(source) => {
+                MonitorStatus::Clean(format!("{}, {} thermal event(s)", source, self.event_count))
+            }
+        };
+        MonitorResult {
+            source: "KERNEL".to_string(),
+            status,
+        }
+    }
+
+    /// Renders the findings as a section of the run telemetry report.
+    pub fn render(&self) -> String {
+        match &self.source {
+            Err(reason) => format!("Kernel log: not watched ({})\n", reason),
+            Ok(source) if self.events.is_empty() => format!(
+                "Kernel log: no machine check, I/O error, OOM or thermal events ({})\n",
+                source
+            ),
+            Ok(source) => {
+                let mut output =
+                    format!("Kernel log: {} event(s) ({})\n", self.event_count, source);
+                for event in &self.events {
+                    output.push_str(&format!("  {:<14} {}\n", event.kind.label(), event.message));
+                }
+                output
+            }
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! Kubernetes Domain Entity
+//!
+//! This module provides what the DaemonSet mode needs to know about the
+//! Kubernetes node it qualifies: the identity of the node and pod, exposed to
+//! the container through the downward API, and the labels and taint that
+//! record the verdict of the qualification on the node. Schedulers and
+//! operators select on the labels, and the taint keeps new pods off a node
+//! whose hardware failed until it passes again.
+
+use std::collections::BTreeMap;
+
+/// The environment variable holding the node name, set from `spec.nodeName` by the downward API.
+pub const NODE_NAME_ENV: &str = "NODE_NAME";
+
+/// The environment variable holding the pod name, set from `metadata.name` by the downward API.
+pub const POD_NAME_ENV: &str = "POD_NAME";
+
+/// The environment variable holding the pod namespace, set from `metadata.namespace` by the downward API.
+pub const POD_NAMESPACE_ENV: &str = "POD_NAMESPACE";
+
+/// The label holding the verdict of the qualification, "passed" or "failed".
+pub const QUALIFICATION_LABEL: &str = "oneforall.io/qualification";
+
+/// The label holding the name of the profile the node was qualified with.
+pub const PROFILE_LABEL: &str = "oneforall.io/profile";
+
+/// The label holding when the qualification finished, in seconds since the Unix epoch.
+pub const QUALIFIED_AT_LABEL: &str = "oneforall.io/qualified-at";
+
+/// The key of the taint placed on nodes that fail qualification.
+pub const FAILURE_TAINT_KEY: &str = "oneforall.io/hardware-failed";
+
+/// The longest value a label may have.
+const MAX_LABEL_VALUE_LEN: usize = 63;
+
+/// The node and pod a DaemonSet pod runs as.
+#[derive(Debug, Clone)]
+pub struct NodeIdentity {
+    /// The name of the node, e.g. "gpu-node-17".
+    pub node_name: String,
+
+    /// The name of the pod, if exposed.
+    pub pod_name: Option<String>,
+
+    /// The namespace of the pod, if exposed.
+    pub namespace: Option<String>,
+}
+
+impl NodeIdentity {
+    /// Describes the node and pod for log messages, e.g. "node gpu-node-17 (pod oneforall/oneforall-x7k2p)".
+    pub fn describe(&self) -> String {
+        match (&self.namespace, &self.pod_name) {
+            (Some(namespace), Some(pod)) => {
+                format!("node {} (pod {}/{})", self.node_name, namespace, pod)
+            }
+            (None, Some(pod)) => format!("node {} (pod {})", self.node_name, pod),
+            _ => format!("node {}", self.node_name),
+        }
+    }
+}
+
+/// A taint on a node, which repels pods that do not tolerate it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeTaint {
+    /// The taint key.
+    pub key: String,
+
+    /// The taint value.
+    pub value: String,
+
+    /// What happens to pods not tolerating the taint, e.g. "NoSchedule".
+    pub effect: String,
+}
+
+/// Turns text into a valid label value: at most 63 letters, digits, '-', '_'
+/// and '.', starting and ending with a letter or digit.
+pub fn label_value(text: &str) -> String {
+    let value: String = text
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .take(MAX_LABEL_VALUE_LEN)
+        .collect();
+    value
+        .trim_matches(|c: char| !c.is_ascii_alphanumeric())
+        .to_string()
+}
+
+/// Builds the labels recording the verdict of a qualification.
+///
+/// # Arguments
+///
+/// * `profile` - The name of the profile the node was qualified with.
+/// * `passed` - Whether every step of the profile passed.
+/// * `finished_at` - When the qualification finished, in seconds since the Unix epoch.
+///
+/// # Returns
+///
+/// * `BTreeMap<String, String>` - The labels to set on the node.
+pub fn qualification_labels(
+    profile: &str,
+    passed: bool,
+    finished_at: i64,
+) -> BTreeMap<String, String> {
+    BTreeMap::from([
+        (
+            QUALIFICATION_LABEL.to_string(),
+            if passed { "passed" } else { "failed" }.to_string(),
+        ),
+        (PROFILE_LABEL.to_string(), label_value(profile)),
+        (QUALIFIED_AT_LABEL.to_string(), finished_at.to_string()),
+    ])
+}
+
+/// Builds the taint placed on a node that failed qualification with a profile.
+pub fn failure_taint(profile: &str) -> NodeTaint {
+    NodeTaint {
+        key: FAILURE_TAINT_KEY.to_string(),
+        value: label_value(profile),
+        effect: "NoSchedule".to_string(),
+    }
+}
@@ -1,7 +1,8 @@
-
+use common::ports::log_port::LoggerPort;
 
 /// `MyLogger` is a simple implementation of the `LoggerPort` trait.
 /// This struct provides basic logging functionality to the console.
+#[derive(Debug)]
 pub struct MyLogger;
 
 impl LoggerPort for MyLogger {
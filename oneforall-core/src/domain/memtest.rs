@@ -0,0 +1,247 @@
+//! Memtest Domain Entity
+//!
+//! This module provides the RAM test started by `oneforall stress --memtest`.
+//! Like memtester, it locks a buffer of memory in RAM and runs a sequence of
+//! pattern tests over it, pass after pass, reporting the address of every word
+//! that reads back differently. The ECC error counters of the memory
+//! controllers are read before and after the test, so a failing word can be
+//! told apart from a fault ECC corrected before the test could see it.
+
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::domain::burn_in::WorkloadOutcome;
+use crate::domain::endurance::format_volume;
+use crate::domain::integrity::{pattern_word, Corruption, MAX_REPORTED_CORRUPTIONS};
+use crate::domain::profile::format_duration;
+
+/// A pattern test run over the locked buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemtestPattern {
+    /// Pseudorandom words, catching faults that depend on neighbouring cells.
+    RandomPattern,
+
+    /// A single set bit walked across every bit of each word.
+    WalkingOnes,
+
+    /// A single cleared bit walked across every bit of each word.
+    WalkingZeros,
+
+    /// Each word holds its own address, then its complement, catching address
+    /// lines that are stuck or shorted.
+    AddressInAddress,
+}
+
+impl MemtestPattern {
+    /// Every test, in the order a pass runs them.
+    pub const ALL: [MemtestPattern; 4] = [
+        MemtestPattern::RandomPattern,
+        MemtestPattern::WalkingOnes,
+        MemtestPattern::WalkingZeros,
+        MemtestPattern::AddressInAddress,
+    ];
+
+    /// The name of the test, e.g. "walking ones".
+    pub fn name(&self) -> &'static str {
+        match self {
+            MemtestPattern::RandomPattern => "random pattern",
+            MemtestPattern::WalkingOnes => "walking ones",
+            MemtestPattern::WalkingZeros => "walking zeros",
+            MemtestPattern::AddressInAddress => "address in address",
+        }
+    }
+
+    /// The number of times the buffer is written and verified by the test.
+    pub fn rounds(&self) -> u32 {
+        match self {
+            MemtestPattern::RandomPattern => 1,
+            MemtestPattern::WalkingOnes | MemtestPattern::WalkingZeros => u64::BITS,
+            MemtestPattern::AddressInAddress => 2,
+        }
+    }
+
+    /// Returns the word the test writes at one place in the buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `round` - The round of the test, below `rounds()`.
+    /// * `seed` - The seed of the random pattern.
+    /// * `index` - The index of the word in the buffer.
+    /// * `address` - The virtual address of the word.
+    ///
+    /// # Returns
+    ///
+    /// * `u64` - The expected word.
+    pub fn word(&self, round: u32, seed: u64, index: u64, address: u64) -> u64 {
+        match self {
+            MemtestPattern::RandomPattern => pattern_word(seed, index),
+            MemtestPattern::WalkingOnes => 1 << round,
+            MemtestPattern::WalkingZeros => !(1 << round),
+            MemtestPattern::AddressInAddress if round == 0 => address,
+            MemtestPattern::AddressInAddress => !address,
+        }
+    }
+}
+
+/// The failures found by one pattern test over every pass.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MemtestTestResult {
+    /// The name of the test.
+    pub test: String,
+
+    /// The number of words that read back differently.
+    pub failures: u64,
+
+    /// The first failing words, with their address.
+    pub corruptions: Vec<Corruption>,
+}
+
+impl MemtestTestResult {
+    /// Creates the result of a test that has found nothing yet.
+    pub fn new(pattern: MemtestPattern) -> Self {
+        MemtestTestResult {
+            test: pattern.name().to_string(),
+            failures: 0,
+            corruptions: Vec::new(),
+        }
+    }
+
+    /// Records a failing word, keeping only the first locations.
+    pub fn record(&mut self, location: String, detail: String) {
+        self.failures += 1;
+        if self.corruptions.len() < MAX_REPORTED_CORRUPTIONS {
+            self.corruptions.push(Corruption { location, detail });
+        }
+    }
+}
+
+/// What the pattern tests found in the locked buffer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MemtestResult {
+    /// The size of the buffer tested.
+    pub bytes: u64,
+
+    /// Whether the buffer was locked in RAM; an unlocked buffer may be partly
+    /// swapped out, so its pages are not always tested in RAM.
+    pub locked: bool,
+
+    /// The number of complete passes of every test.
+    pub passes: u64,
+
+    /// The failures of each test.
+    pub tests: Vec<MemtestTestResult>,
+}
+
+impl MemtestResult {
+    /// Returns the number of failing words over every test.
+    pub fn failures(&self) -> u64 {
+        self.tests.iter().map(|test| test.failures).sum()
+    }
+}
+
+/// The result of a RAM test.
+#[derive(Debug, Clone, Serialize)]
+pub struct MemtestReport {
+    /// The local time the test started, in RFC 3339 format.
+    pub started_at: String,
+
+    /// The configured duration, in seconds.
+    pub planned_secs: u64,
+
+    /// The time the test actually ran, in seconds.
+    pub elapsed_secs: u64,
+
+    /// How the test ended.
+    pub outcome: WorkloadOutcome,
+
+    /// What the pattern tests found, once they ran.
+    pub result: Option<MemtestResult>,
+
+    /// The ECC errors reported by the memory controllers during the test, or
+    /// why they could not be read.
+    pub ecc_errors: Result<Vec<String>, String>,
+}
+
+impl MemtestReport {
+    /// Returns `true` if every test ran at least once, no word failed and ECC
+    /// reported no error.
+    pub fn passed(&self) -> bool {
+        self.outcome == WorkloadOutcome::Completed
+            && self
+                .result
+                .as_ref()
+                .is_some_and(|result| result.passes > 0 && result.failures() == 0)
+            && self.ecc_errors.as_ref().map_or(true, |e| e.is_empty())
+    }
+
+    /// Compares the failures found by the tests with the errors reported by ECC.
+    pub fn ecc_confirmation(&self) -> String {
+        let failures = self.result.as_ref().map_or(0, MemtestResult::failures);
+        match (&self.ecc_errors, failures) {
+            (Err(reason), _) => format!("unavailable ({})", reason),
+            (Ok(errors), 0) if errors.is_empty() => "no error reported".to_string(),
+            (Ok(errors), 0) => format!(
+                "{}; the faults were caught by ECC before the tests could see them",
+                errors.join(", ")
+            ),
+            (Ok(errors), _) if errors.is_empty() => {
+                "no error reported; the failures were not seen by ECC".to_string()
+            }
+            (Ok(errors), _) => format!("{}; confirms the failures", errors.join(", ")),
+        }
+    }
+
+    /// Renders the report as plain text suitable for the terminal.
+    pub fn render(&self) -> String {
+        let mut output = String::from("== RAM test report ==\n");
+        output.push_str(&format!(
+            "Duration:    {} of {} planned\n",
+            format_duration(Duration::from_secs(self.elapsed_secs)),
+            format_duration(Duration::from_secs(self.planned_secs))
+        ));
+        output.push_str(&format!(
+            "Test:        {}\n",
+            match &self.outcome {
+                WorkloadOutcome::Completed => "completed".to_string(),
+                WorkloadOutcome::Failed(e) => format!("FAILED ({})", e),
+                WorkloadOutcome::Cancelled => "cancelled".to_string(),
+            }
+        ));
+        if let Some(result) = &self.result {
+            output.push_str(&format!(
+                "Memory:      {} {}, {} pass(es)\n",
+                format_volume(result.bytes),
+                if result.locked {
+                    "locked in RAM"
+                } else {
+                    "NOT locked in RAM"
+                },
+                result.passes
+            ));
+            for test in &result.tests {
+                if test.failures == 0 {
+                    output.push_str(&format!("  {:<20} ok\n", test.test));
+                    continue;
+                }
+                output.push_str(&format!(
+                    "  {:<20} FAIL {} word(s)\n",
+                    test.test, test.failures
+                ));
+                for corruption in &test.corruptions {
+                    output.push_str(&format!(
+                        "    {}: {}\n",
+                        corruption.location, corruption.detail
+                    ));
+                }
+            }
+        }
+        output.push_str(&format!("ECC:         {}\n", self.ecc_confirmation()));
+
+        output.push_str(&format!(
+            "Result: {}\n",
+            if self.passed() { "PASS" } else { "FAIL" }
+        ));
+        output
+    }
+}
@@ -0,0 +1,227 @@
+//! Microburst Domain Entity
+//!
+//! This module provides the readings of `overwatch --burst-interval`, which
+//! samples CPU utilization every 100 ms or faster to catch the stalls and
+//! microbursts that readings a second apart average away. The ticks of each
+//! second are stored together as one frame rather than one reading each, and
+//! frames older than `burst_age` of `[database.retention]` are downsampled to a
+//! summary per minute keeping the peaks, so the database does not grow ten
+//! times faster for keeping a week of history.
+
+use std::time::Duration;
+
+use chrono::{DateTime, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+
+/// The collector the frames of ticks are stored under.
+pub const BURSTS_COLLECTOR: &str = "bursts";
+
+/// The collector the downsampled frames are stored under.
+pub const BURST_SUMMARIES_COLLECTOR: &str = "burst_summaries";
+
+/// The shortest burst interval; the kernel counts CPU time in ticks of 10 ms.
+pub const MIN_BURST_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How much time the ticks of one frame cover.
+pub const BURST_FRAME: Duration = Duration::from_secs(1);
+
+/// How much time a downsampled summary covers.
+pub const BURST_SUMMARY_WINDOW: Duration = Duration::from_secs(60);
+
+/// How long frames are kept at full resolution when no age is configured.
+pub const DEFAULT_BURST_AGE: Duration = Duration::from_secs(24 * 3600);
+
+/// The utilization of a CPU, in percent, from which a tick counts as a burst.
+pub const BURST_PERCENT: f64 = 95.0;
+
+/// The ticks of one second of sub-second sampling, stored under the time the
+/// last of them was taken.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BurstFrame {
+    /// The time between ticks, in milliseconds.
+    pub interval_ms: f64,
+
+    /// The utilization of every CPU together over each tick, in percent.
+    pub busy_percent: Vec<f64>,
+
+    /// The utilization of the busiest CPU over each tick, in percent.
+    pub peak_core_percent: Vec<f64>,
+
+    /// The busiest CPU over each tick.
+    pub peak_core: Vec<u32>,
+
+    /// How late each tick was taken, in milliseconds; a tick taken more than an
+    /// interval late means the sampler itself could not run, a stall.
+    pub late_ms: Vec<f64>,
+}
+
+impl BurstFrame {
+    /// Creates an empty frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - The time between ticks.
+    ///
+    /// # Returns
+    ///
+    /// * `BurstFrame` - The frame.
+    pub fn new(interval: Duration) -> Self {
+        BurstFrame {
+            interval_ms: interval.as_secs_f64() * 1000.0,
+            ..BurstFrame::default()
+        }
+    }
+
+    /// Adds a tick to the frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `cores` - The utilization of each CPU over the tick, by logical CPU ID.
+    /// * `late` - How late the tick was taken.
+    pub fn push(&mut self, cores: &[(u32, f64)], late: Duration) {
+        let busy = if cores.is_empty() {
+            0.0
+        } else {
+            cores.iter().map(|(_, percent)| percent).sum::<f64>() / cores.len() as f64
+        };
+        let (core, peak) = cores
+            .iter()
+            .copied()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap_or((0, 0.0));
+        self.busy_percent.push(busy);
+        self.peak_core_percent.push(peak);
+        self.peak_core.push(core);
+        self.late_ms.push(late.as_secs_f64() * 1000.0);
+    }
+
+    /// Returns the number of ticks in the frame.
+    pub fn ticks(&self) -> usize {
+        self.busy_percent.len()
+    }
+
+    /// Returns the ticks in which a CPU was busy for at least `BURST_PERCENT`.
+    pub fn bursts(&self) -> usize {
+        self.peak_core_percent
+            .iter()
+            .filter(|peak| **peak >= BURST_PERCENT)
+            .count()
+    }
+
+    /// Returns the ticks taken more than an interval late.
+    pub fn stalls(&self) -> usize {
+        self.late_ms
+            .iter()
+            .filter(|late| **late > self.interval_ms)
+            .count()
+    }
+}
+
+/// The frames of one window, downsampled for long-term retention.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BurstSummary {
+    /// The time between ticks, in milliseconds.
+    pub interval_ms: f64,
+
+    /// The ticks the summary stands for.
+    pub ticks: usize,
+
+    /// The average utilization of every CPU together, in percent.
+    pub busy_mean_percent: f64,
+
+    /// The highest utilization of every CPU together over a tick, in percent.
+    pub busy_max_percent: f64,
+
+    /// The lowest utilization of the busiest CPU over a tick, in percent.
+    pub peak_core_min_percent: f64,
+
+    /// The average utilization of the busiest CPU over a tick, in percent.
+    pub peak_core_mean_percent: f64,
+
+    /// The highest utilization of any CPU over a tick, in percent.
+    pub peak_core_max_percent: f64,
+
+    /// The ticks in which a CPU was busy for at least `BURST_PERCENT`.
+    pub bursts: usize,
+
+    /// The ticks taken more than an interval late.
+    pub stalls: usize,
+
+    /// The latest a tick was taken, in milliseconds.
+    pub late_max_ms: f64,
+}
+
+impl BurstSummary {
+    /// Summarizes frames.
+    ///
+    /// # Arguments
+    ///
+    /// * `frames` - The frames of the window.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<BurstSummary>` - The summary, or `None` if the frames hold no tick.
+    pub fn from_frames(frames: &[&BurstFrame]) -> Option<BurstSummary> {
+        let ticks: usize = frames.iter().map(|frame| frame.ticks()).sum();
+        if ticks == 0 {
+            return None;
+        }
+        let values = |field: fn(&BurstFrame) -> &Vec<f64>| {
+            frames
+                .iter()
+                .flat_map(move |frame| field(frame).iter().copied())
+        };
+        let mean = |field| values(field).sum::<f64>() / ticks as f64;
+        let max = |field| values(field).fold(f64::NEG_INFINITY, f64::max);
+        Some(BurstSummary {
+            interval_ms: frames
+                .iter()
+                .map(|frame| frame.interval_ms)
+                .fold(0.0, f64::max),
+            ticks,
+            busy_mean_percent: mean(|frame| &frame.busy_percent),
+            busy_max_percent: max(|frame| &frame.busy_percent),
+            peak_core_min_percent: values(|frame| &frame.peak_core_percent)
+                .fold(f64::INFINITY, f64::min),
+            peak_core_mean_percent: mean(|frame| &frame.peak_core_percent),
+            peak_core_max_percent: max(|frame| &frame.peak_core_percent),
+            bursts: frames.iter().map(|frame| frame.bursts()).sum(),
+            stalls: frames.iter().map(|frame| frame.stalls()).sum(),
+            late_max_ms: max(|frame| &frame.late_ms),
+        })
+    }
+}
+
+/// Downsamples frames to a summary per window.
+///
+/// # Arguments
+///
+/// * `frames` - The frames with the time each was taken at, oldest first.
+/// * `window` - The time each summary covers, e.g. `BURST_SUMMARY_WINDOW`.
+///
+/// # Returns
+///
+/// * `Vec<(DateTime<Utc>, BurstSummary)>` - A summary per window holding frames, with
+///   the time the window starts at, oldest first.
+pub fn downsample_frames(
+    frames: &[(DateTime<Utc>, BurstFrame)],
+    window: Duration,
+) -> Vec<(DateTime<Utc>, BurstSummary)> {
+    let Ok(window) = chrono::Duration::from_std(window) else {
+        return Vec::new();
+    };
+    let mut windows: Vec<(DateTime<Utc>, Vec<&BurstFrame>)> = Vec::new();
+    for (taken, frame) in frames {
+        let start = taken.duration_trunc(window).unwrap_or(*taken);
+        match windows.last_mut() {
+            Some((last, frames)) if *last == start => frames.push(frame),
+            _ => windows.push((start, vec![frame])),
+        }
+    }
+    windows
+        .into_iter()
+        .filter_map(|(start, frames)| {
+            BurstSummary::from_frames(&frames).map(|summary| (start, summary))
+        })
+        .collect()
+}
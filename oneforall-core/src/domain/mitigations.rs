@@ -0,0 +1,169 @@
+//! Mitigations Domain Entity
+//!
+//! This module provides the CPU vulnerability mitigations the kernel applies,
+//! such as those for Spectre, Meltdown and Retbleed, and the cost they add on
+//! the machine they run on. Most mitigations work on the boundary between user
+//! space and the kernel or between processes, so their cost shows up in system
+//! calls and context switches rather than in compute-bound benchmarks.
+
+use crate::domain::cpu_features::{Vulnerability, VulnerabilityState};
+use crate::domain::statistics::Measurement;
+
+/// The kernel command-line parameters that turn mitigations on or off.
+const MITIGATION_PARAMETERS: [&str; 19] = [
+    "mitigations",
+    "nopti",
+    "pti",
+    "nospectre_v1",
+    "nospectre_v2",
+    "spectre_v2",
+    "spectre_v2_user",
+    "spectre_bhi",
+    "spec_store_bypass_disable",
+    "nospec_store_bypass_disable",
+    "retbleed",
+    "spec_rstack_overflow",
+    "mds",
+    "tsx_async_abort",
+    "mmio_stale_data",
+    "l1tf",
+    "srbds",
+    "gather_data_sampling",
+    "nosmt",
+];
+
+/// Lists the parameters of a kernel command line that change mitigations.
+///
+/// # Arguments
+///
+/// * `cmdline` - The kernel command line, as read from `/proc/cmdline`.
+///
+/// # Returns
+///
+/// * `Vec<String>` - The parameters, e.g. "mitigations=off", in command-line order.
+pub fn mitigation_parameters(cmdline: &str) -> Vec<String> {
+    cmdline
+        .split_whitespace()
+        .filter(|parameter| {
+            let name = parameter.split('=').next().unwrap_or_default();
+            MITIGATION_PARAMETERS.contains(&name)
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// The cost of entering the kernel and switching processes, measured on this machine.
+#[derive(Debug, Clone)]
+pub struct MitigationOverhead {
+    /// The time of a `clock_gettime` served by the vDSO, without entering the kernel, in nanoseconds.
+    pub vdso_call_ns: f64,
+
+    /// The time of the cheapest system call, `getppid`, in nanoseconds.
+    pub syscall_ns: f64,
+
+    /// The time of a context switch between two processes on the same CPU, in nanoseconds.
+    pub context_switch_ns: f64,
+}
+
+impl MitigationOverhead {
+    /// Returns the cost of entering and leaving the kernel, in nanoseconds.
+    ///
+    /// This is where page table isolation, return stack stuffing and buffer
+    /// clearing add their cost, so it is the number to compare between boots
+    /// with and without `mitigations=off`.
+    pub fn kernel_entry_ns(&self) -> f64 {
+        (self.syscall_ns - self.vdso_call_ns).max(0.0)
+    }
+}
+
+/// The mitigations of a machine and, if measured, their cost.
+#[derive(Debug, Clone)]
+pub struct MitigationReport {
+    /// Every vulnerability the kernel reports on.
+    pub vulnerabilities: Vec<Vulnerability>,
+
+    /// The kernel parameters that change mitigations.
+    pub kernel_parameters: Vec<String>,
+
+    /// The measured cost, if the micro-benchmarks were run.
+    pub overhead: Option<MitigationOverhead>,
+}
+
+/// Lists the measured costs, for statistics over repeated runs.
+pub fn mitigation_measurements(overhead: &MitigationOverhead) -> Vec<Measurement> {
+    vec![
+        Measurement::new("vDSO call", "ns", overhead.vdso_call_ns),
+        Measurement::new("system call", "ns", overhead.syscall_ns),
+        Measurement::new("kernel entry", "ns", overhead.kernel_entry_ns()),
+        Measurement::new("context switch", "ns", overhead.context_switch_ns),
+    ]
+}
+
+/// Renders a mitigation report.
+///
+/// # Arguments
+///
+/// * `report` - The mitigations and their measured cost.
+///
+/// # Returns
+///
+/// * `String` - The vulnerabilities the CPU is affected by with their
+///   mitigation, the kernel parameters, and the measured cost if any.
+pub fn format_mitigation_report(report: &MitigationReport) -> String {
+    let mut output = String::new();
+
+    if report.vulnerabilities.is_empty() {
+        output.push_str("The kernel reports no CPU vulnerabilities on this machine.\n");
+    } else {
+        output.push_str("Vulnerability               State         Mitigation\n");
+        let mut not_affected = 0;
+        for vulnerability in &report.vulnerabilities {
+            let state = match vulnerability.state() {
+                VulnerabilityState::NotAffected => {
+                    not_affected += 1;
+                    continue;
+                }
+                VulnerabilityState::Mitigated => "mitigated",
+                VulnerabilityState::Vulnerable => "VULNERABLE",
+                VulnerabilityState::Unknown => "unknown",
+            };
+            let status = vulnerability
+                .status
+                .strip_prefix("Mitigation: ")
+                .unwrap_or(&vulnerability.status);
+            output.push_str(&format!(
+                "{:<26}  {:<12}  {}\n",
+                vulnerability.name, state, status
+            ));
+        }
+        output.push_str(&format!("Not affected by {} more\n", not_affected));
+    }
+
+    output.push_str(&format!(
+        "\nKernel parameters: {}\n",
+        if report.kernel_parameters.is_empty() {
+            "none, the kernel defaults apply".to_string()
+        } else {
+            report.kernel_parameters.join(" ")
+        }
+    ));
+
+    if let Some(overhead) = &report.overhead {
+        output.push_str(&format!(
+            "\nvDSO call (clock_gettime)     {:>9.1} ns\n\
+             System call (getppid)         {:>9.1} ns\n\
+             Kernel entry and exit         {:>9.1} ns\n\
+             Context switch                {:>9.1} ns\n",
+            overhead.vdso_call_ns,
+            overhead.syscall_ns,
+            overhead.kernel_entry_ns(),
+            overhead.context_switch_ns
+        ));
+        output.push_str(
+            "Boot with and without mitigations=off and compare these numbers to \
+             quantify what the mitigations cost on this machine.\n",
+        );
+    }
+
+    output
+}
@@ -0,0 +1,76 @@
+pub mod alert;
+pub mod anomaly;
+pub mod archive;
+pub mod battery;
+pub mod block_device;
+pub mod boot;
+pub mod burn_in;
+pub mod cache;
+pub mod chaos;
+pub mod checkpoint;
+pub mod clock;
+pub mod cloud;
+pub mod codec;
+pub mod container;
+pub mod cpu_architecture;
+pub mod cpu_features;
+pub mod cpu_frequency;
+pub mod cpu_topology;
+pub mod database;
+pub mod dimm;
+pub mod discovery;
+pub mod endurance;
+pub mod entropy;
+pub mod export;
+pub mod filesystem;
+pub mod firmware;
+pub mod flamegraph;
+pub mod fleet;
+pub mod gpu;
+pub mod gpu_interconnect;
+pub mod grpc;
+pub mod hardware_changes;
+pub mod hardware_errors;
+pub mod hardware_manifest;
+pub mod hook;
+pub mod integrity;
+pub mod interrupts;
+pub mod kernel_latency;
+pub mod kernel_log;
+pub mod kubernetes;
+pub mod logging;
+pub mod memtest;
+pub mod microburst;
+pub mod mitigations;
+pub mod network_matrix;
+pub mod numa;
+pub mod nvme;
+pub mod pcie;
+pub mod perf_counters;
+pub mod plan;
+pub mod platform;
+pub mod plugin;
+pub mod power;
+pub mod power_cycle;
+pub mod powermetrics;
+pub mod profile;
+pub mod ps_command;
+pub mod rdma;
+pub mod rollup;
+pub mod scaling;
+pub mod scheduler_latency;
+pub mod schema;
+pub mod score;
+pub mod sel;
+pub mod simd;
+pub mod soak;
+pub mod statistics;
+pub mod stress_ng;
+pub mod system_stats;
+pub mod tags;
+pub mod thermal;
+pub mod time_sync;
+pub mod trend;
+pub mod virtualization;
+pub mod vram;
+pub mod workload;
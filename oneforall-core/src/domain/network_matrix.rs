@@ -0,0 +1,292 @@
+//! Network Matrix Domain Entity
+//!
+//! This module provides the all-to-all network matrix of a fleet: every agent
+//! measures the latency and bandwidth of the path to every other agent, one
+//! pair at a time so the measurements do not compete. A path much slower than
+//! the rest of the fleet points at a bad cable or optic, an oversubscribed
+//! switch uplink or a NIC that negotiated a lower speed; a path that is only
+//! slow in one direction points at the sending or receiving side.
+
+use common::domain::network_probe::LinkMeasurement;
+use serde::Serialize;
+
+use crate::domain::statistics::median;
+
+/// A path is flagged when its latency exceeds the fleet median by this factor.
+pub const LATENCY_OUTLIER_FACTOR: f64 = 2.0;
+
+/// A path is flagged when its bandwidth falls below the fleet median by this factor.
+pub const BANDWIDTH_OUTLIER_FACTOR: f64 = 0.5;
+
+/// The measurement of the path from one agent to another.
+#[derive(Debug, Clone, Serialize)]
+pub struct MatrixCell {
+    /// The agent that measured.
+    pub from: String,
+
+    /// The agent measured to.
+    pub to: String,
+
+    /// The latency and bandwidth, or why the path could not be measured.
+    pub measurement: Result<LinkMeasurement, String>,
+}
+
+/// Escapes text for an HTML document.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The latency and bandwidth of every path between the agents of a fleet.
+#[derive(Debug, Clone, Serialize)]
+pub struct NetworkMatrix {
+    /// The agents, in the order of the rows and columns.
+    pub agents: Vec<String>,
+
+    /// One cell per ordered pair of distinct agents.
+    pub cells: Vec<MatrixCell>,
+}
+
+impl NetworkMatrix {
+    /// Returns the measurement of the path from one agent to another, if it succeeded.
+    fn measurement(&self, from: &str, to: &str) -> Option<&LinkMeasurement> {
+        self.cells
+            .iter()
+            .find(|cell| cell.from == from && cell.to == to)
+            .and_then(|cell| cell.measurement.as_ref().ok())
+    }
+
+    /// Returns the median latency of every measured path, in microseconds.
+    pub fn median_latency_us(&self) -> Option<f64> {
+        median(
+            &self
+                .cells
+                .iter()
+                .filter_map(|cell| cell.measurement.as_ref().ok())
+                .map(|m| m.latency_us)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Returns the median bandwidth of every measured path, in megabits per second.
+    pub fn median_bandwidth_mbit_s(&self) -> Option<f64> {
+        median(
+            &self
+                .cells
+                .iter()
+                .filter_map(|cell| cell.measurement.as_ref().ok())
+                .map(|m| m.bandwidth_mbit_s)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Returns `true` if a latency is an outlier against the fleet median.
+    fn slow_latency(&self, latency_us: f64) -> bool {
+        self.median_latency_us()
+            .is_some_and(|median| latency_us > median * LATENCY_OUTLIER_FACTOR)
+    }
+
+    /// Returns `true` if a bandwidth is an outlier against the fleet median.
+    fn low_bandwidth(&self, bandwidth_mbit_s: f64) -> bool {
+        self.median_bandwidth_mbit_s()
+            .is_some_and(|median| bandwidth_mbit_s < median * BANDWIDTH_OUTLIER_FACTOR)
+    }
+
+    /// Lists the paths that failed or stand out from the rest of the fleet.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<String>` - One description per path; empty if every path looks alike.
+    pub fn anomalies(&self) -> Vec<String> {
+        let mut anomalies = Vec::new();
+        for cell in &self.cells {
+            let path = format!("{} -> {}", cell.from, cell.to);
+            match &cell.measurement {
+                Err(e) => anomalies.push(format!("{}: not measured ({})", path, e)),
+                Ok(measurement) => {
+                    if self.slow_latency(measurement.latency_us) {
+                        anomalies.push(format!(
+                            "{}: latency {:.0} us, over {:.0}x the fleet median of {:.0} us",
+                            path,
+                            measurement.latency_us,
+                            LATENCY_OUTLIER_FACTOR,
+                            self.median_latency_us().unwrap_or_default()
+                        ));
+                    }
+                    if self.low_bandwidth(measurement.bandwidth_mbit_s) {
+                        anomalies.push(format!(
+                            "{}: bandwidth {:.0} Mbit/s, under {:.0}% of the fleet median of {:.0} Mbit/s",
+                            path,
+                            measurement.bandwidth_mbit_s,
+                            BANDWIDTH_OUTLIER_FACTOR * 100.0,
+                            self.median_bandwidth_mbit_s().unwrap_or_default()
+                        ));
+                    }
+                }
+            }
+        }
+        anomalies
+    }
+
+    /// Renders one table of the matrix for the terminal, marking outliers with "!".
+    fn render_table(
+        &self,
+        title: &str,
+        value: impl Fn(&LinkMeasurement) -> f64,
+        outlier: impl Fn(f64) -> bool,
+    ) -> String {
+        let width = self.agents.iter().map(String::len).max().unwrap_or(0);
+        let mut output = format!("{}, from row to column:\n", title);
+        output.push_str(&format!("{:>4} {:<width$}", "", "", width = width));
+        for column in 1..=self.agents.len() {
+            output.push_str(&format!(" {:>9}", column));
+        }
+        output.push('\n');
+        for (row, from) in self.agents.iter().enumerate() {
+            output.push_str(&format!("{:>4} {:<width$}", row + 1, from, width = width));
+            for to in &self.agents {
+                let cell = if from == to {
+                    "-".to_string()
+                } else {
+                    match self.measurement(from, to) {
+                        Some(measurement) => {
+                            let value = value(measurement);
+                            format!("{:.0}{}", value, if outlier(value) { "!" } else { "" })
+                        }
+                        None => "failed".to_string(),
+                    }
+                };
+                output.push_str(&format!(" {:>9}", cell));
+            }
+            output.push('\n');
+        }
+        output
+    }
+
+    /// Renders the matrix as plain text suitable for the terminal.
+    pub fn render(&self) -> String {
+        let mut output = String::from("== Fleet network matrix ==\n");
+        output.push_str(&self.render_table(
+            "Latency (us)",
+            |m| m.latency_us,
+            |v| self.slow_latency(v),
+        ));
+        output.push('\n');
+        output.push_str(&self.render_table(
+            "Bandwidth (Mbit/s)",
+            |m| m.bandwidth_mbit_s,
+            |v| self.low_bandwidth(v),
+        ));
+
+        let anomalies = self.anomalies();
+        if anomalies.is_empty() {
+            output.push_str("\nNo outlying paths.\n");
+        } else {
+            output.push_str("\nOutlying paths:\n");
+            for anomaly in anomalies {
+                output.push_str(&format!("  {}\n", anomaly));
+            }
+        }
+        output
+    }
+
+    /// Renders one heatmap of the matrix as an HTML table.
+    ///
+    /// Each cell is colored from green, at or better than the fleet median, to
+    /// red, at the outlier threshold or worse.
+    fn render_heatmap(
+        &self,
+        title: &str,
+        unit: &str,
+        value: impl Fn(&LinkMeasurement) -> f64,
+        badness: impl Fn(f64) -> f64,
+    ) -> String {
+        let mut html = format!(
+            "<h2>{}</h2>\n<table>\n<tr><th>from \\ to</th>",
+            escape_html(title)
+        );
+        for to in &self.agents {
+            html.push_str(&format!("<th>{}</th>", escape_html(to)));
+        }
+        html.push_str("</tr>\n");
+        for from in &self.agents {
+            html.push_str(&format!("<tr><th>{}</th>", escape_html(from)));
+            for to in &self.agents {
+                if from == to {
+                    html.push_str("<td class=\"self\"></td>");
+                    continue;
+                }
+                let cell = self
+                    .cells
+                    .iter()
+                    .find(|cell| cell.from == *from && cell.to == *to);
+                match cell.map(|cell| &cell.measurement) {
+                    Some(Ok(measurement)) => {
+                        let value = value(measurement);
+                        let hue = 120.0 * (1.0 - badness(value).clamp(0.0, 1.0));
+                        html.push_str(&format!(
+                            "<td style=\"background: hsl({:.0}, 70%, 75%)\">{:.0} {}</td>",
+                            hue,
+                            value,
+                            escape_html(unit)
+                        ));
+                    }
+                    Some(Err(e)) => html.push_str(&format!(
+                        "<td class=\"failed\" title=\"{}\">failed</td>",
+                        escape_html(e)
+                    )),
+                    None => html.push_str("<td class=\"failed\">-</td>"),
+                }
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n");
+        html
+    }
+
+    /// Renders the matrix as a standalone HTML report with a latency and a bandwidth heatmap.
+    pub fn render_html(&self) -> String {
+        let median_latency = self.median_latency_us().unwrap_or_default();
+        let median_bandwidth = self.median_bandwidth_mbit_s().unwrap_or_default();
+        let mut html = String::from(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>OneForAll fleet network matrix</title>\n<style>\n\
+             body { font-family: sans-serif; margin: 2em; }\n\
+             table { border-collapse: collapse; margin-bottom: 2em; }\n\
+             th, td { border: 1px solid #ccc; padding: 0.4em 0.8em; text-align: right; }\n\
+             td.self { background: #eee; }\n\
+             td.failed { background: #555; color: #fff; }\n\
+             </style>\n</head>\n<body>\n<h1>Fleet network matrix</h1>\n",
+        );
+        html.push_str(&format!(
+            "<p>Each cell is measured from the agent of its row to the agent of its column. \
+             Fleet median: {:.0} us latency, {:.0} Mbit/s bandwidth.</p>\n",
+            median_latency, median_bandwidth
+        ));
+        html.push_str(&self.render_heatmap(
+            "Latency",
+            "us",
+            |m| m.latency_us,
+            |v| (v / median_latency - 1.0) / (LATENCY_OUTLIER_FACTOR - 1.0),
+        ));
+        html.push_str(&self.render_heatmap(
+            "Bandwidth",
+            "Mbit/s",
+            |m| m.bandwidth_mbit_s,
+            |v| (1.0 - v / median_bandwidth) / (1.0 - BANDWIDTH_OUTLIER_FACTOR),
+        ));
+
+        let anomalies = self.anomalies();
+        if !anomalies.is_empty() {
+            html.push_str("<h2>Outlying paths</h2>\n<ul>\n");
+            for anomaly in anomalies {
+                html.push_str(&format!("<li>{}</li>\n", escape_html(&anomaly)));
+            }
+            html.push_str("</ul>\n");
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
@@ -0,0 +1,174 @@
+//! NUMA Domain Entity
+//!
+//! This module provides domain entities describing NUMA nodes and the results
+//! of the cross-node memory benchmark, which compares local and remote memory
+//! bandwidth and latency to catch degraded or misconfigured interconnects.
+
+use std::collections::BTreeSet;
+
+use crate::domain::cpu_topology::format_cpu_list;
+use crate::domain::discovery::DiscoverySection;
+use crate::domain::statistics::Measurement;
+
+/// Represents a single NUMA node.
+#[derive(Debug, Clone)]
+pub struct NumaNode {
+    /// The node number, e.g. `1` for `/sys/devices/system/node/node1`.
+    pub id: u32,
+
+    /// The logical CPUs attached to this node.
+    pub cpus: BTreeSet<u32>,
+
+    /// Total memory attached to this node in kilobytes, if known.
+    pub memory_total_kb: Option<u64>,
+
+    /// Free memory on this node in kilobytes, if known.
+    pub memory_free_kb: Option<u64>,
+
+    /// The SLIT distances from this node to every node, indexed by node id.
+    pub distances: Vec<u32>,
+}
+
+/// The result of measuring memory placed on one node from CPUs on another.
+#[derive(Debug, Clone)]
+pub struct NumaMemoryResult {
+    /// The node whose CPU performed the accesses.
+    pub cpu_node: u32,
+
+    /// The node the memory was allocated on.
+    pub memory_node: u32,
+
+    /// Sequential read bandwidth in megabytes per second.
+    pub bandwidth_mb_s: f64,
+
+    /// Average dependent-load latency in nanoseconds.
+    pub latency_ns: f64,
+}
+
+impl NumaMemoryResult {
+    /// Returns `true` if the CPU and memory are on the same node.
+    pub fn is_local(&self) -> bool {
+        self.cpu_node == self.memory_node
+    }
+}
+
+/// Builds the discovery section describing the NUMA layout of the machine.
+///
+/// # Arguments
+///
+/// * `nodes` - The NUMA nodes discovered on the machine.
+///
+/// # Returns
+///
+/// * `DiscoverySection` - One entry per node with its CPUs, memory, and distances.
+pub fn numa_section(nodes: &[NumaNode]) -> DiscoverySection {
+    let mut section = DiscoverySection::new("NUMA Topology");
+    section.push("Nodes", nodes.len());
+
+    for node in nodes {
+        let memory = match (node.memory_total_kb, node.memory_free_kb) {
+            (Some(total), Some(free)) => {
+                format!("{} MiB total, {} MiB free", total / 1024, free / 1024)
+            }
+            (Some(total), None) => format!("{} MiB total", total / 1024),
+            _ => "unknown".to_string(),
+        };
+        let distances = node
+            .distances
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        section.push(
+            &format!("Node {}", node.id),
+            format!(
+                "CPUs {} | memory {} | distances [{}]",
+                format_cpu_list(&node.cpus),
+                memory,
+                distances
+            ),
+        );
+    }
+
+    section
+}
+
+/// Lists the metrics of a NUMA benchmark run, for statistics over repeated runs.
+pub fn numa_measurements(results: &[NumaMemoryResult]) -> Vec<Measurement> {
+    results
+        .iter()
+        .flat_map(|r| {
+            let pair = format!("CPU node {} -> memory node {}", r.cpu_node, r.memory_node);
+            [
+                Measurement::new(format!("{} bandwidth", pair), "MB/s", r.bandwidth_mb_s),
+                Measurement::new(format!("{} latency", pair), "ns", r.latency_ns),
+            ]
+        })
+        .collect()
+}
+
+/// Renders the cross-node results as a table, flagging remote accesses that are
+/// disproportionately slow compared to local accesses on the same memory node.
+///
+/// # Arguments
+///
+/// * `results` - The measured CPU-node/memory-node combinations.
+/// * `remote_penalty_limit` - The maximum acceptable ratio of remote to local
+///   latency before a combination is flagged as degraded.
+///
+/// # Returns
+///
+/// * `String` - The formatted table.
+pub fn format_numa_results(results: &[NumaMemoryResult], remote_penalty_limit: f64) -> String {
+    let mut output = String::from("CPU node  Mem node  Bandwidth (MB/s)  Latency (ns)  Status\n");
+
+    for result in results {
+        let status = match remote_penalty(results, result) {
+            _ if result.is_local() => "local".to_string(),
+            Some(penalty) if penalty > remote_penalty_limit => {
+                format!("DEGRADED ({:.1}x local)", penalty)
+            }
+            Some(penalty) => format!("remote ({:.1}x local)", penalty),
+            None => "remote".to_string(),
+        };
+
+        output.push_str(&format!(
+            "{:>8}  {:>8}  {:>16.0}  {:>12.1}  {}\n",
+            result.cpu_node, result.memory_node, result.bandwidth_mb_s, result.latency_ns, status
+        ));
+    }
+
+    output
+}
+
+/// Returns the ratio of a remote latency to the local latency of the same memory node.
+///
+/// # Arguments
+///
+/// * `results` - Every measured CPU-node/memory-node combination.
+/// * `result` - The combination to compare against the local measurement.
+///
+/// # Returns
+///
+/// * `Option<f64>` - The ratio, or `None` for local combinations and when no
+///   usable local measurement exists.
+pub fn remote_penalty(results: &[NumaMemoryResult], result: &NumaMemoryResult) -> Option<f64> {
+    if result.is_local() {
+        return None;
+    }
+    results
+        .iter()
+        .find(|r| r.is_local() && r.memory_node == result.memory_node)
+        .map(|r| r.latency_ns)
+        .filter(|local| *local > 0.0)
+        .map(|local| result.latency_ns / local)
+}
+
+/// Returns the largest remote/local latency ratio across all combinations, if any.
+pub fn worst_remote_penalty(results: &[NumaMemoryResult]) -> Option<f64> {
+    results
+        .iter()
+        .filter_map(|result| remote_penalty(results, result))
+        .reduce(f64::max)
+}
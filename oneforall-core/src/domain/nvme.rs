@@ -0,0 +1,388 @@
+//! NVMe Domain Entity
+//!
+//! This module provides the NVMe-specific view of the drives of a machine: the
+//! controllers and the layout of their namespaces, and their health as read
+//! from the SMART / Health Information and Error Information log pages of the
+//! admin command set. The health is read before and after storage stress, and
+//! growth of the error log, new media errors or a new critical warning is a
+//! fault, even when the generic SMART checks see nothing wrong.
+
+use std::cmp::Reverse;
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::discovery::DiscoverySection;
+use crate::domain::endurance::format_volume;
+
+/// The number of new error log entries described in a fault; the rest are only counted.
+const MAX_REPORTED_ERRORS: usize = 3;
+
+/// The critical warning bits of the SMART / Health Information log page.
+const CRITICAL_WARNINGS: [&str; 6] = [
+    "available spare below threshold",
+    "temperature out of range",
+    "reliability degraded by media or internal errors",
+    "media placed in read-only mode",
+    "volatile memory backup failed",
+    "persistent memory region read-only",
+];
+
+/// A namespace of an NVMe controller.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NvmeNamespace {
+    /// The block device of the namespace, e.g. "nvme0n1".
+    pub name: String,
+
+    /// The capacity of the namespace in bytes.
+    pub size_bytes: u64,
+
+    /// The logical block size in bytes, e.g. 512 or 4096.
+    pub block_size: u64,
+}
+
+/// An NVMe controller and its namespaces.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NvmeController {
+    /// The character device of the controller, e.g. "nvme0".
+    pub name: String,
+
+    /// The model number reported by the controller.
+    pub model: String,
+
+    /// The serial number reported by the controller.
+    pub serial: String,
+
+    /// The firmware revision running on the controller.
+    pub firmware: String,
+
+    /// How the controller is attached, e.g. "pcie", "tcp" or "rdma".
+    pub transport: String,
+
+    /// The namespaces attached to the controller.
+    pub namespaces: Vec<NvmeNamespace>,
+}
+
+/// An entry of the Error Information log page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NvmeErrorEntry {
+    /// The error count of the entry; it increases with every error the controller logs.
+    pub error_count: u64,
+
+    /// The submission queue of the failed command, 0 for the admin queue.
+    pub queue: u64,
+
+    /// The identifier of the failed command.
+    pub command: u64,
+
+    /// The status code type and status code the command completed with.
+    pub status: u64,
+
+    /// The first LBA the error affected.
+    pub lba: u64,
+
+    /// The namespace the error affected.
+    pub namespace: u64,
+}
+
+impl NvmeErrorEntry {
+    /// Returns `true` for errors caused by the host rather than the drive: a
+    /// command with an invalid field, as sent by tools probing for optional
+    /// log pages and features the drive does not support.
+    pub fn is_host_probe(&self) -> bool {
+        self.status == 0x002
+    }
+
+    /// Describes the entry, e.g. "error 12: status 0x281 on I/O queue 3, namespace 1 LBA 4096".
+    pub fn describe(&self) -> String {
+        let queue = if self.queue == 0 {
+            "the admin queue".to_string()
+        } else {
+            format!("I/O queue {}", self.queue)
+        };
+        format!(
+            "error {}: status {:#05x} on {}, namespace {} LBA {}",
+            self.error_count, self.status, queue, self.namespace, self.lba
+        )
+    }
+}
+
+/// The health of an NVMe controller at one point in time.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NvmeHealth {
+    /// The character device of the controller, e.g. "nvme0".
+    pub controller: String,
+
+    /// The composite temperature in degrees Celsius.
+    pub temperature_celsius: Option<f64>,
+
+    /// The critical warning bits, 0 when the controller reports none.
+    pub critical_warning: u64,
+
+    /// The unrecovered data integrity errors over the life of the controller.
+    pub media_errors: u64,
+
+    /// The error log entries over the life of the controller.
+    pub error_log_entries: u64,
+
+    /// The minutes spent above the warning composite temperature.
+    pub warning_temp_minutes: u64,
+
+    /// The minutes spent above the critical composite temperature.
+    pub critical_temp_minutes: u64,
+
+    /// The times the controller throttled itself for temperature, light and heavy throttling together.
+    pub throttle_transitions: u64,
+
+    /// The seconds spent throttled for temperature, light and heavy throttling together.
+    pub throttle_secs: u64,
+
+    /// The most recent entries of the error log, newest first.
+    pub errors: Vec<NvmeErrorEntry>,
+}
+
+/// Reads an unsigned counter of an nvme-cli JSON object, written as a number or a string.
+fn json_u64(object: &serde_json::Value, key: &str) -> Option<u64> {
+    let value = object.get(key)?;
+    value
+        .as_u64()
+        .or_else(|| value.as_f64().map(|v| v as u64))
+        .or_else(|| value.as_str()?.replace(',', "").trim().parse().ok())
+}
+
+/// Returns the controller of an NVMe block device, e.g. "nvme0" for "/dev/nvme0n1".
+///
+/// # Returns
+///
+/// * `Option<String>` - The controller, or `None` if the device is not an NVMe namespace.
+pub fn nvme_controller(device: &str) -> Option<String> {
+    let name = device.strip_prefix("/dev/").unwrap_or(device);
+    let instance = name.strip_prefix("nvme")?;
+    let digits = instance
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(instance.len());
+    (digits > 0 && instance[digits..].starts_with('n'))
+        .then(|| format!("nvme{}", &instance[..digits]))
+}
+
+/// Describes the critical warning bits that are set, e.g. "temperature out of range".
+pub fn describe_critical_warning(bits: u64) -> String {
+    let warnings: Vec<&str> = CRITICAL_WARNINGS
+        .iter()
+        .enumerate()
+        .filter(|(bit, _)| bits & (1 << bit) != 0)
+        .map(|(_, warning)| *warning)
+        .collect();
+    if warnings.is_empty() {
+        "none".to_string()
+    } else {
+        warnings.join(", ")
+    }
+}
+
+/// Parses the output of `nvme smart-log <device> -o json`.
+///
+/// # Arguments
+///
+/// * `controller` - The character device of the controller, e.g. "nvme0".
+/// * `smart_log` - The SMART / Health Information log page as JSON.
+///
+/// # Returns
+///
+/// * `NvmeHealth` - The health of the controller, without error log entries.
+pub fn parse_smart_log(controller: &str, smart_log: &serde_json::Value) -> NvmeHealth {
+    let counter = |key: &str| json_u64(smart_log, key).unwrap_or(0);
+    NvmeHealth {
+        controller: controller.to_string(),
+        // nvme-cli reports the composite temperature in kelvins.
+        temperature_celsius: json_u64(smart_log, "temperature")
+            .filter(|kelvins| *kelvins > 0)
+            .map(|kelvins| kelvins as f64 - 273.15),
+        critical_warning: counter("critical_warning"),
+        media_errors: counter("media_errors"),
+        error_log_entries: counter("num_err_log_entries"),
+        warning_temp_minutes: counter("warning_temp_time"),
+        critical_temp_minutes: counter("critical_comp_time"),
+        throttle_transitions: counter("thm_temp1_trans_count") + counter("thm_temp2_trans_count"),
+        throttle_secs: counter("thm_temp1_total_time") + counter("thm_temp2_total_time"),
+        errors: Vec::new(),
+    }
+}
+
+/// Parses the output of `nvme error-log <device> -o json`.
+///
+/// Older nvme-cli releases report the raw status field, phase tag included,
+/// while newer ones report the phase tag apart; both are read as the status
+/// code type and status code alone.
+///
+/// # Returns
+///
+/// * `Vec<NvmeErrorEntry>` - The logged errors, newest first; unused entries are skipped.
+pub fn parse_error_log(error_log: &serde_json::Value) -> Vec<NvmeErrorEntry> {
+    let entries = match error_log.get("errors").and_then(|e| e.as_array()) {
+        Some(entries) => entries,
+        None => return Vec::new(),
+    };
+    let mut errors: Vec<NvmeErrorEntry> = entries
+        .iter()
+        .filter_map(|entry| {
+            let error_count = json_u64(entry, "error_count").filter(|count| *count > 0)?;
+            let status_field = json_u64(entry, "status_field").unwrap_or(0);
+            let status = if entry.get("phase_tag").is_some() {
+                status_field
+            } else {
+                status_field >> 1
+            };
+            Some(NvmeErrorEntry {
+                error_count,
+                queue: json_u64(entry, "sqid").unwrap_or(0),
+                command: json_u64(entry, "cmdid").unwrap_or(0),
+                status: status & 0x7ff,
+                lba: json_u64(entry, "lba").unwrap_or(0),
+                namespace: json_u64(entry, "nsid").unwrap_or(0),
+            })
+        })
+        .collect();
+    errors.sort_by_key(|e| Reverse(e.error_count));
+    errors
+}
+
+/// Lists the NVMe faults that appeared between two readings of every controller.
+///
+/// # Arguments
+///
+/// * `before` - The health read when the run started.
+/// * `after` - The health read when the run finished.
+///
+/// # Returns
+///
+/// * `Vec<String>` - One description per fault; empty if there were none. Error
+///   log entries caused by tools probing the drive are not faults.
+pub fn nvme_faults(before: &[NvmeHealth], after: &[NvmeHealth]) -> Vec<String> {
+    let mut faults = Vec::new();
+    for after in after {
+        let Some(before) = before.iter().find(|b| b.controller == after.controller) else {
+            continue;
+        };
+        let new_warnings = after.critical_warning & !before.critical_warning;
+        if new_warnings != 0 {
+            faults.push(format!(
+                "{}: new critical warning: {}",
+                after.controller,
+                describe_critical_warning(new_warnings)
+            ));
+        }
+        let media_errors = after.media_errors.saturating_sub(before.media_errors);
+        if media_errors > 0 {
+            faults.push(format!(
+                "{}: {} new media error(s)",
+                after.controller, media_errors
+            ));
+        }
+        let new_errors: Vec<&NvmeErrorEntry> = after
+            .errors
+            .iter()
+            .filter(|e| e.error_count > before.error_log_entries && !e.is_host_probe())
+            .collect();
+        if !new_errors.is_empty() {
+            faults.push(format!(
+                "{}: {} new error log entr{}: {}",
+                after.controller,
+                new_errors.len(),
+                if new_errors.len() == 1 { "y" } else { "ies" },
+                new_errors
+                    .iter()
+                    .take(MAX_REPORTED_ERRORS)
+                    .map(|e| e.describe())
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ));
+        }
+    }
+    faults
+}
+
+/// Builds the discovery section describing the NVMe controllers of the machine.
+///
+/// # Arguments
+///
+/// * `controllers` - The controllers discovered on the machine.
+/// * `health` - The health of each controller, or why it could not be read.
+///
+/// # Returns
+///
+/// * `DiscoverySection` - The identity, namespaces and health of each controller.
+pub fn nvme_section(
+    controllers: &[NvmeController],
+    health: &[Result<NvmeHealth, String>],
+) -> DiscoverySection {
+    let mut section = DiscoverySection::new("NVMe Drives");
+    section.push("Controllers", controllers.len());
+
+    for (controller, health) in controllers.iter().zip(health) {
+        section.push(
+            &controller.name,
+            format!(
+                "{} | S/N {} | firmware {} | {}",
+                controller.model, controller.serial, controller.firmware, controller.transport
+            ),
+        );
+        let namespaces = controller
+            .namespaces
+            .iter()
+            .map(|ns| {
+                format!(
+                    "{} {} ({} B blocks)",
+                    ns.name,
+                    format_volume(ns.size_bytes),
+                    ns.block_size
+                )
+            })
+            .collect::<Vec<_>>();
+        section.push(
+            &format!("{} namespaces", controller.name),
+            if namespaces.is_empty() {
+                "none".to_string()
+            } else {
+                namespaces.join(", ")
+            },
+        );
+
+        let key = format!("{} health", controller.name);
+        match health {
+            Ok(health) => {
+                section.push(
+                    &key,
+                    format!(
+                        "{} | critical warning {} | {} media error(s) | {} error log entries",
+                        health.temperature_celsius.map_or(
+                            "temperature unknown".to_string(),
+                            |t| format!("{:.0} °C", t)
+                        ),
+                        describe_critical_warning(health.critical_warning),
+                        health.media_errors,
+                        health.error_log_entries
+                    ),
+                );
+                section.push(
+                    &format!("{} thermal", controller.name),
+                    format!(
+                        "throttled {} time(s), {} s in total | {} min above warning, {} min above critical temperature",
+                        health.throttle_transitions,
+                        health.throttle_secs,
+                        health.warning_temp_minutes,
+                        health.critical_temp_minutes
+                    ),
+                );
+                if let Some(latest) = health.errors.first() {
+                    section.push(
+                        &format!("{} last error", controller.name),
+                        latest.describe(),
+                    );
+                }
+            }
+            Err(e) => section.push(&key, format!("unavailable ({})", e)),
+        }
+    }
+
+    section
+}
@@ -0,0 +1,308 @@
+//! PCIe Domain Entity
+//!
+//! This module provides the PCIe links of the devices of a machine, as reported
+//! by the Linux PCI subsystem, and the bandwidth measured across them. A device
+//! that negotiated a slower or narrower link than it supports, such as an x16
+//! card trained at x4 or a Gen4 drive running at Gen1, is flagged: a badly
+//! seated card, a dirty edge connector or a wrong BIOS bifurcation setting all
+//! show up this way long before anything fails outright.
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::discovery::DiscoverySection;
+
+/// The PCI class of NVMe controllers.
+pub const CLASS_NVME: u32 = 0x010802;
+
+/// Returns the PCIe generation of a link speed, e.g. 4 for 16 GT/s.
+pub fn pcie_generation(speed_gts: f64) -> u32 {
+    match speed_gts {
+        s if s >= 64.0 => 6,
+        s if s >= 32.0 => 5,
+        s if s >= 16.0 => 4,
+        s if s >= 8.0 => 3,
+        s if s >= 5.0 => 2,
+        _ => 1,
+    }
+}
+
+/// Parses a link speed as written by the kernel, e.g. "16.0 GT/s PCIe" or "2.5 GT/s".
+///
+/// # Returns
+///
+/// * `Option<f64>` - The speed in GT/s, or `None` for "Unknown" and other text.
+pub fn parse_link_speed(text: &str) -> Option<f64> {
+    text.split_whitespace()
+        .next()?
+        .parse::<f64>()
+        .ok()
+        .filter(|speed| *speed > 0.0)
+}
+
+/// Returns the usable bandwidth of a link in one direction, in GB/s.
+///
+/// Gen1 and Gen2 links lose a fifth of their raw rate to 8b/10b encoding, and
+/// later generations about 1.5% to 128b/130b encoding.
+///
+/// # Arguments
+///
+/// * `speed_gts` - The speed of each lane in GT/s.
+/// * `width` - The number of lanes.
+///
+/// # Returns
+///
+/// * `f64` - The bandwidth in GB/s.
+pub fn link_bandwidth_gb_s(speed_gts: f64, width: u32) -> f64 {
+    let encoding = if speed_gts < 8.0 { 0.8 } else { 128.0 / 130.0 };
+    speed_gts * encoding / 8.0 * f64::from(width)
+}
+
+/// Describes a link as generation and width, e.g. "Gen4 x16 (16.0 GT/s)".
+pub(crate) fn describe_link(speed_gts: Option<f64>, width: Option<u32>) -> String {
+    match (speed_gts, width) {
+        (Some(speed), Some(width)) => {
+            format!(
+                "Gen{} x{} ({:.1} GT/s)",
+                pcie_generation(speed),
+                width,
+                speed
+            )
+        }
+        (Some(speed), None) => format!("Gen{} ({:.1} GT/s)", pcie_generation(speed), speed),
+        (None, Some(width)) => format!("x{}", width),
+        (None, None) => "unknown".to_string(),
+    }
+}
+
+/// The PCIe link of a single device.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PcieLink {
+    /// The PCI address, e.g. "0000:41:00.0".
+    pub address: String,
+
+    /// The PCI class code, e.g. 0x010802 for an NVMe controller.
+    pub class: u32,
+
+    /// The vendor and device IDs, e.g. "144d:a80a".
+    pub id: String,
+
+    /// The kernel name of the device, e.g. "nvme0n1" or "eth0", when it has one.
+    pub name: Option<String>,
+
+    /// The speed the link trained at, in GT/s per lane.
+    pub current_speed_gts: Option<f64>,
+
+    /// The number of lanes the link trained at.
+    pub current_width: Option<u32>,
+
+    /// The fastest speed the device supports, in GT/s per lane.
+    pub max_speed_gts: Option<f64>,
+
+    /// The most lanes the device supports.
+    pub max_width: Option<u32>,
+
+    /// The fastest speed the port the device is plugged into supports.
+    pub slot_speed_gts: Option<f64>,
+
+    /// The most lanes the port the device is plugged into supports.
+    pub slot_width: Option<u32>,
+}
+
+impl PcieLink {
+    /// Returns a short name for the class of the device, e.g. "NVMe controller".
+    pub fn class_name(&self) -> &'static str {
+        match self.class >> 8 {
+            0x0108 => "NVMe controller",
+            0x0106 => "SATA controller",
+            0x0104 => "RAID controller",
+            0x0107 => "SAS controller",
+            0x0200 => "Ethernet controller",
+            0x0207 => "InfiniBand controller",
+            0x0300 => "VGA controller",
+            0x0302 => "3D controller",
+            0x0380 => "Display controller",
+            0x0c03 => "USB controller",
+            0x1200 => "Processing accelerator",
+            _ => "PCI device",
+        }
+    }
+
+    /// Returns `true` for GPUs and other display controllers.
+    pub fn is_gpu(&self) -> bool {
+        self.class >> 16 == 0x03
+    }
+
+    /// Returns the bandwidth of the link as it trained, in GB/s per direction.
+    pub fn current_bandwidth_gb_s(&self) -> Option<f64> {
+        Some(link_bandwidth_gb_s(
+            self.current_speed_gts?,
+            self.current_width?,
+        ))
+    }
+
+    /// Lists how the link falls short of what both the device and its port support.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<String>` - One description per shortfall; empty if the link trained at full capability.
+    pub fn degradations(&self) -> Vec<String> {
+        let mut degradations = Vec::new();
+        let capable_speed = match (self.max_speed_gts, self.slot_speed_gts) {
+            (Some(device), Some(slot)) => Some(device.min(slot)),
+            (device, slot) => device.or(slot),
+        };
+        if let (Some(current), Some(capable)) = (self.current_speed_gts, capable_speed) {
+            if pcie_generation(current) < pcie_generation(capable) {
+                degradations.push(format!(
+                    "running at Gen{}, capable of Gen{}{}",
+                    pcie_generation(current),
+                    pcie_generation(capable),
+                    // GPUs drop their link speed when idle to save power.
+                    if self.is_gpu() {
+                        " (may be idle power saving; check under load)"
+                    } else {
+                        ""
+                    }
+                ));
+            }
+        }
+        let capable_width = match (self.max_width, self.slot_width) {
+            (Some(device), Some(slot)) => Some(device.min(slot)),
+            (device, slot) => device.or(slot),
+        };
+        if let (Some(current), Some(capable)) = (self.current_width, capable_width) {
+            if current < capable {
+                degradations.push(format!("running at x{}, capable of x{}", current, capable));
+            }
+        }
+        degradations
+    }
+
+    /// Describes the device, e.g. "NVMe controller 144d:a80a (nvme0n1)".
+    pub fn describe(&self) -> String {
+        match &self.name {
+            Some(name) => format!("{} {} ({})", self.class_name(), self.id, name),
+            None => format!("{} {}", self.class_name(), self.id),
+        }
+    }
+}
+
+/// A bandwidth measured across a PCIe link.
+#[derive(Debug, Clone)]
+pub struct PcieBandwidth {
+    /// What was measured, e.g. "nvme0n1 read" or "GPU 0 host to device".
+    pub target: String,
+
+    /// The bandwidth measured, in GB/s.
+    pub measured_gb_s: f64,
+
+    /// The bandwidth of the link it crossed, in GB/s, when known.
+    pub link_gb_s: Option<f64>,
+}
+
+/// Builds the discovery section describing the PCIe links of the machine.
+///
+/// # Arguments
+///
+/// * `links` - The links of the PCIe devices, bridges excluded.
+///
+/// # Returns
+///
+/// * `DiscoverySection` - One entry per device with its link and any shortfall.
+pub fn pcie_section(links: &[PcieLink]) -> DiscoverySection {
+    let mut section = DiscoverySection::new("PCIe Links");
+    let degraded = links
+        .iter()
+        .filter(|link| !link.degradations().is_empty())
+        .count();
+    section.push("Devices", links.len());
+    section.push("Degraded", degraded);
+
+    for link in links {
+        let mut value = format!(
+            "{} | {} of {}",
+            link.describe(),
+            describe_link(link.current_speed_gts, link.current_width),
+            describe_link(link.max_speed_gts, link.max_width)
+        );
+        if let Some(bandwidth) = link.current_bandwidth_gb_s() {
+            value.push_str(&format!(" | {:.1} GB/s", bandwidth));
+        }
+        for degradation in link.degradations() {
+            value.push_str(&format!(" | DEGRADED: {}", degradation));
+        }
+        section.push(&link.address, value);
+    }
+
+    section
+}
+
+/// Builds the discovery section listing the bandwidth measured across PCIe links.
+///
+/// # Arguments
+///
+/// * `bandwidths` - The measured bandwidths.
+///
+/// # Returns
+///
+/// * `DiscoverySection` - One entry per measurement, compared to its link when known.
+pub fn bandwidth_section(bandwidths: &[PcieBandwidth]) -> DiscoverySection {
+    let mut section = DiscoverySection::new("PCIe Bandwidth");
+    for bandwidth in bandwidths {
+        let value = match bandwidth.link_gb_s {
+            Some(link) if link > 0.0 => format!(
+                "{:.1} GB/s of {:.1} GB/s link ({:.0}%)",
+                bandwidth.measured_gb_s,
+                link,
+                bandwidth.measured_gb_s / link * 100.0
+            ),
+            _ => format!("{:.1} GB/s", bandwidth.measured_gb_s),
+        };
+        section.push(&bandwidth.target, value);
+    }
+    section
+}
+
+/// Parses the matrix printed by nvbandwidth for one test case.
+///
+/// The matrix follows a heading ending in its unit, such as "bandwidth (GB/s)"
+/// or "(ns)" for latency tests, with one column per device and one row per
+/// device or CPU; cells that were not measured, such as a GPU to itself, read
+/// "N/A".
+///
+/// # Arguments
+///
+/// * `output` - The output of nvbandwidth.
+/// * `heading_suffix` - The end of the heading preceding the matrix.
+///
+/// # Returns
+///
+/// * `Vec<(u32, u32, f64)>` - The row, column and value of every measured cell.
+pub fn parse_nvbandwidth_matrix(output: &str, heading_suffix: &str) -> Vec<(u32, u32, f64)> {
+    let mut lines = output
+        .lines()
+        .skip_while(|line| !line.trim_end().ends_with(heading_suffix))
+        .skip(1);
+    let columns: Vec<u32> = match lines.next() {
+        Some(header) => header
+            .split_whitespace()
+            .filter_map(|column| column.parse().ok())
+            .collect(),
+        None => return Vec::new(),
+    };
+
+    let mut cells = Vec::new();
+    for line in lines {
+        let mut fields = line.split_whitespace();
+        let row = match fields.next().and_then(|row| row.parse::<u32>().ok()) {
+            Some(row) => row,
+            None => break,
+        };
+        for (column, value) in columns.iter().zip(fields) {
+            if let Ok(value) = value.parse::<f64>() {
+                cells.push((row, *column, value));
+            }
+        }
+    }
+    cells
+}
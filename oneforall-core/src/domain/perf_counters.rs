@@ -0,0 +1,283 @@
+//! Performance Counter Domain Entity
+//!
+//! This module provides the hardware performance counters read on every CPU
+//! while a benchmark or stress test runs: instructions, cycles, branches and
+//! last-level cache references with their misses. A score only says how fast
+//! a run was; the instructions retired per cycle and the miss rates say why,
+//! e.g. a low IPC with a high cache miss rate points at memory rather than at
+//! the cores, and a high branch miss rate at the code being measured.
+
+use std::fmt;
+
+/// The instructions per cycle below which a busy CPU is mostly stalled.
+pub const LOW_IPC: f64 = 1.0;
+
+/// The share of last-level cache references missing, in percent, from which a
+/// run is likely bound by memory.
+pub const HIGH_CACHE_MISS_PERCENT: f64 = 30.0;
+
+/// The share of branches mispredicted, in percent, from which a run loses
+/// noticeable time to refilling the pipeline.
+pub const HIGH_BRANCH_MISS_PERCENT: f64 = 5.0;
+
+/// The share of the cycles of the busiest CPU a CPU must have counted to be
+/// listed, so idle CPUs do not crowd the report.
+pub const MIN_ACTIVE_SHARE: f64 = 0.1;
+
+/// A hardware event counted on every CPU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PerfEvent {
+    /// CPU cycles while not halted.
+    Cycles,
+
+    /// Instructions retired.
+    Instructions,
+
+    /// References to the last-level cache.
+    CacheReferences,
+
+    /// Last-level cache references that missed.
+    CacheMisses,
+
+    /// Branch instructions retired.
+    Branches,
+
+    /// Branches mispredicted.
+    BranchMisses,
+}
+
+impl PerfEvent {
+    /// Every event counted.
+    pub const ALL: [PerfEvent; 6] = [
+        PerfEvent::Cycles,
+        PerfEvent::Instructions,
+        PerfEvent::CacheReferences,
+        PerfEvent::CacheMisses,
+        PerfEvent::Branches,
+        PerfEvent::BranchMisses,
+    ];
+
+    /// Returns the `PERF_COUNT_HW_*` number of the generalized hardware event
+    /// `perf_event_open` counts.
+    pub fn config(&self) -> u64 {
+        match self {
+            PerfEvent::Cycles => 0,
+            PerfEvent::Instructions => 1,
+            PerfEvent::CacheReferences => 2,
+            PerfEvent::CacheMisses => 3,
+            PerfEvent::Branches => 4,
+            PerfEvent::BranchMisses => 5,
+        }
+    }
+}
+
+impl fmt::Display for PerfEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            PerfEvent::Cycles => "cycles",
+            PerfEvent::Instructions => "instructions",
+            PerfEvent::CacheReferences => "cache references",
+            PerfEvent::CacheMisses => "cache misses",
+            PerfEvent::Branches => "branches",
+            PerfEvent::BranchMisses => "branch misses",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The counters of one logical CPU over a run; `None` for an event the CPU or
+/// the hypervisor does not count.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CoreCounters {
+    /// The logical CPU ID.
+    pub cpu: u32,
+
+    /// CPU cycles while not halted.
+    pub cycles: Option<u64>,
+
+    /// Instructions retired.
+    pub instructions: Option<u64>,
+
+    /// References to the last-level cache.
+    pub cache_references: Option<u64>,
+
+    /// Last-level cache references that missed.
+    pub cache_misses: Option<u64>,
+
+    /// Branch instructions retired.
+    pub branches: Option<u64>,
+
+    /// Branches mispredicted.
+    pub branch_misses: Option<u64>,
+}
+
+impl CoreCounters {
+    /// Creates the counters of a CPU with no event counted yet.
+    pub fn new(cpu: u32) -> Self {
+        CoreCounters {
+            cpu,
+            ..CoreCounters::default()
+        }
+    }
+
+    /// Records the count of an event.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The event counted.
+    /// * `count` - The count, scaled up if the event shared its counter with others.
+    pub fn set(&mut self, event: PerfEvent, count: u64) {
+        let field = match event {
+            PerfEvent::Cycles => &mut self.cycles,
+            PerfEvent::Instructions => &mut self.instructions,
+            PerfEvent::CacheReferences => &mut self.cache_references,
+            PerfEvent::CacheMisses => &mut self.cache_misses,
+            PerfEvent::Branches => &mut self.branches,
+            PerfEvent::BranchMisses => &mut self.branch_misses,
+        };
+        *field = Some(count);
+    }
+
+    /// Returns the instructions retired per cycle.
+    pub fn ipc(&self) -> Option<f64> {
+        ratio(self.instructions, self.cycles)
+    }
+
+    /// Returns the share of branches mispredicted, in percent.
+    pub fn branch_miss_percent(&self) -> Option<f64> {
+        ratio(self.branch_misses, self.branches).map(|share| share * 100.0)
+    }
+
+    /// Returns the share of last-level cache references that missed, in percent.
+    pub fn cache_miss_percent(&self) -> Option<f64> {
+        ratio(self.cache_misses, self.cache_references).map(|share| share * 100.0)
+    }
+
+    /// Sums the counters of several CPUs; an event is only summed if every CPU counted it.
+    ///
+    /// # Arguments
+    ///
+    /// * `cores` - The counters of each CPU.
+    ///
+    /// # Returns
+    ///
+    /// * `CoreCounters` - The totals, under the ID of the first CPU.
+    pub fn total(cores: &[&CoreCounters]) -> CoreCounters {
+        let sum = |field: fn(&CoreCounters) -> Option<u64>| {
+            cores.iter().map(|core| field(core)).sum::<Option<u64>>()
+        };
+        CoreCounters {
+            cpu: cores.first().map_or(0, |core| core.cpu),
+            cycles: sum(|core| core.cycles),
+            instructions: sum(|core| core.instructions),
+            cache_references: sum(|core| core.cache_references),
+            cache_misses: sum(|core| core.cache_misses),
+            branches: sum(|core| core.branches),
+            branch_misses: sum(|core| core.branch_misses),
+        }
+    }
+}
+
+/// Divides two counts, `None` if either is missing or the divisor is zero.
+fn ratio(numerator: Option<u64>, denominator: Option<u64>) -> Option<f64> {
+    let denominator = denominator.filter(|count| *count > 0)?;
+    Some(numerator? as f64 / denominator as f64)
+}
+
+/// Formats a count with a decimal prefix, e.g. "12.3 G".
+fn format_count(count: Option<u64>) -> String {
+    match count {
+        None => "-".to_string(),
+        Some(count) if count >= 1_000_000_000 => format!("{:.1} G", count as f64 / 1e9),
+        Some(count) if count >= 1_000_000 => format!("{:.1} M", count as f64 / 1e6),
+        Some(count) if count >= 1000 => format!("{:.1} K", count as f64 / 1e3),
+        Some(count) => count.to_string(),
+    }
+}
+
+/// Formats an optional value with a number of decimals and a suffix, or "-".
+fn format_value(value: Option<f64>, decimals: usize, suffix: &str) -> String {
+    value.map_or("-".to_string(), |value| {
+        format!("{:.*}{}", decimals, value, suffix)
+    })
+}
+
+/// Renders the counters of the busy CPUs and of the whole machine, with what they
+/// suggest limited the run.
+///
+/// # Arguments
+///
+/// * `cores` - The counters of every CPU.
+///
+/// # Returns
+///
+/// * `String` - A table of the counters, or nothing if no CPU counted cycles.
+pub fn format_counter_report(cores: &[CoreCounters]) -> String {
+    let busiest = cores
+        .iter()
+        .filter_map(|core| core.cycles)
+        .max()
+        .unwrap_or(0);
+    if busiest == 0 {
+        return String::new();
+    }
+    let active: Vec<&CoreCounters> = cores
+        .iter()
+        .filter(|core| core.cycles.unwrap_or(0) as f64 >= busiest as f64 * MIN_ACTIVE_SHARE)
+        .collect();
+
+    let mut output = String::from("Hardware performance counters\n");
+    output.push_str("  CPU      Cycles  Instructions    IPC  Branch miss  Cache miss\n");
+    let row = |label: String, core: &CoreCounters| {
+        format!(
+            "  {:<5} {:>9} {:>13} {:>6} {:>12} {:>11}\n",
+            label,
+            format_count(core.cycles),
+            format_count(core.instructions),
+            format_value(core.ipc(), 2, ""),
+            format_value(core.branch_miss_percent(), 1, "%"),
+            format_value(core.cache_miss_percent(), 1, "%")
+        )
+    };
+    for core in &active {
+        output.push_str(&row(core.cpu.to_string(), core));
+    }
+    let total = CoreCounters::total(&active);
+    if active.len() > 1 {
+        output.push_str(&row("all".to_string(), &total));
+    }
+
+    if total.ipc().is_some_and(|ipc| ipc < LOW_IPC) {
+        let cause = if total
+            .cache_miss_percent()
+            .is_some_and(|share| share >= HIGH_CACHE_MISS_PERCENT)
+        {
+            "waiting on memory, as the cache miss rate shows"
+        } else {
+            "stalled"
+        };
+        output.push_str(&format!(
+            "NOTE: the busy CPUs retired {} instructions per cycle and were mostly {}.\n",
+            format_value(total.ipc(), 2, ""),
+            cause
+        ));
+    } else if total
+        .cache_miss_percent()
+        .is_some_and(|share| share >= HIGH_CACHE_MISS_PERCENT)
+    {
+        output.push_str(&format!(
+            "NOTE: {} of the last-level cache references missed; the run is likely bound by memory.\n",
+            format_value(total.cache_miss_percent(), 1, "%")
+        ));
+    }
+    if total
+        .branch_miss_percent()
+        .is_some_and(|share| share >= HIGH_BRANCH_MISS_PERCENT)
+    {
+        output.push_str(&format!(
+            "NOTE: {} of the branches were mispredicted, costing a pipeline refill each.\n",
+            format_value(total.branch_miss_percent(), 1, "%")
+        ));
+    }
+    output
+}
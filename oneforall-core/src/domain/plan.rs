@@ -0,0 +1,180 @@
+//! Execution Plan Domain Entity
+//!
+//! This module provides what `oneforall --dry-run` prints instead of running a
+//! command or test profile: every workload it would start, with the command
+//! line, duration, resources and targets of each, and the data each would
+//! write to disk. Nothing is started, queued or stored while planning, so a
+//! run can be reviewed before it is let loose on production-adjacent drives.
+
+use std::time::Duration;
+
+use common::domain::job::Resource;
+
+use crate::domain::endurance::format_volume;
+use crate::domain::profile::format_duration;
+
+/// How much data a planned workload writes to disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiskWrites {
+    /// The workload does not write to disk.
+    None,
+
+    /// The workload writes at most this many bytes.
+    Volume(u64),
+
+    /// The workload rewrites a working set of this many bytes for its whole duration,
+    /// so the volume written depends on the drive's speed.
+    Sustained(u64),
+
+    /// The workload writes to disk, but how much is not known in advance.
+    Unknown,
+}
+
+impl DiskWrites {
+    /// Renders the writes for the plan, e.g. "10.0 GiB".
+    pub fn render(&self) -> String {
+        match self {
+            DiskWrites::None => "none".to_string(),
+            DiskWrites::Volume(bytes) => format_volume(*bytes),
+            DiskWrites::Sustained(bytes) => {
+                format!("{} rewritten for the duration", format_volume(*bytes))
+            }
+            DiskWrites::Unknown => "yes, volume not known in advance".to_string(),
+        }
+    }
+}
+
+/// One workload a command or profile would start.
+#[derive(Debug, Clone)]
+pub struct PlannedStep {
+    /// The step or workload name, e.g. "cpu-soak".
+    pub name: String,
+
+    /// What would execute, e.g. "stress-ng --cpu 8 --timeout 1800s --metrics-brief".
+    pub command: String,
+
+    /// How long the workload runs, if it is timed.
+    pub duration: Option<Duration>,
+
+    /// The resources the job of the workload would hold.
+    pub resources: Vec<Resource>,
+
+    /// The CPUs, devices, directories or nodes the workload targets.
+    pub targets: Vec<String>,
+
+    /// The data the workload writes to disk.
+    pub disk_writes: DiskWrites,
+}
+
+impl PlannedStep {
+    /// Creates a step running on the whole machine without writing to disk.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The step or workload name.
+    /// * `command` - What would execute.
+    /// * `resources` - The resources its job would hold.
+    ///
+    /// # Returns
+    ///
+    /// * `PlannedStep` - An untimed step; set the other fields as needed.
+    pub fn new(name: &str, command: impl Into<String>, resources: &[Resource]) -> Self {
+        PlannedStep {
+            name: name.to_string(),
+            command: command.into(),
+            duration: None,
+            resources: resources.to_vec(),
+            targets: Vec::new(),
+            disk_writes: DiskWrites::None,
+        }
+    }
+}
+
+/// Everything a command or profile would execute, in order.
+#[derive(Debug, Clone)]
+pub struct ExecutionPlan {
+    /// What is planned, e.g. "profile burn-in-24h" or "stress --all".
+    pub title: String,
+
+    /// The workloads, in the order they would run.
+    pub steps: Vec<PlannedStep>,
+}
+
+impl ExecutionPlan {
+    /// Creates a plan without steps.
+    pub fn new(title: &str) -> Self {
+        ExecutionPlan {
+            title: title.to_string(),
+            steps: Vec::new(),
+        }
+    }
+
+    /// Returns the combined duration of the timed steps.
+    pub fn planned_duration(&self) -> Duration {
+        self.steps.iter().filter_map(|step| step.duration).sum()
+    }
+
+    /// Renders the plan as plain text suitable for the terminal.
+    pub fn render(&self) -> String {
+        let mut output = format!("== Dry run: {} (nothing is executed) ==\n", self.title);
+        for (index, step) in self.steps.iter().enumerate() {
+            let resources: Vec<String> = step
+                .resources
+                .iter()
+                .map(|resource| format!("{:?}", resource).to_lowercase())
+                .collect();
+            output.push_str(&format!("  {}. {}\n", index + 1, step.name));
+            output.push_str(&format!("       runs:        {}\n", step.command));
+            output.push_str(&format!(
+                "       duration:    {}\n",
+                step.duration
+                    .map_or("until done".to_string(), format_duration)
+            ));
+            output.push_str(&format!(
+                "       resources:   {}\n",
+                if resources.is_empty() {
+                    "none".to_string()
+                } else {
+                    resources.join(", ")
+                }
+            ));
+            if !step.targets.is_empty() {
+                output.push_str(&format!(
+                    "       targets:     {}\n",
+                    step.targets.join(", ")
+                ));
+            }
+            output.push_str(&format!(
+                "       disk writes: {}\n",
+                step.disk_writes.render()
+            ));
+        }
+
+        let volume: u64 = self
+            .steps
+            .iter()
+            .map(|step| match step.disk_writes {
+                DiskWrites::Volume(bytes) => bytes,
+                _ => 0,
+            })
+            .sum();
+        let open_ended = self.steps.iter().any(|step| {
+            matches!(
+                step.disk_writes,
+                DiskWrites::Sustained(_) | DiskWrites::Unknown
+            )
+        });
+        output.push_str(&format!(
+            "Total: {} step(s), {} of timed workloads, {} written to disk{}\n",
+            self.steps.len(),
+            format_duration(self.planned_duration()),
+            format_volume(volume),
+            if open_ended {
+                " plus sustained or unknown writes"
+            } else {
+                ""
+            }
+        ));
+        output
+    }
+}
@@ -0,0 +1,127 @@
+//! Platform Domain Entity
+//!
+//! This module provides the operating system and CPU architecture pair used to
+//! pick external tools such as stress-ng, and reads the platform an executable
+//! was built for from its ELF or Mach-O header, so that a binary built for the
+//! wrong platform is rejected before it is run.
+
+use std::fmt;
+
+/// An operating system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Os {
+    Linux,
+    MacOS,
+    Other(String),
+}
+
+/// A CPU architecture.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Arch {
+    X86_64,
+    Aarch64,
+    Other(String),
+}
+
+/// An operating system and CPU architecture pair, e.g. `linux/aarch64`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Platform {
+    pub os: Os,
+    pub arch: Arch,
+}
+
+impl Platform {
+    /// Returns the platform OneForAll is running on.
+    pub fn current() -> Platform {
+        let os = match std::env::consts::OS {
+            "linux" => Os::Linux,
+            "macos" => Os::MacOS,
+            other => Os::Other(other.to_string()),
+        };
+        let arch = match std::env::consts::ARCH {
+            "x86_64" => Arch::X86_64,
+            "aarch64" => Arch::Aarch64,
+            other => Arch::Other(other.to_string()),
+        };
+        Platform { os, arch }
+    }
+
+    /// Returns `true` if an executable built for `built_for` can run on this platform.
+    ///
+    /// Besides native executables, Apple Silicon Macs run x86_64 macOS executables
+    /// through Rosetta 2.
+    pub fn can_run(&self, built_for: &Platform) -> bool {
+        self == built_for
+            || (self.os == Os::MacOS
+                && self.arch == Arch::Aarch64
+                && built_for.os == Os::MacOS
+                && built_for.arch == Arch::X86_64)
+    }
+
+    /// Reads the platform an executable was built for from its header.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The executable, or at least its first 20 bytes.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Platform>` - The platform, or `None` if the header is not a
+    ///   64-bit little-endian ELF or Mach-O header.
+    pub fn of_executable(bytes: &[u8]) -> Option<Platform> {
+        let header = bytes.get(..20)?;
+        let u16_at = |offset: usize| u16::from_le_bytes([header[offset], header[offset + 1]]);
+        let u32_at = |offset: usize| {
+            u32::from_le_bytes([
+                header[offset],
+                header[offset + 1],
+                header[offset + 2],
+                header[offset + 3],
+            ])
+        };
+
+        // ELF: class 2 is 64-bit, data 1 is little-endian, e_machine is at offset 18.
+        if header.starts_with(b"\x7fELF") && header[4] == 2 && header[5] == 1 {
+            let arch = match u16_at(18) {
+                0x3e => Arch::X86_64,
+                0xb7 => Arch::Aarch64,
+                other => Arch::Other(format!("elf-machine-{:#x}", other)),
+            };
+            return Some(Platform {
+                os: Os::Linux,
+                arch,
+            });
+        }
+
+        // Mach-O 64-bit: magic 0xfeedfacf followed by the CPU type.
+        if u32_at(0) == 0xfeed_facf {
+            let arch = match u32_at(4) {
+                0x0100_0007 => Arch::X86_64,
+                0x0100_000c => Arch::Aarch64,
+                other => Arch::Other(format!("mach-o-cpu-{:#x}", other)),
+            };
+            return Some(Platform {
+                os: Os::MacOS,
+                arch,
+            });
+        }
+
+        None
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let os = match &self.os {
+            Os::Linux => "linux",
+            Os::MacOS => "macos",
+            Os::Other(name) => name,
+        };
+        let arch = match &self.arch {
+            Arch::X86_64 => "x86_64",
+            Arch::Aarch64 => "aarch64",
+            Arch::Other(name) => name,
+        };
+        write!(f, "{}/{}", os, arch)
+    }
+}
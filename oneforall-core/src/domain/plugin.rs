@@ -0,0 +1,105 @@
+//! Plugin Domain Entity
+//!
+//! This module provides what third-party benchmarks and stress tests, such as
+//! an FPGA loopback test, exchange with OneForAll. A plugin is either compiled
+//! into a service embedding `oneforall-core` and registered with the
+//! `PluginRegistry`, or built as a shared library that OneForAll loads from the
+//! paths of the `[plugins]` section. Either way it becomes a subcommand of its
+//! own, runs as a job holding the resources it declares, and its metrics are
+//! stored as a benchmark result like those of the built-in benchmarks.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::statistics::Measurement;
+
+/// The configuration file; sections other than `[plugins]` are left to their own readers.
+#[derive(Debug, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    plugins: PluginConfig,
+}
+
+/// The plugin settings.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PluginConfig {
+    /// Shared libraries to load, or directories whose `.so` and `.dylib` files are loaded.
+    #[serde(default)]
+    pub paths: Vec<PathBuf>,
+}
+
+impl PluginConfig {
+    /// Parses the `[plugins]` section of a configuration file.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The TOML text of the configuration file.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<PluginConfig, String>` - The plugin settings, or why they are invalid.
+    pub fn from_toml(text: &str) -> Result<PluginConfig, String> {
+        let file: ConfigFile = toml::from_str(text).map_err(|e| e.to_string())?;
+        Ok(file.plugins)
+    }
+}
+
+/// What a plugin run produced.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginOutput {
+    /// The results rendered for the terminal.
+    #[serde(default)]
+    pub report: String,
+
+    /// The metrics of the run, stored with the benchmark results.
+    #[serde(default)]
+    pub measurements: Vec<Measurement>,
+
+    /// Whether the hardware passed, for plugins that test rather than measure.
+    #[serde(default)]
+    pub passed: Option<bool>,
+}
+
+/// Parses what a plugin run returned: its output, or `{"error": "..."}` if it failed.
+///
+/// # Arguments
+///
+/// * `text` - The JSON text returned by the plugin.
+///
+/// # Returns
+///
+/// * `Result<PluginOutput, String>` - The output, or the error of the plugin.
+pub fn parse_plugin_response(text: &str) -> Result<PluginOutput, String> {
+    let response: serde_json::Value =
+        serde_json::from_str(text).map_err(|e| format!("Invalid plugin result: {}", e))?;
+    if let Some(error) = response.get("error").and_then(|error| error.as_str()) {
+        return Err(error.to_string());
+    }
+    serde_json::from_value(response).map_err(|e| format!("Invalid plugin result: {}", e))
+}
+
+/// Checks that a plugin name can be used as a subcommand, e.g. "fpga-loopback".
+///
+/// # Arguments
+///
+/// * `name` - The name the plugin gives itself.
+///
+/// # Returns
+///
+/// * `Result<(), String>` - Whether the name is valid, or why not.
+pub fn validate_plugin_name(name: &str) -> Result<(), String> {
+    let valid = name.chars().next().is_some_and(|c| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid plugin name '{}'; use lowercase letters, digits and '-', starting with a letter",
+            name
+        ))
+    }
+}
@@ -0,0 +1,199 @@
+//! Power Domain Entity
+//!
+//! This module provides domain entities for cumulative energy counters (Intel
+//! RAPL, AMD energy) and the power summary attached to stress test and
+//! benchmark results, including the ops-per-joule efficiency metric.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use common::domain::telemetry::{DomainPower, PowerSample};
+
+/// A cumulative energy counter exposed by the CPU.
+#[derive(Debug, Clone)]
+pub struct EnergyCounter {
+    /// The measurement domain, e.g. "package-0" or "package-0/dram".
+    pub domain: String,
+
+    /// The cumulative energy in microjoules.
+    pub energy_uj: u64,
+
+    /// The value at which the counter wraps around, or `0` if it never wraps.
+    pub max_energy_range_uj: u64,
+}
+
+/// Returns the energy consumed between two readings of the same counter,
+/// accounting for a single wrap-around of the counter.
+fn energy_delta_uj(before: &EnergyCounter, after: &EnergyCounter) -> u64 {
+    if after.energy_uj >= before.energy_uj {
+        after.energy_uj - before.energy_uj
+    } else if after.max_energy_range_uj > 0 {
+        (after.max_energy_range_uj - before.energy_uj) + after.energy_uj
+    } else {
+        0
+    }
+}
+
+/// Converts two successive counter readings into the average power per domain.
+///
+/// # Arguments
+///
+/// * `before` - The earlier counter readings.
+/// * `after` - The later counter readings.
+/// * `elapsed` - The time between the two readings.
+///
+/// # Returns
+///
+/// * `Vec<DomainPower>` - The average power of every domain present in both readings.
+pub fn power_between(
+    before: &[EnergyCounter],
+    after: &[EnergyCounter],
+    elapsed: Duration,
+) -> Vec<DomainPower> {
+    let seconds = elapsed.as_secs_f64();
+    if seconds <= 0.0 {
+        return Vec::new();
+    }
+
+    after
+        .iter()
+        .filter_map(|end| {
+            let start = before.iter().find(|c| c.domain == end.domain)?;
+            Some(DomainPower {
+                domain: end.domain.clone(),
+                watts: energy_delta_uj(start, end) as f64 / 1_000_000.0 / seconds,
+            })
+        })
+        .collect()
+}
+
+/// The power drawn over a whole run.
+#[derive(Debug, Clone)]
+pub struct PowerSummary {
+    /// The average power of every domain over the run.
+    pub average: Vec<DomainPower>,
+
+    /// The average whole-system power reported by the BMC, if available.
+    pub average_bmc_watts: Option<f64>,
+
+    /// The energy consumed over the run in joules.
+    pub energy_joules: f64,
+
+    /// The source the energy figure was derived from, e.g. "BMC" or "CPU packages".
+    pub energy_source: &'static str,
+}
+
+impl PowerSummary {
+    /// Summarizes the power samples collected during a run.
+    ///
+    /// The BMC reading is preferred for the energy figure since it covers the
+    /// whole system; the CPU package counters are used otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `samples` - Power samples with their offset into the run.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<PowerSummary>` - The summary, or `None` if no power source reported.
+    pub fn from_samples(samples: &[(Duration, PowerSample)]) -> Option<PowerSummary> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        let mut totals: BTreeMap<String, (f64, usize)> = BTreeMap::new();
+        let mut bmc_readings = Vec::new();
+        let mut bmc_energy = 0.0;
+        let mut package_energy = 0.0;
+        let mut previous = Duration::ZERO;
+
+        for (offset, sample) in samples {
+            // Each sample covers the time since the previous sample.
+            let interval = offset.saturating_sub(previous).as_secs_f64();
+            previous = *offset;
+
+            for domain in &sample.domains {
+                let entry = totals.entry(domain.domain.clone()).or_default();
+                entry.0 += domain.watts;
+                entry.1 += 1;
+            }
+            if let Some(watts) = sample.bmc_watts {
+                bmc_readings.push(watts);
+                bmc_energy += watts * interval;
+            }
+            if let Some(watts) = sample.package_watts() {
+                package_energy += watts * interval;
+            }
+        }
+
+        let average = totals
+            .into_iter()
+            .map(|(domain, (sum, count))| DomainPower {
+                domain,
+                watts: sum / count as f64,
+            })
+            .collect::<Vec<_>>();
+        let average_bmc_watts = if bmc_readings.is_empty() {
+            None
+        } else {
+            Some(bmc_readings.iter().sum::<f64>() / bmc_readings.len() as f64)
+        };
+
+        if average.is_empty() && average_bmc_watts.is_none() {
+            return None;
+        }
+
+        let (energy_joules, energy_source) = if average_bmc_watts.is_some() {
+            (bmc_energy, "BMC")
+        } else {
+            (package_energy, "CPU packages")
+        };
+
+        Some(PowerSummary {
+            average,
+            average_bmc_watts,
+            energy_joules,
+            energy_source,
+        })
+    }
+
+    /// Renders the power summary for the run report.
+    pub fn render(&self) -> String {
+        let mut output = String::from("Power consumption\n");
+        for domain in &self.average {
+            output.push_str(&format!(
+                "  {:<20} {:>8.1} W avg\n",
+                domain.domain, domain.watts
+            ));
+        }
+        if let Some(watts) = self.average_bmc_watts {
+            output.push_str(&format!("  {:<20} {:>8.1} W avg\n", "system (BMC)", watts));
+        }
+        output.push_str(&format!(
+            "  Energy consumed: {:.1} J ({})\n",
+            self.energy_joules, self.energy_source
+        ));
+        output
+    }
+
+    /// Formats the efficiency metric for a number of completed operations.
+    ///
+    /// # Arguments
+    ///
+    /// * `ops` - The number of operations completed during the run, e.g. bogo-ops.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>` - The ops-per-joule line, or `None` if no energy was measured.
+    pub fn format_efficiency(&self, ops: u64) -> Option<String> {
+        if self.energy_joules <= 0.0 {
+            return None;
+        }
+        Some(format!(
+            "  Efficiency: {:.2} ops/J ({} ops over {:.1} J)\n",
+            ops as f64 / self.energy_joules,
+            ops,
+            self.energy_joules
+        ))
+    }
+}
@@ -0,0 +1,279 @@
+//! Power Cycle Domain Entity
+//!
+//! This module provides the power-cycle endurance test that `oneforall
+//! power-cycle` runs from a controller machine. The BMC of the machine under
+//! test power cycles it a number of times, over Redfish or IPMI, and after
+//! each cycle the OneForAll agent on it, started at boot by its systemd unit,
+//! must stop answering and then pass its readiness probe again within a
+//! limit. A cycle after which the agent is not ready in time is a boot
+//! failure; for every other cycle the time from the power cycle until the
+//! agent was ready is recorded, as fleets accept machines on it.
+
+use std::fmt;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::domain::profile::format_duration;
+
+/// How long the agent may take to be ready again after a power cycle when no limit is given.
+pub const DEFAULT_READY_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// The BMC that power cycles the machine under test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BmcTarget {
+    /// A BMC reached over Redfish at its base URL, e.g. "https://10.0.0.5".
+    Redfish(String),
+
+    /// A BMC reached with IPMI over LAN at its host name or address.
+    Lan(String),
+
+    /// The BMC of this machine, through the local IPMI device.
+    Local,
+}
+
+impl BmcTarget {
+    /// Parses the BMC of a remote machine.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - A URL, for Redfish, or a host name or address, for IPMI over LAN.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<BmcTarget, String>` - The BMC, or why the text names none.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim().trim_end_matches('/');
+        if text.starts_with("http://") || text.starts_with("https://") {
+            if text.split("://").nth(1).is_none_or(str::is_empty) {
+                return Err(format!("Invalid BMC '{}': the URL names no host", text));
+            }
+            return Ok(BmcTarget::Redfish(text.to_string()));
+        }
+        if text.is_empty() || text.contains(['/', ' ']) {
+            return Err(format!(
+                "Invalid BMC '{}': expected a Redfish URL or the host of an IPMI BMC",
+                text
+            ));
+        }
+        Ok(BmcTarget::Lan(text.to_string()))
+    }
+}
+
+impl fmt::Display for BmcTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BmcTarget::Redfish(url) => write!(f, "Redfish at {}", url),
+            BmcTarget::Lan(host) => write!(f, "IPMI over LAN at {}", host),
+            BmcTarget::Local => write!(f, "the local IPMI device"),
+        }
+    }
+}
+
+/// The token `--confirm` must repeat to power cycle a machine, e.g. "power-cycle-10.0.0.5".
+///
+/// # Arguments
+///
+/// * `bmc` - The BMC that power cycles the machine.
+///
+/// # Returns
+///
+/// * `String` - The token naming the host of the BMC.
+pub fn confirmation_token(bmc: &BmcTarget) -> String {
+    let host = match bmc {
+        BmcTarget::Redfish(url) => url
+            .split("://")
+            .nth(1)
+            .and_then(|rest| rest.split('/').next())
+            .unwrap_or(url),
+        BmcTarget::Lan(host) => host,
+        BmcTarget::Local => "local",
+    };
+    format!("power-cycle-{}", host)
+}
+
+/// Checks that power cycling the machine behind a BMC was confirmed.
+///
+/// # Arguments
+///
+/// * `bmc` - The BMC that power cycles the machine.
+/// * `confirm` - The token given with `--confirm`, if any.
+///
+/// # Returns
+///
+/// * `Result<(), String>` - Nothing, or why the power cycles are refused.
+pub fn confirm_power_cycle(bmc: &BmcTarget, confirm: Option<&str>) -> Result<(), String> {
+    let token = confirmation_token(bmc);
+    if confirm == Some(token.as_str()) {
+        return Ok(());
+    }
+    Err(format!(
+        "Refusing to power cycle the machine behind {}, which cuts its power and loses \
+         whatever it is running; pass --confirm {} to power cycle it",
+        bmc, token
+    ))
+}
+
+/// How one power cycle ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CycleOutcome {
+    /// The agent went down and was ready again within the limit.
+    Ready,
+
+    /// The BMC refused the power cycle, or the agent kept answering as if the
+    /// machine never lost power.
+    NotCycled,
+
+    /// The agent was not ready again within the limit.
+    BootFailure,
+}
+
+/// One power cycle of the test.
+#[derive(Debug, Clone, Serialize)]
+pub struct CycleRecord {
+    /// The number of the cycle, from 1.
+    pub cycle: u32,
+
+    /// How the cycle ended.
+    pub outcome: CycleOutcome,
+
+    /// When the agent stopped answering, in seconds after the power cycle, if it was
+    /// answering before.
+    pub down_secs: Option<f64>,
+
+    /// When the agent was ready again, in seconds after the power cycle.
+    pub ready_secs: Option<f64>,
+
+    /// Why the cycle failed: what the BMC answered, or the last answer of the agent.
+    pub error: Option<String>,
+}
+
+/// The report of a power-cycle endurance test.
+#[derive(Debug, Clone, Serialize)]
+pub struct PowerCycleReport {
+    /// The base URL of the agent on the machine under test.
+    pub agent: String,
+
+    /// The BMC that power cycled the machine.
+    pub bmc: String,
+
+    /// When the test started, in RFC 3339.
+    pub started_at: String,
+
+    /// The number of power cycles asked for.
+    pub planned: u32,
+
+    /// How long the agent was allowed to take to be ready again, in seconds.
+    pub ready_timeout_secs: u64,
+
+    /// Every cycle run, in order.
+    pub cycles: Vec<CycleRecord>,
+
+    /// Whether the test was cancelled before every cycle ran.
+    pub cancelled: bool,
+}
+
+impl PowerCycleReport {
+    /// Returns the number of cycles after which the agent was not ready in time.
+    pub fn boot_failures(&self) -> usize {
+        self.count(CycleOutcome::BootFailure)
+    }
+
+    /// Returns the number of cycles the machine did not go through.
+    pub fn not_cycled(&self) -> usize {
+        self.count(CycleOutcome::NotCycled)
+    }
+
+    fn count(&self, outcome: CycleOutcome) -> usize {
+        self.cycles
+            .iter()
+            .filter(|cycle| cycle.outcome == outcome)
+            .count()
+    }
+
+    /// Returns the shortest, mean and longest time until the agent was ready, in seconds.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<(f64, f64, f64)>` - The times, or `None` if no cycle ended ready.
+    pub fn time_to_ready(&self) -> Option<(f64, f64, f64)> {
+        let times: Vec<f64> = self
+            .cycles
+            .iter()
+            .filter(|cycle| cycle.outcome == CycleOutcome::Ready)
+            .filter_map(|cycle| cycle.ready_secs)
+            .collect();
+        if times.is_empty() {
+            return None;
+        }
+        let min = times.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = times.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let mean = times.iter().sum::<f64>() / times.len() as f64;
+        Some((min, mean, max))
+    }
+
+    /// Returns whether every planned cycle ran and ended with the agent ready.
+    pub fn passed(&self) -> bool {
+        !self.cancelled
+            && self.cycles.len() == self.planned as usize
+            && self
+                .cycles
+                .iter()
+                .all(|cycle| cycle.outcome == CycleOutcome::Ready)
+    }
+
+    /// Renders the report as plain text suitable for the terminal.
+    pub fn render(&self) -> String {
+        let mut output = String::from("== Power cycle endurance report ==\n");
+        output.push_str(&format!("Agent:       {}\n", self.agent));
+        output.push_str(&format!("BMC:         {}\n", self.bmc));
+        output.push_str(&format!(
+            "Cycles:      {} of {}, {} boot failure(s), {} not power cycled\n",
+            self.cycles.len(),
+            self.planned,
+            self.boot_failures(),
+            self.not_cycled()
+        ));
+        match self.time_to_ready() {
+            Some((min, mean, max)) => output.push_str(&format!(
+                "Ready after: min {:.0} s, mean {:.0} s, max {:.0} s (limit {})\n",
+                min,
+                mean,
+                max,
+                format_duration(Duration::from_secs(self.ready_timeout_secs))
+            )),
+            None => output.push_str("Ready after: -\n"),
+        }
+        for cycle in &self.cycles {
+            let line = match cycle.outcome {
+                CycleOutcome::Ready => format!(
+                    "ready after {:.0} s{}",
+                    cycle.ready_secs.unwrap_or_default(),
+                    cycle
+                        .down_secs
+                        .map(|down| format!(" (down after {:.0} s)", down))
+                        .unwrap_or_default()
+                ),
+                CycleOutcome::NotCycled => "NOT POWER CYCLED".to_string(),
+                CycleOutcome::BootFailure => format!(
+                    "BOOT FAILURE: not ready within {}",
+                    format_duration(Duration::from_secs(self.ready_timeout_secs))
+                ),
+            };
+            output.push_str(&format!("  cycle {:>4}  {}", cycle.cycle, line));
+            if let Some(error) = &cycle.error {
+                output.push_str(&format!(": {}", error));
+            }
+            output.push('\n');
+        }
+        if self.cancelled {
+            output.push_str("Cancelled before every cycle ran.\n");
+        }
+        output.push_str(&format!(
+            "Result: {}\n",
+            if self.passed() { "PASSED" } else { "FAILED" }
+        ));
+        output
+    }
+}
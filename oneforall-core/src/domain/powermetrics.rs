@@ -0,0 +1,250 @@
+//! Powermetrics Domain Entity
+//!
+//! This module provides domain entities for the readings of `powermetrics` on
+//! macOS, which reports what Apple Silicon hides from the interfaces sampled on
+//! Linux: how busy and how fast the performance and efficiency clusters run,
+//! the power drawn by the CPU, GPU and Neural Engine, and the thermal pressure
+//! the system is under. The report attached to stress test and benchmark
+//! results follows both classes of cores, since a workload the scheduler left
+//! on the efficiency cores scores far below one running on the performance cores.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use common::domain::telemetry::{DomainPower, PowerSample};
+
+use crate::domain::statistics::mean;
+
+/// The thermal pressure level of a system that is not throttling for temperature.
+pub const NOMINAL_PRESSURE: &str = "Nominal";
+
+/// The activity of one cluster of cores over a sample.
+#[derive(Debug, Clone, Default)]
+pub struct ClusterActivity {
+    /// The cluster name, e.g. "E" or "P0".
+    pub name: String,
+
+    /// The average frequency of the cluster while active, in MHz.
+    pub frequency_mhz: Option<f64>,
+
+    /// The share of the sample the cluster was active, in percent.
+    pub active_percent: Option<f64>,
+}
+
+impl ClusterActivity {
+    /// Returns the class of the cores of the cluster, "efficiency" or "performance",
+    /// named as the core classes of the CPU architecture.
+    pub fn core_class(&self) -> &'static str {
+        if self.name.starts_with('E') {
+            "efficiency"
+        } else {
+            "performance"
+        }
+    }
+}
+
+/// One reading of `powermetrics`.
+#[derive(Debug, Clone, Default)]
+pub struct PowermetricsSample {
+    /// The activity of each cluster, e.g. "E", "P0" and "P1".
+    pub clusters: Vec<ClusterActivity>,
+
+    /// The power drawn by the CPU, in watts.
+    pub cpu_watts: Option<f64>,
+
+    /// The power drawn by the GPU, in watts.
+    pub gpu_watts: Option<f64>,
+
+    /// The power drawn by the Neural Engine, in watts.
+    pub ane_watts: Option<f64>,
+
+    /// The power drawn by the whole package, in watts.
+    pub package_watts: Option<f64>,
+
+    /// The thermal pressure level, e.g. "Nominal", "Moderate" or "Heavy".
+    pub thermal_pressure: Option<String>,
+}
+
+impl PowermetricsSample {
+    /// Returns the activity of a class of cores, averaged over its clusters.
+    ///
+    /// # Arguments
+    ///
+    /// * `class` - The class of cores, "performance" or "efficiency".
+    ///
+    /// # Returns
+    ///
+    /// * `Option<(f64, Option<f64>)>` - The share of the sample the class was active,
+    ///   in percent, and its frequency in MHz, or `None` if the CPU has no such class.
+    pub fn class_activity(&self, class: &str) -> Option<(f64, Option<f64>)> {
+        let clusters: Vec<&ClusterActivity> = self
+            .clusters
+            .iter()
+            .filter(|cluster| cluster.core_class() == class)
+            .collect();
+        let active: Vec<f64> = clusters.iter().filter_map(|c| c.active_percent).collect();
+        let frequencies: Vec<f64> = clusters.iter().filter_map(|c| c.frequency_mhz).collect();
+        Some((mean(&active)?, mean(&frequencies)))
+    }
+
+    /// Returns the sample as a power reading, so the power report and the REST API
+    /// cover Macs like machines with RAPL counters.
+    pub fn power_sample(&self) -> Option<PowerSample> {
+        let package = self.package_watts.or_else(|| {
+            [self.cpu_watts, self.gpu_watts, self.ane_watts]
+                .into_iter()
+                .flatten()
+                .reduce(|total, watts| total + watts)
+        })?;
+        let mut domains = vec![DomainPower {
+            domain: "package-0".to_string(),
+            watts: package,
+        }];
+        for (name, watts) in [
+            ("cpu", self.cpu_watts),
+            ("gpu", self.gpu_watts),
+            ("ane", self.ane_watts),
+        ] {
+            if let Some(watts) = watts {
+                domains.push(DomainPower {
+                    domain: format!("package-0/{}", name),
+                    watts,
+                });
+            }
+        }
+        Some(PowerSample {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            domains,
+            bmc_watts: None,
+        })
+    }
+}
+
+/// Parses a power reading of powermetrics, e.g. "2311 mW" or "3.45W", into watts.
+fn parse_watts(value: &str) -> Option<f64> {
+    let value = value.trim();
+    if let Some(milliwatts) = value.strip_suffix("mW") {
+        return milliwatts.trim().parse::<f64>().ok().map(|mw| mw / 1000.0);
+    }
+    value.strip_suffix('W')?.trim().parse().ok()
+}
+
+/// Parses the text output of `powermetrics --samplers cpu_power,gpu_power,thermal -n 1`.
+///
+/// # Arguments
+///
+/// * `output` - The output of powermetrics.
+///
+/// # Returns
+///
+/// * `PowermetricsSample` - The readings found; those powermetrics did not report are left out.
+pub fn parse_powermetrics(output: &str) -> PowermetricsSample {
+    let mut sample = PowermetricsSample::default();
+    let mut clusters: BTreeMap<String, ClusterActivity> = BTreeMap::new();
+
+    for line in output.lines().map(str::trim) {
+        if let Some((name, value)) = line.split_once("-Cluster HW active frequency:") {
+            clusters.entry(name.to_string()).or_default().frequency_mhz =
+                value.trim().trim_end_matches("MHz").trim().parse().ok();
+        } else if let Some((name, value)) = line.split_once("-Cluster HW active residency:") {
+            // The residency is followed by its breakdown per frequency, e.g. "62.81% (600 MHz: 0% ...)".
+            clusters.entry(name.to_string()).or_default().active_percent = value
+                .split('%')
+                .next()
+                .and_then(|percent| percent.trim().parse().ok());
+        } else if let Some(value) = line.strip_prefix("CPU Power:") {
+            sample.cpu_watts = parse_watts(value);
+        } else if let Some(value) = line.strip_prefix("GPU Power:") {
+            // The GPU sampler repeats the reading of the CPU sampler.
+            sample.gpu_watts = sample.gpu_watts.or_else(|| parse_watts(value));
+        } else if let Some(value) = line.strip_prefix("ANE Power:") {
+            sample.ane_watts = parse_watts(value);
+        } else if line.starts_with("Combined Power")
+            || line.starts_with("Package Power")
+            || line.starts_with("Intel energy model derived package power")
+        {
+            sample.package_watts = line
+                .rsplit_once(':')
+                .and_then(|(_, value)| parse_watts(value));
+        } else if let Some(level) = line.strip_prefix("Current pressure level:") {
+            sample.thermal_pressure = Some(level.trim().to_string());
+        }
+    }
+
+    sample.clusters = clusters
+        .into_iter()
+        .map(|(name, activity)| ClusterActivity { name, ..activity })
+        .collect();
+    sample
+}
+
+/// Formats the powermetrics report of a run.
+///
+/// # Arguments
+///
+/// * `samples` - Powermetrics readings with their offset into the run.
+///
+/// # Returns
+///
+/// * `String` - The rendered report, empty if powermetrics was not sampled.
+pub fn format_powermetrics_report(samples: &[(Duration, PowermetricsSample)]) -> String {
+    if samples.is_empty() {
+        return String::new();
+    }
+
+    let cell = |value: Option<f64>, suffix: &str| {
+        value
+            .map(|value| format!("{:.0}{}", value, suffix))
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let mut output = String::from("Apple Silicon activity (powermetrics)\n");
+    output.push_str(
+        "  Elapsed   P active   P MHz   E active   E MHz   Package W   Thermal pressure\n",
+    );
+    for (offset, sample) in samples {
+        let performance = sample.class_activity("performance");
+        let efficiency = sample.class_activity("efficiency");
+        output.push_str(&format!(
+            "  {:>6.0}s   {:>8}   {:>5}   {:>8}   {:>5}   {:>9}   {}\n",
+            offset.as_secs_f64(),
+            cell(performance.map(|(active, _)| active), "%"),
+            cell(performance.and_then(|(_, mhz)| mhz), ""),
+            cell(efficiency.map(|(active, _)| active), "%"),
+            cell(efficiency.and_then(|(_, mhz)| mhz), ""),
+            sample
+                .package_watts
+                .map(|watts| format!("{:.1}", watts))
+                .unwrap_or_else(|| "-".to_string()),
+            sample.thermal_pressure.as_deref().unwrap_or("-")
+        ));
+    }
+
+    // Any level above nominal means macOS slowed the chip down to cool it.
+    let mut levels: Vec<(&str, usize)> = Vec::new();
+    for level in samples
+        .iter()
+        .filter_map(|(_, sample)| sample.thermal_pressure.as_deref())
+    {
+        match levels.iter_mut().find(|(name, _)| *name == level) {
+            Some((_, count)) => *count += 1,
+            None => levels.push((level, 1)),
+        }
+    }
+    if !levels.is_empty() {
+        let summary: Vec<String> = levels
+            .iter()
+            .map(|(level, count)| format!("{} for {} sample(s)", level, count))
+            .collect();
+        output.push_str(&format!(
+            "\nThermal pressure: {}{}\n",
+            summary.join(", "),
+            if levels.iter().any(|(level, _)| *level != NOMINAL_PRESSURE) {
+                " | THROTTLED FOR TEMPERATURE"
+            } else {
+                ""
+            }
+        ));
+    }
+
+    output
+}
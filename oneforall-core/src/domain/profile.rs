@@ -0,0 +1,863 @@
+//! Test Profile Domain Entity
+//!
+//! This module provides named test profiles, such as `burn-in-24h` or
+//! `quick-sanity`, which chain several stress tests and benchmarks with their
+//! durations and acceptance criteria. Profiles are written in TOML and run
+//! with `oneforall run --profile <name>`, producing a single pass/fail report.
+//! A step may run in several passes and reboot or power cycle the machine
+//! after each, e.g. a RAM test on a freshly booted machine every time; the
+//! passes of a step are reported together as one result.
+
+use std::time::Duration;
+
+use common::domain::job::Resource;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::domain::firmware::FirmwareCheck;
+use crate::domain::hook::{HookAction, RedfishOperation};
+use crate::domain::memtest::MemtestResult;
+use crate::domain::soak::DutyCycle;
+use crate::domain::stress_ng::{stressor_resource, StressorCatalog};
+
+/// A named bundle of tests run one after another.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// The profile name, e.g. "burn-in-24h".
+    pub name: String,
+
+    /// A one-line summary shown when listing profiles.
+    #[serde(default)]
+    pub description: String,
+
+    /// Clears the BMC event log before the first step, so the run starts from an
+    /// empty log and the log left afterwards holds only what the run caused.
+    #[serde(default)]
+    pub clear_sel: bool,
+
+    /// The tests to run, in order.
+    #[serde(rename = "step")]
+    pub steps: Vec<ProfileStep>,
+}
+
+/// The kind of test run by a profile step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepTest {
+    /// A stress-ng stressor run for the step duration.
+    Stress,
+
+    /// The cross-node NUMA memory benchmark.
+    NumaBenchmark,
+
+    /// A check of the firmware versions against an expected-firmware manifest.
+    Firmware,
+
+    /// A command or Redfish operation run before, between or after the tests,
+    /// such as a firmware update, optionally followed by a reboot.
+    Hook,
+
+    /// The RAM test of `stress --memtest` for the step duration.
+    Memtest,
+
+    /// A power cycle of the machine through its BMC, once per pass.
+    PowerCycle,
+}
+
+/// A single test within a profile.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileStep {
+    /// A short name for the step, e.g. "cpu-soak".
+    pub name: String,
+
+    /// The test to run.
+    pub test: StepTest,
+
+    /// How long a stress step runs, e.g. "90s", "30m" or "24h".
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub duration: Option<Duration>,
+
+    /// The stress-ng stressor of a stress step, e.g. "cpu", "vm" or "hdd".
+    #[serde(default = "default_stressor")]
+    pub stressor: String,
+
+    /// The number of stress-ng workers; `0` starts one per online CPU.
+    #[serde(default)]
+    pub workers: u32,
+
+    /// Directories on the drives a disk stress step loads at once, one stress-ng run per
+    /// drive, e.g. `["/mnt/sdb", "/mnt/sdc"]`; the working directory when empty.
+    #[serde(default)]
+    pub devices: Vec<String>,
+
+    /// The load period when a stress step alternates load and idle as a soak test, e.g. "10m".
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub on: Option<Duration>,
+
+    /// The idle period after each load period of a soak test, e.g. "5m".
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub off: Option<Duration>,
+
+    /// The buffer placed on each node by a NUMA benchmark step, or tested by a memtest step,
+    /// in megabytes.
+    #[serde(default = "default_size_mb")]
+    pub size_mb: usize,
+
+    /// The expected-firmware manifest a firmware step checks against, e.g. "firmware-baseline.toml".
+    #[serde(default)]
+    pub manifest: Option<String>,
+
+    /// The command a hook step runs, e.g. `["fwupdmgr", "update", "-y"]`.
+    #[serde(default)]
+    pub command: Vec<String>,
+
+    /// The Redfish operation a hook step sends to the BMC instead, e.g.
+    /// "POST /redfish/v1/UpdateService/Actions/UpdateService.SimpleUpdate".
+    #[serde(default)]
+    pub redfish: Option<String>,
+
+    /// The JSON body of the Redfish operation, e.g. `'{"ImageURI": "http://lab/bios.bin"}'`.
+    #[serde(default)]
+    pub body: Option<String>,
+
+    /// The longest a hook step may run, e.g. "30m"; a command is not limited when unset.
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub timeout: Option<Duration>,
+
+    /// Reboots the machine after each pass of the step that succeeds; the run continues
+    /// after the reboot, with `oneforall resume --after-reboot`.
+    #[serde(default)]
+    pub reboot: bool,
+
+    /// How often the step runs, e.g. 3 passes of a RAM test or 50 power cycles; a
+    /// pass that does not pass ends the step.
+    #[serde(default = "default_passes")]
+    pub passes: u32,
+
+    /// The conditions the step must meet to pass.
+    #[serde(default)]
+    pub accept: AcceptanceCriteria,
+}
+
+impl ProfileStep {
+    /// Returns `true` if the step reboots or power cycles the machine after each pass.
+    pub fn restarts(&self) -> bool {
+        self.reboot || self.test == StepTest::PowerCycle
+    }
+
+    /// Returns what a hook step does, from its `command` or its `redfish` operation.
+    pub fn hook_action(&self) -> Result<HookAction, String> {
+        match (&self.redfish, self.command.is_empty()) {
+            (Some(operation), true) => RedfishOperation::parse(operation, self.body.as_deref())
+                .map(HookAction::Redfish)
+                .map_err(|e| format!("Hook step '{}': {}", self.name, e)),
+            (None, false) => Ok(HookAction::Command(self.command.clone())),
+            _ => Err(format!(
+                "Hook step '{}' needs either a command or a Redfish operation",
+                self.name
+            )),
+        }
+    }
+
+    /// Returns the duty cycle of a stress step run as a soak test, if `on` and `off` are set.
+    pub fn duty_cycle(&self) -> Option<DutyCycle> {
+        Some(DutyCycle {
+            on: self.on?,
+            off: self.off?,
+        })
+    }
+}
+
+/// Limits a step's measurements must stay within to pass.
+///
+/// Every limit is optional. A limit that cannot be checked because the
+/// machine does not expose the measurement is reported as not verified
+/// rather than failing the step.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AcceptanceCriteria {
+    /// The hottest temperature allowed during the step, in degrees Celsius.
+    pub max_temperature_celsius: Option<f64>,
+
+    /// The lowest acceptable stress-ng throughput, in bogo-ops per second.
+    pub min_bogo_ops_per_sec: Option<f64>,
+
+    /// The highest acceptable ratio of remote to local NUMA memory latency.
+    pub max_remote_penalty: Option<f64>,
+
+    /// The most new ECC errors, corrected or not, allowed during the step.
+    pub max_ecc_errors: Option<f64>,
+}
+
+/// The check of one measurement against one acceptance limit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CriterionCheck {
+    /// What was measured, e.g. "peak temperature (°C)".
+    pub label: &'static str,
+
+    /// The limit set by the criteria.
+    pub limit: f64,
+
+    /// The measurement, or `None` if the machine does not expose it.
+    pub value: Option<f64>,
+
+    /// Whether the limit is a maximum rather than a minimum.
+    pub upper: bool,
+}
+
+impl CriterionCheck {
+    /// Returns `true` if the measurement is beyond the limit.
+    pub fn missed(&self) -> bool {
+        self.value.is_some_and(|value| {
+            (self.upper && value > self.limit) || (!self.upper && value < self.limit)
+        })
+    }
+
+    /// Renders the check as one line of a step result, e.g.
+    /// "FAIL peak temperature (°C): 96.0 (limit 90.0)".
+    pub fn render(&self) -> String {
+        match self.value {
+            Some(value) if self.missed() => {
+                format!(
+                    "FAIL {}: {:.1} (limit {:.1})",
+                    self.label, value, self.limit
+                )
+            }
+            Some(value) => format!(
+                "ok   {}: {:.1} (limit {:.1})",
+                self.label, value, self.limit
+            ),
+            None => format!("n/a  {}: not measured on this machine", self.label),
+        }
+    }
+}
+
+impl AcceptanceCriteria {
+    /// Checks measurements against every limit that is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `metrics` - The measurements to check.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<CriterionCheck>` - One check per limit set, in a fixed order.
+    pub fn check(&self, metrics: &StepMetrics) -> Vec<CriterionCheck> {
+        [
+            (
+                "peak temperature (°C)",
+                self.max_temperature_celsius,
+                metrics.peak_celsius,
+                true,
+            ),
+            (
+                "bogo-ops/s",
+                self.min_bogo_ops_per_sec,
+                metrics.bogo_ops_per_sec,
+                false,
+            ),
+            (
+                "remote/local latency",
+                self.max_remote_penalty,
+                metrics.worst_remote_penalty,
+                true,
+            ),
+            (
+                "new ECC errors",
+                self.max_ecc_errors,
+                metrics.ecc_errors,
+                true,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(label, limit, value, upper)| {
+            limit.map(|limit| CriterionCheck {
+                label,
+                limit,
+                value,
+                upper,
+            })
+        })
+        .collect()
+    }
+}
+
+fn default_stressor() -> String {
+    "cpu".to_string()
+}
+
+fn default_size_mb() -> usize {
+    256
+}
+
+fn default_passes() -> u32 {
+    1
+}
+
+/// Deserializes durations written as a number followed by `s`, `m`, `h` or `d`.
+pub(crate) fn deserialize_duration<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let text = String::deserialize(deserializer)?;
+    parse_duration(&text)
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+/// Parses a duration such as "500ms", "90s", "30m", "24h" or "2d".
+///
+/// # Arguments
+///
+/// * `text` - The duration; a bare number is read as seconds.
+///
+/// # Returns
+///
+/// * `Result<Duration, String>` - The duration, or an error message.
+pub fn parse_duration(text: &str) -> Result<Duration, String> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (value, unit) = text.split_at(split);
+    let value: u64 = value
+        .parse()
+        .map_err(|_| format!("Invalid duration '{}'", text))?;
+    let seconds = match unit {
+        "ms" => return Ok(Duration::from_millis(value)),
+        "" | "s" => value,
+        "m" => value * 60,
+        "h" => value * 3600,
+        "d" => value * 86400,
+        _ => return Err(format!("Invalid duration unit in '{}'", text)),
+    };
+    Ok(Duration::from_secs(seconds))
+}
+
+/// Formats a duration in the largest unit that divides it, e.g. "24h".
+pub fn format_duration(duration: Duration) -> String {
+    if duration.subsec_millis() > 0 {
+        return format!("{}ms", duration.as_millis());
+    }
+    let seconds = duration.as_secs();
+    match seconds {
+        s if s > 0 && s % 86400 == 0 => format!("{}d", s / 86400),
+        s if s > 0 && s % 3600 == 0 => format!("{}h", s / 3600),
+        s if s > 0 && s % 60 == 0 => format!("{}m", s / 60),
+        s => format!("{}s", s),
+    }
+}
+
+impl Profile {
+    /// Parses and validates a profile written in TOML.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The TOML document.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Profile, String>` - The profile, or a description of the problem.
+    pub fn from_toml(text: &str) -> Result<Profile, String> {
+        let profile: Profile = toml::from_str(text).map_err(|e| e.to_string())?;
+        if profile.steps.is_empty() {
+            return Err(format!("Profile '{}' has no steps", profile.name));
+        }
+        for step in &profile.steps {
+            if step.test == StepTest::Stress && step.duration.is_none() {
+                return Err(format!("Stress step '{}' needs a duration", step.name));
+            }
+            if step.test == StepTest::Firmware && step.manifest.is_none() {
+                return Err(format!("Firmware step '{}' needs a manifest", step.name));
+            }
+            if step.test == StepTest::Memtest && step.duration.is_none() {
+                return Err(format!("Memtest step '{}' needs a duration", step.name));
+            }
+            if step.test == StepTest::Hook {
+                step.hook_action()?;
+            } else if !step.command.is_empty()
+                || step.redfish.is_some()
+                || step.body.is_some()
+                || step.timeout.is_some()
+            {
+                return Err(format!(
+                    "Only hook steps run commands or Redfish operations, not '{}'",
+                    step.name
+                ));
+            }
+            if step.passes == 0 {
+                return Err(format!("Step '{}' needs at least one pass", step.name));
+            }
+            if step.test == StepTest::PowerCycle && step.reboot {
+                return Err(format!(
+                    "Power cycle step '{}' restarts the machine already; remove `reboot`",
+                    step.name
+                ));
+            }
+            if step.on.is_some() != step.off.is_some() {
+                return Err(format!(
+                    "Step '{}' needs both `on` and `off` to alternate load and idle",
+                    step.name
+                ));
+            }
+            if !step.devices.is_empty() {
+                if step.test != StepTest::Stress
+                    || stressor_resource(&step.stressor) != Resource::Disk
+                {
+                    return Err(format!(
+                        "Only disk stress steps can load several devices, not '{}'",
+                        step.name
+                    ));
+                }
+                if step.on.is_some() {
+                    return Err(format!(
+                        "Step '{}' cannot both load several devices and alternate load and idle",
+                        step.name
+                    ));
+                }
+                for (index, device) in step.devices.iter().enumerate() {
+                    if step.devices[..index].contains(device) {
+                        return Err(format!(
+                            "Step '{}' lists the device {} twice",
+                            step.name, device
+                        ));
+                    }
+                }
+            }
+            if let (Some(on), Some(off)) = (step.on, step.off) {
+                if step.test != StepTest::Stress {
+                    return Err(format!(
+                        "Only stress steps can alternate load and idle, not '{}'",
+                        step.name
+                    ));
+                }
+                DutyCycle::new(on, off).map_err(|e| format!("Step '{}': {}", step.name, e))?;
+            }
+        }
+        Ok(profile)
+    }
+
+    /// Returns the combined duration of the timed steps, over all their passes.
+    pub fn planned_duration(&self) -> Duration {
+        self.steps
+            .iter()
+            .filter_map(|step| step.duration.map(|duration| duration * step.passes))
+            .sum()
+    }
+
+    /// Returns `true` if a step runs a stress-ng stressor.
+    pub fn runs_stress_ng(&self) -> bool {
+        self.steps.iter().any(|step| step.test == StepTest::Stress)
+    }
+
+    /// Returns `true` if a step reboots or power cycles the machine.
+    pub fn reboots(&self) -> bool {
+        self.steps.iter().any(ProfileStep::restarts)
+    }
+
+    /// Checks the stressor of every stress step against those the stress-ng binary offers.
+    ///
+    /// # Arguments
+    ///
+    /// * `catalog` - The stressors of the stress-ng binary of this machine.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), String>` - An error listing each step whose stressor does not exist.
+    pub fn check_stressors(&self, catalog: &StressorCatalog) -> Result<(), String> {
+        let errors: Vec<String> = self
+            .steps
+            .iter()
+            .filter(|step| step.test == StepTest::Stress)
+            .filter_map(|step| {
+                catalog
+                    .check_stressor(&step.stressor)
+                    .err()
+                    .map(|e| format!("Step '{}': {}", step.name, e))
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+}
+
+/// The outcome of a single profile step.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StepVerdict {
+    /// The step ran and met every acceptance criterion.
+    Passed,
+
+    /// The step ran but missed at least one acceptance criterion.
+    Failed,
+
+    /// The step could not be run.
+    Error,
+
+    /// The step was cancelled, or skipped after the profile was cancelled.
+    Cancelled,
+}
+
+/// The measurements taken during a step that acceptance criteria are checked against.
+#[derive(Debug, Clone)]
+pub struct StepMetrics {
+    /// The stress-ng throughput, in bogo-ops per second.
+    pub bogo_ops_per_sec: Option<f64>,
+
+    /// The hottest temperature seen during the step, in degrees Celsius.
+    pub peak_celsius: Option<f64>,
+
+    /// The largest remote/local NUMA latency ratio.
+    pub worst_remote_penalty: Option<f64>,
+
+    /// The new ECC errors, corrected or not, counted during the step.
+    pub ecc_errors: Option<f64>,
+
+    /// The serious kernel log events seen during the step, or why the log was not watched.
+    pub kernel_faults: Result<Vec<String>, String>,
+
+    /// The entries the BMC added to its event log during the step, or why it was not read.
+    pub sel_entries: Result<Vec<String>, String>,
+
+    /// The firmware versions checked against the expected-firmware manifest.
+    pub firmware: Vec<FirmwareCheck>,
+
+    /// What a hook step ran and how it ended; `None` for tests.
+    pub hook: Option<String>,
+
+    /// What the RAM test of a memtest step found; `None` for other steps.
+    pub memtest: Option<MemtestResult>,
+
+    /// The measurements of each drive of a stress step that loads several; empty otherwise.
+    pub devices: Vec<DeviceMetrics>,
+}
+
+/// The measurements of one drive of a stress step that loads several at once.
+#[derive(Debug, Clone)]
+pub struct DeviceMetrics {
+    /// The directory loaded, on the drive.
+    pub device: String,
+
+    /// The stress-ng throughput on the drive, in bogo-ops per second, or why its run failed.
+    pub bogo_ops_per_sec: Result<Option<f64>, String>,
+
+    /// How long the run on the drive took.
+    pub elapsed: Duration,
+}
+
+/// The result of one drive of a stress step that loads several at once.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceResult {
+    /// The directory loaded, on the drive.
+    pub device: String,
+
+    /// Whether the drive passed.
+    pub verdict: StepVerdict,
+
+    /// How long the run on the drive took, in seconds.
+    pub elapsed_secs: f64,
+
+    /// The throughput check, or why the run failed.
+    pub notes: Vec<String>,
+}
+
+impl DeviceResult {
+    /// Checks the throughput of one drive against the step's minimum.
+    ///
+    /// # Arguments
+    ///
+    /// * `criteria` - The acceptance criteria of the step.
+    /// * `metrics` - The measurements of the drive.
+    ///
+    /// # Returns
+    ///
+    /// * `DeviceResult` - A passed or failed result, or an error if the run on the drive failed.
+    pub fn evaluate(criteria: &AcceptanceCriteria, metrics: &DeviceMetrics) -> DeviceResult {
+        let (verdict, notes) = match &metrics.bogo_ops_per_sec {
+            Ok(rate) => {
+                let check = criteria.min_bogo_ops_per_sec.map(|limit| CriterionCheck {
+                    label: "bogo-ops/s",
+                    limit,
+                    value: *rate,
+                    upper: false,
+                });
+                let verdict = if check.as_ref().is_some_and(CriterionCheck::missed) {
+                    StepVerdict::Failed
+                } else {
+                    StepVerdict::Passed
+                };
+                (
+                    verdict,
+                    check.iter().map(CriterionCheck::render).collect::<Vec<_>>(),
+                )
+            }
+            Err(e) => (StepVerdict::Error, vec![e.clone()]),
+        };
+        DeviceResult {
+            device: metrics.device.clone(),
+            verdict,
+            elapsed_secs: metrics.elapsed.as_secs_f64(),
+            notes,
+        }
+    }
+}
+
+/// The result of a single profile step.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepResult {
+    /// The step name.
+    pub name: String,
+
+    /// Whether the step passed.
+    pub verdict: StepVerdict,
+
+    /// How long the step took, in seconds.
+    pub elapsed_secs: f64,
+
+    /// One line per criterion checked, failure or error.
+    pub notes: Vec<String>,
+
+    /// The result of each drive of a stress step that loads several.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub devices: Vec<DeviceResult>,
+}
+
+impl StepResult {
+    /// Builds the result of a step that ran, checking its acceptance criteria.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The step name.
+    /// * `criteria` - The acceptance criteria of the step.
+    /// * `metrics` - The measurements taken during the step.
+    /// * `elapsed` - How long the step took.
+    ///
+    /// # Returns
+    ///
+    /// * `StepResult` - A passed result, or a failed one listing the missed criteria.
+    pub fn evaluate(
+        name: &str,
+        criteria: &AcceptanceCriteria,
+        metrics: &StepMetrics,
+        elapsed: Duration,
+    ) -> StepResult {
+        // Throughput is judged per drive, so one slow drive does not drag the others down.
+        let devices: Vec<DeviceResult> = metrics
+            .devices
+            .iter()
+            .map(|device| DeviceResult::evaluate(criteria, device))
+            .collect();
+        let checks = if devices.is_empty() {
+            criteria.check(metrics)
+        } else {
+            AcceptanceCriteria {
+                min_bogo_ops_per_sec: None,
+                ..criteria.clone()
+            }
+            .check(metrics)
+        };
+        let mut failed = checks.iter().any(CriterionCheck::missed)
+            || devices
+                .iter()
+                .any(|device| device.verdict != StepVerdict::Passed);
+        let mut notes: Vec<String> = checks.iter().map(CriterionCheck::render).collect();
+        if let Some(hook) = &metrics.hook {
+            notes.push(format!("ok   hook: {}", hook));
+        }
+
+        // Any failing word fails a RAM test, as does a test that never completed a pass.
+        if let Some(memtest) = &metrics.memtest {
+            if memtest.passes == 0 {
+                failed = true;
+                notes.push("FAIL memory test: no pass completed".to_string());
+            } else if memtest.failures() > 0 {
+                failed = true;
+                notes.push(format!(
+                    "FAIL memory test: {} failing word(s) in {} pass(es)",
+                    memtest.failures(),
+                    memtest.passes
+                ));
+            } else {
+                notes.push(format!(
+                    "ok   memory test: {} pass(es), no failing word",
+                    memtest.passes
+                ));
+            }
+        }
+
+        // Firmware that drifted from the manifest fails the step.
+        for check in &metrics.firmware {
+            failed |= check.drifted();
+            notes.push(check.render());
+        }
+
+        // Serious kernel log events fail every step, whatever its criteria.
+        match &metrics.kernel_faults {
+            Ok(faults) if faults.is_empty() => {
+                notes.push("ok   kernel log: no serious events".to_string())
+            }
+            Ok(faults) => {
+                failed = true;
+                notes.push(format!(
+                    "FAIL kernel log: {} serious event(s)",
+                    faults.len()
+                ));
+                notes.extend(faults.iter().map(|fault| format!("       {}", fault)));
+            }
+            Err(reason) => notes.push(format!("n/a  kernel log: {}", reason)),
+        }
+
+        // So does any entry the BMC logged, such as a corrected memory error the OS never saw.
+        match &metrics.sel_entries {
+            Ok(entries) if entries.is_empty() => {
+                notes.push("ok   BMC event log: no new entries".to_string())
+            }
+            Ok(entries) => {
+                failed = true;
+                notes.push(format!("FAIL BMC event log: {} new entries", entries.len()));
+                notes.extend(entries.iter().map(|entry| format!("       {}", entry)));
+            }
+            Err(reason) => notes.push(format!("n/a  BMC event log: {}", reason)),
+        }
+
+        StepResult {
+            name: name.to_string(),
+            verdict: if failed {
+                StepVerdict::Failed
+            } else {
+                StepVerdict::Passed
+            },
+            elapsed_secs: elapsed.as_secs_f64(),
+            notes,
+            devices,
+        }
+    }
+
+    /// Combines the results of the passes of a step into the result of the step.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The step name.
+    /// * `passes` - The result of each pass run, in order; only the last may have
+    ///   missed, as a pass that does not pass ends the step.
+    ///
+    /// # Returns
+    ///
+    /// * `StepResult` - The result of the only pass, or one with the verdict of the last
+    ///   pass and the notes of every pass under its number.
+    pub fn combine(name: &str, passes: &[StepResult]) -> StepResult {
+        match passes {
+            [] => StepResult::aborted(name, StepVerdict::Error, "no pass ran", Duration::ZERO),
+            [pass] => pass.clone(),
+            _ => {
+                let mut notes = Vec::new();
+                for (index, pass) in passes.iter().enumerate() {
+                    notes.push(format!(
+                        "pass {}: {}",
+                        index + 1,
+                        format!("{:?}", pass.verdict).to_lowercase()
+                    ));
+                    notes.extend(pass.notes.iter().map(|note| format!("  {}", note)));
+                }
+                StepResult {
+                    name: name.to_string(),
+                    verdict: passes[passes.len() - 1].verdict.clone(),
+                    elapsed_secs: passes.iter().map(|pass| pass.elapsed_secs).sum(),
+                    notes,
+                    // The drives of earlier passes all passed.
+                    devices: passes[passes.len() - 1].devices.clone(),
+                }
+            }
+        }
+    }
+
+    /// Builds the result of a step that did not run to completion.
+    pub fn aborted(name: &str, verdict: StepVerdict, note: &str, elapsed: Duration) -> StepResult {
+        StepResult {
+            name: name.to_string(),
+            verdict,
+            elapsed_secs: elapsed.as_secs_f64(),
+            notes: vec![note.to_string()],
+            devices: Vec::new(),
+        }
+    }
+}
+
+/// The results of a whole profile run.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileReport {
+    /// The profile name.
+    pub profile: String,
+
+    /// The local time the run started, in RFC 3339 format.
+    pub started_at: String,
+
+    /// One result per step, in order.
+    pub steps: Vec<StepResult>,
+
+    /// Whether the run stopped for a step to reboot or power cycle the machine, to
+    /// continue once the machine is back.
+    #[serde(skip)]
+    pub awaiting_reboot: bool,
+}
+
+impl ProfileReport {
+    /// Returns `true` if every step passed.
+    pub fn passed(&self) -> bool {
+        self.steps
+            .iter()
+            .all(|step| step.verdict == StepVerdict::Passed)
+    }
+
+    /// Returns the names of the steps that failed or could not be run.
+    pub fn failed_steps(&self) -> Vec<&str> {
+        self.steps
+            .iter()
+            .filter(|step| matches!(step.verdict, StepVerdict::Failed | StepVerdict::Error))
+            .map(|step| step.name.as_str())
+            .collect()
+    }
+
+    /// Renders the report as plain text suitable for the terminal.
+    pub fn render(&self) -> String {
+        let mut output = format!("== Profile {} ==\n", self.profile);
+        for step in &self.steps {
+            let verdict = format!("{:?}", step.verdict).to_uppercase();
+            output.push_str(&format!(
+                "  {:<24} {:<10} {}\n",
+                step.name,
+                verdict,
+                format_duration(Duration::from_secs(step.elapsed_secs.round() as u64))
+            ));
+            for note in &step.notes {
+                output.push_str(&format!("      {}\n", note));
+            }
+            for device in &step.devices {
+                output.push_str(&format!(
+                    "    {:<22} {:<10} {}\n",
+                    device.device,
+                    format!("{:?}", device.verdict).to_uppercase(),
+                    format_duration(Duration::from_secs(device.elapsed_secs.round() as u64))
+                ));
+                for note in &device.notes {
+                    output.push_str(&format!("        {}\n", note));
+                }
+            }
+        }
+        output.push_str(&format!(
+            "Result: {}\n",
+            if self.awaiting_reboot {
+                "REBOOTING, the run continues after the reboot"
+            } else if self.passed() {
+                "PASS"
+            } else {
+                "FAIL"
+            }
+        ));
+        output
+    }
+}
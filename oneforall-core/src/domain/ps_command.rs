@@ -0,0 +1,345 @@
+//! PS (Process Status) Domain Entity
+//!
+//! This module provides a domain entity for the `ps` command, a tool for displaying
+//! current process statuses on Unix-based systems like Linux and macOS, and the
+//! process tree built from it, which attributes CPU and memory use to the
+//! service or container each process belongs to by its cgroup.
+
+use std::collections::{BTreeMap, HashMap};
+
+use common::domain::metric::Metric;
+use serde::{Deserialize, Serialize};
+
+/// Prefixes container runtimes give the cgroup scope of a container, before its ID.
+const CONTAINER_SCOPE_PREFIXES: [&str; 4] = ["docker-", "cri-containerd-", "crio-", "libpod-"];
+
+/// Number of characters a container ID is shortened to, as `docker ps` does.
+const SHORT_CONTAINER_ID: usize = 12;
+
+/// Represents the configuration for a `ps` command execution.
+/// This struct is used to configure and manage parameters for the `ps` command
+/// to monitor processes and their resource utilization.
+pub struct PsConfig {
+    /// The sorting criteria for processes, e.g., CPU usage or memory usage.
+    pub sort_by: String,
+
+    /// The maximum number of processes to display.
+    /// This value limits the output to the top N processes based on the sorting criteria.
+    pub limit: usize,
+
+    /// Include processes of all users, not just the current user.
+    pub all_users: bool,
+
+    /// Show full command line for each process instead of just the command name.
+    pub full_format: bool,
+
+    /// Custom options for the `ps` command.
+    /// This allows specifying additional flags or arguments to customize the `ps` output.
+    pub custom_options: HashMap<String, String>,
+}
+
+impl PsConfig {
+    /// Creates a new `ps` command configuration with specified parameters.
+    ///
+    /// This constructor initializes a `PsConfig` instance with detailed settings
+    /// for running the `ps` command. Each parameter can be customized to tailor the process
+    /// monitoring to specific requirements or interests.
+    ///
+    /// # Arguments
+    ///
+    /// * `sort_by` - Criteria for sorting processes.
+    /// * `limit` - Maximum number of processes to display.
+    /// * `all_users` - Whether to include processes from all users.
+    /// * `full_format` - Show full command line for each process.
+    /// * `custom_options` - Additional custom options for `ps`.
+    ///
+    /// # Returns
+    ///
+    /// * `PsConfig` - A new instance with the provided configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use oneforall_core::domain::ps_command::PsConfig;
+    ///
+    /// let config = PsConfig::new("cpu".to_string(), 10, true, false, HashMap::new());
+    /// // Use `config` to run and parse `ps` command
+    /// ```
+    pub fn new(
+        sort_by: String,
+        limit: usize,
+        all_users: bool,
+        full_format: bool,
+        custom_options: HashMap<String, String>,
+    ) -> Self {
+        Self {
+            sort_by,
+            limit,
+            all_users,
+            full_format,
+            custom_options,
+        }
+    }
+
+    // Additional methods to execute and parse the `ps` command can be added here...
+}
+
+/// A single process as listed by `ps`.
+#[derive(Debug, Clone)]
+pub struct ProcessInfo {
+    /// The process ID.
+    pub pid: u32,
+
+    /// The ID of the parent process.
+    pub ppid: u32,
+
+    /// The CPU usage in percent of one CPU.
+    pub cpu_percent: f64,
+
+    /// The share of physical memory used, in percent.
+    pub memory_percent: f64,
+
+    /// The cgroup path, e.g. "/system.slice/nginx.service"; empty if unknown.
+    pub cgroup: String,
+
+    /// The command name.
+    pub command: String,
+}
+
+/// Formats the busiest processes as a table, as `ps` sorted by CPU usage would list them.
+///
+/// # Arguments
+///
+/// * `processes` - The processes, the busiest first.
+/// * `limit` - The maximum number of processes listed.
+///
+/// # Returns
+///
+/// * `String` - A header followed by one row per process.
+pub fn format_top_processes(processes: &[ProcessInfo], limit: usize) -> String {
+    let mut output = format!(
+        "{:>8} {:>8} {:>6} {:>6} {}\n",
+        "PID", "PPID", "%CPU", "%MEM", "COMMAND"
+    );
+    for process in processes.iter().take(limit) {
+        output.push_str(&format!(
+            "{:>8} {:>8} {:>6.1} {:>6.1} {}\n",
+            process.pid, process.ppid, process.cpu_percent, process.memory_percent, process.command
+        ));
+    }
+    output
+}
+
+/// The CPU and memory use of every process in one cgroup.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CgroupUsage {
+    /// The cgroup path, e.g. "/system.slice/nginx.service".
+    pub cgroup: String,
+
+    /// The service or container the cgroup belongs to, e.g. "nginx.service".
+    pub name: String,
+
+    /// The number of processes in the cgroup.
+    pub processes: usize,
+
+    /// The CPU usage of the processes together, in percent of one CPU.
+    pub cpu_percent: f64,
+
+    /// The share of physical memory the processes use together, in percent.
+    pub memory_percent: f64,
+
+    /// The ID of the process that started the others, the busiest one whose parent is outside the cgroup.
+    pub leader_pid: u32,
+
+    /// The command name of the leading process.
+    pub leader_command: String,
+}
+
+impl CgroupUsage {
+    /// Returns the usage as metrics labelled with the cgroup and the workload it belongs to.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp` - The local time the usage was read, in RFC 3339 format.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Metric>` - The `cgroup_cpu_percent`, `cgroup_memory_percent` and
+    ///   `cgroup_processes` metrics of the cgroup.
+    pub fn metrics(&self, timestamp: &str) -> Vec<Metric> {
+        [
+            ("cgroup_cpu_percent", "percent", self.cpu_percent),
+            ("cgroup_memory_percent", "percent", self.memory_percent),
+            ("cgroup_processes", "processes", self.processes as f64),
+        ]
+        .into_iter()
+        .map(|(name, unit, value)| {
+            Metric::new(name, unit, value, timestamp)
+                .with_label("cgroup", &self.cgroup)
+                .with_label("workload", &self.name)
+        })
+        .collect()
+    }
+}
+
+/// Names the service or container a cgroup path belongs to.
+///
+/// # Arguments
+///
+/// * `cgroup` - The cgroup path, e.g. "/system.slice/docker-4f3c...e1.scope".
+///
+/// # Returns
+///
+/// * `String` - The systemd unit, e.g. "nginx.service", "container 4f3c8a9b21d0"
+///   for a container scope, or "/" for the root cgroup.
+pub fn workload_name(cgroup: &str) -> String {
+    let leaf = cgroup.rsplit('/').find(|part| !part.is_empty());
+    let Some(leaf) = leaf else {
+        return "/".to_string();
+    };
+    let id = CONTAINER_SCOPE_PREFIXES
+        .iter()
+        .find_map(|prefix| leaf.strip_prefix(prefix))
+        .unwrap_or(leaf)
+        .trim_end_matches(".scope");
+    // Kubernetes names the cgroups of containers by their bare 64-digit ID.
+    if id.len() == 64 && id.chars().all(|c| c.is_ascii_hexdigit()) {
+        format!("container {}", &id[..SHORT_CONTAINER_ID])
+    } else {
+        leaf.to_string()
+    }
+}
+
+/// The processes of the machine, linked to their parents.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessTree {
+    processes: BTreeMap<u32, ProcessInfo>,
+    children: BTreeMap<u32, Vec<u32>>,
+}
+
+impl ProcessTree {
+    /// Builds the tree from a list of processes.
+    ///
+    /// Processes whose cgroup could not be read, e.g. because they exited in
+    /// between or on systems without cgroups, are placed in the cgroup of
+    /// their closest ancestor that has one.
+    ///
+    /// # Arguments
+    ///
+    /// * `processes` - Every process, in any order.
+    ///
+    /// # Returns
+    ///
+    /// * `ProcessTree` - The processes keyed by ID with their children.
+    pub fn new(processes: Vec<ProcessInfo>) -> Self {
+        let mut tree = ProcessTree::default();
+        for process in processes {
+            if process.ppid != process.pid {
+                tree.children
+                    .entry(process.ppid)
+                    .or_default()
+                    .push(process.pid);
+            }
+            tree.processes.insert(process.pid, process);
+        }
+
+        let inherited: Vec<(u32, String)> = tree
+            .processes
+            .values()
+            .filter(|process| process.cgroup.is_empty())
+            .filter_map(|process| Some((process.pid, tree.ancestor_cgroup(process.ppid)?)))
+            .collect();
+        for (pid, cgroup) in inherited {
+            if let Some(process) = tree.processes.get_mut(&pid) {
+                process.cgroup = cgroup;
+            }
+        }
+        tree
+    }
+
+    /// Returns the cgroup of the closest ancestor starting at `pid` that has one.
+    fn ancestor_cgroup(&self, mut pid: u32) -> Option<String> {
+        // Bounded by the number of processes, in case a listing taken while
+        // processes exit and PIDs are reused contains a cycle.
+        for _ in 0..self.processes.len() {
+            let process = self.processes.get(&pid)?;
+            if !process.cgroup.is_empty() {
+                return Some(process.cgroup.clone());
+            }
+            pid = process.ppid;
+        }
+        None
+    }
+
+    /// Returns the CPU usage of a process and all its descendants, in percent of one CPU.
+    ///
+    /// # Arguments
+    ///
+    /// * `pid` - The ID of the process at the top of the subtree.
+    ///
+    /// # Returns
+    ///
+    /// * `f64` - The summed usage; zero for an unknown process.
+    pub fn subtree_cpu_percent(&self, pid: u32) -> f64 {
+        let mut total = 0.0;
+        let mut pending = vec![pid];
+        let mut visited = 0;
+        while let Some(pid) = pending.pop() {
+            visited += 1;
+            if visited > self.processes.len() {
+                break;
+            }
+            if let Some(process) = self.processes.get(&pid) {
+                total += process.cpu_percent;
+            }
+            if let Some(children) = self.children.get(&pid) {
+                pending.extend(children);
+            }
+        }
+        total
+    }
+
+    /// Sums the CPU and memory use of the processes of every cgroup.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<CgroupUsage>` - One entry per cgroup, the busiest first.
+    pub fn cgroup_usage(&self) -> Vec<CgroupUsage> {
+        let mut groups: BTreeMap<&str, Vec<&ProcessInfo>> = BTreeMap::new();
+        for process in self.processes.values() {
+            groups.entry(&process.cgroup).or_default().push(process);
+        }
+
+        let mut usage: Vec<CgroupUsage> = groups
+            .into_iter()
+            .map(|(cgroup, members)| {
+                // The leader is the busiest process whose parent lives in another cgroup.
+                let leader = members
+                    .iter()
+                    .filter(|process| {
+                        self.processes
+                            .get(&process.ppid)
+                            .is_none_or(|parent| parent.cgroup != cgroup)
+                    })
+                    .max_by(|a, b| {
+                        self.subtree_cpu_percent(a.pid)
+                            .total_cmp(&self.subtree_cpu_percent(b.pid))
+                    })
+                    .or(members.first());
+                CgroupUsage {
+                    cgroup: cgroup.to_string(),
+                    name: workload_name(cgroup),
+                    processes: members.len(),
+                    cpu_percent: members.iter().map(|p| p.cpu_percent).sum(),
+                    memory_percent: members.iter().map(|p| p.memory_percent).sum(),
+                    leader_pid: leader.map(|p| p.pid).unwrap_or_default(),
+                    leader_command: leader.map(|p| p.command.clone()).unwrap_or_default(),
+                }
+            })
+            .collect();
+        usage.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent));
+        usage
+    }
+}
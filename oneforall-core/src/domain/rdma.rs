@@ -0,0 +1,267 @@
+//! RDMA Domain Entity
+//!
+//! This module provides the RDMA devices of a machine, InfiniBand and RoCE host
+//! channel adapters as reported by the Linux RDMA subsystem, and the results of
+//! benchmarking them with perftest. HPC and AI clusters move their traffic over
+//! RDMA rather than TCP, so a fabric is only accepted once each port is up at
+//! its expected rate and its RDMA bandwidth and latency have been measured.
+
+use crate::domain::discovery::DiscoverySection;
+use crate::domain::statistics::Measurement;
+
+/// A port of an RDMA device.
+#[derive(Debug, Clone)]
+pub struct RdmaDevicePort {
+    /// The port number, starting at 1.
+    pub number: u32,
+
+    /// The logical state, e.g. "ACTIVE" or "DOWN".
+    pub state: String,
+
+    /// The physical state, e.g. "LinkUp" or "Polling".
+    pub phys_state: String,
+
+    /// The rate as reported, e.g. "200 Gb/sec (4X HDR)".
+    pub rate: String,
+
+    /// The link layer, "InfiniBand" or "Ethernet" for RoCE.
+    pub link_layer: String,
+}
+
+impl RdmaDevicePort {
+    /// Returns `true` if the port is up and can carry traffic.
+    pub fn is_active(&self) -> bool {
+        self.state == "ACTIVE" && self.phys_state == "LinkUp"
+    }
+
+    /// Returns the rate of the port in Gbit/s, e.g. 200 for "200 Gb/sec (4X HDR)".
+    pub fn rate_gbit_s(&self) -> Option<f64> {
+        self.rate
+            .split_whitespace()
+            .next()?
+            .parse()
+            .ok()
+            .filter(|rate| *rate > 0.0)
+    }
+}
+
+/// An RDMA device, such as an InfiniBand HCA or a RoCE NIC.
+#[derive(Debug, Clone)]
+pub struct RdmaDevice {
+    /// The device name, e.g. "mlx5_0".
+    pub name: String,
+
+    /// The hardware type, e.g. "MT4123".
+    pub hca_type: String,
+
+    /// The board ID, which identifies the exact card model.
+    pub board_id: String,
+
+    /// The firmware version.
+    pub firmware: String,
+
+    /// The node GUID.
+    pub node_guid: String,
+
+    /// The ports of the device.
+    pub ports: Vec<RdmaDevicePort>,
+}
+
+/// Strips the numeric prefix the kernel writes before port states, e.g. "4: ACTIVE".
+pub fn port_state_name(text: &str) -> String {
+    text.split_once(':')
+        .map_or(text, |(_, name)| name)
+        .trim()
+        .to_string()
+}
+
+/// Builds the discovery section describing the RDMA devices of the machine.
+///
+/// # Arguments
+///
+/// * `devices` - The RDMA devices discovered on the machine.
+///
+/// # Returns
+///
+/// * `DiscoverySection` - One entry per device and per port, flagging ports that are not up.
+pub fn rdma_section(devices: &[RdmaDevice]) -> DiscoverySection {
+    let mut section = DiscoverySection::new("RDMA Devices");
+    section.push("Devices", devices.len());
+
+    for device in devices {
+        section.push(
+            &device.name,
+            format!(
+                "{} | board {} | firmware {} | GUID {}",
+                device.hca_type, device.board_id, device.firmware, device.node_guid
+            ),
+        );
+        for port in &device.ports {
+            let mut value = format!(
+                "{}, {} | {} | {}",
+                port.state, port.phys_state, port.rate, port.link_layer
+            );
+            if !port.is_active() {
+                value.push_str(" | NOT ACTIVE");
+            }
+            section.push(&format!("{} port {}", device.name, port.number), value);
+        }
+    }
+
+    section
+}
+
+/// The bandwidth measured by a perftest bandwidth test such as ib_write_bw.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RdmaBandwidth {
+    /// The size of each message, in bytes.
+    pub message_bytes: u64,
+
+    /// The peak bandwidth, in Gbit/s.
+    pub peak_gbit_s: f64,
+
+    /// The average bandwidth, in Gbit/s.
+    pub average_gbit_s: f64,
+
+    /// The message rate, in millions of messages per second.
+    pub message_rate_mpps: f64,
+}
+
+/// The latency measured by a perftest latency test such as ib_read_lat.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RdmaLatency {
+    /// The size of each message, in bytes.
+    pub message_bytes: u64,
+
+    /// The lowest latency, in microseconds.
+    pub min_us: f64,
+
+    /// The typical (median) latency, in microseconds.
+    pub typical_us: f64,
+
+    /// The average latency, in microseconds.
+    pub average_us: f64,
+
+    /// The 99th percentile latency, in microseconds.
+    pub p99_us: f64,
+
+    /// The highest latency, in microseconds.
+    pub max_us: f64,
+}
+
+/// Returns the numeric fields of the result row following perftest's "#bytes" header.
+fn perftest_row(output: &str) -> Option<Vec<f64>> {
+    output
+        .lines()
+        .skip_while(|line| !line.trim_start().starts_with("#bytes"))
+        .skip(1)
+        .map(|line| {
+            line.split_whitespace()
+                .map(|field| field.parse::<f64>())
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .find_map(|fields| fields.ok().filter(|fields| !fields.is_empty()))
+}
+
+/// Parses the output of a perftest bandwidth test run with `--report_gbits`.
+///
+/// The result row reads "#bytes #iterations BW peak BW average MsgRate".
+///
+/// # Returns
+///
+/// * `Option<RdmaBandwidth>` - The bandwidth, or `None` if the output has no result row.
+pub fn parse_perftest_bandwidth(output: &str) -> Option<RdmaBandwidth> {
+    let row = perftest_row(output)?;
+    Some(RdmaBandwidth {
+        message_bytes: *row.first()? as u64,
+        peak_gbit_s: *row.get(2)?,
+        average_gbit_s: *row.get(3)?,
+        message_rate_mpps: *row.get(4)?,
+    })
+}
+
+/// Parses the output of a perftest latency test.
+///
+/// The result row reads "#bytes #iterations t_min t_max t_typical t_avg t_stdev
+/// 99% percentile 99.9% percentile".
+///
+/// # Returns
+///
+/// * `Option<RdmaLatency>` - The latency, or `None` if the output has no result row.
+pub fn parse_perftest_latency(output: &str) -> Option<RdmaLatency> {
+    let row = perftest_row(output)?;
+    Some(RdmaLatency {
+        message_bytes: *row.first()? as u64,
+        min_us: *row.get(2)?,
+        max_us: *row.get(3)?,
+        typical_us: *row.get(4)?,
+        average_us: *row.get(5)?,
+        p99_us: *row.get(7)?,
+    })
+}
+
+/// The results of benchmarking an RDMA device.
+#[derive(Debug, Clone)]
+pub struct RdmaBenchmarkResult {
+    /// The device benchmarked, e.g. "mlx5_0".
+    pub device: String,
+
+    /// The machine measured to, or `None` for a loopback through the device.
+    pub peer: Option<String>,
+
+    /// The rate of the port used, in Gbit/s, if known.
+    pub link_gbit_s: Option<f64>,
+
+    /// The RDMA write bandwidth.
+    pub bandwidth: RdmaBandwidth,
+
+    /// The RDMA read latency.
+    pub latency: RdmaLatency,
+}
+
+/// Lists the metrics of an RDMA benchmark run, for statistics over repeated runs.
+pub fn rdma_measurements(result: &RdmaBenchmarkResult) -> Vec<Measurement> {
+    vec![
+        Measurement::new("write bandwidth", "Gbit/s", result.bandwidth.average_gbit_s),
+        Measurement::new("message rate", "Mpps", result.bandwidth.message_rate_mpps),
+        Measurement::new("read latency typical", "us", result.latency.typical_us),
+        Measurement::new("read latency p99", "us", result.latency.p99_us),
+    ]
+}
+
+/// Formats the results of the RDMA benchmark.
+pub fn format_rdma_results(result: &RdmaBenchmarkResult) -> String {
+    let mut output = format!(
+        "RDMA benchmark of {} {}\n",
+        result.device,
+        result
+            .peer
+            .as_ref()
+            .map_or("in loopback".to_string(), |peer| format!("to {}", peer))
+    );
+    output.push_str(&format!(
+        "Write bandwidth ({} B messages): {:.2} Gbit/s average, {:.2} Gbit/s peak, {:.2} Mpps",
+        result.bandwidth.message_bytes,
+        result.bandwidth.average_gbit_s,
+        result.bandwidth.peak_gbit_s,
+        result.bandwidth.message_rate_mpps
+    ));
+    if let Some(link) = result.link_gbit_s {
+        output.push_str(&format!(
+            " ({:.0}% of the {:.0} Gbit/s link)",
+            result.bandwidth.average_gbit_s / link * 100.0,
+            link
+        ));
+    }
+    output.push('\n');
+    output.push_str(&format!(
+        "Read latency ({} B messages): typical {:.2} us, average {:.2} us, min {:.2} us, p99 {:.2} us, max {:.2} us\n",
+        result.latency.message_bytes,
+        result.latency.typical_us,
+        result.latency.average_us,
+        result.latency.min_us,
+        result.latency.p99_us,
+        result.latency.max_us
+    ));
+    output
+}
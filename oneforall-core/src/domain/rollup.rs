@@ -0,0 +1,154 @@
+//! Rollup Domain Entity
+//!
+//! This module provides the rollups of the metrics of the history API: the
+//! readings of every minute and of every hour reduced to their count, lowest,
+//! highest, average and 99th percentile, and stored next to the readings they
+//! stand for. A history of a month then reads some 720 hourly rollups rather
+//! than millions of readings taken a second apart, and minute and hour
+//! rollups outlive the readings deleted by the retention policy, so months of
+//! monitoring remain queryable.
+
+use std::time::Duration;
+
+use chrono::{DateTime, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::statistics::{mean, quantile};
+
+/// How long minute rollups are kept when no age is configured; hour rollups
+/// are never deleted.
+pub const DEFAULT_ROLLUP_AGE: Duration = Duration::from_secs(90 * 24 * 3600);
+
+/// The percentile each rollup keeps besides the extremes.
+pub const ROLLUP_PERCENTILE: f64 = 99.0;
+
+/// The time a rollup covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// A rollup per minute.
+    Minute,
+
+    /// A rollup per hour.
+    Hour,
+}
+
+impl Resolution {
+    /// Every resolution, finest first.
+    pub const ALL: [Resolution; 2] = [Resolution::Minute, Resolution::Hour];
+
+    /// Returns the name of the resolution in histories, e.g. "1m".
+    pub fn name(&self) -> &'static str {
+        match self {
+            Resolution::Minute => "1m",
+            Resolution::Hour => "1h",
+        }
+    }
+
+    /// Returns the time a rollup covers.
+    pub fn window(&self) -> Duration {
+        match self {
+            Resolution::Minute => Duration::from_secs(60),
+            Resolution::Hour => Duration::from_secs(3600),
+        }
+    }
+
+    /// Returns the collector the rollups of a metric are stored under, e.g.
+    /// "rollup_1m_cpu".
+    pub fn collector(&self, metric: &str) -> String {
+        format!("rollup_{}_{}", self.name(), metric)
+    }
+
+    /// Picks the coarsest resolution whose rollups are no further apart than
+    /// the points of a history, so a chart loses no detail it could draw.
+    ///
+    /// # Arguments
+    ///
+    /// * `spacing` - The time between the points of the history.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Resolution>` - The resolution, or `None` if the readings themselves are needed.
+    pub fn for_spacing(spacing: Duration) -> Option<Resolution> {
+        Resolution::ALL
+            .into_iter()
+            .rev()
+            .find(|resolution| resolution.window() <= spacing)
+    }
+}
+
+/// The readings of one window reduced to their distribution.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rollup {
+    /// How many readings the rollup stands for.
+    pub count: usize,
+
+    /// The lowest of the readings.
+    pub min: f64,
+
+    /// The highest of the readings.
+    pub max: f64,
+
+    /// The average of the readings.
+    pub avg: f64,
+
+    /// The value `ROLLUP_PERCENTILE` percent of the readings are at or below.
+    pub p99: f64,
+}
+
+impl Rollup {
+    /// Reduces readings to their distribution.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The values of the readings, in any order.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Rollup>` - The rollup, or `None` if there are no readings.
+    pub fn from_values(values: &[f64]) -> Option<Rollup> {
+        if values.is_empty() {
+            return None;
+        }
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        Some(Rollup {
+            count: sorted.len(),
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+            avg: mean(&sorted)?,
+            p99: quantile(&sorted, ROLLUP_PERCENTILE / 100.0),
+        })
+    }
+}
+
+/// Rolls readings up to one rollup per window.
+///
+/// # Arguments
+///
+/// * `values` - The readings with the time each was taken at, oldest first.
+/// * `resolution` - The time each rollup covers.
+///
+/// # Returns
+///
+/// * `Vec<(DateTime<Utc>, Rollup)>` - A rollup per window holding readings, with the
+///   time the window starts at, oldest first.
+pub fn roll_up(
+    values: &[(DateTime<Utc>, f64)],
+    resolution: Resolution,
+) -> Vec<(DateTime<Utc>, Rollup)> {
+    let Ok(window) = chrono::Duration::from_std(resolution.window()) else {
+        return Vec::new();
+    };
+    let mut windows: Vec<(DateTime<Utc>, Vec<f64>)> = Vec::new();
+    for (taken, value) in values {
+        let start = taken.duration_trunc(window).unwrap_or(*taken);
+        match windows.last_mut() {
+            Some((last, values)) if *last == start => values.push(*value),
+            _ => windows.push((start, vec![*value])),
+        }
+    }
+    windows
+        .into_iter()
+        .filter_map(|(start, values)| Rollup::from_values(&values).map(|rollup| (start, rollup)))
+        .collect()
+}
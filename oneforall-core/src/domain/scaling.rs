@@ -0,0 +1,369 @@
+//! CPU Scaling Domain Entity
+//!
+//! This module provides the CPU scaling benchmark, which runs the same compute
+//! workload on 1, 2, 4 ... N threads and reports how its throughput scales.
+//! Threads are added compactly: one per physical core of the first socket,
+//! then of the next socket, and only then on SMT siblings, so the curve shows
+//! each boundary a growing workload crosses. Efficiency that drops where the
+//! threads start spanning another socket points at the socket interconnect,
+//! SMT siblings that add no throughput point at broken or misconfigured SMT,
+//! and a socket slower than the others on its own points at its memory,
+//! cooling or power delivery.
+
+use std::collections::BTreeSet;
+
+use crate::domain::cpu_topology::CpuTopology;
+use crate::domain::statistics::Measurement;
+
+/// Drop in parallel efficiency, in percentage points, flagged where the threads start spanning another socket.
+const SOCKET_EFFICIENCY_DROP: f64 = 15.0;
+
+/// Throughput SMT siblings are expected to add to that of the physical cores alone.
+const SMT_MIN_GAIN: f64 = 0.10;
+
+/// Share of the fastest socket's throughput below which a socket is flagged.
+const SOCKET_IMBALANCE_RATIO: f64 = 0.90;
+
+/// One thread count the benchmark runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScalingStep {
+    /// The logical CPUs a thread is pinned to, one thread each.
+    pub cpus: Vec<u32>,
+
+    /// The sockets the threads span.
+    pub sockets: usize,
+
+    /// Whether some threads share a physical core with another.
+    pub smt: bool,
+}
+
+/// Orders the logical CPUs the way the benchmark adds threads: the physical cores
+/// of each socket in turn, then their SMT siblings.
+///
+/// # Arguments
+///
+/// * `topology` - The CPU topology of the machine.
+///
+/// # Returns
+///
+/// * `Vec<u32>` - Every online logical CPU, in the order threads are added.
+pub fn scaling_order(topology: &CpuTopology) -> Vec<u32> {
+    let physical = topology.physical_cpu_ids();
+    let mut cpus: Vec<_> = topology.cpus.iter().collect();
+    cpus.sort_by_key(|cpu| (!physical.contains(&cpu.id), cpu.package_id, cpu.id));
+    cpus.into_iter().map(|cpu| cpu.id).collect()
+}
+
+/// Plans the thread counts of the benchmark: the powers of two, the physical
+/// cores of each socket in turn, every physical core, and the largest count.
+///
+/// # Arguments
+///
+/// * `topology` - The CPU topology of the machine.
+/// * `max_threads` - The most threads to run; every online CPU when `None`.
+///
+/// # Returns
+///
+/// * `Result<Vec<ScalingStep>, String>` - The steps, fewest threads first, or why
+///   there is no CPU to run on.
+pub fn plan_scaling(
+    topology: &CpuTopology,
+    max_threads: Option<usize>,
+) -> Result<Vec<ScalingStep>, String> {
+    let order = scaling_order(topology);
+    if order.is_empty() {
+        return Err("No online CPU to run the scaling benchmark on".to_string());
+    }
+    let max = max_threads.unwrap_or(order.len()).clamp(1, order.len());
+    let physical = topology.physical_cpu_ids();
+    let package_of = |id: u32| {
+        topology
+            .cpus
+            .iter()
+            .find(|cpu| cpu.id == id)
+            .map(|cpu| cpu.package_id)
+    };
+
+    let mut counts = BTreeSet::from([max, physical.len().min(max)]);
+    let mut threads = 1;
+    while threads < max {
+        counts.insert(threads);
+        threads *= 2;
+    }
+    let mut cores = 0;
+    for package in socket_ids(topology) {
+        cores += topology
+            .cpus
+            .iter()
+            .filter(|cpu| cpu.package_id == package && physical.contains(&cpu.id))
+            .count();
+        if cores <= max {
+            counts.insert(cores);
+        }
+    }
+
+    Ok(counts
+        .into_iter()
+        .filter(|threads| *threads > 0)
+        .map(|threads| {
+            let cpus = order[..threads].to_vec();
+            ScalingStep {
+                sockets: cpus
+                    .iter()
+                    .filter_map(|id| package_of(*id))
+                    .collect::<BTreeSet<_>>()
+                    .len(),
+                smt: cpus.iter().any(|id| !physical.contains(id)),
+                cpus,
+            }
+        })
+        .collect())
+}
+
+/// Returns the sockets of the machine, in order.
+fn socket_ids(topology: &CpuTopology) -> BTreeSet<u32> {
+    topology.cpus.iter().map(|cpu| cpu.package_id).collect()
+}
+
+/// Plans the runs of each socket on its own, one thread per physical core; none
+/// on single-socket machines.
+///
+/// # Arguments
+///
+/// * `topology` - The CPU topology of the machine.
+///
+/// # Returns
+///
+/// * `Vec<(u32, Vec<u32>)>` - Each socket with the logical CPUs its threads are pinned to.
+pub fn plan_socket_runs(topology: &CpuTopology) -> Vec<(u32, Vec<u32>)> {
+    let sockets = socket_ids(topology);
+    if sockets.len() < 2 {
+        return Vec::new();
+    }
+    let physical = topology.physical_cpu_ids();
+    sockets
+        .into_iter()
+        .map(|package| {
+            let cpus = topology
+                .cpus
+                .iter()
+                .filter(|cpu| cpu.package_id == package && physical.contains(&cpu.id))
+                .map(|cpu| cpu.id)
+                .collect();
+            (package, cpus)
+        })
+        .collect()
+}
+
+/// The throughput measured at one thread count.
+#[derive(Debug, Clone)]
+pub struct ScalingPoint {
+    /// The threads run.
+    pub threads: usize,
+
+    /// The sockets the threads spanned.
+    pub sockets: usize,
+
+    /// Whether some threads shared a physical core with another.
+    pub smt: bool,
+
+    /// The workload iterations completed per second by every thread together.
+    pub ops_per_sec: f64,
+}
+
+/// The throughput of one socket on its own.
+#[derive(Debug, Clone)]
+pub struct SocketThroughput {
+    /// The socket (package) number.
+    pub socket: u32,
+
+    /// The physical cores of the socket, one thread each.
+    pub cores: usize,
+
+    /// The workload iterations completed per second by the threads of the socket.
+    pub ops_per_sec: f64,
+}
+
+/// The results of the CPU scaling benchmark.
+#[derive(Debug, Clone, Default)]
+pub struct ScalingResult {
+    /// The throughput at each thread count, fewest threads first.
+    pub points: Vec<ScalingPoint>,
+
+    /// The throughput of each socket on its own; empty on single-socket machines.
+    pub sockets: Vec<SocketThroughput>,
+}
+
+impl ScalingResult {
+    /// Returns the throughput of a single thread.
+    fn single_thread(&self) -> Option<f64> {
+        self.points
+            .iter()
+            .find(|point| point.threads == 1)
+            .map(|point| point.ops_per_sec)
+            .filter(|ops| *ops > 0.0)
+    }
+
+    /// Returns the speedup of a point over a single thread.
+    pub fn speedup(&self, point: &ScalingPoint) -> Option<f64> {
+        self.single_thread()
+            .map(|single| point.ops_per_sec / single)
+    }
+
+    /// Returns the parallel efficiency of a point, its speedup per thread, in percent.
+    pub fn efficiency(&self, point: &ScalingPoint) -> Option<f64> {
+        self.speedup(point)
+            .map(|speedup| speedup / point.threads as f64 * 100.0)
+    }
+
+    /// Lists the bottlenecks the curve shows, one sentence each.
+    pub fn findings(&self) -> Vec<String> {
+        let mut findings = Vec::new();
+
+        // Compare the last point within fewer sockets with the first spanning more.
+        for pair in self.points.windows(2) {
+            let (before, after) = (&pair[0], &pair[1]);
+            if after.sockets <= before.sockets || after.smt {
+                continue;
+            }
+            if let (Some(from), Some(to)) = (self.efficiency(before), self.efficiency(after)) {
+                if from - to > SOCKET_EFFICIENCY_DROP {
+                    findings.push(format!(
+                        "Efficiency drops from {:.0}% at {} threads to {:.0}% at {} threads, where \
+                         the threads span {} sockets: the socket interconnect is a bottleneck",
+                        from, before.threads, to, after.threads, after.sockets
+                    ));
+                }
+            }
+        }
+
+        let cores = self.points.iter().rfind(|point| !point.smt);
+        let siblings = self.points.iter().rfind(|point| point.smt);
+        if let (Some(cores), Some(siblings)) = (cores, siblings) {
+            let gain = siblings.ops_per_sec / cores.ops_per_sec - 1.0;
+            if gain < 0.0 {
+                findings.push(format!(
+                    "SMT siblings lower the throughput by {:.0}% ({} threads against {} cores): \
+                     SMT looks broken",
+                    -gain * 100.0,
+                    siblings.threads,
+                    cores.threads
+                ));
+            } else if gain < SMT_MIN_GAIN {
+                findings.push(format!(
+                    "SMT siblings add only {:.0}% to the throughput of {} cores",
+                    gain * 100.0,
+                    cores.threads
+                ));
+            }
+        }
+
+        let fastest = self
+            .sockets
+            .iter()
+            .max_by(|a, b| a.ops_per_sec.total_cmp(&b.ops_per_sec));
+        if let Some(fastest) = fastest {
+            for socket in &self.sockets {
+                let ratio = socket.ops_per_sec / fastest.ops_per_sec;
+                if ratio < SOCKET_IMBALANCE_RATIO {
+                    findings.push(format!(
+                        "Socket {} reaches only {:.0}% of the throughput of socket {} on its own",
+                        socket.socket,
+                        ratio * 100.0,
+                        fastest.socket
+                    ));
+                }
+            }
+        }
+
+        findings
+    }
+}
+
+/// Lists the metrics of a scaling benchmark run, for statistics over repeated runs.
+pub fn scaling_measurements(result: &ScalingResult) -> Vec<Measurement> {
+    let mut measurements = Vec::new();
+    for point in &result.points {
+        measurements.push(Measurement::new(
+            format!("throughput at {} threads", point.threads),
+            "Mops/s",
+            point.ops_per_sec / 1e6,
+        ));
+        if let Some(efficiency) = result.efficiency(point) {
+            measurements.push(Measurement::new(
+                format!("efficiency at {} threads", point.threads),
+                "%",
+                efficiency,
+            ));
+        }
+    }
+    measurements.extend(result.sockets.iter().map(|socket| {
+        Measurement::new(
+            format!("socket {} throughput", socket.socket),
+            "Mops/s",
+            socket.ops_per_sec / 1e6,
+        )
+    }));
+    measurements
+}
+
+/// Formats the results of the scaling benchmark as a table.
+///
+/// # Arguments
+///
+/// * `result` - The results of the benchmark.
+///
+/// # Returns
+///
+/// * `String` - One row per thread count, then one per socket, then the findings.
+pub fn format_scaling_results(result: &ScalingResult) -> String {
+    let mut output = String::from("CPU scaling\n");
+    output.push_str(&format!(
+        "{:>7}  {:>7}  {:>3}  {:>10}  {:>7}  {:>10}\n",
+        "Threads", "Sockets", "SMT", "Mops/s", "Speedup", "Efficiency"
+    ));
+    for point in &result.points {
+        output.push_str(&format!(
+            "{:>7}  {:>7}  {:>3}  {:>10.1}  {:>7}  {:>10}\n",
+            point.threads,
+            point.sockets,
+            if point.smt { "yes" } else { "no" },
+            point.ops_per_sec / 1e6,
+            result
+                .speedup(point)
+                .map(|speedup| format!("{:.2}x", speedup))
+                .unwrap_or_else(|| "-".to_string()),
+            result
+                .efficiency(point)
+                .map(|efficiency| format!("{:.0}%", efficiency))
+                .unwrap_or_else(|| "-".to_string())
+        ));
+    }
+
+    if !result.sockets.is_empty() {
+        output.push_str("\nSockets on their own\n");
+        output.push_str(&format!(
+            "{:>7}  {:>7}  {:>10}\n",
+            "Socket", "Cores", "Mops/s"
+        ));
+        for socket in &result.sockets {
+            output.push_str(&format!(
+                "{:>7}  {:>7}  {:>10.1}\n",
+                socket.socket,
+                socket.cores,
+                socket.ops_per_sec / 1e6
+            ));
+        }
+    }
+
+    let findings = result.findings();
+    if findings.is_empty() {
+        output.push_str("\nNo scaling bottleneck found.\n");
+    } else {
+        output.push('\n');
+        for finding in findings {
+            output.push_str(&format!("WARNING: {}\n", finding));
+        }
+    }
+    output
+}
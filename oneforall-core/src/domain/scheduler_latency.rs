@@ -0,0 +1,106 @@
+//! Scheduler Latency Domain Entity
+//!
+//! This module provides the results of the scheduler latency benchmark, which
+//! qualifies machines for low-latency work such as trading or real-time audio.
+//! Those workloads care about the worst case rather than the average: how long
+//! a switch between two threads takes, how long a sleeping thread needs to run
+//! again once woken, and how late a periodic timer fires, in the manner of
+//! cyclictest.
+
+use crate::domain::filesystem::LatencyStats;
+use crate::domain::statistics::Measurement;
+
+/// The results of the scheduler latency benchmark.
+#[derive(Debug, Clone)]
+pub struct SchedulerLatencyResult {
+    /// The logical CPU the measured threads ran on.
+    pub cpu: u32,
+
+    /// The logical CPU the wake-ups were sent from.
+    pub waker_cpu: u32,
+
+    /// Whether every measured thread ran with a real-time (`SCHED_FIFO`) priority.
+    pub realtime: bool,
+
+    /// The time of a switch between two threads sharing the CPU.
+    pub context_switch: LatencyStats,
+
+    /// The time from waking a blocked thread until it runs.
+    pub wakeup: LatencyStats,
+
+    /// The period of the timer whose jitter was measured, in microseconds.
+    pub timer_interval_us: u64,
+
+    /// How late each expiry of the periodic timer was handled.
+    pub timer_jitter: LatencyStats,
+}
+
+/// Lists the metrics of a scheduler latency benchmark run, for statistics over repeated runs.
+pub fn scheduler_latency_measurements(result: &SchedulerLatencyResult) -> Vec<Measurement> {
+    let distributions = [
+        ("context switch", &result.context_switch),
+        ("wake-up", &result.wakeup),
+        ("timer jitter", &result.timer_jitter),
+    ];
+    distributions
+        .into_iter()
+        .flat_map(|(name, stats)| {
+            [
+                Measurement::new(format!("{} p50", name), "us", stats.p50_us),
+                Measurement::new(format!("{} p99", name), "us", stats.p99_us),
+                Measurement::new(format!("{} max", name), "us", stats.max_us),
+            ]
+        })
+        .collect()
+}
+
+/// Formats the results of the scheduler latency benchmark as a table.
+///
+/// # Arguments
+///
+/// * `result` - The results of the benchmark.
+///
+/// # Returns
+///
+/// * `String` - One row per measurement with its latency distribution.
+pub fn format_scheduler_latency_results(result: &SchedulerLatencyResult) -> String {
+    let mut output = format!(
+        "Scheduler latency on CPU {} (wake-ups from CPU {}, {} priority)\n",
+        result.cpu,
+        result.waker_cpu,
+        if result.realtime {
+            "real-time"
+        } else {
+            "normal"
+        }
+    );
+    output.push_str(&format!(
+        "{:<24}  {:>8}  {:>8}  {:>8}  {:>8}  {:>8}  {:>8}  {:>8}\n",
+        "Measurement", "Samples", "Min us", "Mean us", "p50 us", "p99 us", "p99.9 us", "Max us"
+    ));
+    let timer = format!("Timer jitter ({} us)", result.timer_interval_us);
+    for (name, stats) in [
+        ("Context switch", &result.context_switch),
+        ("Wake-up", &result.wakeup),
+        (timer.as_str(), &result.timer_jitter),
+    ] {
+        output.push_str(&format!(
+            "{:<24}  {:>8}  {:>8.1}  {:>8.1}  {:>8.1}  {:>8.1}  {:>8.1}  {:>8.1}\n",
+            name,
+            stats.samples,
+            stats.min_us,
+            stats.mean_us,
+            stats.p50_us,
+            stats.p99_us,
+            stats.p999_us,
+            stats.max_us
+        ));
+    }
+    if !result.realtime {
+        output.push_str(
+            "Run as root for a real-time priority; without it other tasks delay the \
+             measured threads and inflate the tail latencies.\n",
+        );
+    }
+    output
+}
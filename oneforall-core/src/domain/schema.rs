@@ -0,0 +1,168 @@
+//! Schema Domain Entity
+//!
+//! This module provides the versioning of the results kept in the database.
+//! Every result is stored with the `schema_version` it was written in, and is
+//! migrated forward to the current version whenever it is read, so results
+//! written by an older OneForAll keep the shape the current code expects
+//! after an upgrade. Each version lists what changed since the one before:
+//!
+//! * version 1: results written before they carried a version. Jobs stored
+//!   before they were queued by resource lack `resources`, and endurance
+//!   reports stored before the kernel log and NVMe error log were watched lack
+//!   `kernel_faults`, `nvme_before` and `nvme_after`. The earliest results were
+//!   stored under keys holding the local start time, or the job ID, rather
+//!   than the UTC start time, so time ranges do not find them.
+//! * version 2: every result carries `schema_version` and every field above.
+//!
+//! A result written by a newer version than this one is reported as such
+//! rather than misread. `db migrate` rewrites every stored result at the
+//! current version, under the current key schema.
+
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value};
+
+use crate::domain::database::RunKey;
+
+/// The field of a stored result holding the schema version it was written in.
+pub const SCHEMA_VERSION_FIELD: &str = "schema_version";
+
+/// The version of the results written by this build.
+pub const CURRENT_SCHEMA_VERSION: u64 = 2;
+
+/// The version of results written before they carried one.
+const UNVERSIONED: u64 = 1;
+
+/// Migrates the fields of a result of a kind from one version to the next.
+type Migration = fn(kind: &str, result: &mut Map<String, Value>);
+
+/// The migrations in order: the first one turns version 1 into version 2.
+const MIGRATIONS: [Migration; 1] = [add_missing_fields];
+
+/// Version 1 to 2: adds the fields introduced after results of a kind were
+/// first stored, with the value that means "not recorded".
+fn add_missing_fields(kind: &str, result: &mut Map<String, Value>) {
+    let added = match kind {
+        "job" => vec![("resources", Value::Array(Vec::new()))],
+        "endurance" => vec![
+            ("kernel_faults", Value::Array(Vec::new())),
+            ("nvme_before", Value::Null),
+            ("nvme_after", Value::Null),
+        ],
+        _ => Vec::new(),
+    };
+    for (field, value) in added {
+        result.entry(field).or_insert(value);
+    }
+}
+
+/// Returns the schema version a stored result was written in.
+pub fn schema_version(result: &Value) -> u64 {
+    result
+        .get(SCHEMA_VERSION_FIELD)
+        .and_then(Value::as_u64)
+        .unwrap_or(UNVERSIONED)
+}
+
+/// Returns a result as JSON stamped with the current schema version; results
+/// that are not JSON objects are returned unchanged.
+pub fn versioned(mut result: Value) -> Value {
+    if let Some(object) = result.as_object_mut() {
+        object.insert(
+            SCHEMA_VERSION_FIELD.to_string(),
+            Value::from(CURRENT_SCHEMA_VERSION),
+        );
+    }
+    result
+}
+
+/// Migrates a stored result to the current schema version.
+///
+/// # Arguments
+///
+/// * `kind` - The kind of run, one of `RESULT_KINDS`.
+/// * `result` - The result as stored.
+///
+/// # Returns
+///
+/// * `Result<Value, String>` - The result at the current version, or an error
+///   if it was written by a newer version of OneForAll.
+pub fn migrate(kind: &str, mut result: Value) -> Result<Value, String> {
+    let version = schema_version(&result);
+    if version > CURRENT_SCHEMA_VERSION {
+        return Err(format!(
+            "Written by a newer version of OneForAll (schema version {}; \
+             this version reads up to {})",
+            version, CURRENT_SCHEMA_VERSION
+        ));
+    }
+    let Some(object) = result.as_object_mut() else {
+        return Ok(result);
+    };
+    for migration in MIGRATIONS
+        .iter()
+        .skip(version.saturating_sub(UNVERSIONED) as usize)
+    {
+        migration(kind, object);
+    }
+    Ok(versioned(result))
+}
+
+/// Builds the current key of a result stored under a key of the earliest
+/// schema, e.g. "burn-in:2024-05-02T10:00:00+02:00", "job:17" or
+/// "profile:burn-in-24h:2024-05-02T10:00:00+02:00".
+///
+/// # Arguments
+///
+/// * `kind` - The kind of run, one of `RESULT_KINDS`.
+/// * `result` - The result, whose start time and name make up the key.
+///
+/// # Returns
+///
+/// * `Option<RunKey>` - The key, or `None` if the result has no valid start time.
+pub fn legacy_run_key(kind: &str, result: &Value) -> Option<RunKey> {
+    let started_at = result.get("started_at")?.as_str()?;
+    let started_at = DateTime::parse_from_rfc3339(started_at)
+        .ok()?
+        .with_timezone(&Utc);
+    let name = match kind {
+        "job" => Some(result.get("id")?.as_u64()?.to_string()),
+        "profile" => Some(result.get("profile")?.as_str()?.to_string()),
+        _ => None,
+    };
+    Some(RunKey {
+        kind: kind.to_string(),
+        started_at,
+        name,
+    })
+}
+
+/// What a migration of the stored results did.
+#[derive(Debug, Clone, Default)]
+pub struct MigrationSummary {
+    /// How many results were rewritten at the current version.
+    pub upgraded: usize,
+
+    /// How many of them were moved from a key of the earliest schema.
+    pub rekeyed: usize,
+
+    /// How many results were already current.
+    pub current: usize,
+
+    /// The results that could not be migrated, with why.
+    pub failed: Vec<String>,
+}
+
+impl MigrationSummary {
+    /// Renders the summary for the terminal, one line per failure.
+    pub fn render(&self) -> String {
+        let mut output = format!(
+            "Migrated {} results to schema version {} ({} moved to current keys); \
+             {} were already current.\n",
+            self.upgraded, CURRENT_SCHEMA_VERSION, self.rekeyed, self.current
+        );
+        for failure in &self.failed {
+            output.push_str(&format!("  Skipped {}\n", failure));
+        }
+        output
+    }
+}
@@ -0,0 +1,447 @@
+//! Score Domain Entity
+//!
+//! This module turns benchmark results into scores that compare machines at a
+//! glance, in the manner of SPEC ratios. Every scored metric is divided by a
+//! fixed reference value, inverted for metrics where lower is better, and
+//! multiplied by 1000, so a machine matching the reference scores 1000 and
+//! one twice as fast scores 2000:
+//!
+//! ```text
+//! metric score    = 1000 × value / reference      (higher is better)
+//!                 = 1000 × reference / value      (lower is better)
+//! subsystem score = geometric mean of its metric scores
+//! overall index   = geometric mean of the subsystem scores
+//! ```
+//!
+//! The geometric mean keeps one outstanding metric from hiding a poor one and
+//! makes the ratio of two scores independent of the reference. A metric is
+//! taken from the latest stored run of its benchmark, as the median over the
+//! repetitions when repeated. The reference values are fixed, so scores stay
+//! comparable between versions; they roughly describe a current two-socket
+//! server with NVMe storage, 100 Gbit/s RDMA and PCIe Gen4 GPUs.
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::statistics::BenchmarkRecord;
+
+/// The score of a metric equal to its reference value.
+pub const REFERENCE_SCORE: f64 = 1000.0;
+
+/// A part of the machine scored on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Subsystem {
+    /// Cache and DRAM bandwidth and latency.
+    Memory,
+
+    /// Filesystem metadata rates, small random writes and fsync latency.
+    Storage,
+
+    /// RDMA bandwidth and latency.
+    Network,
+
+    /// Bandwidth and latency between GPUs.
+    Gpu,
+
+    /// Context switch, wake-up, timer and system call latencies.
+    Scheduling,
+}
+
+impl Subsystem {
+    /// Returns the name shown in reports.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Subsystem::Memory => "Memory",
+            Subsystem::Storage => "Storage",
+            Subsystem::Network => "Network",
+            Subsystem::Gpu => "GPU",
+            Subsystem::Scheduling => "Scheduling",
+        }
+    }
+}
+
+/// A metric that counts towards a subsystem score.
+struct ScoredMetric {
+    subsystem: Subsystem,
+    benchmark: &'static str,
+    metric: &'static str,
+    reference: f64,
+    higher_is_better: bool,
+}
+
+/// Every scored metric with its reference value.
+const SCORED_METRICS: [ScoredMetric; 17] = [
+    ScoredMetric {
+        subsystem: Subsystem::Memory,
+        benchmark: "cache",
+        metric: "L1 bandwidth",
+        reference: 100_000.0,
+        higher_is_better: true,
+    },
+    ScoredMetric {
+        subsystem: Subsystem::Memory,
+        benchmark: "cache",
+        metric: "L1 latency",
+        reference: 1.5,
+        higher_is_better: false,
+    },
+    ScoredMetric {
+        subsystem: Subsystem::Memory,
+        benchmark: "cache",
+        metric: "DRAM bandwidth",
+        reference: 12_000.0,
+        higher_is_better: true,
+    },
+    ScoredMetric {
+        subsystem: Subsystem::Memory,
+        benchmark: "cache",
+        metric: "DRAM latency",
+        reference: 100.0,
+        higher_is_better: false,
+    },
+    ScoredMetric {
+        subsystem: Subsystem::Storage,
+        benchmark: "filesystem",
+        metric: "create rate",
+        reference: 20_000.0,
+        higher_is_better: true,
+    },
+    ScoredMetric {
+        subsystem: Subsystem::Storage,
+        benchmark: "filesystem",
+        metric: "stat rate",
+        reference: 200_000.0,
+        higher_is_better: true,
+    },
+    ScoredMetric {
+        subsystem: Subsystem::Storage,
+        benchmark: "filesystem",
+        metric: "delete rate",
+        reference: 20_000.0,
+        higher_is_better: true,
+    },
+    ScoredMetric {
+        subsystem: Subsystem::Storage,
+        benchmark: "filesystem",
+        metric: "random write rate",
+        reference: 5_000.0,
+        higher_is_better: true,
+    },
+    ScoredMetric {
+        subsystem: Subsystem::Storage,
+        benchmark: "filesystem",
+        metric: "fsync p99",
+        reference: 2_000.0,
+        higher_is_better: false,
+    },
+    ScoredMetric {
+        subsystem: Subsystem::Network,
+        benchmark: "rdma",
+        metric: "write bandwidth",
+        reference: 100.0,
+        higher_is_better: true,
+    },
+    ScoredMetric {
+        subsystem: Subsystem::Network,
+        benchmark: "rdma",
+        metric: "read latency typical",
+        reference: 2.0,
+        higher_is_better: false,
+    },
+    ScoredMetric {
+        subsystem: Subsystem::Gpu,
+        benchmark: "interconnect",
+        metric: "median read",
+        reference: 25.0,
+        higher_is_better: true,
+    },
+    ScoredMetric {
+        subsystem: Subsystem::Gpu,
+        benchmark: "interconnect",
+        metric: "median latency",
+        reference: 2.0,
+        higher_is_better: false,
+    },
+    ScoredMetric {
+        subsystem: Subsystem::Scheduling,
+        benchmark: "latency",
+        metric: "context switch p50",
+        reference: 2.0,
+        higher_is_better: false,
+    },
+    ScoredMetric {
+        subsystem: Subsystem::Scheduling,
+        benchmark: "latency",
+        metric: "wake-up p99",
+        reference: 20.0,
+        higher_is_better: false,
+    },
+    ScoredMetric {
+        subsystem: Subsystem::Scheduling,
+        benchmark: "latency",
+        metric: "timer jitter p99",
+        reference: 50.0,
+        higher_is_better: false,
+    },
+    ScoredMetric {
+        subsystem: Subsystem::Scheduling,
+        benchmark: "mitigations",
+        metric: "system call",
+        reference: 100.0,
+        higher_is_better: false,
+    },
+];
+
+/// The score of one metric.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricScore {
+    /// The benchmark that measured the metric, e.g. "cache".
+    pub benchmark: String,
+
+    /// The metric, e.g. "DRAM latency".
+    pub metric: String,
+
+    /// The value measured.
+    pub value: f64,
+
+    /// The value scoring 1000.
+    pub reference: f64,
+
+    /// The score of the value.
+    pub score: f64,
+}
+
+/// The score of one subsystem.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubsystemScore {
+    /// The subsystem.
+    pub subsystem: Subsystem,
+
+    /// The geometric mean of the metric scores.
+    pub score: f64,
+
+    /// The metrics the score is made of.
+    pub metrics: Vec<MetricScore>,
+}
+
+/// The scores of a machine.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MachineScore {
+    /// The subsystems with at least one scored metric, in a fixed order.
+    pub subsystems: Vec<SubsystemScore>,
+
+    /// The geometric mean of the subsystem scores, or `None` without any.
+    pub overall: Option<f64>,
+}
+
+impl MachineScore {
+    /// Returns the score of a subsystem, if it was scored.
+    pub fn subsystem(&self, subsystem: Subsystem) -> Option<f64> {
+        self.subsystems
+            .iter()
+            .find(|s| s.subsystem == subsystem)
+            .map(|s| s.score)
+    }
+}
+
+/// Returns the geometric mean of positive values, or `None` without any.
+fn geometric_mean(values: impl IntoIterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values
+        .into_iter()
+        .filter(|v| *v > 0.0)
+        .fold((0.0, 0usize), |(sum, count), v| (sum + v.ln(), count + 1));
+    (count > 0).then(|| (sum / count as f64).exp())
+}
+
+/// Scores a machine from its stored benchmark results.
+///
+/// # Arguments
+///
+/// * `records` - The stored benchmark results, oldest first.
+///
+/// # Returns
+///
+/// * `MachineScore` - The score of every subsystem with a scored metric, and the overall index.
+pub fn score_machine(records: &[BenchmarkRecord]) -> MachineScore {
+    let mut subsystems: Vec<SubsystemScore> = Vec::new();
+    for scored in &SCORED_METRICS {
+        let value = records
+            .iter()
+            .rev()
+            .filter(|record| record.benchmark == scored.benchmark)
+            .find_map(|record| record.value(scored.metric))
+            .filter(|value| *value > 0.0 && value.is_finite());
+        let Some(value) = value else {
+            continue;
+        };
+        let ratio = if scored.higher_is_better {
+            value / scored.reference
+        } else {
+            scored.reference / value
+        };
+        let metric = MetricScore {
+            benchmark: scored.benchmark.to_string(),
+            metric: scored.metric.to_string(),
+            value,
+            reference: scored.reference,
+            score: REFERENCE_SCORE * ratio,
+        };
+        match subsystems
+            .iter_mut()
+            .find(|s| s.subsystem == scored.subsystem)
+        {
+            Some(subsystem) => subsystem.metrics.push(metric),
+            None => subsystems.push(SubsystemScore {
+                subsystem: scored.subsystem,
+                score: 0.0,
+                metrics: vec![metric],
+            }),
+        }
+    }
+    for subsystem in &mut subsystems {
+        subsystem.score = geometric_mean(subsystem.metrics.iter().map(|m| m.score)).unwrap_or(0.0);
+    }
+    let overall = geometric_mean(subsystems.iter().map(|s| s.score));
+    MachineScore {
+        subsystems,
+        overall,
+    }
+}
+
+/// Renders the scores of a machine with the metrics behind them.
+///
+/// # Arguments
+///
+/// * `score` - The scores of the machine.
+///
+/// # Returns
+///
+/// * `String` - The overall index, then every subsystem score with its metrics.
+pub fn format_machine_score(score: &MachineScore) -> String {
+    let Some(overall) = score.overall else {
+        return "No benchmark results to score; run `benchmark cache`, `filesystem`, `rdma`, \
+                `interconnect`, `latency` or `mitigations --measure` first.\n"
+            .to_string();
+    };
+    let mut output = format!("Overall index: {:.0} (reference machine: 1000)\n", overall);
+    for subsystem in &score.subsystems {
+        output.push_str(&format!(
+            "\n{:<12} {:>6.0}\n",
+            subsystem.subsystem.label(),
+            subsystem.score
+        ));
+        for metric in &subsystem.metrics {
+            output.push_str(&format!(
+                "  {:<22} {:>12.2}  (reference {:>9.1})  {:>6.0}\n",
+                metric.metric, metric.value, metric.reference, metric.score
+            ));
+        }
+    }
+    output
+}
+
+/// A machine of the fleet, ranked by its index.
+#[derive(Debug, Clone, Serialize)]
+pub struct RankedMachine {
+    /// The agent name of the machine.
+    pub name: String,
+
+    /// The geometric mean of the subsystem scores every ranked machine has.
+    pub index: f64,
+
+    /// The scores of the machine.
+    pub score: MachineScore,
+}
+
+/// Ranks machines by the subsystems they were all scored on.
+///
+/// Machines scored on different subsystems are not comparable by their
+/// overall index, so each is ranked by the geometric mean of the subsystems
+/// common to every machine with a score.
+///
+/// # Arguments
+///
+/// * `machines` - The agent name and scores of every machine.
+///
+/// # Returns
+///
+/// * `(Vec<RankedMachine>, Vec<Subsystem>)` - The machines, best first, and
+///   the subsystems they were ranked by; machines without a score are left out.
+pub fn rank_machines(machines: &[(String, MachineScore)]) -> (Vec<RankedMachine>, Vec<Subsystem>) {
+    let scored: Vec<&(String, MachineScore)> = machines
+        .iter()
+        .filter(|(_, score)| score.overall.is_some())
+        .collect();
+    let common: Vec<Subsystem> = SCORED_METRICS
+        .iter()
+        .map(|metric| metric.subsystem)
+        .fold(Vec::new(), |mut subsystems, subsystem| {
+            if !subsystems.contains(&subsystem) {
+                subsystems.push(subsystem);
+            }
+            subsystems
+        })
+        .into_iter()
+        .filter(|subsystem| {
+            scored
+                .iter()
+                .all(|(_, score)| score.subsystem(*subsystem).is_some())
+        })
+        .collect();
+
+    let mut ranked: Vec<RankedMachine> = scored
+        .into_iter()
+        .filter_map(|(name, score)| {
+            let index = geometric_mean(common.iter().filter_map(|s| score.subsystem(*s)))?;
+            Some(RankedMachine {
+                name: name.clone(),
+                index,
+                score: score.clone(),
+            })
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.index.total_cmp(&a.index));
+    (ranked, common)
+}
+
+/// Renders the ranking of the fleet.
+///
+/// # Arguments
+///
+/// * `ranked` - The machines, best first.
+/// * `subsystems` - The subsystems the machines were ranked by.
+///
+/// # Returns
+///
+/// * `String` - One row per machine with its index and subsystem scores.
+pub fn format_ranking(ranked: &[RankedMachine], subsystems: &[Subsystem]) -> String {
+    if ranked.is_empty() {
+        return "No machine has benchmark results in common to rank.\n".to_string();
+    }
+    let width = ranked
+        .iter()
+        .map(|machine| machine.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Machine".len());
+    let mut output = format!("  #  {:<width$}  {:>6}", "Machine", "Index", width = width);
+    for subsystem in subsystems {
+        output.push_str(&format!("  {:>10}", subsystem.label()));
+    }
+    output.push('\n');
+    for (position, machine) in ranked.iter().enumerate() {
+        output.push_str(&format!(
+            "{:>3}  {:<width$}  {:>6.0}",
+            position + 1,
+            machine.name,
+            machine.index,
+            width = width
+        ));
+        for subsystem in subsystems {
+            let score = machine.score.subsystem(*subsystem).unwrap_or_default();
+            output.push_str(&format!("  {:>10.0}", score));
+        }
+        output.push('\n');
+    }
+    output
+}
@@ -0,0 +1,215 @@
+//! SEL Domain Entity
+//!
+//! This module provides the System Event Log of the BMC, where the firmware
+//! records what the operating system may never see: correctable memory errors
+//! the BIOS handled itself, PCIe link errors, power supply and fan failures,
+//! and sensors crossing their thresholds. The log is read before a run and
+//! again after it, and any entry added in between is attached to the results
+//! and fails the run, as hardware that logged an event under load is not
+//! ready for production.
+
+use serde::Serialize;
+
+use crate::domain::burn_in::{MonitorResult, MonitorStatus};
+
+/// The events the BMC logs when its own log is cleared, which are not hardware faults.
+const LOG_CLEARED_EVENTS: &[&str] = &["Log area reset/cleared"];
+
+/// The number of new entries kept in the findings; the rest are only counted.
+const MAX_RECORDED_ENTRIES: usize = 50;
+
+/// An entry of the System Event Log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SelEntry {
+    /// The record ID the BMC gave the entry, in hexadecimal, e.g. "1c".
+    pub id: String,
+
+    /// The date the BMC logged the entry, e.g. "04/18/2026".
+    pub date: String,
+
+    /// The time the BMC logged the entry, e.g. "15:17:27".
+    pub time: String,
+
+    /// The sensor that logged the entry, e.g. "Memory #0x87".
+    pub sensor: String,
+
+    /// The event, e.g. "Correctable ECC | DIMM A1".
+    pub event: String,
+
+    /// Whether the event was asserted or deasserted.
+    pub state: String,
+}
+
+impl SelEntry {
+    /// Describes the entry, e.g. "04/18/2026 15:17:27 Memory #0x87: Correctable ECC (Asserted)".
+    pub fn describe(&self) -> String {
+        let mut text = format!(
+            "{} {} {}: {}",
+            self.date, self.time, self.sensor, self.event
+        );
+        if !self.state.is_empty() {
+            text.push_str(&format!(" ({})", self.state));
+        }
+        text
+    }
+}
+
+/// Parses the output of `ipmitool sel elist`, e.g.
+/// "1c | 04/18/2026 | 15:17:27 | Memory #0x87 | Correctable ECC | DIMM A1 | Asserted".
+///
+/// # Arguments
+///
+/// * `text` - The output of `ipmitool sel elist`.
+///
+/// # Returns
+///
+/// * `Vec<SelEntry>` - The entries, in the order the BMC logged them.
+pub fn parse_sel_elist(text: &str) -> Vec<SelEntry> {
+    text.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('|').map(str::trim).collect();
+            if fields.len() < 5 || fields[0].is_empty() {
+                return None;
+            }
+            // The event may span several fields, e.g. a description and the DIMM it names.
+            let (state, event) = match fields[4..].split_last() {
+                Some((state, event)) if !event.is_empty() => (state.to_string(), event.join(" | ")),
+                _ => (String::new(), fields[4].to_string()),
+            };
+            Some(SelEntry {
+                id: fields[0].to_string(),
+                date: fields[1].to_string(),
+                time: fields[2].to_string(),
+                sensor: fields[3].to_string(),
+                event,
+                state,
+            })
+        })
+        .collect()
+}
+
+/// Finds the entries added to the log between two readings.
+///
+/// # Arguments
+///
+/// * `before` - The entries read before the run.
+/// * `after` - The entries read after the run.
+///
+/// # Returns
+///
+/// * `Vec<SelEntry>` - The entries of `after` missing from `before`; a record ID reused
+///   after the log was cleared or wrapped counts as new when its entry differs.
+pub fn new_sel_entries(before: &[SelEntry], after: &[SelEntry]) -> Vec<SelEntry> {
+    after
+        .iter()
+        .filter(|entry| !before.contains(entry))
+        .cloned()
+        .collect()
+}
+
+/// The SEL entries added during a run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SelFindings {
+    /// The number of entries in the log before the run, or why it could not be read.
+    pub source: Result<usize, String>,
+
+    /// The number of entries added during the run.
+    pub new_count: usize,
+
+    /// The first entries added during the run.
+    pub entries: Vec<SelEntry>,
+}
+
+impl SelFindings {
+    /// Compares the readings of the log taken before and after a run.
+    ///
+    /// # Arguments
+    ///
+    /// * `before` - The entries read before the run, or why the log could not be read.
+    /// * `after` - The entries read after the run, or why the log could not be read.
+    ///
+    /// # Returns
+    ///
+    /// * `SelFindings` - The entries added during the run, leaving out the BMC
+    ///   recording that its log was cleared.
+    pub fn compare(
+        before: &Result<Vec<SelEntry>, String>,
+        after: &Result<Vec<SelEntry>, String>,
+    ) -> Self {
+        let (before, after) = match (before, after) {
+            (Ok(before), Ok(after)) => (before, after),
+            (Err(reason), _) | (_, Err(reason)) => {
+                return SelFindings {
+                    source: Err(reason.clone()),
+                    new_count: 0,
+                    entries: Vec::new(),
+                }
+            }
+        };
+        let added: Vec<SelEntry> = new_sel_entries(before, after)
+            .into_iter()
+            .filter(|entry| {
+                !LOG_CLEARED_EVENTS
+                    .iter()
+                    .any(|event| entry.event.contains(event))
+            })
+            .collect();
+        SelFindings {
+            source: Ok(before.len()),
+            new_count: added.len(),
+            entries: added.into_iter().take(MAX_RECORDED_ENTRIES).collect(),
+        }
+    }
+
+    /// Returns `true` if the BMC logged an entry during the run.
+    pub fn has_new_entries(&self) -> bool {
+        self.new_count > 0
+    }
+
+    /// Describes the entries added during the run, for run results and acceptance checks.
+    pub fn faults(&self) -> Vec<String> {
+        self.entries.iter().map(SelEntry::describe).collect()
+    }
+
+    /// Returns the entries added during the run, or why the log was not read.
+    pub fn watched_faults(&self) -> Result<Vec<String>, String> {
+        self.source
+            .as_ref()
+            .map(|_| self.faults())
+            .map_err(Clone::clone)
+    }
+
+    /// Converts the findings into a fault monitor result for the burn-in report.
+    pub fn monitor_result(&self) -> MonitorResult {
+        let status = match &self.source {
+            Err(reason) => MonitorStatus::Unavailable(reason.clone()),
+            Ok(_) if self.has_new_entries() => MonitorStatus::Faults(self.faults()),
+            Ok(count) => MonitorStatus::Clean(format!("BMC event log, {} earlier entries", count)),
+        };
+        MonitorResult {
+            source: "SEL".to_string(),
+            status,
+        }
+    }
+
+    /// Renders the findings as a section of the run telemetry report.
+    pub fn render(&self) -> String {
+        match &self.source {
+            Err(reason) => format!("BMC event log: not read ({})\n", reason),
+            Ok(_) if !self.has_new_entries() => {
+                "BMC event log: no new entries during the run\n".to_string()
+            }
+            Ok(_) => {
+                let mut output = format!(
+                    "BMC event log: {} new entr{} during the run\n",
+                    self.new_count,
+                    if self.new_count == 1 { "y" } else { "ies" }
+                );
+                for entry in &self.entries {
+                    output.push_str(&format!("  {}\n", entry.describe()));
+                }
+                output
+            }
+        }
+    }
+}
@@ -0,0 +1,285 @@
+//! SIMD Stress Domain Entity
+//!
+//! This module provides the report of the SIMD stress test started by
+//! `oneforall stress --simd`. Wide vector instructions draw more current than
+//! scalar code, so many CPUs lower their clock while running them: Intel calls
+//! these frequency licenses, and a core running AVX-512 can lose several
+//! hundred MHz against the same core running scalar code. The test runs a
+//! scalar phase first, then one phase per vector instruction set, and compares
+//! the clock each phase held with the scalar one, since an HPC node is sized by
+//! the clock its vectorized code actually runs at.
+
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::domain::burn_in::WorkloadOutcome;
+use crate::domain::cpu_frequency::CoreFrequency;
+use crate::domain::profile::format_duration;
+
+/// Drop of the clock against the scalar phase, in percent, reported as downclocking.
+pub const DOWNCLOCK_THRESHOLD_PERCENT: f64 = 5.0;
+
+/// Time at the start of a phase whose clock readings are left out, while the
+/// clock settles from the previous phase.
+pub const PHASE_SETTLE_TIME: Duration = Duration::from_secs(1);
+
+/// An instruction set the SIMD stress test exercises.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SimdIsa {
+    /// Scalar floating point, the baseline clock.
+    Scalar,
+
+    /// 256-bit AVX2 with FMA on x86.
+    Avx2,
+
+    /// 512-bit AVX-512 on x86.
+    Avx512,
+
+    /// 128-bit NEON (Advanced SIMD) on Arm.
+    Neon,
+
+    /// Scalable Vector Extension on Arm, as wide as the CPU implements it.
+    Sve,
+}
+
+impl SimdIsa {
+    /// The vector instruction sets, from the narrowest to the widest per architecture.
+    pub const VECTOR: [SimdIsa; 4] = [SimdIsa::Avx2, SimdIsa::Avx512, SimdIsa::Neon, SimdIsa::Sve];
+
+    /// Returns the name of the instruction set on the command line, e.g. "avx512".
+    pub fn name(&self) -> &'static str {
+        match self {
+            SimdIsa::Scalar => "scalar",
+            SimdIsa::Avx2 => "avx2",
+            SimdIsa::Avx512 => "avx512",
+            SimdIsa::Neon => "neon",
+            SimdIsa::Sve => "sve",
+        }
+    }
+
+    /// Returns the name of the instruction set in reports, e.g. "AVX-512".
+    pub fn label(&self) -> &'static str {
+        match self {
+            SimdIsa::Scalar => "Scalar",
+            SimdIsa::Avx2 => "AVX2",
+            SimdIsa::Avx512 => "AVX-512",
+            SimdIsa::Neon => "NEON",
+            SimdIsa::Sve => "SVE",
+        }
+    }
+
+    /// Parses the name of a vector instruction set given on the command line.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name, e.g. "avx2" or "sve".
+    ///
+    /// # Returns
+    ///
+    /// * `Option<SimdIsa>` - The instruction set, or `None` if the name is unknown.
+    pub fn from_name(name: &str) -> Option<SimdIsa> {
+        SimdIsa::VECTOR.into_iter().find(|isa| isa.name() == name)
+    }
+}
+
+/// Lists the phases of a test: the scalar baseline, then the requested
+/// instruction set or every supported one.
+///
+/// # Arguments
+///
+/// * `supported` - The vector instruction sets the CPU supports.
+/// * `requested` - The vector instruction set to test; every supported one when `None`.
+///
+/// # Returns
+///
+/// * `Result<Vec<SimdIsa>, String>` - The phases in order, or why the CPU cannot run them.
+pub fn select_phases(
+    supported: &[SimdIsa],
+    requested: Option<SimdIsa>,
+) -> Result<Vec<SimdIsa>, String> {
+    let vector = match requested {
+        Some(isa) if supported.contains(&isa) => vec![isa],
+        Some(isa) if supported.is_empty() => {
+            return Err(format!(
+                "This CPU does not support {} nor any vector instruction set OneForAll can test",
+                isa.label()
+            ))
+        }
+        Some(isa) => {
+            return Err(format!(
+                "This CPU does not support {}; it supports {}",
+                isa.label(),
+                supported
+                    .iter()
+                    .map(SimdIsa::label)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        }
+        None if supported.is_empty() => {
+            return Err(
+                "This CPU supports no vector instruction set OneForAll can test".to_string(),
+            )
+        }
+        None => supported.to_vec(),
+    };
+    Ok(std::iter::once(SimdIsa::Scalar).chain(vector).collect())
+}
+
+/// The throughput and clock of one phase of the test.
+#[derive(Debug, Clone, Serialize)]
+pub struct SimdPhase {
+    /// The instruction set the phase ran.
+    pub isa: SimdIsa,
+
+    /// The time the phase ran, in seconds.
+    pub elapsed_secs: f64,
+
+    /// The floating-point operations completed per second by every core, in GFLOPS.
+    pub gflops: f64,
+
+    /// The average clock of the cores over the phase, in MHz, if the platform reports it.
+    pub avg_mhz: Option<f64>,
+
+    /// The lowest average clock of the cores in a single reading, in MHz.
+    pub min_mhz: Option<f64>,
+}
+
+/// Averages clock readings taken during a phase.
+///
+/// # Arguments
+///
+/// * `samples` - Readings of every core with their offset into the phase.
+///
+/// # Returns
+///
+/// * `(Option<f64>, Option<f64>)` - The average and the lowest mean clock of the
+///   cores over the readings taken once the clock settled, in MHz.
+pub fn phase_clock(samples: &[(Duration, Vec<CoreFrequency>)]) -> (Option<f64>, Option<f64>) {
+    let means: Vec<f64> = samples
+        .iter()
+        .filter(|(offset, _)| *offset >= PHASE_SETTLE_TIME)
+        .filter_map(|(_, cores)| {
+            let clocks: Vec<f64> = cores
+                .iter()
+                .filter_map(|core| core.frequency_mhz.map(f64::from))
+                .collect();
+            (!clocks.is_empty()).then(|| clocks.iter().sum::<f64>() / clocks.len() as f64)
+        })
+        .collect();
+    if means.is_empty() {
+        return (None, None);
+    }
+    (
+        Some(means.iter().sum::<f64>() / means.len() as f64),
+        means.iter().cloned().reduce(f64::min),
+    )
+}
+
+/// The result of a SIMD stress test.
+#[derive(Debug, Clone, Serialize)]
+pub struct SimdStressReport {
+    /// The local time the test started, in RFC 3339 format.
+    pub started_at: String,
+
+    /// The configured duration, in seconds.
+    pub planned_secs: u64,
+
+    /// The time the test actually ran, in seconds.
+    pub elapsed_secs: u64,
+
+    /// How the test ended.
+    pub outcome: WorkloadOutcome,
+
+    /// The threads each phase ran, one per online CPU.
+    pub threads: usize,
+
+    /// The phases run, the scalar baseline first.
+    pub phases: Vec<SimdPhase>,
+}
+
+impl SimdStressReport {
+    /// Returns how far the clock of a phase fell below the scalar phase, in percent.
+    pub fn downclock_percent(&self, phase: &SimdPhase) -> Option<f64> {
+        let scalar = self
+            .phases
+            .iter()
+            .find(|phase| phase.isa == SimdIsa::Scalar)?
+            .avg_mhz?;
+        let mhz = phase.avg_mhz?;
+        (scalar > 0.0).then(|| (1.0 - mhz / scalar) * 100.0)
+    }
+
+    /// Returns `true` if every phase ran to the end. Downclocking is reported but
+    /// does not fail the test, as it is how the CPU is designed to behave.
+    pub fn passed(&self) -> bool {
+        self.outcome == WorkloadOutcome::Completed && !self.phases.is_empty()
+    }
+
+    /// Renders the report as plain text suitable for the terminal.
+    pub fn render(&self) -> String {
+        let mut output = String::from("== SIMD stress test report ==\n");
+        output.push_str(&format!(
+            "Duration:    {} of {} planned\n",
+            format_duration(Duration::from_secs(self.elapsed_secs)),
+            format_duration(Duration::from_secs(self.planned_secs))
+        ));
+        output.push_str(&format!(
+            "Test:        {}\n",
+            match &self.outcome {
+                WorkloadOutcome::Completed => "completed".to_string(),
+                WorkloadOutcome::Failed(e) => format!("FAILED ({})", e),
+                WorkloadOutcome::Cancelled => "cancelled".to_string(),
+            }
+        ));
+        output.push_str(&format!("Threads:     {}\n", self.threads));
+
+        if !self.phases.is_empty() {
+            let mhz = |value: Option<f64>| {
+                value
+                    .map(|mhz| format!("{:.0}", mhz))
+                    .unwrap_or_else(|| "-".to_string())
+            };
+            output.push_str(&format!(
+                "\n  {:<8} {:>10} {:>9} {:>9} {:>10}\n",
+                "ISA", "GFLOPS", "Avg MHz", "Min MHz", "vs scalar"
+            ));
+            for phase in &self.phases {
+                let downclock = self.downclock_percent(phase);
+                output.push_str(&format!(
+                    "  {:<8} {:>10.1} {:>9} {:>9} {:>10}{}\n",
+                    phase.isa.label(),
+                    phase.gflops,
+                    mhz(phase.avg_mhz),
+                    mhz(phase.min_mhz),
+                    match downclock {
+                        Some(_) if phase.isa == SimdIsa::Scalar => "-".to_string(),
+                        Some(percent) => format!("{:+.1}%", -percent),
+                        None => "-".to_string(),
+                    },
+                    match downclock {
+                        Some(percent)
+                            if phase.isa != SimdIsa::Scalar
+                                && percent > DOWNCLOCK_THRESHOLD_PERCENT =>
+                        {
+                            " LICENSE DOWNCLOCK"
+                        }
+                        _ => "",
+                    }
+                ));
+            }
+            if self.phases.iter().all(|phase| phase.avg_mhz.is_none()) {
+                output.push_str("\n  Clocks were not reported on this platform.\n");
+            }
+            output.push('\n');
+        }
+
+        output.push_str(&format!(
+            "Result: {}\n",
+            if self.passed() { "PASS" } else { "FAIL" }
+        ));
+        output
+    }
+}
@@ -0,0 +1,282 @@
+//! Soak Domain Entity
+//!
+//! This module provides the duty cycle and the report of a soak test, which
+//! alternates load and idle periods, e.g. 10 minutes on and 5 off for 24
+//! hours, instead of loading the machine without a break. Every cycle heats
+//! the hardware and lets it cool again, and the expansion and contraction
+//! that comes with it is what cracks solder joints and unseats heatsinks and
+//! connectors, faults a sustained load at a steady temperature rarely finds.
+//! The report is aligned on the cycles: the peak temperature of each load
+//! period, the lowest of the idle period that follows, and the ECC errors,
+//! machine checks and kernel log faults counted over the cycle, so a fault
+//! can be placed in the cycle that caused it.
+
+use std::ops::Range;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::domain::burn_in::WorkloadOutcome;
+use crate::domain::profile::format_duration;
+use crate::domain::thermal::ThermalSample;
+
+/// The load and idle periods a soak test alternates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DutyCycle {
+    /// How long each load period lasts.
+    pub on: Duration,
+
+    /// How long each idle period lasts.
+    pub off: Duration,
+}
+
+impl DutyCycle {
+    /// Creates a duty cycle, checking that it loads the machine at all.
+    ///
+    /// # Arguments
+    ///
+    /// * `on` - How long each load period lasts.
+    /// * `off` - How long each idle period lasts.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<DutyCycle, String>` - The duty cycle, or an error if the load period is empty.
+    pub fn new(on: Duration, off: Duration) -> Result<DutyCycle, String> {
+        if on.is_zero() {
+            return Err("The load period of a soak test must be longer than zero".to_string());
+        }
+        Ok(DutyCycle { on, off })
+    }
+
+    /// Splits a soak test into cycles, the last one cut short at the end of the duration.
+    ///
+    /// # Arguments
+    ///
+    /// * `total` - How long the whole soak test runs.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<(Duration, Duration)>` - The load and idle period of each cycle.
+    pub fn cycles(&self, total: Duration) -> Vec<(Duration, Duration)> {
+        let mut cycles = Vec::new();
+        let mut elapsed = Duration::ZERO;
+        while elapsed < total {
+            let load = self.on.min(total - elapsed);
+            let idle = self.off.min(total - elapsed - load);
+            cycles.push((load, idle));
+            elapsed += load + idle;
+        }
+        cycles
+    }
+
+    /// Describes the duty cycle, e.g. "10m on / 5m off".
+    pub fn describe(&self) -> String {
+        format!(
+            "{} on / {} off",
+            format_duration(self.on),
+            format_duration(self.off)
+        )
+    }
+}
+
+/// Returns the peak temperature of a load period and the lowest of the idle period after it.
+///
+/// # Arguments
+///
+/// * `samples` - Thermal readings with their offset from the start of the soak test.
+/// * `load` - The offsets the load period spans.
+/// * `idle` - The offsets the idle period spans.
+///
+/// # Returns
+///
+/// * `(Option<f64>, Option<f64>)` - The hottest sensor at its hottest under load, and
+///   the hottest sensor at its coolest while idle, in degrees Celsius.
+pub fn cycle_temperatures(
+    samples: &[(Duration, ThermalSample)],
+    load: Range<Duration>,
+    idle: Range<Duration>,
+) -> (Option<f64>, Option<f64>) {
+    let hottest_in = |period: &Range<Duration>| {
+        samples
+            .iter()
+            .filter(|(at, _)| period.contains(at))
+            .filter_map(|(_, sample)| sample.hottest())
+            .collect::<Vec<f64>>()
+    };
+    (
+        hottest_in(&load).into_iter().reduce(f64::max),
+        hottest_in(&idle).into_iter().reduce(f64::min),
+    )
+}
+
+/// What one load and idle cycle of a soak test saw.
+#[derive(Debug, Clone, Serialize)]
+pub struct SoakCycle {
+    /// The cycle number, from 1.
+    pub number: u32,
+
+    /// The time the machine was loaded, in seconds.
+    pub load_secs: f64,
+
+    /// The time the machine was left idle, in seconds.
+    pub idle_secs: f64,
+
+    /// The bogo-ops completed by the stressor during the load period.
+    pub bogo_ops: Option<u64>,
+
+    /// The hottest temperature of the load period, in degrees Celsius.
+    pub load_peak_celsius: Option<f64>,
+
+    /// The lowest the hottest sensor fell to during the idle period, in degrees Celsius.
+    pub idle_low_celsius: Option<f64>,
+
+    /// The new ECC errors, corrected or not, counted over the cycle.
+    pub ecc_errors: Option<u64>,
+
+    /// The new machine check events counted over the cycle.
+    pub machine_checks: Option<u64>,
+
+    /// The serious kernel log events seen over the cycle.
+    pub kernel_faults: Vec<String>,
+}
+
+impl SoakCycle {
+    /// Returns how far the temperature fell from the load period to the idle period.
+    pub fn swing_celsius(&self) -> Option<f64> {
+        Some(self.load_peak_celsius? - self.idle_low_celsius?)
+    }
+
+    /// Returns `true` if an error counter rose or the kernel logged a fault during the cycle.
+    pub fn faulted(&self) -> bool {
+        self.ecc_errors.unwrap_or(0) > 0
+            || self.machine_checks.unwrap_or(0) > 0
+            || !self.kernel_faults.is_empty()
+    }
+}
+
+/// The result of a soak test.
+#[derive(Debug, Clone, Serialize)]
+pub struct SoakReport {
+    /// The local time the soak test started, in RFC 3339 format.
+    pub started_at: String,
+
+    /// The configured duration, in seconds.
+    pub planned_secs: u64,
+
+    /// The time the soak test actually ran, in seconds.
+    pub elapsed_secs: u64,
+
+    /// How the load periods ended.
+    pub outcome: WorkloadOutcome,
+
+    /// The stress-ng stressor of the load periods, e.g. "cpu".
+    pub stressor: String,
+
+    /// The length of each load period, in seconds.
+    pub on_secs: u64,
+
+    /// The length of each idle period, in seconds.
+    pub off_secs: u64,
+
+    /// The cycles run, in order.
+    pub cycles: Vec<SoakCycle>,
+
+    /// Why the kernel log could not be watched, if it could not.
+    pub kernel_log_unavailable: Option<String>,
+}
+
+impl SoakReport {
+    /// Returns the largest temperature swing of any cycle.
+    pub fn max_swing_celsius(&self) -> Option<f64> {
+        self.cycles
+            .iter()
+            .filter_map(SoakCycle::swing_celsius)
+            .reduce(f64::max)
+    }
+
+    /// Returns `true` if every load period completed and no cycle saw a fault.
+    pub fn passed(&self) -> bool {
+        self.outcome == WorkloadOutcome::Completed
+            && !self.cycles.is_empty()
+            && !self.cycles.iter().any(SoakCycle::faulted)
+    }
+
+    /// Renders the report as plain text suitable for the terminal.
+    pub fn render(&self) -> String {
+        let mut output = String::from("== Soak report ==\n");
+        output.push_str(&format!(
+            "Duration:    {} of {} planned\n",
+            format_duration(Duration::from_secs(self.elapsed_secs)),
+            format_duration(Duration::from_secs(self.planned_secs))
+        ));
+        output.push_str(&format!(
+            "Duty cycle:  {} of stress-ng {}\n",
+            DutyCycle {
+                on: Duration::from_secs(self.on_secs),
+                off: Duration::from_secs(self.off_secs),
+            }
+            .describe(),
+            self.stressor
+        ));
+        output.push_str(&format!(
+            "Workloads:   {}\n",
+            match &self.outcome {
+                WorkloadOutcome::Completed => "completed".to_string(),
+                WorkloadOutcome::Failed(e) => format!("FAILED ({})", e),
+                WorkloadOutcome::Cancelled => "cancelled".to_string(),
+            }
+        ));
+
+        if !self.cycles.is_empty() {
+            let celsius = |value: Option<f64>| {
+                value
+                    .map(|c| format!("{:.1}", c))
+                    .unwrap_or_else(|| "-".to_string())
+            };
+            let count = |value: Option<u64>| {
+                value
+                    .map(|n| n.to_string())
+                    .unwrap_or_else(|| "-".to_string())
+            };
+            output.push_str(&format!(
+                "\n  {:>5} {:>6} {:>6} {:>8} {:>8} {:>8} {:>5} {:>5}  {}\n",
+                "Cycle", "Load", "Idle", "Peak °C", "Idle °C", "Swing °C", "ECC", "MCE", "Kernel"
+            ));
+            for cycle in &self.cycles {
+                output.push_str(&format!(
+                    "  {:>5} {:>6} {:>6} {:>8} {:>8} {:>8} {:>5} {:>5}  {}\n",
+                    cycle.number,
+                    format_duration(Duration::from_secs(cycle.load_secs.round() as u64)),
+                    format_duration(Duration::from_secs(cycle.idle_secs.round() as u64)),
+                    celsius(cycle.load_peak_celsius),
+                    celsius(cycle.idle_low_celsius),
+                    celsius(cycle.swing_celsius()),
+                    count(cycle.ecc_errors),
+                    count(cycle.machine_checks),
+                    if cycle.kernel_faults.is_empty() {
+                        "ok".to_string()
+                    } else {
+                        format!("{} FAULT(S)", cycle.kernel_faults.len())
+                    }
+                ));
+            }
+            for cycle in self.cycles.iter().filter(|cycle| cycle.faulted()) {
+                for fault in &cycle.kernel_faults {
+                    output.push_str(&format!("  cycle {}: {}\n", cycle.number, fault));
+                }
+            }
+            if let Some(swing) = self.max_swing_celsius() {
+                output.push_str(&format!("\nLargest swing: {:.1} °C\n", swing));
+            }
+        }
+        if let Some(reason) = &self.kernel_log_unavailable {
+            output.push_str(&format!("Kernel log:  not watched ({})\n", reason));
+        }
+
+        output.push_str(&format!(
+            "Result: {}\n",
+            if self.passed() { "PASS" } else { "FAIL" }
+        ));
+        output
+    }
+}
@@ -0,0 +1,329 @@
+//! Statistics Domain Entity
+//!
+//! This module provides what turns repeated benchmark runs into numbers that
+//! can be compared between machines and between runs: how many runs to
+//! discard as warm-up and how many to measure, and per metric the mean,
+//! median, standard deviation and 95% confidence interval of the measured
+//! runs after outliers are rejected. A single run is skewed by whatever else
+//! the machine did at the time; a change smaller than the confidence
+//! interval is noise rather than a regression. Each benchmark run is stored
+//! as a record of its metrics, from which machines are scored.
+
+use serde::{Deserialize, Serialize};
+
+/// Two-sided 95% critical values of Student's t distribution, by degrees of freedom from 1.
+const T_CRITICAL_95: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+    2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+    2.052, 2.048, 2.045, 2.042,
+];
+
+/// The normal critical value used beyond the table.
+const Z_CRITICAL_95: f64 = 1.96;
+
+/// Multiple of the interquartile range beyond the quartiles at which a run is an outlier.
+const TUKEY_FENCE: f64 = 1.5;
+
+/// Number of runs below which no run is rejected, as quartiles of fewer mean little.
+const MIN_RUNS_FOR_OUTLIERS: usize = 4;
+
+/// How often a benchmark runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RepetitionPlan {
+    /// Runs made first and discarded, to warm caches, clocks and the page cache.
+    pub warmup: u32,
+
+    /// Runs measured and summarized; at least one.
+    pub repetitions: u32,
+}
+
+/// One metric of one benchmark run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Measurement {
+    /// The metric, e.g. "L1 latency" or "fsync p99".
+    pub metric: String,
+
+    /// The unit of the metric, e.g. "ns".
+    pub unit: String,
+
+    /// The value measured.
+    pub value: f64,
+}
+
+impl Measurement {
+    /// Creates a measurement.
+    pub fn new(metric: impl Into<String>, unit: &str, value: f64) -> Self {
+        Measurement {
+            metric: metric.into(),
+            unit: unit.to_string(),
+            value,
+        }
+    }
+}
+
+/// The summary of one metric over the measured runs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricStatistics {
+    /// The metric, e.g. "L1 latency".
+    pub metric: String,
+
+    /// The unit of the metric, e.g. "ns".
+    pub unit: String,
+
+    /// The runs kept after outlier rejection.
+    pub runs: usize,
+
+    /// The runs rejected as outliers.
+    pub outliers: usize,
+
+    /// The mean of the kept runs.
+    pub mean: f64,
+
+    /// The median of the kept runs.
+    pub median: f64,
+
+    /// The sample standard deviation of the kept runs; zero for a single run.
+    pub stddev: f64,
+
+    /// Half the width of the 95% confidence interval of the mean; zero for a single run.
+    pub ci95: f64,
+}
+
+impl MetricStatistics {
+    /// Returns the standard deviation relative to the mean, in percent.
+    pub fn relative_stddev_percent(&self) -> f64 {
+        if self.mean == 0.0 {
+            0.0
+        } else {
+            self.stddev * 100.0 / self.mean.abs()
+        }
+    }
+}
+
+/// Returns the value at a fraction of sorted values, interpolating between neighbors.
+///
+/// # Arguments
+///
+/// * `sorted` - The values in ascending order; there must be at least one.
+/// * `fraction` - Where the value lies, from 0.0 for the lowest to 1.0 for the highest.
+pub fn quantile(sorted: &[f64], fraction: f64) -> f64 {
+    let position = fraction * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+}
+
+/// Returns a percentile of values in any order, interpolating between the
+/// values around it, or `None` without any.
+///
+/// # Arguments
+///
+/// * `values` - The values, in any order.
+/// * `percent` - The percentile, e.g. 99.0.
+pub fn percentile(values: &[f64], percent: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    Some(quantile(&sorted, percent / 100.0))
+}
+
+/// Returns the median of values in any order, or `None` without any.
+pub fn median(values: &[f64]) -> Option<f64> {
+    percentile(values, 50.0)
+}
+
+/// Returns the mean of values, or `None` without any.
+pub fn mean(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// Summarizes the values one metric took over the measured runs.
+///
+/// Runs beyond Tukey's fences, 1.5 interquartile ranges outside the
+/// quartiles, are rejected first, so one run disturbed by another workload
+/// does not move the mean.
+///
+/// # Arguments
+///
+/// * `metric` - The metric, e.g. "L1 latency".
+/// * `unit` - The unit of the metric.
+/// * `values` - The value of every measured run.
+///
+/// # Returns
+///
+/// * `Option<MetricStatistics>` - The summary, or `None` without values.
+pub fn summarize(metric: &str, unit: &str, values: &[f64]) -> Option<MetricStatistics> {
+    let mut sorted: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    if sorted.is_empty() {
+        return None;
+    }
+    sorted.sort_by(f64::total_cmp);
+
+    let kept: Vec<f64> = if sorted.len() < MIN_RUNS_FOR_OUTLIERS {
+        sorted.clone()
+    } else {
+        let q1 = quantile(&sorted, 0.25);
+        let q3 = quantile(&sorted, 0.75);
+        let fence = TUKEY_FENCE * (q3 - q1);
+        sorted
+            .iter()
+            .copied()
+            .filter(|v| *v >= q1 - fence && *v <= q3 + fence)
+            .collect()
+    };
+
+    let runs = kept.len();
+    let mean = kept.iter().sum::<f64>() / runs as f64;
+    let (stddev, ci95) = if runs > 1 {
+        let variance = kept.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (runs - 1) as f64;
+        let stddev = variance.sqrt();
+        let critical = T_CRITICAL_95
+            .get(runs - 2)
+            .copied()
+            .unwrap_or(Z_CRITICAL_95);
+        (stddev, critical * stddev / (runs as f64).sqrt())
+    } else {
+        (0.0, 0.0)
+    };
+
+    Some(MetricStatistics {
+        metric: metric.to_string(),
+        unit: unit.to_string(),
+        runs,
+        outliers: sorted.len() - runs,
+        mean,
+        median: quantile(&kept, 0.5),
+        stddev,
+        ci95,
+    })
+}
+
+/// Summarizes every metric over the measured runs of a benchmark.
+///
+/// # Arguments
+///
+/// * `runs` - The measurements of every measured run.
+///
+/// # Returns
+///
+/// * `Vec<MetricStatistics>` - One summary per metric, in the order the metrics were first measured.
+pub fn summarize_runs(runs: &[Vec<Measurement>]) -> Vec<MetricStatistics> {
+    let mut metrics: Vec<(&str, &str, Vec<f64>)> = Vec::new();
+    for measurement in runs.iter().flatten() {
+        match metrics
+            .iter_mut()
+            .find(|(metric, _, _)| *metric == measurement.metric)
+        {
+            Some((_, _, values)) => values.push(measurement.value),
+            None => metrics.push((
+                &measurement.metric,
+                &measurement.unit,
+                vec![measurement.value],
+            )),
+        }
+    }
+    metrics
+        .iter()
+        .filter_map(|(metric, unit, values)| summarize(metric, unit, values))
+        .collect()
+}
+
+/// The stored result of a benchmark: its metrics and, when repeated, their statistics.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkRecord {
+    /// The benchmark, as named on the command line, e.g. "cache".
+    pub benchmark: String,
+
+    /// When the first run started, in RFC 3339 format.
+    pub started_at: String,
+
+    /// The warm-up runs discarded.
+    pub warmup: u32,
+
+    /// The runs measured.
+    pub repetitions: u32,
+
+    /// The metrics of the last measured run.
+    pub measurements: Vec<Measurement>,
+
+    /// The statistics of every metric over the measured runs; empty for a single run.
+    #[serde(default)]
+    pub statistics: Vec<MetricStatistics>,
+}
+
+impl BenchmarkRecord {
+    /// Returns the representative value of a metric: the median over the
+    /// measured runs when repeated, the value of the single run otherwise.
+    pub fn value(&self, metric: &str) -> Option<f64> {
+        self.statistics
+            .iter()
+            .find(|s| s.metric == metric)
+            .map(|s| s.median)
+            .or_else(|| {
+                self.measurements
+                    .iter()
+                    .find(|m| m.metric == metric)
+                    .map(|m| m.value)
+            })
+    }
+}
+
+/// Renders the summary of every metric over the measured runs of a benchmark.
+///
+/// # Arguments
+///
+/// * `plan` - How often the benchmark ran.
+/// * `statistics` - The summary of every metric.
+///
+/// # Returns
+///
+/// * `String` - One row per metric with its mean, median, spread and confidence interval.
+pub fn format_statistics(plan: &RepetitionPlan, statistics: &[MetricStatistics]) -> String {
+    let mut output = format!(
+        "Statistics over {} repetition(s) after {} warm-up run(s)\n",
+        plan.repetitions, plan.warmup
+    );
+    if statistics.is_empty() {
+        output.push_str("  No metrics were measured.\n");
+        return output;
+    }
+    let width = statistics
+        .iter()
+        .map(|s| s.metric.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Metric".len());
+    output.push_str(&format!(
+        "  {:<width$}  {:>12}  {:>12}  {:>8}  {:>14}  {:>5}  Unit\n",
+        "Metric",
+        "Mean",
+        "Median",
+        "Stddev",
+        "95% CI",
+        "Runs",
+        width = width
+    ));
+    for s in statistics {
+        output.push_str(&format!(
+            "  {:<width$}  {:>12.2}  {:>12.2}  {:>7.1}%  {:>14}  {:>5}  {}\n",
+            s.metric,
+            s.mean,
+            s.median,
+            s.relative_stddev_percent(),
+            format!("± {:.2}", s.ci95),
+            if s.outliers > 0 {
+                format!("{}-{}", s.runs + s.outliers, s.outliers)
+            } else {
+                s.runs.to_string()
+            },
+            s.unit,
+            width = width
+        ));
+    }
+    if statistics.iter().any(|s| s.outliers > 0) {
+        output.push_str("  Runs shown as N-M had M outliers rejected.\n");
+    }
+    output
+}
@@ -0,0 +1,442 @@
+//! Stress-ng Domain Entity
+//!
+//! This module provides a domain entity for stress-ng, a tool for stress testing
+//! CPU and memory on Linux and macOS systems.
+
+use std::collections::{BTreeMap, HashSet};
+
+use common::domain::job::Resource;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::plan::DiskWrites;
+
+/// Represents the stress-ng configuration.
+/// This struct is used to configure and manage the parameters for a stress-ng test.
+/// It includes settings for CPU load, memory load, test duration, and other options.
+pub struct StressNgConfig {
+    /// The desired CPU load as a percentage.
+    /// This value is used to set the amount of CPU stress to be applied during the test.
+    pub cpu_load: u32,
+
+    /// The desired memory load in megabytes.
+    /// This value is used to set the amount of memory stress to be applied during the test.
+    pub memory_load: u32,
+
+    /// The duration of the stress test in seconds.
+    /// This value defines how long the stress test will run.
+    pub timeout: u32,
+
+    /// Enables more aggressive file, cache, and memory stress options.
+    /// When set to true, the test may apply more intense stress on the system.
+    pub aggressive: bool,
+
+    /// Keeps the process names the same as the parent process.
+    /// This option can be useful for logging or monitoring purposes.
+    pub keep_name: bool,
+
+    /// Outputs messages without the program name, message type, and process ID.
+    /// This option simplifies the log output for easier parsing or reading.
+    pub log_brief: bool,
+
+    /// Sets CPU affinity based on the list of CPUs provided.
+    /// This option allows specifying which CPUs should be stressed, providing more control over the test.
+    pub taskset: Option<HashSet<u32>>,
+}
+
+impl StressNgConfig {
+    /// Creates a new stress-ng configuration with the specified parameters.
+    ///
+    /// This constructor initializes a `StressNgConfig` instance with detailed settings
+    /// for running a stress test. Each parameter can be customized to tailor the stress test
+    /// to specific needs or conditions.
+    ///
+    /// # Arguments
+    ///
+    /// * `cpu_load` - CPU load percentage.
+    /// * `memory_load` - Memory load in megabytes.
+    /// * `timeout` - Duration of the test in seconds.
+    /// * `aggressive` - Enable more aggressive test options.
+    /// * `keep_name` - Keep process names unchanged.
+    /// * `log_brief` - Simplify log output format.
+    /// * `taskset` - Set specific CPU affinity for the test.
+    ///
+    /// # Returns
+    ///
+    /// * `StressNgConfig` - A new instance with the provided configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oneforall_core::domain::stress_ng::StressNgConfig;
+    ///
+    /// let config = StressNgConfig::new(50, 1024, 60, true, false, true, None);
+    /// // Use `config` to run a stress-ng test
+    /// ```
+    pub fn new(
+        cpu_load: u32, //
+        memory_load: u32,
+        timeout: u32,
+        aggressive: bool,
+        keep_name: bool,
+        log_brief: bool,
+        taskset: Option<HashSet<u32>>,
+    ) -> Self {
+        // Implement the `new` method for `StressNgConfig`
+        Self {
+            // Return a new instance of `StressNgConfig` with the specified parameters
+            cpu_load,
+            memory_load,
+            timeout,
+            aggressive,
+            keep_name,
+            log_brief,
+            taskset,
+        }
+    }
+
+    // Additional methods to run the stress tests can be added here...
+}
+
+/// The way a stress-ng run ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StressRunOutcome {
+    /// stress-ng ran for its full duration and exited.
+    Completed,
+
+    /// The run was cancelled and stress-ng was stopped.
+    Cancelled,
+}
+
+/// Reads the bogo-ops of each stressor from the `--metrics-brief` table of a stress-ng run.
+///
+/// Metrics lines look like `stress-ng: metrc: [1234] cpu  123456  60.00 ...`,
+/// where the second column after the PID is the bogo-op count of a stressor.
+///
+/// # Arguments
+///
+/// * `output` - The captured stress-ng output.
+///
+/// # Returns
+///
+/// * `Vec<(String, u64)>` - The stressor names and their bogo-ops, in report order.
+pub fn parse_stressor_bogo_ops(output: &str) -> Vec<(String, u64)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (stressor, mut fields) = metrics_row(line)?;
+            Some((stressor.to_string(), fields.next()?.parse().ok()?))
+        })
+        .collect()
+}
+
+/// Splits a `--metrics-brief` line into its stressor name and remaining columns.
+fn metrics_row(line: &str) -> Option<(&str, std::str::SplitWhitespace<'_>)> {
+    let (_, metrics) = line.split_once("] ")?;
+    let mut fields = metrics.split_whitespace();
+    let stressor = fields.next()?;
+    if !stressor
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return None;
+    }
+    Some((stressor, fields))
+}
+
+/// Sums the bogo-ops reported by the `--metrics-brief` table of a stress-ng run.
+///
+/// # Arguments
+///
+/// * `output` - The captured stress-ng output.
+///
+/// # Returns
+///
+/// * `Option<u64>` - The total bogo-ops, or `None` if no metrics were found.
+pub fn parse_bogo_ops(output: &str) -> Option<u64> {
+    let counts = parse_stressor_bogo_ops(output);
+    if counts.is_empty() {
+        None
+    } else {
+        Some(counts.iter().map(|(_, ops)| ops).sum())
+    }
+}
+
+/// Sums the bogo-ops rates reported by the `--metrics-brief` table of a stress-ng run.
+///
+/// stress-ng measures each rate over the real time its workers ran, in the
+/// sixth column, so unlike a rate taken over the wall time of the command it
+/// leaves out process startup and the wait for workers to exit.
+///
+/// # Arguments
+///
+/// * `output` - The captured stress-ng output.
+///
+/// # Returns
+///
+/// * `Option<f64>` - The bogo-ops per second, or `None` if no metrics were found.
+pub fn parse_bogo_ops_rate(output: &str) -> Option<f64> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (_, mut fields) = metrics_row(line)?;
+            fields.nth(4)?.parse::<f64>().ok()
+        })
+        .reduce(|a, b| a + b)
+}
+
+/// Returns the machine resource a stress-ng stressor mainly loads, which decides
+/// the workloads it may run alongside.
+///
+/// # Arguments
+///
+/// * `stressor` - The stressor name, e.g. "cpu", "vm" or "hdd".
+///
+/// # Returns
+///
+/// * `Resource` - The loaded resource; unknown stressors are assumed to load the CPU.
+pub fn stressor_resource(stressor: &str) -> Resource {
+    match stressor {
+        "vm" | "bigheap" | "malloc" | "memcpy" | "memrate" | "mmap" | "stream" => Resource::Memory,
+        "hdd" | "aio" | "fallocate" | "iomix" | "readahead" | "seek" | "sync-file" => {
+            Resource::Disk
+        }
+        "sock" | "icmp-flood" | "netdev" | "sctp" | "udp" | "udp-flood" => Resource::Network,
+        "gpu" => Resource::Gpu,
+        _ => Resource::Cpu,
+    }
+}
+
+/// The file each stress-ng `--hdd` worker writes and rewrites, by default.
+pub const HDD_BYTES_PER_WORKER: u64 = 1024 * 1024 * 1024;
+
+/// Returns the data a stress-ng stressor writes to disk, for `--dry-run`.
+///
+/// # Arguments
+///
+/// * `stressor` - The stressor name, e.g. "cpu" or "hdd".
+/// * `workers` - The number of workers started.
+///
+/// # Returns
+///
+/// * `DiskWrites` - The file of each `hdd` worker rewritten for the duration, an unknown
+///   volume for the other disk stressors, and nothing for the rest.
+pub fn stressor_disk_writes(stressor: &str, workers: u64) -> DiskWrites {
+    match (stressor, stressor_resource(stressor)) {
+        ("hdd", _) => DiskWrites::Sustained(HDD_BYTES_PER_WORKER * workers),
+        (_, Resource::Disk) => DiskWrites::Unknown,
+        _ => DiskWrites::None,
+    }
+}
+
+/// An option of a stress-ng stressor, as listed by `stress-ng --help`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StressorOption {
+    /// The option, e.g. "--cpu-method".
+    pub flag: String,
+
+    /// The placeholder of the value it takes, e.g. "M", if it takes one.
+    pub argument: Option<String>,
+
+    /// What the option does.
+    pub description: String,
+}
+
+/// The stressors a stress-ng binary offers on this platform, and their options.
+///
+/// Which stressors exist depends on the stress-ng version and on what it was
+/// built with: a build without libaio has no `aio` stressor, a macOS build no
+/// Linux-only stressors. The catalog is read from the binary itself, so a
+/// stressor chosen in a profile can be checked before the run instead of
+/// stress-ng failing on it hours into a qualification.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StressorCatalog {
+    /// The stress-ng executable the catalog was read from.
+    pub binary: String,
+
+    /// The stress-ng version, e.g. "0.17.05".
+    pub version: String,
+
+    /// The options of each stressor, by stressor name.
+    pub stressors: BTreeMap<String, Vec<StressorOption>>,
+}
+
+impl StressorCatalog {
+    /// Reads the catalog from the output of a stress-ng binary.
+    ///
+    /// `--stressors` lists the stressor names on one line. `--help` lists every
+    /// option as `      --cpu-method M   specify stress cpu method M`, and an
+    /// option belongs to the stressor its name starts with, the longest one when
+    /// several do, so `--vm-addr-method` goes to `vm-addr` and `--vm-bytes` to `vm`.
+    ///
+    /// # Arguments
+    ///
+    /// * `binary` - The stress-ng executable the output came from.
+    /// * `version` - The stress-ng version, as read by `parse_stress_ng_version`.
+    /// * `stressors` - The output of `stress-ng --stressors`.
+    /// * `help` - The output of `stress-ng --help`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<StressorCatalog, String>` - The catalog, or an error if no stressor is listed.
+    pub fn parse(
+        binary: &str,
+        version: &str,
+        stressors: &str,
+        help: &str,
+    ) -> Result<StressorCatalog, String> {
+        let mut catalog = StressorCatalog {
+            binary: binary.to_string(),
+            version: version.to_string(),
+            stressors: stressors
+                .split_whitespace()
+                .map(|name| (name.to_string(), Vec::new()))
+                .collect(),
+        };
+        if catalog.stressors.is_empty() {
+            return Err("stress-ng --stressors listed no stressor".to_string());
+        }
+
+        for line in help.lines() {
+            let Some(start) = line.find("--") else {
+                continue;
+            };
+            if !line.trim_start().starts_with('-') {
+                continue;
+            }
+            let (spec, description) = line[start..]
+                .split_once("  ")
+                .unwrap_or((&line[start..], ""));
+            let mut spec = spec.split_whitespace();
+            let Some(flag) = spec.next().map(|flag| flag.trim_end_matches(',')) else {
+                continue;
+            };
+            let name = flag.trim_start_matches('-');
+            let owner = catalog
+                .stressors
+                .keys()
+                .filter(|stressor| {
+                    name == stressor.as_str()
+                        || name
+                            .strip_prefix(stressor.as_str())
+                            .is_some_and(|rest| rest.starts_with('-'))
+                })
+                .max_by_key(|stressor| stressor.len())
+                .cloned();
+            if let Some(owner) = owner {
+                let options = catalog.stressors.entry(owner).or_default();
+                if !options.iter().any(|option| option.flag == flag) {
+                    options.push(StressorOption {
+                        flag: flag.to_string(),
+                        argument: spec.next().map(str::to_string),
+                        description: description.trim().to_string(),
+                    });
+                }
+            }
+        }
+        Ok(catalog)
+    }
+
+    /// Returns `true` if the binary offers the stressor.
+    pub fn contains(&self, stressor: &str) -> bool {
+        self.stressors.contains_key(stressor)
+    }
+
+    /// Checks that the binary offers a stressor, suggesting the closest names if not.
+    ///
+    /// # Arguments
+    ///
+    /// * `stressor` - The stressor name, e.g. "cpu".
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), String>` - An error naming the stressors it may have been meant to be.
+    pub fn check_stressor(&self, stressor: &str) -> Result<(), String> {
+        if self.contains(stressor) {
+            return Ok(());
+        }
+        let mut close: Vec<(usize, &String)> = self
+            .stressors
+            .keys()
+            .map(|name| (edit_distance(stressor, name), name))
+            .filter(|(distance, name)| {
+                *distance <= 2 || name.starts_with(stressor) || stressor.starts_with(name.as_str())
+            })
+            .collect();
+        close.sort();
+        let mut message = format!(
+            "stress-ng {} has no stressor '{}' on this platform",
+            self.version, stressor
+        );
+        if close.is_empty() {
+            message.push('.');
+        } else {
+            message.push_str(&format!(
+                "; did you mean {}?",
+                close
+                    .iter()
+                    .take(3)
+                    .map(|(_, name)| name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        message.push_str(" Run `oneforall stress --list` for the stressors available");
+        Err(message)
+    }
+
+    /// Renders the stressors and their options as plain text suitable for the terminal.
+    pub fn render(&self) -> String {
+        let mut output = format!(
+            "stress-ng {} ({}): {} stressors\n",
+            self.version,
+            self.binary,
+            self.stressors.len()
+        );
+        for (name, options) in &self.stressors {
+            output.push_str(&format!("\n{}\n", name));
+            for option in options {
+                let spec = match &option.argument {
+                    Some(argument) => format!("{} {}", option.flag, argument),
+                    None => option.flag.clone(),
+                };
+                output.push_str(&format!("  {:<28} {}\n", spec, option.description));
+            }
+        }
+        output
+    }
+}
+
+/// Reads the version number from the output of `stress-ng --version`, e.g.
+/// "0.17.05" from `stress-ng, version 0.17.05 (gcc 13.2, x86_64 Linux 6.5.0)`.
+///
+/// # Arguments
+///
+/// * `output` - The output of `stress-ng --version`.
+///
+/// # Returns
+///
+/// * `String` - The version number, or the first line of the output if it has none.
+pub fn parse_stress_ng_version(output: &str) -> String {
+    let first_line = output.lines().next().unwrap_or_default().trim();
+    first_line
+        .split_whitespace()
+        .skip_while(|word| *word != "version")
+        .nth(1)
+        .unwrap_or(first_line)
+        .to_string()
+}
+
+/// Returns the number of single-character edits turning one name into another.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
@@ -0,0 +1,127 @@
+//! System Statistics Domain Entity
+//!
+//! This module provides domain entities for the live system statistics shown
+//! by Overwatch: per-core CPU utilization and steal time, memory usage, and
+//! the processes consuming the most CPU.
+
+/// Cumulative CPU time counters of a single logical CPU, in clock ticks.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CpuTimes {
+    /// Ticks spent idle, including waiting for I/O.
+    pub idle: u64,
+
+    /// Ticks the hypervisor ran another guest while this virtual CPU was runnable.
+    pub steal: u64,
+
+    /// Ticks spent in every state.
+    pub total: u64,
+}
+
+/// The utilization of a single logical CPU over a sampling interval.
+#[derive(Debug, Clone)]
+pub struct CoreUsage {
+    /// The logical CPU ID.
+    pub cpu: u32,
+
+    /// The share of the interval the CPU was busy, in percent.
+    pub percent: f64,
+
+    /// The share of the interval the hypervisor withheld the CPU, in percent.
+    pub steal_percent: f64,
+}
+
+/// Converts two successive CPU time readings into per-core utilization.
+///
+/// # Arguments
+///
+/// * `before` - The earlier readings, keyed by logical CPU ID.
+/// * `after` - The later readings, keyed by logical CPU ID.
+///
+/// # Returns
+///
+/// * `Vec<CoreUsage>` - The utilization of every CPU present in both readings.
+pub fn core_usage_between(before: &[(u32, CpuTimes)], after: &[(u32, CpuTimes)]) -> Vec<CoreUsage> {
+    after
+        .iter()
+        .filter_map(|(cpu, end)| {
+            let (_, start) = before.iter().find(|(c, _)| c == cpu)?;
+            let total = end.total.saturating_sub(start.total);
+            let idle = end.idle.saturating_sub(start.idle);
+            let steal = end.steal.saturating_sub(start.steal);
+            let (percent, steal_percent) = if total == 0 {
+                (0.0, 0.0)
+            } else {
+                (
+                    (total - idle.min(total)) as f64 * 100.0 / total as f64,
+                    steal.min(total) as f64 * 100.0 / total as f64,
+                )
+            };
+            Some(CoreUsage {
+                cpu: *cpu,
+                percent,
+                steal_percent,
+            })
+        })
+        .collect()
+}
+
+/// Returns the share of CPU time stolen by the hypervisor since boot, across all CPUs.
+///
+/// # Arguments
+///
+/// * `times` - The cumulative CPU time counters of every logical CPU.
+///
+/// # Returns
+///
+/// * `f64` - The stolen share of the CPU time of every CPU together, in percent.
+pub fn steal_percent_since_boot(times: &[(u32, CpuTimes)]) -> f64 {
+    let total: u64 = times.iter().map(|(_, t)| t.total).sum();
+    let steal: u64 = times.iter().map(|(_, t)| t.steal).sum();
+    if total == 0 {
+        0.0
+    } else {
+        steal.min(total) as f64 * 100.0 / total as f64
+    }
+}
+
+/// The memory usage of the system.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MemoryUsage {
+    /// The total memory in kilobytes.
+    pub total_kb: u64,
+
+    /// The memory available for new allocations in kilobytes.
+    pub available_kb: u64,
+}
+
+impl MemoryUsage {
+    /// Returns the memory in use, in kilobytes.
+    pub fn used_kb(&self) -> u64 {
+        self.total_kb.saturating_sub(self.available_kb)
+    }
+
+    /// Returns the share of memory in use, in percent.
+    pub fn used_percent(&self) -> f64 {
+        if self.total_kb == 0 {
+            0.0
+        } else {
+            self.used_kb() as f64 * 100.0 / self.total_kb as f64
+        }
+    }
+}
+
+/// The resource usage of a single process.
+#[derive(Debug, Clone)]
+pub struct ProcessUsage {
+    /// The process ID.
+    pub pid: u32,
+
+    /// The CPU usage in percent of one CPU.
+    pub cpu_percent: f64,
+
+    /// The share of physical memory used, in percent.
+    pub memory_percent: f64,
+
+    /// The command name.
+    pub command: String,
+}
@@ -0,0 +1,68 @@
+//! Tags Domain Entity
+//!
+//! This module provides the tags attached to runs with `--tag key=value`, such
+//! as the rack, asset ID, firmware version or operator of a machine under test.
+//! Tags are stored in the `tags` object of each result, so the results of one
+//! rack or firmware can be picked out once hundreds of runs have accumulated.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+/// The field of a stored result holding its tags.
+pub const TAGS_FIELD: &str = "tags";
+
+/// Tags by key, e.g. "rack" to "r12".
+pub type Tags = BTreeMap<String, String>;
+
+/// Parses a tag written as `key=value`, e.g. "asset=SN-4411".
+///
+/// # Arguments
+///
+/// * `text` - The tag; keys are made of letters, digits, '-', '_' and '.'.
+///
+/// # Returns
+///
+/// * `Result<(String, String), String>` - The key and value, or an error message.
+pub fn parse_tag(text: &str) -> Result<(String, String), String> {
+    let (key, value) = text
+        .split_once('=')
+        .ok_or_else(|| format!("Invalid tag '{}'; expected key=value", text))?;
+    let key = key.trim();
+    if key.is_empty()
+        || !key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        return Err(format!(
+            "Invalid tag key '{}'; use letters, digits, '-', '_' and '.'",
+            key
+        ));
+    }
+    Ok((key.to_string(), value.trim().to_string()))
+}
+
+/// Returns a result as JSON with tags added to it; results that are not JSON
+/// objects, or have no tags, are returned unchanged.
+pub fn tagged<T: Serialize>(result: &T, tags: &Tags) -> Result<serde_json::Value, String> {
+    let mut value = serde_json::to_value(result).map_err(|e| e.to_string())?;
+    if let (Some(object), false) = (value.as_object_mut(), tags.is_empty()) {
+        let tags = tags
+            .iter()
+            .map(|(key, value)| (key.clone(), serde_json::Value::from(value.as_str())))
+            .collect();
+        object.insert(TAGS_FIELD.to_string(), serde_json::Value::Object(tags));
+    }
+    Ok(value)
+}
+
+/// Returns whether a stored result carries every tag of a filter.
+pub fn has_tags(result: &serde_json::Value, filter: &Tags) -> bool {
+    filter.iter().all(|(key, value)| {
+        result
+            .get(TAGS_FIELD)
+            .and_then(|tags| tags.get(key))
+            .and_then(|tag| tag.as_str())
+            == Some(value.as_str())
+    })
+}
@@ -0,0 +1,230 @@
+//! Thermal Domain Entity
+//!
+//! This module provides domain entities for fan speed and temperature readings
+//! and the thermal report attached to stress test results, which correlates
+//! fan response with temperature ramps and flags fans that fail the usual
+//! acceptance checks.
+
+use std::time::Duration;
+
+use common::domain::metric::Metric;
+
+/// Minimum temperature rise, in °C, after which every fan is expected to respond.
+const EXPECTED_RAMP_CELSIUS: f64 = 10.0;
+
+/// Minimum relative RPM increase that counts as a fan spinning up.
+const SPIN_UP_RATIO: f64 = 0.10;
+
+/// Fraction of the rated maximum above which a fan is considered pegged.
+const PEGGED_RATIO: f64 = 0.95;
+
+/// The speed of a single fan.
+#[derive(Debug, Clone)]
+pub struct FanReading {
+    /// The fan name, e.g. "nct6775/fan2" or "FAN1".
+    pub name: String,
+
+    /// The current speed in RPM.
+    pub rpm: u32,
+
+    /// The rated maximum speed in RPM, if the sensor reports one.
+    pub max_rpm: Option<u32>,
+}
+
+/// The reading of a single temperature sensor.
+#[derive(Debug, Clone)]
+pub struct TemperatureReading {
+    /// The sensor name, e.g. "coretemp/Package id 0" or "CPU Temp".
+    pub name: String,
+
+    /// The temperature in degrees Celsius.
+    pub celsius: f64,
+}
+
+/// One reading of every fan and temperature sensor.
+#[derive(Debug, Clone, Default)]
+pub struct ThermalSample {
+    /// The fan readings.
+    pub fans: Vec<FanReading>,
+
+    /// The temperature readings.
+    pub temperatures: Vec<TemperatureReading>,
+}
+
+impl ThermalSample {
+    /// Returns the hottest temperature of the sample.
+    pub fn hottest(&self) -> Option<f64> {
+        self.temperatures
+            .iter()
+            .map(|t| t.celsius)
+            .fold(None, |max, c| Some(max.map_or(c, |m: f64| m.max(c))))
+    }
+
+    /// Returns the sample as metrics: `temperature_celsius` by sensor and `fan_rpm` by fan.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp` - The local time the sample was taken, in RFC 3339 format.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Metric>` - One metric per sensor and fan.
+    pub fn metrics(&self, timestamp: &str) -> Vec<Metric> {
+        let temperatures = self.temperatures.iter().map(|t| {
+            Metric::new("temperature_celsius", "celsius", t.celsius, timestamp)
+                .with_label("sensor", &t.name)
+        });
+        let fans = self.fans.iter().map(|f| {
+            Metric::new("fan_rpm", "rpm", f.rpm as f64, timestamp).with_label("fan", &f.name)
+        });
+        temperatures.chain(fans).collect()
+    }
+}
+
+/// Returns the Pearson correlation coefficient of two equally long series.
+fn correlation(xs: &[f64], ys: &[f64]) -> Option<f64> {
+    let n = xs.len().min(ys.len());
+    if n < 2 {
+        return None;
+    }
+    let mean_x = xs[..n].iter().sum::<f64>() / n as f64;
+    let mean_y = ys[..n].iter().sum::<f64>() / n as f64;
+
+    let mut covariance = 0.0;
+    let mut variance_x = 0.0;
+    let mut variance_y = 0.0;
+    for i in 0..n {
+        let dx = xs[i] - mean_x;
+        let dy = ys[i] - mean_y;
+        covariance += dx * dy;
+        variance_x += dx * dx;
+        variance_y += dy * dy;
+    }
+
+    if variance_x == 0.0 || variance_y == 0.0 {
+        None
+    } else {
+        Some(covariance / (variance_x.sqrt() * variance_y.sqrt()))
+    }
+}
+
+/// Classifies the response of a fan over a run.
+///
+/// # Arguments
+///
+/// * `rpms` - The fan speed of every sample.
+/// * `max_rpm` - The rated maximum speed, if known.
+/// * `temperature_rise` - The rise of the hottest temperature over the run.
+///
+/// # Returns
+///
+/// * `&'static str` - "OK", or the acceptance check the fan failed.
+fn fan_verdict(rpms: &[f64], max_rpm: Option<u32>, temperature_rise: f64) -> &'static str {
+    let lowest = rpms.iter().cloned().fold(f64::INFINITY, f64::min);
+    let highest = rpms.iter().cloned().fold(0.0, f64::max);
+    let first = rpms.first().copied().unwrap_or(0.0);
+
+    if highest == 0.0 {
+        return "STOPPED";
+    }
+    if let Some(max_rpm) = max_rpm {
+        if max_rpm > 0 && lowest >= max_rpm as f64 * PEGGED_RATIO {
+            return "PEGGED";
+        }
+    }
+    if temperature_rise >= EXPECTED_RAMP_CELSIUS && highest < first * (1.0 + SPIN_UP_RATIO) {
+        return "NO SPIN-UP";
+    }
+    "OK"
+}
+
+/// Formats the thermal report of a run.
+///
+/// # Arguments
+///
+/// * `samples` - Thermal samples with their offset into the run.
+///
+/// # Returns
+///
+/// * `String` - The rendered report.
+pub fn format_thermal_report(samples: &[(Duration, ThermalSample)]) -> String {
+    let has_fans = samples.iter().any(|(_, s)| !s.fans.is_empty());
+    let has_temperatures = samples.iter().any(|(_, s)| !s.temperatures.is_empty());
+    if !has_fans && !has_temperatures {
+        return "Fans and temperatures: no hwmon or BMC sensors available.\n".to_string();
+    }
+
+    let hottest: Vec<f64> = samples
+        .iter()
+        .map(|(_, s)| s.hottest().unwrap_or(0.0))
+        .collect();
+
+    let mut output = String::from("Thermal timeline\n");
+    output.push_str("  Elapsed   Hottest °C   Avg fan RPM\n");
+    for ((offset, sample), temperature) in samples.iter().zip(&hottest) {
+        let average_rpm = if sample.fans.is_empty() {
+            "-".to_string()
+        } else {
+            let total: u64 = sample.fans.iter().map(|f| f.rpm as u64).sum();
+            (total / sample.fans.len() as u64).to_string()
+        };
+        output.push_str(&format!(
+            "  {:>6.0}s   {:>10.1}   {:>11}\n",
+            offset.as_secs_f64(),
+            temperature,
+            average_rpm
+        ));
+    }
+
+    if !has_fans {
+        output.push_str("\nFan response: no fan sensors available.\n");
+        return output;
+    }
+
+    let temperature_rise = match (hottest.first(), hottest.iter().cloned().reduce(f64::max)) {
+        (Some(first), Some(peak)) => peak - first,
+        _ => 0.0,
+    };
+
+    // Fans are matched by name since sensors may disappear between samples.
+    let mut names: Vec<&str> = samples
+        .iter()
+        .flat_map(|(_, s)| s.fans.iter().map(|f| f.name.as_str()))
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+
+    output.push_str(&format!(
+        "\nFan response (hottest sensor rose {:.1} °C)\n",
+        temperature_rise
+    ));
+    output.push_str("  Fan                      Min RPM   Max RPM   Temp corr.   Status\n");
+    for name in names {
+        let mut rpms = Vec::new();
+        let mut temperatures = Vec::new();
+        let mut max_rpm = None;
+        for ((_, sample), temperature) in samples.iter().zip(&hottest) {
+            if let Some(fan) = sample.fans.iter().find(|f| f.name == name) {
+                rpms.push(fan.rpm as f64);
+                temperatures.push(*temperature);
+                max_rpm = max_rpm.or(fan.max_rpm);
+            }
+        }
+
+        let lowest = rpms.iter().cloned().fold(f64::INFINITY, f64::min);
+        let highest = rpms.iter().cloned().fold(0.0, f64::max);
+        let corr = correlation(&temperatures, &rpms)
+            .map(|r| format!("{:+.2}", r))
+            .unwrap_or_else(|| "-".to_string());
+        output.push_str(&format!(
+            "  {:<24} {:>7.0}   {:>7.0}   {:>10}   {}\n",
+            name,
+            lowest,
+            highest,
+            corr,
+            fan_verdict(&rpms, max_rpm, temperature_rise)
+        ));
+    }
+
+    output
+}
@@ -0,0 +1,283 @@
+//! Time Synchronization Domain Entity
+//!
+//! This module provides what the results of several nodes need to be lined up
+//! by time. Before a run starts, the offset of the system clock from its NTP or
+//! PTP reference is checked against the `[time_sync]` section of
+//! `oneforall.toml` and recorded with every result, so readings from different
+//! machines are only correlated as closely as their clocks agree.
+//!
+//! Every collector reading also carries two timestamps: the wall-clock time,
+//! for correlating across machines, and the monotonic clock of its boot, which
+//! a step of the wall clock does not move. Readings are ordered by wall-clock
+//! time as long as it advances with the monotonic clock; when it jumps back,
+//! they are ordered by the monotonic clock until the wall clock catches up, so
+//! a clock correction never reorders the time-series.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::profile::deserialize_duration;
+use crate::domain::tags::Tags;
+
+/// The offset from the reference clock a run starts with when no limit is configured.
+pub const DEFAULT_MAX_CLOCK_OFFSET: Duration = Duration::from_millis(100);
+
+/// How far the wall clock may drift from the monotonic clock between two
+/// readings before it counts as a jump, in seconds.
+pub const CLOCK_JUMP_TOLERANCE_SECS: f64 = 1.0;
+
+/// The configuration file; sections other than `[time_sync]` are left to their own readers.
+#[derive(Debug, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    time_sync: TimeSyncConfig,
+}
+
+/// How closely the clock must follow its reference for a run to start.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TimeSyncConfig {
+    /// The largest offset from the reference clock a run starts with, e.g. "10ms".
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub max_offset: Option<Duration>,
+
+    /// Whether a run refuses to start while the clock is not synchronized or is
+    /// further off than `max_offset`, rather than only warning.
+    #[serde(default)]
+    pub required: bool,
+}
+
+impl TimeSyncConfig {
+    /// Parses the `[time_sync]` section of a configuration file.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The TOML text of the configuration file.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<TimeSyncConfig, String>` - The settings, or why they are invalid.
+    pub fn from_toml(text: &str) -> Result<TimeSyncConfig, String> {
+        let file: ConfigFile = toml::from_str(text).map_err(|e| e.to_string())?;
+        Ok(file.time_sync)
+    }
+
+    /// Returns the largest offset from the reference clock a run starts with.
+    pub fn max_offset(&self) -> Duration {
+        self.max_offset.unwrap_or(DEFAULT_MAX_CLOCK_OFFSET)
+    }
+}
+
+/// The synchronization of the system clock with its reference.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClockSync {
+    /// What keeps the clock synchronized, e.g. "ptp4l", "chrony" or "kernel".
+    pub source: String,
+
+    /// Whether the clock is synchronized to its reference.
+    pub synchronized: bool,
+
+    /// The offset of the clock from its reference, in seconds, if reported.
+    pub offset_secs: Option<f64>,
+
+    /// The largest error of the clock the source estimates, in seconds, if reported.
+    pub max_error_secs: Option<f64>,
+
+    /// The reference the clock follows, e.g. an NTP server or a PTP grandmaster.
+    pub reference: Option<String>,
+}
+
+impl ClockSync {
+    /// Returns how far the clock may be from its reference: the offset when
+    /// reported, otherwise the estimated error.
+    pub fn uncertainty_secs(&self) -> Option<f64> {
+        self.offset_secs.map(f64::abs).or(self.max_error_secs)
+    }
+
+    /// Checks the clock against the configured limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The `[time_sync]` settings.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), String>` - Whether the clock is close enough, or why not.
+    pub fn check(&self, config: &TimeSyncConfig) -> Result<(), String> {
+        if !self.synchronized {
+            return Err(format!(
+                "The clock is not synchronized ({}); timestamps cannot be correlated with other machines",
+                self.source
+            ));
+        }
+        let max_offset = config.max_offset().as_secs_f64();
+        match self.uncertainty_secs() {
+            Some(uncertainty) if uncertainty > max_offset => Err(format!(
+                "The clock is {:.3} ms off its reference ({}), more than the {:.3} ms allowed",
+                uncertainty * 1e3,
+                self.source,
+                max_offset * 1e3
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the tags recording the clock synchronization with each result.
+    pub fn tags(&self) -> Tags {
+        let mut tags = Tags::new();
+        tags.insert("time_source".to_string(), self.source.clone());
+        let offset = match self.uncertainty_secs() {
+            _ if !self.synchronized => "unsynchronized".to_string(),
+            Some(uncertainty) => format!("{:.0}", uncertainty * 1e6),
+            None => "unknown".to_string(),
+        };
+        tags.insert("clock_offset_us".to_string(), offset);
+        tags
+    }
+
+    /// Describes the synchronization on one line, e.g. for the log.
+    pub fn render_line(&self) -> String {
+        let mut line = format!(
+            "{} {}",
+            self.source,
+            if self.synchronized {
+                "synchronized"
+            } else {
+                "not synchronized"
+            }
+        );
+        if let Some(reference) = &self.reference {
+            line.push_str(&format!(" to {}", reference));
+        }
+        if let Some(offset) = self.offset_secs {
+            line.push_str(&format!(", offset {:+.3} ms", offset * 1e3));
+        }
+        if let Some(max_error) = self.max_error_secs {
+            line.push_str(&format!(", max error {:.3} ms", max_error * 1e3));
+        }
+        line
+    }
+}
+
+/// The two timestamps of a collector reading.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SampleTime {
+    /// The wall-clock time the reading was taken, in RFC 3339 format.
+    pub wall: String,
+
+    /// The monotonic clock when the reading was taken, in nanoseconds since boot.
+    pub monotonic_ns: u64,
+
+    /// The boot the monotonic clock counts from, from `/proc/sys/kernel/random/boot_id`.
+    pub boot_id: String,
+}
+
+/// A clock jump noticed between two readings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClockJump {
+    /// How far the wall clock moved beyond the monotonic clock, in seconds;
+    /// negative when it was set back.
+    pub secs: f64,
+}
+
+/// Orders readings by time in spite of steps of the wall clock.
+#[derive(Debug, Clone, Default)]
+pub struct SampleClock {
+    // The time the last reading was ordered at, its wall-clock time and its monotonic time.
+    last: Option<(DateTime<Utc>, DateTime<Utc>, u64)>,
+}
+
+impl SampleClock {
+    /// Returns the time a reading is ordered at: its wall-clock time, unless the
+    /// wall clock was set back, in which case the time of the previous reading
+    /// advanced by the monotonic clock.
+    ///
+    /// # Arguments
+    ///
+    /// * `wall` - The wall-clock time the reading was taken.
+    /// * `monotonic_ns` - The monotonic clock when the reading was taken.
+    ///
+    /// # Returns
+    ///
+    /// * `(DateTime<Utc>, Option<ClockJump>)` - The time to order the reading at,
+    ///   and the jump of the wall clock since the previous reading, if any.
+    pub fn order(
+        &mut self,
+        wall: DateTime<Utc>,
+        monotonic_ns: u64,
+    ) -> (DateTime<Utc>, Option<ClockJump>) {
+        let Some((last_ordered, last_wall, last_monotonic_ns)) = self.last else {
+            self.last = Some((wall, wall, monotonic_ns));
+            return (wall, None);
+        };
+        let elapsed =
+            chrono::Duration::nanoseconds(monotonic_ns.saturating_sub(last_monotonic_ns) as i64);
+        let drift = (wall - last_wall - elapsed).num_microseconds().unwrap_or(0) as f64 / 1e6;
+        let ordered = wall.max(last_ordered + elapsed);
+        self.last = Some((ordered, wall, monotonic_ns));
+        let jump = (drift.abs() > CLOCK_JUMP_TOLERANCE_SECS).then_some(ClockJump { secs: drift });
+        (ordered, jump)
+    }
+}
+
+/// Parses the output of `chronyc -c tracking`.
+///
+/// # Arguments
+///
+/// * `csv` - The comma-separated tracking report: reference ID, reference
+///   name, stratum, reference time, system time offset, ..., root delay, root
+///   dispersion, update interval and leap status.
+///
+/// # Returns
+///
+/// * `Option<ClockSync>` - The synchronization, or `None` if the output is not a tracking report.
+pub fn parse_chrony_tracking(csv: &str) -> Option<ClockSync> {
+    let fields: Vec<&str> = csv.trim().split(',').collect();
+    if fields.len() < 14 {
+        return None;
+    }
+    let stratum: u32 = fields[2].parse().ok()?;
+    let offset_secs: f64 = fields[4].parse().ok()?;
+    let root_delay: Option<f64> = fields[10].parse().ok();
+    let root_dispersion: Option<f64> = fields[11].parse().ok();
+    Some(ClockSync {
+        source: "chrony".to_string(),
+        synchronized: fields[13] != "Not synchronised" && stratum > 0 && stratum < 16,
+        offset_secs: Some(offset_secs),
+        // chrony bounds the error of the clock by the root dispersion and half the root delay.
+        max_error_secs: root_delay
+            .zip(root_dispersion)
+            .map(|(delay, dispersion)| dispersion + delay / 2.0),
+        reference: Some(fields[1].to_string()).filter(|name| !name.is_empty()),
+    })
+}
+
+/// Parses the answer of ptp4l to `pmc -u -b 0 'GET TIME_STATUS_NP'`.
+///
+/// # Arguments
+///
+/// * `text` - The output of pmc, with `master_offset` in nanoseconds, `gmPresent` and `gmIdentity`.
+///
+/// # Returns
+///
+/// * `Option<ClockSync>` - The synchronization of the PTP hardware clock, or
+///   `None` if ptp4l did not answer.
+pub fn parse_pmc_time_status(text: &str) -> Option<ClockSync> {
+    let field = |name: &str| {
+        text.lines().find_map(|line| {
+            let mut words = line.split_whitespace();
+            (words.next() == Some(name)).then(|| words.next()).flatten()
+        })
+    };
+    let offset_ns: f64 = field("master_offset")?.parse().ok()?;
+    let grandmaster_present = field("gmPresent") == Some("true");
+    Some(ClockSync {
+        source: "ptp4l".to_string(),
+        synchronized: grandmaster_present,
+        offset_secs: Some(offset_ns / 1e9),
+        max_error_secs: None,
+        reference: field("gmIdentity").map(|identity| format!("grandmaster {}", identity)),
+    })
+}
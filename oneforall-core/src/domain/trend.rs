@@ -0,0 +1,448 @@
+//! Trend Domain Entity
+//!
+//! This module follows a metric across the runs stored on this machine, for
+//! `oneforall trend`, and flags a metric that degrades steadily from run to
+//! run, such as an SSD slowing down as it wears or a CPU running hotter as
+//! its thermal paste ages. Noise makes single runs unreliable, so the verdict
+//! rests on two tests over the whole series:
+//!
+//! ```text
+//! change    = fitted last run / fitted first run - 1   (least-squares line over the runs)
+//! tendency  = Kendall's tau between run order and value (1 = worse in every pair of runs)
+//! degrading = change worse by DRIFT_THRESHOLD_PERCENT and tendency at least DRIFT_MIN_TAU
+//! ```
+//!
+//! A metric is read from the benchmark results, as the median over the
+//! repetitions when repeated, or derived from the burn-in and endurance reports.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::statistics::BenchmarkRecord;
+
+/// How many of the latest runs are followed when not specified.
+pub const DEFAULT_TREND_RUNS: usize = 30;
+
+/// The fewest runs a trend is judged on.
+pub const MIN_TREND_RUNS: usize = 3;
+
+/// How much the fitted line must worsen over the runs to flag a degradation, in percent.
+pub const DRIFT_THRESHOLD_PERCENT: f64 = 5.0;
+
+/// How consistently the runs must worsen to flag a degradation, as Kendall's tau.
+pub const DRIFT_MIN_TAU: f64 = 0.6;
+
+/// The width of the bars plotting the runs, in characters.
+const BAR_WIDTH: usize = 40;
+
+/// Returns the name a metric is selected by on the command line: lowercase,
+/// with every run of other characters replaced by '_', e.g. "dram_bandwidth".
+pub fn metric_key(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Returns `true` if lower values of a metric in this unit are better, as for
+/// latencies and temperatures; throughputs and rates are better higher.
+pub fn lower_is_better(unit: &str) -> bool {
+    matches!(unit, "ns" | "us" | "µs" | "ms" | "s" | "°C")
+}
+
+/// The value of a metric in one run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrendPoint {
+    /// When the run started, in RFC 3339 format.
+    pub started_at: String,
+
+    /// The value of the metric.
+    pub value: f64,
+}
+
+/// The values of a metric over the stored runs, oldest first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrendSeries {
+    /// What measured the metric, e.g. "cache" or "burn-in".
+    pub benchmark: String,
+
+    /// The metric, e.g. "DRAM bandwidth".
+    pub metric: String,
+
+    /// The unit of the metric, e.g. "MB/s".
+    pub unit: String,
+
+    /// The value of the metric in each run, oldest first.
+    pub points: Vec<TrendPoint>,
+}
+
+impl TrendSeries {
+    /// Creates an empty series.
+    fn new(benchmark: &str, metric: &str, unit: &str) -> Self {
+        TrendSeries {
+            benchmark: benchmark.to_string(),
+            metric: metric.to_string(),
+            unit: unit.to_string(),
+            points: Vec::new(),
+        }
+    }
+}
+
+/// Gathers the series of every metric the stored benchmark results report.
+///
+/// # Arguments
+///
+/// * `records` - The benchmark results, oldest first.
+///
+/// # Returns
+///
+/// * `Vec<TrendSeries>` - One series per benchmark and metric.
+pub fn benchmark_series(records: &[BenchmarkRecord]) -> Vec<TrendSeries> {
+    let mut series: Vec<TrendSeries> = Vec::new();
+    for record in records {
+        for measurement in &record.measurements {
+            let Some(value) = record.value(&measurement.metric) else {
+                continue;
+            };
+            let index = match series
+                .iter()
+                .position(|s| s.benchmark == record.benchmark && s.metric == measurement.metric)
+            {
+                Some(index) => index,
+                None => {
+                    series.push(TrendSeries::new(
+                        &record.benchmark,
+                        &measurement.metric,
+                        &measurement.unit,
+                    ));
+                    series.len() - 1
+                }
+            };
+            series[index].points.push(TrendPoint {
+                started_at: record.started_at.clone(),
+                value,
+            });
+        }
+    }
+    series
+}
+
+/// The fields of a stored burn-in report a trend follows.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BurnInTemperature {
+    /// When the burn-in started, in RFC 3339 format.
+    pub started_at: String,
+
+    /// The hottest temperature seen during the burn-in, in degrees Celsius.
+    pub peak_celsius: Option<f64>,
+}
+
+/// The fields of a stored endurance report a trend follows.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnduranceThroughput {
+    /// When the test started, in RFC 3339 format.
+    pub started_at: String,
+
+    /// The volume written and synced, in bytes.
+    pub written_bytes: u64,
+
+    /// The time the test ran, in seconds.
+    pub elapsed_secs: u64,
+}
+
+/// Gathers the hottest temperature of each stored burn-in.
+pub fn burn_in_series(reports: &[BurnInTemperature]) -> TrendSeries {
+    let mut series = TrendSeries::new("burn-in", "peak temperature", "°C");
+    series.points = reports
+        .iter()
+        .filter_map(|report| {
+            report.peak_celsius.map(|value| TrendPoint {
+                started_at: report.started_at.clone(),
+                value,
+            })
+        })
+        .collect();
+    series
+}
+
+/// Gathers the average write throughput of each stored endurance test.
+pub fn endurance_series(reports: &[EnduranceThroughput]) -> TrendSeries {
+    let mut series = TrendSeries::new("endurance", "write throughput", "MB/s");
+    series.points = reports
+        .iter()
+        .filter(|report| report.elapsed_secs > 0 && report.written_bytes > 0)
+        .map(|report| TrendPoint {
+            started_at: report.started_at.clone(),
+            value: report.written_bytes as f64 / 1e6 / report.elapsed_secs as f64,
+        })
+        .collect();
+    series
+}
+
+/// Picks the series of a metric.
+///
+/// # Arguments
+///
+/// * `candidates` - The series of every stored metric.
+/// * `metric` - The metric, by name or by its `metric_key`, e.g. "dram_bandwidth".
+/// * `benchmark` - What measured the metric, needed when several benchmarks report it.
+///
+/// # Returns
+///
+/// * `Result<TrendSeries, String>` - The series, or why no single one matches.
+pub fn select_series(
+    candidates: Vec<TrendSeries>,
+    metric: &str,
+    benchmark: Option<&str>,
+) -> Result<TrendSeries, String> {
+    let key = metric_key(metric);
+    let candidates: Vec<TrendSeries> = candidates
+        .into_iter()
+        .filter(|series| !series.points.is_empty())
+        .collect();
+    let available: BTreeSet<String> = candidates
+        .iter()
+        .filter(|series| benchmark.is_none_or(|benchmark| series.benchmark == benchmark))
+        .map(|series| format!("{} ({})", metric_key(&series.metric), series.benchmark))
+        .collect();
+    let mut matching: Vec<TrendSeries> = candidates
+        .into_iter()
+        .filter(|series| metric_key(&series.metric) == key)
+        .filter(|series| benchmark.is_none_or(|benchmark| series.benchmark == benchmark))
+        .collect();
+    match matching.len() {
+        0 if available.is_empty() => {
+            Err("No runs with metrics are stored on this machine".to_string())
+        }
+        0 => Err(format!(
+            "No stored run reports the metric '{}'; stored metrics: {}",
+            metric,
+            available.into_iter().collect::<Vec<_>>().join(", ")
+        )),
+        1 => Ok(matching.remove(0)),
+        _ => Err(format!(
+            "Several benchmarks report the metric '{}'; choose one with --benchmark: {}",
+            metric,
+            matching
+                .iter()
+                .map(|series| series.benchmark.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+/// How a metric has evolved over the runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrendVerdict {
+    /// The metric has worsened steadily.
+    Degrading,
+
+    /// The metric has improved steadily.
+    Improving,
+
+    /// The metric has not moved beyond its noise.
+    Stable,
+
+    /// Too few runs to judge.
+    TooFewRuns,
+}
+
+/// The evolution of a metric over the latest runs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrendReport {
+    /// What measured the metric, e.g. "cache".
+    pub benchmark: String,
+
+    /// The metric, e.g. "DRAM bandwidth".
+    pub metric: String,
+
+    /// The unit of the metric, e.g. "MB/s".
+    pub unit: String,
+
+    /// Whether lower values are better.
+    pub lower_is_better: bool,
+
+    /// The value of the metric in each run followed, oldest first.
+    pub points: Vec<TrendPoint>,
+
+    /// The change of the least-squares line from the first to the last run, in percent.
+    pub change_percent: Option<f64>,
+
+    /// Kendall's tau between run order and value; positive when the value rises.
+    pub tau: Option<f64>,
+
+    /// How many runs were worse than the run before.
+    pub worse_steps: usize,
+
+    /// How the metric has evolved.
+    pub verdict: TrendVerdict,
+}
+
+/// Returns the change of the least-squares line through the values from the
+/// first to the last, in percent of its first value.
+fn fitted_change_percent(values: &[f64]) -> Option<f64> {
+    let n = values.len() as f64;
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = values.iter().sum::<f64>() / n;
+    let (mut covariance, mut variance) = (0.0, 0.0);
+    for (x, y) in values.iter().enumerate() {
+        covariance += (x as f64 - mean_x) * (y - mean_y);
+        variance += (x as f64 - mean_x).powi(2);
+    }
+    let slope = covariance / variance;
+    let first = mean_y - slope * mean_x;
+    (first != 0.0).then(|| slope * (n - 1.0) * 100.0 / first.abs())
+}
+
+/// Returns Kendall's tau between the order of the values and the values.
+fn kendall_tau(values: &[f64]) -> f64 {
+    let mut score = 0.0;
+    for (i, earlier) in values.iter().enumerate() {
+        for later in &values[i + 1..] {
+            score += match later.partial_cmp(earlier) {
+                Some(std::cmp::Ordering::Greater) => 1.0,
+                Some(std::cmp::Ordering::Less) => -1.0,
+                _ => 0.0,
+            };
+        }
+    }
+    let pairs = (values.len() * (values.len() - 1)) as f64 / 2.0;
+    score / pairs
+}
+
+/// Judges how a metric has evolved over its latest runs.
+///
+/// # Arguments
+///
+/// * `series` - The values of the metric over the stored runs, oldest first.
+/// * `last` - How many of the latest runs to follow.
+///
+/// # Returns
+///
+/// * `TrendReport` - The runs followed and the verdict.
+pub fn analyze_trend(series: TrendSeries, last: usize) -> TrendReport {
+    let skip = series.points.len().saturating_sub(last);
+    let points: Vec<TrendPoint> = series.points.into_iter().skip(skip).collect();
+    let values: Vec<f64> = points.iter().map(|point| point.value).collect();
+    let lower_is_better = lower_is_better(&series.unit);
+    let worse = |earlier: f64, later: f64| {
+        if lower_is_better {
+            later > earlier
+        } else {
+            later < earlier
+        }
+    };
+    let worse_steps = values
+        .windows(2)
+        .filter(|pair| worse(pair[0], pair[1]))
+        .count();
+
+    let (change_percent, tau, verdict) = if values.len() < MIN_TREND_RUNS {
+        (None, None, TrendVerdict::TooFewRuns)
+    } else {
+        let change = fitted_change_percent(&values);
+        let tau = kendall_tau(&values);
+        // Oriented so that positive means worse.
+        let sign = if lower_is_better { 1.0 } else { -1.0 };
+        let worsening = change.unwrap_or(0.0) * sign;
+        let tendency = tau * sign;
+        let verdict = if worsening >= DRIFT_THRESHOLD_PERCENT && tendency >= DRIFT_MIN_TAU {
+            TrendVerdict::Degrading
+        } else if worsening <= -DRIFT_THRESHOLD_PERCENT && tendency <= -DRIFT_MIN_TAU {
+            TrendVerdict::Improving
+        } else {
+            TrendVerdict::Stable
+        };
+        (change, Some(tau), verdict)
+    };
+
+    TrendReport {
+        benchmark: series.benchmark,
+        metric: series.metric,
+        unit: series.unit,
+        lower_is_better,
+        points,
+        change_percent,
+        tau,
+        worse_steps,
+        verdict,
+    }
+}
+
+impl TrendReport {
+    /// Renders the runs as a bar plot with the verdict, for the terminal.
+    pub fn render(&self) -> String {
+        let mut output = format!(
+            "== Trend of {} ({}), in {}, {} is better ==\n",
+            self.metric,
+            self.benchmark,
+            self.unit,
+            if self.lower_is_better {
+                "lower"
+            } else {
+                "higher"
+            }
+        );
+        let low = self
+            .points
+            .iter()
+            .map(|point| point.value)
+            .fold(f64::INFINITY, f64::min);
+        let high = self
+            .points
+            .iter()
+            .map(|point| point.value)
+            .fold(f64::NEG_INFINITY, f64::max);
+        for point in &self.points {
+            // The bars span the range of the values, so a drift of a few percent shows.
+            let filled = if high > low {
+                1 + ((point.value - low) / (high - low) * (BAR_WIDTH - 1) as f64).round() as usize
+            } else {
+                BAR_WIDTH
+            };
+            output.push_str(&format!(
+                "{:<32} {:>12.2}  {}\n",
+                point.started_at,
+                point.value,
+                "█".repeat(filled)
+            ));
+        }
+
+        let runs = self.points.len();
+        if let (Some(change), Some(tau)) = (self.change_percent, self.tau) {
+            output.push_str(&format!(
+                "Change:    {:+.1}% over {} runs, along the least-squares line\n",
+                change, runs
+            ));
+            output.push_str(&format!(
+                "Tendency:  worse than the run before in {} of {} runs, Kendall's tau {:+.2}\n",
+                self.worse_steps,
+                runs - 1,
+                tau
+            ));
+        }
+        let verdict = match self.verdict {
+            TrendVerdict::Degrading if self.worse_steps == runs - 1 => {
+                "DEGRADING: worse in every run; check the hardware before it fails".to_string()
+            }
+            TrendVerdict::Degrading => {
+                "DEGRADING: worsening steadily beyond the noise between runs".to_string()
+            }
+            TrendVerdict::Improving => "improving steadily".to_string(),
+            TrendVerdict::Stable => format!(
+                "stable within {:.0}% or without a steady direction",
+                DRIFT_THRESHOLD_PERCENT
+            ),
+            TrendVerdict::TooFewRuns => format!(
+                "too few runs to judge; {} are needed, {} stored",
+                MIN_TREND_RUNS, runs
+            ),
+        };
+        output.push_str(&format!("Verdict:   {}\n", verdict));
+        output
+    }
+}
@@ -0,0 +1,247 @@
+//! Virtualization Domain Entity
+//!
+//! This module provides what is known about the hypervisor a virtual machine
+//! runs on: the hypervisor itself, the paravirtual devices it exposes, and the
+//! CPU time it steals to run other guests. A stress test or benchmark on a VM
+//! measures the share of the host it was given rather than the hardware, so
+//! these numbers are only interpretable with that context.
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::cloud::DmiSystem;
+use crate::domain::discovery::DiscoverySection;
+use crate::domain::system_stats::CoreUsage;
+
+/// The collector storing the steal time Overwatch measures on a virtual machine.
+pub const STEAL_COLLECTOR: &str = "steal";
+
+/// Steal time, in percent of an interval, above which Overwatch warns that
+/// measurements are skewed by other guests.
+pub const STEAL_WARNING_PERCENT: f64 = 10.0;
+
+/// A hypervisor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Hypervisor {
+    Kvm,
+    Vmware,
+    HyperV,
+    Xen,
+    VirtualBox,
+    Qemu,
+    /// A hypervisor identified only by its vendor string.
+    Other(String),
+}
+
+impl Hypervisor {
+    /// Returns the name of the hypervisor.
+    pub fn name(&self) -> &str {
+        match self {
+            Hypervisor::Kvm => "KVM",
+            Hypervisor::Vmware => "VMware",
+            Hypervisor::HyperV => "Hyper-V",
+            Hypervisor::Xen => "Xen",
+            Hypervisor::VirtualBox => "VirtualBox",
+            Hypervisor::Qemu => "QEMU (emulated)",
+            Hypervisor::Other(vendor) => vendor,
+        }
+    }
+}
+
+/// Identifies a hypervisor from the vendor signature of CPUID leaf 0x40000000.
+///
+/// # Arguments
+///
+/// * `signature` - The 12-character signature, e.g. "KVMKVMKVM".
+///
+/// # Returns
+///
+/// * `Option<Hypervisor>` - The hypervisor, or `None` for an empty signature.
+pub fn hypervisor_from_cpuid(signature: &str) -> Option<Hypervisor> {
+    let signature = signature.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+    match signature {
+        "" => None,
+        "KVMKVMKVM" => Some(Hypervisor::Kvm),
+        "VMwareVMware" => Some(Hypervisor::Vmware),
+        "Microsoft Hv" => Some(Hypervisor::HyperV),
+        "XenVMMXenVMM" => Some(Hypervisor::Xen),
+        "VBoxVBoxVBox" => Some(Hypervisor::VirtualBox),
+        "TCGTCGTCGTCG" => Some(Hypervisor::Qemu),
+        other => Some(Hypervisor::Other(other.to_string())),
+    }
+}
+
+/// Identifies a hypervisor from the DMI system fields the firmware of the VM reports.
+///
+/// # Returns
+///
+/// * `Option<Hypervisor>` - The hypervisor, or `None` if the fields name no known hypervisor.
+pub fn hypervisor_from_dmi(dmi: &DmiSystem) -> Option<Hypervisor> {
+    let vendor = dmi.sys_vendor.as_str();
+    let product = dmi.product_name.as_str();
+    if vendor.contains("VMware") {
+        Some(Hypervisor::Vmware)
+    } else if vendor == "Microsoft Corporation" && product == "Virtual Machine" {
+        Some(Hypervisor::HyperV)
+    } else if vendor == "Xen" || dmi.bios_version.to_lowercase().contains("amazon") {
+        Some(Hypervisor::Xen)
+    } else if vendor == "innotek GmbH" || product == "VirtualBox" {
+        Some(Hypervisor::VirtualBox)
+    } else if vendor == "QEMU"
+        || product.contains("KVM")
+        || vendor == "Amazon EC2"
+        || product == "Google Compute Engine"
+    {
+        Some(Hypervisor::Kvm)
+    } else {
+        None
+    }
+}
+
+/// A paravirtual device, which talks to the hypervisor instead of emulating hardware.
+#[derive(Debug, Clone)]
+pub struct ParavirtDevice {
+    /// The bus the device sits on, e.g. "virtio", "vmbus", "xen" or "pci".
+    pub bus: String,
+
+    /// The name of the device on its bus, e.g. "virtio3".
+    pub name: String,
+
+    /// What the device is, e.g. "network" or "block".
+    pub kind: String,
+
+    /// The driver bound to the device, e.g. "virtio_net", if any.
+    pub driver: Option<String>,
+}
+
+/// Returns what a virtio device is from its device ID, as assigned by the virtio specification.
+pub fn virtio_device_kind(id: u32) -> &'static str {
+    match id {
+        1 => "network",
+        2 => "block",
+        3 => "console",
+        4 => "entropy",
+        5 => "memory balloon",
+        8 => "SCSI",
+        9 => "9P transport",
+        16 => "GPU",
+        18 => "input",
+        19 => "vsock",
+        20 => "crypto",
+        23 => "IOMMU",
+        24 => "memory",
+        26 => "file system",
+        27 => "persistent memory",
+        _ => "unknown",
+    }
+}
+
+/// Returns what a paravirtual device is from the driver bound to it, for
+/// buses whose device names do not say.
+pub fn paravirt_driver_kind(driver: &str) -> &'static str {
+    match driver {
+        "hv_netvsc" | "vmxnet3" | "xen-netfront" => "network",
+        "hv_storvsc" | "vmw_pvscsi" => "SCSI",
+        "xen-blkfront" => "block",
+        "hv_balloon" | "vmw_balloon" => "memory balloon",
+        "hyperv_fb" | "hyperv_drm" | "vmwgfx" => "GPU",
+        "hid_hyperv" | "hyperv_keyboard" | "xen-kbdfront" => "input",
+        "hv_utils" => "integration services",
+        "hv_vmbus" | "vmw_vmci" => "hypervisor channel",
+        "hv_sock" | "vmw_vsock_vmci_transport" => "vsock",
+        _ => "unknown",
+    }
+}
+
+/// The virtualization of a machine.
+#[derive(Debug, Clone, Default)]
+pub struct VirtualizationInfo {
+    /// The hypervisor, or `None` on bare metal.
+    pub hypervisor: Option<Hypervisor>,
+
+    /// How the hypervisor was detected, e.g. "CPUID".
+    pub detected_by: Option<String>,
+
+    /// The paravirtual devices of the machine.
+    pub devices: Vec<ParavirtDevice>,
+
+    /// The share of CPU time stolen by the hypervisor since boot, in percent.
+    pub steal_since_boot_percent: f64,
+}
+
+impl VirtualizationInfo {
+    /// Returns `true` if the machine is a virtual machine.
+    pub fn is_virtual(&self) -> bool {
+        self.hypervisor.is_some() || !self.devices.is_empty()
+    }
+}
+
+/// The CPU time stolen by the hypervisor over one Overwatch interval.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StealSample {
+    /// The stolen share of the CPU time of every CPU together, in percent.
+    pub percent: f64,
+
+    /// The CPU the most time was stolen from.
+    pub worst_cpu: u32,
+
+    /// The stolen share of the CPU time of that CPU, in percent.
+    pub worst_percent: f64,
+}
+
+impl StealSample {
+    /// Summarizes the steal time of every CPU over an interval.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<StealSample>` - The summary, or `None` without CPUs.
+    pub fn from_cores(cores: &[CoreUsage]) -> Option<StealSample> {
+        let worst = cores
+            .iter()
+            .max_by(|a, b| a.steal_percent.total_cmp(&b.steal_percent))?;
+        Some(StealSample {
+            percent: cores.iter().map(|core| core.steal_percent).sum::<f64>() / cores.len() as f64,
+            worst_cpu: worst.cpu,
+            worst_percent: worst.steal_percent,
+        })
+    }
+}
+
+/// Builds the discovery section describing the virtualization of the machine.
+///
+/// # Arguments
+///
+/// * `info` - The virtualization of the machine.
+///
+/// # Returns
+///
+/// * `DiscoverySection` - The hypervisor, the steal time since boot and every paravirtual device.
+pub fn virtualization_section(info: &VirtualizationInfo) -> DiscoverySection {
+    let mut section = DiscoverySection::new("Virtualization");
+    section.push(
+        "Hypervisor",
+        match (&info.hypervisor, &info.detected_by) {
+            (Some(hypervisor), Some(source)) => format!("{} (from {})", hypervisor.name(), source),
+            (Some(hypervisor), None) => hypervisor.name().to_string(),
+            (None, _) if info.devices.is_empty() => "none (bare metal)".to_string(),
+            (None, _) => "unknown".to_string(),
+        },
+    );
+    if !info.is_virtual() {
+        return section;
+    }
+    section.push(
+        "Steal time since boot",
+        format!("{:.2}%", info.steal_since_boot_percent),
+    );
+    section.push("Paravirtual devices", info.devices.len());
+    for device in &info.devices {
+        section.push(
+            &format!("{} {}", device.bus, device.name),
+            match &device.driver {
+                Some(driver) => format!("{} ({})", device.kind, driver),
+                None => format!("{} (no driver)", device.kind),
+            },
+        );
+    }
+    section
+}
@@ -0,0 +1,231 @@
+//! VRAM Domain Entity
+//!
+//! This module provides the report of the GPU memory test started by
+//! `oneforall stress --vram`. Like memtest for system RAM, memtest_vulkan fills
+//! the video memory of a GPU with patterns, reads them back and verifies them,
+//! iteration after iteration; any address range that reads back differently is
+//! reported as a bad region, which points at a faulty memory chip of the board.
+
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::domain::burn_in::WorkloadOutcome;
+use crate::domain::endurance::format_volume;
+use crate::domain::profile::format_duration;
+
+/// The number of bad regions reported; the errors in the rest are only counted.
+pub const MAX_REPORTED_REGIONS: usize = 20;
+
+/// An address range of video memory that did not read back as written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct VramRegion {
+    /// The first bad address.
+    pub start: u64,
+
+    /// The last bad address, inclusive.
+    pub end: u64,
+}
+
+impl VramRegion {
+    /// Renders the region, e.g. "0x30dfac000..=0x30dfac07f (128 B)".
+    pub fn render(&self) -> String {
+        format!(
+            "{:#x}..={:#x} ({})",
+            self.start,
+            self.end,
+            format_volume(self.end - self.start + 1)
+        )
+    }
+}
+
+/// A line of memtest_vulkan output that matters to the report.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MemtestLine {
+    /// The GPU under test, e.g. "1: Bus=0x01:00 DevId=0x2204   24GB NVIDIA GeForce RTX 3090".
+    Device(String),
+
+    /// The number of iterations verified so far, and the memory checked by them.
+    Iterations {
+        count: u64,
+        checked_bytes: Option<u64>,
+    },
+
+    /// The number of words of an iteration that read back differently.
+    Errors(u64),
+
+    /// The address range those words were found in.
+    Region(VramRegion),
+}
+
+/// Parses a hexadecimal number written as "0x...".
+fn parse_hex(text: &str) -> Option<u64> {
+    u64::from_str_radix(text.trim().strip_prefix("0x")?, 16).ok()
+}
+
+/// Parses a line of memtest_vulkan output.
+///
+/// # Arguments
+///
+/// * `line` - The line, e.g. "13 iteration. Passed 0.3917 seconds written: 66.0GB 322.4GB/sec
+///   checked: 99.0GB 289.3GB/sec" or "Errors address range: 0x30DFAC000..=0x30DFAC07F iteration:1".
+///
+/// # Returns
+///
+/// * `Option<MemtestLine>` - What the line reports, or `None` for the lines the report ignores.
+pub fn parse_memtest_line(line: &str) -> Option<MemtestLine> {
+    let line = line.trim();
+    if let Some((_, device)) = line.split_once("test of ") {
+        return Some(MemtestLine::Device(device.trim().to_string()));
+    }
+    if let Some((_, rest)) = line.split_once("total errors ") {
+        return parse_hex(rest.split_whitespace().next()?).map(MemtestLine::Errors);
+    }
+    if let Some((_, rest)) = line.split_once("Errors address range:") {
+        let (start, end) = rest.split_whitespace().next()?.split_once("..=")?;
+        return Some(MemtestLine::Region(VramRegion {
+            start: parse_hex(start)?,
+            end: parse_hex(end)?,
+        }));
+    }
+    if let Some((count, rest)) = line.split_once(" iteration. Passed") {
+        let checked_bytes = rest
+            .split_once("checked:")
+            .and_then(|(_, checked)| checked.split_whitespace().next())
+            .and_then(|volume| volume.strip_suffix("GB"))
+            .and_then(|gigabytes| gigabytes.parse::<f64>().ok())
+            .map(|gigabytes| (gigabytes * (1u64 << 30) as f64) as u64);
+        return Some(MemtestLine::Iterations {
+            count: count.trim().parse().ok()?,
+            checked_bytes,
+        });
+    }
+    None
+}
+
+/// The result of a GPU memory test.
+#[derive(Debug, Clone, Serialize)]
+pub struct VramTestReport {
+    /// The local time the test started, in RFC 3339 format.
+    pub started_at: String,
+
+    /// The configured duration, in seconds.
+    pub planned_secs: u64,
+
+    /// The time the test actually ran, in seconds.
+    pub elapsed_secs: u64,
+
+    /// How the test ended.
+    pub outcome: WorkloadOutcome,
+
+    /// The GPU under test, as named by memtest_vulkan.
+    pub device: Option<String>,
+
+    /// The number of write, read back and verify iterations completed.
+    pub iterations: u64,
+
+    /// The video memory verified over all iterations, in bytes.
+    pub checked_bytes: u64,
+
+    /// The number of words that read back differently.
+    pub errors: u64,
+
+    /// The address ranges the errors were found in, merged and in address order.
+    pub bad_regions: Vec<VramRegion>,
+
+    /// The number of bad regions found beyond `MAX_REPORTED_REGIONS`.
+    pub unreported_regions: usize,
+}
+
+impl VramTestReport {
+    /// Adds a line of memtest_vulkan output to the report.
+    pub fn record(&mut self, line: MemtestLine) {
+        match line {
+            MemtestLine::Device(device) => self.device = Some(device),
+            MemtestLine::Iterations {
+                count,
+                checked_bytes,
+            } => {
+                self.iterations = self.iterations.max(count);
+                if let Some(checked) = checked_bytes {
+                    self.checked_bytes = self.checked_bytes.max(checked);
+                }
+            }
+            MemtestLine::Errors(errors) => self.errors += errors,
+            MemtestLine::Region(region) => self.add_region(region),
+        }
+    }
+
+    /// Adds a bad region, merging it with the regions it overlaps or touches, as
+    /// the same faulty chip is usually reported again on every iteration.
+    fn add_region(&mut self, region: VramRegion) {
+        let mut merged = region;
+        self.bad_regions.retain(|known| {
+            let touches = known.start <= merged.end.saturating_add(1)
+                && merged.start <= known.end.saturating_add(1);
+            if touches {
+                merged.start = merged.start.min(known.start);
+                merged.end = merged.end.max(known.end);
+            }
+            !touches
+        });
+        if self.bad_regions.len() < MAX_REPORTED_REGIONS {
+            let at = self.bad_regions.partition_point(|known| known < &merged);
+            self.bad_regions.insert(at, merged);
+        } else {
+            self.unreported_regions += 1;
+        }
+    }
+
+    /// Returns `true` if the test ran to the end, verified at least one iteration
+    /// and found no error.
+    pub fn passed(&self) -> bool {
+        self.outcome == WorkloadOutcome::Completed
+            && self.iterations > 0
+            && self.errors == 0
+            && self.bad_regions.is_empty()
+    }
+
+    /// Renders the report as plain text suitable for the terminal.
+    pub fn render(&self) -> String {
+        let mut output = String::from("== VRAM test report ==\n");
+        output.push_str(&format!(
+            "Duration:    {} of {} planned\n",
+            format_duration(Duration::from_secs(self.elapsed_secs)),
+            format_duration(Duration::from_secs(self.planned_secs))
+        ));
+        output.push_str(&format!(
+            "Test:        {}\n",
+            match &self.outcome {
+                WorkloadOutcome::Completed => "completed".to_string(),
+                WorkloadOutcome::Failed(e) => format!("FAILED ({})", e),
+                WorkloadOutcome::Cancelled => "cancelled".to_string(),
+            }
+        ));
+        output.push_str(&format!(
+            "GPU:         {}\n",
+            self.device.as_deref().unwrap_or("unknown")
+        ));
+        output.push_str(&format!(
+            "Verified:    {} iterations, {} checked\n",
+            self.iterations,
+            format_volume(self.checked_bytes)
+        ));
+        output.push_str(&format!("Errors:      {}\n", self.errors));
+        if !self.bad_regions.is_empty() {
+            output.push_str("Bad regions:\n");
+            for region in &self.bad_regions {
+                output.push_str(&format!("  {}\n", region.render()));
+            }
+            if self.unreported_regions > 0 {
+                output.push_str(&format!("  ... and {} more\n", self.unreported_regions));
+            }
+        }
+
+        output.push_str(&format!(
+            "Result: {}\n",
+            if self.passed() { "PASS" } else { "FAIL" }
+        ));
+        output
+    }
+}
@@ -0,0 +1,83 @@
+//! Workload Domain Entity
+//!
+//! This module provides the results of `benchmark exec`, which times a command
+//! of the user's own, such as a build, a training step or a database load, as
+//! a benchmark: how long it took, the CPU time it and the processes it started
+//! used in user space and in the kernel, and the most memory any of them held.
+//! Run with repetitions, the command gets the same statistics, stored results
+//! and telemetry report as the built-in benchmarks.
+
+use std::time::Duration;
+
+use crate::domain::statistics::Measurement;
+
+/// The results of one run of a command.
+#[derive(Debug, Clone)]
+pub struct WorkloadResult {
+    /// The command line run.
+    pub command: String,
+
+    /// The time from starting the command until it exited.
+    pub wall_time: Duration,
+
+    /// The CPU time spent in user space by the command and the processes it waited for.
+    pub user_time: Duration,
+
+    /// The CPU time spent in the kernel on their behalf.
+    pub system_time: Duration,
+
+    /// The largest resident set of the command or of any process it waited for, in kilobytes.
+    pub max_rss_kb: u64,
+}
+
+impl WorkloadResult {
+    /// Returns the CPUs kept busy on average, e.g. 3.5 for a command using three
+    /// and a half cores over its run.
+    pub fn cpus_used(&self) -> f64 {
+        let wall = self.wall_time.as_secs_f64();
+        if wall > 0.0 {
+            (self.user_time + self.system_time).as_secs_f64() / wall
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Lists the metrics of a run of a command, for statistics over repeated runs.
+pub fn workload_measurements(result: &WorkloadResult) -> Vec<Measurement> {
+    vec![
+        Measurement::new("wall time", "s", result.wall_time.as_secs_f64()),
+        Measurement::new("user CPU time", "s", result.user_time.as_secs_f64()),
+        Measurement::new("system CPU time", "s", result.system_time.as_secs_f64()),
+        Measurement::new("CPUs used", "CPUs", result.cpus_used()),
+        Measurement::new("peak memory", "MB", result.max_rss_kb as f64 / 1024.0),
+    ]
+}
+
+/// Formats the results of a run of a command.
+///
+/// # Arguments
+///
+/// * `result` - The results of the run.
+///
+/// # Returns
+///
+/// * `String` - The figures of the run, one per line.
+pub fn format_workload_results(result: &WorkloadResult) -> String {
+    format!(
+        concat!(
+            "Workload: {}\n",
+            "  Wall time:        {:.3} s\n",
+            "  User CPU time:    {:.3} s\n",
+            "  System CPU time:  {:.3} s\n",
+            "  CPUs used:        {:.2}\n",
+            "  Peak memory:      {:.1} MB\n"
+        ),
+        result.command,
+        result.wall_time.as_secs_f64(),
+        result.user_time.as_secs_f64(),
+        result.system_time.as_secs_f64(),
+        result.cpus_used(),
+        result.max_rss_kb as f64 / 1024.0
+    )
+}
@@ -0,0 +1,31 @@
+//! OneForAll Core
+//!
+//! This crate provides the hardware discovery, benchmarks, stress tests and
+//! result storage of OneForAll as a library, so other Rust services can embed
+//! them instead of running the `one_for_all` binary and parsing its output.
+//! It follows the layout of the application:
+//!
+//! * `ports` - the traits each capability is used through, e.g. `TopologyPort`;
+//! * `domain` - the types the ports exchange and the logic that needs no
+//!   hardware, such as statistics, scores and report rendering;
+//! * `adapters` - the implementations of the ports on Linux and macOS.
+//!
+//! Adapters take the `LoggerPort` of the `common` crate, e.g.:
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//!
+//! use common::ports::log_port::LoggerPort;
+//! use oneforall_core::adapters::topology_adapter::TopologyAdapter;
+//! use oneforall_core::ports::topology_port::TopologyPort;
+//!
+//! fn print_topology(logger: Arc<dyn LoggerPort>) -> Result<(), String> {
+//!     let topology = TopologyAdapter::new(logger).discover_cpu_topology()?;
+//!     println!("{} logical CPUs", topology.cpus.len());
+//!     Ok(())
+//! }
+//! ```
+
+pub mod adapters;
+pub mod domain;
+pub mod ports;
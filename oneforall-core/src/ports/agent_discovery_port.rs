@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+use crate::domain::fleet::{Agent, AgentAdvertisement};
+
+/// `AgentDiscoveryPort` Trait
+///
+/// Defines an interface for announcing this instance as a fleet agent and for
+/// finding the agents announced by other machines, e.g. over mDNS on the lab
+/// subnet.
+pub trait AgentDiscoveryPort: Send + Sync {
+    /// Announces this instance until it is withdrawn or the application exits.
+    ///
+    /// # Arguments
+    /// * `advertisement` - What the agent announces about itself.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the announcement was made, or an error.
+    fn advertise(&self, advertisement: &AgentAdvertisement) -> Result<(), String>;
+
+    /// Withdraws the announcement, telling listeners the agent is going away.
+    fn withdraw(&self);
+
+    /// Listens for the agents announced by other machines.
+    ///
+    /// # Arguments
+    /// * `timeout` - How long to listen.
+    ///
+    /// # Returns
+    /// A `Result` containing every agent found, or an error if discovery is unavailable.
+    fn discover(&self, timeout: Duration) -> Result<Vec<Agent>, String>;
+}
@@ -0,0 +1,23 @@
+use crate::domain::alert::{Alert, AlertKind};
+
+/// `AlertPort` Trait
+///
+/// Defines an interface for delivering alerts to one destination, such as a
+/// webhook or a mailbox. Delivery blocks until the destination accepted or
+/// refused the alert, so callers send from a background thread.
+pub trait AlertPort: Send + Sync {
+    /// Returns a short description of the destination for logs, e.g. "webhook 'ops-slack'".
+    fn describe(&self) -> String;
+
+    /// Returns `true` if the destination subscribes to alerts of this kind.
+    fn accepts(&self, kind: AlertKind) -> bool;
+
+    /// Delivers an alert.
+    ///
+    /// # Arguments
+    /// * `alert` - The alert to deliver.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the destination accepted the alert, or an error.
+    fn send(&self, alert: &Alert) -> Result<(), String>;
+}
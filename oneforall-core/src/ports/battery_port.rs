@@ -0,0 +1,15 @@
+use crate::domain::battery::PowerSupplyState;
+
+/// `BatteryPort` Trait
+///
+/// Defines an interface for reading the AC adapter and batteries of a laptop.
+/// Implementations are read by `discover` and sampled while stress tests run,
+/// to tell how the machine behaves once unplugged.
+pub trait BatteryPort: Send + Sync {
+    /// Reads the AC adapter and every battery.
+    ///
+    /// # Returns
+    /// A `Result` containing the power supplies, with no battery on machines without
+    /// one, or an error if the power supplies cannot be read.
+    fn read_power_supplies(&self) -> Result<PowerSupplyState, String>;
+}
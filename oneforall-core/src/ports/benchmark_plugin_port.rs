@@ -0,0 +1,33 @@
+use common::domain::job::Resource;
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::plugin::PluginOutput;
+
+/// `BenchmarkPluginPort` Trait
+///
+/// Defines an interface for benchmarks and stress tests shipped outside
+/// OneForAll, such as an FPGA loopback test. Each plugin appears as a
+/// subcommand named after it and is handed the arguments that follow; its
+/// output flows through the same job queue, run telemetry and result storage
+/// as the built-in benchmarks. Services embedding `oneforall-core` implement
+/// this trait directly; shared libraries are wrapped by `DynamicPluginAdapter`.
+pub trait BenchmarkPluginPort: Send + Sync {
+    /// Returns the subcommand the plugin runs as, e.g. "fpga-loopback".
+    fn name(&self) -> &str;
+
+    /// Returns one line describing the plugin, shown by `oneforall plugins`.
+    fn description(&self) -> &str;
+
+    /// Returns the resources the plugin loads, which no other job may use while it runs.
+    fn resources(&self) -> &[Resource];
+
+    /// Runs the plugin once.
+    ///
+    /// # Arguments
+    /// * `args` - The command-line arguments after the subcommand.
+    /// * `cancel` - Cancelled when the job is, e.g. by Ctrl+C; the plugin should stop early.
+    ///
+    /// # Returns
+    /// A `Result` containing the report and metrics of the run, or an error.
+    fn run(&self, args: &[String], cancel: &CancellationToken) -> Result<PluginOutput, String>;
+}
@@ -0,0 +1,35 @@
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::block_device::{DeviceAccess, DeviceBenchmarkResult, DeviceUsage};
+
+/// `BlockDevicePort` Trait
+///
+/// Defines an interface for benchmarking a drive through its raw block device,
+/// beneath any filesystem. Writing to the device destroys its data, so what
+/// lives on it is read first and the benchmark is told how it may access it.
+pub trait BlockDevicePort: Send + Sync {
+    /// Reads the partitions, mounts, stacked devices and swap areas of a device.
+    ///
+    /// # Arguments
+    /// * `device` - The block device, e.g. "/dev/nvme1n1" or one of its /dev/disk links.
+    ///
+    /// # Returns
+    /// A `Result` containing what lives on the device, named by its kernel name, or an error.
+    fn read_device_usage(&self, device: &str) -> Result<DeviceUsage, String>;
+
+    /// Measures sequential and random reads and, unless read-only, writes.
+    ///
+    /// # Arguments
+    /// * `device` - The block device.
+    /// * `access` - How the device may be accessed.
+    /// * `cancel` - Stops the benchmark before the next access once cancelled.
+    ///
+    /// # Returns
+    /// A `Result` containing the measured rates, or an error.
+    fn run_device_benchmark(
+        &self,
+        device: &str,
+        access: DeviceAccess,
+        cancel: &CancellationToken,
+    ) -> Result<DeviceBenchmarkResult, String>;
+}
@@ -0,0 +1,14 @@
+use crate::domain::boot::BootTiming;
+
+/// `BootTimingPort` Trait
+///
+/// Defines an interface for measuring how long the last boot of the machine
+/// took, stage by stage, from the POST to the default target.
+pub trait BootTimingPort: Send + Sync {
+    /// Measures the stages of the last boot.
+    ///
+    /// # Returns
+    /// A `Result` containing the stages measured, or an error message if the boot
+    /// cannot be timed, e.g. without systemd or before the boot finished.
+    fn measure_boot(&self) -> Result<BootTiming, String>;
+}
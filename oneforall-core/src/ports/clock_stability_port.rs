@@ -0,0 +1,25 @@
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::clock::ClockResult;
+
+/// `ClockStabilityPort` Trait
+///
+/// Defines an interface for checking the clocks a latency-sensitive workload
+/// relies on: the counter of each core, the kernel clocksource and the timer.
+pub trait ClockStabilityPort: Send + Sync {
+    /// Checks the clocks of the machine.
+    ///
+    /// # Arguments
+    /// * `cpus` - The logical CPUs to compare; the first is the reference.
+    /// * `rounds` - The round trips timed between the reference and each other CPU.
+    /// * `cancel` - Stops the benchmark before the next CPU once cancelled.
+    ///
+    /// # Returns
+    /// A `Result` containing the clock figures and the skew of each CPU, or an error.
+    fn measure_clock_stability(
+        &self,
+        cpus: &[u32],
+        rounds: usize,
+        cancel: &CancellationToken,
+    ) -> Result<ClockResult, String>;
+}
@@ -0,0 +1,15 @@
+use crate::domain::cloud::InstanceMetadata;
+
+/// `CloudMetadataPort` Trait
+///
+/// Defines an interface for describing the cloud instance this machine is, so
+/// that results record the instance type, region, zone and tenancy they were
+/// measured on.
+pub trait CloudMetadataPort: Send + Sync {
+    /// Reads the metadata of the cloud instance.
+    ///
+    /// # Returns
+    /// A `Result` containing the instance metadata, `None` if the machine is not
+    /// a known cloud instance, or an error message if the metadata service failed.
+    fn read_instance_metadata(&self) -> Result<Option<InstanceMetadata>, String>;
+}
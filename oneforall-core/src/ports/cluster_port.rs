@@ -0,0 +1,40 @@
+use std::collections::BTreeMap;
+
+use crate::domain::kubernetes::NodeTaint;
+
+/// `ClusterPort` Trait
+///
+/// Defines an interface for recording hardware verdicts on the nodes of a
+/// cluster orchestrator such as Kubernetes, so its scheduler can avoid nodes
+/// with bad hardware.
+pub trait ClusterPort: Send + Sync {
+    /// Sets labels on a node, replacing any existing value of the same keys.
+    ///
+    /// # Arguments
+    /// * `node` - The name of the node.
+    /// * `labels` - The labels to set.
+    ///
+    /// # Returns
+    /// A `Result` that is `Ok` once the labels are set, or an error message.
+    fn label_node(&self, node: &str, labels: &BTreeMap<String, String>) -> Result<(), String>;
+
+    /// Places a taint on a node, replacing any taint with the same key.
+    ///
+    /// # Arguments
+    /// * `node` - The name of the node.
+    /// * `taint` - The taint to place.
+    ///
+    /// # Returns
+    /// A `Result` that is `Ok` once the taint is placed, or an error message.
+    fn taint_node(&self, node: &str, taint: &NodeTaint) -> Result<(), String>;
+
+    /// Removes the taint with a key from a node, if it has one.
+    ///
+    /// # Arguments
+    /// * `node` - The name of the node.
+    /// * `key` - The key of the taint to remove.
+    ///
+    /// # Returns
+    /// A `Result` that is `Ok` once the node has no taint with the key, or an error message.
+    fn untaint_node(&self, node: &str, key: &str) -> Result<(), String>;
+}
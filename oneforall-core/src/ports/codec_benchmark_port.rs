@@ -0,0 +1,30 @@
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::codec::{CodecAlgorithm, CodecResult};
+use crate::domain::cpu_topology::CpuTopology;
+
+/// `CodecBenchmarkPort` Trait
+///
+/// Defines an interface for measuring how fast the CPU encrypts, hashes,
+/// compresses and decompresses data.
+pub trait CodecBenchmarkPort: Send + Sync {
+    /// Runs each algorithm on a single core, then on every logical CPU of each socket.
+    ///
+    /// # Arguments
+    /// * `algorithms` - The algorithms to measure, in order.
+    /// * `topology` - The CPU topology the threads are placed on.
+    /// * `step_duration` - How long each algorithm runs on one core and on each socket.
+    /// * `cancel` - Stops the benchmark before the next measurement once cancelled.
+    ///
+    /// # Returns
+    /// A `Result` containing the throughput of each algorithm, or an error.
+    fn run_codec_benchmark(
+        &self,
+        algorithms: &[CodecAlgorithm],
+        topology: &CpuTopology,
+        step_duration: Duration,
+        cancel: &CancellationToken,
+    ) -> Result<CodecResult, String>;
+}
@@ -0,0 +1,14 @@
+use crate::domain::container::ResourceLimits;
+
+/// `ContainerPort` Trait
+///
+/// Defines an interface for reading the CPU and memory limits placed on this
+/// process by its cgroup, so workloads can be sized to what the process may
+/// actually use rather than to the whole host.
+pub trait ContainerPort: Send + Sync {
+    /// Reads the resource limits of this process.
+    ///
+    /// # Returns
+    /// A `Result` containing the host resources and the limits of the cgroup, or an error message.
+    fn read_resource_limits(&self) -> Result<ResourceLimits, String>;
+}
@@ -0,0 +1,14 @@
+use crate::domain::cpu_features::CpuFeatures;
+
+/// `CpuFeaturesPort` Trait
+///
+/// Defines an interface for discovering the instruction set extensions,
+/// microcode revision and vulnerability mitigations of the CPU, so that
+/// benchmark results can be interpreted against the features in effect.
+pub trait CpuFeaturesPort: Send + Sync {
+    /// Discovers the features of the CPU.
+    ///
+    /// # Returns
+    /// A `Result` containing the discovered `CpuFeatures`, or an error message.
+    fn discover_cpu_features(&self) -> Result<CpuFeatures, String>;
+}
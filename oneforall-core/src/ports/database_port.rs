@@ -0,0 +1,115 @@
+use sled::{Db, IVec};
+use std::error::Error;
+
+/// `DatabasePort` Trait
+///
+/// Defines an interface for interacting with a database. This trait abstracts the functionality
+/// for performing basic CRUD operations, allowing different implementations to tailor database
+/// interactions.
+pub trait DatabasePort: Send + Sync {
+    fn insert(&self, key: &[u8], value: &[u8]) -> Result<Option<IVec>, Box<dyn Error>>;
+    fn insert_batch(&self, pairs: &[(Vec<u8>, Vec<u8>)]) -> Result<(), Box<dyn Error>>;
+    fn get(&self, key: &[u8]) -> Result<Option<IVec>, Box<dyn Error>>;
+    fn remove(&self, key: &[u8]) -> Result<Option<IVec>, Box<dyn Error>>;
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<Vec<(IVec, IVec)>, Box<dyn Error>>;
+    fn scan_range(&self, start: &[u8], end: &[u8]) -> Result<Vec<(IVec, IVec)>, Box<dyn Error>>;
+    fn remove_range(&self, start: &[u8], end: &[u8]) -> Result<usize, Box<dyn Error>>;
+    fn size_on_disk(&self) -> Result<u64, Box<dyn Error>>;
+    fn compact(&self) -> Result<(), Box<dyn Error>>;
+    fn flush(&self) -> Result<(), Box<dyn Error>>;
+}
+
+/// A struct that implements the `DatabasePort` trait using the Sled embedded database.
+pub struct SledDatabasePort {
+    db: Db,
+}
+
+impl SledDatabasePort {
+    /// Opens a new database or creates it if it doesn't exist.
+    pub fn new(path: &str) -> Result<Self, Box<dyn Error>> {
+        let db = sled::open(path)?;
+        Ok(SledDatabasePort { db })
+    }
+}
+
+impl DatabasePort for SledDatabasePort {
+    /// Inserts a key-value pair into the database.
+    fn insert(&self, key: &[u8], value: &[u8]) -> Result<Option<IVec>, Box<dyn Error>> {
+        let previous_value = self.db.insert(key, value)?;
+        self.db.flush()?; // Ensure that changes are written to disk.
+        Ok(previous_value)
+    }
+
+    /// Inserts key-value pairs atomically, writing them to disk once.
+    fn insert_batch(&self, pairs: &[(Vec<u8>, Vec<u8>)]) -> Result<(), Box<dyn Error>> {
+        let mut batch = sled::Batch::default();
+        for (key, value) in pairs {
+            batch.insert(key.as_slice(), value.as_slice());
+        }
+        self.db.apply_batch(batch)?;
+        self.db.flush()?; // Ensure that changes are written to disk.
+        Ok(())
+    }
+
+    /// Retrieves a value from the database by key.
+    fn get(&self, key: &[u8]) -> Result<Option<IVec>, Box<dyn Error>> {
+        let value = self.db.get(key)?;
+        Ok(value)
+    }
+
+    /// Removes a key-value pair from the database.
+    fn remove(&self, key: &[u8]) -> Result<Option<IVec>, Box<dyn Error>> {
+        let previous_value = self.db.remove(key)?;
+        self.db.flush()?; // Ensure that changes are written to disk.
+        Ok(previous_value)
+    }
+
+    /// Retrieves every key-value pair whose key starts with a prefix, in key order.
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<Vec<(IVec, IVec)>, Box<dyn Error>> {
+        let pairs = self.db.scan_prefix(prefix).collect::<Result<Vec<_>, _>>()?;
+        Ok(pairs)
+    }
+
+    /// Retrieves every key-value pair whose key is in `[start, end)`, in key order.
+    fn scan_range(&self, start: &[u8], end: &[u8]) -> Result<Vec<(IVec, IVec)>, Box<dyn Error>> {
+        if start >= end {
+            return Ok(Vec::new());
+        }
+        let pairs = self.db.range(start..end).collect::<Result<Vec<_>, _>>()?;
+        Ok(pairs)
+    }
+
+    /// Removes every key-value pair whose key is in `[start, end)`, returning how many were removed.
+    fn remove_range(&self, start: &[u8], end: &[u8]) -> Result<usize, Box<dyn Error>> {
+        if start >= end {
+            return Ok(0);
+        }
+        let mut batch = sled::Batch::default();
+        let mut removed = 0;
+        for key in self.db.range(start..end).keys() {
+            batch.remove(key?);
+            removed += 1;
+        }
+        self.db.apply_batch(batch)?;
+        self.db.flush()?; // Ensure that changes are written to disk.
+        Ok(removed)
+    }
+
+    /// Returns the space the database takes on disk, in bytes.
+    fn size_on_disk(&self) -> Result<u64, Box<dyn Error>> {
+        Ok(self.db.size_on_disk()?)
+    }
+
+    /// Writes pending changes to disk; sled reclaims the space of removed
+    /// pairs by itself as it rewrites its segments.
+    fn compact(&self) -> Result<(), Box<dyn Error>> {
+        self.db.flush()?;
+        Ok(())
+    }
+
+    /// Writes everything still buffered to disk, e.g. before the application exits.
+    fn flush(&self) -> Result<(), Box<dyn Error>> {
+        self.db.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,26 @@
+use crate::domain::hardware_manifest::{DiskInfo, NicInfo};
+
+/// `DeviceInventoryPort` Trait
+///
+/// Defines an interface for listing the disks, network interfaces and memory
+/// of the machine, so they can be checked against the hardware it was ordered with.
+pub trait DeviceInventoryPort: Send + Sync {
+    /// Lists the physical disks, leaving out optical drives and virtual devices
+    /// such as loop, RAID and device-mapper devices.
+    ///
+    /// # Returns
+    /// A `Result` containing one entry per disk, sorted by name, or an error message.
+    fn list_disks(&self) -> Result<Vec<DiskInfo>, String>;
+
+    /// Lists the physical network interfaces, leaving out virtual ones such as bridges and bonds.
+    ///
+    /// # Returns
+    /// A `Result` containing one entry per interface, sorted by name, or an error message.
+    fn list_nics(&self) -> Result<Vec<NicInfo>, String>;
+
+    /// Reads the memory visible to the kernel.
+    ///
+    /// # Returns
+    /// A `Result` containing the total memory in bytes, or an error message.
+    fn read_memory_total(&self) -> Result<u64, String>;
+}
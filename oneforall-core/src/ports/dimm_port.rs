@@ -0,0 +1,13 @@
+use crate::domain::dimm::Dimm;
+
+/// `DimmPort` Trait
+///
+/// Defines an interface for listing the memory modules of the machine from the
+/// firmware, including the speed each is rated for and the speed it runs at.
+pub trait DimmPort: Send + Sync {
+    /// Lists every DIMM slot of the machine.
+    ///
+    /// # Returns
+    /// A `Result` containing one entry per slot, populated or not, or an error message.
+    fn discover_dimms(&self) -> Result<Vec<Dimm>, String>;
+}
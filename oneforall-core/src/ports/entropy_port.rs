@@ -0,0 +1,14 @@
+use crate::domain::entropy::EntropyState;
+
+/// `EntropyPort` Trait
+///
+/// Defines an interface for checking the random number sources of the machine:
+/// the random number instructions of the CPU and the kernel entropy pool.
+pub trait EntropyPort: Send + Sync {
+    /// Checks the random number sources, running each CPU instruction briefly.
+    ///
+    /// # Returns
+    /// A `Result` containing the instructions with their throughput and the
+    /// state of the kernel pool, or an error message.
+    fn check_entropy(&self) -> Result<EntropyState, String>;
+}
@@ -0,0 +1,27 @@
+use std::path::Path;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::filesystem::FilesystemBenchmarkResult;
+
+/// `FilesystemBenchmarkPort` Trait
+///
+/// Defines an interface for measuring the metadata and small-file performance
+/// of a filesystem, as opposed to the raw throughput of the drive beneath it.
+pub trait FilesystemBenchmarkPort: Send + Sync {
+    /// Measures create, stat and delete rates, small random writes and fsync latency.
+    ///
+    /// # Arguments
+    /// * `directory` - The directory on the filesystem under test; it must already exist.
+    /// * `files` - The number of files created, stat'ed and deleted.
+    /// * `cancel` - Stops the benchmark before the next operation once cancelled.
+    ///
+    /// # Returns
+    /// A `Result` containing the measured rates and latencies, or an error.
+    fn run_filesystem_benchmark(
+        &self,
+        directory: &Path,
+        files: u64,
+        cancel: &CancellationToken,
+    ) -> Result<FilesystemBenchmarkResult, String>;
+}
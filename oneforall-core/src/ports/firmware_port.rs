@@ -0,0 +1,13 @@
+use crate::domain::firmware::FirmwareComponent;
+
+/// `FirmwarePort` Trait
+///
+/// Defines an interface for collecting the firmware versions of the machine,
+/// so they can be inventoried and checked against an approved baseline.
+pub trait FirmwarePort: Send + Sync {
+    /// Collects the firmware version of every component found.
+    ///
+    /// # Returns
+    /// A `Result` containing one entry per component, or an error message.
+    fn discover_firmware(&self) -> Result<Vec<FirmwareComponent>, String>;
+}
@@ -0,0 +1,25 @@
+use std::path::Path;
+
+/// `FlamegraphPort` Trait
+///
+/// Defines an interface for sampling the stacks of the machine while a run is
+/// in progress and drawing where its time went as a flamegraph. Implementations
+/// start sampling when the run starts and write the flamegraph when it ends.
+pub trait FlamegraphPort: Send + Sync {
+    /// Starts sampling stacks, until `finish`.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether sampling started, or why it could not.
+    fn start(&self) -> Result<(), String>;
+
+    /// Stops sampling and writes the flamegraph of the samples taken since `start`.
+    ///
+    /// # Arguments
+    /// * `title` - The title of the flamegraph, e.g. the name of the benchmark.
+    /// * `output` - The SVG file to write.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of samples drawn, or an error if sampling was not
+    /// started, took no samples or the file could not be written.
+    fn finish(&self, title: &str, output: &Path) -> Result<u64, String>;
+}
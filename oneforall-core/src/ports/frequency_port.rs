@@ -0,0 +1,16 @@
+use crate::domain::cpu_frequency::CoreFrequency;
+
+/// `FrequencyPort` Trait
+///
+/// Defines an interface for reading per-core clock speed, frequency governor,
+/// and C-state/P-state residency counters. Implementations are sampled
+/// repeatedly while stress tests and benchmarks run to expose turbo behavior
+/// and throttling over time.
+pub trait FrequencyPort: Send + Sync {
+    /// Reads the current frequency state of every online logical CPU.
+    ///
+    /// # Returns
+    /// A `Result` containing one reading per CPU, or an error message if the
+    /// platform does not expose frequency information.
+    fn sample_frequencies(&self) -> Result<Vec<CoreFrequency>, String>;
+}
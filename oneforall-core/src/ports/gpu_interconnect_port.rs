@@ -0,0 +1,28 @@
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::gpu_interconnect::{GpuTopology, InterconnectBenchmarkResult};
+
+/// `GpuInterconnectPort` Trait
+///
+/// Defines an interface for discovering how the GPUs of the machine are
+/// connected to each other, and for measuring the bandwidth and latency
+/// between every pair of GPUs.
+pub trait GpuInterconnectPort: Send + Sync {
+    /// Discovers the interconnect topology of the GPUs.
+    ///
+    /// # Returns
+    /// A `Result` containing the topology, empty on a machine without a GPU tool, or an error message.
+    fn discover_gpu_topology(&self) -> Result<GpuTopology, String>;
+
+    /// Measures the copy bandwidth, bidirectional bandwidth and latency between every pair of GPUs.
+    ///
+    /// # Arguments
+    /// * `cancel` - Stops the benchmark before the next measurement once cancelled.
+    ///
+    /// # Returns
+    /// A `Result` containing one measurement per ordered pair of GPUs, or an error message.
+    fn run_interconnect_benchmark(
+        &self,
+        cancel: &CancellationToken,
+    ) -> Result<InterconnectBenchmarkResult, String>;
+}
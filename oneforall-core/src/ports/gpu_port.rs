@@ -0,0 +1,14 @@
+use crate::domain::gpu::GpuReading;
+
+/// `GpuPort` Trait
+///
+/// Defines an interface for reading the temperature, clock, power and utilization
+/// of the GPUs of the machine, through the vendor tools or the kernel drivers.
+/// Implementations are sampled while GPU stress tests run.
+pub trait GpuPort: Send + Sync {
+    /// Reads every GPU.
+    ///
+    /// # Returns
+    /// A `Result` containing one reading per GPU, or an error if no GPU is readable.
+    fn sample_gpus(&self) -> Result<Vec<GpuReading>, String>;
+}
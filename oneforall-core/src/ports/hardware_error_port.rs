@@ -0,0 +1,46 @@
+use crate::domain::endurance::WearIndicators;
+use crate::domain::hardware_errors::{EccCounts, SmartHealth};
+use crate::domain::sel::SelEntry;
+
+/// `HardwareErrorPort` Trait
+///
+/// Defines an interface for reading the hardware error sources watched during
+/// a burn-in. Every source may be missing on a given machine, e.g. EDAC on
+/// hardware without ECC memory, so each is read independently.
+pub trait HardwareErrorPort: Send + Sync {
+    /// Reads the ECC error counters of every memory controller.
+    ///
+    /// # Returns
+    /// A `Result` containing the counters, or an error message if ECC reporting is unavailable.
+    fn read_ecc_counts(&self) -> Result<EccCounts, String>;
+
+    /// Counts the machine check events in the kernel log.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of events, or an error message if the log is unreadable.
+    fn count_machine_checks(&self) -> Result<u64, String>;
+
+    /// Reads the SMART health of every drive.
+    ///
+    /// # Returns
+    /// A `Result` containing one entry per drive, or an error message if SMART is unavailable.
+    fn read_smart_health(&self) -> Result<Vec<SmartHealth>, String>;
+
+    /// Reads the SMART wear indicators of one drive.
+    ///
+    /// # Returns
+    /// A `Result` containing the indicators the drive reports, or an error message if SMART is unavailable.
+    fn read_wear(&self, device: &str) -> Result<WearIndicators, String>;
+
+    /// Reads the System Event Log of the BMC.
+    ///
+    /// # Returns
+    /// A `Result` containing the entries, or an error message if the machine has no BMC to ask.
+    fn read_sel(&self) -> Result<Vec<SelEntry>, String>;
+
+    /// Clears the System Event Log of the BMC.
+    ///
+    /// # Returns
+    /// A `Result` indicating success, or an error message if the log could not be cleared.
+    fn clear_sel(&self) -> Result<(), String>;
+}
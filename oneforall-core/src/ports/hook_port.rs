@@ -0,0 +1,47 @@
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::hook::HookAction;
+
+/// `HookPort` Trait
+///
+/// Defines an interface for running the hooks of a test profile, commands or
+/// Redfish operations such as a firmware update, and for restarting the machine
+/// between the steps and passes of a profile.
+pub trait HookPort: Send + Sync {
+    /// Runs the action of a hook until it finishes, waiting for a task the BMC starts.
+    ///
+    /// # Arguments
+    /// * `action` - The command or Redfish operation to run.
+    /// * `timeout` - How long the hook may run before it fails, if limited.
+    /// * `cancel` - Stops the hook once cancelled.
+    ///
+    /// # Returns
+    /// A `Result` containing a one-line account of what the hook did, or an error if it
+    /// could not be run, failed, timed out or was cancelled.
+    fn run_hook(
+        &self,
+        action: &HookAction,
+        timeout: Option<Duration>,
+        cancel: &CancellationToken,
+    ) -> Result<String, String>;
+
+    /// Asks the operating system to reboot the machine.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the reboot was started.
+    fn reboot(&self) -> Result<(), String>;
+
+    /// Asks the BMC to power cycle the machine.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the BMC accepted the power cycle.
+    fn power_cycle(&self) -> Result<(), String>;
+
+    /// Reads the ID of the running boot, which changes every time the machine starts.
+    ///
+    /// # Returns
+    /// A `Result` containing the boot ID, or an error if it cannot be read.
+    fn boot_id(&self) -> Result<String, String>;
+}
@@ -0,0 +1,42 @@
+use std::path::Path;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::integrity::IntegrityResult;
+
+/// `IntegrityPort` Trait
+///
+/// Defines an interface for verifying that data written to memory and disk
+/// reads back unchanged while the machine is under load. Each method writes a
+/// pattern, re-reads it until told to stop and then verifies it one last time.
+pub trait IntegrityPort: Send + Sync {
+    /// Verifies a pattern held in a memory buffer.
+    ///
+    /// # Arguments
+    /// * `bytes` - The size of the buffer.
+    /// * `stop` - Ends the verification after a final pass once cancelled.
+    ///
+    /// # Returns
+    /// A `Result` containing the passes made and any corruption found, or an error if the buffer could not be set up.
+    fn verify_memory(
+        &self,
+        bytes: usize,
+        stop: &CancellationToken,
+    ) -> Result<IntegrityResult, String>;
+
+    /// Verifies a pattern written to a file, reading it back from the drive on every pass.
+    ///
+    /// # Arguments
+    /// * `directory` - The directory the file is written to; the file is deleted afterwards.
+    /// * `bytes` - The size of the file.
+    /// * `stop` - Ends the verification after a final pass once cancelled.
+    ///
+    /// # Returns
+    /// A `Result` containing the passes made and any corruption found, or an error if the file could not be written.
+    fn verify_disk(
+        &self,
+        directory: &Path,
+        bytes: u64,
+        stop: &CancellationToken,
+    ) -> Result<IntegrityResult, String>;
+}
@@ -0,0 +1,15 @@
+use crate::domain::interrupts::CpuInterrupts;
+
+/// `InterruptPort` Trait
+///
+/// Defines an interface for reading the cumulative per-CPU counters of network
+/// interrupts and softirqs, and the CPU time spent servicing interrupts.
+/// Implementations are sampled while runs load the network to show how the
+/// interrupts are spread over the CPUs.
+pub trait InterruptPort: Send + Sync {
+    /// Reads the interrupt counters of every logical CPU.
+    ///
+    /// # Returns
+    /// A `Result` containing the counters by CPU ID, or an error if they are unavailable.
+    fn sample_interrupts(&self) -> Result<Vec<CpuInterrupts>, String>;
+}
@@ -0,0 +1,22 @@
+use crate::domain::kernel_latency::KernelLatencyReport;
+
+/// `KernelLatencyPort` Trait
+///
+/// Defines an interface for measuring run queue latency, off-CPU time and block
+/// I/O latency in the kernel for the duration of a run. Implementations attach
+/// probes when the run starts and read the distributions they measured when it
+/// ends.
+pub trait KernelLatencyPort: Send + Sync {
+    /// Attaches the probes, which measure every event until `finish`.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the probes were attached, or why they could not be.
+    fn start(&self) -> Result<(), String>;
+
+    /// Reads the distributions measured since `start` and detaches the probes.
+    ///
+    /// # Returns
+    /// A `Result` containing the distributions, or an error if the probes were not attached
+    /// or could not be read.
+    fn finish(&self) -> Result<KernelLatencyReport, String>;
+}
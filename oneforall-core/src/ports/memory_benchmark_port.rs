@@ -0,0 +1,49 @@
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::cache::{CacheLevelResult, CacheSweepLevel};
+use crate::domain::numa::{NumaMemoryResult, NumaNode};
+
+/// `MemoryBenchmarkPort` Trait
+///
+/// Defines an interface for measuring memory subsystem performance. This trait
+/// abstracts how buffers are placed and measured, allowing implementations to
+/// use first-touch placement, libnuma, or external tools as the platform permits.
+pub trait MemoryBenchmarkPort: Send + Sync {
+    /// Measures memory bandwidth and latency between every pair of NUMA nodes.
+    ///
+    /// For each memory node, a buffer is placed on that node and then accessed
+    /// from a CPU on every node, producing a full local/remote matrix.
+    ///
+    /// # Arguments
+    /// * `nodes` - The NUMA nodes to include in the matrix.
+    /// * `buffer_bytes` - The size of the buffer placed on each node.
+    /// * `cancel` - Stops the benchmark before the next node pair once cancelled.
+    ///
+    /// # Returns
+    /// A `Result` containing one result per (CPU node, memory node) pair, or an error.
+    fn run_numa_matrix(
+        &self,
+        nodes: &[NumaNode],
+        buffer_bytes: usize,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<NumaMemoryResult>, String>;
+
+    /// Measures bandwidth and latency with a working set sized for each cache level.
+    ///
+    /// Every level is measured from the same CPU, so that the working sets only
+    /// compete with each other for its caches.
+    ///
+    /// # Arguments
+    /// * `cpu` - The logical CPU to run the measurements on.
+    /// * `levels` - The planned working sets, smallest first.
+    /// * `cancel` - Stops the benchmark before the next level once cancelled.
+    ///
+    /// # Returns
+    /// A `Result` containing one result per level, or an error.
+    fn run_cache_sweep(
+        &self,
+        cpu: u32,
+        levels: &[CacheSweepLevel],
+        cancel: &CancellationToken,
+    ) -> Result<Vec<CacheLevelResult>, String>;
+}
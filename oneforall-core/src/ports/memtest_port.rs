@@ -0,0 +1,27 @@
+use std::time::Instant;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::memtest::MemtestResult;
+
+/// `MemtestPort` Trait
+///
+/// Defines an interface for testing RAM with memtester-style pattern tests over
+/// a buffer locked in memory.
+pub trait MemtestPort: Send + Sync {
+    /// Runs passes of every pattern test until the deadline; the first pass always completes.
+    ///
+    /// # Arguments
+    /// * `bytes` - The size of the buffer to test.
+    /// * `deadline` - When to stop starting new tests.
+    /// * `stop` - Ends the test at once when cancelled.
+    ///
+    /// # Returns
+    /// A `Result` containing the passes made and the failing words, or an error if the buffer could not be set up.
+    fn run_memtest(
+        &self,
+        bytes: usize,
+        deadline: Instant,
+        stop: &CancellationToken,
+    ) -> Result<MemtestResult, String>;
+}
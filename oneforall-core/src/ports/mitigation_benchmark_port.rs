@@ -0,0 +1,30 @@
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::mitigations::MitigationOverhead;
+
+/// `MitigationBenchmarkPort` Trait
+///
+/// Defines an interface for reading how the kernel was told to mitigate CPU
+/// vulnerabilities and for measuring the system call and context switch
+/// costs those mitigations add to.
+pub trait MitigationBenchmarkPort: Send + Sync {
+    /// Reads the kernel command line.
+    ///
+    /// # Returns
+    /// A `Result` containing the command line, or an error message.
+    fn read_kernel_command_line(&self) -> Result<String, String>;
+
+    /// Measures the cost of a vDSO call, a system call and a context switch.
+    ///
+    /// # Arguments
+    /// * `cpu` - The logical CPU to run the measurements on.
+    /// * `cancel` - Stops the benchmark before the next measurement once cancelled.
+    ///
+    /// # Returns
+    /// A `Result` containing the measured costs, or an error.
+    fn measure_overhead(
+        &self,
+        cpu: u32,
+        cancel: &CancellationToken,
+    ) -> Result<MitigationOverhead, String>;
+}
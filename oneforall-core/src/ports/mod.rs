@@ -0,0 +1,53 @@
+pub mod agent_discovery_port;
+pub mod alert_port;
+pub mod battery_port;
+pub mod benchmark_plugin_port;
+pub mod block_device_port;
+pub mod boot_timing_port;
+pub mod clock_stability_port;
+pub mod cloud_metadata_port;
+pub mod cluster_port;
+pub mod codec_benchmark_port;
+pub mod container_port;
+pub mod cpu_features_port;
+pub mod database_port;
+pub mod device_inventory_port;
+pub mod dimm_port;
+pub mod entropy_port;
+pub mod filesystem_benchmark_port;
+pub mod firmware_port;
+pub mod flamegraph_port;
+pub mod frequency_port;
+pub mod gpu_interconnect_port;
+pub mod gpu_port;
+pub mod hardware_error_port;
+pub mod hook_port;
+pub mod integrity_port;
+pub mod interrupt_port;
+pub mod kernel_latency_port;
+pub mod memory_benchmark_port;
+pub mod memtest_port;
+pub mod mitigation_benchmark_port;
+pub mod nvme_port;
+pub mod object_store_port;
+pub mod pcie_port;
+pub mod perf_counter_port;
+pub mod power_port;
+pub mod powermetrics_port;
+pub mod profile_port;
+pub mod ps_command_port;
+pub mod rdma_port;
+pub mod scaling_benchmark_port;
+pub mod scheduler_latency_port;
+pub mod service_manager_port;
+pub mod simd_stress_port;
+pub mod storage_endurance_port;
+pub mod stress_ng_binary_port;
+pub mod stress_test_port;
+pub mod stressor_catalog_port;
+pub mod system_stats_port;
+pub mod thermal_port;
+pub mod time_sync_port;
+pub mod topology_port;
+pub mod virtualization_port;
+pub mod workload_port;
@@ -0,0 +1,28 @@
+use crate::domain::nvme::{NvmeController, NvmeHealth};
+
+/// `NvmePort` Trait
+///
+/// Defines an interface for reading the NVMe controllers of the machine, their
+/// namespaces, and the health and error log pages of the admin command set.
+pub trait NvmePort: Send + Sync {
+    /// Discovers every NVMe controller and its namespaces.
+    ///
+    /// # Returns
+    /// A `Result` containing the controllers ordered by name, or an error message.
+    fn discover_nvme_controllers(&self) -> Result<Vec<NvmeController>, String>;
+
+    /// Reads the SMART / Health Information and Error Information log pages of one controller.
+    ///
+    /// # Arguments
+    /// * `controller` - The character device of the controller, e.g. "nvme0".
+    ///
+    /// # Returns
+    /// A `Result` containing the health of the controller, or an error message if the log pages are unreadable.
+    fn read_nvme_health(&self, controller: &str) -> Result<NvmeHealth, String>;
+
+    /// Reads the health of every NVMe controller.
+    ///
+    /// # Returns
+    /// A `Result` containing one entry per controller, or an error message if there are none.
+    fn read_all_nvme_health(&self) -> Result<Vec<NvmeHealth>, String>;
+}
@@ -0,0 +1,21 @@
+/// `ObjectStorePort` Trait
+///
+/// Defines an interface for storing files in an object store, such as an
+/// S3-compatible bucket, where run artifacts are kept beyond the life of the
+/// machine that produced them. Uploads block until the store accepted or
+/// refused the object.
+pub trait ObjectStorePort: Send + Sync {
+    /// Returns a short description of the store for logs, e.g. "s3://qualification".
+    fn describe(&self) -> String;
+
+    /// Stores an object, replacing any object under the same key.
+    ///
+    /// # Arguments
+    /// * `key` - The object key, e.g. "rack12-node3/burn-in-20240502T100000/result.json".
+    /// * `content_type` - The MIME type of the content.
+    /// * `body` - The content.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the store accepted the object, or an error.
+    fn put_object(&self, key: &str, content_type: &str, body: &[u8]) -> Result<(), String>;
+}
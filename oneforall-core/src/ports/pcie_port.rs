@@ -0,0 +1,31 @@
+use crate::domain::pcie::{PcieBandwidth, PcieLink};
+
+/// `PciePort` Trait
+///
+/// Defines an interface for discovering the PCIe links of the devices of the
+/// machine and measuring the bandwidth that actually crosses them.
+pub trait PciePort: Send + Sync {
+    /// Discovers the link of every PCIe device, bridges excluded.
+    ///
+    /// # Returns
+    /// A `Result` containing the links ordered by PCI address, or an error message.
+    fn discover_pcie_links(&self) -> Result<Vec<PcieLink>, String>;
+
+    /// Measures the read bandwidth of an NVMe drive from the host.
+    ///
+    /// # Arguments
+    /// * `link` - The link of the NVMe controller; its namespace is read, never written.
+    ///
+    /// # Returns
+    /// A `Result` containing the measured bandwidth, or an error message.
+    fn measure_nvme_bandwidth(&self, link: &PcieLink) -> Result<PcieBandwidth, String>;
+
+    /// Measures the host to GPU, GPU to host and GPU peer-to-peer bandwidth with nvbandwidth.
+    ///
+    /// # Arguments
+    /// * `links` - The links of every device, used to compare each GPU with its link.
+    ///
+    /// # Returns
+    /// A `Result` containing the measured bandwidths, or an error message.
+    fn measure_gpu_bandwidth(&self, links: &[PcieLink]) -> Result<Vec<PcieBandwidth>, String>;
+}
@@ -0,0 +1,22 @@
+use crate::domain::perf_counters::CoreCounters;
+
+/// `PerfCounterPort` Trait
+///
+/// Defines an interface for counting hardware events such as instructions,
+/// cycles, branch misses and cache misses on every CPU for the duration of a
+/// run. Implementations open the counters when the run starts and read them
+/// when it ends.
+pub trait PerfCounterPort: Send + Sync {
+    /// Opens the counters, which count every event until `finish`.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether any counter was opened, or why none could be.
+    fn start(&self) -> Result<(), String>;
+
+    /// Reads the counts since `start` and closes the counters.
+    ///
+    /// # Returns
+    /// A `Result` containing the counts of each CPU, or an error if the counters were not
+    /// opened.
+    fn finish(&self) -> Result<Vec<CoreCounters>, String>;
+}
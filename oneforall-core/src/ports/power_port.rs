@@ -0,0 +1,20 @@
+use crate::domain::power::EnergyCounter;
+
+/// `PowerPort` Trait
+///
+/// Defines an interface for reading the power sources of the machine: the
+/// cumulative energy counters exposed by the CPU (Intel RAPL, AMD energy) and
+/// the whole-system power reading of the BMC where one is present.
+pub trait PowerPort: Send + Sync {
+    /// Reads every cumulative CPU energy counter.
+    ///
+    /// # Returns
+    /// A `Result` containing the counters, or an error if none are exposed.
+    fn read_energy_counters(&self) -> Result<Vec<EnergyCounter>, String>;
+
+    /// Reads the instantaneous whole-system power from the BMC.
+    ///
+    /// # Returns
+    /// A `Result` containing the power in watts, or an error if no BMC is reachable.
+    fn read_bmc_power_watts(&self) -> Result<f64, String>;
+}
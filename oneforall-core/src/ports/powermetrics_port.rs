@@ -0,0 +1,19 @@
+use std::time::Duration;
+
+use crate::domain::powermetrics::PowermetricsSample;
+
+/// `PowermetricsPort` Trait
+///
+/// Defines an interface for sampling the activity of the performance and
+/// efficiency cores, the power and the thermal pressure of a Mac. Implementations
+/// are sampled while stress tests and benchmarks run on macOS.
+pub trait PowermetricsPort: Send + Sync {
+    /// Takes one reading over a sampling window.
+    ///
+    /// # Arguments
+    /// * `window` - The time the reading averages over.
+    ///
+    /// # Returns
+    /// A `Result` containing the reading, or an error if powermetrics cannot run.
+    fn sample_powermetrics(&self, window: Duration) -> Result<PowermetricsSample, String>;
+}
@@ -0,0 +1,23 @@
+use crate::domain::profile::Profile;
+
+/// `ProfilePort` Trait
+///
+/// Defines an interface for finding the test profiles that can be run with
+/// `oneforall run --profile <name>`, whether they ship with OneForAll or are
+/// written by the operator.
+pub trait ProfilePort: Send + Sync {
+    /// Lists every available profile.
+    ///
+    /// # Returns
+    /// A `Result` containing the profiles ordered by name, or an error message.
+    fn list_profiles(&self) -> Result<Vec<Profile>, String>;
+
+    /// Loads a profile by name or from a TOML file.
+    ///
+    /// # Arguments
+    /// * `name` - A profile name such as `burn-in-24h`, or the path of a `.toml` file.
+    ///
+    /// # Returns
+    /// A `Result` containing the validated profile, or an error message.
+    fn load_profile(&self, name: &str) -> Result<Profile, String>;
+}
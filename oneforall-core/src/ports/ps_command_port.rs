@@ -1,3 +1,7 @@
+use std::time::Duration;
+
+use crate::domain::ps_command::ProcessTree;
+
 /// `PsCommandPort` Trait
 ///
 /// Defines an interface for executing the `ps` command to monitor system processes.
@@ -31,6 +35,15 @@ pub trait PsCommandPort: Send + Sync {
     /// A `Result` indicating the success or failure of the write operation.
     fn write_to_file(&self, output: String, file_path: &str) -> Result<(), String>;
 
+    /// Lists every process with its parent and cgroup.
+    ///
+    /// The tree attributes CPU and memory use to the service or container
+    /// running the processes rather than to single processes.
+    ///
+    /// # Returns
+    /// A `Result` containing the process tree, or an error.
+    fn read_process_tree(&self) -> Result<ProcessTree, String>;
+
     // Placeholder for additional methods related to `ps` command management, e.g., custom sorting or filtering.
     // fn sort_processes(&self, criteria: &str) -> Result<Vec<ProcessInfo>, String>;
     // fn filter_processes(&self, filter: &str) -> Result<Vec<ProcessInfo>, String>;
@@ -39,7 +52,8 @@ pub trait PsCommandPort: Send + Sync {
     ///
     /// # Arguments
     /// * `output_file_path` - The path to the file where the command output will be saved.
-    fn collect_cpu_statistics(&self, output_file_path: &str);
+    /// * `interval` - The time between two executions of the command.
+    fn collect_cpu_statistics(&self, output_file_path: &str, interval: Duration);
 
     /// Writes the output of the `ps` command to a database.
     ///
@@ -48,7 +62,7 @@ pub trait PsCommandPort: Send + Sync {
     ///
     /// # Arguments
     /// * `output` - The output string from the `ps` command.
-    /// * `key` - The key to use for writing to the database.
+    /// * `key` - The collector name to store the output under in the database.
     ///
     /// # Returns
     /// A `Result` indicating the success or failure of the write operation.
@@ -0,0 +1,51 @@
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::rdma::{RdmaBenchmarkResult, RdmaDevice};
+
+/// `RdmaPort` Trait
+///
+/// Defines an interface for discovering the RDMA devices of the machine and
+/// benchmarking the RDMA bandwidth and latency of a fabric, either between two
+/// machines or in loopback through one device.
+pub trait RdmaPort: Send + Sync {
+    /// Discovers every RDMA device and its ports.
+    ///
+    /// # Returns
+    /// A `Result` containing the devices ordered by name, or an error message.
+    fn discover_rdma_devices(&self) -> Result<Vec<RdmaDevice>, String>;
+
+    /// Measures the RDMA write bandwidth and read latency of a device.
+    ///
+    /// # Arguments
+    /// * `device` - The RDMA device to use, e.g. "mlx5_0".
+    /// * `port` - The port of the device to use.
+    /// * `peer` - The machine serving the other side with `serve_rdma_benchmark`, or
+    ///   `None` to measure in loopback through the device.
+    /// * `cancel` - Stops the benchmark before the next test once cancelled.
+    ///
+    /// # Returns
+    /// A `Result` containing the bandwidth and latency measured, or an error message.
+    fn run_rdma_benchmark(
+        &self,
+        device: &str,
+        port: u32,
+        peer: Option<&str>,
+        cancel: &CancellationToken,
+    ) -> Result<RdmaBenchmarkResult, String>;
+
+    /// Serves the other side of one `run_rdma_benchmark` from a peer, then returns.
+    ///
+    /// # Arguments
+    /// * `device` - The RDMA device to use, e.g. "mlx5_0".
+    /// * `port` - The port of the device to use.
+    /// * `cancel` - Stops serving before the next test once cancelled.
+    ///
+    /// # Returns
+    /// A `Result` that is `Ok` once the peer has run every test, or an error message.
+    fn serve_rdma_benchmark(
+        &self,
+        device: &str,
+        port: u32,
+        cancel: &CancellationToken,
+    ) -> Result<(), String>;
+}
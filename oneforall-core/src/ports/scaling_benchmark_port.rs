@@ -0,0 +1,30 @@
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::cpu_topology::CpuTopology;
+use crate::domain::scaling::ScalingResult;
+
+/// `ScalingBenchmarkPort` Trait
+///
+/// Defines an interface for measuring how the throughput of a CPU workload
+/// scales with the number of threads running it.
+pub trait ScalingBenchmarkPort: Send + Sync {
+    /// Runs the workload at each planned thread count, then on each socket alone.
+    ///
+    /// # Arguments
+    /// * `topology` - The CPU topology the threads are placed on.
+    /// * `max_threads` - The most threads to run; every online CPU when `None`.
+    /// * `step_duration` - How long the workload runs at each thread count.
+    /// * `cancel` - Stops the benchmark before the next thread count once cancelled.
+    ///
+    /// # Returns
+    /// A `Result` containing the throughput at each thread count and of each socket, or an error.
+    fn run_scaling_benchmark(
+        &self,
+        topology: &CpuTopology,
+        max_threads: Option<usize>,
+        step_duration: Duration,
+        cancel: &CancellationToken,
+    ) -> Result<ScalingResult, String>;
+}
@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::scheduler_latency::SchedulerLatencyResult;
+
+/// `SchedulerLatencyPort` Trait
+///
+/// Defines an interface for measuring the latencies a low-latency workload
+/// sees from the scheduler: context switches, wake-ups and timer expiries.
+pub trait SchedulerLatencyPort: Send + Sync {
+    /// Measures context switch, wake-up and timer latencies.
+    ///
+    /// # Arguments
+    /// * `cpu` - The logical CPU the measured threads run on.
+    /// * `waker_cpu` - The logical CPU wake-ups are sent from; may equal `cpu`.
+    /// * `timer_interval` - The period of the timer whose jitter is measured.
+    /// * `timer_duration` - How long the timer jitter is measured for.
+    /// * `cancel` - Stops the benchmark before the next sample once cancelled.
+    ///
+    /// # Returns
+    /// A `Result` containing the measured latency distributions, or an error.
+    fn measure_scheduler_latency(
+        &self,
+        cpu: u32,
+        waker_cpu: u32,
+        timer_interval: Duration,
+        timer_duration: Duration,
+        cancel: &CancellationToken,
+    ) -> Result<SchedulerLatencyResult, String>;
+}
@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+/// `ServiceManagerPort` Trait
+///
+/// Defines an interface for reporting the state of a long-running instance to
+/// the service manager that started it, such as systemd. Every notification is
+/// best effort: an instance that was not started by a service manager simply
+/// has nobody to tell.
+pub trait ServiceManagerPort: Send + Sync {
+    /// Returns `true` if a service manager is listening for notifications.
+    fn is_supervised(&self) -> bool;
+
+    /// Reports that start-up is complete and the instance is serving requests.
+    ///
+    /// # Arguments
+    /// * `status` - A one-line status shown by the service manager, e.g. in `systemctl status`.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the notification was sent, or an error.
+    fn notify_ready(&self, status: &str) -> Result<(), String>;
+
+    /// Reports that the instance is shutting down, e.g. after SIGTERM.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the notification was sent, or an error.
+    fn notify_stopping(&self) -> Result<(), String>;
+
+    /// Returns how often the instance must report that it is alive, if the
+    /// service manager watches it.
+    fn watchdog_interval(&self) -> Option<Duration>;
+
+    /// Reports that the instance is alive and responsive.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the notification was sent, or an error.
+    fn notify_watchdog(&self) -> Result<(), String>;
+}
@@ -0,0 +1,36 @@
+use std::time::Instant;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::simd::SimdIsa;
+
+/// `SimdStressPort` Trait
+///
+/// Defines an interface for loading every core with floating-point work in one
+/// instruction set, scalar or vector.
+pub trait SimdStressPort: Send + Sync {
+    /// Lists the vector instruction sets this CPU and OS can run.
+    ///
+    /// # Returns
+    /// The supported instruction sets, from the narrowest to the widest.
+    fn supported_isas(&self) -> Vec<SimdIsa>;
+
+    /// Runs fused multiply-adds in one instruction set on several threads until the deadline.
+    ///
+    /// # Arguments
+    /// * `isa` - The instruction set the threads run.
+    /// * `threads` - The number of threads, one per online CPU.
+    /// * `deadline` - When the threads stop.
+    /// * `stop` - Ends the phase at once when cancelled.
+    ///
+    /// # Returns
+    /// A `Result` containing the floating-point operations completed per second by every
+    /// thread together, or an error if the CPU cannot run the instruction set.
+    fn run_simd_load(
+        &self,
+        isa: SimdIsa,
+        threads: usize,
+        deadline: Instant,
+        stop: &CancellationToken,
+    ) -> Result<f64, String>;
+}
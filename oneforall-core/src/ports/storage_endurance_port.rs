@@ -0,0 +1,36 @@
+use std::path::Path;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::endurance::WriteVerifyResult;
+
+/// `StorageEndurancePort` Trait
+///
+/// Defines an interface for the write and verify workload of the storage
+/// endurance test. Implementations must make sure every byte is read back from
+/// the drive rather than from the page cache, or corruption would go unnoticed.
+pub trait StorageEndurancePort: Send + Sync {
+    /// Writes pseudorandom data to files in a directory, syncs it and reads it back.
+    ///
+    /// # Arguments
+    /// * `directory` - The directory on the drive under test; it must already exist.
+    /// * `total_bytes` - The volume to write.
+    /// * `file_bytes` - The size of each test file; each is deleted once verified.
+    /// * `cancel` - Stops the workload before the next block once cancelled.
+    ///
+    /// # Returns
+    /// A `Result` containing the volumes written and verified and any corruption found, or an error.
+    fn write_and_verify(
+        &self,
+        directory: &Path,
+        total_bytes: u64,
+        file_bytes: u64,
+        cancel: &CancellationToken,
+    ) -> Result<WriteVerifyResult, String>;
+
+    /// Finds the drive a path is stored on.
+    ///
+    /// # Returns
+    /// A `Result` containing the whole-disk device, e.g. `/dev/nvme0n1`, or an error.
+    fn backing_device(&self, path: &Path) -> Result<String, String>;
+}
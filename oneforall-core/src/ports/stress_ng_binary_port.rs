@@ -0,0 +1,15 @@
+use std::path::PathBuf;
+
+/// `StressNgBinaryPort` Trait
+///
+/// Defines an interface for locating a stress-ng executable that can run on
+/// this machine, whether it is bundled with OneForAll, cached from an earlier
+/// run, or installed on the system.
+pub trait StressNgBinaryPort: Send + Sync {
+    /// Returns the path of a verified stress-ng executable for this platform.
+    ///
+    /// # Returns
+    /// A `Result` containing the executable path, or an error message explaining
+    /// why no compatible binary is available and how to provide one.
+    fn locate_binary(&self) -> Result<PathBuf, String>;
+}
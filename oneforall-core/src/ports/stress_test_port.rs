@@ -1,7 +1,7 @@
 use common::ports::log_port::LoggerPort;
 
+use crate::domain::platform::Platform;
 use crate::ports::database_port::DatabasePort;
-use crate::StressNgArch;
 
 /// `StressTestPort` Trait
 ///
@@ -28,15 +28,15 @@ pub trait StressTestPort {
     /// The serial number as a `String`.
     fn get_system_serial_number(&self) -> String;
 
-    /// Determines the appropriate `stress-ng` binary version based on the OS.
+    /// Determines the platform whose `stress-ng` binary should be used.
     ///
-    /// This method should identify the operating system and return the corresponding
-    /// `StressNgArch` enum variant, ensuring compatibility and proper functioning
-    /// of stress tests across different platforms.
+    /// This method should identify the operating system and CPU architecture,
+    /// ensuring compatibility and proper functioning of stress tests across
+    /// different platforms.
     ///
     /// # Returns
-    /// An enum variant representing the architecture-specific version of `stress-ng`.
-    fn decide_stress_ng_arch() -> StressNgArch;
+    /// The `Platform` to select the `stress-ng` binary for.
+    fn decide_stress_ng_platform() -> Platform;
 
     /// Retrieves the serial number of the system based on the operating system.
     ///
@@ -55,7 +55,7 @@ pub trait StressTestPort {
     /// or limiting the output for specific monitoring requirements.
     ///
     /// # Arguments
-    /// * `stress_ng_arch` - The architecture-specific version of `stress-ng` to use.
+    /// * `platform` - The platform whose `stress-ng` binary to use.
     /// * `stress_ng_args` - A vector of arguments to pass to the `stress-ng` command.
     /// * `output_file_path` - The path to the file where the command output will be saved.
     /// * `log_file_path` - The path to the file where the command output will be saved.
@@ -72,9 +72,10 @@ pub trait StressTestPort {
     ///
     /// # Returns
     /// A `Result` containing either the command output as a `String` or an error.
+    #[allow(clippy::too_many_arguments)]
     fn execute_stress_ng_command(
         &self,
-        stress_ng_arch: StressNgArch,
+        platform: Platform,
         stress_ng_args: Vec<String>,
         output_file_path: &str,
         log_file_path: &str,
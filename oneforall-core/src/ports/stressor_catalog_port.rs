@@ -0,0 +1,14 @@
+use crate::domain::stress_ng::StressorCatalog;
+
+/// `StressorCatalogPort` Trait
+///
+/// Defines an interface for inspecting the stressors the stress-ng binary of
+/// this machine offers, without running any of them.
+pub trait StressorCatalogPort: Send + Sync {
+    /// Returns the stressors and their options, from a cache when the binary is unchanged.
+    ///
+    /// # Returns
+    /// A `Result` containing the catalog, or an error message if stress-ng is not
+    /// available or could not be inspected.
+    fn load_catalog(&self) -> Result<StressorCatalog, String>;
+}
@@ -0,0 +1,29 @@
+use crate::domain::system_stats::{CpuTimes, MemoryUsage, ProcessUsage};
+
+/// `SystemStatsPort` Trait
+///
+/// Defines an interface for reading the live system statistics shown by
+/// Overwatch: cumulative per-core CPU times, memory usage, and the processes
+/// consuming the most CPU.
+pub trait SystemStatsPort: Send + Sync {
+    /// Reads the cumulative CPU time counters of every logical CPU.
+    ///
+    /// # Returns
+    /// A `Result` containing the counters keyed by logical CPU ID, or an error message.
+    fn read_cpu_times(&self) -> Result<Vec<(u32, CpuTimes)>, String>;
+
+    /// Reads the current memory usage.
+    ///
+    /// # Returns
+    /// A `Result` containing the memory usage, or an error message.
+    fn read_memory_usage(&self) -> Result<MemoryUsage, String>;
+
+    /// Lists the processes consuming the most CPU.
+    ///
+    /// # Arguments
+    /// * `limit` - The maximum number of processes to return.
+    ///
+    /// # Returns
+    /// A `Result` containing the processes ordered by CPU usage, or an error message.
+    fn read_top_processes(&self, limit: usize) -> Result<Vec<ProcessUsage>, String>;
+}
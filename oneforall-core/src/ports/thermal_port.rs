@@ -0,0 +1,14 @@
+use crate::domain::thermal::ThermalSample;
+
+/// `ThermalPort` Trait
+///
+/// Defines an interface for reading fan speeds and temperatures, either from
+/// the kernel hwmon drivers or from the BMC. Implementations are sampled while
+/// stress tests run to correlate fan response with temperature ramps.
+pub trait ThermalPort: Send + Sync {
+    /// Reads every fan and temperature sensor.
+    ///
+    /// # Returns
+    /// A `Result` containing the readings, or an error if no sensor is readable.
+    fn sample_thermals(&self) -> Result<ThermalSample, String>;
+}
@@ -0,0 +1,14 @@
+use crate::domain::time_sync::ClockSync;
+
+/// `TimeSyncPort` Trait
+///
+/// Defines an interface for checking how closely the system clock follows its
+/// NTP or PTP reference, so results from several machines can be correlated by time.
+pub trait TimeSyncPort: Send + Sync {
+    /// Reads the synchronization of the system clock.
+    ///
+    /// # Returns
+    /// A `Result` containing the synchronization reported by the first source
+    /// found, or an error message if none could be read.
+    fn check_clock(&self) -> Result<ClockSync, String>;
+}
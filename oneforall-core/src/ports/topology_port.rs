@@ -0,0 +1,42 @@
+use crate::domain::cache::CacheLevel;
+use crate::domain::cpu_architecture::CpuArchitecture;
+use crate::domain::cpu_topology::CpuTopology;
+use crate::domain::numa::NumaNode;
+
+/// `TopologyPort` Trait
+///
+/// Defines an interface for discovering the CPU topology of the running system.
+/// This trait abstracts how logical CPUs, physical cores, packages, and NUMA nodes
+/// are enumerated, allowing platform-specific implementations (sysfs on Linux,
+/// sysctl on macOS) to be swapped without touching the stress placement logic.
+pub trait TopologyPort: Send + Sync {
+    /// Discovers the CPU topology of the machine.
+    ///
+    /// # Returns
+    /// A `Result` containing the discovered `CpuTopology` or an error message.
+    fn discover_cpu_topology(&self) -> Result<CpuTopology, String>;
+
+    /// Discovers the NUMA nodes of the machine, their CPUs, memory, and distances.
+    ///
+    /// Platforms without NUMA information report a single node containing every CPU.
+    ///
+    /// # Returns
+    /// A `Result` containing the discovered nodes sorted by id, or an error message.
+    fn discover_numa_nodes(&self) -> Result<Vec<NumaNode>, String>;
+
+    /// Discovers the architecture-specific details of the CPU: its core classes
+    /// (e.g. big.LITTLE or hybrid performance and efficiency cores) and SIMD extensions.
+    ///
+    /// # Returns
+    /// A `Result` containing the discovered `CpuArchitecture`, or an error message.
+    fn discover_cpu_architecture(&self) -> Result<CpuArchitecture, String>;
+
+    /// Discovers the data and unified caches seen from a logical CPU.
+    ///
+    /// # Arguments
+    /// * `cpu` - The logical CPU whose caches to describe.
+    ///
+    /// # Returns
+    /// A `Result` containing the caches ordered by level, or an error message.
+    fn discover_caches(&self, cpu: u32) -> Result<Vec<CacheLevel>, String>;
+}
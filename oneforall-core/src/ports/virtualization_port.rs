@@ -0,0 +1,14 @@
+use crate::domain::virtualization::VirtualizationInfo;
+
+/// `VirtualizationPort` Trait
+///
+/// Defines an interface for detecting whether this machine is a virtual
+/// machine, which hypervisor it runs on and which paravirtual devices it has.
+pub trait VirtualizationPort: Send + Sync {
+    /// Detects the virtualization of this machine.
+    ///
+    /// # Returns
+    /// A `Result` containing the hypervisor, the paravirtual devices and the
+    /// steal time since boot, or an error message.
+    fn detect_virtualization(&self) -> Result<VirtualizationInfo, String>;
+}
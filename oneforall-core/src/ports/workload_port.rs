@@ -0,0 +1,28 @@
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::domain::workload::WorkloadResult;
+
+/// `WorkloadPort` Trait
+///
+/// Defines an interface for running a command of the user's own as a benchmark
+/// and measuring the time, CPU and memory it used.
+pub trait WorkloadPort: Send + Sync {
+    /// Runs a command until it exits.
+    ///
+    /// # Arguments
+    /// * `command` - The program and its arguments.
+    /// * `timeout` - How long the command may run before it is killed, if limited.
+    /// * `cancel` - Kills the command once cancelled.
+    ///
+    /// # Returns
+    /// A `Result` containing the figures of the run, or an error if the command could not
+    /// be started, failed, timed out or was cancelled.
+    fn run_workload(
+        &self,
+        command: &[String],
+        timeout: Option<Duration>,
+        cancel: &CancellationToken,
+    ) -> Result<WorkloadResult, String>;
+}
@@ -0,0 +1,185 @@
+//! Remote Agent
+//!
+//! This module calls the gRPC control plane of a remote agent through the
+//! `oneforall-client` crate. Each call blocks until the agent answers, without
+//! holding the GIL, so other Python threads keep running meanwhile.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::future::Future;
+
+use oneforall_client::{ClientBuilder, OneForAllClient, ResultFilter};
+use pyo3::prelude::*;
+use serde_json::{json, Value};
+use tokio::runtime::Runtime;
+
+use crate::{error, to_python};
+
+/// A connection to the control plane of a remote agent.
+#[pyclass(module = "oneforall")]
+pub struct Agent {
+    client: OneForAllClient,
+    runtime: Runtime, // runs the calls, which are async
+}
+
+impl Agent {
+    /// Runs a call to the agent without holding the GIL.
+    fn call<T: Send>(
+        &self,
+        py: Python<'_>,
+        call: impl Future<Output = Result<T, String>> + Send,
+    ) -> PyResult<T> {
+        py.allow_threads(|| self.runtime.block_on(call))
+            .map_err(error)
+    }
+}
+
+#[pymethods]
+impl Agent {
+    /// Connects to the control plane of an agent, e.g. "http://rack12-node3:50051".
+    ///
+    /// `token` is a token of the agent's `[api]` section; launching and
+    /// cancelling jobs needs an operator token. For HTTPS, `ca_certificate` is
+    /// the PEM file of the CA that signed the agent's certificate, and
+    /// `certificate` and `private_key` the client certificate, if the agent
+    /// requires one.
+    #[new]
+    #[pyo3(signature = (endpoint, token = None, ca_certificate = None, certificate = None, private_key = None))]
+    fn new(
+        py: Python<'_>,
+        endpoint: &str,
+        token: Option<&str>,
+        ca_certificate: Option<&str>,
+        certificate: Option<&str>,
+        private_key: Option<&str>,
+    ) -> PyResult<Self> {
+        let read = |path: &str| {
+            fs::read(path).map_err(|e| error(format!("Failed to read {}: {}", path, e)))
+        };
+        let mut builder = ClientBuilder::new(endpoint);
+        if let Some(token) = token {
+            builder = builder.with_token(token);
+        }
+        if let Some(path) = ca_certificate {
+            builder = builder.with_ca_certificate(read(path)?);
+        }
+        match (certificate, private_key) {
+            (Some(certificate), Some(private_key)) => {
+                builder = builder.with_identity(read(certificate)?, read(private_key)?);
+            }
+            (None, None) => {}
+            _ => return Err(error("certificate and private_key must be given together")),
+        }
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .map_err(|e| error(format!("Failed to start the runtime: {}", e)))?;
+        let client = py
+            .allow_threads(|| runtime.block_on(builder.connect()))
+            .map_err(error)?;
+        Ok(Agent { client, runtime })
+    }
+
+    /// Starts running a built-in or saved profile, e.g. "burn-in-24h", as a job
+    /// on the agent and returns the job as a dict without waiting for it.
+    fn launch_profile(&self, py: Python<'_>, profile: &str) -> PyResult<PyObject> {
+        let job = self.call(py, self.client.launch_profile(profile))?;
+        to_python(py, &job)
+    }
+
+    /// Lists every job submitted on the agent since it started, including queued ones.
+    fn list_jobs(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let jobs = self.call(py, self.client.list_jobs())?;
+        to_python(py, &jobs)
+    }
+
+    /// Returns a job, or `None` if the agent does not know it.
+    fn get_job(&self, py: Python<'_>, id: u64) -> PyResult<Option<PyObject>> {
+        let job = self.call(py, self.client.get_job(id))?;
+        job.map(|job| to_python(py, &job)).transpose()
+    }
+
+    /// Requests cancellation of a queued or running job and returns it.
+    fn cancel_job(&self, py: Python<'_>, id: u64) -> PyResult<PyObject> {
+        let job = self.call(py, self.client.cancel_job(id))?;
+        to_python(py, &job)
+    }
+
+    /// Waits until a job has finished and returns it.
+    fn wait_for_job(&self, py: Python<'_>, id: u64) -> PyResult<PyObject> {
+        let job = self.call(py, self.client.wait_for_job(id))?;
+        to_python(py, &job)
+    }
+
+    /// Lists the results stored on the agent as dicts with their `key` and
+    /// `kind`, optionally only those of one kind, of runs started in an
+    /// RFC 3339 time range, or carrying every one of the tags.
+    #[pyo3(signature = (kind = None, since = None, until = None, tags = None))]
+    fn list_results(
+        &self,
+        py: Python<'_>,
+        kind: Option<String>,
+        since: Option<String>,
+        until: Option<String>,
+        tags: Option<BTreeMap<String, String>>,
+    ) -> PyResult<PyObject> {
+        let filter = ResultFilter {
+            kind,
+            since,
+            until,
+            tags: tags.unwrap_or_default(),
+        };
+        let results = self.call(py, self.client.list_results(&filter))?;
+        let results: Vec<Value> = results
+            .into_iter()
+            .map(|result| json!({ "key": result.key, "kind": result.kind }))
+            .collect();
+        to_python(py, &results)
+    }
+
+    /// Returns a stored result as a dict with its `key`, `kind` and `report`,
+    /// or `None` if there is none under the key.
+    fn get_result(&self, py: Python<'_>, key: &str) -> PyResult<Option<PyObject>> {
+        let result = self.call(py, self.client.get_result::<Value>(key))?;
+        result
+            .map(|result| {
+                to_python(
+                    py,
+                    &json!({ "key": result.key, "kind": result.kind, "report": result.report }),
+                )
+            })
+            .transpose()
+    }
+
+    /// Lists the readings a collector, e.g. "power" or "processes", stored on
+    /// the agent in an RFC 3339 time range, oldest first, as dicts with the
+    /// `sample` and the times it was taken at.
+    #[pyo3(signature = (collector, since = None, until = None))]
+    fn list_samples(
+        &self,
+        py: Python<'_>,
+        collector: &str,
+        since: Option<&str>,
+        until: Option<&str>,
+    ) -> PyResult<PyObject> {
+        let samples = self.call(
+            py,
+            self.client.list_samples::<Value>(collector, since, until),
+        )?;
+        let samples: Vec<Value> = samples
+            .into_iter()
+            .map(|stored| {
+                json!({
+                    "recorded_at": stored.recorded_at,
+                    "sample": stored.sample,
+                    "wall_time": stored.wall_time,
+                    "monotonic_ns": stored.monotonic_ns,
+                    "boot_id": stored.boot_id,
+                })
+            })
+            .collect();
+        to_python(py, &samples)
+    }
+}
@@ -0,0 +1,135 @@
+//! Local Database
+//!
+//! This module reads the results and readings stored by OneForAll on the
+//! machine Python runs on, from the database the `[database]` section of the
+//! configuration file selects. sled allows one process at a time, so while
+//! Overwatch runs, its results are queried through `Agent` instead.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use oneforall_core::adapters::database_adapter::{load_database_config, open_database};
+use oneforall_core::adapters::repository::Repository;
+use oneforall_core::domain::database::key_time;
+use oneforall_core::domain::grpc::{result_kind, RESULT_KINDS};
+use oneforall_core::domain::tags::Tags;
+use pyo3::prelude::*;
+use serde_json::{json, Value};
+
+use crate::{error, logger, parse_time_bound, to_python};
+
+/// The results and readings stored by OneForAll on this machine.
+#[pyclass(module = "oneforall")]
+pub struct Database {
+    repository: Repository,
+}
+
+#[pymethods]
+impl Database {
+    /// Opens the database configured in `config`, the default one when the
+    /// file does not exist.
+    #[new]
+    #[pyo3(signature = (config = "oneforall.toml"))]
+    fn new(config: &str) -> PyResult<Self> {
+        let config = load_database_config(Path::new(config)).map_err(error)?;
+        let database = open_database(&config, logger()).map_err(error)?;
+        Ok(Database {
+            repository: Repository::new(database),
+        })
+    }
+
+    /// Lists the stored results as dicts with their `key` and `kind`, oldest
+    /// first within each kind, optionally only those of one kind, of runs
+    /// started in an RFC 3339 time range, or carrying every one of the tags.
+    #[pyo3(signature = (kind = None, since = None, until = None, tags = None))]
+    fn list_results(
+        &self,
+        py: Python<'_>,
+        kind: Option<&str>,
+        since: Option<&str>,
+        until: Option<&str>,
+        tags: Option<BTreeMap<String, String>>,
+    ) -> PyResult<PyObject> {
+        let kinds: Vec<&str> = match kind {
+            None => RESULT_KINDS.to_vec(),
+            Some(kind) if RESULT_KINDS.contains(&kind) => vec![kind],
+            Some(kind) => {
+                return Err(error(format!(
+                    "Unknown result kind '{}'; expected one of {}",
+                    kind,
+                    RESULT_KINDS.join(", ")
+                )))
+            }
+        };
+        let since = parse_time_bound("since", since)?;
+        let until = parse_time_bound("until", until)?;
+        let tags: Tags = tags.unwrap_or_default();
+
+        let mut results = Vec::new();
+        for kind in kinds {
+            let keys = py
+                .allow_threads(|| {
+                    self.repository
+                        .find_runs(kind, since.as_ref(), until.as_ref(), &tags)
+                })
+                .map_err(error)?;
+            results.extend(
+                keys.into_iter()
+                    .map(|key| json!({ "key": key, "kind": kind })),
+            );
+        }
+        to_python(py, &results)
+    }
+
+    /// Returns a stored result as a dict with its `key`, `kind` and `report`,
+    /// migrated to the current schema version, or `None` if there is none under the key.
+    fn get_result(&self, py: Python<'_>, key: &str) -> PyResult<Option<PyObject>> {
+        let Some(kind) = result_kind(key) else {
+            return Ok(None);
+        };
+        let report: Option<Value> = py
+            .allow_threads(|| self.repository.get_run(key))
+            .map_err(error)?;
+        report
+            .map(|report| to_python(py, &json!({ "key": key, "kind": kind, "report": report })))
+            .transpose()
+    }
+
+    /// Lists the readings a collector, e.g. "power" or "processes", stored in an
+    /// RFC 3339 time range, oldest first, as dicts with the `sample` and the
+    /// times it was taken at.
+    #[pyo3(signature = (collector, since = None, until = None))]
+    fn list_samples(
+        &self,
+        py: Python<'_>,
+        collector: &str,
+        since: Option<&str>,
+        until: Option<&str>,
+    ) -> PyResult<PyObject> {
+        let since = parse_time_bound("since", since)?;
+        let until = parse_time_bound("until", until)?;
+        let samples = py
+            .allow_threads(|| {
+                self.repository.get_samples_in_range::<Value>(
+                    collector,
+                    since.as_ref(),
+                    until.as_ref(),
+                )
+            })
+            .map_err(error)?;
+        let samples: Vec<Value> = samples
+            .into_iter()
+            .map(|(recorded_at, stored)| {
+                let time = stored.time.unwrap_or_default();
+                json!({
+                    "recorded_at": key_time(&recorded_at),
+                    "sample": stored.sample,
+                    "wall_time": Some(time.wall).filter(|wall| !wall.is_empty()),
+                    "monotonic_ns": time.monotonic_ns,
+                    "boot_id": time.boot_id,
+                })
+            })
+            .collect();
+        to_python(py, &samples)
+    }
+}
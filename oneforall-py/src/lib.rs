@@ -0,0 +1,100 @@
+//! OneForAll Python Bindings
+//!
+//! This crate builds the `oneforall` Python module. `discover()` runs the same
+//! hardware discovery as `one_for_all discover` on the machine Python runs on,
+//! `Database` queries the results and readings stored by a local OneForAll,
+//! and `Agent` launches profiles on, follows the jobs of and queries the
+//! results of a remote agent through its gRPC control plane. Reports, jobs and
+//! readings are returned as the dicts and lists their JSON decodes to, so they
+//! load straight into pandas; failures raise `oneforall.OneForAllError`.
+//!
+//! ```python
+//! import oneforall
+//!
+//! report = oneforall.discover()
+//! agent = oneforall.Agent("http://rack12-node3:50051", token="operator-token")
+//! job = agent.wait_for_job(agent.launch_profile("quick-sanity")["id"])
+//! ```
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use common::adapters::log_adapter::FernLogger;
+use common::ports::log_port::LoggerPort;
+use oneforall_core::adapters::hardware_discovery::HardwareDiscovery;
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use serde::Serialize;
+
+mod agent;
+mod database;
+
+use crate::agent::Agent;
+use crate::database::Database;
+
+create_exception!(
+    oneforall,
+    OneForAllError,
+    PyException,
+    "Raised when hardware, the database or an agent cannot be read."
+);
+
+/// Turns an error of OneForAll into a Python exception.
+pub(crate) fn error(message: impl ToString) -> PyErr {
+    OneForAllError::new_err(message.to_string())
+}
+
+/// Hands a value to Python as the dicts, lists and scalars its JSON decodes to.
+pub(crate) fn to_python(py: Python<'_>, value: &impl Serialize) -> PyResult<PyObject> {
+    let text = serde_json::to_string(value).map_err(error)?;
+    let json = py.import_bound("json")?;
+    Ok(json.call_method1("loads", (text,))?.unbind())
+}
+
+/// Parses an optional RFC 3339 bound of a time range.
+pub(crate) fn parse_time_bound(
+    field: &str,
+    value: Option<&str>,
+) -> PyResult<Option<DateTime<Utc>>> {
+    value
+        .map(|value| {
+            DateTime::parse_from_rfc3339(value)
+                .map(|time| time.with_timezone(&Utc))
+                .map_err(|e| error(format!("Invalid {} '{}': {}", field, value, e)))
+        })
+        .transpose()
+}
+
+/// The logger of the adapters, which writes through the `log` crate; nothing is
+/// written unless the embedding application sets up a logger.
+pub(crate) fn logger() -> Arc<dyn LoggerPort> {
+    Arc::new(FernLogger::new())
+}
+
+/// Discovers the hardware of this machine, as `one_for_all discover` does.
+///
+/// Returns the report as a dict with the titled `sections` of facts and the
+/// `snapshot` of the firmware, memory modules, PCIe links and NVMe drives found.
+/// With `bandwidth=True`, the PCIe bandwidth of the NVMe drives and GPUs is
+/// measured too, which takes a few seconds per device.
+#[pyfunction]
+#[pyo3(signature = (bandwidth = false))]
+fn discover(py: Python<'_>, bandwidth: bool) -> PyResult<PyObject> {
+    let (report, _) = py.allow_threads(|| {
+        HardwareDiscovery::new(logger())
+            .with_bandwidth(bandwidth)
+            .discover()
+    });
+    to_python(py, &report)
+}
+
+/// The `oneforall` Python module.
+#[pymodule]
+fn oneforall(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("OneForAllError", m.py().get_type_bound::<OneForAllError>())?;
+    m.add_function(wrap_pyfunction!(discover, m)?)?;
+    m.add_class::<Database>()?;
+    m.add_class::<Agent>()?;
+    Ok(())
+}
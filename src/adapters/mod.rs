@@ -1,5 +0,0 @@
-// src/adapters/domain
-pub mod database_adapter;
-pub(crate) mod ps_command_adapter;
-pub mod stress_ng_adapter;
-pub mod stress_ng_manager_adapter;
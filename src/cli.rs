@@ -0,0 +1,730 @@
+//! Command Line
+//!
+//! This module defines the command line of `one_for_all`: the global flags and
+//! the subcommands with their flags, which clap parses into a `Cli`.
+
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+use clap_complete::Shell;
+
+use common::domain::api_config::CONFIG_FILE;
+
+use oneforall_core::domain::gpu::DEFAULT_MAX_GPU_CELSIUS;
+use oneforall_core::domain::tags::parse_tag;
+use oneforall_core::domain::trend::DEFAULT_TREND_RUNS;
+
+// OneForAll CLI Application
+// This struct represents the command-line interface of the application,
+// defining the available subcommands and their respective functionalities.
+#[derive(Parser, Debug)]
+#[clap(author = "Kenny Sheridan", version = "0.1 (Dev)", about = "OneForAll -\
+ An advanced tool for hardware performance testing and diagnostics.",
+long_about = long_description())]
+pub struct Cli {
+    /// Run as a systemd service: log to the journal, report readiness and liveness
+    /// with sd_notify, and shut down gracefully on SIGTERM
+    #[clap(long, global = true)]
+    pub service: bool,
+
+    /// The configuration file holding the REST API, gRPC, database, alert, export and fleet settings
+    #[clap(long, global = true, default_value = CONFIG_FILE)]
+    pub config: PathBuf,
+
+    /// Attach a tag to the results of this run, e.g. `--tag rack=r12 --tag operator=kim`;
+    /// may be repeated
+    #[clap(long = "tag", global = true, value_parser = parse_tag)]
+    pub tags: Vec<(String, String)>,
+
+    /// Print what the command or test profile would execute: stressors, durations, target
+    /// devices and expected disk writes, without running anything
+    #[clap(long, global = true)]
+    pub dry_run: bool,
+
+    /// Developer mode: inject synthetic faults to check that alerts, acceptance criteria and
+    /// reports respond to them; `ecc`, `thermal`, `kill-stressor` or `all`, comma-separated
+    #[clap(long, global = true, value_name = "EVENTS")]
+    pub chaos: Option<String>,
+
+    /// How long after starting the `--chaos` faults are injected, e.g. `30s` or `5m`
+    #[clap(long, global = true, default_value = "30s", requires = "chaos")]
+    pub chaos_after: String,
+
+    /// Append every reading the collectors publish to a CSV file, one row per reading:
+    /// power, temperatures, GPUs, cgroup usage and stress test progress
+    #[clap(long, global = true, value_name = "FILE")]
+    pub metrics_csv: Option<PathBuf>,
+
+    /// Sample stacks with perf while a benchmark or plugin runs and draw where its time went
+    /// as a flamegraph SVG, e.g. `--flamegraph cache.svg`
+    #[clap(long, global = true, value_name = "FILE")]
+    pub flamegraph: Option<PathBuf>,
+
+    #[clap(subcommand)]
+    pub command: Commands,
+}
+
+// Enum representing the different subcommands available in the CLI.
+// Each variant corresponds to a specific functionality of the application.
+#[derive(Subcommand, Debug)]
+pub enum Commands {
+    // Runs benchmark tests
+    Benchmark {
+        /// Runs made first and discarded, to warm caches, clocks and the page cache
+        #[clap(long, global = true, default_value_t = 0)]
+        warmup: u32,
+
+        /// Runs measured; with more than one, the mean, median, standard deviation and 95%
+        /// confidence interval of every metric are reported after rejecting outliers
+        #[clap(
+            long,
+            global = true,
+            default_value_t = 1,
+            value_parser = clap::value_parser!(u32).range(1..)
+        )]
+        repetitions: u32,
+
+        #[clap(subcommand)]
+        kind: BenchmarkKind,
+    },
+
+    // Executes stress tests
+    Stress(StressArgs),
+
+    // Writes and verifies a volume of data to a drive and estimates its write amplification
+    Endurance {
+        /// The directory to write the test files to, on the drive under test
+        #[clap(long)]
+        path: String,
+
+        /// The volume of data to write, e.g. `500G` or `2T`
+        #[clap(long, default_value = "10G")]
+        volume: String,
+
+        /// The size of each test file; files are deleted once verified
+        #[clap(long, default_value = "1G")]
+        file_size: String,
+
+        /// The drive to read SMART wear indicators from, e.g. `/dev/nvme0`; found
+        /// from the path when omitted
+        #[clap(long)]
+        device: Option<String>,
+    },
+
+    // Scans and analyzes hardware
+    Discover {
+        /// Also measure the bandwidth crossing the PCIe links of NVMe drives (read
+        /// only) and NVIDIA GPUs (with nvbandwidth)
+        #[clap(long)]
+        bandwidth: bool,
+
+        /// Compare the hardware with the last discovery instead of printing the report,
+        /// exiting with status 1 if anything changed
+        #[clap(long)]
+        diff: bool,
+
+        /// Check the hardware against a TOML manifest of the expected CPU, memory, disks
+        /// and NICs, exiting with status 1 if it does not match
+        #[clap(long, value_name = "PATH")]
+        expect: Option<PathBuf>,
+    },
+
+    // Monitors hardware performance in real-time
+    Overwatch {
+        /// Show an interactive terminal interface, e.g. on headless machines over SSH
+        #[clap(long)]
+        tui: bool,
+
+        /// How often power and process readings are taken, e.g. `500ms` or `5s`
+        #[clap(long, default_value = "2s")]
+        interval: String,
+
+        /// Also sample CPU utilization this often, e.g. `100ms`, to catch short stalls
+        /// and microbursts; stored as a frame of ticks per second
+        #[clap(long, value_name = "DURATION")]
+        burst_interval: Option<String>,
+    },
+
+    // Embedded Database Operations
+    DatabaseOps,
+
+    // Maintains the embedded database
+    Db {
+        #[clap(subcommand)]
+        action: DbAction,
+    },
+
+    // Moves stored runs between machines, e.g. from an air-gapped lab machine to an
+    // analyst's workstation
+    Archive {
+        #[clap(subcommand)]
+        action: ArchiveAction,
+    },
+
+    // Runs a named test profile, a bundle of stress tests and benchmarks
+    Run {
+        /// The profile to run, e.g. `burn-in-24h`, or the path of a TOML file; lists the
+        /// available profiles when omitted
+        #[clap(long)]
+        profile: Option<String>,
+    },
+
+    // Continues a profile run interrupted by a restart of the daemon or the machine
+    Resume {
+        /// The profile whose unfinished run to continue; needed when several are unfinished
+        #[clap(long)]
+        profile: Option<String>,
+
+        /// Discard the unfinished run instead of continuing it
+        #[clap(long)]
+        discard: bool,
+
+        /// Continue only a run a hook step rebooted the machine for, and do nothing when
+        /// there is none; run at boot by `oneforall-resume.service`
+        #[clap(long, conflicts_with = "discard")]
+        after_reboot: bool,
+    },
+
+    // Runs as a pod of a Kubernetes DaemonSet: qualifies the node with a profile, records
+    // the verdict on the node, then stays up serving the API until the pod is stopped
+    Daemonset {
+        /// The profile to qualify the node with, e.g. `quick-sanity`
+        #[clap(long)]
+        profile: String,
+
+        /// Label the node with the verdict, the profile and when it finished
+        #[clap(long)]
+        label: bool,
+
+        /// Taint a failed node NoSchedule, and remove the taint once it passes again
+        #[clap(long)]
+        taint: bool,
+    },
+
+    // Lists the queued and running jobs of a running instance
+    Jobs {
+        /// Cancel the job with this ID instead of listing the jobs
+        #[clap(long)]
+        cancel: Option<u64>,
+    },
+
+    // Prints the recent log messages of a running instance, on this machine or another
+    Logs {
+        /// Keep printing the messages as they are logged, like `tail -f`
+        #[clap(long)]
+        follow: bool,
+
+        /// The number of recent messages to print first
+        #[clap(long, default_value_t = 20)]
+        lines: usize,
+
+        /// The least severe level to print: error, warn, info, debug or trace
+        #[clap(long, default_value = "info")]
+        level: String,
+
+        /// The base URL of the instance to read, e.g. `https://rack12-node3:8000`; the
+        /// instance on this machine when omitted
+        #[clap(long)]
+        agent: Option<String>,
+    },
+
+    // Lists the agents announced over mDNS on the lab subnet and those listed in the
+    // `[fleet]` configuration, with whether each can be reached
+    Fleet {
+        /// Print the agents as JSON, e.g. for lab automation enrolling them into a test
+        #[clap(long)]
+        json: bool,
+
+        /// Have every reachable agent measure the latency and bandwidth to every other
+        /// agent, one pair at a time, and print the matrix
+        #[clap(long)]
+        matrix: bool,
+
+        /// Also write the matrix as an HTML report with latency and bandwidth heatmaps
+        #[clap(long, requires = "matrix")]
+        html: Option<PathBuf>,
+    },
+
+    // Power cycles another machine through its BMC a number of times, following the agent
+    // on it after each cycle, and reports boot failures and the time until it was ready
+    PowerCycle(PowerCycleArgs),
+
+    // Scores this machine from its latest stored benchmark results, or ranks the agents
+    // of the fleet by theirs
+    Compare {
+        /// Ask every reachable agent of the fleet for its scores and rank the agents
+        #[clap(long)]
+        fleet: bool,
+
+        /// Print the scores as JSON
+        #[clap(long)]
+        json: bool,
+    },
+
+    // Follows a metric across the runs stored on this machine and flags a steady
+    // degradation, e.g. `trend --metric dram_bandwidth --last 30`
+    Trend {
+        /// The metric to follow, e.g. `dram_bandwidth`, `fsync_p99` or `peak_temperature`
+        #[clap(long)]
+        metric: String,
+
+        /// How many of the latest runs to follow
+        #[clap(long, default_value_t = DEFAULT_TREND_RUNS)]
+        last: usize,
+
+        /// The benchmark that measured the metric, when several report it, e.g. `cache`
+        #[clap(long)]
+        benchmark: Option<String>,
+
+        /// Print the trend as JSON
+        #[clap(long)]
+        json: bool,
+    },
+
+    // Lists the benchmark and stress test plugins loaded from the `[plugins]` section
+    Plugins,
+
+    // Prints the completion script of a shell, e.g. `completions bash`
+    Completions {
+        /// The shell to complete the commands and flags in
+        #[clap(value_enum)]
+        shell: Shell,
+    },
+
+    // Writes a man page for the command and for each of its subcommands
+    Man {
+        /// The directory to write the pages to, e.g. /usr/local/share/man/man1
+        #[clap(long, default_value = ".")]
+        dir: PathBuf,
+    },
+
+    // Runs a plugin by name, e.g. `fpga-loopback --lanes 4`, handing it the arguments that follow
+    #[clap(external_subcommand)]
+    Plugin(Vec<String>),
+}
+
+// The flags of the `stress` subcommand.
+#[derive(Args, Debug)]
+pub struct StressArgs {
+    /// Pin stress workers to a list of logical CPUs, e.g. `0-7` or `0,2,4`
+    #[clap(long)]
+    pub pin: Option<String>,
+
+    /// Restrict stress workers to the CPUs of a single NUMA node
+    #[clap(long)]
+    pub numa: Option<u32>,
+
+    /// Place one worker per physical core, skipping SMT siblings
+    #[clap(long)]
+    pub physical_only: bool,
+
+    /// Run the workers on one class of cores of a hybrid CPU, `performance` or
+    /// `efficiency`, e.g. the P-cores or E-cores of Apple Silicon
+    #[clap(long, value_parser = ["performance", "efficiency"])]
+    pub core_class: Option<String>,
+
+    /// How long to run, e.g. `90s`, `30m` or `12h`
+    #[clap(long, default_value = "120s")]
+    pub duration: String,
+
+    /// Load CPU, memory, disk and network at once and report pass/fail from the
+    /// hardware error monitors (ECC, MCE, SMART)
+    #[clap(long, conflicts_with_all = ["pin", "numa", "physical_only", "core_class"])]
+    pub all: bool,
+
+    /// Load the GPUs with the stress-ng GPU stressor instead of the CPU, watching
+    /// their temperature, clock and power
+    #[clap(long, conflicts_with_all = ["pin", "numa", "physical_only", "core_class", "all", "verify"])]
+    pub gpu: bool,
+
+    /// The hottest a GPU may run during `--gpu`, in degrees Celsius
+    #[clap(long, default_value_t = DEFAULT_MAX_GPU_CELSIUS, requires = "gpu")]
+    pub max_gpu_celsius: f64,
+
+    /// Verify the video memory of a GPU with memtest_vulkan for the duration of the
+    /// test, reporting the address ranges that read back wrong
+    #[clap(long, conflicts_with_all = ["pin", "numa", "physical_only", "core_class", "all", "gpu", "verify"])]
+    pub vram: bool,
+
+    /// The GPU whose memory `--vram` verifies, numbered from 1 as memtest_vulkan lists them
+    #[clap(long, default_value_t = 1, requires = "vram")]
+    pub vram_device: u32,
+
+    /// Test this much RAM, e.g. `2G`, with memtester-style pattern tests over locked
+    /// memory for the duration of the test, cross-checked against ECC errors
+    #[clap(
+        long,
+        value_name = "SIZE",
+        conflicts_with_all = ["pin", "numa", "physical_only", "core_class", "all", "gpu", "vram", "verify"]
+    )]
+    pub memtest: Option<String>,
+
+    /// Load every CPU with AVX2, AVX-512, NEON or SVE fused multiply-adds after a
+    /// scalar baseline, reporting the clock each instruction set runs at; `auto`
+    /// tests every one the CPU supports
+    #[clap(
+        long,
+        value_name = "ISA",
+        value_parser = ["auto", "avx2", "avx512", "neon", "sve"],
+        conflicts_with_all = ["pin", "numa", "physical_only", "core_class", "all", "gpu", "vram", "memtest", "verify"]
+    )]
+    pub simd: Option<String>,
+
+    /// Alternate load and idle periods for the duration of the test to cycle the
+    /// temperature, reporting the temperatures and error counters of each cycle
+    #[clap(
+        long,
+        conflicts_with_all = ["pin", "numa", "physical_only", "core_class", "all", "gpu", "vram", "memtest", "simd", "verify"]
+    )]
+    pub soak: bool,
+
+    /// How long each load period of `--soak` lasts, e.g. `10m`
+    #[clap(long, default_value = "10m", requires = "soak")]
+    pub on: String,
+
+    /// How long the machine idles after each load period of `--soak`, e.g. `5m`
+    #[clap(long, default_value = "5m", requires = "soak")]
+    pub off: String,
+
+    /// Write pseudorandom patterns to memory and disk and re-read them throughout the
+    /// run, failing on any corruption
+    #[clap(long)]
+    pub verify: bool,
+
+    /// The memory to verify with `--verify`, e.g. `512M` or `4G`
+    #[clap(long, default_value = "512M", requires = "verify")]
+    pub verify_memory: String,
+
+    /// The disk space to verify with `--verify`, e.g. `1G`
+    #[clap(long, default_value = "1G", requires = "verify")]
+    pub verify_disk: String,
+
+    /// The directory the `--verify` file is written to; the system temporary
+    /// directory when omitted
+    #[clap(long, requires = "verify")]
+    pub verify_path: Option<String>,
+
+    /// Measure run queue latency, off-CPU time and block I/O latency in the kernel
+    /// with eBPF for the duration of the test; needs a build with the `ebpf`
+    /// feature and root
+    #[clap(
+        long,
+        conflicts_with_all = ["all", "gpu", "vram", "memtest", "simd", "soak"]
+    )]
+    pub ebpf: bool,
+
+    /// Clear the BMC event log before the test, so what it holds afterwards was
+    /// logged during the test; new entries fail the test either way
+    #[clap(long)]
+    pub clear_sel: bool,
+
+    /// List the stressors the stress-ng binary of this machine offers, with their
+    /// options, instead of running a test
+    #[clap(
+        long,
+        conflicts_with_all = ["pin", "numa", "physical_only", "core_class", "all", "gpu", "vram", "memtest", "simd", "soak", "verify", "ebpf", "clear_sel"]
+    )]
+    pub list: bool,
+}
+
+// The flags of the `power-cycle` subcommand.
+#[derive(Args, Debug)]
+pub struct PowerCycleArgs {
+    /// The agent on the machine under test, e.g. `https://rack14-node1.lab.example:8000`;
+    /// it must start at boot and be ready before the first power cycle
+    #[clap(long)]
+    pub agent: String,
+
+    /// The BMC of the machine under test: its Redfish URL, e.g. `https://10.0.0.5`, or its
+    /// host for IPMI over LAN, logging in as `ONEFORALL_BMC_USERNAME` with
+    /// `ONEFORALL_BMC_PASSWORD`
+    #[clap(long)]
+    pub bmc: String,
+
+    /// The number of power cycles
+    #[clap(
+        long,
+        default_value_t = 10,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    pub cycles: u32,
+
+    /// How long the agent may take to be ready again after each power cycle, e.g. `20m`
+    #[clap(long, default_value = "15m")]
+    pub ready_timeout: String,
+
+    /// The confirmation token printed when the power cycles are refused, e.g.
+    /// power-cycle-10.0.0.5
+    #[clap(long)]
+    pub confirm: Option<String>,
+
+    /// Print the report as JSON
+    #[clap(long)]
+    pub json: bool,
+}
+
+// Enum representing the maintenance tasks available under the `db` subcommand.
+#[derive(Subcommand, Debug)]
+pub enum DbAction {
+    // Applies the retention policy now and returns the space of deleted records to the
+    // file system
+    Vacuum,
+
+    // Rewrites every stored result at the current schema version, including those written
+    // by older versions under the earliest key schema
+    Migrate,
+}
+
+// Enum representing the actions available under the `archive` subcommand.
+#[derive(Subcommand, Debug)]
+pub enum ArchiveAction {
+    // Writes a stored run, with the readings taken over it, the hardware inventory and
+    // its log messages, to a compressed archive
+    Export {
+        /// The key of the run, e.g. `profile:2024-05-02T08:00:00.000000Z:burn-in-24h`
+        run: String,
+
+        /// The file to write; named after the machine and the run when omitted
+        #[clap(long)]
+        output: Option<PathBuf>,
+
+        /// Sign the archive with this ed25519 private key in PEM, e.g. as written by
+        /// `openssl genpkey -algorithm ed25519`
+        #[clap(long)]
+        sign_key: Option<PathBuf>,
+    },
+
+    // Reads an archive written by `archive export` into the database of this machine
+    Import {
+        /// The archive to import
+        file: PathBuf,
+
+        /// Only import the archive if it is signed with this ed25519 public key in PEM;
+        /// may be repeated
+        #[clap(long)]
+        trusted_key: Vec<PathBuf>,
+    },
+}
+
+// Enum representing the benchmarks available under the `benchmark` subcommand.
+#[derive(Subcommand, Debug)]
+pub enum BenchmarkKind {
+    // Measures local vs remote memory bandwidth and latency across NUMA nodes
+    Numa {
+        /// Size of the buffer placed on each NUMA node, in megabytes
+        #[clap(long, default_value_t = 256)]
+        size_mb: usize,
+
+        /// Remote/local latency ratio above which a node pair is flagged as degraded
+        #[clap(long, default_value_t = 2.5)]
+        max_remote_penalty: f64,
+    },
+
+    // Measures bandwidth and latency of each cache level and DRAM
+    Cache {
+        /// Logical CPU to run the measurements on; defaults to the first online CPU
+        #[clap(long)]
+        cpu: Option<u32>,
+    },
+
+    // Measures create/stat/delete rates, small random writes and fsync latency of a filesystem
+    Filesystem {
+        /// The directory to run the benchmark in, on the filesystem under test
+        #[clap(long)]
+        path: String,
+
+        /// The number of small files created, stat'ed and deleted
+        #[clap(long, default_value_t = 10000)]
+        files: u64,
+    },
+
+    // Measures sequential and random reads and writes of a raw block device, or of every disk
+    #[clap(alias = "storage")]
+    Device {
+        /// The block device to benchmark, e.g. /dev/nvme1n1 or a /dev/disk/by-id link
+        #[clap(long, required_unless_present = "all_disks")]
+        device: Option<String>,
+
+        /// Benchmark every disk of the device inventory but the boot disk, one after another
+        #[clap(long, conflicts_with = "device")]
+        all_disks: bool,
+
+        /// With --all-disks, benchmark the disks at the same time instead of one after another
+        #[clap(long, requires = "all_disks")]
+        parallel: bool,
+
+        /// Only read from the device, leaving its data intact
+        #[clap(long)]
+        read_only: bool,
+
+        /// Write to the device even though it is mounted, partitioned or in use, erasing it
+        #[clap(long, conflicts_with_all = ["read_only", "all_disks"])]
+        force: bool,
+
+        /// The confirmation token printed when the device is refused, e.g. erase-nvme1n1
+        #[clap(long, requires = "force")]
+        confirm: Option<String>,
+    },
+
+    // Measures GPU-to-GPU copy bandwidth and latency over NVLink, xGMI or PCIe with nvbandwidth
+    Interconnect,
+
+    // Measures RDMA write bandwidth and read latency with perftest, to a peer or in loopback
+    Rdma {
+        /// The RDMA device to use; defaults to the first device with an active port
+        #[clap(long)]
+        device: Option<String>,
+
+        /// The port of the device to use; defaults to its first active port
+        #[clap(long)]
+        port: Option<u32>,
+
+        /// The machine running `benchmark rdma --server` to measure to; without it the
+        /// device is measured in loopback
+        #[clap(long, conflicts_with = "server")]
+        peer: Option<String>,
+
+        /// Serve the other side of one benchmark from a peer, then exit
+        #[clap(long)]
+        server: bool,
+    },
+
+    // Reports the active CPU vulnerability mitigations and measures the system call and
+    // context switch costs they add to
+    Mitigations {
+        /// Also run the system call and context switch micro-benchmarks
+        #[clap(long)]
+        measure: bool,
+
+        /// Logical CPU to run the measurements on; defaults to the first online CPU
+        #[clap(long)]
+        cpu: Option<u32>,
+    },
+
+    // Measures context switch, wake-up and timer latencies, cyclictest-style, for low-latency work
+    Latency {
+        /// Logical CPU to run the measured threads on; defaults to the first online CPU
+        #[clap(long)]
+        cpu: Option<u32>,
+
+        /// Logical CPU to send wake-ups from; defaults to the second online CPU
+        #[clap(long)]
+        waker_cpu: Option<u32>,
+
+        /// Period of the timer whose jitter is measured, in microseconds
+        #[clap(long, default_value_t = 1000)]
+        interval_us: u64,
+
+        /// How long the timer jitter is measured for, in seconds
+        #[clap(long, default_value_t = 10)]
+        duration_secs: u64,
+    },
+
+    // Runs a CPU workload at 1, 2, 4 ... N threads and reports how throughput scales across cores, SMT siblings and sockets
+    Scaling {
+        /// How long the workload runs at each thread count, in seconds
+        #[clap(long, default_value_t = 3)]
+        step_secs: u64,
+
+        /// Most threads to run; defaults to every online CPU
+        #[clap(long)]
+        max_threads: Option<usize>,
+    },
+
+    // Measures AES-256-GCM and SHA-256 throughput per core and per socket, with hardware acceleration when present
+    Crypto {
+        /// How long each algorithm runs on one core and on each socket, in seconds
+        #[clap(long, default_value_t = 3)]
+        step_secs: u64,
+    },
+
+    // Measures zstd and gzip compression and decompression throughput per core and per socket
+    Compression {
+        /// How long each algorithm runs on one core and on each socket, in seconds
+        #[clap(long, default_value_t = 3)]
+        step_secs: u64,
+    },
+
+    // Checks TSC invariance, the clocksource, timer resolution and cross-core clock skew for latency-sensitive work
+    Clock {
+        /// Round trips timed between the first online CPU and each other CPU
+        #[clap(long, default_value_t = 10000)]
+        rounds: usize,
+    },
+
+    // Breaks the last boot down into POST, loader, kernel, initrd and userspace time
+    Boot {
+        /// Fail the benchmark if the POST took longer, e.g. 90s
+        #[clap(long)]
+        max_post: Option<String>,
+    },
+
+    // Runs a command of your own as a benchmark, with the full telemetry report
+    Exec {
+        /// Kill the command and fail the run if it runs longer, e.g. 30m
+        #[clap(long)]
+        timeout: Option<String>,
+
+        /// The command to run and its arguments, after `--`
+        #[clap(last = true, required = true)]
+        command: Vec<String>,
+    },
+}
+
+/// # OneForAll
+///
+/// OneForAll is a comprehensive tool designed for in-depth hardware
+/// performance analysis and diagnostics. It leverages advanced testing
+/// methodologies to provide users with detailed insights into their
+/// system's capabilities and bottlenecks. With OneForAll, you can run
+/// various tests, including benchmarks, stress tests, and hardware
+/// discovery, to understand the full scope of your hardware's performance.
+///
+/// ## Modules
+///
+/// The tool is structured into several modules, each targeting a specific
+/// aspect of hardware performance:
+///
+/// - **Benchmark**: Run extensive benchmarks to measure the speed and efficiency
+///   of your CPU, GPU, memory, and storage devices.
+///
+/// - **Stress**: Put your system under intense stress to test stability and
+///   endurance under heavy loads.
+///
+/// - **Discover**: Analyze and report on the configuration and current state of
+///   your hardware components.
+///
+/// - **Overwatch**: Watch your system's performance in real-time, capturing
+///   critical metrics and providing live feedback.
+///
+/// OneForAll is designed with both simplicity and power in mind, making it
+/// suitable for both casual users looking to check their system's performance
+/// and professionals requiring detailed hardware analysis.
+fn long_description() -> &'static str {
+    "\n\n\nOneForAll is a comprehensive tool designed for in-depth hardware \
+    performance analysis and diagnostics. \
+    It leverages advanced testing methodologies to provide users with \
+    detailed insights into their system's capabilities \
+    and bottlenecks. With OneForAll, you can run various tests, including \
+    benchmarks, stress tests, and hardware discovery, \
+    to understand the full scope of your hardware's performance.\n\n\
+    The tool is structured into several modules, each targeting a specific \
+    aspect of hardware performance:\n\n\
+    \
+    - Benchmark: Run extensive benchmarks to measure the speed and efficiency \
+      of your CPU, GPU, memory, and storage devices.\n
+    
+    - Stress: Put your system under intense stress to test stability and \
+    endurance under heavy loads.\n\
+    \
+    - Discover: Analyze and report on the configuration and current state of \
+    your hardware components.\n\
+    \
+    - Overwatch: Watch your system's performance in real-time from the web browser, or \
+    from the terminal with --tui, capturing critical metrics and providing live feedback.\n
+   
+    OneForAll is designed with both simplicity and power in mind, making it \
+    suitable for both casual users looking to \
+    check their system's performance and professionals requiring detailed \
+    hardware analysis."
+}
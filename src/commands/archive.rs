@@ -0,0 +1,84 @@
+//! Archive Command
+//!
+//! This module runs `one_for_all archive`, which exports a stored run to a
+//! signed archive file or imports one into this machine's database.
+
+use std::sync::Arc;
+
+use common::domain::api_config::ApiConfig;
+use common::ports::log_port::LoggerPort;
+
+use oneforall_core::adapters::archive_adapter::{
+    load_signing_key, load_trusted_key, ArchiveAdapter,
+};
+use oneforall_core::adapters::jobs_client_adapter::JobsClientAdapter;
+use oneforall_core::adapters::repository::Repository;
+
+use crate::cli::ArchiveAction;
+
+/// Exports a run with its results, readings and log messages, or imports an archive.
+/// The log messages of an exported run are read from the instance running on this
+/// machine, if there is one.
+///
+/// # Arguments
+///
+/// * `logger` - Logger implementation for logging messages.
+/// * `api_config` - The address, token and TLS settings of the local REST API.
+/// * `repository` - The repository the run is read from or written to.
+/// * `action` - Whether to export or import, with its flags.
+///
+/// # Returns
+///
+/// * `std::io::Result<()>` - Whether the run was exported or imported.
+pub fn run(
+    logger: Arc<dyn LoggerPort>,
+    api_config: &ApiConfig,
+    repository: Repository,
+    action: &ArchiveAction,
+) -> std::io::Result<()> {
+    let mut archives = ArchiveAdapter::new(logger.clone(), repository);
+    if let Ok(agent) = JobsClientAdapter::new(logger.clone(), api_config) {
+        archives = archives.with_agent(agent);
+    }
+    let outcome = match action {
+        ArchiveAction::Export {
+            run,
+            output,
+            sign_key,
+        } => sign_key
+            .as_deref()
+            .map(load_signing_key)
+            .transpose()
+            .and_then(|key| {
+                if let Some(key) = key {
+                    archives = archives.with_signing_key(key);
+                }
+                archives.export(run, output.as_deref())
+            })
+            .map(|(path, manifest)| {
+                format!("Wrote {} to {}", manifest.render_line(), path.display())
+            }),
+        ArchiveAction::Import { file, trusted_key } => trusted_key
+            .iter()
+            .map(|path| load_trusted_key(path))
+            .collect::<Result<Vec<_>, _>>()
+            .and_then(|keys| archives.with_trusted_keys(keys).import(file))
+            .map(|(manifest, check)| {
+                format!(
+                    "Imported {}; {}",
+                    manifest.render_line(),
+                    check.render_line()
+                )
+            }),
+    };
+    match outcome {
+        Ok(summary) => {
+            println!("{}", summary);
+            Ok(())
+        }
+        Err(e) => {
+            logger.log_error(&e);
+            Err(std::io::Error::other(e))
+        }
+    }
+}
@@ -0,0 +1,1485 @@
+//! Benchmark Command
+//!
+//! This module runs `one_for_all benchmark`: one of the micro-benchmarks
+//! measuring the NUMA fabric, caches, filesystems, raw devices, GPU and RDMA links,
+//! mitigations, scheduler latency, scaling, codecs, clocks, boot and workloads,
+//! each with warm-up runs and repetitions, storing the result of every run.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use tokio::time::Duration;
+
+use common::domain::job::{JobSpec, JobStatus, Resource};
+use common::ports::log_port::LoggerPort;
+
+use oneforall_core::adapters::benchmark_runner::BenchmarkRunner;
+use oneforall_core::adapters::block_device_adapter::{BlockDeviceAdapter, WRITE_SPAN_BYTES};
+use oneforall_core::adapters::boot_timing_adapter::BootTimingAdapter;
+use oneforall_core::adapters::clock_stability_adapter::ClockStabilityAdapter;
+use oneforall_core::adapters::codec_benchmark_adapter::CodecBenchmarkAdapter;
+use oneforall_core::adapters::cpu_features_adapter::CpuFeaturesAdapter;
+use oneforall_core::adapters::device_inventory_adapter::DeviceInventoryAdapter;
+use oneforall_core::adapters::filesystem_benchmark_adapter::FilesystemBenchmarkAdapter;
+use oneforall_core::adapters::gpu_interconnect_adapter::GpuInterconnectAdapter;
+use oneforall_core::adapters::memory_benchmark_adapter::MemoryBenchmarkAdapter;
+use oneforall_core::adapters::mitigation_benchmark_adapter::MitigationBenchmarkAdapter;
+use oneforall_core::adapters::rdma_adapter::RdmaAdapter;
+use oneforall_core::adapters::run_monitor::{RunMonitor, DEFAULT_SAMPLE_INTERVAL};
+use oneforall_core::adapters::scaling_benchmark_adapter::ScalingBenchmarkAdapter;
+use oneforall_core::adapters::scheduler_latency_adapter::SchedulerLatencyAdapter;
+use oneforall_core::adapters::topology_adapter::TopologyAdapter;
+use oneforall_core::adapters::workload_adapter::WorkloadAdapter;
+use oneforall_core::domain::block_device::{
+    device_access, device_measurements, format_device_results, DeviceAccess, DeviceUsage,
+};
+use oneforall_core::domain::boot::{boot_measurements, format_boot_results};
+use oneforall_core::domain::cache::{
+    cache_measurements, format_cache_results, has_cache_anomalies, plan_cache_sweep,
+};
+use oneforall_core::domain::clock::{clock_measurements, format_clock_results};
+use oneforall_core::domain::codec::{codec_measurements, format_codec_results, CodecAlgorithm};
+use oneforall_core::domain::filesystem::{filesystem_measurements, format_filesystem_results};
+use oneforall_core::domain::gpu_interconnect::{
+    format_interconnect_results, interconnect_measurements,
+};
+use oneforall_core::domain::mitigations::{
+    format_mitigation_report, mitigation_measurements, mitigation_parameters, MitigationReport,
+};
+use oneforall_core::domain::numa::{format_numa_results, numa_measurements};
+use oneforall_core::domain::plan::{DiskWrites, ExecutionPlan, PlannedStep};
+use oneforall_core::domain::profile::parse_duration;
+use oneforall_core::domain::rdma::{format_rdma_results, rdma_measurements};
+use oneforall_core::domain::scaling::{
+    format_scaling_results, plan_scaling, plan_socket_runs, scaling_measurements,
+};
+use oneforall_core::domain::scheduler_latency::{
+    format_scheduler_latency_results, scheduler_latency_measurements,
+};
+use oneforall_core::domain::statistics::{format_statistics, RepetitionPlan};
+use oneforall_core::domain::workload::{format_workload_results, workload_measurements};
+use oneforall_core::ports::block_device_port::BlockDevicePort;
+use oneforall_core::ports::boot_timing_port::BootTimingPort;
+use oneforall_core::ports::clock_stability_port::ClockStabilityPort;
+use oneforall_core::ports::codec_benchmark_port::CodecBenchmarkPort;
+use oneforall_core::ports::cpu_features_port::CpuFeaturesPort;
+use oneforall_core::ports::device_inventory_port::DeviceInventoryPort;
+use oneforall_core::ports::filesystem_benchmark_port::FilesystemBenchmarkPort;
+use oneforall_core::ports::gpu_interconnect_port::GpuInterconnectPort;
+use oneforall_core::ports::memory_benchmark_port::MemoryBenchmarkPort;
+use oneforall_core::ports::mitigation_benchmark_port::MitigationBenchmarkPort;
+use oneforall_core::ports::rdma_port::RdmaPort;
+use oneforall_core::ports::scaling_benchmark_port::ScalingBenchmarkPort;
+use oneforall_core::ports::scheduler_latency_port::SchedulerLatencyPort;
+use oneforall_core::ports::topology_port::TopologyPort;
+use oneforall_core::ports::workload_port::WorkloadPort;
+
+use crate::cli::BenchmarkKind;
+use crate::commands::{finish_flamegraph, start_flamegraph, CommandContext};
+
+/// Runs a benchmark, with warm-up runs and repetitions, and stores its result.
+pub async fn run(ctx: CommandContext, warmup: u32, repetitions: u32, kind: BenchmarkKind) {
+    let CommandContext {
+        logger,
+        telemetry,
+        jobs,
+        repository,
+        flamegraph_title,
+        flamegraph,
+        ..
+    } = ctx;
+    let plan = RepetitionPlan {
+        warmup,
+        repetitions,
+    };
+    let runner = BenchmarkRunner::new(logger.clone(), plan);
+    let profiler = start_flamegraph(logger.clone(), flamegraph.as_deref());
+    match kind {
+        BenchmarkKind::Numa {
+            size_mb,
+            max_remote_penalty,
+        } => {
+            // Map the NUMA nodes first so every node pair can be measured.
+            let topology_adapter = TopologyAdapter::new(logger.clone());
+            let nodes = match topology_adapter.discover_numa_nodes() {
+                Ok(nodes) => nodes,
+                Err(e) => {
+                    logger.log_error(&format!("NUMA discovery failed: {}", e));
+                    return;
+                }
+            };
+            logger.log_info(&format!(
+                "Running cross-node memory benchmark on {} NUMA node(s) with {} MB buffers",
+                nodes.len(),
+                size_mb
+            ));
+
+            // The benchmark loads both the CPUs and memory, so it waits for any
+            // stress test to finish rather than measuring a busy system.
+            let mut ticket = jobs.submit_job(JobSpec::new(
+                "benchmark:numa",
+                &[Resource::Cpu, Resource::Memory],
+            ));
+            let job_id = ticket.job.id;
+            if ticket.job.status == JobStatus::Queued {
+                logger.log_info(&format!(
+                    "NUMA benchmark job {} queued behind a conflicting job.",
+                    job_id
+                ));
+            }
+            if !ticket.admitted().await {
+                logger.log_warn(&format!(
+                    "NUMA benchmark job {} cancelled while queued.",
+                    job_id
+                ));
+                return;
+            }
+
+            let memory_benchmark = MemoryBenchmarkAdapter::new(logger.clone());
+            let run_monitor =
+                RunMonitor::start(logger.clone(), telemetry.clone(), DEFAULT_SAMPLE_INTERVAL);
+            let outcome = runner.run(
+                "numa",
+                || memory_benchmark.run_numa_matrix(&nodes, size_mb * 1024 * 1024, &ticket.cancel),
+                |results| numa_measurements(results),
+            );
+            let run_telemetry = run_monitor.finish();
+
+            match outcome {
+                Ok((results, record)) => {
+                    println!("{}", format_numa_results(&results, max_remote_penalty));
+                    if plan.repetitions > 1 {
+                        println!("{}", format_statistics(&plan, &record.statistics));
+                    }
+                    if let Err(e) = repository.store_run(
+                        "benchmark",
+                        &record.started_at,
+                        Some(&record.benchmark),
+                        &record,
+                    ) {
+                        logger.log_warn(&format!("Failed to store the benchmark result: {}", e));
+                    }
+                    println!("{}", run_telemetry.report);
+                    logger.log_info("NUMA memory benchmark completed.");
+                    jobs.finish_job(job_id, JobStatus::Completed, None);
+                }
+                Err(_) if ticket.cancel.is_cancelled() => {
+                    logger.log_warn("NUMA memory benchmark cancelled.");
+                    jobs.finish_job(job_id, JobStatus::Cancelled, None);
+                }
+                Err(e) => {
+                    logger.log_error(&format!("NUMA memory benchmark failed: {}", e));
+                    jobs.finish_job(job_id, JobStatus::Failed, Some(e));
+                }
+            }
+        }
+        BenchmarkKind::Cache { cpu } => {
+            // Size a working set for each cache level of the CPU being measured.
+            let topology_adapter = TopologyAdapter::new(logger.clone());
+            let cpu = match cpu {
+                Some(cpu) => cpu,
+                None => match topology_adapter.discover_cpu_topology() {
+                    Ok(topology) => topology.cpus.first().map_or(0, |c| c.id),
+                    Err(_) => 0,
+                },
+            };
+            let caches = match topology_adapter.discover_caches(cpu) {
+                Ok(caches) => caches,
+                Err(e) => {
+                    logger.log_error(&format!("Cache discovery failed: {}", e));
+                    return;
+                }
+            };
+            let levels = plan_cache_sweep(&caches);
+            logger.log_info(&format!(
+                "Running cache benchmark on CPU {} across {} level(s)",
+                cpu,
+                levels.len()
+            ));
+
+            // Other workloads would evict the working sets, so wait for them.
+            let mut ticket = jobs.submit_job(JobSpec::new(
+                "benchmark:cache",
+                &[Resource::Cpu, Resource::Memory],
+            ));
+            let job_id = ticket.job.id;
+            if ticket.job.status == JobStatus::Queued {
+                logger.log_info(&format!(
+                    "Cache benchmark job {} queued behind a conflicting job.",
+                    job_id
+                ));
+            }
+            if !ticket.admitted().await {
+                logger.log_warn(&format!(
+                    "Cache benchmark job {} cancelled while queued.",
+                    job_id
+                ));
+                return;
+            }
+
+            let memory_benchmark = MemoryBenchmarkAdapter::new(logger.clone());
+            let run_monitor =
+                RunMonitor::start(logger.clone(), telemetry.clone(), DEFAULT_SAMPLE_INTERVAL);
+            let outcome = runner.run(
+                "cache",
+                || memory_benchmark.run_cache_sweep(cpu, &levels, &ticket.cancel),
+                |results| cache_measurements(results),
+            );
+            let run_telemetry = run_monitor.finish();
+
+            match outcome {
+                Ok((results, record)) => {
+                    println!("{}", format_cache_results(&results));
+                    if plan.repetitions > 1 {
+                        println!("{}", format_statistics(&plan, &record.statistics));
+                    }
+                    if let Err(e) = repository.store_run(
+                        "benchmark",
+                        &record.started_at,
+                        Some(&record.benchmark),
+                        &record,
+                    ) {
+                        logger.log_warn(&format!("Failed to store the benchmark result: {}", e));
+                    }
+                    println!("{}", run_telemetry.report);
+                    if has_cache_anomalies(&results) {
+                        logger.log_warn(
+                            "Cache benchmark found levels slower or smaller than reported.",
+                        );
+                    }
+                    logger.log_info("Cache benchmark completed.");
+                    jobs.finish_job(job_id, JobStatus::Completed, None);
+                }
+                Err(_) if ticket.cancel.is_cancelled() => {
+                    logger.log_warn("Cache benchmark cancelled.");
+                    jobs.finish_job(job_id, JobStatus::Cancelled, None);
+                }
+                Err(e) => {
+                    logger.log_error(&format!("Cache benchmark failed: {}", e));
+                    jobs.finish_job(job_id, JobStatus::Failed, Some(e));
+                }
+            }
+        }
+        BenchmarkKind::Filesystem { path, files } => {
+            logger.log_info(&format!(
+                "Running filesystem benchmark in {} with {} files",
+                path, files
+            ));
+
+            // Other disk workloads would compete for the drive, so wait for them.
+            let mut ticket =
+                jobs.submit_job(JobSpec::new("benchmark:filesystem", &[Resource::Disk]));
+            let job_id = ticket.job.id;
+            if ticket.job.status == JobStatus::Queued {
+                logger.log_info(&format!(
+                    "Filesystem benchmark job {} queued behind a conflicting job.",
+                    job_id
+                ));
+            }
+            if !ticket.admitted().await {
+                logger.log_warn(&format!(
+                    "Filesystem benchmark job {} cancelled while queued.",
+                    job_id
+                ));
+                return;
+            }
+
+            let filesystem_benchmark = FilesystemBenchmarkAdapter::new(logger.clone());
+            let run_monitor =
+                RunMonitor::start(logger.clone(), telemetry.clone(), DEFAULT_SAMPLE_INTERVAL);
+            let outcome = runner.run(
+                "filesystem",
+                || {
+                    filesystem_benchmark.run_filesystem_benchmark(
+                        Path::new(&path),
+                        files,
+                        &ticket.cancel,
+                    )
+                },
+                filesystem_measurements,
+            );
+            let run_telemetry = run_monitor.finish();
+
+            match outcome {
+                Ok((result, record)) => {
+                    println!("{}", format_filesystem_results(&result));
+                    if plan.repetitions > 1 {
+                        println!("{}", format_statistics(&plan, &record.statistics));
+                    }
+                    if let Err(e) = repository.store_run(
+                        "benchmark",
+                        &record.started_at,
+                        Some(&record.benchmark),
+                        &record,
+                    ) {
+                        logger.log_warn(&format!("Failed to store the benchmark result: {}", e));
+                    }
+                    println!("{}", run_telemetry.report);
+                    logger.log_info("Filesystem benchmark completed.");
+                    jobs.finish_job(job_id, JobStatus::Completed, None);
+                }
+                Err(_) if ticket.cancel.is_cancelled() => {
+                    logger.log_warn("Filesystem benchmark cancelled.");
+                    jobs.finish_job(job_id, JobStatus::Cancelled, None);
+                }
+                Err(e) => {
+                    logger.log_error(&format!("Filesystem benchmark failed: {}", e));
+                    jobs.finish_job(job_id, JobStatus::Failed, Some(e));
+                }
+            }
+        }
+        BenchmarkKind::Device {
+            device,
+            all_disks,
+            parallel,
+            read_only,
+            force,
+            confirm,
+        } => {
+            // Decide before queueing which devices may be written to at all.
+            let targets = match device_targets(
+                logger.clone(),
+                device.as_deref(),
+                all_disks,
+                read_only,
+                force,
+                confirm.as_deref(),
+            ) {
+                Ok(targets) => targets,
+                Err(e) => {
+                    logger.log_error(&e);
+                    return;
+                }
+            };
+            for (_, usage, access) in &targets {
+                if *access == DeviceAccess::Forced {
+                    logger.log_warn(&format!(
+                        "Writing to {} although it {}; its data will be destroyed.",
+                        usage.device,
+                        usage.reasons().join(" and ")
+                    ));
+                }
+            }
+            let devices: Vec<&str> = targets
+                .iter()
+                .map(|(_, usage, _)| usage.device.as_str())
+                .collect();
+            logger.log_info(&format!(
+                "Running block device benchmark on {}",
+                devices.join(", ")
+            ));
+
+            // Other disk workloads would compete for the drives, so wait for them.
+            let mut ticket = jobs.submit_job(JobSpec::new("benchmark:device", &[Resource::Disk]));
+            let job_id = ticket.job.id;
+            if ticket.job.status == JobStatus::Queued {
+                logger.log_info(&format!(
+                    "Block device benchmark job {} queued behind a conflicting job.",
+                    job_id
+                ));
+            }
+            if !ticket.admitted().await {
+                logger.log_warn(&format!(
+                    "Block device benchmark job {} cancelled while queued.",
+                    job_id
+                ));
+                return;
+            }
+
+            let block_device = BlockDeviceAdapter::new(logger.clone());
+            let cancel = &ticket.cancel;
+            let run_device = |(benchmark, usage, access): &(String, DeviceUsage, DeviceAccess)| {
+                runner.run(
+                    benchmark,
+                    || block_device.run_device_benchmark(&usage.device, *access, cancel),
+                    device_measurements,
+                )
+            };
+            let run_monitor =
+                RunMonitor::start(logger.clone(), telemetry.clone(), DEFAULT_SAMPLE_INTERVAL);
+            let outcomes: Vec<_> = if parallel {
+                // Each disk has its own queue, so the disks are measured side by side.
+                std::thread::scope(|scope| {
+                    let handles: Vec<_> = targets
+                        .iter()
+                        .map(|target| scope.spawn(|| run_device(target)))
+                        .collect();
+                    handles
+                        .into_iter()
+                        .map(|handle| {
+                            handle.join().unwrap_or_else(|_| {
+                                Err("The benchmark thread panicked".to_string())
+                            })
+                        })
+                        .collect()
+                })
+            } else {
+                // Once cancelled, the disks not yet measured are skipped.
+                targets
+                    .iter()
+                    .map_while(|target| (!cancel.is_cancelled()).then(|| run_device(target)))
+                    .collect()
+            };
+            let run_telemetry = run_monitor.finish();
+
+            let mut failures = Vec::new();
+            for ((_, usage, _), outcome) in targets.iter().zip(outcomes) {
+                match outcome {
+                    Ok((result, record)) => {
+                        println!("{}", format_device_results(&result));
+                        if plan.repetitions > 1 {
+                            println!("{}", format_statistics(&plan, &record.statistics));
+                        }
+                        if let Err(e) = repository.store_run(
+                            "benchmark",
+                            &record.started_at,
+                            Some(&record.benchmark),
+                            &record,
+                        ) {
+                            logger
+                                .log_warn(&format!("Failed to store the benchmark result: {}", e));
+                        }
+                    }
+                    Err(_) if cancel.is_cancelled() => {}
+                    Err(e) => {
+                        logger.log_error(&format!(
+                            "Block device benchmark of {} failed: {}",
+                            usage.device, e
+                        ));
+                        failures.push(format!("{}: {}", usage.device, e));
+                    }
+                }
+            }
+            println!("{}", run_telemetry.report);
+
+            if cancel.is_cancelled() {
+                logger.log_warn("Block device benchmark cancelled.");
+                jobs.finish_job(job_id, JobStatus::Cancelled, None);
+            } else if failures.is_empty() {
+                logger.log_info("Block device benchmark completed.");
+                jobs.finish_job(job_id, JobStatus::Completed, None);
+            } else {
+                jobs.finish_job(job_id, JobStatus::Failed, Some(failures.join("; ")));
+            }
+        }
+        BenchmarkKind::Interconnect => {
+            logger.log_info("Running GPU interconnect benchmark");
+
+            // GPU stress tests would compete for the GPUs and their links, so wait for them.
+            let mut ticket =
+                jobs.submit_job(JobSpec::new("benchmark:interconnect", &[Resource::Gpu]));
+            let job_id = ticket.job.id;
+            if ticket.job.status == JobStatus::Queued {
+                logger.log_info(&format!(
+                    "GPU interconnect benchmark job {} queued behind a conflicting job.",
+                    job_id
+                ));
+            }
+            if !ticket.admitted().await {
+                logger.log_warn(&format!(
+                    "GPU interconnect benchmark job {} cancelled while queued.",
+                    job_id
+                ));
+                return;
+            }
+
+            let interconnect = GpuInterconnectAdapter::new(logger.clone());
+            let run_monitor =
+                RunMonitor::start(logger.clone(), telemetry.clone(), DEFAULT_SAMPLE_INTERVAL);
+            let outcome = runner.run(
+                "interconnect",
+                || interconnect.run_interconnect_benchmark(&ticket.cancel),
+                interconnect_measurements,
+            );
+            let run_telemetry = run_monitor.finish();
+
+            match outcome {
+                Ok((result, record)) => {
+                    println!("{}", format_interconnect_results(&result));
+                    if plan.repetitions > 1 {
+                        println!("{}", format_statistics(&plan, &record.statistics));
+                    }
+                    if let Err(e) = repository.store_run(
+                        "benchmark",
+                        &record.started_at,
+                        Some(&record.benchmark),
+                        &record,
+                    ) {
+                        logger.log_warn(&format!("Failed to store the benchmark result: {}", e));
+                    }
+                    println!("{}", run_telemetry.report);
+                    logger.log_info("GPU interconnect benchmark completed.");
+                    jobs.finish_job(job_id, JobStatus::Completed, None);
+                }
+                Err(_) if ticket.cancel.is_cancelled() => {
+                    logger.log_warn("GPU interconnect benchmark cancelled.");
+                    jobs.finish_job(job_id, JobStatus::Cancelled, None);
+                }
+                Err(e) => {
+                    logger.log_error(&format!("GPU interconnect benchmark failed: {}", e));
+                    jobs.finish_job(job_id, JobStatus::Failed, Some(e));
+                }
+            }
+        }
+        BenchmarkKind::Rdma {
+            device,
+            port,
+            peer,
+            server,
+        } => {
+            let rdma = RdmaAdapter::new(logger.clone());
+            let devices = match rdma.discover_rdma_devices() {
+                Ok(devices) => devices,
+                Err(e) => {
+                    logger.log_error(&format!("Failed to list RDMA devices: {}", e));
+                    return;
+                }
+            };
+            // Without a choice, use the first active port, since a down port cannot be measured.
+            let selected = devices
+                .iter()
+                .filter(|d| device.as_ref().is_none_or(|name| d.name == *name))
+                .flat_map(|d| d.ports.iter().map(move |p| (d, p)))
+                .filter(|(_, p)| port.is_none_or(|number| p.number == number))
+                .min_by_key(|(_, p)| !p.is_active());
+            let Some((device, port)) = selected else {
+                logger.log_error("No matching RDMA device port found.");
+                return;
+            };
+            if !port.is_active() {
+                logger.log_warn(&format!(
+                    "RDMA port {} of {} is not active; the benchmark will likely fail.",
+                    port.number, device.name
+                ));
+            }
+            logger.log_info(&format!(
+                "Running RDMA benchmark on {} port {} {}",
+                device.name,
+                port.number,
+                match (&peer, server) {
+                    (_, true) => "as the server".to_string(),
+                    (Some(peer), _) => format!("to {}", peer),
+                    (None, _) => "in loopback".to_string(),
+                }
+            ));
+
+            // Other network workloads would compete for the fabric, so wait for them.
+            let mut ticket = jobs.submit_job(JobSpec::new("benchmark:rdma", &[Resource::Network]));
+            let job_id = ticket.job.id;
+            if ticket.job.status == JobStatus::Queued {
+                logger.log_info(&format!(
+                    "RDMA benchmark job {} queued behind a conflicting job.",
+                    job_id
+                ));
+            }
+            if !ticket.admitted().await {
+                logger.log_warn(&format!(
+                    "RDMA benchmark job {} cancelled while queued.",
+                    job_id
+                ));
+                return;
+            }
+
+            if server {
+                // The peer runs every warm-up run and repetition against this side,
+                // so it is served as often.
+                match runner.run(
+                    "rdma",
+                    || rdma.serve_rdma_benchmark(&device.name, port.number, &ticket.cancel),
+                    |_| Vec::new(),
+                ) {
+                    Ok(_) => {
+                        logger.log_info("RDMA benchmark served.");
+                        jobs.finish_job(job_id, JobStatus::Completed, None);
+                    }
+                    Err(_) if ticket.cancel.is_cancelled() => {
+                        logger.log_warn("RDMA benchmark cancelled.");
+                        jobs.finish_job(job_id, JobStatus::Cancelled, None);
+                    }
+                    Err(e) => {
+                        logger.log_error(&format!("RDMA benchmark failed: {}", e));
+                        jobs.finish_job(job_id, JobStatus::Failed, Some(e));
+                    }
+                }
+                return;
+            }
+
+            let run_monitor =
+                RunMonitor::start(logger.clone(), telemetry.clone(), DEFAULT_SAMPLE_INTERVAL);
+            let outcome = runner.run(
+                "rdma",
+                || {
+                    rdma.run_rdma_benchmark(
+                        &device.name,
+                        port.number,
+                        peer.as_deref(),
+                        &ticket.cancel,
+                    )
+                },
+                rdma_measurements,
+            );
+            let run_telemetry = run_monitor.finish();
+
+            match outcome {
+                Ok((result, record)) => {
+                    println!("{}", format_rdma_results(&result));
+                    if plan.repetitions > 1 {
+                        println!("{}", format_statistics(&plan, &record.statistics));
+                    }
+                    if let Err(e) = repository.store_run(
+                        "benchmark",
+                        &record.started_at,
+                        Some(&record.benchmark),
+                        &record,
+                    ) {
+                        logger.log_warn(&format!("Failed to store the benchmark result: {}", e));
+                    }
+                    println!("{}", run_telemetry.report);
+                    logger.log_info("RDMA benchmark completed.");
+                    jobs.finish_job(job_id, JobStatus::Completed, None);
+                }
+                Err(_) if ticket.cancel.is_cancelled() => {
+                    logger.log_warn("RDMA benchmark cancelled.");
+                    jobs.finish_job(job_id, JobStatus::Cancelled, None);
+                }
+                Err(e) => {
+                    logger.log_error(&format!("RDMA benchmark failed: {}", e));
+                    jobs.finish_job(job_id, JobStatus::Failed, Some(e));
+                }
+            }
+        }
+        BenchmarkKind::Mitigations { measure, cpu } => {
+            let vulnerabilities =
+                match CpuFeaturesAdapter::new(logger.clone()).discover_cpu_features() {
+                    Ok(features) => features.vulnerabilities,
+                    Err(e) => {
+                        logger.log_error(&format!("CPU feature discovery failed: {}", e));
+                        return;
+                    }
+                };
+            let mitigations = MitigationBenchmarkAdapter::new(logger.clone());
+            let kernel_parameters = match mitigations.read_kernel_command_line() {
+                Ok(cmdline) => mitigation_parameters(&cmdline),
+                Err(e) => {
+                    logger.log_warn(&format!("Skipping kernel parameters: {}", e));
+                    Vec::new()
+                }
+            };
+            let mut report = MitigationReport {
+                vulnerabilities,
+                kernel_parameters,
+                overhead: None,
+            };
+            if !measure {
+                println!("{}", format_mitigation_report(&report));
+                return;
+            }
+
+            let cpu = cpu.unwrap_or_else(|| {
+                TopologyAdapter::new(logger.clone())
+                    .discover_cpu_topology()
+                    .ok()
+                    .and_then(|topology| topology.cpus.first().map(|c| c.id))
+                    .unwrap_or(0)
+            });
+            logger.log_info(&format!(
+                "Measuring system call and context switch costs on CPU {}",
+                cpu
+            ));
+
+            // Other workloads on the CPU would be switched to in between, so wait for them.
+            let mut ticket =
+                jobs.submit_job(JobSpec::new("benchmark:mitigations", &[Resource::Cpu]));
+            let job_id = ticket.job.id;
+            if ticket.job.status == JobStatus::Queued {
+                logger.log_info(&format!(
+                    "Mitigation benchmark job {} queued behind a conflicting job.",
+                    job_id
+                ));
+            }
+            if !ticket.admitted().await {
+                logger.log_warn(&format!(
+                    "Mitigation benchmark job {} cancelled while queued.",
+                    job_id
+                ));
+                return;
+            }
+
+            match runner.run(
+                "mitigations",
+                || mitigations.measure_overhead(cpu, &ticket.cancel),
+                mitigation_measurements,
+            ) {
+                Ok((overhead, record)) => {
+                    report.overhead = Some(overhead);
+                    println!("{}", format_mitigation_report(&report));
+                    if plan.repetitions > 1 {
+                        println!("{}", format_statistics(&plan, &record.statistics));
+                    }
+                    if let Err(e) = repository.store_run(
+                        "benchmark",
+                        &record.started_at,
+                        Some(&record.benchmark),
+                        &record,
+                    ) {
+                        logger.log_warn(&format!("Failed to store the benchmark result: {}", e));
+                    }
+                    logger.log_info("Mitigation benchmark completed.");
+                    jobs.finish_job(job_id, JobStatus::Completed, None);
+                }
+                Err(_) if ticket.cancel.is_cancelled() => {
+                    logger.log_warn("Mitigation benchmark cancelled.");
+                    jobs.finish_job(job_id, JobStatus::Cancelled, None);
+                }
+                Err(e) => {
+                    logger.log_error(&format!("Mitigation benchmark failed: {}", e));
+                    jobs.finish_job(job_id, JobStatus::Failed, Some(e));
+                }
+            }
+        }
+        BenchmarkKind::Latency {
+            cpu,
+            waker_cpu,
+            interval_us,
+            duration_secs,
+        } => {
+            let online: Vec<u32> = TopologyAdapter::new(logger.clone())
+                .discover_cpu_topology()
+                .map(|topology| topology.cpus.iter().map(|c| c.id).collect())
+                .unwrap_or_default();
+            let cpu = cpu.unwrap_or_else(|| online.first().copied().unwrap_or(0));
+            let waker_cpu = waker_cpu
+                .unwrap_or_else(|| online.iter().copied().find(|&id| id != cpu).unwrap_or(cpu));
+            logger.log_info(&format!(
+                "Measuring scheduler latency on CPU {} with wake-ups from CPU {}",
+                cpu, waker_cpu
+            ));
+
+            // Other workloads would preempt the measured threads, so wait for them.
+            let mut ticket = jobs.submit_job(JobSpec::new("benchmark:latency", &[Resource::Cpu]));
+            let job_id = ticket.job.id;
+            if ticket.job.status == JobStatus::Queued {
+                logger.log_info(&format!(
+                    "Scheduler latency benchmark job {} queued behind a conflicting job.",
+                    job_id
+                ));
+            }
+            if !ticket.admitted().await {
+                logger.log_warn(&format!(
+                    "Scheduler latency benchmark job {} cancelled while queued.",
+                    job_id
+                ));
+                return;
+            }
+
+            let scheduler_latency = SchedulerLatencyAdapter::new(logger.clone());
+            match runner.run(
+                "latency",
+                || {
+                    scheduler_latency.measure_scheduler_latency(
+                        cpu,
+                        waker_cpu,
+                        Duration::from_micros(interval_us.max(1)),
+                        Duration::from_secs(duration_secs),
+                        &ticket.cancel,
+                    )
+                },
+                scheduler_latency_measurements,
+            ) {
+                Ok((result, record)) => {
+                    println!("{}", format_scheduler_latency_results(&result));
+                    if plan.repetitions > 1 {
+                        println!("{}", format_statistics(&plan, &record.statistics));
+                    }
+                    if let Err(e) = repository.store_run(
+                        "benchmark",
+                        &record.started_at,
+                        Some(&record.benchmark),
+                        &record,
+                    ) {
+                        logger.log_warn(&format!("Failed to store the benchmark result: {}", e));
+                    }
+                    logger.log_info("Scheduler latency benchmark completed.");
+                    jobs.finish_job(job_id, JobStatus::Completed, None);
+                }
+                Err(_) if ticket.cancel.is_cancelled() => {
+                    logger.log_warn("Scheduler latency benchmark cancelled.");
+                    jobs.finish_job(job_id, JobStatus::Cancelled, None);
+                }
+                Err(e) => {
+                    logger.log_error(&format!("Scheduler latency benchmark failed: {}", e));
+                    jobs.finish_job(job_id, JobStatus::Failed, Some(e));
+                }
+            }
+        }
+        BenchmarkKind::Scaling {
+            step_secs,
+            max_threads,
+        } => {
+            let topology = match TopologyAdapter::new(logger.clone()).discover_cpu_topology() {
+                Ok(topology) => topology,
+                Err(e) => {
+                    logger.log_error(&format!("Failed to discover the CPU topology: {}", e));
+                    return;
+                }
+            };
+
+            // Any other workload on the CPUs would bend the curve, so wait for it.
+            let mut ticket = jobs.submit_job(JobSpec::new("benchmark:scaling", &[Resource::Cpu]));
+            let job_id = ticket.job.id;
+            if ticket.job.status == JobStatus::Queued {
+                logger.log_info(&format!(
+                    "CPU scaling benchmark job {} queued behind a conflicting job.",
+                    job_id
+                ));
+            }
+            if !ticket.admitted().await {
+                logger.log_warn(&format!(
+                    "CPU scaling benchmark job {} cancelled while queued.",
+                    job_id
+                ));
+                return;
+            }
+
+            let scaling = ScalingBenchmarkAdapter::new(logger.clone());
+            match runner.run(
+                "scaling",
+                || {
+                    scaling.run_scaling_benchmark(
+                        &topology,
+                        max_threads,
+                        Duration::from_secs(step_secs.max(1)),
+                        &ticket.cancel,
+                    )
+                },
+                scaling_measurements,
+            ) {
+                Ok((result, record)) => {
+                    println!("{}", format_scaling_results(&result));
+                    if plan.repetitions > 1 {
+                        println!("{}", format_statistics(&plan, &record.statistics));
+                    }
+                    if let Err(e) = repository.store_run(
+                        "benchmark",
+                        &record.started_at,
+                        Some(&record.benchmark),
+                        &record,
+                    ) {
+                        logger.log_warn(&format!("Failed to store the benchmark result: {}", e));
+                    }
+                    logger.log_info("CPU scaling benchmark completed.");
+                    jobs.finish_job(job_id, JobStatus::Completed, None);
+                }
+                Err(_) if ticket.cancel.is_cancelled() => {
+                    logger.log_warn("CPU scaling benchmark cancelled.");
+                    jobs.finish_job(job_id, JobStatus::Cancelled, None);
+                }
+                Err(e) => {
+                    logger.log_error(&format!("CPU scaling benchmark failed: {}", e));
+                    jobs.finish_job(job_id, JobStatus::Failed, Some(e));
+                }
+            }
+        }
+        codec
+        @ (BenchmarkKind::Crypto { step_secs } | BenchmarkKind::Compression { step_secs }) => {
+            let (name, title, algorithms) = if matches!(codec, BenchmarkKind::Crypto { .. }) {
+                ("crypto", "Crypto throughput", &CodecAlgorithm::CRYPTO[..])
+            } else {
+                (
+                    "compression",
+                    "Compression throughput",
+                    &CodecAlgorithm::COMPRESSION[..],
+                )
+            };
+            let topology = match TopologyAdapter::new(logger.clone()).discover_cpu_topology() {
+                Ok(topology) => topology,
+                Err(e) => {
+                    logger.log_error(&format!("Failed to discover the CPU topology: {}", e));
+                    return;
+                }
+            };
+
+            let mut ticket = jobs.submit_job(JobSpec::new(
+                &format!("benchmark:{}", name),
+                &[Resource::Cpu],
+            ));
+            let job_id = ticket.job.id;
+            if ticket.job.status == JobStatus::Queued {
+                logger.log_info(&format!(
+                    "{} benchmark job {} queued behind a conflicting job.",
+                    title, job_id
+                ));
+            }
+            if !ticket.admitted().await {
+                logger.log_warn(&format!(
+                    "{} benchmark job {} cancelled while queued.",
+                    title, job_id
+                ));
+                return;
+            }
+
+            let codecs = CodecBenchmarkAdapter::new(logger.clone());
+            match runner.run(
+                name,
+                || {
+                    codecs.run_codec_benchmark(
+                        algorithms,
+                        &topology,
+                        Duration::from_secs(step_secs.max(1)),
+                        &ticket.cancel,
+                    )
+                },
+                codec_measurements,
+            ) {
+                Ok((result, record)) => {
+                    println!("{}", format_codec_results(title, &result));
+                    if plan.repetitions > 1 {
+                        println!("{}", format_statistics(&plan, &record.statistics));
+                    }
+                    if let Err(e) = repository.store_run(
+                        "benchmark",
+                        &record.started_at,
+                        Some(&record.benchmark),
+                        &record,
+                    ) {
+                        logger.log_warn(&format!("Failed to store the benchmark result: {}", e));
+                    }
+                    logger.log_info(&format!("{} benchmark completed.", title));
+                    jobs.finish_job(job_id, JobStatus::Completed, None);
+                }
+                Err(_) if ticket.cancel.is_cancelled() => {
+                    logger.log_warn(&format!("{} benchmark cancelled.", title));
+                    jobs.finish_job(job_id, JobStatus::Cancelled, None);
+                }
+                Err(e) => {
+                    logger.log_error(&format!("{} benchmark failed: {}", title, e));
+                    jobs.finish_job(job_id, JobStatus::Failed, Some(e));
+                }
+            }
+        }
+        BenchmarkKind::Clock { rounds } => {
+            let cpus: Vec<u32> = TopologyAdapter::new(logger.clone())
+                .discover_cpu_topology()
+                .map(|topology| topology.cpus.iter().map(|c| c.id).collect())
+                .unwrap_or_else(|_| vec![0]);
+
+            // Other workloads would delay the token passed between CPUs, so wait for them.
+            let mut ticket = jobs.submit_job(JobSpec::new("benchmark:clock", &[Resource::Cpu]));
+            let job_id = ticket.job.id;
+            if ticket.job.status == JobStatus::Queued {
+                logger.log_info(&format!(
+                    "Clock stability benchmark job {} queued behind a conflicting job.",
+                    job_id
+                ));
+            }
+            if !ticket.admitted().await {
+                logger.log_warn(&format!(
+                    "Clock stability benchmark job {} cancelled while queued.",
+                    job_id
+                ));
+                return;
+            }
+
+            let clocks = ClockStabilityAdapter::new(logger.clone());
+            match runner.run(
+                "clock",
+                || clocks.measure_clock_stability(&cpus, rounds, &ticket.cancel),
+                clock_measurements,
+            ) {
+                Ok((result, record)) => {
+                    println!("{}", format_clock_results(&result));
+                    if plan.repetitions > 1 {
+                        println!("{}", format_statistics(&plan, &record.statistics));
+                    }
+                    if let Err(e) = repository.store_run(
+                        "benchmark",
+                        &record.started_at,
+                        Some(&record.benchmark),
+                        &record,
+                    ) {
+                        logger.log_warn(&format!("Failed to store the benchmark result: {}", e));
+                    }
+                    logger.log_info("Clock stability benchmark completed.");
+                    jobs.finish_job(job_id, JobStatus::Completed, None);
+                }
+                Err(_) if ticket.cancel.is_cancelled() => {
+                    logger.log_warn("Clock stability benchmark cancelled.");
+                    jobs.finish_job(job_id, JobStatus::Cancelled, None);
+                }
+                Err(e) => {
+                    logger.log_error(&format!("Clock stability benchmark failed: {}", e));
+                    jobs.finish_job(job_id, JobStatus::Failed, Some(e));
+                }
+            }
+        }
+        BenchmarkKind::Boot { max_post } => {
+            let max_post = match max_post.as_deref().map(parse_duration).transpose() {
+                Ok(max_post) => max_post,
+                Err(e) => {
+                    logger.log_error(&format!("Invalid --max-post value: {}", e));
+                    return;
+                }
+            };
+
+            // Timing the last boot only reads what systemd and the BMC recorded.
+            let mut ticket = jobs.submit_job(JobSpec::new("benchmark:boot", &[]));
+            let job_id = ticket.job.id;
+            if !ticket.admitted().await {
+                logger.log_warn(&format!(
+                    "Boot timing job {} cancelled while queued.",
+                    job_id
+                ));
+                return;
+            }
+
+            let boot = BootTimingAdapter::new(logger.clone());
+            match runner.run("boot", || boot.measure_boot(), boot_measurements) {
+                Ok((timing, record)) => {
+                    println!("{}", format_boot_results(&timing, max_post));
+                    if let Err(e) = repository.store_run(
+                        "benchmark",
+                        &record.started_at,
+                        Some(&record.benchmark),
+                        &record,
+                    ) {
+                        logger.log_warn(&format!("Failed to store the benchmark result: {}", e));
+                    }
+                    match (timing.post(), max_post) {
+                        (Some(post), Some(max_post)) if post > max_post => {
+                            let message = format!(
+                                "The POST took {:.1} s, longer than the {:.1} s accepted",
+                                post.as_secs_f64(),
+                                max_post.as_secs_f64()
+                            );
+                            logger.log_error(&message);
+                            jobs.finish_job(job_id, JobStatus::Failed, Some(message));
+                        }
+                        (None, Some(_)) => {
+                            let message =
+                                "--max-post was given but the POST time is unknown".to_string();
+                            logger.log_error(&message);
+                            jobs.finish_job(job_id, JobStatus::Failed, Some(message));
+                        }
+                        _ => {
+                            logger.log_info("Boot timing completed.");
+                            jobs.finish_job(job_id, JobStatus::Completed, None);
+                        }
+                    }
+                }
+                Err(e) => {
+                    logger.log_error(&format!("Boot timing failed: {}", e));
+                    jobs.finish_job(job_id, JobStatus::Failed, Some(e));
+                }
+            }
+        }
+        BenchmarkKind::Exec { timeout, command } => {
+            let timeout = match timeout.as_deref().map(parse_duration).transpose() {
+                Ok(timeout) => timeout,
+                Err(e) => {
+                    logger.log_error(&format!("Invalid --timeout value: {}", e));
+                    return;
+                }
+            };
+
+            // The command may use any part of the machine, so wait for other CPU and memory work.
+            let mut ticket = jobs.submit_job(JobSpec::new(
+                "benchmark:exec",
+                &[Resource::Cpu, Resource::Memory],
+            ));
+            let job_id = ticket.job.id;
+            if ticket.job.status == JobStatus::Queued {
+                logger.log_info(&format!(
+                    "Workload benchmark job {} queued behind a conflicting job.",
+                    job_id
+                ));
+            }
+            if !ticket.admitted().await {
+                logger.log_warn(&format!(
+                    "Workload benchmark job {} cancelled while queued.",
+                    job_id
+                ));
+                return;
+            }
+
+            let workload = WorkloadAdapter::new(logger.clone());
+            let run_monitor =
+                RunMonitor::start(logger.clone(), telemetry.clone(), DEFAULT_SAMPLE_INTERVAL);
+            // Each command line is stored as a benchmark of its own, so its trend stays apart.
+            let outcome = runner.run(
+                &format!("exec {}", command.join(" ")),
+                || workload.run_workload(&command, timeout, &ticket.cancel),
+                workload_measurements,
+            );
+            let run_telemetry = run_monitor.finish();
+
+            match outcome {
+                Ok((result, record)) => {
+                    println!("{}", format_workload_results(&result));
+                    if plan.repetitions > 1 {
+                        println!("{}", format_statistics(&plan, &record.statistics));
+                    }
+                    if let Err(e) = repository.store_run(
+                        "benchmark",
+                        &record.started_at,
+                        Some(&record.benchmark),
+                        &record,
+                    ) {
+                        logger.log_warn(&format!("Failed to store the benchmark result: {}", e));
+                    }
+                    println!("{}", run_telemetry.report);
+                    logger.log_info("Workload benchmark completed.");
+                    jobs.finish_job(job_id, JobStatus::Completed, None);
+                }
+                Err(_) if ticket.cancel.is_cancelled() => {
+                    logger.log_warn("Workload benchmark cancelled.");
+                    jobs.finish_job(job_id, JobStatus::Cancelled, None);
+                }
+                Err(e) => {
+                    logger.log_error(&format!("Workload benchmark failed: {}", e));
+                    jobs.finish_job(job_id, JobStatus::Failed, Some(e));
+                }
+            }
+        }
+    }
+    finish_flamegraph(
+        logger.clone(),
+        profiler,
+        &flamegraph_title,
+        flamegraph.as_deref(),
+    );
+}
+
+/// Decides which block devices `benchmark device` runs on and how it may access each:
+/// the device given with `--device`, or with `--all-disks` every disk of the device
+/// inventory but the boot disk and, unless it runs read-only, the disks in use.
+///
+/// # Arguments
+///
+/// * `logger` - An Arc-wrapped LoggerPort trait object for logging.
+/// * `device` - The device given with `--device`, if any.
+/// * `all_disks` - Whether `--all-disks` was given.
+/// * `read_only` - Whether `--read-only` was given.
+/// * `force` - Whether `--force` was given.
+/// * `confirm` - The token given with `--confirm`, if any.
+///
+/// # Returns
+///
+/// * `Result<Vec<(String, DeviceUsage, DeviceAccess)>, String>` - The name each device's
+///   result is stored under, what it is used for and how it may be accessed, or why
+///   no device may be benchmarked.
+pub fn device_targets(
+    logger: Arc<dyn LoggerPort>,
+    device: Option<&str>,
+    all_disks: bool,
+    read_only: bool,
+    force: bool,
+    confirm: Option<&str>,
+) -> Result<Vec<(String, DeviceUsage, DeviceAccess)>, String> {
+    let block_device = BlockDeviceAdapter::new(logger.clone());
+    if !all_disks {
+        let device = device.ok_or("Either --device or --all-disks is required")?;
+        let usage = block_device.read_device_usage(device)?;
+        let access = device_access(&usage, read_only, force, confirm)?;
+        return Ok(vec![("device".to_string(), usage, access)]);
+    }
+
+    let mut targets = Vec::new();
+    for disk in DeviceInventoryAdapter::new(logger.clone()).list_disks()? {
+        let usage = match block_device.read_device_usage(&format!("/dev/{}", disk.name)) {
+            Ok(usage) => usage,
+            Err(e) => {
+                logger.log_warn(&format!("Skipping {}: {}", disk.name, e));
+                continue;
+            }
+        };
+        if usage.is_boot_disk() {
+            logger.log_info(&format!("Skipping {}, the boot disk.", usage.device));
+            continue;
+        }
+        match device_access(&usage, read_only, false, None) {
+            Ok(access) => targets.push((format!("device:{}", disk.name), usage, access)),
+            Err(_) => logger.log_warn(&format!(
+                "Skipping {}, which {}; use --read-only to benchmark it without writing.",
+                usage.device,
+                usage.reasons().join(" and ")
+            )),
+        }
+    }
+    if targets.is_empty() {
+        return Err("No disk to benchmark: every disk is the boot disk or in use".to_string());
+    }
+    Ok(targets)
+}
+
+/// Plans a benchmark for `--dry-run`.
+///
+/// # Arguments
+///
+/// * `logger` - Logger implementation for logging messages.
+/// * `warmup` - The number of warm-up runs.
+/// * `repetitions` - The number of measured runs.
+/// * `kind` - The benchmark to plan.
+///
+/// # Returns
+///
+/// * `Result<ExecutionPlan, String>` - The plan, or why the benchmark cannot be planned.
+pub fn plan(
+    logger: Arc<dyn LoggerPort>,
+    warmup: u32,
+    repetitions: u32,
+    kind: &BenchmarkKind,
+) -> Result<ExecutionPlan, String> {
+    let runs = warmup + repetitions;
+    let step = match kind {
+        BenchmarkKind::Numa { size_mb, .. } => PlannedStep {
+            targets: vec!["every NUMA node".to_string()],
+            ..PlannedStep::new(
+                "numa",
+                format!("cross-node memory benchmark with {} MB buffers", size_mb),
+                &[Resource::Cpu, Resource::Memory],
+            )
+        },
+        BenchmarkKind::Cache { cpu } => PlannedStep {
+            targets: vec![cpu.map_or("the first online CPU".to_string(), |cpu| {
+                format!("CPU {}", cpu)
+            })],
+            ..PlannedStep::new(
+                "cache",
+                "bandwidth and latency sweep of each cache level and DRAM",
+                &[Resource::Cpu, Resource::Memory],
+            )
+        },
+        BenchmarkKind::Filesystem { path, files } => PlannedStep {
+            targets: vec![path.clone()],
+            disk_writes: DiskWrites::Volume(
+                FilesystemBenchmarkAdapter::planned_writes(*files) * runs as u64,
+            ),
+            ..PlannedStep::new(
+                "filesystem",
+                format!(
+                    "create, stat and delete {} files, random writes and fsyncs",
+                    files
+                ),
+                &[Resource::Disk],
+            )
+        },
+        BenchmarkKind::Device {
+            device,
+            all_disks,
+            parallel,
+            read_only,
+            force,
+            confirm,
+        } => {
+            let targets = device_targets(
+                logger.clone(),
+                device.as_deref(),
+                *all_disks,
+                *read_only,
+                *force,
+                confirm.as_deref(),
+            )?;
+            let writes = targets
+                .iter()
+                .any(|(_, _, access)| *access != DeviceAccess::ReadOnly);
+            PlannedStep {
+                targets: targets
+                    .iter()
+                    .map(|(_, usage, _)| usage.device.clone())
+                    .collect(),
+                disk_writes: if writes {
+                    DiskWrites::Sustained(WRITE_SPAN_BYTES * targets.len() as u64)
+                } else {
+                    DiskWrites::None
+                },
+                ..PlannedStep::new(
+                    "device",
+                    format!(
+                        "direct sequential and random {} of the raw device{}",
+                        if writes { "reads and writes" } else { "reads" },
+                        match (targets.len(), *parallel) {
+                            (1, _) => "",
+                            (_, true) => "s, in parallel",
+                            (_, false) => "s, one after another",
+                        }
+                    ),
+                    &[Resource::Disk],
+                )
+            }
+        }
+        BenchmarkKind::Interconnect => PlannedStep::new(
+            "interconnect",
+            "nvbandwidth copy bandwidth and latency tests between GPUs",
+            &[Resource::Gpu],
+        ),
+        BenchmarkKind::Rdma {
+            device,
+            peer,
+            server,
+            ..
+        } => PlannedStep {
+            targets: vec![
+                device
+                    .clone()
+                    .unwrap_or_else(|| "the first active RDMA device".to_string()),
+                if *server {
+                    "serving a peer".to_string()
+                } else {
+                    peer.clone().unwrap_or_else(|| "loopback".to_string())
+                },
+            ],
+            ..PlannedStep::new(
+                "rdma",
+                "perftest ib_write_bw and ib_read_lat",
+                &[Resource::Network],
+            )
+        },
+        BenchmarkKind::Mitigations { measure, cpu } => PlannedStep {
+            targets: vec![cpu.map_or("the first online CPU".to_string(), |cpu| {
+                format!("CPU {}", cpu)
+            })],
+            ..PlannedStep::new(
+                "mitigations",
+                if *measure {
+                    "read the active mitigations, then time system calls and context switches"
+                } else {
+                    "read the active mitigations"
+                },
+                &[Resource::Cpu],
+            )
+        },
+        BenchmarkKind::Latency {
+            cpu,
+            waker_cpu,
+            interval_us,
+            duration_secs,
+        } => PlannedStep {
+            duration: Some(Duration::from_secs(*duration_secs)),
+            targets: vec![
+                cpu.map_or("the first online CPU".to_string(), |cpu| {
+                    format!("CPU {}", cpu)
+                }),
+                waker_cpu.map_or("waker on the second online CPU".to_string(), |cpu| {
+                    format!("waker on CPU {}", cpu)
+                }),
+            ],
+            ..PlannedStep::new(
+                "latency",
+                format!(
+                    "context switch and wake-up latency, then {} us timer jitter",
+                    interval_us
+                ),
+                &[Resource::Cpu],
+            )
+        },
+        BenchmarkKind::Scaling {
+            step_secs,
+            max_threads,
+        } => {
+            let topology = TopologyAdapter::new(logger.clone()).discover_cpu_topology()?;
+            let steps = plan_scaling(&topology, *max_threads)?;
+            let sockets = plan_socket_runs(&topology);
+            let counts: Vec<String> = steps
+                .iter()
+                .map(|step| step.cpus.len().to_string())
+                .collect();
+            PlannedStep {
+                duration: Some(Duration::from_secs(
+                    (*step_secs).max(1) * (steps.len() + sockets.len()) as u64,
+                )),
+                targets: vec![format!(
+                    "{} logical CPUs across {} socket(s)",
+                    topology.cpus.len(),
+                    topology.package_count()
+                )],
+                ..PlannedStep::new(
+                    "scaling",
+                    format!(
+                        "CPU workload at {} threads, {} s each{}",
+                        counts.join(", "),
+                        (*step_secs).max(1),
+                        if sockets.is_empty() {
+                            String::new()
+                        } else {
+                            format!(", then on each of {} sockets alone", sockets.len())
+                        }
+                    ),
+                    &[Resource::Cpu],
+                )
+            }
+        }
+        BenchmarkKind::Crypto { step_secs } | BenchmarkKind::Compression { step_secs } => {
+            let (name, algorithms) = if matches!(kind, BenchmarkKind::Crypto { .. }) {
+                ("crypto", &CodecAlgorithm::CRYPTO[..])
+            } else {
+                ("compression", &CodecAlgorithm::COMPRESSION[..])
+            };
+            let topology = TopologyAdapter::new(logger.clone()).discover_cpu_topology()?;
+            let sockets = topology.package_count();
+            PlannedStep {
+                duration: Some(Duration::from_secs(
+                    (*step_secs).max(1) * (algorithms.len() * (1 + sockets)) as u64,
+                )),
+                targets: vec![format!(
+                    "one core, then the {} logical CPUs of each of {} socket(s)",
+                    topology.cpus.len() / sockets.max(1),
+                    sockets
+                )],
+                ..PlannedStep::new(
+                    name,
+                    algorithms
+                        .iter()
+                        .map(CodecAlgorithm::label)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    &[Resource::Cpu],
+                )
+            }
+        }
+        BenchmarkKind::Clock { rounds } => {
+            let cpus = TopologyAdapter::new(logger.clone())
+                .discover_cpu_topology()
+                .map_or(1, |topology| topology.cpus.len());
+            PlannedStep {
+                targets: vec![format!(
+                    "{} CPUs against the first online CPU",
+                    cpus.saturating_sub(1)
+                )],
+                ..PlannedStep::new(
+                    "clock",
+                    format!(
+                        "TSC invariance, clocksource, timer resolution and {} round trips per CPU pair",
+                        rounds
+                    ),
+                    &[Resource::Cpu],
+                )
+            }
+        }
+        BenchmarkKind::Boot { .. } => PlannedStep::new(
+            "boot",
+            "systemd-analyze time and blame, and the boot events of the BMC event log",
+            &[],
+        ),
+        BenchmarkKind::Exec { timeout, command } => PlannedStep {
+            duration: timeout
+                .as_deref()
+                .map(parse_duration)
+                .transpose()
+                .map_err(|e| format!("Invalid --timeout value: {}", e))?,
+            ..PlannedStep::new(
+                "exec",
+                command.join(" "),
+                &[Resource::Cpu, Resource::Memory],
+            )
+        },
+    };
+    let mut plan = ExecutionPlan::new(&format!("benchmark {}", step.name));
+    if runs > 1 {
+        plan.title.push_str(&format!(
+            ", {} warm-up and {} measured run(s)",
+            warmup, repetitions
+        ));
+    }
+    plan.steps.push(step);
+    Ok(plan)
+}
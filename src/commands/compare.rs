@@ -0,0 +1,89 @@
+//! Compare Command
+//!
+//! This module runs `one_for_all compare`, which scores this machine from
+//! its stored benchmark results, or ranks the agents of the fleet by theirs.
+
+use std::sync::Arc;
+
+use common::domain::api_config::ApiConfig;
+use common::ports::log_port::LoggerPort;
+
+use oneforall_core::adapters::jobs_client_adapter::JobsClientAdapter;
+use oneforall_core::adapters::mdns_adapter::discover_agents;
+use oneforall_core::adapters::score_adapter::ScoreAdapter;
+use oneforall_core::domain::fleet::FleetConfig;
+use oneforall_core::domain::score::{
+    format_machine_score, format_ranking, rank_machines, MachineScore,
+};
+
+use crate::commands::CommandContext;
+
+/// Scores this machine from its latest stored benchmark results.
+pub async fn score(ctx: CommandContext, json: bool) {
+    let CommandContext {
+        logger, repository, ..
+    } = ctx;
+    let scores = ScoreAdapter::new(logger.clone(), repository.clone());
+    match scores.machine_score() {
+        Ok(score) if json => match serde_json::to_string_pretty(&score) {
+            Ok(json) => println!("{}", json),
+            Err(e) => logger.log_error(&format!("Failed to encode the scores: {}", e)),
+        },
+        Ok(score) => print!("{}", format_machine_score(&score)),
+        Err(e) => logger.log_error(&format!("Failed to score this machine: {}", e)),
+    }
+}
+
+/// Ranks the agents of the fleet, each scoring itself from its own stored benchmark
+/// results through its REST API. Agents that cannot be scored are left out with a warning.
+///
+/// # Arguments
+///
+/// * `logger` - Logger implementation for logging messages.
+/// * `api_config` - The token and TLS settings the agents are called with.
+/// * `fleet_config` - The agents listed in the `[fleet]` section.
+/// * `json` - Whether to print JSON rather than a table.
+pub fn rank_fleet(
+    logger: Arc<dyn LoggerPort>,
+    api_config: &ApiConfig,
+    fleet_config: &FleetConfig,
+    json: bool,
+) {
+    let agents = discover_agents(logger.clone(), fleet_config);
+    let scores: Vec<(String, Result<MachineScore, String>)> = std::thread::scope(|scope| {
+        let queries: Vec<_> = agents
+            .into_iter()
+            .map(|agent| {
+                let logger = logger.clone();
+                scope.spawn(move || {
+                    let score = JobsClientAdapter::for_agent(logger, api_config, &agent.url)
+                        .and_then(|client| client.machine_score());
+                    (agent.name, score)
+                })
+            })
+            .collect();
+        queries
+            .into_iter()
+            .filter_map(|query| query.join().ok())
+            .collect()
+    });
+    let machines: Vec<(String, MachineScore)> = scores
+        .into_iter()
+        .filter_map(|(name, score)| match score {
+            Ok(score) => Some((name, score)),
+            Err(e) => {
+                logger.log_warn(&format!("Leaving {} out of the ranking: {}", name, e));
+                None
+            }
+        })
+        .collect();
+    let (ranked, subsystems) = rank_machines(&machines);
+    if json {
+        match serde_json::to_string_pretty(&ranked) {
+            Ok(json) => println!("{}", json),
+            Err(e) => logger.log_error(&format!("Failed to encode the ranking: {}", e)),
+        }
+    } else {
+        print!("{}", format_ranking(&ranked, &subsystems));
+    }
+}
@@ -0,0 +1,159 @@
+//! DaemonSet Command
+//!
+//! This module runs `one_for_all daemonset` in a pod of a Kubernetes
+//! DaemonSet: it qualifies the node with a profile, records the verdict on the
+//! node, and keeps serving the API until the pod is stopped.
+
+use common::domain::job::{JobSpec, JobStatus};
+use common::ports::log_port::LoggerPort;
+
+use oneforall_core::adapters::kubernetes_adapter::KubernetesAdapter;
+use oneforall_core::adapters::profile_adapter::ProfileAdapter;
+use oneforall_core::adapters::profile_runner::ProfileRunner;
+use oneforall_core::domain::export::RunArtifacts;
+use oneforall_core::domain::kubernetes::{
+    failure_taint, qualification_labels, NodeIdentity, FAILURE_TAINT_KEY, NODE_NAME_ENV,
+    POD_NAMESPACE_ENV, POD_NAME_ENV,
+};
+use oneforall_core::domain::profile::format_duration;
+use oneforall_core::ports::cluster_port::ClusterPort;
+use oneforall_core::ports::profile_port::ProfilePort;
+
+use crate::commands::profile::check_profile_stressors;
+use crate::commands::CommandContext;
+
+/// Qualifies the node of the pod with a profile and records the verdict on it.
+pub async fn run(ctx: CommandContext, profile: String, label: bool, taint: bool) {
+    let CommandContext {
+        logger,
+        telemetry,
+        jobs,
+        repository,
+        alerts,
+        exporter,
+        tags,
+        ..
+    } = ctx;
+    let identity = match std::env::var(NODE_NAME_ENV) {
+        Ok(node_name) if !node_name.is_empty() => NodeIdentity {
+            node_name,
+            pod_name: std::env::var(POD_NAME_ENV).ok(),
+            namespace: std::env::var(POD_NAMESPACE_ENV).ok(),
+        },
+        _ => {
+            logger.log_error(&format!(
+                "{} is not set; expose spec.nodeName to the container through the \
+                 downward API.",
+                NODE_NAME_ENV
+            ));
+            std::process::exit(1);
+        }
+    };
+    // Check the API is reachable before hours of testing, not after.
+    let cluster = if label || taint {
+        match KubernetesAdapter::in_cluster(logger.clone()) {
+            Ok(cluster) => Some(cluster),
+            Err(e) => {
+                logger.log_error(&format!("Cannot use the Kubernetes API: {}", e));
+                std::process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
+    let profile = match ProfileAdapter::new(logger.clone()).load_profile(&profile) {
+        Ok(profile) => profile,
+        Err(e) => {
+            logger.log_error(&e);
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) = check_profile_stressors(logger.clone(), &profile) {
+        logger.log_error(&e);
+        std::process::exit(1);
+    }
+    logger.log_info(&format!(
+        "Qualifying {} with profile {} ({} steps, {} of timed tests)",
+        identity.describe(),
+        profile.name,
+        profile.steps.len(),
+        format_duration(profile.planned_duration())
+    ));
+
+    let profile_job = jobs.submit_job(JobSpec::new(&format!("profile:{}", profile.name), &[]));
+    let runner = ProfileRunner::new(logger.clone(), telemetry.clone(), jobs.clone());
+    let recording = exporter.record(&telemetry);
+    let report = runner.run(&profile, &profile_job.cancel).await;
+    let rendered = report.render();
+    println!("{}", rendered);
+
+    if let Err(e) = repository.store_run(
+        "profile",
+        &report.started_at,
+        Some(&report.profile),
+        &report,
+    ) {
+        logger.log_warn(&format!("Failed to store profile report: {}", e));
+    }
+    let cancelled = profile_job.cancel.is_cancelled();
+    let (status, message) = if cancelled {
+        (JobStatus::Cancelled, None)
+    } else if report.passed() {
+        (JobStatus::Completed, None)
+    } else {
+        (
+            JobStatus::Failed,
+            Some(format!(
+                "failed steps: {}",
+                report.failed_steps().join(", ")
+            )),
+        )
+    };
+    jobs.finish_job(profile_job.job.id, status, message);
+    if exporter.is_enabled() {
+        let kind = format!("profile-{}", report.profile);
+        let mut artifacts = RunArtifacts::new(&kind, &report.started_at);
+        artifacts.add_result(&report, &tags);
+        artifacts.add_text("report.txt", &rendered);
+        exporter.export(artifacts, recording);
+    }
+    alerts.flush();
+
+    // An interrupted qualification says nothing about the hardware.
+    if cancelled {
+        logger.log_warn("Qualification cancelled; the node is left as it was.");
+        return;
+    }
+    let passed = report.passed();
+    if let Some(cluster) = &cluster {
+        let node = &identity.node_name;
+        if label {
+            let labels =
+                qualification_labels(&profile.name, passed, chrono::Utc::now().timestamp());
+            if let Err(e) = cluster.label_node(node, &labels) {
+                logger.log_error(&format!("Failed to label node {}: {}", node, e));
+            }
+        }
+        if taint {
+            let outcome = if passed {
+                cluster.untaint_node(node, FAILURE_TAINT_KEY)
+            } else {
+                cluster.taint_node(node, &failure_taint(&profile.name))
+            };
+            if let Err(e) = outcome {
+                logger.log_error(&format!(
+                    "Failed to update the taint of node {}: {}",
+                    node, e
+                ));
+            }
+        }
+    }
+
+    // A DaemonSet restarts pods that exit, which would qualify the node over
+    // and over, so the pod stays up serving the API until it is stopped.
+    logger.log_info(&format!(
+        "Qualification of {} {}; idling until the pod is stopped.",
+        identity.describe(),
+        if passed { "passed" } else { "failed" }
+    ));
+}
@@ -0,0 +1,145 @@
+//! Database Commands
+//!
+//! This module runs `one_for_all db` and `one_for_all database-ops`, which
+//! maintain and list the database.
+
+use std::sync::Arc;
+
+use common::ports::log_port::LoggerPort;
+
+use oneforall_core::adapters::repository::Repository;
+use oneforall_core::adapters::retention_monitor::RetentionMonitor;
+use oneforall_core::domain::database::RetentionConfig;
+
+use crate::commands::CommandContext;
+
+/// Applies the retention policy at once, even without a configured one since it still
+/// compacts the database.
+///
+/// # Arguments
+///
+/// * `logger` - Logger implementation for logging messages.
+/// * `repository` - The repository to vacuum.
+/// * `retention` - The `[database.retention]` policy.
+///
+/// # Returns
+///
+/// * `std::io::Result<()>` - Whether the database was vacuumed.
+pub fn vacuum(
+    logger: Arc<dyn LoggerPort>,
+    repository: Repository,
+    retention: RetentionConfig,
+) -> std::io::Result<()> {
+    let mut retention = RetentionMonitor::new(logger.clone(), repository, retention);
+    match retention.vacuum() {
+        Ok(summary) => {
+            println!("{}", summary.render());
+            Ok(())
+        }
+        Err(e) => {
+            logger.log_error(&e);
+            Err(std::io::Error::other(e))
+        }
+    }
+}
+
+/// Brings the results written by older versions to the current schema version for
+/// good, rather than each time they are read.
+///
+/// # Arguments
+///
+/// * `logger` - Logger implementation for logging messages.
+/// * `repository` - The repository whose results are migrated.
+///
+/// # Returns
+///
+/// * `std::io::Result<()>` - Whether the results were migrated.
+pub fn migrate(logger: Arc<dyn LoggerPort>, repository: &Repository) -> std::io::Result<()> {
+    match repository.migrate_runs() {
+        Ok(summary) => {
+            print!("{}", summary.render());
+            Ok(())
+        }
+        Err(e) => {
+            logger.log_error(&e);
+            Err(std::io::Error::other(e))
+        }
+    }
+}
+
+/// Prints every key of the embedded database.
+pub async fn list_keys(ctx: CommandContext) {
+    let CommandContext { logger, .. } = ctx;
+    // Assuming `db_logger` is a reference to an implementation of `LoggerPort`
+    logger.log_info("Database operations functionality not yet implemented.");
+
+    match get_all_keys(logger.clone()) {
+        // Pass a reference, not a clone
+        Ok(_) => println!("Successfully retrieved all keys"),
+        Err(e) => eprintln!("Error retrieving keys: {:?}", e),
+    }
+}
+
+/// Retrieves all keys from the Sled database.
+///
+/// This function attempts to open the Sled database and create an iterator over all key-value pairs.
+/// It logs the process and counts the total number of keys found.
+///
+/// # Arguments
+///
+/// * `logger` - An Arc-wrapped LoggerPort trait object for logging.
+///
+/// # Returns
+///
+/// * `Result<(), sled::Error>` - Returns Ok(()) if successful, or an Err containing the sled::Error if an error occurred.
+pub fn get_all_keys(logger: Arc<dyn LoggerPort>) -> Result<(), sled::Error> {
+    // Log the attempt to open the Sled database.
+    logger.log_info("Attempting to open the Sled database.");
+
+    // Attempt to open the Sled database, gracefully handling errors.
+    let db = match sled::open("OneForAll_database_file.db") {
+        Ok(db) => {
+            // Log the successful opening of the database.
+            logger.log_info("Database opened successfully.");
+            db
+        }
+        Err(e) => {
+            // Log the error and return it if the database fails to open.
+            logger.log_error(&format!("Error opening database: {}", e));
+            return Err(e);
+        }
+    };
+
+    // Log the creation of an iterator over all key-value pairs in the database.
+    logger.log_debug("Creating an iterator over all key-value pairs in the database.");
+
+    // Create an iterator over all key-value pairs in the database.
+    let mut iter = db.iter();
+    let mut key_count = 0;
+
+    // Iterate over all keys.
+    while let Some(result) = iter.next() {
+        match result {
+            Ok((key, _)) => {
+                // Increment key count.
+                key_count += 1;
+
+                // Log each key.
+                logger.log_debug(&format!("Key: {:?}", key));
+            }
+            Err(e) => {
+                // Log the error and return it if an error occurs while iterating over the keys.
+                logger.log_error(&format!("Error iterating over keys: {}", e));
+                return Err(e);
+            }
+        }
+    }
+
+    // Log the total number of keys found.
+    logger.log_info(&format!(
+        "Successfully iterated over all keys. Total keys found: {}",
+        key_count
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+//! Discover Command
+//!
+//! This module runs `one_for_all discover`, which reports the hardware of
+//! the machine, or compares it with the last discovery or a manifest.
+
+use std::path::PathBuf;
+
+use common::ports::log_port::LoggerPort;
+
+use oneforall_core::adapters::device_inventory_adapter::{
+    load_hardware_manifest, DeviceInventoryAdapter,
+};
+use oneforall_core::adapters::hardware_discovery::HardwareDiscovery;
+use oneforall_core::domain::discovery::DiscoveryReport;
+use oneforall_core::domain::hardware_changes::{diff_snapshots, format_changes};
+use oneforall_core::domain::hardware_manifest::format_manifest_checks;
+use oneforall_core::ports::device_inventory_port::DeviceInventoryPort;
+
+use crate::commands::CommandContext;
+
+/// Discovers the hardware, or compares it with the last discovery or a manifest.
+pub async fn run(ctx: CommandContext, bandwidth: bool, diff: bool, expect: Option<PathBuf>) {
+    let CommandContext {
+        logger,
+        repository,
+        cloud_instance,
+        ..
+    } = ctx;
+    // Read the manifest first, so a mistake in it does not wait for the discovery.
+    let manifest = match expect.as_deref().map(load_hardware_manifest).transpose() {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            logger.log_error(&e);
+            std::process::exit(1);
+        }
+    };
+    // Each discovery adapter contributes a section to the report, and the
+    // hardware compared between discoveries is kept in its snapshot.
+    let (report, mut facts) = HardwareDiscovery::new(logger.clone())
+        .with_cloud_instance(cloud_instance.clone())
+        .with_bandwidth(bandwidth)
+        .discover();
+    let snapshot = report.snapshot.clone().unwrap_or_default();
+
+    // `--diff` compares with the inventory stored by the previous discovery,
+    // before this one takes its place.
+    let mut changed = false;
+    if diff {
+        match repository.get_latest_inventory::<DiscoveryReport>() {
+            Ok(Some((
+                collected_at,
+                DiscoveryReport {
+                    snapshot: Some(previous),
+                    ..
+                },
+            ))) => {
+                let changes = diff_snapshots(&previous, &snapshot);
+                print!("{}", format_changes(&changes, &collected_at));
+                changed = !changes.is_empty();
+            }
+            Ok(Some((collected_at, _))) => logger.log_warn(&format!(
+                "The discovery of {} kept no hardware snapshot; this one is the \
+                 baseline of the next --diff.",
+                collected_at
+            )),
+            Ok(None) => logger.log_warn(
+                "No earlier discovery to compare with; this one is the baseline of \
+                 the next --diff.",
+            ),
+            Err(e) => logger.log_warn(&e),
+        }
+    } else {
+        println!("{}", report.render());
+    }
+
+    // `--expect` checks the machine against the hardware it was ordered with.
+    let mut mismatched = false;
+    if let (Some(manifest), Some(path)) = (&manifest, &expect) {
+        let inventory = DeviceInventoryAdapter::new(logger.clone());
+        match inventory.read_memory_total() {
+            Ok(total) => facts.kernel_memory_bytes = total,
+            Err(e) => logger.log_warn(&e),
+        }
+        match inventory.list_disks() {
+            Ok(disks) => facts.disks = disks,
+            Err(e) => logger.log_warn(&format!("Skipping disks: {}", e)),
+        }
+        match inventory.list_nics() {
+            Ok(nics) => facts.nics = nics,
+            Err(e) => logger.log_warn(&format!("Skipping network interfaces: {}", e)),
+        }
+        let checks = manifest.check(&facts);
+        print!(
+            "{}",
+            format_manifest_checks(&path.display().to_string(), &checks)
+        );
+        mismatched = checks.iter().any(|check| !check.passed);
+    }
+    // Keep the inventory, so changes to the hardware of a machine can be traced.
+    if let Err(e) = repository.store_inventory(&chrono::Utc::now(), &report) {
+        logger.log_warn(&format!("Failed to store the inventory: {}", e));
+    }
+    logger.log_info("Hardware discovery completed.");
+    // Like diff(1), scripts checking a machine after maintenance rely on the status.
+    if changed || mismatched {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,111 @@
+//! Endurance Command
+//!
+//! This module runs `one_for_all endurance`, which writes and verifies a
+//! volume of data to a drive and estimates its write amplification.
+
+use std::path::Path;
+
+use common::domain::job::Resource;
+use common::ports::log_port::LoggerPort;
+
+use oneforall_core::adapters::endurance_runner::EnduranceRunner;
+use oneforall_core::domain::endurance::{format_volume, parse_size};
+use oneforall_core::domain::export::RunArtifacts;
+use oneforall_core::domain::plan::{DiskWrites, ExecutionPlan, PlannedStep};
+
+use crate::commands::CommandContext;
+
+/// Writes and verifies a volume of data to a drive and exits with the verdict.
+pub async fn run(
+    ctx: CommandContext,
+    path: String,
+    volume: String,
+    file_size: String,
+    device: Option<String>,
+) {
+    let CommandContext {
+        logger,
+        telemetry,
+        jobs,
+        repository,
+        alerts,
+        exporter,
+        tags,
+        ..
+    } = ctx;
+    let sizes = parse_size(&volume)
+        .map_err(|e| format!("Invalid --volume value: {}", e))
+        .and_then(|volume| {
+            parse_size(&file_size)
+                .map(|file_size| (volume, file_size))
+                .map_err(|e| format!("Invalid --file-size value: {}", e))
+        });
+    let (volume, file_size) = match sizes {
+        Ok(sizes) => sizes,
+        Err(e) => {
+            logger.log_error(&e);
+            std::process::exit(1);
+        }
+    };
+
+    let runner = EnduranceRunner::new(logger.clone(), telemetry.clone(), jobs.clone());
+    let recording = exporter.record(&telemetry);
+    let report = runner
+        .run(Path::new(&path), volume, file_size, device)
+        .await;
+    let rendered = report.render();
+    println!("{}", rendered);
+    if let Err(e) = repository.store_run("endurance", &report.started_at, None, &report) {
+        logger.log_warn(&format!("Failed to store endurance report: {}", e));
+    }
+    if exporter.is_enabled() {
+        let mut artifacts = RunArtifacts::new("endurance", &report.started_at);
+        artifacts.add_result(&report, &tags);
+        artifacts.add_text("report.txt", &rendered);
+        exporter.export(artifacts, recording);
+    }
+    alerts.flush();
+    std::process::exit(if report.passed() { 0 } else { 1 });
+}
+
+/// Plans an endurance test for `--dry-run`.
+///
+/// # Arguments
+///
+/// * `path` - The directory the test files are written to.
+/// * `volume` - The volume of data to write, e.g. `500G`.
+/// * `file_size` - The size of each test file, e.g. `1G`.
+/// * `device` - The drive to read SMART wear indicators from, or `None` for the drive
+///   holding the path.
+///
+/// # Returns
+///
+/// * `Result<ExecutionPlan, String>` - The plan, or why the sizes are invalid.
+pub fn plan(
+    path: &str,
+    volume: &str,
+    file_size: &str,
+    device: Option<&str>,
+) -> Result<ExecutionPlan, String> {
+    let volume = parse_size(volume).map_err(|e| format!("Invalid --volume value: {}", e))?;
+    let file_size =
+        parse_size(file_size).map_err(|e| format!("Invalid --file-size value: {}", e))?;
+    let mut plan = ExecutionPlan::new("endurance");
+    plan.steps.push(PlannedStep {
+        targets: vec![
+            path.to_string(),
+            device.map_or("the drive holding the path".to_string(), str::to_string),
+        ],
+        disk_writes: DiskWrites::Volume(volume),
+        ..PlannedStep::new(
+            "endurance",
+            format!(
+                "write, verify and delete {} in {} files",
+                format_volume(volume),
+                format_volume(file_size)
+            ),
+            &[Resource::Disk],
+        )
+    });
+    Ok(plan)
+}
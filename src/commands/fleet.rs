@@ -0,0 +1,120 @@
+//! Fleet Command
+//!
+//! This module runs `one_for_all fleet`, which lists the agents of the fleet
+//! with whether each can be reached, or measures the network between them.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use common::domain::api_config::ApiConfig;
+use common::ports::log_port::LoggerPort;
+
+use oneforall_core::adapters::jobs_client_adapter::JobsClientAdapter;
+use oneforall_core::adapters::mdns_adapter::discover_agents;
+use oneforall_core::domain::fleet::FleetConfig;
+use oneforall_core::domain::fleet::{format_fleet_table, Agent, FleetMember};
+use oneforall_core::domain::network_matrix::{MatrixCell, NetworkMatrix};
+
+/// Lists the agents of the fleet, probed in parallel through their REST APIs, or with
+/// `matrix` measures the network paths between every pair of them.
+///
+/// # Arguments
+///
+/// * `logger` - Logger implementation for logging messages.
+/// * `api_config` - The token and TLS settings the agents are called with.
+/// * `fleet_config` - The agents listed in the `[fleet]` section.
+/// * `json` - Whether to print JSON rather than a table.
+/// * `matrix` - Whether to measure the network matrix.
+/// * `html` - The HTML report of the matrix to write, if any.
+///
+/// # Returns
+///
+/// * `std::io::Result<()>` - Always `Ok`; agents that cannot be reached are listed as such.
+pub fn run(
+    logger: Arc<dyn LoggerPort>,
+    api_config: &ApiConfig,
+    fleet_config: &FleetConfig,
+    json: bool,
+    matrix: bool,
+    html: Option<PathBuf>,
+) -> std::io::Result<()> {
+    let agents = discover_agents(logger.clone(), fleet_config);
+    let members: Vec<FleetMember> = std::thread::scope(|scope| {
+        let probes: Vec<_> = agents
+            .into_iter()
+            .map(|agent| {
+                let logger = logger.clone();
+                scope.spawn(move || {
+                    let jobs = JobsClientAdapter::for_agent(logger, api_config, &agent.url)
+                        .and_then(|client| client.list_jobs());
+                    FleetMember { agent, jobs }
+                })
+            })
+            .collect();
+        probes
+            .into_iter()
+            .filter_map(|probe| probe.join().ok())
+            .collect()
+    });
+    if !matrix {
+        if json {
+            match serde_json::to_string_pretty(&members) {
+                Ok(json) => println!("{}", json),
+                Err(e) => logger.log_error(&format!("Failed to encode the fleet: {}", e)),
+            }
+        } else {
+            print!("{}", format_fleet_table(&members));
+        }
+        return Ok(());
+    }
+
+    // Each agent measures to every other through its own REST API. The pairs are
+    // measured one at a time, since concurrent transfers would share the links.
+    let agents: Vec<&Agent> = members
+        .iter()
+        .filter(|member| {
+            if let Err(e) = &member.jobs {
+                logger.log_warn(&format!(
+                    "Leaving {} out of the matrix: {}",
+                    member.agent.name, e
+                ));
+            }
+            member.jobs.is_ok()
+        })
+        .map(|member| &member.agent)
+        .collect();
+    let mut network_matrix = NetworkMatrix {
+        agents: agents.iter().map(|agent| agent.name.clone()).collect(),
+        cells: Vec::new(),
+    };
+    for from in &agents {
+        let client = JobsClientAdapter::for_agent(logger.clone(), api_config, &from.url);
+        for to in agents.iter().filter(|to| to.url != from.url) {
+            logger.log_info(&format!("Measuring {} -> {}", from.name, to.name));
+            network_matrix.cells.push(MatrixCell {
+                from: from.name.clone(),
+                to: to.name.clone(),
+                measurement: client
+                    .as_ref()
+                    .map_err(String::clone)
+                    .and_then(|client| client.probe_link(&to.url)),
+            });
+        }
+    }
+
+    if json {
+        match serde_json::to_string_pretty(&network_matrix) {
+            Ok(json) => println!("{}", json),
+            Err(e) => logger.log_error(&format!("Failed to encode the matrix: {}", e)),
+        }
+    } else {
+        print!("{}", network_matrix.render());
+    }
+    if let Some(html) = html {
+        match std::fs::write(&html, network_matrix.render_html()) {
+            Ok(()) => logger.log_info(&format!("Wrote the matrix report to {}", html.display())),
+            Err(e) => logger.log_error(&format!("Failed to write {}: {}", html.display(), e)),
+        }
+    }
+    Ok(())
+}
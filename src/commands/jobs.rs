@@ -0,0 +1,47 @@
+//! Jobs Command
+//!
+//! This module runs `one_for_all jobs`, which lists the jobs of an instance
+//! that is already running, or cancels one, through its REST API.
+
+use std::sync::Arc;
+
+use common::domain::api_config::ApiConfig;
+use common::domain::job::format_job_table;
+use common::ports::log_port::LoggerPort;
+
+use oneforall_core::adapters::jobs_client_adapter::JobsClientAdapter;
+
+/// Lists the jobs of the running instance, or requests the cancellation of one.
+///
+/// # Arguments
+///
+/// * `logger` - Logger implementation for logging messages.
+/// * `api_config` - The address, token and TLS settings of the REST API.
+/// * `cancel` - The job to cancel, if any.
+///
+/// # Returns
+///
+/// * `std::io::Result<()>` - Whether the instance answered.
+pub fn run(
+    logger: Arc<dyn LoggerPort>,
+    api_config: &ApiConfig,
+    cancel: Option<u64>,
+) -> std::io::Result<()> {
+    let outcome =
+        JobsClientAdapter::new(logger.clone(), api_config).and_then(|client| match cancel {
+            Some(id) => client
+                .cancel_job(id)
+                .map(|job| format!("Cancellation of job {} ({}) requested.\n", job.id, job.kind)),
+            None => client.list_jobs().map(|jobs| format_job_table(&jobs)),
+        });
+    match outcome {
+        Ok(output) => {
+            print!("{}", output);
+            Ok(())
+        }
+        Err(e) => {
+            logger.log_error(&e);
+            Err(std::io::Error::other(e))
+        }
+    }
+}
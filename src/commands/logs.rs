@@ -0,0 +1,60 @@
+//! Logs Command
+//!
+//! This module runs `one_for_all logs`, which prints the log messages of a
+//! running instance, on this machine or another, through its REST API.
+
+use std::sync::Arc;
+
+use common::domain::api_config::ApiConfig;
+use common::domain::log_record::parse_log_level;
+use common::ports::log_port::LoggerPort;
+
+use oneforall_core::adapters::jobs_client_adapter::JobsClientAdapter;
+
+/// Prints the recent log messages of a running instance, and with `follow` those
+/// logged from then on.
+///
+/// # Arguments
+///
+/// * `logger` - Logger implementation for logging messages.
+/// * `api_config` - The address, token and TLS settings of the REST API.
+/// * `follow` - Whether to keep printing the messages as they are logged.
+/// * `lines` - How many recent messages to print.
+/// * `level` - The least severe level to print.
+/// * `agent` - The URL of the agent to read from, or `None` for this machine.
+///
+/// # Returns
+///
+/// * `std::io::Result<()>` - Whether the instance answered.
+pub fn run(
+    logger: Arc<dyn LoggerPort>,
+    api_config: &ApiConfig,
+    follow: bool,
+    lines: usize,
+    level: &str,
+    agent: Option<&str>,
+) -> std::io::Result<()> {
+    let outcome = parse_log_level(level)
+        .and_then(|_| match agent {
+            Some(url) => JobsClientAdapter::for_agent(logger.clone(), api_config, url),
+            None => JobsClientAdapter::new(logger.clone(), api_config),
+        })
+        .and_then(|client| {
+            if follow {
+                client.follow_logs(level, lines, |record| println!("{}", record.render_line()))
+            } else {
+                client.recent_logs(level, lines).map(|records| {
+                    for record in records {
+                        println!("{}", record.render_line());
+                    }
+                })
+            }
+        });
+    match outcome {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            logger.log_error(&e);
+            Err(std::io::Error::other(e))
+        }
+    }
+}
@@ -0,0 +1,311 @@
+//! Commands
+//!
+//! This module runs the subcommands of `one_for_all`, one module per command.
+//! `main` parses the command line, opens the services a command needs and hands
+//! them over in a `CommandContext`.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use tokio::signal;
+
+use common::adapters::log_adapter::FernLogger;
+use common::domain::footprint::FootprintConfig;
+use common::ports::job_port::JobPort;
+use common::ports::log_port::LoggerPort;
+use common::ports::telemetry_port::TelemetryPort;
+
+use oneforall_core::adapters::alert_dispatcher::AlertDispatcher;
+use oneforall_core::adapters::footprint_governor::FootprintGovernor;
+use oneforall_core::adapters::health_monitor::HealthMonitor;
+use oneforall_core::adapters::perf_flamegraph_adapter::PerfFlamegraphAdapter;
+use oneforall_core::adapters::plugin_registry::PluginRegistry;
+use oneforall_core::adapters::repository::Repository;
+use oneforall_core::adapters::result_exporter::ResultExporter;
+use oneforall_core::adapters::sample_writer::SampleWriter;
+use oneforall_core::adapters::shutdown_coordinator::ShutdownCoordinator;
+use oneforall_core::domain::cloud::InstanceMetadata;
+use oneforall_core::domain::plan::ExecutionPlan;
+use oneforall_core::domain::tags::Tags;
+use oneforall_core::ports::flamegraph_port::FlamegraphPort;
+
+use crate::cli::Commands;
+
+pub mod archive;
+pub mod benchmark;
+pub mod compare;
+pub mod daemonset;
+pub mod db;
+pub mod discover;
+pub mod endurance;
+pub mod fleet;
+pub mod jobs;
+pub mod logs;
+pub mod overwatch;
+pub mod plugin;
+pub mod power_cycle;
+pub mod profile;
+pub mod stress;
+pub mod trend;
+
+/// The services a command runs with, opened by `main` before it starts.
+pub struct CommandContext {
+    /// Logs the progress of the command.
+    pub logger: Arc<FernLogger>,
+
+    /// Receives the live readings and progress served by the REST API.
+    pub telemetry: Arc<dyn TelemetryPort>,
+
+    /// Queues the workloads, so those competing for a resource run one after another.
+    pub jobs: Arc<dyn JobPort>,
+
+    /// Stores the results, readings and inventories.
+    pub repository: Repository,
+
+    /// Writes the readings of the collectors in batches.
+    pub samples: SampleWriter,
+
+    /// Sends the alerts of the `[alerts]` section.
+    pub alerts: Arc<AlertDispatcher>,
+
+    /// Uploads the results of finished runs, with a bucket in the `[export.s3]` section.
+    pub exporter: ResultExporter,
+
+    /// Keeps the collectors to the budget of the `[footprint]` section.
+    pub footprint: Arc<FootprintGovernor>,
+
+    /// The budget of the `[footprint]` section.
+    pub footprint_config: FootprintConfig,
+
+    /// Follows the collectors for the readiness probe.
+    pub health: Arc<HealthMonitor>,
+
+    /// The plugins loaded from the `[plugins]` section.
+    pub plugin_registry: Arc<PluginRegistry>,
+
+    /// The cloud instance the machine is, if any.
+    pub cloud_instance: Option<InstanceMetadata>,
+
+    /// The tags stored with every result.
+    pub tags: Tags,
+
+    /// Stops the application, e.g. once the terminal interface closes.
+    pub shutdown: Arc<ShutdownCoordinator>,
+
+    /// The SVG file given with `--flamegraph`.
+    pub flamegraph: Option<PathBuf>,
+
+    /// The title of the flamegraph: the command line, which names the benchmark or plugin.
+    pub flamegraph_title: String,
+}
+
+/// Runs a command with the services opened for it.
+///
+/// # Arguments
+///
+/// * `ctx` - The services the command runs with.
+/// * `command` - The command to run.
+pub async fn run(ctx: CommandContext, command: Commands) {
+    match command {
+        Commands::Benchmark {
+            warmup,
+            repetitions,
+            kind,
+        } => benchmark::run(ctx, warmup, repetitions, kind).await,
+        Commands::Stress(args) => stress::run(ctx, args).await,
+        Commands::Endurance {
+            path,
+            volume,
+            file_size,
+            device,
+        } => endurance::run(ctx, path, volume, file_size, device).await,
+        command @ (Commands::Run { .. } | Commands::Resume { .. }) => {
+            profile::run(ctx, command).await
+        }
+        Commands::Daemonset {
+            profile,
+            label,
+            taint,
+        } => daemonset::run(ctx, profile, label, taint).await,
+        Commands::Discover {
+            bandwidth,
+            diff,
+            expect,
+        } => discover::run(ctx, bandwidth, diff, expect).await,
+        Commands::Overwatch {
+            tui,
+            interval,
+            burst_interval,
+        } => overwatch::run(ctx, tui, interval, burst_interval).await,
+        Commands::DatabaseOps => db::list_keys(ctx).await,
+        Commands::Compare { json, .. } => compare::score(ctx, json).await,
+        Commands::Trend {
+            metric,
+            last,
+            benchmark,
+            json,
+        } => trend::run(ctx, metric, last, benchmark, json).await,
+        Commands::Plugin(args) => plugin::run(ctx, args).await,
+        // Handled before the web server is started, and for `jobs`, `logs`, `fleet`,
+        // `power-cycle`, `compare --fleet`, `plugins`, `completions` and `man` before the
+        // database is opened.
+        Commands::Jobs { .. }
+        | Commands::Logs { .. }
+        | Commands::Fleet { .. }
+        | Commands::PowerCycle(_)
+        | Commands::Db { .. }
+        | Commands::Archive { .. }
+        | Commands::Plugins
+        | Commands::Completions { .. }
+        | Commands::Man { .. } => {}
+    }
+}
+
+/// Plans what a command would execute for `--dry-run`, without running, queueing
+/// or storing anything.
+///
+/// # Arguments
+///
+/// * `logger` - An Arc-wrapped LoggerPort trait object for logging.
+/// * `command` - The command to plan.
+/// * `config` - The configuration file, for the `[plugins]` a plugin is loaded from.
+///
+/// # Returns
+///
+/// * `Result<ExecutionPlan, String>` - The plan, or why the command cannot be planned.
+pub fn plan(
+    logger: Arc<dyn LoggerPort>,
+    command: &Commands,
+    config: &Path,
+) -> Result<ExecutionPlan, String> {
+    match command {
+        Commands::Benchmark {
+            warmup,
+            repetitions,
+            kind,
+        } => benchmark::plan(logger, *warmup, *repetitions, kind),
+        Commands::Stress(args) => stress::plan(logger, args),
+        Commands::Endurance {
+            path,
+            volume,
+            file_size,
+            device,
+        } => endurance::plan(path, volume, file_size, device.as_deref()),
+        Commands::Run {
+            profile: Some(name),
+        }
+        | Commands::Daemonset { profile: name, .. } => profile::plan(logger, name),
+        Commands::Plugin(args) => plugin::plan(logger, config, args),
+        Commands::PowerCycle(args) => power_cycle::plan(args),
+        Commands::Run { profile: None } => {
+            Err("--dry-run needs the profile to plan, e.g. `run --profile burn-in-24h`".to_string())
+        }
+        _ => Err(
+            "--dry-run plans the benchmark, stress, endurance, power-cycle, run, daemonset and plugin commands; this one starts no workload"
+                .to_string(),
+        ),
+    }
+}
+
+/// Returns `true` for the commands that measure the machine, whose results and readings
+/// are correlated with those of other machines by time.
+pub fn measures_machine(command: &Commands) -> bool {
+    matches!(
+        command,
+        Commands::Benchmark { .. }
+            | Commands::Stress(_)
+            | Commands::Endurance { .. }
+            | Commands::Overwatch { .. }
+            | Commands::Run { .. }
+            | Commands::Resume { .. }
+            | Commands::Daemonset { .. }
+            | Commands::Plugin(_)
+    )
+}
+
+/// Starts sampling stacks for `--flamegraph`, if it was given.
+///
+/// # Arguments
+///
+/// * `logger` - Logger implementation for logging messages.
+/// * `output` - The SVG file given with `--flamegraph`.
+///
+/// # Returns
+///
+/// * `Option<PerfFlamegraphAdapter>` - The sampling profiler, or `None` if no flamegraph was
+///   asked for or sampling could not start, which is logged.
+pub fn start_flamegraph(
+    logger: Arc<dyn LoggerPort>,
+    output: Option<&Path>,
+) -> Option<PerfFlamegraphAdapter> {
+    output?;
+    let profiler = PerfFlamegraphAdapter::new(logger.clone());
+    match profiler.start() {
+        Ok(()) => Some(profiler),
+        Err(e) => {
+            logger.log_warn(&format!("Not drawing a flamegraph: {}", e));
+            None
+        }
+    }
+}
+
+/// Stops sampling stacks and writes the flamegraph of the run.
+///
+/// # Arguments
+///
+/// * `logger` - Logger implementation for logging messages.
+/// * `profiler` - The profiler returned by `start_flamegraph`.
+/// * `title` - The title of the flamegraph.
+/// * `output` - The SVG file given with `--flamegraph`.
+pub fn finish_flamegraph(
+    logger: Arc<dyn LoggerPort>,
+    profiler: Option<PerfFlamegraphAdapter>,
+    title: &str,
+    output: Option<&Path>,
+) {
+    let (Some(profiler), Some(output)) = (profiler, output) else {
+        return;
+    };
+    match profiler.finish(title, output) {
+        Ok(samples) => logger.log_info(&format!(
+            "Wrote the flamegraph of {} stack samples to {}",
+            samples,
+            output.display()
+        )),
+        Err(e) => logger.log_warn(&format!("Failed to draw the flamegraph: {}", e)),
+    }
+}
+
+/// Waits for a request to shut down: Ctrl+C, or SIGTERM from a service manager
+/// such as systemd.
+///
+/// # Returns
+///
+/// * `&'static str` - The name of the signal received.
+#[cfg(unix)]
+pub async fn shutdown_signal() -> &'static str {
+    use tokio::signal::unix::{signal as unix_signal, SignalKind};
+
+    let mut terminate = match unix_signal(SignalKind::terminate()) {
+        Ok(terminate) => terminate,
+        Err(_) => {
+            signal::ctrl_c().await.expect("Failed to listen for Ctrl+C");
+            return "Ctrl+C";
+        }
+    };
+    tokio::select! {
+        _ = signal::ctrl_c() => "Ctrl+C",
+        _ = terminate.recv() => "SIGTERM",
+    }
+}
+
+/// Waits for Ctrl+C, the only shutdown request outside Unix.
+///
+/// # Returns
+///
+/// * `&'static str` - The name of the signal received.
+#[cfg(not(unix))]
+pub async fn shutdown_signal() -> &'static str {
+    signal::ctrl_c().await.expect("Failed to listen for Ctrl+C");
+    "Ctrl+C"
+}
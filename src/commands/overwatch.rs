@@ -0,0 +1,232 @@
+//! Overwatch Command
+//!
+//! This module runs `one_for_all overwatch`: the collectors of power,
+//! processes, cgroups, microbursts and steal time, with the terminal interface when
+//! asked for, for as long as the application runs.
+
+use std::sync::Arc;
+
+use common::ports::footprint_port::FootprintPort;
+use common::ports::log_port::LoggerPort;
+
+use oneforall_core::adapters::burst_sampler::BurstSampler;
+use oneforall_core::adapters::footprint_governor::COLLECTOR_THREAD_PREFIX;
+use oneforall_core::adapters::metric_sink::MetricSink;
+use oneforall_core::adapters::power_adapter::{PowerAdapter, PowerMeter};
+use oneforall_core::adapters::ps_command_adapter::PsAdapter;
+use oneforall_core::adapters::system_stats_adapter::SystemStatsAdapter;
+use oneforall_core::adapters::thermal_adapter::ThermalAdapter;
+use oneforall_core::adapters::tui_adapter::OverwatchTui;
+use oneforall_core::adapters::virtualization_adapter::VirtualizationAdapter;
+use oneforall_core::domain::microburst::{BURSTS_COLLECTOR, BURST_FRAME, MIN_BURST_INTERVAL};
+use oneforall_core::domain::profile::{format_duration, parse_duration};
+use oneforall_core::domain::system_stats::core_usage_between;
+use oneforall_core::domain::virtualization::{StealSample, STEAL_COLLECTOR, STEAL_WARNING_PERCENT};
+use oneforall_core::ports::ps_command_port::PsCommandPort;
+use oneforall_core::ports::system_stats_port::SystemStatsPort;
+use oneforall_core::ports::thermal_port::ThermalPort;
+use oneforall_core::ports::virtualization_port::VirtualizationPort;
+
+use crate::commands::CommandContext;
+
+/// Starts the collectors and, with `--tui`, the terminal interface.
+pub async fn run(ctx: CommandContext, tui: bool, interval: String, burst_interval: Option<String>) {
+    let CommandContext {
+        logger,
+        telemetry,
+        samples,
+        footprint,
+        footprint_config,
+        health,
+        shutdown,
+        ..
+    } = ctx;
+    let interval = match parse_duration(&interval) {
+        Ok(interval) if !interval.is_zero() => interval,
+        Ok(_) => {
+            logger.log_error("Invalid --interval value: must be longer than zero");
+            return;
+        }
+        Err(e) => {
+            logger.log_error(&format!("Invalid --interval value: {}", e));
+            return;
+        }
+    };
+    let burst_interval = match burst_interval.as_deref().map(parse_duration) {
+        Some(Ok(burst)) if burst < MIN_BURST_INTERVAL => {
+            logger.log_error(&format!(
+                "Invalid --burst-interval value: must be at least {}",
+                format_duration(MIN_BURST_INTERVAL)
+            ));
+            return;
+        }
+        Some(Err(e)) => {
+            logger.log_error(&format!("Invalid --burst-interval value: {}", e));
+            return;
+        }
+        Some(Ok(burst)) => Some(burst),
+        None => None,
+    };
+    logger.log_info("System overwatch functionality started.");
+
+    // Name the collector the CPU statistics are stored under
+    let collector = "processes";
+
+    // Initialize the PsAdapter for process monitoring and CPU usage analysis, measuring
+    // CPU usage over the same interval the readings are taken at.
+    let ps_adapter = Arc::new(
+        PsAdapter::new(logger.clone(), samples.clone())
+            .with_sample_window(interval)
+            .with_footprint(footprint.clone())
+            .with_telemetry(telemetry.clone()),
+    ) as Arc<dyn PsCommandPort>;
+    let process_tree = ps_adapter.clone();
+
+    // Spawn a new thread to run the process monitoring task
+    // This allows the Overwatch functionality to operate in the background
+    // without blocking the main async executor. Collector threads are named
+    // so the footprint governor can tell their CPU time from a benchmark's.
+    let collector_thread = |name: &str| {
+        std::thread::Builder::new().name(format!("{}{}", COLLECTOR_THREAD_PREFIX, name))
+    };
+    match collector_thread(collector).spawn(move || {
+        ps_adapter.collect_cpu_statistics(collector, interval);
+    }) {
+        Ok(_) => health.watch_collector(collector, interval),
+        Err(e) => logger.log_error(&format!("Failed to start the process collector: {}", e)),
+    }
+
+    // Publish power readings for the web console and keep them in the database
+    // alongside the CPU statistics.
+    let power_logger = logger.clone();
+    let power_samples = samples.clone();
+    let power_telemetry = telemetry.clone();
+    let power_footprint = footprint.clone();
+    let power_shutdown = shutdown.token();
+    if let Err(e) = collector_thread("power").spawn(move || {
+        let mut power_meter = PowerMeter::new(Box::new(PowerAdapter::new(power_logger.clone())));
+        while !power_shutdown.is_cancelled() {
+            if let Some(sample) = power_meter.sample() {
+                if let Err(e) = power_samples.record("power", chrono::Utc::now(), &sample) {
+                    power_logger.log_warn(&format!("Failed to store power reading: {}", e));
+                }
+                power_telemetry.publish_power(sample);
+            }
+            std::thread::sleep(power_footprint.pace(interval));
+        }
+    }) {
+        logger.log_error(&format!("Failed to start the power collector: {}", e));
+    }
+
+    // Publish the temperatures and fan speeds to the metric registry, for the
+    // metric sinks and Prometheus.
+    let thermal_telemetry = telemetry.clone();
+    let thermal_footprint = footprint.clone();
+    let thermal_shutdown = shutdown.token();
+    let thermal_logger = logger.clone();
+    if let Err(e) = collector_thread("thermal").spawn(move || {
+        let thermal = ThermalAdapter::new(thermal_logger);
+        while !thermal_shutdown.is_cancelled() {
+            if let Ok(sample) = thermal.sample_thermals() {
+                thermal_telemetry
+                    .publish_metrics(sample.metrics(&chrono::Local::now().to_rfc3339()));
+            }
+            std::thread::sleep(thermal_footprint.pace(interval));
+        }
+    }) {
+        logger.log_error(&format!("Failed to start the thermal collector: {}", e));
+    }
+
+    // With `--burst-interval`, CPU utilization is also sampled every few dozen
+    // milliseconds, for the stalls and microbursts the collectors above miss.
+    if let Some(burst_interval) = burst_interval {
+        let sampler = BurstSampler::new(
+            logger.clone(),
+            Arc::new(SystemStatsAdapter::new(logger.clone())),
+            samples.clone(),
+            burst_interval,
+        )
+        .with_telemetry(telemetry.clone());
+        let burst_shutdown = shutdown.token();
+        match collector_thread(BURSTS_COLLECTOR).spawn(move || sampler.run(burst_shutdown)) {
+            Ok(_) => health.watch_collector(BURSTS_COLLECTOR, BURST_FRAME),
+            Err(e) => logger.log_error(&format!("Failed to start the burst sampler: {}", e)),
+        }
+    }
+
+    // Every metric published meanwhile is stored under the `metrics` collector.
+    MetricSink::database(
+        logger.clone(),
+        &telemetry,
+        samples.clone(),
+        &footprint_config,
+    );
+
+    // On a virtual machine, keep the CPU time stolen by the hypervisor alongside
+    // the other readings, since it skews every measurement taken meanwhile.
+    match VirtualizationAdapter::new(logger.clone()).detect_virtualization() {
+        Ok(info) if info.is_virtual() => {
+            let steal_logger = logger.clone();
+            let steal_samples = samples.clone();
+            let steal_footprint = footprint.clone();
+            let steal_shutdown = shutdown.token();
+            let spawned = collector_thread(STEAL_COLLECTOR).spawn(move || {
+                let stats = SystemStatsAdapter::new(steal_logger.clone());
+                let mut previous = stats.read_cpu_times().unwrap_or_default();
+                while !steal_shutdown.is_cancelled() {
+                    std::thread::sleep(steal_footprint.pace(interval));
+                    let Ok(times) = stats.read_cpu_times() else {
+                        continue;
+                    };
+                    let cores = core_usage_between(&previous, &times);
+                    previous = times;
+                    let Some(sample) = StealSample::from_cores(&cores) else {
+                        continue;
+                    };
+                    if sample.percent >= STEAL_WARNING_PERCENT {
+                        steal_logger.log_warn(&format!(
+                            "The hypervisor is stealing {:.1}% of CPU time (up to {:.1}% \
+                             on CPU {}); measurements are skewed by other guests.",
+                            sample.percent, sample.worst_percent, sample.worst_cpu
+                        ));
+                    }
+                    if let Err(e) =
+                        steal_samples.record(STEAL_COLLECTOR, chrono::Utc::now(), &sample)
+                    {
+                        steal_logger.log_warn(&format!("Failed to store steal reading: {}", e));
+                    }
+                }
+            });
+            match spawned {
+                Ok(_) => health.watch_collector(STEAL_COLLECTOR, interval),
+                Err(e) => logger.log_warn(&format!("Not monitoring steal time: {}", e)),
+            }
+        }
+        Ok(_) => {}
+        Err(e) => logger.log_warn(&format!("Not monitoring steal time: {}", e)),
+    }
+
+    logger.log_info("Monitoring CPU usage and top processes.");
+
+    // The terminal interface takes over the screen, so log messages are only
+    // written to the log files while it runs. Quitting it ends the application.
+    if tui {
+        common::adapters::log_adapter::set_console_output(false);
+        let tui_logger = logger.clone();
+        let outcome = tokio::task::spawn_blocking(move || {
+            OverwatchTui::new(tui_logger, telemetry, process_tree).run()
+        })
+        .await;
+        common::adapters::log_adapter::set_console_output(true);
+        if let Err(e) = samples.flush() {
+            logger.log_warn(&e);
+        }
+
+        match outcome {
+            Ok(Ok(())) => logger.log_info("Overwatch terminal interface closed."),
+            Ok(Err(e)) => logger.log_error(&format!("Overwatch terminal interface failed: {}", e)),
+            Err(e) => logger.log_error(&format!("Overwatch terminal interface panicked: {}", e)),
+        }
+        shutdown.request("the terminal interface closed");
+    }
+}
@@ -0,0 +1,151 @@
+//! Plugin Command
+//!
+//! This module runs a benchmark or stress test plugin by name as a job of
+//! its own.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use common::domain::job::{JobSpec, JobStatus};
+use common::ports::log_port::LoggerPort;
+
+use oneforall_core::adapters::benchmark_runner::BenchmarkRunner;
+use oneforall_core::adapters::plugin_registry::{load_plugin_config, PluginRegistry};
+use oneforall_core::adapters::run_monitor::{RunMonitor, DEFAULT_SAMPLE_INTERVAL};
+use oneforall_core::domain::plan::{DiskWrites, ExecutionPlan, PlannedStep};
+use oneforall_core::domain::statistics::RepetitionPlan;
+
+use crate::commands::{finish_flamegraph, start_flamegraph, CommandContext};
+
+/// Runs a plugin as a job and stores its result.
+pub async fn run(ctx: CommandContext, args: Vec<String>) {
+    let CommandContext {
+        logger,
+        telemetry,
+        jobs,
+        repository,
+        plugin_registry,
+        flamegraph_title,
+        flamegraph,
+        ..
+    } = ctx;
+    let Some(plugin) = plugin_registry.get(&args[0]) else {
+        logger.log_error(&format!(
+            "Unknown command '{}'; no plugin of that name is loaded",
+            args[0]
+        ));
+        return;
+    };
+    let name = plugin.name().to_string();
+    logger.log_info(&format!("Running plugin {}", name));
+
+    // A plugin waits for the jobs using the resources it declares.
+    let mut ticket = jobs.submit_job(JobSpec::new(
+        &format!("plugin:{}", name),
+        plugin.resources(),
+    ));
+    let job_id = ticket.job.id;
+    if ticket.job.status == JobStatus::Queued {
+        logger.log_info(&format!(
+            "Plugin {} job {} queued behind a conflicting job.",
+            name, job_id
+        ));
+    }
+    if !ticket.admitted().await {
+        logger.log_warn(&format!(
+            "Plugin {} job {} cancelled while queued.",
+            name, job_id
+        ));
+        return;
+    }
+
+    let runner = BenchmarkRunner::new(
+        logger.clone(),
+        RepetitionPlan {
+            warmup: 0,
+            repetitions: 1,
+        },
+    );
+    let profiler = start_flamegraph(logger.clone(), flamegraph.as_deref());
+    let run_monitor = RunMonitor::start(logger.clone(), telemetry.clone(), DEFAULT_SAMPLE_INTERVAL);
+    let outcome = runner.run(
+        &name,
+        || plugin.run(&args[1..], &ticket.cancel),
+        |output| output.measurements.clone(),
+    );
+    let run_telemetry = run_monitor.finish();
+    finish_flamegraph(
+        logger.clone(),
+        profiler,
+        &flamegraph_title,
+        flamegraph.as_deref(),
+    );
+
+    match outcome {
+        Ok((output, record)) => {
+            print!("{}", output.report);
+            if let Err(e) = repository.store_run(
+                "benchmark",
+                &record.started_at,
+                Some(&record.benchmark),
+                &record,
+            ) {
+                logger.log_warn(&format!("Failed to store the benchmark result: {}", e));
+            }
+            println!("{}", run_telemetry.report);
+            if output.passed == Some(false) {
+                let error = format!("Plugin {} reported a failure", name);
+                logger.log_error(&error);
+                jobs.finish_job(job_id, JobStatus::Failed, Some(error));
+            } else {
+                logger.log_info(&format!("Plugin {} completed.", name));
+                jobs.finish_job(job_id, JobStatus::Completed, None);
+            }
+        }
+        Err(_) if ticket.cancel.is_cancelled() => {
+            logger.log_warn(&format!("Plugin {} cancelled.", name));
+            jobs.finish_job(job_id, JobStatus::Cancelled, None);
+        }
+        Err(e) => {
+            logger.log_error(&format!("Plugin {} failed: {}", name, e));
+            jobs.finish_job(job_id, JobStatus::Failed, Some(e));
+        }
+    }
+}
+
+/// Plans a plugin for `--dry-run`, loading it from the `[plugins]` section.
+///
+/// # Arguments
+///
+/// * `logger` - Logger implementation for logging messages.
+/// * `config` - The configuration file, for the `[plugins]` the plugin is loaded from.
+/// * `args` - The plugin name followed by its arguments.
+///
+/// # Returns
+///
+/// * `Result<ExecutionPlan, String>` - The plan, or why no such plugin is loaded.
+pub fn plan(
+    logger: Arc<dyn LoggerPort>,
+    config: &Path,
+    args: &[String],
+) -> Result<ExecutionPlan, String> {
+    let plugin_config = load_plugin_config(config)?;
+    let mut plugin_registry = PluginRegistry::new(logger);
+    plugin_registry.load(&plugin_config);
+    let plugin = plugin_registry.get(&args[0]).ok_or_else(|| {
+        format!(
+            "Unknown command '{}'; no plugin of that name is loaded",
+            args[0]
+        )
+    })?;
+    let mut plan = ExecutionPlan::new(&format!("plugin {}", plugin.name()));
+    plan.steps.push(PlannedStep {
+        disk_writes: DiskWrites::Unknown,
+        ..PlannedStep::new(
+            plugin.name(),
+            format!("{} {}", plugin.name(), args[1..].join(" ")),
+            plugin.resources(),
+        )
+    });
+    Ok(plan)
+}
@@ -0,0 +1,123 @@
+//! Power Cycle Command
+//!
+//! This module runs `one_for_all power-cycle` on a controller machine, which
+//! power cycles the machine under test through its BMC and follows its agent.
+
+use std::sync::Arc;
+
+use tokio::spawn;
+
+use common::domain::api_config::ApiConfig;
+use common::ports::log_port::LoggerPort;
+
+use oneforall_core::adapters::jobs_client_adapter::JobsClientAdapter;
+use oneforall_core::adapters::power_cycle_runner::PowerCycleRunner;
+use oneforall_core::domain::fleet::agent_url;
+use oneforall_core::domain::plan::{ExecutionPlan, PlannedStep};
+use oneforall_core::domain::power_cycle::{self, BmcTarget};
+use oneforall_core::domain::profile::{format_duration, parse_duration};
+
+use crate::cli::PowerCycleArgs;
+use crate::commands::shutdown_signal;
+
+/// Power cycles the machine under test, once its agent is ready and the confirmation
+/// token is given, and exits with whether every cycle came back ready. Ctrl+C stops it
+/// at the next heartbeat, with the cycles so far.
+///
+/// # Arguments
+///
+/// * `logger` - Logger implementation for logging messages.
+/// * `api_config` - The token and TLS settings the agent is called with.
+/// * `args` - The flags of the `power-cycle` subcommand.
+///
+/// # Returns
+///
+/// * `std::io::Result<()>` - Why the power cycles could not start; once they ran, the
+///   process exits with whether they passed.
+pub async fn run(
+    logger: Arc<dyn LoggerPort>,
+    api_config: &ApiConfig,
+    args: &PowerCycleArgs,
+) -> std::io::Result<()> {
+    let runner = agent_url(&args.agent).and_then(|url| {
+        let bmc = BmcTarget::parse(&args.bmc)?;
+        power_cycle::confirm_power_cycle(&bmc, args.confirm.as_deref())?;
+        let ready_timeout = parse_duration(&args.ready_timeout)
+            .map_err(|e| format!("Invalid --ready-timeout value: {}", e))?;
+        let client = JobsClientAdapter::for_agent(logger.clone(), api_config, &url)?;
+        // The machine can only be seen going down if the agent answers beforehand.
+        client.readiness().map_err(|e| {
+            format!(
+                "The agent at {} must be ready before the first power cycle: {}",
+                url, e
+            )
+        })?;
+        Ok((
+            Arc::new(PowerCycleRunner::new(logger.clone(), client, &url, bmc)),
+            ready_timeout,
+        ))
+    });
+    let (runner, ready_timeout) = match runner {
+        Ok(runner) => runner,
+        Err(e) => {
+            logger.log_error(&e);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, e));
+        }
+    };
+    let signal_runner = runner.clone();
+    spawn(async move {
+        shutdown_signal().await;
+        signal_runner.cancel();
+    });
+    let cycles = args.cycles;
+    let report = match tokio::task::spawn_blocking(move || runner.run(cycles, ready_timeout)).await
+    {
+        Ok(report) => report,
+        Err(e) => {
+            let e = format!("The power cycle test failed: {}", e);
+            logger.log_error(&e);
+            return Err(std::io::Error::other(e));
+        }
+    };
+    if args.json {
+        match serde_json::to_string_pretty(&report) {
+            Ok(json) => println!("{}", json),
+            Err(e) => logger.log_error(&format!("Failed to encode the report: {}", e)),
+        }
+    } else {
+        print!("{}", report.render());
+    }
+    std::process::exit(if report.passed() { 0 } else { 1 });
+}
+
+/// Plans the power cycles for `--dry-run`, with the confirmation token they need.
+///
+/// # Arguments
+///
+/// * `args` - The flags of the `power-cycle` subcommand.
+///
+/// # Returns
+///
+/// * `Result<ExecutionPlan, String>` - The plan, or why the flags are invalid.
+pub fn plan(args: &PowerCycleArgs) -> Result<ExecutionPlan, String> {
+    let url = agent_url(&args.agent)?;
+    let bmc = BmcTarget::parse(&args.bmc)?;
+    let ready_timeout = parse_duration(&args.ready_timeout)
+        .map_err(|e| format!("Invalid --ready-timeout value: {}", e))?;
+    let mut plan = ExecutionPlan::new("power-cycle");
+    plan.steps.push(PlannedStep {
+        targets: vec![url, bmc.to_string()],
+        ..PlannedStep::new(
+            "power-cycle",
+            format!(
+                "{} power cycles through the BMC, each waiting up to {} for the agent \
+                 to be ready; needs --confirm {}",
+                args.cycles,
+                format_duration(ready_timeout),
+                power_cycle::confirmation_token(&bmc)
+            ),
+            &[],
+        )
+    });
+    Ok(plan)
+}
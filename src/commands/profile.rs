@@ -0,0 +1,317 @@
+//! Profile Commands
+//!
+//! This module runs `one_for_all run` and `one_for_all resume`: a test
+//! profile from its first step, or the run of one a restart interrupted from its
+//! checkpoint.
+
+use std::sync::Arc;
+
+use common::domain::job::{JobSpec, JobStatus};
+use common::ports::log_port::LoggerPort;
+
+use oneforall_core::adapters::hook_adapter::HookAdapter;
+use oneforall_core::adapters::profile_adapter::ProfileAdapter;
+use oneforall_core::adapters::profile_runner::ProfileRunner;
+use oneforall_core::adapters::repository::Repository;
+use oneforall_core::adapters::stressor_catalog_adapter::StressorCatalogAdapter;
+use oneforall_core::domain::checkpoint::{RunCheckpoint, RunPhase};
+use oneforall_core::domain::export::RunArtifacts;
+use oneforall_core::domain::plan::ExecutionPlan;
+use oneforall_core::domain::profile::{format_duration, Profile};
+use oneforall_core::ports::hook_port::HookPort;
+use oneforall_core::ports::profile_port::ProfilePort;
+use oneforall_core::ports::stressor_catalog_port::StressorCatalogPort;
+
+use crate::cli::Commands;
+use crate::commands::CommandContext;
+
+/// Runs a test profile, or continues the run of one a restart interrupted.
+pub async fn run(ctx: CommandContext, command: Commands) {
+    let CommandContext {
+        logger,
+        telemetry,
+        jobs,
+        repository,
+        alerts,
+        exporter,
+        tags,
+        ..
+    } = ctx;
+    let profiles = ProfileAdapter::new(logger.clone());
+
+    // `resume` continues the run a restart interrupted from its checkpoint,
+    // loading the profile it was started with again.
+    let (source, resumed) = match command {
+        Commands::Resume {
+            profile,
+            discard,
+            after_reboot,
+        } => {
+            // At boot there is usually no run waiting for the reboot, which is
+            // no error.
+            if after_reboot
+                && repository
+                    .get_checkpoints::<RunCheckpoint>()
+                    .is_ok_and(|checkpoints| {
+                        !checkpoints
+                            .iter()
+                            .any(|checkpoint| checkpoint.phase == RunPhase::AwaitingReboot)
+                    })
+            {
+                println!("No profile run is waiting for a reboot.");
+                std::process::exit(0);
+            }
+            let checkpoint = match find_checkpoint(&repository, profile.as_deref(), after_reboot) {
+                Ok(checkpoint) => checkpoint,
+                Err(e) => {
+                    logger.log_error(&e);
+                    std::process::exit(1);
+                }
+            };
+            if discard {
+                if let Err(e) = repository.remove_checkpoint(&checkpoint.profile) {
+                    logger.log_error(&e);
+                    std::process::exit(1);
+                }
+                println!(
+                    "Discarded the unfinished run of profile {} started at {}.",
+                    checkpoint.profile, checkpoint.started_at
+                );
+                std::process::exit(0);
+            }
+            (checkpoint.source.clone(), Some(checkpoint))
+        }
+        Commands::Run {
+            profile: Some(source),
+        } => (source, None),
+        // Without a profile name, show what can be run.
+        _ => {
+            match profiles.list_profiles() {
+                Ok(available) => {
+                    println!("Available profiles:");
+                    for profile in available {
+                        println!(
+                            "  {:<16} {:>4} steps  {:>5}  {}",
+                            profile.name,
+                            profile.steps.len(),
+                            format_duration(profile.planned_duration()),
+                            profile.description
+                        );
+                    }
+                }
+                Err(e) => {
+                    logger.log_error(&e);
+                    std::process::exit(1);
+                }
+            }
+            std::process::exit(0);
+        }
+    };
+
+    let profile = match profiles.load_profile(&source) {
+        Ok(profile) => profile,
+        Err(e) => {
+            logger.log_error(&e);
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) = check_profile_stressors(logger.clone(), &profile) {
+        logger.log_error(&e);
+        std::process::exit(1);
+    }
+    let checkpoint = match resumed {
+        Some(mut checkpoint) => {
+            if let Err(e) = checkpoint.check_profile(&profile) {
+                logger.log_error(&e);
+                std::process::exit(1);
+            }
+            let boot_id = HookAdapter::new(logger.clone()).boot_id().ok();
+            if let Err(e) = checkpoint.check_restarted(boot_id.as_deref()) {
+                logger.log_error(&e);
+                std::process::exit(1);
+            }
+            checkpoint.resumes += 1;
+            logger.log_info(&format!(
+                "Resuming profile {} started at {} ({} step(s) left: {})",
+                profile.name,
+                checkpoint.started_at,
+                checkpoint.remaining_steps().len(),
+                checkpoint.remaining_steps().join(", ")
+            ));
+            checkpoint
+        }
+        None => {
+            logger.log_info(&format!(
+                "Running profile {} ({} steps, {} of timed tests)",
+                profile.name,
+                profile.steps.len(),
+                format_duration(profile.planned_duration())
+            ));
+            RunCheckpoint::new(&source, &profile)
+        }
+    };
+
+    // The profile itself is a job without resources of its own, so the whole
+    // run shows up in `oneforall jobs` and can be cancelled at once, while each
+    // step is queued separately for the resources it loads.
+    let profile_job = jobs.submit_job(JobSpec::new(&format!("profile:{}", profile.name), &[]));
+    let runner = ProfileRunner::new(logger.clone(), telemetry.clone(), jobs.clone());
+    let recording = exporter.record(&telemetry);
+    // The telemetry recorded before a restart leads the time-series of the run.
+    if let Some(recording) = &recording {
+        recording.prepend(checkpoint.telemetry.clone());
+    }
+
+    // The checkpoint is saved before the first step and after every step that
+    // finishes, so a restart loses at most the step that was running.
+    let save_checkpoint = |checkpoint: &RunCheckpoint| {
+        let mut checkpoint = checkpoint.clone();
+        if let Some(recording) = &recording {
+            checkpoint.telemetry = recording.events();
+        }
+        if let Err(e) = repository.store_checkpoint(&checkpoint.profile, &checkpoint) {
+            logger.log_warn(&format!("Failed to save the checkpoint: {}", e));
+        }
+    };
+    save_checkpoint(&checkpoint);
+    let report = runner
+        .resume(&profile, checkpoint, &profile_job.cancel, &save_checkpoint)
+        .await;
+    let rendered = report.render();
+    println!("{}", rendered);
+
+    // A step rebooted or power cycled the machine: the run is not over, and its
+    // checkpoint continues it once the machine is back.
+    if report.awaiting_reboot {
+        jobs.finish_job(
+            profile_job.job.id,
+            JobStatus::Completed,
+            Some("rebooting; the run continues after the reboot".to_string()),
+        );
+        alerts.flush();
+        std::process::exit(0);
+    }
+
+    if let Err(e) = repository.store_run(
+        "profile",
+        &report.started_at,
+        Some(&report.profile),
+        &report,
+    ) {
+        logger.log_warn(&format!("Failed to store profile report: {}", e));
+    }
+    // Finished or cancelled, the run is over and no longer resumable.
+    if let Err(e) = repository.remove_checkpoint(&report.profile) {
+        logger.log_warn(&e);
+    }
+
+    let (status, message) = if profile_job.cancel.is_cancelled() {
+        (JobStatus::Cancelled, None)
+    } else if report.passed() {
+        (JobStatus::Completed, None)
+    } else {
+        (
+            JobStatus::Failed,
+            Some(format!(
+                "failed steps: {}",
+                report.failed_steps().join(", ")
+            )),
+        )
+    };
+    jobs.finish_job(profile_job.job.id, status, message);
+
+    if exporter.is_enabled() {
+        let kind = format!("profile-{}", report.profile);
+        let mut artifacts = RunArtifacts::new(&kind, &report.started_at);
+        artifacts.add_result(&report, &tags);
+        artifacts.add_text("report.txt", &rendered);
+        exporter.export(artifacts, recording);
+    }
+
+    // Scripts chaining profiles rely on the exit status.
+    alerts.flush();
+    std::process::exit(if report.passed() { 0 } else { 1 });
+}
+
+/// Checks the stressors of a profile against the stress-ng binary of this machine, so a
+/// stressor it lacks is reported before the run rather than when its step starts.
+///
+/// # Arguments
+///
+/// * `logger` - Receives a warning if stress-ng cannot be inspected.
+/// * `profile` - The profile to check.
+///
+/// # Returns
+///
+/// * `Result<(), String>` - An error naming each step whose stressor does not exist; the
+///   check is skipped with a warning when stress-ng cannot be inspected.
+pub fn check_profile_stressors(
+    logger: Arc<dyn LoggerPort>,
+    profile: &Profile,
+) -> Result<(), String> {
+    if !profile.runs_stress_ng() {
+        return Ok(());
+    }
+    match StressorCatalogAdapter::new(logger.clone()).load_catalog() {
+        Ok(catalog) => profile.check_stressors(&catalog),
+        Err(e) => {
+            logger.log_warn(&format!("Cannot check the stressors of the profile: {}", e));
+            Ok(())
+        }
+    }
+}
+
+/// Finds the checkpoint of the unfinished profile run `resume` continues.
+///
+/// # Arguments
+///
+/// * `repository` - Where the checkpoints are saved.
+/// * `profile` - The profile given with `--profile`, a name or a path; needed when
+///   several runs are unfinished.
+/// * `after_reboot` - Whether to consider only the runs waiting for a reboot a hook asked for.
+///
+/// # Returns
+///
+/// * `Result<RunCheckpoint, String>` - The checkpoint, or why there is none to resume.
+pub fn find_checkpoint(
+    repository: &Repository,
+    profile: Option<&str>,
+    after_reboot: bool,
+) -> Result<RunCheckpoint, String> {
+    let mut checkpoints: Vec<RunCheckpoint> = repository.get_checkpoints()?;
+    checkpoints.retain(|checkpoint| checkpoint.phase == RunPhase::AwaitingReboot || !after_reboot);
+    if let Some(profile) = profile {
+        return checkpoints
+            .into_iter()
+            .find(|checkpoint| checkpoint.profile == profile || checkpoint.source == profile)
+            .ok_or_else(|| format!("No unfinished run of profile {} to resume", profile));
+    }
+    match checkpoints.len() {
+        0 => Err("No unfinished profile run to resume".to_string()),
+        1 => Ok(checkpoints.remove(0)),
+        _ => Err(format!(
+            "Several profile runs are unfinished; choose one with --profile:\n{}",
+            checkpoints
+                .iter()
+                .map(RunCheckpoint::render_line)
+                .collect::<Vec<_>>()
+                .join("\n")
+        )),
+    }
+}
+
+/// Plans a profile for `--dry-run`, after checking that stress-ng has its stressors.
+///
+/// # Arguments
+///
+/// * `logger` - Logger implementation for logging messages.
+/// * `name` - The profile to plan, e.g. `burn-in-24h`.
+///
+/// # Returns
+///
+/// * `Result<ExecutionPlan, String>` - The plan, or why the profile cannot be run.
+pub fn plan(logger: Arc<dyn LoggerPort>, name: &str) -> Result<ExecutionPlan, String> {
+    let profile = ProfileAdapter::new(logger.clone()).load_profile(name)?;
+    check_profile_stressors(logger.clone(), &profile)?;
+    Ok(ProfileRunner::plan(logger, &profile))
+}
@@ -0,0 +1,809 @@
+//! Stress Command
+//!
+//! This module runs `one_for_all stress`: the CPU stress test, with its
+//! retries, or one of the burn-in, GPU, VRAM, RAM, SIMD and soak tests, judged
+//! by the hardware error monitors and reported with the telemetry of the run.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use tokio::time::{sleep, Duration};
+
+use common::domain::job::{JobSpec, JobStatus, Resource};
+use common::ports::log_port::LoggerPort;
+
+use oneforall_core::adapters::burn_in_runner::BurnInRunner;
+use oneforall_core::adapters::cgroup_adapter::CgroupAdapter;
+use oneforall_core::adapters::ebpf_latency_adapter::EbpfLatencyAdapter;
+use oneforall_core::adapters::gpu_stress_runner::GpuStressRunner;
+use oneforall_core::adapters::hardware_error_adapter::HardwareErrorAdapter;
+use oneforall_core::adapters::integrity_monitor::IntegrityMonitor;
+use oneforall_core::adapters::memtest_runner::MemtestRunner;
+use oneforall_core::adapters::progress_reporter::ProgressReporter;
+use oneforall_core::adapters::run_monitor::{RunMonitor, DEFAULT_SAMPLE_INTERVAL};
+use oneforall_core::adapters::simd_stress_adapter::SimdStressAdapter;
+use oneforall_core::adapters::simd_stress_runner::SimdStressRunner;
+use oneforall_core::adapters::soak_runner::SoakRunner;
+use oneforall_core::adapters::stress_ng_adapter::{
+    run_output_file, take_run_output, StressNgAdapter,
+};
+use oneforall_core::adapters::stressor_catalog_adapter::StressorCatalogAdapter;
+use oneforall_core::adapters::topology_adapter::TopologyAdapter;
+use oneforall_core::adapters::vram_test_runner::VramTestRunner;
+use oneforall_core::domain::cpu_topology::{format_cpu_list, parse_cpu_list, AffinitySpec};
+use oneforall_core::domain::endurance::{format_volume, parse_size};
+use oneforall_core::domain::export::RunArtifacts;
+use oneforall_core::domain::integrity::IntegrityPlan;
+use oneforall_core::domain::plan::{ExecutionPlan, PlannedStep};
+use oneforall_core::domain::profile::parse_duration;
+use oneforall_core::domain::simd::{select_phases, SimdIsa};
+use oneforall_core::domain::soak::DutyCycle;
+use oneforall_core::domain::stress_ng::{parse_bogo_ops, parse_bogo_ops_rate, StressRunOutcome};
+use oneforall_core::ports::container_port::ContainerPort;
+use oneforall_core::ports::hardware_error_port::HardwareErrorPort;
+use oneforall_core::ports::kernel_latency_port::KernelLatencyPort;
+use oneforall_core::ports::simd_stress_port::SimdStressPort;
+use oneforall_core::ports::stressor_catalog_port::StressorCatalogPort;
+use oneforall_core::ports::topology_port::TopologyPort;
+
+use crate::cli::StressArgs;
+use crate::commands::CommandContext;
+
+/// Runs a stress test and exits with its verdict.
+pub async fn run(ctx: CommandContext, args: StressArgs) {
+    let CommandContext {
+        logger,
+        telemetry,
+        jobs,
+        repository,
+        alerts,
+        exporter,
+        tags,
+        ..
+    } = ctx;
+    let StressArgs {
+        pin,
+        numa,
+        physical_only,
+        core_class,
+        duration: requested_duration,
+        all,
+        gpu,
+        max_gpu_celsius,
+        vram,
+        vram_device,
+        memtest,
+        simd,
+        soak,
+        on,
+        off,
+        verify,
+        verify_memory,
+        verify_disk,
+        verify_path,
+        ebpf,
+        clear_sel,
+        list,
+    } = args;
+
+    // Listing the stressors inspects stress-ng without running a test.
+    if list {
+        match StressorCatalogAdapter::new(logger.clone()).load_catalog() {
+            Ok(catalog) => print!("{}", catalog.render()),
+            Err(e) => logger.log_error(&e),
+        }
+        return;
+    }
+
+    let test_duration = match parse_duration(&requested_duration) {
+        Ok(duration) => duration,
+        Err(e) => {
+            logger.log_error(&format!("Invalid --duration value: {}", e));
+            return;
+        }
+    };
+
+    // The data-integrity patterns to re-read while the machine is under load.
+    let integrity_plan =
+        match integrity_plan(verify, &verify_memory, &verify_disk, verify_path.as_deref()) {
+            Ok(integrity_plan) => integrity_plan,
+            Err(e) => {
+                logger.log_error(&e);
+                return;
+            }
+        };
+
+    // Entries logged before the test would otherwise be mistaken for its faults
+    // when the log is reviewed afterwards.
+    if clear_sel {
+        if let Err(e) = HardwareErrorAdapter::new(logger.clone()).clear_sel() {
+            logger.log_warn(&format!("Failed to clear the BMC event log: {}", e));
+        }
+    }
+
+    // A full-system burn-in runs every stressor at once and is judged by the
+    // hardware error monitors rather than retried.
+    if all {
+        let runner = BurnInRunner::new(logger.clone(), telemetry.clone(), jobs.clone());
+        let recording = exporter.record(&telemetry);
+        let report = runner.run(test_duration, integrity_plan).await;
+        let rendered = report.render();
+        println!("{}", rendered);
+        if let Err(e) = repository.store_run("burn-in", &report.started_at, None, &report) {
+            logger.log_warn(&format!("Failed to store burn-in report: {}", e));
+        }
+        if exporter.is_enabled() {
+            let mut artifacts = RunArtifacts::new("burn-in", &report.started_at);
+            artifacts.add_result(&report, &tags);
+            artifacts.add_text("report.txt", &rendered);
+            exporter.export(artifacts, recording);
+        }
+        alerts.flush();
+        std::process::exit(if report.passed() { 0 } else { 1 });
+    }
+
+    // A GPU stress test is judged by the thermal limit of each GPU.
+    if gpu {
+        let runner = GpuStressRunner::new(logger.clone(), telemetry.clone(), jobs.clone());
+        let recording = exporter.record(&telemetry);
+        let report = runner.run(test_duration, max_gpu_celsius).await;
+        let rendered = report.render();
+        println!("{}", rendered);
+        if let Err(e) = repository.store_run("gpu-stress", &report.started_at, None, &report) {
+            logger.log_warn(&format!("Failed to store GPU stress report: {}", e));
+        }
+        if exporter.is_enabled() {
+            let mut artifacts = RunArtifacts::new("gpu-stress", &report.started_at);
+            artifacts.add_result(&report, &tags);
+            artifacts.add_text("report.txt", &rendered);
+            exporter.export(artifacts, recording);
+        }
+        alerts.flush();
+        std::process::exit(if report.passed() { 0 } else { 1 });
+    }
+
+    // A RAM test is judged by its pattern tests and the ECC errors reported meanwhile.
+    if let Some(size) = memtest {
+        let bytes = match parse_size(&size) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                logger.log_error(&format!("Invalid --memtest value: {}", e));
+                return;
+            }
+        };
+        // Locking more than the cgroup allows gets the test OOM-killed mid-pass.
+        if let Ok(limits) = CgroupAdapter::new(logger.clone()).read_resource_limits() {
+            if limits.is_memory_limited() && bytes >= limits.effective_memory_bytes() {
+                logger.log_error(&format!(
+                    "--memtest {} exceeds the cgroup memory limit of {}.",
+                    format_volume(bytes),
+                    format_volume(limits.effective_memory_bytes())
+                ));
+                return;
+            }
+        }
+        let runner = MemtestRunner::new(logger.clone(), telemetry.clone(), jobs.clone());
+        let recording = exporter.record(&telemetry);
+        let report = runner.run(bytes, test_duration).await;
+        let rendered = report.render();
+        println!("{}", rendered);
+        if let Err(e) = repository.store_run("memtest", &report.started_at, None, &report) {
+            logger.log_warn(&format!("Failed to store RAM test report: {}", e));
+        }
+        if exporter.is_enabled() {
+            let mut artifacts = RunArtifacts::new("memtest", &report.started_at);
+            artifacts.add_result(&report, &tags);
+            artifacts.add_text("report.txt", &rendered);
+            exporter.export(artifacts, recording);
+        }
+        alerts.flush();
+        std::process::exit(if report.passed() { 0 } else { 1 });
+    }
+
+    // A VRAM test is judged by the memory errors memtest_vulkan finds.
+    if vram {
+        let runner = VramTestRunner::new(logger.clone(), telemetry.clone(), jobs.clone());
+        let recording = exporter.record(&telemetry);
+        let report = runner.run(vram_device, test_duration).await;
+        let rendered = report.render();
+        println!("{}", rendered);
+        if let Err(e) = repository.store_run("vram-test", &report.started_at, None, &report) {
+            logger.log_warn(&format!("Failed to store VRAM test report: {}", e));
+        }
+        if exporter.is_enabled() {
+            let mut artifacts = RunArtifacts::new("vram-test", &report.started_at);
+            artifacts.add_result(&report, &tags);
+            artifacts.add_text("report.txt", &rendered);
+            exporter.export(artifacts, recording);
+        }
+        alerts.flush();
+        std::process::exit(if report.passed() { 0 } else { 1 });
+    }
+
+    // A SIMD stress test is judged like the CPU stress test, and reports the
+    // clock each instruction set held against the scalar baseline.
+    if let Some(isa) = simd {
+        let runner = SimdStressRunner::new(logger.clone(), telemetry.clone(), jobs.clone());
+        let recording = exporter.record(&telemetry);
+        let report = runner.run(SimdIsa::from_name(&isa), test_duration).await;
+        let rendered = report.render();
+        println!("{}", rendered);
+        if let Err(e) = repository.store_run("simd-stress", &report.started_at, None, &report) {
+            logger.log_warn(&format!("Failed to store SIMD stress report: {}", e));
+        }
+        if exporter.is_enabled() {
+            let mut artifacts = RunArtifacts::new("simd-stress", &report.started_at);
+            artifacts.add_result(&report, &tags);
+            artifacts.add_text("report.txt", &rendered);
+            exporter.export(artifacts, recording);
+        }
+        alerts.flush();
+        std::process::exit(if report.passed() { 0 } else { 1 });
+    }
+
+    // A soak test heats and cools the machine cycle after cycle, and fails on
+    // any error counted in a cycle rather than being retried.
+    if soak {
+        let duty = match duty_cycle(&on, &off) {
+            Ok(duty) => duty,
+            Err(e) => {
+                logger.log_error(&e);
+                return;
+            }
+        };
+        let runner = SoakRunner::new(logger.clone(), telemetry.clone(), jobs.clone());
+        let recording = exporter.record(&telemetry);
+        let report = runner.run(duty, test_duration).await;
+        let rendered = report.render();
+        println!("{}", rendered);
+        if let Err(e) = repository.store_run("soak", &report.started_at, None, &report) {
+            logger.log_warn(&format!("Failed to store soak report: {}", e));
+        }
+        if exporter.is_enabled() {
+            let mut artifacts = RunArtifacts::new("soak", &report.started_at);
+            artifacts.add_result(&report, &tags);
+            artifacts.add_text("report.txt", &rendered);
+            exporter.export(artifacts, recording);
+        }
+        alerts.flush();
+        std::process::exit(if report.passed() { 0 } else { 1 });
+    }
+
+    // Collect the placement constraints requested on the command line.
+    let affinity = match pin.as_deref().map(parse_cpu_list).transpose() {
+        Ok(pin) => AffinitySpec {
+            pin,
+            numa_node: numa,
+            physical_only,
+        },
+        Err(e) => {
+            logger.log_error(&format!("Invalid --pin value: {}", e));
+            return;
+        }
+    };
+
+    // Define the arguments for the stress test.
+    let args = match cpu_stress_args(
+        logger.clone(),
+        &affinity,
+        core_class.as_deref(),
+        test_duration,
+    ) {
+        Ok(args) => args,
+        Err(e) => {
+            logger.log_error(&e);
+            return;
+        }
+    };
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    // Initialize the retry mechanism. This allows the stress test to be retried
+    // a specified number of times in case of failure. In this case, the test
+    // will be attempted up to 3 times (initial try + 2 retries).
+    let mut retries = 2;
+
+    // Queue the stress test as a job so it can be cancelled with Ctrl+C or
+    // `DELETE /api/v1/jobs/<id>`, and so it never overlaps another CPU workload.
+    let mut ticket = jobs.submit_job(JobSpec::new("stress:cpu", &[Resource::Cpu]));
+    let (job, cancel) = (ticket.job.clone(), ticket.cancel.clone());
+    if job.status == JobStatus::Queued {
+        logger.log_info(&format!(
+            "Stress test job {} queued behind a conflicting job.",
+            job.id
+        ));
+    }
+    if !ticket.admitted().await {
+        logger.log_warn(&format!(
+            "Stress test job {} cancelled while queued.",
+            job.id
+        ));
+        return;
+    }
+    logger.log_info(&format!("Started stress test job {}.", job.id));
+    let recording = exporter.record(&telemetry);
+
+    // Sample clocks, idle/performance states and power for the whole test so
+    // the report can show turbo behavior, throttling and efficiency over time.
+    let run_monitor = RunMonitor::start(logger.clone(), telemetry.clone(), DEFAULT_SAMPLE_INTERVAL);
+    let integrity_monitor =
+        integrity_plan.map(|plan| IntegrityMonitor::start(logger.clone(), plan));
+
+    // With --ebpf, the kernel times how long threads wait for a CPU and I/O
+    // requests for their drive, which sampling /proc cannot see.
+    let kernel_latency = ebpf
+        .then(|| EbpfLatencyAdapter::new(logger.clone()))
+        .filter(|probes| match probes.start() {
+            Ok(()) => true,
+            Err(e) => {
+                logger.log_warn(&format!("Not measuring kernel latency: {}", e));
+                false
+            }
+        });
+
+    // The bogo-ops reported by the successful attempt, if any.
+    let mut bogo_ops = None;
+    // The output of stress-ng in the last attempt.
+    let mut stress_output = String::new();
+
+    let mut job_status = JobStatus::Failed;
+    let mut job_message = None;
+
+    // Start a loop for executing the stress test with retries.
+    while retries >= 0 {
+        // Log the start of a stress test attempt. This is useful for monitoring
+        // and debugging purposes, allowing users to track the test's progress
+        // and retries.
+        logger.log_info(&format!(
+            "Executing CPU stress test. Attempts remaining: {}",
+            retries,
+        ));
+
+        // Stream elapsed time, the bogo-ops rate, temperatures and power while
+        // stress-ng runs, so the terminal and WebSocket clients are not left
+        // waiting for the whole test.
+        let progress =
+            ProgressReporter::start(logger.clone(), telemetry.clone(), "cpu", test_duration);
+
+        // Execute the stress test command asynchronously.
+        // `StressNgAdapter::execute_stress_ng_command_to` is responsible for running
+        // the stress test using the `stress-ng` tool, capturing its output in a file
+        // of this run's own. The command is awaited to ensure the execution is
+        // complete before proceeding.
+        let output_file = run_output_file();
+        progress.follow_runs(std::slice::from_ref(&output_file));
+        let outcome = StressNgAdapter::execute_stress_ng_command_to(
+            logger.clone(),
+            &args,
+            core_class.as_deref() == Some("efficiency"),
+            &output_file,
+            &cancel,
+        )
+        .await;
+        stress_output = take_run_output(&output_file);
+        bogo_ops = parse_bogo_ops(&stress_output);
+        progress.finish(parse_bogo_ops_rate(&stress_output));
+
+        match outcome {
+            // In case of a successful execution, log the success and exit the loop.
+            // This indicates that the stress test was completed without errors.
+            Ok(StressRunOutcome::Completed) => {
+                logger.log_info("CPU stress test executed successfully.");
+                job_status = JobStatus::Completed;
+                break;
+            }
+            // A cancelled run is recorded as such and never retried.
+            Ok(StressRunOutcome::Cancelled) => {
+                logger.log_warn("CPU stress test cancelled.");
+                job_status = JobStatus::Cancelled;
+                break;
+            }
+            // In case of an error, handle the retry mechanism.
+            Err(e) => {
+                // If there are retries left, log a warning and decrement the retry counter.
+                // The `sleep` call introduces a delay before the next attempt, giving
+                // the system some time to stabilize.
+                job_message = Some(e.clone());
+                if retries > 0 {
+                    logger.log_warn(&format!(
+                        "Retrying CPU stress test. Attempts remaining: {}",
+                        retries
+                    ));
+                    tokio::select! {
+                        _ = sleep(Duration::from_secs(10)) => {}
+                        _ = cancel.cancelled() => {
+                            logger.log_warn("CPU stress test cancelled.");
+                            job_status = JobStatus::Cancelled;
+                            job_message = None;
+                            break;
+                        }
+                    }
+                } else {
+                    // If there are no retries left, log the error and exit the loop.
+                    // This indicates that all attempts to run the stress test have failed.
+                    logger.log_error(&format!("Error executing CPU stress test: {}", e));
+                }
+            }
+        }
+        // Decrement the retry counter after each attempt.
+        retries -= 1;
+    }
+
+    // Corruption found by the verification, a serious kernel log event such as
+    // a machine check, or a new entry in the BMC event log fails the job even if
+    // stress-ng succeeded.
+    let run_telemetry = run_monitor.finish();
+    let integrity = integrity_monitor.map(IntegrityMonitor::finish);
+    let kernel_latency =
+        kernel_latency.and_then(|probes| probes.finish().map_err(|e| logger.log_warn(&e)).ok());
+    let corrupted = integrity.as_ref().is_some_and(|report| !report.passed());
+    let kernel_faults = run_telemetry.kernel_log.has_serious_events();
+    let sel_faults = run_telemetry.sel.has_new_entries();
+    if job_status == JobStatus::Completed && (corrupted || kernel_faults || sel_faults) {
+        job_status = JobStatus::Failed;
+        job_message = Some(if corrupted {
+            "data corruption detected".to_string()
+        } else if kernel_faults {
+            "serious kernel log events".to_string()
+        } else {
+            "new BMC event log entries".to_string()
+        });
+    }
+
+    // Record the outcome of the job, and keep it in the database for later review.
+    let finished = jobs.finish_job(job.id, job_status, job_message);
+    if let Some(job) = &finished {
+        let id = job.id.to_string();
+        if let Err(e) = repository.store_run("job", &job.started_at, Some(&id), job) {
+            logger.log_warn(&format!("Failed to store job result: {}", e));
+        }
+    }
+
+    // Print the telemetry gathered while the stress test was running, along
+    // with the work done per joule when both bogo-ops and energy are known.
+    let mut report = run_telemetry.report.clone();
+
+    // Bogo-ops on big.LITTLE, hybrid or SVE-capable CPUs only compare with
+    // results from the same core classes and SIMD extensions.
+    match TopologyAdapter::new(logger.clone()).discover_cpu_architecture() {
+        Ok(architecture) => report.push_str(&architecture.report()),
+        Err(e) => logger.log_warn(&format!("Skipping CPU architecture report: {}", e)),
+    }
+    if let (Some(power), Some(ops)) = (&run_telemetry.power, bogo_ops) {
+        if let Some(efficiency) = power.format_efficiency(ops) {
+            report.push_str(&efficiency);
+        }
+    }
+
+    if let Some(latency) = &kernel_latency {
+        report.push_str(&latency.render());
+    }
+    if let Some(integrity) = &integrity {
+        report.push_str(&integrity.render());
+    }
+    print!("{}", report);
+
+    // Upload the job, the report and the raw stress-ng output of the run.
+    if let (true, Some(job)) = (exporter.is_enabled(), &finished) {
+        let mut artifacts = RunArtifacts::new(&job.kind, &job.started_at);
+        artifacts.add_result(job, &tags);
+        artifacts.add_text("report.txt", &report);
+        if !stress_output.is_empty() {
+            artifacts.add_text("stress-ng.log", &stress_output);
+        }
+        exporter.export(artifacts, recording);
+    }
+    if corrupted {
+        logger.log_error("Data corruption detected during the stress test.");
+    }
+    if kernel_faults {
+        logger.log_error("Serious kernel log events occurred during the stress test.");
+    }
+    if sel_faults {
+        logger.log_error("The BMC logged new events during the stress test.");
+    }
+    if corrupted || kernel_faults || sel_faults {
+        alerts.flush();
+        std::process::exit(1);
+    }
+}
+
+/// Builds the stress-ng arguments of the CPU stress test.
+///
+/// # Arguments
+///
+/// * `logger` - An Arc-wrapped LoggerPort trait object for logging.
+/// * `affinity` - The placement constraints requested on the command line.
+/// * `core_class` - The class of cores given with `--core-class`, if any.
+/// * `test_duration` - How long the test runs.
+///
+/// # Returns
+///
+/// * `Result<Vec<String>, String>` - The arguments, or why the workers could not be placed.
+pub fn cpu_stress_args(
+    logger: Arc<dyn LoggerPort>,
+    affinity: &AffinitySpec,
+    core_class: Option<&str>,
+    test_duration: Duration,
+) -> Result<Vec<String>, String> {
+    // The arguments are modified to create a more comprehensive and informative CPU stress test.
+
+    // "--cpu N" starts one worker per CPU the process may use: every online
+    // CPU on a host, or as many as the cgroup quota and cpuset allow in a
+    // container, so the test neither oversubscribes nor underloads it.
+    let mut number_of_cores = match CgroupAdapter::new(logger.clone()).read_resource_limits() {
+        Ok(limits) => {
+            if limits.is_cpu_limited() {
+                logger.log_info(&format!(
+                    "Sizing the stress test to the cgroup limit of {} of {} CPUs.",
+                    limits.effective_cpus(),
+                    limits.host_cpus.len()
+                ));
+            }
+            limits.effective_cpus().to_string()
+        }
+        Err(e) => {
+            logger.log_warn(&format!("Starting a worker per online CPU: {}", e));
+            "0".to_string()
+        }
+    };
+
+    // Linux says which CPUs belong to each class of cores, so the workers are pinned
+    // to them; macOS does not, so one worker is started per core of the class.
+    let mut class_cpus = None;
+    if let Some(label) = core_class {
+        let architecture = TopologyAdapter::new(logger.clone())
+            .discover_cpu_architecture()
+            .map_err(|e| format!("Unable to place stress workers: {}", e))?;
+        let class = architecture.core_class(label).ok_or_else(|| {
+            format!(
+                "This CPU has no {} cores; its cores are {}",
+                label,
+                architecture
+                    .core_classes
+                    .iter()
+                    .map(|class| class.label.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })?;
+        if class.cpus.is_empty() {
+            logger.log_info(&format!(
+                "Starting {} stress workers for the {} cores",
+                class.cpu_count, label
+            ));
+            number_of_cores = class.cpu_count.to_string();
+        } else {
+            class_cpus = Some(class.cpus.clone());
+        }
+    }
+
+    // When placement constraints are given, resolve them against the discovered
+    // topology and pin one worker to each selected CPU with "--taskset".
+    let mut placement_args: Vec<String> = Vec::new();
+    if !affinity.is_unconstrained() || class_cpus.is_some() {
+        let mut cpus = TopologyAdapter::new(logger.clone())
+            .discover_cpu_topology()
+            .and_then(|topology| affinity.resolve(&topology))
+            .map_err(|e| format!("Unable to place stress workers: {}", e))?;
+        if let Some(class_cpus) = &class_cpus {
+            cpus = cpus.intersection(class_cpus).copied().collect();
+            if cpus.is_empty() {
+                return Err(format!(
+                    "Unable to place stress workers: no {} core matches the placement constraints",
+                    core_class.unwrap_or_default()
+                ));
+            }
+        }
+        let cpu_list = format_cpu_list(&cpus);
+        logger.log_info(&format!(
+            "Pinning {} stress workers to CPUs {}",
+            cpus.len(),
+            cpu_list
+        ));
+        number_of_cores = cpus.len().to_string();
+        placement_args.push("--taskset".to_string());
+        placement_args.push(cpu_list);
+    }
+
+    // "--timeout" sets how long the test runs, 120 seconds unless `--duration` says
+    // otherwise. This allows for a longer observation of CPU behavior under stress.
+    // "--metrics-brief" outputs brief metrics about the stress test upon completion,
+    // and "--verbose" details the stress test's operation to aid in diagnosing issues.
+    let mut args = vec![
+        "--cpu".to_string(),
+        number_of_cores,
+        "--timeout".to_string(),
+        format!("{}s", test_duration.as_secs()),
+        "--metrics-brief".to_string(),
+        "--verbose".to_string(),
+    ];
+    args.extend(placement_args);
+    Ok(args)
+}
+
+/// Reads the load and idle periods requested with `stress --soak`.
+///
+/// # Arguments
+///
+/// * `on` - How long each load period lasts, e.g. `10m`.
+/// * `off` - How long each idle period lasts, e.g. `5m`.
+///
+/// # Returns
+///
+/// * `Result<DutyCycle, String>` - The duty cycle, or why a period is invalid.
+pub fn duty_cycle(on: &str, off: &str) -> Result<DutyCycle, String> {
+    let on = parse_duration(on).map_err(|e| format!("Invalid --on value: {}", e))?;
+    let off = parse_duration(off).map_err(|e| format!("Invalid --off value: {}", e))?;
+    DutyCycle::new(on, off)
+}
+
+/// Reads the data-integrity verification requested with `stress --verify`.
+///
+/// # Arguments
+///
+/// * `verify` - Whether `--verify` was given.
+/// * `verify_memory` - The memory to verify, e.g. `512M`.
+/// * `verify_disk` - The disk space to verify, e.g. `1G`.
+/// * `verify_path` - The directory of the verified file; the system temporary directory when `None`.
+///
+/// # Returns
+///
+/// * `Result<Option<IntegrityPlan>, String>` - The verification, if requested, or why a size is invalid.
+pub fn integrity_plan(
+    verify: bool,
+    verify_memory: &str,
+    verify_disk: &str,
+    verify_path: Option<&str>,
+) -> Result<Option<IntegrityPlan>, String> {
+    if !verify {
+        return Ok(None);
+    }
+    let memory_bytes =
+        parse_size(verify_memory).map_err(|e| format!("Invalid --verify-memory value: {}", e))?;
+    let disk_bytes =
+        parse_size(verify_disk).map_err(|e| format!("Invalid --verify-disk value: {}", e))?;
+    Ok(Some(IntegrityPlan {
+        memory_bytes: memory_bytes as usize,
+        disk_bytes,
+        directory: verify_path
+            .map(PathBuf::from)
+            .unwrap_or_else(std::env::temp_dir),
+    }))
+}
+
+/// Plans a stress test for `--dry-run`.
+///
+/// # Arguments
+///
+/// * `logger` - Logger implementation for logging messages.
+/// * `args` - The flags of the `stress` subcommand.
+///
+/// # Returns
+///
+/// * `Result<ExecutionPlan, String>` - The plan, or why the stress test cannot be planned.
+pub fn plan(logger: Arc<dyn LoggerPort>, args: &StressArgs) -> Result<ExecutionPlan, String> {
+    let StressArgs {
+        pin,
+        numa,
+        physical_only,
+        core_class,
+        duration,
+        all,
+        gpu,
+        vram,
+        vram_device,
+        memtest,
+        simd,
+        soak,
+        on,
+        off,
+        verify,
+        verify_memory,
+        verify_disk,
+        verify_path,
+        list,
+        ..
+    } = args;
+    if *list {
+        return Err("stress --list starts no workload; run it without --dry-run".to_string());
+    }
+    let test_duration =
+        parse_duration(duration).map_err(|e| format!("Invalid --duration value: {}", e))?;
+    let integrity_plan =
+        integrity_plan(*verify, verify_memory, verify_disk, verify_path.as_deref())?;
+    if *all {
+        return Ok(BurnInRunner::plan(
+            logger,
+            test_duration,
+            integrity_plan.as_ref(),
+        ));
+    }
+    if *soak {
+        return Ok(SoakRunner::plan(
+            logger,
+            duty_cycle(on, off)?,
+            test_duration,
+        ));
+    }
+    let step = if *gpu {
+        PlannedStep {
+            targets: vec!["one worker per GPU".to_string()],
+            ..PlannedStep::new(
+                "gpu",
+                format!(
+                    "stress-ng --gpu <GPUs> --timeout {}s --metrics-brief",
+                    test_duration.as_secs()
+                ),
+                &[Resource::Gpu],
+            )
+        }
+    } else if let Some(size) = memtest {
+        let bytes = parse_size(size).map_err(|e| format!("Invalid --memtest value: {}", e))?;
+        PlannedStep {
+            targets: vec![format!("{} of locked memory", format_volume(bytes))],
+            ..PlannedStep::new(
+                "memtest",
+                "memtester-style pattern tests",
+                &[Resource::Memory],
+            )
+        }
+    } else if *vram {
+        PlannedStep {
+            targets: vec![format!("GPU {}", vram_device)],
+            ..PlannedStep::new("vram", "memtest_vulkan", &[Resource::Gpu])
+        }
+    } else if let Some(isa) = simd {
+        let supported = SimdStressAdapter::new(logger.clone()).supported_isas();
+        let phases = select_phases(&supported, SimdIsa::from_name(isa))?;
+        PlannedStep {
+            targets: vec!["one thread per online CPU".to_string()],
+            ..PlannedStep::new(
+                "simd",
+                format!(
+                    "{} fused multiply-adds, {} s each",
+                    phases
+                        .iter()
+                        .map(SimdIsa::label)
+                        .collect::<Vec<_>>()
+                        .join(", then "),
+                    (test_duration / phases.len() as u32).as_secs()
+                ),
+                &[Resource::Cpu],
+            )
+        }
+    } else {
+        let affinity = AffinitySpec {
+            pin: pin
+                .as_deref()
+                .map(parse_cpu_list)
+                .transpose()
+                .map_err(|e| format!("Invalid --pin value: {}", e))?,
+            numa_node: *numa,
+            physical_only: *physical_only,
+        };
+        let args = cpu_stress_args(logger, &affinity, core_class.as_deref(), test_duration)?;
+        // macOS keeps efficiency workers off the performance cores by their priority.
+        let background = cfg!(target_os = "macos") && core_class.as_deref() == Some("efficiency");
+        PlannedStep::new(
+            "cpu",
+            format!(
+                "stress-ng {}{}",
+                args.join(" "),
+                if background {
+                    " at background priority"
+                } else {
+                    ""
+                }
+            ),
+            &[Resource::Cpu],
+        )
+    };
+    let mut plan = ExecutionPlan::new(&format!("stress {}", step.name));
+    plan.steps.push(PlannedStep {
+        duration: Some(test_duration),
+        ..step
+    });
+    plan.steps
+        .extend(integrity_plan.map(|integrity_plan| integrity_plan.planned_step(test_duration)));
+    Ok(plan)
+}
@@ -0,0 +1,67 @@
+//! Trend Command
+//!
+//! This module runs `one_for_all trend`, which follows a metric across the
+//! stored runs and flags a steady degradation.
+
+use common::ports::log_port::LoggerPort;
+
+use oneforall_core::domain::statistics::BenchmarkRecord;
+use oneforall_core::domain::trend::{
+    analyze_trend, benchmark_series, burn_in_series, endurance_series, select_series,
+    BurnInTemperature, EnduranceThroughput, TrendVerdict,
+};
+
+use crate::commands::CommandContext;
+
+/// Follows a metric across the stored runs and exits non-zero if it degrades.
+pub async fn run(
+    ctx: CommandContext,
+    metric: String,
+    last: usize,
+    benchmark: Option<String>,
+    json: bool,
+) {
+    let CommandContext {
+        logger, repository, ..
+    } = ctx;
+    // Benchmark metrics, the burn-in temperature and the endurance throughput
+    // can all be followed.
+    let candidates = repository
+        .get_runs_in_range::<BenchmarkRecord>("benchmark", None, None)
+        .and_then(|records| {
+            let records: Vec<_> = records.into_iter().map(|(_, r)| r).collect();
+            let mut candidates = benchmark_series(&records);
+            let burn_ins =
+                repository.get_runs_in_range::<BurnInTemperature>("burn-in", None, None)?;
+            let burn_ins: Vec<_> = burn_ins.into_iter().map(|(_, r)| r).collect();
+            candidates.push(burn_in_series(&burn_ins));
+            let endurance =
+                repository.get_runs_in_range::<EnduranceThroughput>("endurance", None, None)?;
+            let endurance: Vec<_> = endurance.into_iter().map(|(_, r)| r).collect();
+            candidates.push(endurance_series(&endurance));
+            Ok(candidates)
+        });
+    let report = match candidates
+        .and_then(|candidates| select_series(candidates, &metric, benchmark.as_deref()))
+    {
+        Ok(series) => analyze_trend(series, last),
+        Err(e) => {
+            logger.log_error(&format!("Failed to follow {}: {}", metric, e));
+            std::process::exit(1);
+        }
+    };
+    if json {
+        match serde_json::to_string_pretty(&report) {
+            Ok(json) => println!("{}", json),
+            Err(e) => logger.log_error(&format!("Failed to encode the trend: {}", e)),
+        }
+    } else {
+        print!("{}", report.render());
+    }
+    // A degrading metric fails the command, so scheduled checks can alert on it.
+    std::process::exit(if report.verdict == TrendVerdict::Degrading {
+        1
+    } else {
+        0
+    });
+}
@@ -1,2 +0,0 @@
-pub mod logging;
-pub mod stress_ng;
@@ -1,122 +1,61 @@
+mod cli;
+mod commands;
+
+use std::net::SocketAddr;
 use std::sync::Arc;
 
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser};
 use futures::SinkExt;
+use tokio::spawn;
 use tokio::time::{sleep, Duration};
-use tokio::{signal, spawn};
 
-use common::adapters::web_server_adapter::WebServerAdapter;
+use common::adapters::job_adapter::InMemoryJobAdapter;
+use common::adapters::telemetry_adapter::InMemoryTelemetryAdapter;
+use common::adapters::web_server_adapter::{load_api_config, WebServerAdapter, WebServerPorts};
+use common::ports::job_port::JobPort;
 use common::ports::log_port::LoggerPort;
+use common::ports::telemetry_port::TelemetryPort;
 use common::ports::web_server_port::WebServerPort;
 
-use crate::adapters::database_adapter::DatabaseAdapter;
-use crate::adapters::ps_command_adapter::PsAdapter;
-use crate::adapters::stress_ng_adapter::StressNgAdapter;
-use crate::ports::database_port::DatabasePort;
-use crate::ports::ps_command_port::PsCommandPort;
-
-mod adapters;
-mod ports;
-
-// Enumeration representing the supported architectures for the `stress-ng`
-// binary.
-// This enum is used to select the correct binary for the running operating
-// system.
-#[derive(Debug)]
-enum StressNgArch {
-    Linux,
-    MacOS,
-}
-
-// OneForAll CLI Application
-// This struct represents the command-line interface of the application,
-// defining the available subcommands and their respective functionalities.
-#[derive(Parser, Debug)]
-#[clap(author = "Kenny Sheridan", version = "0.1 (Dev)", about = "OneForAll -\
- An advanced tool for hardware performance testing and diagnostics.",
-long_about = long_description())]
-struct Cli {
-    #[clap(subcommand)]
-    command: Commands,
-}
-
-// Enum representing the different subcommands available in the CLI.
-// Each variant corresponds to a specific functionality of the application.
-#[derive(Subcommand, Debug)]
-enum Commands {
-    // Runs benchmark tests
-    Benchmark,
-
-    // Executes stress tests
-    Stress,
-
-    // Scans and analyzes hardware
-    Discover,
-
-    // Monitors hardware performance in real-time
-    Overwatch,
-
-    // Embedded Database Operations
-    DatabaseOps,
-}
-
-/// # OneForAll
-///
-/// OneForAll is a comprehensive tool designed for in-depth hardware
-/// performance analysis and diagnostics. It leverages advanced testing
-/// methodologies to provide users with detailed insights into their
-/// system's capabilities and bottlenecks. With OneForAll, you can run
-/// various tests, including benchmarks, stress tests, and hardware
-/// discovery, to understand the full scope of your hardware's performance.
-///
-/// ## Modules
-///
-/// The tool is structured into several modules, each targeting a specific
-/// aspect of hardware performance:
-///
-/// - **Benchmark**: Run extensive benchmarks to measure the speed and efficiency
-///   of your CPU, GPU, memory, and storage devices.
-///
-/// - **Stress**: Put your system under intense stress to test stability and
-///   endurance under heavy loads.
-///
-/// - **Discover**: Analyze and report on the configuration and current state of
-///   your hardware components.
-///
-/// - **Overwatch**: Watch your system's performance in real-time, capturing
-///   critical metrics and providing live feedback.
-///
-/// OneForAll is designed with both simplicity and power in mind, making it
-/// suitable for both casual users looking to check their system's performance
-/// and professionals requiring detailed hardware analysis.
-fn long_description() -> &'static str {
-    "\n\n\nOneForAll is a comprehensive tool designed for in-depth hardware \
-    performance analysis and diagnostics. \
-    It leverages advanced testing methodologies to provide users with \
-    detailed insights into their system's capabilities \
-    and bottlenecks. With OneForAll, you can run various tests, including \
-    benchmarks, stress tests, and hardware discovery, \
-    to understand the full scope of your hardware's performance.\n\n\
-    The tool is structured into several modules, each targeting a specific \
-    aspect of hardware performance:\n\n\
-    \
-    - Benchmark: Run extensive benchmarks to measure the speed and efficiency \
-      of your CPU, GPU, memory, and storage devices.\n
-    
-    - Stress: Put your system under intense stress to test stability and \
-    endurance under heavy loads.\n\
-    \
-    - Discover: Analyze and report on the configuration and current state of \
-    your hardware components.\n\
-    \
-    - Overwatch: Watch your system's performance in real-time from the web browser, capturing \
-    critical metrics and providing live feedback.\n
-   
-    OneForAll is designed with both simplicity and power in mind, making it \
-    suitable for both casual users looking to \
-    check their system's performance and professionals requiring detailed \
-    hardware analysis."
-}
+use oneforall_core::adapters::alert_dispatcher::{hostname, load_alert_config, AlertDispatcher};
+use oneforall_core::adapters::alerting_job_adapter::AlertingJobAdapter;
+use oneforall_core::adapters::chaos_injector::arm_chaos;
+use oneforall_core::adapters::cloud_metadata_adapter::CloudMetadataAdapter;
+use oneforall_core::adapters::database_adapter::{load_database_config, open_database};
+use oneforall_core::adapters::footprint_governor::{load_footprint_config, FootprintGovernor};
+use oneforall_core::adapters::grpc_server_adapter::{load_grpc_config, GrpcServerAdapter};
+use oneforall_core::adapters::health_monitor::HealthMonitor;
+use oneforall_core::adapters::history_adapter::HistoryAdapter;
+use oneforall_core::adapters::mdns_adapter::{load_fleet_config, MdnsAdapter};
+use oneforall_core::adapters::metric_sink::MetricSink;
+use oneforall_core::adapters::network_probe_adapter::NetworkProbeAdapter;
+use oneforall_core::adapters::plugin_registry::{load_plugin_config, PluginRegistry};
+use oneforall_core::adapters::profile_launcher::ProfileLauncher;
+use oneforall_core::adapters::repository::Repository;
+use oneforall_core::adapters::result_exporter::{load_export_config, ResultExporter};
+use oneforall_core::adapters::retention_monitor::RetentionMonitor;
+use oneforall_core::adapters::sample_writer::SampleWriter;
+use oneforall_core::adapters::score_adapter::ScoreAdapter;
+use oneforall_core::adapters::shutdown_coordinator::{
+    ShutdownCoordinator, DEFAULT_SHUTDOWN_DEADLINE,
+};
+use oneforall_core::adapters::stress_ng_adapter::kill_running_stress_ng;
+use oneforall_core::adapters::systemd_adapter::SystemdAdapter;
+use oneforall_core::adapters::threshold_monitor::ThresholdMonitor;
+use oneforall_core::adapters::time_sync_adapter::{load_time_sync_config, TimeSyncAdapter};
+use oneforall_core::domain::chaos::ChaosPlan;
+use oneforall_core::domain::fleet::AgentAdvertisement;
+use oneforall_core::domain::kubernetes::NODE_NAME_ENV;
+use oneforall_core::domain::profile::parse_duration;
+use oneforall_core::domain::tags::Tags;
+use oneforall_core::ports::agent_discovery_port::AgentDiscoveryPort;
+use oneforall_core::ports::cloud_metadata_port::CloudMetadataPort;
+use oneforall_core::ports::database_port::DatabasePort;
+use oneforall_core::ports::service_manager_port::ServiceManagerPort;
+use oneforall_core::ports::time_sync_port::TimeSyncPort;
+
+use crate::cli::{Cli, Commands, DbAction};
+use crate::commands::CommandContext;
 
 // The entry point of the application using Actix's asynchronous runtime.
 // This runtime is essential for handling asynchronous tasks and is particularly suitable
@@ -140,22 +79,270 @@ async fn main() -> std::io::Result<()> {
     // changing the core logic, adhering to the principles of the Ports and Adapters architecture.
     let logger_as_port: Arc<dyn LoggerPort> = logger.clone();
 
+    // Parse command-line arguments using the Cli struct, which is defined using the
+    // `clap` crate. This struct represents the command-line interface of the application,
+    // defining the available subcommands and their functionalities.
+    let cli = Cli::parse();
+
+    // `completions` and `man` only describe the command line, so they need neither the
+    // configuration nor the database, and print nothing but what they generate.
+    if let Commands::Completions { shell } = cli.command {
+        clap_complete::generate(
+            shell,
+            &mut Cli::command(),
+            "one_for_all",
+            &mut std::io::stdout(),
+        );
+        return Ok(());
+    }
+    if let Commands::Man { dir } = cli.command {
+        return match clap_mangen::generate_to(Cli::command(), &dir) {
+            Ok(()) => {
+                logger.log_info(&format!("Wrote the man pages to {}", dir.display()));
+                Ok(())
+            }
+            Err(e) => {
+                logger.log_error(&format!(
+                    "Failed to write the man pages to {}: {}",
+                    dir.display(),
+                    e
+                ));
+                Err(e)
+            }
+        };
+    }
+
+    // Only benchmarks and plugins measure one workload whose stacks are worth drawing.
+    if cli.flamegraph.is_some()
+        && !matches!(
+            cli.command,
+            Commands::Benchmark { .. } | Commands::Plugin(_)
+        )
+    {
+        let e = "--flamegraph profiles the benchmark and plugin commands".to_string();
+        logger.log_error(&e);
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, e));
+    }
+
+    // `--dry-run` plans the command before the database, the web server or the job queue
+    // exist, so nothing is started, queued or stored.
+    if cli.dry_run {
+        return match commands::plan(logger_as_port.clone(), &cli.command, &cli.config) {
+            Ok(plan) => {
+                print!("{}", plan.render());
+                Ok(())
+            }
+            Err(e) => {
+                logger.log_error(&e);
+                Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, e))
+            }
+        };
+    }
+
+    // Under systemd, log messages go to the journal with their priority instead of
+    // being printed, since standard output would file every line a second time.
+    let service_mode = cli.service;
+    if service_mode {
+        match common::adapters::log_adapter::enable_journald_output() {
+            Ok(()) => common::adapters::log_adapter::set_console_output(false),
+            Err(e) => logger.log_warn(&format!("Logging to standard output: {}", e)),
+        }
+    }
+
+    // The REST API address, token and TLS settings, shared by the web server and
+    // the `jobs` client.
+    let api_config = match load_api_config(&cli.config) {
+        Ok(api_config) => api_config,
+        Err(e) => {
+            logger.log_error(&e);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, e));
+        }
+    };
+
+    // `jobs` only talks to an instance that is already running, which holds the
+    // database lock and the web server address, so it is handled before either is opened.
+    if let Commands::Jobs { cancel } = cli.command {
+        return commands::jobs::run(logger_as_port, &api_config, cancel);
+    }
+
+    // `logs` reads the log stream of a running instance the same way.
+    if let Commands::Logs {
+        follow,
+        lines,
+        level,
+        agent,
+    } = &cli.command
+    {
+        return commands::logs::run(
+            logger_as_port,
+            &api_config,
+            *follow,
+            *lines,
+            level,
+            agent.as_deref(),
+        );
+    }
+
+    // Which agents make up the fleet, and whether this instance announces itself as one.
+    let fleet_config = match load_fleet_config(&cli.config) {
+        Ok(fleet_config) => fleet_config,
+        Err(e) => {
+            logger.log_error(&e);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, e));
+        }
+    };
+
+    // `fleet` only talks to other instances, so like `jobs` it needs neither the database
+    // nor the web server. Agents are probed in parallel, through the same REST API client.
+    if let Commands::Fleet { json, matrix, html } = cli.command {
+        return commands::fleet::run(
+            logger_as_port,
+            &api_config,
+            &fleet_config,
+            json,
+            matrix,
+            html,
+        );
+    }
+
+    // `power-cycle` runs on a controller machine and only talks to the BMC and the agent of
+    // the machine under test. Ctrl+C stops it at the next heartbeat, with the cycles so far.
+    if let Commands::PowerCycle(args) = &cli.command {
+        return commands::power_cycle::run(logger_as_port, &api_config, args).await;
+    }
+
+    // `compare --fleet` likewise only talks to other instances: each agent scores itself
+    // from its own stored benchmark results, and the agents are ranked here.
+    if let Commands::Compare { fleet: true, json } = cli.command {
+        commands::compare::rank_fleet(logger_as_port, &api_config, &fleet_config, json);
+        return Ok(());
+    }
+
+    // Plugins add benchmarks and stress tests shipped outside OneForAll, each run as a
+    // subcommand of its own. A plugin that fails to load is skipped with a warning.
+    let plugin_config = match load_plugin_config(&cli.config) {
+        Ok(plugin_config) => plugin_config,
+        Err(e) => {
+            logger.log_error(&e);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, e));
+        }
+    };
+    let mut plugin_registry = PluginRegistry::new(logger_as_port.clone());
+    plugin_registry.load(&plugin_config);
+    for plugin in plugin_registry.plugins() {
+        if Cli::command().find_subcommand(plugin.name()).is_some() {
+            logger.log_warn(&format!(
+                "Plugin {} is shadowed by the built-in command of the same name.",
+                plugin.name()
+            ));
+        }
+    }
+    if let Commands::Plugins = cli.command {
+        print!("{}", plugin_registry.render());
+        return Ok(());
+    }
+    let plugin_registry = Arc::new(plugin_registry);
+
+    // `--chaos` fakes an ECC error, a temperature spike or a killed stressor through the
+    // adapters the real readings come from, so alerts and acceptance criteria can be checked.
+    let chaos_plan = match cli
+        .chaos
+        .as_deref()
+        .map(|events| {
+            parse_duration(&cli.chaos_after)
+                .map_err(|e| format!("Invalid --chaos-after value: {}", e))
+                .and_then(|after| ChaosPlan::parse(events, after))
+        })
+        .transpose()
+    {
+        Ok(chaos_plan) => chaos_plan,
+        Err(e) => {
+            logger.log_error(&e);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, e));
+        }
+    };
+    if let Some(plan) = &chaos_plan {
+        if let Err(e) = arm_chaos(logger_as_port.clone(), plan.clone()) {
+            logger.log_warn(&e);
+        }
+    }
+
+    // Live telemetry (e.g. power readings) is published here by the collectors and
+    // served by the web server's REST API.
+    let telemetry: Arc<dyn TelemetryPort> = Arc::new(InMemoryTelemetryAdapter::new());
+
+    // Alerts are sent to the webhooks and mailboxes of the `[alerts]` section when a job
+    // fails, a burn-in completes or a temperature or ECC threshold is crossed, or when either
+    // reading jumps away from its recent level.
+    let alert_config = match load_alert_config(&cli.config) {
+        Ok(alert_config) => alert_config,
+        Err(e) => {
+            logger.log_error(&e);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, e));
+        }
+    };
+    let alerts = Arc::new(AlertDispatcher::new(logger_as_port.clone(), &alert_config));
+    if alert_config.is_enabled() && (alert_config.has_thresholds() || alert_config.anomalies) {
+        ThresholdMonitor::start(
+            logger_as_port.clone(),
+            alerts.clone(),
+            alert_config.thresholds.clone(),
+            alert_config.anomalies,
+            alert_config.check_interval(),
+        );
+    }
+
+    // With a bucket in the `[export.s3]` section, the results, reports and telemetry of each
+    // finished run are also uploaded, so they outlive the machine being reimaged.
+    let exporter = match load_export_config(&cli.config) {
+        Ok(export_config) => ResultExporter::new(logger_as_port.clone(), &export_config),
+        Err(e) => {
+            logger.log_error(&e);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, e));
+        }
+    };
+
+    // Long-running jobs such as stress tests are queued here, so that workloads competing
+    // for the same resources run one after another, and so they can be listed and
+    // cancelled through the REST API or with Ctrl+C. Finishing a job raises its alerts.
+    let jobs: Arc<dyn JobPort> = Arc::new(AlertingJobAdapter::new(
+        Arc::new(InMemoryJobAdapter::new()),
+        alerts.clone(),
+    ));
+
     // Initialize the web server adapter with the logger. This adapter is responsible for
     // handling HTTP requests and serving web content. It represents the web server
     // "adapter" in the architecture.
-    let web_server = WebServerAdapter::new(logger.clone());
-
+    let api_address = api_config.address.clone();
+    let api_scheme = if api_config.tls.is_some() {
+        "https"
+    } else {
+        "http"
+    };
+    let grpc_api_config = api_config.clone(); // The gRPC control plane accepts the same tokens.
+                                              // Fleet agents measure the network paths to each other with the same credentials.
+    let network_probe = Arc::new(NetworkProbeAdapter::new(
+        logger_as_port.clone(),
+        api_config.clone(),
+    ));
     let db_logger = logger.clone(); // Clone the logger for database handling.
 
-    // Attempt to create a new DatabaseAdapter
-    let path_to_db = "OneForAll_database_file.db"; // database path
-    let db_adapter_result = DatabaseAdapter::new(path_to_db, db_logger.clone());
+    // Results and readings are kept in sled, or in SQLite when the `[database]` section
+    // selects it so they can be queried with SQL.
+    let database_config = match load_database_config(&cli.config) {
+        Ok(database_config) => database_config,
+        Err(e) => {
+            logger.log_error(&e);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, e));
+        }
+    };
+    let db_adapter_result = open_database(&database_config, db_logger.clone());
 
     // Handle the Result and create an Arc<dyn DatabasePort> if successful
     let db_adapter: Arc<dyn DatabasePort> = match db_adapter_result {
         Ok(adapter) => {
             db_logger.log_info("DatabaseAdapter created successfully.");
-            Arc::new(adapter) // Cast the DatabaseAdapter to a trait object
+            adapter
         }
         Err(e) => {
             db_logger.log_error(&format!("Error creating DatabaseAdapter: {}", e));
@@ -166,263 +353,418 @@ async fn main() -> std::io::Result<()> {
         }
     };
 
-    // Initialize the PsAdapter with the logger and the DbAdapter for process monitoring and CPU usage analysis.
-    let ps_adapter =
-        Arc::new(PsAdapter::new(logger.clone(), db_adapter.clone())) as Arc<dyn PsCommandPort>;
+    // Results, readings and inventories are stored through the repository, which owns
+    // their key schema.
+    // Tags given with `--tag`, such as the rack or asset ID, are stored with every result.
+    let mut tags: Tags = cli.tags.iter().cloned().collect();
+    // Results of a run with injected faults are marked, so they are never mistaken for real ones.
+    if let Some(plan) = &chaos_plan {
+        tags.insert("chaos".to_string(), plan.event_names());
+    }
+    // In a DaemonSet, results are tagged with the node they were measured on.
+    if let (Commands::Daemonset { .. }, Ok(node)) = (&cli.command, std::env::var(NODE_NAME_ENV)) {
+        tags.entry("node".to_string()).or_insert(node);
+    }
+    // On a cloud instance, results record the instance type, region, zone and tenancy,
+    // unless given with `--tag`.
+    let cloud_instance =
+        match CloudMetadataAdapter::new(logger_as_port.clone()).read_instance_metadata() {
+            Ok(instance) => instance,
+            Err(e) => {
+                logger.log_warn(&format!("Failed to read cloud instance metadata: {}", e));
+                None
+            }
+        };
+    if let Some(instance) = &cloud_instance {
+        for (key, value) in instance.tags() {
+            tags.entry(key).or_insert(value);
+        }
+    }
+    // Results from several machines are only correlated as closely as their clocks agree,
+    // so before measuring, the offset from the NTP or PTP reference is checked against the
+    // `[time_sync]` section and recorded with every result.
+    let time_sync_config = match load_time_sync_config(&cli.config) {
+        Ok(time_sync_config) => time_sync_config,
+        Err(e) => {
+            logger.log_error(&e);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, e));
+        }
+    };
+    if commands::measures_machine(&cli.command) {
+        let checked = TimeSyncAdapter::new(logger_as_port.clone())
+            .check_clock()
+            .and_then(|clock| {
+                logger.log_info(&format!("Clock: {}", clock.render_line()));
+                for (key, value) in clock.tags() {
+                    tags.entry(key).or_insert(value);
+                }
+                clock.check(&time_sync_config)
+            });
+        if let Err(e) = checked {
+            if time_sync_config.required {
+                logger.log_error(&format!(
+                    "{}; not starting, as time_sync.required is set",
+                    e
+                ));
+                return Err(std::io::Error::other(e));
+            }
+            logger.log_warn(&e);
+        }
+    }
+    let repository = Repository::new(db_adapter.clone()).with_tags(tags.clone());
 
-    // Parse command-line arguments using the Cli struct, which is defined using the
-    // `clap` crate. This struct represents the command-line interface of the application,
-    // defining the available subcommands and their functionalities.
-    let cli = Cli::parse();
+    // `archive export` and `archive import` move a run between databases and exit; the
+    // log messages of an exported run are read from the instance running on this machine.
+    if let Commands::Archive { action } = &cli.command {
+        return commands::archive::run(logger_as_port, &api_config, repository, action);
+    }
 
-    // Initialize the StressNgAdapter with the logger. This adapter is responsible for
-    // conducting stress tests on the system, utilizing tools like `stress-ng`.
-    let _stress_tester = StressNgAdapter::new(logger_as_port.clone());
+    // Readings are written in batches from a journaled buffer, so collectors can sample
+    // every second or faster; readings journaled before a crash are written now.
+    let samples = match SampleWriter::open(
+        logger_as_port.clone(),
+        repository.clone(),
+        &database_config.write_buffer,
+        &database_config.journal_path(),
+    ) {
+        Ok(samples) => samples.with_telemetry(telemetry.clone()),
+        Err(e) => {
+            logger.log_error(&e);
+            return Err(std::io::Error::other(e));
+        }
+    };
 
-    // Handle different commands provided via CLI in an async task. This design allows
-    // the main thread to remain responsive and not blocked by long-running operations
-    // triggered by CLI commands.
-    let command_logger = logger.clone(); // Clone the logger for command handling.
+    // The collectors and the web server keep to the CPU and memory budget of the
+    // `[footprint]` section, so monitoring does not perturb the benchmark it observes.
+    let footprint_config = match load_footprint_config(&cli.config) {
+        Ok(footprint_config) => footprint_config,
+        Err(e) => {
+            logger.log_error(&e);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, e));
+        }
+    };
+    let footprint = Arc::new(
+        FootprintGovernor::new(logger_as_port.clone(), footprint_config.clone())
+            .with_samples(samples.clone()),
+    );
+
+    // Every reading is also published to the metric registry, which `--metrics-csv` follows
+    // for as long as the application runs, through a queue bounded by the footprint budget.
+    let _metrics_csv = match cli
+        .metrics_csv
+        .as_deref()
+        .map(|path| MetricSink::csv(logger_as_port.clone(), &telemetry, path, &footprint_config))
+        .transpose()
+    {
+        Ok(sink) => sink,
+        Err(e) => {
+            logger.log_error(&e);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, e));
+        }
+    };
 
-    let ctrl_c_logger = logger.clone(); // Clone the logger for this specific task.
+    // `/healthz` and `/readyz` report whether the database, the collectors and the job
+    // manager work, for load balancers, Kubernetes and monitoring.
+    let health = Arc::new(HealthMonitor::new(
+        logger_as_port.clone(),
+        repository.clone(),
+        samples.clone(),
+        jobs.clone(),
+    ));
 
-    let server_handle_logger = logger.clone(); // Clone the logger for the web server task.
+    // Remote callers launch profiles as jobs through the REST and gRPC APIs.
+    let launcher = Arc::new(ProfileLauncher::new(
+        logger_as_port.clone(),
+        telemetry.clone(),
+        jobs.clone(),
+        repository.clone(),
+    ));
 
-    let (shutdown_sender, shutdown_receiver) = tokio::sync::mpsc::channel::<()>(1);
-
-    // Set up handling for the Ctrl+C (interrupt) signal in a separate async task.
-    // This approach enables the application to gracefully shut down in response to
-    // interrupt signals.
-    let ctrl_c_logger = logger.clone(); // Clone the logger for this specific task.
-    let ctrl_c_handle = spawn(async move {
-        signal::ctrl_c().await.expect("Failed to listen for Ctrl+C");
-        ctrl_c_logger.log_info("Received Ctrl+C, shutting down.");
-        // Send a shutdown signal to the web server task.
-        let _ = shutdown_sender.send(()).await;
-    });
+    // The REST API serves the scores of this machine from its stored benchmark results,
+    // which `compare --fleet` ranks the agents by, its own overhead, and the history of
+    // the stored readings the dashboard charts.
+    let web_server = WebServerAdapter::new(
+        logger.clone(),
+        WebServerPorts {
+            telemetry: telemetry.clone(),
+            jobs: jobs.clone(),
+            launcher: launcher.clone(),
+            probe: network_probe,
+            scores: Arc::new(ScoreAdapter::new(
+                logger_as_port.clone(),
+                repository.clone(),
+            )),
+            footprint: footprint.clone(),
+            logs: common::adapters::log_adapter::log_stream(),
+            health: health.clone(),
+            history: Arc::new(HistoryAdapter::new(
+                logger_as_port.clone(),
+                repository.clone(),
+            )),
+        },
+        api_config,
+    );
+
+    // `db vacuum` applies the retention policy at once, even without a configured one
+    // since it still compacts the database, and exits without starting the web server.
+    if let Commands::Db {
+        action: DbAction::Vacuum,
+    } = cli.command
+    {
+        return commands::db::vacuum(logger_as_port, repository, database_config.retention);
+    }
 
-    let db_logger = logger.clone(); // Clone the logger for database handling.
+    // `db migrate` brings the results written by older versions to the current schema
+    // version for good, rather than each time they are read.
+    if let Commands::Db {
+        action: DbAction::Migrate,
+    } = cli.command
+    {
+        return commands::db::migrate(logger_as_port, &repository);
+    }
 
-    // Attempt to create a new DatabaseAdapter
-    let path_to_db = "OneForAll_database_file.db"; // database path
-    let db_adapter_result = DatabaseAdapter::new(path_to_db, db_logger.clone());
-
-    let _command_handle = spawn(async move {
-        match cli.command {
-            // Handle each CLI command by invoking the appropriate functionality
-            // and logging as needed. This part of the code can be seen as part of
-            // the application's "core" or "domain logic."
-            Commands::Benchmark => {
-                // Logic for handling the 'Benchmark' command.
-                command_logger.log_info("Benchmarking functionality not yet implemented.");
-            }
-            Commands::Stress => {
-                // Define the arguments for the stress test.
-                // The arguments are modified to create a more comprehensive and informative CPU stress test.
-
-                // "--cpu 4" specifies that the stress test should use 4 CPU cores instead of 2.
-                // This increases the load on the CPU for a more intensive stress test.
-                let cpu_cores = "--cpu";
-                let number_of_cores = "4";
-
-                // "--timeout 120s" sets the test to run for 120 seconds, doubling the duration of the test
-                // compared to the initial 60 seconds. This allows for a longer observation of CPU behavior
-                // under stress.
-                let timeout = "--timeout";
-                let duration = "120s";
-
-                // "--metrics-brief" outputs brief metrics about the stress test upon completion.
-                // This option provides a summary of how the system responded to the stress test.
-                let metrics = "--metrics-brief";
-
-                // "--verbose" increases the verbosity of the output. This is useful for getting detailed
-                // information about the stress test's operation and can aid in diagnosing issues or
-                // understanding the system's behavior under load.
-                let verbose = "--verbose";
-
-                // Combine all the arguments into an array. These arguments will configure the behavior
-                // of the `stress-ng` command to perform a more extensive and detailed stress test.
-                let args = [
-                    cpu_cores,
-                    number_of_cores,
-                    timeout,
-                    duration,
-                    metrics,
-                    verbose,
-                ];
-
-                // Initialize the retry mechanism. This allows the stress test to be retried
-                // a specified number of times in case of failure. In this case, the test
-                // will be attempted up to 3 times (initial try + 2 retries).
-                let mut retries = 2;
-                // Start a loop for executing the stress test with retries.
-                while retries >= 0 {
-                    // Log the start of a stress test attempt. This is useful for monitoring
-                    // and debugging purposes, allowing users to track the test's progress
-                    // and retries.
-                    command_logger.log_info(&format!(
-                        "Executing CPU stress test. Attempts remaining: {}",
-                        retries,
-                    ));
-
-                    // Execute the stress test command asynchronously.
-                    // `StressNgAdapter::execute_stress_ng_command` is responsible for running
-                    // the stress test using the `stress-ng` tool. The command is awaited
-                    // to ensure the execution is complete before proceeding.
-                    match StressNgAdapter::execute_stress_ng_command(command_logger.clone(), &args)
-                        .await
-                    {
-                        // In case of a successful execution, log the success and exit the loop.
-                        // This indicates that the stress test was completed without errors.
-                        Ok(()) => {
-                            command_logger.log_info("CPU stress test executed successfully.");
-                            break;
-                        }
-                        // In case of an error, handle the retry mechanism.
-                        Err(e) => {
-                            // If there are retries left, log a warning and decrement the retry counter.
-                            // The `sleep` call introduces a delay before the next attempt, giving
-                            // the system some time to stabilize.
-                            if retries > 0 {
-                                command_logger.log_warn(&format!(
-                                    "Retrying CPU stress test. Attempts remaining: {}",
-                                    retries
-                                ));
-                                sleep(Duration::from_secs(10)).await;
-                            } else {
-                                // If there are no retries left, log the error and exit the loop.
-                                // This indicates that all attempts to run the stress test have failed.
-                                command_logger
-                                    .log_error(&format!("Error executing CPU stress test: {}", e));
-                            }
-                        }
-                    }
-                    // Decrement the retry counter after each attempt.
-                    retries -= 1;
-                }
-            }
+    // Old readings are deleted while the application runs, per `[database.retention]`;
+    // sub-second frames are downsampled and the history metrics rolled up even without
+    // a policy, so long histories are read from the rollups.
+    RetentionMonitor::new(
+        logger_as_port.clone(),
+        repository.clone(),
+        database_config.retention.clone(),
+    )
+    .start();
+    footprint.start();
+
+    // With an address in the `[grpc]` section, jobs, live telemetry and the results stored in
+    // the database are also served over gRPC, for lab automation that prefers it to REST.
+    let grpc_server = match load_grpc_config(&cli.config) {
+        Ok(grpc_config) => grpc_config.socket_address().map(|address| {
+            GrpcServerAdapter::new(
+                logger_as_port.clone(),
+                telemetry.clone(),
+                jobs.clone(),
+                launcher.clone(),
+                repository.clone(),
+                grpc_api_config,
+                address,
+            )
+        }),
+        Err(e) => {
+            logger.log_error(&e);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, e));
+        }
+    };
 
-            Commands::Discover => {
-                // Logic for handling the 'Discover' command.
-                command_logger.log_info("Discovery functionality not yet implemented.");
-            }
-            Commands::Overwatch => {
-                command_logger.log_info("System overwatch functionality started.");
+    let server_handle_logger = logger.clone(); // Clone the logger for the web server task.
 
-                // Specify the output file path for CPU statistics
-                let output_file_path = "cpu_stats.txt";
+    let grpc_logger = logger.clone(); // Clone the logger for the gRPC server task.
 
-                // Spawn a new thread to run the process monitoring task
-                // This allows the Overwatch functionality to operate in the background
-                // without blocking the main async executor
-                std::thread::spawn(move || {
-                    ps_adapter.collect_cpu_statistics(output_file_path);
-                });
+    // Ctrl+C, SIGTERM or a failed REST API stops the subsystems in order within one
+    // deadline, rather than exiting with tasks still writing.
+    let web_server = Arc::new(web_server);
+    let grpc_server = grpc_server.map(Arc::new);
+    let shutdown = Arc::new(ShutdownCoordinator::new(
+        logger_as_port.clone(),
+        DEFAULT_SHUTDOWN_DEADLINE,
+    ));
 
-                command_logger.log_info("Monitoring CPU usage and top processes.");
+    // With `advertise` set, this instance announces itself as a fleet agent once its REST
+    // API is serving, and withdraws the announcement when shutting down.
+    let agent_discovery: Option<Arc<dyn AgentDiscoveryPort>> = if fleet_config.advertise {
+        match MdnsAdapter::new(logger_as_port.clone()) {
+            Ok(mdns) => Some(Arc::new(mdns)),
+            Err(e) => {
+                logger.log_warn(&format!("Not announcing this agent: {}", e));
+                None
             }
-            Commands::DatabaseOps => {
-                // Assuming `db_logger` is a reference to an implementation of `LoggerPort`
-                command_logger.log_info("Database operations functionality not yet implemented.");
-
-                match get_all_keys(logger.clone()) {
-                    // Pass a reference, not a clone
-                    Ok(_) => println!("Successfully retrieved all keys"),
-                    Err(e) => eprintln!("Error retrieving keys: {:?}", e),
-                }
+        }
+    } else {
+        None
+    };
+    let service_manager: Arc<dyn ServiceManagerPort> =
+        Arc::new(SystemdAdapter::new(logger_as_port.clone()));
+    // Load balancers see the instance as not ready before anything stops.
+    let stopping_health = health.clone();
+    shutdown.on_shutdown("readiness probe", move || async move {
+        stopping_health.set_stopping();
+    });
+    if service_mode {
+        let stopping_service = service_manager.clone();
+        let stopping_logger = logger.clone();
+        shutdown.on_shutdown("service manager", move || async move {
+            if let Err(e) = stopping_service.notify_stopping() {
+                stopping_logger.log_debug(&e);
             }
+        });
+    }
+    // Running jobs are given time to kill their processes and record their cancelled
+    // result; stress-ng processes a job left behind are killed after them.
+    let stopping_jobs = jobs.clone();
+    shutdown.on_shutdown("jobs", move || async move {
+        stopping_jobs.cancel_all();
+        while stopping_jobs
+            .list_jobs()
+            .iter()
+            .any(|job| !job.status.is_finished())
+        {
+            sleep(Duration::from_millis(100)).await;
         }
     });
-
-    // Initialize signal handling for graceful shutdown.
-    let ctrl_c_signal = signal::ctrl_c();
-
-    // Start the web server and await its completion.
-    let server_handle = spawn(async move {
-        // Start the web server and await its completion.
-        if let Err(e) = web_server.start_server().await {
-            // Log an error if the web server fails to start.
+    let stress_logger = logger.clone();
+    shutdown.on_shutdown("stress-ng", move || async move {
+        let killed = kill_running_stress_ng();
+        if killed > 0 {
+            stress_logger.log_warn(&format!(
+                "Killed {} stress-ng process group(s) still running.",
+                killed
+            ));
         }
     });
-
-    // Await the completion of either the web server task or the Ctrl+C signal handling.
-    // This is achieved using `tokio::select!`, which waits for multiple asynchronous
-    // operations, proceeding when one of them completes. This is crucial for responsive
-    // multitasking in asynchronous applications.
-    // Await the completion of either the web server task or the Ctrl+C signal handling
-    tokio::select! {
-        _ = server_handle => {
-            println!("Web server has stopped.");
-        },
-        _ = ctrl_c_handle => {
-            println!("Shutdown initiated by Ctrl+C.");
-        },
+    if let Some(discovery) = agent_discovery.clone() {
+        shutdown.on_shutdown("mDNS announcement", move || async move {
+            discovery.withdraw();
+        });
     }
-
-    println!("Application is shutting down.");
-    Ok(())
-}
-
-/// Retrieves all keys from the Sled database.
-///
-/// This function attempts to open the Sled database and create an iterator over all key-value pairs.
-/// It logs the process and counts the total number of keys found.
-///
-/// # Arguments
-///
-/// * `logger` - An Arc-wrapped LoggerPort trait object for logging.
-///
-/// # Returns
-///
-/// * `Result<(), sled::Error>` - Returns Ok(()) if successful, or an Err containing the sled::Error if an error occurred.
-fn get_all_keys(logger: Arc<dyn LoggerPort>) -> Result<(), sled::Error> {
-    // Log the attempt to open the Sled database.
-    logger.log_info("Attempting to open the Sled database.");
-
-    // Attempt to open the Sled database, gracefully handling errors.
-    let db = match sled::open("OneForAll_database_file.db") {
-        Ok(db) => {
-            // Log the successful opening of the database.
-            logger.log_info("Database opened successfully.");
-            db
+    let stopping_web_server = web_server.clone();
+    shutdown.on_shutdown("REST API", move || async move {
+        stopping_web_server.stop_server().await;
+    });
+    if let Some(grpc_server) = grpc_server.clone() {
+        shutdown.on_shutdown("gRPC control plane", move || async move {
+            grpc_server.stop_server().await;
+        });
+    }
+    // Queued alerts, buffered readings and the database are written out last, once
+    // nothing produces them any more.
+    let stopping_alerts = alerts.clone();
+    shutdown.on_shutdown("alerts", move || async move {
+        stopping_alerts.flush();
+    });
+    let stopping_samples = samples.clone();
+    let samples_logger = logger.clone();
+    shutdown.on_shutdown("readings", move || async move {
+        if let Err(e) = stopping_samples.flush() {
+            samples_logger.log_warn(&e);
         }
-        Err(e) => {
-            // Log the error and return it if the database fails to open.
-            logger.log_error(&format!("Error opening database: {}", e));
-            return Err(e);
+    });
+    let stopping_repository = repository.clone();
+    let database_logger = logger.clone();
+    shutdown.on_shutdown("database", move || async move {
+        if let Err(e) = stopping_repository.flush() {
+            database_logger.log_warn(&e);
         }
-    };
+    });
+    shutdown.on_shutdown("log files", || async {
+        log::logger().flush();
+    });
 
-    // Log the creation of an iterator over all key-value pairs in the database.
-    logger.log_debug("Creating an iterator over all key-value pairs in the database.");
+    // Once the REST API accepts connections, announce the agent, and in service mode tell
+    // systemd the instance is ready and keep its watchdog fed for as long as the runtime
+    // is responsive.
+    if service_mode || agent_discovery.is_some() {
+        let listening = web_server.listening();
+        let ready_logger = logger.clone();
+        let advertisement =
+            api_address
+                .parse::<SocketAddr>()
+                .ok()
+                .map(|address| AgentAdvertisement {
+                    name: fleet_config.name.clone().unwrap_or_else(hostname),
+                    address,
+                    scheme: api_scheme,
+                    url: fleet_config.url.clone(),
+                });
+        let ready_shutdown = shutdown.token();
+        let ready_handle = spawn(async move {
+            listening.notified().await;
+            if let (Some(discovery), Some(advertisement)) = (agent_discovery, advertisement) {
+                match discovery.advertise(&advertisement) {
+                    Ok(()) => ready_logger.log_info(&format!(
+                        "Announced this agent as '{}' over mDNS.",
+                        advertisement.name
+                    )),
+                    Err(e) => ready_logger.log_warn(&format!("Not announcing this agent: {}", e)),
+                }
+            }
+            if !service_mode {
+                return;
+            }
+            if !service_manager.is_supervised() {
+                ready_logger.log_debug("Not started by systemd; readiness is not reported.");
+                return;
+            }
+            let status = format!("Serving the REST API on {}", api_address);
+            match service_manager.notify_ready(&status) {
+                Ok(()) => ready_logger.log_info("Reported readiness to systemd."),
+                Err(e) => ready_logger.log_warn(&format!("Readiness not reported: {}", e)),
+            }
+            if let Some(interval) = service_manager.watchdog_interval() {
+                loop {
+                    if let Err(e) = service_manager.notify_watchdog() {
+                        ready_logger.log_warn(&format!("Failed to feed the watchdog: {}", e));
+                    }
+                    tokio::select! {
+                        _ = sleep(interval / 2) => {}
+                        _ = ready_shutdown.cancelled() => break,
+                    }
+                }
+            }
+        });
+        shutdown.track("readiness", ready_handle);
+    }
 
-    // Create an iterator over all key-value pairs in the database.
-    let mut iter = db.iter();
-    let mut key_count = 0;
+    // Handle different commands provided via CLI in an async task. This design allows
+    // the main thread to remain responsive and not blocked by long-running operations
+    // triggered by CLI commands.
+    let ctx = CommandContext {
+        logger: logger.clone(),
+        telemetry,
+        jobs,
+        repository,
+        samples,
+        alerts,
+        exporter,
+        footprint,
+        footprint_config,
+        health,
+        plugin_registry,
+        cloud_instance,
+        tags,
+        shutdown: shutdown.clone(),
+        flamegraph: cli.flamegraph,
+        // The flamegraph is titled with the command line, which names the benchmark or plugin.
+        flamegraph_title: std::env::args()
+            .skip(1)
+            .fold("one_for_all".to_string(), |title, arg| title + " " + &arg),
+    };
+    let command_handle = spawn(commands::run(ctx, cli.command));
+    shutdown.track("command", command_handle);
 
-    // Iterate over all keys.
-    while let Some(result) = iter.next() {
-        match result {
-            Ok((key, _)) => {
-                // Increment key count.
-                key_count += 1;
+    // Start the web server; the application cannot be reached without it, so it shuts
+    // down if the server fails to start.
+    let server_shutdown = shutdown.clone();
+    let server_web_server = web_server.clone();
+    let server_handle = spawn(async move {
+        if let Err(e) = server_web_server.start_server().await {
+            server_handle_logger.log_error(&format!("Failed to start the web server: {}", e));
+            server_shutdown.request("the REST API failed");
+        }
+    });
+    shutdown.track("REST API", server_handle);
 
-                // Log each key.
-                logger.log_debug(&format!("Key: {:?}", key));
+    // Serve the gRPC control plane alongside the REST API.
+    if let Some(grpc_server) = grpc_server {
+        let grpc_handle = spawn(async move {
+            if let Err(e) = grpc_server.start_server().await {
+                grpc_logger.log_error(&format!("Failed to start the gRPC control plane: {}", e));
             }
-            Err(e) => {
-                // Log the error and return it if an error occurs while iterating over the keys.
-                logger.log_error(&format!("Error iterating over keys: {}", e));
-                return Err(e);
-            }
-        }
+        });
+        shutdown.track("gRPC control plane", grpc_handle);
     }
 
-    // Log the total number of keys found.
-    logger.log_info(&format!(
-        "Successfully iterated over all keys. Total keys found: {}",
-        key_count
-    ));
-
+    let reason = tokio::select! {
+        received = commands::shutdown_signal() => received.to_string(),
+        requested = shutdown.requested() => requested,
+    };
+    shutdown.shutdown(&reason).await;
     Ok(())
 }
@@ -1,3 +0,0 @@
-pub mod database_port;
-pub(crate) mod ps_command_port;
-pub mod stress_test_port;